@@ -1,27 +1,66 @@
 //! Demiarch CLI - local-first AI app builder
 
 use clap::{Parser, Subcommand};
-use demiarch_core::agents::{extract_files_from_response, AgentTool, AgentToolResult};
+use demiarch_core::agents::dependencies::{self, DependencyChange};
+use demiarch_core::agents::security_audit::SecurityFinding;
+use demiarch_core::agents::{
+    events::cancel_active_agents, extract_files_from_response, AgentTool, AgentToolResult,
+    ContentSanitizer,
+};
+use demiarch_core::commands::approval::{
+    ApprovalDecision, ApprovalGates, ApprovalRequest, Approver,
+};
 use demiarch_core::commands::{
-    chat, checkpoint, document, feature, generate, graph, image, project,
+    analytics, blame, changelog, chat, checkpoint, cost_compare, criteria, digest, document,
+    editor, environment, estimate, eval, feature, feature_branch, feature_import, generate,
+    generation, graph, health, image, inbox, integrity, invoice, jobs, license, lifecycle, open,
+    persona, phase, planner, project, pull_request, queue, related, report, roadmap, secrets,
+    snippets, spec, staging, update, upgrade_assist, workspace, worktree,
 };
-use demiarch_core::config::Config;
-use demiarch_core::context::ContextManager;
-use demiarch_core::cost::CostTracker;
-use demiarch_core::domain::knowledge::{EntityType, RelationshipType};
+use demiarch_core::config::{watch, Config};
+use demiarch_core::context::{ContextManager, TokenAllocation, WindowStats};
+use demiarch_core::cost::{forecast, preview, CostTracker};
+use demiarch_core::deeplink::DeepLink;
+use demiarch_core::domain::feature_decomposition::PlanTask;
+use demiarch_core::domain::knowledge::{DecayModel, EntityType, RelationshipType};
 use demiarch_core::domain::locking::{LockConfig, LockManager};
-use demiarch_core::domain::memory::{PersistentMemoryStore, RecallQuery};
+use demiarch_core::domain::memory::{
+    ConsolidationReport, PersistentMemoryStore, RecallQuery, DEFAULT_CONSOLIDATION_THRESHOLD,
+};
 use demiarch_core::domain::session::{
-    SessionManager, SessionStatus, ShutdownConfig, ShutdownHandler,
+    time::format_secs, FeatureTimeRepository, SessionManager, SessionNote, SessionStatus,
+    ShutdownConfig, ShutdownHandler, ShutdownSignal, SignalGuard,
+};
+use demiarch_core::events;
+use demiarch_core::hooks::{HookDecision, HookEvent, HooksManager};
+use demiarch_core::i18n::{self, t, t_args};
+use demiarch_core::infrastructure::knowledge::SqliteKnowledgeGraphRepository;
+use demiarch_core::infrastructure::network;
+use demiarch_core::infrastructure::sandbox::SandboxedRunner;
+use demiarch_core::llm::{LlmClient, Message, ResponseCache, StreamEvent};
+use demiarch_core::notify::{NotificationFeed, Notifier};
+use demiarch_core::pagination::{Page, PageRequest};
+use demiarch_core::progress::{Progress, Stage};
+use demiarch_core::routing::{benchmark, ModelRegistry, RoutingStore};
+use demiarch_core::storage::content::ContentStore;
+use demiarch_core::storage::{self, export, Database, DatabaseManager};
+use demiarch_core::transcription;
+use demiarch_core::visualization::{glyphs, HierarchyTree, NodeStyle, RenderOptions, TreeBuilder};
+use demiarch_core::ErrorPayload;
+use demiarch_plugins::events as plugin_events;
+use demiarch_plugins::loader;
+use demiarch_plugins::metering::StatsStore;
+use demiarch_plugins::permissions::{
+    DenyPrompt, GrantStore, PermissionMediator, PermissionPrompt, PermissionRequest, PromptAnswer,
 };
-use demiarch_core::llm::{LlmClient, Message, StreamEvent};
-use demiarch_core::storage::{self, Database, DatabaseManager};
-use demiarch_core::visualization::{HierarchyTree, NodeStyle, RenderOptions, TreeBuilder};
+use demiarch_plugins::sandbox::{ResourceLimits, Sandbox};
+use demiarch_plugins::scaffold::{scaffold, Capability};
+use demiarch_plugins::Permission;
 use futures_util::StreamExt;
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
 use serde_json;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 use std::sync::Arc;
 use tracing::{info, warn};
 use uuid::Uuid;
@@ -33,13 +72,29 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    /// Output format (text or json)
+    /// Output format (text or json; csv or parquet for `db export`)
     #[arg(long, global = true, default_value = "text")]
     format: OutputFormat,
 
     /// Quiet mode (minimal output)
     #[arg(short, long, global = true)]
     quiet: bool,
+
+    /// Disable all network calls (LLM, image generation); local features keep working
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Plain output: ASCII words and lines instead of emoji and box drawing
+    #[arg(long, global = true)]
+    plain: bool,
+
+    /// Open the database read-only; commands that would modify it fail
+    #[arg(long, global = true)]
+    read_only: bool,
+
+    /// Use this database file instead of the per-user one (e.g. a synced snapshot)
+    #[arg(long, global = true, value_name = "PATH")]
+    database: Option<std::path::PathBuf>,
 }
 
 #[derive(Clone, Copy, Default, clap::ValueEnum)]
@@ -47,6 +102,10 @@ enum OutputFormat {
     #[default]
     Text,
     Json,
+    /// Only used by `db export`; other commands print text
+    Csv,
+    /// Only used by `db export`; other commands print text
+    Parquet,
 }
 
 #[derive(Subcommand)]
@@ -77,7 +136,14 @@ enum Commands {
     },
 
     /// Start conversational discovery
-    Chat,
+    Chat {
+        /// Voice note to transcribe and send as the first message
+        #[arg(long)]
+        audio: Option<std::path::PathBuf>,
+        /// Send one message, print the reply and exit; `-` reads it from stdin
+        #[arg(short, long, conflicts_with = "audio")]
+        message: Option<String>,
+    },
 
     /// Manage projects
     Projects {
@@ -91,13 +157,80 @@ enum Commands {
         action: FeatureAction,
     },
 
+    /// Manage phases (milestones) and track their progress
+    Phases {
+        #[command(subcommand)]
+        action: PhaseAction,
+    },
+
     /// Generate code from a natural language description
     Generate {
-        /// Natural language description of what to generate
-        description: String,
+        /// Natural language description of what to generate; `-` reads it from stdin
+        #[arg(required_unless_present_any = ["resume", "from_file", "phase", "feature"])]
+        description: Option<String>,
         /// Dry run (preview without writing files)
         #[arg(short, long)]
         dry_run: bool,
+        /// Review diffs and accept/reject each file before writing
+        #[arg(short, long, conflicts_with = "dry_run")]
+        review: bool,
+        /// Resume a failed generation, re-running only its unfinished tasks
+        #[arg(long, value_name = "GENERATION_ID", conflicts_with = "description")]
+        resume: Option<String>,
+        /// Generate from a Markdown spec; each `##` section is a task and only
+        /// sections changed since the last run are regenerated
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["description", "resume", "review"])]
+        from_file: Option<std::path::PathBuf>,
+        /// Keep running and regenerate whenever the spec file changes
+        #[arg(short, long, requires = "from_file")]
+        watch: bool,
+        /// Generate the unfinished features of a phase (ID or name)
+        #[arg(long, conflicts_with_all = ["description", "resume", "from_file"])]
+        phase: Option<String>,
+        /// Generate a feature (ID, ID prefix or title) with its own plan; repeat
+        /// to queue several features
+        #[arg(long, conflicts_with_all = ["description", "resume", "from_file", "phase", "review"])]
+        feature: Vec<String>,
+        /// Most queued features generating at once
+        #[arg(long, default_value_t = queue::DEFAULT_CONCURRENCY, requires = "feature")]
+        concurrency: usize,
+        /// Stop starting queued features once the run has spent this many USD
+        #[arg(long, value_name = "USD", requires = "feature")]
+        budget: Option<f64>,
+        /// Don't checkpoint the project before each queued feature
+        #[arg(long, requires = "feature")]
+        no_checkpoint: bool,
+        /// Generate on a worktree branch and, once verification passes, push it
+        /// and open a GitHub/GitLab pull request instead of merging
+        #[arg(long, conflicts_with_all = ["dry_run", "resume", "from_file", "feature"])]
+        open_pr: bool,
+        /// Skip the confirmation asked when the estimated cost is over cost.confirm_above_usd
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Browse, review and apply recorded generations
+    Generations {
+        #[command(subcommand)]
+        action: GenerationAction,
+    },
+
+    /// Trace generated code back to the feature, agent, model and prompt
+    Artifacts {
+        #[command(subcommand)]
+        action: ArtifactAction,
+    },
+
+    /// Queue generations and document builds to run later
+    Jobs {
+        #[command(subcommand)]
+        action: JobAction,
+    },
+
+    /// Compare models on the same generation task
+    Eval {
+        #[command(subcommand)]
+        action: EvalAction,
     },
 
     /// Generate and manage documents (PRD, Architecture, etc.)
@@ -106,12 +239,95 @@ enum Commands {
         action: DocumentAction,
     },
 
+    /// Open a project, feature, document or checkpoint by ID or ID prefix
+    Open {
+        /// What to open
+        #[arg(value_enum)]
+        record: OpenRecord,
+        /// ID or unique ID prefix
+        id: String,
+        /// Open a project's directory in $EDITOR instead of the file manager
+        #[arg(short, long, conflicts_with = "app")]
+        editor: bool,
+        /// Open in the desktop app (projects and features)
+        #[arg(long)]
+        app: bool,
+        /// Print the path or link instead of opening it
+        #[arg(long)]
+        print: bool,
+    },
+
+    /// List everything in a project awaiting review: conflicts, blocking
+    /// findings, failed verifications and features in review
+    Inbox {
+        /// Project ID or name (defaults to the project in the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+
+    /// Summarize a day or week of activity as Markdown and store it as a document
+    Digest {
+        /// Span of activity to cover
+        #[arg(long, value_enum, default_value = "week")]
+        period: DigestSpan,
+        /// Project ID or name (defaults to the project in the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Model to write the summary with (defaults to the cheapest configured model)
+        #[arg(long)]
+        model: Option<String>,
+        /// Send the digest to webhooks subscribed to digest_created (e.g. Slack)
+        #[arg(long)]
+        post: bool,
+    },
+
+    /// Build a CHANGELOG section from features completed since a tag or date
+    Changelog {
+        /// Git tag or date (YYYY-MM-DD) the section starts at
+        #[arg(long)]
+        since: String,
+        /// Project ID or name (defaults to the project in the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Section title
+        #[arg(long, default_value = "Unreleased")]
+        version: String,
+        /// Add the section to the top of the project's CHANGELOG.md
+        #[arg(short, long)]
+        write: bool,
+    },
+
+    /// Inventory code affected by a framework upgrade and plan the migration as features
+    UpgradeAssist {
+        /// Framework and major version to upgrade to, e.g. nextjs@15
+        #[arg(long)]
+        target: String,
+        /// Only report the inventory; don't create features
+        #[arg(short, long)]
+        dry_run: bool,
+        /// Generate the mechanical steps, one queued feature each
+        #[arg(long, conflicts_with = "dry_run")]
+        generate: bool,
+        /// Most steps generating at once
+        #[arg(long, default_value_t = queue::DEFAULT_CONCURRENCY, requires = "generate")]
+        concurrency: usize,
+        /// Stop starting steps once the run has spent this many USD
+        #[arg(long, value_name = "USD", requires = "generate")]
+        budget: Option<f64>,
+    },
+
     /// Manage learned skills
     Skills {
         #[command(subcommand)]
         action: SkillAction,
     },
 
+    /// LLM response cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
     /// Model routing configuration
     Routing {
         #[command(subcommand)]
@@ -135,6 +351,26 @@ enum Commands {
         /// Project ID (optional, defaults to current)
         #[arg(short, long)]
         project: Option<String>,
+
+        #[command(subcommand)]
+        action: Option<CostAction>,
+    },
+
+    /// Local usage analytics (opt-in, never leaves this machine)
+    Stats {
+        /// Project ID (optional, defaults to all projects)
+        #[arg(short, long)]
+        project: Option<String>,
+
+        /// Number of weeks to show
+        #[arg(short, long, default_value_t = analytics::DEFAULT_WEEKS)]
+        weeks: u32,
+    },
+
+    /// Costs and activity rolled up across projects
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceAction,
     },
 
     /// Sync SQLite <-> JSONL
@@ -158,6 +394,43 @@ enum Commands {
     /// Run health check
     Doctor,
 
+    /// Manage encrypted per-project secrets (environment variables for generated apps)
+    Secrets {
+        #[command(subcommand)]
+        action: SecretAction,
+    },
+
+    /// Manage per-project personas for chat and generation prompts
+    Personas {
+        #[command(subcommand)]
+        action: PersonaAction,
+    },
+
+    /// Activate, inspect or remove your Demiarch license
+    License {
+        #[command(subcommand)]
+        action: LicenseAction,
+    },
+
+    /// Store the GitHub/GitLab tokens `generate --open-pr` uses
+    Pr {
+        #[command(subcommand)]
+        action: PrAction,
+    },
+
+    /// Manage the demiarch binary itself
+    #[command(name = "self")]
+    SelfManage {
+        #[command(subcommand)]
+        action: SelfAction,
+    },
+
+    /// Database maintenance
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
+    },
+
     /// Open TUI monitor (watch mode)
     Watch,
 
@@ -173,6 +446,18 @@ enum Commands {
         action: SessionAction,
     },
 
+    /// Scratchpad notes for the active session
+    Notes {
+        #[command(subcommand)]
+        action: NoteAction,
+    },
+
+    /// Prompt snippets referenced as {{snippet:name}} in chat and generate
+    Snippets {
+        #[command(subcommand)]
+        action: SnippetAction,
+    },
+
     /// Explore knowledge graph (entities, relationships)
     Graph {
         #[command(subcommand)]
@@ -184,14 +469,41 @@ enum Commands {
         #[command(subcommand)]
         action: ImageAction,
     },
+
+    /// Run WASM plugins and manage their permission grants
+    Plugins {
+        #[command(subcommand)]
+        action: PluginAction,
+    },
 }
 
 #[derive(Subcommand)]
 enum ProjectAction {
     /// List all projects
-    List,
+    List {
+        /// Show at most this many projects
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Skip this many projects
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+    },
     /// Show project details
     Show { id: String },
+    /// Score project health and list what needs attention
+    Health {
+        /// Project ID or name
+        id: String,
+        /// Days after which in-progress features and the context index count as stale
+        #[arg(long, default_value_t = health::DEFAULT_STALE_DAYS)]
+        stale_days: i64,
+    },
+    /// Archive idle projects and purge old checkpoints, logs and events
+    Cleanup {
+        /// Report what would be archived or purged without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Archive a project
     Archive { id: String },
     /// Delete a project
@@ -208,23 +520,347 @@ enum FeatureAction {
     List {
         #[arg(short, long)]
         status: Option<String>,
+        /// Show at most this many features
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Skip this many features
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
     },
     /// Show feature details
     Show { id: String },
     /// Create a new feature
     Create {
-        title: String,
+        #[arg(required_unless_present = "edit")]
+        title: Option<String>,
         #[arg(short, long)]
         phase: Option<String>,
+        /// Voice note to transcribe into the description
+        #[arg(long)]
+        audio: Option<std::path::PathBuf>,
+        /// Write the feature's fields and description in $EDITOR
+        #[arg(short, long)]
+        edit: bool,
     },
     /// Update a feature
     Update {
         id: String,
         #[arg(short, long)]
         status: Option<String>,
+        /// Edit the feature's fields and description in $EDITOR
+        #[arg(short, long)]
+        edit: bool,
+    },
+    /// Delete one or more features
+    Delete {
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    /// Show time spent on a feature, or per feature for the project
+    Time {
+        /// Feature ID; omit for a summary of the project's features
+        id: Option<String>,
+    },
+    /// Create features from a Markdown task list or CSV file
+    Import {
+        /// `.md` checkbox list or headings, or `.csv` with a title column
+        #[arg(long)]
+        file: std::path::PathBuf,
+        /// Phase to put the imported features in
+        #[arg(short, long)]
+        phase: Option<String>,
+        /// Title similarity (0-1) at which a row is skipped as a duplicate
+        #[arg(long, default_value_t = feature_import::DEFAULT_DUPLICATE_THRESHOLD)]
+        threshold: f32,
+        /// Show the preview without creating anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Create without asking after the preview
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Predict tokens, cost and time for a feature from similar past work
+    Estimate { id: String },
+    /// Create or switch to the feature's branch (named by generate.branch_template)
+    /// and link it onto the feature
+    Branch {
+        /// Feature ID or ID prefix
+        id: String,
+        /// Check the branch out into a new worktree beside the repository instead
+        #[arg(long)]
+        worktree: bool,
+    },
+    /// Summarize a conversation into Given/When/Then acceptance criteria
+    DeriveCriteria {
+        id: String,
+        /// Conversation to derive the criteria from
+        #[arg(long)]
+        from_conversation: String,
+        /// Store the criteria without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ArtifactAction {
+    /// Show which generation produced each range of lines in a file
+    Blame { file: std::path::PathBuf },
+}
+
+#[derive(Subcommand)]
+enum PhaseAction {
+    /// List phases with their completion
+    List {
+        /// Project ID or name (defaults to the project in the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Show a phase's features and burndown
+    Show {
+        /// Phase ID or name
+        phase: String,
+        /// Project ID or name (defaults to the project in the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Create a phase
+    Create {
+        name: String,
+        /// Target date, YYYY-MM-DD
+        #[arg(long)]
+        target: Option<chrono::NaiveDate>,
+        #[arg(short, long)]
+        description: Option<String>,
+        /// Position in the phase order (defaults to after the last phase)
+        #[arg(long)]
+        order: Option<i32>,
+        /// Project ID or name (defaults to the project in the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Update a phase's name, target date, description, order or status
+    Update {
+        /// Phase ID or name
+        phase: String,
+        #[arg(long)]
+        name: Option<String>,
+        /// Target date, YYYY-MM-DD
+        #[arg(long, conflicts_with = "clear_target")]
+        target: Option<chrono::NaiveDate>,
+        /// Remove the target date
+        #[arg(long)]
+        clear_target: bool,
+        #[arg(short, long)]
+        description: Option<String>,
+        #[arg(long)]
+        order: Option<i32>,
+        #[arg(short, long, value_parser = ["pending", "in_progress", "complete", "skipped"])]
+        status: Option<String>,
+        /// Project ID or name (defaults to the project in the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Assign features to a phase
+    Assign {
+        /// Phase ID or name
+        phase: String,
+        /// Feature IDs
+        #[arg(required = true)]
+        features: Vec<String>,
+        /// Project ID or name (defaults to the project in the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum OpenRecord {
+    Project,
+    Feature,
+    Document,
+    Checkpoint,
+}
+
+impl From<OpenRecord> for open::OpenKind {
+    fn from(record: OpenRecord) -> Self {
+        match record {
+            OpenRecord::Project => Self::Project,
+            OpenRecord::Feature => Self::Feature,
+            OpenRecord::Document => Self::Document,
+            OpenRecord::Checkpoint => Self::Checkpoint,
+        }
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum DigestSpan {
+    Day,
+    Week,
+}
+
+impl From<DigestSpan> for digest::DigestPeriod {
+    fn from(span: DigestSpan) -> Self {
+        match span {
+            DigestSpan::Day => Self::Day,
+            DigestSpan::Week => Self::Week,
+        }
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum JobKind {
+    /// Generate code from a description
+    Generate,
+    /// Generate a PRD for a project
+    Prd,
+    /// Generate an architecture document for a project
+    Architecture,
+    /// Merge near-duplicate context memories for a project
+    Consolidate,
+    /// Decay and archive stale knowledge, repeating at an interval
+    AgeKnowledge,
+}
+
+#[derive(Subcommand)]
+enum JobAction {
+    /// Queue a job
+    Enqueue {
+        /// What to run
+        #[arg(value_enum)]
+        kind: JobKind,
+        /// Description to generate from, the project ID for documents and consolidation,
+        /// or how often to repeat knowledge aging (12h, 1d, once)
+        target: String,
+        /// When to run: HH:MM, "YYYY-MM-DD HH:MM", +30m/+2h/+1d, or RFC 3339
+        #[arg(long, default_value = "now")]
+        at: String,
+        /// Attempts before the job is marked failed
+        #[arg(long, default_value_t = jobs::DEFAULT_MAX_ATTEMPTS)]
+        max_attempts: i64,
+        /// Language to write documents in (prd, architecture)
+        #[arg(long)]
+        language: Option<String>,
+    },
+    /// List jobs
+    List {
+        /// Filter by status (queued, running, succeeded, failed, cancelled)
+        #[arg(short, long)]
+        status: Option<String>,
+        /// Maximum number of jobs to show
+        #[arg(short, long)]
+        limit: Option<i64>,
+    },
+    /// Show job details
+    Show { id: String },
+    /// Cancel a queued job
+    Cancel { id: String },
+    /// Run due jobs as they become due
+    Run {
+        /// Run the jobs that are due now, then exit
+        #[arg(long)]
+        once: bool,
+        /// Seconds between checks for due jobs
+        #[arg(long, default_value_t = 30)]
+        poll_secs: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum EvalAction {
+    /// Run a task against several models and report how they compare
+    Run {
+        /// Task file (YAML, TOML or JSON) with name, description, and
+        /// optional framework and test_command
+        #[arg(short, long, value_name = "FILE")]
+        task: std::path::PathBuf,
+        /// Comma-separated models to compare
+        #[arg(short, long)]
+        models: String,
+        /// Runs per model
+        #[arg(short, long, default_value_t = 1)]
+        repeat: u32,
+        /// Directory for run files and the report (default: .demiarch/evals/<task>-<time>)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Record model responses in the response cache and replay them on
+        /// later runs, so the evaluation is reproducible
+        #[arg(long)]
+        replay: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum GenerationAction {
+    /// List past generations, newest first
+    List {
+        /// Only generations for this project
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Only generations for this feature
+        #[arg(short, long)]
+        feature: Option<String>,
+        /// Only generations started in this session
+        #[arg(long)]
+        session: Option<String>,
+        /// Filter by status (running, completed, failed, interrupted)
+        #[arg(long)]
+        status: Option<String>,
+        /// Maximum number to show
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
+    },
+    /// Show a generation's plan, usage and files
+    Show {
+        /// Generation ID
+        id: String,
+    },
+    /// Delete a generation record (files already written are kept)
+    Delete {
+        /// Generation ID
+        id: String,
+        /// Delete without asking
+        #[arg(long)]
+        force: bool,
+    },
+    /// Interactively review pending files of a generation
+    Review {
+        /// Generation ID
+        id: String,
+    },
+    /// Apply pending or rejected files of a generation
+    Apply {
+        /// Generation ID
+        id: String,
+        /// Only apply this file (path as shown in review)
+        #[arg(short, long)]
+        file: Option<String>,
+    },
+    /// Regenerate one file by re-running only the task that produced it
+    Regen {
+        /// Generation ID
+        id: String,
+        /// File to regenerate (path as shown in review)
+        #[arg(short, long)]
+        file: String,
+        /// Extra instructions for the model, e.g. "use react-hook-form"
+        #[arg(short, long)]
+        instructions: String,
+    },
+    /// Show the environment a generation ran under and what has changed since
+    Env {
+        /// Generation ID
+        id: String,
+    },
+    /// Finish or undo file writes that were interrupted partway
+    Recover {
+        /// Output directory the files were written to
+        #[arg(short, long, default_value = ".")]
+        dir: std::path::PathBuf,
+        /// Restore the files already replaced instead of writing the rest
+        #[arg(long)]
+        undo: bool,
     },
-    /// Delete a feature
-    Delete { id: String },
 }
 
 #[derive(Subcommand)]
@@ -234,19 +870,37 @@ enum DocumentAction {
         /// Project ID
         #[arg(short, long)]
         project: String,
+        /// Language to write the document in (de, fr, ja, pt-BR, ...; en for
+        /// English); defaults to the language of the previous one
+        #[arg(long)]
+        language: Option<String>,
     },
     /// Generate an architecture document for a project
     GenerateArchitecture {
         /// Project ID
         #[arg(short, long)]
         project: String,
+        /// Language to write the document in (de, fr, ja, pt-BR, ...; en for
+        /// English); defaults to the language of the previous one
+        #[arg(long)]
+        language: Option<String>,
+    },
+    /// Generate (or refresh) a roadmap from the project's phases and features
+    GenerateRoadmap {
+        /// Project ID or name (defaults to the project in the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Language to translate the roadmap into (de, fr, ja, ...; en for
+        /// English); defaults to the language of the current roadmap
+        #[arg(long)]
+        language: Option<String>,
     },
     /// List documents for a project
     List {
         /// Project ID
         #[arg(short, long)]
         project: String,
-        /// Document type (prd, architecture, design, tech_spec)
+        /// Document type (prd, architecture, design, tech_spec, roadmap, digest)
         #[arg(short, long)]
         doc_type: Option<String>,
     },
@@ -268,6 +922,14 @@ enum DocumentAction {
         #[arg(short, long)]
         output: String,
     },
+    /// Edit a document's content in $EDITOR and store it as a new version
+    Edit {
+        /// Document ID
+        id: String,
+        /// Summary of the change, kept with the version
+        #[arg(short, long)]
+        summary: Option<String>,
+    },
     /// Delete a document
     Delete { id: String },
 }
@@ -310,6 +972,30 @@ enum RoutingAction {
         #[arg(short, long)]
         limit: Option<usize>,
     },
+    /// Score models on a task suite and seed routing statistics
+    Benchmark {
+        /// Bundled suite name or path to a suite YAML file
+        #[arg(short, long, default_value = benchmark::DEFAULT_SUITE)]
+        suite: String,
+        /// Comma-separated models (defaults to the default and fallback models)
+        #[arg(short, long)]
+        models: Option<String>,
+        /// Print scores without recording them
+        #[arg(long)]
+        no_seed: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Show cache statistics
+    Stats,
+    /// Remove cached responses
+    Clear {
+        /// Only remove expired entries and entries over the size cap
+        #[arg(long)]
+        expired: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -332,10 +1018,39 @@ enum ContextAction {
         #[arg(long)]
         dry_run: bool,
     },
-    /// Rebuild context index
+    /// Rebuild context index and consolidate near-duplicate entries
     Rebuild {
         #[arg(short, long)]
         project: Option<String>,
+        /// Skip the consolidation pass
+        #[arg(long)]
+        no_consolidate: bool,
+        /// Similarity (0-1) above which entries are merged
+        #[arg(long, default_value_t = DEFAULT_CONSOLIDATION_THRESHOLD)]
+        threshold: f32,
+    },
+    /// Merge near-duplicate context entries
+    Consolidate {
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Similarity (0-1) above which entries are merged
+        #[arg(long, default_value_t = DEFAULT_CONSOLIDATION_THRESHOLD)]
+        threshold: f32,
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Pin a decision so it is always recalled and never pruned
+    Pin {
+        text: String,
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Unpin a previously pinned entry
+    Unpin { id: String },
+    /// List pinned entries
+    Pins {
+        #[arg(short, long)]
+        project: Option<String>,
     },
 }
 
@@ -366,16 +1081,70 @@ enum HookAction {
     },
 }
 
+#[derive(Subcommand)]
+enum WorkspaceAction {
+    /// Costs, sessions, features and agent runs per project, with totals
+    Stats {
+        /// Member projects by ID or name (comma-separated, defaults to all active projects)
+        #[arg(short, long, value_delimiter = ',')]
+        projects: Vec<String>,
+        /// Also write the per-project breakdown to a CSV file
+        #[arg(long, value_name = "FILE")]
+        csv: Option<std::path::PathBuf>,
+    },
+}
+
 #[derive(Subcommand)]
 enum SyncAction {
     /// Flush SQLite to JSONL
-    Flush,
+    Flush {
+        /// Write zstd-compressed `.jsonl.zst` files (for large chat/event tables)
+        #[arg(long)]
+        compress: bool,
+    },
     /// Import JSONL to SQLite
     Import,
     /// Show sync status
     Status,
 }
 
+#[derive(Subcommand)]
+enum CostAction {
+    /// Compare spend between two periods
+    Compare {
+        /// Baseline period, YYYY-MM-DD..YYYY-MM-DD
+        #[arg(long)]
+        from: String,
+        /// Period to compare against the baseline, YYYY-MM-DD..YYYY-MM-DD
+        #[arg(long)]
+        to: String,
+        /// Break the comparison down by model or feature
+        #[arg(long, value_parser = ["model", "feature"])]
+        by: Option<String>,
+        /// Project ID (optional, defaults to all projects)
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Export a month of a project's costs as an invoice for client billing
+    Invoice {
+        /// Project ID or name (defaults to the project in the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Month to bill, YYYY-MM
+        #[arg(long)]
+        month: String,
+        /// CSV file to write (prints to stdout if omitted)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Markup percentage (defaults to invoice.markup_percent)
+        #[arg(long)]
+        markup: Option<f64>,
+        /// Currency code (defaults to invoice.currency)
+        #[arg(long)]
+        currency: Option<String>,
+    },
+}
+
 #[derive(Subcommand)]
 enum CheckpointAction {
     /// List checkpoints for a project
@@ -415,6 +1184,22 @@ enum CheckpointAction {
         /// Checkpoint ID
         id: String,
     },
+    /// Export a checkpoint, with its files and signature, to an archive
+    Export {
+        /// Checkpoint ID
+        id: String,
+        /// Archive to write, e.g. cp.tar.zst
+        #[arg(short, long)]
+        output: std::path::PathBuf,
+    },
+    /// Import a checkpoint archive into a project
+    Import {
+        /// Archive written by `checkpoints export`
+        file: std::path::PathBuf,
+        /// Project ID to attach the checkpoint to
+        #[arg(short, long)]
+        project: String,
+    },
     /// Delete a checkpoint
     Delete {
         /// Checkpoint ID
@@ -431,6 +1216,210 @@ enum CheckpointAction {
     },
 }
 
+#[derive(Subcommand)]
+enum SecretAction {
+    /// Store or replace a secret; the value is read from stdin when omitted
+    Set {
+        /// Environment variable name, e.g. DATABASE_URL
+        name: String,
+        /// Secret value (prefer stdin so it stays out of shell history)
+        value: Option<String>,
+        /// Project ID or name (default: project in the current directory)
+        #[arg(short, long)]
+        project: Option<String>,
+        /// What the secret is for
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+    /// Print a secret's value
+    Get {
+        name: String,
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// List secret names
+    List {
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Remove a secret
+    Unset {
+        name: String,
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Export all secrets with their values
+    Export {
+        /// Write in .env format (the only format supported)
+        #[arg(long, required = true)]
+        dotenv: bool,
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PersonaAction {
+    /// List the project's personas
+    List {
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Show a persona and the prompt it adds
+    Show {
+        name: String,
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Add or replace a persona
+    Add {
+        /// Persona name, e.g. "reviewer"
+        name: String,
+        /// How replies should sound, e.g. "concise and direct"
+        #[arg(long)]
+        tone: Option<String>,
+        /// Experience level to write for, e.g. "senior"
+        #[arg(long)]
+        seniority: Option<String>,
+        /// Preferred languages and frameworks (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        stack: Vec<String>,
+        /// Further instructions for the assistant
+        #[arg(long)]
+        instructions: Option<String>,
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Remove a persona
+    Remove {
+        name: String,
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Export personas as TOML to share with other projects
+    Export {
+        /// Personas to export (default: all)
+        names: Vec<String>,
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Import personas from a TOML file
+    Import {
+        file: std::path::PathBuf,
+        /// Replace personas that already exist
+        #[arg(long)]
+        replace: bool,
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum LicenseAction {
+    /// Verify a license key offline and store it in the encrypted key store
+    Activate {
+        /// License key from your purchase email
+        key: String,
+    },
+    /// Show the active license's tier and expiry
+    Status,
+    /// Remove the stored license (falls back to the Free tier)
+    Deactivate,
+}
+
+#[derive(Subcommand)]
+enum PrAction {
+    /// Store or replace a forge token; read from stdin when omitted
+    SetToken {
+        #[arg(value_parser = ["github", "gitlab"])]
+        forge: String,
+        /// Token (prefer stdin so it stays out of shell history)
+        token: Option<String>,
+    },
+    /// Remove a stored forge token
+    RemoveToken {
+        #[arg(value_parser = ["github", "gitlab"])]
+        forge: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SelfAction {
+    /// Download and install the latest signed release
+    Update {
+        /// Release channel: stable or beta (default: updates.channel)
+        #[arg(long)]
+        channel: Option<String>,
+        /// Only report whether an update is available
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum DbAction {
+    /// Deep-scan the database: orphaned rows, checkpoint signatures, generated file hashes
+    Verify {
+        /// Fix orphaned rows (clear dangling optional references, delete the rest)
+        #[arg(long)]
+        repair: bool,
+        /// Hex-encoded public key to verify checkpoint signatures with
+        #[arg(long, value_name = "HEX")]
+        public_key: Option<String>,
+        /// Skip reading generated files from disk
+        #[arg(long)]
+        skip_files: bool,
+    },
+    /// Show how much the compressed, deduplicated content store saves
+    Stats {
+        /// Move content still stored inline into the store and drop content
+        /// nothing refers to any more
+        #[arg(long)]
+        compact: bool,
+    },
+    /// List tables and their columns
+    Schema {
+        /// Show only this table (aliases: costs, daily_costs, usage, artifacts)
+        table: Option<String>,
+    },
+    /// Export a table for analytics (use --format csv or --format parquet)
+    Export {
+        /// Table to export (aliases: costs, daily_costs, usage, artifacts)
+        #[arg(long)]
+        table: String,
+        /// Output file; CSV goes to stdout when omitted
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Comma-separated columns to include (default: all)
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+        /// First day to include (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// Last day to include (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<chrono::NaiveDate>,
+        /// Column the date range applies to (default: created_at, then date)
+        #[arg(long)]
+        date_column: Option<String>,
+    },
+    /// Review statements from the slow-query log (enable with database.instrument)
+    SlowQueries {
+        /// Number of statements to show
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+        /// Empty the log after showing it
+        #[arg(long)]
+        clear: bool,
+    },
+}
+
 #[derive(Subcommand)]
 enum ConfigAction {
     /// Get a configuration value
@@ -443,6 +1432,9 @@ enum ConfigAction {
     Reset,
     /// Show config file path
     Path,
+    /// Validate the config file and have running processes (GUI, job
+    /// runner) apply it now
+    Reload,
 }
 
 #[derive(Subcommand)]
@@ -469,6 +1461,79 @@ enum AgentAction {
     Status,
     /// List agent types and their capabilities
     Types,
+    /// Show recorded agent events, including archived sessions
+    Events {
+        /// Event session ID (defaults to the current session)
+        #[arg(long)]
+        session: Option<String>,
+        /// List sessions with recorded events instead
+        #[arg(long)]
+        list: bool,
+    },
+    /// Export a session's agent hierarchy as a standalone SVG or HTML file
+    Export {
+        /// Event session ID (defaults to the current session)
+        #[arg(long)]
+        session: Option<String>,
+        /// Output format: svg or html
+        #[arg(long, default_value = "html")]
+        format: String,
+        /// Output file (defaults to agents-<session>.<format>)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Show a session's agent runs as a Gantt timeline
+    Timeline {
+        /// Event session ID (defaults to the current session)
+        #[arg(long)]
+        session: Option<String>,
+        /// Output format: text or svg
+        #[arg(long, default_value = "text")]
+        format: String,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Width of the bars in text output
+        #[arg(long, default_value = "60")]
+        width: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum NoteAction {
+    /// Attach a note to the active session
+    Add {
+        /// Note text
+        content: String,
+        /// Also remind the next generation of the note
+        #[arg(long)]
+        remind: bool,
+    },
+    /// List the notes of the active session
+    List {
+        /// Session ID (defaults to active session)
+        #[arg(short, long)]
+        session: Option<String>,
+    },
+    /// Delete a note from the active session
+    Remove {
+        /// Note ID or prefix
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnippetAction {
+    /// List the snippets in the snippets directory
+    List,
+    /// Print a snippet
+    Show {
+        /// Snippet name (file name without .md)
+        name: String,
+        /// Expand nested snippets and template variables for the current project
+        #[arg(short, long)]
+        expand: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -528,6 +1593,19 @@ enum SessionAction {
         #[arg(short, long)]
         limit: Option<i32>,
     },
+    /// Compile a report of a session's events, features, generations,
+    /// costs and checkpoints
+    Report {
+        /// Session ID
+        id: String,
+        /// Write the report to a file; the format follows the extension
+        /// (.md, .html, .json)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Report format when printing to stdout (markdown, html, json)
+        #[arg(long)]
+        report_format: Option<String>,
+    },
     /// Clean up old sessions
     Cleanup {
         /// Delete sessions older than this many days
@@ -600,6 +1678,96 @@ enum GraphAction {
         #[arg(short, long, default_value = "20")]
         limit: usize,
     },
+
+    /// Merge a duplicate entity into another, keeping its names as aliases
+    Merge {
+        /// ID of the entity to keep
+        keep_id: String,
+
+        /// ID of the duplicate to fold into it and delete
+        duplicate_id: String,
+    },
+
+    /// Record another name for an entity
+    Alias {
+        /// Entity ID
+        entity_id: String,
+
+        /// Alternative name or spelling
+        alias: String,
+    },
+
+    /// Report pairs of entities that are probably duplicates
+    Duplicates {
+        /// Minimum name similarity (0.0-1.0)
+        #[arg(short, long, default_value_t = graph::DEFAULT_DUPLICATE_THRESHOLD)]
+        threshold: f32,
+
+        /// Maximum pairs to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Find skills and earlier features related to a task description
+    Related {
+        /// Description of the task to plan
+        description: String,
+
+        /// Maximum skills and features to return
+        #[arg(short, long, default_value = "5")]
+        limit: usize,
+    },
+
+    /// Decay confidence of knowledge not reinforced recently and archive what has faded
+    Age {
+        /// Show what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PluginAction {
+    /// Scaffold a Rust-to-WASM plugin project
+    New {
+        /// Plugin name (lowercase letters, digits and hyphens)
+        name: String,
+        /// Example to start from: generator, hook or panel
+        #[arg(long, default_value = "generator")]
+        capability: String,
+        /// Directory to create the project in (default: current directory)
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Run a plugin, asking before it first uses each permission
+    Run {
+        /// Path to the plugin's manifest.json (under the plugin directory)
+        manifest: std::path::PathBuf,
+        /// WASM module (default: plugin.wasm next to the manifest)
+        #[arg(long)]
+        wasm: Option<std::path::PathBuf>,
+        /// Host the plugin may reach without asking (repeatable)
+        #[arg(long = "allow-host")]
+        allow_hosts: Vec<String>,
+        /// Program the plugin may run through the sandboxed runner (repeatable)
+        #[arg(long = "allow-program")]
+        allow_programs: Vec<String>,
+        /// Deny anything not already granted instead of prompting
+        #[arg(long)]
+        no_prompt: bool,
+    },
+    /// Show remembered permission decisions
+    Permissions {
+        /// Only this plugin
+        plugin: Option<String>,
+    },
+    /// Forget all permission decisions for a plugin
+    Revoke { plugin: String },
+    /// Show executions, fuel used and failures per plugin
+    Stats {
+        /// Only this plugin
+        plugin: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -741,29 +1909,100 @@ fn validate_license_key_on_startup() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Subscribe configured webhooks and installed plugins to core events, once
+async fn attach_event_subscribers(db: &Database, config: Option<&Config>) {
+    static ATTACHED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+    if ATTACHED.swap(true, std::sync::atomic::Ordering::SeqCst) {
+        return;
+    }
+
+    let bus = events::global();
+    if let Some(config) = config {
+        if let Err(e) = events::attach_webhooks(bus, &config.events) {
+            warn!("Event webhooks not attached: {}", e);
+        }
+    }
+
+    let tier = license::LicenseManager::new(db).active_tier().await;
+    let limits = config
+        .map(|c| ResourceLimits::from(c.plugins.limits.for_tier(tier)))
+        .unwrap_or_default();
+    let attached = std::env::current_dir()
+        .map_err(anyhow::Error::from)
+        .and_then(|dir| {
+            Ok(plugin_events::attach_installed(
+                bus,
+                tier.into(),
+                limits,
+                &dir,
+            )?)
+        });
+    if let Err(e) = attached {
+        warn!("Plugin event subscribers not attached: {}", e);
+    }
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Load .env file if present (silently ignore if not found)
     dotenvy::dotenv().ok();
 
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("demiarch=info".parse()?),
-        )
-        .init();
+    let config = Config::load().ok();
+
+    // Initialize tracing; query timing gets its own layer so it sees sqlx
+    // statements whatever RUST_LOG says
+    {
+        use tracing_subscriber::layer::SubscriberExt;
+        use tracing_subscriber::util::SubscriberInitExt;
+        use tracing_subscriber::Layer;
+
+        let query_layer = config
+            .as_ref()
+            .and_then(|c| storage::instrumentation::layer(&c.database));
+        tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer().with_filter(
+                    tracing_subscriber::EnvFilter::from_default_env()
+                        .add_directive("demiarch=info".parse()?),
+                ),
+            )
+            .with(query_layer)
+            .init();
+    }
 
     // Validate license issuer key early if license enforcement is enabled
     validate_license_key_on_startup()?;
 
     let cli = Cli::parse();
 
-    // Initialize database manager for commands that need it
-    // We lazily initialize it only when needed to avoid startup overhead
-    let get_db = || async { DatabaseManager::new().await.map(|mgr| mgr.global().clone()) };
+    let config_offline = config.as_ref().is_some_and(|c| c.network.offline);
+    network::set_offline(cli.offline || config_offline);
+    i18n::init(config.as_ref());
+    glyphs::set_plain(cli.plain || config.as_ref().is_some_and(|c| c.ui.plain));
+    if let Some(path) = &cli.database {
+        storage::set_database_path(path);
+    }
+    storage::set_read_only(cli.read_only || storage::is_read_only());
+    if let Some(operation) = write_operation(&cli.command) {
+        storage::ensure_writable(operation)?;
+    }
+
+    // The global database is opened on first use and shared through the
+    // connection cache, so commands that never touch it pay nothing
+    let databases = DatabaseManager::new();
+    let get_db = || async {
+        let db = databases.global().await?.clone();
+        if !db.is_read_only() {
+            record_heartbeat(&db).await;
+        }
+        attach_event_subscribers(&db, config.as_ref()).await;
+        Ok::<_, anyhow::Error>(db)
+    };
 
-    match cli.command {
+    let format = cli.format;
+    // Spinners and bars for long-running commands; hidden in quiet/JSON modes
+    let progress = || Progress::for_output(cli.quiet, matches!(format, OutputFormat::Json));
+    let result = match cli.command {
         Commands::New {
             name,
             framework,
@@ -779,11 +2018,23 @@ async fn main() -> anyhow::Result<()> {
             cmd_init(&db, &framework, repo.as_deref(), cli.quiet).await
         }
 
-        Commands::Chat => cmd_chat(cli.quiet).await,
+        Commands::Chat { audio, message } => {
+            let message = read_stdin_arg(message)?;
+            cmd_chat(
+                audio.as_deref(),
+                message.as_deref(),
+                cli.quiet,
+                matches!(format, OutputFormat::Json),
+            )
+            .await
+        }
 
         Commands::Projects { action } => {
             let db = get_db().await?;
-            cmd_projects(&db, action, cli.quiet).await
+            if !matches!(action, ProjectAction::Cleanup { .. }) {
+                auto_archive_projects(&db, cli.quiet).await;
+            }
+            cmd_projects(&db, action, cli.quiet, matches!(format, OutputFormat::Json)).await
         }
 
         Commands::Features { action } => {
@@ -791,58 +2042,503 @@ async fn main() -> anyhow::Result<()> {
             cmd_features(&db, action, cli.quiet).await
         }
 
-        Commands::Generate {
-            description,
-            dry_run,
-        } => cmd_generate(&description, dry_run, cli.quiet).await,
-
-        Commands::Documents { action } => {
+        Commands::Phases { action } => {
             let db = get_db().await?;
-            cmd_documents(&db, action, cli.quiet).await
+            cmd_phases(&db, action, cli.quiet, matches!(format, OutputFormat::Json)).await
         }
 
-        Commands::Skills { action } => {
+        Commands::Generate {
+            dry_run,
+            from_file: Some(spec_path),
+            watch,
+            ..
+        } => {
             let db = get_db().await?;
-            cmd_skills(&db, action, cli.quiet).await
+            cmd_generate_spec(&db, &spec_path, watch, dry_run, cli.quiet, &progress()).await
         }
 
-        Commands::Routing { action } => cmd_routing(action, cli.quiet).await,
-
-        Commands::Context { action } => {
+        Commands::Generate {
+            dry_run,
+            feature,
+            concurrency,
+            budget,
+            no_checkpoint,
+            ..
+        } if !feature.is_empty() => {
             let db = get_db().await?;
-            cmd_context(&db, action, cli.quiet).await
+            let options = queue::QueueOptions {
+                concurrency,
+                budget_usd: budget,
+                checkpoint: !no_checkpoint,
+                dry_run,
+            };
+            cmd_generate_queue(
+                &db,
+                &feature,
+                &options,
+                cli.quiet,
+                matches!(format, OutputFormat::Json),
+                &progress(),
+            )
+            .await
         }
 
-        Commands::Hooks { action } => cmd_hooks(action, cli.quiet).await,
-
-        Commands::Costs { project } => cmd_costs(project.as_deref(), cli.quiet).await,
+        Commands::Generate {
+            description,
+            dry_run,
+            review,
+            resume,
+            from_file: None,
+            phase,
+            open_pr,
+            yes,
+            ..
+        } => {
+            if review && description.as_deref() == Some("-") {
+                anyhow::bail!("--review asks questions on stdin, so it cannot be used with a piped description");
+            }
+            let description = read_stdin_arg(description)?;
+            let db = get_db().await?;
+            let description = match phase {
+                Some(phase) => {
+                    let project = resolve_project(&db, None).await?;
+                    let phase = phase::resolve_phase(&db, &project.id, &phase).await?;
+                    Some(planner::scope_to_phase(&db, &phase).await?)
+                }
+                None => description,
+            };
+            cmd_generate(
+                &db,
+                description.as_deref(),
+                resume.as_deref(),
+                dry_run,
+                review,
+                open_pr,
+                yes,
+                cli.quiet,
+                &progress(),
+            )
+            .await
+        }
+
+        Commands::Artifacts {
+            action: ArtifactAction::Blame { file },
+        } => {
+            let db = get_db().await?;
+            cmd_artifacts_blame(&db, &file, cli.quiet, matches!(format, OutputFormat::Json)).await
+        }
+
+        Commands::Generations { action } => {
+            let db = get_db().await?;
+            cmd_generations(&db, action, cli.quiet, matches!(format, OutputFormat::Json)).await
+        }
+
+        Commands::Jobs { action } => {
+            let db = get_db().await?;
+            cmd_jobs(&db, action, cli.quiet).await
+        }
+
+        Commands::Eval { action } => {
+            let db = get_db().await?;
+            cmd_eval(
+                &db,
+                action,
+                cli.quiet,
+                matches!(format, OutputFormat::Json),
+                &progress(),
+            )
+            .await
+        }
+
+        Commands::Open {
+            record,
+            id,
+            editor,
+            app,
+            print,
+        } => {
+            let db = get_db().await?;
+            cmd_open(&db, record.into(), &id, editor, app, print, cli.quiet).await
+        }
+
+        Commands::Inbox { project } => {
+            let db = get_db().await?;
+            cmd_inbox(
+                &db,
+                project.as_deref(),
+                cli.quiet,
+                matches!(format, OutputFormat::Json),
+            )
+            .await
+        }
+
+        Commands::Digest {
+            period,
+            project,
+            model,
+            post,
+        } => {
+            let db = get_db().await?;
+            cmd_digest(
+                &db,
+                period.into(),
+                project.as_deref(),
+                model.as_deref(),
+                post,
+                cli.quiet,
+                matches!(format, OutputFormat::Json),
+            )
+            .await
+        }
+
+        Commands::Changelog {
+            since,
+            project,
+            version,
+            write,
+        } => {
+            let db = get_db().await?;
+            cmd_changelog(
+                &db,
+                &since,
+                project.as_deref(),
+                &version,
+                write,
+                cli.quiet,
+                matches!(format, OutputFormat::Json),
+            )
+            .await
+        }
+
+        Commands::UpgradeAssist {
+            target,
+            dry_run,
+            generate,
+            concurrency,
+            budget,
+        } => {
+            let db = get_db().await?;
+            let options = queue::QueueOptions {
+                concurrency,
+                budget_usd: budget,
+                ..Default::default()
+            };
+            cmd_upgrade_assist(
+                &db,
+                &target,
+                dry_run,
+                generate.then_some(&options),
+                cli.quiet,
+                matches!(format, OutputFormat::Json),
+                &progress(),
+            )
+            .await
+        }
+
+        Commands::Documents { action } => {
+            let db = get_db().await?;
+            cmd_documents(&db, action, cli.quiet, &progress()).await
+        }
+
+        Commands::Skills { action } => {
+            let db = get_db().await?;
+            cmd_skills(&db, action, cli.quiet).await
+        }
+
+        Commands::Cache { action } => {
+            let db = get_db().await?;
+            cmd_cache(&db, action, cli.quiet).await
+        }
+
+        Commands::Routing { action } => {
+            let db = get_db().await?;
+            cmd_routing(
+                &db,
+                action,
+                cli.quiet,
+                matches!(format, OutputFormat::Json),
+                &progress(),
+            )
+            .await
+        }
+
+        Commands::Context { action } => {
+            let db = get_db().await?;
+            cmd_context(&db, action, cli.quiet).await
+        }
+
+        Commands::Hooks { action } => cmd_hooks(action, cli.quiet).await,
+
+        Commands::Costs {
+            action:
+                Some(CostAction::Compare {
+                    from,
+                    to,
+                    by,
+                    project,
+                }),
+            ..
+        } => {
+            let db = get_db().await?;
+            cmd_costs_compare(
+                &db,
+                &from,
+                &to,
+                by.as_deref(),
+                project.as_deref(),
+                cli.quiet,
+                matches!(format, OutputFormat::Json),
+            )
+            .await
+        }
+        Commands::Costs {
+            action:
+                Some(CostAction::Invoice {
+                    project,
+                    month,
+                    output,
+                    markup,
+                    currency,
+                }),
+            ..
+        } => {
+            let db = get_db().await?;
+            cmd_costs_invoice(
+                &db,
+                project.as_deref(),
+                &month,
+                output.as_deref(),
+                markup,
+                currency.as_deref(),
+                cli.quiet,
+                matches!(format, OutputFormat::Json),
+            )
+            .await
+        }
+        Commands::Costs {
+            project,
+            action: None,
+        } => {
+            let db = get_db().await?;
+            cmd_costs(&db, project.as_deref(), cli.quiet).await
+        }
+
+        Commands::Stats { project, weeks } => {
+            cmd_stats(
+                project.as_deref(),
+                weeks,
+                cli.quiet,
+                matches!(format, OutputFormat::Json),
+            )
+            .await
+        }
+
+        Commands::Workspace {
+            action: WorkspaceAction::Stats { projects, csv },
+        } => {
+            let db = get_db().await?;
+            cmd_workspace_stats(
+                &db,
+                &projects,
+                csv.as_deref(),
+                cli.quiet,
+                matches!(format, OutputFormat::Json),
+            )
+            .await
+        }
 
         Commands::Sync { action } => {
             let db = get_db().await?;
-            cmd_sync(&db, action, cli.quiet).await
+            cmd_sync(&db, action, cli.quiet, &progress()).await
         }
 
-        Commands::Checkpoints { action } => cmd_checkpoints(action, cli.quiet).await,
+        Commands::Checkpoints { action } => cmd_checkpoints(action, cli.quiet, &progress()).await,
 
         Commands::Config { action } => cmd_config(action, cli.quiet),
 
         Commands::Doctor => cmd_doctor(cli.quiet).await,
 
+        Commands::Secrets { action } => {
+            let db = get_db().await?;
+            cmd_secrets(&db, action, cli.quiet, matches!(format, OutputFormat::Json)).await
+        }
+
+        Commands::Personas { action } => {
+            let db = get_db().await?;
+            cmd_personas(&db, action, cli.quiet, matches!(format, OutputFormat::Json)).await
+        }
+
+        Commands::License { action } => {
+            let db = get_db().await?;
+            cmd_license(&db, action, cli.quiet, matches!(format, OutputFormat::Json)).await
+        }
+
+        Commands::Pr { action } => {
+            let db = get_db().await?;
+            cmd_pr(&db, action, cli.quiet).await
+        }
+
+        Commands::SelfManage { action } => {
+            cmd_self(action, cli.quiet, matches!(format, OutputFormat::Json)).await
+        }
+
+        Commands::Db {
+            action: DbAction::SlowQueries { limit, clear },
+        } => cmd_db_slow_queries(
+            limit,
+            clear,
+            cli.quiet,
+            matches!(format, OutputFormat::Json),
+        ),
+        Commands::Db { action } => {
+            let db = get_db().await?;
+            cmd_db(&db, action, cli.quiet, format).await
+        }
+
         Commands::Watch => cmd_watch(cli.quiet),
 
-        Commands::Agents { action } => cmd_agents(action, cli.quiet),
+        Commands::Agents { action } => {
+            cmd_agents(action, cli.quiet, matches!(format, OutputFormat::Json))
+        }
 
         Commands::Sessions { action } => {
             let db = get_db().await?;
             cmd_sessions(&db, action, cli.quiet).await
         }
 
+        Commands::Notes { action } => {
+            let db = get_db().await?;
+            cmd_notes(&db, action, cli.quiet).await
+        }
+
+        Commands::Snippets { action } => {
+            let db = get_db().await?;
+            cmd_snippets(&db, action, matches!(format, OutputFormat::Json)).await
+        }
+
         Commands::Graph { action } => {
             let db = get_db().await?;
             cmd_graph(&db, action, cli.quiet).await
         }
 
-        Commands::Image { action } => cmd_image(action, cli.quiet).await,
+        Commands::Image { action } => {
+            let db = get_db().await?;
+            cmd_image(&db, action, cli.quiet).await
+        }
+
+        Commands::Plugins { action } => {
+            cmd_plugins(action, cli.quiet, matches!(format, OutputFormat::Json)).await
+        }
+    };
+
+    match (result, format) {
+        (Err(e), OutputFormat::Json) => {
+            print_json_error(e);
+            std::process::exit(1);
+        }
+        (result, _) => result,
+    }
+}
+
+/// Name of the database change a command makes, if any
+///
+/// Used to fail fast in read-only mode instead of partway through a command.
+/// Commands not listed here still cannot write: SQLite rejects the statement.
+fn write_operation(command: &Commands) -> Option<&'static str> {
+    match command {
+        Commands::New { .. } => Some("project creation"),
+        Commands::Init { .. } => Some("project initialization"),
+        Commands::Chat { .. } => Some("chat"),
+        Commands::Generate { .. } => Some("code generation"),
+        Commands::Phases {
+            action:
+                PhaseAction::Create { .. } | PhaseAction::Update { .. } | PhaseAction::Assign { .. },
+        } => Some("phase update"),
+        Commands::Watch => Some("watch"),
+        Commands::Image { .. } => Some("image generation"),
+        Commands::Projects {
+            action:
+                ProjectAction::Cleanup { .. }
+                | ProjectAction::Archive { .. }
+                | ProjectAction::Delete { .. },
+        } => Some("project update"),
+        Commands::Features {
+            action:
+                FeatureAction::Create { .. }
+                | FeatureAction::Update { .. }
+                | FeatureAction::Delete { .. }
+                | FeatureAction::Import { dry_run: false, .. }
+                | FeatureAction::DeriveCriteria { .. }
+                | FeatureAction::Branch { .. },
+        } => Some("feature update"),
+        Commands::Documents {
+            action: DocumentAction::Edit { .. } | DocumentAction::GenerateRoadmap { .. },
+        } => Some("document update"),
+        Commands::Changelog { write: true, .. } => Some("changelog write"),
+        Commands::Digest { .. } => Some("digest"),
+        Commands::UpgradeAssist { dry_run: false, .. } => Some("upgrade planning"),
+        Commands::Jobs {
+            action: JobAction::Enqueue { .. } | JobAction::Cancel { .. } | JobAction::Run { .. },
+        } => Some("job update"),
+        Commands::Sync {
+            action: SyncAction::Import,
+        } => Some("sync import"),
+        Commands::Generations {
+            action: GenerationAction::Delete { .. },
+        } => Some("generation delete"),
+        Commands::Generations {
+            action: GenerationAction::Regen { .. },
+        } => Some("code generation"),
+        Commands::Checkpoints {
+            action:
+                CheckpointAction::Create { .. }
+                | CheckpointAction::Restore { .. }
+                | CheckpointAction::Import { .. }
+                | CheckpointAction::Delete { .. }
+                | CheckpointAction::DeleteAll { .. },
+        } => Some("checkpoint update"),
+        Commands::Db {
+            action: DbAction::Verify { repair: true, .. },
+        } => Some("integrity repair"),
+        Commands::Db {
+            action: DbAction::Stats { compact: true },
+        } => Some("content compaction"),
+        Commands::Secrets {
+            action: SecretAction::Set { .. } | SecretAction::Unset { .. },
+        } => Some("secret update"),
+        Commands::Personas {
+            action:
+                PersonaAction::Add { .. } | PersonaAction::Remove { .. } | PersonaAction::Import { .. },
+        } => Some("persona update"),
+        Commands::License {
+            action: LicenseAction::Activate { .. } | LicenseAction::Deactivate,
+        } => Some("license update"),
+        Commands::Pr { .. } => Some("forge token update"),
+        Commands::Notes {
+            action: NoteAction::Add { .. } | NoteAction::Remove { .. },
+        } => Some("session note"),
+        Commands::Graph {
+            action:
+                GraphAction::Merge { .. }
+                | GraphAction::Alias { .. }
+                | GraphAction::Age { dry_run: false },
+        } => Some("knowledge graph update"),
+        Commands::SelfManage {
+            action: SelfAction::Update { check: false, .. },
+        } => Some("self update"),
+        _ => None,
+    }
+}
+
+/// Print a failed command's error as a structured JSON payload
+///
+/// Errors that originate from demiarch-core keep their code, category and
+/// details; anything else is reported as an internal error.
+fn print_json_error(error: anyhow::Error) {
+    let payload = ErrorPayload::from(error);
+    match serde_json::to_string_pretty(&serde_json::json!({ "error": payload })) {
+        Ok(json) => println!("{}", json),
+        Err(_) => eprintln!("Error: {}", payload),
     }
 }
 
@@ -1095,15 +2791,23 @@ async fn run_with_agents_in_project(
             )
         })?;
 
+    let mut builder = LlmClient::builder()
+        .config(config.llm.clone())
+        .api_key(api_key);
+    if config.cache.enabled {
+        let db = Database::shared().await?;
+        if let Some(cache) = ResponseCache::from_config(&db, &config.cache) {
+            builder = builder.response_cache(cache);
+        }
+    }
     let llm_client = Arc::new(
-        LlmClient::builder()
-            .config(config.llm.clone())
-            .api_key(api_key)
+        builder
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to create LLM client: {}", e))?,
     );
 
-    let mut tool = AgentTool::new(llm_client);
+    let mut tool =
+        AgentTool::new(llm_client).with_sanitizer(ContentSanitizer::from_config(&config.safety));
     if let Some(path) = project_path {
         tool = tool.with_project_path(path.to_path_buf());
     }
@@ -1116,9 +2820,257 @@ async fn run_with_agents_in_project(
     Ok(result)
 }
 
-async fn cmd_chat(quiet: bool) -> anyhow::Result<()> {
+fn print_chat_context_usage(usage: &chat::ChatContextUsage) {
+    print_context_indicator(usage.stats());
+    println!("  System prompt: {} tokens", usage.system_tokens);
+    println!(
+        "  History: {} tokens ({} messages verbatim)",
+        usage.history_tokens, usage.history.kept
+    );
+    if usage.history.summarized > 0 {
+        println!(
+            "  Rolling summary: {} older messages in {} tokens",
+            usage.history.summarized, usage.history.summary_tokens
+        );
+    }
+}
+
+/// One-line context usage after a chat turn, flagged above 80%
+fn print_context_indicator(stats: WindowStats) {
+    let line = format!("Context: {} ({:.0}%)", stats, stats.ratio() * 100.0);
+    if stats.is_near_limit() {
+        println!(
+            "{}",
+            glyphs::warning(&format!("{}; older messages will be summarized", line))
+        );
+    } else {
+        println!("{}", line);
+    }
+}
+
+/// Show the next chat message's prompt tokens and cost; when a full reply
+/// would go over what is left of today's budget, offer the models that fit
+///
+/// Returns the model to send with, or `None` when the message is held back.
+async fn preview_chat_cost(
+    db: &Database,
+    config: &Config,
+    messages: &[Message],
+    model: &str,
+) -> anyhow::Result<Option<String>> {
+    let registry = ModelRegistry::with_defaults();
+    let remaining = preview::remaining_today(db, &config.cost, chrono::Utc::now()).await?;
+    let max_output = config.llm.max_tokens;
+    let current = preview::PromptPreview::new(&registry, model, messages, max_output, remaining);
+    println!("{}", prompt_preview_line(&current));
+    if current.fits() {
+        return Ok(Some(current.model));
+    }
+
+    println!(
+        "{}",
+        glyphs::warning("A full reply would go over today's remaining budget; not sent")
+    );
+    let cheaper = preview::alternatives(&registry, model, messages, max_output, remaining);
+    if cheaper.is_empty() || !io::stdin().is_terminal() {
+        println!("Raise cost.daily_limit_usd or start a shorter conversation to continue.");
+        return Ok(None);
+    }
+    let cheaper = &cheaper[..cheaper.len().min(9)];
+    println!("Models that fit:");
+    for (i, p) in cheaper.iter().enumerate() {
+        println!("  {}. {}", i + 1, prompt_preview_line(p));
+    }
+    let choice = prompt_choice(&format!("Send with [1-{}], or cancel [N]?", cheaper.len()))?;
+    Ok(choice
+        .to_digit(10)
+        .and_then(|n| cheaper.get((n as usize).checked_sub(1)?))
+        .map(|p| p.model.clone()))
+}
+
+fn prompt_preview_line(preview: &preview::PromptPreview) -> String {
+    let mut line = format!("{}: {} prompt tokens", preview.model, preview.prompt_tokens);
+    match (preview.prompt_cost_usd, preview.max_cost_usd) {
+        (Some(prompt), Some(max)) => line.push_str(&format!(
+            ", ${:.4} (up to ${:.4} with a full reply)",
+            prompt, max
+        )),
+        _ => line.push_str(", pricing unknown"),
+    }
+    if let Some(remaining) = preview.remaining_usd {
+        line.push_str(&format!(", ${:.2} left today", remaining));
+    }
+    line
+}
+
+/// What the next chat reply answers
+enum ChatReply {
+    /// The message just typed
+    Input,
+    /// The active branch as it stands, after /edit or a reply that failed
+    Branch,
+    /// The same messages as an earlier reply, which gains a variant (/retry)
+    Retry(chat::ChatMessage),
+}
+
+/// Chat commands for exploring alternative branches of a conversation
+const CHAT_BRANCH_COMMANDS: &[&str] = &["/history", "/branch", "/branches", "/switch", "/compare"];
+
+/// Run a branch command typed in chat
+async fn chat_branch_command(
+    db: &Database,
+    conversation_id: &str,
+    input: &str,
+) -> anyhow::Result<()> {
+    let mut words = input.split_whitespace();
+    let command = words.next().unwrap_or_default();
+    let args: Vec<&str> = words.collect();
+
+    match command {
+        "/history" => {
+            let branch = chat::active_branch(db, conversation_id).await?;
+            let history = chat::branch_history(db, &branch).await?;
+            println!("Branch '{}' ({} messages)", branch.name, history.len());
+            for (i, message) in history.iter().enumerate() {
+                let first_line = message.content.lines().next().unwrap_or_default();
+                println!(
+                    "  #{} {}: {}",
+                    i + 1,
+                    message.role.as_str(),
+                    truncate_str(first_line, 80)
+                );
+            }
+        }
+        "/branch" => {
+            let (at, name): (Vec<&str>, Vec<&str>) = args.iter().partition(|a| a.starts_with('#'));
+            let at = match at.first() {
+                Some(number) => {
+                    let index = number[1..]
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&i| i > 0)
+                        .ok_or_else(|| anyhow::anyhow!("Invalid message number: {}", number))?;
+                    let branch = chat::active_branch(db, conversation_id).await?;
+                    let history = chat::branch_history(db, &branch).await?;
+                    let message = history.get(index - 1).ok_or_else(|| {
+                        anyhow::anyhow!("No message {} on this branch; see /history", number)
+                    })?;
+                    Some(message.id.clone())
+                }
+                None => None,
+            };
+            let branch =
+                chat::create_branch(db, conversation_id, name.first().copied(), at.as_deref())
+                    .await?;
+            let history = chat::branch_history(db, &branch).await?;
+            println!(
+                "Switched to new branch '{}' after message #{}",
+                branch.name,
+                history.len()
+            );
+        }
+        "/branches" => {
+            for node in chat::branch_tree(db, conversation_id).await? {
+                println!(
+                    "{}{}{} ({} messages)",
+                    if node.active { "* " } else { "  " },
+                    "  ".repeat(node.depth),
+                    node.branch.name,
+                    node.message_count
+                );
+            }
+        }
+        "/switch" => {
+            let Some(name) = args.first() else {
+                println!("Usage: /switch <branch>");
+                return Ok(());
+            };
+            let branch = chat::switch_branch(db, conversation_id, name).await?;
+            let history = chat::branch_history(db, &branch).await?;
+            println!(
+                "Switched to branch '{}' ({} messages)",
+                branch.name,
+                history.len()
+            );
+            if let Some(last) = history.last() {
+                let first_line = last.content.lines().next().unwrap_or_default();
+                println!(
+                    "  Last: {}: {}",
+                    last.role.as_str(),
+                    truncate_str(first_line, 80)
+                );
+            }
+        }
+        "/compare" => {
+            let (left, right) = match args.as_slice() {
+                [left, right] => (left.to_string(), right.to_string()),
+                [other] => (
+                    chat::active_branch(db, conversation_id).await?.name,
+                    other.to_string(),
+                ),
+                _ => {
+                    println!("Usage: /compare <branch> [other-branch]");
+                    return Ok(());
+                }
+            };
+            let comparison = chat::compare_branches(db, conversation_id, &left, &right).await?;
+            print_branch_comparison(&comparison);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn print_branch_comparison(comparison: &chat::BranchComparison) {
+    println!(
+        "'{}' and '{}' share {} message(s)",
+        comparison.left.name, comparison.right.name, comparison.shared
+    );
+    for (branch, only, reply) in [
+        (
+            &comparison.left,
+            &comparison.left_only,
+            comparison.left_reply(),
+        ),
+        (
+            &comparison.right,
+            &comparison.right_only,
+            comparison.right_reply(),
+        ),
+    ] {
+        println!();
+        println!("{}: {} message(s) since the split", branch.name, only.len());
+        match reply {
+            // The diff below shows how the replies differ
+            Some(reply) if !comparison.reply_diff.is_empty() => {
+                let first_line = reply.content.lines().next().unwrap_or_default();
+                println!("  Latest reply: {}", truncate_str(first_line, 80));
+            }
+            Some(reply) => {
+                for line in reply.content.lines() {
+                    println!("  {}", line);
+                }
+            }
+            None => println!("  (no reply)"),
+        }
+    }
+    if !comparison.reply_diff.is_empty() {
+        println!();
+        print!("{}", comparison.reply_diff);
+    }
+}
+
+async fn cmd_chat(
+    audio: Option<&std::path::Path>,
+    message: Option<&str>,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    // A single message prints only the reply, so scripts can consume it
+    let chatter = !quiet && message.is_none();
+
     let config = Config::load()?;
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| anyhow::anyhow!("Failed to open database: {}", e))?;
     let context_store = PersistentMemoryStore::new(db.pool().clone());
@@ -1130,7 +3082,7 @@ async fn cmd_chat(quiet: bool) -> anyhow::Result<()> {
         .await
         .map_err(|e| anyhow::anyhow!("Failed to find project: {}", e))?
     {
-        if !quiet {
+        if chatter {
             println!("Detected project '{}' from current directory", p.name);
         }
         p
@@ -1141,6 +3093,10 @@ async fn cmd_chat(quiet: bool) -> anyhow::Result<()> {
 
         if let Some(p) = projects.first() {
             p.clone()
+        } else if message.is_some() {
+            anyhow::bail!(
+                "No projects found. Create one first with: demiarch new <name> --framework <framework>"
+            );
         } else {
             if !quiet {
                 println!("No projects found. Create one first with:");
@@ -1172,12 +3128,55 @@ async fn cmd_chat(quiet: bool) -> anyhow::Result<()> {
         .await
         .map_err(|e| anyhow::anyhow!("Failed to create conversation: {}", e))?;
 
+    // System prompt for chat, with the project's persona if one is set
+    let base_prompt = format!(
+        "You are Demiarch, an AI assistant specialized in software development. \
+         You're helping with the project '{}' (framework: {}). \
+         Help the user design features, write code, and solve problems. \
+         When the user wants code generated, describe what you would create and ask if they want to proceed.",
+        active_project.name, active_project.framework
+    );
+    let mut active_persona = project_persona(&db, Some(&active_project)).await;
+    let mut system_prompt = persona::apply(&base_prompt, active_persona.as_ref());
+
+    let chat_allocation = chat::chat_allocation(config.llm.max_tokens);
+    let mut cost_preview = config.cost.chat_preview;
+
+    if let Some(message) = message {
+        let message = expand_snippets(message, Some(&active_project))?;
+        return chat_once(
+            &db,
+            &llm_client,
+            &conversation.id,
+            &system_prompt,
+            chat_allocation,
+            &message,
+            json,
+        )
+        .await;
+    }
+
     if !quiet {
         println!("Demiarch Chat - Project: {}", active_project.name);
+        if let Some(ref p) = active_persona {
+            println!("Persona: {}", p.name);
+        }
         println!("Type your message, or use these commands:");
         println!("  /quit      - Exit chat");
         println!("  /generate  - Generate code from the conversation");
         println!("  /clear     - Clear conversation history");
+        println!("  /context   - Show how much of the context budget is in use");
+        println!("  /criteria <feature-id> - Derive acceptance criteria from this conversation");
+        println!("  /retry [model] - Regenerate the last reply, optionally with another model");
+        println!("  /edit [text] - Change your last message and resend it (no text opens $EDITOR)");
+        println!("  /history   - List this branch's messages");
+        println!("  /branch [name] [#n] - Branch after message #n (default: the latest)");
+        println!("  /branches  - Show the branch tree");
+        println!("  /switch <branch> - Continue on another branch");
+        println!("  /compare <branch> [branch] - Compare the replies of two branches");
+        println!("  /persona [name|off] - Switch persona, or list the project's personas");
+        println!("  /note [text] - Add a note to the active session, or list its notes");
+        println!("  /preview [on|off] - Show each message's tokens and cost before sending it");
         println!();
     }
 
@@ -1191,17 +3190,31 @@ async fn cmd_chat(quiet: bool) -> anyhow::Result<()> {
     // Try to load history, ignore errors
     let _ = rl.load_history(&history_path);
 
-    // System prompt for chat
-    let system_prompt = format!(
-        "You are Demiarch, an AI assistant specialized in software development. \
-         You're helping with the project '{}' (framework: {}). \
-         Help the user design features, write code, and solve problems. \
-         When the user wants code generated, describe what you would create and ask if they want to proceed.",
-        active_project.name, active_project.framework
-    );
+    // Checkpoint triggers for the session's end and long sessions
+    let hooks = hooks_with_checkpoints(&db, &config);
+    let checkpoint_interval = (config.checkpoint.triggers.interval_mins > 0).then(|| {
+        std::time::Duration::from_secs(u64::from(config.checkpoint.triggers.interval_mins) * 60)
+    });
+    let mut last_checkpoint = std::time::Instant::now();
+
+    // A voice note becomes the first message
+    let mut pending_input = match audio {
+        Some(path) => {
+            Some(transcribe_note(&db, Some(active_project.id.as_str()), path, quiet).await?)
+        }
+        None => None,
+    };
 
     loop {
-        let readline = rl.readline("> ");
+        let readline = match pending_input.take() {
+            Some(text) => {
+                if !quiet {
+                    println!("> {}", text);
+                }
+                Ok(text)
+            }
+            None => rl.readline("> "),
+        };
         match readline {
             Ok(line) => {
                 let input = line.trim();
@@ -1214,6 +3227,8 @@ async fn cmd_chat(quiet: bool) -> anyhow::Result<()> {
                 let _ = rl.add_history_entry(input);
 
                 // Handle commands
+                let mut reply_to = ChatReply::Input;
+                let mut reply_model: Option<String> = None;
                 if input.starts_with('/') {
                     match input {
                         "/quit" | "/exit" | "/q" => {
@@ -1250,12 +3265,15 @@ async fn cmd_chat(quiet: bool) -> anyhow::Result<()> {
                             }
 
                             // Build a task from the conversation
-                            let task = history
+                            let mut task = history
                                 .iter()
                                 .filter(|m| m.role == chat::MessageRole::User)
                                 .map(|m| m.content.as_str())
                                 .collect::<Vec<_>>()
                                 .join("\n");
+                            if let Some(ref p) = active_persona {
+                                task = format!("{}\n\n{}", p.prompt(), task);
+                            }
 
                             // Get project path for writing generated files
                             let project_path =
@@ -1307,36 +3325,265 @@ async fn cmd_chat(quiet: bool) -> anyhow::Result<()> {
                             }
                             continue;
                         }
-                        cmd => {
-                            println!("Unknown command: {}", cmd);
-                            println!("Available commands: /quit, /generate, /clear");
+                        "/context" => {
+                            let history = chat::get_history(
+                                &db,
+                                &conversation.id,
+                                Some(chat::CHAT_HISTORY_LIMIT),
+                            )
+                            .await
+                            .map_err(|e| anyhow::anyhow!("Failed to get history: {}", e))?;
+                            let prompt =
+                                chat::build_prompt(&system_prompt, &history, chat_allocation);
+                            print_chat_context_usage(&prompt.usage);
                             continue;
                         }
-                    }
-                }
-
+                        cmd if cmd == "/criteria" || cmd.starts_with("/criteria ") => {
+                            let Some(feature_id) = cmd.split_whitespace().nth(1) else {
+                                println!("Usage: /criteria <feature-id>");
+                                continue;
+                            };
+                            match criteria::derive(&db, &llm_client, feature_id, &conversation.id)
+                                .await
+                            {
+                                Ok(proposal) => {
+                                    confirm_criteria(&db, &proposal, false).await?;
+                                }
+                                Err(e) => println!("Could not derive criteria: {}", e),
+                            }
+                            continue;
+                        }
+                        cmd if cmd == "/retry" || cmd.starts_with("/retry ") => {
+                            reply_model = cmd.split_whitespace().nth(1).map(str::to_string);
+                            let last = chat::get_history(&db, &conversation.id, Some(1))
+                                .await
+                                .map_err(|e| anyhow::anyhow!("Failed to get history: {}", e))?
+                                .pop();
+                            match last {
+                                Some(reply) if reply.role == chat::MessageRole::Assistant => {
+                                    reply_to = ChatReply::Retry(reply);
+                                }
+                                // The last message never got a reply
+                                Some(_) => reply_to = ChatReply::Branch,
+                                None => {
+                                    println!("Nothing to retry yet.");
+                                    continue;
+                                }
+                            }
+                        }
+                        cmd if cmd == "/edit" || cmd.starts_with("/edit ") => {
+                            let history = chat::get_history(&db, &conversation.id, None)
+                                .await
+                                .map_err(|e| anyhow::anyhow!("Failed to get history: {}", e))?;
+                            let Some(original) = history
+                                .into_iter()
+                                .rev()
+                                .find(|m| m.role == chat::MessageRole::User)
+                            else {
+                                println!("No message to edit yet.");
+                                continue;
+                            };
+                            let text = match cmd["/edit".len()..].trim() {
+                                "" => match editor::edit_text(&original.content, "md") {
+                                    Ok(text) => text.trim().to_string(),
+                                    Err(e) => {
+                                        println!("Could not open the editor: {}", e);
+                                        continue;
+                                    }
+                                },
+                                text => text.to_string(),
+                            };
+                            let text = match expand_snippets(&text, Some(&active_project)) {
+                                Ok(text) => text,
+                                Err(e) => {
+                                    println!("{}", e);
+                                    continue;
+                                }
+                            };
+                            if text.is_empty() || text == original.content {
+                                println!("Message unchanged.");
+                                continue;
+                            }
+                            chat::add_variant(&db, &original, &text, None)
+                                .await
+                                .map_err(|e| anyhow::anyhow!("Failed to save message: {}", e))?;
+                            reply_to = ChatReply::Branch;
+                        }
+                        cmd if cmd == "/persona" || cmd.starts_with("/persona ") => {
+                            match cmd["/persona".len()..].trim() {
+                                "" => {
+                                    let personas =
+                                        persona::list(&db, &active_project.id).await.map_err(
+                                            |e| anyhow::anyhow!("Failed to list personas: {}", e),
+                                        )?;
+                                    if personas.is_empty() {
+                                        println!(
+                                            "No personas yet. Add one with: demiarch personas add <name>"
+                                        );
+                                    }
+                                    for p in &personas {
+                                        let marker = if active_persona
+                                            .as_ref()
+                                            .is_some_and(|a| a.name == p.persona.name)
+                                        {
+                                            "*"
+                                        } else {
+                                            " "
+                                        };
+                                        println!("{} {}", marker, p.persona.name);
+                                    }
+                                }
+                                "off" | "none" => {
+                                    active_persona = None;
+                                    system_prompt = base_prompt.clone();
+                                    println!("Persona off.");
+                                }
+                                name => {
+                                    match persona::resolve(
+                                        &db,
+                                        &active_project.id,
+                                        Some(name),
+                                        &config,
+                                    )
+                                    .await
+                                    {
+                                        Ok(p) => {
+                                            active_persona = p;
+                                            system_prompt = persona::apply(
+                                                &base_prompt,
+                                                active_persona.as_ref(),
+                                            );
+                                            println!("Persona: {}", name);
+                                        }
+                                        Err(e) => println!("{}", e),
+                                    }
+                                }
+                            }
+                            continue;
+                        }
+                        cmd if cmd == "/note" || cmd.starts_with("/note ") => {
+                            let manager = SessionManager::new(db.pool().clone());
+                            let text = cmd["/note".len()..].trim();
+                            let result = if text.is_empty() {
+                                active_session_notes(&manager).await.map(|notes| {
+                                    if notes.is_empty() {
+                                        println!("No notes in this session.");
+                                    }
+                                    print_session_notes(&notes);
+                                })
+                            } else {
+                                add_session_note(&manager, text, false)
+                                    .await
+                                    .map(|_| println!("Noted."))
+                            };
+                            if let Err(e) = result {
+                                println!("{}", e);
+                            }
+                            continue;
+                        }
+                        cmd if cmd == "/preview" || cmd.starts_with("/preview ") => {
+                            cost_preview = match cmd["/preview".len()..].trim() {
+                                "" => !cost_preview,
+                                "on" => true,
+                                "off" => false,
+                                other => {
+                                    println!("Usage: /preview [on|off] (got '{}')", other);
+                                    continue;
+                                }
+                            };
+                            println!("Cost preview {}.", if cost_preview { "on" } else { "off" });
+                            continue;
+                        }
+                        cmd if cmd
+                            .split_whitespace()
+                            .next()
+                            .is_some_and(|c| CHAT_BRANCH_COMMANDS.contains(&c)) =>
+                        {
+                            if let Err(e) = chat_branch_command(&db, &conversation.id, cmd).await {
+                                println!("{}", e);
+                            }
+                            continue;
+                        }
+                        cmd => {
+                            println!("Unknown command: {}", cmd);
+                            println!(
+                                "Available commands: /quit, /generate, /clear, /context, /criteria, \
+                                 /retry, /edit, /history, /branch, /branches, /switch, /compare, \
+                                 /persona, /note, /preview"
+                            );
+                            continue;
+                        }
+                    }
+                }
+
+                // Expand snippets in the typed message
+                let pending = match reply_to {
+                    ChatReply::Input => match expand_snippets(input, Some(&active_project)) {
+                        Ok(message) => Some(message),
+                        Err(e) => {
+                            println!("{}", e);
+                            continue;
+                        }
+                    },
+                    _ => None,
+                };
+
+                // Preview the prompt's cost before anything is sent or saved
+                if cost_preview {
+                    let mut history =
+                        chat::get_history(&db, &conversation.id, Some(chat::CHAT_HISTORY_LIMIT))
+                            .await
+                            .map_err(|e| anyhow::anyhow!("Failed to get history: {}", e))?;
+                    if let ChatReply::Retry(ref reply) = reply_to {
+                        history.retain(|m| m.id != reply.id);
+                    }
+                    let mut messages =
+                        chat::build_prompt(&system_prompt, &history, chat_allocation).messages;
+                    if let Some(ref message) = pending {
+                        messages.push(Message::user(message.as_str()));
+                    }
+                    let model = reply_model
+                        .clone()
+                        .unwrap_or_else(|| config.llm.default_model.clone());
+                    match preview_chat_cost(&db, &config, &messages, &model).await? {
+                        Some(model) => reply_model = Some(model),
+                        None => {
+                            println!("Message not sent.");
+                            continue;
+                        }
+                    }
+                }
+
                 // Save user message
-                chat::send_message(&db, &conversation.id, chat::MessageRole::User, input)
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Failed to save message: {}", e))?;
+                if let Some(message) = pending {
+                    chat::send_message(&db, &conversation.id, chat::MessageRole::User, &message)
+                        .await
+                        .map_err(|e| anyhow::anyhow!("Failed to save message: {}", e))?;
+                }
 
-                // Build messages for LLM including history
-                let history = chat::get_history(&db, &conversation.id, Some(20))
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Failed to get history: {}", e))?;
-
-                let mut messages = vec![Message::system(&system_prompt)];
-                for msg in &history {
-                    let llm_msg = match msg.role {
-                        chat::MessageRole::User => Message::user(&msg.content),
-                        chat::MessageRole::Assistant => Message::assistant(&msg.content),
-                        chat::MessageRole::System => Message::system(&msg.content),
-                    };
-                    messages.push(llm_msg);
+                // Build messages for LLM, summarizing history beyond the budget
+                let mut history =
+                    chat::get_history(&db, &conversation.id, Some(chat::CHAT_HISTORY_LIMIT))
+                        .await
+                        .map_err(|e| anyhow::anyhow!("Failed to get history: {}", e))?;
+                if let ChatReply::Retry(ref reply) = reply_to {
+                    history.retain(|m| m.id != reply.id);
+                }
+                let prompt = chat::build_prompt(&system_prompt, &history, chat_allocation);
+                if prompt.usage.history.summarized > 0 && !quiet {
+                    println!(
+                        "(summarized {} older messages to fit the context budget)",
+                        prompt.usage.history.summarized
+                    );
                 }
+                let messages = prompt.messages;
+                let mut context_stats = prompt.usage.stats();
 
                 // Stream the response
-                match llm_client.complete_streaming(messages, None).await {
+                match llm_client
+                    .complete_streaming(messages, reply_model.as_deref())
+                    .await
+                {
                     Ok(stream) => {
                         let mut response = String::new();
                         let mut stream = std::pin::pin!(stream);
@@ -1349,6 +3596,10 @@ async fn cmd_chat(quiet: bool) -> anyhow::Result<()> {
                                         io::stdout().flush()?;
                                         response.push_str(content);
                                     }
+                                    if let Some(usage) = chunk.usage {
+                                        context_stats =
+                                            context_stats.with_measured(usage.prompt_tokens);
+                                    }
                                 }
                                 Ok(StreamEvent::Done) => {
                                     break;
@@ -1364,17 +3615,32 @@ async fn cmd_chat(quiet: bool) -> anyhow::Result<()> {
                             }
                         }
                         println!(); // New line after response
+                        if !quiet {
+                            print_context_indicator(context_stats);
+                        }
 
                         // Save assistant message
                         if !response.is_empty() {
-                            let saved = chat::send_message(
-                                &db,
-                                &conversation.id,
-                                chat::MessageRole::Assistant,
-                                &response,
-                            )
-                            .await
+                            let saved = match reply_to {
+                                ChatReply::Retry(ref reply) => {
+                                    chat::add_variant(&db, reply, &response, reply_model.as_deref())
+                                        .await
+                                }
+                                _ => {
+                                    chat::send_message(
+                                        &db,
+                                        &conversation.id,
+                                        chat::MessageRole::Assistant,
+                                        &response,
+                                    )
+                                    .await
+                                }
+                            }
                             .map_err(|e| anyhow::anyhow!("Failed to save response: {}", e))?;
+                            if matches!(reply_to, ChatReply::Retry(_)) && !quiet {
+                                let position = chat::variant_position(&db, &saved).await?;
+                                println!("(reply {}/{})", position.index, position.count);
+                            }
 
                             // Ingest conversation slice into context store for progressive recall
                             let history = chat::get_history(&db, &conversation.id, Some(12))
@@ -1405,6 +3671,18 @@ async fn cmd_chat(quiet: bool) -> anyhow::Result<()> {
                         eprintln!("Error calling LLM: {}", e);
                     }
                 }
+
+                if checkpoint_interval.is_some_and(|interval| last_checkpoint.elapsed() >= interval)
+                {
+                    session_hook(
+                        &hooks,
+                        HookEvent::SessionInterval,
+                        &active_project.id,
+                        &conversation.id,
+                    )
+                    .await;
+                    last_checkpoint = std::time::Instant::now();
+                }
             }
             Err(ReadlineError::Interrupted) => {
                 // Ctrl-C
@@ -1432,9 +3710,34 @@ async fn cmd_chat(quiet: bool) -> anyhow::Result<()> {
     }
     let _ = rl.save_history(&history_path);
 
+    session_hook(
+        &hooks,
+        HookEvent::SessionEnd,
+        &active_project.id,
+        &conversation.id,
+    )
+    .await;
+
     Ok(())
 }
 
+/// Fire a session hook, logging failures rather than ending the session
+async fn session_hook(
+    hooks: &HooksManager,
+    event: HookEvent,
+    project_id: &str,
+    conversation_id: &str,
+) {
+    let payload = serde_json::json!({
+        "event": event.as_str(),
+        "project_id": project_id,
+        "conversation_id": conversation_id,
+    });
+    if let Err(e) = fire_hook(hooks, event, &payload).await {
+        tracing::warn!(event = event.as_str(), error = %e, "Session hook failed");
+    }
+}
+
 /// Check if the assistant's response suggests code generation would be helpful
 fn should_offer_generation(response: &str) -> bool {
     let lower = response.to_lowercase();
@@ -1451,33 +3754,51 @@ fn should_offer_generation(response: &str) -> bool {
     generation_hints.iter().any(|hint| lower.contains(hint))
 }
 
-async fn cmd_projects(db: &Database, action: ProjectAction, quiet: bool) -> anyhow::Result<()> {
+async fn cmd_projects(
+    db: &Database,
+    action: ProjectAction,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
     match action {
-        ProjectAction::List => {
-            let projects = project::list_with_db(db, Some(project::ProjectStatus::Active)).await?;
-            if projects.is_empty() {
+        ProjectAction::List { limit, offset } => {
+            let status = Some(project::ProjectStatus::Active);
+            let projects = match page_request(limit, offset) {
+                Some(request) => project::list_page_with_db(db, status, request).await?,
+                None => Page::all(project::list_with_db(db, status).await?),
+            };
+            if projects.total == 0 {
                 if !quiet {
-                    println!("No projects found.");
-                    println!("\nCreate one with: demiarch new <name> --framework <framework>");
+                    println!("{}", t("projects-none"));
+                    println!("\n{}", t("projects-create-hint"));
                 }
             } else {
+                let mut out = Pager::new();
                 if !quiet {
-                    println!("Projects:");
+                    out.line(t("projects-header"));
                 }
-                for p in projects {
+                for p in &projects.items {
                     let status_indicator = match p.status {
-                        project::ProjectStatus::Active => "",
-                        project::ProjectStatus::Archived => " [archived]",
-                        project::ProjectStatus::Deleted => " [deleted]",
+                        project::ProjectStatus::Active => String::new(),
+                        project::ProjectStatus::Archived => {
+                            format!(" {}", t("projects-status-archived"))
+                        }
+                        project::ProjectStatus::Deleted => {
+                            format!(" {}", t("projects-status-deleted"))
+                        }
                     };
-                    println!(
+                    out.line(format!(
                         "  {} - {} ({}){}",
                         &p.id[..8],
                         p.name,
                         p.framework,
                         status_indicator
-                    );
+                    ));
+                }
+                if !quiet {
+                    out.page_footer(&projects);
                 }
+                out.finish();
             }
         }
         ProjectAction::Show { id } => {
@@ -1492,45 +3813,101 @@ async fn cmd_projects(db: &Database, action: ProjectAction, quiet: bool) -> anyh
 
             match found_project {
                 Some(p) => {
-                    println!("Project: {}", p.name);
-                    println!("  ID: {}", p.id);
-                    println!("  Framework: {}", p.framework);
-                    println!("  Status: {}", p.status.as_str());
+                    println!("{}: {}", t("label-project"), p.name);
+                    println!("  {}: {}", t("label-id"), p.id);
+                    println!("  {}: {}", t("label-framework"), p.framework);
+                    println!("  {}: {}", t("label-status"), p.status.as_str());
                     if !p.repo_url.is_empty() {
-                        println!("  Repository: {}", p.repo_url);
+                        println!("  {}: {}", t("label-repository"), p.repo_url);
                     }
                     if let Some(desc) = &p.description {
-                        println!("  Description: {}", desc);
+                        println!("  {}: {}", t("label-description"), desc);
                     }
-                    println!("  Created: {}", p.created_at.format("%Y-%m-%d %H:%M:%S"));
-                    println!("  Updated: {}", p.updated_at.format("%Y-%m-%d %H:%M:%S"));
+                    println!(
+                        "  {}: {}",
+                        t("label-created"),
+                        p.created_at.format("%Y-%m-%d %H:%M:%S")
+                    );
+                    println!(
+                        "  {}: {}",
+                        t("label-updated"),
+                        p.updated_at.format("%Y-%m-%d %H:%M:%S")
+                    );
+                    println!(
+                        "  {}: {}",
+                        t("label-open-in-app"),
+                        DeepLink::Project(p.id.clone())
+                    );
                 }
                 None => {
-                    return Err(anyhow::anyhow!(
-                        "Project '{}' not found. Run `demiarch projects list` to see all projects.",
-                        id
-                    ));
+                    return Err(anyhow::anyhow!(t_args(
+                        "projects-not-found",
+                        &[("id", &id)]
+                    )));
+                }
+            }
+        }
+        ProjectAction::Health { id, stale_days } => {
+            let found_project = match project::get_with_db(db, &id).await? {
+                Some(p) => Some(p),
+                None => project::ProjectRepository::new(db).get_by_name(&id).await?,
+            };
+            let Some(p) = found_project else {
+                return Err(anyhow::anyhow!(t_args(
+                    "projects-not-found",
+                    &[("id", &id)]
+                )));
+            };
+            let config = Config::load()?;
+            let options = health::HealthOptions {
+                stale_days,
+                daily_limit_usd: config.cost.daily_limit_usd,
+                alert_threshold: config.cost.alert_threshold,
+            };
+            let report = health::check(db, &p.id, options, chrono::Utc::now()).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else if !quiet {
+                print_health(&report);
+            }
+        }
+        ProjectAction::Cleanup { dry_run } => {
+            let config = Config::load()?;
+            let plan = lifecycle::plan(db, &config.lifecycle, chrono::Utc::now()).await?;
+            if dry_run {
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&plan)?);
+                } else if !quiet {
+                    print_cleanup_plan(&plan);
                 }
+                return Ok(());
+            }
+            let hooks = HooksManager::from_config(&config.hooks);
+            let outcome = lifecycle::apply(db, &plan, &hooks).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&outcome)?);
+            } else if !quiet {
+                print_cleanup_outcome(&outcome);
             }
         }
         ProjectAction::Archive { id } => {
             project::archive_with_db(db, &id).await?;
             if !quiet {
-                println!("Project '{}' archived.", id);
+                println!("{}", t_args("projects-archived", &[("id", &id)]));
             }
         }
         ProjectAction::Delete { id, force } => {
             if !force && !quiet {
-                println!("Warning: This will permanently delete project '{}'.", id);
-                println!("Use --force to confirm deletion.");
+                println!("{}", t_args("projects-delete-warning", &[("id", &id)]));
+                println!("{}", t("projects-delete-confirm-hint"));
                 return Ok(());
             }
             project::delete_with_db(db, &id, force).await?;
             if !quiet {
                 if force {
-                    println!("Project '{}' permanently deleted.", id);
+                    println!("{}", t_args("projects-deleted-permanently", &[("id", &id)]));
                 } else {
-                    println!("Project '{}' marked as deleted.", id);
+                    println!("{}", t_args("projects-marked-deleted", &[("id", &id)]));
                 }
             }
         }
@@ -1538,332 +3915,369 @@ async fn cmd_projects(db: &Database, action: ProjectAction, quiet: bool) -> anyh
     Ok(())
 }
 
-async fn cmd_features(db: &Database, action: FeatureAction, quiet: bool) -> anyhow::Result<()> {
-    // Get the most recent project as the active project
-    let project_repo = project::ProjectRepository::new(db);
-    let projects = project_repo.list(None).await?;
-    let active_project = projects.first().ok_or_else(|| {
-        anyhow::anyhow!(
-            "No projects found. Create one with: demiarch new <name> --framework <framework>"
-        )
-    })?;
-    let project_id = &active_project.id;
+/// Hooks from `[hooks]`, taking the checkpoints `[checkpoint.triggers]` enables
+fn hooks_with_checkpoints(db: &Database, config: &Config) -> HooksManager {
+    HooksManager::from_config(&config.hooks)
+        .with_checkpoint_triggers(db.clone(), &config.checkpoint.triggers)
+}
 
-    match action {
-        FeatureAction::List { status } => {
-            let status_enum = status.as_deref().and_then(feature::FeatureStatus::parse);
-            let features = feature::list_with_db(db, project_id, status_enum).await?;
-            if features.is_empty() {
-                if !quiet {
-                    println!("No features found for project '{}'.", active_project.name);
-                    println!("\nCreate one with: demiarch features create <title>");
-                }
-            } else {
-                if !quiet {
-                    println!("Features for '{}' ({}):", active_project.name, project_id);
-                }
-                for f in features {
-                    let status_icon = match f.status {
-                        feature::FeatureStatus::Backlog => "○",
-                        feature::FeatureStatus::Todo => "◐",
-                        feature::FeatureStatus::InProgress => "◑",
-                        feature::FeatureStatus::Review => "◕",
-                        feature::FeatureStatus::Done => "●",
-                    };
-                    println!(
-                        "  {} [{}] {} (P{})",
-                        status_icon,
-                        &f.id[..8],
-                        f.title,
-                        f.priority
-                    );
-                }
-            }
-        }
-        FeatureAction::Show { id } => {
-            let repo = feature::FeatureRepository::new(db);
-            if let Some(f) = repo.get(&id).await? {
-                println!("Feature: {}", f.title);
-                println!("  ID: {}", f.id);
-                println!("  Project: {}", f.project_id);
-                println!("  Status: {}", f.status.as_str());
-                println!("  Priority: {}", f.priority);
-                if let Some(desc) = &f.description {
-                    println!("  Description: {}", desc);
-                }
-                if let Some(criteria) = &f.acceptance_criteria {
-                    println!("  Acceptance Criteria: {}", criteria);
-                }
-                if let Some(labels) = &f.labels {
-                    println!("  Labels: {}", labels.join(", "));
-                }
-                println!("  Created: {}", f.created_at);
-                println!("  Updated: {}", f.updated_at);
-            } else {
-                println!("Feature not found: {}", id);
-            }
-        }
-        FeatureAction::Create { title, phase } => {
-            let f = feature::create_with_db(db, project_id, &title, None, phase.as_deref()).await?;
-            if !quiet {
-                println!("Feature created: {} ({})", f.title, &f.id[..8]);
-                println!(
-                    "  Project: {} ({})",
-                    active_project.name,
-                    &active_project.id[..8]
-                );
-                println!(
-                    "\nNext: Run `demiarch generate \"{}\"` to generate code.",
-                    f.title
+/// Fire the hooks for an event, failing if one vetoes it
+async fn fire_hook(
+    hooks: &HooksManager,
+    event: HookEvent,
+    payload: &serde_json::Value,
+) -> anyhow::Result<()> {
+    match hooks.fire(event, payload).await? {
+        HookDecision::Proceed => Ok(()),
+        HookDecision::Veto { command, reason } => Err(anyhow::anyhow!(
+            "Stopped by {} hook `{}`: {}",
+            event.as_str(),
+            command,
+            reason
+        )),
+    }
+}
+
+/// Archive projects idle for longer than `lifecycle.archive_after_days`
+///
+/// Failures are logged rather than returned so they never block the
+/// command the user actually asked for.
+async fn auto_archive_projects(db: &Database, quiet: bool) {
+    let Ok(config) = Config::load() else {
+        return;
+    };
+    if config.lifecycle.archive_after_days == 0 {
+        return;
+    }
+    let hooks = HooksManager::from_config(&config.hooks);
+    match lifecycle::auto_archive(db, &config.lifecycle, &hooks, chrono::Utc::now()).await {
+        Ok(outcome) if !quiet => {
+            for project in &outcome.archived {
+                eprintln!(
+                    "Archived '{}' after {} days without activity.",
+                    project.name, project.idle_days
                 );
             }
         }
-        FeatureAction::Update { id, status } => {
-            let status_enum = status.as_deref().and_then(feature::FeatureStatus::parse);
-            feature::update_with_db(db, &id, status_enum, None).await?;
-            if !quiet {
-                println!("Feature '{}' updated.", id);
-            }
-        }
-        FeatureAction::Delete { id } => {
-            feature::delete_with_db(db, &id).await?;
-            if !quiet {
-                println!("Feature '{}' deleted.", id);
-            }
-        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!(error = %e, "Automatic project archival failed"),
     }
-    Ok(())
 }
 
-async fn cmd_generate(description: &str, dry_run: bool, quiet: bool) -> anyhow::Result<()> {
-    if !quiet {
-        if dry_run {
-            println!("Dry run: Generating code for: {}", description);
-        } else {
-            println!("Generating code for: {}", description);
-        }
-        println!();
+fn print_cleanup_plan(plan: &lifecycle::CleanupPlan) {
+    if plan.is_empty() {
+        println!("Nothing to clean up.");
+        return;
+    }
+    println!("Cleanup would:");
+    for project in &plan.archive {
+        println!(
+            "  archive  {}  {} (no activity for {} days)",
+            &project.project_id[..8.min(project.project_id.len())],
+            project.name,
+            project.idle_days
+        );
+    }
+    for purge in &plan.purge {
+        println!(
+            "  purge    {} {} older than {}",
+            purge.count,
+            purge.kind.as_str().replace('_', " "),
+            purge.older_than.format("%Y-%m-%d")
+        );
     }
+    println!("\nRun without --dry-run to apply. Configured hooks can still veto each step.");
+}
 
-    let result = generate::generate(description, dry_run)
-        .await
-        .map_err(|e| anyhow::anyhow!("{}", e))?;
+fn print_cleanup_outcome(outcome: &lifecycle::CleanupOutcome) {
+    if outcome.archived.is_empty() && outcome.purged.is_empty() && outcome.vetoed.is_empty() {
+        println!("Nothing to clean up.");
+        return;
+    }
+    for project in &outcome.archived {
+        println!("{} Archived {}", glyphs::check(), project.name);
+    }
+    for purge in &outcome.purged {
+        println!(
+            "{} Purged {} {}",
+            glyphs::check(),
+            purge.count,
+            purge.kind.as_str().replace('_', " ")
+        );
+    }
+    for vetoed in &outcome.vetoed {
+        println!(
+            "{} Skipped {}: vetoed by `{}`: {}",
+            glyphs::cross(),
+            vetoed.target,
+            vetoed.command,
+            vetoed.reason
+        );
+    }
+}
 
-    if !quiet {
-        println!("Generation complete!");
+fn print_health(report: &health::HealthReport) {
+    let mark = match report.status {
+        health::HealthStatus::Healthy => glyphs::check(),
+        _ => glyphs::cross(),
+    };
+    println!(
+        "{} {}: {}/100 ({})",
+        mark,
+        report.project_name,
+        report.score,
+        report.status.as_str()
+    );
+    if report.signals.is_empty() {
+        println!("  Nothing needs attention.");
+        return;
+    }
+    for signal in &report.signals {
         println!();
-        println!("  Files created: {}", result.files_created);
-        println!("  Files modified: {}", result.files_modified);
-        println!("  Tokens used: {}", result.tokens_used);
-        println!("  Estimated cost: ${:.4}", result.cost_usd);
-
-        if !result.files.is_empty() {
-            println!();
-            println!("Generated files:");
-            for file in &result.files {
-                let status = if file.is_new { "new" } else { "modified" };
-                let lang = file
-                    .language
-                    .as_ref()
-                    .map(|l| format!(" ({})", l))
-                    .unwrap_or_default();
-                if dry_run {
-                    println!("  [would create] {}{}", file.path.display(), lang);
-                } else {
-                    println!("  [{}] {}{}", status, file.path.display(), lang);
-                }
-            }
-        }
-
-        if dry_run {
-            println!();
-            println!("Dry run complete. No files were written.");
-            println!("Run without --dry-run to create the files.");
+        println!("  -{:<3} {}", signal.penalty, signal.summary);
+        for item in &signal.items {
+            println!(
+                "        {}  {}  ({})",
+                &item.id[..8.min(item.id.len())],
+                item.title,
+                item.detail
+            );
         }
+        println!("        -> {}", signal.recommendation);
     }
+}
 
-    Ok(())
+/// Resolve a `--phase` argument (ID or name) to the phase's ID
+async fn resolve_phase_id(
+    db: &Database,
+    project_id: &str,
+    phase: Option<&str>,
+) -> anyhow::Result<Option<String>> {
+    match phase {
+        Some(p) => Ok(Some(phase::resolve_phase(db, project_id, p).await?.id)),
+        None => Ok(None),
+    }
 }
 
-async fn cmd_documents(db: &Database, action: DocumentAction, quiet: bool) -> anyhow::Result<()> {
-    let config = Config::load()?;
-    let cost_tracker = Arc::new(CostTracker::from_config(&config.cost));
+async fn cmd_phases(
+    db: &Database,
+    action: PhaseAction,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let today = chrono::Utc::now().date_naive();
 
     match action {
-        DocumentAction::GeneratePrd { project } => {
-            if !quiet {
-                println!("Generating PRD for project '{}'...", project);
-                println!();
+        PhaseAction::List { project } => {
+            let project = resolve_project(db, project.as_deref()).await?;
+            let progress = phase::project_progress(db, &project.id, today).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&progress)?);
+                return Ok(());
+            }
+            if quiet {
+                return Ok(());
+            }
+            if progress.is_empty() {
+                println!("No phases in {}.", project.name);
+                println!("\nCreate one with: demiarch phases create <name> --target YYYY-MM-DD");
+                return Ok(());
             }
 
-            let doc = document::generate_prd(db, &project, Some(cost_tracker.clone()))
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-
-            if !quiet {
-                println!("PRD generated successfully!");
-                println!();
-                println!("  Document ID: {}", doc.id);
-                println!("  Title: {}", doc.title);
-                println!("  Status: {:?}", doc.status);
-                if let Some(model) = &doc.model_used {
-                    println!("  Model: {}", model);
-                }
-                if let Some(tokens) = doc.tokens_used {
-                    println!("  Tokens used: {}", tokens);
-                }
-                if let Some(cost) = doc.generation_cost_usd {
-                    println!("  Generation cost: ${:.4}", cost);
-                }
-                println!();
-                println!("View with: demiarch documents show {}", doc.id);
+            println!("Phases for {}:", project.name);
+            println!();
+            for p in &progress {
+                let target = p
+                    .phase
+                    .target_date
+                    .map(|d| format!("  target {}", d))
+                    .unwrap_or_default();
+                let overdue = match p.phase.target_date {
+                    Some(d) if d < today && !p.is_complete() => " (overdue)",
+                    _ => "",
+                };
                 println!(
-                    "Export with: demiarch documents export {} --output prd.md",
-                    doc.id
+                    "  {:<20} {:<10} {:<20} {:>3.0}% ({}/{}){}{}",
+                    p.phase.name,
+                    &p.phase.id[..8],
+                    glyphs::bar((p.percent / 5.0).round() as usize),
+                    p.percent,
+                    p.done,
+                    p.total,
+                    target,
+                    overdue
                 );
             }
         }
-
-        DocumentAction::GenerateArchitecture { project } => {
-            if !quiet {
+        PhaseAction::Show {
+            phase: name,
+            project,
+        } => {
+            let project = resolve_project(db, project.as_deref()).await?;
+            let found = phase::resolve_phase(db, &project.id, &name).await?;
+            let features = feature::FeatureRepository::new(db)
+                .list_by_phase(&found.id)
+                .await?;
+            let progress = phase::phase_progress(db, found, today).await?;
+            if json {
                 println!(
-                    "Generating architecture document for project '{}'...",
-                    project
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "progress": progress,
+                        "features": features,
+                    }))?
                 );
-                println!();
+                return Ok(());
+            }
+            if quiet {
+                return Ok(());
             }
 
-            let doc = document::generate_architecture(db, &project, Some(cost_tracker.clone()))
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-
-            if !quiet {
-                println!("Architecture document generated successfully!");
-                println!();
-                println!("  Document ID: {}", doc.id);
-                println!("  Title: {}", doc.title);
-                println!("  Status: {:?}", doc.status);
-                if let Some(model) = &doc.model_used {
-                    println!("  Model: {}", model);
-                }
-                if let Some(tokens) = doc.tokens_used {
-                    println!("  Tokens used: {}", tokens);
-                }
-                if let Some(cost) = doc.generation_cost_usd {
-                    println!("  Generation cost: ${:.4}", cost);
-                }
-                println!();
-                println!("View with: demiarch documents show {}", doc.id);
+            let p = &progress.phase;
+            println!("Phase: {}", p.name);
+            println!("  ID: {}", p.id);
+            println!("  Status: {}", p.status.as_str());
+            if let Some(desc) = &p.description {
+                println!("  Description: {}", desc);
+            }
+            if let Some(target) = p.target_date {
                 println!(
-                    "Export with: demiarch documents export {} --output architecture.md",
-                    doc.id
+                    "  Target: {} ({} days left)",
+                    target,
+                    (target - today).num_days()
                 );
             }
-        }
-
-        DocumentAction::List { project, doc_type } => {
-            let dt = doc_type
-                .as_ref()
-                .and_then(|s| document::DocumentType::parse(s));
-
-            let docs = document::list_documents(db, &project, dt)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            println!(
+                "  Progress: {:.0}% ({} done, {} in progress, {} total)",
+                progress.percent, progress.done, progress.in_progress, progress.total
+            );
 
-            if docs.is_empty() {
-                if !quiet {
-                    println!("No documents found for project '{}'.", project);
-                    println!();
-                    println!("Generate one with:");
-                    println!("  demiarch documents generate-prd --project {}", project);
+            if !features.is_empty() {
+                println!();
+                println!("Features:");
+                for f in &features {
                     println!(
-                        "  demiarch documents generate-architecture --project {}",
-                        project
+                        "  {} {} {}",
+                        glyphs::feature_status(f.status),
+                        &f.id[..8],
+                        f.title
                     );
                 }
-            } else {
-                if !quiet {
-                    println!("Documents for project '{}':", project);
-                    println!();
-                }
-                for doc in docs {
+            }
+
+            if !progress.burndown.is_empty() {
+                println!();
+                println!("Burndown (remaining features):");
+                let step = progress.burndown.len().div_ceil(14);
+                for point in progress.burndown.iter().step_by(step) {
+                    let ideal = point
+                        .ideal
+                        .map(|i| format!("  ideal {:.1}", i))
+                        .unwrap_or_default();
                     println!(
-                        "  {} - {} ({:?}) [{}]",
-                        &doc.id[..8],
-                        doc.title,
-                        doc.doc_type,
-                        doc.status.as_str()
+                        "  {} {:>3} {}{}",
+                        point.date,
+                        point.remaining,
+                        glyphs::bar(point.remaining as usize),
+                        ideal
                     );
                 }
             }
         }
-
-        DocumentAction::Show { id } => {
-            let doc = document::get_document(db, &id)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?
-                .ok_or_else(|| anyhow::anyhow!("Document '{}' not found", id))?;
-
-            println!("Document: {}", doc.title);
-            println!("=========={}", "=".repeat(doc.title.len()));
-            println!();
-            println!("ID: {}", doc.id);
-            println!("Type: {}", doc.doc_type.display_name());
-            println!("Status: {:?}", doc.status);
-            println!("Version: {}", doc.version);
-            if let Some(model) = &doc.model_used {
-                println!("Model: {}", model);
-            }
-            if let Some(tokens) = doc.tokens_used {
-                println!("Tokens: {}", tokens);
+        PhaseAction::Create {
+            name,
+            target,
+            description,
+            order,
+            project,
+        } => {
+            let project = resolve_project(db, project.as_deref()).await?;
+            let order = match order {
+                Some(order) => order,
+                None => phase::next_order_index(db, &project.id).await?,
+            };
+            let mut created = phase::Phase::new(&project.id, name, order);
+            if let Some(desc) = description {
+                created = created.with_description(desc);
             }
-            if let Some(cost) = doc.generation_cost_usd {
-                println!("Cost: ${:.4}", cost);
+            if let Some(target) = target {
+                created = created.with_target_date(target);
             }
-            println!("Created: {}", doc.created_at.format("%Y-%m-%d %H:%M:%S"));
-            println!("Updated: {}", doc.updated_at.format("%Y-%m-%d %H:%M:%S"));
-            println!();
-            println!("Content:");
-            println!("--------");
-            println!("{}", doc.content);
-        }
-
-        DocumentAction::UpdateStatus { id, status } => {
-            let new_status = document::DocumentStatus::parse(&status).ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Invalid status '{}'. Use: draft, review, final, archived",
-                    status
-                )
-            })?;
+            phase::PhaseRepository::new(db).create(&created).await?;
 
-            document::update_document_status(db, &id, new_status)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-
-            if !quiet {
-                println!("Document '{}' status updated to '{}'.", id, status);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&created)?);
+            } else if !quiet {
+                println!("Created phase {} ({})", created.name, &created.id[..8]);
             }
         }
+        PhaseAction::Update {
+            phase: name,
+            name: new_name,
+            target,
+            clear_target,
+            description,
+            order,
+            status,
+            project,
+        } => {
+            let project = resolve_project(db, project.as_deref()).await?;
+            let mut found = phase::resolve_phase(db, &project.id, &name).await?;
+            if let Some(new_name) = new_name {
+                found.name = new_name;
+            }
+            if target.is_some() || clear_target {
+                found.target_date = target;
+            }
+            if let Some(desc) = description {
+                found.description = Some(desc);
+            }
+            if let Some(order) = order {
+                found.order_index = order;
+            }
+            if let Some(status) = status.as_deref().and_then(phase::PhaseStatus::parse) {
+                found.status = status;
+            }
+            phase::PhaseRepository::new(db).update(&found).await?;
 
-        DocumentAction::Export { id, output } => {
-            let path = std::path::PathBuf::from(&output);
-
-            document::export_document(db, &id, &path)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-
-            if !quiet {
-                println!("Document '{}' exported to '{}'.", id, output);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&found)?);
+            } else if !quiet {
+                println!("Updated phase {}", found.name);
             }
         }
+        PhaseAction::Assign {
+            phase: name,
+            features,
+            project,
+        } => {
+            let project = resolve_project(db, project.as_deref()).await?;
+            let target = phase::resolve_phase(db, &project.id, &name).await?;
+            let repo = feature::FeatureRepository::new(db);
+            for id in &features {
+                match repo.get(id).await? {
+                    Some(f) if f.project_id == project.id => {}
+                    _ => anyhow::bail!(t_args("features-not-found", &[("id", id)])),
+                }
+            }
+            for id in &features {
+                repo.move_to_phase(id, Some(&target.id)).await?;
+            }
 
-        DocumentAction::Delete { id } => {
-            document::delete_document(db, &id)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-
-            if !quiet {
-                println!("Document '{}' deleted.", id);
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "phase_id": target.id,
+                        "features": features,
+                    }))?
+                );
+            } else if !quiet {
+                println!(
+                    "Assigned {} feature(s) to phase {}",
+                    features.len(),
+                    target.name
+                );
             }
         }
     }
@@ -1871,773 +4285,5127 @@ async fn cmd_documents(db: &Database, action: DocumentAction, quiet: bool) -> an
     Ok(())
 }
 
-async fn cmd_skills(db: &Database, action: SkillAction, quiet: bool) -> anyhow::Result<()> {
-    use demiarch_core::skills::{LearnedSkill, SkillCategory, SkillPattern, SkillsManager};
-
-    let manager = SkillsManager::with_database(db.clone());
+async fn cmd_features(db: &Database, action: FeatureAction, quiet: bool) -> anyhow::Result<()> {
+    // Get the most recent project as the active project
+    let project_repo = project::ProjectRepository::new(db);
+    let projects = project_repo.list(None).await?;
+    let active_project = projects
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("{} {}", t("projects-none"), t("projects-create-hint")))?;
+    let project_id = &active_project.id;
 
     match action {
-        SkillAction::List { category } => {
-            let cat = category.as_deref().map(SkillCategory::parse);
-            let skills = manager.list(cat, None).await?;
-            if skills.is_empty() {
+        FeatureAction::List {
+            status,
+            limit,
+            offset,
+        } => {
+            let status_enum = status.as_deref().and_then(feature::FeatureStatus::parse);
+            let features = match page_request(limit, offset) {
+                Some(request) => {
+                    feature::list_page_with_db(db, project_id, status_enum, request).await?
+                }
+                None => Page::all(feature::list_with_db(db, project_id, status_enum).await?),
+            };
+            if features.total == 0 {
                 if !quiet {
-                    println!("No skills learned yet.");
+                    println!(
+                        "{}",
+                        t_args("features-none", &[("project", &active_project.name)])
+                    );
+                    println!("\n{}", t("features-create-hint"));
                 }
-                return Ok(());
+            } else {
+                let mut out = Pager::new();
+                if !quiet {
+                    out.line(t_args(
+                        "features-header",
+                        &[("project", &active_project.name), ("id", project_id)],
+                    ));
+                }
+                for f in &features.items {
+                    let status_icon = glyphs::feature_status(f.status);
+                    out.line(format!(
+                        "  {} [{}] {} (P{})",
+                        status_icon,
+                        &f.id[..8],
+                        f.title,
+                        f.priority
+                    ));
+                }
+                if !quiet {
+                    out.page_footer(&features);
+                }
+                out.finish();
             }
-
-            if !quiet {
-                println!("Skills ({}):", skills.len());
+        }
+        FeatureAction::Show { id } => {
+            let repo = feature::FeatureRepository::new(db);
+            if let Some(f) = repo.get(&id).await? {
+                println!("{}: {}", t("label-feature"), f.title);
+                println!("  {}: {}", t("label-id"), f.id);
+                println!("  {}: {}", t("label-project"), f.project_id);
+                println!("  {}: {}", t("label-status"), f.status.as_str());
+                println!("  {}: {}", t("label-priority"), f.priority);
+                if let Some(desc) = &f.description {
+                    println!("  {}: {}", t("label-description"), desc);
+                }
+                if let Some(criteria) = &f.acceptance_criteria {
+                    println!("  {}: {}", t("label-acceptance-criteria"), criteria);
+                }
+                if let Some(labels) = &f.labels {
+                    println!("  {}: {}", t("label-labels"), labels.join(", "));
+                }
+                if let Some(branch) = &f.branch {
+                    println!("  {}: {}", t("label-branch"), branch);
+                }
+                println!("  {}: {}", t("label-created"), f.created_at);
+                println!("  {}: {}", t("label-updated"), f.updated_at);
+                println!(
+                    "  {}: {}",
+                    t("label-open-in-app"),
+                    DeepLink::Feature(f.id.clone())
+                );
+            } else {
+                println!("{}", t_args("features-not-found", &[("id", &id)]));
             }
-            for skill in skills {
+        }
+        FeatureAction::Create {
+            title,
+            phase,
+            audio,
+            edit,
+        } => {
+            let description = match audio {
+                Some(path) => {
+                    Some(transcribe_note(db, Some(project_id.as_str()), &path, quiet).await?)
+                }
+                None => None,
+            };
+            let f = if edit {
+                let mut draft = feature::FeatureDraft::new(title.unwrap_or_default());
+                draft.description = description.unwrap_or_default();
+                draft.phase = phase;
+                let mut draft =
+                    feature::FeatureDraft::parse(&editor::edit_text(&draft.to_buffer()?, "md")?)?;
+                draft.phase = resolve_phase_id(db, project_id, draft.phase.as_deref()).await?;
+                let mut f = feature::create_with_db(
+                    db,
+                    project_id,
+                    &draft.title,
+                    Some(draft.description.as_str()).filter(|d| !d.is_empty()),
+                    draft.phase.as_deref(),
+                )
+                .await?;
+                draft.apply_to(&mut f);
+                feature::FeatureRepository::new(db).update(&f).await?;
+                f
+            } else {
+                let phase_id = resolve_phase_id(db, project_id, phase.as_deref()).await?;
+                feature::create_with_db(
+                    db,
+                    project_id,
+                    title.as_deref().unwrap_or_default(),
+                    description.as_deref(),
+                    phase_id.as_deref(),
+                )
+                .await?
+            };
+            if !quiet {
                 println!(
-                    "- {} [{}] (used {}x)",
-                    skill.name,
-                    skill.category.as_str(),
-                    skill.usage_stats.times_used
+                    "{}",
+                    t_args(
+                        "features-created",
+                        &[("title", &f.title), ("id", &&f.id[..8])]
+                    )
+                );
+                println!(
+                    "  {}: {} ({})",
+                    t("label-project"),
+                    active_project.name,
+                    &active_project.id[..8]
+                );
+                println!(
+                    "\n{}",
+                    t_args("features-next-generate", &[("title", &f.title)])
                 );
             }
         }
-        SkillAction::Search { query } => {
-            let skills = manager.search(&query).await?;
-            if skills.is_empty() {
-                if !quiet {
-                    println!("No matching skills for query '{}'.", query);
+        FeatureAction::Update { id, status, edit } => {
+            let status_enum = status.as_deref().and_then(feature::FeatureStatus::parse);
+            if edit {
+                let repo = feature::FeatureRepository::new(db);
+                let mut f = repo
+                    .get(&id)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!(t_args("features-not-found", &[("id", &id)])))?;
+                if let Some(status) = status_enum {
+                    f.status = status;
                 }
+                let mut draft = feature::FeatureDraft::parse(&editor::edit_text(
+                    &feature::FeatureDraft::from_feature(&f).to_buffer()?,
+                    "md",
+                )?)?;
+                draft.phase = resolve_phase_id(db, &f.project_id, draft.phase.as_deref()).await?;
+                draft.apply_to(&mut f);
+                repo.update(&f).await?;
             } else {
+                feature::update_with_db(db, &id, status_enum, None).await?;
+            }
+            if !quiet {
+                println!("{}", t_args("features-updated", &[("id", &id)]));
+            }
+        }
+        FeatureAction::Delete { ids } => {
+            if ids.len() > 1 {
+                let config = Config::load()?;
+                let payload = serde_json::json!({
+                    "event": HookEvent::BeforeBulkFeatures.as_str(),
+                    "project_id": project_id,
+                    "operation": "delete",
+                    "feature_ids": ids,
+                });
+                fire_hook(
+                    &hooks_with_checkpoints(db, &config),
+                    HookEvent::BeforeBulkFeatures,
+                    &payload,
+                )
+                .await?;
+            }
+            for id in ids {
+                feature::delete_with_db(db, &id).await?;
                 if !quiet {
-                    println!("Found {} matching skills:", skills.len());
-                }
-                for skill in skills {
-                    println!("- {} [{}]", skill.name, skill.category.as_str());
+                    println!("{}", t_args("features-deleted", &[("id", &id)]));
                 }
             }
         }
-        SkillAction::Extract { description } => {
-            let desc = description.unwrap_or_else(|| "Manually added skill".to_string());
-            let mut skill = LearnedSkill::new(
-                desc.clone(),
+        FeatureAction::Time { id: Some(id) } => {
+            let time = FeatureTimeRepository::new(db.pool().clone())
+                .for_feature(&id)
+                .await?;
+            if time.sessions.is_empty() {
+                println!("{}", t_args("features-time-none", &[("id", &id)]));
+            } else {
+                let title = feature::FeatureRepository::new(db)
+                    .get(&id)
+                    .await?
+                    .map(|f| f.title)
+                    .unwrap_or_else(|| id.clone());
+                println!(
+                    "{}",
+                    t_args(
+                        "features-time-header",
+                        &[
+                            ("title", &title),
+                            ("id", &&id[..8.min(id.len())]),
+                            ("total", &format_secs(time.total_secs)),
+                        ]
+                    )
+                );
+                for s in &time.sessions {
+                    println!(
+                        "  [{}] {}  {} - {}",
+                        &s.session_id[..8],
+                        format_secs(s.active_secs),
+                        s.first_active_at.format("%Y-%m-%d %H:%M"),
+                        s.last_active_at.format("%H:%M")
+                    );
+                }
+            }
+        }
+        FeatureAction::Time { id: None } => {
+            let summary = FeatureTimeRepository::new(db.pool().clone())
+                .project_summary(project_id)
+                .await?;
+            if summary.is_empty() {
+                println!(
+                    "{}",
+                    t_args(
+                        "features-time-summary-none",
+                        &[("project", &active_project.name)]
+                    )
+                );
+            } else {
+                println!(
+                    "{}",
+                    t_args(
+                        "features-time-summary-header",
+                        &[("project", &active_project.name)]
+                    )
+                );
+                for f in &summary {
+                    println!(
+                        "  [{}] {:>8}  {} ({} session(s), last {})",
+                        &f.feature_id[..8.min(f.feature_id.len())],
+                        format_secs(f.total_secs),
+                        f.title.as_deref().unwrap_or("(deleted feature)"),
+                        f.sessions,
+                        f.last_active_at.format("%Y-%m-%d")
+                    );
+                }
+                let total: i64 = summary.iter().map(|f| f.total_secs).sum();
+                println!("\n  Total: {}", format_secs(total));
+            }
+        }
+        FeatureAction::Estimate { id } => {
+            let (feature, estimate) = estimate::for_feature(db, &id).await?;
+            let short_id = &feature.id[..8.min(feature.id.len())];
+            match estimate {
+                None => println!("{}", t_args("features-estimate-none", &[("id", &short_id)])),
+                Some(estimate) => {
+                    println!(
+                        "{}",
+                        t_args(
+                            "features-estimate-header",
+                            &[
+                                ("title", &feature.title),
+                                ("id", &short_id),
+                                ("confidence", &estimate.confidence.as_str()),
+                            ]
+                        )
+                    );
+                    print_estimate(&estimate);
+                    if !quiet {
+                        println!("\n{}", t("features-estimate-similar"));
+                        for work in &estimate.similar {
+                            println!(
+                                "  {:>4.0}%  {} ({} tokens, ${:.4}, {})",
+                                work.similarity * 100.0,
+                                work.title.lines().next().unwrap_or_default(),
+                                work.tokens,
+                                work.cost_usd,
+                                format_secs(work.duration_secs)
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        FeatureAction::Branch { id, worktree } => {
+            let id = open::resolve_id(db, open::OpenKind::Feature, &id).await?;
+            let f = feature::FeatureRepository::new(db)
+                .get(&id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!(t_args("features-not-found", &[("id", &id)])))?;
+            let dir = match project::get_with_db(db, &f.project_id)
+                .await?
+                .and_then(|p| p.path)
+            {
+                Some(path) => std::path::PathBuf::from(path),
+                None => std::env::current_dir()?,
+            };
+            let template = Config::load()?.generate.branch_template;
+            let checkout =
+                feature_branch::branch_feature(db, &f, &dir, &template, worktree).await?;
+            let key = if checkout.created {
+                "features-branch-created"
+            } else {
+                "features-branch-switched"
+            };
+            println!(
+                "{} {}",
+                glyphs::check(),
+                t_args(key, &[("branch", &checkout.branch), ("title", &f.title)])
+            );
+            if let Some(path) = &checkout.worktree {
+                println!(
+                    "  {}",
+                    t_args("features-branch-worktree", &[("path", &path.display())])
+                );
+            }
+        }
+        FeatureAction::DeriveCriteria {
+            id,
+            from_conversation,
+            yes,
+        } => {
+            let llm_client = criteria_llm_client(&Config::load()?)?;
+            let proposal = criteria::derive(db, &llm_client, &id, &from_conversation).await?;
+            confirm_criteria(db, &proposal, yes || quiet).await?;
+        }
+        FeatureAction::Import {
+            file,
+            phase,
+            threshold,
+            dry_run,
+            yes,
+        } => {
+            let rows = feature_import::read_file(&file)?;
+            let plan = feature_import::plan(db, project_id, rows, threshold).await?;
+            if plan.rows.is_empty() {
+                println!("{}", t("features-import-nothing"));
+                return Ok(());
+            }
+
+            if !quiet || dry_run {
+                println!(
+                    "{}",
+                    t_args(
+                        "features-import-preview",
+                        &[("project", &active_project.name), ("file", &file.display())]
+                    )
+                );
+                for planned in &plan.rows {
+                    println!("{}", import_preview_line(planned));
+                }
+                println!(
+                    "\n{}",
+                    t_args(
+                        "features-import-summary",
+                        &[("create", &plan.to_create()), ("skip", &plan.to_skip())]
+                    )
+                );
+            }
+            if dry_run || plan.to_create() == 0 {
+                return Ok(());
+            }
+            if !(yes || quiet) && prompt_choice(&t("features-import-confirm"))? != 'y' {
+                println!("{}", t("features-import-cancelled"));
+                return Ok(());
+            }
+
+            let phase_id = resolve_phase_id(db, project_id, phase.as_deref()).await?;
+            let report = feature_import::apply(db, &plan, phase_id.as_deref()).await?;
+            if !quiet {
+                for skipped in &report.skipped {
+                    println!(
+                        "  {} line {}: {} ({})",
+                        t("features-import-skipped"),
+                        skipped.line,
+                        truncate_str(&skipped.title, 50),
+                        skipped.reason
+                    );
+                }
+            }
+            println!(
+                "{}",
+                t_args(
+                    "features-import-done",
+                    &[
+                        ("created", &report.created.len()),
+                        ("skipped", &report.skipped.len())
+                    ]
+                )
+            );
+        }
+    }
+    Ok(())
+}
+
+/// One row of an import preview: `+` creates, `=` is a duplicate, `!` is invalid
+fn import_preview_line(planned: &feature_import::PlannedRow) -> String {
+    let row = &planned.row;
+    let title = if row.title.trim().is_empty() {
+        "(untitled)".to_string()
+    } else {
+        truncate_str(&row.title, 50)
+    };
+    match &planned.action {
+        feature_import::RowAction::Create => {
+            let mut line = format!("  + {:>4}  {}", row.line, title);
+            if let Some(priority) = row.priority {
+                line.push_str(&format!(" (P{})", priority));
+            }
+            if !row.labels.is_empty() {
+                line.push_str(&format!(" [{}]", row.labels.join(", ")));
+            }
+            if row.status == feature::FeatureStatus::Done {
+                line.push_str(&format!(" {}", glyphs::feature_status(row.status)));
+            }
+            line
+        }
+        feature_import::RowAction::Duplicate {
+            feature_id,
+            title: existing,
+            similarity,
+        } => {
+            let existing = match feature_id {
+                Some(id) => format!("'{}' ({})", existing, &id[..8.min(id.len())]),
+                None => format!("'{}' earlier in the file", existing),
+            };
+            format!(
+                "  = {:>4}  {} - duplicate of {}, {:.0}% similar",
+                row.line,
+                title,
+                existing,
+                similarity * 100.0
+            )
+        }
+        feature_import::RowAction::Invalid { reason } => {
+            format!("  ! {:>4}  {} - {}", row.line, title, reason)
+        }
+    }
+}
+
+/// Send one chat message and print the reply (`demiarch chat --message`)
+async fn chat_once(
+    db: &Database,
+    llm_client: &LlmClient,
+    conversation_id: &str,
+    system_prompt: &str,
+    allocation: TokenAllocation,
+    message: &str,
+    json: bool,
+) -> anyhow::Result<()> {
+    chat::send_message(db, conversation_id, chat::MessageRole::User, message).await?;
+    let history = chat::get_history(db, conversation_id, Some(chat::CHAT_HISTORY_LIMIT)).await?;
+    let prompt = chat::build_prompt(system_prompt, &history, allocation);
+    let response = llm_client.complete(prompt.messages, None).await?;
+    let context = prompt.usage.stats().with_measured(response.input_tokens);
+    chat::send_message(
+        db,
+        conversation_id,
+        chat::MessageRole::Assistant,
+        &response.content,
+    )
+    .await?;
+
+    if json {
+        let reply = serde_json::json!({
+            "conversation_id": conversation_id,
+            "reply": response.content,
+            "model": response.model,
+            "input_tokens": response.input_tokens,
+            "output_tokens": response.output_tokens,
+            "context": context,
+        });
+        println!("{}", serde_json::to_string_pretty(&reply)?);
+    } else {
+        println!("{}", response.content.trim_end());
+    }
+    Ok(())
+}
+
+/// Resolve a `-` argument to the text piped on stdin
+fn read_stdin_arg(value: Option<String>) -> anyhow::Result<Option<String>> {
+    use std::io::Read;
+
+    if value.as_deref() != Some("-") {
+        return Ok(value);
+    }
+    let mut stdin = io::stdin();
+    if stdin.is_terminal() {
+        anyhow::bail!("`-` reads from stdin, but nothing was piped in");
+    }
+    let mut text = String::new();
+    stdin.read_to_string(&mut text)?;
+    let text = text.trim();
+    if text.is_empty() {
+        anyhow::bail!("Nothing was read from stdin");
+    }
+    Ok(Some(text.to_string()))
+}
+
+/// Transcribe a voice note with the configured backend and record its cost
+async fn transcribe_note(
+    db: &Database,
+    project_id: Option<&str>,
+    path: &std::path::Path,
+    quiet: bool,
+) -> anyhow::Result<String> {
+    let config = Config::load()?;
+    let transcriber = transcription::Transcriber::new(&config.transcription)?
+        .with_cost_tracker(Arc::new(CostTracker::from_config(&config.cost)));
+    if !quiet {
+        println!(
+            "Transcribing {} with {}...",
+            path.display(),
+            transcriber.backend().as_str()
+        );
+    }
+    let transcript = transcriber.transcribe(path).await?;
+    transcription::record_cost(db, project_id, &transcript).await?;
+    if !quiet {
+        match transcript.duration_secs {
+            Some(secs) => println!(
+                "Transcribed {} of audio (${:.4})",
+                format_secs(secs.round() as i64),
+                transcript.cost_usd
+            ),
+            None => println!("Transcribed with {}", transcript.model),
+        }
+    }
+    Ok(transcript.text)
+}
+
+/// LLM client for deriving acceptance criteria
+fn criteria_llm_client(config: &Config) -> anyhow::Result<LlmClient> {
+    let api_key = config
+        .llm
+        .resolved_api_key()
+        .map_err(|e| anyhow::anyhow!("Config error: {}", e))?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "API key not configured. Set DEMIARCH_API_KEY or OPENROUTER_API_KEY environment variable."
+            )
+        })?;
+    LlmClient::builder()
+        .config(config.llm.clone())
+        .api_key(api_key)
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create LLM client: {}", e))
+}
+
+/// Show derived criteria against the feature's current ones and store them if confirmed
+async fn confirm_criteria(
+    db: &Database,
+    proposal: &criteria::CriteriaProposal,
+    assume_yes: bool,
+) -> anyhow::Result<()> {
+    let feature = &proposal.feature;
+    let short_id = &feature.id[..8.min(feature.id.len())];
+    if proposal.is_unchanged() {
+        println!(
+            "{}",
+            t_args("features-criteria-unchanged", &[("id", &short_id)])
+        );
+        return Ok(());
+    }
+
+    if !assume_yes {
+        println!(
+            "{}",
+            t_args(
+                "features-criteria-header",
+                &[("title", &feature.title), ("id", &short_id)]
+            )
+        );
+        print!("{}", proposal.diff);
+        if prompt_choice(&t("features-criteria-confirm"))? != 'y' {
+            println!("{}", t("features-criteria-discarded"));
+            return Ok(());
+        }
+    }
+
+    criteria::apply(db, proposal).await?;
+    println!(
+        "{}",
+        t_args(
+            "features-criteria-saved",
+            &[
+                ("count", &proposal.criteria.len().to_string()),
+                ("id", &short_id)
+            ]
+        )
+    );
+    Ok(())
+}
+
+/// Print an estimate's expected values and ranges
+fn print_estimate(estimate: &estimate::FeatureEstimate) {
+    let tokens = &estimate.tokens;
+    let cost = &estimate.cost_usd;
+    let time = &estimate.duration_secs;
+    println!(
+        "  Tokens: ~{:.0} ({:.0} - {:.0})",
+        tokens.expected, tokens.low, tokens.high
+    );
+    println!(
+        "  Cost:   ~${:.4} (${:.4} - ${:.4})",
+        cost.expected, cost.low, cost.high
+    );
+    println!(
+        "  Time:   ~{} ({} - {})",
+        format_secs(time.expected as i64),
+        format_secs(time.low as i64),
+        format_secs(time.high as i64)
+    );
+}
+
+/// Ask before a generation whose estimated cost is over `cost.confirm_above_usd`
+///
+/// Returns false if the user declined.
+async fn confirm_generation_cost(
+    db: &Database,
+    project_id: Option<&str>,
+    description: &str,
+) -> anyhow::Result<bool> {
+    let threshold = Config::load()?.cost.confirm_above_usd;
+    if threshold <= 0.0 {
+        return Ok(true);
+    }
+    let Some(estimate) = estimate::for_description(db, project_id, description).await? else {
+        return Ok(true);
+    };
+    if estimate.cost_usd.expected <= threshold {
+        return Ok(true);
+    }
+
+    println!(
+        "Estimated cost is over ${:.2} ({} confidence, from {} similar generation(s)):",
+        threshold,
+        estimate.confidence.as_str(),
+        estimate.similar.len()
+    );
+    print_estimate(&estimate);
+    if !io::stdin().is_terminal() {
+        anyhow::bail!("Confirmation needs a terminal; pass --yes to proceed");
+    }
+    Ok(prompt_choice("Proceed? [y/N]")? == 'y')
+}
+
+/// Asks on the terminal when a generation reaches an approval gate
+///
+/// The answer is read on its own thread so the gate's timeout can abort a
+/// prompt nobody answers. Without a terminal every request is rejected.
+struct TerminalApprover;
+
+impl Approver for TerminalApprover {
+    fn request(
+        &self,
+        request: ApprovalRequest,
+    ) -> tokio::sync::oneshot::Receiver<ApprovalDecision> {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        if !io::stdin().is_terminal() {
+            eprintln!(
+                "Approval needed ({}) but there is no terminal to ask",
+                request.gate
+            );
+            return receiver;
+        }
+
+        eprintln!();
+        eprintln!("Approval needed: {}", request.summary);
+        for detail in &request.details {
+            eprintln!("  {}", detail);
+        }
+        let wait = (request.expires_at - request.requested_at).num_seconds();
+        eprint!("Continue? [y/N] (aborts in {}s): ", wait);
+        let _ = io::stderr().flush();
+        std::thread::spawn(move || {
+            let mut line = String::new();
+            if io::stdin().read_line(&mut line).is_ok() {
+                let decision = match line.trim().to_ascii_lowercase().as_str() {
+                    "y" | "yes" => ApprovalDecision::Approve,
+                    _ => ApprovalDecision::Reject,
+                };
+                let _ = sender.send(decision);
+            }
+        });
+        receiver
+    }
+}
+
+/// Approval gates from `[approvals]`, answered on the terminal
+fn terminal_approval_gates() -> anyhow::Result<ApprovalGates> {
+    Ok(ApprovalGates::new(&Config::load()?.approvals).with_approver(Arc::new(TerminalApprover)))
+}
+
+/// Mark the active session as active now
+///
+/// Every command that opens the database counts as a heartbeat, so time
+/// between commands is attributed to the session's current feature.
+async fn record_heartbeat(db: &Database) {
+    let manager = SessionManager::new(db.pool().clone());
+    if let Ok(Some(session)) = manager.get_active().await {
+        if let Err(e) = manager.touch(session.id).await {
+            warn!(error = %e, "Failed to record session heartbeat");
+        }
+    }
+}
+
+/// Write a plan run's accepted files through its generation record
+///
+/// Only this run's files are applied; earlier runs' files were already handled.
+async fn apply_run_files(
+    db: &Database,
+    run: &generation::PlanRun,
+    progress: &Progress,
+) -> anyhow::Result<()> {
+    let accepted: Vec<_> = run
+        .result
+        .files
+        .iter()
+        .filter(|f| !f.has_syntax_errors() && !f.is_blocked())
+        .collect();
+    progress.stage(Stage::Write, format!("Writing {} file(s)", accepted.len()));
+    for (i, file) in accepted.iter().enumerate() {
+        generation::apply(db, &run.generation.id, Some(&file.path.to_string_lossy())).await?;
+        progress.advance((i + 1) as u64, accepted.len() as u64);
+    }
+    Ok(())
+}
+
+/// Clean up after a generation stopped by a shutdown signal, before exiting
+///
+/// Marks running agents cancelled, waits briefly for queued event and cost
+/// deliveries, checkpoints the project, then pauses the session, releases
+/// locks and closes the database. Every step is best-effort so the rest
+/// still run if one fails. The generation itself was already marked
+/// interrupted with its unfinished tasks pending.
+async fn shutdown_interrupted_generation(
+    db: &Database,
+    record: &generation::Generation,
+    signal: ShutdownSignal,
+    quiet: bool,
+) {
+    warn!(generation_id = %record.id, signal = %signal, "Generation interrupted");
+    let cancelled = cancel_active_agents(&format!("Interrupted by {}", signal));
+
+    let undelivered = events::global()
+        .flush(std::time::Duration::from_secs(5))
+        .await;
+    if undelivered > 0 {
+        warn!(count = undelivered, "Shutting down with undelivered events");
+    }
+
+    let checkpoint = match record
+        .project_id
+        .as_deref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+    {
+        Some(project_id) => checkpoint::create_checkpoint_with_db(
+            db,
+            project_id,
+            format!("Interrupted generation {}", record.id),
+            None,
+        )
+        .await
+        .map_err(|e| warn!(error = %e, "Failed to checkpoint interrupted generation"))
+        .ok(),
+        None => None,
+    };
+
+    let lock_dir = dirs::config_dir()
+        .unwrap_or_default()
+        .join("demiarch")
+        .join("locks");
+    let lock_manager = Arc::new(LockManager::new(
+        LockConfig::default().with_lock_dir(lock_dir),
+    ));
+    if let Err(e) = lock_manager.initialize().await {
+        warn!(error = %e, "Failed to load locks for release");
+    }
+    let handler = ShutdownHandler::new(
+        SessionManager::new(db.pool().clone()),
+        lock_manager,
+        db.clone(),
+        ShutdownConfig::quick(),
+    );
+    let shutdown = handler.shutdown_gracefully().await;
+
+    if quiet {
+        return;
+    }
+    eprintln!();
+    eprintln!("Generation {} interrupted by {}.", record.id, signal);
+    eprintln!(
+        "  Completed tasks: {} of {}",
+        record
+            .plan
+            .as_ref()
+            .map_or(0, |p| p.tasks.len() - record.unfinished_tasks().len()),
+        record.plan.as_ref().map_or(0, |p| p.tasks.len())
+    );
+    if cancelled > 0 {
+        eprintln!("  Agents cancelled: {}", cancelled);
+    }
+    if let Some(checkpoint) = checkpoint {
+        eprintln!("  Checkpoint: {}", checkpoint.id);
+    }
+    match shutdown {
+        Ok(result) => {
+            if result.locks_released > 0 {
+                eprintln!("  Released {} locks", result.locks_released);
+            }
+            for warning in &result.warnings {
+                eprintln!("  Warning: {}", warning);
+            }
+        }
+        Err(e) => eprintln!("  Warning: shutdown did not finish: {}", e),
+    }
+    eprintln!("Resume with: demiarch generate --resume {}", record.id);
+}
+
+/// Generate from a Markdown spec, optionally regenerating on every change
+///
+/// Each iteration only runs the spec sections that were added or edited since
+/// the previous one, and is recorded in the spec's cost/token ledger.
+async fn cmd_generate_spec(
+    db: &Database,
+    spec_path: &std::path::Path,
+    watch: bool,
+    dry_run: bool,
+    quiet: bool,
+    progress: &Progress,
+) -> anyhow::Result<()> {
+    let output_dir = std::env::current_dir()?;
+    let current_project = project::find_by_directory(db, &output_dir).await?;
+    let framework = current_project.as_ref().map(|p| p.framework.clone());
+    let secret_names = generation_secret_names(db, current_project.as_ref()).await;
+    let persona = project_persona(db, current_project.as_ref()).await;
+    let project_id = current_project.as_ref().map(|p| p.id.clone());
+    let spec_key = spec::spec_key(spec_path);
+
+    let mut watcher = spec::SpecWatcher::new(spec_path);
+    let mut source = watcher
+        .poll()
+        .ok_or_else(|| anyhow::anyhow!("Cannot read spec file {}", spec_path.display()))?;
+
+    if watch && !quiet {
+        println!(
+            "Watching {} for changes (Ctrl+C to stop)",
+            spec_path.display()
+        );
+        println!();
+    }
+
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        let run_task = |task: PlanTask| {
+            let framework = framework.clone();
+            let secret_names = secret_names.clone();
+            let persona = persona.clone();
+            let progress = progress.clone();
+            async move {
+                progress.stage(
+                    Stage::Plan,
+                    format!("Task {}: {}", task.id, task.description),
+                );
+                generate::generate_with_persona(
+                    &task.description,
+                    framework.as_deref(),
+                    secret_names,
+                    persona,
+                    true,
+                    &progress,
+                )
+                .await
+            }
+        };
+        let iteration = spec::run_iteration(
+            db,
+            spec_path,
+            &source,
+            &output_dir,
+            project_id.as_deref(),
+            run_task,
+        );
+        let outcome = if watch {
+            tokio::select! {
+                outcome = iteration => outcome,
+                _ = &mut ctrl_c => break,
+            }
+        } else {
+            iteration.await
+        };
+
+        let failed = match outcome {
+            Ok(outcome) => {
+                if let (Some(run), false) = (&outcome.run, dry_run) {
+                    apply_run_files(db, run, progress).await?;
+                }
+                progress.finish("");
+                if !quiet {
+                    print_spec_iteration(db, &spec_key, &outcome, dry_run).await?;
+                }
+                outcome.run.map(|r| r.failures.len()).unwrap_or(0)
+            }
+            // A bad iteration shouldn't end the loop; the next save retries it
+            Err(e) if watch => {
+                progress.finish("");
+                eprintln!("Iteration failed: {}", e);
+                0
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if !watch {
+            if failed > 0 {
+                anyhow::bail!("{} generation task(s) failed", failed);
+            }
+            return Ok(());
+        }
+
+        tokio::select! {
+            changed = watcher.changed() => source = changed?,
+            _ = &mut ctrl_c => break,
+        }
+
+        if !quiet {
+            println!();
+            println!("Spec changed, regenerating...");
+        }
+    }
+
+    progress.finish("");
+    if !quiet {
+        let totals = spec::SpecLedgerRepository::new(db)
+            .totals(&spec_key)
+            .await?;
+        println!();
+        println!(
+            "Stopped watching. {} iteration(s), {} tokens, ${:.4} total.",
+            totals.iterations, totals.tokens_used, totals.cost_usd
+        );
+    }
+    Ok(())
+}
+
+/// Print what one spec iteration did and the spec's running totals
+async fn print_spec_iteration(
+    db: &Database,
+    spec_key: &str,
+    outcome: &spec::SpecRun,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let diff = &outcome.diff;
+    let (Some(iteration), Some(run)) = (&outcome.iteration, &outcome.run) else {
+        println!("No spec sections changed; nothing to regenerate.");
+        return Ok(());
+    };
+
+    println!(
+        "Iteration {}: {} task(s) run, {} unchanged skipped",
+        iteration.iteration, iteration.tasks_run, iteration.tasks_skipped
+    );
+    for (label, ids) in [
+        ("added", &diff.added),
+        ("changed", &diff.changed),
+        ("removed", &diff.removed),
+    ] {
+        if !ids.is_empty() {
+            println!("  {}: {}", label, ids.join(", "));
+        }
+    }
+    for file in &run.result.files {
+        let status = if file.has_syntax_errors() || file.is_blocked() {
+            "rejected"
+        } else if dry_run {
+            "would write"
+        } else if file.is_new {
+            "new"
+        } else {
+            "modified"
+        };
+        println!("  [{}] {}", status, file.path.display());
+    }
+    for failure in &run.failures {
+        eprintln!("  failed {}: {}", failure.task_id, failure.error);
+    }
+
+    let totals = spec::SpecLedgerRepository::new(db).totals(spec_key).await?;
+    println!(
+        "  Tokens: {} (${:.4}) | Spec total: {} iteration(s), {} tokens, ${:.4}",
+        iteration.tokens_used,
+        iteration.cost_usd,
+        totals.iterations,
+        totals.tokens_used,
+        totals.cost_usd
+    );
+    println!("  Generation ID: {}", iteration.generation_id);
+    Ok(())
+}
+
+/// Generate several features, each planned on its own, and report on all of them
+async fn cmd_generate_queue(
+    db: &Database,
+    features: &[String],
+    options: &queue::QueueOptions,
+    quiet: bool,
+    json: bool,
+    progress: &Progress,
+) -> anyhow::Result<()> {
+    let output_dir = std::env::current_dir()?;
+    let project = resolve_project(db, None).await?;
+    let features = queue::resolve_features(db, &project.id, features).await?;
+    let secret_names = generation_secret_names(db, Some(&project)).await;
+    let persona = project_persona(db, Some(&project)).await;
+    let gates = terminal_approval_gates()?;
+
+    if !quiet && !json {
+        println!(
+            "Generating {} feature(s), {} at a time{}",
+            features.len(),
+            options.concurrency.max(1),
+            if options.dry_run { " (dry run)" } else { "" }
+        );
+        println!();
+    }
+
+    let run_task = |task: PlanTask| {
+        let framework = project.framework.clone();
+        let secret_names = secret_names.clone();
+        let persona = persona.clone();
+        let progress = progress.clone();
+        async move {
+            progress.stage(Stage::Plan, task.description.clone());
+            generate::generate_with_persona(
+                &task.description,
+                Some(framework.as_str()),
+                secret_names,
+                persona,
+                true,
+                &progress,
+            )
+            .await
+        }
+    };
+    let config = Config::load()?;
+    let planner =
+        generation::planner_client(&config, Arc::new(CostTracker::from_config(&config.cost)))?;
+    let report = queue::run(
+        db,
+        &output_dir.to_string_lossy(),
+        features,
+        options,
+        &gates,
+        planner.as_ref(),
+        run_task,
+    )
+    .await?;
+    progress.finish("");
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if !quiet {
+        for run in &report.runs {
+            println!("[{}] {}", run.status.as_str(), run.title);
+            if let Some(ref id) = run.generation_id {
+                println!("  Generation ID: {}", id);
+            }
+            if let Some(ref id) = run.checkpoint_id {
+                println!("  Checkpoint: {}", id);
+            }
+            for file in &run.files {
+                println!("  {}", file);
+            }
+            if run.status != queue::FeatureRunStatus::Skipped {
+                println!("  Tokens: {} (${:.4})", run.tokens_used, run.cost_usd);
+            }
+            if let Some(ref error) = run.error {
+                println!("  Error: {}", error);
+            }
+        }
+
+        let overlapping = report.overlapping_files();
+        if !overlapping.is_empty() {
+            println!();
+            println!("Generated by more than one feature (the last to finish was kept):");
+            for (file, titles) in &overlapping {
+                println!("  {}: {}", file, titles.join(", "));
+            }
+        }
+
+        println!();
+        println!(
+            "{} completed, {} failed, {} skipped | {} tokens, ${:.4} in {}",
+            report.count(queue::FeatureRunStatus::Completed),
+            report.count(queue::FeatureRunStatus::Failed),
+            report.count(queue::FeatureRunStatus::Skipped),
+            report.total_tokens(),
+            report.total_cost_usd(),
+            format_secs(report.duration_ms / 1000)
+        );
+    }
+
+    let failed = report.count(queue::FeatureRunStatus::Failed);
+    if failed > 0 {
+        anyhow::bail!("{} queued feature(s) failed", failed);
+    }
+    Ok(())
+}
+
+async fn cmd_generate(
+    db: &Database,
+    description: Option<&str>,
+    resume: Option<&str>,
+    dry_run: bool,
+    review: bool,
+    open_pr: bool,
+    yes: bool,
+    quiet: bool,
+    progress: &Progress,
+) -> anyhow::Result<()> {
+    let output_dir = std::env::current_dir()?;
+
+    let resumed = match resume {
+        Some(id) => {
+            let existing = generation::GenerationRepository::new(db)
+                .get(id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Generation not found: {}", id))?;
+            if std::path::Path::new(&existing.output_dir) != output_dir {
+                anyhow::bail!(
+                    "Generation {} was run in {}; resume it from that directory",
+                    id,
+                    existing.output_dir
+                );
+            }
+            Some(existing)
+        }
+        None => None,
+    };
+    // A resumed generation already has its snippets expanded
+    let current_project = project::find_by_directory(db, &output_dir).await?;
+    let description = match (&resumed, description) {
+        (Some(existing), _) => existing.description.clone(),
+        (None, Some(description)) => expand_snippets(description, current_project.as_ref())?,
+        (None, None) => anyhow::bail!("A description or --resume is required"),
+    };
+
+    let environment = current_environment();
+    if !quiet {
+        if let Some(ref existing) = resumed {
+            println!(
+                "Resuming generation {} ({} unfinished task(s)): {}",
+                existing.id,
+                existing.unfinished_tasks().len(),
+                description
+            );
+            let changes = existing
+                .environment
+                .as_ref()
+                .map(|recorded| recorded.changes_from(&environment))
+                .unwrap_or_default();
+            if !changes.is_empty() {
+                warn!(generation_id = %existing.id, changes = changes.len(), "Resuming under a different environment");
+                println!(
+                    "Warning: the environment has changed since this generation started; \
+                     output may differ from the completed tasks:"
+                );
+                print_environment_changes(&changes);
+            }
+        } else if dry_run {
+            println!("Dry run: Generating code for: {}", description);
+        } else {
+            println!("Generating code for: {}", description);
+        }
+        println!();
+    }
+
+    let framework = current_project.as_ref().map(|p| p.framework.clone());
+    let secret_names = generation_secret_names(db, current_project.as_ref()).await;
+    let persona = project_persona(db, current_project.as_ref()).await;
+    let gates = terminal_approval_gates()?;
+
+    if resumed.is_none()
+        && !yes
+        && !confirm_generation_cost(
+            db,
+            current_project.as_ref().map(|p| p.id.as_str()),
+            &description,
+        )
+        .await?
+    {
+        println!("Generation cancelled.");
+        return Ok(());
+    }
+
+    // With worktree isolation, new generations write into a worktree of their
+    // own and only reach the working directory by merging its branch (or
+    // through a pull request with --open-pr)
+    let generate_config = Config::load()?.generate;
+    let isolation = if open_pr {
+        worktree::Isolation::Worktree
+    } else {
+        worktree::Isolation::from_config(&generate_config)
+    };
+    let isolated = match isolation {
+        worktree::Isolation::Worktree if resumed.is_none() && !dry_run => {
+            let name = Uuid::new_v4().simple().to_string();
+            let isolated = worktree::Worktree::create(&output_dir, &name[..8]).await?;
+            if !quiet {
+                println!("Generating on branch {}", isolated.branch);
+            }
+            Some(isolated)
+        }
+        _ => None,
+    };
+    let write_dir = isolated
+        .as_ref()
+        .map(|w| w.output_dir(&output_dir))
+        .unwrap_or_else(|| output_dir.clone());
+
+    // Generate without writing; files are written through the generation record
+    // so every accept/reject decision is tracked
+    let run_task = |task: PlanTask| {
+        let framework = framework.clone();
+        let secret_names = secret_names.clone();
+        let persona = persona.clone();
+        let progress = progress.clone();
+        async move {
+            progress.stage(
+                Stage::Plan,
+                format!("Task {}: {}", task.id, task.description),
+            );
+            generate::generate_with_persona(
+                &task.description,
+                framework.as_deref(),
+                secret_names,
+                persona,
+                true,
+                &progress,
+            )
+            .await
+        }
+    };
+    // A resumed generation keeps the plan it was started with
+    let planned = match resumed.as_ref().and_then(|g| g.plan.clone()) {
+        Some(plan) => plan.into(),
+        None => {
+            let config = Config::load()?;
+            let planner = generation::planner_client(
+                &config,
+                Arc::new(CostTracker::from_config(&config.cost)),
+            )?;
+            generation::plan_generation(&description, planner.as_ref()).await
+        }
+    };
+    // Ctrl-C or SIGTERM stops the run between or during tasks so the
+    // generation can be resumed instead of leaving it half-written
+    let signals = SignalGuard::install();
+    let run = match resumed {
+        Some(existing) => {
+            generation::resume_cancellable(db, &existing.id, signals.token(), run_task).await
+        }
+        None => {
+            let mut new_generation =
+                generation::Generation::new(&description, write_dir.to_string_lossy())
+                    .with_environment(environment)
+                    .with_planning(&planned);
+            if let Some(ref p) = current_project {
+                new_generation = new_generation.with_project(&p.id);
+            }
+            match gates.after_planning(&description, &planned.plan).await {
+                Ok(()) => {
+                    generation::start_cancellable(
+                        db,
+                        new_generation,
+                        planned.plan.clone(),
+                        signals.token(),
+                        run_task,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            }
+        }
+    };
+    let run = match run {
+        Ok(run) => run,
+        Err(e) => {
+            if let Some(ref isolated) = isolated {
+                isolated.discard().await;
+            }
+            return Err(e.into());
+        }
+    };
+    if run.generation.status == generation::GenerationStatus::Interrupted {
+        progress.finish("");
+        let signal = signals.received().unwrap_or(ShutdownSignal::Interrupt);
+        shutdown_interrupted_generation(db, &run.generation, signal, quiet).await;
+        if let (Some(isolated), false) = (&isolated, quiet) {
+            eprintln!(
+                "Partial work is kept on branch {}; resume from {}",
+                isolated.branch,
+                write_dir.display()
+            );
+        }
+        std::process::exit(signal.exit_code());
+    }
+    drop(signals);
+    let record = &run.generation;
+    let result = &run.result;
+
+    // Packages the generated manifests add, compared with disk before writing
+    let added = if review || dry_run {
+        Vec::new()
+    } else {
+        let files: Vec<_> = result
+            .files
+            .iter()
+            .filter(|f| !f.has_syntax_errors())
+            .map(|f| (f.path.as_path(), f.content.as_str()))
+            .collect();
+        dependencies::added_dependencies(&write_dir, &files)
+    };
+
+    let written = if review {
+        progress.finish("");
+        review_generation_interactive(db, &record.id, quiet).await
+    } else if !dry_run {
+        if let Err(e) = gates.after_review(&description, result).await {
+            progress.finish("");
+            if let Some(ref isolated) = isolated {
+                isolated.discard().await;
+            }
+            eprintln!(
+                "Files were kept on generation {}; review them with: demiarch generations review {}",
+                record.id, record.id
+            );
+            return Err(e.into());
+        }
+        apply_run_files(db, &run, progress).await
+    } else {
+        Ok(())
+    };
+    if let Err(e) = written {
+        if let Some(ref isolated) = isolated {
+            isolated.discard().await;
+        }
+        return Err(e);
+    }
+    let installs = if added.is_empty() {
+        Vec::new()
+    } else {
+        record_dependencies(db, &added, framework.as_deref()).await;
+        if generate_config.install_dependencies {
+            install_dependencies(&write_dir, &added, progress).await
+        } else {
+            Vec::new()
+        }
+    };
+    let outcome = match isolated {
+        Some(ref isolated) => {
+            progress.stage(
+                Stage::Write,
+                format!("Verifying branch {}", isolated.branch),
+            );
+            Some(
+                isolated
+                    .finish(&generate_config, &description, !open_pr)
+                    .await?,
+            )
+        }
+        None => None,
+    };
+    let opened = match (&isolated, &outcome) {
+        (Some(isolated), Some(outcome))
+            if open_pr && outcome.committed && outcome.checks_passed() =>
+        {
+            progress.stage(Stage::Write, "Opening pull request".to_string());
+            let base = pull_request::current_branch(&isolated.repo_root).await?;
+            let body = pull_request::body(&description, &planned.plan, result, outcome, &record.id);
+            Some(
+                pull_request::open(
+                    &isolated.repo_root,
+                    &pull_request::ForgeTokens::new(db),
+                    &isolated.branch,
+                    &base,
+                    &pull_request::title(&description),
+                    &body,
+                )
+                .await?,
+            )
+        }
+        _ => None,
+    };
+    progress.finish("");
+
+    if !quiet {
+        if run.failures.is_empty() {
+            println!("Generation complete!");
+        } else {
+            println!("Generation incomplete.");
+        }
+        println!();
+        println!("  Generation ID: {}", record.id);
+        println!("  Open in app: {}", DeepLink::Generation(record.id.clone()));
+        println!("  Files created: {}", result.files_created);
+        println!("  Files modified: {}", result.files_modified);
+        println!("  Tokens used: {}", result.tokens_used);
+        println!("  Estimated cost: ${:.4}", result.cost_usd);
+        if resume.is_some() {
+            println!(
+                "  Total across runs: {} tokens, ${:.4}",
+                record.tokens_used, record.cost_usd
+            );
+        }
+
+        let rejected: Vec<_> = result.rejected_files().collect();
+        if !rejected.is_empty() {
+            println!();
+            println!("Rejected (syntax errors after repair):");
+            for file in &rejected {
+                println!("  {}", file.path.display());
+                for error in file.validation.iter().flat_map(|v| &v.errors) {
+                    println!("    line {}", error);
+                }
+            }
+            println!(
+                "Force-apply with: demiarch generations apply {} --file <path>",
+                record.id
+            );
+        }
+
+        let security: Vec<_> = result.security_findings().cloned().collect();
+        if !security.is_empty() {
+            println!();
+            println!("Security findings ({}):", security.len());
+            print_security_findings(&security);
+            if result.blocked_files().next().is_some() {
+                println!(
+                    "Files with critical findings were not written. Force-apply with: demiarch generations apply {} --file <path>",
+                    record.id
+                );
+            }
+        }
+
+        let findings: Vec<_> = result.lint_findings().collect();
+        if !findings.is_empty() {
+            println!();
+            println!("Lint findings ({}):", findings.len());
+            for finding in findings {
+                println!("  {}", finding);
+            }
+        }
+
+        if !result.files.is_empty() && !review {
+            println!();
+            println!("Generated files:");
+            for file in &result.files {
+                let status = if file.has_syntax_errors() || file.is_blocked() {
+                    "rejected"
+                } else if file.is_new {
+                    "new"
+                } else {
+                    "modified"
+                };
+                let lang = file
+                    .language
+                    .as_ref()
+                    .map(|l| format!(" ({})", l))
+                    .unwrap_or_default();
+                if dry_run {
+                    println!("  [would create] {}{}", file.path.display(), lang);
+                } else {
+                    println!("  [{}] {}{}", status, file.path.display(), lang);
+                }
+            }
+        }
+
+        if !added.is_empty() {
+            print_added_dependencies(&added, &installs);
+        }
+        if let Some(ref outcome) = outcome {
+            print_isolation_outcome(outcome);
+        }
+        if let Some(ref pr) = opened {
+            println!("Opened pull request #{}: {}", pr.number, pr.url);
+        }
+
+        if dry_run {
+            println!();
+            println!("Dry run complete. No files were written.");
+            println!("Review and apply later with:");
+            println!("  demiarch generations review {}", record.id);
+        }
+    }
+
+    if !run.failures.is_empty() {
+        eprintln!();
+        eprintln!("Failed tasks:");
+        for failure in &run.failures {
+            eprintln!("  {}: {}", failure.task_id, failure.error);
+        }
+        eprintln!("Resume with: demiarch generate --resume {}", record.id);
+        anyhow::bail!("{} generation task(s) failed", run.failures.len());
+    }
+
+    Ok(())
+}
+
+/// Record packages added by a generation in the knowledge graph
+///
+/// Best-effort: a failure is logged and does not fail the generation.
+async fn record_dependencies(db: &Database, added: &[DependencyChange], framework: Option<&str>) {
+    let repo = SqliteKnowledgeGraphRepository::new(db.pool().clone());
+    if let Err(e) = dependencies::record_in_graph(&repo, added, framework).await {
+        warn!(error = %e, "Failed to record dependencies in the knowledge graph");
+    }
+}
+
+/// Run each ecosystem's install command, returning an error message per failure
+async fn install_dependencies(
+    dir: &std::path::Path,
+    added: &[DependencyChange],
+    progress: &Progress,
+) -> Vec<(DependencyChange, Option<String>)> {
+    let mut installs = Vec::new();
+    for change in added {
+        progress.stage(
+            Stage::Write,
+            format!("Installing {} package(s)", change.ecosystem),
+        );
+        let error = match dependencies::install(dir, change).await {
+            Ok(output) if output.success() => None,
+            Ok(output) => Some(
+                output
+                    .stderr
+                    .lines()
+                    .last()
+                    .unwrap_or("install command failed")
+                    .to_string(),
+            ),
+            Err(e) => Some(e.to_string()),
+        };
+        installs.push((change.clone(), error));
+    }
+    installs
+}
+
+/// Print the dependencies a generation added and how their install went
+fn print_added_dependencies(
+    added: &[DependencyChange],
+    installs: &[(DependencyChange, Option<String>)],
+) {
+    println!();
+    println!("Dependencies added:");
+    for change in added {
+        println!(
+            "  {}: {}",
+            change.manifest.display(),
+            change.packages.join(", ")
+        );
+        let (program, args) = change.ecosystem.install_command(&change.packages);
+        match installs.iter().find(|(c, _)| c == change) {
+            Some((_, None)) => println!("    installed with {} {}", program, args.join(" ")),
+            Some((_, Some(error))) => println!("    install failed: {}", error),
+            None => println!("    install with: {} {}", program, args.join(" ")),
+        }
+    }
+}
+
+/// Print security audit findings, one line each with the suggested fix below
+fn print_security_findings(findings: &[SecurityFinding]) {
+    for finding in findings {
+        let blocking = if finding.blocking { " (blocking)" } else { "" };
+        println!("  {}{}", finding, blocking);
+        if let Some(ref suggestion) = finding.suggestion {
+            println!("    {}", suggestion);
+        }
+    }
+}
+
+/// Print the verification results and what happened to an isolated branch
+fn print_isolation_outcome(outcome: &worktree::IsolationOutcome) {
+    println!();
+    if !outcome.committed {
+        println!("No files changed; branch {} was removed.", outcome.branch);
+        return;
+    }
+    for check in &outcome.checks {
+        let status = if check.passed { "pass" } else { "FAIL" };
+        println!("  [{}] {}", status, check.command);
+        if !check.passed && !check.output.is_empty() {
+            for line in check.output.lines() {
+                println!("    {}", line);
+            }
+        }
+    }
+    if outcome.merged {
+        println!(
+            "Merged branch {} into the working directory.",
+            outcome.branch
+        );
+    } else if let Some(ref error) = outcome.merge_error {
+        println!("Could not merge branch {}: {}", outcome.branch, error);
+        println!("Merge it yourself with: git merge {}", outcome.branch);
+    } else if outcome.checks_passed() {
+        println!(
+            "Verification passed; the files are on branch {}.",
+            outcome.branch
+        );
+    } else {
+        println!(
+            "Verification failed; the files were kept on branch {}.",
+            outcome.branch
+        );
+    }
+}
+
+/// Walk through unapplied files of a generation, prompting accept/reject for each
+async fn review_generation_interactive(
+    db: &Database,
+    generation_id: &str,
+    quiet: bool,
+) -> anyhow::Result<()> {
+    let review = generation::review(db, generation_id).await?;
+
+    let pending: Vec<_> = review.unapplied().collect();
+    if pending.is_empty() {
+        if !quiet {
+            println!("No unapplied files in generation {}.", generation_id);
+        }
+        return Ok(());
+    }
+
+    let mut accept_rest = false;
+    let mut accepted = 0;
+    let mut rejected = 0;
+
+    for (i, file) in pending.iter().enumerate() {
+        let decision = if accept_rest {
+            generation::ArtifactDecision::Accepted
+        } else {
+            println!();
+            println!(
+                "[{}/{}] {} ({}, +{} -{})",
+                i + 1,
+                pending.len(),
+                file.path,
+                if file.is_new { "new" } else { "modified" },
+                file.additions,
+                file.deletions
+            );
+            if !file.validation_errors.is_empty() {
+                println!("  Syntax errors:");
+                for error in &file.validation_errors {
+                    println!("    line {}", error);
+                }
+            }
+            if file.diff.is_empty() {
+                println!("  (no changes compared to the file on disk)");
+            } else {
+                print!("{}", file.diff);
+            }
+
+            match prompt_choice("Apply this file? [y]es / [n]o / [a]ll remaining / [q]uit")? {
+                'y' => generation::ArtifactDecision::Accepted,
+                'a' => {
+                    accept_rest = true;
+                    generation::ArtifactDecision::Accepted
+                }
+                'q' => break,
+                _ => generation::ArtifactDecision::Rejected,
+            }
+        };
+
+        generation::decide(db, generation_id, &file.path, decision).await?;
+        match decision {
+            generation::ArtifactDecision::Accepted => accepted += 1,
+            _ => rejected += 1,
+        }
+    }
+
+    if !quiet {
+        println!();
+        println!(
+            "Review complete: {} accepted, {} rejected, {} left pending.",
+            accepted,
+            rejected,
+            pending.len() - accepted - rejected
+        );
+        if accepted < pending.len() {
+            println!("Apply remaining files later with:");
+            println!("  demiarch generations apply {}", generation_id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Prompt on stdin and return the lowercased first character of the answer
+fn prompt_choice(prompt: &str) -> anyhow::Result<char> {
+    print!("{} ", prompt);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(answer
+        .trim()
+        .chars()
+        .next()
+        .map(|c| c.to_ascii_lowercase())
+        .unwrap_or('n'))
+}
+
+/// Snapshot of the current environment, including installed plugin versions
+fn current_environment() -> environment::GenerationEnvironment {
+    let plugins = loader::discover_manifests()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| loader::load_manifest(path).ok())
+        .map(|manifest| (manifest.id, manifest.version))
+        .collect::<Vec<_>>();
+    environment::GenerationEnvironment::current().with_plugins(plugins)
+}
+
+fn print_environment_changes(changes: &[environment::EnvironmentChange]) {
+    for change in changes {
+        println!(
+            "  {}: {} -> {}",
+            change.field,
+            change.recorded.as_deref().unwrap_or("(none)"),
+            change.current.as_deref().unwrap_or("(none)")
+        );
+    }
+}
+
+async fn cmd_generations(
+    db: &Database,
+    action: GenerationAction,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    match action {
+        GenerationAction::List {
+            project,
+            feature,
+            session,
+            status,
+            limit,
+        } => {
+            let status = status
+                .map(|s| {
+                    generation::GenerationStatus::parse(&s).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Invalid status: {}. Use: running, completed, failed, interrupted",
+                            s
+                        )
+                    })
+                })
+                .transpose()?;
+            let filter = generation::GenerationFilter {
+                project_id: project,
+                feature_id: feature,
+                session_id: session,
+                status,
+                limit: Some(limit),
+            };
+            let list = generation::list(db, &filter).await?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&list)?);
+            } else if list.is_empty() {
+                if !quiet {
+                    println!("No generations found.");
+                }
+            } else {
+                if !quiet {
+                    println!("Generations:");
+                    println!();
+                }
+                for g in list {
+                    println!(
+                        "  {} [{}] {} {} file(s), {} tokens, ${:.4}, {} - {}",
+                        &g.id[..8.min(g.id.len())],
+                        g.status.as_str(),
+                        g.created_at
+                            .with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M"),
+                        g.file_count,
+                        g.tokens_used,
+                        g.cost_usd,
+                        format_duration(chrono::Duration::milliseconds(g.duration_ms)),
+                        truncate_str(&g.description, 50)
+                    );
+                }
+            }
+            Ok(())
+        }
+        GenerationAction::Show { id } => {
+            let detail = generation::show(db, &id).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&detail)?);
+                return Ok(());
+            }
+            let g = &detail.generation;
+            println!("Generation {}", g.id);
+            println!("  Description: {}", g.description);
+            println!("  Status:      {}", g.status.as_str());
+            println!(
+                "  Started:     {}",
+                g.created_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+            );
+            println!(
+                "  Duration:    {}",
+                format_duration(chrono::Duration::milliseconds(g.duration_ms))
+            );
+            println!("  Tokens:      {} (${:.4})", g.tokens_used, g.cost_usd);
+            println!("  Output dir:  {}", g.output_dir);
+            if let Some(project_id) = &g.project_id {
+                println!("  Project:     {}", project_id);
+            }
+            if let Some(feature_id) = &g.feature_id {
+                println!("  Feature:     {}", feature_id);
+            }
+            if let Some(session_id) = &g.session_id {
+                println!("  Session:     {}", session_id);
+            }
+            if let Some(plan) = &g.plan {
+                println!("\nPlan:");
+                for task in &plan.tasks {
+                    println!("  {} [{}] {}", task.id, task.status, task.description);
+                }
+            }
+            println!("\nFiles ({}):", detail.artifacts.len());
+            for artifact in &detail.artifacts {
+                println!(
+                    "  {} {} [{}{}]",
+                    if artifact.is_new { "+" } else { "~" },
+                    artifact.file_path,
+                    artifact.decision.as_str(),
+                    if artifact.is_applied() {
+                        ", applied"
+                    } else {
+                        ""
+                    }
+                );
+            }
+            if !detail.findings.is_empty() {
+                println!("\nSecurity findings ({}):", detail.findings.len());
+                print_security_findings(&detail.findings);
+            }
+            Ok(())
+        }
+        GenerationAction::Delete { id, force } => {
+            if !force {
+                if quiet {
+                    anyhow::bail!("Pass --force to delete generation {} without asking", id);
+                }
+                print!("Delete generation {} and its stored files? [y/N] ", id);
+                io::stdout().flush()?;
+                let mut answer = String::new();
+                io::stdin().read_line(&mut answer)?;
+                if !answer.trim().eq_ignore_ascii_case("y") {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+            }
+            generation::delete(db, &id).await?;
+            if !quiet {
+                println!("{} Deleted generation {}", glyphs::check(), id);
+            }
+            Ok(())
+        }
+        GenerationAction::Regen {
+            id,
+            file,
+            instructions,
+        } => {
+            let record = generation::GenerationRepository::new(db)
+                .get(&id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Generation not found: {}", id))?;
+            let project = match &record.project_id {
+                Some(project_id) => project::get_with_db(db, project_id).await?,
+                None => None,
+            };
+            let framework = project.as_ref().map(|p| p.framework.clone());
+            let secret_names = generation_secret_names(db, project.as_ref()).await;
+            let persona = project_persona(db, project.as_ref()).await;
+            let progress = Progress::for_output(quiet, json);
+
+            if !quiet && !json {
+                println!("Regenerating {} in generation {}...", file, record.id);
+            }
+            let target = file.clone();
+            let regen = generation::regenerate(db, &id, &file, &instructions, |task| {
+                let progress = progress.clone();
+                async move {
+                    progress.stage(Stage::Plan, format!("Task {}: {}", task.id, target));
+                    generate::generate_with_persona(
+                        &task.description,
+                        framework.as_deref(),
+                        secret_names,
+                        persona,
+                        true,
+                        &progress,
+                    )
+                    .await
+                }
+            })
+            .await?;
+            progress.finish("");
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "generation_id": id,
+                        "file": regen.artifact.file_path,
+                        "task_id": regen.task_id,
+                        "version": regen.artifact.version,
+                        "previous_version": regen.previous_version,
+                        "tokens_used": regen.tokens_used,
+                        "cost_usd": regen.cost_usd,
+                    }))?
+                );
+                return Ok(());
+            }
+            if quiet {
+                return Ok(());
+            }
+
+            println!(
+                "{} {} v{} -> v{} (task {}, {} tokens, ${:.4})",
+                glyphs::check(),
+                regen.artifact.file_path,
+                regen.previous_version,
+                regen.artifact.version,
+                regen.task_id,
+                regen.tokens_used,
+                regen.cost_usd
+            );
+            let review = generation::review(db, &id).await?;
+            if let Some(diff) = review
+                .files
+                .iter()
+                .find(|f| f.path == regen.artifact.file_path)
+            {
+                if diff.diff.is_empty() {
+                    println!("  (no changes compared to the file on disk)");
+                } else {
+                    print!("{}", diff.diff);
+                }
+            }
+            match prompt_choice("Apply this version? [y]es / [n]o")? {
+                'y' => {
+                    generation::decide(
+                        db,
+                        &id,
+                        &regen.artifact.file_path,
+                        generation::ArtifactDecision::Accepted,
+                    )
+                    .await?;
+                    println!("Applied {}.", regen.artifact.file_path);
+                }
+                _ => println!(
+                    "Left pending. Apply later with: demiarch generations apply {} --file {}",
+                    id, regen.artifact.file_path
+                ),
+            }
+            Ok(())
+        }
+        GenerationAction::Review { id } => review_generation_interactive(db, &id, quiet).await,
+        GenerationAction::Recover { dir, undo } => {
+            let interrupted = staging::pending(&dir)?;
+            if interrupted.is_empty() {
+                if !quiet {
+                    println!("No interrupted writes in {}.", dir.display());
+                }
+                return Ok(());
+            }
+            let action = if undo {
+                staging::RecoveryAction::Undo
+            } else {
+                staging::RecoveryAction::Complete
+            };
+            for manifest in &interrupted {
+                staging::recover(&dir, &manifest.id, action)?;
+                if !quiet {
+                    println!(
+                        "{} {} {} ({} of {} file(s) had been written)",
+                        glyphs::check(),
+                        if undo { "Undid" } else { "Completed" },
+                        manifest.label,
+                        manifest.moved(),
+                        manifest.writes.len()
+                    );
+                }
+            }
+            Ok(())
+        }
+        GenerationAction::Apply { id, file } => {
+            let written = generation::apply(db, &id, file.as_deref()).await?;
+            if !quiet {
+                if written.is_empty() {
+                    println!("Nothing to apply; all files are already written.");
+                } else {
+                    println!("Applied {} file(s):", written.len());
+                    for path in &written {
+                        println!("  {}", path);
+                    }
+                }
+            }
+            Ok(())
+        }
+        GenerationAction::Env { id } => {
+            let record = generation::GenerationRepository::new(db)
+                .get(&id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Generation not found: {}", id))?;
+            let current = current_environment();
+            let changes = record
+                .environment
+                .as_ref()
+                .map(|recorded| recorded.changes_from(&current));
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "generation_id": record.id,
+                        "environment": record.environment,
+                        "changes": changes,
+                    }))?
+                );
+                return Ok(());
+            }
+            if quiet {
+                return Ok(());
+            }
+
+            let Some(env) = &record.environment else {
+                println!(
+                    "Generation {} has no environment snapshot (recorded before snapshots were kept).",
+                    record.id
+                );
+                return Ok(());
+            };
+            println!("Generation {}", record.id);
+            println!(
+                "  Captured: {}",
+                env.captured_at.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+            println!("  Demiarch: v{}", env.demiarch_version);
+            println!("  Models:   {}", env.models.join(", "));
+            println!("\nConfig:");
+            for (key, value) in &env.config {
+                println!("  {} = {}", key, value);
+            }
+            println!("\nPrompt templates:");
+            for (name, hash) in &env.prompt_hashes {
+                println!("  {} {}", name, &hash[..hash.len().min(12)]);
+            }
+            match &env.plugins {
+                Some(plugins) if !plugins.is_empty() => {
+                    println!("\nPlugins:");
+                    for (id, version) in plugins {
+                        println!("  {} {}", id, version);
+                    }
+                }
+                Some(_) => println!("\nPlugins: none"),
+                None => println!("\nPlugins: not recorded"),
+            }
+
+            match changes.as_deref() {
+                Some([]) | None => {
+                    println!("\n{} Current environment matches", glyphs::check())
+                }
+                Some(changes) => {
+                    println!("\nChanged since this generation:");
+                    print_environment_changes(changes);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn cmd_jobs(db: &Database, action: JobAction, quiet: bool) -> anyhow::Result<()> {
+    let repo = jobs::JobRepository::new(db);
+
+    match action {
+        JobAction::Enqueue {
+            kind,
+            target,
+            at,
+            max_attempts,
+            language,
+        } => {
+            let spec = match kind {
+                JobKind::Generate => {
+                    let output_dir = std::env::current_dir()?;
+                    let current_project = project::find_by_directory(db, &output_dir).await?;
+                    jobs::JobSpec::Generate {
+                        description: target,
+                        output_dir: output_dir.to_string_lossy().to_string(),
+                        project_id: current_project.as_ref().map(|p| p.id.clone()),
+                        framework: current_project.map(|p| p.framework),
+                    }
+                }
+                JobKind::Prd => jobs::JobSpec::Prd {
+                    project_id: target,
+                    language,
+                },
+                JobKind::Architecture => jobs::JobSpec::Architecture {
+                    project_id: target,
+                    language,
+                },
+                JobKind::Consolidate => jobs::JobSpec::ConsolidateContext { project_id: target },
+                JobKind::AgeKnowledge => jobs::JobSpec::AgeKnowledge {
+                    every_hours: parse_interval_hours(&target)?,
+                },
+            };
+            let run_at = jobs::parse_run_at(&at, chrono::Local::now())?;
+            let job = jobs::enqueue(
+                db,
+                jobs::Job::new(spec)
+                    .with_run_at(run_at)
+                    .with_max_attempts(max_attempts),
+            )
+            .await?;
+
+            if !quiet {
+                println!("Job queued: {}", job.id);
+                println!(
+                    "  Runs at: {}",
+                    job.run_at
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                );
+                println!();
+                println!("Jobs run while a worker is active: demiarch jobs run");
+            }
+        }
+
+        JobAction::List { status, limit } => {
+            let status = status
+                .map(|s| {
+                    jobs::JobStatus::parse(&s).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Invalid status: {}. Use: queued, running, succeeded, failed, cancelled",
+                            s
+                        )
+                    })
+                })
+                .transpose()?;
+            let list = repo.list(status, limit).await?;
+
+            if list.is_empty() {
+                if !quiet {
+                    println!("No jobs found.");
+                    println!("\nQueue one with: demiarch jobs enqueue generate \"...\" --at 02:00");
+                }
+            } else {
+                if !quiet {
+                    println!("Jobs:");
+                    println!();
+                }
+                for job in list {
+                    println!(
+                        "  {} [{}] {} {} - {}",
+                        &job.id[..8],
+                        job.status,
+                        job.run_at
+                            .with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M"),
+                        job.spec.kind(),
+                        job.spec.summary()
+                    );
+                }
+            }
+        }
+
+        JobAction::Show { id } => {
+            let job = find_job(&repo, &id).await?;
+
+            println!("Job: {}", job.id);
+            println!("  Kind: {}", job.spec.kind());
+            println!("  Task: {}", job.spec.summary());
+            println!("  Status: {}", job.status);
+            println!(
+                "  Runs at: {}",
+                job.run_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+            );
+            println!("  Attempts: {}/{}", job.attempts, job.max_attempts);
+            if let Some(error) = &job.last_error {
+                println!("  Last error: {}", error);
+            }
+            if let Some(result) = &job.result_ref {
+                println!("  Result: {}", result);
+            }
+            if let Some(finished) = job.finished_at {
+                println!(
+                    "  Finished: {}",
+                    finished
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                );
+            }
+        }
+
+        JobAction::Cancel { id } => {
+            let job = find_job(&repo, &id).await?;
+            jobs::cancel(db, &job.id).await?;
+            if !quiet {
+                println!("Job '{}' cancelled.", &job.id[..8]);
+            }
+        }
+
+        JobAction::Run { once, poll_secs } => {
+            let requeued = repo.requeue_interrupted().await?;
+            if !quiet {
+                if requeued > 0 {
+                    println!("Re-queued {} interrupted job(s).", requeued);
+                }
+                if !once {
+                    println!("Running jobs as they become due (Ctrl+C to stop)");
+                }
+            }
+
+            // Ring the bell when a job's generation finishes or trips the budget
+            let config = watch::current();
+            let mut notifier = Notifier::for_output(&config.notifications, quiet, false);
+            let mut feed = NotificationFeed::new(&config.cost, chrono::Utc::now());
+            let mut gates =
+                ApprovalGates::new(&config.approvals).with_approver(Arc::new(TerminalApprover));
+
+            // Apply config edits between jobs without restarting the runner;
+            // budget limits reach the jobs' cost tracker as soon as they load
+            let mut config_updates = watch::subscribe();
+            if !once {
+                watch::ConfigWatcher::new()?.spawn();
+            }
+
+            let ctrl_c = tokio::signal::ctrl_c();
+            tokio::pin!(ctrl_c);
+
+            loop {
+                let mut reloaded = None;
+                while let Ok(update) = config_updates.try_recv() {
+                    reloaded = Some(update.config.clone());
+                }
+                if let Some(config) = reloaded {
+                    if !quiet {
+                        println!("Configuration reloaded.");
+                    }
+                    notifier = Notifier::for_output(&config.notifications, quiet, false);
+                    feed.set_cost(&config.cost);
+                    gates = ApprovalGates::new(&config.approvals)
+                        .with_approver(Arc::new(TerminalApprover));
+                }
+
+                let next = tokio::select! {
+                    next = jobs::process_next(db, |job| jobs::execute_with_approvals(db, job, &gates)) => next?,
+                    _ = &mut ctrl_c => break,
+                };
+
+                match next {
+                    Some(job) => {
+                        if !quiet {
+                            print_job_outcome(&job);
+                        }
+                        if notifier.is_enabled() {
+                            notifier.notify_all(&feed.poll(db, chrono::Utc::now()).await?);
+                        }
+                    }
+                    None if once => break,
+                    None => {
+                        tokio::select! {
+                            _ = tokio::time::sleep(std::time::Duration::from_secs(poll_secs)) => {}
+                            _ = &mut ctrl_c => break,
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Find a job by full ID or unique ID prefix
+async fn find_job(repo: &jobs::JobRepository<'_>, id: &str) -> anyhow::Result<jobs::Job> {
+    if let Some(job) = repo.get(id).await? {
+        return Ok(job);
+    }
+    let matches: Vec<_> = repo
+        .list(None, Some(i64::MAX))
+        .await?
+        .into_iter()
+        .filter(|j| j.id.starts_with(id))
+        .collect();
+    match matches.len() {
+        1 => Ok(matches.into_iter().next().unwrap()),
+        0 => anyhow::bail!("Job '{}' not found", id),
+        _ => anyhow::bail!("Job ID '{}' is ambiguous", id),
+    }
+}
+
+/// Print the result of a job the worker just ran
+fn print_job_outcome(job: &jobs::Job) {
+    let id = &job.id[..8];
+    match job.status {
+        jobs::JobStatus::Succeeded => println!(
+            "{} Job {} ({}) succeeded: {}",
+            glyphs::check(),
+            id,
+            job.spec.kind(),
+            job.result_ref.as_deref().unwrap_or("-")
+        ),
+        jobs::JobStatus::Queued => println!(
+            "{} Job {} ({}) failed, retrying at {}: {}",
+            glyphs::cross(),
+            id,
+            job.spec.kind(),
+            job.run_at.with_timezone(&chrono::Local).format("%H:%M"),
+            job.last_error.as_deref().unwrap_or("unknown error")
+        ),
+        _ => println!(
+            "{} Job {} ({}) {}: {}",
+            glyphs::cross(),
+            id,
+            job.spec.kind(),
+            job.status,
+            job.last_error.as_deref().unwrap_or("unknown error")
+        ),
+    }
+}
+
+async fn cmd_artifacts_blame(
+    db: &Database,
+    file: &std::path::Path,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let blame = blame::blame(db, file).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&blame)?);
+        return Ok(());
+    }
+    if quiet {
+        return Ok(());
+    }
+
+    println!(
+        "{} ({} of {} lines generated)",
+        blame.path.display(),
+        blame.generated_lines,
+        blame.lines
+    );
+    println!();
+    let mut prompts: Vec<(&str, &str)> = Vec::new();
+    for range in &blame.ranges {
+        let lines = if range.start == range.end {
+            range.start.to_string()
+        } else {
+            format!("{}-{}", range.start, range.end)
+        };
+        let mut origin = match &range.source {
+            Some(source) => {
+                let mut parts = Vec::new();
+                if let Some(title) = &source.feature_title {
+                    parts.push(format!("feature \"{}\"", title));
+                }
+                if let Some(agent) = &source.agent {
+                    parts.push(agent.clone());
+                }
+                if !source.models.is_empty() {
+                    parts.push(source.models.join(", "));
+                }
+                parts.push(format!(
+                    "gen {} v{}",
+                    &source.generation_id[..8],
+                    source.version
+                ));
+                if !prompts.iter().any(|(id, _)| *id == source.generation_id) {
+                    prompts.push((source.generation_id.as_str(), source.prompt.as_str()));
+                }
+                parts.join(" · ")
+            }
+            None => "by hand".to_string(),
+        };
+        if let Some(commit) = &range.commit {
+            origin.push_str(&format!(" · commit {} ({})", commit.sha, commit.author));
+        }
+        println!("  {:>9}  {}", lines, origin);
+    }
+
+    if !prompts.is_empty() {
+        println!();
+        println!("Prompts:");
+        for (id, prompt) in prompts {
+            let prompt = prompt.lines().next().unwrap_or_default();
+            println!("  gen {}: {}", &id[..8], truncate_str(prompt, 100));
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_changelog(
+    db: &Database,
+    since: &str,
+    project: Option<&str>,
+    version: &str,
+    write: bool,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let project = resolve_project(db, project).await?;
+    let since = changelog::Since::parse(since);
+    let log = changelog::build(db, &project, &since, version, chrono::Utc::now()).await?;
+
+    if write {
+        let dir = project.path.as_deref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Project '{}' has no directory to write CHANGELOG.md to",
+                project.name
+            )
+        })?;
+        let path = changelog::write_to_project(std::path::Path::new(dir), &log)?;
+        if !quiet && !json {
+            println!(
+                "{} Added {} entries to {}",
+                glyphs::check(),
+                log.entries.len(),
+                path.display()
+            );
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&log)?);
+    } else if !write {
+        print!("{}", log.to_markdown());
+    }
+    Ok(())
+}
+
+/// Write, store and optionally post an activity digest
+async fn cmd_digest(
+    db: &Database,
+    period: digest::DigestPeriod,
+    project: Option<&str>,
+    model: Option<&str>,
+    post: bool,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let project = resolve_project(db, project).await?;
+    let digest = digest::generate_digest(db, &project.id, period, model).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&digest)?);
+    } else {
+        print!("{}", digest.markdown());
+    }
+
+    if post {
+        match digest::post(&digest).await {
+            Some(outcome) => {
+                for annotation in &outcome.annotations {
+                    info!(subscriber = %annotation.subscriber, "{}", annotation.text);
+                }
+            }
+            None => eprintln!(
+                "{}",
+                glyphs::warning(
+                    "Nothing was posted: no webhook subscribes to digest_created. Add one under [[events.webhooks]] in config.toml."
+                )
+            ),
+        }
+    }
+    if !quiet && !json {
+        eprintln!(
+            "{} Saved as document {}",
+            glyphs::check(),
+            &digest.document.id[..8.min(digest.document.id.len())]
+        );
+    }
+    Ok(())
+}
+
+/// Inventory an upgrade, plan its steps as features and optionally generate
+/// the mechanical ones
+async fn cmd_upgrade_assist(
+    db: &Database,
+    target: &str,
+    dry_run: bool,
+    generate: Option<&queue::QueueOptions>,
+    quiet: bool,
+    json: bool,
+    progress: &Progress,
+) -> anyhow::Result<()> {
+    let target = upgrade_assist::UpgradeTarget::parse(target)?;
+    let project = resolve_project(db, None).await?;
+    let root = match project.path.as_deref() {
+        Some(path) => std::path::PathBuf::from(path),
+        None => std::env::current_dir()?,
+    };
+    let mut report = upgrade_assist::inventory(&root, &target)?;
+    if !dry_run {
+        upgrade_assist::plan_features(db, &project.id, &mut report).await?;
+    }
+
+    // With --generate, JSON output is the queue report
+    if json && generate.is_none() {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if !quiet && !json {
+        println!(
+            "Upgrade to {} ({} {}, {} files scanned)",
+            report.target,
+            report.package,
+            report
+                .current_version
+                .as_deref()
+                .unwrap_or("not in package.json"),
+            report.files_scanned
+        );
+        if report.is_current() {
+            println!("  package.json already requires {}", report.target);
+        }
+        println!();
+        if report.steps.is_empty() {
+            println!("No affected code found.");
+        }
+        for step in &report.steps {
+            let status = if step.done {
+                "done"
+            } else {
+                step.kind.as_str()
+            };
+            println!("[{}] {}", status, step.title);
+            println!(
+                "  {} location(s) in {} file(s)",
+                step.occurrences.len(),
+                step.files().len()
+            );
+            if let Some(ref id) = step.feature_id {
+                println!("  Feature: {}", id);
+            }
+        }
+
+        let manual: Vec<_> = report.manual_steps().collect();
+        if !manual.is_empty() {
+            println!();
+            println!("Manual steps remaining:");
+            for step in manual {
+                println!("  {}", step.title);
+                println!("    {}", step.instructions);
+                for occurrence in step.occurrences.iter().take(5) {
+                    println!("    {}:{}", occurrence.path, occurrence.line);
+                }
+                if step.occurrences.len() > 5 {
+                    println!("    ... and {} more", step.occurrences.len() - 5);
+                }
+            }
+        }
+    }
+
+    let Some(options) = generate else {
+        if !quiet && !json && !dry_run && report.mechanical_steps().next().is_some() {
+            println!();
+            println!("Run again with --generate to generate the mechanical steps.");
+        }
+        return Ok(());
+    };
+    let features: Vec<String> = report
+        .mechanical_steps()
+        .filter_map(|s| s.feature_id.clone())
+        .collect();
+    if features.is_empty() {
+        if !quiet && !json {
+            println!();
+            println!("No mechanical steps left to generate.");
+        }
+        return Ok(());
+    }
+    if !quiet && !json {
+        println!();
+    }
+    cmd_generate_queue(db, &features, options, quiet, json, progress).await
+}
+
+async fn cmd_documents(
+    db: &Database,
+    action: DocumentAction,
+    quiet: bool,
+    progress: &Progress,
+) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let cost_tracker = Arc::new(CostTracker::from_config(&config.cost));
+
+    match action {
+        DocumentAction::GeneratePrd { project, language } => {
+            if !quiet {
+                println!("Generating PRD for project '{}'...", project);
+                println!();
+            }
+
+            let doc = document::generate_prd(
+                db,
+                &project,
+                Some(cost_tracker.clone()),
+                language.as_deref(),
+                progress,
+            )
+            .await?;
+            progress.finish("");
+
+            if !quiet {
+                println!("PRD generated successfully!");
+                println!();
+                println!("  Document ID: {}", doc.id);
+                println!("  Title: {}", doc.title);
+                println!("  Status: {:?}", doc.status);
+                if let Some(model) = &doc.model_used {
+                    println!("  Model: {}", model);
+                }
+                if let Some(tokens) = doc.tokens_used {
+                    println!("  Tokens used: {}", tokens);
+                }
+                if let Some(cost) = doc.generation_cost_usd {
+                    println!("  Generation cost: ${:.4}", cost);
+                }
+                println!();
+                println!("View with: demiarch documents show {}", doc.id);
+                println!(
+                    "Export with: demiarch documents export {} --output prd.md",
+                    doc.id
+                );
+            }
+        }
+
+        DocumentAction::GenerateArchitecture { project, language } => {
+            if !quiet {
+                println!(
+                    "Generating architecture document for project '{}'...",
+                    project
+                );
+                println!();
+            }
+
+            let doc = document::generate_architecture(
+                db,
+                &project,
+                Some(cost_tracker.clone()),
+                language.as_deref(),
+                progress,
+            )
+            .await?;
+            progress.finish("");
+
+            if !quiet {
+                println!("Architecture document generated successfully!");
+                println!();
+                println!("  Document ID: {}", doc.id);
+                println!("  Title: {}", doc.title);
+                println!("  Status: {:?}", doc.status);
+                if let Some(model) = &doc.model_used {
+                    println!("  Model: {}", model);
+                }
+                if let Some(tokens) = doc.tokens_used {
+                    println!("  Tokens used: {}", tokens);
+                }
+                if let Some(cost) = doc.generation_cost_usd {
+                    println!("  Generation cost: ${:.4}", cost);
+                }
+                println!();
+                println!("View with: demiarch documents show {}", doc.id);
+                println!(
+                    "Export with: demiarch documents export {} --output architecture.md",
+                    doc.id
+                );
+            }
+        }
+
+        DocumentAction::GenerateRoadmap { project, language } => {
+            let project = resolve_project(db, project.as_deref()).await?;
+            let doc = roadmap::generate_roadmap(
+                db,
+                &project.id,
+                chrono::Utc::now().date_naive(),
+                language.as_deref(),
+            )
+            .await?;
+
+            if !quiet {
+                println!("Roadmap generated (version {})", doc.version);
+                println!();
+                println!("  Document ID: {}", doc.id);
+                println!("  Title: {}", doc.title);
+                println!();
+                println!("View with: demiarch documents show {}", doc.id);
+                println!(
+                    "Export with: demiarch documents export {} --output roadmap.md",
+                    doc.id
+                );
+            }
+        }
+
+        DocumentAction::List { project, doc_type } => {
+            let dt = doc_type
+                .as_ref()
+                .and_then(|s| document::DocumentType::parse(s));
+
+            let docs = document::list_documents(db, &project, dt).await?;
+
+            if docs.is_empty() {
+                if !quiet {
+                    println!("No documents found for project '{}'.", project);
+                    println!();
+                    println!("Generate one with:");
+                    println!("  demiarch documents generate-prd --project {}", project);
+                    println!(
+                        "  demiarch documents generate-architecture --project {}",
+                        project
+                    );
+                }
+            } else {
+                if !quiet {
+                    println!("Documents for project '{}':", project);
+                    println!();
+                }
+                for doc in docs {
+                    println!(
+                        "  {} - {} ({:?}) [{}]",
+                        &doc.id[..8],
+                        doc.title,
+                        doc.doc_type,
+                        doc.status.as_str()
+                    );
+                }
+            }
+        }
+
+        DocumentAction::Show { id } => {
+            let doc = document::get_document(db, &id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Document '{}' not found", id))?;
+
+            println!("Document: {}", doc.title);
+            println!("=========={}", "=".repeat(doc.title.len()));
+            println!();
+            println!("ID: {}", doc.id);
+            println!("Type: {}", doc.doc_type.display_name());
+            println!("Status: {:?}", doc.status);
+            println!("Version: {}", doc.version);
+            if let Some(language) = &doc.language {
+                println!("Language: {}", document::language_name(language));
+            }
+            if let Some(model) = &doc.model_used {
+                println!("Model: {}", model);
+            }
+            if let Some(tokens) = doc.tokens_used {
+                println!("Tokens: {}", tokens);
+            }
+            if let Some(cost) = doc.generation_cost_usd {
+                println!("Cost: ${:.4}", cost);
+            }
+            println!("Created: {}", doc.created_at.format("%Y-%m-%d %H:%M:%S"));
+            println!("Updated: {}", doc.updated_at.format("%Y-%m-%d %H:%M:%S"));
+            println!();
+            println!("Content:");
+            println!("--------");
+            println!("{}", doc.content);
+        }
+
+        DocumentAction::UpdateStatus { id, status } => {
+            let new_status = document::DocumentStatus::parse(&status).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Invalid status '{}'. Use: draft, review, final, archived",
+                    status
+                )
+            })?;
+
+            document::update_document_status(db, &id, new_status).await?;
+
+            if !quiet {
+                println!("Document '{}' status updated to '{}'.", id, status);
+            }
+        }
+
+        DocumentAction::Export { id, output } => {
+            let path = std::path::PathBuf::from(&output);
+
+            document::export_document(db, &id, &path).await?;
+
+            if !quiet {
+                println!("Document '{}' exported to '{}'.", id, output);
+            }
+        }
+
+        DocumentAction::Edit { id, summary } => {
+            let doc = document::get_document(db, &id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Document '{}' not found", id))?;
+            let extension = if doc.format == "json" { "json" } else { "md" };
+            let content = editor::edit_text(&doc.content, extension)?;
+
+            if content == doc.content {
+                if !quiet {
+                    println!("Document '{}' unchanged.", id);
+                }
+            } else {
+                let doc = document::revise_document(db, &id, content, summary).await?;
+                if !quiet {
+                    println!("Document '{}' saved as version {}.", id, doc.version);
+                }
+            }
+        }
+
+        DocumentAction::Delete { id } => {
+            document::delete_document(db, &id).await?;
+
+            if !quiet {
+                println!("Document '{}' deleted.", id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn cmd_skills(db: &Database, action: SkillAction, quiet: bool) -> anyhow::Result<()> {
+    use demiarch_core::skills::{LearnedSkill, SkillCategory, SkillPattern, SkillsManager};
+
+    let manager = SkillsManager::with_database(db.clone());
+
+    match action {
+        SkillAction::List { category } => {
+            let cat = category.as_deref().map(SkillCategory::parse);
+            let skills = manager.list(cat, None).await?;
+            if skills.is_empty() {
+                if !quiet {
+                    println!("No skills learned yet.");
+                }
+                return Ok(());
+            }
+
+            if !quiet {
+                println!("Skills ({}):", skills.len());
+            }
+            for skill in skills {
+                println!(
+                    "- {} [{}] (used {}x)",
+                    skill.name,
+                    skill.category.as_str(),
+                    skill.usage_stats.times_used
+                );
+            }
+        }
+        SkillAction::Search { query } => {
+            let skills = manager.search(&query).await?;
+            if skills.is_empty() {
+                if !quiet {
+                    println!("No matching skills for query '{}'.", query);
+                }
+            } else {
+                if !quiet {
+                    println!("Found {} matching skills:", skills.len());
+                }
+                for skill in skills {
+                    println!("- {} [{}]", skill.name, skill.category.as_str());
+                }
+            }
+        }
+        SkillAction::Extract { description } => {
+            let desc = description.unwrap_or_else(|| "Manually added skill".to_string());
+            let mut skill = LearnedSkill::new(
+                desc.clone(),
                 desc.clone(),
                 SkillCategory::Other,
                 SkillPattern::technique(desc.clone()),
             )
-            .with_tags(vec!["manual".into()]);
-            manager.save(&skill).await?;
-            // Record immediate usage to populate stats slightly
-            skill.record_usage(true);
-            manager.save(&skill).await?;
+            .with_tags(vec!["manual".into()]);
+            manager.save(&skill).await?;
+            // Record immediate usage to populate stats slightly
+            skill.record_usage(true);
+            manager.save(&skill).await?;
+            if !quiet {
+                println!("Saved manual skill '{}'", skill.name);
+            }
+        }
+        SkillAction::Delete { id } => {
+            let removed = manager.delete(&id).await?;
+            if !quiet {
+                if removed {
+                    println!("Deleted skill {}", id);
+                } else {
+                    println!("Skill {} not found", id);
+                }
+            }
+        }
+        SkillAction::Stats => {
+            let stats = manager.stats().await?;
+            if !quiet {
+                println!("Skill Statistics:");
+                println!("  Total skills: {}", stats.total_skills);
+                println!("  Total uses: {}", stats.total_uses);
+                println!("  High-confidence: {}", stats.high_confidence_skills);
+                println!("  By category:");
+                for (cat, count) in stats.skills_by_category {
+                    println!("    - {}: {}", cat.as_str(), count);
+                }
+            }
+        }
+        SkillAction::Show { id } => match manager.get(&id).await? {
+            Some(skill) => {
+                manager.record_usage(&id, true).await.ok();
+                let skill = manager.get(&id).await?.unwrap_or(skill);
+                if quiet {
+                    println!("{}", serde_json::to_string_pretty(&skill)?);
+                } else {
+                    println!("{}", skill.name);
+                    println!("  ID: {}", skill.id);
+                    println!("  Category: {}", skill.category.as_str());
+                    println!("  Description: {}", skill.description);
+                    println!("  Tags: {}", skill.tags.join(", "));
+                    println!("  Confidence: {}", skill.confidence.as_str());
+                    println!("  Pattern: {}", skill.pattern.pattern_type.as_str());
+                    println!("  Template:\n{}", indent(&skill.pattern.template, 4));
+                    if !skill.pattern.variables.is_empty() {
+                        println!("  Variables:");
+                        for v in &skill.pattern.variables {
+                            println!("    - {}: {}", v.name, v.description);
+                        }
+                    }
+                    if !skill.pattern.applicability.is_empty() {
+                        println!(
+                            "  Applicability: {}",
+                            skill.pattern.applicability.join("; ")
+                        );
+                    }
+                    if !skill.pattern.limitations.is_empty() {
+                        println!("  Limitations: {}", skill.pattern.limitations.join("; "));
+                    }
+                    println!(
+                        "  Usage: {} used, {} successes, {} failures",
+                        skill.usage_stats.times_used,
+                        skill.usage_stats.success_count,
+                        skill.usage_stats.failure_count
+                    );
+                    if let Some(last) = skill.usage_stats.last_used_at {
+                        println!("  Last used: {}", last.to_rfc3339());
+                    }
+                }
+            }
+            None => println!("Skill not found: {}", id),
+        },
+    }
+    Ok(())
+}
+
+async fn cmd_eval(
+    db: &Database,
+    action: EvalAction,
+    quiet: bool,
+    json: bool,
+    progress: &Progress,
+) -> anyhow::Result<()> {
+    match action {
+        EvalAction::Run {
+            task,
+            models,
+            repeat,
+            output,
+            replay,
+        } => {
+            let config = Config::load()?;
+            let task = eval::EvalTask::load(&task)?;
+            let cache = replay.then(|| {
+                ResponseCache::new(db.clone())
+                    .with_ttl_secs(config.cache.ttl_secs)
+                    .with_max_bytes(config.cache.max_size_mb * 1024 * 1024)
+            });
+            let options = eval::EvalOptions {
+                models: eval::parse_models(&models),
+                repeat,
+                output_dir: output.unwrap_or_else(|| eval::default_output_dir(&task)),
+                cache,
+            };
+
+            let report = eval::run(&config, &task, options, progress).await;
+            progress.finish("");
+            let report = report?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else if !quiet {
+                println!("{}", report.to_markdown());
+                println!(
+                    "Report saved to {}",
+                    report.output_dir.join("report.md").display()
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn cmd_cache(db: &Database, action: CacheAction, quiet: bool) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let cache = ResponseCache::new(db.clone())
+        .with_ttl_secs(config.cache.ttl_secs)
+        .with_max_bytes(config.cache.max_size_mb * 1024 * 1024);
+
+    match action {
+        CacheAction::Stats => {
+            let stats = cache.stats().await?;
+            if !quiet {
+                println!("LLM Response Cache:");
+                println!(
+                    "  Enabled: {}",
+                    if config.cache.enabled { "yes" } else { "no" }
+                );
+                println!("  Entries: {} ({} expired)", stats.entries, stats.expired);
+                println!(
+                    "  Size: {:.2} MB / {} MB",
+                    stats.total_bytes as f64 / (1024.0 * 1024.0),
+                    config.cache.max_size_mb
+                );
+                println!("  TTL: {}s", config.cache.ttl_secs);
+                println!("  Hits: {}", stats.hits);
+                println!("  Tokens saved: {}", stats.tokens_saved);
+                if let (Some(oldest), Some(newest)) = (stats.oldest, stats.newest) {
+                    println!(
+                        "  Cached between: {} and {}",
+                        oldest.format("%Y-%m-%d %H:%M"),
+                        newest.format("%Y-%m-%d %H:%M")
+                    );
+                }
+                if !config.cache.enabled {
+                    println!();
+                    println!("Enable with: demiarch config set cache.enabled true");
+                }
+            }
+        }
+        CacheAction::Clear { expired } => {
+            let removed = if expired {
+                cache.prune().await
+            } else {
+                cache.clear().await
+            }?;
+            if !quiet {
+                println!("Removed {} cached response(s)", removed);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_routing(
+    db: &Database,
+    action: RoutingAction,
+    quiet: bool,
+    json: bool,
+    progress: &Progress,
+) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let store = RoutingStore::new(db.pool().clone());
+
+    match action {
+        RoutingAction::Status => {
+            if !quiet {
+                println!("Routing Status:");
+                println!("  Preference: {}", config.routing.preference);
+                println!("  Default model: {}", config.llm.default_model);
+                println!(
+                    "  Fallback models: {}",
+                    config.llm.fallback_models.join(", ")
+                );
+            }
+        }
+        RoutingAction::SetPreference { preference } => {
+            let mut config = config;
+            config.set("routing.preference", &preference)?;
+            config.save()?;
+            if !quiet {
+                println!("Routing preference set to: {}", preference);
+            }
+        }
+        RoutingAction::Performance { task } => {
+            store.init().await?;
+            let mut keys: Vec<_> = store
+                .load_all_stats()
+                .await?
+                .into_iter()
+                .filter(|(key, _)| task.as_deref().is_none_or(|t| key.starts_with(t)))
+                .collect();
+            keys.sort_by(|a, b| a.0.cmp(&b.0));
+
+            if !quiet {
+                println!("Model Performance:");
+                if let Some(t) = &task {
+                    println!("  (filtered by task: {})", t);
+                }
+                if keys.is_empty() {
+                    println!("  (No performance data yet)");
+                    println!("  Seed it with: demiarch routing benchmark");
+                }
+                for (key, models) in keys {
+                    println!();
+                    println!("  {}", key);
+                    let mut models: Vec<_> = models.into_values().collect();
+                    models.sort_by(|a, b| b.expected_value().total_cmp(&a.expected_value()));
+                    for stats in models {
+                        println!(
+                            "    {:<40} {:>4} uses  {:>4.0}% ok  quality {:.2}  ${:.4}/call",
+                            stats.model_id,
+                            stats.total_uses,
+                            stats.success_rate() * 100.0,
+                            stats.expected_value(),
+                            stats.avg_cost_usd
+                        );
+                    }
+                }
+            }
+        }
+        RoutingAction::History { limit } => {
+            if !quiet {
+                let limit = limit.unwrap_or(10);
+                println!("Routing History (last {}):", limit);
+                println!("  (No routing history yet)");
+            }
+        }
+        RoutingAction::Benchmark {
+            suite,
+            models,
+            no_seed,
+        } => {
+            let suite = benchmark::BenchmarkSuite::resolve(&suite)?;
+            let models = match models {
+                Some(models) => eval::parse_models(&models),
+                None => std::iter::once(config.llm.default_model.clone())
+                    .chain(config.llm.fallback_models.iter().cloned())
+                    .fold(Vec::new(), |mut models, model| {
+                        if !models.contains(&model) {
+                            models.push(model);
+                        }
+                        models
+                    }),
+            };
+            let api_key = config
+                .llm
+                .resolved_api_key()
+                .map_err(|e| anyhow::anyhow!("Config error: {}", e))?
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "API key not configured. Set DEMIARCH_API_KEY or OPENROUTER_API_KEY environment variable."
+                    )
+                })?;
+            let client = LlmClient::builder()
+                .config(config.llm.clone())
+                .api_key(api_key)
+                .cost_tracker(Arc::new(CostTracker::from_config(&config.cost)))
+                .build()?;
+
+            let report = benchmark::run(&client, &suite, &models, progress).await;
+            progress.finish("");
+            let report = report?;
+            let seeded = if no_seed {
+                0
+            } else {
+                benchmark::seed(&store, &report).await?
+            };
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else if !quiet {
+                println!(
+                    "Benchmark '{}' ({} tasks, {} models):",
+                    report.suite,
+                    suite.tasks.len(),
+                    report.models.len()
+                );
+                for result in &report.results {
+                    let score = result
+                        .score
+                        .map(|s| format!("{:.2}", s))
+                        .unwrap_or_else(|| "skip".to_string());
+                    println!(
+                        "  {} {:<40} {:<16} {:>5}  {}",
+                        if result.passed {
+                            glyphs::check()
+                        } else {
+                            glyphs::cross()
+                        },
+                        result.model,
+                        result.task,
+                        score,
+                        result.error.as_deref().unwrap_or_default()
+                    );
+                }
+                println!();
+                for summary in report.summaries() {
+                    println!(
+                        "  {:<40} {}/{} passed  avg score {}  ${:.4}{}",
+                        summary.model,
+                        summary.passed,
+                        summary.scored,
+                        summary
+                            .avg_score
+                            .map(|s| format!("{:.2}", s))
+                            .unwrap_or_else(|| "-".to_string()),
+                        summary.cost_usd,
+                        if summary.skipped > 0 {
+                            format!("  ({} skipped)", summary.skipped)
+                        } else {
+                            String::new()
+                        }
+                    );
+                }
+                println!();
+                if no_seed {
+                    println!("Scores not recorded (--no-seed).");
+                } else {
+                    println!("Seeded routing statistics with {} result(s).", seeded);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_context(db: &Database, action: ContextAction, quiet: bool) -> anyhow::Result<()> {
+    use demiarch_core::domain::memory::PersistentMemoryStore;
+
+    let project_repo = project::ProjectRepository::new(db);
+    let active_project = project_repo
+        .list(None)
+        .await?
+        .into_iter()
+        .find(|p| p.path.is_some())
+        .ok_or_else(|| {
+            anyhow::anyhow!("No projects found. Create one with: demiarch new <name>")
+        })?;
+
+    let store = PersistentMemoryStore::new(db.pool().clone());
+    let manager = ContextManager::new().with_persistent_store(store);
+
+    match action {
+        ContextAction::Stats { project } => {
+            let project_id = project.unwrap_or(active_project.id.clone());
+            let stats = if let Some(p) = manager.persistent() {
+                p.stats(Some(&project_id)).await?
+            } else {
+                Default::default()
+            };
+
+            if !quiet {
+                println!("Context Statistics (project: {}):", project_id);
+                println!("  Total entries: {}", stats.stats.total_records);
+                println!("  Total tokens: {}", stats.total_tokens);
+                if let Some(oldest) = stats.stats.oldest_at {
+                    println!("  Oldest: {}", oldest);
+                }
+                if let Some(newest) = stats.stats.newest_at {
+                    println!("  Newest: {}", newest);
+                }
+            }
+        }
+        ContextAction::Search { query, level: _ } => {
+            let results = if let Some(p) = manager.persistent() {
+                p.recall(
+                    Some(&active_project.id),
+                    RecallQuery {
+                        query: query.clone(),
+                        ..Default::default()
+                    },
+                )
+                .await?
+            } else {
+                Vec::new()
+            };
+
+            if results.is_empty() {
+                if !quiet {
+                    println!("No matching context found for '{}'.", query);
+                }
+            } else {
+                if !quiet {
+                    println!("Context matches ({}):", results.len());
+                }
+                for rec in results {
+                    println!("- {} | {}", rec.created_at.to_rfc3339(), rec.index_summary);
+                }
+            }
+        }
+        ContextAction::Prune {
+            older_than,
+            dry_run,
+        } => {
+            let days = older_than.unwrap_or(30);
+            let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+            let removed = if let Some(p) = manager.persistent() {
+                p.prune(Some(&active_project.id), cutoff, dry_run).await?
+            } else {
+                0usize
+            };
+            if !quiet {
+                if dry_run {
+                    println!(
+                        "Dry run: would remove {} entries older than {} days",
+                        removed, days
+                    );
+                } else {
+                    println!(
+                        "Removed {} context entries older than {} days",
+                        removed, days
+                    );
+                }
+            }
+        }
+        ContextAction::Rebuild {
+            project,
+            no_consolidate,
+            threshold,
+        } => {
+            let project_id = project.unwrap_or(active_project.id.clone());
+            let updated = if let Some(p) = manager.persistent() {
+                p.rebuild(Some(&project_id)).await?
+            } else {
+                0usize
+            };
+            if !quiet {
+                println!(
+                    "Rebuilt {} context entries for project {}",
+                    updated, project_id
+                );
+            }
+            if !no_consolidate {
+                if let Some(p) = manager.persistent() {
+                    let report = p.consolidate(Some(&project_id), threshold, false).await?;
+                    if !quiet {
+                        print_consolidation_report(&report);
+                    }
+                }
+            }
+        }
+        ContextAction::Consolidate {
+            project,
+            threshold,
+            dry_run,
+        } => {
+            if !(0.0..=1.0).contains(&threshold) {
+                anyhow::bail!("Threshold must be between 0 and 1, got {}", threshold);
+            }
+            let project_id = project.unwrap_or(active_project.id.clone());
+            if let Some(p) = manager.persistent() {
+                let report = p.consolidate(Some(&project_id), threshold, dry_run).await?;
+                if !quiet {
+                    print_consolidation_report(&report);
+                }
+            }
+        }
+        ContextAction::Pin { text, project } => {
+            let project_id = project.unwrap_or(active_project.id.clone());
+            let record = manager.pin(&project_id, &text).await?;
+            if quiet {
+                println!("{}", record.id);
+            } else {
+                println!("Pinned {} ({} tokens)", record.id, record.token_estimate());
+                let used = match manager.persistent() {
+                    Some(p) => p.pinned_tokens(&project_id).await?,
+                    None => record.token_estimate(),
+                };
+                println!("  Pin budget: {}/{} tokens", used, manager.pin_budget());
+            }
+        }
+        ContextAction::Unpin { id } => {
+            let uuid = uuid::Uuid::parse_str(&id)
+                .map_err(|e| anyhow::anyhow!("Invalid context ID '{}': {}", id, e))?;
+            if !manager.unpin(uuid).await? {
+                anyhow::bail!("Context entry not found: {}", id);
+            }
+            if !quiet {
+                println!("Unpinned {}", id);
+            }
+        }
+        ContextAction::Pins { project } => {
+            let project_id = project.unwrap_or(active_project.id.clone());
+            let pinned = manager.pinned(Some(&project_id)).await?;
+            if pinned.is_empty() {
+                if !quiet {
+                    println!("No pinned context for project {}.", project_id);
+                }
+            } else {
+                if !quiet {
+                    let used: usize = pinned.iter().map(|r| r.token_estimate()).sum();
+                    println!(
+                        "Pinned context ({}, {}/{} tokens):",
+                        pinned.len(),
+                        used,
+                        manager.pin_budget()
+                    );
+                }
+                for rec in pinned {
+                    println!("- {} | {}", rec.id, rec.full_context);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_consolidation_report(report: &ConsolidationReport) {
+    if report.merged.is_empty() {
+        println!("No near-duplicate context entries found");
+        return;
+    }
+    let verb = if report.dry_run {
+        "Would merge"
+    } else {
+        "Merged"
+    };
+    println!(
+        "{} {} entries into {} ({} -> {} entries)",
+        verb,
+        report.merged.iter().map(|m| m.sources.len()).sum::<usize>(),
+        report.merged.len(),
+        report.records_before,
+        report.records_after
+    );
+    println!(
+        "  Tokens: {} -> {} ({} saved)",
+        report.tokens_before,
+        report.tokens_after,
+        report.tokens_saved()
+    );
+    for merged in &report.merged {
+        let sources: Vec<String> = merged
+            .sources
+            .iter()
+            .map(|id| id.to_string()[..8].to_string())
+            .collect();
+        println!(
+            "  {} <- {} ({} tokens)",
+            &merged.id.to_string()[..8],
+            sources.join(", "),
+            merged.tokens
+        );
+    }
+}
+
+async fn cmd_hooks(action: HookAction, quiet: bool) -> anyhow::Result<()> {
+    match action {
+        HookAction::List { r#type } => {
+            if !quiet {
+                println!("Registered Hooks:");
+                if let Some(t) = r#type {
+                    println!("  (filtered by type: {})", t);
+                }
+                println!("  (No hooks registered)");
+            }
+        }
+        HookAction::Register {
+            hook_type,
+            name,
+            handler,
+        } => {
+            if !quiet {
+                println!("Registering hook:");
+                println!("  Type: {}", hook_type);
+                println!("  Name: {}", name);
+                println!("  Handler: {}", handler);
+                println!("  (Hook registration not yet implemented)");
+            }
+        }
+        HookAction::Enable { id } => {
+            if !quiet {
+                println!("Enabling hook: {}", id);
+                println!("  (Hook not found)");
+            }
+        }
+        HookAction::Disable { id } => {
+            if !quiet {
+                println!("Disabling hook: {}", id);
+                println!("  (Hook not found)");
+            }
+        }
+        HookAction::Remove { id } => {
+            if !quiet {
+                println!("Removing hook: {}", id);
+                println!("  (Hook not found)");
+            }
+        }
+        HookAction::History { limit } => {
+            if !quiet {
+                let limit = limit.unwrap_or(10);
+                println!("Hook Execution History (last {}):", limit);
+                println!("  (No hook executions yet)");
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_stats(
+    project: Option<&str>,
+    weeks: u32,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    if !config.analytics.enabled {
+        if !quiet {
+            println!("Usage analytics are disabled.");
+            println!("They are computed locally and never leave this machine. To opt in:");
+            println!("  demiarch config set analytics.enabled true");
+        }
+        return Ok(());
+    }
+
+    let db = Database::shared().await?;
+    let stats = analytics::compute(&db, project, weeks, chrono::Utc::now()).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    if quiet {
+        return Ok(());
+    }
+
+    println!("Usage since {}:", stats.since);
+    if let Some(p) = &stats.project_id {
+        println!("  Project: {}", p);
+    }
+    println!();
+
+    let peak = stats
+        .weeks
+        .iter()
+        .map(|w| w.generations)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for week in &stats.weeks {
+        let len = (week.generations * 30 / peak) as usize;
+        let rate = week
+            .success_rate()
+            .map(|r| format!("{:.0}%", r * 100.0))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "  {}  {:<30} {:>4} gen  {:>4} ok  ${:.2}",
+            week.week_start,
+            glyphs::bar(len),
+            week.generations,
+            rate,
+            week.cost_usd
+        );
+    }
+
+    println!();
+    println!("  Generations: {}", stats.total_generations);
+    match stats.success_rate {
+        Some(rate) => println!("  Success rate: {:.0}%", rate * 100.0),
+        None => println!("  Success rate: -"),
+    }
+    println!("  Total cost: ${:.2}", stats.total_cost_usd);
+    match stats.avg_cost_per_feature {
+        Some(avg) => println!(
+            "  Avg cost per feature: ${:.2} ({} features)",
+            avg, stats.features_generated
+        ),
+        None => println!("  Avg cost per feature: - (no features generated)"),
+    }
+
+    if !stats.model_mix.is_empty() {
+        println!();
+        println!("  Model mix:");
+        for model in &stats.model_mix {
+            println!(
+                "    {:<40} {:>5.1}%  {} calls  ${:.2}",
+                model.model,
+                model.share * 100.0,
+                model.calls,
+                model.cost_usd
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn cmd_workspace_stats(
+    db: &Database,
+    members: &[String],
+    csv: Option<&std::path::Path>,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let projects = workspace::members(db, members).await?;
+    let stats = workspace::stats(db, &projects).await?;
+    if let Some(path) = csv {
+        workspace::write_csv(&stats, std::fs::File::create(path)?)?;
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    if quiet {
+        return Ok(());
+    }
+    if stats.projects.is_empty() {
+        println!("No active projects.");
+        return Ok(());
+    }
+
+    println!(
+        "  {:<24} {:>10} {:>6} {:>8} {:>9} {:>5} {:>6} {:>8}",
+        "Project", "Cost", "Calls", "Sessions", "Done", "Runs", "Failed", "Active"
+    );
+    let totals = workspace::ProjectRollup {
+        project_id: String::new(),
+        project_name: "Total".to_string(),
+        status: String::new(),
+        rollup: stats.totals.clone(),
+    };
+    for (i, project) in stats.projects.iter().chain([&totals]).enumerate() {
+        if i == stats.projects.len() {
+            println!();
+        }
+        let r = &project.rollup;
+        println!(
+            "  {:<24} {:>10} {:>6} {:>8} {:>9} {:>5} {:>6} {:>7.1}h",
+            truncate_str(&project.project_name, 24),
+            format!("${:.2}", r.cost_usd),
+            r.llm_calls,
+            r.sessions,
+            format!("{}/{}", r.features_done, r.features),
+            r.generations,
+            r.failed_generations,
+            r.active_secs as f64 / 3600.0
+        );
+    }
+    if let Some(path) = csv {
+        println!();
+        println!("  Written to {}", path.display());
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn cmd_costs_invoice(
+    db: &Database,
+    project: Option<&str>,
+    month: &str,
+    output: Option<&std::path::Path>,
+    markup: Option<f64>,
+    currency: Option<&str>,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let month = invoice::Month::parse(month)?;
+    let options = invoice::InvoiceOptions::resolve(&config.invoice, markup, currency)?;
+    let project = resolve_project(db, project).await?;
+    let invoice = invoice::build(db, &project, month, options).await?;
+
+    match output {
+        Some(path) => invoice::write_csv(&invoice, std::fs::File::create(path)?)?,
+        None if !json => invoice::write_csv(&invoice, std::io::stdout().lock())?,
+        None => {}
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&invoice)?);
+        return Ok(());
+    }
+    if let (Some(path), false) = (output, quiet) {
+        println!(
+            "Invoice for {} ({}): {} line item(s), total {} {:.2}",
+            invoice.project_name,
+            invoice.month,
+            invoice.lines.len(),
+            invoice.options.currency,
+            invoice.total
+        );
+        if invoice.options.markup_percent > 0.0 {
+            println!(
+                "  Cost {} {:.2} + {}% markup {} {:.2}",
+                invoice.options.currency,
+                invoice.subtotal,
+                invoice.options.markup_percent,
+                invoice.options.currency,
+                invoice.markup
+            );
+        }
+        println!("  Written to {}", path.display());
+    }
+    Ok(())
+}
+
+async fn cmd_costs_compare(
+    db: &Database,
+    from: &str,
+    to: &str,
+    by: Option<&str>,
+    project: Option<&str>,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let from = cost_compare::Period::parse(from)?;
+    let to = cost_compare::Period::parse(to)?;
+    let by = by.and_then(cost_compare::CompareBy::parse);
+    let comparison = cost_compare::compare(db, project, from, to, by).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&comparison)?);
+        return Ok(());
+    }
+    if quiet {
+        return Ok(());
+    }
+
+    let delta = |a: f64, b: f64| match cost_compare::percent_change(a, b) {
+        Some(pct) => format!("{:+.0}%", pct),
+        None => "-".to_string(),
+    };
+    let per_feature = |v: Option<f64>| match v {
+        Some(v) => format!("${:.4}", v),
+        None => "-".to_string(),
+    };
+
+    println!("Cost comparison:");
+    if let Some(p) = &comparison.project_id {
+        println!("  Project: {}", p);
+    }
+    println!(
+        "  From: {} ({} days)",
+        comparison.from_period,
+        comparison.from_period.days()
+    );
+    println!(
+        "  To:   {} ({} days)",
+        comparison.to_period,
+        comparison.to_period.days()
+    );
+    println!();
+
+    let (a, b) = (&comparison.from, &comparison.to);
+    println!("  {:<20} {:>12} {:>12} {:>8}", "", "From", "To", "Change");
+    println!(
+        "  {:<20} {:>12} {:>12} {:>8}",
+        "Spend",
+        format!("${:.4}", a.usage.spend_usd),
+        format!("${:.4}", b.usage.spend_usd),
+        delta(a.usage.spend_usd, b.usage.spend_usd)
+    );
+    println!(
+        "  {:<20} {:>12} {:>12} {:>8}",
+        "Tokens",
+        a.usage.tokens,
+        b.usage.tokens,
+        delta(a.usage.tokens as f64, b.usage.tokens as f64)
+    );
+    println!(
+        "  {:<20} {:>12} {:>12} {:>8}",
+        "Calls",
+        a.usage.calls,
+        b.usage.calls,
+        delta(a.usage.calls as f64, b.usage.calls as f64)
+    );
+    println!(
+        "  {:<20} {:>12} {:>12} {:>8}",
+        "Features completed",
+        a.features_completed,
+        b.features_completed,
+        delta(a.features_completed as f64, b.features_completed as f64)
+    );
+    let per_feature_delta = match (a.cost_per_feature_usd, b.cost_per_feature_usd) {
+        (Some(x), Some(y)) => delta(x, y),
+        _ => "-".to_string(),
+    };
+    println!(
+        "  {:<20} {:>12} {:>12} {:>8}",
+        "Cost per feature",
+        per_feature(a.cost_per_feature_usd),
+        per_feature(b.cost_per_feature_usd),
+        per_feature_delta
+    );
+
+    if let Some(by) = comparison.by {
+        println!();
+        match by {
+            cost_compare::CompareBy::Model => println!("  By model:"),
+            cost_compare::CompareBy::Feature => println!("  By feature:"),
+        }
+        if comparison.breakdown.is_empty() {
+            println!("    No usage in either period.");
+        }
+        for row in &comparison.breakdown {
+            let name = row.label.as_deref().unwrap_or(&row.key);
+            println!(
+                "    {:<30} ${:>10.4} -> ${:>10.4}  {:+.4} ({})",
+                name,
+                row.from.spend_usd,
+                row.to.spend_usd,
+                row.spend_delta_usd(),
+                delta(row.from.spend_usd, row.to.spend_usd)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn cmd_costs(db: &Database, project: Option<&str>, quiet: bool) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let tracker = CostTracker::from_config(&config.cost);
+    let forecast = forecast::forecast(db, project, &config.cost, chrono::Utc::now()).await?;
+
+    if !quiet {
+        println!("Cost Summary:");
+        if let Some(p) = project {
+            println!("  Project: {}", p);
+        }
+        println!();
+
+        // Get today's summary from the tracker
+        let today_total = tracker.today_total();
+        let remaining = tracker.remaining_budget();
+
+        println!("  Today: ${:.4}", today_total);
+
+        // Show summary if we have one
+        if let Some(summary) = tracker.today_summary() {
+            println!("    Calls: {}", summary.call_count);
+            println!(
+                "    Tokens: {} input, {} output",
+                summary.total_input_tokens, summary.total_output_tokens
+            );
+            if !summary.by_model.is_empty() {
+                println!("    By model:");
+                for (model, model_summary) in &summary.by_model {
+                    println!(
+                        "      {}: ${:.4} ({} calls)",
+                        model, model_summary.total_cost_usd, model_summary.call_count
+                    );
+                }
+            }
+        }
+
+        println!();
+        println!("  Daily limit: ${:.2}", tracker.daily_limit());
+        println!("  Remaining: ${:.2}", remaining);
+        println!(
+            "  Alert threshold: {:.0}%",
+            config.cost.alert_threshold * 100.0
+        );
+
+        // Show warnings if approaching or over limit
+        if tracker.is_over_limit() {
+            println!();
+            println!("  [WARNING] Daily limit exceeded!");
+        } else if tracker.is_approaching_limit() {
+            println!();
+            println!(
+                "  [WARNING] Approaching daily limit ({}% used)",
+                ((today_total / tracker.daily_limit()) * 100.0) as u32
+            );
+        }
+
+        println!();
+        println!(
+            "  This month ({}): ${:.2}",
+            forecast.month_start.format("%b %Y"),
+            forecast.month_to_date_usd
+        );
+        println!(
+            "    Burn rate: ${:.2}/day (last {} days)",
+            forecast.burn_rate_usd_per_day, forecast.window_days
+        );
+        match forecast.projected_share() {
+            Some(share) => println!(
+                "    Projected by {}: ${:.2} ({:.0}% of the ${:.2} monthly limit)",
+                forecast.month_end.format("%b %-d"),
+                forecast.projected_usd,
+                share * 100.0,
+                forecast.monthly_limit_usd
+            ),
+            None => println!(
+                "    Projected by {}: ${:.2}",
+                forecast.month_end.format("%b %-d"),
+                forecast.projected_usd
+            ),
+        }
+        if forecast.over_limit {
+            println!();
+            match forecast.limit_reached_on {
+                Some(day) => println!(
+                    "  [WARNING] Projected to exceed the monthly limit around {}",
+                    day.format("%b %-d")
+                ),
+                None => println!("  [WARNING] Projected to exceed the monthly limit"),
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_sync(
+    db: &Database,
+    action: SyncAction,
+    quiet: bool,
+    progress: &Progress,
+) -> anyhow::Result<()> {
+    // Resolve the active project (prefer current directory, fallback to most recent with a path)
+    let current_dir = std::env::current_dir()?;
+    let project = if let Some(p) = project::find_by_directory(db, &current_dir)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to detect project: {}", e))?
+    {
+        p
+    } else {
+        let repo = project::ProjectRepository::new(db);
+        repo.list(None)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list projects: {}", e))?
+            .into_iter()
+            .find(|p| p.path.is_some())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No projects registered. Run `demiarch new` or `demiarch init` in your project directory first."
+                )
+            })?
+    };
+
+    let project_path = project.path.clone().ok_or_else(|| {
+        anyhow::anyhow!(
+            "Project '{}' has no saved path. Re-run `demiarch init` inside the project directory to register it.",
+            project.name
+        )
+    })?;
+    let project_dir = std::path::PathBuf::from(&project_path);
+
+    match action {
+        SyncAction::Flush { compress } => {
+            if !quiet {
+                println!(
+                    "Flushing SQLite to JSONL for project '{}' ({}):",
+                    project.name,
+                    project_dir.display()
+                );
+            }
+
+            let options = storage::SyncOptions {
+                compress,
+                ..Default::default()
+            };
+            let result =
+                storage::export_to_jsonl_with(db.pool(), &project_dir, &options, progress).await?;
+            progress.finish("");
+
+            if !quiet {
+                println!(
+                    "  Wrote {} records to {}",
+                    result.metadata.total_records,
+                    result.sync_dir.display()
+                );
+                let mut counts: Vec<_> = result.metadata.record_counts.iter().collect();
+                counts.sort_by(|a, b| a.0.cmp(b.0));
+                for (table, count) in counts {
+                    println!("    {:<24} {}", table, count);
+                }
+                println!(
+                    "  Exported at: {}",
+                    result.metadata.exported_at.to_rfc3339()
+                );
+            }
+        }
+        SyncAction::Import => {
+            let sync_dir = project_dir.join(storage::SYNC_DIR);
+            if !sync_dir.exists() {
+                return Err(anyhow::anyhow!(
+                    "No sync directory found at {}. Run `demiarch sync flush` first.",
+                    sync_dir.display()
+                ));
+            }
+
+            let config = Config::load()?;
+            let payload = serde_json::json!({
+                "event": HookEvent::BeforeSyncImport.as_str(),
+                "project_id": project.id,
+                "sync_dir": sync_dir,
+            });
+            fire_hook(
+                &hooks_with_checkpoints(db, &config),
+                HookEvent::BeforeSyncImport,
+                &payload,
+            )
+            .await?;
+
             if !quiet {
-                println!("Saved manual skill '{}'", skill.name);
+                println!("Importing JSONL from {}...", sync_dir.display());
+            }
+
+            let result = storage::import_from_jsonl_with(
+                db.pool(),
+                &project_dir,
+                &storage::SyncOptions::default(),
+                progress,
+            )
+            .await?;
+            progress.finish("");
+
+            if !quiet {
+                println!("  Imported {} records", result.total_records);
+                let mut counts: Vec<_> = result.record_counts.iter().collect();
+                counts.sort_by(|a, b| a.0.cmp(b.0));
+                for (table, count) in counts {
+                    println!("    {:<24} {}", table, count);
+                }
+                if !result.warnings.is_empty() {
+                    println!("Warnings:");
+                    for warn in &result.warnings {
+                        println!("  - {}", warn);
+                    }
+                }
+            }
+        }
+        SyncAction::Status => {
+            let status = storage::check_sync_status(db.pool(), &project_dir).await?;
+
+            if !quiet {
+                println!(
+                    "Sync status for '{}' ({}):",
+                    project.name,
+                    project_dir.display()
+                );
+                println!(
+                    "  State: {}",
+                    if status.dirty {
+                        "needs export"
+                    } else {
+                        "clean"
+                    }
+                );
+                println!(
+                    "  Last sync: {}",
+                    status
+                        .last_sync_at
+                        .as_deref()
+                        .unwrap_or("never (no export yet)")
+                );
+                println!("  Pending changes: {}", status.pending_changes);
+                println!("  Note: {}", status.message);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_checkpoints(
+    action: CheckpointAction,
+    quiet: bool,
+    progress: &Progress,
+) -> anyhow::Result<()> {
+    match action {
+        CheckpointAction::List { project } => {
+            let project_id = uuid::Uuid::parse_str(&project)
+                .map_err(|_| anyhow::anyhow!("Invalid project ID: {}", project))?;
+
+            let checkpoints = checkpoint::list_checkpoints(project_id).await?;
+
+            if checkpoints.is_empty() {
+                if !quiet {
+                    println!("No checkpoints found for project '{}'.", project);
+                    println!();
+                    println!("Checkpoints are created automatically before code generation.");
+                    println!("You can also create a manual checkpoint with:");
+                    println!(
+                        "  demiarch checkpoints create --project {} --description \"Before my changes\"",
+                        project
+                    );
+                }
+            } else {
+                if !quiet {
+                    println!("Checkpoints for project '{}':", &project[..8]);
+                    println!();
+                }
+                for cp in checkpoints {
+                    let feature_info = cp
+                        .feature_id
+                        .map(|f| format!(" [feature: {}]", &f.to_string()[..8]))
+                        .unwrap_or_default();
+                    println!(
+                        "  {} - {} ({}){}",
+                        &cp.id.to_string()[..8],
+                        cp.description,
+                        cp.display_size(),
+                        feature_info
+                    );
+                    println!(
+                        "       Created: {}",
+                        cp.created_at.format("%Y-%m-%d %H:%M:%S")
+                    );
+                }
+            }
+        }
+
+        CheckpointAction::Stats { project } => {
+            let project_id = uuid::Uuid::parse_str(&project)
+                .map_err(|_| anyhow::anyhow!("Invalid project ID: {}", project))?;
+
+            let stats = checkpoint::get_checkpoint_stats(project_id).await?;
+
+            if !quiet {
+                println!("Checkpoint Statistics:");
+                println!("  Project: {}", &project[..8]);
+                println!("  Total checkpoints: {}", stats.total_count);
+                println!("  Total size: {}", stats.display_total_size());
+                if let Some(oldest) = stats.oldest_checkpoint {
+                    println!("  Oldest: {}", oldest.format("%Y-%m-%d %H:%M:%S"));
+                }
+                if let Some(newest) = stats.newest_checkpoint {
+                    println!("  Newest: {}", newest.format("%Y-%m-%d %H:%M:%S"));
+                }
+            }
+        }
+
+        CheckpointAction::Create {
+            project,
+            description,
+            feature,
+        } => {
+            let project_id = uuid::Uuid::parse_str(&project)
+                .map_err(|_| anyhow::anyhow!("Invalid project ID: {}", project))?;
+
+            let feature_id = feature
+                .map(|f| uuid::Uuid::parse_str(&f))
+                .transpose()
+                .map_err(|_| anyhow::anyhow!("Invalid feature ID"))?;
+
+            if !quiet {
+                println!("Creating checkpoint...");
+            }
+
+            let cp = checkpoint::create_checkpoint(project_id, description, feature_id).await?;
+
+            if !quiet {
+                println!("Checkpoint created successfully!");
+                println!("  ID: {}", cp.id);
+                println!("  Description: {}", cp.description);
+                println!("  Size: {}", cp.display_size());
+            }
+        }
+
+        CheckpointAction::Restore { id, force } => {
+            let checkpoint_id = uuid::Uuid::parse_str(&id)
+                .map_err(|_| anyhow::anyhow!("Invalid checkpoint ID: {}", id))?;
+
+            if !force && !quiet {
+                println!(
+                    "Warning: This will restore project state to checkpoint '{}'.",
+                    &id[..8]
+                );
+                println!("Current project state will be backed up automatically unless checkpoint.triggers.before_restore is off.");
+                println!("Use --force to skip this confirmation.");
+                return Ok(());
             }
-        }
-        SkillAction::Delete { id } => {
-            let removed = manager.delete(&id).await?;
+
             if !quiet {
-                if removed {
-                    println!("Deleted skill {}", id);
-                } else {
-                    println!("Skill {} not found", id);
-                }
+                println!("Restoring checkpoint '{}'...", &id[..8]);
             }
-        }
-        SkillAction::Stats => {
-            let stats = manager.stats().await?;
+
+            let safety_backup = Config::load()
+                .map(|config| config.checkpoint.triggers.before_restore)
+                .unwrap_or(true);
+            let result =
+                checkpoint::restore_checkpoint(checkpoint_id, safety_backup, progress).await?;
+            progress.finish("");
+
             if !quiet {
-                println!("Skill Statistics:");
-                println!("  Total skills: {}", stats.total_skills);
-                println!("  Total uses: {}", stats.total_uses);
-                println!("  High-confidence: {}", stats.high_confidence_skills);
-                println!("  By category:");
-                for (cat, count) in stats.skills_by_category {
-                    println!("    - {}: {}", cat.as_str(), count);
+                println!();
+                println!("Checkpoint restored successfully!");
+                println!();
+                println!(
+                    "  Restored to: {}",
+                    result.checkpoint_timestamp.format("%Y-%m-%d %H:%M:%S")
+                );
+                println!("  Description: {}", result.checkpoint_description);
+                println!();
+                println!("  Phases restored: {}", result.phases_restored);
+                println!("  Features restored: {}", result.features_restored);
+                println!("  Messages restored: {}", result.messages_restored);
+                if result.files_restored > 0 {
+                    println!("  Files restored: {}", result.files_restored);
+                }
+                if let Some(safety_backup_id) = result.safety_backup_id {
+                    println!();
+                    println!(
+                        "  Safety backup created: {}",
+                        &safety_backup_id.to_string()[..8]
+                    );
+                    println!();
+                    println!("To undo this restore, run:");
+                    println!(
+                        "  demiarch checkpoints restore {} --force",
+                        &safety_backup_id.to_string()[..8]
+                    );
                 }
             }
         }
-        SkillAction::Show { id } => match manager.get(&id).await? {
-            Some(skill) => {
-                manager.record_usage(&id, true).await.ok();
-                let skill = manager.get(&id).await?.unwrap_or(skill);
-                if quiet {
-                    println!("{}", serde_json::to_string_pretty(&skill)?);
+
+        CheckpointAction::Verify { id } => {
+            let checkpoint_id = uuid::Uuid::parse_str(&id)
+                .map_err(|_| anyhow::anyhow!("Invalid checkpoint ID: {}", id))?;
+
+            let is_valid = checkpoint::verify_checkpoint(checkpoint_id).await?;
+
+            if !quiet {
+                if is_valid {
+                    println!("[OK] Checkpoint '{}' signature is valid.", &id[..8]);
                 } else {
-                    println!("{}", skill.name);
-                    println!("  ID: {}", skill.id);
-                    println!("  Category: {}", skill.category.as_str());
-                    println!("  Description: {}", skill.description);
-                    println!("  Tags: {}", skill.tags.join(", "));
-                    println!("  Confidence: {}", skill.confidence.as_str());
-                    println!("  Pattern: {}", skill.pattern.pattern_type.as_str());
-                    println!("  Template:\n{}", indent(&skill.pattern.template, 4));
-                    if !skill.pattern.variables.is_empty() {
-                        println!("  Variables:");
-                        for v in &skill.pattern.variables {
-                            println!("    - {}: {}", v.name, v.description);
-                        }
-                    }
-                    if !skill.pattern.applicability.is_empty() {
-                        println!(
-                            "  Applicability: {}",
-                            skill.pattern.applicability.join("; ")
-                        );
-                    }
-                    if !skill.pattern.limitations.is_empty() {
-                        println!("  Limitations: {}", skill.pattern.limitations.join("; "));
-                    }
                     println!(
-                        "  Usage: {} used, {} successes, {} failures",
-                        skill.usage_stats.times_used,
-                        skill.usage_stats.success_count,
-                        skill.usage_stats.failure_count
+                        "[!!] Checkpoint '{}' signature verification FAILED.",
+                        &id[..8]
                     );
-                    if let Some(last) = skill.usage_stats.last_used_at {
-                        println!("  Last used: {}", last.to_rfc3339());
-                    }
+                    println!("     The checkpoint data may have been corrupted or tampered with.");
                 }
             }
-            None => println!("Skill not found: {}", id),
-        },
-    }
-    Ok(())
-}
+        }
 
-async fn cmd_routing(action: RoutingAction, quiet: bool) -> anyhow::Result<()> {
-    let config = Config::load()?;
+        CheckpointAction::Export { id, output } => {
+            let checkpoint_id = uuid::Uuid::parse_str(&id)
+                .map_err(|_| anyhow::anyhow!("Invalid checkpoint ID: {}", id))?;
+
+            let manifest = checkpoint::export_checkpoint(checkpoint_id, &output).await?;
 
-    match action {
-        RoutingAction::Status => {
             if !quiet {
-                println!("Routing Status:");
-                println!("  Preference: {}", config.routing.preference);
-                println!("  Default model: {}", config.llm.default_model);
                 println!(
-                    "  Fallback models: {}",
-                    config.llm.fallback_models.join(", ")
+                    "Checkpoint '{}' exported to '{}' ({} file(s)).",
+                    &id[..8],
+                    output.display(),
+                    manifest.files.len()
                 );
             }
         }
-        RoutingAction::SetPreference { preference } => {
-            let mut config = config;
-            config.set("routing.preference", &preference)?;
-            config.save()?;
+
+        CheckpointAction::Import { file, project } => {
+            let project_id = uuid::Uuid::parse_str(&project)
+                .map_err(|_| anyhow::anyhow!("Invalid project ID: {}", project))?;
+
+            let cp = checkpoint::import_checkpoint(&file, project_id).await?;
+
             if !quiet {
-                println!("Routing preference set to: {}", preference);
+                println!("[OK] Archive signature verified.");
+                println!("Checkpoint imported: {}", &cp.id.to_string()[..8]);
+                println!("  Description: {}", cp.description);
+                println!("  Size: {}", cp.display_size());
+                println!();
+                println!("To restore it, run:");
+                println!("  demiarch checkpoints restore {} --force", cp.id);
             }
         }
-        RoutingAction::Performance { task } => {
+
+        CheckpointAction::Delete { id } => {
+            let checkpoint_id = uuid::Uuid::parse_str(&id)
+                .map_err(|_| anyhow::anyhow!("Invalid checkpoint ID: {}", id))?;
+
+            let deleted = checkpoint::delete_checkpoint(checkpoint_id).await?;
+
             if !quiet {
-                println!("Model Performance:");
-                if let Some(t) = task {
-                    println!("  (filtered by task: {})", t);
+                if deleted {
+                    println!("Checkpoint '{}' deleted.", &id[..8]);
+                } else {
+                    println!("Checkpoint '{}' not found.", &id[..8]);
                 }
-                println!("  (No performance data yet)");
             }
         }
-        RoutingAction::History { limit } => {
+
+        CheckpointAction::DeleteAll { project, force } => {
+            let project_id = uuid::Uuid::parse_str(&project)
+                .map_err(|_| anyhow::anyhow!("Invalid project ID: {}", project))?;
+
+            if !force && !quiet {
+                println!(
+                    "Warning: This will delete ALL checkpoints for project '{}'.",
+                    &project[..8]
+                );
+                println!("Use --force to confirm deletion.");
+                return Ok(());
+            }
+
+            let deleted = checkpoint::delete_all_checkpoints(project_id).await?;
+
             if !quiet {
-                let limit = limit.unwrap_or(10);
-                println!("Routing History (last {}):", limit);
-                println!("  (No routing history yet)");
+                println!(
+                    "Deleted {} checkpoint(s) for project '{}'.",
+                    deleted,
+                    &project[..8]
+                );
             }
         }
     }
     Ok(())
 }
 
-async fn cmd_context(db: &Database, action: ContextAction, quiet: bool) -> anyhow::Result<()> {
-    use demiarch_core::domain::memory::PersistentMemoryStore;
-
-    let project_repo = project::ProjectRepository::new(db);
-    let active_project = project_repo
-        .list(None)
-        .await?
-        .into_iter()
-        .find(|p| p.path.is_some())
-        .ok_or_else(|| {
-            anyhow::anyhow!("No projects found. Create one with: demiarch new <name>")
-        })?;
-
-    let store = PersistentMemoryStore::new(db.pool().clone());
-    let manager = ContextManager::new().with_persistent_store(store);
-
+fn cmd_config(action: ConfigAction, quiet: bool) -> anyhow::Result<()> {
     match action {
-        ContextAction::Stats { project } => {
-            let project_id = project.unwrap_or(active_project.id.clone());
-            let stats = if let Some(p) = manager.persistent() {
-                p.stats(Some(&project_id)).await?
-            } else {
-                Default::default()
-            };
-
+        ConfigAction::Get { key } => {
+            let config = Config::load()?;
+            let value = config.get(&key)?;
+            println!("{}", value);
+        }
+        ConfigAction::Set { key, value } => {
+            let mut config = Config::load()?;
+            config.set(&key, &value)?;
+            config.save()?;
             if !quiet {
-                println!("Context Statistics (project: {}):", project_id);
-                println!("  Total entries: {}", stats.stats.total_records);
-                println!("  Total tokens: {}", stats.total_tokens);
-                if let Some(oldest) = stats.stats.oldest_at {
-                    println!("  Oldest: {}", oldest);
-                }
-                if let Some(newest) = stats.stats.newest_at {
-                    println!("  Newest: {}", newest);
-                }
+                println!("Set {} = {}", key, value);
             }
         }
-        ContextAction::Search { query, level: _ } => {
-            let results = if let Some(p) = manager.persistent() {
-                p.recall(
-                    Some(&active_project.id),
-                    RecallQuery {
-                        query: query.clone(),
-                        ..Default::default()
-                    },
-                )
-                .await?
-            } else {
-                Vec::new()
-            };
-
-            if results.is_empty() {
-                if !quiet {
-                    println!("No matching context found for '{}'.", query);
-                }
-            } else {
-                if !quiet {
-                    println!("Context matches ({}):", results.len());
-                }
-                for rec in results {
-                    println!("- {} | {}", rec.created_at.to_rfc3339(), rec.index_summary);
-                }
+        ConfigAction::List => {
+            let config = Config::load()?;
+            let items = config.list()?;
+            for (key, value) in items {
+                println!("{} = {}", key, value);
             }
         }
-        ContextAction::Prune {
-            older_than,
-            dry_run,
-        } => {
-            let days = older_than.unwrap_or(30);
-            let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
-            let removed = if let Some(p) = manager.persistent() {
-                p.prune(Some(&active_project.id), cutoff, dry_run).await?
-            } else {
-                0usize
-            };
-            if !quiet {
-                if dry_run {
-                    println!(
-                        "Dry run: would remove {} entries older than {} days",
-                        removed, days
-                    );
-                } else {
-                    println!(
-                        "Removed {} context entries older than {} days",
-                        removed, days
-                    );
-                }
+        ConfigAction::Reset => {
+            Config::reset()?;
+            if !quiet {
+                println!("Configuration reset to defaults.");
             }
         }
-        ContextAction::Rebuild { project } => {
-            let project_id = project.unwrap_or(active_project.id.clone());
-            let updated = if let Some(p) = manager.persistent() {
-                p.rebuild(Some(&project_id)).await?
-            } else {
-                0usize
-            };
+        ConfigAction::Path => {
+            let path = Config::config_path()?;
+            println!("{}", path.display());
+        }
+        ConfigAction::Reload => {
+            // Running processes skip an invalid file, so report it here
+            Config::load()?;
+            watch::request_reload()?;
             if !quiet {
                 println!(
-                    "Rebuilt {} context entries for project {}",
-                    updated, project_id
+                    "Configuration is valid; running processes will apply it within {}s.",
+                    watch::DEFAULT_POLL_INTERVAL.as_secs()
                 );
             }
         }
     }
-
     Ok(())
 }
 
-async fn cmd_hooks(action: HookAction, quiet: bool) -> anyhow::Result<()> {
+/// The project's persona named by `persona.default`, if any
+async fn project_persona(
+    db: &Database,
+    project: Option<&project::Project>,
+) -> Option<persona::Persona> {
+    let (Some(project), Ok(config)) = (project, Config::load()) else {
+        return None;
+    };
+    persona::resolve(db, &project.id, None, &config)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to read project persona");
+            None
+        })
+}
+
+/// Secret names generation may reference, per `secrets.reference_in_generation`
+async fn generation_secret_names(db: &Database, project: Option<&project::Project>) -> Vec<String> {
+    let enabled = Config::load().is_ok_and(|c| c.secrets.reference_in_generation);
+    match project {
+        Some(p) if enabled => secrets::names(db, &p.id).await.unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to read project secret names");
+            Vec::new()
+        }),
+        _ => Vec::new(),
+    }
+}
+
+/// Project named by `--project` (ID or name), else the one in the current directory
+async fn resolve_project(
+    db: &Database,
+    id_or_name: Option<&str>,
+) -> anyhow::Result<project::Project> {
+    if let Some(id) = id_or_name {
+        let found = match project::get_with_db(db, id).await? {
+            Some(p) => Some(p),
+            None => project::ProjectRepository::new(db).get_by_name(id).await?,
+        };
+        return found.ok_or_else(|| anyhow::anyhow!(t_args("projects-not-found", &[("id", id)])));
+    }
+    project::find_by_directory(db, &std::env::current_dir()?)
+        .await?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No project in the current directory. Pass --project <id or name>, or run `demiarch init` here."
+            )
+        })
+}
+
+async fn cmd_secrets(
+    db: &Database,
+    action: SecretAction,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let vault = secrets::SecretsVault::new(db);
     match action {
-        HookAction::List { r#type } => {
-            if !quiet {
-                println!("Registered Hooks:");
-                if let Some(t) = r#type {
-                    println!("  (filtered by type: {})", t);
-                }
-                println!("  (No hooks registered)");
-            }
-        }
-        HookAction::Register {
-            hook_type,
+        SecretAction::Set {
             name,
-            handler,
+            value,
+            project,
+            description,
         } => {
+            let p = resolve_project(db, project.as_deref()).await?;
+            let value = match value {
+                Some(value) => value,
+                None => {
+                    if !quiet {
+                        eprint!("Value for {}: ", name);
+                        io::stderr().flush()?;
+                    }
+                    let mut line = String::new();
+                    io::stdin().read_line(&mut line)?;
+                    line.trim_end_matches(['\r', '\n']).to_string()
+                }
+            };
+            vault
+                .set(&p.id, &name, &value, description.as_deref())
+                .await?;
             if !quiet {
-                println!("Registering hook:");
-                println!("  Type: {}", hook_type);
-                println!("  Name: {}", name);
-                println!("  Handler: {}", handler);
-                println!("  (Hook registration not yet implemented)");
+                println!("{} Stored {} for {}", glyphs::check(), name, p.name);
             }
         }
-        HookAction::Enable { id } => {
-            if !quiet {
-                println!("Enabling hook: {}", id);
-                println!("  (Hook not found)");
-            }
+        SecretAction::Get { name, project } => {
+            let p = resolve_project(db, project.as_deref()).await?;
+            let value = vault.get(&p.id, &name).await?;
+            println!("{}", value.as_str());
         }
-        HookAction::Disable { id } => {
-            if !quiet {
-                println!("Disabling hook: {}", id);
-                println!("  (Hook not found)");
+        SecretAction::List { project } => {
+            let p = resolve_project(db, project.as_deref()).await?;
+            let listed = secrets::list(db, &p.id).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&listed)?);
+            } else if listed.is_empty() {
+                if !quiet {
+                    println!("No secrets stored for {}.", p.name);
+                }
+            } else {
+                for secret in &listed {
+                    match &secret.description {
+                        Some(description) => println!("{:<32} {}", secret.name, description),
+                        None => println!("{}", secret.name),
+                    }
+                }
             }
         }
-        HookAction::Remove { id } => {
+        SecretAction::Unset { name, project } => {
+            let p = resolve_project(db, project.as_deref()).await?;
+            if !vault.remove(&p.id, &name).await? {
+                anyhow::bail!("No secret named {} for {}", name, p.name);
+            }
             if !quiet {
-                println!("Removing hook: {}", id);
-                println!("  (Hook not found)");
+                println!("{} Removed {}", glyphs::check(), name);
             }
         }
-        HookAction::History { limit } => {
-            if !quiet {
-                let limit = limit.unwrap_or(10);
-                println!("Hook Execution History (last {}):", limit);
-                println!("  (No hook executions yet)");
+        SecretAction::Export {
+            dotenv: _,
+            output,
+            project,
+        } => {
+            let p = resolve_project(db, project.as_deref()).await?;
+            let contents = vault.export_dotenv(&p.id).await?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, contents)?;
+                    if !quiet {
+                        eprintln!(
+                            "{} Wrote {}. Keep it out of version control.",
+                            glyphs::check(),
+                            path.display()
+                        );
+                    }
+                }
+                None => print!("{}", contents),
             }
         }
     }
     Ok(())
 }
 
-async fn cmd_costs(project: Option<&str>, quiet: bool) -> anyhow::Result<()> {
-    let config = Config::load()?;
-    let tracker = CostTracker::from_config(&config.cost);
-
-    if !quiet {
-        println!("Cost Summary:");
-        if let Some(p) = project {
-            println!("  Project: {}", p);
-        }
-        println!();
-
-        // Get today's summary from the tracker
-        let today_total = tracker.today_total();
-        let remaining = tracker.remaining_budget();
-
-        println!("  Today: ${:.4}", today_total);
-
-        // Show summary if we have one
-        if let Some(summary) = tracker.today_summary() {
-            println!("    Calls: {}", summary.call_count);
-            println!(
-                "    Tokens: {} input, {} output",
-                summary.total_input_tokens, summary.total_output_tokens
-            );
-            if !summary.by_model.is_empty() {
-                println!("    By model:");
-                for (model, model_summary) in &summary.by_model {
-                    println!(
-                        "      {}: ${:.4} ({} calls)",
-                        model, model_summary.total_cost_usd, model_summary.call_count
-                    );
+async fn cmd_personas(
+    db: &Database,
+    action: PersonaAction,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    match action {
+        PersonaAction::List { project } => {
+            let p = resolve_project(db, project.as_deref()).await?;
+            let listed = persona::list(db, &p.id).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&listed)?);
+            } else if listed.is_empty() {
+                if !quiet {
+                    println!("No personas for {}.", p.name);
+                }
+            } else {
+                let default = Config::load()?.persona.default;
+                for stored in &listed {
+                    let marker = if stored.persona.name == default {
+                        " (default)"
+                    } else {
+                        ""
+                    };
+                    let summary = [
+                        stored.persona.tone.as_deref(),
+                        stored.persona.seniority.as_deref(),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                    println!("{:<24} {}{}", stored.persona.name, summary, marker);
                 }
             }
         }
-
-        println!();
-        println!("  Daily limit: ${:.2}", tracker.daily_limit());
-        println!("  Remaining: ${:.2}", remaining);
-        println!(
-            "  Alert threshold: {:.0}%",
-            config.cost.alert_threshold * 100.0
-        );
-
-        // Show warnings if approaching or over limit
-        if tracker.is_over_limit() {
-            println!();
-            println!("  [WARNING] Daily limit exceeded!");
-        } else if tracker.is_approaching_limit() {
-            println!();
-            println!(
-                "  [WARNING] Approaching daily limit ({}% used)",
-                ((today_total / tracker.daily_limit()) * 100.0) as u32
-            );
+        PersonaAction::Show { name, project } => {
+            let p = resolve_project(db, project.as_deref()).await?;
+            let stored = persona::get(db, &p.id, &name)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("No persona named {} for {}", name, p.name))?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stored)?);
+            } else {
+                println!("{}", stored.persona.prompt());
+            }
         }
-    }
-    Ok(())
-}
-
-async fn cmd_sync(db: &Database, action: SyncAction, quiet: bool) -> anyhow::Result<()> {
-    // Resolve the active project (prefer current directory, fallback to most recent with a path)
-    let current_dir = std::env::current_dir()?;
-    let project = if let Some(p) = project::find_by_directory(db, &current_dir)
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to detect project: {}", e))?
-    {
-        p
-    } else {
-        let repo = project::ProjectRepository::new(db);
-        repo.list(None)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to list projects: {}", e))?
-            .into_iter()
-            .find(|p| p.path.is_some())
-            .ok_or_else(|| {
-                anyhow::anyhow!(
-                    "No projects registered. Run `demiarch new` or `demiarch init` in your project directory first."
-                )
-            })?
-    };
-
-    let project_path = project.path.clone().ok_or_else(|| {
-        anyhow::anyhow!(
-            "Project '{}' has no saved path. Re-run `demiarch init` inside the project directory to register it.",
-            project.name
-        )
-    })?;
-    let project_dir = std::path::PathBuf::from(&project_path);
-
-    match action {
-        SyncAction::Flush => {
+        PersonaAction::Add {
+            name,
+            tone,
+            seniority,
+            stack,
+            instructions,
+            project,
+        } => {
+            let p = resolve_project(db, project.as_deref()).await?;
+            let new_persona = persona::Persona {
+                name,
+                tone,
+                seniority,
+                stack: stack
+                    .into_iter()
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                instructions,
+            };
+            persona::save(db, &p.id, &new_persona).await?;
             if !quiet {
                 println!(
-                    "Flushing SQLite to JSONL for project '{}' ({}):",
-                    project.name,
-                    project_dir.display()
+                    "{} Saved persona {} for {}",
+                    glyphs::check(),
+                    new_persona.name,
+                    p.name
                 );
             }
-
-            let result = storage::export_to_jsonl(db.pool(), &project_dir)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-
+        }
+        PersonaAction::Remove { name, project } => {
+            let p = resolve_project(db, project.as_deref()).await?;
+            if !persona::remove(db, &p.id, &name).await? {
+                anyhow::bail!("No persona named {} for {}", name, p.name);
+            }
             if !quiet {
-                println!(
-                    "  Wrote {} records to {}",
-                    result.metadata.total_records,
-                    result.sync_dir.display()
-                );
-                let mut counts: Vec<_> = result.metadata.record_counts.iter().collect();
-                counts.sort_by(|a, b| a.0.cmp(b.0));
-                for (table, count) in counts {
-                    println!("    {:<24} {}", table, count);
-                }
-                println!(
-                    "  Exported at: {}",
-                    result.metadata.exported_at.to_rfc3339()
-                );
+                println!("{} Removed {}", glyphs::check(), name);
             }
         }
-        SyncAction::Import => {
-            let sync_dir = project_dir.join(storage::SYNC_DIR);
-            if !sync_dir.exists() {
-                return Err(anyhow::anyhow!(
-                    "No sync directory found at {}. Run `demiarch sync flush` first.",
-                    sync_dir.display()
-                ));
+        PersonaAction::Export {
+            names,
+            output,
+            project,
+        } => {
+            let p = resolve_project(db, project.as_deref()).await?;
+            let contents = persona::export(db, &p.id, &names).await?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, contents)?;
+                    if !quiet {
+                        eprintln!("{} Wrote {}", glyphs::check(), path.display());
+                    }
+                }
+                None => print!("{}", contents),
             }
-
-            if !quiet {
-                println!("Importing JSONL from {}...", sync_dir.display());
+        }
+        PersonaAction::Import {
+            file,
+            replace,
+            project,
+        } => {
+            let p = resolve_project(db, project.as_deref()).await?;
+            let text = std::fs::read_to_string(&file)?;
+            let summary = persona::import(db, &p.id, &text, replace).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&summary)?);
+            } else if !quiet {
+                for name in &summary.added {
+                    println!("{} Added {}", glyphs::check(), name);
+                }
+                for name in &summary.replaced {
+                    println!("{} Replaced {}", glyphs::check(), name);
+                }
+                for name in &summary.skipped {
+                    println!("  Skipped {} (exists; use --replace)", name);
+                }
             }
+        }
+    }
+    Ok(())
+}
 
-            let result = storage::import_from_jsonl(db.pool(), &project_dir)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-
-            if !quiet {
-                println!("  Imported {} records", result.total_records);
-                let mut counts: Vec<_> = result.record_counts.iter().collect();
-                counts.sort_by(|a, b| a.0.cmp(b.0));
-                for (table, count) in counts {
-                    println!("    {:<24} {}", table, count);
-                }
-                if !result.warnings.is_empty() {
-                    println!("Warnings:");
-                    for warn in &result.warnings {
-                        println!("  - {}", warn);
+async fn cmd_pr(db: &Database, action: PrAction, quiet: bool) -> anyhow::Result<()> {
+    let tokens = pull_request::ForgeTokens::new(db);
+    match action {
+        PrAction::SetToken { forge, token } => {
+            let forge = pull_request::Forge::parse(&forge)
+                .ok_or_else(|| anyhow::anyhow!("Unknown forge: {}", forge))?;
+            let token = match token {
+                Some(token) => token,
+                None => {
+                    if !quiet {
+                        eprint!("{} token: ", forge);
+                        io::stderr().flush()?;
                     }
+                    let mut line = String::new();
+                    io::stdin().read_line(&mut line)?;
+                    line.trim_end_matches(['\r', '\n']).to_string()
+                }
+            };
+            if token.trim().is_empty() {
+                anyhow::bail!("The token is empty");
+            }
+            tokens.set(forge, &token).await?;
+            if !quiet {
+                println!("{} Stored {} token", glyphs::check(), forge);
+            }
+        }
+        PrAction::RemoveToken { forge } => {
+            let forge = pull_request::Forge::parse(&forge)
+                .ok_or_else(|| anyhow::anyhow!("Unknown forge: {}", forge))?;
+            let removed = tokens.remove(forge).await?;
+            if !quiet {
+                if removed {
+                    println!("{} Removed {} token", glyphs::check(), forge);
+                } else {
+                    println!("No {} token stored", forge);
                 }
             }
         }
-        SyncAction::Status => {
-            let status = storage::check_sync_status(db.pool(), &project_dir)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
+    }
+    Ok(())
+}
 
-            if !quiet {
+async fn cmd_license(
+    db: &Database,
+    action: LicenseAction,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let manager = license::LicenseManager::new(db);
+    match action {
+        LicenseAction::Activate { key } => {
+            let claims = manager.activate(&key).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&claims)?);
+            } else if !quiet {
                 println!(
-                    "Sync status for '{}' ({}):",
-                    project.name,
-                    project_dir.display()
+                    "{} Activated {} license for {} (expires {})",
+                    glyphs::check(),
+                    claims.tier,
+                    claims.licensee,
+                    claims.expires_at.format("%Y-%m-%d")
                 );
+            }
+        }
+        LicenseAction::Status => {
+            let claims = manager.status().await?;
+            if json {
                 println!(
-                    "  State: {}",
-                    if status.dirty {
-                        "needs export"
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "license": claims,
+                        "active_tier": claims
+                            .as_ref()
+                            .map_or(license::LicenseTier::Free, |c| c.effective_tier()),
+                        "expired": claims.as_ref().is_some_and(|c| c.is_expired()),
+                    }))?
+                );
+                return Ok(());
+            }
+            match claims {
+                None => println!("No license activated. Tier: Free"),
+                Some(claims) => {
+                    println!("Tier:      {}", claims.effective_tier());
+                    println!("Licensee:  {}", claims.licensee);
+                    println!("License:   {}", claims.id);
+                    if claims.is_expired() {
+                        println!(
+                            "Expired:   {} ({} license; renew to restore it)",
+                            claims.expires_at.format("%Y-%m-%d"),
+                            claims.tier
+                        );
                     } else {
-                        "clean"
+                        println!(
+                            "Expires:   {} ({} days left)",
+                            claims.expires_at.format("%Y-%m-%d"),
+                            claims.days_remaining()
+                        );
                     }
-                );
-                println!(
-                    "  Last sync: {}",
-                    status
-                        .last_sync_at
-                        .as_deref()
-                        .unwrap_or("never (no export yet)")
-                );
-                println!("  Pending changes: {}", status.pending_changes);
-                println!("  Note: {}", status.message);
+                }
+            }
+        }
+        LicenseAction::Deactivate => {
+            let removed = manager.deactivate().await?;
+            if !quiet {
+                if removed {
+                    println!("{} License removed. Tier: Free", glyphs::check());
+                } else {
+                    println!("No license was activated.");
+                }
             }
         }
     }
     Ok(())
 }
 
-async fn cmd_checkpoints(action: CheckpointAction, quiet: bool) -> anyhow::Result<()> {
+async fn cmd_db(
+    db: &Database,
+    action: DbAction,
+    quiet: bool,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let json = matches!(format, OutputFormat::Json);
     match action {
-        CheckpointAction::List { project } => {
-            let project_id = uuid::Uuid::parse_str(&project)
-                .map_err(|_| anyhow::anyhow!("Invalid project ID: {}", project))?;
-
-            let checkpoints = checkpoint::list_checkpoints(project_id)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-
-            if checkpoints.is_empty() {
-                if !quiet {
-                    println!("No checkpoints found for project '{}'.", project);
-                    println!();
-                    println!("Checkpoints are created automatically before code generation.");
-                    println!("You can also create a manual checkpoint with:");
-                    println!(
-                        "  demiarch checkpoints create --project {} --description \"Before my changes\"",
-                        project
-                    );
-                }
+        DbAction::Verify {
+            repair,
+            public_key,
+            skip_files,
+        } => {
+            let mut options = integrity::VerifyOptions {
+                skip_files,
+                ..Default::default()
+            };
+            if let Some(key) = public_key {
+                options = options.with_verifying_key_hex(&key)?;
+            }
+            let report = integrity::verify(db, &options).await?;
+            let outcome = if repair && report.repairable() > 0 {
+                Some(integrity::repair(db, &report).await?)
             } else {
-                if !quiet {
-                    println!("Checkpoints for project '{}':", &project[..8]);
-                    println!();
-                }
-                for cp in checkpoints {
-                    let feature_info = cp
-                        .feature_id
-                        .map(|f| format!(" [feature: {}]", &f.to_string()[..8]))
-                        .unwrap_or_default();
+                None
+            };
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "report": report,
+                        "repair": outcome,
+                    }))?
+                );
+            } else if !quiet {
+                print_integrity_report(&report);
+                if let Some(outcome) = &outcome {
                     println!(
-                        "  {} - {} ({}){}",
-                        &cp.id.to_string()[..8],
-                        cp.description,
-                        cp.display_size(),
-                        feature_info
+                        "\n{} Repaired: {} references cleared, {} rows deleted",
+                        glyphs::check(),
+                        outcome.cleared,
+                        outcome.deleted
                     );
+                } else if report.repairable() > 0 {
                     println!(
-                        "       Created: {}",
-                        cp.created_at.format("%Y-%m-%d %H:%M:%S")
+                        "\n{} issue(s) can be fixed with `demiarch db verify --repair`.",
+                        report.repairable()
                     );
                 }
             }
-        }
-
-        CheckpointAction::Stats { project } => {
-            let project_id = uuid::Uuid::parse_str(&project)
-                .map_err(|_| anyhow::anyhow!("Invalid project ID: {}", project))?;
-
-            let stats = checkpoint::get_checkpoint_stats(project_id)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-
-            if !quiet {
-                println!("Checkpoint Statistics:");
-                println!("  Project: {}", &project[..8]);
-                println!("  Total checkpoints: {}", stats.total_count);
-                println!("  Total size: {}", stats.display_total_size());
-                if let Some(oldest) = stats.oldest_checkpoint {
-                    println!("  Oldest: {}", oldest.format("%Y-%m-%d %H:%M:%S"));
-                }
-                if let Some(newest) = stats.newest_checkpoint {
-                    println!("  Newest: {}", newest.format("%Y-%m-%d %H:%M:%S"));
-                }
-            }
-        }
-
-        CheckpointAction::Create {
-            project,
-            description,
-            feature,
-        } => {
-            let project_id = uuid::Uuid::parse_str(&project)
-                .map_err(|_| anyhow::anyhow!("Invalid project ID: {}", project))?;
-
-            let feature_id = feature
-                .map(|f| uuid::Uuid::parse_str(&f))
-                .transpose()
-                .map_err(|_| anyhow::anyhow!("Invalid feature ID"))?;
-
-            if !quiet {
-                println!("Creating checkpoint...");
-            }
-
-            let cp = checkpoint::create_checkpoint(project_id, description, feature_id)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
 
-            if !quiet {
-                println!("Checkpoint created successfully!");
-                println!("  ID: {}", cp.id);
-                println!("  Description: {}", cp.description);
-                println!("  Size: {}", cp.display_size());
+            let unresolved = if outcome.is_some() {
+                report.issues.len() - report.repairable()
+            } else {
+                report.issues.len()
+            };
+            if unresolved > 0 {
+                std::process::exit(1);
             }
+            Ok(())
         }
+        DbAction::Stats { compact } => {
+            let store = ContentStore::new(db.pool());
+            let compacted = if compact {
+                let moved = store.compact().await?;
+                let pruned = store.prune().await?;
+                Some((moved, pruned))
+            } else {
+                None
+            };
+            let stats = store.stats().await?;
 
-        CheckpointAction::Restore { id, force } => {
-            let checkpoint_id = uuid::Uuid::parse_str(&id)
-                .map_err(|_| anyhow::anyhow!("Invalid checkpoint ID: {}", id))?;
-
-            if !force && !quiet {
+            if json {
                 println!(
-                    "Warning: This will restore project state to checkpoint '{}'.",
-                    &id[..8]
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "content": stats,
+                        "dedup_savings_bytes": stats.dedup_savings(),
+                        "compression_savings_bytes": stats.compression_savings(),
+                        "compacted": compacted.as_ref().map(|(moved, pruned)| serde_json::json!({
+                            "artifacts": moved.artifacts,
+                            "checkpoints": moved.checkpoints,
+                            "pruned_blobs": pruned,
+                        })),
+                    }))?
                 );
-                println!("Current project state will be backed up automatically.");
-                println!("Use --force to skip this confirmation.");
                 return Ok(());
             }
-
-            if !quiet {
-                println!("Restoring checkpoint '{}'...", &id[..8]);
+            if quiet {
+                return Ok(());
             }
 
-            let result = checkpoint::restore_checkpoint(checkpoint_id)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-
-            if !quiet {
-                println!();
-                println!("Checkpoint restored successfully!");
-                println!();
+            let mb = |bytes: i64| bytes as f64 / (1024.0 * 1024.0);
+            if let Some((moved, pruned)) = &compacted {
                 println!(
-                    "  Restored to: {}",
-                    result.checkpoint_timestamp.format("%Y-%m-%d %H:%M:%S")
+                    "{} Moved {} artifact(s) and {} checkpoint(s) into the store, pruned {} unused blob(s)\n",
+                    glyphs::check(),
+                    moved.artifacts,
+                    moved.checkpoints,
+                    pruned
                 );
-                println!("  Description: {}", result.checkpoint_description);
+            }
+            println!("Content store");
+            println!(
+                "  Stored:       {} blob(s) for {} row(s)",
+                stats.blobs, stats.references
+            );
+            println!("  Content:      {:.2} MB", mb(stats.logical_bytes));
+            println!(
+                "  Deduplicated: {:.2} MB (saves {:.2} MB)",
+                mb(stats.unique_bytes),
+                mb(stats.dedup_savings())
+            );
+            println!(
+                "  Compressed:   {:.2} MB (saves {:.2} MB)",
+                mb(stats.stored_bytes),
+                mb(stats.compression_savings())
+            );
+            println!("  Ratio:        {:.1}%", stats.ratio() * 100.0);
+            if stats.inline_rows > 0 || stats.unreferenced_blobs > 0 {
                 println!();
-                println!("  Phases restored: {}", result.phases_restored);
-                println!("  Features restored: {}", result.features_restored);
-                println!("  Messages restored: {}", result.messages_restored);
-                if result.files_restored > 0 {
-                    println!("  Files restored: {}", result.files_restored);
+                if stats.inline_rows > 0 {
+                    println!(
+                        "  {} row(s) still inline ({:.2} MB)",
+                        stats.inline_rows,
+                        mb(stats.inline_bytes)
+                    );
                 }
-                println!();
-                println!(
-                    "  Safety backup created: {}",
-                    &result.safety_backup_id.to_string()[..8]
-                );
-                println!();
-                println!("To undo this restore, run:");
-                println!(
-                    "  demiarch checkpoints restore {} --force",
-                    &result.safety_backup_id.to_string()[..8]
-                );
-            }
-        }
-
-        CheckpointAction::Verify { id } => {
-            let checkpoint_id = uuid::Uuid::parse_str(&id)
-                .map_err(|_| anyhow::anyhow!("Invalid checkpoint ID: {}", id))?;
-
-            let is_valid = checkpoint::verify_checkpoint(checkpoint_id)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-
-            if !quiet {
-                if is_valid {
-                    println!("[OK] Checkpoint '{}' signature is valid.", &id[..8]);
-                } else {
+                if stats.unreferenced_blobs > 0 {
                     println!(
-                        "[!!] Checkpoint '{}' signature verification FAILED.",
-                        &id[..8]
+                        "  {} unused blob(s) ({:.2} MB)",
+                        stats.unreferenced_blobs,
+                        mb(stats.unreferenced_bytes)
                     );
-                    println!("     The checkpoint data may have been corrupted or tampered with.");
                 }
+                println!("Run `demiarch db stats --compact` to reclaim them.");
             }
+            Ok(())
         }
+        DbAction::Schema { table } => {
+            let tables = match table {
+                Some(table) => vec![export::describe_table(db, &table).await?],
+                None => export::list_tables(db).await?,
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tables)?);
+            } else if !quiet {
+                for table in &tables {
+                    println!("{} ({} rows)", table.name, table.rows);
+                    for column in &table.columns {
+                        println!(
+                            "  {:<28} {:<12}{}{}",
+                            column.name,
+                            column.declared_type,
+                            if column.primary_key {
+                                " primary key"
+                            } else {
+                                ""
+                            },
+                            if column.nullable { "" } else { " not null" }
+                        );
+                    }
+                }
+            }
+            Ok(())
+        }
+        DbAction::Export {
+            table,
+            output,
+            columns,
+            since,
+            until,
+            date_column,
+        } => {
+            let file_format = match format {
+                OutputFormat::Csv => export::ExportFormat::Csv,
+                OutputFormat::Parquet => export::ExportFormat::Parquet,
+                // Without an explicit format, go by the file extension
+                OutputFormat::Text | OutputFormat::Json => {
+                    match output.as_ref().and_then(|p| p.extension()) {
+                        Some(ext) if ext.eq_ignore_ascii_case("parquet") => {
+                            export::ExportFormat::Parquet
+                        }
+                        _ => export::ExportFormat::Csv,
+                    }
+                }
+            };
+            let mut request = export::TableExport::new(&table, file_format)
+                .with_columns(columns)
+                .with_range(since, until);
+            if let Some(column) = date_column {
+                request = request.with_date_column(column);
+            }
 
-        CheckpointAction::Delete { id } => {
-            let checkpoint_id = uuid::Uuid::parse_str(&id)
-                .map_err(|_| anyhow::anyhow!("Invalid checkpoint ID: {}", id))?;
-
-            let deleted = checkpoint::delete_checkpoint(checkpoint_id)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-
+            let rows = match &output {
+                Some(path) => {
+                    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+                    export::export_table(db, &request, file).await?
+                }
+                None if file_format == export::ExportFormat::Parquet => {
+                    anyhow::bail!("Parquet export needs --output <file>");
+                }
+                None => export::export_table(db, &request, io::stdout()).await?,
+            };
+            // Progress goes to stderr so stdout stays a clean CSV stream
             if !quiet {
-                if deleted {
-                    println!("Checkpoint '{}' deleted.", &id[..8]);
-                } else {
-                    println!("Checkpoint '{}' not found.", &id[..8]);
+                match &output {
+                    Some(path) => eprintln!(
+                        "{} Exported {} rows from {} to {}",
+                        glyphs::check(),
+                        rows,
+                        export::resolve_table(&table),
+                        path.display()
+                    ),
+                    None => eprintln!("Exported {} rows", rows),
                 }
             }
+            Ok(())
         }
+        DbAction::SlowQueries { limit, clear } => cmd_db_slow_queries(limit, clear, quiet, json),
+    }
+}
 
-        CheckpointAction::DeleteAll { project, force } => {
-            let project_id = uuid::Uuid::parse_str(&project)
-                .map_err(|_| anyhow::anyhow!("Invalid project ID: {}", project))?;
+fn cmd_db_slow_queries(limit: usize, clear: bool, quiet: bool, json: bool) -> anyhow::Result<()> {
+    let path = storage::instrumentation::slow_query_log_path();
+    let entries = storage::instrumentation::read_slow_queries(&path)?;
+    let mut hotspots = storage::instrumentation::hotspots(&entries);
+    hotspots.truncate(limit);
 
-            if !force && !quiet {
+    if json {
+        println!("{}", serde_json::to_string_pretty(&hotspots)?);
+    } else if !quiet {
+        if hotspots.is_empty() {
+            println!("No slow queries logged.");
+            if !Config::load().is_ok_and(|c| c.database.instrument) {
+                println!("Query timing is off. To enable it:");
+                println!("  demiarch config set database.instrument true");
+            }
+        } else {
+            println!(
+                "{:>6} {:>10} {:>10} {:>10} {:>8}  SQL",
+                "Calls", "Total ms", "Avg ms", "Max ms", "Rows"
+            );
+            for stat in &hotspots {
                 println!(
-                    "Warning: This will delete ALL checkpoints for project '{}'.",
-                    &project[..8]
+                    "{:>6} {:>10.1} {:>10.1} {:>10.1} {:>8}  {}",
+                    stat.calls,
+                    stat.total_us as f64 / 1000.0,
+                    stat.avg_us() as f64 / 1000.0,
+                    stat.max_us as f64 / 1000.0,
+                    stat.rows,
+                    truncate_str(&stat.sql, 100)
                 );
-                println!("Use --force to confirm deletion.");
-                return Ok(());
             }
+            println!(
+                "\n{} slow statement(s) logged in {}",
+                entries.len(),
+                path.display()
+            );
+        }
+    }
 
-            let deleted = checkpoint::delete_all_checkpoints(project_id)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
+    if clear && path.exists() {
+        std::fs::remove_file(&path)?;
+        if !quiet && !json {
+            println!("Cleared the slow-query log.");
+        }
+    }
+    Ok(())
+}
 
-            if !quiet {
-                println!(
-                    "Deleted {} checkpoint(s) for project '{}'.",
-                    deleted,
-                    &project[..8]
-                );
+fn print_integrity_report(report: &integrity::IntegrityReport) {
+    for check in &report.checks {
+        let mark = if check.issues == 0 { "[OK]" } else { "[!!]" };
+        println!("{} {} ({} checked)", mark, check.name, check.rows);
+        let shown = integrity::issues_for(report, &check.name);
+        for issue in &shown {
+            match &issue.record {
+                Some(record) => println!("     {}: {}", record, issue.detail),
+                None => println!("     {}", issue.detail),
             }
         }
+        if (shown.len() as i64) < check.issues {
+            println!("     ... and {} more", check.issues - shown.len() as i64);
+        }
+    }
+    if !report.signatures_verified {
+        println!("\nCheckpoint signatures were checked for format only; pass --public-key to verify them.");
+    }
+    if report.is_clean() {
+        println!("\n{} No integrity issues found.", glyphs::check());
+    } else {
+        println!("\n{} issue(s) found.", report.issues.len());
     }
-    Ok(())
 }
 
-fn cmd_config(action: ConfigAction, quiet: bool) -> anyhow::Result<()> {
+async fn cmd_self(action: SelfAction, quiet: bool, json: bool) -> anyhow::Result<()> {
     match action {
-        ConfigAction::Get { key } => {
+        SelfAction::Update { channel, check } => {
             let config = Config::load()?;
-            let value = config.get(&key)?;
-            println!("{}", value);
-        }
-        ConfigAction::Set { key, value } => {
-            let mut config = Config::load()?;
-            config.set(&key, &value)?;
-            config.save()?;
-            if !quiet {
-                println!("Set {} = {}", key, value);
+            let channel: update::Channel = channel
+                .as_deref()
+                .unwrap_or(&config.updates.channel)
+                .parse()?;
+            let updater = update::Updater::new(&config.updates);
+
+            if check {
+                let status = updater.check(channel).await?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&status)?);
+                } else if !quiet {
+                    if status.update_available {
+                        println!(
+                            "Update available on {}: v{} -> v{}",
+                            channel, status.current_version, status.latest_version
+                        );
+                        if let Some(notes) = &status.notes {
+                            println!("\n{}", notes);
+                        }
+                        println!("\nRun `demiarch self update` to install it.");
+                    } else {
+                        println!(
+                            "{} demiarch v{} is the latest {} release",
+                            glyphs::check(),
+                            status.current_version,
+                            channel
+                        );
+                    }
+                }
+                return Ok(());
             }
-        }
-        ConfigAction::List => {
-            let config = Config::load()?;
-            let items = config.list()?;
-            for (key, value) in items {
-                println!("{} = {}", key, value);
+
+            if !quiet && !json {
+                println!("Checking the {} channel...", channel);
             }
-        }
-        ConfigAction::Reset => {
-            Config::reset()?;
-            if !quiet {
-                println!("Configuration reset to defaults.");
+            match updater.install(channel).await? {
+                Some(status) => {
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&status)?);
+                    } else if !quiet {
+                        println!(
+                            "{} Updated demiarch v{} -> v{}",
+                            glyphs::check(),
+                            status.current_version,
+                            status.latest_version
+                        );
+                    }
+                }
+                None => {
+                    if json {
+                        println!("null");
+                    } else if !quiet {
+                        println!(
+                            "{} demiarch v{} is already up to date",
+                            glyphs::check(),
+                            update::CURRENT_VERSION
+                        );
+                    }
+                }
             }
         }
-        ConfigAction::Path => {
-            let path = Config::config_path()?;
-            println!("{}", path.display());
-        }
     }
     Ok(())
 }
@@ -2738,13 +9506,17 @@ async fn cmd_doctor(quiet: bool) -> anyhow::Result<()> {
 
     // Check database
     if !quiet {
-        match DatabaseManager::new().await {
-            Ok(manager) => {
-                let db = manager.global();
+        let manager = DatabaseManager::new();
+        match manager.global().await {
+            Ok(db) => {
                 match db.health_check().await {
                     Ok(()) => {
                         println!("[OK] Database: Connected");
                         println!("     Path: {}", db.path().display());
+                        println!(
+                            "     Opened in: {:.1} ms",
+                            manager.cache_metrics().last_open_us as f64 / 1000.0
+                        );
 
                         // Check migration status
                         match db.migration_status().await {
@@ -2766,6 +9538,22 @@ async fn cmd_doctor(quiet: bool) -> anyhow::Result<()> {
                         // Show project count
                         let projects = project::list_with_db(db, None).await.unwrap_or_default();
                         println!("     Projects: {}", projects.len());
+
+                        // Opening the database replays the write queue, so
+                        // anything left failed again
+                        if let Some(queue) = storage::WriteQueue::for_database(db) {
+                            match queue.depth() {
+                                0 => println!("[OK] Write queue: Empty"),
+                                depth => {
+                                    all_ok = false;
+                                    println!(
+                                        "[!!] Write queue: {} cost/event write(s) waiting for the database",
+                                        depth
+                                    );
+                                    println!("     Path: {}", queue.path().display());
+                                }
+                            }
+                        }
                     }
                     Err(e) => {
                         all_ok = false;
@@ -2780,6 +9568,23 @@ async fn cmd_doctor(quiet: bool) -> anyhow::Result<()> {
         }
     }
 
+    // Passive update notice; silent when offline or the check fails
+    if !quiet {
+        if let Ok(config) = Config::load() {
+            if config.updates.notify {
+                if let Ok(channel) = config.updates.channel.parse::<update::Channel>() {
+                    let updater = update::Updater::new(&config.updates);
+                    if let Some(status) = updater.notice(channel).await {
+                        println!(
+                            "[--] Update: v{} available (demiarch self update)",
+                            status.latest_version
+                        );
+                    }
+                }
+            }
+        }
+    }
+
     // Summary
     if !quiet {
         println!();
@@ -2788,6 +9593,7 @@ async fn cmd_doctor(quiet: bool) -> anyhow::Result<()> {
         } else {
             println!("Some checks failed. See above for details.");
         }
+        println!("Run `demiarch db verify` for a deep scan of the stored data.");
     }
 
     Ok(())
@@ -2824,7 +9630,7 @@ fn cmd_watch(quiet: bool) -> anyhow::Result<()> {
     }
 }
 
-fn cmd_agents(action: AgentAction, quiet: bool) -> anyhow::Result<()> {
+fn cmd_agents(action: AgentAction, quiet: bool, json: bool) -> anyhow::Result<()> {
     use demiarch_core::agents::AgentType;
 
     match action {
@@ -2845,7 +9651,7 @@ fn cmd_agents(action: AgentAction, quiet: bool) -> anyhow::Result<()> {
                 let style = if ascii {
                     NodeStyle::Ascii
                 } else {
-                    NodeStyle::Unicode
+                    glyphs::default_style()
                 };
                 RenderOptions::default()
                     .with_style(style)
@@ -2922,19 +9728,175 @@ fn cmd_agents(action: AgentAction, quiet: bool) -> anyhow::Result<()> {
                 println!("  6. Orchestrator returns complete implementation");
             }
         }
+
+        AgentAction::Events { session, list } => {
+            use demiarch_core::agents::events;
+
+            if list {
+                let sessions = events::list_event_sessions();
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&sessions)?);
+                } else if sessions.is_empty() {
+                    if !quiet {
+                        println!("No agent events recorded.");
+                    }
+                } else {
+                    for s in sessions {
+                        println!(
+                            "{}  {:<8}  {}",
+                            s.session_id,
+                            if s.archived { "archived" } else { "current" },
+                            s.last_event_at
+                                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                                .unwrap_or_default()
+                        );
+                    }
+                }
+                return Ok(());
+            }
+
+            let session_id = session
+                .map(|id| Uuid::parse_str(&id))
+                .transpose()
+                .map_err(|_| anyhow::anyhow!("Invalid session ID"))?;
+            let events = events::read_events_for(session_id);
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&events)?);
+            } else if events.is_empty() {
+                if !quiet {
+                    println!("No events for this session.");
+                }
+            } else {
+                if !quiet {
+                    println!("Session {}", events[0].session_id);
+                    println!();
+                }
+                for event in &events {
+                    let subject = if event.agent.name.is_empty() {
+                        &event.agent.id
+                    } else {
+                        &event.agent.name
+                    };
+                    let detail = event
+                        .file
+                        .as_ref()
+                        .map(|f| f.path.clone())
+                        .or_else(|| event.agent.error.clone())
+                        .or_else(|| event.agent.task.clone())
+                        .unwrap_or_default();
+                    println!(
+                        "{}  {}  {}  {}",
+                        event.timestamp.format("%H:%M:%S"),
+                        glyphs::agent_event(&event.event_type),
+                        subject,
+                        detail
+                    );
+                }
+            }
+        }
+
+        AgentAction::Export {
+            session,
+            format,
+            output,
+        } => {
+            use demiarch_core::agents::events;
+            use demiarch_core::visualization::{export_tree, ExportFormat};
+
+            let format = ExportFormat::parse(&format)
+                .ok_or_else(|| anyhow::anyhow!("Invalid format: {}. Use: svg, html", format))?;
+            let session_id = session
+                .map(|id| Uuid::parse_str(&id))
+                .transpose()
+                .map_err(|_| anyhow::anyhow!("Invalid session ID"))?;
+
+            let events = events::read_events_for(session_id);
+            let Some(first) = events.first() else {
+                anyhow::bail!("No agent events recorded for this session");
+            };
+            let session_id = first.session_id;
+
+            let tree = TreeBuilder::build_from_events(&events);
+            let title = format!(
+                "Agent hierarchy - session {} ({})",
+                &session_id.to_string()[..8],
+                first.timestamp.format("%Y-%m-%d %H:%M")
+            );
+            let path = output.unwrap_or_else(|| {
+                std::path::PathBuf::from(format!(
+                    "agents-{}.{}",
+                    &session_id.to_string()[..8],
+                    format.extension()
+                ))
+            });
+            std::fs::write(&path, export_tree(&tree, &title, format))?;
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "session_id": session_id,
+                        "path": path,
+                        "agents": tree.count(),
+                    })
+                );
+            } else if !quiet {
+                println!("Exported {} agents to {}", tree.count(), path.display());
+            }
+        }
+
+        AgentAction::Timeline {
+            session,
+            format,
+            output,
+            width,
+        } => {
+            use demiarch_core::agents::events;
+            use demiarch_core::visualization::Timeline;
+
+            let session_id = session
+                .map(|id| Uuid::parse_str(&id))
+                .transpose()
+                .map_err(|_| anyhow::anyhow!("Invalid session ID"))?;
+            let events = events::read_events_for(session_id);
+            let Some(first) = events.first() else {
+                anyhow::bail!("No agent events recorded for this session");
+            };
+            let title = format!(
+                "Agent timeline - session {}",
+                &first.session_id.to_string()[..8]
+            );
+            let timeline = Timeline::from_events(&events);
+
+            let rendered = match format.to_lowercase().as_str() {
+                "text" => timeline.render_text(width),
+                "svg" => timeline.render_svg(&title),
+                other => anyhow::bail!("Invalid format: {}. Use: text, svg", other),
+            };
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, rendered)?;
+                    if !quiet {
+                        println!("Wrote timeline to {}", path.display());
+                    }
+                }
+                None => {
+                    if !quiet {
+                        println!("{}", title);
+                        println!();
+                    }
+                    print!("{}", rendered);
+                }
+            }
+        }
     }
     Ok(())
 }
 
 fn format_agent_type(agent_type: demiarch_core::agents::AgentType) -> String {
-    use demiarch_core::agents::AgentType;
-    match agent_type {
-        AgentType::Orchestrator => "🎭 Orchestrator".to_string(),
-        AgentType::Planner => "📋 Planner".to_string(),
-        AgentType::Coder => "💻 Coder".to_string(),
-        AgentType::Reviewer => "🔍 Reviewer".to_string(),
-        AgentType::Tester => "🧪 Tester".to_string(),
-    }
+    glyphs::agent_type_label(agent_type)
 }
 
 // ============================================================================
@@ -2969,12 +9931,7 @@ async fn cmd_sessions(db: &Database, action: SessionAction, quiet: bool) -> anyh
                     println!();
                 }
                 for s in sessions {
-                    let status_icon = match s.status {
-                        SessionStatus::Active => "🟢",
-                        SessionStatus::Paused => "⏸️ ",
-                        SessionStatus::Completed => "✅",
-                        SessionStatus::Abandoned => "❌",
-                    };
+                    let status_icon = glyphs::session_status(s.status);
                     let desc = s.description.as_deref().unwrap_or("(no description)");
                     let age = format_duration(chrono::Utc::now() - s.created_at);
                     println!(
@@ -3148,7 +10105,7 @@ async fn cmd_sessions(db: &Database, action: SessionAction, quiet: bool) -> anyh
             println!("  Paused:    {}", stats.paused);
             println!("  Completed: {}", stats.completed);
             println!("  Abandoned: {}", stats.abandoned);
-            println!("  ─────────────");
+            println!("  {}", glyphs::rule(13));
             println!("  Total:     {}", stats.total);
         }
 
@@ -3179,6 +10136,40 @@ async fn cmd_sessions(db: &Database, action: SessionAction, quiet: bool) -> anyh
             }
         }
 
+        SessionAction::Report {
+            id,
+            output,
+            report_format,
+        } => {
+            let session_id = parse_session_id(&manager, &id).await?;
+            let report = report::SessionReport::build(db, session_id).await?;
+
+            let format = match report_format {
+                Some(name) => report::ReportFormat::parse(&name).ok_or_else(|| {
+                    anyhow::anyhow!("Invalid report format: {}. Use: markdown, html, json", name)
+                })?,
+                None => output
+                    .as_deref()
+                    .map(report::ReportFormat::from_path)
+                    .unwrap_or_default(),
+            };
+            let rendered = report.render(format)?;
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, rendered)?;
+                    if !quiet {
+                        println!(
+                            "{} Session report written to {}",
+                            glyphs::check(),
+                            path.display()
+                        );
+                    }
+                }
+                None => println!("{}", rendered),
+            }
+        }
+
         SessionAction::Cleanup {
             days,
             events,
@@ -3238,31 +10229,200 @@ async fn cmd_sessions(db: &Database, action: SessionAction, quiet: bool) -> anyh
             };
 
             if !quiet {
-                if let Some(session_id) = result.session_id {
-                    let action = if abandon { "abandoned" } else { "completed" };
-                    println!("Session {} {}", &session_id.to_string()[..8], action);
-                } else {
-                    println!("No active session to end.");
+                if let Some(session_id) = result.session_id {
+                    let action = if abandon { "abandoned" } else { "completed" };
+                    println!("Session {} {}", &session_id.to_string()[..8], action);
+                } else {
+                    println!("No active session to end.");
+                }
+
+                if result.locks_released > 0 {
+                    println!("  Released {} locks", result.locks_released);
+                }
+
+                if result.sessions_cleaned > 0 || result.events_cleaned > 0 {
+                    println!(
+                        "  Cleaned up {} sessions, {} events",
+                        result.sessions_cleaned, result.events_cleaned
+                    );
+                }
+
+                if result.has_warnings() {
+                    println!();
+                    println!("Warnings:");
+                    for warning in &result.warnings {
+                        println!("  - {}", warning);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a session ID, supporting both full UUIDs and short prefixes
+async fn parse_session_id(manager: &SessionManager, id: &str) -> anyhow::Result<Uuid> {
+    // Try to parse as full UUID first
+    if let Ok(uuid) = Uuid::parse_str(id) {
+        return Ok(uuid);
+    }
+
+    // Try to find by prefix
+    let sessions = manager.list(Some(100)).await?;
+    let matches: Vec<_> = sessions
+        .iter()
+        .filter(|s| s.id.to_string().starts_with(id))
+        .collect();
+
+    match matches.len() {
+        0 => Err(anyhow::anyhow!("No session found matching '{}'", id)),
+        1 => Ok(matches[0].id),
+        _ => Err(anyhow::anyhow!(
+            "Ambiguous session ID '{}' matches {} sessions. Use a longer prefix.",
+            id,
+            matches.len()
+        )),
+    }
+}
+
+// ============================================================================
+// Open Command
+// ============================================================================
+
+async fn cmd_open(
+    db: &Database,
+    kind: open::OpenKind,
+    id: &str,
+    in_editor: bool,
+    in_app: bool,
+    print: bool,
+    quiet: bool,
+) -> anyhow::Result<()> {
+    if in_editor && kind != open::OpenKind::Project {
+        return Err(anyhow::anyhow!("--editor only applies to projects"));
+    }
+
+    let resolved = open::resolve(db, kind, id, in_app).await?;
+    if print {
+        println!("{}", resolved.target);
+        return Ok(());
+    }
+
+    if !quiet {
+        println!(
+            "Opening {} '{}' ({})",
+            resolved.kind.as_str(),
+            resolved.label,
+            resolved.target
+        );
+    }
+    open::launch(&resolved.target, in_editor)?;
+    Ok(())
+}
+
+async fn cmd_inbox(
+    db: &Database,
+    project: Option<&str>,
+    quiet: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let project = resolve_project(db, project).await?;
+    let queue = inbox::review_queue(db, &project.id, &[]).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&queue)?);
+        return Ok(());
+    }
+
+    if queue.items.is_empty() {
+        if !quiet {
+            println!(
+                "{} Nothing awaiting review in {}",
+                glyphs::check(),
+                queue.project_name
+            );
+        }
+        return Ok(());
+    }
+    if !quiet {
+        println!(
+            "{} item(s) awaiting review in {}",
+            queue.total, queue.project_name
+        );
+    }
+    let mut current = None;
+    for item in &queue.items {
+        if current != Some(item.kind) {
+            current = Some(item.kind);
+            let count = queue.items.iter().filter(|i| i.kind == item.kind).count();
+            println!();
+            println!("{} ({})", item.kind.label(), count);
+        }
+        println!(
+            "  {}  {}  ({})",
+            &item.id[..8.min(item.id.len())],
+            item.title,
+            item.detail
+        );
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Note Commands
+// ============================================================================
+
+async fn cmd_notes(db: &Database, action: NoteAction, quiet: bool) -> anyhow::Result<()> {
+    let manager = SessionManager::new(db.pool().clone());
+
+    match action {
+        NoteAction::Add { content, remind } => {
+            let note = add_session_note(&manager, &content, remind).await?;
+            if !quiet {
+                println!("Note added: {}", &note.id[..8]);
+                if remind {
+                    println!("  The next generation will be reminded of it.");
                 }
+            }
+        }
 
-                if result.locks_released > 0 {
-                    println!("  Released {} locks", result.locks_released);
+        NoteAction::List { session } => {
+            let notes = match session {
+                Some(id) => {
+                    let session_id = parse_session_id(&manager, &id).await?;
+                    manager.notes().list(session_id).await?
                 }
+                None => active_session_notes(&manager).await?,
+            };
 
-                if result.sessions_cleaned > 0 || result.events_cleaned > 0 {
-                    println!(
-                        "  Cleaned up {} sessions, {} events",
-                        result.sessions_cleaned, result.events_cleaned
-                    );
+            if notes.is_empty() {
+                if !quiet {
+                    println!("No notes in this session.");
+                    println!("\nAdd one with: demiarch notes add \"...\"");
                 }
+            } else {
+                print_session_notes(&notes);
+            }
+        }
 
-                if result.has_warnings() {
-                    println!();
-                    println!("Warnings:");
-                    for warning in &result.warnings {
-                        println!("  - {}", warning);
-                    }
+        NoteAction::Remove { id } => {
+            let notes = active_session_notes(&manager).await?;
+            let matches: Vec<_> = notes.iter().filter(|n| n.id.starts_with(&id)).collect();
+            let note = match matches.as_slice() {
+                [note] => note,
+                [] => return Err(anyhow::anyhow!("No note found matching '{}'", id)),
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Ambiguous note ID '{}' matches {} notes. Use a longer prefix.",
+                        id,
+                        matches.len()
+                    ))
                 }
+            };
+
+            manager.notes().delete(&note.id).await?;
+            if !quiet {
+                println!("Note removed: {}", note.content);
             }
         }
     }
@@ -3270,29 +10430,120 @@ async fn cmd_sessions(db: &Database, action: SessionAction, quiet: bool) -> anyh
     Ok(())
 }
 
-/// Parse a session ID, supporting both full UUIDs and short prefixes
-async fn parse_session_id(manager: &SessionManager, id: &str) -> anyhow::Result<Uuid> {
-    // Try to parse as full UUID first
-    if let Ok(uuid) = Uuid::parse_str(id) {
-        return Ok(uuid);
+/// ID of the active session, or an error saying how to start one
+async fn active_session_id(manager: &SessionManager) -> anyhow::Result<Uuid> {
+    manager.get_active().await?.map(|s| s.id).ok_or_else(|| {
+        anyhow::anyhow!("No active session. Start one with: demiarch sessions start")
+    })
+}
+
+/// Notes of the active session, oldest first
+async fn active_session_notes(manager: &SessionManager) -> anyhow::Result<Vec<SessionNote>> {
+    let session_id = active_session_id(manager).await?;
+    Ok(manager.notes().list(session_id).await?)
+}
+
+/// Attach a note to the active session
+async fn add_session_note(
+    manager: &SessionManager,
+    content: &str,
+    remind: bool,
+) -> anyhow::Result<SessionNote> {
+    let session_id = active_session_id(manager).await?;
+    Ok(manager.notes().add(session_id, content, remind).await?)
+}
+
+fn print_session_notes(notes: &[SessionNote]) {
+    for note in notes {
+        let marker = if note.is_pending_reminder() {
+            " [remind]"
+        } else {
+            ""
+        };
+        println!(
+            "  {}  {}  {}{}",
+            &note.id[..8],
+            note.created_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+            note.content,
+            marker
+        );
     }
+}
 
-    // Try to find by prefix
-    let sessions = manager.list(Some(100)).await?;
-    let matches: Vec<_> = sessions
-        .iter()
-        .filter(|s| s.id.to_string().starts_with(id))
-        .collect();
+// ============================================================================
+// Snippet Commands
+// ============================================================================
 
-    match matches.len() {
-        0 => Err(anyhow::anyhow!("No session found matching '{}'", id)),
-        1 => Ok(matches[0].id),
-        _ => Err(anyhow::anyhow!(
-            "Ambiguous session ID '{}' matches {} sessions. Use a longer prefix.",
-            id,
-            matches.len()
-        )),
+async fn cmd_snippets(db: &Database, action: SnippetAction, json: bool) -> anyhow::Result<()> {
+    let library = snippets::SnippetLibrary::load()?;
+    let dir = snippets::snippets_dir()
+        .map(|d| d.display().to_string())
+        .unwrap_or_default();
+
+    match action {
+        SnippetAction::List => {
+            if json {
+                let list: Vec<_> = library
+                    .list()
+                    .map(|s| {
+                        serde_json::json!({
+                            "name": s.name,
+                            "path": s.path,
+                            "summary": s.summary(),
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&list)?);
+            } else if library.is_empty() {
+                println!("No snippets. Add Markdown files to {}", dir);
+            } else {
+                println!("Snippets in {}:", dir);
+                for snippet in library.list() {
+                    println!("  {:<24} {}", snippet.name, snippet.summary());
+                }
+                println!();
+                println!("Reference one as {{{{snippet:<name>}}}} in chat or generate.");
+            }
+        }
+        SnippetAction::Show { name, expand } => {
+            let snippet = library
+                .get(&name)
+                .ok_or_else(|| anyhow::anyhow!("Snippet '{}' not found in {}", name, dir))?;
+            let content = if expand {
+                let current_dir = std::env::current_dir()?;
+                let current = project::find_by_directory(db, &current_dir).await?;
+                expand_snippets(&snippet.content, current.as_ref())?
+            } else {
+                snippet.content.clone()
+            };
+            if json {
+                let value = serde_json::json!({
+                    "name": snippet.name,
+                    "path": snippet.path,
+                    "content": content,
+                });
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                print!("{}", content);
+                if !content.ends_with('\n') {
+                    println!();
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Expand `{{snippet:name}}` references and template variables before text
+/// is sent to the LLM
+fn expand_snippets(text: &str, project: Option<&project::Project>) -> anyhow::Result<String> {
+    if !text.contains("{{") {
+        return Ok(text.to_string());
     }
+    let library = snippets::SnippetLibrary::load()?;
+    Ok(library.expand(text, &snippets::builtin_variables(project))?)
 }
 
 /// Format a duration in human-readable form
@@ -3474,11 +10725,182 @@ async fn cmd_graph(db: &Database, action: GraphAction, quiet: bool) -> anyhow::R
                 }
             }
         }
+
+        GraphAction::Merge {
+            keep_id,
+            duplicate_id,
+        } => {
+            let merge = graph::merge_entities(pool, &keep_id, &duplicate_id).await?;
+
+            if !quiet {
+                println!(
+                    "Merged {} into {} ({}).",
+                    duplicate_id, merge.entity.name, merge.entity.id
+                );
+                println!(
+                    "  Relationships: {} moved, {} combined, {} dropped",
+                    merge.relationships_moved,
+                    merge.relationships_combined,
+                    merge.relationships_dropped
+                );
+                println!("  Skill links moved: {}", merge.skill_links_moved);
+                println!("  Confidence: {:.0}%", merge.entity.confidence * 100.0);
+                if !merge.aliases_recorded.is_empty() {
+                    println!("  Aliases: {}", merge.aliases_recorded.join(", "));
+                }
+            }
+        }
+
+        GraphAction::Alias { entity_id, alias } => {
+            let entity = graph::add_alias(pool, &entity_id, &alias).await?;
+
+            if !quiet {
+                println!(
+                    "'{}' now resolves to {} ({}).",
+                    alias, entity.name, entity.id
+                );
+            }
+        }
+
+        GraphAction::Duplicates { threshold, limit } => {
+            let candidates = graph::find_duplicate_candidates(pool, threshold).await?;
+
+            if candidates.is_empty() {
+                if !quiet {
+                    println!("No duplicate candidates found.");
+                }
+            } else {
+                if !quiet {
+                    println!("Found {} duplicate candidates:", candidates.len());
+                    println!();
+                }
+                for candidate in candidates.iter().take(limit) {
+                    println!(
+                        "  {:.0}%  {} ({}) <- {} ({})  [{}]",
+                        candidate.similarity * 100.0,
+                        candidate.keep.name,
+                        candidate.keep.id,
+                        candidate.duplicate.name,
+                        candidate.duplicate.id,
+                        candidate.reason
+                    );
+                }
+                if !quiet {
+                    if candidates.len() > limit {
+                        println!();
+                        println!("  ... and {} more", candidates.len() - limit);
+                    }
+                    println!();
+                    println!("Merge a pair with: demiarch graph merge <keep-id> <duplicate-id>");
+                }
+            }
+        }
+
+        GraphAction::Related { description, limit } => {
+            let work = related::find_related(db, &description, None, limit).await?;
+
+            if work.is_empty() {
+                if !quiet {
+                    println!("No related prior work found.");
+                }
+                return Ok(());
+            }
+
+            if !quiet && !work.concepts.is_empty() {
+                println!("Concepts: {}", work.concepts.join(", "));
+                println!();
+            }
+            if !work.features.is_empty() {
+                if !quiet {
+                    println!("Similar features:");
+                }
+                for feature in &work.features {
+                    let patterns = if feature.patterns.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" using {}", feature.patterns.join(", "))
+                    };
+                    println!(
+                        "  {} [{}] in {} ({:.0}% match){}",
+                        feature.title,
+                        feature.status,
+                        feature.project_name,
+                        feature.score * 100.0,
+                        patterns
+                    );
+                }
+            }
+            if !work.skills.is_empty() {
+                if !quiet {
+                    if !work.features.is_empty() {
+                        println!();
+                    }
+                    println!("Related skills:");
+                }
+                for skill in &work.skills {
+                    let project = skill
+                        .project_name
+                        .as_ref()
+                        .map(|p| format!(" from {}", p))
+                        .unwrap_or_default();
+                    println!(
+                        "  {} ({}){} via {}",
+                        skill.name,
+                        skill.pattern_type,
+                        project,
+                        skill.via.join(", ")
+                    );
+                }
+            }
+        }
+
+        GraphAction::Age { dry_run } => {
+            let config = Config::load()?;
+            let decay = DecayModel::new(config.knowledge.half_life_days);
+            if !decay.is_enabled() {
+                if !quiet {
+                    println!("Knowledge decay is off (knowledge.half_life_days = 0).");
+                }
+                return Ok(());
+            }
+
+            let report =
+                graph::age_knowledge(pool, decay, config.knowledge.archive_below, dry_run).await?;
+            if !quiet {
+                if dry_run {
+                    println!(
+                        "Dry run (half-life {} days, archive below {:.0}%):",
+                        decay.half_life_days(),
+                        config.knowledge.archive_below * 100.0
+                    );
+                    println!();
+                }
+                print!("{}", graph::format_aging_report(&report));
+            }
+        }
     }
 
     Ok(())
 }
 
+/// Parse a repeat interval for a recurring job (`12h`, `1d`, or `once`/`0`)
+fn parse_interval_hours(s: &str) -> anyhow::Result<u32> {
+    let s = s.trim().to_lowercase();
+    if s == "once" {
+        return Ok(0);
+    }
+    let (amount, per_unit) = if let Some(days) = s.strip_suffix('d') {
+        (days, 24)
+    } else {
+        (s.strip_suffix('h').unwrap_or(&s), 1)
+    };
+    amount
+        .parse::<u32>()
+        .ok()
+        .and_then(|n| n.checked_mul(per_unit))
+        .ok_or_else(|| anyhow::anyhow!("Invalid interval '{}'. Use 12h, 1d or once.", s))
+}
+
 /// Parse entity type from string
 fn parse_entity_type(s: &str) -> anyhow::Result<EntityType> {
     EntityType::parse(s).ok_or_else(|| {
@@ -3499,11 +10921,287 @@ fn truncate_str(s: &str, max_len: usize) -> String {
     }
 }
 
+/// The page a list command asked for, or `None` to list everything
+fn page_request(limit: Option<usize>, offset: usize) -> Option<PageRequest> {
+    (limit.is_some() || offset > 0).then(|| PageRequest::from_parts(limit, Some(offset)))
+}
+
+/// Buffers list output and shows it through `$PAGER` (default `less -FRX`)
+/// when it would scroll past the terminal
+///
+/// Output that fits, or that isn't going to a terminal, is printed as is.
+/// Setting `PAGER` to an empty string or `cat` turns paging off.
+struct Pager {
+    lines: Vec<String>,
+}
+
+impl Pager {
+    fn new() -> Self {
+        Self { lines: Vec::new() }
+    }
+
+    fn line(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+    }
+
+    /// Say which slice of a longer listing was shown and how to get the next
+    fn page_footer<T>(&mut self, page: &Page<T>) {
+        if page.offset == 0 && !page.has_more() {
+            return;
+        }
+        let from = (page.offset + 1).min(page.total);
+        let to = (page.offset + page.items.len()).min(page.total);
+        self.line(String::new());
+        self.line(t_args(
+            "list-page-range",
+            &[("from", &from), ("to", &to), ("total", &page.total)],
+        ));
+        if let Some(next) = page.next() {
+            self.line(t_args("list-page-next", &[("offset", &next.offset)]));
+        }
+    }
+
+    fn finish(self) {
+        let rows = std::env::var("LINES")
+            .ok()
+            .and_then(|lines| lines.parse::<usize>().ok())
+            .unwrap_or(24);
+        let pager = std::env::var("PAGER").unwrap_or_else(|_| "less -FRX".to_string());
+        let mut command = pager.split_whitespace();
+        if self.lines.len() >= rows && io::stdout().is_terminal() {
+            if let Some(program) = command.next().filter(|p| *p != "cat") {
+                if self.page(program, command).is_ok() {
+                    return;
+                }
+            }
+        }
+        for line in &self.lines {
+            println!("{}", line);
+        }
+    }
+
+    fn page<'a>(&self, program: &str, args: impl Iterator<Item = &'a str>) -> io::Result<()> {
+        let mut child = std::process::Command::new(program)
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            for line in &self.lines {
+                // The user quitting the pager early closes the pipe
+                if writeln!(stdin, "{}", line).is_err() {
+                    break;
+                }
+            }
+        }
+        child.wait()?;
+        Ok(())
+    }
+}
+
 // ============================================================================
 // Image Generation Commands
 // ============================================================================
 
-async fn cmd_image(action: ImageAction, quiet: bool) -> anyhow::Result<()> {
+/// Asks on the terminal before a plugin first uses a permission
+struct TerminalPrompt;
+
+impl PermissionPrompt for TerminalPrompt {
+    fn ask(&self, request: &PermissionRequest) -> PromptAnswer {
+        let action = match request.permission {
+            Permission::ReadFiles => "read",
+            Permission::WriteFiles => "write",
+            Permission::Network => "connect to",
+            Permission::Subprocess => "run",
+        };
+        eprint!(
+            "Plugin '{}' wants to {} {}\n  Allow? [y] once / [a] always / [n] no / [v] never: ",
+            request.plugin_id, action, request.detail
+        );
+        let _ = io::stderr().flush();
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line).is_err() {
+            return PromptAnswer::Deny;
+        }
+        match line.trim().to_ascii_lowercase().as_str() {
+            "y" | "yes" => PromptAnswer::AllowOnce,
+            "a" | "always" => PromptAnswer::AllowAlways,
+            "v" | "never" => PromptAnswer::DenyAlways,
+            _ => PromptAnswer::Deny,
+        }
+    }
+}
+
+async fn cmd_plugins(action: PluginAction, quiet: bool, json: bool) -> anyhow::Result<()> {
+    match action {
+        PluginAction::New {
+            name,
+            capability,
+            path,
+        } => {
+            let capability: Capability = capability.parse()?;
+            let parent = match path {
+                Some(path) => path,
+                None => std::env::current_dir()?,
+            };
+            let root = scaffold(&name, capability, &parent)?;
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "name": name,
+                        "capability": capability.as_str(),
+                        "path": root,
+                    })
+                );
+            } else if !quiet {
+                println!(
+                    "{} Created {} plugin {} in {}",
+                    glyphs::check(),
+                    capability,
+                    name,
+                    root.display()
+                );
+                println!("  cd {} && cargo test", root.display());
+                println!("  cargo build --release --target wasm32-unknown-unknown");
+                println!("  See README.md for installing it into the plugin directory.");
+            }
+        }
+        PluginAction::Run {
+            manifest,
+            wasm,
+            allow_hosts,
+            allow_programs,
+            no_prompt,
+        } => {
+            let db = Database::shared().await?;
+            let tier = license::LicenseManager::new(&db).active_tier().await;
+            let plugin = loader::load_manifest_for_tier(&manifest, tier.into())?;
+            let config = Config::load().unwrap_or_default();
+            let limits = ResourceLimits::from(config.plugins.limits.for_tier(tier));
+            let wasm_path = match wasm {
+                Some(path) => path,
+                None => manifest
+                    .parent()
+                    .map(|dir| dir.join("plugin.wasm"))
+                    .ok_or_else(|| anyhow::anyhow!("Manifest path has no parent directory"))?,
+            };
+            let bytes = loader::load_wasm_bytes(&wasm_path, 32 * 1024 * 1024)?;
+
+            let project_dir = std::env::current_dir()?;
+            let mut runner = SandboxedRunner::new(&project_dir);
+            for program in allow_programs {
+                runner = runner.allow(program);
+            }
+            let prompt: Box<dyn PermissionPrompt> = if no_prompt {
+                Box::new(DenyPrompt)
+            } else {
+                Box::new(TerminalPrompt)
+            };
+            let mut mediator =
+                PermissionMediator::new(&plugin.id, &plugin.permissions, &project_dir)?
+                    .with_grants(GrantStore::open()?)
+                    .with_prompt(prompt)
+                    .with_runner(runner);
+            for host in allow_hosts {
+                mediator = mediator.with_allowed_host(host.to_ascii_lowercase());
+            }
+
+            let sandbox = Sandbox::new(plugin.permissions.clone())?
+                .with_host(mediator)
+                .with_limits(limits)
+                .with_meter(&plugin.id)?;
+            let permissions = plugin.permissions.clone();
+            tokio::task::spawn_blocking(move || sandbox.execute(&bytes, &permissions)).await??;
+            if !quiet {
+                println!("{} Plugin {} finished", glyphs::check(), plugin.name);
+            }
+        }
+        PluginAction::Permissions { plugin } => {
+            let store = GrantStore::open()?;
+            let entries: Vec<_> = store
+                .plugins()
+                .filter(|(id, _)| plugin.as_deref().is_none_or(|p| p == id.as_str()))
+                .collect();
+            if json {
+                let map: std::collections::BTreeMap<_, _> = entries.into_iter().collect();
+                println!("{}", serde_json::to_string_pretty(&map)?);
+            } else if entries.is_empty() {
+                if !quiet {
+                    println!("No permission decisions stored.");
+                }
+            } else {
+                for (id, grants) in entries {
+                    println!("{}", id);
+                    if !grants.allowed.is_empty() {
+                        println!("  allowed: {:?}", grants.allowed);
+                    }
+                    if !grants.denied.is_empty() {
+                        println!("  denied:  {:?}", grants.denied);
+                    }
+                    if !grants.hosts.is_empty() {
+                        println!("  hosts:   {}", grants.hosts.join(", "));
+                    }
+                }
+            }
+        }
+        PluginAction::Revoke { plugin } => {
+            let mut store = GrantStore::open()?;
+            if !store.revoke(&plugin) {
+                anyhow::bail!("No permission decisions stored for {}", plugin);
+            }
+            store.save()?;
+            if !quiet {
+                println!("{} Revoked all grants for {}", glyphs::check(), plugin);
+            }
+        }
+        PluginAction::Stats { plugin } => {
+            let store = StatsStore::open()?;
+            let entries: Vec<_> = store
+                .plugins()
+                .filter(|(id, _)| plugin.as_deref().is_none_or(|p| p == id.as_str()))
+                .collect();
+            if json {
+                let map: std::collections::BTreeMap<_, _> = entries.into_iter().collect();
+                println!("{}", serde_json::to_string_pretty(&map)?);
+            } else if entries.is_empty() {
+                if !quiet {
+                    println!("No plugin executions recorded.");
+                }
+            } else {
+                println!(
+                    "{:<24} {:>6} {:>8} {:>8} {:>8} {:>14} {:>10}",
+                    "PLUGIN", "RUNS", "FAILED", "NO FUEL", "TIMEOUT", "AVG FUEL", "AVG MS"
+                );
+                for (id, stats) in entries {
+                    println!(
+                        "{:<24} {:>6} {:>8} {:>8} {:>8} {:>14} {:>10}",
+                        truncate_str(id, 24),
+                        stats.executions,
+                        stats.failures,
+                        stats.fuel_exhausted,
+                        stats.timeouts,
+                        stats.average_fuel(),
+                        stats.average_duration_ms()
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Record an image operation's cost against the project in the current directory
+async fn record_image_cost(db: &Database, model: Option<&str>) {
+    let project = match std::env::current_dir() {
+        Ok(dir) => project::find_by_directory(db, &dir).await.ok().flatten(),
+        Err(_) => None,
+    };
+    if let Err(e) = image::record_cost(db, project.as_ref().map(|p| p.id.as_str()), model).await {
+        warn!(error = %e, "Failed to record image cost");
+    }
+}
+
+async fn cmd_image(db: &Database, action: ImageAction, quiet: bool) -> anyhow::Result<()> {
     match action {
         ImageAction::Generate {
             prompt,
@@ -3523,10 +11221,10 @@ async fn cmd_image(action: ImageAction, quiet: bool) -> anyhow::Result<()> {
                 println!();
             }
 
+            let cost_model = model.clone();
             let output_path =
-                image::generate(prompt, output, Some(size), style, model, negative, seed)
-                    .await
-                    .map_err(|e| anyhow::anyhow!("{}", e))?;
+                image::generate(prompt, output, Some(size), style, model, negative, seed).await?;
+            record_image_cost(db, cost_model.as_deref()).await;
 
             if !quiet {
                 println!("Image saved to: {}", output_path.display());
@@ -3550,9 +11248,10 @@ async fn cmd_image(action: ImageAction, quiet: bool) -> anyhow::Result<()> {
                 println!();
             }
 
-            let output_path = image::transform(input, prompt, output, Some(strength), model)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            let cost_model = model.clone();
+            let output_path =
+                image::transform(input, prompt, output, Some(strength), model).await?;
+            record_image_cost(db, cost_model.as_deref()).await;
 
             if !quiet {
                 println!("Transformed image saved to: {}", output_path.display());
@@ -3574,9 +11273,9 @@ async fn cmd_image(action: ImageAction, quiet: bool) -> anyhow::Result<()> {
                 println!();
             }
 
-            let output_path = image::upscale(input, scale, output, model)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            let cost_model = model.clone();
+            let output_path = image::upscale(input, scale, output, model).await?;
+            record_image_cost(db, cost_model.as_deref()).await;
 
             if !quiet {
                 println!("Upscaled image saved to: {}", output_path.display());
@@ -3600,9 +11299,9 @@ async fn cmd_image(action: ImageAction, quiet: bool) -> anyhow::Result<()> {
                 println!();
             }
 
-            let output_path = image::inpaint(input, mask, prompt, output, model)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            let cost_model = model.clone();
+            let output_path = image::inpaint(input, mask, prompt, output, model).await?;
+            record_image_cost(db, cost_model.as_deref()).await;
 
             if !quiet {
                 println!("Inpainted image saved to: {}", output_path.display());
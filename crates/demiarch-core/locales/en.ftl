@@ -0,0 +1,137 @@
+# Demiarch English messages
+#
+# This catalog is the fallback for every locale. To translate, copy the
+# messages you want into <config dir>/locales/<locale>.ftl (e.g. de.ftl,
+# pt-BR.ftl) and change the text to the right of "=". Keep { $name }
+# placeables as they are.
+
+## Projects
+
+projects-none = No projects found.
+projects-create-hint = Create one with: demiarch new <name> --framework <framework>
+projects-header = Projects:
+projects-status-archived = [archived]
+projects-status-deleted = [deleted]
+projects-not-found = Project '{ $id }' not found. Run `demiarch projects list` to see all projects.
+projects-archived = Project '{ $id }' archived.
+projects-delete-warning = Warning: This will permanently delete project '{ $id }'.
+projects-delete-confirm-hint = Use --force to confirm deletion.
+projects-deleted-permanently = Project '{ $id }' permanently deleted.
+projects-marked-deleted = Project '{ $id }' marked as deleted.
+
+## Features
+
+features-none = No features found for project '{ $project }'.
+features-create-hint = Create one with: demiarch features create <title>
+features-header = Features for '{ $project }' ({ $id }):
+features-not-found = Feature not found: { $id }
+features-created = Feature created: { $title } ({ $id })
+features-next-generate = Next: Run `demiarch generate "{ $title }"` to generate code.
+features-updated = Feature '{ $id }' updated.
+features-deleted = Feature '{ $id }' deleted.
+features-time-header = Time on '{ $title }' ({ $id }): { $total }
+features-time-none = No time recorded for feature '{ $id }'.
+features-time-summary-header = Time by feature for '{ $project }':
+features-time-summary-none = No time recorded for project '{ $project }'.
+features-estimate-header = Estimate for '{ $title }' ({ $id }), { $confidence } confidence:
+features-estimate-none = No completed generations to estimate feature '{ $id }' from yet.
+features-estimate-similar = Based on:
+features-branch-created = Created branch { $branch } for '{ $title }'.
+features-branch-switched = Switched to branch { $branch } for '{ $title }'.
+features-branch-worktree = Checked out in { $path }
+features-criteria-header = Proposed acceptance criteria for '{ $title }' ({ $id }):
+features-criteria-confirm = Store these criteria? [y/N]
+features-criteria-saved = Stored { $count } acceptance criteria on feature '{ $id }'.
+features-criteria-discarded = Criteria discarded.
+features-criteria-unchanged = Acceptance criteria for feature '{ $id }' are already up to date.
+features-import-preview = Importing into '{ $project }' from { $file }:
+features-import-summary = { $create } to create, { $skip } to skip.
+features-import-confirm = Create these features? [y/N]
+features-import-cancelled = Import cancelled.
+features-import-nothing = No features found in the file.
+features-import-skipped = Skipped
+features-import-done = Created { $created } feature(s), skipped { $skipped }.
+
+## Paged lists
+
+list-page-range = Showing { $from }-{ $to } of { $total }.
+list-page-next = Next page: --offset { $offset }
+
+## Detail labels
+
+label-project = Project
+label-feature = Feature
+label-id = ID
+label-framework = Framework
+label-status = Status
+label-priority = Priority
+label-repository = Repository
+label-description = Description
+label-acceptance-criteria = Acceptance Criteria
+label-labels = Labels
+label-branch = Branch
+label-created = Created
+label-updated = Updated
+label-open-in-app = Open in app
+
+## TUI
+
+tui-title = Demiarch Monitor
+tui-tab-projects = Projects
+tui-tab-agents = Agents
+tui-tab-stats = Stats
+tui-tab-timeline = Timeline
+tui-tab-help = Help
+tui-projects-empty =
+    No active projects
+
+    Projects will appear here during code generation.
+
+    To create a project:
+    $ demiarch new <name> --framework <framework>
+
+    To start code generation:
+    $ demiarch generate "description"
+tui-agent-hierarchy = Agent Hierarchy
+tui-details = Details
+tui-session-statistics = Session Statistics
+tui-session-stats =
+    Total Agents: { $total }
+    Active Agents: { $active }
+    Completed: { $completed }
+    Failed: { $failed }
+    Events: { $events }
+tui-token-usage = Token Usage
+tui-token-stats =
+    Total Tokens: { $tokens }
+    Estimated Cost: ${ $cost }
+
+    (Token breakdown per agent
+    shown in agent tree)
+tui-spend-forecast = Spend Forecast
+tui-forecast =
+    This month: ${ $spent }
+    Burn rate: ${ $rate }/day ({ $days }d)
+    Projected by { $end }: ${ $projected }
+    Monthly limit: ${ $limit }
+tui-forecast-warning = Projected to exceed the monthly limit
+tui-forecast-empty = No cost history available
+tui-files = Files ({ $written }/{ $total } written)
+tui-files-empty = No files yet
+tui-recent-activity = Recent Activity
+tui-activity-empty =
+    No recent activity
+
+    Events will appear here during code generation:
+    • Agent spawned
+    • Agent completed
+    • File created
+tui-timeline = Agent Timeline (* critical path)
+tui-timeline-empty =
+    No agent activity yet
+
+    Agent runs appear here as a Gantt chart during code generation.
+tui-key-hints = q: Quit | Tab: Switch | ↑↓: Scroll | a: Toggle { $mode } | ?: Help
+tui-replay-playing = ▶
+tui-replay-paused = ⏸
+tui-replay-hints = { $state } { $speed }x | { $shown }/{ $total } @ { $clock } | Space: Play | +/-: Speed | ,/.: Step | r: Restart
@@ -5,17 +5,25 @@
 use std::future::Future;
 use std::pin::Pin;
 
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use super::code_extraction::{
     extract_code_blocks, extract_files_from_response, language_to_extension,
 };
 use super::context::AgentContext;
 use super::message_builder::build_messages_from_input;
+use super::patch::{
+    build_fallback_prompt, extract_edits_from_response, resolve_edits, EditMode, PatchFailure,
+    ResolvedEdit,
+};
 use super::status::StatusTracker;
 use super::traits::{Agent, AgentArtifact, AgentCapability, AgentInput, AgentResult, AgentStatus};
+use super::validation::{
+    build_repair_prompt, validate_source, ValidationReport, MAX_REPAIR_ATTEMPTS,
+};
 use super::AgentType;
 use crate::error::Result;
+use crate::llm::Message;
 
 /// Coder agent - generates code implementations
 ///
@@ -69,6 +77,7 @@ impl CoderAgent {
         let messages = build_messages_from_input(&self.system_prompt(), &input, &context);
 
         // Call the LLM to generate code
+        let context_stats = context.context_stats(&messages);
         let llm_client = context.llm_client();
         let response = match llm_client.complete(messages, None).await {
             Ok(resp) => resp,
@@ -80,25 +89,68 @@ impl CoderAgent {
             }
         };
 
+        context.report_context_usage(context_stats.with_measured(response.input_tokens));
+
         debug!(tokens = response.tokens_used, "Coder received LLM response");
 
         // Build result with artifacts
-        let mut result = AgentResult::success(&response.content).with_tokens(response.tokens_used);
+        let mut tokens_used = response.tokens_used;
+        let mut result = AgentResult::success(&response.content);
         let file_count;
 
-        // First, try to extract files with proper paths from the response
-        let extracted_files = extract_files_from_response(&response.content);
+        // First, try to extract whole files and patches with proper paths
+        let edits = extract_edits_from_response(&response.content);
+
+        if !edits.is_empty() {
+            let project_root = context.project_path().map(|p| p.as_path());
+            let (mut resolved, failures) = resolve_edits(project_root, &edits);
 
-        if !extracted_files.is_empty() {
-            // Use properly named files from the response
-            for file in &extracted_files {
-                let path_str = file.path.to_string_lossy().to_string();
-                result = result.with_artifact(AgentArtifact::code(&path_str, &file.content));
+            if !failures.is_empty() {
+                warn!(
+                    agent_id = %context.id,
+                    failed = failures.len(),
+                    "Patch application failed, requesting whole-file rewrites"
+                );
+                let (rewrites, fallback_tokens) = self
+                    .request_whole_files(&input, &context, &response.content, &failures)
+                    .await;
+                tokens_used += fallback_tokens;
+                resolved.extend(rewrites);
+            }
+
+            let (resolved, reports, repair_tokens) = self
+                .validate_and_repair(&input, &context, &response.content, resolved)
+                .await;
+            tokens_used += repair_tokens;
+
+            let mut accepted = 0;
+            for (edit, report) in resolved.iter().zip(&reports) {
+                if report.is_invalid() {
+                    warn!(
+                        agent_id = %context.id,
+                        path = %edit.path.display(),
+                        errors = report.errors.len(),
+                        "Rejecting file with syntax errors"
+                    );
+                    continue;
+                }
+                let path_str = edit.path.to_string_lossy().to_string();
+                result = result.with_artifact(
+                    AgentArtifact::code(&path_str, &edit.content).with_metadata(
+                        serde_json::json!({
+                            "edit_mode": edit.mode.as_str(),
+                            "validation": { "status": report.status, "errors": report.errors },
+                        }),
+                    ),
+                );
+                accepted += 1;
             }
-            file_count = extracted_files.len();
+            file_count = accepted;
             info!(
                 agent_id = %context.id,
-                files = extracted_files.len(),
+                files = accepted,
+                rejected = resolved.len() - accepted,
+                patched = resolved.iter().filter(|e| e.mode == EditMode::Patched).count(),
                 "Coder extracted files with paths"
             );
         } else {
@@ -114,6 +166,7 @@ impl CoderAgent {
             }
             file_count = code_blocks.len();
         }
+        result = result.with_tokens(tokens_used);
 
         // Mark as completed
         self.status.set(AgentStatus::Completed);
@@ -121,13 +174,122 @@ impl CoderAgent {
 
         info!(
             agent_id = %context.id,
-            tokens = response.tokens_used,
+            tokens = tokens_used,
             files = file_count,
             "Coder completed"
         );
 
         Ok(result)
     }
+
+    /// Ask the LLM for complete contents of files whose patches did not apply
+    ///
+    /// Returns the rewritten files and the tokens spent. Files the LLM still
+    /// fails to provide are dropped (and logged) rather than failing the task.
+    async fn request_whole_files(
+        &self,
+        input: &AgentInput,
+        context: &AgentContext,
+        original_response: &str,
+        failures: &[PatchFailure],
+    ) -> (Vec<ResolvedEdit>, u32) {
+        let mut messages = build_messages_from_input(&self.system_prompt(), input, context);
+        messages.push(Message::assistant(original_response));
+        messages.push(Message::user(build_fallback_prompt(failures)));
+
+        let response = match context.llm_client().complete(messages, None).await {
+            Ok(resp) => resp,
+            Err(e) => {
+                warn!(agent_id = %context.id, error = %e, "Whole-file fallback request failed");
+                return (Vec::new(), 0);
+            }
+        };
+
+        let rewrites: Vec<ResolvedEdit> = extract_files_from_response(&response.content)
+            .into_iter()
+            .filter(|file| failures.iter().any(|f| f.path == file.path))
+            .map(|file| ResolvedEdit {
+                path: file.path,
+                content: file.content,
+                mode: EditMode::Rewritten,
+                language: file.language,
+            })
+            .collect();
+
+        for failure in failures {
+            if !rewrites.iter().any(|r| r.path == failure.path) {
+                warn!(agent_id = %context.id, "{}", failure);
+            }
+        }
+
+        (rewrites, response.tokens_used)
+    }
+
+    /// Syntax-check resolved files, asking the LLM to fix any that fail
+    ///
+    /// At most [`MAX_REPAIR_ATTEMPTS`] follow-up requests are made. Returns the
+    /// (possibly repaired) files, one validation report per file in the same
+    /// order, and the tokens spent on repairs.
+    async fn validate_and_repair(
+        &self,
+        input: &AgentInput,
+        context: &AgentContext,
+        original_response: &str,
+        mut resolved: Vec<ResolvedEdit>,
+    ) -> (Vec<ResolvedEdit>, Vec<ValidationReport>, u32) {
+        let mut reports: Vec<ValidationReport> = resolved
+            .iter()
+            .map(|e| validate_source(&e.path, e.language.as_deref(), &e.content))
+            .collect();
+        let mut last_response = original_response.to_string();
+        let mut tokens_used = 0;
+
+        for attempt in 1..=MAX_REPAIR_ATTEMPTS {
+            let invalid: Vec<&ValidationReport> =
+                reports.iter().filter(|r| r.is_invalid()).collect();
+            if invalid.is_empty() {
+                break;
+            }
+
+            warn!(
+                agent_id = %context.id,
+                attempt,
+                invalid = invalid.len(),
+                "Generated files failed syntax validation, requesting repair"
+            );
+
+            let mut messages = build_messages_from_input(&self.system_prompt(), input, context);
+            messages.push(Message::assistant(&last_response));
+            messages.push(Message::user(build_repair_prompt(&invalid)));
+
+            let response = match context.llm_client().complete(messages, None).await {
+                Ok(resp) => resp,
+                Err(e) => {
+                    warn!(agent_id = %context.id, error = %e, "Syntax repair request failed");
+                    break;
+                }
+            };
+            tokens_used += response.tokens_used;
+
+            for file in extract_files_from_response(&response.content) {
+                let Some(i) = resolved.iter().position(|e| e.path == file.path) else {
+                    continue;
+                };
+                if reports[i].is_invalid() {
+                    let edit = &mut resolved[i];
+                    edit.content = file.content;
+                    if file.language.is_some() {
+                        edit.language = file.language;
+                    }
+                    reports[i] =
+                        validate_source(&edit.path, edit.language.as_deref(), &edit.content);
+                }
+            }
+            last_response = response.content;
+        }
+
+        (resolved, reports, tokens_used)
+    }
 }
 
 impl Default for CoderAgent {
@@ -181,6 +343,17 @@ Output Format:
 - Include file paths when creating new files
 - Explain your implementation choices briefly
 
+Editing Existing Files:
+- Do not rewrite whole files that already exist; emit edit blocks instead
+- Put the file path on its own line, then a code block containing one or more:
+  <<<<<<< SEARCH
+  exact lines currently in the file
+  =======
+  the replacement lines
+  >>>>>>> REPLACE
+- Keep SEARCH sections short but unique within the file
+- A unified diff (```diff with ---/+++/@@ headers) is also accepted
+
 Do not include tests - those will be generated by the Tester agent.
 Focus on clean, working implementation code."#
             .to_string()
@@ -18,10 +18,12 @@ use tokio::sync::RwLock;
 use uuid::Uuid;
 
 use super::events::AgentEventWriter;
+use super::sanitize::ContentSanitizer;
 use super::traits::{AgentResult, AgentStatus};
 use super::AgentType;
 use crate::context::{
     estimate_messages_tokens, ContextBudget, ContextWindow, DisclosureLevel, TokenAllocation,
+    WindowStats,
 };
 use crate::cost::CostTracker;
 use crate::domain::memory::PersistentMemoryStore;
@@ -198,6 +200,8 @@ pub struct SharedAgentState {
     event_writer: Arc<AgentEventWriter>,
     /// Token for cancelling agent execution
     cancellation_token: CancellationToken,
+    /// Screens untrusted content before it reaches an agent
+    sanitizer: ContentSanitizer,
 }
 
 impl SharedAgentState {
@@ -214,6 +218,7 @@ impl SharedAgentState {
             context_budget: Arc::new(ContextBudget::default()),
             event_writer: Arc::new(AgentEventWriter::new()),
             cancellation_token: CancellationToken::new(),
+            sanitizer: ContentSanitizer::default(),
         }
     }
 
@@ -240,6 +245,7 @@ impl SharedAgentState {
             context_budget: Arc::new(budget),
             event_writer: Arc::new(AgentEventWriter::new()),
             cancellation_token: CancellationToken::new(),
+            sanitizer: ContentSanitizer::default(),
         }
     }
 
@@ -259,6 +265,33 @@ impl SharedAgentState {
         self
     }
 
+    /// Set the sanitizer for untrusted content
+    pub fn with_sanitizer(mut self, sanitizer: ContentSanitizer) -> Self {
+        self.sanitizer = sanitizer;
+        self
+    }
+
+    /// Get the sanitizer for untrusted content
+    pub fn sanitizer(&self) -> ContentSanitizer {
+        self.sanitizer
+    }
+
+    /// Screen untrusted content on behalf of an agent
+    ///
+    /// Returns the text to give the model. Quarantined content is replaced by
+    /// a notice and logged as an event.
+    pub fn screen_untrusted(&self, agent: &AgentId, source: &str, content: &str) -> String {
+        let screened = self.sanitizer.sanitize(source, content);
+        if screened.quarantined {
+            let summary = screened.summary(source);
+            tracing::warn!(agent = %agent, "{}", summary);
+            self.event_writer.emit_content_quarantined(agent, &summary);
+        } else if screened.is_suspicious() {
+            tracing::warn!(agent = %agent, "{}", screened.summary(source));
+        }
+        screened.text
+    }
+
     /// Set the project ID
     pub fn with_project_id(mut self, id: Uuid) -> Self {
         self.project_id = Some(id);
@@ -344,7 +377,7 @@ impl SharedAgentState {
             let feature_id = self.feature_id.clone();
             let llm_client = self.llm_client.clone();
             task::spawn(async move {
-                if let Ok(db) = Database::default().await {
+                if let Ok(db) = Database::shared().await {
                     let store = PersistentMemoryStore::new(db.pool().clone());
                     let _ = store
                         .ingest(
@@ -523,6 +556,13 @@ impl AgentContext {
         &self.shared_state
     }
 
+    /// Screen untrusted content (file contents, tool output) before adding
+    /// it to this agent's prompt
+    pub fn screen_untrusted(&self, source: &str, content: &str) -> String {
+        self.shared_state
+            .screen_untrusted(&self.id, source, content)
+    }
+
     /// Get the cancellation token
     pub fn cancellation_token(&self) -> &CancellationToken {
         &self.shared_state.cancellation_token
@@ -614,6 +654,18 @@ impl AgentContext {
         ContextWindow::new(allocation).with_disclosure_level(self.disclosure_level())
     }
 
+    /// Estimated context window usage of a prompt for this agent
+    pub fn context_stats(&self, messages: &[Message]) -> WindowStats {
+        WindowStats::for_messages(messages, self.token_allocation().total_input())
+    }
+
+    /// Report how full this agent's context window is, for the TUI
+    pub fn report_context_usage(&self, stats: WindowStats) {
+        self.shared_state
+            .event_writer
+            .emit_context_usage(&self.id, stats);
+    }
+
     /// Estimate total tokens used by inherited messages
     pub fn estimate_inherited_tokens(&self) -> usize {
         estimate_messages_tokens(&self.inherited_messages)
@@ -0,0 +1,976 @@
+//! Dependency manifest awareness for generated code
+//!
+//! Generated code often imports packages the project does not declare yet.
+//! [`propose`] finds the imports in generated JavaScript/TypeScript, Rust and
+//! Python files that are missing from the project's `package.json`,
+//! `Cargo.toml` or `pyproject.toml`, and returns edited manifests so the
+//! additions show up in the generation's diff like any other file.
+//!
+//! Manifests are edited as text so their formatting and key order survive;
+//! an edit that would not parse afterwards is dropped. Standard library
+//! modules, relative imports and modules that are part of the generated code
+//! are never proposed. Once the manifest is written, [`install`] can run the
+//! ecosystem's install command through the [`SandboxedRunner`] and
+//! [`record_in_graph`] adds the packages to the knowledge graph.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::domain::knowledge::{
+    EntityType, KnowledgeEntity, KnowledgeGraphRepository, KnowledgeRelationship, RelationshipType,
+};
+use crate::error::Result;
+use crate::infrastructure::sandbox::{SandboxOutput, SandboxedRunner};
+
+/// Wall-clock limit for an install command
+pub const INSTALL_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Version requirement written for proposed npm packages
+const NPM_VERSION: &str = "latest";
+
+/// Version requirement written for proposed crates
+const CARGO_VERSION: &str = "*";
+
+const NODE_BUILTINS: &[&str] = &[
+    "assert",
+    "async_hooks",
+    "buffer",
+    "child_process",
+    "cluster",
+    "console",
+    "constants",
+    "crypto",
+    "dgram",
+    "diagnostics_channel",
+    "dns",
+    "domain",
+    "events",
+    "fs",
+    "http",
+    "http2",
+    "https",
+    "inspector",
+    "module",
+    "net",
+    "os",
+    "path",
+    "perf_hooks",
+    "process",
+    "punycode",
+    "querystring",
+    "readline",
+    "repl",
+    "stream",
+    "string_decoder",
+    "sys",
+    "timers",
+    "tls",
+    "trace_events",
+    "tty",
+    "url",
+    "util",
+    "v8",
+    "vm",
+    "wasi",
+    "worker_threads",
+    "zlib",
+];
+
+const RUST_BUILTINS: &[&str] = &[
+    "std",
+    "core",
+    "alloc",
+    "crate",
+    "self",
+    "super",
+    "proc_macro",
+    "test",
+];
+
+const PYTHON_STDLIB: &[&str] = &[
+    "__future__",
+    "abc",
+    "argparse",
+    "array",
+    "ast",
+    "asyncio",
+    "base64",
+    "binascii",
+    "bisect",
+    "builtins",
+    "calendar",
+    "cmath",
+    "codecs",
+    "collections",
+    "concurrent",
+    "configparser",
+    "contextlib",
+    "contextvars",
+    "copy",
+    "csv",
+    "ctypes",
+    "dataclasses",
+    "datetime",
+    "decimal",
+    "difflib",
+    "dis",
+    "email",
+    "enum",
+    "errno",
+    "fnmatch",
+    "fractions",
+    "ftplib",
+    "functools",
+    "gc",
+    "getpass",
+    "gettext",
+    "glob",
+    "gzip",
+    "hashlib",
+    "heapq",
+    "hmac",
+    "html",
+    "http",
+    "imaplib",
+    "importlib",
+    "inspect",
+    "io",
+    "ipaddress",
+    "itertools",
+    "json",
+    "keyword",
+    "locale",
+    "logging",
+    "lzma",
+    "math",
+    "mimetypes",
+    "multiprocessing",
+    "numbers",
+    "operator",
+    "os",
+    "pathlib",
+    "pickle",
+    "platform",
+    "pprint",
+    "queue",
+    "random",
+    "re",
+    "sched",
+    "secrets",
+    "select",
+    "shlex",
+    "shutil",
+    "signal",
+    "smtplib",
+    "socket",
+    "sqlite3",
+    "ssl",
+    "stat",
+    "statistics",
+    "string",
+    "struct",
+    "subprocess",
+    "sys",
+    "tempfile",
+    "textwrap",
+    "threading",
+    "time",
+    "timeit",
+    "tkinter",
+    "token",
+    "tokenize",
+    "tomllib",
+    "traceback",
+    "types",
+    "typing",
+    "unicodedata",
+    "unittest",
+    "urllib",
+    "uuid",
+    "venv",
+    "warnings",
+    "weakref",
+    "xml",
+    "zipfile",
+    "zlib",
+    "zoneinfo",
+];
+
+/// Python modules whose distribution has a different name
+const PYTHON_DISTRIBUTIONS: &[(&str, &str)] = &[
+    ("bs4", "beautifulsoup4"),
+    ("cv2", "opencv-python"),
+    ("dotenv", "python-dotenv"),
+    ("jwt", "PyJWT"),
+    ("PIL", "Pillow"),
+    ("sklearn", "scikit-learn"),
+    ("yaml", "PyYAML"),
+];
+
+/// A package ecosystem with a manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ecosystem {
+    Npm,
+    Cargo,
+    Python,
+}
+
+impl Ecosystem {
+    pub const ALL: [Ecosystem; 3] = [Self::Npm, Self::Cargo, Self::Python];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Cargo => "cargo",
+            Self::Python => "python",
+        }
+    }
+
+    /// Manifest file at the project root
+    pub fn manifest(&self) -> &'static str {
+        match self {
+            Self::Npm => "package.json",
+            Self::Cargo => "Cargo.toml",
+            Self::Python => "pyproject.toml",
+        }
+    }
+
+    /// Language of the manifest, as recorded on generated files
+    pub fn manifest_language(&self) -> &'static str {
+        match self {
+            Self::Npm => "json",
+            Self::Cargo | Self::Python => "toml",
+        }
+    }
+
+    /// Ecosystem of a source file, by extension
+    pub fn for_source(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Some(Self::Npm),
+            "rs" => Some(Self::Cargo),
+            "py" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    /// Program and arguments that install `packages` once the manifest lists them
+    pub fn install_command(&self, packages: &[String]) -> (&'static str, Vec<String>) {
+        match self {
+            Self::Npm => ("npm", vec!["install".to_string()]),
+            Self::Cargo => ("cargo", vec!["fetch".to_string()]),
+            Self::Python => (
+                "pip",
+                std::iter::once("install".to_string())
+                    .chain(packages.iter().cloned())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Name used to compare imports with declared packages
+    fn normalize(&self, name: &str) -> String {
+        match self {
+            Self::Npm => name.to_string(),
+            Self::Cargo => name.to_lowercase().replace('-', "_"),
+            Self::Python => name.to_lowercase().replace(['_', '.'], "-"),
+        }
+    }
+}
+
+impl fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Packages added to one manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyChange {
+    pub ecosystem: Ecosystem,
+    /// Manifest path, relative to the project root
+    pub manifest: PathBuf,
+    pub packages: Vec<String>,
+}
+
+/// A proposed manifest with the missing packages added
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEdit {
+    pub change: DependencyChange,
+    /// Complete new manifest content
+    pub content: String,
+}
+
+/// Packages imported by one source file, as they would be declared
+pub fn imported_packages(ecosystem: Ecosystem, content: &str) -> BTreeSet<String> {
+    match ecosystem {
+        Ecosystem::Npm => js_imports(content),
+        Ecosystem::Cargo => rust_imports(content),
+        Ecosystem::Python => python_imports(content),
+    }
+}
+
+fn js_imports(content: &str) -> BTreeSet<String> {
+    let mut packages = BTreeSet::new();
+    for marker in ["from ", "require(", "import(", "import "] {
+        let mut rest = content;
+        while let Some(i) = rest.find(marker) {
+            rest = &rest[i + marker.len()..];
+            let after = rest.trim_start();
+            let Some(quote) = after
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '\'' | '"' | '`'))
+            else {
+                continue;
+            };
+            let Some(end) = after[1..].find(quote) else {
+                continue;
+            };
+            if let Some(package) = js_package(&after[1..1 + end]) {
+                packages.insert(package);
+            }
+        }
+    }
+    packages
+}
+
+/// Package name of a module specifier, if it names an installable package
+fn js_package(specifier: &str) -> Option<String> {
+    if specifier.is_empty()
+        || specifier.starts_with(['.', '/', '#', '~'])
+        || specifier.starts_with("@/")
+        || specifier.starts_with("node:")
+        || specifier.contains("://")
+    {
+        return None;
+    }
+    let mut segments = specifier.split('/');
+    let first = segments.next()?;
+    let package = if first.starts_with('@') {
+        format!("{}/{}", first, segments.next()?)
+    } else {
+        first.to_string()
+    };
+    (!NODE_BUILTINS.contains(&package.as_str())).then_some(package)
+}
+
+fn rust_imports(content: &str) -> BTreeSet<String> {
+    let local: BTreeSet<&str> = content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start().trim_start_matches("pub ");
+            let name = line.strip_prefix("mod ")?;
+            Some(name.trim_end_matches([';', '{', ' ']).trim())
+        })
+        .collect();
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            let line = line
+                .strip_prefix("pub(crate) ")
+                .or_else(|| line.strip_prefix("pub "))
+                .unwrap_or(line);
+            let path = line
+                .strip_prefix("use ")
+                .or_else(|| line.strip_prefix("extern crate "))?;
+            let path = path.trim_start().trim_start_matches("::");
+            let end = path
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(path.len());
+            let name = &path[..end];
+            (!name.is_empty()
+                && !RUST_BUILTINS.contains(&name)
+                && !local.contains(name)
+                && name.starts_with(|c: char| c.is_ascii_lowercase()))
+            .then(|| name.to_string())
+        })
+        .collect()
+}
+
+fn python_imports(content: &str) -> BTreeSet<String> {
+    let mut modules = BTreeSet::new();
+    for line in content.lines() {
+        let line = line.trim_start();
+        if let Some(rest) = line.strip_prefix("from ") {
+            if let Some(module) = rest.split_whitespace().next() {
+                modules.insert(module.to_string());
+            }
+        } else if let Some(rest) = line.strip_prefix("import ") {
+            for part in rest.split(',') {
+                if let Some(module) = part.split_whitespace().next() {
+                    modules.insert(module.to_string());
+                }
+            }
+        }
+    }
+    modules
+        .into_iter()
+        .filter(|m| !m.starts_with('.'))
+        .filter_map(|m| m.split('.').next().map(str::to_string))
+        .filter(|m| !m.is_empty() && !PYTHON_STDLIB.contains(&m.as_str()))
+        .map(|m| {
+            PYTHON_DISTRIBUTIONS
+                .iter()
+                .find(|(module, _)| *module == m)
+                .map(|(_, distribution)| distribution.to_string())
+                .unwrap_or(m)
+        })
+        .collect()
+}
+
+/// Packages a manifest declares, normalized for comparison
+pub fn declared_packages(ecosystem: Ecosystem, manifest: &str) -> BTreeSet<String> {
+    let names: Vec<String> = match ecosystem {
+        Ecosystem::Npm => {
+            let Ok(value) = serde_json::from_str::<serde_json::Value>(manifest) else {
+                return BTreeSet::new();
+            };
+            let sections = [
+                "dependencies",
+                "devDependencies",
+                "peerDependencies",
+                "optionalDependencies",
+            ];
+            sections
+                .iter()
+                .filter_map(|s| value[s].as_object())
+                .flat_map(|deps| deps.keys().cloned())
+                .chain(value["name"].as_str().map(str::to_string))
+                .collect()
+        }
+        Ecosystem::Cargo => {
+            let Ok(value) = manifest.parse::<toml::Table>() else {
+                return BTreeSet::new();
+            };
+            let mut tables: Vec<&toml::Table> = Vec::new();
+            for key in ["dependencies", "dev-dependencies", "build-dependencies"] {
+                tables.extend(value.get(key).and_then(|v| v.as_table()));
+                if let Some(targets) = value.get("target").and_then(|v| v.as_table()) {
+                    tables.extend(
+                        targets
+                            .values()
+                            .filter_map(|t| t.get(key).and_then(|v| v.as_table())),
+                    );
+                }
+            }
+            tables.extend(
+                value
+                    .get("workspace")
+                    .and_then(|w| w.get("dependencies"))
+                    .and_then(|v| v.as_table()),
+            );
+            tables
+                .iter()
+                .flat_map(|t| t.keys().cloned())
+                .chain(
+                    value
+                        .get("package")
+                        .and_then(|p| p.get("name"))
+                        .and_then(|n| n.as_str())
+                        .map(str::to_string),
+                )
+                .collect()
+        }
+        Ecosystem::Python => {
+            let Ok(value) = manifest.parse::<toml::Table>() else {
+                return BTreeSet::new();
+            };
+            let project = value.get("project");
+            let requirements = project
+                .and_then(|p| p.get("dependencies"))
+                .and_then(|d| d.as_array())
+                .into_iter()
+                .flatten()
+                .chain(
+                    project
+                        .and_then(|p| p.get("optional-dependencies"))
+                        .and_then(|o| o.as_table())
+                        .into_iter()
+                        .flat_map(|groups| groups.values())
+                        .filter_map(|g| g.as_array())
+                        .flatten(),
+                )
+                .filter_map(|r| r.as_str())
+                .filter_map(|r| {
+                    r.split(['[', '<', '>', '=', '!', '~', ';', ' ', '('])
+                        .next()
+                })
+                .map(str::to_string);
+            let poetry = value
+                .get("tool")
+                .and_then(|t| t.get("poetry"))
+                .and_then(|p| p.get("dependencies"))
+                .and_then(|d| d.as_table())
+                .into_iter()
+                .flat_map(|d| d.keys().cloned());
+            let own = project
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str())
+                .map(str::to_string);
+            requirements.chain(poetry).chain(own).collect()
+        }
+    };
+    names.iter().map(|n| ecosystem.normalize(n)).collect()
+}
+
+/// `manifest` with `packages` added, or `None` if it cannot be edited safely
+pub fn add_to_manifest(
+    ecosystem: Ecosystem,
+    manifest: &str,
+    packages: &[String],
+) -> Option<String> {
+    if packages.is_empty() {
+        return None;
+    }
+    let edited = match ecosystem {
+        Ecosystem::Npm => add_to_package_json(manifest, packages)?,
+        Ecosystem::Cargo => add_to_cargo_toml(manifest, packages)?,
+        Ecosystem::Python => add_to_pyproject(manifest, packages)?,
+    };
+    let parses = match ecosystem {
+        Ecosystem::Npm => serde_json::from_str::<serde_json::Value>(&edited).is_ok(),
+        Ecosystem::Cargo | Ecosystem::Python => edited.parse::<toml::Table>().is_ok(),
+    };
+    let declared = declared_packages(ecosystem, &edited);
+    let complete = packages
+        .iter()
+        .all(|p| declared.contains(&ecosystem.normalize(p)));
+    (parses && complete).then_some(edited)
+}
+
+fn add_to_package_json(manifest: &str, packages: &[String]) -> Option<String> {
+    let entries = |indent: &str| -> Vec<String> {
+        packages
+            .iter()
+            .map(|p| format!("{}\"{}\": \"{}\"", indent, p, NPM_VERSION))
+            .collect()
+    };
+    let Some(key) = manifest.find("\"dependencies\"") else {
+        // Add a dependencies object as the root object's first key
+        let open = manifest.find('{')?;
+        let body = manifest[open + 1..].trim_start();
+        let separator = if body.starts_with('}') { "" } else { "," };
+        return Some(format!(
+            "{}\n  \"dependencies\": {{\n{}\n  }}{}{}",
+            &manifest[..=open],
+            entries("    ").join(",\n"),
+            separator,
+            &manifest[open + 1..]
+        ));
+    };
+    let open = key + manifest[key..].find('{')?;
+    let after = &manifest[open + 1..];
+    let body = after.trim_start();
+    if body.starts_with('}') {
+        let end = open + 1 + (after.len() - body.len()) + 1;
+        return Some(format!(
+            "{}\n{}\n  }}{}",
+            &manifest[..=open],
+            entries("    ").join(",\n"),
+            &manifest[end..]
+        ));
+    }
+    let indent: String = after
+        .trim_start_matches(['\r', '\n'])
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect();
+    let indent = if indent.is_empty() {
+        "    ".to_string()
+    } else {
+        indent
+    };
+    Some(format!(
+        "{}\n{},{}",
+        &manifest[..=open],
+        entries(&indent).join(",\n"),
+        after
+    ))
+}
+
+fn add_to_cargo_toml(manifest: &str, packages: &[String]) -> Option<String> {
+    let value = manifest.parse::<toml::Table>().ok()?;
+    if value.contains_key("workspace") && !value.contains_key("package") {
+        // A virtual workspace manifest; the member that needs the crate is unknown
+        return None;
+    }
+    let entries: Vec<String> = packages
+        .iter()
+        .map(|p| format!("{} = \"{}\"", p, CARGO_VERSION))
+        .collect();
+    Some(insert_into_section(manifest, "[dependencies]", &entries))
+}
+
+fn add_to_pyproject(manifest: &str, packages: &[String]) -> Option<String> {
+    let value = manifest.parse::<toml::Table>().ok()?;
+    let quoted: Vec<String> = packages.iter().map(|p| format!("\"{}\"", p)).collect();
+    let project = value.get("project")?;
+    if project.get("dependencies").is_none() {
+        let line = format!("dependencies = [{}]", quoted.join(", "));
+        return Some(insert_into_section(manifest, "[project]", &[line]));
+    }
+
+    let lines: Vec<&str> = manifest.lines().collect();
+    let mut in_project = false;
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && !trimmed.starts_with("[[") {
+            in_project = trimmed == "[project]";
+            continue;
+        }
+        let Some(rest) = trimmed.strip_prefix("dependencies") else {
+            continue;
+        };
+        let Some(list) = rest.trim_start().strip_prefix('=') else {
+            continue;
+        };
+        if !in_project {
+            continue;
+        }
+        let list = list.trim();
+        let mut edited: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        if let Some(inner) = list.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let inner = inner.trim().trim_end_matches(',');
+            let separator = if inner.is_empty() { "" } else { ", " };
+            edited[i] = format!(
+                "dependencies = [{}{}{}]",
+                inner,
+                separator,
+                quoted.join(", ")
+            );
+        } else {
+            let items: Vec<String> = quoted.iter().map(|q| format!("    {},", q)).collect();
+            edited.splice(i + 1..i + 1, items);
+        }
+        let mut content = edited.join("\n");
+        if manifest.ends_with('\n') {
+            content.push('\n');
+        }
+        return Some(content);
+    }
+    None
+}
+
+/// Add lines at the end of a TOML section, creating the section if needed
+fn insert_into_section(manifest: &str, header: &str, entries: &[String]) -> String {
+    let lines: Vec<&str> = manifest.lines().collect();
+    let Some(start) = lines.iter().position(|l| l.trim() == header) else {
+        let mut content = manifest.trim_end().to_string();
+        content.push_str(&format!("\n\n{}\n{}\n", header, entries.join("\n")));
+        return content;
+    };
+    let end = lines[start + 1..]
+        .iter()
+        .position(|l| l.trim_start().starts_with('['))
+        .map(|i| start + 1 + i)
+        .unwrap_or(lines.len());
+    // Keep blank lines between the section and the next one
+    let insert_at = (start + 1..end)
+        .rev()
+        .find(|&i| !lines[i].trim().is_empty())
+        .map(|i| i + 1)
+        .unwrap_or(start + 1);
+    let mut edited: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+    edited.splice(insert_at..insert_at, entries.iter().cloned());
+    let mut content = edited.join("\n");
+    if manifest.ends_with('\n') || manifest.is_empty() {
+        content.push('\n');
+    }
+    content
+}
+
+/// Manifest edits adding the packages `files` import but no manifest declares
+///
+/// `files` are `(path, content)` pairs relative to `root`. A manifest among
+/// them is edited in its generated form; otherwise the manifest is read from
+/// `root`. Ecosystems without a manifest get no proposal.
+pub fn propose(root: &Path, files: &[(&Path, &str)]) -> Vec<ManifestEdit> {
+    let local = local_modules(root, files);
+    let mut edits = Vec::new();
+    for ecosystem in Ecosystem::ALL {
+        let manifest_path = PathBuf::from(ecosystem.manifest());
+        let manifest = match files.iter().find(|(path, _)| *path == manifest_path) {
+            Some((_, content)) => content.to_string(),
+            None => match std::fs::read_to_string(root.join(&manifest_path)) {
+                Ok(content) => content,
+                Err(_) => continue,
+            },
+        };
+        let declared = declared_packages(ecosystem, &manifest);
+        let missing: Vec<String> = files
+            .iter()
+            .filter(|(path, _)| Ecosystem::for_source(path) == Some(ecosystem))
+            .flat_map(|(_, content)| imported_packages(ecosystem, content))
+            .filter(|p| !declared.contains(&ecosystem.normalize(p)))
+            .filter(|p| ecosystem == Ecosystem::Npm || !local.contains(p.as_str()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if missing.is_empty() {
+            continue;
+        }
+        match add_to_manifest(ecosystem, &manifest, &missing) {
+            Some(content) => {
+                debug!(ecosystem = %ecosystem, packages = ?missing, "Proposing manifest dependencies");
+                edits.push(ManifestEdit {
+                    change: DependencyChange {
+                        ecosystem,
+                        manifest: manifest_path,
+                        packages: missing,
+                    },
+                    content,
+                });
+            }
+            None => debug!(ecosystem = %ecosystem, "Could not edit manifest; skipping proposal"),
+        }
+    }
+    edits
+}
+
+/// Module names defined by the generated files or the project itself
+fn local_modules(root: &Path, files: &[(&Path, &str)]) -> BTreeSet<String> {
+    let mut local: BTreeSet<String> = files
+        .iter()
+        .flat_map(|(path, _)| {
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(str::to_string);
+            let parents = path
+                .components()
+                .filter_map(|c| c.as_os_str().to_str().map(str::to_string))
+                .collect::<Vec<_>>();
+            stem.into_iter().chain(parents)
+        })
+        .collect();
+    for dir in [root.to_path_buf(), root.join("src")] {
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            local.extend(entries.flatten().filter_map(|e| {
+                let path = e.path();
+                path.file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(str::to_string)
+            }));
+        }
+    }
+    local
+}
+
+/// Packages added by the manifests among `files`, compared with `root` on disk
+///
+/// Covers proposals from [`propose`] as well as manifest edits the model
+/// wrote itself. Call before the files are written.
+pub fn added_dependencies(root: &Path, files: &[(&Path, &str)]) -> Vec<DependencyChange> {
+    Ecosystem::ALL
+        .iter()
+        .filter_map(|&ecosystem| {
+            let manifest = PathBuf::from(ecosystem.manifest());
+            let (_, content) = files.iter().find(|(path, _)| *path == manifest)?;
+            let before = std::fs::read_to_string(root.join(&manifest))
+                .map(|c| declared_packages(ecosystem, &c))
+                .unwrap_or_default();
+            let after = declared_packages(ecosystem, content);
+            let packages: Vec<String> = after.difference(&before).cloned().collect();
+            (!packages.is_empty()).then_some(DependencyChange {
+                ecosystem,
+                manifest,
+                packages,
+            })
+        })
+        .collect()
+}
+
+/// Run the ecosystem's install command in `root` through the sandbox
+pub async fn install(root: &Path, change: &DependencyChange) -> Result<SandboxOutput> {
+    let (program, args) = change.ecosystem.install_command(&change.packages);
+    SandboxedRunner::new(root)
+        .with_search_path(root.join(".venv").join("bin"))
+        .allow(program)
+        .with_timeout(INSTALL_TIMEOUT)
+        .run(program, &args, None)
+        .await
+}
+
+/// Record added packages as libraries the framework depends on
+///
+/// Returns the number of packages recorded.
+pub async fn record_in_graph(
+    repo: &dyn KnowledgeGraphRepository,
+    changes: &[DependencyChange],
+    framework: Option<&str>,
+) -> Result<usize> {
+    let framework = match framework.filter(|f| !f.is_empty()) {
+        Some(name) => Some(find_or_create(repo, name, EntityType::Framework, None).await?),
+        None => None,
+    };
+    let mut recorded = 0;
+    for change in changes {
+        for package in &change.packages {
+            let description = format!("{} package", change.ecosystem);
+            let library =
+                find_or_create(repo, package, EntityType::Library, Some(&description)).await?;
+            if let Some(ref framework) = framework {
+                let existing = repo
+                    .get_relationship_between(
+                        &framework.id,
+                        &library.id,
+                        RelationshipType::DependsOn,
+                    )
+                    .await?;
+                if existing.is_none() {
+                    repo.save_relationship(&KnowledgeRelationship::new(
+                        &framework.id,
+                        &library.id,
+                        RelationshipType::DependsOn,
+                    ))
+                    .await?;
+                }
+            }
+            recorded += 1;
+        }
+    }
+    Ok(recorded)
+}
+
+async fn find_or_create(
+    repo: &dyn KnowledgeGraphRepository,
+    name: &str,
+    entity_type: EntityType,
+    description: Option<&str>,
+) -> Result<KnowledgeEntity> {
+    let canonical = KnowledgeEntity::canonicalize(name);
+    if let Some(entity) = repo.get_entity_by_canonical_name(&canonical).await? {
+        return Ok(entity);
+    }
+    let mut entity = KnowledgeEntity::new(name, entity_type);
+    if let Some(description) = description {
+        entity = entity.with_description(description);
+    }
+    repo.save_entity(&entity).await?;
+    Ok(entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_js_imports_skip_relative_and_builtin_modules() {
+        let source = r#"
+import React from "react";
+import { z } from 'zod';
+import '@fontsource/inter/400.css';
+import fs from "node:fs";
+import path from "path";
+import { helper } from "./helper";
+import Button from "@/components/Button";
+const express = require("express");
+const lazy = await import("lodash/debounce");
+"#;
+        let packages: Vec<String> = imported_packages(Ecosystem::Npm, source)
+            .into_iter()
+            .collect();
+        assert_eq!(
+            packages,
+            vec!["@fontsource/inter", "express", "lodash", "react", "zod"]
+        );
+    }
+
+    #[test]
+    fn test_rust_and_python_imports() {
+        let rust = "mod routes;\nuse std::fmt;\nuse serde::Serialize;\npub use routes::Router;\nuse ::tokio::sync;\nuse crate::x;\n";
+        let packages: Vec<String> = imported_packages(Ecosystem::Cargo, rust)
+            .into_iter()
+            .collect();
+        assert_eq!(packages, vec!["serde", "tokio"]);
+
+        let python = "import os, requests\nfrom yaml import safe_load\nfrom . import models\nimport numpy as np\n";
+        let packages: Vec<String> = imported_packages(Ecosystem::Python, python)
+            .into_iter()
+            .collect();
+        assert_eq!(packages, vec!["PyYAML", "numpy", "requests"]);
+    }
+
+    #[test]
+    fn test_add_to_package_json_keeps_key_order() {
+        let manifest = "{\n  \"name\": \"app\",\n  \"dependencies\": {\n    \"react\": \"^18.0.0\"\n  },\n  \"devDependencies\": {}\n}\n";
+        let edited = add_to_manifest(Ecosystem::Npm, manifest, &["zod".to_string()]).unwrap();
+        assert!(edited.find("\"name\"").unwrap() < edited.find("\"zod\"").unwrap());
+        assert!(edited.contains("    \"zod\": \"latest\",\n    \"react\""));
+
+        let bare = "{\n  \"name\": \"app\"\n}\n";
+        let edited = add_to_manifest(Ecosystem::Npm, bare, &["zod".to_string()]).unwrap();
+        assert!(declared_packages(Ecosystem::Npm, &edited).contains("zod"));
+
+        let empty = "{\n  \"dependencies\": {}\n}\n";
+        let edited = add_to_manifest(Ecosystem::Npm, empty, &["zod".to_string()]).unwrap();
+        assert!(declared_packages(Ecosystem::Npm, &edited).contains("zod"));
+    }
+
+    #[test]
+    fn test_add_to_cargo_and_pyproject() {
+        let cargo = "[package]\nname = \"app\"\n\n[dependencies]\nserde = \"1\"\n\n[dev-dependencies]\ntempfile = \"3\"\n";
+        let edited = add_to_manifest(Ecosystem::Cargo, cargo, &["tokio".to_string()]).unwrap();
+        assert!(edited.contains("serde = \"1\"\ntokio = \"*\"\n\n[dev-dependencies]"));
+        let virtual_workspace = "[workspace]\nmembers = [\"a\"]\n";
+        assert!(
+            add_to_manifest(Ecosystem::Cargo, virtual_workspace, &["tokio".to_string()]).is_none()
+        );
+
+        let inline = "[project]\nname = \"app\"\ndependencies = [\"requests>=2\"]\n";
+        let edited = add_to_manifest(Ecosystem::Python, inline, &["PyYAML".to_string()]).unwrap();
+        assert!(edited.contains("dependencies = [\"requests>=2\", \"PyYAML\"]"));
+
+        let multiline = "[project]\nname = \"app\"\ndependencies = [\n    \"requests\",\n]\n";
+        let edited = add_to_manifest(Ecosystem::Python, multiline, &["numpy".to_string()]).unwrap();
+        assert_eq!(
+            declared_packages(Ecosystem::Python, &edited),
+            ["app", "numpy", "requests"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect()
+        );
+    }
+
+    #[test]
+    fn test_propose_and_added_dependencies() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(
+            root.path().join("Cargo.toml"),
+            "[package]\nname = \"my-app\"\n\n[dependencies]\nserde = \"1\"\n",
+        )
+        .unwrap();
+        let main = "mod config;\nuse my_app::run;\nuse serde::Deserialize;\nuse anyhow::Result;\n";
+        let config = "use tokio::fs;\n";
+        let files = [
+            (Path::new("src/main.rs"), main),
+            (Path::new("src/config.rs"), config),
+        ];
+
+        let edits = propose(root.path(), &files);
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].change.packages, vec!["anyhow", "tokio"]);
+        assert_eq!(edits[0].change.manifest, PathBuf::from("Cargo.toml"));
+
+        let with_manifest = [
+            (Path::new("src/main.rs"), main),
+            (Path::new("Cargo.toml"), edits[0].content.as_str()),
+        ];
+        let added = added_dependencies(root.path(), &with_manifest);
+        assert_eq!(added[0].packages, vec!["anyhow", "tokio"]);
+
+        // No package.json, so nothing is proposed for JavaScript
+        assert!(propose(
+            root.path(),
+            &[(Path::new("index.js"), "import x from 'x';")]
+        )
+        .is_empty());
+    }
+}
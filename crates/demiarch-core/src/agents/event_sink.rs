@@ -0,0 +1,371 @@
+//! Background writer for the agent event file
+//!
+//! [`AgentEventWriter`](super::events::AgentEventWriter) hands serialized
+//! events to an [`EventSink`] instead of appending them itself, so parallel
+//! agents never wait on disk IO. A writer thread drains a bounded channel in
+//! batches through one buffered write, flushes after every batch so watchers
+//! see events promptly, and syncs the file to disk at most once per
+//! [`EventSinkConfig::fsync_interval`].
+//!
+//! When the channel is full, senders wait up to
+//! [`EventSinkConfig::send_timeout`] for room and then drop the event. Both
+//! cases are counted in [`EventSinkMetrics`], along with batch flush latency,
+//! per sink and for the whole process ([`process_metrics`]).
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Tuning for an [`EventSink`]
+#[derive(Debug, Clone)]
+pub struct EventSinkConfig {
+    /// Events that can wait in the channel before senders are held up
+    pub capacity: usize,
+    /// Most events written per batch
+    pub batch_size: usize,
+    /// Longest time written events may go without an fsync
+    pub fsync_interval: Duration,
+    /// How long a sender waits for room in a full channel before dropping
+    pub send_timeout: Duration,
+}
+
+impl Default for EventSinkConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            batch_size: 256,
+            fsync_interval: Duration::from_secs(1),
+            send_timeout: Duration::from_millis(50),
+        }
+    }
+}
+
+/// Counters for an event sink
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventSinkMetrics {
+    /// Events written to the file
+    pub written: u64,
+    /// Events lost to a full channel or a failed write
+    pub dropped: u64,
+    /// Sends that found the channel full and had to wait
+    pub backpressure_waits: u64,
+    /// Batches written
+    pub batches: u64,
+    /// Times the file was synced to disk
+    pub fsyncs: u64,
+    /// Time to write and flush the latest batch, in microseconds
+    pub last_flush_us: u64,
+    /// Slowest batch write and flush, in microseconds
+    pub max_flush_us: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    written: AtomicU64,
+    dropped: AtomicU64,
+    backpressure_waits: AtomicU64,
+    batches: AtomicU64,
+    fsyncs: AtomicU64,
+    last_flush_us: AtomicU64,
+    max_flush_us: AtomicU64,
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            written: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            backpressure_waits: AtomicU64::new(0),
+            batches: AtomicU64::new(0),
+            fsyncs: AtomicU64::new(0),
+            last_flush_us: AtomicU64::new(0),
+            max_flush_us: AtomicU64::new(0),
+        }
+    }
+
+    fn snapshot(&self) -> EventSinkMetrics {
+        EventSinkMetrics {
+            written: self.written.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            backpressure_waits: self.backpressure_waits.load(Ordering::Relaxed),
+            batches: self.batches.load(Ordering::Relaxed),
+            fsyncs: self.fsyncs.load(Ordering::Relaxed),
+            last_flush_us: self.last_flush_us.load(Ordering::Relaxed),
+            max_flush_us: self.max_flush_us.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Totals across every sink in this process
+static PROCESS: Counters = Counters::new();
+
+/// Event sink metrics summed over every sink this process has opened
+pub fn process_metrics() -> EventSinkMetrics {
+    PROCESS.snapshot()
+}
+
+/// Applies each update to a sink's counters and the process totals
+struct Stats(Arc<Counters>);
+
+impl Stats {
+    fn add(&self, field: fn(&Counters) -> &AtomicU64, n: u64) {
+        field(&self.0).fetch_add(n, Ordering::Relaxed);
+        field(&PROCESS).fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn flushed(&self, elapsed: Duration) {
+        let us = elapsed.as_micros() as u64;
+        for counters in [self.0.as_ref(), &PROCESS] {
+            counters.last_flush_us.store(us, Ordering::Relaxed);
+            counters.max_flush_us.fetch_max(us, Ordering::Relaxed);
+        }
+    }
+}
+
+enum Message {
+    Event(String),
+    /// Acknowledged once everything sent before it is written and synced
+    Flush(mpsc::Sender<()>),
+}
+
+/// Bounded channel to a thread that appends events to a file
+pub struct EventSink {
+    sender: Option<SyncSender<Message>>,
+    worker: Option<JoinHandle<()>>,
+    counters: Arc<Counters>,
+    send_timeout: Duration,
+}
+
+impl EventSink {
+    /// Start a writer thread appending to `file`
+    pub fn spawn(file: File, config: EventSinkConfig) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(config.capacity.max(1));
+        let counters = Arc::new(Counters::default());
+        let stats = Stats(counters.clone());
+        let send_timeout = config.send_timeout;
+        let worker = std::thread::Builder::new()
+            .name("agent-event-sink".to_string())
+            .spawn(move || run(file, receiver, config, stats))
+            .map_err(|e| tracing::warn!(error = %e, "Failed to start agent event writer"))
+            .ok();
+        Self {
+            sender: worker.as_ref().map(|_| sender),
+            worker,
+            counters,
+            send_timeout,
+        }
+    }
+
+    /// Queue one serialized event
+    ///
+    /// Waits up to the configured send timeout when the channel is full,
+    /// then drops the event.
+    pub fn send(&self, line: String) {
+        let stats = Stats(self.counters.clone());
+        let Some(sender) = &self.sender else {
+            stats.add(|c| &c.dropped, 1);
+            return;
+        };
+        let mut message = match sender.try_send(Message::Event(line)) {
+            Ok(()) => return,
+            Err(TrySendError::Full(message)) => message,
+            Err(TrySendError::Disconnected(_)) => {
+                stats.add(|c| &c.dropped, 1);
+                return;
+            }
+        };
+
+        stats.add(|c| &c.backpressure_waits, 1);
+        let deadline = Instant::now() + self.send_timeout;
+        loop {
+            std::thread::sleep(Duration::from_millis(1));
+            message = match sender.try_send(message) {
+                Ok(()) => return,
+                Err(TrySendError::Full(message)) if Instant::now() < deadline => message,
+                Err(_) => {
+                    stats.add(|c| &c.dropped, 1);
+                    return;
+                }
+            };
+        }
+    }
+
+    /// Wait until every event sent so far is written and synced to disk
+    pub fn flush(&self) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        let (ack, done) = mpsc::channel();
+        if sender.send(Message::Flush(ack)).is_ok() {
+            let _ = done.recv();
+        }
+    }
+
+    /// This sink's counters
+    pub fn metrics(&self) -> EventSinkMetrics {
+        self.counters.snapshot()
+    }
+}
+
+impl Drop for EventSink {
+    /// Writes out queued events before returning
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        let metrics = self.metrics();
+        if metrics.dropped > 0 {
+            tracing::warn!(
+                written = metrics.written,
+                dropped = metrics.dropped,
+                backpressure_waits = metrics.backpressure_waits,
+                "Agent events were dropped"
+            );
+        } else if metrics.written > 0 {
+            tracing::debug!(
+                written = metrics.written,
+                batches = metrics.batches,
+                max_flush_us = metrics.max_flush_us,
+                "Agent event writer closed"
+            );
+        }
+    }
+}
+
+/// Writer thread: drain the channel in batches until every sender is gone
+fn run(file: File, receiver: Receiver<Message>, config: EventSinkConfig, stats: Stats) {
+    let mut out = BufWriter::new(file);
+    let mut unsynced = false;
+    let mut last_sync = Instant::now();
+
+    loop {
+        let first = match receiver.recv_timeout(config.fsync_interval) {
+            Ok(message) => Some(message),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        let mut lines = Vec::new();
+        let mut acks = Vec::new();
+        let mut next = first;
+        while let Some(message) = next {
+            match message {
+                Message::Event(line) => lines.push(line),
+                Message::Flush(ack) => acks.push(ack),
+            }
+            next = if lines.len() < config.batch_size {
+                receiver.try_recv().ok()
+            } else {
+                None
+            };
+        }
+
+        if !lines.is_empty() {
+            let started = Instant::now();
+            let written = lines
+                .iter()
+                .try_for_each(|line| writeln!(out, "{}", line))
+                .and_then(|()| out.flush());
+            match written {
+                Ok(()) => {
+                    stats.add(|c| &c.written, lines.len() as u64);
+                    stats.add(|c| &c.batches, 1);
+                    stats.flushed(started.elapsed());
+                    unsynced = true;
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, count = lines.len(), "Failed to write agent events");
+                    stats.add(|c| &c.dropped, lines.len() as u64);
+                }
+            }
+        }
+
+        if unsynced && (!acks.is_empty() || last_sync.elapsed() >= config.fsync_interval) {
+            sync(&out, &stats);
+            unsynced = false;
+            last_sync = Instant::now();
+        }
+        for ack in acks {
+            let _ = ack.send(());
+        }
+    }
+
+    if unsynced {
+        sync(&out, &stats);
+    }
+}
+
+fn sync(out: &BufWriter<File>, stats: &Stats) {
+    match out.get_ref().sync_data() {
+        Ok(()) => stats.add(|c| &c.fsyncs, 1),
+        Err(e) => tracing::debug!(error = %e, "Failed to sync agent events"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::OpenOptions;
+
+    fn open(path: &std::path::Path) -> File {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_events_are_written_in_order_and_synced_on_flush() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let sink = EventSink::spawn(open(&path), EventSinkConfig::default());
+
+        for i in 0..100 {
+            sink.send(format!("{{\"n\":{}}}", i));
+        }
+        sink.flush();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 100);
+        assert_eq!(lines[0], "{\"n\":0}");
+        assert_eq!(lines[99], "{\"n\":99}");
+
+        let metrics = sink.metrics();
+        assert_eq!(metrics.written, 100);
+        assert_eq!(metrics.dropped, 0);
+        assert!(metrics.batches >= 1);
+        assert!(metrics.fsyncs >= 1);
+    }
+
+    #[test]
+    fn test_drop_writes_out_queued_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let sink = EventSink::spawn(
+            open(&path),
+            EventSinkConfig {
+                capacity: 4,
+                batch_size: 2,
+                ..EventSinkConfig::default()
+            },
+        );
+        for i in 0..20 {
+            sink.send(format!("{}", i));
+        }
+        let metrics = sink.metrics();
+        drop(sink);
+
+        let written = std::fs::read_to_string(&path).unwrap().lines().count() as u64;
+        assert_eq!(written + metrics.dropped, 20);
+        assert!(process_metrics().written >= written);
+    }
+}
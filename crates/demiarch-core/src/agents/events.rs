@@ -2,16 +2,31 @@
 //!
 //! Provides real-time event streaming for agent lifecycle events.
 //! Events are written to a JSONL file that can be watched by the TUI.
+//!
+//! The hot file only holds the active session. When a new writer starts,
+//! earlier sessions are rolled into gzipped archives under
+//! `~/.demiarch/events/<session>.jsonl.gz`, so watchers that reread the hot
+//! file stay cheap. [`read_session_events`] reads a session from either place.
+//!
+//! Writes go through an [`EventSink`] on a background thread, so emitting an
+//! event never waits on disk; see [`super::event_sink`] for batching and
+//! backpressure.
 
 use chrono::{DateTime, Utc};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+use super::event_sink::{EventSink, EventSinkConfig, EventSinkMetrics};
 use super::{AgentId, AgentStatus, AgentType};
+use crate::context::WindowStats;
+use crate::pagination::{Page, PageRequest};
 
 /// Path to the agent events file
 pub fn events_file_path() -> PathBuf {
@@ -21,6 +36,23 @@ pub fn events_file_path() -> PathBuf {
         .join("agent-events.jsonl")
 }
 
+/// Directory holding archived sessions
+pub fn events_archive_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".demiarch")
+        .join("events")
+}
+
+/// Path to a session's archive
+pub fn session_archive_path(session_id: Uuid) -> PathBuf {
+    archive_path_in(&events_archive_dir(), session_id)
+}
+
+fn archive_path_in(dir: &Path, session_id: Uuid) -> PathBuf {
+    dir.join(format!("{}.jsonl.gz", session_id))
+}
+
 /// Agent lifecycle event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentEvent {
@@ -34,6 +66,12 @@ pub struct AgentEvent {
     pub event_type: AgentEventType,
     /// Agent details
     pub agent: AgentEventData,
+    /// File details (file events only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<FileEventData>,
+    /// Context window usage (context update events only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<WindowStats>,
 }
 
 /// Type of agent event
@@ -54,6 +92,14 @@ pub enum AgentEventType {
     Cancelled,
     /// Token usage update
     TokenUpdate,
+    /// A file was extracted from an LLM response
+    FileExtracted,
+    /// A file was written to disk
+    FileWritten,
+    /// Untrusted content was withheld from an agent as a suspected prompt injection
+    ContentQuarantined,
+    /// The agent's prompt filled part of its context window
+    ContextUpdate,
 }
 
 /// Agent data included in events
@@ -79,15 +125,68 @@ pub struct AgentEventData {
     pub error: Option<String>,
 }
 
+/// File data included in file events
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileEventData {
+    /// File path (relative to the output directory)
+    pub path: String,
+    /// Content size in bytes
+    pub bytes: u64,
+    /// Whether the file is new (false if it modifies an existing file)
+    pub is_new: bool,
+    /// 1-based position of this file in the batch
+    pub index: usize,
+    /// Number of files in the batch
+    pub total: usize,
+}
+
+impl FileEventData {
+    /// Create file data for the `index`-th (1-based) of `total` files
+    pub fn new(
+        path: impl Into<String>,
+        content: &str,
+        is_new: bool,
+        index: usize,
+        total: usize,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            bytes: content.len() as u64,
+            is_new,
+            index,
+            total,
+        }
+    }
+}
+
+/// Progress of a single file within the current session
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileProgress {
+    /// File path (relative to the output directory)
+    pub path: String,
+    /// Content size in bytes (from the latest event)
+    pub bytes: u64,
+    /// Whether the file is new
+    pub is_new: bool,
+    /// Whether the file has been written to disk
+    pub written: bool,
+}
+
 /// Event writer for streaming agent events to file
 pub struct AgentEventWriter {
     session_id: Uuid,
-    file: Mutex<Option<File>>,
+    sink: Option<EventSink>,
 }
 
 impl AgentEventWriter {
     /// Create a new event writer
+    ///
+    /// Starts a new session, so every earlier session is archived.
     pub fn new() -> Self {
+        Self::open(Uuid::new_v4())
+    }
+
+    fn open(session_id: Uuid) -> Self {
         let path = events_file_path();
 
         // Ensure directory exists
@@ -95,17 +194,33 @@ impl AgentEventWriter {
             let _ = std::fs::create_dir_all(parent);
         }
 
+        // Roll other sessions out of the hot file
+        if let Err(e) = compact_events_at(&path, &events_archive_dir(), Some(session_id)) {
+            tracing::warn!(error = %e, "Failed to archive agent events");
+        }
+
         // Open file for appending (create if doesn't exist)
-        let file = OpenOptions::new()
+        let sink = OpenOptions::new()
             .create(true)
             .append(true)
             .open(&path)
-            .ok();
+            .ok()
+            .map(|file| EventSink::spawn(file, EventSinkConfig::default()));
 
-        Self {
-            session_id: Uuid::new_v4(),
-            file: Mutex::new(file),
-        }
+        Self { session_id, sink }
+    }
+
+    /// Create a writer that appends to the most recent session
+    ///
+    /// Used for follow-up work (such as applying reviewed files) so that UIs
+    /// watching the current session see it. Starts a new session if there
+    /// are no events yet.
+    pub fn resume_latest() -> Self {
+        let session_id = read_recent_events(1)
+            .pop()
+            .map(|last| last.session_id)
+            .unwrap_or_else(Uuid::new_v4);
+        Self::open(session_id)
     }
 
     /// Get the session ID
@@ -113,22 +228,48 @@ impl AgentEventWriter {
         self.session_id
     }
 
+    /// Wait until every event emitted so far is on disk
+    ///
+    /// Dropping the writer does the same.
+    pub fn flush(&self) {
+        if let Some(ref sink) = self.sink {
+            sink.flush();
+        }
+    }
+
+    /// Write counters for this writer
+    pub fn metrics(&self) -> EventSinkMetrics {
+        self.sink
+            .as_ref()
+            .map(EventSink::metrics)
+            .unwrap_or_default()
+    }
+
     /// Write an event to the file
     pub fn write_event(&self, event_type: AgentEventType, agent: AgentEventData) {
+        self.write(event_type, agent, None, None);
+    }
+
+    fn write(
+        &self,
+        event_type: AgentEventType,
+        agent: AgentEventData,
+        file: Option<FileEventData>,
+        context: Option<WindowStats>,
+    ) {
         let event = AgentEvent {
             timestamp: Utc::now(),
             event_id: Uuid::new_v4(),
             session_id: self.session_id,
             event_type,
             agent,
+            file,
+            context,
         };
 
-        if let Ok(mut file_guard) = self.file.lock() {
-            if let Some(ref mut file) = *file_guard {
-                if let Ok(json) = serde_json::to_string(&event) {
-                    let _ = writeln!(file, "{}", json);
-                    let _ = file.flush();
-                }
+        if let Some(ref sink) = self.sink {
+            if let Ok(json) = serde_json::to_string(&event) {
+                sink.send(json);
             }
         }
     }
@@ -195,6 +336,73 @@ impl AgentEventWriter {
         );
     }
 
+    /// Emit a file extracted event
+    pub fn emit_file_extracted(&self, id: &AgentId, file: FileEventData) {
+        self.emit_file(AgentEventType::FileExtracted, id, file);
+    }
+
+    /// Emit a file written event
+    pub fn emit_file_written(&self, id: &AgentId, file: FileEventData) {
+        self.emit_file(AgentEventType::FileWritten, id, file);
+    }
+
+    fn emit_file(&self, event_type: AgentEventType, id: &AgentId, file: FileEventData) {
+        self.write(
+            event_type,
+            AgentEventData {
+                id: id.to_string(),
+                agent_type: String::new(),
+                name: String::new(),
+                parent_id: None,
+                path: String::new(),
+                status: "running".to_string(),
+                tokens: 0,
+                task: None,
+                error: None,
+            },
+            Some(file),
+            None,
+        );
+    }
+
+    /// Emit a context update event with the agent's context window usage
+    pub fn emit_context_usage(&self, id: &AgentId, stats: WindowStats) {
+        self.write(
+            AgentEventType::ContextUpdate,
+            AgentEventData {
+                id: id.to_string(),
+                agent_type: String::new(),
+                name: String::new(),
+                parent_id: None,
+                path: String::new(),
+                status: "running".to_string(),
+                tokens: 0,
+                task: None,
+                error: None,
+            },
+            None,
+            Some(stats),
+        );
+    }
+
+    /// Emit a content quarantined event
+    pub fn emit_content_quarantined(&self, id: &AgentId, summary: &str) {
+        self.write_event(
+            AgentEventType::ContentQuarantined,
+            AgentEventData {
+                id: id.to_string(),
+                agent_type: String::new(),
+                name: String::new(),
+                parent_id: None,
+                path: String::new(),
+                status: "running".to_string(),
+                tokens: 0,
+                task: None,
+                error: Some(summary.to_string()),
+            },
+        );
+    }
+
     /// Emit a failed event
     pub fn emit_failed(&self, id: &AgentId, error: &str) {
         self.write_event(
@@ -323,6 +531,290 @@ pub fn read_current_session_events() -> Vec<AgentEvent> {
     }
 }
 
+/// Result of archiving sessions out of the hot file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompactionSummary {
+    /// Sessions appended to archives
+    pub sessions: usize,
+    /// Events moved out of the hot file
+    pub events: usize,
+}
+
+/// A session with recorded agent events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventSession {
+    /// Agent event session ID
+    pub session_id: Uuid,
+    /// Whether the session lives in an archive rather than the hot file
+    pub archived: bool,
+    /// Last time the session was written to
+    pub last_event_at: Option<DateTime<Utc>>,
+}
+
+/// Just enough of an event line to route it during compaction
+#[derive(Deserialize)]
+struct EventSessionRef {
+    session_id: Uuid,
+}
+
+/// Archive every session except `keep` out of the hot file
+pub fn compact_events(keep: Option<Uuid>) -> io::Result<CompactionSummary> {
+    compact_events_at(&events_file_path(), &events_archive_dir(), keep)
+}
+
+fn compact_events_at(
+    hot: &Path,
+    archive_dir: &Path,
+    keep: Option<Uuid>,
+) -> io::Result<CompactionSummary> {
+    let content = match std::fs::read_to_string(hot) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(CompactionSummary::default()),
+        Err(e) => return Err(e),
+    };
+
+    let mut kept = String::new();
+    let mut archived: HashMap<Uuid, String> = HashMap::new();
+    let mut summary = CompactionSummary::default();
+    for line in content.lines() {
+        // Lines that don't parse are partial writes; drop them
+        let Ok(event) = serde_json::from_str::<EventSessionRef>(line) else {
+            continue;
+        };
+        let target = if Some(event.session_id) == keep {
+            &mut kept
+        } else {
+            summary.events += 1;
+            archived.entry(event.session_id).or_default()
+        };
+        target.push_str(line);
+        target.push('\n');
+    }
+
+    if archived.is_empty() {
+        return Ok(summary);
+    }
+
+    std::fs::create_dir_all(archive_dir)?;
+    for (session_id, lines) in &archived {
+        // Each compaction appends a gzip member; readers decode them in sequence
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(archive_path_in(archive_dir, *session_id))?;
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        encoder.write_all(lines.as_bytes())?;
+        encoder.finish()?;
+    }
+    summary.sessions = archived.len();
+
+    // Truncate in place rather than replacing the file, so writers holding an
+    // append handle keep writing to the hot file
+    let mut file = OpenOptions::new().write(true).truncate(true).open(hot)?;
+    file.write_all(kept.as_bytes())?;
+
+    Ok(summary)
+}
+
+fn parse_events(reader: impl BufRead) -> Vec<AgentEvent> {
+    reader
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+fn read_archive(path: &Path) -> io::Result<Vec<AgentEvent>> {
+    let file = File::open(path)?;
+    Ok(parse_events(BufReader::new(MultiGzDecoder::new(file))))
+}
+
+fn read_hot(path: &Path) -> Vec<AgentEvent> {
+    File::open(path)
+        .map(|file| parse_events(BufReader::new(file)))
+        .unwrap_or_default()
+}
+
+/// Read all events of a session, archived and hot, in write order
+pub fn read_session_events(session_id: Uuid) -> Vec<AgentEvent> {
+    read_session_events_at(&events_file_path(), &events_archive_dir(), session_id)
+}
+
+fn read_session_events_at(hot: &Path, archive_dir: &Path, session_id: Uuid) -> Vec<AgentEvent> {
+    let mut events = read_archive(&archive_path_in(archive_dir, session_id)).unwrap_or_default();
+    events.extend(
+        read_hot(hot)
+            .into_iter()
+            .filter(|e| e.session_id == session_id),
+    );
+    events
+}
+
+/// Read events of a session, or of the current session if none is given
+pub fn read_events_for(session_id: Option<Uuid>) -> Vec<AgentEvent> {
+    match session_id {
+        Some(id) => read_session_events(id),
+        None => read_current_session_events(),
+    }
+}
+
+/// Read one page of a session's events (or the current session's), counting
+/// `offset` back from the newest; events within the page are in write order
+pub fn read_events_page(session_id: Option<Uuid>, page: PageRequest) -> Page<AgentEvent> {
+    Page::from_vec_newest(read_events_for(session_id), page)
+}
+
+/// Read events written at or after `since`, across archives and the hot file
+///
+/// Archives last modified before `since` can only hold older events, so they
+/// are skipped without being decompressed.
+pub fn read_events_since(since: DateTime<Utc>) -> Vec<AgentEvent> {
+    let mut events: Vec<AgentEvent> = archive_entries(&events_archive_dir())
+        .into_iter()
+        .filter(|(_, _, modified)| modified.is_none_or(|m| m >= since))
+        .filter_map(|(_, path, _)| read_archive(&path).ok())
+        .flatten()
+        .chain(read_hot(&events_file_path()))
+        .filter(|e| e.timestamp >= since)
+        .collect();
+    events.sort_by_key(|e| e.timestamp);
+    events
+}
+
+/// Sessions with recorded events, most recent first
+pub fn list_event_sessions() -> Vec<EventSession> {
+    list_event_sessions_at(&events_file_path(), &events_archive_dir())
+}
+
+fn list_event_sessions_at(hot: &Path, archive_dir: &Path) -> Vec<EventSession> {
+    let mut sessions: Vec<EventSession> = Vec::new();
+    for event in read_hot(hot) {
+        match sessions
+            .iter_mut()
+            .find(|s| s.session_id == event.session_id)
+        {
+            Some(session) => session.last_event_at = Some(event.timestamp),
+            None => sessions.push(EventSession {
+                session_id: event.session_id,
+                archived: false,
+                last_event_at: Some(event.timestamp),
+            }),
+        }
+    }
+
+    for (session_id, _, modified) in archive_entries(archive_dir) {
+        // A session still in the hot file is listed there
+        if sessions.iter().any(|s| s.session_id == session_id) {
+            continue;
+        }
+        sessions.push(EventSession {
+            session_id,
+            archived: true,
+            last_event_at: modified,
+        });
+    }
+
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.last_event_at));
+    sessions
+}
+
+/// Archives in a directory as (session, path, last modified)
+fn archive_entries(dir: &Path) -> Vec<(Uuid, PathBuf, Option<DateTime<Utc>>)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path.file_name()?.to_str()?;
+            let session_id = Uuid::parse_str(name.strip_suffix(".jsonl.gz")?).ok()?;
+            let modified = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .map(DateTime::<Utc>::from);
+            Some((session_id, path, modified))
+        })
+        .collect()
+}
+
+/// Collapse file events into per-file progress, in extraction order
+pub fn file_progress(events: &[AgentEvent]) -> Vec<FileProgress> {
+    let mut files: Vec<FileProgress> = Vec::new();
+    for event in events {
+        let Some(ref file) = event.file else {
+            continue;
+        };
+        let written = event.event_type == AgentEventType::FileWritten;
+        match files.iter_mut().find(|f| f.path == file.path) {
+            Some(existing) => {
+                existing.bytes = file.bytes;
+                existing.is_new = file.is_new;
+                existing.written |= written;
+            }
+            None => files.push(FileProgress {
+                path: file.path.clone(),
+                bytes: file.bytes,
+                is_new: file.is_new,
+                written,
+            }),
+        }
+    }
+    files
+}
+
+/// Number of agents that were spawned but have not finished
+///
+/// An agent is finished once it has a completed, failed or cancelled event.
+pub fn active_agent_count(events: &[AgentEvent]) -> usize {
+    active_agents(events).len()
+}
+
+/// Agents that were spawned but have not finished, in spawn order
+fn active_agents(events: &[AgentEvent]) -> Vec<&AgentEventData> {
+    let mut active: Vec<&AgentEventData> = Vec::new();
+    for event in events {
+        let id = event.agent.id.as_str();
+        match event.event_type {
+            AgentEventType::Spawned | AgentEventType::Started
+                if !active.iter().any(|a| a.id == id) =>
+            {
+                active.push(&event.agent);
+            }
+            AgentEventType::Completed | AgentEventType::Failed | AgentEventType::Cancelled => {
+                active.retain(|a| a.id != id);
+            }
+            _ => {}
+        }
+    }
+    active
+}
+
+/// Record every unfinished agent of the current session as cancelled
+///
+/// Used when the process is stopped mid-run so that watchers do not show
+/// agents as running forever. Returns the number of agents cancelled.
+pub fn cancel_active_agents(reason: &str) -> usize {
+    let events = read_current_session_events();
+    let active = active_agents(&events);
+    if active.is_empty() {
+        return 0;
+    }
+    let writer = AgentEventWriter::resume_latest();
+    for agent in &active {
+        writer.write_event(
+            AgentEventType::Cancelled,
+            AgentEventData {
+                status: "cancelled".to_string(),
+                error: Some(reason.to_string()),
+                ..(*agent).clone()
+            },
+        );
+    }
+    active.len()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -345,10 +837,140 @@ mod tests {
                 task: Some("Build a hello world app".to_string()),
                 error: None,
             },
+            file: None,
+            context: None,
         };
 
         let json = serde_json::to_string(&event).unwrap();
+        assert!(!json.contains("\"file\""));
         let parsed: AgentEvent = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.event_type, AgentEventType::Spawned);
     }
+
+    fn file_event(event_type: AgentEventType, path: &str, bytes: usize) -> AgentEvent {
+        AgentEvent {
+            timestamp: Utc::now(),
+            event_id: Uuid::new_v4(),
+            session_id: Uuid::nil(),
+            event_type,
+            agent: AgentEventData {
+                id: "coder".to_string(),
+                agent_type: String::new(),
+                name: String::new(),
+                parent_id: None,
+                path: String::new(),
+                status: "running".to_string(),
+                tokens: 0,
+                task: None,
+                error: None,
+            },
+            file: Some(FileEventData::new(path, &"x".repeat(bytes), true, 1, 2)),
+            context: None,
+        }
+    }
+
+    fn write_hot(path: &Path, events: &[AgentEvent]) {
+        let mut file = File::create(path).unwrap();
+        for event in events {
+            writeln!(file, "{}", serde_json::to_string(event).unwrap()).unwrap();
+        }
+    }
+
+    fn session_event(session_id: Uuid, path: &str) -> AgentEvent {
+        let mut event = file_event(AgentEventType::FileExtracted, path, 1);
+        event.session_id = session_id;
+        event
+    }
+
+    #[test]
+    fn test_compaction_archives_other_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let hot = dir.path().join("agent-events.jsonl");
+        let archive_dir = dir.path().join("events");
+        let old = Uuid::new_v4();
+        let active = Uuid::new_v4();
+
+        write_hot(
+            &hot,
+            &[
+                session_event(old, "a.rs"),
+                session_event(active, "b.rs"),
+                session_event(old, "c.rs"),
+            ],
+        );
+        let summary = compact_events_at(&hot, &archive_dir, Some(active)).unwrap();
+        assert_eq!(summary.sessions, 1);
+        assert_eq!(summary.events, 2);
+
+        let remaining = read_hot(&hot);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].session_id, active);
+
+        // A second compaction appends another gzip member to the same archive
+        write_hot(&hot, &[session_event(old, "d.rs")]);
+        compact_events_at(&hot, &archive_dir, Some(active)).unwrap();
+        write_hot(&hot, &[session_event(old, "e.rs")]);
+
+        let events = read_session_events_at(&hot, &archive_dir, old);
+        let paths: Vec<_> = events
+            .iter()
+            .map(|e| e.file.as_ref().unwrap().path.as_str())
+            .collect();
+        assert_eq!(paths, ["a.rs", "c.rs", "d.rs", "e.rs"]);
+
+        let sessions = list_event_sessions_at(&hot, &archive_dir);
+        assert_eq!(sessions.len(), 1);
+        assert!(!sessions[0].archived);
+    }
+
+    #[test]
+    fn test_compaction_without_other_sessions_is_noop() {
+        let dir = tempfile::tempdir().unwrap();
+        let hot = dir.path().join("agent-events.jsonl");
+        let archive_dir = dir.path().join("events");
+        let active = Uuid::new_v4();
+
+        assert_eq!(
+            compact_events_at(&hot, &archive_dir, Some(active)).unwrap(),
+            CompactionSummary::default()
+        );
+
+        write_hot(&hot, &[session_event(active, "a.rs")]);
+        compact_events_at(&hot, &archive_dir, Some(active)).unwrap();
+        assert!(!archive_dir.exists());
+        assert_eq!(read_hot(&hot).len(), 1);
+    }
+
+    #[test]
+    fn test_file_progress() {
+        let events = vec![
+            file_event(AgentEventType::FileExtracted, "src/a.rs", 10),
+            file_event(AgentEventType::FileExtracted, "src/b.rs", 20),
+            file_event(AgentEventType::FileWritten, "src/a.rs", 12),
+        ];
+
+        let json = serde_json::to_string(&events[0]).unwrap();
+        let parsed: AgentEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.file.unwrap().bytes, 10);
+
+        let progress = file_progress(&events);
+        assert_eq!(progress.len(), 2);
+        assert_eq!(progress[0].path, "src/a.rs");
+        assert!(progress[0].written);
+        assert_eq!(progress[0].bytes, 12);
+        assert!(!progress[1].written);
+    }
+
+    #[test]
+    fn test_active_agent_count() {
+        let mut spawned = file_event(AgentEventType::Spawned, "", 0);
+        spawned.file = None;
+        let mut other = spawned.clone();
+        other.agent.id = "reviewer".to_string();
+        let mut done = spawned.clone();
+        done.event_type = AgentEventType::Completed;
+
+        assert_eq!(active_agent_count(&[spawned.clone(), other.clone()]), 2);
+        assert_eq!(active_agent_count(&[spawned, other, done]), 1);
+    }
 }
@@ -0,0 +1,499 @@
+//! Post-generation formatting and linting
+//!
+//! Runs the project's formatters and linters (rustfmt, prettier, black,
+//! eslint) over generated files before they are finalized. Tools run through
+//! the [`SandboxedRunner`]: source goes in on stdin and the formatted result
+//! comes back on stdout, so no tool ever writes to the project directly.
+//!
+//! Which tools run is decided by [`select_tools`]: an explicit list from
+//! configuration, or otherwise the tools the project has configuration files
+//! for plus defaults for the project's framework. Tools that are not
+//! installed are skipped.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::config::FormattingConfig;
+use crate::infrastructure::sandbox::{SandboxOutput, SandboxedRunner};
+
+/// A supported formatter or linter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FormatterTool {
+    Eslint,
+    Prettier,
+    Rustfmt,
+    Black,
+}
+
+impl FormatterTool {
+    /// All tools, in the order they are run (linters before formatters)
+    pub const ALL: [FormatterTool; 4] = [Self::Eslint, Self::Prettier, Self::Rustfmt, Self::Black];
+
+    /// Tool name, also the program that is run
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Eslint => "eslint",
+            Self::Prettier => "prettier",
+            Self::Rustfmt => "rustfmt",
+            Self::Black => "black",
+        }
+    }
+
+    /// Parse a tool name
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s.trim().to_lowercase())
+    }
+
+    /// Whether the tool reports lint findings (as opposed to only formatting)
+    pub fn is_linter(&self) -> bool {
+        matches!(self, Self::Eslint)
+    }
+
+    /// Whether the tool applies to a file, based on its extension
+    pub fn handles(&self, path: &Path) -> bool {
+        let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+            return false;
+        };
+        let extensions: &[&str] = match self {
+            Self::Eslint => &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts", "vue"],
+            Self::Prettier => &[
+                "js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts", "json", "css", "scss",
+                "less", "html", "vue", "md", "yaml", "yml", "graphql",
+            ],
+            Self::Rustfmt => &["rs"],
+            Self::Black => &["py", "pyi"],
+        };
+        extensions.contains(&ext.to_lowercase().as_str())
+    }
+
+    /// Configuration files that indicate the project uses this tool
+    fn config_files(&self) -> &'static [&'static str] {
+        match self {
+            Self::Eslint => &[
+                "eslint.config.js",
+                "eslint.config.mjs",
+                "eslint.config.cjs",
+                "eslint.config.ts",
+                ".eslintrc",
+                ".eslintrc.js",
+                ".eslintrc.cjs",
+                ".eslintrc.json",
+                ".eslintrc.yml",
+                ".eslintrc.yaml",
+            ],
+            Self::Prettier => &[
+                ".prettierrc",
+                ".prettierrc.json",
+                ".prettierrc.yml",
+                ".prettierrc.yaml",
+                ".prettierrc.js",
+                ".prettierrc.cjs",
+                ".prettierrc.toml",
+                "prettier.config.js",
+                "prettier.config.cjs",
+                "prettier.config.mjs",
+            ],
+            Self::Rustfmt => &["rustfmt.toml", ".rustfmt.toml"],
+            Self::Black => &[],
+        }
+    }
+
+    /// Whether the project at `root` has configuration for this tool
+    pub fn is_configured(&self, root: &Path) -> bool {
+        if self.config_files().iter().any(|f| root.join(f).is_file()) {
+            return true;
+        }
+        match self {
+            Self::Black => std::fs::read_to_string(root.join("pyproject.toml"))
+                .is_ok_and(|contents| contents.contains("[tool.black]")),
+            _ => false,
+        }
+    }
+
+    /// Arguments for formatting `path` read from stdin
+    fn args(&self, path: &Path) -> Vec<String> {
+        let path = path.to_string_lossy().to_string();
+        match self {
+            Self::Eslint => vec![
+                "--stdin".to_string(),
+                "--stdin-filename".to_string(),
+                path,
+                "--fix-dry-run".to_string(),
+                "--format".to_string(),
+                "json".to_string(),
+            ],
+            Self::Prettier => vec!["--stdin-filepath".to_string(), path],
+            Self::Rustfmt => vec!["--edition".to_string(), "2021".to_string()],
+            Self::Black => vec![
+                "--quiet".to_string(),
+                "--stdin-filename".to_string(),
+                path,
+                "-".to_string(),
+            ],
+        }
+    }
+}
+
+impl fmt::Display for FormatterTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Severity of a lint finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintSeverity {
+    Warning,
+    Error,
+}
+
+/// A lint finding that remained after auto-fixes were applied
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintFinding {
+    /// File the finding is in
+    pub path: PathBuf,
+    /// Tool that reported it
+    pub tool: FormatterTool,
+    /// 1-based line, if reported
+    pub line: Option<usize>,
+    /// 1-based column, if reported
+    pub column: Option<usize>,
+    /// Severity
+    pub severity: LintSeverity,
+    /// Rule identifier (e.g. "no-unused-vars")
+    pub rule: Option<String>,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        let severity = match self.severity {
+            LintSeverity::Warning => "warning",
+            LintSeverity::Error => "error",
+        };
+        write!(f, ": {} {}", severity, self.message)?;
+        if let Some(ref rule) = self.rule {
+            write!(f, " [{}]", rule)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of running the selected tools over one file
+#[derive(Debug, Clone, Default)]
+pub struct FormatOutcome {
+    /// Content after formatting and auto-fixes
+    pub content: String,
+    /// Whether the content differs from the input
+    pub changed: bool,
+    /// Tools that ran successfully on the file
+    pub applied: Vec<FormatterTool>,
+    /// Lint findings that could not be fixed automatically
+    pub findings: Vec<LintFinding>,
+    /// Tools that failed or timed out (the file is left as it was)
+    pub tool_errors: Vec<String>,
+}
+
+/// Default tools for a project framework
+pub fn framework_defaults(framework: &str) -> Vec<FormatterTool> {
+    match framework.to_lowercase().as_str() {
+        "rust" | "actix" | "axum" | "rocket" => vec![FormatterTool::Rustfmt],
+        "python" | "django" | "flask" | "fastapi" => vec![FormatterTool::Black],
+        "node" | "nodejs" | "react" | "vue" | "angular" | "express" | "nextjs" | "next"
+        | "svelte" => vec![FormatterTool::Eslint, FormatterTool::Prettier],
+        _ => Vec::new(),
+    }
+}
+
+/// Decide which tools to run for a project
+///
+/// An explicit `tools` list in the configuration wins; otherwise tools the
+/// project has configuration files for are combined with the framework
+/// defaults. Linters are dropped when linting is disabled, and the result is
+/// in run order.
+pub fn select_tools(
+    config: &FormattingConfig,
+    framework: Option<&str>,
+    root: &Path,
+) -> Vec<FormatterTool> {
+    if !config.enabled {
+        return Vec::new();
+    }
+
+    let wanted: Vec<FormatterTool> = if config.tools.is_empty() {
+        let mut tools: Vec<FormatterTool> = FormatterTool::ALL
+            .into_iter()
+            .filter(|t| t.is_configured(root))
+            .collect();
+        tools.extend(framework.map(framework_defaults).unwrap_or_default());
+        tools
+    } else {
+        config
+            .tools
+            .iter()
+            .filter_map(|t| FormatterTool::parse(t))
+            .collect()
+    };
+
+    FormatterTool::ALL
+        .into_iter()
+        .filter(|t| wanted.contains(t))
+        .filter(|t| config.lint || !t.is_linter())
+        .collect()
+}
+
+/// Build a sandboxed runner allowed to run exactly the given tools
+///
+/// Tools are started in the project root so they pick up its configuration,
+/// and `node_modules/.bin` is searched before PATH.
+pub fn runner_for(root: &Path, tools: &[FormatterTool], timeout: Duration) -> SandboxedRunner {
+    tools.iter().fold(
+        SandboxedRunner::new(root)
+            .with_search_path(root.join("node_modules").join(".bin"))
+            .with_timeout(timeout),
+        |runner, tool| runner.allow(tool.as_str()),
+    )
+}
+
+/// Run the applicable tools over one file
+///
+/// Tool failures never fail the file: the content from before the failing
+/// tool is kept and the failure is recorded in [`FormatOutcome::tool_errors`].
+pub async fn format_source(
+    runner: &SandboxedRunner,
+    tools: &[FormatterTool],
+    path: &Path,
+    content: &str,
+) -> FormatOutcome {
+    let mut outcome = FormatOutcome {
+        content: content.to_string(),
+        ..Default::default()
+    };
+
+    for tool in tools.iter().filter(|t| t.handles(path)) {
+        if runner.resolve(tool.as_str()).is_none() {
+            debug!(tool = %tool, "Formatter not installed, skipping");
+            continue;
+        }
+
+        let output = match runner
+            .run(tool.as_str(), &tool.args(path), Some(&outcome.content))
+            .await
+        {
+            Ok(output) => output,
+            Err(e) => {
+                outcome.tool_errors.push(format!("{}: {}", tool, e));
+                continue;
+            }
+        };
+        if output.timed_out {
+            outcome.tool_errors.push(format!("{}: timed out", tool));
+            continue;
+        }
+
+        match tool {
+            FormatterTool::Eslint => match parse_eslint_output(path, &output.stdout) {
+                Some((fixed, findings)) if output.exit_code != Some(2) => {
+                    if let Some(fixed) = fixed {
+                        outcome.content = fixed;
+                    }
+                    outcome.findings.extend(findings);
+                    outcome.applied.push(*tool);
+                }
+                _ => outcome.tool_errors.push(failure_message(*tool, &output)),
+            },
+            _ => {
+                if output.success() && !output.truncated && !output.stdout.trim().is_empty() {
+                    outcome.content = output.stdout;
+                    outcome.applied.push(*tool);
+                } else {
+                    outcome.tool_errors.push(failure_message(*tool, &output));
+                }
+            }
+        }
+    }
+
+    outcome.changed = outcome.content != content;
+    outcome
+}
+
+/// Summarize a failed tool run from the first line of stderr
+fn failure_message(tool: FormatterTool, output: &SandboxOutput) -> String {
+    let detail = output
+        .stderr
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or("no output");
+    match output.exit_code {
+        Some(code) => format!("{} exited with {}: {}", tool, code, detail),
+        None => format!("{} was terminated: {}", tool, detail),
+    }
+}
+
+#[derive(Deserialize)]
+struct EslintFileResult {
+    #[serde(default)]
+    messages: Vec<EslintMessage>,
+    output: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EslintMessage {
+    rule_id: Option<String>,
+    severity: u8,
+    message: String,
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+/// Parse `eslint --format json` output into the fixed source and findings
+fn parse_eslint_output(path: &Path, stdout: &str) -> Option<(Option<String>, Vec<LintFinding>)> {
+    let results: Vec<EslintFileResult> = serde_json::from_str(stdout.trim()).ok()?;
+    let mut fixed = None;
+    let mut findings = Vec::new();
+
+    for result in results {
+        if result.output.is_some() {
+            fixed = result.output;
+        }
+        findings.extend(result.messages.into_iter().map(|m| LintFinding {
+            path: path.to_path_buf(),
+            tool: FormatterTool::Eslint,
+            line: m.line,
+            column: m.column,
+            severity: if m.severity >= 2 {
+                LintSeverity::Error
+            } else {
+                LintSeverity::Warning
+            },
+            rule: m.rule_id,
+            message: m.message,
+        }));
+    }
+
+    Some((fixed, findings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_parse_and_handles() {
+        assert_eq!(
+            FormatterTool::parse("Prettier"),
+            Some(FormatterTool::Prettier)
+        );
+        assert_eq!(FormatterTool::parse("gofmt"), None);
+        assert!(FormatterTool::Rustfmt.handles(Path::new("src/lib.rs")));
+        assert!(FormatterTool::Prettier.handles(Path::new("web/App.TSX")));
+        assert!(!FormatterTool::Black.handles(Path::new("src/lib.rs")));
+        assert!(!FormatterTool::Eslint.handles(Path::new("Makefile")));
+    }
+
+    #[test]
+    fn test_select_tools_from_framework_and_config_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = FormattingConfig::default();
+
+        assert_eq!(
+            select_tools(&config, Some("axum"), dir.path()),
+            vec![FormatterTool::Rustfmt]
+        );
+        assert!(select_tools(&config, None, dir.path()).is_empty());
+
+        std::fs::write(dir.path().join(".prettierrc"), "{}").unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "[tool.black]\n").unwrap();
+        assert_eq!(
+            select_tools(&config, Some("react"), dir.path()),
+            vec![
+                FormatterTool::Eslint,
+                FormatterTool::Prettier,
+                FormatterTool::Black
+            ]
+        );
+
+        let no_lint = FormattingConfig {
+            lint: false,
+            ..FormattingConfig::default()
+        };
+        assert_eq!(
+            select_tools(&no_lint, Some("react"), dir.path()),
+            vec![FormatterTool::Prettier, FormatterTool::Black]
+        );
+    }
+
+    #[test]
+    fn test_select_tools_explicit_and_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let explicit = FormattingConfig {
+            tools: vec!["rustfmt".to_string(), "eslint".to_string()],
+            ..FormattingConfig::default()
+        };
+        assert_eq!(
+            select_tools(&explicit, Some("python"), dir.path()),
+            vec![FormatterTool::Eslint, FormatterTool::Rustfmt]
+        );
+
+        let disabled = FormattingConfig {
+            enabled: false,
+            ..FormattingConfig::default()
+        };
+        assert!(select_tools(&disabled, Some("rust"), dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_parse_eslint_output() {
+        let stdout = r#"[{"filePath":"/p/src/a.js","messages":[
+            {"ruleId":"no-unused-vars","severity":2,"message":"'x' is unused","line":1,"column":5},
+            {"ruleId":null,"severity":1,"message":"note","line":3,"column":1}
+        ],"output":"const y = 1;\n"}]"#;
+
+        let (fixed, findings) = parse_eslint_output(Path::new("src/a.js"), stdout).unwrap();
+        assert_eq!(fixed.as_deref(), Some("const y = 1;\n"));
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].severity, LintSeverity::Error);
+        assert_eq!(findings[0].rule.as_deref(), Some("no-unused-vars"));
+        assert_eq!(
+            findings[0].to_string(),
+            "src/a.js:1:5: error 'x' is unused [no-unused-vars]"
+        );
+        assert_eq!(findings[1].severity, LintSeverity::Warning);
+
+        assert!(parse_eslint_output(Path::new("a.js"), "Oops").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_format_source_without_installed_tools() {
+        let runner = SandboxedRunner::new(".");
+        let outcome = format_source(
+            &runner,
+            &[FormatterTool::Rustfmt],
+            Path::new("src/lib.rs"),
+            "fn main(){}\n",
+        )
+        .await;
+        assert!(!outcome.changed);
+        assert_eq!(outcome.content, "fn main(){}\n");
+        assert!(outcome.applied.is_empty());
+        assert!(outcome.tool_errors.is_empty());
+    }
+}
@@ -5,6 +5,7 @@
 
 use tracing::debug;
 
+use crate::context::{estimate_messages_tokens, ContextPacker};
 use crate::domain::knowledge::{
     ContextEnricher, EnrichedContext, EnrichmentConfig, KnowledgeGraphRepository,
 };
@@ -51,11 +52,28 @@ pub fn build_messages_from_input(
     build_agent_messages(
         system_prompt,
         &context.inherited_messages,
-        &input.context_messages,
+        &input_context(input, context),
         &input.task,
     )
 }
 
+/// The input's context messages, followed by the candidate pieces that fit
+/// what is left of the agent's context budget
+pub fn input_context(input: &AgentInput, context: &AgentContext) -> Vec<Message> {
+    let mut messages = input.context_messages.clone();
+    if input.context_pieces.is_empty() {
+        return messages;
+    }
+
+    let room = context
+        .remaining_context_budget()
+        .saturating_sub(estimate_messages_tokens(&messages));
+    let report = ContextPacker::new(room).pack(input.context_pieces.clone());
+    report.log(&context.path.to_string());
+    messages.extend(report.messages());
+    messages
+}
+
 // ========== Knowledge Graph Enriched Message Building ==========
 
 /// Build messages with knowledge graph context enrichment
@@ -133,7 +151,7 @@ pub async fn build_enriched_messages_from_input<R: KnowledgeGraphRepository>(
     build_enriched_agent_messages(
         system_prompt,
         &context.inherited_messages,
-        &input.context_messages,
+        &input_context(input, context),
         &input.task,
         enriched.as_ref(),
     )
@@ -152,7 +170,7 @@ pub fn build_messages_with_enrichment(
     build_enriched_agent_messages(
         system_prompt,
         &context.inherited_messages,
-        &input.context_messages,
+        &input_context(input, context),
         &input.task,
         Some(enriched),
     )
@@ -254,7 +272,7 @@ impl<'a, R: KnowledgeGraphRepository> EnrichedMessageBuilder<'a, R> {
         let mut messages = build_enriched_agent_messages(
             self.system_prompt,
             &self.context.inherited_messages,
-            &self.input.context_messages,
+            &input_context(self.input, self.context),
             &self.input.task,
             enriched.as_ref(),
         );
@@ -275,7 +293,7 @@ impl<'a, R: KnowledgeGraphRepository> EnrichedMessageBuilder<'a, R> {
         let mut messages = build_agent_messages(
             self.system_prompt,
             &self.context.inherited_messages,
-            &self.input.context_messages,
+            &input_context(self.input, self.context),
             &self.input.task,
         );
 
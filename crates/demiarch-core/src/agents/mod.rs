@@ -23,15 +23,22 @@
 pub mod code_extraction;
 pub mod coder;
 pub mod context;
+pub mod dependencies;
+pub mod event_sink;
 pub mod events;
+pub mod formatting;
 pub mod message_builder;
 pub mod orchestrator;
+pub mod patch;
 pub mod planner;
 pub mod reviewer;
+pub mod sanitize;
+pub mod security_audit;
 pub mod status;
 pub mod tester;
 pub mod tool;
 pub mod traits;
+pub mod validation;
 
 pub use code_extraction::{
     extract_code_blocks, extract_file_path, extract_files_from_response, looks_like_path,
@@ -39,9 +46,12 @@ pub use code_extraction::{
 };
 pub use coder::CoderAgent;
 pub use context::{AgentContext, AgentId, AgentPath};
+pub use event_sink::{EventSinkConfig, EventSinkMetrics};
 pub use events::{
-    clear_events, read_current_session_events, read_recent_events, AgentEvent, AgentEventData,
-    AgentEventReader, AgentEventType, AgentEventWriter,
+    clear_events, compact_events, file_progress, list_event_sessions, read_current_session_events,
+    read_events_for, read_recent_events, read_session_events, AgentEvent, AgentEventData,
+    AgentEventReader, AgentEventType, AgentEventWriter, CompactionSummary, EventSession,
+    FileEventData, FileProgress,
 };
 pub use message_builder::{
     build_agent_messages, build_enriched_agent_messages, build_enriched_messages_from_input,
@@ -49,11 +59,17 @@ pub use message_builder::{
     EnrichedMessageConfig,
 };
 pub use orchestrator::OrchestratorAgent;
+pub use patch::{
+    apply_patch, extract_edits_from_response, resolve_edit, resolve_edits, EditMode, FileEdit,
+    FilePatch, PatchFailure, PatchFormat, PatchHunk, ResolvedEdit,
+};
 pub use planner::PlannerAgent;
 pub use reviewer::ReviewerAgent;
+pub use sanitize::{ContentSanitizer, SanitizedContent, Strictness};
 pub use tester::TesterAgent;
 pub use tool::{AgentTool, AgentToolResult};
 pub use traits::{Agent, AgentCapability, AgentResult, AgentStatus};
+pub use validation::{validate_source, SyntaxLanguage, ValidationReport, ValidationStatus};
 
 use serde::{Deserialize, Serialize};
 
@@ -67,6 +67,7 @@ impl OrchestratorAgent {
         let messages = self.build_messages(&input, &context);
 
         // Call the LLM to analyze the request and plan the approach
+        let context_stats = context.context_stats(&messages);
         let llm_client = context.llm_client();
         let response = match llm_client.complete(messages, None).await {
             Ok(resp) => resp,
@@ -78,6 +79,8 @@ impl OrchestratorAgent {
             }
         };
 
+        context.report_context_usage(context_stats.with_measured(response.input_tokens));
+
         debug!(
             tokens = response.tokens_used,
             "Orchestrator received LLM response"
@@ -0,0 +1,1084 @@
+//! Patch-based file editing
+//!
+//! Coder agents may describe changes to existing files as search/replace edit
+//! blocks or unified diffs instead of re-emitting whole files. This module
+//! parses both formats out of an LLM response and applies them to the current
+//! file contents with whitespace-tolerant matching.
+//!
+//! When a patch cannot be applied, the failure is reported per hunk so the
+//! caller can fall back to requesting a whole-file rewrite for just that file.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::code_extraction::{extract_file_path, ExtractedFile};
+
+/// Opening marker of a search/replace edit block
+pub const SEARCH_MARKER: &str = "<<<<<<< SEARCH";
+/// Divider between the search and replace sections of an edit block
+pub const DIVIDER_MARKER: &str = "=======";
+/// Closing marker of a search/replace edit block
+pub const REPLACE_MARKER: &str = ">>>>>>> REPLACE";
+
+/// Format a patch was expressed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatchFormat {
+    /// `<<<<<<< SEARCH` / `=======` / `>>>>>>> REPLACE` blocks
+    EditBlock,
+    /// `---`/`+++`/`@@` unified diff
+    UnifiedDiff,
+}
+
+/// A single search/replace operation within a file
+///
+/// Unified diff hunks are normalized into this form: context and removed
+/// lines become the search text, context and added lines the replacement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchHunk {
+    /// Text expected to exist in the file (empty to append/create)
+    pub search: String,
+    /// Text to put in its place
+    pub replace: String,
+}
+
+impl PatchHunk {
+    /// Create a new hunk
+    pub fn new(search: impl Into<String>, replace: impl Into<String>) -> Self {
+        Self {
+            search: search.into(),
+            replace: replace.into(),
+        }
+    }
+}
+
+/// A set of hunks targeting one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// Path of the file being edited
+    pub path: PathBuf,
+    /// Format the patch was written in
+    pub format: PatchFormat,
+    /// Hunks in the order they should be applied
+    pub hunks: Vec<PatchHunk>,
+}
+
+impl FilePatch {
+    /// Whether every hunk has an empty search section (i.e. the patch creates the file)
+    pub fn creates_file(&self) -> bool {
+        self.hunks.iter().all(|h| h.search.trim().is_empty())
+    }
+}
+
+/// An edit extracted from an LLM response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileEdit {
+    /// Full file contents
+    Whole(ExtractedFile),
+    /// Incremental patch against the existing file
+    Patch(FilePatch),
+}
+
+impl FileEdit {
+    /// Path of the file this edit targets
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Whole(file) => &file.path,
+            Self::Patch(patch) => &patch.path,
+        }
+    }
+
+    /// Whether this edit is a patch rather than a whole-file write
+    pub fn is_patch(&self) -> bool {
+        matches!(self, Self::Patch(_))
+    }
+}
+
+/// How leniently a hunk's search text had to be matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchQuality {
+    /// Lines matched byte for byte
+    Exact,
+    /// Lines matched after ignoring trailing whitespace
+    TrailingWhitespace,
+    /// Lines matched after ignoring leading and trailing whitespace
+    Indentation,
+}
+
+impl MatchQuality {
+    const ALL: [MatchQuality; 3] = [Self::Exact, Self::TrailingWhitespace, Self::Indentation];
+
+    fn lines_match(&self, file_line: &str, search_line: &str) -> bool {
+        match self {
+            Self::Exact => file_line == search_line,
+            Self::TrailingWhitespace => file_line.trim_end() == search_line.trim_end(),
+            Self::Indentation => file_line.trim() == search_line.trim(),
+        }
+    }
+}
+
+/// Why a single hunk could not be applied
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HunkFailure {
+    /// Zero-based index of the hunk within its patch
+    pub hunk_index: usize,
+    /// Human-readable reason
+    pub reason: String,
+}
+
+/// A patch that could not be applied to its target file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchFailure {
+    /// File the patch targeted
+    pub path: PathBuf,
+    /// Per-hunk failures
+    pub failures: Vec<HunkFailure>,
+}
+
+impl fmt::Display for PatchFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to patch {}", self.path.display())?;
+        for failure in &self.failures {
+            write!(f, "; hunk {}: {}", failure.hunk_index + 1, failure.reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PatchFailure {}
+
+/// Result of successfully applying a patch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchOutcome {
+    /// Patched file contents
+    pub content: String,
+    /// Loosest match quality any hunk needed
+    pub worst_match: MatchQuality,
+}
+
+/// How an edit ended up being written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditMode {
+    /// File did not exist before
+    Created,
+    /// Existing file was replaced wholesale
+    Rewritten,
+    /// Existing file was patched in place
+    Patched,
+}
+
+impl EditMode {
+    /// Get the string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Rewritten => "rewritten",
+            Self::Patched => "patched",
+        }
+    }
+}
+
+/// An edit resolved to final file contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedEdit {
+    /// File path (as given by the LLM, relative to the project root)
+    pub path: PathBuf,
+    /// Final file contents
+    pub content: String,
+    /// How the contents were produced
+    pub mode: EditMode,
+    /// Language, when known
+    pub language: Option<String>,
+}
+
+/// Extract whole-file writes and patches from an LLM response
+///
+/// Code fences following a file path marker are treated as whole-file
+/// contents, unless they contain edit-block markers or a unified diff.
+/// Unified diffs may also carry their own `+++ b/path` headers, in which case
+/// no preceding path marker is needed. Multiple patches to the same file are
+/// merged into a single [`FilePatch`].
+pub fn extract_edits_from_response(content: &str) -> Vec<FileEdit> {
+    let mut edits: Vec<FileEdit> = Vec::new();
+    let mut current_path: Option<PathBuf> = None;
+    let mut whole_content = String::new();
+    let mut whole_language: Option<String> = None;
+    let mut block = String::new();
+    let mut block_language: Option<String> = None;
+    let mut in_code_block = false;
+
+    for line in content.lines() {
+        if !in_code_block {
+            if let Some(path) = extract_file_path(line) {
+                flush_whole(
+                    &mut edits,
+                    current_path.take(),
+                    &mut whole_content,
+                    &mut whole_language,
+                );
+                current_path = Some(path);
+                continue;
+            }
+        }
+
+        if line.starts_with("```") {
+            if in_code_block {
+                in_code_block = false;
+                let language = block_language.take();
+                let patches = classify_block(&block, language.as_deref(), current_path.as_deref());
+                match patches {
+                    Some(patches) => {
+                        for patch in patches {
+                            push_patch(&mut edits, patch);
+                        }
+                    }
+                    None if current_path.is_some() => {
+                        whole_content.push_str(&block);
+                        if language.is_some() {
+                            whole_language = language;
+                        }
+                    }
+                    None => {}
+                }
+                block.clear();
+            } else {
+                in_code_block = true;
+                let lang = line.trim_start_matches('`').trim();
+                block_language = (!lang.is_empty()).then(|| lang.to_string());
+            }
+            continue;
+        }
+
+        if in_code_block {
+            block.push_str(line);
+            block.push('\n');
+        }
+    }
+
+    flush_whole(
+        &mut edits,
+        current_path,
+        &mut whole_content,
+        &mut whole_language,
+    );
+
+    edits
+}
+
+/// Push accumulated whole-file content for `path`, if any
+fn flush_whole(
+    edits: &mut Vec<FileEdit>,
+    path: Option<PathBuf>,
+    content: &mut String,
+    language: &mut Option<String>,
+) {
+    if let Some(path) = path {
+        let trimmed = content.trim().to_string();
+        if !trimmed.is_empty() {
+            edits.push(FileEdit::Whole(ExtractedFile::new(
+                path,
+                trimmed,
+                language.take(),
+            )));
+        }
+    }
+    content.clear();
+    *language = None;
+}
+
+/// Decide whether a fenced block is a patch, returning the parsed patches if so
+fn classify_block(
+    block: &str,
+    language: Option<&str>,
+    path: Option<&Path>,
+) -> Option<Vec<FilePatch>> {
+    if block.lines().any(|l| l.trim() == SEARCH_MARKER) {
+        let path = path?;
+        let hunks = parse_edit_blocks(block);
+        if hunks.is_empty() {
+            return None;
+        }
+        return Some(vec![FilePatch {
+            path: path.to_path_buf(),
+            format: PatchFormat::EditBlock,
+            hunks,
+        }]);
+    }
+
+    let is_diff_language = matches!(language, Some("diff") | Some("patch") | Some("udiff"));
+    let looks_like_diff = block.lines().any(|l| l.starts_with("@@"));
+    if is_diff_language || looks_like_diff {
+        let patches = parse_unified_diff(block, path);
+        if !patches.is_empty() {
+            return Some(patches);
+        }
+    }
+
+    None
+}
+
+/// Append a patch, merging it into an earlier patch for the same file
+fn push_patch(edits: &mut Vec<FileEdit>, patch: FilePatch) {
+    for edit in edits.iter_mut() {
+        if let FileEdit::Patch(existing) = edit {
+            if existing.path == patch.path && existing.format == patch.format {
+                existing.hunks.extend(patch.hunks);
+                return;
+            }
+        }
+    }
+    edits.push(FileEdit::Patch(patch));
+}
+
+/// Parse search/replace edit blocks
+///
+/// Malformed blocks (missing divider or closing marker) are skipped.
+pub fn parse_edit_blocks(text: &str) -> Vec<PatchHunk> {
+    enum State {
+        Outside,
+        Search,
+        Replace,
+    }
+
+    let mut hunks = Vec::new();
+    let mut state = State::Outside;
+    let mut search = String::new();
+    let mut replace = String::new();
+
+    for line in text.lines() {
+        let marker = line.trim();
+        match state {
+            State::Outside => {
+                if marker == SEARCH_MARKER {
+                    search.clear();
+                    replace.clear();
+                    state = State::Search;
+                }
+            }
+            State::Search => {
+                if marker == DIVIDER_MARKER {
+                    state = State::Replace;
+                } else {
+                    search.push_str(line);
+                    search.push('\n');
+                }
+            }
+            State::Replace => {
+                if marker == REPLACE_MARKER {
+                    hunks.push(PatchHunk::new(
+                        std::mem::take(&mut search),
+                        std::mem::take(&mut replace),
+                    ));
+                    state = State::Outside;
+                } else {
+                    replace.push_str(line);
+                    replace.push('\n');
+                }
+            }
+        }
+    }
+
+    hunks
+}
+
+/// Parse a unified diff into per-file patches
+///
+/// File paths are taken from `+++` headers (with any `b/` prefix removed);
+/// hunks appearing before any header are attributed to `default_path`.
+/// Deletions (`+++ /dev/null`) are not supported and are skipped.
+pub fn parse_unified_diff(text: &str, default_path: Option<&Path>) -> Vec<FilePatch> {
+    let mut patches: Vec<FilePatch> = Vec::new();
+    let mut path: Option<PathBuf> = default_path.map(Path::to_path_buf);
+    let mut hunk: Option<PatchHunk> = None;
+
+    fn finish(patches: &mut Vec<FilePatch>, path: &Option<PathBuf>, hunk: Option<PatchHunk>) {
+        let (Some(path), Some(hunk)) = (path, hunk) else {
+            return;
+        };
+        if hunk.search.is_empty() && hunk.replace.is_empty() {
+            return;
+        }
+        match patches.iter_mut().find(|p| &p.path == path) {
+            Some(patch) => patch.hunks.push(hunk),
+            None => patches.push(FilePatch {
+                path: path.clone(),
+                format: PatchFormat::UnifiedDiff,
+                hunks: vec![hunk],
+            }),
+        }
+    }
+
+    for line in text.lines() {
+        if line.starts_with("--- ") {
+            finish(&mut patches, &path, hunk.take());
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("+++ ") {
+            finish(&mut patches, &path, hunk.take());
+            let target = rest.split('\t').next().unwrap_or("").trim();
+            path = if target == "/dev/null" {
+                None
+            } else {
+                Some(PathBuf::from(target.strip_prefix("b/").unwrap_or(target)))
+            };
+            continue;
+        }
+        if line.starts_with("@@") {
+            finish(&mut patches, &path, hunk.take());
+            hunk = Some(PatchHunk::new(String::new(), String::new()));
+            continue;
+        }
+        let Some(current) = hunk.as_mut() else {
+            continue;
+        };
+        if line.starts_with('\\') {
+            // "\ No newline at end of file"
+            continue;
+        }
+        if let Some(removed) = line.strip_prefix('-') {
+            current.search.push_str(removed);
+            current.search.push('\n');
+        } else if let Some(added) = line.strip_prefix('+') {
+            current.replace.push_str(added);
+            current.replace.push('\n');
+        } else {
+            // Context line; LLMs frequently drop the leading space on blank lines
+            let context = line.strip_prefix(' ').unwrap_or(line);
+            current.search.push_str(context);
+            current.search.push('\n');
+            current.replace.push_str(context);
+            current.replace.push('\n');
+        }
+    }
+    finish(&mut patches, &path, hunk.take());
+
+    patches
+}
+
+/// Apply a patch to the given file contents
+///
+/// Each hunk's search text is located line by line, first exactly, then
+/// ignoring trailing whitespace, then ignoring indentation. When a hunk
+/// matches in several places, the first match at or after the previous
+/// hunk's position wins. Indentation-level matches re-indent the
+/// replacement to line up with the file.
+///
+/// All hunks are attempted so that the returned failure lists every hunk
+/// that did not apply.
+pub fn apply_patch(original: &str, patch: &FilePatch) -> Result<PatchOutcome, PatchFailure> {
+    let had_trailing_newline = original.is_empty() || original.ends_with('\n');
+    let mut lines: Vec<String> = original.lines().map(str::to_string).collect();
+    let mut cursor = 0;
+    let mut worst_match = MatchQuality::Exact;
+    let mut failures = Vec::new();
+
+    for (index, hunk) in patch.hunks.iter().enumerate() {
+        let search: Vec<&str> = hunk.search.lines().collect();
+        let replace: Vec<&str> = hunk.replace.lines().collect();
+
+        if search.iter().all(|l| l.trim().is_empty()) {
+            lines.extend(replace.iter().map(|l| l.to_string()));
+            cursor = lines.len();
+            continue;
+        }
+
+        let Some((start, quality)) = find_hunk(&lines, &search, cursor) else {
+            failures.push(HunkFailure {
+                hunk_index: index,
+                reason: format!(
+                    "search text not found (starting with {:?})",
+                    search.iter().find(|l| !l.trim().is_empty()).unwrap_or(&"")
+                ),
+            });
+            continue;
+        };
+
+        let replacement: Vec<String> = if quality == MatchQuality::Indentation {
+            reindent(&search, &replace, &lines[start..start + search.len()])
+        } else {
+            replace.iter().map(|l| l.to_string()).collect()
+        };
+
+        let tail = lines.split_off(start + search.len());
+        lines.truncate(start);
+        lines.extend(replacement);
+        cursor = lines.len();
+        lines.extend(tail);
+        worst_match = worst_match.max(quality);
+    }
+
+    if !failures.is_empty() {
+        return Err(PatchFailure {
+            path: patch.path.clone(),
+            failures,
+        });
+    }
+
+    let mut content = lines.join("\n");
+    if had_trailing_newline && !content.is_empty() {
+        content.push('\n');
+    }
+
+    Ok(PatchOutcome {
+        content,
+        worst_match,
+    })
+}
+
+/// Locate `search` within `lines`, returning the start index and match quality
+fn find_hunk(lines: &[String], search: &[&str], cursor: usize) -> Option<(usize, MatchQuality)> {
+    if search.len() > lines.len() {
+        return None;
+    }
+
+    for quality in MatchQuality::ALL {
+        let matches: Vec<usize> = (0..=lines.len() - search.len())
+            .filter(|&start| {
+                search
+                    .iter()
+                    .enumerate()
+                    .all(|(i, s)| quality.lines_match(&lines[start + i], s))
+            })
+            .collect();
+
+        if let Some(&first) = matches.iter().find(|&&m| m >= cursor).or(matches.first()) {
+            return Some((first, quality));
+        }
+    }
+
+    None
+}
+
+/// Shift replacement lines by the indentation difference between the search
+/// text and the lines it actually matched
+fn reindent(search: &[&str], replace: &[&str], matched: &[String]) -> Vec<String> {
+    let reference = search
+        .iter()
+        .zip(matched)
+        .find(|(s, _)| !s.trim().is_empty());
+
+    let Some((search_line, file_line)) = reference else {
+        return replace.iter().map(|l| l.to_string()).collect();
+    };
+
+    let search_indent = leading_whitespace(search_line);
+    let file_indent = leading_whitespace(file_line);
+
+    replace
+        .iter()
+        .map(|line| {
+            if line.trim().is_empty() {
+                return line.to_string();
+            }
+            if let Some(extra) = file_indent.strip_prefix(search_indent) {
+                format!("{}{}", extra, line)
+            } else if let Some(surplus) = search_indent.strip_prefix(file_indent) {
+                line.strip_prefix(surplus).unwrap_or(line).to_string()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect()
+}
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Resolve an edit against the current contents of its target file
+///
+/// `existing` is `None` when the file does not exist yet. Patches to missing
+/// files only succeed when every hunk has an empty search section.
+pub fn resolve_edit(edit: &FileEdit, existing: Option<&str>) -> Result<ResolvedEdit, PatchFailure> {
+    match edit {
+        FileEdit::Whole(file) => Ok(ResolvedEdit {
+            path: file.path.clone(),
+            content: file.content.clone(),
+            mode: if existing.is_some() {
+                EditMode::Rewritten
+            } else {
+                EditMode::Created
+            },
+            language: file.language.clone(),
+        }),
+        FileEdit::Patch(patch) => {
+            let Some(original) = existing else {
+                if patch.creates_file() {
+                    let outcome = apply_patch("", patch)?;
+                    return Ok(ResolvedEdit {
+                        path: patch.path.clone(),
+                        content: outcome.content,
+                        mode: EditMode::Created,
+                        language: None,
+                    });
+                }
+                return Err(PatchFailure {
+                    path: patch.path.clone(),
+                    failures: vec![HunkFailure {
+                        hunk_index: 0,
+                        reason: "file does not exist".to_string(),
+                    }],
+                });
+            };
+
+            let outcome = apply_patch(original, patch)?;
+            Ok(ResolvedEdit {
+                path: patch.path.clone(),
+                content: outcome.content,
+                mode: EditMode::Patched,
+                language: None,
+            })
+        }
+    }
+}
+
+/// Resolve a batch of edits against files on disk
+///
+/// Paths are resolved relative to `root` when given, otherwise relative to
+/// the current directory. Returns the edits that resolved cleanly alongside
+/// the patches that need a whole-file fallback.
+pub fn resolve_edits(
+    root: Option<&Path>,
+    edits: &[FileEdit],
+) -> (Vec<ResolvedEdit>, Vec<PatchFailure>) {
+    let mut resolved = Vec::new();
+    let mut failures = Vec::new();
+
+    for edit in edits {
+        let full_path = match root {
+            Some(root) => root.join(edit.path()),
+            None => edit.path().to_path_buf(),
+        };
+        let existing = std::fs::read_to_string(&full_path).ok();
+
+        match resolve_edit(edit, existing.as_deref()) {
+            Ok(r) => resolved.push(r),
+            Err(f) => failures.push(f),
+        }
+    }
+
+    (resolved, failures)
+}
+
+/// Number of unchanged lines shown around each change in [`unified_diff`]
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Files larger than this (old lines x new lines) are diffed as a full replacement
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// A single line in a line-level diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    /// Line present in both versions
+    Context(&'a str),
+    /// Line only in the old version
+    Removed(&'a str),
+    /// Line only in the new version
+    Added(&'a str),
+}
+
+/// Compute a line-level diff between two texts
+///
+/// Uses a longest-common-subsequence table after trimming the common prefix
+/// and suffix. Very large inputs degrade to "remove everything, add
+/// everything" rather than allocating an enormous table.
+pub fn line_diff<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_mid = &old_lines[prefix..old_lines.len() - suffix];
+    let new_mid = &new_lines[prefix..new_lines.len() - suffix];
+
+    let mut diff: Vec<DiffLine> = old_lines[..prefix]
+        .iter()
+        .map(|l| DiffLine::Context(l))
+        .collect();
+
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        diff.extend(old_mid.iter().map(|l| DiffLine::Removed(l)));
+        diff.extend(new_mid.iter().map(|l| DiffLine::Added(l)));
+    } else {
+        // lcs[i][j] = LCS length of old_mid[i..] and new_mid[j..]
+        let (n, m) = (old_mid.len(), new_mid.len());
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old_mid[i] == new_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if old_mid[i] == new_mid[j] {
+                diff.push(DiffLine::Context(old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                diff.push(DiffLine::Removed(old_mid[i]));
+                i += 1;
+            } else {
+                diff.push(DiffLine::Added(new_mid[j]));
+                j += 1;
+            }
+        }
+        diff.extend(old_mid[i..].iter().map(|l| DiffLine::Removed(l)));
+        diff.extend(new_mid[j..].iter().map(|l| DiffLine::Added(l)));
+    }
+
+    diff.extend(
+        old_lines[old_lines.len() - suffix..]
+            .iter()
+            .map(|l| DiffLine::Context(l)),
+    );
+    diff
+}
+
+/// Render a unified diff between two versions of `path`
+///
+/// Returns an empty string when the texts have identical lines. New files
+/// are rendered against `/dev/null`.
+pub fn unified_diff(path: &Path, old: Option<&str>, new: &str) -> String {
+    let diff = line_diff(old.unwrap_or(""), new);
+    if diff.iter().all(|l| matches!(l, DiffLine::Context(_))) {
+        return String::new();
+    }
+
+    let display = path.display();
+    let mut out = match old {
+        Some(_) => format!("--- a/{}\n+++ b/{}\n", display, display),
+        None => format!("--- /dev/null\n+++ b/{}\n", display),
+    };
+
+    // Group changes into hunks separated by more than 2x context lines
+    let changed: Vec<usize> = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| !matches!(l, DiffLine::Context(_)))
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &idx in &changed {
+        let start = idx.saturating_sub(DIFF_CONTEXT_LINES);
+        let end = (idx + DIFF_CONTEXT_LINES + 1).min(diff.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    for (start, end) in ranges {
+        // Line numbers (1-based) of the hunk start in each version
+        let old_start = 1 + diff[..start]
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Added(_)))
+            .count();
+        let new_start = 1 + diff[..start]
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Removed(_)))
+            .count();
+        let hunk = &diff[start..end];
+        let old_count = hunk
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Added(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Removed(_)))
+            .count();
+
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            if old_count == 0 {
+                old_start - 1
+            } else {
+                old_start
+            },
+            old_count,
+            if new_count == 0 {
+                new_start - 1
+            } else {
+                new_start
+            },
+            new_count
+        ));
+        for line in hunk {
+            let (prefix, text) = match line {
+                DiffLine::Context(t) => (' ', t),
+                DiffLine::Removed(t) => ('-', t),
+                DiffLine::Added(t) => ('+', t),
+            };
+            out.push(prefix);
+            out.push_str(text);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// Build a follow-up prompt asking for complete contents of files whose patches failed
+pub fn build_fallback_prompt(failures: &[PatchFailure]) -> String {
+    let mut prompt = String::from(
+        "Some of your edits could not be applied because the search text did not match \
+         the current file contents:\n\n",
+    );
+    for failure in failures {
+        prompt.push_str(&format!("- {}\n", failure));
+    }
+    prompt.push_str(
+        "\nFor each of these files, reply with the COMPLETE updated file contents \
+         using the **`path/to/file`** header followed by a fenced code block. \
+         Do not use edit blocks or diffs.",
+    );
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patch(path: &str, hunks: Vec<PatchHunk>) -> FilePatch {
+        FilePatch {
+            path: PathBuf::from(path),
+            format: PatchFormat::EditBlock,
+            hunks,
+        }
+    }
+
+    #[test]
+    fn test_parse_edit_blocks() {
+        let text = "<<<<<<< SEARCH\nfn a() {}\n=======\nfn a() { b(); }\n>>>>>>> REPLACE\n";
+        let hunks = parse_edit_blocks(text);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].search, "fn a() {}\n");
+        assert_eq!(hunks[0].replace, "fn a() { b(); }\n");
+    }
+
+    #[test]
+    fn test_parse_edit_blocks_skips_unterminated() {
+        let text = "<<<<<<< SEARCH\nfn a() {}\n=======\nfn b() {}\n";
+        assert!(parse_edit_blocks(text).is_empty());
+    }
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let diff = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,3 +1,3 @@\n fn main() {\n-    old();\n+    new();\n }\n";
+        let patches = parse_unified_diff(diff, None);
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].path, PathBuf::from("src/lib.rs"));
+        assert_eq!(patches[0].format, PatchFormat::UnifiedDiff);
+        assert_eq!(patches[0].hunks[0].search, "fn main() {\n    old();\n}\n");
+        assert_eq!(patches[0].hunks[0].replace, "fn main() {\n    new();\n}\n");
+    }
+
+    #[test]
+    fn test_apply_patch_exact() {
+        let original = "fn a() {}\nfn b() {}\n";
+        let p = patch("x.rs", vec![PatchHunk::new("fn b() {}\n", "fn c() {}\n")]);
+        let outcome = apply_patch(original, &p).unwrap();
+        assert_eq!(outcome.content, "fn a() {}\nfn c() {}\n");
+        assert_eq!(outcome.worst_match, MatchQuality::Exact);
+    }
+
+    #[test]
+    fn test_apply_patch_preserves_untouched_regions() {
+        let original = "// header\nfn a() {}\nfn b() {}\n// footer\n";
+        let p = patch(
+            "x.rs",
+            vec![PatchHunk::new("fn a() {}\n", "fn a() { 1 }\n")],
+        );
+        let outcome = apply_patch(original, &p).unwrap();
+        assert_eq!(
+            outcome.content,
+            "// header\nfn a() { 1 }\nfn b() {}\n// footer\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_patch_fuzzy_indentation() {
+        let original = "impl X {\n    fn a(&self) {\n        1\n    }\n}\n";
+        let p = patch(
+            "x.rs",
+            vec![PatchHunk::new(
+                "fn a(&self) {\n    1\n}\n",
+                "fn a(&self) {\n    2\n}\n",
+            )],
+        );
+        let outcome = apply_patch(original, &p).unwrap();
+        assert_eq!(
+            outcome.content,
+            "impl X {\n    fn a(&self) {\n        2\n    }\n}\n"
+        );
+        assert_eq!(outcome.worst_match, MatchQuality::Indentation);
+    }
+
+    #[test]
+    fn test_apply_patch_trailing_whitespace() {
+        let original = "let x = 1;   \n";
+        let p = patch("x.rs", vec![PatchHunk::new("let x = 1;\n", "let x = 2;\n")]);
+        let outcome = apply_patch(original, &p).unwrap();
+        assert_eq!(outcome.content, "let x = 2;\n");
+        assert_eq!(outcome.worst_match, MatchQuality::TrailingWhitespace);
+    }
+
+    #[test]
+    fn test_apply_patch_reports_failed_hunks() {
+        let original = "fn a() {}\n";
+        let p = patch(
+            "x.rs",
+            vec![
+                PatchHunk::new("fn a() {}\n", "fn b() {}\n"),
+                PatchHunk::new("fn missing() {}\n", ""),
+            ],
+        );
+        let failure = apply_patch(original, &p).unwrap_err();
+        assert_eq!(failure.path, PathBuf::from("x.rs"));
+        assert_eq!(failure.failures.len(), 1);
+        assert_eq!(failure.failures[0].hunk_index, 1);
+        assert!(failure.to_string().contains("hunk 2"));
+    }
+
+    #[test]
+    fn test_apply_patch_prefers_match_after_cursor() {
+        let original = "x\ny\nx\n";
+        let p = patch(
+            "x.txt",
+            vec![PatchHunk::new("y\n", "Y\n"), PatchHunk::new("x\n", "X\n")],
+        );
+        let outcome = apply_patch(original, &p).unwrap();
+        assert_eq!(outcome.content, "x\nY\nX\n");
+    }
+
+    #[test]
+    fn test_extract_edits_mixed() {
+        let response = r#"
+**`src/new.rs`**
+```rust
+pub fn new() {}
+```
+
+**`src/lib.rs`**
+```rust
+<<<<<<< SEARCH
+fn old() {}
+=======
+fn updated() {}
+>>>>>>> REPLACE
+```
+
+```diff
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1 +1 @@
+-fn main() {}
++fn main() { run(); }
+```
+"#;
+        let edits = extract_edits_from_response(response);
+        assert_eq!(edits.len(), 3);
+        assert!(!edits[0].is_patch());
+        assert_eq!(edits[0].path(), Path::new("src/new.rs"));
+        assert!(edits[1].is_patch());
+        assert_eq!(edits[1].path(), Path::new("src/lib.rs"));
+        assert!(edits[2].is_patch());
+        assert_eq!(edits[2].path(), Path::new("src/main.rs"));
+    }
+
+    #[test]
+    fn test_resolve_edit_modes() {
+        let whole = FileEdit::Whole(ExtractedFile::new(
+            PathBuf::from("a.rs"),
+            "fn a() {}".to_string(),
+            Some("rust".to_string()),
+        ));
+        assert_eq!(resolve_edit(&whole, None).unwrap().mode, EditMode::Created);
+        assert_eq!(
+            resolve_edit(&whole, Some("old")).unwrap().mode,
+            EditMode::Rewritten
+        );
+
+        let edit = FileEdit::Patch(patch(
+            "a.rs",
+            vec![PatchHunk::new("fn a() {}\n", "fn b() {}\n")],
+        ));
+        let resolved = resolve_edit(&edit, Some("fn a() {}\n")).unwrap();
+        assert_eq!(resolved.mode, EditMode::Patched);
+        assert_eq!(resolved.content, "fn b() {}\n");
+        assert!(resolve_edit(&edit, None).is_err());
+
+        let create = FileEdit::Patch(patch("b.rs", vec![PatchHunk::new("", "fn b() {}\n")]));
+        let resolved = resolve_edit(&create, None).unwrap();
+        assert_eq!(resolved.mode, EditMode::Created);
+        assert_eq!(resolved.content, "fn b() {}\n");
+    }
+
+    #[test]
+    fn test_build_fallback_prompt_lists_files() {
+        let failures = vec![PatchFailure {
+            path: PathBuf::from("src/lib.rs"),
+            failures: vec![HunkFailure {
+                hunk_index: 0,
+                reason: "search text not found".to_string(),
+            }],
+        }];
+        let prompt = build_fallback_prompt(&failures);
+        assert!(prompt.contains("src/lib.rs"));
+        assert!(prompt.contains("COMPLETE"));
+    }
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff("a\nb\nc\n", "a\nB\nc\nd\n");
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Context("a"),
+                DiffLine::Removed("b"),
+                DiffLine::Added("B"),
+                DiffLine::Context("c"),
+                DiffLine::Added("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_roundtrips_through_parser() {
+        let old = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n";
+        let new = "one\nTWO\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\neleven\n";
+        let diff = unified_diff(Path::new("src/n.txt"), Some(old), new);
+        assert!(diff.starts_with("--- a/src/n.txt\n+++ b/src/n.txt\n"));
+        assert_eq!(diff.matches("@@ ").count(), 2);
+
+        let patches = parse_unified_diff(&diff, None);
+        assert_eq!(patches.len(), 1);
+        assert_eq!(apply_patch(old, &patches[0]).unwrap().content, new);
+    }
+
+    #[test]
+    fn test_unified_diff_new_file_and_unchanged() {
+        let diff = unified_diff(Path::new("a.rs"), None, "fn a() {}\n");
+        assert!(diff.starts_with("--- /dev/null\n+++ b/a.rs\n@@ -0,0 +1,1 @@\n+fn a() {}"));
+        assert!(unified_diff(Path::new("a.rs"), Some("x\n"), "x\n").is_empty());
+    }
+}
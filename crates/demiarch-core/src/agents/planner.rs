@@ -18,9 +18,11 @@ use super::traits::{
     Agent, AgentArtifact, AgentCapability, AgentInput, AgentResult, AgentStatus, ArtifactType,
 };
 use super::AgentType;
+use crate::commands::related;
 use crate::domain::feature_decomposition::{ExecutionPlan, PlanTask, TaskStatus};
 use crate::error::Result;
 use crate::llm::Message;
+use crate::storage::Database;
 
 /// Planner agent - coordinates task decomposition
 ///
@@ -66,10 +68,15 @@ impl PlannerAgent {
         self.status.set(AgentStatus::Running);
         context.update_status(AgentStatus::Running).await;
 
-        // Build messages for the LLM
-        let messages = build_messages_from_input(&self.system_prompt(), &input, &context);
+        // Build messages for the LLM, pointing at related prior work
+        let mut system_prompt = self.system_prompt();
+        if let Some(recommendations) = self.related_work(&input.task, &context).await {
+            system_prompt = format!("{}\n\n{}", system_prompt, recommendations);
+        }
+        let messages = build_messages_from_input(&system_prompt, &input, &context);
 
         // Call the LLM to create an execution plan
+        let context_stats = context.context_stats(&messages);
         let llm_client = context.llm_client();
         let response = match llm_client.complete(messages, None).await {
             Ok(resp) => resp,
@@ -81,6 +88,8 @@ impl PlannerAgent {
             }
         };
 
+        context.report_context_usage(context_stats.with_measured(response.input_tokens));
+
         debug!(
             tokens = response.tokens_used,
             "Planner received LLM response"
@@ -132,7 +141,14 @@ impl PlannerAgent {
             let code_context = if !code_artifacts.is_empty() {
                 let code_summary: String = code_artifacts
                     .iter()
-                    .map(|a| format!("**{}**\n```\n{}\n```", a.name, a.content))
+                    .map(|a| {
+                        let code = format!("```\n{}\n```", a.content);
+                        format!(
+                            "**{}**\n{}",
+                            a.name,
+                            context.screen_untrusted(&a.name, &code)
+                        )
+                    })
                     .collect::<Vec<_>>()
                     .join("\n\n");
                 Some(code_summary)
@@ -322,6 +338,24 @@ impl PlannerAgent {
 
         plan
     }
+
+    /// Recommendations from earlier skills and features similar to `task`
+    ///
+    /// Only runs for project-scoped work; lookup failures just leave the
+    /// prompt as it was.
+    async fn related_work(&self, task: &str, context: &AgentContext) -> Option<String> {
+        context.project_id()?;
+        let db = Database::shared().await.ok()?;
+        let feature_id = context.feature_id().map(|id| id.to_string());
+        match related::find_related(&db, task, feature_id.as_deref(), related::DEFAULT_LIMIT).await
+        {
+            Ok(work) => work.recommendations(),
+            Err(e) => {
+                debug!(error = %e, "Could not look up related prior work");
+                None
+            }
+        }
+    }
 }
 
 impl Default for PlannerAgent {
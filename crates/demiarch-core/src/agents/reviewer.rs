@@ -158,6 +158,7 @@ impl ReviewerAgent {
         let messages = build_messages_from_input(&self.system_prompt(), &input, &context);
 
         // Call the LLM to review the code
+        let context_stats = context.context_stats(&messages);
         let llm_client = context.llm_client();
         let response = match llm_client.complete(messages, None).await {
             Ok(resp) => resp,
@@ -169,6 +170,8 @@ impl ReviewerAgent {
             }
         };
 
+        context.report_context_usage(context_stats.with_measured(response.input_tokens));
+
         debug!(
             tokens = response.tokens_used,
             "Reviewer received LLM response"
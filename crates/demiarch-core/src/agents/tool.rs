@@ -15,6 +15,7 @@ use super::context::{AgentContext, SharedAgentState};
 use super::orchestrator::OrchestratorAgent;
 use super::planner::PlannerAgent;
 use super::reviewer::ReviewerAgent;
+use super::sanitize::ContentSanitizer;
 use super::tester::TesterAgent;
 use super::traits::{Agent, AgentInput, AgentResult};
 use super::AgentType;
@@ -84,6 +85,7 @@ impl AgentTool {
         // We need to rebuild the shared state since it's immutable
         let llm_client = Arc::clone(&self.shared_state.llm_client);
         let mut new_state = SharedAgentState::new(llm_client).with_project_id(project_id);
+        new_state = new_state.with_sanitizer(self.shared_state.sanitizer());
         if let Some(tracker) = self.shared_state.cost_tracker.clone() {
             new_state = new_state.with_cost_tracker(tracker);
         }
@@ -101,6 +103,7 @@ impl AgentTool {
     pub fn with_feature(mut self, feature_id: uuid::Uuid) -> Self {
         let llm_client = Arc::clone(&self.shared_state.llm_client);
         let mut new_state = SharedAgentState::new(llm_client).with_feature_id(feature_id);
+        new_state = new_state.with_sanitizer(self.shared_state.sanitizer());
         if let Some(tracker) = self.shared_state.cost_tracker.clone() {
             new_state = new_state.with_cost_tracker(tracker);
         }
@@ -114,10 +117,18 @@ impl AgentTool {
         self
     }
 
+    /// Configure how untrusted content is screened before reaching agents
+    pub fn with_sanitizer(mut self, sanitizer: ContentSanitizer) -> Self {
+        let state = (*self.shared_state).clone().with_sanitizer(sanitizer);
+        self.shared_state = Arc::new(state);
+        self
+    }
+
     /// Configure with project filesystem path
     pub fn with_project_path(mut self, path: PathBuf) -> Self {
         let llm_client = Arc::clone(&self.shared_state.llm_client);
         let mut new_state = SharedAgentState::new(llm_client).with_project_path(path);
+        new_state = new_state.with_sanitizer(self.shared_state.sanitizer());
         if let Some(tracker) = self.shared_state.cost_tracker.clone() {
             new_state = new_state.with_cost_tracker(tracker);
         }
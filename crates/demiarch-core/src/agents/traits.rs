@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 
 use super::context::AgentContext;
 use super::AgentType;
+use crate::context::ContextPiece;
 use crate::error::Result;
 use crate::llm::Message;
 
@@ -248,6 +249,8 @@ pub struct AgentInput {
     pub task: String,
     /// Additional context as messages
     pub context_messages: Vec<Message>,
+    /// Candidate context, of which only what fits the agent's budget is sent
+    pub context_pieces: Vec<ContextPiece>,
     /// Parameters for the task
     pub parameters: serde_json::Value,
 }
@@ -258,6 +261,7 @@ impl AgentInput {
         Self {
             task: task.into(),
             context_messages: Vec::new(),
+            context_pieces: Vec::new(),
             parameters: serde_json::Value::Null,
         }
     }
@@ -268,6 +272,12 @@ impl AgentInput {
         self
     }
 
+    /// Add candidate context pieces, packed per call by relevance
+    pub fn with_context_pieces(mut self, pieces: Vec<ContextPiece>) -> Self {
+        self.context_pieces = pieces;
+        self
+    }
+
     /// Add parameters
     pub fn with_parameters(mut self, params: serde_json::Value) -> Self {
         self.parameters = params;
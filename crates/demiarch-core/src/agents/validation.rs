@@ -0,0 +1,360 @@
+//! Syntax validation of generated code
+//!
+//! Generated files are parsed with tree-sitter before they are written so
+//! that syntactically broken output is caught early. Files with errors can be
+//! sent back to the LLM for a bounded repair attempt (see
+//! [`build_repair_prompt`]); files that still fail are rejected instead of
+//! being written.
+//!
+//! Languages without a bundled grammar are skipped, not rejected.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Language, Node, Parser};
+
+/// Maximum number of repair round-trips for files that fail validation
+pub const MAX_REPAIR_ATTEMPTS: usize = 2;
+
+/// Maximum number of syntax errors reported per file
+const MAX_REPORTED_ERRORS: usize = 5;
+
+/// Maximum length of the offending source snippet in an error message
+const MAX_SNIPPET_LEN: usize = 40;
+
+/// Languages with a bundled tree-sitter grammar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyntaxLanguage {
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Python,
+    Rust,
+    Go,
+}
+
+impl SyntaxLanguage {
+    /// Detect the language from a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "js" | "jsx" | "mjs" | "cjs" => Some(Self::JavaScript),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" => Some(Self::Tsx),
+            "py" | "pyi" => Some(Self::Python),
+            "rs" => Some(Self::Rust),
+            "go" => Some(Self::Go),
+            _ => None,
+        }
+    }
+
+    /// Detect the language from a code fence tag (e.g. "rust", "ts")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "javascript" | "js" | "jsx" | "mjs" => Some(Self::JavaScript),
+            "typescript" | "ts" => Some(Self::TypeScript),
+            "tsx" => Some(Self::Tsx),
+            "python" | "py" => Some(Self::Python),
+            "rust" | "rs" => Some(Self::Rust),
+            "go" | "golang" => Some(Self::Go),
+            _ => None,
+        }
+    }
+
+    /// Detect the language of a file, preferring the extension over the hint
+    pub fn detect(path: &Path, hint: Option<&str>) -> Option<Self> {
+        Self::from_path(path).or_else(|| hint.and_then(Self::from_name))
+    }
+
+    /// Human-readable name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::JavaScript => "javascript",
+            Self::TypeScript => "typescript",
+            Self::Tsx => "tsx",
+            Self::Python => "python",
+            Self::Rust => "rust",
+            Self::Go => "go",
+        }
+    }
+
+    /// The tree-sitter grammar for this language
+    fn grammar(&self) -> Language {
+        match self {
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+        }
+    }
+}
+
+/// Outcome of validating one file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationStatus {
+    /// Parsed without errors
+    Valid,
+    /// Parsed with syntax errors
+    Invalid,
+    /// No grammar available for the file
+    #[default]
+    Skipped,
+}
+
+impl ValidationStatus {
+    /// Convert to string for database storage
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::Invalid => "invalid",
+            Self::Skipped => "skipped",
+        }
+    }
+
+    /// Parse from database string
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "valid" => Some(Self::Valid),
+            "invalid" => Some(Self::Invalid),
+            "skipped" => Some(Self::Skipped),
+            _ => None,
+        }
+    }
+}
+
+/// A syntax error located in a generated file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyntaxError {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number
+    pub column: usize,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Validation result for one generated file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// Path of the validated file
+    pub path: PathBuf,
+    /// Language the file was parsed as
+    pub language: Option<SyntaxLanguage>,
+    /// Overall outcome
+    pub status: ValidationStatus,
+    /// Syntax errors found (at most a handful are reported)
+    pub errors: Vec<SyntaxError>,
+}
+
+impl ValidationReport {
+    /// Whether the file failed validation
+    pub fn is_invalid(&self) -> bool {
+        self.status == ValidationStatus::Invalid
+    }
+}
+
+/// Parse a generated file and report syntax errors
+///
+/// `language_hint` is the code fence language, used when the path has no
+/// recognised extension.
+pub fn validate_source(
+    path: &Path,
+    language_hint: Option<&str>,
+    content: &str,
+) -> ValidationReport {
+    let mut report = ValidationReport {
+        path: path.to_path_buf(),
+        language: SyntaxLanguage::detect(path, language_hint),
+        status: ValidationStatus::Skipped,
+        errors: Vec::new(),
+    };
+
+    let Some(language) = report.language else {
+        return report;
+    };
+
+    let mut parser = Parser::new();
+    if parser.set_language(&language.grammar()).is_err() {
+        return report;
+    }
+    let Some(tree) = parser.parse(content, None) else {
+        return report;
+    };
+
+    let root = tree.root_node();
+    if root.has_error() {
+        collect_errors(root, content, &mut report.errors);
+        if report.errors.is_empty() {
+            report.errors.push(SyntaxError {
+                line: 1,
+                column: 1,
+                message: "syntax error".to_string(),
+            });
+        }
+        report.status = ValidationStatus::Invalid;
+    } else {
+        report.status = ValidationStatus::Valid;
+    }
+
+    report
+}
+
+/// Walk the subtrees containing errors, recording ERROR and MISSING nodes
+fn collect_errors(node: Node<'_>, source: &str, errors: &mut Vec<SyntaxError>) {
+    if errors.len() >= MAX_REPORTED_ERRORS {
+        return;
+    }
+
+    let position = node.start_position();
+    if node.is_missing() {
+        errors.push(SyntaxError {
+            line: position.row + 1,
+            column: position.column + 1,
+            message: format!("missing `{}`", node.kind()),
+        });
+        return;
+    }
+    if node.is_error() {
+        let snippet = source
+            .get(node.byte_range())
+            .and_then(|text| text.lines().map(str::trim).find(|l| !l.is_empty()))
+            .unwrap_or("");
+        let message = if snippet.is_empty() {
+            "unexpected end of input".to_string()
+        } else {
+            let snippet: String = snippet.chars().take(MAX_SNIPPET_LEN).collect();
+            format!("unexpected `{}`", snippet)
+        };
+        errors.push(SyntaxError {
+            line: position.row + 1,
+            column: position.column + 1,
+            message,
+        });
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.has_error() {
+            collect_errors(child, source, errors);
+        }
+    }
+}
+
+/// Build a follow-up prompt asking for corrected versions of invalid files
+pub fn build_repair_prompt(reports: &[&ValidationReport]) -> String {
+    let mut prompt =
+        String::from("The following files contain syntax errors and were not written:\n\n");
+    for report in reports {
+        let language = report
+            .language
+            .map(|l| format!(" ({})", l.as_str()))
+            .unwrap_or_default();
+        prompt.push_str(&format!("- {}{}\n", report.path.display(), language));
+        for error in &report.errors {
+            prompt.push_str(&format!("  - line {}\n", error));
+        }
+    }
+    prompt.push_str(
+        "\nReply with the COMPLETE corrected contents of each file listed above. \
+         Put the file path on its own line followed by a single code block. \
+         Do not use edit blocks or diffs, and do not include other files.",
+    );
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            SyntaxLanguage::detect(Path::new("src/main.rs"), None),
+            Some(SyntaxLanguage::Rust)
+        );
+        assert_eq!(
+            SyntaxLanguage::detect(Path::new("web/App.tsx"), Some("typescript")),
+            Some(SyntaxLanguage::Tsx)
+        );
+        assert_eq!(
+            SyntaxLanguage::detect(Path::new("Dockerfile"), Some("golang")),
+            Some(SyntaxLanguage::Go)
+        );
+        assert_eq!(SyntaxLanguage::detect(Path::new("README.md"), None), None);
+    }
+
+    #[test]
+    fn test_validate_valid_sources() {
+        let cases = [
+            (
+                "lib.rs",
+                "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+            ),
+            ("app.py", "def add(a, b):\n    return a + b\n"),
+            (
+                "index.js",
+                "export function add(a, b) {\n  return a + b;\n}\n",
+            ),
+            (
+                "index.ts",
+                "export const add = (a: number, b: number): number => a + b;\n",
+            ),
+            (
+                "main.go",
+                "package main\n\nfunc add(a, b int) int {\n\treturn a + b\n}\n",
+            ),
+        ];
+        for (path, source) in cases {
+            let report = validate_source(Path::new(path), None, source);
+            assert_eq!(report.status, ValidationStatus::Valid, "{}", path);
+            assert!(report.errors.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_validate_reports_errors() {
+        let report = validate_source(
+            Path::new("src/lib.rs"),
+            None,
+            "pub fn add(a: i32, b: i32) -> i32 {\n    a +\n",
+        );
+        assert!(report.is_invalid());
+        assert!(!report.errors.is_empty());
+        assert!(report.errors.iter().all(|e| e.line >= 1 && e.column >= 1));
+
+        let report = validate_source(
+            Path::new("app.py"),
+            None,
+            "def add(a, b)\n    return a + b\n",
+        );
+        assert!(report.is_invalid());
+    }
+
+    #[test]
+    fn test_validate_skips_unknown_language() {
+        let report = validate_source(Path::new("notes.txt"), Some("text"), "{{{ not code");
+        assert_eq!(report.status, ValidationStatus::Skipped);
+        assert!(report.language.is_none());
+    }
+
+    #[test]
+    fn test_repair_prompt_lists_errors() {
+        let report = validate_source(Path::new("src/lib.rs"), None, "fn broken( {\n");
+        let prompt = build_repair_prompt(&[&report]);
+        assert!(prompt.contains("src/lib.rs (rust)"));
+        assert!(prompt.contains("line "));
+        assert!(prompt.contains("COMPLETE"));
+    }
+}
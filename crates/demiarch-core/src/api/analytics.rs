@@ -0,0 +1,51 @@
+//! Analytics API
+//!
+//! Provides the local usage dashboard for GUI. Analytics are opt-in and
+//! computed entirely from the local database.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::analytics::{self, UsageStats, DEFAULT_WEEKS};
+use crate::config::Config;
+use crate::{Error, Result};
+
+use super::get_database;
+
+/// Usage dashboard for GUI display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageDashboard {
+    /// Whether the user has opted in to analytics
+    pub enabled: bool,
+    /// Statistics, present only when enabled
+    pub stats: Option<UsageStats>,
+}
+
+/// Get usage statistics for the last `weeks` weeks (default 8)
+pub async fn usage(project_id: Option<&str>, weeks: Option<u32>) -> Result<UsageDashboard> {
+    if !load_config()?.analytics.enabled {
+        return Ok(UsageDashboard {
+            enabled: false,
+            stats: None,
+        });
+    }
+
+    let db = get_database().await?;
+    let stats =
+        analytics::compute(&db, project_id, weeks.unwrap_or(DEFAULT_WEEKS), Utc::now()).await?;
+    Ok(UsageDashboard {
+        enabled: true,
+        stats: Some(stats),
+    })
+}
+
+/// Opt in to or out of usage analytics
+pub fn set_enabled(enabled: bool) -> Result<()> {
+    let mut config = load_config()?;
+    config.analytics.enabled = enabled;
+    config.save().map_err(|e| Error::ConfigError(e.to_string()))
+}
+
+fn load_config() -> Result<Config> {
+    Config::load().map_err(|e| Error::ConfigError(e.to_string()))
+}
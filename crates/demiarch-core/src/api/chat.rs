@@ -0,0 +1,85 @@
+//! Chat API
+//!
+//! Provides conversation history for GUI, with the position of each message
+//! among its variants so retried replies and edited messages can be paged
+//! through ("variant 2/3").
+
+use crate::commands::chat::{self, ChatMessage};
+use crate::pagination::{Page, PageRequest};
+use crate::storage::Database;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+
+use super::get_database;
+
+/// Chat message for GUI display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessageSummary {
+    pub id: String,
+    pub role: String,
+    pub content: String,
+    pub model: Option<String>,
+    pub created_at: String,
+    /// 1-based position among the message's variants
+    pub variant: usize,
+    pub variant_count: usize,
+    /// IDs of all variants, oldest first, for navigating between them
+    pub variant_ids: Vec<String>,
+}
+
+impl ChatMessageSummary {
+    fn new(message: ChatMessage, variants: Vec<ChatMessage>) -> Self {
+        let variant = variants
+            .iter()
+            .position(|v| v.id == message.id)
+            .map_or(1, |i| i + 1);
+        Self {
+            id: message.id,
+            role: message.role.as_str().to_string(),
+            content: message.content,
+            model: message.model,
+            created_at: message.created_at.to_rfc3339(),
+            variant,
+            variant_count: variants.len().max(1),
+            variant_ids: variants.into_iter().map(|v| v.id).collect(),
+        }
+    }
+}
+
+/// Messages on the conversation's active branch, oldest first
+pub async fn history(conversation_id: &str) -> Result<Vec<ChatMessageSummary>> {
+    let db = get_database().await?;
+    let messages = chat::get_history(&db, conversation_id, None).await?;
+    summarize(&db, messages).await
+}
+
+/// One page of the active branch, newest page first, for loading long
+/// conversations as the user scrolls back
+pub async fn history_page(
+    conversation_id: &str,
+    page: PageRequest,
+) -> Result<Page<ChatMessageSummary>> {
+    let db = get_database().await?;
+    let messages = chat::get_history_page(&db, conversation_id, page).await?;
+    let summaries = summarize(&db, messages.items).await?;
+    Ok(Page::new(summaries, messages.total, page))
+}
+
+async fn summarize(db: &Database, messages: Vec<ChatMessage>) -> Result<Vec<ChatMessageSummary>> {
+    let mut summaries = Vec::with_capacity(messages.len());
+    for message in messages {
+        let variants = chat::message_variants(db, &message).await?;
+        summaries.push(ChatMessageSummary::new(message, variants));
+    }
+    Ok(summaries)
+}
+
+/// Show another variant of a message and return the updated history
+pub async fn select_variant(
+    conversation_id: &str,
+    message_id: &str,
+) -> Result<Vec<ChatMessageSummary>> {
+    let db = get_database().await?;
+    chat::select_variant(&db, conversation_id, message_id).await?;
+    history(conversation_id).await
+}
@@ -2,8 +2,10 @@
 //!
 //! Provides high-level operations for cost tracking from GUI.
 
+use crate::config::Config;
+use crate::cost::forecast::{self, SpendForecast};
 use crate::cost::{CostTracker, ModelCostSummary};
-use crate::Result;
+use crate::{Error, Result};
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
@@ -80,7 +82,7 @@ pub fn get_today_summary(tracker: &CostTracker) -> CostSummaryDto {
 /// Get cost summary for a specific date
 pub fn get_summary_for_date(tracker: &CostTracker, date: &str) -> Result<Option<CostSummaryDto>> {
     let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
-        .map_err(|_| crate::Error::InvalidInput(format!("Invalid date: {}", date)))?;
+        .map_err(|_| Error::InvalidInput(format!("Invalid date: {}", date)))?;
 
     let summary = tracker.summary_for_date(date);
 
@@ -164,3 +166,29 @@ pub fn get_budget_status(tracker: &CostTracker) -> BudgetStatus {
         is_approaching_limit: tracker.is_approaching_limit(),
     }
 }
+
+/// Total recorded spend since midnight UTC, across all projects
+pub async fn spent_today() -> Result<f64> {
+    use sqlx::Row;
+
+    let db = super::get_database().await?;
+    let midnight = chrono::Utc::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .map(|t| t.and_utc().to_rfc3339())
+        .unwrap_or_default();
+    let row = sqlx::query(
+        "SELECT COALESCE(SUM(input_cost_usd + output_cost_usd), 0.0) AS spent FROM llm_costs WHERE julianday(created_at) >= julianday(?)",
+    )
+    .bind(midnight)
+    .fetch_one(db.pool())
+    .await?;
+    Ok(row.get("spent"))
+}
+
+/// Projected end-of-month spend, optionally for one project
+pub async fn spend_forecast(project_id: Option<&str>) -> Result<SpendForecast> {
+    let config = Config::load().map_err(|e| Error::ConfigError(e.to_string()))?;
+    let db = super::get_database().await?;
+    forecast::forecast(&db, project_id, &config.cost, chrono::Utc::now()).await
+}
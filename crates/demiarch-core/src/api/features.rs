@@ -3,6 +3,7 @@
 //! Provides high-level operations for feature management from GUI.
 
 use crate::commands::feature::{Feature, FeatureRepository, FeatureStatus};
+use crate::pagination::{Page, PageRequest};
 use crate::Result;
 use serde::{Deserialize, Serialize};
 
@@ -19,6 +20,7 @@ pub struct FeatureSummary {
     pub priority: i32,
     pub labels: Vec<String>,
     pub phase_id: Option<String>,
+    pub branch: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -34,6 +36,7 @@ impl From<Feature> for FeatureSummary {
             priority: f.priority,
             labels: f.labels.unwrap_or_default(),
             phase_id: f.phase_id,
+            branch: f.branch,
             created_at: f.created_at.to_rfc3339(),
             updated_at: f.updated_at.to_rfc3339(),
         }
@@ -78,6 +81,23 @@ pub async fn list_by_project(
     Ok(features.into_iter().map(FeatureSummary::from).collect())
 }
 
+/// List one page of a project's features, with the total count
+pub async fn list_page(
+    project_id: &str,
+    status: Option<&str>,
+    page: PageRequest,
+) -> Result<Page<FeatureSummary>> {
+    let db = get_database().await?;
+    let repo = FeatureRepository::new(&db);
+
+    let status_filter = status.and_then(FeatureStatus::parse);
+    let features = repo
+        .list_by_project_page(project_id, status_filter, page)
+        .await?;
+
+    Ok(features.map(FeatureSummary::from))
+}
+
 /// Get a single feature by ID
 pub async fn get(id: &str) -> Result<Option<FeatureSummary>> {
     let db = get_database().await?;
@@ -0,0 +1,275 @@
+//! Project files API
+//!
+//! Read-only access to the files under a project's root directory so the GUI
+//! can show a file tree and preview generated files. Every path is
+//! canonicalized and must stay inside the project root; `..` segments and
+//! symlinks pointing outside it are rejected.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Largest file returned by [`read_project_file`]
+pub const MAX_PREVIEW_BYTES: u64 = 1024 * 1024;
+
+/// Directories left out of listings
+const SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "target"];
+
+/// A file or directory inside a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub name: String,
+    /// Absolute path, suitable for `read_project_file` and `file_metadata`
+    pub path: String,
+    /// Path relative to the project root, using `/` separators
+    pub relative_path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<String>,
+}
+
+/// Contents of a previewed file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileContent {
+    pub entry: FileEntry,
+    pub content: String,
+}
+
+/// List one directory of a project, directories first
+pub async fn list_project_files(project_id: &str, subpath: Option<&str>) -> Result<Vec<FileEntry>> {
+    let root = project_root(project_id).await?;
+    list_dir(&root, subpath.unwrap_or(""))
+}
+
+/// Read a text file that lives inside one of the registered projects
+pub async fn read_project_file(path: &str) -> Result<FileContent> {
+    let (root, path) = owning_project(path).await?;
+    read_file(&root, &path)
+}
+
+/// Size, kind and modification time of a project file
+pub async fn file_metadata(path: &str) -> Result<FileEntry> {
+    let (root, path) = owning_project(path).await?;
+    entry_for(&root, &path)
+}
+
+/// Canonical root directory of a project
+async fn project_root(project_id: &str) -> Result<PathBuf> {
+    let project = super::projects::get(project_id)
+        .await?
+        .ok_or_else(|| Error::ProjectNotFound(project_id.to_string()))?;
+    let path = project.path.ok_or_else(|| {
+        Error::InvalidInput(format!("Project '{}' has no path on disk", project.name))
+    })?;
+    Path::new(&path)
+        .canonicalize()
+        .map_err(|_| Error::NotFound(format!("Project directory {}", path)))
+}
+
+/// Find the project whose root contains `path`
+async fn owning_project(path: &str) -> Result<(PathBuf, PathBuf)> {
+    let canonical = Path::new(path)
+        .canonicalize()
+        .map_err(|_| Error::NotFound(format!("File {}", path)))?;
+
+    let mut roots: Vec<PathBuf> = super::projects::list(None)
+        .await?
+        .into_iter()
+        .filter_map(|p| p.path)
+        .filter_map(|p| Path::new(&p).canonicalize().ok())
+        .filter(|root| canonical.starts_with(root))
+        .collect();
+    // Prefer the most specific root when projects are nested
+    roots.sort_by_key(|root| std::cmp::Reverse(root.components().count()));
+
+    roots
+        .into_iter()
+        .next()
+        .map(|root| (root, canonical))
+        .ok_or_else(|| Error::InvalidInput(format!("{} is outside every project", path)))
+}
+
+/// Resolve `relative` under `root`, refusing anything that escapes it
+pub fn resolve_within(root: &Path, relative: &str) -> Result<PathBuf> {
+    let root = root
+        .canonicalize()
+        .map_err(|_| Error::NotFound(format!("Directory {}", root.display())))?;
+    let relative = relative.trim_start_matches(['/', '\\']);
+    let candidate = if relative.is_empty() {
+        root.clone()
+    } else {
+        root.join(relative)
+    };
+    let resolved = candidate
+        .canonicalize()
+        .map_err(|_| Error::NotFound(format!("Path {}", relative)))?;
+    if !resolved.starts_with(&root) {
+        return Err(Error::InvalidInput(format!(
+            "Path {} is outside the project",
+            relative
+        )));
+    }
+    Ok(resolved)
+}
+
+fn list_dir(root: &Path, subpath: &str) -> Result<Vec<FileEntry>> {
+    let root = root.canonicalize()?;
+    let dir = resolve_within(&root, subpath)?;
+    if !dir.is_dir() {
+        return Err(Error::InvalidInput(format!(
+            "{} is not a directory",
+            subpath
+        )));
+    }
+
+    let mut entries = Vec::new();
+    for item in std::fs::read_dir(&dir)? {
+        let item = item?;
+        let name = item.file_name().to_string_lossy().into_owned();
+        if SKIPPED_DIRS.contains(&name.as_str()) {
+            continue;
+        }
+        // Symlinks that leave the project are hidden rather than reported
+        let Ok(path) = item.path().canonicalize() else {
+            continue;
+        };
+        if !path.starts_with(&root) {
+            continue;
+        }
+        let mut entry = entry_for(&root, &path)?;
+        entry.name = name;
+        entries.push(entry);
+    }
+    entries.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    Ok(entries)
+}
+
+fn read_file(root: &Path, path: &Path) -> Result<FileContent> {
+    let entry = entry_for(root, path)?;
+    if entry.is_dir {
+        return Err(Error::InvalidInput(format!(
+            "{} is a directory",
+            entry.relative_path
+        )));
+    }
+    if entry.size > MAX_PREVIEW_BYTES {
+        return Err(Error::InvalidInput(format!(
+            "{} is too large to preview ({} bytes)",
+            entry.relative_path, entry.size
+        )));
+    }
+    let bytes = std::fs::read(path)?;
+    if bytes.contains(&0) {
+        return Err(Error::InvalidInput(format!(
+            "{} is a binary file",
+            entry.relative_path
+        )));
+    }
+    Ok(FileContent {
+        entry,
+        content: String::from_utf8_lossy(&bytes).into_owned(),
+    })
+}
+
+fn entry_for(root: &Path, path: &Path) -> Result<FileEntry> {
+    let metadata = std::fs::metadata(path)?;
+    let relative = path
+        .strip_prefix(root)
+        .map_err(|_| Error::InvalidInput(format!("{} is outside the project", path.display())))?;
+    let relative_path = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok(FileEntry {
+        name,
+        path: path.to_string_lossy().into_owned(),
+        relative_path,
+        is_dir: metadata.is_dir(),
+        size: if metadata.is_dir() { 0 } else { metadata.len() },
+        modified: metadata
+            .modified()
+            .ok()
+            .map(|t| DateTime::<Utc>::from(t).to_rfc3339()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".git")).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(dir.path().join("README.md"), "# demo\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_lists_directories_first_and_skips_vcs() {
+        let dir = project();
+        let entries = list_dir(dir.path(), "").unwrap();
+        let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["src", "README.md"]);
+
+        let nested = list_dir(dir.path(), "src").unwrap();
+        assert_eq!(nested[0].relative_path, "src/main.rs");
+        assert_eq!(nested[0].size, 13);
+    }
+
+    #[test]
+    fn test_rejects_paths_outside_root() {
+        let outer = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(outer.path().join("project")).unwrap();
+        std::fs::write(outer.path().join("secret.txt"), "nope").unwrap();
+        let root = outer.path().join("project");
+
+        assert!(matches!(
+            resolve_within(&root, "../secret.txt"),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(list_dir(&root, ".."), Err(Error::InvalidInput(_))));
+        // A leading slash is relative to the root, not the filesystem
+        assert!(matches!(
+            resolve_within(&root, "/etc/passwd"),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hides_symlinks_that_escape() {
+        let outer = tempfile::tempdir().unwrap();
+        let root = outer.path().join("project");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(outer.path().join("secret.txt"), "nope").unwrap();
+        std::os::unix::fs::symlink(outer.path().join("secret.txt"), root.join("link")).unwrap();
+
+        assert!(list_dir(&root, "").unwrap().is_empty());
+        assert!(resolve_within(&root, "link").is_err());
+    }
+
+    #[test]
+    fn test_read_refuses_binary_files() {
+        let dir = project();
+        std::fs::write(dir.path().join("blob.bin"), [0u8, 1, 2]).unwrap();
+        let root = dir.path().canonicalize().unwrap();
+
+        let text = read_file(&root, &root.join("README.md")).unwrap();
+        assert_eq!(text.content, "# demo\n");
+        assert!(read_file(&root, &root.join("blob.bin")).is_err());
+    }
+}
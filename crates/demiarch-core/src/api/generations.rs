@@ -0,0 +1,110 @@
+//! Generations API
+//!
+//! Provides generation operations for GUI: browsing past generations,
+//! per-file diffs, accept or reject decisions, applying previously rejected
+//! files, live progress of files being extracted and written, and paged
+//! agent session events.
+
+use crate::agents::events::{
+    file_progress, read_events_for, read_events_page, AgentEvent, FileProgress,
+};
+use crate::commands::generation::{
+    self, ArtifactDecision, GenerationDetail, GenerationFilter, GenerationReview, GenerationStatus,
+    GenerationSummary,
+};
+use crate::pagination::{Page, PageRequest};
+use crate::{Error, Result};
+
+use super::get_database;
+
+/// List past generations, newest first
+///
+/// `status` is one of `running`, `completed` or `failed`.
+pub async fn list(
+    project_id: Option<&str>,
+    status: Option<&str>,
+    limit: Option<i64>,
+) -> Result<Vec<GenerationSummary>> {
+    let status = status
+        .map(|s| {
+            GenerationStatus::parse(s)
+                .ok_or_else(|| Error::InvalidInput(format!("Invalid generation status: {}", s)))
+        })
+        .transpose()?;
+    let filter = GenerationFilter {
+        project_id: project_id.map(str::to_string),
+        status,
+        limit,
+        ..Default::default()
+    };
+
+    let db = get_database().await?;
+    generation::list(&db, &filter).await
+}
+
+/// Get a generation with its plan, environment and files
+pub async fn show(generation_id: &str) -> Result<GenerationDetail> {
+    let db = get_database().await?;
+    generation::show(&db, generation_id).await
+}
+
+/// Delete a generation record; written files are left alone
+pub async fn delete(generation_id: &str) -> Result<()> {
+    let db = get_database().await?;
+    generation::delete(&db, generation_id).await
+}
+
+/// Get per-file diffs for a generation
+pub async fn review(generation_id: &str) -> Result<GenerationReview> {
+    let db = get_database().await?;
+    generation::review(&db, generation_id).await
+}
+
+/// Accept or reject a single generated file
+///
+/// `decision` is one of `accepted` or `rejected`. Accepting writes the file.
+pub async fn decide(generation_id: &str, file_path: &str, decision: &str) -> Result<()> {
+    let decision = match ArtifactDecision::parse(decision) {
+        Some(d @ (ArtifactDecision::Accepted | ArtifactDecision::Rejected)) => d,
+        _ => {
+            return Err(Error::InvalidInput(format!(
+                "Invalid decision '{}'. Expected 'accepted' or 'rejected'.",
+                decision
+            )))
+        }
+    };
+
+    let db = get_database().await?;
+    generation::decide(&db, generation_id, file_path, decision).await
+}
+
+/// Apply unapplied files from a generation (all, or just `file_path`)
+pub async fn apply(generation_id: &str, file_path: Option<&str>) -> Result<Vec<String>> {
+    let db = get_database().await?;
+    generation::apply(&db, generation_id, file_path).await
+}
+
+/// Files extracted and written in an agent session, in order
+///
+/// Defaults to the current session; archived sessions can be named by ID.
+pub fn progress(session_id: Option<&str>) -> Result<Vec<FileProgress>> {
+    Ok(file_progress(&read_events_for(parse_session_id(
+        session_id,
+    )?)))
+}
+
+/// One page of an agent session's events, newest page first
+///
+/// Defaults to the current session; archived sessions can be named by ID.
+pub fn events_page(session_id: Option<&str>, page: PageRequest) -> Result<Page<AgentEvent>> {
+    Ok(read_events_page(parse_session_id(session_id)?, page))
+}
+
+fn parse_session_id(session_id: Option<&str>) -> Result<Option<uuid::Uuid>> {
+    session_id
+        .map(|id| {
+            uuid::Uuid::parse_str(id)
+                .map_err(|_| Error::InvalidInput(format!("Invalid session ID: {}", id)))
+        })
+        .transpose()
+}
@@ -1,8 +1,17 @@
 //! Health API
 //!
-//! Provides system health checks and diagnostics for GUI.
+//! Provides system health checks and diagnostics for GUI, and the
+//! per-project health score and review queue behind the project health
+//! widget and the inbox.
 
-use crate::Result;
+use crate::agents::event_sink;
+use crate::commands::approval::ApprovalRequest;
+use crate::commands::health::{self as project_health, HealthOptions};
+use crate::commands::inbox::{self, ReviewQueue};
+use crate::config::Config;
+use crate::storage::database::default_database_path;
+use crate::storage::WriteQueue;
+use crate::{Error, Result};
 use serde::{Deserialize, Serialize};
 
 use super::get_database;
@@ -46,6 +55,20 @@ pub async fn doctor() -> Result<HealthReport> {
     }
     checks.push(db_check);
 
+    // Check writes waiting for the database
+    let queue_check = check_write_queue().await;
+    if queue_check.status == HealthStatus::Warning && worst_status == HealthStatus::Ok {
+        worst_status = HealthStatus::Warning;
+    }
+    checks.push(queue_check);
+
+    // Check agent event writes in this process
+    let events_check = check_agent_events();
+    if events_check.status == HealthStatus::Warning && worst_status == HealthStatus::Ok {
+        worst_status = HealthStatus::Warning;
+    }
+    checks.push(events_check);
+
     // Check config file
     let config_check = check_config().await;
     if config_check.status == HealthStatus::Error && worst_status != HealthStatus::Error {
@@ -97,6 +120,54 @@ async fn check_database() -> HealthCheck {
     }
 }
 
+/// Check the write queue of cost records and session events
+///
+/// Opening the database replays the queue, so anything still queued failed
+/// again.
+async fn check_write_queue() -> HealthCheck {
+    let depth = match get_database().await {
+        Ok(db) => WriteQueue::for_database(&db).map_or(0, |queue| queue.depth()),
+        Err(_) => WriteQueue::for_path(&default_database_path()).map_or(0, |queue| queue.depth()),
+    };
+    if depth == 0 {
+        HealthCheck {
+            name: "Write Queue".to_string(),
+            status: HealthStatus::Ok,
+            message: Some("Empty".to_string()),
+        }
+    } else {
+        HealthCheck {
+            name: "Write Queue".to_string(),
+            status: HealthStatus::Warning,
+            message: Some(format!(
+                "{} cost/event write(s) waiting for the database",
+                depth
+            )),
+        }
+    }
+}
+
+/// Check the agent event sink for dropped events and slow flushes
+fn check_agent_events() -> HealthCheck {
+    let metrics = event_sink::process_metrics();
+    let summary = format!(
+        "{} written, {} dropped, {} backpressure waits, slowest flush {:.1} ms",
+        metrics.written,
+        metrics.dropped,
+        metrics.backpressure_waits,
+        metrics.max_flush_us as f64 / 1000.0
+    );
+    HealthCheck {
+        name: "Agent Events".to_string(),
+        status: if metrics.dropped > 0 {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Ok
+        },
+        message: Some(summary),
+    }
+}
+
 /// Check config file
 async fn check_config() -> HealthCheck {
     let config_path = dirs::config_dir().map(|p| p.join("demiarch").join("config.toml"));
@@ -183,3 +254,22 @@ pub fn get_system_info() -> SystemInfo {
         data_path: dirs::data_dir().map(|p| p.join("demiarch").display().to_string()),
     }
 }
+
+/// Score a project's health using the configured budget limits
+pub async fn project(project_id: &str) -> Result<project_health::HealthReport> {
+    let config = Config::load().map_err(|e| Error::ConfigError(e.to_string()))?;
+    let options = HealthOptions {
+        daily_limit_usd: config.cost.daily_limit_usd,
+        alert_threshold: config.cost.alert_threshold,
+        ..HealthOptions::default()
+    };
+    let db = get_database().await?;
+    project_health::check(&db, project_id, options, chrono::Utc::now()).await
+}
+
+/// Everything in a project awaiting human attention, with the approval
+/// requests this process is waiting on
+pub async fn review_queue(project_id: &str, approvals: &[ApprovalRequest]) -> Result<ReviewQueue> {
+    let db = get_database().await?;
+    inbox::review_queue(&db, project_id, approvals).await
+}
@@ -0,0 +1,63 @@
+//! Jobs API
+//!
+//! Provides job queue visibility for GUI: queued, running and finished
+//! generation and document jobs, and cancelling jobs that have not run yet.
+
+use crate::commands::jobs::{self, Job, JobRepository, JobStatus};
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+use super::get_database;
+
+/// Job summary for GUI display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobSummary {
+    pub id: String,
+    pub kind: String,
+    pub summary: String,
+    pub status: String,
+    pub run_at: String,
+    pub attempts: i64,
+    pub max_attempts: i64,
+    pub last_error: Option<String>,
+    pub result_ref: Option<String>,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+}
+
+impl From<Job> for JobSummary {
+    fn from(job: Job) -> Self {
+        Self {
+            id: job.id,
+            kind: job.spec.kind().to_string(),
+            summary: job.spec.summary(),
+            status: job.status.as_str().to_string(),
+            run_at: job.run_at.to_rfc3339(),
+            attempts: job.attempts,
+            max_attempts: job.max_attempts,
+            last_error: job.last_error,
+            result_ref: job.result_ref,
+            created_at: job.created_at.to_rfc3339(),
+            finished_at: job.finished_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+/// List jobs, newest first
+pub async fn list(status: Option<&str>, limit: Option<i64>) -> Result<Vec<JobSummary>> {
+    let status = status
+        .map(|s| {
+            JobStatus::parse(s).ok_or_else(|| Error::InvalidInput(format!("Invalid status: {}", s)))
+        })
+        .transpose()?;
+
+    let db = get_database().await?;
+    let jobs = JobRepository::new(&db).list(status, limit).await?;
+    Ok(jobs.into_iter().map(JobSummary::from).collect())
+}
+
+/// Cancel a queued job
+pub async fn cancel(id: &str) -> Result<JobSummary> {
+    let db = get_database().await?;
+    jobs::cancel(&db, id).await.map(JobSummary::from)
+}
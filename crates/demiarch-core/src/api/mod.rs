@@ -4,11 +4,17 @@
 //! demiarch-core functionality. This module handles database connections
 //! and translates domain types to DTOs suitable for serialization.
 
+pub mod analytics;
+pub mod chat;
 pub mod costs;
 pub mod features;
+pub mod files;
+pub mod generations;
 pub mod health;
+pub mod jobs;
 pub mod projects;
 pub mod sessions;
+pub mod transcription;
 
 use crate::storage::{Database, DatabaseConfig};
 use crate::Result;
@@ -34,7 +40,7 @@ pub async fn get_database() -> Result<Database> {
     // Initialize database
     let mut guard = db_holder.write().await;
     if guard.is_none() {
-        let db = Database::default()
+        let db = Database::shared()
             .await
             .map_err(|e| crate::Error::DatabaseError(sqlx::Error::Configuration(e.into())))?;
         *guard = Some(db);
@@ -49,7 +55,8 @@ pub async fn init_database(path: PathBuf) -> Result<()> {
     let mut guard = db_holder.write().await;
 
     let config = DatabaseConfig::with_path(path);
-    let db = Database::new(config)
+    let db = crate::storage::connection_cache::shared()
+        .open(config)
         .await
         .map_err(|e| crate::Error::DatabaseError(sqlx::Error::Configuration(e.into())))?;
     *guard = Some(db);
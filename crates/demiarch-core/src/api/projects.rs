@@ -1,8 +1,11 @@
 //! Projects API
 //!
-//! High-level async functions for project operations.
+//! High-level async functions for project operations, and the workspace
+//! rollup behind the workspace overview.
 
 use crate::commands::project::{Project, ProjectRepository, ProjectStatus};
+use crate::commands::workspace::{self, WorkspaceStats};
+use crate::pagination::{Page, PageRequest};
 use crate::Result;
 use serde::{Deserialize, Serialize};
 
@@ -59,6 +62,17 @@ pub async fn list(status: Option<&str>) -> Result<Vec<ProjectSummary>> {
     Ok(projects.into_iter().map(ProjectSummary::from).collect())
 }
 
+/// List one page of projects, with the total count
+pub async fn list_page(status: Option<&str>, page: PageRequest) -> Result<Page<ProjectSummary>> {
+    let db = get_database().await?;
+    let repo = ProjectRepository::new(&db);
+
+    let status_filter = status.and_then(ProjectStatus::parse);
+    let projects = repo.list_page(status_filter, page).await?;
+
+    Ok(projects.map(ProjectSummary::from))
+}
+
 /// Get a project by ID
 pub async fn get(id: &str) -> Result<Option<ProjectSummary>> {
     let db = get_database().await?;
@@ -131,3 +145,11 @@ pub async fn delete(id: &str, hard: bool) -> Result<()> {
         repo.soft_delete(id).await
     }
 }
+
+/// Costs and activity across member projects (all active projects when
+/// `members` is empty)
+pub async fn workspace_overview(members: &[String]) -> Result<WorkspaceStats> {
+    let db = get_database().await?;
+    let projects = workspace::members(&db, members).await?;
+    workspace::stats(&db, &projects).await
+}
@@ -3,7 +3,7 @@
 //! Provides high-level operations for session management from GUI.
 
 use crate::domain::session::{
-    Session, SessionInfo, SessionPhase, SessionRepository, SessionStatus,
+    Session, SessionInfo, SessionManager, SessionPhase, SessionRepository, SessionStatus,
 };
 use crate::Result;
 use serde::{Deserialize, Serialize};
@@ -121,6 +121,77 @@ pub async fn create(
     Ok(SessionSummary::from(session))
 }
 
+/// Start a new session, pausing the active one
+pub async fn start(
+    project_id: Option<&str>,
+    feature_id: Option<&str>,
+    description: Option<&str>,
+) -> Result<SessionSummary> {
+    let db = get_database().await?;
+    let manager = SessionManager::new(db.pool().clone());
+
+    let project_uuid = project_id.map(|id| parse_id(id, "project")).transpose()?;
+    let feature_uuid = feature_id.map(|id| parse_id(id, "feature")).transpose()?;
+
+    let session = manager
+        .create(project_uuid, feature_uuid, description.map(String::from))
+        .await?;
+    Ok(SessionSummary::from(session))
+}
+
+/// Pause a session, or the active session if no ID is given
+pub async fn pause(id: Option<&str>) -> Result<SessionSummary> {
+    let db = get_database().await?;
+    let manager = SessionManager::new(db.pool().clone());
+
+    let uuid = resolve_session(&manager, id).await?;
+    let session = manager.pause(uuid).await?;
+    Ok(SessionSummary::from(session))
+}
+
+/// Resume a paused session, pausing any other active session
+pub async fn resume(id: &str) -> Result<SessionSummary> {
+    let db = get_database().await?;
+    let manager = SessionManager::new(db.pool().clone());
+
+    let session = manager.resume(parse_id(id, "session")?).await?;
+    Ok(SessionSummary::from(session))
+}
+
+/// End a session, or the active session if no ID is given
+///
+/// The session is marked completed, or abandoned when `abandon` is set.
+pub async fn end(id: Option<&str>, abandon: bool) -> Result<SessionSummary> {
+    let db = get_database().await?;
+    let manager = SessionManager::new(db.pool().clone());
+
+    let uuid = resolve_session(&manager, id).await?;
+    let session = if abandon {
+        manager.abandon(uuid).await?
+    } else {
+        manager.complete(uuid).await?
+    };
+    Ok(SessionSummary::from(session))
+}
+
+/// Parse an ID, naming the kind of ID in the error
+fn parse_id(id: &str, kind: &str) -> Result<uuid::Uuid> {
+    uuid::Uuid::parse_str(id)
+        .map_err(|_| crate::Error::InvalidInput(format!("Invalid {} ID: {}", kind, id)))
+}
+
+/// Resolve an explicit session ID, falling back to the active session
+async fn resolve_session(manager: &SessionManager, id: Option<&str>) -> Result<uuid::Uuid> {
+    match id {
+        Some(id) => parse_id(id, "session"),
+        None => manager
+            .get_active()
+            .await?
+            .map(|s| s.id)
+            .ok_or_else(|| crate::Error::NotFound("No active session".to_string())),
+    }
+}
+
 /// Update session status
 pub async fn update_status(id: &str, status: &str) -> Result<()> {
     let db = get_database().await?;
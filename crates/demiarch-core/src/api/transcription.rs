@@ -0,0 +1,49 @@
+//! Transcription API
+//!
+//! Provides voice note transcription for GUI. The webview hands over the
+//! recorded or picked file's bytes, which are written to a temporary file for
+//! the configured backend; the cost is recorded against the project.
+
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::cost::CostTracker;
+use crate::transcription::{self, Transcriber, Transcript};
+use crate::{Error, Result};
+
+use super::get_database;
+
+/// Transcribe audio uploaded from the GUI
+///
+/// `file_name` is only used for its extension, which tells the backend the
+/// audio format.
+pub async fn transcribe_audio(
+    file_name: &str,
+    audio: &[u8],
+    project_id: Option<&str>,
+) -> Result<Transcript> {
+    if audio.is_empty() {
+        return Err(Error::InvalidInput("The audio file is empty".to_string()));
+    }
+    let config = Config::load().map_err(|e| Error::ConfigError(e.to_string()))?;
+    let transcriber = Transcriber::new(&config.transcription)?
+        .with_cost_tracker(Arc::new(CostTracker::from_config(&config.cost)));
+
+    let extension = std::path::Path::new(file_name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let path = std::env::temp_dir().join(format!(
+        "demiarch-{}.{}",
+        uuid::Uuid::new_v4().simple(),
+        extension
+    ));
+    tokio::fs::write(&path, audio).await?;
+    let result = transcriber.transcribe(&path).await;
+    let _ = tokio::fs::remove_file(&path).await;
+    let transcript = result?;
+
+    let db = get_database().await?;
+    transcription::record_cost(&db, project_id, &transcript).await?;
+    Ok(transcript)
+}
@@ -0,0 +1,386 @@
+//! Local usage analytics
+//!
+//! `demiarch stats` summarizes how demiarch has been used over time:
+//! generations per week, their success rate, average cost per feature and
+//! the mix of models called. Everything is computed from the local database;
+//! nothing is sent anywhere.
+//!
+//! Weekly totals are rolled up into the `usage_rollups` table so history
+//! survives pruning of the raw generation and cost records. Weeks before the
+//! most recent rollup are frozen; that week and later ones are recomputed on
+//! every refresh.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Default number of weeks shown by `demiarch stats`
+pub const DEFAULT_WEEKS: u32 = 8;
+
+/// Usage totals for one week (Monday to Sunday, UTC)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyUsage {
+    pub week_start: NaiveDate,
+    pub generations: i64,
+    pub completed: i64,
+    pub failed: i64,
+    pub tokens: i64,
+    pub cost_usd: f64,
+    pub features_completed: i64,
+}
+
+impl WeeklyUsage {
+    /// Share of finished generations that completed, if any finished
+    pub fn success_rate(&self) -> Option<f64> {
+        success_rate(self.completed, self.failed)
+    }
+}
+
+/// One model's share of LLM calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelShare {
+    pub model: String,
+    pub calls: i64,
+    pub tokens: i64,
+    pub cost_usd: f64,
+    /// Fraction of all calls, 0.0 to 1.0
+    pub share: f64,
+}
+
+/// Usage over a window of weeks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageStats {
+    pub project_id: Option<String>,
+    pub since: NaiveDate,
+    /// One entry per week, oldest first, including weeks without activity
+    pub weeks: Vec<WeeklyUsage>,
+    pub total_generations: i64,
+    pub total_cost_usd: f64,
+    pub success_rate: Option<f64>,
+    /// Features with at least one generation in the window
+    pub features_generated: i64,
+    pub avg_cost_per_feature: Option<f64>,
+    pub model_mix: Vec<ModelShare>,
+}
+
+/// Monday of the week containing `date`
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn success_rate(completed: i64, failed: i64) -> Option<f64> {
+    let finished = completed + failed;
+    (finished > 0).then(|| completed as f64 / finished as f64)
+}
+
+/// SQLite expression for the Monday starting the week of a timestamp column
+fn week_of(column: &str) -> String {
+    format!("date({}, '-6 days', 'weekday 1')", column)
+}
+
+/// Recompute weekly rollups from the most recent rolled-up week onwards.
+///
+/// Returns the number of week/project rows written.
+pub async fn refresh_rollups(db: &Database) -> Result<usize> {
+    let from: Option<String> = sqlx::query_scalar("SELECT MAX(week_start) FROM usage_rollups")
+        .fetch_one(db.pool())
+        .await?;
+    let from = from.unwrap_or_else(|| "0000-01-01".to_string());
+
+    let mut rollups: BTreeMap<(String, String), WeeklyUsage> = BTreeMap::new();
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT {week} AS week, COALESCE(project_id, '') AS project_id,
+               COUNT(*) AS generations,
+               COALESCE(SUM(CASE WHEN status = 'completed' THEN 1 ELSE 0 END), 0) AS completed,
+               COALESCE(SUM(CASE WHEN status = 'failed' THEN 1 ELSE 0 END), 0) AS failed,
+               COALESCE(SUM(tokens_used), 0) AS tokens,
+               COALESCE(SUM(cost_usd), 0.0) AS cost_usd
+        FROM generations
+        WHERE {week} >= ?
+        GROUP BY week, project_id
+        "#,
+        week = week_of("created_at")
+    ))
+    .bind(&from)
+    .fetch_all(db.pool())
+    .await?;
+    for row in rows {
+        let entry = rollup_entry(&mut rollups, row.get("week"), row.get("project_id"))?;
+        entry.generations = row.get("generations");
+        entry.completed = row.get("completed");
+        entry.failed = row.get("failed");
+        entry.tokens = row.get("tokens");
+        entry.cost_usd = row.get("cost_usd");
+    }
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT {week} AS week, project_id, COUNT(*) AS done
+        FROM features
+        WHERE status = 'done' AND {week} >= ?
+        GROUP BY week, project_id
+        "#,
+        week = week_of("updated_at")
+    ))
+    .bind(&from)
+    .fetch_all(db.pool())
+    .await?;
+    for row in rows {
+        let entry = rollup_entry(&mut rollups, row.get("week"), row.get("project_id"))?;
+        entry.features_completed = row.get("done");
+    }
+
+    let now = Utc::now();
+    for ((week, project_id), usage) in &rollups {
+        sqlx::query(
+            r#"
+            INSERT INTO usage_rollups (
+                week_start, project_id, generations, completed, failed,
+                tokens, cost_usd, features_completed, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(week_start, project_id) DO UPDATE SET
+                generations = excluded.generations,
+                completed = excluded.completed,
+                failed = excluded.failed,
+                tokens = excluded.tokens,
+                cost_usd = excluded.cost_usd,
+                features_completed = excluded.features_completed,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(week)
+        .bind(project_id)
+        .bind(usage.generations)
+        .bind(usage.completed)
+        .bind(usage.failed)
+        .bind(usage.tokens)
+        .bind(usage.cost_usd)
+        .bind(usage.features_completed)
+        .bind(now)
+        .execute(db.pool())
+        .await?;
+    }
+
+    Ok(rollups.len())
+}
+
+fn rollup_entry(
+    rollups: &mut BTreeMap<(String, String), WeeklyUsage>,
+    week: String,
+    project_id: String,
+) -> Result<&mut WeeklyUsage> {
+    let week_start = NaiveDate::parse_from_str(&week, "%Y-%m-%d")
+        .map_err(|e| Error::Other(format!("Invalid week '{}': {}", week, e)))?;
+    Ok(rollups
+        .entry((week, project_id))
+        .or_insert_with(|| WeeklyUsage {
+            week_start,
+            ..Default::default()
+        }))
+}
+
+/// Compute usage statistics for the last `weeks` weeks, refreshing rollups
+/// first
+pub async fn compute(
+    db: &Database,
+    project_id: Option<&str>,
+    weeks: u32,
+    now: DateTime<Utc>,
+) -> Result<UsageStats> {
+    refresh_rollups(db).await?;
+
+    let current = week_start(now.date_naive());
+    let since = current - Duration::weeks(weeks.max(1) as i64 - 1);
+    let since_ts = since.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+
+    let mut by_week: BTreeMap<NaiveDate, WeeklyUsage> = BTreeMap::new();
+    let mut week = since;
+    while week <= current {
+        by_week.insert(
+            week,
+            WeeklyUsage {
+                week_start: week,
+                ..Default::default()
+            },
+        );
+        week += Duration::weeks(1);
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT week_start, SUM(generations) AS generations, SUM(completed) AS completed,
+               SUM(failed) AS failed, SUM(tokens) AS tokens, SUM(cost_usd) AS cost_usd,
+               SUM(features_completed) AS features_completed
+        FROM usage_rollups
+        WHERE week_start >= ? AND (? IS NULL OR project_id = ?)
+        GROUP BY week_start
+        "#,
+    )
+    .bind(since.to_string())
+    .bind(project_id)
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?;
+    for row in rows {
+        let week: String = row.get("week_start");
+        let Ok(week) = NaiveDate::parse_from_str(&week, "%Y-%m-%d") else {
+            continue;
+        };
+        if let Some(entry) = by_week.get_mut(&week) {
+            entry.generations = row.get("generations");
+            entry.completed = row.get("completed");
+            entry.failed = row.get("failed");
+            entry.tokens = row.get("tokens");
+            entry.cost_usd = row.get("cost_usd");
+            entry.features_completed = row.get("features_completed");
+        }
+    }
+    let weeks: Vec<WeeklyUsage> = by_week.into_values().collect();
+
+    let feature_row = sqlx::query(
+        r#"
+        SELECT COUNT(DISTINCT feature_id) AS features, COALESCE(SUM(cost_usd), 0.0) AS cost_usd
+        FROM generations
+        WHERE feature_id IS NOT NULL AND datetime(created_at) >= datetime(?)
+          AND (? IS NULL OR project_id = ?)
+        "#,
+    )
+    .bind(since_ts)
+    .bind(project_id)
+    .bind(project_id)
+    .fetch_one(db.pool())
+    .await?;
+    let features_generated: i64 = feature_row.get("features");
+    let feature_cost: f64 = feature_row.get("cost_usd");
+
+    let rows = sqlx::query(
+        r#"
+        SELECT model, COUNT(*) AS calls,
+               COALESCE(SUM(input_tokens + output_tokens), 0) AS tokens,
+               COALESCE(SUM(input_cost_usd + output_cost_usd), 0.0) AS cost_usd
+        FROM llm_costs
+        WHERE datetime(created_at) >= datetime(?) AND (? IS NULL OR project_id = ?)
+        GROUP BY model
+        ORDER BY calls DESC, model
+        "#,
+    )
+    .bind(since_ts)
+    .bind(project_id)
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?;
+    let total_calls: i64 = rows.iter().map(|r| r.get::<i64, _>("calls")).sum();
+    let model_mix = rows
+        .into_iter()
+        .map(|r| {
+            let calls: i64 = r.get("calls");
+            ModelShare {
+                model: r.get("model"),
+                calls,
+                tokens: r.get("tokens"),
+                cost_usd: r.get("cost_usd"),
+                share: calls as f64 / total_calls.max(1) as f64,
+            }
+        })
+        .collect();
+
+    let completed: i64 = weeks.iter().map(|w| w.completed).sum();
+    let failed: i64 = weeks.iter().map(|w| w.failed).sum();
+    Ok(UsageStats {
+        project_id: project_id.map(str::to_string),
+        since,
+        total_generations: weeks.iter().map(|w| w.generations).sum(),
+        total_cost_usd: weeks.iter().map(|w| w.cost_usd).sum(),
+        success_rate: success_rate(completed, failed),
+        features_generated,
+        avg_cost_per_feature: (features_generated > 0)
+            .then(|| feature_cost / features_generated as f64),
+        model_mix,
+        weeks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    async fn generation(db: &Database, id: &str, feature: Option<&str>, status: &str, at: &str) {
+        sqlx::query(
+            "INSERT INTO generations (id, feature_id, description, output_dir, status, tokens_used, cost_usd, created_at, updated_at) \
+             VALUES (?, ?, 'test', '.', ?, 100, 0.5, ?, ?)",
+        )
+        .bind(id)
+        .bind(feature)
+        .bind(status)
+        .bind(at)
+        .bind(at)
+        .execute(db.pool())
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn test_week_start_is_monday() {
+        let sunday = NaiveDate::from_ymd_opt(2026, 3, 8).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        assert_eq!(week_start(sunday), monday);
+        assert_eq!(week_start(monday), monday);
+    }
+
+    #[tokio::test]
+    async fn test_compute_weekly_usage_and_model_mix() {
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'Demo')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO features (id, project_id, title) VALUES ('f1', 'p1', 'Login')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        generation(&db, "g1", Some("f1"), "completed", "2026-03-02 10:00:00").await;
+        generation(&db, "g2", Some("f1"), "failed", "2026-03-03 10:00:00").await;
+        generation(&db, "g3", None, "completed", "2026-03-10 10:00:00").await;
+        for (id, model) in [("c1", "a/fast"), ("c2", "a/fast"), ("c3", "b/smart")] {
+            sqlx::query(
+                "INSERT INTO llm_costs (id, model, input_tokens, output_tokens, input_cost_usd, output_cost_usd, created_at) \
+                 VALUES (?, ?, 10, 10, 0.01, 0.01, '2026-03-03 10:00:00')",
+            )
+            .bind(id)
+            .bind(model)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+
+        let now = Utc.with_ymd_and_hms(2026, 3, 12, 12, 0, 0).unwrap();
+        let stats = compute(&db, None, 2, now).await.unwrap();
+
+        assert_eq!(stats.weeks.len(), 2);
+        assert_eq!(stats.weeks[0].generations, 2);
+        assert_eq!(stats.weeks[0].success_rate(), Some(0.5));
+        assert_eq!(stats.weeks[1].generations, 1);
+        assert_eq!(stats.total_generations, 3);
+        assert_eq!(stats.features_generated, 1);
+        assert_eq!(stats.avg_cost_per_feature, Some(1.0));
+        assert_eq!(stats.model_mix[0].model, "a/fast");
+        assert!((stats.model_mix[0].share - 2.0 / 3.0).abs() < 1e-9);
+
+        // Rollups keep history once the raw rows are gone
+        sqlx::query("DELETE FROM generations WHERE id = 'g1'")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let stats = compute(&db, None, 2, now).await.unwrap();
+        assert_eq!(stats.weeks[0].generations, 2);
+    }
+}
@@ -0,0 +1,374 @@
+//! Human approval gates between agent phases
+//!
+//! The `[approvals]` configuration section can require a person to confirm a
+//! generation's plan before any task runs, and to confirm review findings
+//! (syntax errors or lint findings) before files are written. Each gate asks
+//! an [`Approver`]: the CLI prompts in the terminal, while the GUI uses an
+//! [`ApprovalBroker`] that the frontend answers with `respond_to_approval`.
+//!
+//! A gate that is not answered within `approvals.timeout_secs`, or has no
+//! approver to ask, fails the same way as a rejection. Nothing has been
+//! written at either gate, so an abort leaves the project untouched; files
+//! held at the review gate stay on the generation as unapplied artifacts.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::commands::generate::GenerationResult;
+use crate::config::ApprovalsConfig;
+use crate::domain::feature_decomposition::ExecutionPlan;
+use crate::{Error, Result};
+
+/// Point in a generation that can wait for a person
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalGate {
+    /// The plan is ready and no task has run
+    AfterPlanning,
+    /// Review found problems in files that are about to be written
+    AfterReview,
+}
+
+impl ApprovalGate {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AfterPlanning => "after_planning",
+            Self::AfterReview => "after_review",
+        }
+    }
+}
+
+impl fmt::Display for ApprovalGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Answer to an approval request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    Approve,
+    Reject,
+}
+
+impl ApprovalDecision {
+    /// Parse "approve"/"reject" (or yes/no)
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "approve" | "approved" | "yes" | "y" => Ok(Self::Approve),
+            "reject" | "rejected" | "no" | "n" => Ok(Self::Reject),
+            other => Err(Error::InvalidInput(format!(
+                "Unknown approval decision '{}'. Use approve or reject.",
+                other
+            ))),
+        }
+    }
+}
+
+/// What a gate asks a person to confirm
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub gate: ApprovalGate,
+    /// Description the generation was started from
+    pub generation: String,
+    pub summary: String,
+    /// Plan tasks or review findings, one per line
+    pub details: Vec<String>,
+    pub requested_at: DateTime<Utc>,
+    /// When the gate gives up and aborts
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Something that can put an approval request in front of a person
+///
+/// The decision is sent on the returned channel. Dropping the sender
+/// without a decision counts as a rejection.
+pub trait Approver: Send + Sync {
+    fn request(&self, request: ApprovalRequest) -> oneshot::Receiver<ApprovalDecision>;
+}
+
+/// Approval gates configured for generations
+#[derive(Clone)]
+pub struct ApprovalGates {
+    config: ApprovalsConfig,
+    approver: Option<Arc<dyn Approver>>,
+}
+
+impl fmt::Debug for ApprovalGates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApprovalGates")
+            .field("config", &self.config)
+            .field("approver", &self.approver.is_some())
+            .finish()
+    }
+}
+
+impl ApprovalGates {
+    /// Gates from the `[approvals]` section, with no one to ask yet
+    pub fn new(config: &ApprovalsConfig) -> Self {
+        Self {
+            config: config.clone(),
+            approver: None,
+        }
+    }
+
+    /// Ask `approver` when a gate is reached
+    pub fn with_approver(mut self, approver: Arc<dyn Approver>) -> Self {
+        self.approver = Some(approver);
+        self
+    }
+
+    /// Whether a gate stops for confirmation
+    pub fn is_required(&self, gate: ApprovalGate) -> bool {
+        match gate {
+            ApprovalGate::AfterPlanning => self.config.after_planning,
+            ApprovalGate::AfterReview => self.config.after_review,
+        }
+    }
+
+    /// Confirm a plan before any of its tasks run
+    pub async fn after_planning(&self, description: &str, plan: &ExecutionPlan) -> Result<()> {
+        if !self.is_required(ApprovalGate::AfterPlanning) {
+            return Ok(());
+        }
+        let details = plan
+            .tasks
+            .iter()
+            .map(|task| format!("{} [{}] {}", task.id, task.agent_type, task.description))
+            .collect();
+        self.ask(
+            ApprovalGate::AfterPlanning,
+            description,
+            format!("Plan with {} task(s) is ready", plan.tasks.len()),
+            details,
+        )
+        .await
+    }
+
+    /// Confirm review findings before the files are written
+    ///
+    /// Results without syntax errors, security or lint findings pass without
+    /// asking.
+    pub async fn after_review(&self, description: &str, result: &GenerationResult) -> Result<()> {
+        if !self.is_required(ApprovalGate::AfterReview) {
+            return Ok(());
+        }
+        let details: Vec<String> = result
+            .rejected_files()
+            .map(|file| {
+                format!(
+                    "{}: syntax errors, will not be written",
+                    file.path.display()
+                )
+            })
+            .chain(result.security_findings().map(ToString::to_string))
+            .chain(result.lint_findings().map(ToString::to_string))
+            .collect();
+        if details.is_empty() {
+            return Ok(());
+        }
+        self.ask(
+            ApprovalGate::AfterReview,
+            description,
+            format!(
+                "Review found {} issue(s) in {} file(s)",
+                details.len(),
+                result.files.len()
+            ),
+            details,
+        )
+        .await
+    }
+
+    async fn ask(
+        &self,
+        gate: ApprovalGate,
+        description: &str,
+        summary: String,
+        details: Vec<String>,
+    ) -> Result<()> {
+        let Some(approver) = &self.approver else {
+            return Err(Error::ApprovalRejected(format!(
+                "{} requires approval but there is no one to ask",
+                gate
+            )));
+        };
+
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        let requested_at = Utc::now();
+        let request = ApprovalRequest {
+            id: Uuid::new_v4().to_string(),
+            gate,
+            generation: description.to_string(),
+            summary,
+            details,
+            requested_at,
+            expires_at: requested_at + chrono::Duration::seconds(self.config.timeout_secs as i64),
+        };
+        let id = request.id.clone();
+        tracing::info!(approval_id = %id, gate = %gate, "Waiting for approval");
+
+        match tokio::time::timeout(timeout, approver.request(request)).await {
+            Ok(Ok(ApprovalDecision::Approve)) => Ok(()),
+            Ok(Ok(ApprovalDecision::Reject)) | Ok(Err(_)) => {
+                Err(Error::ApprovalRejected(format!("{} was rejected", gate)))
+            }
+            Err(_) => {
+                tracing::warn!(approval_id = %id, gate = %gate, "Approval timed out");
+                Err(Error::ApprovalTimeout(self.config.timeout_secs))
+            }
+        }
+    }
+}
+
+/// A request waiting in an [`ApprovalBroker`] and where its answer goes
+type PendingApproval = (ApprovalRequest, oneshot::Sender<ApprovalDecision>);
+
+/// Approval requests answered from elsewhere, such as the GUI
+///
+/// Each request is handed to the listener and held until [`respond`] is
+/// called with its ID or the gate stops waiting.
+///
+/// [`respond`]: ApprovalBroker::respond
+#[derive(Default)]
+pub struct ApprovalBroker {
+    pending: Mutex<HashMap<String, PendingApproval>>,
+    listener: Option<Box<dyn Fn(&ApprovalRequest) + Send + Sync>>,
+}
+
+impl ApprovalBroker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `listener` with every new request
+    pub fn with_listener(
+        mut self,
+        listener: impl Fn(&ApprovalRequest) + Send + Sync + 'static,
+    ) -> Self {
+        self.listener = Some(Box::new(listener));
+        self
+    }
+
+    /// Requests still waiting for an answer
+    pub fn pending(&self) -> Vec<ApprovalRequest> {
+        let mut pending = self.lock();
+        pending.retain(|_, (_, sender)| !sender.is_closed());
+        let mut requests: Vec<ApprovalRequest> = pending
+            .values()
+            .map(|(request, _)| request.clone())
+            .collect();
+        requests.sort_by_key(|r| r.requested_at);
+        requests
+    }
+
+    /// Answer a waiting request
+    pub fn respond(&self, request_id: &str, decision: ApprovalDecision) -> Result<()> {
+        let (_, sender) = self.lock().remove(request_id).ok_or_else(|| {
+            Error::NotFound(format!("No pending approval request '{}'", request_id))
+        })?;
+        sender.send(decision).map_err(|_| {
+            Error::InvalidInput(format!(
+                "Approval request '{}' is no longer waiting",
+                request_id
+            ))
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, PendingApproval>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Approver for ApprovalBroker {
+    fn request(&self, request: ApprovalRequest) -> oneshot::Receiver<ApprovalDecision> {
+        let (sender, receiver) = oneshot::channel();
+        if let Some(listener) = &self.listener {
+            listener(&request);
+        }
+        let mut pending = self.lock();
+        pending.retain(|_, (_, sender)| !sender.is_closed());
+        pending.insert(request.id.clone(), (request, sender));
+        receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::feature_decomposition::PlanTask;
+
+    fn config(timeout_secs: u64) -> ApprovalsConfig {
+        ApprovalsConfig {
+            after_planning: true,
+            after_review: true,
+            timeout_secs,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_broker_approves_and_rejects() {
+        let broker = Arc::new(ApprovalBroker::new());
+        let gates = ApprovalGates::new(&config(30)).with_approver(broker.clone());
+        let plan = ExecutionPlan::new("Add login").with_task(PlanTask::coding("task-1", "Login"));
+
+        let responder = {
+            let broker = broker.clone();
+            tokio::spawn(async move {
+                for decision in [ApprovalDecision::Approve, ApprovalDecision::Reject] {
+                    let request = loop {
+                        if let Some(request) = broker.pending().pop() {
+                            break request;
+                        }
+                        tokio::task::yield_now().await;
+                    };
+                    assert_eq!(request.gate, ApprovalGate::AfterPlanning);
+                    assert_eq!(request.details, vec!["task-1 [coder] Login"]);
+                    broker.respond(&request.id, decision).unwrap();
+                }
+            })
+        };
+
+        gates.after_planning("Add login", &plan).await.unwrap();
+        let err = gates.after_planning("Add login", &plan).await.unwrap_err();
+        assert!(matches!(err, Error::ApprovalRejected(_)));
+        responder.await.unwrap();
+        assert!(broker
+            .respond("missing", ApprovalDecision::Approve)
+            .is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unanswered_gate_times_out() {
+        let broker = Arc::new(ApprovalBroker::new());
+        let gates = ApprovalGates::new(&config(5)).with_approver(broker.clone());
+        let plan = ExecutionPlan::new("Add login");
+
+        let err = gates.after_planning("Add login", &plan).await.unwrap_err();
+        assert!(matches!(err, Error::ApprovalTimeout(5)));
+        assert!(broker.pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_gates_pass_when_not_required_or_clean() {
+        let gates = ApprovalGates::new(&ApprovalsConfig::default());
+        let plan = ExecutionPlan::new("Add login");
+        gates.after_planning("Add login", &plan).await.unwrap();
+
+        let gates = ApprovalGates::new(&config(5));
+        gates
+            .after_review("Add login", &GenerationResult::default())
+            .await
+            .unwrap();
+        assert!(gates.after_planning("Add login", &plan).await.is_err());
+    }
+}
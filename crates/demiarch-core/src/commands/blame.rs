@@ -0,0 +1,332 @@
+//! Provenance of generated code, line by line
+//!
+//! `demiarch artifacts blame <file>` traces each region of a file back to
+//! the generation that wrote it: the feature it implemented, the plan
+//! task's agent, the model and the prompt. A line is attributed to the most
+//! recent recorded artifact of the file that contains it verbatim; lines no
+//! artifact contains were written or changed by hand. When the file is in a
+//! git repository, `git blame` adds the commit that last touched each line.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::commands::feature::FeatureRepository;
+use crate::commands::generation::{artifact_target, ArtifactDecision, GenerationRepository};
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Generation that produced a range of lines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub generation_id: String,
+    pub artifact_id: String,
+    /// Artifact version (increases each time the file is regenerated)
+    pub version: i64,
+    pub feature_id: Option<String>,
+    pub feature_title: Option<String>,
+    /// Agent type of the plan task that produced the file
+    pub agent: Option<String>,
+    pub models: Vec<String>,
+    /// Description the generation was run from
+    pub prompt: String,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Last commit to touch a range of lines, from `git blame`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitInfo {
+    pub sha: String,
+    pub author: String,
+    pub summary: String,
+    pub committed_at: Option<DateTime<Utc>>,
+}
+
+/// Consecutive lines with the same origin (1-based, inclusive)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlameRange {
+    pub start: usize,
+    pub end: usize,
+    /// Generation the lines came from; `None` when written by hand
+    pub source: Option<Provenance>,
+    pub commit: Option<CommitInfo>,
+}
+
+/// Provenance of every line of a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileBlame {
+    pub path: PathBuf,
+    pub lines: usize,
+    /// Lines attributed to a generation
+    pub generated_lines: usize,
+    pub ranges: Vec<BlameRange>,
+}
+
+/// Attribute each line to the index of an artifact that contains it
+///
+/// `artifacts` are ordered newest first. A line keeps the previous line's
+/// artifact when that artifact also contains it, so a region is not split
+/// between generations that share boilerplate lines. Blank lines follow the
+/// line before them.
+pub fn attribute_lines(lines: &[&str], artifacts: &[&str]) -> Vec<Option<usize>> {
+    let sets: Vec<std::collections::HashSet<&str>> = artifacts
+        .iter()
+        .map(|content| content.lines().map(str::trim_end).collect())
+        .collect();
+
+    let mut owners = Vec::with_capacity(lines.len());
+    let mut previous: Option<usize> = None;
+    for line in lines {
+        let line = line.trim_end();
+        let owner = if line.trim().is_empty() || previous.is_some_and(|p| sets[p].contains(line)) {
+            previous
+        } else {
+            sets.iter().position(|set| set.contains(line))
+        };
+        owners.push(owner);
+        previous = owner;
+    }
+    owners
+}
+
+/// Trace the lines of `file` to the generations that produced them
+pub async fn blame(db: &Database, file: &Path) -> Result<FileBlame> {
+    let path = std::fs::canonicalize(file)
+        .map_err(|e| Error::InvalidInput(format!("Cannot read {}: {}", file.display(), e)))?;
+    let content = std::fs::read_to_string(&path)?;
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_string();
+
+    // Candidate generations wrote a file with the same name; the full path
+    // is checked against each generation's output directory below.
+    let generation_ids: Vec<String> = sqlx::query(
+        r#"
+        SELECT a.generation_id, MAX(a.created_at) AS latest
+        FROM generation_artifacts a
+        WHERE a.file_path = ? OR a.file_path LIKE '%/' || ?
+        GROUP BY a.generation_id
+        ORDER BY latest DESC
+        "#,
+    )
+    .bind(&name)
+    .bind(&name)
+    .fetch_all(db.pool())
+    .await?
+    .into_iter()
+    .map(|row| row.get("generation_id"))
+    .collect();
+
+    let generations = GenerationRepository::new(db);
+    let features = FeatureRepository::new(db);
+    let mut sources: Vec<(Provenance, String)> = Vec::new();
+    for generation_id in generation_ids {
+        let Some(generation) = generations.get(&generation_id).await? else {
+            continue;
+        };
+        let feature_title = match &generation.feature_id {
+            Some(id) => features.get(id).await?.map(|f| f.title),
+            None => None,
+        };
+        for artifact in generations.list_artifacts(&generation.id).await? {
+            if artifact.decision == ArtifactDecision::Rejected {
+                continue;
+            }
+            let matches = artifact_target(&generation.output_dir, &artifact.file_path)
+                .ok()
+                .and_then(|target| std::fs::canonicalize(target).ok())
+                .is_some_and(|target| target == path);
+            if !matches {
+                continue;
+            }
+            let agent = artifact.task_id.as_ref().and_then(|task_id| {
+                generation
+                    .plan
+                    .iter()
+                    .flat_map(|p| &p.tasks)
+                    .find(|t| &t.id == task_id)
+                    .map(|t| t.agent_type.clone())
+            });
+            sources.push((
+                Provenance {
+                    generation_id: generation.id.clone(),
+                    artifact_id: artifact.id,
+                    version: artifact.version,
+                    feature_id: generation.feature_id.clone(),
+                    feature_title: feature_title.clone(),
+                    agent,
+                    models: generation
+                        .environment
+                        .as_ref()
+                        .map(|e| e.models.clone())
+                        .unwrap_or_default(),
+                    prompt: generation.description.clone(),
+                    generated_at: artifact.created_at,
+                },
+                artifact.content,
+            ));
+        }
+    }
+    sources.sort_by_key(|(source, _)| std::cmp::Reverse(source.generated_at));
+
+    let lines: Vec<&str> = content.lines().collect();
+    let contents: Vec<&str> = sources.iter().map(|(_, c)| c.as_str()).collect();
+    let owners = attribute_lines(&lines, &contents);
+    let commits = git_blame(&path).await;
+
+    let mut ranges: Vec<BlameRange> = Vec::new();
+    for (index, owner) in owners.iter().enumerate() {
+        let source = owner.map(|i| sources[i].0.clone());
+        let commit = commits.get(&(index + 1)).cloned();
+        match ranges.last_mut() {
+            Some(last) if last.source == source && last.commit == commit => last.end = index + 1,
+            _ => ranges.push(BlameRange {
+                start: index + 1,
+                end: index + 1,
+                source,
+                commit,
+            }),
+        }
+    }
+
+    Ok(FileBlame {
+        path,
+        lines: lines.len(),
+        generated_lines: owners.iter().filter(|o| o.is_some()).count(),
+        ranges,
+    })
+}
+
+/// Commit per line number from `git blame`; empty outside a repository
+async fn git_blame(path: &Path) -> HashMap<usize, CommitInfo> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return HashMap::new();
+    };
+    let output = tokio::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["blame", "--line-porcelain", "--"])
+        .arg(name)
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_line_porcelain(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => HashMap::new(),
+    }
+}
+
+/// Parse `git blame --line-porcelain` output
+fn parse_line_porcelain(output: &str) -> HashMap<usize, CommitInfo> {
+    let mut commits = HashMap::new();
+    let mut current: Option<(usize, CommitInfo)> = None;
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            if let Some((number, commit)) = current.take() {
+                commits.insert(number, commit);
+            }
+            continue;
+        }
+        match current.as_mut() {
+            None => {
+                let mut fields = line.split_whitespace();
+                let (Some(sha), Some(_), Some(number)) =
+                    (fields.next(), fields.next(), fields.next())
+                else {
+                    continue;
+                };
+                if let Ok(number) = number.parse() {
+                    current = Some((
+                        number,
+                        CommitInfo {
+                            sha: sha[..sha.len().min(8)].to_string(),
+                            author: String::new(),
+                            summary: String::new(),
+                            committed_at: None,
+                        },
+                    ));
+                }
+            }
+            Some((_, commit)) => {
+                if let Some(author) = line.strip_prefix("author ") {
+                    commit.author = author.to_string();
+                } else if let Some(time) = line.strip_prefix("author-time ") {
+                    commit.committed_at = time
+                        .parse()
+                        .ok()
+                        .and_then(|secs| Utc.timestamp_opt(secs, 0).single());
+                } else if let Some(summary) = line.strip_prefix("summary ") {
+                    commit.summary = summary.to_string();
+                }
+            }
+        }
+    }
+    commits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::generation::{Generation, GenerationArtifact};
+
+    #[test]
+    fn test_attribute_lines_prefers_continuity() {
+        let newest = "fn a() {}\nshared\n";
+        let oldest = "fn b() {}\nshared\nfn c() {}\n";
+        let lines = ["fn b() {}", "shared", "", "fn a() {}", "by hand"];
+
+        assert_eq!(
+            attribute_lines(&lines, &[newest, oldest]),
+            vec![Some(1), Some(1), Some(1), Some(0), None]
+        );
+    }
+
+    #[test]
+    fn test_parse_line_porcelain() {
+        let output = "abcdef1234567890 1 1 2\nauthor Ada\nauthor-time 1700000000\nsummary Add login\n\tfn a() {}\nabcdef1234567890 2 2\nauthor Ada\nauthor-time 1700000000\nsummary Add login\n\tshared\n";
+        let commits = parse_line_porcelain(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[&1].sha, "abcdef12");
+        assert_eq!(commits[&2].author, "Ada");
+        assert_eq!(commits[&2].summary, "Add login");
+        assert!(commits[&1].committed_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_blame_traces_lines_to_generation() {
+        let db = Database::in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("lib.rs"),
+            "pub fn generated() {}\n\npub fn edited() {}\n",
+        )
+        .unwrap();
+
+        let repo = GenerationRepository::new(&db);
+        let generation = Generation::new("Add helpers", dir.path().to_string_lossy());
+        repo.create(&generation).await.unwrap();
+        repo.save_artifact(&GenerationArtifact::new(
+            &generation.id,
+            "lib.rs",
+            "pub fn generated() {}\n",
+        ))
+        .await
+        .unwrap();
+
+        let blame = blame(&db, &dir.path().join("lib.rs")).await.unwrap();
+        assert_eq!(blame.lines, 3);
+        assert_eq!(blame.generated_lines, 2);
+        assert_eq!(blame.ranges.len(), 2);
+        let source = blame.ranges[0].source.as_ref().unwrap();
+        assert_eq!(source.generation_id, generation.id);
+        assert_eq!(source.prompt, "Add helpers");
+        assert_eq!((blame.ranges[1].start, blame.ranges[1].end), (3, 3));
+        assert!(blame.ranges[1].source.is_none());
+    }
+}
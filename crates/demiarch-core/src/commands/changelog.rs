@@ -0,0 +1,531 @@
+//! Changelog generation from completed work
+//!
+//! `demiarch changelog --since <tag|date>` turns what was finished in a
+//! period into a CHANGELOG section: features moved to done (with their
+//! acceptance criteria and the files their generations produced), plus
+//! commits from the project's git history. Commits that mention a feature's
+//! ID are listed under that feature; other conventional commits
+//! (`fix(api): ...`) get entries of their own. Entries are grouped by
+//! conventional-commit type.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::commands::feature::{Feature, FeatureRepository, FeatureStatus};
+use crate::commands::project::Project;
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Changelog file written by `--write`
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+/// Heading of the top-level changelog document
+const CHANGELOG_HEADER: &str = "# Changelog";
+
+/// Where a changelog starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Since {
+    /// A calendar day (UTC), YYYY-MM-DD
+    Date(NaiveDate),
+    /// A git tag, resolved to its commit time
+    Tag(String),
+}
+
+impl Since {
+    /// Parse a date, falling back to treating the value as a tag
+    pub fn parse(s: &str) -> Self {
+        match NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d") {
+            Ok(date) => Self::Date(date),
+            Err(_) => Self::Tag(s.trim().to_string()),
+        }
+    }
+}
+
+/// Conventional-commit change type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Feat,
+    Fix,
+    Perf,
+    Refactor,
+    Docs,
+    Test,
+    Build,
+    Ci,
+    Chore,
+}
+
+impl ChangeKind {
+    /// Parse a conventional-commit type or a feature label
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "feat" | "feature" => Some(Self::Feat),
+            "fix" | "bug" | "bugfix" => Some(Self::Fix),
+            "perf" | "performance" => Some(Self::Perf),
+            "refactor" => Some(Self::Refactor),
+            "docs" | "doc" | "documentation" => Some(Self::Docs),
+            "test" | "tests" => Some(Self::Test),
+            "build" => Some(Self::Build),
+            "ci" => Some(Self::Ci),
+            "chore" => Some(Self::Chore),
+            _ => None,
+        }
+    }
+
+    /// Section heading in the changelog
+    pub fn heading(&self) -> &'static str {
+        match self {
+            Self::Feat => "Features",
+            Self::Fix => "Bug Fixes",
+            Self::Perf => "Performance",
+            Self::Refactor => "Refactoring",
+            Self::Docs => "Documentation",
+            Self::Test => "Tests",
+            Self::Build => "Build",
+            Self::Ci => "CI",
+            Self::Chore => "Chores",
+        }
+    }
+}
+
+/// A commit subject split into its conventional-commit parts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Commit {
+    /// Abbreviated hash
+    pub sha: String,
+    pub kind: Option<ChangeKind>,
+    pub scope: Option<String>,
+    pub summary: String,
+    pub breaking: bool,
+    /// Full message, used to find feature IDs
+    #[serde(skip)]
+    pub message: String,
+}
+
+impl Commit {
+    /// Parse `type(scope)!: summary`; subjects without a known type keep
+    /// the whole subject as the summary
+    pub fn parse(sha: &str, subject: &str, body: &str) -> Self {
+        let mut commit = Self {
+            sha: sha.to_string(),
+            kind: None,
+            scope: None,
+            summary: subject.trim().to_string(),
+            breaking: body.contains("BREAKING CHANGE"),
+            message: format!("{}\n{}", subject, body),
+        };
+        let Some((head, summary)) = subject.split_once(':') else {
+            return commit;
+        };
+        let (head, bang) = match head.strip_suffix('!') {
+            Some(head) => (head, true),
+            None => (head, false),
+        };
+        let (kind, scope) = match head.split_once('(') {
+            Some((kind, scope)) => (kind, scope.strip_suffix(')').map(str::to_string)),
+            None => (head, None),
+        };
+        if let Some(kind) = ChangeKind::parse(kind) {
+            commit.kind = Some(kind);
+            commit.scope = scope;
+            commit.summary = summary.trim().to_string();
+            commit.breaking |= bang;
+        }
+        commit
+    }
+}
+
+/// One line of the changelog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeEntry {
+    /// Feature the entry describes; `None` for a standalone commit
+    pub feature_id: Option<String>,
+    pub kind: ChangeKind,
+    pub scope: Option<String>,
+    pub title: String,
+    pub breaking: bool,
+    pub acceptance_criteria: Vec<String>,
+    /// Abbreviated hashes of linked commits
+    pub commits: Vec<String>,
+    /// Files produced by the feature's generations
+    pub artifacts: Vec<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// A changelog section for one period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Changelog {
+    /// Section title, e.g. "Unreleased" or "1.2.0"
+    pub version: String,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub entries: Vec<ChangeEntry>,
+}
+
+impl Changelog {
+    /// Render the section as Markdown, grouped by change type
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("## [{}] - {}\n", self.version, self.until.date_naive());
+        if self.entries.is_empty() {
+            out.push_str("\nNo changes.\n");
+            return out;
+        }
+
+        let mut kinds: Vec<ChangeKind> = self.entries.iter().map(|e| e.kind).collect();
+        kinds.sort();
+        kinds.dedup();
+        for kind in kinds {
+            out.push_str(&format!("\n### {}\n\n", kind.heading()));
+            for entry in self.entries.iter().filter(|e| e.kind == kind) {
+                out.push_str("- ");
+                if entry.breaking {
+                    out.push_str("**BREAKING** ");
+                }
+                if let Some(scope) = &entry.scope {
+                    out.push_str(&format!("**{}:** ", scope));
+                }
+                out.push_str(&entry.title);
+                if !entry.commits.is_empty() {
+                    out.push_str(&format!(" ({})", entry.commits.join(", ")));
+                }
+                out.push('\n');
+                for criterion in &entry.acceptance_criteria {
+                    out.push_str(&format!("  - {}\n", criterion));
+                }
+                if !entry.artifacts.is_empty() {
+                    out.push_str(&format!("  - Files: {}\n", entry.artifacts.join(", ")));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Change type for a feature: a type label, a `type:` title prefix, or feat
+fn feature_kind(feature: &Feature) -> (ChangeKind, String) {
+    let from_label = feature
+        .labels
+        .iter()
+        .flatten()
+        .find_map(|label| ChangeKind::parse(label));
+    let parsed = Commit::parse("", &feature.title, "");
+    match (from_label, parsed.kind) {
+        (Some(kind), _) => (kind, feature.title.clone()),
+        (None, Some(kind)) => (kind, parsed.summary),
+        (None, None) => (ChangeKind::Feat, feature.title.clone()),
+    }
+}
+
+/// Acceptance criteria as one line per criterion
+fn criteria_lines(criteria: Option<&str>) -> Vec<String> {
+    criteria
+        .unwrap_or_default()
+        .lines()
+        .map(|l| l.trim().trim_start_matches(['-', '*']).trim())
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Build the changelog for a project from `since` up to `now`
+pub async fn build(
+    db: &Database,
+    project: &Project,
+    since: &Since,
+    version: &str,
+    now: DateTime<Utc>,
+) -> Result<Changelog> {
+    let repo_dir = project.path.as_deref().map(PathBuf::from);
+    let since = match since {
+        Since::Date(date) => date
+            .and_hms_opt(0, 0, 0)
+            .map(|t| t.and_utc())
+            .unwrap_or_default(),
+        Since::Tag(tag) => {
+            let dir = repo_dir.as_deref().ok_or_else(|| {
+                Error::InvalidInput(format!(
+                    "Project '{}' has no directory, so tag '{}' cannot be resolved; pass a date",
+                    project.name, tag
+                ))
+            })?;
+            tag_time(dir, tag).await?
+        }
+    };
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, completed_at FROM features
+        WHERE project_id = ? AND status = 'done'
+          AND julianday(completed_at) >= julianday(?)
+        ORDER BY completed_at
+        "#,
+    )
+    .bind(&project.id)
+    .bind(since.to_rfc3339_opts(SecondsFormat::Millis, true))
+    .fetch_all(db.pool())
+    .await?;
+    let features = FeatureRepository::new(db)
+        .list_by_project(&project.id, Some(FeatureStatus::Done))
+        .await?;
+    let commits = match &repo_dir {
+        Some(dir) => commits_since(dir, since).await,
+        None => Vec::new(),
+    };
+
+    let mut linked = vec![false; commits.len()];
+    let mut entries = Vec::new();
+    for row in rows {
+        let id: String = row.get("id");
+        let Some(feature) = features.iter().find(|f| f.id == id) else {
+            continue;
+        };
+        let short_id = &feature.id[..feature.id.len().min(8)];
+        let mut feature_commits = Vec::new();
+        let mut breaking = false;
+        for (i, commit) in commits.iter().enumerate() {
+            if commit.message.contains(short_id) {
+                linked[i] = true;
+                breaking |= commit.breaking;
+                feature_commits.push(commit.sha.clone());
+            }
+        }
+        let (kind, title) = feature_kind(feature);
+        entries.push(ChangeEntry {
+            feature_id: Some(feature.id.clone()),
+            kind,
+            scope: None,
+            title,
+            breaking,
+            acceptance_criteria: criteria_lines(feature.acceptance_criteria.as_deref()),
+            commits: feature_commits,
+            artifacts: feature_artifacts(db, &feature.id).await?,
+            completed_at: row.get("completed_at"),
+        });
+    }
+
+    for (commit, _) in commits.iter().zip(&linked).filter(|(_, linked)| !**linked) {
+        if let Some(kind) = commit.kind {
+            entries.push(ChangeEntry {
+                feature_id: None,
+                kind,
+                scope: commit.scope.clone(),
+                title: commit.summary.clone(),
+                breaking: commit.breaking,
+                acceptance_criteria: Vec::new(),
+                commits: vec![commit.sha.clone()],
+                artifacts: Vec::new(),
+                completed_at: None,
+            });
+        }
+    }
+
+    Ok(Changelog {
+        version: version.to_string(),
+        since,
+        until: now,
+        entries,
+    })
+}
+
+/// Files written by a feature's completed generations
+async fn feature_artifacts(db: &Database, feature_id: &str) -> Result<Vec<String>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT a.file_path FROM generation_artifacts a
+        JOIN generations g ON g.id = a.generation_id
+        WHERE g.feature_id = ? AND g.status = 'completed' AND a.decision != 'rejected'
+        ORDER BY a.file_path
+        "#,
+    )
+    .bind(feature_id)
+    .fetch_all(db.pool())
+    .await?;
+    Ok(rows.into_iter().map(|r| r.get("file_path")).collect())
+}
+
+/// Commit time of a git tag
+async fn tag_time(dir: &Path, tag: &str) -> Result<DateTime<Utc>> {
+    let output = git(dir, &["log", "-1", "--format=%cI", tag]).await?;
+    DateTime::parse_from_rfc3339(output.trim())
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| {
+            Error::InvalidInput(format!(
+                "'{}' is neither a date (YYYY-MM-DD) nor a tag in {}",
+                tag,
+                dir.display()
+            ))
+        })
+}
+
+/// Commits since `since`, oldest first; empty when git is unavailable
+async fn commits_since(dir: &Path, since: DateTime<Utc>) -> Vec<Commit> {
+    let since = format!("--since={}", since.to_rfc3339());
+    let output = match git(
+        dir,
+        &[
+            "log",
+            "--no-merges",
+            "--reverse",
+            &since,
+            "--format=%h%x1f%s%x1f%b%x1e",
+        ],
+    )
+    .await
+    {
+        Ok(output) => output,
+        Err(e) => {
+            tracing::warn!(error = %e, "Could not read git history for changelog");
+            return Vec::new();
+        }
+    };
+    output
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(3, '\x1f');
+            let sha = fields.next().filter(|s| !s.is_empty())?;
+            let subject = fields.next().unwrap_or_default();
+            Some(Commit::parse(
+                sha,
+                subject,
+                fields.next().unwrap_or_default(),
+            ))
+        })
+        .collect()
+}
+
+async fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = tokio::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(Error::InvalidInput(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Insert a section at the top of a changelog, below its `# Changelog` header
+pub fn prepend_section(existing: &str, section: &str) -> String {
+    let section = section.trim_end();
+    if existing.trim().is_empty() {
+        return format!("{}\n\n{}\n", CHANGELOG_HEADER, section);
+    }
+    match existing.strip_prefix(CHANGELOG_HEADER) {
+        Some(rest) => {
+            // Keep any intro text between the header and the first release
+            let split = rest.find("\n## ").map(|i| i + 1).unwrap_or(rest.len());
+            let (intro, releases) = rest.split_at(split);
+            let updated = format!(
+                "{}{}\n\n{}\n\n{}",
+                CHANGELOG_HEADER,
+                intro.trim_end(),
+                section,
+                releases.trim_start()
+            );
+            format!("{}\n", updated.trim_end())
+        }
+        None => format!("{}\n\n{}\n", section, existing.trim_end()),
+    }
+}
+
+/// Write the section into the project's CHANGELOG.md, creating it if needed
+pub fn write_to_project(dir: &Path, changelog: &Changelog) -> Result<PathBuf> {
+    let path = dir.join(CHANGELOG_FILE);
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    std::fs::write(&path, prepend_section(&existing, &changelog.to_markdown()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::project::ProjectRepository;
+
+    #[test]
+    fn test_parse_conventional_commits() {
+        let commit = Commit::parse("abc1234", "fix(api)!: reject empty ids", "");
+        assert_eq!(commit.kind, Some(ChangeKind::Fix));
+        assert_eq!(commit.scope.as_deref(), Some("api"));
+        assert_eq!(commit.summary, "reject empty ids");
+        assert!(commit.breaking);
+
+        let plain = Commit::parse("def5678", "Update readme: typo", "");
+        assert_eq!(plain.kind, None);
+        assert_eq!(plain.summary, "Update readme: typo");
+
+        assert_eq!(
+            Since::parse("2025-03-01"),
+            Since::Date(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap())
+        );
+        assert_eq!(Since::parse("v1.2.0"), Since::Tag("v1.2.0".to_string()));
+    }
+
+    #[test]
+    fn test_prepend_section_keeps_header_and_history() {
+        let existing =
+            "# Changelog\n\nAll notable changes.\n\n## [1.0.0] - 2025-01-01\n\n- Initial\n";
+        let updated = prepend_section(existing, "## [Unreleased] - 2025-02-01\n\n- New\n");
+        assert_eq!(
+            updated,
+            "# Changelog\n\nAll notable changes.\n\n## [Unreleased] - 2025-02-01\n\n- New\n\n## [1.0.0] - 2025-01-01\n\n- Initial\n"
+        );
+        assert_eq!(
+            prepend_section("", "## [Unreleased] - 2025-02-01\n"),
+            "# Changelog\n\n## [Unreleased] - 2025-02-01\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_build_groups_completed_features() {
+        let db = Database::in_memory().await.unwrap();
+        let project = Project::new("shop", "rust", "");
+        ProjectRepository::new(&db).create(&project).await.unwrap();
+
+        let features = FeatureRepository::new(&db);
+        let mut login = Feature::new(&project.id, "Login");
+        login.acceptance_criteria = Some("- Given a user\n- When they sign in".to_string());
+        let bug = Feature::new(&project.id, "fix: cart total rounding");
+        let open = Feature::new(&project.id, "Search");
+        for f in [&login, &bug, &open] {
+            features.create(f).await.unwrap();
+        }
+        features.update(&login).await.unwrap();
+        features
+            .update_status(&login.id, FeatureStatus::Done)
+            .await
+            .unwrap();
+        features
+            .update_status(&bug.id, FeatureStatus::Done)
+            .await
+            .unwrap();
+
+        let since = Since::Date(Utc::now().date_naive());
+        let changelog = build(&db, &project, &since, "Unreleased", Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(changelog.entries.len(), 2);
+
+        let markdown = changelog.to_markdown();
+        assert!(markdown.starts_with("## [Unreleased] - "));
+        assert!(
+            markdown.contains("### Features\n\n- Login\n  - Given a user\n  - When they sign in\n")
+        );
+        assert!(markdown.contains("### Bug Fixes\n\n- cart total rounding\n"));
+        assert!(!markdown.contains("Search"));
+        assert!(markdown.find("### Features") < markdown.find("### Bug Fixes"));
+    }
+}
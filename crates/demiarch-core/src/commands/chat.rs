@@ -1,7 +1,17 @@
 //! Chat commands for conversational discovery
 //!
 //! Provides conversation management and message threading for demiarch projects.
-
+//!
+//! Messages form a tree: each one points at the message it follows. A branch
+//! is a named path through that tree, so a conversation can be forked at any
+//! message and each branch explored, switched to and compared on its own.
+
+use crate::agents::patch::unified_diff;
+use crate::context::{
+    estimate_message_tokens, ContextWindow, HistoryFit, TokenAllocation, WindowStats,
+};
+use crate::llm::Message;
+use crate::pagination::{Page, PageRequest};
 use crate::storage::Database;
 use crate::Result;
 use chrono::{DateTime, Utc};
@@ -9,7 +19,9 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 // Re-export repositories from infrastructure for backwards compatibility
-pub use crate::infrastructure::chat::{ConversationRepository, MessageRepository};
+pub use crate::infrastructure::chat::{
+    BranchRepository, ConversationRepository, MessageRepository,
+};
 
 /// Message role in a conversation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -57,6 +69,12 @@ pub struct ChatMessage {
     pub model: Option<String>,
     /// Number of tokens used (optional)
     pub tokens_used: Option<i32>,
+    /// Message this one follows; messages with a shared parent are branches
+    #[serde(default)]
+    pub parent_message_id: Option<String>,
+    /// Original message this one is a retry or edit of
+    #[serde(default)]
+    pub variant_of: Option<String>,
     /// When the message was created
     pub created_at: DateTime<Utc>,
 }
@@ -71,6 +89,8 @@ impl ChatMessage {
             content: content.into(),
             model: None,
             tokens_used: None,
+            parent_message_id: None,
+            variant_of: None,
             created_at: Utc::now(),
         }
     }
@@ -84,6 +104,8 @@ impl ChatMessage {
             content: content.into(),
             model: None,
             tokens_used: None,
+            parent_message_id: None,
+            variant_of: None,
             created_at: Utc::now(),
         }
     }
@@ -97,6 +119,8 @@ impl ChatMessage {
             content: content.into(),
             model: None,
             tokens_used: None,
+            parent_message_id: None,
+            variant_of: None,
             created_at: Utc::now(),
         }
     }
@@ -112,6 +136,12 @@ impl ChatMessage {
         self.tokens_used = Some(tokens);
         self
     }
+
+    /// Set the message this one follows
+    pub fn with_parent(mut self, parent_message_id: impl Into<String>) -> Self {
+        self.parent_message_id = Some(parent_message_id.into());
+        self
+    }
 }
 
 /// A conversation thread containing multiple messages
@@ -123,6 +153,9 @@ pub struct Conversation {
     pub project_id: String,
     /// Optional title for the conversation
     pub title: Option<String>,
+    /// Branch new messages are added to
+    #[serde(default)]
+    pub active_branch_id: Option<String>,
     /// When the conversation was created
     pub created_at: DateTime<Utc>,
     /// When the conversation was last updated
@@ -137,6 +170,7 @@ impl Conversation {
             id: Uuid::new_v4().to_string(),
             project_id: project_id.into(),
             title: None,
+            active_branch_id: None,
             created_at: now,
             updated_at: now,
         }
@@ -149,6 +183,55 @@ impl Conversation {
     }
 }
 
+/// Name of the branch a conversation starts on
+pub const MAIN_BRANCH: &str = "main";
+
+/// A named path through a conversation's message tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationBranch {
+    /// Unique branch identifier
+    pub id: String,
+    /// ID of the conversation this branch belongs to
+    pub conversation_id: String,
+    /// Branch name, unique within the conversation
+    pub name: String,
+    /// Branch this one was forked from
+    pub parent_branch_id: Option<String>,
+    /// Last message shared with the parent branch
+    pub fork_message_id: Option<String>,
+    /// Newest message on the branch
+    pub head_message_id: Option<String>,
+    /// When the branch was created
+    pub created_at: DateTime<Utc>,
+    /// When a message was last added to the branch
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ConversationBranch {
+    /// Create a new branch of a conversation
+    pub fn new(conversation_id: impl Into<String>, name: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            conversation_id: conversation_id.into(),
+            name: name.into(),
+            parent_branch_id: None,
+            fork_message_id: None,
+            head_message_id: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Fork from `parent` after `message_id`
+    pub fn forked_from(mut self, parent: &ConversationBranch, message_id: Option<String>) -> Self {
+        self.parent_branch_id = Some(parent.id.clone());
+        self.head_message_id = message_id.clone();
+        self.fork_message_id = message_id;
+        self
+    }
+}
+
 // ============================================================================
 // High-level chat API functions
 // ============================================================================
@@ -207,6 +290,8 @@ pub async fn delete_conversation(db: &Database, id: &str) -> Result<()> {
 }
 
 /// Send a message in a conversation
+///
+/// The message is added to the conversation's active branch.
 pub async fn send_message(
     db: &Database,
     conversation_id: &str,
@@ -222,14 +307,30 @@ pub async fn send_message(
         )));
     }
 
-    let message = match role {
+    let mut message = match role {
         MessageRole::User => ChatMessage::user(conversation_id, content),
         MessageRole::Assistant => ChatMessage::assistant(conversation_id, content),
         MessageRole::System => ChatMessage::system(conversation_id, content),
     };
 
+    let branch = active_branch(db, conversation_id).await?;
     let msg_repo = MessageRepository::new(db);
+    // A root branch without a head has lost it to a restore; continue from
+    // the newest message
+    let parent = match branch.head_message_id {
+        Some(head) => Some(head),
+        None if branch.parent_branch_id.is_none() => {
+            msg_repo.latest(conversation_id).await?.map(|m| m.id)
+        }
+        None => None,
+    };
+    if let Some(parent) = parent {
+        message = message.with_parent(parent);
+    }
     msg_repo.create(&message).await?;
+    BranchRepository::new(db)
+        .set_head(&branch.id, &message.id)
+        .await?;
 
     // Update conversation's updated_at timestamp
     conv_repo.touch(conversation_id).await?;
@@ -238,6 +339,9 @@ pub async fn send_message(
 }
 
 /// Get chat history for a conversation
+///
+/// Follows the active branch; conversations that were never branched are
+/// returned in creation order.
 pub async fn get_history(
     db: &Database,
     conversation_id: &str,
@@ -245,6 +349,17 @@ pub async fn get_history(
 ) -> Result<Vec<ChatMessage>> {
     let msg_repo = MessageRepository::new(db);
 
+    let conversation = ConversationRepository::new(db).get(conversation_id).await?;
+    if let Some(branch_id) = conversation.and_then(|c| c.active_branch_id) {
+        if let Some(head) = BranchRepository::new(db)
+            .get(&branch_id)
+            .await?
+            .and_then(|b| b.head_message_id)
+        {
+            return msg_repo.list_path(&head, limit).await;
+        }
+    }
+
     if let Some(limit) = limit {
         msg_repo.list_recent(conversation_id, limit).await
     } else {
@@ -252,6 +367,31 @@ pub async fn get_history(
     }
 }
 
+/// Get one page of chat history, for loading long conversations incrementally
+///
+/// Follows the active branch like [`get_history`]. Offset 0 is the newest
+/// page; larger offsets reach further back.
+pub async fn get_history_page(
+    db: &Database,
+    conversation_id: &str,
+    page: PageRequest,
+) -> Result<Page<ChatMessage>> {
+    let msg_repo = MessageRepository::new(db);
+
+    let conversation = ConversationRepository::new(db).get(conversation_id).await?;
+    if let Some(branch_id) = conversation.and_then(|c| c.active_branch_id) {
+        if let Some(head) = BranchRepository::new(db)
+            .get(&branch_id)
+            .await?
+            .and_then(|b| b.head_message_id)
+        {
+            return msg_repo.list_path_page(&head, page).await;
+        }
+    }
+
+    msg_repo.list_recent_page(conversation_id, page).await
+}
+
 /// Get all messages in a conversation with pagination
 pub async fn get_history_paginated(
     db: &Database,
@@ -271,6 +411,488 @@ pub async fn count_messages(db: &Database, conversation_id: &str) -> Result<i64>
     msg_repo.count_by_conversation(conversation_id).await
 }
 
+// ============================================================================
+// Branching
+// ============================================================================
+
+/// Get the branch new messages are added to
+///
+/// Conversations from before branching, or whose branches were not synced,
+/// get a `main` branch ending at their newest message.
+pub async fn active_branch(db: &Database, conversation_id: &str) -> Result<ConversationBranch> {
+    let conv_repo = ConversationRepository::new(db);
+    let conversation = conv_repo.get(conversation_id).await?.ok_or_else(|| {
+        crate::Error::NotFound(format!("Conversation not found: {}", conversation_id))
+    })?;
+
+    let branch_repo = BranchRepository::new(db);
+    if let Some(ref id) = conversation.active_branch_id {
+        if let Some(branch) = branch_repo.get(id).await? {
+            return Ok(branch);
+        }
+    }
+
+    let branches = branch_repo.list_by_conversation(conversation_id).await?;
+    let branch = match branches.into_iter().find(|b| b.name == MAIN_BRANCH) {
+        Some(branch) => branch,
+        None => {
+            let mut branch = ConversationBranch::new(conversation_id, MAIN_BRANCH);
+            branch.head_message_id = MessageRepository::new(db)
+                .latest(conversation_id)
+                .await?
+                .map(|m| m.id);
+            branch_repo.create(&branch).await?;
+            branch
+        }
+    };
+    conv_repo
+        .set_active_branch(conversation_id, &branch.id)
+        .await?;
+    Ok(branch)
+}
+
+/// List a conversation's branches, oldest first
+pub async fn list_branches(
+    db: &Database,
+    conversation_id: &str,
+) -> Result<Vec<ConversationBranch>> {
+    active_branch(db, conversation_id).await?;
+    BranchRepository::new(db)
+        .list_by_conversation(conversation_id)
+        .await
+}
+
+/// Find a branch by name or ID prefix
+pub async fn resolve_branch(
+    db: &Database,
+    conversation_id: &str,
+    name_or_id: &str,
+) -> Result<ConversationBranch> {
+    let branches = list_branches(db, conversation_id).await?;
+    if let Some(branch) = branches.iter().find(|b| b.name == name_or_id) {
+        return Ok(branch.clone());
+    }
+    let mut matches = branches
+        .into_iter()
+        .filter(|b| b.id.starts_with(name_or_id));
+    match (matches.next(), matches.next()) {
+        (Some(branch), None) => Ok(branch),
+        (Some(_), Some(_)) => Err(crate::Error::InvalidInput(format!(
+            "Branch ID prefix '{}' is ambiguous",
+            name_or_id
+        ))),
+        (None, _) => Err(crate::Error::NotFound(format!(
+            "Branch not found: {}",
+            name_or_id
+        ))),
+    }
+}
+
+/// Messages on a branch, oldest first
+pub async fn branch_history(
+    db: &Database,
+    branch: &ConversationBranch,
+) -> Result<Vec<ChatMessage>> {
+    match branch.head_message_id {
+        Some(ref head) => MessageRepository::new(db).list_path(head, None).await,
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Fork the conversation after `at_message_id` (default: the newest message
+/// of the active branch) and switch to the new branch
+///
+/// Without a name the branch is called `branch-<n>`.
+pub async fn create_branch(
+    db: &Database,
+    conversation_id: &str,
+    name: Option<&str>,
+    at_message_id: Option<&str>,
+) -> Result<ConversationBranch> {
+    let current = active_branch(db, conversation_id).await?;
+    let branches = BranchRepository::new(db)
+        .list_by_conversation(conversation_id)
+        .await?;
+
+    let name = match name.map(str::trim) {
+        Some("") => return Err(crate::Error::InvalidInput("Branch name is empty".into())),
+        Some(name) => name.to_string(),
+        None => (branches.len()..)
+            .map(|n| format!("branch-{}", n))
+            .find(|n| !branches.iter().any(|b| &b.name == n))
+            .unwrap_or_default(),
+    };
+    if branches.iter().any(|b| b.name == name) {
+        return Err(crate::Error::InvalidInput(format!(
+            "Branch '{}' already exists",
+            name
+        )));
+    }
+
+    let fork = match at_message_id {
+        Some(id) => {
+            let message = MessageRepository::new(db)
+                .get(id)
+                .await?
+                .filter(|m| m.conversation_id == conversation_id)
+                .ok_or_else(|| crate::Error::NotFound(format!("Message not found: {}", id)))?;
+            Some(message.id)
+        }
+        None => current.head_message_id.clone(),
+    };
+
+    // The parent is the branch the fork point is on, preferring the active one
+    let mut parent = current;
+    if let Some(ref fork) = fork {
+        for branch in std::iter::once(parent.clone()).chain(branches) {
+            if branch_history(db, &branch)
+                .await?
+                .iter()
+                .any(|m| &m.id == fork)
+            {
+                parent = branch;
+                break;
+            }
+        }
+    }
+
+    let branch = ConversationBranch::new(conversation_id, name).forked_from(&parent, fork);
+    BranchRepository::new(db).create(&branch).await?;
+    ConversationRepository::new(db)
+        .set_active_branch(conversation_id, &branch.id)
+        .await?;
+    Ok(branch)
+}
+
+/// Make a branch (name or ID prefix) the one new messages are added to
+pub async fn switch_branch(
+    db: &Database,
+    conversation_id: &str,
+    name_or_id: &str,
+) -> Result<ConversationBranch> {
+    let branch = resolve_branch(db, conversation_id, name_or_id).await?;
+    ConversationRepository::new(db)
+        .set_active_branch(conversation_id, &branch.id)
+        .await?;
+    Ok(branch)
+}
+
+/// A branch's place in the conversation's branch tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchNode {
+    pub branch: ConversationBranch,
+    /// Forks between the root branch and this one
+    pub depth: usize,
+    /// Messages on the branch, including those shared with its parent
+    pub message_count: usize,
+    pub active: bool,
+}
+
+/// A conversation's branches in tree order: each branch followed by the
+/// branches forked from it
+pub async fn branch_tree(db: &Database, conversation_id: &str) -> Result<Vec<BranchNode>> {
+    let active = active_branch(db, conversation_id).await?;
+    let branches = BranchRepository::new(db)
+        .list_by_conversation(conversation_id)
+        .await?;
+
+    let is_root = |b: &ConversationBranch| {
+        b.parent_branch_id
+            .as_ref()
+            .is_none_or(|p| !branches.iter().any(|o| &o.id == p))
+    };
+    let mut stack: Vec<(&ConversationBranch, usize)> = branches
+        .iter()
+        .rev()
+        .filter(|b| is_root(b))
+        .map(|b| (b, 0))
+        .collect();
+
+    let mut nodes = Vec::with_capacity(branches.len());
+    while let Some((branch, depth)) = stack.pop() {
+        nodes.push(BranchNode {
+            branch: branch.clone(),
+            depth,
+            message_count: branch_history(db, branch).await?.len(),
+            active: branch.id == active.id,
+        });
+        stack.extend(
+            branches
+                .iter()
+                .rev()
+                .filter(|b| b.parent_branch_id.as_ref() == Some(&branch.id))
+                .map(|b| (b, depth + 1)),
+        );
+    }
+    Ok(nodes)
+}
+
+/// How two branches differ after the point where they split
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchComparison {
+    pub left: ConversationBranch,
+    pub right: ConversationBranch,
+    /// Messages both branches share
+    pub shared: usize,
+    pub left_only: Vec<ChatMessage>,
+    pub right_only: Vec<ChatMessage>,
+    /// Unified diff from the left branch's latest reply to the right's;
+    /// empty unless both replied after the split and the replies differ
+    pub reply_diff: String,
+}
+
+impl BranchComparison {
+    /// Latest assistant reply on the left branch since the split
+    pub fn left_reply(&self) -> Option<&ChatMessage> {
+        latest_reply(&self.left_only)
+    }
+
+    /// Latest assistant reply on the right branch since the split
+    pub fn right_reply(&self) -> Option<&ChatMessage> {
+        latest_reply(&self.right_only)
+    }
+}
+
+fn with_newline(text: &str) -> String {
+    format!("{}\n", text.trim_end())
+}
+
+fn latest_reply(messages: &[ChatMessage]) -> Option<&ChatMessage> {
+    messages
+        .iter()
+        .rev()
+        .find(|m| m.role == MessageRole::Assistant)
+}
+
+/// Compare two branches (names or ID prefixes) of a conversation
+pub async fn compare_branches(
+    db: &Database,
+    conversation_id: &str,
+    left: &str,
+    right: &str,
+) -> Result<BranchComparison> {
+    let left = resolve_branch(db, conversation_id, left).await?;
+    let right = resolve_branch(db, conversation_id, right).await?;
+    let mut left_history = branch_history(db, &left).await?;
+    let mut right_history = branch_history(db, &right).await?;
+
+    let shared = left_history
+        .iter()
+        .zip(&right_history)
+        .take_while(|(l, r)| l.id == r.id)
+        .count();
+    let left_only = left_history.split_off(shared);
+    let right_only = right_history.split_off(shared);
+    let reply_diff = match (latest_reply(&left_only), latest_reply(&right_only)) {
+        (Some(l), Some(r)) => unified_diff(
+            std::path::Path::new("reply"),
+            Some(&with_newline(&l.content)),
+            &with_newline(&r.content),
+        ),
+        _ => String::new(),
+    };
+    Ok(BranchComparison {
+        left,
+        right,
+        shared,
+        left_only,
+        right_only,
+        reply_diff,
+    })
+}
+
+// ============================================================================
+// Variants
+// ============================================================================
+
+/// Where a message sits among its variants, for "variant 2/3" navigation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariantPosition {
+    /// 1-based position, oldest first
+    pub index: usize,
+    pub count: usize,
+}
+
+/// A message and its alternatives: the original and each retry or edit,
+/// oldest first
+pub async fn message_variants(db: &Database, message: &ChatMessage) -> Result<Vec<ChatMessage>> {
+    let original = message.variant_of.as_deref().unwrap_or(&message.id);
+    MessageRepository::new(db).list_variants(original).await
+}
+
+/// Position of a message among its variants
+pub async fn variant_position(db: &Database, message: &ChatMessage) -> Result<VariantPosition> {
+    let variants = message_variants(db, message).await?;
+    let index = variants
+        .iter()
+        .position(|m| m.id == message.id)
+        .map_or(1, |i| i + 1);
+    Ok(VariantPosition {
+        index,
+        count: variants.len().max(1),
+    })
+}
+
+/// Add an alternative to `original` and continue the active branch from it
+///
+/// Used by retry (a new reply to the same message) and edit (a new version
+/// of a user message). The earlier variants stay in the conversation and can
+/// be selected again with [`select_variant`].
+pub async fn add_variant(
+    db: &Database,
+    original: &ChatMessage,
+    content: &str,
+    model: Option<&str>,
+) -> Result<ChatMessage> {
+    let branch = active_branch(db, &original.conversation_id).await?;
+    let mut variant = ChatMessage {
+        id: Uuid::new_v4().to_string(),
+        conversation_id: original.conversation_id.clone(),
+        role: original.role,
+        content: content.to_string(),
+        model: model.map(str::to_string),
+        tokens_used: None,
+        parent_message_id: original.parent_message_id.clone(),
+        variant_of: Some(
+            original
+                .variant_of
+                .clone()
+                .unwrap_or_else(|| original.id.clone()),
+        ),
+        created_at: Utc::now(),
+    };
+    if model.is_none() && original.role == MessageRole::Assistant {
+        variant.model = original.model.clone();
+    }
+
+    MessageRepository::new(db).create(&variant).await?;
+    BranchRepository::new(db)
+        .set_head(&branch.id, &variant.id)
+        .await?;
+    ConversationRepository::new(db)
+        .touch(&original.conversation_id)
+        .await?;
+    Ok(variant)
+}
+
+/// Switch the active branch to another variant of a message
+///
+/// The branch continues from the variant's most recent follow-up, so
+/// selecting an earlier edit brings back the replies it received. Returns
+/// the branch's new newest message.
+pub async fn select_variant(
+    db: &Database,
+    conversation_id: &str,
+    message_id: &str,
+) -> Result<ChatMessage> {
+    let msg_repo = MessageRepository::new(db);
+    let mut head = msg_repo
+        .get(message_id)
+        .await?
+        .filter(|m| m.conversation_id == conversation_id)
+        .ok_or_else(|| crate::Error::NotFound(format!("Message not found: {}", message_id)))?;
+    while let Some(child) = msg_repo.list_children(&head.id).await?.pop() {
+        head = child;
+    }
+
+    let branch = active_branch(db, conversation_id).await?;
+    BranchRepository::new(db)
+        .set_head(&branch.id, &head.id)
+        .await?;
+    Ok(head)
+}
+
+// ============================================================================
+// Prompt budgeting
+// ============================================================================
+
+/// Tokens of conversation history sent with each chat turn
+pub const CHAT_HISTORY_TOKENS: usize = 12_000;
+
+/// Most recent messages considered when building a chat prompt
+pub const CHAT_HISTORY_LIMIT: usize = 200;
+
+/// Token allocation for a chat turn with `output_tokens` reserved for the reply
+pub fn chat_allocation(output_tokens: usize) -> TokenAllocation {
+    TokenAllocation::new(1024, CHAT_HISTORY_TOKENS, 0, output_tokens)
+}
+
+/// Messages to send for a chat turn, and how the budget was spent
+#[derive(Debug, Clone)]
+pub struct ChatPrompt {
+    pub messages: Vec<Message>,
+    pub usage: ChatContextUsage,
+}
+
+/// Token usage of a chat prompt, shown by `/context`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ChatContextUsage {
+    pub system_tokens: usize,
+    pub history_tokens: usize,
+    /// Tokens available for system prompt and history
+    pub budget_tokens: usize,
+    pub history: HistoryFit,
+}
+
+impl ChatContextUsage {
+    /// Total prompt tokens
+    pub fn total_tokens(&self) -> usize {
+        self.system_tokens + self.history_tokens
+    }
+
+    /// Share of the budget in use, 0.0 to 1.0
+    pub fn ratio(&self) -> f32 {
+        if self.budget_tokens == 0 {
+            return 0.0;
+        }
+        self.total_tokens() as f32 / self.budget_tokens as f32
+    }
+
+    /// Usage of the chat context window, for the indicator after each turn
+    pub fn stats(&self) -> WindowStats {
+        WindowStats::new(self.total_tokens(), self.budget_tokens)
+    }
+}
+
+/// Build the prompt for a chat turn
+///
+/// History is routed through a [`ContextWindow`], so the newest messages are
+/// sent verbatim and older ones are summarized once the allocation is full.
+pub fn build_prompt(
+    system_prompt: &str,
+    history: &[ChatMessage],
+    allocation: TokenAllocation,
+) -> ChatPrompt {
+    let system = Message::system(system_prompt);
+    let system_tokens = estimate_message_tokens(&system);
+
+    let mut window = ContextWindow::new(allocation);
+    window.add_system_message(system);
+    let history: Vec<Message> = history.iter().map(ChatMessage::to_llm_message).collect();
+    let fit = window.add_history(&history);
+
+    ChatPrompt {
+        usage: ChatContextUsage {
+            system_tokens,
+            history_tokens: window.context_tokens(),
+            budget_tokens: allocation.system_tokens + allocation.context_tokens,
+            history: fit,
+        },
+        messages: window.messages(),
+    }
+}
+
+impl ChatMessage {
+    /// Convert to a message for the LLM
+    pub fn to_llm_message(&self) -> Message {
+        match self.role {
+            MessageRole::User => Message::user(&self.content),
+            MessageRole::Assistant => Message::assistant(&self.content),
+            MessageRole::System => Message::system(&self.content),
+        }
+    }
+}
+
 // ============================================================================
 // Legacy API (for backwards compatibility)
 // ============================================================================
@@ -685,4 +1307,195 @@ mod tests {
         assert_eq!(MessageRole::parse("system"), Some(MessageRole::System));
         assert_eq!(MessageRole::parse("invalid"), None);
     }
+
+    #[test]
+    fn test_build_prompt_sends_short_history_verbatim() {
+        let history = vec![
+            ChatMessage::user("c", "Add a login page"),
+            ChatMessage::assistant("c", "Sure, with email and password?"),
+        ];
+        let prompt = build_prompt("You are Demiarch.", &history, chat_allocation(4096));
+
+        assert_eq!(prompt.messages.len(), 3);
+        assert_eq!(prompt.messages[2].content, "Sure, with email and password?");
+        assert_eq!(prompt.usage.history.kept, 2);
+        assert_eq!(prompt.usage.history.summarized, 0);
+    }
+
+    #[test]
+    fn test_build_prompt_summarizes_older_turns() {
+        let history: Vec<ChatMessage> = (0..40)
+            .map(|i| ChatMessage::user("c", format!("Message {}. {}", i, "word ".repeat(100))))
+            .collect();
+        let allocation = TokenAllocation::new(1024, 1000, 0, 4096);
+        let prompt = build_prompt("You are Demiarch.", &history, allocation);
+
+        let fit = prompt.usage.history;
+        assert!(fit.summarized > 0);
+        assert_eq!(fit.kept + fit.summarized, 40);
+        assert!(prompt.messages[1].content.starts_with("Summary of"));
+        assert!(prompt
+            .messages
+            .last()
+            .unwrap()
+            .content
+            .starts_with("Message 39"));
+        assert!(prompt.usage.history_tokens <= 1000);
+    }
+
+    async fn branching_setup() -> (Database, Conversation) {
+        let db = Database::in_memory()
+            .await
+            .expect("Failed to create database");
+        sqlx::query("INSERT INTO projects (id, name) VALUES (?, ?)")
+            .bind("test-project-id")
+            .bind("Test Project")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let conversation = create_conversation(&db, "test-project-id", None)
+            .await
+            .unwrap();
+        (db, conversation)
+    }
+
+    fn contents(messages: &[ChatMessage]) -> Vec<&str> {
+        messages.iter().map(|m| m.content.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_branch_and_switch() {
+        let (db, conversation) = branching_setup().await;
+        let question = send_message(&db, &conversation.id, MessageRole::User, "Which database?")
+            .await
+            .unwrap();
+        send_message(
+            &db,
+            &conversation.id,
+            MessageRole::Assistant,
+            "Use Postgres",
+        )
+        .await
+        .unwrap();
+
+        let branch = create_branch(&db, &conversation.id, Some("sqlite"), Some(&question.id))
+            .await
+            .unwrap();
+        assert_eq!(
+            branch.fork_message_id.as_deref(),
+            Some(question.id.as_str())
+        );
+        let reply = send_message(&db, &conversation.id, MessageRole::Assistant, "Use SQLite")
+            .await
+            .unwrap();
+        assert_eq!(
+            reply.parent_message_id.as_deref(),
+            Some(question.id.as_str())
+        );
+
+        let history = get_history(&db, &conversation.id, None).await.unwrap();
+        assert_eq!(contents(&history), vec!["Which database?", "Use SQLite"]);
+        let recent = get_history(&db, &conversation.id, Some(1)).await.unwrap();
+        assert_eq!(contents(&recent), vec!["Use SQLite"]);
+
+        switch_branch(&db, &conversation.id, MAIN_BRANCH)
+            .await
+            .unwrap();
+        let history = get_history(&db, &conversation.id, None).await.unwrap();
+        assert_eq!(contents(&history), vec!["Which database?", "Use Postgres"]);
+        assert_eq!(count_messages(&db, &conversation.id).await.unwrap(), 3);
+
+        assert!(create_branch(&db, &conversation.id, Some("sqlite"), None)
+            .await
+            .is_err());
+        assert!(switch_branch(&db, &conversation.id, "missing")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_branch_tree_and_compare() {
+        let (db, conversation) = branching_setup().await;
+        send_message(&db, &conversation.id, MessageRole::User, "Plan a blog")
+            .await
+            .unwrap();
+        create_branch(&db, &conversation.id, None, None)
+            .await
+            .unwrap();
+        send_message(&db, &conversation.id, MessageRole::Assistant, "Static site")
+            .await
+            .unwrap();
+        create_branch(&db, &conversation.id, Some("nested"), None)
+            .await
+            .unwrap();
+        switch_branch(&db, &conversation.id, MAIN_BRANCH)
+            .await
+            .unwrap();
+        send_message(&db, &conversation.id, MessageRole::Assistant, "Rails app")
+            .await
+            .unwrap();
+
+        let tree = branch_tree(&db, &conversation.id).await.unwrap();
+        let names: Vec<(&str, usize)> = tree
+            .iter()
+            .map(|n| (n.branch.name.as_str(), n.depth))
+            .collect();
+        assert_eq!(names, vec![("main", 0), ("branch-1", 1), ("nested", 2)]);
+        assert!(tree[0].active);
+        assert_eq!(tree[2].message_count, 2);
+
+        let comparison = compare_branches(&db, &conversation.id, "main", "branch-1")
+            .await
+            .unwrap();
+        assert_eq!(comparison.shared, 1);
+        assert_eq!(comparison.left_reply().unwrap().content, "Rails app");
+        assert_eq!(comparison.right_reply().unwrap().content, "Static site");
+        assert!(comparison.reply_diff.contains("-Rails app\n+Static site"));
+    }
+
+    #[tokio::test]
+    async fn test_retry_and_edit_keep_variants() {
+        let (db, conversation) = branching_setup().await;
+        let question = send_message(&db, &conversation.id, MessageRole::User, "Name the app")
+            .await
+            .unwrap();
+        let first = send_message(&db, &conversation.id, MessageRole::Assistant, "Shopr")
+            .await
+            .unwrap();
+
+        let retry = add_variant(&db, &first, "Cartly", Some("other-model"))
+            .await
+            .unwrap();
+        assert_eq!(retry.variant_of.as_deref(), Some(first.id.as_str()));
+        assert_eq!(retry.model.as_deref(), Some("other-model"));
+        let history = get_history(&db, &conversation.id, None).await.unwrap();
+        assert_eq!(contents(&history), vec!["Name the app", "Cartly"]);
+        assert_eq!(
+            variant_position(&db, &retry).await.unwrap(),
+            VariantPosition { index: 2, count: 2 }
+        );
+
+        let edited = add_variant(&db, &question, "Name the shop", None)
+            .await
+            .unwrap();
+        send_message(
+            &db,
+            &conversation.id,
+            MessageRole::Assistant,
+            "Corner Store",
+        )
+        .await
+        .unwrap();
+        let history = get_history(&db, &conversation.id, None).await.unwrap();
+        assert_eq!(contents(&history), vec!["Name the shop", "Corner Store"]);
+        assert_eq!(message_variants(&db, &edited).await.unwrap().len(), 2);
+
+        // Going back to the original question brings back its latest reply
+        let head = select_variant(&db, &conversation.id, &question.id)
+            .await
+            .unwrap();
+        assert_eq!(head.id, retry.id);
+        let history = get_history(&db, &conversation.id, None).await.unwrap();
+        assert_eq!(contents(&history), vec!["Name the app", "Cartly"]);
+    }
 }
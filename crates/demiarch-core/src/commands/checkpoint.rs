@@ -2,17 +2,22 @@
 //!
 //! Provides CLI commands for listing, viewing, and managing checkpoints.
 
+use std::path::Path;
+
+use crate::commands::project::ProjectRepository;
+use crate::domain::recovery::archive::{self, ArchiveManifest};
 use crate::domain::recovery::{
-    CheckpointConfig, CheckpointInfo, CheckpointManager, CheckpointSigner, CheckpointStats,
-    RestoreResult,
+    Checkpoint, CheckpointConfig, CheckpointInfo, CheckpointManager, CheckpointRepository,
+    CheckpointSigner, CheckpointStats, RestoreResult,
 };
 use crate::error::Result;
+use crate::progress::Progress;
 use crate::storage::Database;
 use uuid::Uuid;
 
 /// List all checkpoints for a project
 pub async fn list_checkpoints(project_id: Uuid) -> Result<Vec<CheckpointInfo>> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let signer = get_or_create_signer()?;
@@ -23,7 +28,7 @@ pub async fn list_checkpoints(project_id: Uuid) -> Result<Vec<CheckpointInfo>> {
 
 /// Get checkpoint statistics for a project
 pub async fn get_checkpoint_stats(project_id: Uuid) -> Result<CheckpointStats> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let signer = get_or_create_signer()?;
@@ -34,7 +39,7 @@ pub async fn get_checkpoint_stats(project_id: Uuid) -> Result<CheckpointStats> {
 
 /// Delete a specific checkpoint
 pub async fn delete_checkpoint(checkpoint_id: Uuid) -> Result<bool> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let signer = get_or_create_signer()?;
@@ -45,7 +50,7 @@ pub async fn delete_checkpoint(checkpoint_id: Uuid) -> Result<bool> {
 
 /// Delete all checkpoints for a project
 pub async fn delete_all_checkpoints(project_id: Uuid) -> Result<u64> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let signer = get_or_create_signer()?;
@@ -56,7 +61,7 @@ pub async fn delete_all_checkpoints(project_id: Uuid) -> Result<u64> {
 
 /// Verify a checkpoint's signature integrity
 pub async fn verify_checkpoint(checkpoint_id: Uuid) -> Result<bool> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let signer = get_or_create_signer()?;
@@ -81,9 +86,19 @@ pub async fn create_checkpoint(
     description: String,
     feature_id: Option<Uuid>,
 ) -> Result<CheckpointInfo> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
+    create_checkpoint_with_db(&db, project_id, description, feature_id).await
+}
+
+/// Create a checkpoint with database
+pub async fn create_checkpoint_with_db(
+    db: &Database,
+    project_id: Uuid,
+    description: String,
+    feature_id: Option<Uuid>,
+) -> Result<CheckpointInfo> {
     let signer = get_or_create_signer()?;
     let manager = CheckpointManager::new(db.pool().clone(), signer);
 
@@ -102,15 +117,103 @@ pub async fn create_checkpoint(
 /// 3. Restore database state (phases, features, messages)
 /// 4. Restore any tracked generated code files
 ///
-/// Returns a RestoreResult containing details about what was restored.
-pub async fn restore_checkpoint(checkpoint_id: Uuid) -> Result<RestoreResult> {
-    let db = Database::default()
+/// The safety backup is skipped when `safety_backup` is false, as set by
+/// `checkpoint.triggers.before_restore`. Each step is reported to
+/// `progress`. Returns a RestoreResult containing details about what was
+/// restored.
+pub async fn restore_checkpoint(
+    checkpoint_id: Uuid,
+    safety_backup: bool,
+    progress: &Progress,
+) -> Result<RestoreResult> {
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let signer = get_or_create_signer()?;
-    let manager = CheckpointManager::new(db.pool().clone(), signer);
+    let manager = CheckpointManager::with_config(
+        db.pool().clone(),
+        signer,
+        CheckpointConfig {
+            safety_backup,
+            ..CheckpointConfig::default()
+        },
+    );
+
+    manager
+        .restore_checkpoint_with_progress(checkpoint_id, progress)
+        .await
+}
+
+/// Export a checkpoint to a `.tar.zst` archive
+///
+/// The archive carries the snapshot, its generated file blobs and a
+/// signature that [`import_checkpoint`] verifies.
+pub async fn export_checkpoint(checkpoint_id: Uuid, output: &Path) -> Result<ArchiveManifest> {
+    let db = Database::shared()
+        .await
+        .map_err(|e| crate::error::Error::Other(e.to_string()))?;
+    export_checkpoint_with_db(&db, checkpoint_id, output).await
+}
+
+/// Export a checkpoint with database
+pub async fn export_checkpoint_with_db(
+    db: &Database,
+    checkpoint_id: Uuid,
+    output: &Path,
+) -> Result<ArchiveManifest> {
+    let checkpoint = CheckpointRepository::new(db.pool().clone())
+        .get(checkpoint_id)
+        .await?
+        .ok_or_else(|| {
+            crate::error::Error::NotFound(format!("Checkpoint {} not found", checkpoint_id))
+        })?;
+    let signer = get_or_create_signer()?;
+    Ok(archive::write_archive(&checkpoint, &signer, output)?)
+}
+
+/// Import a checkpoint archive into a project
+///
+/// The archive's signature and file blobs are verified first. The imported
+/// checkpoint gets a new ID and is re-signed locally, so it can be verified
+/// and restored like any other.
+pub async fn import_checkpoint(path: &Path, project_id: Uuid) -> Result<CheckpointInfo> {
+    let db = Database::shared()
+        .await
+        .map_err(|e| crate::error::Error::Other(e.to_string()))?;
+    import_checkpoint_with_db(&db, path, project_id).await
+}
+
+/// Import a checkpoint archive with database
+pub async fn import_checkpoint_with_db(
+    db: &Database,
+    path: &Path,
+    project_id: Uuid,
+) -> Result<CheckpointInfo> {
+    let archive = archive::read_archive(path)?;
+    if ProjectRepository::new(db)
+        .get(&project_id.to_string())
+        .await?
+        .is_none()
+    {
+        return Err(crate::error::Error::ProjectNotFound(project_id.to_string()));
+    }
 
-    manager.restore_checkpoint(checkpoint_id).await
+    let signer = get_or_create_signer()?;
+    let snapshot_bytes = serde_json::to_vec(&archive.snapshot_data).map_err(|e| {
+        crate::error::Error::Other(format!("Failed to serialize for signing: {}", e))
+    })?;
+    let checkpoint = Checkpoint::new(
+        project_id,
+        None,
+        format!("{} (imported)", archive.manifest.description),
+        archive.snapshot_data,
+        signer.sign(&snapshot_bytes),
+    );
+    CheckpointRepository::new(db.pool().clone())
+        .save(&checkpoint)
+        .await?;
+
+    Ok(CheckpointInfo::from(&checkpoint))
 }
 
 /// Get or create the signing key
@@ -144,4 +247,38 @@ mod tests {
         assert_eq!(config.retention_days, 30);
         assert_eq!(config.max_per_project, 50);
     }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        use crate::commands::project::Project;
+
+        let db = Database::in_memory().await.unwrap();
+        let projects = ProjectRepository::new(&db);
+        let source = Project::new("source", "rust", "");
+        let target = Project::new("target", "rust", "");
+        projects.create(&source).await.unwrap();
+        projects.create(&target).await.unwrap();
+
+        let source_id = Uuid::parse_str(&source.id).unwrap();
+        let target_id = Uuid::parse_str(&target.id).unwrap();
+        let original = create_checkpoint_with_db(&db, source_id, "Before bug".to_string(), None)
+            .await
+            .unwrap();
+
+        let path = std::env::temp_dir().join(format!("cp-{}.tar.zst", Uuid::new_v4()));
+        let manifest = export_checkpoint_with_db(&db, original.id, &path)
+            .await
+            .unwrap();
+        assert_eq!(manifest.checkpoint_id, original.id);
+
+        let imported = import_checkpoint_with_db(&db, &path, target_id).await;
+        let missing = import_checkpoint_with_db(&db, &path, Uuid::new_v4()).await;
+        let _ = std::fs::remove_file(&path);
+
+        let imported = imported.unwrap();
+        assert_ne!(imported.id, original.id);
+        assert_eq!(imported.project_id, target_id);
+        assert_eq!(imported.description, "Before bug (imported)");
+        assert!(missing.is_err());
+    }
 }
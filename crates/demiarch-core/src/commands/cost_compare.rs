@@ -0,0 +1,380 @@
+//! Cost comparison between two periods
+//!
+//! `demiarch costs compare --from 2025-01-01..2025-01-15 --to 2025-01-16..2025-01-31`
+//! puts spend, tokens, LLM calls and cost per completed feature for two
+//! date ranges side by side, so the effect of a routing or config change
+//! shows up as a delta. `--by model` breaks the spend down by model from
+//! `llm_costs`; `--by feature` breaks down generation spend by feature,
+//! counting generations as calls since LLM calls are not tied to features.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// An inclusive range of days, UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Period {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Period {
+    /// Parse `YYYY-MM-DD..YYYY-MM-DD`; a single date is a one-day period
+    pub fn parse(s: &str) -> Result<Self> {
+        let (start, end) = s.split_once("..").unwrap_or((s, s));
+        let date = |d: &str| {
+            NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").map_err(|_| {
+                Error::InvalidInput(format!(
+                    "Invalid period '{}': use YYYY-MM-DD..YYYY-MM-DD",
+                    s
+                ))
+            })
+        };
+        let period = Self {
+            start: date(start)?,
+            end: date(end)?,
+        };
+        if period.end < period.start {
+            return Err(Error::InvalidInput(format!(
+                "Invalid period '{}': the end is before the start",
+                s
+            )));
+        }
+        Ok(period)
+    }
+
+    /// Number of days in the period
+    pub fn days(&self) -> i64 {
+        (self.end - self.start).num_days() + 1
+    }
+
+    /// Half-open timestamp bounds for SQL comparisons
+    fn bounds(&self) -> (String, String) {
+        (
+            format!("{} 00:00:00", self.start),
+            format!("{} 00:00:00", self.end + Duration::days(1)),
+        )
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// What a comparison is broken down by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompareBy {
+    Model,
+    Feature,
+}
+
+impl CompareBy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "model" => Some(Self::Model),
+            "feature" => Some(Self::Feature),
+            _ => None,
+        }
+    }
+}
+
+/// Spend, tokens and calls over a period
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub spend_usd: f64,
+    pub tokens: i64,
+    pub calls: i64,
+}
+
+/// Everything reported for one period
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeriodSummary {
+    #[serde(flatten)]
+    pub usage: UsageTotals,
+    /// Features moved to done during the period
+    pub features_completed: i64,
+    pub cost_per_feature_usd: Option<f64>,
+}
+
+/// One model's or feature's usage in both periods
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakdownRow {
+    /// Model name or feature ID
+    pub key: String,
+    /// Feature title, for feature breakdowns
+    pub label: Option<String>,
+    pub from: UsageTotals,
+    pub to: UsageTotals,
+}
+
+impl BreakdownRow {
+    pub fn spend_delta_usd(&self) -> f64 {
+        self.to.spend_usd - self.from.spend_usd
+    }
+}
+
+/// Two periods compared
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostComparison {
+    pub project_id: Option<String>,
+    pub from_period: Period,
+    pub to_period: Period,
+    pub from: PeriodSummary,
+    pub to: PeriodSummary,
+    pub by: Option<CompareBy>,
+    /// Rows sorted by the largest spend change first
+    pub breakdown: Vec<BreakdownRow>,
+}
+
+impl CostComparison {
+    pub fn spend_delta_usd(&self) -> f64 {
+        self.to.usage.spend_usd - self.from.usage.spend_usd
+    }
+}
+
+/// Relative change from `from` to `to`, in percent; `None` when `from` is 0
+pub fn percent_change(from: f64, to: f64) -> Option<f64> {
+    (from != 0.0).then(|| (to - from) / from * 100.0)
+}
+
+/// Compare spend between two periods, optionally for one project
+pub async fn compare(
+    db: &Database,
+    project_id: Option<&str>,
+    from_period: Period,
+    to_period: Period,
+    by: Option<CompareBy>,
+) -> Result<CostComparison> {
+    let from = summarize(db, project_id, from_period).await?;
+    let to = summarize(db, project_id, to_period).await?;
+
+    let mut rows: BTreeMap<String, BreakdownRow> = BTreeMap::new();
+    if let Some(by) = by {
+        for (period, is_from) in [(from_period, true), (to_period, false)] {
+            for (key, label, usage) in breakdown(db, project_id, period, by).await? {
+                let row = rows.entry(key.clone()).or_insert_with(|| BreakdownRow {
+                    key,
+                    label: None,
+                    from: UsageTotals::default(),
+                    to: UsageTotals::default(),
+                });
+                row.label = row.label.take().or(label);
+                if is_from {
+                    row.from = usage;
+                } else {
+                    row.to = usage;
+                }
+            }
+        }
+    }
+    let mut breakdown: Vec<BreakdownRow> = rows.into_values().collect();
+    breakdown.sort_by(|a, b| {
+        b.spend_delta_usd()
+            .abs()
+            .total_cmp(&a.spend_delta_usd().abs())
+            .then_with(|| a.key.cmp(&b.key))
+    });
+
+    Ok(CostComparison {
+        project_id: project_id.map(str::to_string),
+        from_period,
+        to_period,
+        from,
+        to,
+        by,
+        breakdown,
+    })
+}
+
+async fn summarize(
+    db: &Database,
+    project_id: Option<&str>,
+    period: Period,
+) -> Result<PeriodSummary> {
+    let (start, end) = period.bounds();
+    let row = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS calls,
+               COALESCE(SUM(input_tokens + output_tokens), 0) AS tokens,
+               COALESCE(SUM(input_cost_usd + output_cost_usd), 0.0) AS spend_usd
+        FROM llm_costs
+        WHERE datetime(created_at) >= datetime(?) AND datetime(created_at) < datetime(?)
+          AND (? IS NULL OR project_id = ?)
+        "#,
+    )
+    .bind(&start)
+    .bind(&end)
+    .bind(project_id)
+    .bind(project_id)
+    .fetch_one(db.pool())
+    .await?;
+    let usage = UsageTotals {
+        spend_usd: row.get("spend_usd"),
+        tokens: row.get("tokens"),
+        calls: row.get("calls"),
+    };
+
+    let features_completed: i64 = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS done
+        FROM features
+        WHERE status = 'done'
+          AND datetime(updated_at) >= datetime(?) AND datetime(updated_at) < datetime(?)
+          AND (? IS NULL OR project_id = ?)
+        "#,
+    )
+    .bind(&start)
+    .bind(&end)
+    .bind(project_id)
+    .bind(project_id)
+    .fetch_one(db.pool())
+    .await?
+    .get("done");
+
+    Ok(PeriodSummary {
+        usage,
+        features_completed,
+        cost_per_feature_usd: (features_completed > 0)
+            .then(|| usage.spend_usd / features_completed as f64),
+    })
+}
+
+async fn breakdown(
+    db: &Database,
+    project_id: Option<&str>,
+    period: Period,
+    by: CompareBy,
+) -> Result<Vec<(String, Option<String>, UsageTotals)>> {
+    let (start, end) = period.bounds();
+    let sql = match by {
+        CompareBy::Model => {
+            r#"
+            SELECT model AS key, NULL AS label, COUNT(*) AS calls,
+                   COALESCE(SUM(input_tokens + output_tokens), 0) AS tokens,
+                   COALESCE(SUM(input_cost_usd + output_cost_usd), 0.0) AS spend_usd
+            FROM llm_costs
+            WHERE datetime(created_at) >= datetime(?) AND datetime(created_at) < datetime(?)
+              AND (? IS NULL OR project_id = ?)
+            GROUP BY model
+            "#
+        }
+        CompareBy::Feature => {
+            r#"
+            SELECT g.feature_id AS key, f.title AS label, COUNT(*) AS calls,
+                   COALESCE(SUM(g.tokens_used), 0) AS tokens,
+                   COALESCE(SUM(g.cost_usd), 0.0) AS spend_usd
+            FROM generations g
+            LEFT JOIN features f ON f.id = g.feature_id
+            WHERE g.feature_id IS NOT NULL
+              AND datetime(g.created_at) >= datetime(?) AND datetime(g.created_at) < datetime(?)
+              AND (? IS NULL OR g.project_id = ?)
+            GROUP BY g.feature_id
+            "#
+        }
+    };
+    let rows = sqlx::query(sql)
+        .bind(&start)
+        .bind(&end)
+        .bind(project_id)
+        .bind(project_id)
+        .fetch_all(db.pool())
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            (
+                r.get("key"),
+                r.get("label"),
+                UsageTotals {
+                    spend_usd: r.get("spend_usd"),
+                    tokens: r.get("tokens"),
+                    calls: r.get("calls"),
+                },
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn cost(db: &Database, id: &str, model: &str, usd: f64, at: &str) {
+        sqlx::query(
+            "INSERT INTO llm_costs (id, project_id, model, input_tokens, output_tokens, input_cost_usd, created_at) \
+             VALUES (?, 'p1', ?, 100, 50, ?, ?)",
+        )
+        .bind(id)
+        .bind(model)
+        .bind(usd)
+        .bind(at)
+        .execute(db.pool())
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn test_period_parse() {
+        let period = Period::parse("2025-01-01..2025-01-15").unwrap();
+        assert_eq!(period.days(), 15);
+        assert_eq!(period.to_string(), "2025-01-01..2025-01-15");
+        assert_eq!(Period::parse("2025-01-01").unwrap().days(), 1);
+        assert!(Period::parse("2025-01-15..2025-01-01").is_err());
+        assert!(Period::parse("last week").is_err());
+        assert_eq!(percent_change(2.0, 1.0), Some(-50.0));
+        assert_eq!(percent_change(0.0, 1.0), None);
+    }
+
+    #[tokio::test]
+    async fn test_compare_periods_by_model() {
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'Demo')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO features (id, project_id, title, status, updated_at) \
+             VALUES ('f1', 'p1', 'Login', 'done', '2025-01-20 09:00:00')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        cost(&db, "c1", "a/smart", 3.0, "2025-01-05 10:00:00").await;
+        cost(&db, "c2", "a/smart", 1.0, "2025-01-15 23:59:59").await;
+        cost(&db, "c3", "b/fast", 0.5, "2025-01-16 00:00:00").await;
+        cost(&db, "c4", "a/smart", 1.0, "2025-01-31 12:00:00").await;
+
+        let comparison = compare(
+            &db,
+            Some("p1"),
+            Period::parse("2025-01-01..2025-01-15").unwrap(),
+            Period::parse("2025-01-16..2025-01-31").unwrap(),
+            Some(CompareBy::Model),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(comparison.from.usage.calls, 2);
+        assert_eq!(comparison.from.usage.tokens, 300);
+        assert_eq!(comparison.from.cost_per_feature_usd, None);
+        assert_eq!(comparison.to.usage.calls, 2);
+        assert_eq!(comparison.to.features_completed, 1);
+        assert_eq!(comparison.to.cost_per_feature_usd, Some(1.5));
+        assert_eq!(comparison.spend_delta_usd(), -2.5);
+
+        assert_eq!(comparison.breakdown[0].key, "a/smart");
+        assert_eq!(comparison.breakdown[0].spend_delta_usd(), -3.0);
+        assert_eq!(comparison.breakdown[1].key, "b/fast");
+        assert_eq!(comparison.breakdown[1].from, UsageTotals::default());
+    }
+}
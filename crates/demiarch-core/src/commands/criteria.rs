@@ -0,0 +1,305 @@
+//! Acceptance criteria derived from a conversation
+//!
+//! `/criteria <feature-id>` in chat and `demiarch features derive-criteria`
+//! summarize what a conversation settled about a feature into Given/When/Then
+//! criteria. The proposal carries a diff against the feature's current
+//! criteria so the caller can confirm before [`apply`] stores it.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::agents::patch::unified_diff;
+use crate::commands::chat::{self, ChatMessage};
+use crate::commands::feature::{Feature, FeatureRepository};
+use crate::llm::{LlmClient, Message};
+use crate::storage::Database;
+use crate::{Error, Result};
+
+const CRITERIA_PROMPT: &str = r#"You are an expert at writing acceptance criteria for software features.
+
+You are given a feature and a conversation. Summarize what the conversation
+decided about this feature into acceptance criteria in Given/When/Then form.
+Ignore parts of the conversation about other features. If the feature already
+has criteria, keep the ones the conversation does not change.
+
+OUTPUT FORMAT:
+Respond with valid JSON only:
+{
+  "criteria": [
+    {"given": "a precondition", "when": "an action", "then": "an observable outcome"}
+  ]
+}
+"#;
+
+/// Name the criteria are diffed under
+const DIFF_PATH: &str = "acceptance-criteria";
+
+/// One Given/When/Then acceptance criterion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AcceptanceCriterion {
+    pub given: String,
+    pub when: String,
+    pub then: String,
+}
+
+impl AcceptanceCriterion {
+    /// Render as a single `Given … When … Then …` line, the stored form
+    pub fn to_line(&self) -> String {
+        format!(
+            "Given {} When {} Then {}",
+            strip_keyword(&self.given, "given"),
+            strip_keyword(&self.when, "when"),
+            strip_keyword(&self.then, "then")
+        )
+    }
+}
+
+/// Criteria proposed for a feature, not yet stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriteriaProposal {
+    /// Feature the criteria are for, as it is now
+    pub feature: Feature,
+    /// Conversation the criteria were derived from
+    pub conversation_id: String,
+    /// Derived criteria
+    pub criteria: Vec<AcceptanceCriterion>,
+    /// Criteria as they would be stored, one per line
+    pub proposed: String,
+    /// Unified diff from the current criteria; empty when they are unchanged
+    pub diff: String,
+}
+
+impl CriteriaProposal {
+    /// Build a proposal for `feature` from derived criteria
+    pub fn new(
+        feature: Feature,
+        conversation_id: impl Into<String>,
+        criteria: Vec<AcceptanceCriterion>,
+    ) -> Self {
+        let proposed = render(&criteria);
+        let diff = unified_diff(
+            Path::new(DIFF_PATH),
+            feature
+                .acceptance_criteria
+                .as_deref()
+                .map(with_newline)
+                .as_deref(),
+            &with_newline(&proposed),
+        );
+        Self {
+            feature,
+            conversation_id: conversation_id.into(),
+            criteria,
+            proposed,
+            diff,
+        }
+    }
+
+    /// Whether storing the proposal would change the feature
+    pub fn is_unchanged(&self) -> bool {
+        self.diff.is_empty()
+    }
+}
+
+/// Render criteria in the stored form, one per line
+pub fn render(criteria: &[AcceptanceCriterion]) -> String {
+    criteria
+        .iter()
+        .map(AcceptanceCriterion::to_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Derive acceptance criteria for a feature from a conversation
+///
+/// Nothing is stored; pass the proposal to [`apply`] once it is confirmed.
+pub async fn derive(
+    db: &Database,
+    llm_client: &LlmClient,
+    feature_id: &str,
+    conversation_id: &str,
+) -> Result<CriteriaProposal> {
+    let feature = FeatureRepository::new(db)
+        .get(feature_id)
+        .await?
+        .ok_or_else(|| Error::FeatureNotFound(feature_id.to_string()))?;
+    if chat::get_conversation(db, conversation_id).await?.is_none() {
+        return Err(Error::NotFound(format!(
+            "Conversation not found: {}",
+            conversation_id
+        )));
+    }
+    let history = chat::get_history(db, conversation_id, None).await?;
+    if history.is_empty() {
+        return Err(Error::InvalidInput(format!(
+            "Conversation {} has no messages to derive criteria from",
+            conversation_id
+        )));
+    }
+
+    let messages = vec![
+        Message::system(CRITERIA_PROMPT),
+        Message::user(criteria_request(&feature, &history)),
+    ];
+    let response = llm_client.complete(messages, None).await?;
+    let criteria = parse_response(&response.content)?;
+
+    Ok(CriteriaProposal::new(feature, conversation_id, criteria))
+}
+
+/// Store a confirmed proposal on its feature
+pub async fn apply(db: &Database, proposal: &CriteriaProposal) -> Result<Feature> {
+    let repo = FeatureRepository::new(db);
+    let mut feature = repo
+        .get(&proposal.feature.id)
+        .await?
+        .ok_or_else(|| Error::FeatureNotFound(proposal.feature.id.clone()))?;
+    feature.acceptance_criteria = Some(proposal.proposed.clone());
+    repo.update(&feature).await?;
+    Ok(feature)
+}
+
+/// User message describing the feature and the conversation
+fn criteria_request(feature: &Feature, history: &[ChatMessage]) -> String {
+    let mut request = format!("Feature: {}\n", feature.title);
+    if let Some(description) = &feature.description {
+        request.push_str(&format!("Description: {}\n", description));
+    }
+    if let Some(existing) = &feature.acceptance_criteria {
+        request.push_str(&format!("Current criteria:\n{}\n", existing));
+    }
+    request.push_str("\nConversation:\n");
+    for message in history {
+        request.push_str(&format!(
+            "{}: {}\n\n",
+            message.role.as_str(),
+            message.content
+        ));
+    }
+    request
+}
+
+/// Parse the model's JSON, tolerating a markdown code fence around it
+fn parse_response(content: &str) -> Result<Vec<AcceptanceCriterion>> {
+    let content = content.trim();
+    let json_content = if content.contains("```json") {
+        content
+            .split("```json")
+            .nth(1)
+            .and_then(|s| s.split("```").next())
+            .unwrap_or(content)
+            .trim()
+    } else if content.contains("```") {
+        content.split("```").nth(1).unwrap_or(content).trim()
+    } else {
+        content
+    };
+
+    #[derive(Deserialize)]
+    struct DerivedCriteria {
+        criteria: Vec<AcceptanceCriterion>,
+    }
+
+    let derived: DerivedCriteria = serde_json::from_str(json_content).map_err(|e| {
+        Error::Parse(format!(
+            "Failed to parse acceptance criteria response: {}",
+            e
+        ))
+    })?;
+    if derived.criteria.is_empty() {
+        return Err(Error::Parse(
+            "The conversation did not yield any acceptance criteria".to_string(),
+        ));
+    }
+    Ok(derived.criteria)
+}
+
+/// Drop a leading Given/When/Then the model repeated inside the clause
+fn strip_keyword<'a>(clause: &'a str, keyword: &str) -> &'a str {
+    let clause = clause.trim();
+    match clause.get(..keyword.len()) {
+        Some(head)
+            if head.eq_ignore_ascii_case(keyword)
+                && clause[keyword.len()..].starts_with(char::is_whitespace) =>
+        {
+            clause[keyword.len()..].trim_start()
+        }
+        _ => clause,
+    }
+}
+
+fn with_newline(text: &str) -> String {
+    format!("{}\n", text.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn criterion(given: &str, when: &str, then: &str) -> AcceptanceCriterion {
+        AcceptanceCriterion {
+            given: given.to_string(),
+            when: when.to_string(),
+            then: then.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_fenced_response() {
+        let content = "Here you go:\n```json\n{\"criteria\": [{\"given\": \"a signed-in user\", \"when\": \"they click logout\", \"then\": \"the session ends\"}]}\n```";
+        let criteria = parse_response(content).unwrap();
+        assert_eq!(
+            criteria,
+            vec![criterion(
+                "a signed-in user",
+                "they click logout",
+                "the session ends"
+            )]
+        );
+
+        assert!(parse_response("{\"criteria\": []}").is_err());
+        assert!(parse_response("not json").is_err());
+    }
+
+    #[test]
+    fn test_render_strips_repeated_keywords() {
+        let criteria = vec![
+            criterion("Given a cart", "when checking out", "Then an order exists"),
+            criterion("given-ness", "whenever", "thenceforth"),
+        ];
+        assert_eq!(
+            render(&criteria),
+            "Given a cart When checking out Then an order exists\n\
+             Given given-ness When whenever Then thenceforth"
+        );
+    }
+
+    #[test]
+    fn test_proposal_diffs_against_current_criteria() {
+        let feature = Feature::new("project", "Logout")
+            .with_acceptance_criteria("Given a user When they log out Then the session ends");
+        let proposal = CriteriaProposal::new(
+            feature.clone(),
+            "conv",
+            vec![
+                criterion("a user", "they log out", "the session ends"),
+                criterion("a user", "they log out", "they see the sign-in page"),
+            ],
+        );
+        assert!(!proposal.is_unchanged());
+        assert!(proposal
+            .diff
+            .contains("+Given a user When they log out Then they see the sign-in page"));
+        assert!(!proposal
+            .diff
+            .contains("-Given a user When they log out Then the session ends"));
+
+        let same = CriteriaProposal::new(
+            feature,
+            "conv",
+            vec![criterion("a user", "they log out", "the session ends")],
+        );
+        assert!(same.is_unchanged());
+    }
+}
@@ -0,0 +1,539 @@
+//! Daily and weekly activity digests
+//!
+//! `demiarch digest --period week` summarizes what happened in a project
+//! over the last day or week: features completed, generations run, LLM spend
+//! and notable failures. The figures come straight from the database and
+//! are rendered as Markdown; a cheap model (the least expensive of
+//! `llm.default_model` and `llm.fallback_models` with known pricing) writes
+//! a short summary on top of them, so the numbers never depend on the model.
+//!
+//! Every digest is stored as a `digest` document for history. `--post`
+//! publishes it as a `digest_created` event to the `[events]` webhooks; the
+//! Markdown is in the event's `text` field, which Slack incoming webhooks
+//! post as the message.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::commands::document::{Document, DocumentRepository, DocumentType};
+use crate::commands::project::ProjectRepository;
+use crate::config::{Config, LlmConfig};
+use crate::cost::CostTracker;
+use crate::events::{self, CoreEvent, EventKind, PublishOutcome};
+use crate::llm::{LlmClient, Message};
+use crate::routing::ModelRegistry;
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Failures listed in a digest
+const MAX_FAILURES: usize = 10;
+
+const DIGEST_PROMPT: &str = "You write short activity digests for a software team. \
+Given the facts for a period, write a summary of two to four sentences in Markdown: \
+what moved forward, where the money went and what needs attention. \
+Use only the facts given; never invent features, names or numbers. No headings.";
+
+/// Time span a digest covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestPeriod {
+    Day,
+    Week,
+}
+
+impl DigestPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "day" | "daily" => Ok(Self::Day),
+            "week" | "weekly" => Ok(Self::Week),
+            other => Err(Error::InvalidInput(format!(
+                "Unknown digest period '{}'. Use day or week.",
+                other
+            ))),
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            Self::Day => Duration::days(1),
+            Self::Week => Duration::days(7),
+        }
+    }
+
+    /// Adjective used in titles
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Day => "Daily",
+            Self::Week => "Weekly",
+        }
+    }
+}
+
+/// A feature or failure mentioned in a digest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityItem {
+    pub id: String,
+    pub title: String,
+    pub detail: String,
+}
+
+/// Figures for one project and period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Activity {
+    pub project_id: String,
+    pub project_name: String,
+    pub period: DigestPeriod,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub features_completed: Vec<ActivityItem>,
+    pub generations_run: u32,
+    pub generations_completed: u32,
+    pub generations_failed: u32,
+    /// Files produced by the period's generations
+    pub files_generated: u32,
+    pub spend_usd: f64,
+    /// Spend per model, largest first
+    pub spend_by_model: Vec<(String, f64)>,
+    /// Failed generations and files that failed validation, up to ten
+    pub failures: Vec<ActivityItem>,
+}
+
+impl Activity {
+    pub fn is_empty(&self) -> bool {
+        self.features_completed.is_empty()
+            && self.generations_run == 0
+            && self.spend_usd == 0.0
+            && self.failures.is_empty()
+    }
+
+    pub fn title(&self) -> String {
+        format!(
+            "{} digest: {} ({} to {})",
+            self.period.label(),
+            self.project_name,
+            self.since.format("%Y-%m-%d"),
+            self.until.format("%Y-%m-%d")
+        )
+    }
+
+    /// The figures as a Markdown section
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("## Activity\n\n");
+        md.push_str(&format!(
+            "- **Features completed:** {}\n",
+            self.features_completed.len()
+        ));
+        md.push_str(&format!(
+            "- **Generations:** {} run, {} completed, {} failed ({} files)\n",
+            self.generations_run,
+            self.generations_completed,
+            self.generations_failed,
+            self.files_generated
+        ));
+        let by_model = self
+            .spend_by_model
+            .iter()
+            .map(|(model, usd)| format!("{} ${:.2}", model, usd))
+            .collect::<Vec<_>>();
+        if by_model.is_empty() {
+            md.push_str(&format!("- **Spend:** ${:.2}\n", self.spend_usd));
+        } else {
+            md.push_str(&format!(
+                "- **Spend:** ${:.2} ({})\n",
+                self.spend_usd,
+                by_model.join(", ")
+            ));
+        }
+
+        if !self.features_completed.is_empty() {
+            md.push_str("\n### Features completed\n\n");
+            for item in &self.features_completed {
+                md.push_str(&format!("- {} ({})\n", item.title, item.detail));
+            }
+        }
+        if !self.failures.is_empty() {
+            md.push_str("\n### Notable failures\n\n");
+            for item in &self.failures {
+                md.push_str(&format!("- {}: {}\n", item.title, item.detail));
+            }
+        }
+        md
+    }
+}
+
+/// A stored digest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Digest {
+    pub activity: Activity,
+    /// The `digest` document holding the Markdown
+    pub document: Document,
+}
+
+impl Digest {
+    pub fn markdown(&self) -> &str {
+        &self.document.content
+    }
+}
+
+fn sql_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn short(id: &str) -> &str {
+    &id[..8.min(id.len())]
+}
+
+/// The cheapest configured model with known pricing, else the default model
+pub fn cheapest_model(config: &LlmConfig) -> String {
+    let registry = ModelRegistry::with_defaults();
+    std::iter::once(&config.default_model)
+        .chain(&config.fallback_models)
+        .filter_map(|model| {
+            registry.get(model).map(|candidate| {
+                (
+                    model,
+                    candidate.input_cost_per_million + candidate.output_cost_per_million,
+                )
+            })
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(model, _)| model.clone())
+        .unwrap_or_else(|| config.default_model.clone())
+}
+
+/// Gather a project's activity for the period ending at `now`
+pub async fn collect(
+    db: &Database,
+    project_id: &str,
+    period: DigestPeriod,
+    now: DateTime<Utc>,
+) -> Result<Activity> {
+    let project = ProjectRepository::new(db)
+        .get(project_id)
+        .await?
+        .ok_or_else(|| Error::ProjectNotFound(project_id.to_string()))?;
+    let since = now - period.duration();
+    let (from, to) = (sql_time(since), sql_time(now));
+
+    let features = sqlx::query(
+        r#"
+        SELECT id, title, completed_at
+        FROM features
+        WHERE project_id = ? AND status = 'done'
+          AND julianday(completed_at) >= julianday(?) AND julianday(completed_at) <= julianday(?)
+        ORDER BY completed_at
+        "#,
+    )
+    .bind(&project.id)
+    .bind(&from)
+    .bind(&to)
+    .fetch_all(db.pool())
+    .await?;
+    let features_completed = features
+        .iter()
+        .map(|row| {
+            let completed: DateTime<Utc> = row.get("completed_at");
+            ActivityItem {
+                id: row.get("id"),
+                title: row.get("title"),
+                detail: format!("done {}", completed.format("%Y-%m-%d")),
+            }
+        })
+        .collect();
+
+    let generations = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS run,
+               COALESCE(SUM(status = 'completed'), 0) AS completed,
+               COALESCE(SUM(status = 'failed'), 0) AS failed,
+               (SELECT COUNT(*) FROM generation_artifacts a
+                JOIN generations g ON g.id = a.generation_id
+                WHERE g.project_id = ?1
+                  AND julianday(g.created_at) >= julianday(?2)
+                  AND julianday(g.created_at) <= julianday(?3)) AS files
+        FROM generations
+        WHERE project_id = ?1
+          AND julianday(created_at) >= julianday(?2) AND julianday(created_at) <= julianday(?3)
+        "#,
+    )
+    .bind(&project.id)
+    .bind(&from)
+    .bind(&to)
+    .fetch_one(db.pool())
+    .await?;
+
+    let spend = sqlx::query(
+        r#"
+        SELECT model, SUM(input_cost_usd + output_cost_usd) AS cost
+        FROM llm_costs
+        WHERE project_id = ?
+          AND julianday(created_at) >= julianday(?) AND julianday(created_at) <= julianday(?)
+        GROUP BY model
+        ORDER BY cost DESC
+        "#,
+    )
+    .bind(&project.id)
+    .bind(&from)
+    .bind(&to)
+    .fetch_all(db.pool())
+    .await?;
+    let spend_by_model: Vec<(String, f64)> = spend
+        .iter()
+        .map(|row| (row.get("model"), row.get("cost")))
+        .collect();
+
+    let failed = sqlx::query(
+        r#"
+        SELECT id, description
+        FROM generations
+        WHERE project_id = ? AND status = 'failed'
+          AND julianday(updated_at) >= julianday(?) AND julianday(updated_at) <= julianday(?)
+        ORDER BY updated_at
+        "#,
+    )
+    .bind(&project.id)
+    .bind(&from)
+    .bind(&to)
+    .fetch_all(db.pool())
+    .await?;
+    let invalid = sqlx::query(
+        r#"
+        SELECT a.id, a.file_path, a.generation_id
+        FROM generation_artifacts a
+        JOIN generations g ON g.id = a.generation_id
+        WHERE g.project_id = ? AND a.validation_status = 'invalid'
+          AND julianday(a.created_at) >= julianday(?) AND julianday(a.created_at) <= julianday(?)
+        ORDER BY a.created_at
+        "#,
+    )
+    .bind(&project.id)
+    .bind(&from)
+    .bind(&to)
+    .fetch_all(db.pool())
+    .await?;
+    let failures = failed
+        .iter()
+        .map(|row| ActivityItem {
+            id: row.get("id"),
+            title: row.get("description"),
+            detail: "generation failed".to_string(),
+        })
+        .chain(invalid.iter().map(|row| {
+            let generation_id: String = row.get("generation_id");
+            ActivityItem {
+                id: row.get("id"),
+                title: row.get("file_path"),
+                detail: format!("syntax errors in generation {}", short(&generation_id)),
+            }
+        }))
+        .take(MAX_FAILURES)
+        .collect();
+
+    Ok(Activity {
+        project_id: project.id,
+        project_name: project.name,
+        period,
+        since,
+        until: now,
+        features_completed,
+        generations_run: generations.get::<i64, _>("run") as u32,
+        generations_completed: generations.get::<i64, _>("completed") as u32,
+        generations_failed: generations.get::<i64, _>("failed") as u32,
+        files_generated: generations.get::<i64, _>("files") as u32,
+        spend_usd: spend_by_model.iter().map(|(_, usd)| usd).sum(),
+        spend_by_model,
+        failures,
+    })
+}
+
+/// Write a digest of the period ending now and store it as a document
+///
+/// `model` overrides the cheap model. A period without activity is stored
+/// without asking the model.
+pub async fn generate_digest(
+    db: &Database,
+    project_id: &str,
+    period: DigestPeriod,
+    model: Option<&str>,
+) -> Result<Digest> {
+    let activity = collect(db, project_id, period, Utc::now()).await?;
+    let title = activity.title();
+    let facts = activity.to_markdown();
+
+    let mut document = if activity.is_empty() {
+        Document::new(
+            &activity.project_id,
+            DocumentType::Digest,
+            &title,
+            format!("# {}\n\nNo activity in this period.\n\n{}", title, facts),
+        )
+    } else {
+        let config = Config::load().map_err(|e| Error::ConfigError(e.to_string()))?;
+        let model = model
+            .map(str::to_string)
+            .unwrap_or_else(|| cheapest_model(&config.llm));
+        let summary = summarize(&config, &model, &title, &facts).await?;
+        let registry = ModelRegistry::with_defaults();
+        let cost = registry.get(&summary.model).map(|candidate| {
+            candidate.estimate_cost(
+                summary.input_tokens as usize,
+                summary.output_tokens as usize,
+            )
+        });
+        let mut document = Document::new(
+            &activity.project_id,
+            DocumentType::Digest,
+            &title,
+            format!("# {}\n\n{}\n\n{}", title, summary.content.trim(), facts),
+        )
+        .with_model(&summary.model)
+        .with_tokens(summary.tokens_used as i32);
+        if let Some(cost) = cost {
+            document = document.with_cost(cost);
+        }
+        document
+    };
+    document = document.with_description(format!(
+        "Activity from {} to {}",
+        activity.since.format("%Y-%m-%d %H:%M"),
+        activity.until.format("%Y-%m-%d %H:%M")
+    ));
+    DocumentRepository::new(db).create(&document).await?;
+
+    Ok(Digest { activity, document })
+}
+
+async fn summarize(
+    config: &Config,
+    model: &str,
+    title: &str,
+    facts: &str,
+) -> Result<crate::llm::LlmResponse> {
+    let api_key = config
+        .llm
+        .resolved_api_key()
+        .map_err(|e| Error::ConfigError(e.to_string()))?
+        .ok_or_else(|| {
+            Error::LLMError(
+                "API key not configured. Set DEMIARCH_API_KEY or OPENROUTER_API_KEY environment variable.".to_string()
+            )
+        })?;
+    let client = LlmClient::builder()
+        .config(config.llm.clone())
+        .api_key(api_key)
+        .cost_tracker(Arc::new(CostTracker::from_config(&config.cost)))
+        .build()?;
+
+    let messages = vec![
+        Message::system(DIGEST_PROMPT),
+        Message::user(format!("{}\n\n{}", title, facts)),
+    ];
+    client.complete(messages, Some(model)).await
+}
+
+/// Publish a digest to event subscribers as `digest_created`
+///
+/// Returns `None` when no webhook or plugin subscribes to digests.
+pub async fn post(digest: &Digest) -> Option<PublishOutcome> {
+    let bus = events::global();
+    if !bus.has_subscribers(EventKind::DigestCreated) {
+        return None;
+    }
+    let outcome = bus
+        .publish(CoreEvent::DigestCreated {
+            document_id: digest.document.id.clone(),
+            project_id: digest.activity.project_id.clone(),
+            period: digest.activity.period.as_str().to_string(),
+            title: digest.document.title.clone(),
+            text: digest.document.content.clone(),
+        })
+        .await;
+    Some(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_collects_the_periods_activity() {
+        let db = Database::in_memory().await.unwrap();
+        let now = Utc::now();
+        let recent = sql_time(now - Duration::hours(3));
+        let old = sql_time(now - Duration::days(10));
+        sqlx::raw_sql(&format!(
+            r#"
+            INSERT INTO projects (id, name) VALUES ('p1', 'Shop');
+            INSERT INTO features (id, project_id, title, status, completed_at)
+                VALUES ('f1', 'p1', 'Checkout', 'done', '{recent}'),
+                       ('f2', 'p1', 'Login', 'done', '{old}');
+            INSERT INTO generations (id, project_id, description, output_dir, status, created_at, updated_at)
+                VALUES ('g1', 'p1', 'add cart', '/tmp', 'completed', '{recent}', '{recent}'),
+                       ('g2', 'p1', 'add search', '/tmp', 'failed', '{recent}', '{recent}'),
+                       ('g3', 'p1', 'old work', '/tmp', 'completed', '{old}', '{old}');
+            INSERT INTO generation_artifacts (id, generation_id, file_path, content)
+                VALUES ('a1', 'g1', 'src/cart.rs', ''), ('a2', 'g3', 'src/old.rs', '');
+            INSERT INTO llm_costs (id, project_id, model, input_cost_usd, output_cost_usd, created_at)
+                VALUES ('c1', 'p1', 'openai/gpt-4o', 0.50, 1.00, '{recent}'),
+                       ('c2', 'p1', 'openai/gpt-4o-mini', 0.10, 0.15, '{recent}'),
+                       ('c3', 'p1', 'openai/gpt-4o', 9.00, 9.00, '{old}');
+            "#
+        ))
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let activity = collect(&db, "p1", DigestPeriod::Week, now).await.unwrap();
+        assert_eq!(activity.features_completed.len(), 1);
+        assert_eq!(activity.features_completed[0].title, "Checkout");
+        assert_eq!(
+            (
+                activity.generations_run,
+                activity.generations_completed,
+                activity.generations_failed,
+                activity.files_generated
+            ),
+            (2, 1, 1, 1)
+        );
+        assert!((activity.spend_usd - 1.75).abs() < 1e-9);
+        assert_eq!(activity.spend_by_model[0].0, "openai/gpt-4o");
+        assert_eq!(activity.failures[0].title, "add search");
+
+        let md = activity.to_markdown();
+        assert!(md.contains("- **Generations:** 2 run, 1 completed, 1 failed (1 files)"));
+        assert!(md.contains("### Notable failures\n\n- add search: generation failed"));
+        assert!(activity.title().starts_with("Weekly digest: Shop ("));
+    }
+
+    #[test]
+    fn test_cheapest_model_prefers_known_low_prices() {
+        let config = LlmConfig {
+            default_model: "anthropic/claude-sonnet-4-20250514".to_string(),
+            fallback_models: vec![
+                "openai/gpt-4o-mini".to_string(),
+                "custom/unknown".to_string(),
+            ],
+            ..LlmConfig::default()
+        };
+        assert_eq!(cheapest_model(&config), "openai/gpt-4o-mini");
+
+        let unknown = LlmConfig {
+            default_model: "custom/unknown".to_string(),
+            fallback_models: Vec::new(),
+            ..LlmConfig::default()
+        };
+        assert_eq!(cheapest_model(&unknown), "custom/unknown");
+        assert_eq!(DigestPeriod::parse("Weekly").unwrap(), DigestPeriod::Week);
+        assert!(DigestPeriod::parse("month").is_err());
+    }
+}
@@ -13,7 +13,8 @@ use uuid::Uuid;
 use crate::config::Config;
 use crate::cost::CostTracker;
 use crate::error::{Error, Result};
-use crate::llm::{LlmClient, Message};
+use crate::llm::{LlmClient, Message, ResponseCache};
+use crate::progress::{Progress, Stage};
 use crate::storage::Database;
 
 use super::feature::{Feature, FeatureRepository};
@@ -34,6 +35,10 @@ pub enum DocumentType {
     Design,
     /// Technical Specification
     TechSpec,
+    /// Roadmap assembled from phases and features
+    Roadmap,
+    /// Daily or weekly activity digest
+    Digest,
     /// Custom document type
     Custom,
 }
@@ -46,6 +51,8 @@ impl DocumentType {
             DocumentType::Architecture => "architecture",
             DocumentType::Design => "design",
             DocumentType::TechSpec => "tech_spec",
+            DocumentType::Roadmap => "roadmap",
+            DocumentType::Digest => "digest",
             DocumentType::Custom => "custom",
         }
     }
@@ -57,6 +64,8 @@ impl DocumentType {
             "architecture" => Some(DocumentType::Architecture),
             "design" => Some(DocumentType::Design),
             "tech_spec" => Some(DocumentType::TechSpec),
+            "roadmap" => Some(DocumentType::Roadmap),
+            "digest" => Some(DocumentType::Digest),
             "custom" => Some(DocumentType::Custom),
             _ => None,
         }
@@ -69,6 +78,8 @@ impl DocumentType {
             DocumentType::Architecture => "Architecture Document",
             DocumentType::Design => "Design Document",
             DocumentType::TechSpec => "Technical Specification",
+            DocumentType::Roadmap => "Roadmap",
+            DocumentType::Digest => "Activity Digest",
             DocumentType::Custom => "Custom Document",
         }
     }
@@ -135,6 +146,8 @@ pub struct Document {
     pub tokens_used: Option<i32>,
     /// Generation cost in USD
     pub generation_cost_usd: Option<f64>,
+    /// Language the document was written in (e.g. "de"); `None` for English
+    pub language: Option<String>,
     /// When the document was created
     pub created_at: DateTime<Utc>,
     /// When the document was last updated
@@ -163,6 +176,7 @@ impl Document {
             model_used: None,
             tokens_used: None,
             generation_cost_usd: None,
+            language: None,
             created_at: now,
             updated_at: now,
         }
@@ -191,6 +205,12 @@ impl Document {
         self.generation_cost_usd = Some(cost);
         self
     }
+
+    /// Set the language the document is written in
+    pub fn with_language(mut self, language: Option<&str>) -> Self {
+        self.language = language.map(str::to_string);
+        self
+    }
 }
 
 /// A document version for history tracking
@@ -215,6 +235,8 @@ pub struct DocumentVersion {
 /// Document generator using LLM
 pub struct DocumentGenerator {
     llm_client: LlmClient,
+    language: Option<String>,
+    progress: Progress,
 }
 
 impl DocumentGenerator {
@@ -240,7 +262,42 @@ impl DocumentGenerator {
 
         let llm_client = builder.build()?;
 
-        Ok(Self { llm_client })
+        Ok(Self {
+            llm_client,
+            language: None,
+            progress: Progress::hidden(),
+        })
+    }
+
+    /// Serve repeated identical requests from a response cache
+    pub fn with_response_cache(mut self, cache: ResponseCache) -> Self {
+        self.llm_client = self.llm_client.with_response_cache(cache);
+        self
+    }
+
+    /// Write generated documents in this language (e.g. "de", "German", "pt-BR")
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        let language = language.into();
+        self.language = (!language.trim().is_empty()).then_some(language);
+        self
+    }
+
+    /// Report document stages to a progress handle
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Append the output-language instruction to a user prompt, if set
+    fn localize_prompt(&self, prompt: String) -> String {
+        match &self.language {
+            Some(language) => format!(
+                "{}\n\nWrite the entire document in {}. Keep code, identifiers, file paths and commands in English, unchanged.",
+                prompt,
+                language_name(language)
+            ),
+            None => prompt,
+        }
     }
 
     /// Generate a PRD for a project
@@ -299,13 +356,15 @@ Please create a complete PRD with the following sections:
 
         let messages = vec![
             Message::system(PRD_SYSTEM_PROMPT),
-            Message::user(user_prompt),
+            Message::user(self.localize_prompt(user_prompt)),
         ];
 
         debug!(
             message_count = messages.len(),
             "Sending PRD generation request to LLM"
         );
+        self.progress
+            .stage(Stage::Document, format!("Writing PRD for {}", project.name));
 
         let response = self.llm_client.complete_with_fallback(messages).await?;
 
@@ -395,13 +454,17 @@ Please create a complete architecture document with the following sections:
 
         let messages = vec![
             Message::system(ARCHITECTURE_SYSTEM_PROMPT),
-            Message::user(user_prompt),
+            Message::user(self.localize_prompt(user_prompt)),
         ];
 
         debug!(
             message_count = messages.len(),
             "Sending architecture generation request to LLM"
         );
+        self.progress.stage(
+            Stage::Document,
+            format!("Writing architecture document for {}", project.name),
+        );
 
         let response = self.llm_client.complete_with_fallback(messages).await?;
 
@@ -426,6 +489,104 @@ Please create a complete architecture document with the following sections:
             cost_usd,
         })
     }
+
+    /// Translate a document rendered without the model (such as a roadmap)
+    /// into the generator's language
+    pub async fn translate(
+        &self,
+        doc_type: DocumentType,
+        title: &str,
+        content: &str,
+    ) -> Result<GeneratedDocument> {
+        let language = self
+            .language
+            .as_deref()
+            .ok_or_else(|| Error::InvalidInput("No language to translate into".to_string()))?;
+        let messages = vec![
+            Message::system(TRANSLATION_SYSTEM_PROMPT),
+            Message::user(format!(
+                "Translate this {} into {}:\n\n{}",
+                doc_type.display_name(),
+                language_name(language),
+                content
+            )),
+        ];
+
+        self.progress.stage(
+            Stage::Document,
+            format!("Translating {} into {}", title, language_name(language)),
+        );
+        let response = self.llm_client.complete_with_fallback(messages).await?;
+        let cost_usd = estimate_cost(
+            &response.model,
+            response.input_tokens,
+            response.output_tokens,
+        );
+
+        Ok(GeneratedDocument {
+            doc_type,
+            title: title.to_string(),
+            content: response.content,
+            model: response.model,
+            tokens_used: response.tokens_used,
+            cost_usd,
+        })
+    }
+}
+
+/// English name of a language code such as `de` or `pt-BR`, for prompts
+///
+/// Anything else (e.g. "German") is used as written.
+pub fn language_name(language: &str) -> String {
+    let language = language.trim();
+    let name = match language.to_ascii_lowercase().as_str() {
+        "de" => "German",
+        "es" => "Spanish",
+        "fr" => "French",
+        "it" => "Italian",
+        "ja" => "Japanese",
+        "ko" => "Korean",
+        "nl" => "Dutch",
+        "pl" => "Polish",
+        "pt" => "Portuguese",
+        "pt-br" => "Brazilian Portuguese",
+        "ru" => "Russian",
+        "sv" => "Swedish",
+        "tr" => "Turkish",
+        "uk" => "Ukrainian",
+        "zh" | "zh-cn" => "Simplified Chinese",
+        "zh-tw" => "Traditional Chinese",
+        _ => return language.to_string(),
+    };
+    name.to_string()
+}
+
+/// Language to generate a document in
+///
+/// An explicit `requested` language wins; "en" or "English" switches back
+/// to the default. Without one, the language of the project's latest
+/// document of the same type is kept, so regenerating doesn't silently
+/// change it. `None` means English.
+pub async fn resolve_language(
+    db: &Database,
+    project_id: &str,
+    doc_type: DocumentType,
+    requested: Option<&str>,
+) -> Result<Option<String>> {
+    if let Some(requested) = requested {
+        let requested = requested.trim();
+        let english = requested.is_empty()
+            || requested.eq_ignore_ascii_case("en")
+            || requested.eq_ignore_ascii_case("english");
+        return Ok((!english).then(|| requested.to_string()));
+    }
+
+    Ok(DocumentRepository::new(db)
+        .list_by_project(project_id, Some(doc_type))
+        .await?
+        .into_iter()
+        .next()
+        .and_then(|doc| doc.language))
 }
 
 /// Result of document generation
@@ -526,15 +687,31 @@ Use ASCII art for architectural diagrams:
 - Document clear boundaries between components
 - Include data flow diagrams where appropriate"#;
 
+/// System prompt for translating a rendered document
+const TRANSLATION_SYSTEM_PROMPT: &str = r#"You translate software project documents. Translate the prose and headings only and return the whole document as Markdown, with nothing before or after it.
+
+Keep unchanged:
+- Markdown structure, tables and links
+- Code blocks, including Mermaid diagrams
+- Code identifiers, file paths, commands and IDs, which stay in English
+- Dates, numbers and amounts
+- Feature, phase and project names"#;
+
 // ============================================================================
 // Public API functions
 // ============================================================================
 
 /// Generate a PRD for a project
+///
+/// `language` asks the model to write the document in that language;
+/// `None` keeps the language of the previous one (see [`resolve_language`]).
+/// Stages are reported to `progress`.
 pub async fn generate_prd(
     db: &Database,
     project_id: &str,
     cost_tracker: Option<Arc<CostTracker>>,
+    language: Option<&str>,
+    progress: &Progress,
 ) -> Result<Document> {
     let config = Config::load().map_err(|e| Error::ConfigError(e.to_string()))?;
 
@@ -548,8 +725,17 @@ pub async fn generate_prd(
         .ok_or_else(|| Error::NotFound(format!("Project not found: {}", project_id)))?;
 
     let features = feature_repo.list_by_project(project_id, None).await?;
+    let language = resolve_language(db, project_id, DocumentType::Prd, language).await?;
 
-    let generator = DocumentGenerator::new(config, cost_tracker)?;
+    let cache = ResponseCache::from_config(db, &config.cache);
+    let mut generator =
+        DocumentGenerator::new(config, cost_tracker)?.with_progress(progress.clone());
+    if let Some(cache) = cache {
+        generator = generator.with_response_cache(cache);
+    }
+    if let Some(language) = &language {
+        generator = generator.with_language(language);
+    }
     let generated = generator.generate_prd(&project, &features).await?;
 
     let document = Document::new(
@@ -560,8 +746,10 @@ pub async fn generate_prd(
     )
     .with_model(generated.model)
     .with_tokens(generated.tokens_used as i32)
-    .with_cost(generated.cost_usd);
+    .with_cost(generated.cost_usd)
+    .with_language(language.as_deref());
 
+    progress.stage(Stage::Write, "Saving document");
     doc_repo.create(&document).await?;
 
     info!(document_id = %document.id, "PRD created successfully");
@@ -570,10 +758,16 @@ pub async fn generate_prd(
 }
 
 /// Generate an architecture document for a project
+///
+/// `language` asks the model to write the document in that language;
+/// `None` keeps the language of the previous one (see [`resolve_language`]).
+/// Stages are reported to `progress`.
 pub async fn generate_architecture(
     db: &Database,
     project_id: &str,
     cost_tracker: Option<Arc<CostTracker>>,
+    language: Option<&str>,
+    progress: &Progress,
 ) -> Result<Document> {
     let config = Config::load().map_err(|e| Error::ConfigError(e.to_string()))?;
 
@@ -587,8 +781,17 @@ pub async fn generate_architecture(
         .ok_or_else(|| Error::NotFound(format!("Project not found: {}", project_id)))?;
 
     let features = feature_repo.list_by_project(project_id, None).await?;
+    let language = resolve_language(db, project_id, DocumentType::Architecture, language).await?;
 
-    let generator = DocumentGenerator::new(config, cost_tracker)?;
+    let cache = ResponseCache::from_config(db, &config.cache);
+    let mut generator =
+        DocumentGenerator::new(config, cost_tracker)?.with_progress(progress.clone());
+    if let Some(cache) = cache {
+        generator = generator.with_response_cache(cache);
+    }
+    if let Some(language) = &language {
+        generator = generator.with_language(language);
+    }
     let generated = generator.generate_architecture(&project, &features).await?;
 
     let document = Document::new(
@@ -599,8 +802,10 @@ pub async fn generate_architecture(
     )
     .with_model(generated.model)
     .with_tokens(generated.tokens_used as i32)
-    .with_cost(generated.cost_usd);
+    .with_cost(generated.cost_usd)
+    .with_language(language.as_deref());
 
+    progress.stage(Stage::Write, "Saving document");
     doc_repo.create(&document).await?;
 
     info!(document_id = %document.id, "Architecture document created successfully");
@@ -643,6 +848,62 @@ pub async fn update_document_status(
     doc_repo.update_status(document_id, status).await
 }
 
+/// Replace a document's content and record it as a new revision
+///
+/// The outgoing content is kept as a version first if the document has no
+/// version history yet, so the edit can always be compared or undone.
+pub async fn revise_document(
+    db: &Database,
+    document_id: &str,
+    content: impl Into<String>,
+    change_summary: Option<String>,
+) -> Result<Document> {
+    let doc_repo = DocumentRepository::new(db);
+
+    let mut document = doc_repo
+        .get(document_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Document not found: {}", document_id)))?;
+
+    if doc_repo.get_versions(document_id).await?.is_empty() {
+        doc_repo
+            .save_version(&DocumentVersion {
+                id: Uuid::new_v4().to_string(),
+                document_id: document.id.clone(),
+                version_number: document.version,
+                content: document.content.clone(),
+                change_summary: None,
+                model_used: document.model_used.clone(),
+                created_at: document.updated_at,
+            })
+            .await?;
+    }
+
+    document.content = content.into();
+    document.version += 1;
+    document.updated_at = Utc::now();
+    doc_repo.update(&document).await?;
+    doc_repo
+        .save_version(&DocumentVersion {
+            id: Uuid::new_v4().to_string(),
+            document_id: document.id.clone(),
+            version_number: document.version,
+            content: document.content.clone(),
+            change_summary,
+            model_used: None,
+            created_at: document.updated_at,
+        })
+        .await?;
+
+    info!(
+        document_id = %document_id,
+        version = document.version,
+        "Document revised"
+    );
+
+    Ok(document)
+}
+
 /// Delete a document
 pub async fn delete_document(db: &Database, document_id: &str) -> Result<()> {
     let doc_repo = DocumentRepository::new(db);
@@ -698,6 +959,8 @@ mod tests {
             DocumentType::parse("tech_spec"),
             Some(DocumentType::TechSpec)
         );
+        assert_eq!(DocumentType::parse("roadmap"), Some(DocumentType::Roadmap));
+        assert_eq!(DocumentType::parse("digest"), Some(DocumentType::Digest));
         assert_eq!(DocumentType::parse("custom"), Some(DocumentType::Custom));
         assert_eq!(DocumentType::parse("invalid"), None);
     }
@@ -837,4 +1100,80 @@ mod tests {
         assert_eq!(versions.len(), 1);
         assert_eq!(versions[0].version_number, 1);
     }
+
+    #[tokio::test]
+    async fn test_revise_document() {
+        let db = Database::in_memory()
+            .await
+            .expect("Failed to create database");
+
+        let project = Project::new("test-project", "rust", "");
+        ProjectRepository::new(&db).create(&project).await.unwrap();
+        let doc_repo = DocumentRepository::new(&db);
+        let doc = Document::new(&project.id, DocumentType::Prd, "Test PRD", "# V1");
+        doc_repo.create(&doc).await.unwrap();
+
+        let revised = revise_document(&db, &doc.id, "# V2", Some("Edited".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(revised.version, 2);
+        revise_document(&db, &doc.id, "# V3", None).await.unwrap();
+
+        let stored = doc_repo.get(&doc.id).await.unwrap().unwrap();
+        assert_eq!(stored.content, "# V3");
+        assert_eq!(stored.version, 3);
+
+        let versions = doc_repo.get_versions(&doc.id).await.unwrap();
+        let numbers: Vec<i32> = versions.iter().map(|v| v.version_number).collect();
+        assert_eq!(numbers, vec![3, 2, 1]);
+        assert_eq!(versions[2].content, "# V1");
+        assert_eq!(versions[1].change_summary, Some("Edited".to_string()));
+
+        assert!(revise_document(&db, "missing", "x", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_language_keeps_previous() {
+        let db = Database::in_memory()
+            .await
+            .expect("Failed to create database");
+
+        let project = Project::new("test-project", "rust", "");
+        ProjectRepository::new(&db).create(&project).await.unwrap();
+        let prd = DocumentType::Prd;
+        assert_eq!(
+            resolve_language(&db, &project.id, prd, None).await.unwrap(),
+            None
+        );
+
+        let doc = Document::new(&project.id, prd, "PRD", "# Produkt").with_language(Some("de"));
+        DocumentRepository::new(&db).create(&doc).await.unwrap();
+        let stored = get_document(&db, &doc.id).await.unwrap().unwrap();
+        assert_eq!(stored.language.as_deref(), Some("de"));
+
+        assert_eq!(
+            resolve_language(&db, &project.id, prd, None).await.unwrap(),
+            Some("de".to_string())
+        );
+        assert_eq!(
+            resolve_language(&db, &project.id, prd, Some("ja"))
+                .await
+                .unwrap(),
+            Some("ja".to_string())
+        );
+        assert_eq!(
+            resolve_language(&db, &project.id, prd, Some("en"))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            resolve_language(&db, &project.id, DocumentType::Architecture, None)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(language_name("pt-BR"), "Brazilian Portuguese");
+        assert_eq!(language_name("Klingon"), "Klingon");
+    }
 }
@@ -0,0 +1,141 @@
+//! Long text input through the user's editor
+//!
+//! `--edit` flags and `demiarch documents edit` open `$VISUAL` or `$EDITOR`
+//! on a temporary file and read it back when the editor exits. Buffers with
+//! fields start with a YAML front-matter block between `---` lines; the rest
+//! of the file is the body.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::{Error, Result};
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Delimiter around the front-matter block
+const FRONT_MATTER_DELIMITER: &str = "---";
+
+/// The editor command: `$VISUAL`, then `$EDITOR`, then a platform default
+///
+/// The command may carry arguments, e.g. `code --wait`.
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
+
+/// Open `initial` in the editor and return the saved text
+///
+/// `extension` picks the file type the editor highlights, e.g. `md`.
+/// Fails if the editor exits unsuccessfully; the text is returned as saved,
+/// even if unchanged.
+pub fn edit_text(initial: &str, extension: &str) -> Result<String> {
+    let path = std::env::temp_dir().join(format!(
+        "demiarch-edit-{}.{}",
+        uuid::Uuid::new_v4().simple(),
+        extension
+    ));
+    std::fs::write(&path, initial)?;
+    let result = run_editor(&editor_command(), &path)
+        .and_then(|()| std::fs::read_to_string(&path).map_err(Error::Io));
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+/// Run `editor` on `path` and wait for it to exit
+pub(crate) fn run_editor(editor: &str, path: &Path) -> Result<()> {
+    // Go through the shell so editors configured with arguments work
+    #[cfg(windows)]
+    let status = Command::new("cmd")
+        .arg("/C")
+        .arg(format!("{} \"{}\"", editor, path.display()))
+        .status();
+    #[cfg(not(windows))]
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(path)
+        .status();
+
+    let status =
+        status.map_err(|e| Error::Other(format!("Could not start editor '{}': {}", editor, e)))?;
+    if !status.success() {
+        return Err(Error::UserCancelled);
+    }
+    Ok(())
+}
+
+/// Join YAML front-matter and a body into one buffer
+pub fn with_front_matter(front_matter: &str, body: &str) -> String {
+    format!(
+        "{delim}\n{}\n{delim}\n\n{}",
+        front_matter.trim_end(),
+        body,
+        delim = FRONT_MATTER_DELIMITER
+    )
+}
+
+/// Split a buffer into its front-matter (without delimiters) and body
+///
+/// Buffers that do not start with `---` have no front-matter.
+pub fn split_front_matter(text: &str) -> Result<(Option<&str>, &str)> {
+    let Some(rest) = text.strip_prefix(FRONT_MATTER_DELIMITER).and_then(|rest| {
+        rest.strip_prefix('\n')
+            .or_else(|| rest.strip_prefix("\r\n"))
+    }) else {
+        return Ok((None, text));
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == FRONT_MATTER_DELIMITER {
+            let front_matter = &rest[..offset];
+            let body = rest[offset + line.len()..].trim_start_matches(['\r', '\n']);
+            return Ok((Some(front_matter), body));
+        }
+        offset += line.len();
+    }
+    Err(Error::Parse(
+        "Front-matter is missing its closing `---` line".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_front_matter_round_trip() {
+        let buffer = with_front_matter("title: Logout\npriority: 2\n", "Ends the session.\n");
+        let (front_matter, body) = split_front_matter(&buffer).unwrap();
+        assert_eq!(front_matter, Some("title: Logout\npriority: 2\n"));
+        assert_eq!(body, "Ends the session.\n");
+    }
+
+    #[test]
+    fn test_split_without_front_matter() {
+        assert_eq!(
+            split_front_matter("# Heading\n---\nrule").unwrap(),
+            (None, "# Heading\n---\nrule")
+        );
+        assert!(split_front_matter("---\ntitle: x\n").is_err());
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_run_editor_reports_failure() {
+        let path = std::env::temp_dir().join("demiarch-editor-test.md");
+        assert!(run_editor("true", &path).is_ok());
+        assert!(matches!(
+            run_editor("false", &path),
+            Err(Error::UserCancelled)
+        ));
+    }
+}
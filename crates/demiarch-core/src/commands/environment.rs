@@ -0,0 +1,231 @@
+//! Environment snapshots for generations
+//!
+//! Each generation records what it ran under: the demiarch version, models,
+//! the configuration that shapes output, hashes of the prompt templates and
+//! the installed plugins. `demiarch generations env <id>` shows the snapshot,
+//! and resuming a generation under a different environment lists what
+//! changed, since its output may not match.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::commands::generate;
+use crate::commands::update::CURRENT_VERSION;
+use crate::config::Config;
+
+/// What a generation ran under
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationEnvironment {
+    /// Version of demiarch that ran the generation
+    pub demiarch_version: String,
+    /// Default model followed by the fallbacks, in order
+    pub models: Vec<String>,
+    /// Config settings that affect output, keyed by config key
+    pub config: BTreeMap<String, String>,
+    /// SHA-256 of each prompt template, keyed by template name
+    pub prompt_hashes: BTreeMap<String, String>,
+    /// Installed plugin versions, keyed by plugin ID
+    ///
+    /// `None` when the caller did not list them; core has no plugin loader.
+    #[serde(default)]
+    pub plugins: Option<BTreeMap<String, String>>,
+    /// When the snapshot was taken
+    pub captured_at: DateTime<Utc>,
+}
+
+/// A setting that differs between two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentChange {
+    /// What changed, e.g. `models` or `config.llm.temperature`
+    pub field: String,
+    /// Value in the recorded snapshot; `None` if it was not set
+    pub recorded: Option<String>,
+    /// Value now; `None` if it is no longer set
+    pub current: Option<String>,
+}
+
+/// Config keys recorded in a snapshot
+///
+/// Only settings that change what the model is asked or what is kept of its
+/// answer; credentials and UI settings are left out.
+const SNAPSHOT_KEYS: &[&str] = &[
+    "llm.temperature",
+    "llm.max_tokens",
+    "routing.preference",
+    "context.total_tokens",
+    "context.output_reserve",
+    "context.enable_compression",
+    "formatting.enabled",
+    "secrets.reference_in_generation",
+];
+
+impl GenerationEnvironment {
+    /// Snapshot the environment described by `config`
+    pub fn capture(config: &Config) -> Self {
+        let models = std::iter::once(config.llm.default_model.clone())
+            .chain(config.llm.fallback_models.iter().cloned())
+            .collect();
+        let config = SNAPSHOT_KEYS
+            .iter()
+            .filter_map(|key| Some((key.to_string(), config.get(key).ok()?)))
+            .collect();
+        let prompt_hashes = generate::prompt_templates()
+            .iter()
+            .map(|(name, template)| (name.to_string(), sha256_hex(template)))
+            .collect();
+
+        Self {
+            demiarch_version: CURRENT_VERSION.to_string(),
+            models,
+            config,
+            prompt_hashes,
+            plugins: None,
+            captured_at: Utc::now(),
+        }
+    }
+
+    /// Snapshot the current environment, using defaults if the config cannot be read
+    pub fn current() -> Self {
+        Self::capture(&Config::load().unwrap_or_default())
+    }
+
+    /// Set the installed plugins as (ID, version) pairs
+    pub fn with_plugins(
+        mut self,
+        plugins: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        self.plugins = Some(
+            plugins
+                .into_iter()
+                .map(|(id, version)| (id.into(), version.into()))
+                .collect(),
+        );
+        self
+    }
+
+    /// Settings that differ in `current`, in a stable order
+    ///
+    /// Plugins are only compared when both snapshots list them.
+    pub fn changes_from(&self, current: &Self) -> Vec<EnvironmentChange> {
+        let mut changes = Vec::new();
+        if self.demiarch_version != current.demiarch_version {
+            changes.push(EnvironmentChange {
+                field: "demiarch_version".to_string(),
+                recorded: Some(self.demiarch_version.clone()),
+                current: Some(current.demiarch_version.clone()),
+            });
+        }
+        if self.models != current.models {
+            changes.push(EnvironmentChange {
+                field: "models".to_string(),
+                recorded: Some(self.models.join(", ")),
+                current: Some(current.models.join(", ")),
+            });
+        }
+        diff_maps("config", &self.config, &current.config, &mut changes);
+        diff_maps(
+            "prompt",
+            &self.prompt_hashes,
+            &current.prompt_hashes,
+            &mut changes,
+        );
+        if let (Some(recorded), Some(current)) = (&self.plugins, &current.plugins) {
+            diff_maps("plugin", recorded, current, &mut changes);
+        }
+        changes
+    }
+}
+
+/// Push a change for every key whose value differs between two maps
+fn diff_maps(
+    prefix: &str,
+    recorded: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+    changes: &mut Vec<EnvironmentChange>,
+) {
+    let mut keys: Vec<&String> = recorded.keys().chain(current.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let (before, after) = (recorded.get(key), current.get(key));
+        if before != after {
+            changes.push(EnvironmentChange {
+                field: format!("{}.{}", prefix, key),
+                recorded: before.cloned(),
+                current: after.cloned(),
+            });
+        }
+    }
+}
+
+fn sha256_hex(text: &str) -> String {
+    hex::encode(Sha256::digest(text.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_records_models_and_prompts() {
+        let env = GenerationEnvironment::capture(&Config::default());
+        assert_eq!(env.demiarch_version, CURRENT_VERSION);
+        assert_eq!(env.models[0], Config::default().llm.default_model);
+        assert!(env.config.contains_key("llm.temperature"));
+        assert!(!env.config.keys().any(|k| k.contains("api_key")));
+        assert!(env.prompt_hashes.contains_key("generate.system"));
+        assert!(env.plugins.is_none());
+    }
+
+    #[test]
+    fn test_identical_environments_have_no_changes() {
+        let env = GenerationEnvironment::capture(&Config::default()).with_plugins([("a", "1.0")]);
+        let again = GenerationEnvironment::capture(&Config::default()).with_plugins([("a", "1.0")]);
+        assert!(env.changes_from(&again).is_empty());
+    }
+
+    #[test]
+    fn test_changes_list_what_differs() {
+        let recorded =
+            GenerationEnvironment::capture(&Config::default()).with_plugins([("lint", "1.0.0")]);
+
+        let mut config = Config::default();
+        config.llm.default_model = "openai/gpt-4o".to_string();
+        config.llm.temperature = 0.2;
+        let current = GenerationEnvironment::capture(&config)
+            .with_plugins([("lint", "1.1.0"), ("new", "0.1.0")]);
+
+        let fields: Vec<String> = recorded
+            .changes_from(&current)
+            .into_iter()
+            .map(|c| c.field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "models",
+                "config.llm.temperature",
+                "plugin.lint",
+                "plugin.new"
+            ]
+        );
+
+        let added = recorded
+            .changes_from(&current)
+            .into_iter()
+            .find(|c| c.field == "plugin.new")
+            .unwrap();
+        assert_eq!(added.recorded, None);
+        assert_eq!(added.current.as_deref(), Some("0.1.0"));
+
+        // Unlisted plugins are not reported as removed
+        let without_plugins = GenerationEnvironment::capture(&config);
+        assert!(!recorded
+            .changes_from(&without_plugins)
+            .iter()
+            .any(|c| c.field.starts_with("plugin.")));
+    }
+}
@@ -0,0 +1,342 @@
+//! Feature estimation from historical generation data
+//!
+//! `demiarch features estimate <id>` predicts the tokens, cost and wall-clock
+//! time a feature will take by comparing it with work that has already been
+//! generated. Each completed generation (or each feature, when generations
+//! were linked to one) is a sample with recorded actuals; samples are ranked
+//! by embedding similarity of their title and description to the new work,
+//! and the nearest ones are blended into an expected value with a range.
+//!
+//! The same estimate backs the confirmation `demiarch generate` asks for
+//! when a run is expected to cost more than `cost.confirm_above_usd`.
+
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::commands::feature::{Feature, FeatureRepository};
+use crate::domain::memory::{cosine_similarity, Embedder, SimpleEmbedder};
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Number of similar samples an estimate is built from
+pub const DEFAULT_NEIGHBOURS: usize = 5;
+
+/// Model name passed to the embedder
+const EMBEDDING_MODEL: &str = "text-embedding-sim";
+
+/// Work with recorded actuals
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoricalWork {
+    /// Feature the work belongs to, if the generations were linked to one
+    pub feature_id: Option<String>,
+    /// Feature title, or the generation description
+    pub title: String,
+    /// Text compared against new work
+    pub text: String,
+    pub tokens: i64,
+    pub cost_usd: f64,
+    pub duration_secs: i64,
+    /// Generations the actuals were summed over
+    pub generations: i64,
+}
+
+/// A historical sample used in an estimate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarWork {
+    pub feature_id: Option<String>,
+    pub title: String,
+    pub similarity: f32,
+    pub tokens: i64,
+    pub cost_usd: f64,
+    pub duration_secs: i64,
+}
+
+/// Expected value with a low-high range
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EstimateRange {
+    pub low: f64,
+    pub expected: f64,
+    pub high: f64,
+}
+
+impl EstimateRange {
+    /// Similarity-weighted mean, plus or minus one weighted standard deviation
+    ///
+    /// A single sample has no spread, so its range is half to one and a half
+    /// times its value.
+    fn weighted(values: &[(f64, f64)]) -> Self {
+        let total: f64 = values.iter().map(|(_, w)| w).sum();
+        if total <= 0.0 {
+            return Self {
+                low: 0.0,
+                expected: 0.0,
+                high: 0.0,
+            };
+        }
+        let mean = values.iter().map(|(v, w)| v * w).sum::<f64>() / total;
+        let spread = if values.len() < 2 {
+            mean * 0.5
+        } else {
+            (values
+                .iter()
+                .map(|(v, w)| w * (v - mean).powi(2))
+                .sum::<f64>()
+                / total)
+                .sqrt()
+        };
+        Self {
+            low: (mean - spread).max(0.0),
+            expected: mean,
+            high: mean + spread,
+        }
+    }
+}
+
+/// How much an estimate can be trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    /// Confidence from the number of samples and how much their costs agree
+    fn from_samples(samples: usize, cost: &EstimateRange) -> Self {
+        let relative_spread = if cost.expected > 0.0 {
+            (cost.high - cost.expected) / cost.expected
+        } else {
+            1.0
+        };
+        match samples {
+            0..=1 => Self::Low,
+            2 if relative_spread <= 0.5 => Self::Medium,
+            2 => Self::Low,
+            _ if relative_spread <= 0.25 => Self::High,
+            _ if relative_spread <= 0.75 => Self::Medium,
+            _ => Self::Low,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+/// Predicted tokens, cost and time for a piece of work
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureEstimate {
+    pub tokens: EstimateRange,
+    pub cost_usd: EstimateRange,
+    pub duration_secs: EstimateRange,
+    pub confidence: Confidence,
+    /// Samples the estimate is based on, most similar first
+    pub similar: Vec<SimilarWork>,
+}
+
+/// Text a feature is compared on
+pub fn feature_text(feature: &Feature) -> String {
+    match &feature.description {
+        Some(description) if !description.trim().is_empty() => {
+            format!("{}\n{}", feature.title, description)
+        }
+        _ => feature.title.clone(),
+    }
+}
+
+/// Estimate work described by `text` from the `neighbours` most similar samples
+///
+/// Returns `None` when there is no history to compare with.
+pub fn estimate(
+    text: &str,
+    history: &[HistoricalWork],
+    neighbours: usize,
+) -> Result<Option<FeatureEstimate>> {
+    if history.is_empty() || neighbours == 0 {
+        return Ok(None);
+    }
+    let embedder = SimpleEmbedder::default();
+    let embed = |text: &str| {
+        embedder
+            .embed(EMBEDDING_MODEL, &[text])
+            .map(|e| e.index)
+            .map_err(|e| Error::Other(e.to_string()))
+    };
+    let target = embed(text)?;
+
+    let mut similar = Vec::with_capacity(history.len());
+    for work in history {
+        let vector = embed(&work.text)?;
+        similar.push(SimilarWork {
+            feature_id: work.feature_id.clone(),
+            title: work.title.clone(),
+            similarity: cosine_similarity(&target, &vector),
+            tokens: work.tokens,
+            cost_usd: work.cost_usd,
+            duration_secs: work.duration_secs,
+        });
+    }
+    similar.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    similar.truncate(neighbours);
+
+    let weighted = |value: &dyn Fn(&SimilarWork) -> f64| {
+        let values: Vec<(f64, f64)> = similar
+            .iter()
+            .map(|s| (value(s), s.similarity.max(0.0) as f64))
+            .collect();
+        EstimateRange::weighted(&values)
+    };
+    let tokens = weighted(&|s| s.tokens as f64);
+    let cost_usd = weighted(&|s| s.cost_usd);
+    let duration_secs = weighted(&|s| s.duration_secs as f64);
+
+    Ok(Some(FeatureEstimate {
+        confidence: Confidence::from_samples(similar.len(), &cost_usd),
+        tokens,
+        cost_usd,
+        duration_secs,
+        similar,
+    }))
+}
+
+/// Load completed work with its actuals
+///
+/// Generations linked to a feature are summed per feature; unlinked
+/// generations are samples of their own. `exclude_feature` leaves out the
+/// feature being estimated.
+pub async fn history(
+    db: &Database,
+    project_id: Option<&str>,
+    exclude_feature: Option<&str>,
+) -> Result<Vec<HistoricalWork>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT g.feature_id,
+               f.title AS feature_title,
+               f.description AS feature_description,
+               MIN(g.description) AS description,
+               SUM(g.tokens_used) AS tokens,
+               SUM(g.cost_usd) AS cost_usd,
+               CAST(SUM((julianday(g.updated_at) - julianday(g.created_at)) * 86400) AS INTEGER)
+                   AS duration_secs,
+               COUNT(*) AS generations
+        FROM generations g
+        LEFT JOIN features f ON f.id = g.feature_id
+        WHERE g.status = 'completed'
+          AND (? IS NULL OR g.project_id = ?)
+          AND (? IS NULL OR g.feature_id IS NULL OR g.feature_id != ?)
+        GROUP BY COALESCE(g.feature_id, g.id)
+        "#,
+    )
+    .bind(project_id)
+    .bind(project_id)
+    .bind(exclude_feature)
+    .bind(exclude_feature)
+    .fetch_all(db.pool())
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let description: String = row.get("description");
+            let (title, text) = match row.get::<Option<String>, _>("feature_title") {
+                Some(title) => {
+                    let text = match row.get::<Option<String>, _>("feature_description") {
+                        Some(d) if !d.trim().is_empty() => format!("{}\n{}", title, d),
+                        _ => title.clone(),
+                    };
+                    (title, text)
+                }
+                None => (description.clone(), description),
+            };
+            HistoricalWork {
+                feature_id: row.get("feature_id"),
+                title,
+                text,
+                tokens: row.get("tokens"),
+                cost_usd: row.get("cost_usd"),
+                duration_secs: row.get::<Option<i64>, _>("duration_secs").unwrap_or(0),
+                generations: row.get("generations"),
+            }
+        })
+        .collect())
+}
+
+/// Estimate a feature from its project's history
+pub async fn for_feature(
+    db: &Database,
+    feature_id: &str,
+) -> Result<(Feature, Option<FeatureEstimate>)> {
+    let feature = FeatureRepository::new(db)
+        .get(feature_id)
+        .await?
+        .ok_or_else(|| Error::FeatureNotFound(feature_id.to_string()))?;
+    let history = history(db, Some(&feature.project_id), Some(&feature.id)).await?;
+    let estimate = estimate(&feature_text(&feature), &history, DEFAULT_NEIGHBOURS)?;
+    Ok((feature, estimate))
+}
+
+/// Estimate a generation from its description
+pub async fn for_description(
+    db: &Database,
+    project_id: Option<&str>,
+    description: &str,
+) -> Result<Option<FeatureEstimate>> {
+    let history = history(db, project_id, None).await?;
+    estimate(description, &history, DEFAULT_NEIGHBOURS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn work(title: &str, tokens: i64, cost_usd: f64) -> HistoricalWork {
+        HistoricalWork {
+            feature_id: None,
+            title: title.to_string(),
+            text: title.to_string(),
+            tokens,
+            cost_usd,
+            duration_secs: 60,
+            generations: 1,
+        }
+    }
+
+    #[test]
+    fn test_estimate_without_history_is_none() {
+        assert!(estimate("Add login", &[], DEFAULT_NEIGHBOURS)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_estimate_ranks_by_similarity_and_brackets_actuals() {
+        let history = vec![
+            work("Billing invoices export to PDF", 9_000, 0.09),
+            work("Add login form", 1_000, 0.01),
+            work("Add login page", 1_200, 0.012),
+        ];
+        let estimate = estimate("Add login form", &history, 2).unwrap().unwrap();
+
+        assert_eq!(estimate.similar.len(), 2);
+        assert_eq!(estimate.similar[0].title, "Add login form");
+        assert!(estimate.tokens.low <= estimate.tokens.expected);
+        assert!(estimate.tokens.expected <= estimate.tokens.high);
+        assert!(estimate.tokens.expected >= 1_000.0 && estimate.tokens.expected <= 1_200.0);
+    }
+
+    #[test]
+    fn test_single_sample_has_low_confidence_and_wide_range() {
+        let estimate = estimate("x", &[work("x", 1_000, 0.02)], DEFAULT_NEIGHBOURS)
+            .unwrap()
+            .unwrap();
+        assert_eq!(estimate.confidence, Confidence::Low);
+        assert!((estimate.cost_usd.low - 0.01).abs() < 1e-9);
+        assert!((estimate.cost_usd.high - 0.03).abs() < 1e-9);
+    }
+}
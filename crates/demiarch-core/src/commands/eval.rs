@@ -0,0 +1,575 @@
+//! A/B evaluation of models on a generation task
+//!
+//! `demiarch eval run --task task.yml --models a,b --repeat 3` runs the same
+//! generation task against several models, captures each run's files,
+//! tokens, cost, review findings and (optionally) whether the task's test
+//! command passes, and aggregates the runs into a comparison report.
+//!
+//! Runs are dry runs: generated files are written into the evaluation's
+//! output directory, one subdirectory per run, never into the project. With
+//! replay on, model responses are recorded in the response cache and a
+//! repeated evaluation replays them, so a report can be reproduced without
+//! calling the models again.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::commands::generate::{CodeGenerator, GenerationResult};
+use crate::config::Config;
+use crate::cost::CostTracker;
+use crate::llm::ResponseCache;
+use crate::progress::{Progress, Stage};
+use crate::{Error, Result};
+
+/// Default time a task's test command may run
+pub const DEFAULT_TEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A generation task to evaluate, loaded from YAML, TOML or JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvalTask {
+    /// Short name used in reports
+    pub name: String,
+    /// Description passed to the code generator
+    pub description: String,
+    /// Project framework, used to pick formatters and linters
+    #[serde(default)]
+    pub framework: Option<String>,
+    /// Shell command run in each run's directory; exit status 0 is a pass
+    #[serde(default)]
+    pub test_command: Option<String>,
+    /// Seconds the test command may run
+    #[serde(default)]
+    pub test_timeout_secs: Option<u64>,
+}
+
+impl EvalTask {
+    /// Load a task file; the format is chosen by extension (YAML by default)
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path).map_err(Error::Io)?;
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let task = match extension.as_str() {
+            "toml" => Self::from_toml(&source)?,
+            "json" => serde_json::from_str(&source).map_err(|e| Error::Parse(format!("{}", e)))?,
+            _ => Self::from_yaml(&source)?,
+        };
+        task.validate()?;
+        Ok(task)
+    }
+
+    /// Parse a YAML task
+    pub fn from_yaml(source: &str) -> Result<Self> {
+        serde_yaml::from_str(source).map_err(|e| Error::Parse(format!("task file: {}", e)))
+    }
+
+    /// Parse a TOML task
+    pub fn from_toml(source: &str) -> Result<Self> {
+        toml::from_str(source).map_err(|e| Error::Parse(format!("task file: {}", e)))
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Error::InvalidInput("Eval task needs a name".to_string()));
+        }
+        if self.description.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Eval task needs a description".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn test_timeout(&self) -> Duration {
+        self.test_timeout_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_TEST_TIMEOUT)
+    }
+}
+
+/// Split a comma-separated model list, dropping blanks and duplicates
+pub fn parse_models(input: &str) -> Vec<String> {
+    let mut models: Vec<String> = Vec::new();
+    for model in input.split(',').map(str::trim).filter(|m| !m.is_empty()) {
+        if !models.iter().any(|m| m == model) {
+            models.push(model.to_string());
+        }
+    }
+    models
+}
+
+/// Outcome of one model run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalRun {
+    /// Model the run used
+    pub model: String,
+    /// Repeat number, starting at 1
+    pub repeat: u32,
+    /// Directory the run's files were written to
+    pub output_dir: PathBuf,
+    /// Files the model produced
+    pub files: usize,
+    /// Files rejected for syntax errors after repair
+    pub rejected_files: usize,
+    /// Lint findings left after auto-fixes
+    pub lint_findings: usize,
+    /// Tokens used
+    pub tokens: u32,
+    /// Cost in USD
+    pub cost_usd: f64,
+    /// Wall-clock time of the generation
+    pub duration_ms: u64,
+    /// Whether the test command passed; `None` without a test command
+    pub tests_passed: Option<bool>,
+    /// Generation error, if the run failed
+    pub error: Option<String>,
+}
+
+impl EvalRun {
+    fn failed(model: &str, repeat: u32, output_dir: PathBuf, error: String) -> Self {
+        Self {
+            model: model.to_string(),
+            repeat,
+            output_dir,
+            files: 0,
+            rejected_files: 0,
+            lint_findings: 0,
+            tokens: 0,
+            cost_usd: 0.0,
+            duration_ms: 0,
+            tests_passed: None,
+            error: Some(error),
+        }
+    }
+
+    fn from_result(
+        model: &str,
+        repeat: u32,
+        output_dir: PathBuf,
+        result: &GenerationResult,
+        elapsed: Duration,
+    ) -> Self {
+        Self {
+            model: model.to_string(),
+            repeat,
+            output_dir,
+            files: result.files.len(),
+            rejected_files: result.rejected_files().count(),
+            lint_findings: result.lint_findings().count(),
+            tokens: result.tokens_used,
+            cost_usd: result.cost_usd,
+            duration_ms: elapsed.as_millis() as u64,
+            tests_passed: None,
+            error: None,
+        }
+    }
+
+    /// Whether generation succeeded
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Aggregate of one model's runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelSummary {
+    /// Model
+    pub model: String,
+    /// Number of runs
+    pub runs: usize,
+    /// Runs where generation failed
+    pub failed_runs: usize,
+    /// Average files per successful run
+    pub avg_files: f64,
+    /// Average rejected files per successful run
+    pub avg_rejected_files: f64,
+    /// Average lint findings per successful run
+    pub avg_lint_findings: f64,
+    /// Average tokens per successful run
+    pub avg_tokens: f64,
+    /// Total cost of all runs in USD
+    pub total_cost_usd: f64,
+    /// Average generation time per successful run
+    pub avg_duration_ms: f64,
+    /// Share of tested runs whose tests passed; `None` if nothing was tested
+    pub test_pass_rate: Option<f64>,
+}
+
+impl ModelSummary {
+    fn from_runs(model: &str, runs: &[&EvalRun]) -> Self {
+        let ok: Vec<&&EvalRun> = runs.iter().filter(|r| r.succeeded()).collect();
+        let avg = |value: &dyn Fn(&EvalRun) -> f64| {
+            if ok.is_empty() {
+                0.0
+            } else {
+                ok.iter().map(|r| value(r)).sum::<f64>() / ok.len() as f64
+            }
+        };
+        let tested: Vec<bool> = runs.iter().filter_map(|r| r.tests_passed).collect();
+        let test_pass_rate = if tested.is_empty() {
+            None
+        } else {
+            Some(tested.iter().filter(|p| **p).count() as f64 / tested.len() as f64)
+        };
+
+        Self {
+            model: model.to_string(),
+            runs: runs.len(),
+            failed_runs: runs.len() - ok.len(),
+            avg_files: avg(&|r| r.files as f64),
+            avg_rejected_files: avg(&|r| r.rejected_files as f64),
+            avg_lint_findings: avg(&|r| r.lint_findings as f64),
+            avg_tokens: avg(&|r| r.tokens as f64),
+            total_cost_usd: runs.iter().map(|r| r.cost_usd).sum(),
+            avg_duration_ms: avg(&|r| r.duration_ms as f64),
+            test_pass_rate,
+        }
+    }
+}
+
+/// Comparison report for an evaluation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalReport {
+    /// Task that was evaluated
+    pub task: EvalTask,
+    /// Models in the order they were given
+    pub models: Vec<String>,
+    /// Runs per model
+    pub repeat: u32,
+    /// Whether responses went through the response cache
+    pub cached: bool,
+    /// Directory holding the runs and the report
+    pub output_dir: PathBuf,
+    /// When the evaluation started
+    pub started_at: DateTime<Utc>,
+    /// Every run, grouped by model
+    pub runs: Vec<EvalRun>,
+    /// Per-model aggregates, in model order
+    pub summaries: Vec<ModelSummary>,
+}
+
+impl EvalReport {
+    fn summarize(&mut self) {
+        self.summaries = self
+            .models
+            .iter()
+            .map(|model| {
+                let runs: Vec<&EvalRun> = self.runs.iter().filter(|r| &r.model == model).collect();
+                ModelSummary::from_runs(model, &runs)
+            })
+            .collect();
+    }
+
+    /// Write the report as `report.json` and `report.md` in the output directory
+    pub fn save(&self) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|e| Error::Parse(format!("{}", e)))?;
+        std::fs::write(self.output_dir.join("report.json"), json).map_err(Error::Io)?;
+        std::fs::write(self.output_dir.join("report.md"), self.to_markdown()).map_err(Error::Io)?;
+        Ok(())
+    }
+
+    /// Render the comparison as a Markdown table
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Eval: {}\n\n", self.task.name);
+        out.push_str(&format!(
+            "{} run(s) per model, started {}{}\n\n",
+            self.repeat,
+            self.started_at.format("%Y-%m-%d %H:%M UTC"),
+            if self.cached {
+                ", responses cached"
+            } else {
+                ""
+            }
+        ));
+        out.push_str(
+            "| Model | Runs | Failed | Files | Rejected | Lint | Tokens | Cost (USD) | Time (s) | Tests |\n",
+        );
+        out.push_str("|---|---|---|---|---|---|---|---|---|---|\n");
+        for s in &self.summaries {
+            out.push_str(&format!(
+                "| {} | {} | {} | {:.1} | {:.1} | {:.1} | {:.0} | {:.4} | {:.1} | {} |\n",
+                s.model,
+                s.runs,
+                s.failed_runs,
+                s.avg_files,
+                s.avg_rejected_files,
+                s.avg_lint_findings,
+                s.avg_tokens,
+                s.total_cost_usd,
+                s.avg_duration_ms / 1000.0,
+                s.test_pass_rate
+                    .map(|r| format!("{:.0}%", r * 100.0))
+                    .unwrap_or_else(|| "-".to_string()),
+            ));
+        }
+
+        let errors: Vec<&EvalRun> = self.runs.iter().filter(|r| !r.succeeded()).collect();
+        if !errors.is_empty() {
+            out.push_str("\n## Failed runs\n\n");
+            for run in errors {
+                out.push_str(&format!(
+                    "- {} #{}: {}\n",
+                    run.model,
+                    run.repeat,
+                    run.error.as_deref().unwrap_or_default()
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Options for an evaluation
+#[derive(Debug, Clone)]
+pub struct EvalOptions {
+    /// Models to compare
+    pub models: Vec<String>,
+    /// Runs per model
+    pub repeat: u32,
+    /// Directory for run files and the report
+    pub output_dir: PathBuf,
+    /// Record and replay model responses through the response cache
+    pub cache: Option<ResponseCache>,
+}
+
+/// Default output directory for an evaluation started now
+pub fn default_output_dir(task: &EvalTask) -> PathBuf {
+    let slug: String = task
+        .name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    PathBuf::from(".demiarch").join("evals").join(format!(
+        "{}-{}",
+        slug,
+        Utc::now().format("%Y%m%d-%H%M%S")
+    ))
+}
+
+/// Run a task against every model `repeat` times and build the report
+pub async fn run(
+    config: &Config,
+    task: &EvalTask,
+    options: EvalOptions,
+    progress: &Progress,
+) -> Result<EvalReport> {
+    if options.models.is_empty() {
+        return Err(Error::InvalidInput(
+            "At least one model is required".to_string(),
+        ));
+    }
+    if options.repeat == 0 {
+        return Err(Error::InvalidInput("Repeat must be at least 1".to_string()));
+    }
+    std::fs::create_dir_all(&options.output_dir).map_err(Error::Io)?;
+
+    let mut report = EvalReport {
+        task: task.clone(),
+        models: options.models.clone(),
+        repeat: options.repeat,
+        cached: options.cache.is_some(),
+        output_dir: options.output_dir.clone(),
+        started_at: Utc::now(),
+        runs: Vec::new(),
+        summaries: Vec::new(),
+    };
+
+    let total = options.models.len() as u64 * options.repeat as u64;
+    for model in &options.models {
+        for repeat in 1..=options.repeat {
+            progress.stage(
+                Stage::Code,
+                format!("{} #{}/{}", model, repeat, options.repeat),
+            );
+            progress.advance(report.runs.len() as u64, total);
+
+            let run_dir = options
+                .output_dir
+                .join(model.replace(['/', ':'], "_"))
+                .join(repeat.to_string());
+            let run = run_once(config, task, model, repeat, run_dir, options.cache.clone()).await;
+            info!(model = %model, repeat, ok = run.succeeded(), "Eval run finished");
+            report.runs.push(run);
+        }
+    }
+    progress.advance(total, total);
+
+    report.summarize();
+    report.save()?;
+    Ok(report)
+}
+
+async fn run_once(
+    config: &Config,
+    task: &EvalTask,
+    model: &str,
+    repeat: u32,
+    run_dir: PathBuf,
+    cache: Option<ResponseCache>,
+) -> EvalRun {
+    // Pin the model; fallbacks would blur which model produced the output
+    let mut config = config.clone();
+    config.llm.default_model = model.to_string();
+    config.llm.fallback_models.clear();
+    let tracker = Arc::new(CostTracker::from_config(&config.cost));
+
+    let generator = match CodeGenerator::new(config, Some(tracker)) {
+        Ok(generator) => generator,
+        Err(e) => return EvalRun::failed(model, repeat, run_dir, e.to_string()),
+    };
+    let mut generator = match &task.framework {
+        Some(framework) => generator.with_framework(framework),
+        None => generator,
+    };
+    if let Some(cache) = cache {
+        generator = generator.with_response_cache(cache);
+    }
+
+    let started = Instant::now();
+    let result = match generator.generate(&task.description, true).await {
+        Ok(result) => result,
+        Err(e) => return EvalRun::failed(model, repeat, run_dir, e.to_string()),
+    };
+    let mut run = EvalRun::from_result(model, repeat, run_dir, &result, started.elapsed());
+
+    if let Err(e) = write_run_files(&run.output_dir, &result) {
+        run.error = Some(format!("Failed to write run files: {}", e));
+        return run;
+    }
+    if let Some(command) = &task.test_command {
+        run.tests_passed = Some(run_tests(command, &run.output_dir, task.test_timeout()).await);
+    }
+    run
+}
+
+/// Write a run's accepted files under its directory
+fn write_run_files(dir: &Path, result: &GenerationResult) -> Result<()> {
+    std::fs::create_dir_all(dir).map_err(Error::Io)?;
+    for file in result.files.iter().filter(|f| !f.has_syntax_errors()) {
+        let path = dir.join(relative_path(&file.path));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(Error::Io)?;
+        }
+        std::fs::write(path, &file.content).map_err(Error::Io)?;
+    }
+    Ok(())
+}
+
+/// Strip roots and `..` so a generated path stays inside the run directory
+fn relative_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter_map(|c| match c {
+            std::path::Component::Normal(part) => Some(part),
+            _ => None,
+        })
+        .collect()
+}
+
+async fn run_tests(command: &str, dir: &Path, timeout: Duration) -> bool {
+    let child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .status();
+
+    match tokio::time::timeout(timeout, child).await {
+        Ok(Ok(status)) => status.success(),
+        Ok(Err(e)) => {
+            warn!(error = %e, "Failed to run eval test command");
+            false
+        }
+        Err(_) => {
+            warn!(command = %command, "Eval test command timed out");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(model: &str, tokens: u32, tests_passed: Option<bool>) -> EvalRun {
+        EvalRun {
+            model: model.to_string(),
+            repeat: 1,
+            output_dir: PathBuf::from("out"),
+            files: 2,
+            rejected_files: 0,
+            lint_findings: 1,
+            tokens,
+            cost_usd: 0.01,
+            duration_ms: 1000,
+            tests_passed,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_task_from_yaml_and_toml() {
+        let yaml = EvalTask::from_yaml(
+            "name: todo\ndescription: Build a todo API\nframework: rust\ntest_command: cargo test\n",
+        )
+        .unwrap();
+        assert_eq!(yaml.framework.as_deref(), Some("rust"));
+        assert_eq!(yaml.test_command.as_deref(), Some("cargo test"));
+
+        let toml =
+            EvalTask::from_toml("name = \"todo\"\ndescription = \"Build a todo API\"\n").unwrap();
+        assert_eq!(toml.name, "todo");
+        assert!(toml.test_command.is_none());
+        assert_eq!(toml.test_timeout(), DEFAULT_TEST_TIMEOUT);
+    }
+
+    #[test]
+    fn test_parse_models() {
+        assert_eq!(
+            parse_models(" a/b , c,,a/b "),
+            vec!["a/b".to_string(), "c".to_string()]
+        );
+        assert!(parse_models(" , ").is_empty());
+    }
+
+    #[test]
+    fn test_summary_aggregates_runs() {
+        let ok = run("a", 100, Some(true));
+        let other = run("a", 300, Some(false));
+        let failed = EvalRun::failed("a", 3, PathBuf::from("out"), "boom".to_string());
+        let summary = ModelSummary::from_runs("a", &[&ok, &other, &failed]);
+
+        assert_eq!(summary.runs, 3);
+        assert_eq!(summary.failed_runs, 1);
+        assert_eq!(summary.avg_tokens, 200.0);
+        assert_eq!(summary.test_pass_rate, Some(0.5));
+        assert!((summary.total_cost_usd - 0.02).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_relative_path_stays_inside_run_dir() {
+        assert_eq!(
+            relative_path(Path::new("/etc/../src/main.rs")),
+            PathBuf::from("etc/src/main.rs")
+        );
+        assert_eq!(
+            relative_path(Path::new("./a/b.rs")),
+            PathBuf::from("a/b.rs")
+        );
+    }
+}
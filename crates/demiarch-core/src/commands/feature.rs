@@ -2,6 +2,9 @@
 //!
 //! Provides CRUD operations for project features.
 
+use crate::commands::editor;
+use crate::events::{self, CoreEvent};
+use crate::pagination::{Page, PageRequest};
 use crate::storage::Database;
 use crate::Result;
 use chrono::{DateTime, Utc};
@@ -67,6 +70,8 @@ pub struct Feature {
     pub status: FeatureStatus,
     /// Priority (1-5, where 1 is highest)
     pub priority: i32,
+    /// Git branch the feature is developed on
+    pub branch: Option<String>,
     /// When the feature was created
     pub created_at: DateTime<Utc>,
     /// When the feature was last updated
@@ -87,6 +92,7 @@ impl Feature {
             phase_id: None,
             status: FeatureStatus::Backlog,
             priority: 3, // Default to medium priority (1-5 scale)
+            branch: None,
             created_at: now,
             updated_at: now,
         }
@@ -129,6 +135,112 @@ impl Feature {
     }
 }
 
+/// Instructions at the top of a feature's editor buffer
+const DRAFT_HELP: &str = "\
+# Edit the fields below and write the description after the closing ---.
+# Save and close the editor to continue; clear the title to cancel.
+# status: backlog, todo, in_progress, review or done. priority: 1 (highest) to 5.
+";
+
+/// A feature as edited in `$EDITOR`
+///
+/// Fields are YAML front-matter; the body is the description.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureDraft {
+    pub title: String,
+    #[serde(default = "FeatureDraft::default_status")]
+    pub status: String,
+    #[serde(default = "FeatureDraft::default_priority")]
+    pub priority: i32,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Phase ID
+    #[serde(default)]
+    pub phase: Option<String>,
+    #[serde(skip)]
+    pub description: String,
+}
+
+impl FeatureDraft {
+    fn default_status() -> String {
+        FeatureStatus::default().as_str().to_string()
+    }
+
+    fn default_priority() -> i32 {
+        Feature::new("", "").priority
+    }
+
+    /// A blank draft for a new feature
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            status: Self::default_status(),
+            priority: Self::default_priority(),
+            labels: Vec::new(),
+            phase: None,
+            description: String::new(),
+        }
+    }
+
+    /// A draft holding a feature's current fields
+    pub fn from_feature(feature: &Feature) -> Self {
+        Self {
+            title: feature.title.clone(),
+            status: feature.status.as_str().to_string(),
+            priority: feature.priority,
+            labels: feature.labels.clone().unwrap_or_default(),
+            phase: feature.phase_id.clone(),
+            description: feature.description.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Render as an editor buffer
+    pub fn to_buffer(&self) -> Result<String> {
+        let fields = serde_yaml::to_string(self)
+            .map_err(|e| crate::Error::Parse(format!("Failed to render feature: {}", e)))?;
+        Ok(editor::with_front_matter(
+            &format!("{}{}", DRAFT_HELP, fields),
+            &self.description,
+        ))
+    }
+
+    /// Parse an edited buffer
+    ///
+    /// Fails with [`crate::Error::UserCancelled`] when the title was cleared.
+    pub fn parse(buffer: &str) -> Result<Self> {
+        let (fields, body) = editor::split_front_matter(buffer)?;
+        let fields = fields.ok_or_else(|| {
+            crate::Error::Parse("The feature's front-matter block is missing".to_string())
+        })?;
+        let mut draft: Self = serde_yaml::from_str(fields)
+            .map_err(|e| crate::Error::Parse(format!("Invalid feature fields: {}", e)))?;
+        draft.title = draft.title.trim().to_string();
+        if draft.title.is_empty() {
+            return Err(crate::Error::UserCancelled);
+        }
+        if FeatureStatus::parse(&draft.status).is_none() {
+            return Err(crate::Error::InvalidInput(format!(
+                "Invalid feature status: {}",
+                draft.status
+            )));
+        }
+        draft.priority = draft.priority.clamp(1, 5);
+        draft.phase = draft.phase.filter(|p| !p.trim().is_empty());
+        draft.description = body.trim().to_string();
+        Ok(draft)
+    }
+
+    /// Copy the draft's fields onto `feature`
+    pub fn apply_to(&self, feature: &mut Feature) {
+        feature.title = self.title.clone();
+        feature.status = FeatureStatus::parse(&self.status).unwrap_or_default();
+        feature.priority = self.priority.clamp(1, 5);
+        feature.labels = (!self.labels.is_empty()).then(|| self.labels.clone());
+        feature.phase_id = self.phase.clone();
+        feature.description = (!self.description.is_empty()).then(|| self.description.clone());
+    }
+}
+
 /// Feature repository for database operations
 pub struct FeatureRepository<'a> {
     db: &'a Database,
@@ -173,7 +285,7 @@ impl<'a> FeatureRepository<'a> {
     /// Get a feature by ID
     pub async fn get(&self, id: &str) -> Result<Option<Feature>> {
         let row = sqlx::query(
-            "SELECT id, project_id, title, description, acceptance_criteria, labels, phase_id, status, priority, created_at, updated_at FROM features WHERE id = ?",
+            "SELECT id, project_id, title, description, acceptance_criteria, labels, phase_id, status, priority, branch, created_at, updated_at FROM features WHERE id = ?",
         )
         .bind(id)
         .fetch_optional(self.db.pool())
@@ -190,7 +302,7 @@ impl<'a> FeatureRepository<'a> {
     ) -> Result<Vec<Feature>> {
         let rows = if let Some(status) = status {
             sqlx::query(
-                "SELECT id, project_id, title, description, acceptance_criteria, labels, phase_id, status, priority, created_at, updated_at FROM features WHERE project_id = ? AND status = ? ORDER BY priority, created_at",
+                "SELECT id, project_id, title, description, acceptance_criteria, labels, phase_id, status, priority, branch, created_at, updated_at FROM features WHERE project_id = ? AND status = ? ORDER BY priority, created_at",
             )
             .bind(project_id)
             .bind(status.as_str())
@@ -198,7 +310,7 @@ impl<'a> FeatureRepository<'a> {
             .await?
         } else {
             sqlx::query(
-                "SELECT id, project_id, title, description, acceptance_criteria, labels, phase_id, status, priority, created_at, updated_at FROM features WHERE project_id = ? ORDER BY priority, created_at",
+                "SELECT id, project_id, title, description, acceptance_criteria, labels, phase_id, status, priority, branch, created_at, updated_at FROM features WHERE project_id = ? ORDER BY priority, created_at",
             )
             .bind(project_id)
             .fetch_all(self.db.pool())
@@ -208,10 +320,49 @@ impl<'a> FeatureRepository<'a> {
         Ok(rows.into_iter().map(|r| self.row_to_feature(r)).collect())
     }
 
+    /// List one page of a project's features, with the total matching count
+    pub async fn list_by_project_page(
+        &self,
+        project_id: &str,
+        status: Option<FeatureStatus>,
+        page: PageRequest,
+    ) -> Result<Page<Feature>> {
+        let filter = if status.is_some() {
+            "project_id = ? AND status = ?"
+        } else {
+            "project_id = ?"
+        };
+
+        let count_sql = format!("SELECT COUNT(*) FROM features WHERE {}", filter);
+        let rows_sql = format!(
+            "SELECT id, project_id, title, description, acceptance_criteria, labels, phase_id, status, priority, branch, created_at, updated_at FROM features WHERE {} ORDER BY priority, created_at LIMIT ? OFFSET ?",
+            filter
+        );
+        let mut count = sqlx::query_as::<_, (i64,)>(&count_sql).bind(project_id);
+        let mut rows = sqlx::query(&rows_sql).bind(project_id);
+        if let Some(status) = status {
+            count = count.bind(status.as_str());
+            rows = rows.bind(status.as_str());
+        }
+
+        let (total,) = count.fetch_one(self.db.pool()).await?;
+        let rows = rows
+            .bind(page.sql_limit())
+            .bind(page.sql_offset())
+            .fetch_all(self.db.pool())
+            .await?;
+
+        Ok(Page::new(
+            rows.into_iter().map(|r| self.row_to_feature(r)).collect(),
+            total as usize,
+            page,
+        ))
+    }
+
     /// List features for a phase
     pub async fn list_by_phase(&self, phase_id: &str) -> Result<Vec<Feature>> {
         let rows = sqlx::query(
-            "SELECT id, project_id, title, description, acceptance_criteria, labels, phase_id, status, priority, created_at, updated_at FROM features WHERE phase_id = ? ORDER BY priority, created_at",
+            "SELECT id, project_id, title, description, acceptance_criteria, labels, phase_id, status, priority, branch, created_at, updated_at FROM features WHERE phase_id = ? ORDER BY priority, created_at",
         )
         .bind(phase_id)
         .fetch_all(self.db.pool())
@@ -230,7 +381,8 @@ impl<'a> FeatureRepository<'a> {
         sqlx::query(
             r#"
             UPDATE features
-            SET title = ?, description = ?, acceptance_criteria = ?, labels = ?, phase_id = ?, status = ?, priority = ?, updated_at = ?
+            SET title = ?, description = ?, acceptance_criteria = ?, labels = ?, phase_id = ?, status = ?, priority = ?, updated_at = ?,
+                completed_at = CASE WHEN ? = 'done' THEN COALESCE(completed_at, ?) ELSE NULL END
             WHERE id = ?
             "#,
         )
@@ -242,6 +394,8 @@ impl<'a> FeatureRepository<'a> {
         .bind(feature.status.as_str())
         .bind(feature.priority)
         .bind(Utc::now())
+        .bind(feature.status.as_str())
+        .bind(Utc::now())
         .bind(&feature.id)
         .execute(self.db.pool())
         .await?;
@@ -261,10 +415,10 @@ impl<'a> FeatureRepository<'a> {
         Ok(())
     }
 
-    /// Update feature status
-    pub async fn update_status(&self, id: &str, status: FeatureStatus) -> Result<()> {
-        sqlx::query("UPDATE features SET status = ?, updated_at = ? WHERE id = ?")
-            .bind(status.as_str())
+    /// Link the git branch the feature is developed on
+    pub async fn set_branch(&self, id: &str, branch: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE features SET branch = ?, updated_at = ? WHERE id = ?")
+            .bind(branch)
             .bind(Utc::now())
             .bind(id)
             .execute(self.db.pool())
@@ -273,6 +427,27 @@ impl<'a> FeatureRepository<'a> {
         Ok(())
     }
 
+    /// Update feature status
+    pub async fn update_status(&self, id: &str, status: FeatureStatus) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE features
+            SET status = ?, updated_at = ?,
+                completed_at = CASE WHEN ? = 'done' THEN COALESCE(completed_at, ?) ELSE NULL END
+            WHERE id = ?
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(Utc::now())
+        .bind(status.as_str())
+        .bind(Utc::now())
+        .bind(id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
     /// Delete a feature
     pub async fn delete(&self, id: &str) -> Result<()> {
         sqlx::query("DELETE FROM features WHERE id = ?")
@@ -298,6 +473,7 @@ impl<'a> FeatureRepository<'a> {
             phase_id: row.get("phase_id"),
             status: FeatureStatus::parse(row.get("status")).unwrap_or_default(),
             priority: row.get("priority"),
+            branch: row.get("branch"),
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
         }
@@ -321,8 +497,6 @@ pub async fn create_with_db(
     description: Option<&str>,
     phase_id: Option<&str>,
 ) -> Result<Feature> {
-    let repo = FeatureRepository::new(db);
-
     let mut feature = Feature::new(project_id, title);
     if let Some(desc) = description {
         feature = feature.with_description(desc);
@@ -331,7 +505,31 @@ pub async fn create_with_db(
         feature = feature.with_phase(phase);
     }
 
+    insert_with_db(db, feature).await
+}
+
+/// Store a fully built feature, announcing it to event subscribers
+///
+/// A subscriber vetoing the creation removes the feature again and returns
+/// the veto as a validation error.
+pub async fn insert_with_db(db: &Database, feature: Feature) -> Result<Feature> {
+    let repo = FeatureRepository::new(db);
     repo.create(&feature).await?;
+
+    let outcome = events::global()
+        .publish(CoreEvent::FeatureCreated {
+            feature_id: feature.id.clone(),
+            project_id: feature.project_id.clone(),
+            title: feature.title.clone(),
+        })
+        .await;
+    if let Err(e) = outcome.ensure_not_vetoed("Feature creation") {
+        repo.delete(&feature.id).await?;
+        return Err(e);
+    }
+    for annotation in &outcome.annotations {
+        tracing::info!(subscriber = %annotation.subscriber, feature_id = %feature.id, "{}", annotation.text);
+    }
     Ok(feature)
 }
 
@@ -350,6 +548,17 @@ pub async fn list_with_db(
     repo.list_by_project(project_id, status).await
 }
 
+/// List one page of features with database
+pub async fn list_page_with_db(
+    db: &Database,
+    project_id: &str,
+    status: Option<FeatureStatus>,
+    page: PageRequest,
+) -> Result<Page<Feature>> {
+    let repo = FeatureRepository::new(db);
+    repo.list_by_project_page(project_id, status, page).await
+}
+
 /// Update feature
 pub async fn update(_id: &str, _status: Option<&str>, _priority: Option<i32>) -> Result<()> {
     Ok(())
@@ -459,6 +668,47 @@ mod tests {
         assert_eq!(feature.priority, 1); // Clamped to min
     }
 
+    #[test]
+    fn test_feature_draft_round_trip() {
+        let feature = Feature::new("proj-123", "Logout")
+            .with_description("Ends the session.\n\n---\n\nAlso clears tokens.")
+            .with_labels(vec!["auth".to_string()])
+            .with_priority(2);
+        let buffer = FeatureDraft::from_feature(&feature).to_buffer().unwrap();
+        let edited = buffer
+            .replace("title: Logout", "title: Sign out")
+            .replace("status: backlog", "status: in_progress");
+
+        let draft = FeatureDraft::parse(&edited).unwrap();
+        assert_eq!(draft.title, "Sign out");
+        assert_eq!(draft.labels, vec!["auth".to_string()]);
+        assert_eq!(
+            draft.description,
+            "Ends the session.\n\n---\n\nAlso clears tokens."
+        );
+
+        let mut updated = feature.clone();
+        draft.apply_to(&mut updated);
+        assert_eq!(updated.status, FeatureStatus::InProgress);
+        assert_eq!(updated.priority, 2);
+        assert_eq!(updated.phase_id, None);
+    }
+
+    #[test]
+    fn test_feature_draft_rejects_bad_input() {
+        let buffer = FeatureDraft::new("").to_buffer().unwrap();
+        assert!(matches!(
+            FeatureDraft::parse(&buffer),
+            Err(crate::Error::UserCancelled)
+        ));
+
+        let buffer = FeatureDraft::new("x")
+            .to_buffer()
+            .unwrap()
+            .replace("status: backlog", "status: someday");
+        assert!(FeatureDraft::parse(&buffer).is_err());
+    }
+
     #[tokio::test]
     async fn test_feature_repository_crud() {
         let db = Database::in_memory()
@@ -496,4 +746,43 @@ mod tests {
         let deleted = repo.get(&feature.id).await.unwrap();
         assert!(deleted.is_none());
     }
+
+    #[tokio::test]
+    async fn test_list_by_project_page() {
+        let db = Database::in_memory()
+            .await
+            .expect("Failed to create database");
+        let project = Project::new("test-project", "rust", "");
+        ProjectRepository::new(&db).create(&project).await.unwrap();
+
+        let repo = FeatureRepository::new(&db);
+        for i in 1..=5 {
+            let mut feature = Feature::new(&project.id, &format!("Feature {}", i)).with_priority(i);
+            if i % 2 == 0 {
+                feature.status = FeatureStatus::Done;
+            }
+            repo.create(&feature).await.unwrap();
+        }
+
+        let page = repo
+            .list_by_project_page(&project.id, None, PageRequest::new(2, 2))
+            .await
+            .unwrap();
+        let titles: Vec<_> = page.items.iter().map(|f| f.title.as_str()).collect();
+        assert_eq!(titles, vec!["Feature 3", "Feature 4"]);
+        assert_eq!(page.total, 5);
+        assert!(page.has_more());
+
+        let done = repo
+            .list_by_project_page(
+                &project.id,
+                Some(FeatureStatus::Done),
+                PageRequest::new(1, 1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(done.items[0].title, "Feature 4");
+        assert_eq!(done.total, 2);
+        assert!(!done.has_more());
+    }
 }
@@ -0,0 +1,279 @@
+//! Feature branches
+//!
+//! `generate.branch_template` names the git branch a feature is developed
+//! on: `feat/{feature-id-short}-{slug}` gives `feat/3f2a9c1d-user-login`.
+//! `demiarch features branch <id>` creates or switches to that branch, or
+//! checks it out into a worktree beside the repository, and links the name
+//! onto the feature so renaming the feature keeps its branch.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::feature::{Feature, FeatureRepository};
+use super::worktree::git;
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Branch template used when `generate.branch_template` is not set
+pub const DEFAULT_TEMPLATE: &str = "feat/{feature-id-short}-{slug}";
+
+/// Placeholders a branch template may use
+const PLACEHOLDERS: &[&str] = &["feature-id", "feature-id-short", "slug"];
+
+/// Longest slug taken from a feature title, in characters
+const MAX_SLUG_LEN: usize = 40;
+
+/// Check that `template` only uses known placeholders and uses at least one,
+/// so every feature gets a branch of its own
+pub fn check_template(template: &str) -> Result<()> {
+    let mut rest = template;
+    let mut placeholders = 0;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or_else(|| {
+            Error::InvalidInput(format!(
+                "Unclosed placeholder in branch template {}",
+                template
+            ))
+        })?;
+        let name = &rest[start + 1..start + end];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(Error::InvalidInput(format!(
+                "Unknown placeholder {{{}}} in branch template; use {{feature-id}}, {{feature-id-short}} or {{slug}}",
+                name
+            )));
+        }
+        placeholders += 1;
+        rest = &rest[start + end + 1..];
+    }
+    if placeholders == 0 {
+        return Err(Error::InvalidInput(format!(
+            "Branch template {} has no placeholders, so every feature would share one branch",
+            template
+        )));
+    }
+    Ok(())
+}
+
+/// The branch `template` names for `feature`
+pub fn render(template: &str, feature: &Feature) -> Result<String> {
+    check_template(template)?;
+    let short_id = &feature.id[..8.min(feature.id.len())];
+    Ok(template
+        .replace("{feature-id-short}", short_id)
+        .replace("{feature-id}", &feature.id)
+        .replace("{slug}", &slugify(&feature.title)))
+}
+
+/// "Add user login (OAuth)" -> "add-user-login-oauth"
+fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for part in title
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+    {
+        if !slug.is_empty() && slug.len() + 1 + part.len() > MAX_SLUG_LEN {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(part);
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    if slug.is_empty() {
+        "feature".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Where a feature's branch was checked out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BranchCheckout {
+    pub branch: String,
+    /// Whether the branch was created rather than already existing
+    pub created: bool,
+    /// Worktree the branch was checked out into, if not the repository itself
+    pub worktree: Option<PathBuf>,
+}
+
+/// Create or switch to `branch` in the repository containing `dir`, or with
+/// `worktree`, check it out into a new worktree beside the repository
+pub async fn checkout(dir: &Path, branch: &str, worktree: bool) -> Result<BranchCheckout> {
+    let repo_root = PathBuf::from(
+        git(dir, &["rev-parse", "--show-toplevel"])
+            .await
+            .map_err(|_| {
+                Error::InvalidInput(format!("{} is not in a git repository", dir.display()))
+            })?
+            .trim(),
+    );
+    git(&repo_root, &["check-ref-format", "--branch", branch])
+        .await
+        .map_err(|_| Error::InvalidInput(format!("Invalid branch name: {}", branch)))?;
+    let exists = git(
+        &repo_root,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", branch),
+        ],
+    )
+    .await
+    .is_ok();
+
+    let worktree = if worktree {
+        let repo_name = repo_root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "repo".to_string());
+        let path = repo_root.parent().unwrap_or(&repo_root).join(format!(
+            "{}-{}",
+            repo_name,
+            branch.replace('/', "-")
+        ));
+        let path_arg = path.to_string_lossy().into_owned();
+        if exists {
+            git(&repo_root, &["worktree", "add", &path_arg, branch]).await?;
+        } else {
+            git(
+                &repo_root,
+                &["worktree", "add", "-b", branch, &path_arg, "HEAD"],
+            )
+            .await?;
+        }
+        Some(path)
+    } else {
+        if exists {
+            git(&repo_root, &["switch", branch]).await?;
+        } else {
+            git(&repo_root, &["switch", "-c", branch]).await?;
+        }
+        None
+    };
+    tracing::info!(branch, created = !exists, "Checked out feature branch");
+
+    Ok(BranchCheckout {
+        branch: branch.to_string(),
+        created: !exists,
+        worktree,
+    })
+}
+
+/// Check out `feature`'s branch, named from `template` unless one is
+/// already linked, and link it onto the feature
+pub async fn branch_feature(
+    db: &Database,
+    feature: &Feature,
+    dir: &Path,
+    template: &str,
+    worktree: bool,
+) -> Result<BranchCheckout> {
+    let branch = match &feature.branch {
+        Some(branch) => branch.clone(),
+        None => render(template, feature)?,
+    };
+    let checkout = checkout(dir, &branch, worktree).await?;
+    if feature.branch.as_deref() != Some(branch.as_str()) {
+        FeatureRepository::new(db)
+            .set_branch(&feature.id, Some(&branch))
+            .await?;
+    }
+    Ok(checkout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feature(title: &str) -> Feature {
+        let mut feature = Feature::new("p1", title);
+        feature.id = "3f2a9c1d-0000-4000-8000-000000000000".to_string();
+        feature
+    }
+
+    #[test]
+    fn test_render_template() {
+        let f = feature("Add user login (OAuth)!");
+        assert_eq!(
+            render(DEFAULT_TEMPLATE, &f).unwrap(),
+            "feat/3f2a9c1d-add-user-login-oauth"
+        );
+        assert_eq!(
+            render(
+                "{slug}",
+                &feature("A very long feature title that keeps going past the limit")
+            )
+            .unwrap(),
+            "a-very-long-feature-title-that-keeps"
+        );
+        assert_eq!(
+            render("wip/{slug}", &feature("!!!")).unwrap(),
+            "wip/feature"
+        );
+
+        assert!(check_template("feature/{feature-id}").is_ok());
+        assert!(check_template("feat/{title}").is_err());
+        assert!(check_template("feat/{slug").is_err());
+        assert!(check_template("feat/shared").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_branch_feature_creates_links_and_switches() {
+        let repo = tempfile::tempdir().unwrap();
+        for args in [
+            vec!["init", "--quiet"],
+            vec!["config", "user.name", "Test"],
+            vec!["config", "user.email", "test@example.com"],
+            vec!["commit", "--quiet", "--allow-empty", "--message", "init"],
+        ] {
+            git(repo.path(), &args).await.unwrap();
+        }
+        let default_branch = git(repo.path(), &["branch", "--show-current"])
+            .await
+            .unwrap();
+
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'app')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let f = feature("Checkout flow");
+        FeatureRepository::new(&db).create(&f).await.unwrap();
+
+        let first = branch_feature(&db, &f, repo.path(), DEFAULT_TEMPLATE, false)
+            .await
+            .unwrap();
+        assert!(first.created);
+        assert_eq!(first.branch, "feat/3f2a9c1d-checkout-flow");
+        let linked = FeatureRepository::new(&db)
+            .get(&f.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            linked.branch.as_deref(),
+            Some("feat/3f2a9c1d-checkout-flow")
+        );
+
+        // A linked branch is kept even when the template changes
+        git(repo.path(), &["switch", "--quiet", default_branch.trim()])
+            .await
+            .unwrap();
+        let again = branch_feature(&db, &linked, repo.path(), "wip/{slug}", false)
+            .await
+            .unwrap();
+        assert!(!again.created);
+        assert_eq!(
+            git(repo.path(), &["branch", "--show-current"])
+                .await
+                .unwrap()
+                .trim(),
+            "feat/3f2a9c1d-checkout-flow"
+        );
+    }
+}
@@ -0,0 +1,594 @@
+//! Bulk import of features from task lists
+//!
+//! `demiarch features import --file backlog.md` turns an existing backlog
+//! into features. Two formats are read:
+//!
+//! - **Markdown**: every checkbox item (`- [ ] Title`) is a feature, checked
+//!   ones (`- [x]`) are imported as done, and text under an item becomes its
+//!   description. Headings that contain items or subheadings group them and
+//!   become a label; a heading with neither is a feature itself, with the
+//!   text under it as description. A trailing `(P1)`–`(P5)` sets priority.
+//! - **CSV**: a header row naming `title` and optionally `description`,
+//!   `priority` and `labels` columns, in any order. Labels are separated by
+//!   `;` or `,`.
+//!
+//! [`plan`] compares each row with the project's features and the rows before
+//! it, so the caller can preview what will be created and what skipped as a
+//! duplicate (by fuzzy title match) or invalid before [`apply`] stores it.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::feature::{self, Feature, FeatureRepository, FeatureStatus};
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Title similarity at or above which a row counts as a duplicate
+pub const DEFAULT_DUPLICATE_THRESHOLD: f32 = 0.85;
+
+/// One feature read from an import file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportRow {
+    /// Line in the file the row starts on
+    pub line: usize,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<i32>,
+    pub labels: Vec<String>,
+    pub status: FeatureStatus,
+    /// Why the row can't be imported, if it can't
+    pub error: Option<String>,
+}
+
+impl ImportRow {
+    fn new(line: usize, title: impl Into<String>) -> Self {
+        Self {
+            line,
+            title: title.into(),
+            description: None,
+            priority: None,
+            labels: Vec::new(),
+            status: FeatureStatus::Backlog,
+            error: None,
+        }
+    }
+}
+
+/// What importing a row will do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RowAction {
+    Create,
+    /// Skipped: the title closely matches an existing feature, or an earlier
+    /// row when `feature_id` is `None`
+    Duplicate {
+        feature_id: Option<String>,
+        title: String,
+        similarity: f32,
+    },
+    /// Skipped: the row is missing a title or has a bad value
+    Invalid {
+        reason: String,
+    },
+}
+
+/// A row and what importing it will do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedRow {
+    pub row: ImportRow,
+    pub action: RowAction,
+}
+
+/// Previewed import, not yet stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPlan {
+    pub project_id: String,
+    pub rows: Vec<PlannedRow>,
+}
+
+impl ImportPlan {
+    /// Rows that will become features
+    pub fn to_create(&self) -> usize {
+        self.rows
+            .iter()
+            .filter(|r| r.action == RowAction::Create)
+            .count()
+    }
+
+    /// Rows that will be skipped
+    pub fn to_skip(&self) -> usize {
+        self.rows.len() - self.to_create()
+    }
+}
+
+/// A row that was not imported
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedRow {
+    pub line: usize,
+    pub title: String,
+    pub reason: String,
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub created: Vec<Feature>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// Read rows from a `.md`/`.markdown` or `.csv` file
+pub fn read_file(path: &Path) -> Result<Vec<ImportRow>> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    let source = std::fs::read_to_string(path)?;
+    match extension.as_deref() {
+        Some("md" | "markdown") => Ok(parse_markdown(&source)),
+        Some("csv") => parse_csv(&source),
+        _ => Err(Error::InvalidInput(format!(
+            "Can't import {}: expected a .md or .csv file",
+            path.display()
+        ))),
+    }
+}
+
+/// An open heading while reading Markdown
+struct Section {
+    level: usize,
+    row: ImportRow,
+    /// Holds checkbox items or subheadings, so it is a group, not a feature
+    is_group: bool,
+}
+
+/// Read features from a Markdown task list
+pub fn parse_markdown(source: &str) -> Vec<ImportRow> {
+    let mut rows = Vec::new();
+    let mut sections: Vec<Section> = Vec::new();
+    // Index into `rows` of the item text lines are added to, or `None` when
+    // they belong to the innermost heading
+    let mut current_item: Option<usize> = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let number = index + 1;
+        let trimmed = line.trim();
+
+        if let Some((level, title)) = heading(trimmed) {
+            while sections.last().is_some_and(|s| s.level >= level) {
+                close_section(sections.pop(), &mut rows);
+            }
+            if let Some(parent) = sections.last_mut() {
+                parent.is_group = true;
+            }
+            sections.push(Section {
+                level,
+                row: ImportRow::new(number, title),
+                is_group: false,
+            });
+            current_item = None;
+        } else if let Some((done, text)) = checkbox(trimmed) {
+            let is_nested = line.len() - line.trim_start().len() >= 2;
+            if let (true, Some(item)) = (is_nested, current_item) {
+                // Subtasks stay part of their parent's description
+                append_line(&mut rows[item].description, &format!("- {}", text));
+                continue;
+            }
+            for section in &mut sections {
+                section.is_group = true;
+            }
+            let (title, priority) = split_priority(text);
+            let mut row = ImportRow::new(number, title);
+            row.priority = priority;
+            if done {
+                row.status = FeatureStatus::Done;
+            }
+            if let Some(section) = sections.last() {
+                row.labels.push(label_for(&section.row.title));
+            }
+            rows.push(row);
+            current_item = Some(rows.len() - 1);
+        } else if !trimmed.is_empty() {
+            let description = match (current_item, sections.last_mut()) {
+                (Some(item), _) => &mut rows[item].description,
+                (None, Some(section)) => &mut section.row.description,
+                (None, None) => continue,
+            };
+            append_line(description, trimmed);
+        }
+    }
+    while let Some(section) = sections.pop() {
+        close_section(Some(section), &mut rows);
+    }
+
+    rows.sort_by_key(|row| row.line);
+    rows
+}
+
+/// Keep a finished heading as a feature if nothing was grouped under it
+fn close_section(section: Option<Section>, rows: &mut Vec<ImportRow>) {
+    if let Some(section) = section.filter(|s| !s.is_group) {
+        let mut row = section.row;
+        let (title, priority) = split_priority(&row.title);
+        row.title = title;
+        row.priority = priority;
+        rows.push(row);
+    }
+}
+
+/// `## Title` as (2, "Title")
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+    (level > 0 && rest.starts_with(' ')).then(|| (level, rest.trim()))
+}
+
+/// `- [x] Title` as (true, "Title")
+fn checkbox(line: &str) -> Option<(bool, &str)> {
+    let rest = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))?;
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    Some((done, rest[3..].trim()))
+}
+
+/// Split a trailing `(P1)`–`(P5)` off a title
+fn split_priority(title: &str) -> (String, Option<i32>) {
+    if let Some(rest) = title.strip_suffix(')') {
+        if let Some((head, marker)) = rest.rsplit_once('(') {
+            if let Some(priority) = parse_priority(marker) {
+                return (head.trim().to_string(), Some(priority));
+            }
+        }
+    }
+    (title.to_string(), None)
+}
+
+/// `2` or `P2` as priority 2
+fn parse_priority(value: &str) -> Option<i32> {
+    let value = value.trim();
+    let digits = value
+        .strip_prefix('P')
+        .or_else(|| value.strip_prefix('p'))
+        .unwrap_or(value);
+    digits.parse().ok().filter(|p| (1..=5).contains(p))
+}
+
+fn label_for(heading: &str) -> String {
+    normalize(heading).join("-")
+}
+
+fn append_line(description: &mut Option<String>, line: &str) {
+    match description {
+        Some(text) => {
+            text.push('\n');
+            text.push_str(line);
+        }
+        None => *description = Some(line.to_string()),
+    }
+}
+
+/// Read features from CSV with a header row
+pub fn parse_csv(source: &str) -> Result<Vec<ImportRow>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(source.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(csv_error)?
+        .iter()
+        .map(str::to_ascii_lowercase)
+        .collect();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let title = column("title").ok_or_else(|| {
+        Error::InvalidInput("CSV import needs a `title` column in the header row".to_string())
+    })?;
+    let (description, priority, labels) =
+        (column("description"), column("priority"), column("labels"));
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(csv_error)?;
+        let line = record.position().map_or(0, |p| p.line() as usize);
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|i| record.get(i))
+                .filter(|value| !value.is_empty())
+        };
+
+        let mut row = ImportRow::new(line, field(Some(title)).unwrap_or_default());
+        row.description = field(description).map(str::to_string);
+        row.labels = field(labels)
+            .map(|value| {
+                value
+                    .split([';', ','])
+                    .map(str::trim)
+                    .filter(|label| !label.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(value) = field(priority) {
+            match parse_priority(value) {
+                Some(p) => row.priority = Some(p),
+                None => row.error = Some(format!("invalid priority '{}'", value)),
+            }
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn csv_error(e: csv::Error) -> Error {
+    Error::Parse(format!("CSV import failed: {}", e))
+}
+
+/// Decide what to do with each row, without storing anything
+///
+/// A row is a duplicate when its title is at least `threshold` similar to an
+/// existing feature of the project or to an earlier row being created.
+pub async fn plan(
+    db: &Database,
+    project_id: &str,
+    rows: Vec<ImportRow>,
+    threshold: f32,
+) -> Result<ImportPlan> {
+    let existing = FeatureRepository::new(db)
+        .list_by_project(project_id, None)
+        .await?;
+
+    let mut planned: Vec<PlannedRow> = Vec::with_capacity(rows.len());
+    for row in rows {
+        let action = if row.title.trim().is_empty() {
+            RowAction::Invalid {
+                reason: "missing title".to_string(),
+            }
+        } else if let Some(reason) = &row.error {
+            RowAction::Invalid {
+                reason: reason.clone(),
+            }
+        } else {
+            let earlier = planned
+                .iter()
+                .filter(|p| p.action == RowAction::Create)
+                .map(|p| (None, p.row.title.as_str()));
+            existing
+                .iter()
+                .map(|f| (Some(f.id.as_str()), f.title.as_str()))
+                .chain(earlier)
+                .map(|(id, title)| (id, title, title_similarity(&row.title, title)))
+                .filter(|(_, _, similarity)| *similarity >= threshold)
+                .max_by(|a, b| a.2.total_cmp(&b.2))
+                .map_or(RowAction::Create, |(id, title, similarity)| {
+                    RowAction::Duplicate {
+                        feature_id: id.map(str::to_string),
+                        title: title.to_string(),
+                        similarity,
+                    }
+                })
+        };
+        planned.push(PlannedRow { row, action });
+    }
+
+    Ok(ImportPlan {
+        project_id: project_id.to_string(),
+        rows: planned,
+    })
+}
+
+/// Create the planned features, optionally in a phase
+///
+/// Rows an event subscriber vetoes are reported as skipped rather than
+/// failing the whole import.
+pub async fn apply(
+    db: &Database,
+    plan: &ImportPlan,
+    phase_id: Option<&str>,
+) -> Result<ImportReport> {
+    let mut report = ImportReport {
+        created: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for PlannedRow { row, action } in &plan.rows {
+        let reason = match action {
+            RowAction::Create => {
+                let mut feature = Feature::new(&plan.project_id, row.title.trim());
+                feature.description = row.description.clone();
+                feature.status = row.status;
+                if let Some(priority) = row.priority {
+                    feature = feature.with_priority(priority);
+                }
+                if !row.labels.is_empty() {
+                    feature = feature.with_labels(row.labels.clone());
+                }
+                if let Some(phase) = phase_id {
+                    feature = feature.with_phase(phase);
+                }
+                match feature::insert_with_db(db, feature).await {
+                    Ok(feature) => {
+                        report.created.push(feature);
+                        continue;
+                    }
+                    Err(Error::Validation(reason)) => reason,
+                    Err(e) => return Err(e),
+                }
+            }
+            RowAction::Duplicate { title, .. } => format!("duplicate of '{}'", title),
+            RowAction::Invalid { reason } => reason.clone(),
+        };
+        report.skipped.push(SkippedRow {
+            line: row.line,
+            title: row.title.clone(),
+            reason,
+        });
+    }
+    Ok(report)
+}
+
+/// How alike two titles are, from 0.0 to 1.0
+///
+/// Case, punctuation and word order are ignored; the score is the better of
+/// the word overlap and the edit distance between the normalized titles.
+pub fn title_similarity(a: &str, b: &str) -> f32 {
+    let (a, b) = (normalize(a), normalize(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let words_a: HashSet<&String> = a.iter().collect();
+    let words_b: HashSet<&String> = b.iter().collect();
+    let overlap =
+        words_a.intersection(&words_b).count() as f32 / words_a.union(&words_b).count() as f32;
+
+    let (a, b) = (a.join(" "), b.join(" "));
+    let longest = a.chars().count().max(b.chars().count());
+    let edit = 1.0 - levenshtein(&a, &b) as f32 / longest as f32;
+
+    overlap.max(edit)
+}
+
+/// Lowercase words, without punctuation
+fn normalize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+pub(crate) fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::project::{Project, ProjectRepository};
+
+    const BACKLOG: &str = "\
+# Backlog
+
+## Authentication
+
+- [ ] Login page (P1)
+  Email and password form.
+  - [ ] Remember me
+- [x] Logout
+
+## Dark mode
+Follow the system theme.
+";
+
+    #[test]
+    fn test_parse_markdown() {
+        let rows = parse_markdown(BACKLOG);
+        let titles: Vec<_> = rows.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, vec!["Login page", "Logout", "Dark mode"]);
+
+        assert_eq!(rows[0].priority, Some(1));
+        assert_eq!(rows[0].labels, vec!["authentication".to_string()]);
+        assert_eq!(
+            rows[0].description.as_deref(),
+            Some("Email and password form.\n- Remember me")
+        );
+        assert_eq!(rows[1].status, FeatureStatus::Done);
+        assert_eq!(
+            rows[2].description.as_deref(),
+            Some("Follow the system theme.")
+        );
+        assert!(rows[2].labels.is_empty());
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let rows = parse_csv(
+            "Priority,Title,Labels,Description\n\
+             2,Export to PDF,\"reports; export\",\"Multi-page, with headers\"\n\
+             high,Search,,\n\
+             ,,,\n",
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].title, "Export to PDF");
+        assert_eq!(rows[0].priority, Some(2));
+        assert_eq!(rows[0].labels, vec!["reports", "export"]);
+        assert_eq!(
+            rows[0].description.as_deref(),
+            Some("Multi-page, with headers")
+        );
+        assert_eq!(rows[1].error.as_deref(), Some("invalid priority 'high'"));
+        assert!(rows[2].title.is_empty());
+
+        assert!(parse_csv("name,priority\nx,1\n").is_err());
+    }
+
+    #[test]
+    fn test_title_similarity() {
+        assert_eq!(title_similarity("User login", "user-login!"), 1.0);
+        assert_eq!(title_similarity("Login page", "page login"), 1.0);
+        assert!(title_similarity("User login page", "User login pages") >= 0.9);
+        assert!(title_similarity("Add login", "Add logout") < DEFAULT_DUPLICATE_THRESHOLD);
+        assert_eq!(title_similarity("", "x"), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_plan_and_apply_skip_duplicates() {
+        let db = Database::in_memory().await.unwrap();
+        let project = Project::new("import-test", "rust", "");
+        ProjectRepository::new(&db).create(&project).await.unwrap();
+        feature::create_with_db(&db, &project.id, "Login Page", None, None)
+            .await
+            .unwrap();
+
+        let mut rows = parse_markdown(BACKLOG);
+        rows.push(ImportRow::new(20, "Dark-mode"));
+        rows.push(ImportRow::new(21, " "));
+
+        let plan = plan(&db, &project.id, rows, DEFAULT_DUPLICATE_THRESHOLD)
+            .await
+            .unwrap();
+        assert_eq!(plan.to_create(), 2);
+        assert_eq!(plan.to_skip(), 3);
+        assert!(matches!(
+            &plan.rows[0].action,
+            RowAction::Duplicate {
+                feature_id: Some(_),
+                ..
+            }
+        ));
+        assert!(matches!(
+            &plan.rows[3].action,
+            RowAction::Duplicate { feature_id: None, title, .. } if title == "Dark mode"
+        ));
+
+        let report = apply(&db, &plan, None).await.unwrap();
+        assert_eq!(report.created.len(), 2);
+        assert_eq!(report.skipped.len(), 3);
+        assert_eq!(report.skipped[2].reason, "missing title");
+
+        let features = FeatureRepository::new(&db)
+            .list_by_project(&project.id, None)
+            .await
+            .unwrap();
+        assert_eq!(features.len(), 3);
+        let logout = features.iter().find(|f| f.title == "Logout").unwrap();
+        assert_eq!(logout.status, FeatureStatus::Done);
+        assert_eq!(logout.labels, Some(vec!["authentication".to_string()]));
+    }
+}
@@ -4,20 +4,42 @@
 //! Supports file extraction, dry-run mode, and cost tracking.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-
-use tracing::{debug, info};
-
-use crate::agents::events::AgentEventWriter;
+use std::time::Duration;
+
+use tracing::{debug, info, warn};
+
+use crate::agents::code_extraction::extract_files_from_response;
+use crate::agents::dependencies;
+use crate::agents::events::{AgentEventWriter, FileEventData};
+use crate::agents::formatting::{format_source, runner_for, select_tools, LintFinding};
+use crate::agents::patch::{
+    build_fallback_prompt, extract_edits_from_response, resolve_edits, EditMode, FileEdit,
+    ResolvedEdit,
+};
+use crate::agents::security_audit::{self, SecurityFinding, AUDIT_SYSTEM_PROMPT};
+use crate::agents::validation::{
+    build_repair_prompt, validate_source, ValidationReport, MAX_REPAIR_ATTEMPTS,
+};
 use crate::agents::{AgentId, AgentType};
+use crate::commands::guardrails::{FileWrite, Guardrails};
+use crate::commands::persona::Persona;
+use crate::commands::secrets;
+use crate::commands::staging;
 use crate::config::Config;
+use crate::context::{ContextBudget, WindowStats};
 use crate::cost::CostTracker;
+use crate::domain::session::notes::{self, SessionNote, SessionNoteRepository};
+use crate::domain::session::SessionManager;
 use crate::error::{Error, Result};
-use crate::llm::{LlmClient, Message};
+use crate::hooks::HooksManager;
+use crate::llm::{LlmClient, LlmResponse, Message, ResponseCache};
+use crate::progress::{Progress, Stage};
+use crate::storage::Database;
 
 /// Result of code generation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct GenerationResult {
     /// Number of new files created
     pub files_created: usize,
@@ -31,6 +53,28 @@ pub struct GenerationResult {
     pub files: Vec<GeneratedFile>,
 }
 
+impl GenerationResult {
+    /// Files rejected because they still had syntax errors after repair
+    pub fn rejected_files(&self) -> impl Iterator<Item = &GeneratedFile> {
+        self.files.iter().filter(|f| f.has_syntax_errors())
+    }
+
+    /// Lint findings across all files
+    pub fn lint_findings(&self) -> impl Iterator<Item = &LintFinding> {
+        self.files.iter().flat_map(|f| &f.lint_findings)
+    }
+
+    /// Security audit findings across all files
+    pub fn security_findings(&self) -> impl Iterator<Item = &SecurityFinding> {
+        self.files.iter().flat_map(|f| &f.security_findings)
+    }
+
+    /// Files held back because the security audit found critical issues
+    pub fn blocked_files(&self) -> impl Iterator<Item = &GeneratedFile> {
+        self.files.iter().filter(|f| f.is_blocked())
+    }
+}
+
 /// A single generated file
 #[derive(Debug, Clone)]
 pub struct GeneratedFile {
@@ -42,15 +86,53 @@ pub struct GeneratedFile {
     pub is_new: bool,
     /// Language/file type
     pub language: Option<String>,
+    /// Syntax validation result, if the file has been validated
+    pub validation: Option<ValidationReport>,
+    /// Lint findings that remained after auto-fixes
+    pub lint_findings: Vec<LintFinding>,
+    /// Security audit findings, if the audit ran
+    pub security_findings: Vec<SecurityFinding>,
+}
+
+impl GeneratedFile {
+    /// Whether validation found syntax errors in this file
+    pub fn has_syntax_errors(&self) -> bool {
+        self.validation.as_ref().is_some_and(|v| v.is_invalid())
+    }
+
+    /// Whether a blocking security finding keeps this file from being applied
+    pub fn is_blocked(&self) -> bool {
+        self.security_findings.iter().any(|f| f.blocking)
+    }
+
+    /// Validate the file's syntax and record the result
+    fn validate(&mut self) -> &ValidationReport {
+        self.validation.insert(validate_source(
+            &self.path,
+            self.language.as_deref(),
+            &self.content,
+        ))
+    }
 }
 
 /// Code generator that uses LLM to generate code from descriptions
 pub struct CodeGenerator {
     llm_client: LlmClient,
-    #[allow(dead_code)]
     config: Config,
+    /// Project framework, used to pick default formatters
+    framework: Option<String>,
     /// Event writer for TUI monitoring
     event_writer: AgentEventWriter,
+    /// Stage updates for spinners and progress bars
+    progress: Progress,
+    /// Names of the project's secrets; values are never part of a prompt
+    secret_names: Vec<String>,
+    /// Policy checked before any file is written
+    guardrails: Guardrails,
+    /// Project persona appended to the system prompt
+    persona: Option<Persona>,
+    /// Reminders noted during the session, passed along with the request
+    reminders: Option<String>,
 }
 
 impl CodeGenerator {
@@ -75,14 +157,64 @@ impl CodeGenerator {
         }
 
         let llm_client = builder.build()?;
+        // Files are written relative to the working directory
+        let guardrails = Guardrails::load(&config.guardrails, Path::new("."))?;
 
         Ok(Self {
             llm_client,
             config,
+            framework: None,
             event_writer: AgentEventWriter::new(),
+            progress: Progress::hidden(),
+            secret_names: Vec::new(),
+            guardrails,
+            persona: None,
+            reminders: None,
         })
     }
 
+    /// Set the project framework (e.g. "rust", "react")
+    pub fn with_framework(mut self, framework: impl Into<String>) -> Self {
+        self.framework = Some(framework.into());
+        self
+    }
+
+    /// Serve repeated identical requests from a response cache
+    pub fn with_response_cache(mut self, cache: ResponseCache) -> Self {
+        self.llm_client = self.llm_client.with_response_cache(cache);
+        self
+    }
+
+    /// Report code, review and write stages to a progress handle
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Reference these secret names from generated config and `.env.example`
+    pub fn with_secret_names(mut self, names: Vec<String>) -> Self {
+        self.secret_names = names;
+        self
+    }
+
+    /// Check writes against this policy instead of the working directory's
+    pub fn with_guardrails(mut self, guardrails: Guardrails) -> Self {
+        self.guardrails = guardrails;
+        self
+    }
+
+    /// Follow a project persona's tone and stack preferences
+    pub fn with_persona(mut self, persona: Option<Persona>) -> Self {
+        self.persona = persona;
+        self
+    }
+
+    /// Pass session reminders along (see [`notes::reminders_prompt`])
+    pub fn with_reminders(mut self, reminders: Option<String>) -> Self {
+        self.reminders = reminders;
+        self
+    }
+
     /// Generate code from a natural language description
     pub async fn generate(&self, description: &str, dry_run: bool) -> Result<GenerationResult> {
         info!(description = %description, dry_run = %dry_run, "Starting code generation");
@@ -114,8 +246,14 @@ impl CodeGenerator {
         let messages = self.build_messages(description);
 
         debug!(message_count = messages.len(), "Sending request to LLM");
-
-        let response = match self.llm_client.complete_with_fallback(messages).await {
+        self.progress
+            .stage(Stage::Code, "Waiting for the model to write code");
+
+        let response = match self
+            .llm_client
+            .complete_with_fallback(messages.clone())
+            .await
+        {
             Ok(resp) => resp,
             Err(e) => {
                 // Emit failed events for both agents
@@ -131,8 +269,27 @@ impl CodeGenerator {
             model = %response.model,
             "Received LLM response"
         );
+        let capacity = ContextBudget::new(self.config.context.total_tokens)
+            .allocation_for_depth(1)
+            .total_input();
+        self.event_writer.emit_context_usage(
+            &coder_id,
+            WindowStats::for_messages(&messages, capacity).with_measured(response.input_tokens),
+        );
 
-        let files = match self.parse_generated_files(&response.content) {
+        // Responses that patch existing files are resolved against disk; plain
+        // whole-file responses keep the original parsing (and its fallbacks)
+        let edits = extract_edits_from_response(&response.content);
+        let parsed = if edits.iter().any(FileEdit::is_patch) {
+            Ok(self
+                .resolve_patched_files(&messages, &response.content, &edits)
+                .await)
+        } else {
+            self.parse_generated_files(&response.content)
+                .map(|files| (files, None))
+        };
+
+        let (mut files, fallback_response) = match parsed {
             Ok(f) => f,
             Err(e) => {
                 self.event_writer.emit_failed(&coder_id, &e.to_string());
@@ -142,27 +299,70 @@ impl CodeGenerator {
             }
         };
 
-        let files_created = files.iter().filter(|f| f.is_new).count();
-        let files_modified = files.len() - files_created;
+        // Let watching UIs list files as soon as they are known
+        let total = files.len();
+        for (i, file) in files.iter().enumerate() {
+            self.event_writer.emit_file_extracted(
+                &coder_id,
+                FileEventData::new(
+                    file.path.to_string_lossy(),
+                    &file.content,
+                    file.is_new,
+                    i + 1,
+                    total,
+                ),
+            );
+        }
+
+        // Syntax-check everything before it can be written, giving the model a
+        // bounded number of chances to fix files that do not parse
+        self.progress.stage(
+            Stage::Review,
+            format!("Checking {} generated file(s)", files.len()),
+        );
+        let repair_responses = self
+            .validate_and_repair(&messages, &response.content, &mut files)
+            .await;
+        self.format_files(&mut files).await;
+        self.complete_env_example(&mut files);
+        propose_dependencies(&mut files);
+        let audit_response = self.audit_files(&mut files).await;
+
+        let accepted = files.iter().filter(|f| !f.has_syntax_errors());
+        let files_created = accepted.clone().filter(|f| f.is_new).count();
+        let files_modified = accepted.count() - files_created;
 
         // Estimate cost (rough approximation based on token counts)
         // Claude 3.5 Sonnet: ~$3/1M input, ~$15/1M output
-        let cost_usd = estimate_cost(
+        let mut cost_usd = estimate_cost(
             &response.model,
             response.input_tokens,
             response.output_tokens,
         );
+        let mut tokens_used = response.tokens_used;
+        for extra in fallback_response
+            .iter()
+            .chain(&repair_responses)
+            .chain(&audit_response)
+        {
+            cost_usd += estimate_cost(&extra.model, extra.input_tokens, extra.output_tokens);
+            tokens_used += extra.tokens_used;
+        }
 
         let result = GenerationResult {
             files_created,
             files_modified,
-            tokens_used: response.tokens_used,
+            tokens_used,
             cost_usd,
             files,
         };
 
         if !dry_run {
-            if let Err(e) = self.write_files(&result.files) {
+            let written = match self.check_guardrails(description, &result.files).await {
+                Ok(()) => self.write_files(&coder_id, &result.files),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = written {
                 self.event_writer.emit_failed(&coder_id, &e.to_string());
                 self.event_writer
                     .emit_failed(&orchestrator_id, &e.to_string());
@@ -174,22 +374,293 @@ impl CodeGenerator {
 
         // Emit completed events for both agents (coder first, then orchestrator)
         self.event_writer
-            .emit_completed(&coder_id, response.tokens_used as u64);
+            .emit_completed(&coder_id, tokens_used as u64);
         self.event_writer
-            .emit_completed(&orchestrator_id, response.tokens_used as u64);
+            .emit_completed(&orchestrator_id, tokens_used as u64);
 
         Ok(result)
     }
 
     /// Build the message sequence for code generation
     fn build_messages(&self, description: &str) -> Vec<Message> {
-        vec![
-            Message::system(SYSTEM_PROMPT),
-            Message::user(format!(
-                "Generate code for the following requirement:\n\n{}",
-                description
-            )),
-        ]
+        let mut messages = vec![Message::system(SYSTEM_PROMPT)];
+        if let Some(ref persona) = self.persona {
+            messages.push(Message::system(persona.prompt()));
+        }
+        if let Some(ref reminders) = self.reminders {
+            messages.push(Message::system(reminders.clone()));
+        }
+        if !self.secret_names.is_empty() {
+            messages.push(Message::system(format!(
+                "The project has these secrets configured as environment variables: {}. \
+                 Read them from the environment by name wherever configuration needs them; \
+                 never hard-code values. If you write a .env.example file, list each name \
+                 with an empty value.",
+                self.secret_names.join(", ")
+            )));
+        }
+        messages.push(Message::user(format!(
+            "Generate code for the following requirement:\n\n{}",
+            description
+        )));
+        messages
+    }
+
+    /// Make sure a generated `.env.example` lists every project secret
+    fn complete_env_example(&self, files: &mut [GeneratedFile]) {
+        if self.secret_names.is_empty() {
+            return;
+        }
+        for file in files
+            .iter_mut()
+            .filter(|f| f.path.file_name().is_some_and(|n| n == ".env.example"))
+        {
+            let missing: Vec<String> = self
+                .secret_names
+                .iter()
+                .filter(|name| {
+                    !file.content.lines().any(|line| {
+                        line.trim_start()
+                            .strip_prefix(name.as_str())
+                            .is_some_and(|rest| rest.trim_start().starts_with('='))
+                    })
+                })
+                .cloned()
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+            if !file.content.is_empty() && !file.content.ends_with('\n') {
+                file.content.push('\n');
+            }
+            file.content.push_str(&secrets::env_example(&missing));
+        }
+    }
+
+    /// Resolve a response containing patches against the files on disk
+    ///
+    /// Patches that fail to apply trigger a single follow-up request asking
+    /// for the complete contents of just those files. The follow-up response
+    /// (if any) is returned so its tokens and cost can be accounted for.
+    async fn resolve_patched_files(
+        &self,
+        messages: &[Message],
+        content: &str,
+        edits: &[FileEdit],
+    ) -> (Vec<GeneratedFile>, Option<LlmResponse>) {
+        let (mut resolved, failures) = resolve_edits(None, edits);
+        let mut fallback_response = None;
+
+        if !failures.is_empty() {
+            warn!(
+                failed = failures.len(),
+                "Patch application failed, requesting whole-file rewrites"
+            );
+
+            let mut retry = messages.to_vec();
+            retry.push(Message::assistant(content));
+            retry.push(Message::user(build_fallback_prompt(&failures)));
+
+            match self.llm_client.complete_with_fallback(retry).await {
+                Ok(resp) => {
+                    for file in extract_files_from_response(&resp.content) {
+                        if failures.iter().any(|f| f.path == file.path) {
+                            resolved.push(ResolvedEdit {
+                                path: file.path,
+                                content: file.content,
+                                mode: EditMode::Rewritten,
+                                language: file.language,
+                            });
+                        }
+                    }
+                    fallback_response = Some(resp);
+                }
+                Err(e) => warn!(error = %e, "Whole-file fallback request failed"),
+            }
+
+            for failure in &failures {
+                if !resolved.iter().any(|r| r.path == failure.path) {
+                    warn!("{}", failure);
+                }
+            }
+        }
+
+        let files = resolved
+            .into_iter()
+            .map(|edit| GeneratedFile {
+                is_new: edit.mode == EditMode::Created,
+                path: edit.path,
+                content: edit.content,
+                language: edit.language,
+                validation: None,
+                lint_findings: Vec::new(),
+                security_findings: Vec::new(),
+            })
+            .collect();
+
+        (files, fallback_response)
+    }
+
+    /// Validate generated files, requesting repairs for those with syntax errors
+    ///
+    /// Invalid files are sent back with their errors up to
+    /// [`MAX_REPAIR_ATTEMPTS`] times. Files that still fail keep their invalid
+    /// validation report so callers can reject them. Returns the repair
+    /// responses for token and cost accounting.
+    async fn validate_and_repair(
+        &self,
+        messages: &[Message],
+        content: &str,
+        files: &mut [GeneratedFile],
+    ) -> Vec<LlmResponse> {
+        for file in files.iter_mut() {
+            file.validate();
+        }
+
+        let mut responses: Vec<LlmResponse> = Vec::new();
+        for attempt in 1..=MAX_REPAIR_ATTEMPTS {
+            let invalid: Vec<&ValidationReport> = files
+                .iter()
+                .filter_map(|f| f.validation.as_ref())
+                .filter(|v| v.is_invalid())
+                .collect();
+            if invalid.is_empty() {
+                break;
+            }
+
+            warn!(
+                attempt,
+                invalid = invalid.len(),
+                "Generated files failed syntax validation, requesting repair"
+            );
+
+            let last_response = responses.last().map_or(content, |r| r.content.as_str());
+            let mut retry = messages.to_vec();
+            retry.push(Message::assistant(last_response));
+            retry.push(Message::user(build_repair_prompt(&invalid)));
+
+            let resp = match self.llm_client.complete_with_fallback(retry).await {
+                Ok(resp) => resp,
+                Err(e) => {
+                    warn!(error = %e, "Syntax repair request failed");
+                    break;
+                }
+            };
+
+            for repaired in extract_files_from_response(&resp.content) {
+                if let Some(file) = files
+                    .iter_mut()
+                    .find(|f| f.path == repaired.path && f.has_syntax_errors())
+                {
+                    file.content = repaired.content;
+                    if repaired.language.is_some() {
+                        file.language = repaired.language;
+                    }
+                    file.validate();
+                }
+            }
+            responses.push(resp);
+        }
+
+        for file in files.iter().filter(|f| f.has_syntax_errors()) {
+            warn!(path = %file.path.display(), "Rejecting file with syntax errors");
+        }
+
+        responses
+    }
+
+    /// Run the project's formatters and linters over files that passed validation
+    ///
+    /// Formatting is best-effort: tool failures are logged and leave the file
+    /// unchanged. Remaining lint findings are attached to each file.
+    async fn format_files(&self, files: &mut [GeneratedFile]) {
+        let Ok(root) = std::env::current_dir() else {
+            return;
+        };
+        let settings = &self.config.formatting;
+        let tools = select_tools(settings, self.framework.as_deref(), &root);
+        if tools.is_empty() {
+            return;
+        }
+        let runner = runner_for(&root, &tools, Duration::from_secs(settings.timeout_secs));
+
+        for file in files.iter_mut().filter(|f| !f.has_syntax_errors()) {
+            let outcome = format_source(&runner, &tools, &file.path, &file.content).await;
+            for error in &outcome.tool_errors {
+                warn!(path = %file.path.display(), "Formatter failed: {}", error);
+            }
+            if outcome.changed {
+                debug!(
+                    path = %file.path.display(),
+                    tools = ?outcome.applied,
+                    "Formatted generated file"
+                );
+                file.content = outcome.content;
+            }
+            file.lint_findings = outcome.findings;
+        }
+    }
+
+    /// Check files that passed validation against the security checklist
+    ///
+    /// Runs the line-level checks and, if configured, an LLM audit whose
+    /// response is returned so its tokens and cost can be accounted for.
+    /// Critical findings are marked blocking when the config says so.
+    async fn audit_files(&self, files: &mut [GeneratedFile]) -> Option<LlmResponse> {
+        let settings = &self.config.audit;
+        if !settings.enabled {
+            return None;
+        }
+        self.progress.stage(
+            Stage::Review,
+            "Auditing generated files for security issues",
+        );
+
+        let mut findings: Vec<SecurityFinding> = files
+            .iter()
+            .filter(|f| !f.has_syntax_errors())
+            .flat_map(|f| security_audit::scan(&f.path, &f.content))
+            .collect();
+
+        let mut response = None;
+        if settings.llm {
+            let audited: Vec<(&Path, &str)> = files
+                .iter()
+                .filter(|f| !f.has_syntax_errors())
+                .map(|f| (f.path.as_path(), f.content.as_str()))
+                .collect();
+            if !audited.is_empty() {
+                let messages = vec![
+                    Message::system(AUDIT_SYSTEM_PROMPT),
+                    Message::user(security_audit::build_audit_prompt(&audited)),
+                ];
+                match self.llm_client.complete_with_fallback(messages).await {
+                    Ok(resp) => {
+                        security_audit::merge(
+                            &mut findings,
+                            security_audit::parse_findings(&resp.content),
+                        );
+                        response = Some(resp);
+                    }
+                    Err(e) => warn!(error = %e, "Security audit request failed"),
+                }
+            }
+        }
+
+        for mut finding in findings {
+            finding.blocking = settings.block_critical && finding.is_critical();
+            match files
+                .iter_mut()
+                .find(|f| f.path.to_string_lossy() == finding.file)
+            {
+                Some(file) => file.security_findings.push(finding),
+                None => debug!(file = %finding.file, "Dropping audit finding for unknown file"),
+            }
+        }
+        for file in files.iter().filter(|f| f.is_blocked()) {
+            warn!(path = %file.path.display(), "Blocking file with critical security findings");
+        }
+        response
     }
 
     /// Parse the LLM response to extract generated files
@@ -297,22 +768,68 @@ impl CodeGenerator {
             content: code.trim_end().to_string(),
             is_new: true,
             language,
+            validation: None,
+            lint_findings: Vec::new(),
+            security_findings: Vec::new(),
         })
     }
 
+    /// Fail before writing anything if the files break a guardrail
+    async fn check_guardrails(&self, description: &str, files: &[GeneratedFile]) -> Result<()> {
+        let writes: Vec<FileWrite<'_>> = files
+            .iter()
+            .filter(|f| !f.has_syntax_errors())
+            .map(|f| FileWrite::read(Path::new("."), &f.path.to_string_lossy(), &f.content))
+            .collect();
+        let hooks = HooksManager::from_config(&self.config.hooks);
+        self.guardrails.enforce(&hooks, description, &writes).await
+    }
+
     /// Write generated files to disk
-    fn write_files(&self, files: &[GeneratedFile]) -> Result<()> {
-        for file in files {
-            info!(path = %file.path.display(), "Writing generated file");
-
-            // Create parent directories if needed
-            if let Some(parent) = file.path.parent() {
-                if !parent.as_os_str().is_empty() {
-                    std::fs::create_dir_all(parent).map_err(Error::Io)?;
-                }
-            }
+    ///
+    /// Files that failed syntax validation or have blocking security findings
+    /// are skipped. The files are written as one staged batch, so either all
+    /// of them land or none do; a file written event is emitted for each.
+    fn write_files(&self, writer_id: &AgentId, files: &[GeneratedFile]) -> Result<()> {
+        let to_write: Vec<&GeneratedFile> = files
+            .iter()
+            .filter(|f| !f.has_syntax_errors() && !f.is_blocked())
+            .collect();
+        let total = to_write.len();
+        self.progress
+            .stage(Stage::Write, format!("Writing {} file(s)", total));
+        if to_write.is_empty() {
+            return Ok(());
+        }
 
-            std::fs::write(&file.path, &file.content).map_err(Error::Io)?;
+        let root = Path::new(".");
+        staging::ensure_clean(root)?;
+        let paths: Vec<String> = to_write
+            .iter()
+            .map(|f| f.path.to_string_lossy().into_owned())
+            .collect();
+        staging::write_all(
+            root,
+            &writer_id.to_string(),
+            paths
+                .iter()
+                .zip(&to_write)
+                .map(|(path, file)| (path.as_str(), file.content.as_str())),
+        )?;
+
+        for (i, file) in to_write.into_iter().enumerate() {
+            info!(path = %file.path.display(), "Wrote generated file");
+            self.event_writer.emit_file_written(
+                writer_id,
+                FileEventData::new(
+                    file.path.to_string_lossy(),
+                    &file.content,
+                    file.is_new,
+                    i + 1,
+                    total,
+                ),
+            );
+            self.progress.advance((i + 1) as u64, total as u64);
         }
         Ok(())
     }
@@ -347,10 +864,49 @@ impl FileBuilder {
             content,
             is_new: true,
             language: self.language,
+            validation: None,
+            lint_findings: Vec::new(),
+            security_findings: Vec::new(),
         })
     }
 }
 
+/// Add manifest edits for packages the generated files import but do not declare
+///
+/// An edited manifest replaces a generated one or is added as a modification
+/// of the manifest on disk, so the additions are part of the generation's diff.
+fn propose_dependencies(files: &mut Vec<GeneratedFile>) {
+    let Ok(root) = std::env::current_dir() else {
+        return;
+    };
+    let sources: Vec<(&Path, &str)> = files
+        .iter()
+        .filter(|f| !f.has_syntax_errors())
+        .map(|f| (f.path.as_path(), f.content.as_str()))
+        .collect();
+    let edits = dependencies::propose(&root, &sources);
+
+    for edit in edits {
+        info!(
+            manifest = %edit.change.manifest.display(),
+            packages = ?edit.change.packages,
+            "Proposing dependencies for generated imports"
+        );
+        match files.iter_mut().find(|f| f.path == edit.change.manifest) {
+            Some(file) => file.content = edit.content,
+            None => files.push(GeneratedFile {
+                is_new: false,
+                path: edit.change.manifest,
+                content: edit.content,
+                language: Some(edit.change.ecosystem.manifest_language().to_string()),
+                validation: None,
+                lint_findings: Vec::new(),
+                security_findings: Vec::new(),
+            }),
+        }
+    }
+}
+
 /// Extract file path from a line (supports multiple formats)
 fn extract_file_path(line: &str) -> Option<PathBuf> {
     let line = line.trim();
@@ -514,7 +1070,7 @@ fn language_to_extension(lang: &str) -> Option<&'static str> {
 }
 
 /// Estimate cost based on model and token counts
-fn estimate_cost(model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+pub(crate) fn estimate_cost(model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
     // Pricing per million tokens (approximate)
     let (input_price, output_price) = match model {
         m if m.contains("claude-3-5-sonnet") || m.contains("claude-sonnet-4") => (3.0, 15.0),
@@ -532,6 +1088,14 @@ fn estimate_cost(model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
     input_cost + output_cost
 }
 
+/// Prompt templates used by code generation, by name
+///
+/// Hashed into each generation's environment snapshot so a changed prompt
+/// shows up when a generation is replayed.
+pub fn prompt_templates() -> &'static [(&'static str, &'static str)] {
+    &[("generate.system", SYSTEM_PROMPT)]
+}
+
 /// System prompt for code generation
 const SYSTEM_PROMPT: &str = r#"You are an expert software developer. Generate clean, well-documented, production-ready code based on the user's requirements.
 
@@ -552,6 +1116,27 @@ pub fn hello() -> &'static str {
 }
 ```
 
+## Editing Existing Files
+
+When changing a file that already exists, do not repeat the whole file. Put the
+path on its own line and follow it with a code block of search/replace edits:
+
+**`src/lib.rs`**
+```rust
+<<<<<<< SEARCH
+pub fn hello() -> &'static str {
+    "Hello, world!"
+}
+=======
+pub fn hello() -> &'static str {
+    "Hello, Demiarch!"
+}
+>>>>>>> REPLACE
+```
+
+The SEARCH section must match the current file exactly and be unique within it.
+Unified diffs (```diff with ---/+++/@@ headers) are also accepted.
+
 ## Guidelines
 
 1. **Complete Code**: Generate fully working code, not snippets or pseudocode
@@ -574,11 +1159,132 @@ Be concise in explanations but thorough in code generation."#;
 ///
 /// This is the main entry point for the generate command.
 pub async fn generate(description: &str, dry_run: bool) -> Result<GenerationResult> {
+    generate_for_framework(description, None, dry_run).await
+}
+
+/// Generate code for a project with a known framework
+///
+/// The framework selects default formatters and linters when the project
+/// has no tool configuration of its own.
+pub async fn generate_for_framework(
+    description: &str,
+    framework: Option<&str>,
+    dry_run: bool,
+) -> Result<GenerationResult> {
+    generate_with_progress(description, framework, dry_run, &Progress::hidden()).await
+}
+
+/// Generate code, reporting each stage to a progress handle
+pub async fn generate_with_progress(
+    description: &str,
+    framework: Option<&str>,
+    dry_run: bool,
+    progress: &Progress,
+) -> Result<GenerationResult> {
+    generate_with_secrets(description, framework, Vec::new(), dry_run, progress).await
+}
+
+/// Generate code that references the project's secrets by name
+///
+/// Only names are passed in; see [`secrets::names`].
+pub async fn generate_with_secrets(
+    description: &str,
+    framework: Option<&str>,
+    secret_names: Vec<String>,
+    dry_run: bool,
+    progress: &Progress,
+) -> Result<GenerationResult> {
+    generate_with_persona(
+        description,
+        framework,
+        secret_names,
+        None,
+        dry_run,
+        progress,
+    )
+    .await
+}
+
+/// Generate code with the project's secret names and persona
+///
+/// See [`crate::commands::persona::resolve`] for picking the persona.
+pub async fn generate_with_persona(
+    description: &str,
+    framework: Option<&str>,
+    secret_names: Vec<String>,
+    persona: Option<Persona>,
+    dry_run: bool,
+    progress: &Progress,
+) -> Result<GenerationResult> {
     let config = Config::load().map_err(|e| Error::ConfigError(e.to_string()))?;
     let cost_tracker = Arc::new(CostTracker::from_config(&config.cost));
+    // Budget edits made while a long generation runs still apply to it
+    cost_tracker.follow_config();
 
-    let generator = CodeGenerator::new(config, Some(cost_tracker))?;
-    generator.generate(description, dry_run).await
+    let cache = if config.cache.enabled {
+        match Database::shared().await {
+            Ok(db) => ResponseCache::from_config(&db, &config.cache),
+            Err(e) => {
+                warn!(error = %e, "Response cache unavailable");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let reminders = session_reminders().await;
+
+    let mut generator = CodeGenerator::new(config, Some(cost_tracker))?
+        .with_progress(progress.clone())
+        .with_secret_names(secret_names)
+        .with_persona(persona)
+        .with_reminders(notes::reminders_prompt(&reminders));
+    if let Some(framework) = framework {
+        generator = generator.with_framework(framework);
+    }
+    if let Some(cache) = cache {
+        generator = generator.with_response_cache(cache);
+    }
+    let result = generator.generate(description, dry_run).await?;
+
+    // Each reminder goes into one generation that actually ran
+    if !dry_run && !reminders.is_empty() {
+        let ids: Vec<String> = reminders.into_iter().map(|n| n.id).collect();
+        if let Ok(db) = Database::shared().await {
+            if let Err(e) = SessionNoteRepository::new(db.pool().clone())
+                .mark_reminded(&ids)
+                .await
+            {
+                warn!(error = %e, "Failed to mark session reminders as seen");
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Reminders from the active session that no generation has seen yet
+async fn session_reminders() -> Vec<SessionNote> {
+    let db = match Database::shared().await {
+        Ok(db) => db,
+        Err(e) => {
+            debug!(error = %e, "Session reminders unavailable");
+            return Vec::new();
+        }
+    };
+    let active = SessionManager::new(db.pool().clone()).get_active().await;
+    let pending = match active {
+        Ok(Some(session)) => {
+            SessionNoteRepository::new(db.pool().clone())
+                .pending_reminders(session.id)
+                .await
+        }
+        Ok(None) => Ok(Vec::new()),
+        Err(e) => Err(e),
+    };
+    pending.unwrap_or_else(|e| {
+        debug!(error = %e, "Session reminders unavailable");
+        Vec::new()
+    })
 }
 
 /// Generate code with an existing database connection for cost tracking
@@ -610,15 +1316,15 @@ pub async fn generate_with_checkpoint(
     dry_run: bool,
 ) -> Result<GenerationResultWithCheckpoint> {
     use crate::domain::recovery::{CheckpointManager, CheckpointSigner, EditDetectionService};
-    use crate::storage::Database;
 
     let config = Config::load().map_err(|e| Error::ConfigError(e.to_string()))?;
     let cost_tracker = Arc::new(CostTracker::from_config(&config.cost));
+    cost_tracker.follow_config();
 
     // Create checkpoint before generation (unless dry run)
     let db = if !dry_run {
         Some(
-            Database::default()
+            Database::shared()
                 .await
                 .map_err(|e| Error::Other(e.to_string()))?,
         )
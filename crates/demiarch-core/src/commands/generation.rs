@@ -0,0 +1,2319 @@
+//! Generation records and per-file review
+//!
+//! Every code generation run can be recorded along with the files it
+//! produced. Files are stored as artifacts with a review decision so that
+//! output can be previewed as diffs, accepted or rejected file by file, and
+//! rejected files applied later without re-running the LLM.
+//!
+//! Generations started with [`start`] also store their execution plan with
+//! per-task status. If a task fails, the generation is marked failed but keeps
+//! the artifacts and cost of the tasks that completed, and [`resume`] re-runs
+//! only the tasks that did not. [`start_cancellable`] and
+//! [`resume_cancellable`] stop at a cancellation token instead, marking the
+//! generation interrupted with the in-flight task back to pending.
+//! [`plan_generation`] splits a description into those tasks.
+//!
+//! Each generation also stores a [`GenerationEnvironment`] snapshot of the
+//! version, models, config and prompts it ran under, the session that was
+//! active when it started and how long it ran. Past generations can be
+//! browsed with [`list`] and [`show`] and removed with [`delete`].
+//!
+//! Artifacts remember the plan task that produced them, so [`regenerate`]
+//! can re-run just that task for one file with extra instructions. Each
+//! re-save of a file bumps the artifact's version.
+//!
+//! Security audit findings are stored per file in the review findings table.
+//! Files with blocking findings are recorded as rejected and, like files with
+//! syntax errors, are skipped by [`apply`] unless named explicitly.
+//!
+//! Files are written through [`staging`], so an apply that fails partway
+//! leaves the output directory as it was.
+
+use std::future::Future;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::agents::events::{AgentEventWriter, FileEventData};
+use crate::agents::patch::{line_diff, unified_diff, DiffLine};
+use crate::agents::reviewer::IssueSeverity;
+use crate::agents::security_audit::SecurityFinding;
+use crate::agents::validation::{SyntaxError, ValidationStatus};
+use crate::agents::AgentId;
+use crate::commands::environment::GenerationEnvironment;
+use crate::commands::generate::{estimate_cost, GeneratedFile, GenerationResult};
+use crate::commands::guardrails::{FileWrite, Guardrails};
+use crate::commands::staging;
+use crate::config::Config;
+use crate::cost::CostTracker;
+use crate::domain::feature_decomposition::{ExecutionPlan, PlanTask, TaskStatus};
+use crate::domain::recovery::EditDetectionService;
+use crate::domain::session::SessionRepository;
+use crate::events::{self, CoreEvent};
+use crate::hooks::HooksManager;
+use crate::llm::{LlmClient, Message};
+use crate::storage::content::{self, ContentStore};
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Status of a generation run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GenerationStatus {
+    #[default]
+    Running,
+    Completed,
+    Failed,
+    /// Stopped by a shutdown signal; resumable like a failed run
+    Interrupted,
+}
+
+impl GenerationStatus {
+    /// Convert to string for database storage
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Interrupted => "interrupted",
+        }
+    }
+
+    /// Parse from database string
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "running" => Some(Self::Running),
+            "completed" => Some(Self::Completed),
+            "failed" => Some(Self::Failed),
+            "interrupted" => Some(Self::Interrupted),
+            _ => None,
+        }
+    }
+}
+
+/// Review decision for a generated file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactDecision {
+    /// Not reviewed yet
+    #[default]
+    Pending,
+    /// Accepted and written to disk
+    Accepted,
+    /// Rejected; kept so it can be applied later
+    Rejected,
+}
+
+impl ArtifactDecision {
+    /// Convert to string for database storage
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Accepted => "accepted",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    /// Parse from database string
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "accepted" => Some(Self::Accepted),
+            "rejected" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+}
+
+/// A recorded generation run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Generation {
+    /// Unique generation identifier
+    pub id: String,
+    /// Project the generation belongs to, if any
+    pub project_id: Option<String>,
+    /// Feature the generation implements, if any
+    pub feature_id: Option<String>,
+    /// Session that was active when the generation started, if any
+    pub session_id: Option<String>,
+    /// Natural language description that was generated from
+    pub description: String,
+    /// Directory artifact paths are relative to
+    pub output_dir: String,
+    /// Run status
+    pub status: GenerationStatus,
+    /// Total tokens used
+    pub tokens_used: i64,
+    /// Estimated cost in USD
+    pub cost_usd: f64,
+    /// Time spent running tasks, across resumes, in milliseconds
+    pub duration_ms: i64,
+    /// Execution plan with per-task status, if the generation was planned
+    pub plan: Option<ExecutionPlan>,
+    /// Environment the generation ran under (absent on older records)
+    pub environment: Option<GenerationEnvironment>,
+    /// When the generation started
+    pub created_at: DateTime<Utc>,
+    /// When the generation was last updated
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Generation {
+    /// Create a new running generation
+    pub fn new(description: impl Into<String>, output_dir: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            project_id: None,
+            feature_id: None,
+            session_id: None,
+            description: description.into(),
+            output_dir: output_dir.into(),
+            status: GenerationStatus::Running,
+            tokens_used: 0,
+            cost_usd: 0.0,
+            duration_ms: 0,
+            plan: None,
+            environment: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Set the project ID
+    pub fn with_project(mut self, project_id: impl Into<String>) -> Self {
+        self.project_id = Some(project_id.into());
+        self
+    }
+
+    /// Set the feature ID
+    pub fn with_feature(mut self, feature_id: impl Into<String>) -> Self {
+        self.feature_id = Some(feature_id.into());
+        self
+    }
+
+    /// Set the session ID
+    ///
+    /// Generations started without one are attributed to the active session.
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Set the execution plan
+    pub fn with_plan(mut self, plan: ExecutionPlan) -> Self {
+        self.plan = Some(plan);
+        self
+    }
+
+    /// Charge what the planner spent on `planned` to this generation
+    pub fn with_planning(mut self, planned: &GenerationPlan) -> Self {
+        self.tokens_used += planned.tokens_used as i64;
+        self.cost_usd += planned.cost_usd;
+        self
+    }
+
+    /// Set the environment snapshot
+    ///
+    /// Generations recorded without one get [`GenerationEnvironment::current`].
+    pub fn with_environment(mut self, environment: GenerationEnvironment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// Tasks that have not completed yet (pending, failed or interrupted)
+    pub fn unfinished_tasks(&self) -> Vec<&PlanTask> {
+        self.plan
+            .iter()
+            .flat_map(|p| &p.tasks)
+            .filter(|t| !matches!(t.status, TaskStatus::Completed | TaskStatus::Skipped))
+            .collect()
+    }
+}
+
+/// A file produced by a generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationArtifact {
+    /// Unique artifact identifier
+    pub id: String,
+    /// Owning generation
+    pub generation_id: String,
+    /// Path relative to the generation's output directory
+    pub file_path: String,
+    /// Plan task that produced the file, if known
+    pub task_id: Option<String>,
+    /// Starts at 1 and increases each time the file is regenerated
+    pub version: i64,
+    /// Generated content
+    pub content: String,
+    /// Language, if known
+    pub language: Option<String>,
+    /// Whether the file did not exist when generated
+    pub is_new: bool,
+    /// Review decision
+    pub decision: ArtifactDecision,
+    /// Syntax validation outcome, if the file was validated
+    pub validation_status: Option<ValidationStatus>,
+    /// Syntax errors found during validation
+    pub validation_errors: Vec<SyntaxError>,
+    /// When the decision was made
+    pub decided_at: Option<DateTime<Utc>>,
+    /// When the content was written to disk
+    pub applied_at: Option<DateTime<Utc>>,
+    /// When the artifact was recorded
+    pub created_at: DateTime<Utc>,
+}
+
+impl GenerationArtifact {
+    /// Create a new pending artifact
+    pub fn new(
+        generation_id: impl Into<String>,
+        file_path: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            generation_id: generation_id.into(),
+            file_path: file_path.into(),
+            task_id: None,
+            version: 1,
+            content: content.into(),
+            language: None,
+            is_new: true,
+            decision: ArtifactDecision::Pending,
+            validation_status: None,
+            validation_errors: Vec::new(),
+            decided_at: None,
+            applied_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Whether the artifact has been written to disk
+    pub fn is_applied(&self) -> bool {
+        self.applied_at.is_some()
+    }
+
+    /// Whether the artifact failed syntax validation
+    pub fn has_syntax_errors(&self) -> bool {
+        self.validation_status == Some(ValidationStatus::Invalid)
+    }
+}
+
+/// Columns selected for a [`Generation`]
+const GENERATION_COLUMNS: &str = "id, project_id, feature_id, session_id, description, output_dir, status, tokens_used, cost_usd, duration_ms, plan, environment, created_at, updated_at";
+
+/// Which generations to list; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct GenerationFilter {
+    pub project_id: Option<String>,
+    pub feature_id: Option<String>,
+    pub session_id: Option<String>,
+    pub status: Option<GenerationStatus>,
+    /// Most generations to return (default 50)
+    pub limit: Option<i64>,
+}
+
+/// A generation in a list, without its plan or environment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationSummary {
+    pub id: String,
+    pub project_id: Option<String>,
+    pub feature_id: Option<String>,
+    pub session_id: Option<String>,
+    pub description: String,
+    pub status: GenerationStatus,
+    pub tokens_used: i64,
+    pub cost_usd: f64,
+    pub duration_ms: i64,
+    /// Number of files produced
+    pub file_count: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+impl GenerationSummary {
+    fn new(generation: Generation, file_count: usize) -> Self {
+        Self {
+            id: generation.id,
+            project_id: generation.project_id,
+            feature_id: generation.feature_id,
+            session_id: generation.session_id,
+            description: generation.description,
+            status: generation.status,
+            tokens_used: generation.tokens_used,
+            cost_usd: generation.cost_usd,
+            duration_ms: generation.duration_ms,
+            file_count,
+            created_at: generation.created_at,
+        }
+    }
+}
+
+/// A generation with all of its files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationDetail {
+    pub generation: Generation,
+    pub artifacts: Vec<GenerationArtifact>,
+    /// Review findings across the files
+    pub findings: Vec<SecurityFinding>,
+}
+
+/// Generation repository for database operations
+pub struct GenerationRepository<'a> {
+    db: &'a Database,
+}
+
+impl<'a> GenerationRepository<'a> {
+    /// Create a new generation repository
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    /// Insert a generation record
+    pub async fn create(&self, generation: &Generation) -> Result<()> {
+        let plan = generation.plan.as_ref().map(plan_to_json).transpose()?;
+        let environment = generation
+            .environment
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| Error::Parse(format!("Failed to serialize environment: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO generations (id, project_id, feature_id, session_id, description, output_dir, status, tokens_used, cost_usd, duration_ms, plan, environment, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&generation.id)
+        .bind(&generation.project_id)
+        .bind(&generation.feature_id)
+        .bind(&generation.session_id)
+        .bind(&generation.description)
+        .bind(&generation.output_dir)
+        .bind(generation.status.as_str())
+        .bind(generation.tokens_used)
+        .bind(generation.cost_usd)
+        .bind(generation.duration_ms)
+        .bind(plan)
+        .bind(environment)
+        .bind(generation.created_at)
+        .bind(generation.updated_at)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get a generation by ID
+    pub async fn get(&self, id: &str) -> Result<Option<Generation>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM generations WHERE id = ?",
+            GENERATION_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row.map(|r| self.row_to_generation(r)))
+    }
+
+    /// List generations, newest first, with their file counts
+    pub async fn list(&self, filter: &GenerationFilter) -> Result<Vec<GenerationSummary>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}, (SELECT COUNT(*) FROM generation_artifacts a WHERE a.generation_id = generations.id) AS file_count
+            FROM generations
+            WHERE (?1 IS NULL OR project_id = ?1)
+              AND (?2 IS NULL OR feature_id = ?2)
+              AND (?3 IS NULL OR session_id = ?3)
+              AND (?4 IS NULL OR status = ?4)
+            ORDER BY created_at DESC
+            LIMIT ?5
+            "#,
+            GENERATION_COLUMNS
+        ))
+        .bind(&filter.project_id)
+        .bind(&filter.feature_id)
+        .bind(&filter.session_id)
+        .bind(filter.status.map(|s| s.as_str()))
+        .bind(filter.limit.unwrap_or(50))
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let file_count: i64 = r.get("file_count");
+                GenerationSummary::new(self.row_to_generation(r), file_count as usize)
+            })
+            .collect())
+    }
+
+    /// Delete a generation; its artifacts and spec ledger entries go with it
+    pub async fn delete(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM generations WHERE id = ?")
+            .bind(id)
+            .execute(self.db.pool())
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Update status, usage totals and run time
+    pub async fn update_status(
+        &self,
+        id: &str,
+        status: GenerationStatus,
+        tokens_used: i64,
+        cost_usd: f64,
+        duration_ms: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE generations SET status = ?, tokens_used = ?, cost_usd = ?, duration_ms = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(status.as_str())
+        .bind(tokens_used)
+        .bind(cost_usd)
+        .bind(duration_ms)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Store the execution plan with its current task statuses
+    pub async fn update_plan(&self, id: &str, plan: &ExecutionPlan) -> Result<()> {
+        sqlx::query("UPDATE generations SET plan = ?, updated_at = ? WHERE id = ?")
+            .bind(plan_to_json(plan)?)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Insert or replace an artifact (keyed by generation and path)
+    ///
+    /// Replacing an artifact bumps its version and makes it unapplied again.
+    /// The content goes to the content store, shared with any earlier
+    /// version or generation that produced the same file.
+    pub async fn save_artifact(&self, artifact: &GenerationArtifact) -> Result<()> {
+        let validation_errors = if artifact.validation_errors.is_empty() {
+            None
+        } else {
+            Some(
+                serde_json::to_string(&artifact.validation_errors).map_err(|e| {
+                    Error::Parse(format!("Failed to serialize validation errors: {}", e))
+                })?,
+            )
+        };
+
+        let content_hash = ContentStore::new(self.db.pool())
+            .put(&artifact.content)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO generation_artifacts (id, generation_id, file_path, task_id, version, content, content_hash, language, is_new, decision, validation_status, validation_errors, decided_at, applied_at, created_at)
+            VALUES (?, ?, ?, ?, ?, '', ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(generation_id, file_path) DO UPDATE SET
+                task_id = COALESCE(excluded.task_id, generation_artifacts.task_id),
+                version = generation_artifacts.version + 1,
+                content = excluded.content,
+                content_hash = excluded.content_hash,
+                language = excluded.language,
+                is_new = excluded.is_new,
+                decision = excluded.decision,
+                validation_status = excluded.validation_status,
+                validation_errors = excluded.validation_errors,
+                decided_at = excluded.decided_at,
+                applied_at = excluded.applied_at
+            "#,
+        )
+        .bind(&artifact.id)
+        .bind(&artifact.generation_id)
+        .bind(&artifact.file_path)
+        .bind(&artifact.task_id)
+        .bind(artifact.version)
+        .bind(content_hash)
+        .bind(&artifact.language)
+        .bind(artifact.is_new)
+        .bind(artifact.decision.as_str())
+        .bind(artifact.validation_status.map(|s| s.as_str()))
+        .bind(validation_errors)
+        .bind(artifact.decided_at)
+        .bind(artifact.applied_at)
+        .bind(artifact.created_at)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// List artifacts for a generation in path order
+    pub async fn list_artifacts(&self, generation_id: &str) -> Result<Vec<GenerationArtifact>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT a.id, a.generation_id, a.file_path, a.task_id, a.version, a.content, b.data AS content_blob,
+                   a.language, a.is_new, a.decision, a.validation_status, a.validation_errors, a.decided_at, a.applied_at, a.created_at
+            FROM generation_artifacts a
+            LEFT JOIN content_blobs b ON b.hash = a.content_hash
+            WHERE a.generation_id = ?
+            ORDER BY a.file_path
+            "#,
+        )
+        .bind(generation_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        rows.into_iter().map(|r| self.row_to_artifact(r)).collect()
+    }
+
+    /// Record a review decision for one artifact
+    pub async fn set_decision(
+        &self,
+        generation_id: &str,
+        file_path: &str,
+        decision: ArtifactDecision,
+    ) -> Result<()> {
+        let result = sqlx::query(
+            "UPDATE generation_artifacts SET decision = ?, decided_at = ? WHERE generation_id = ? AND file_path = ?",
+        )
+        .bind(decision.as_str())
+        .bind(Utc::now())
+        .bind(generation_id)
+        .bind(file_path)
+        .execute(self.db.pool())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound(format!(
+                "Artifact '{}' not found in generation {}",
+                file_path, generation_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Mark an artifact as written to disk
+    pub async fn mark_applied(&self, generation_id: &str, file_path: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE generation_artifacts SET applied_at = ? WHERE generation_id = ? AND file_path = ?",
+        )
+        .bind(Utc::now())
+        .bind(generation_id)
+        .bind(file_path)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replace the security findings recorded for one artifact
+    pub async fn save_findings(
+        &self,
+        generation_id: &str,
+        file_path: &str,
+        findings: &[SecurityFinding],
+    ) -> Result<()> {
+        let mut tx = self.db.pool().begin().await?;
+        sqlx::query(
+            "DELETE FROM review_findings WHERE generation_id = ? AND file_path = ? AND source = 'security_audit'",
+        )
+        .bind(generation_id)
+        .bind(file_path)
+        .execute(&mut *tx)
+        .await?;
+
+        for finding in findings {
+            sqlx::query(
+                r#"
+                INSERT INTO review_findings (id, generation_id, file_path, source, severity, cwe, category, description, line, suggestion, blocking, created_at)
+                VALUES (?, ?, ?, 'security_audit', ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(generation_id)
+            .bind(file_path)
+            .bind(finding.severity.to_string())
+            .bind(&finding.cwe)
+            .bind(&finding.category)
+            .bind(&finding.description)
+            .bind(finding.line.map(|l| l as i64))
+            .bind(&finding.suggestion)
+            .bind(finding.blocking)
+            .bind(Utc::now())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// List review findings for a generation, by file and line
+    pub async fn list_findings(&self, generation_id: &str) -> Result<Vec<SecurityFinding>> {
+        let rows = sqlx::query(
+            "SELECT file_path, severity, cwe, category, description, line, suggestion, blocking FROM review_findings WHERE generation_id = ? ORDER BY file_path, line",
+        )
+        .bind(generation_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| SecurityFinding {
+                severity: parse_severity(row.get("severity")),
+                cwe: row.get::<Option<String>, _>("cwe").unwrap_or_default(),
+                category: row.get("category"),
+                description: row.get("description"),
+                file: row.get("file_path"),
+                line: row.get::<Option<i64>, _>("line").map(|l| l as usize),
+                suggestion: row.get("suggestion"),
+                blocking: row.get("blocking"),
+            })
+            .collect())
+    }
+
+    /// Convert a database row to a Generation
+    fn row_to_generation(&self, row: sqlx::sqlite::SqliteRow) -> Generation {
+        Generation {
+            id: row.get("id"),
+            project_id: row.get("project_id"),
+            feature_id: row.get("feature_id"),
+            session_id: row.get("session_id"),
+            description: row.get("description"),
+            output_dir: row.get("output_dir"),
+            status: GenerationStatus::parse(row.get("status")).unwrap_or_default(),
+            tokens_used: row.get("tokens_used"),
+            cost_usd: row.get("cost_usd"),
+            duration_ms: row.get("duration_ms"),
+            plan: row
+                .get::<Option<String>, _>("plan")
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            environment: row
+                .get::<Option<String>, _>("environment")
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        }
+    }
+
+    /// Convert a database row to a GenerationArtifact
+    fn row_to_artifact(&self, row: sqlx::sqlite::SqliteRow) -> Result<GenerationArtifact> {
+        Ok(GenerationArtifact {
+            id: row.get("id"),
+            generation_id: row.get("generation_id"),
+            file_path: row.get("file_path"),
+            task_id: row.get("task_id"),
+            version: row.get("version"),
+            content: content::decode(row.get("content"), row.get("content_blob"))?,
+            language: row.get("language"),
+            is_new: row.get("is_new"),
+            decision: ArtifactDecision::parse(row.get("decision")).unwrap_or_default(),
+            validation_status: row
+                .get::<Option<String>, _>("validation_status")
+                .and_then(|s| ValidationStatus::parse(&s)),
+            validation_errors: row
+                .get::<Option<String>, _>("validation_errors")
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+            decided_at: row.get("decided_at"),
+            applied_at: row.get("applied_at"),
+            created_at: row.get("created_at"),
+        })
+    }
+}
+
+fn parse_severity(s: &str) -> IssueSeverity {
+    match s {
+        "critical" => IssueSeverity::Critical,
+        "major" => IssueSeverity::Major,
+        "minor" => IssueSeverity::Minor,
+        _ => IssueSeverity::Info,
+    }
+}
+
+/// Serialize an execution plan for storage
+fn plan_to_json(plan: &ExecutionPlan) -> Result<String> {
+    serde_json::to_string(plan)
+        .map_err(|e| Error::Parse(format!("Failed to serialize execution plan: {}", e)))
+}
+
+/// Diff preview for one generated file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileReview {
+    /// Path relative to the output directory
+    pub path: String,
+    /// Whether the file does not exist on disk yet
+    pub is_new: bool,
+    /// Language, if known
+    pub language: Option<String>,
+    /// Current review decision
+    pub decision: ArtifactDecision,
+    /// Whether the artifact has been written to disk
+    pub applied: bool,
+    /// Syntax validation outcome, if the file was validated
+    pub validation_status: Option<ValidationStatus>,
+    /// Syntax errors found during validation
+    pub validation_errors: Vec<SyntaxError>,
+    /// Lines added relative to the file on disk
+    pub additions: usize,
+    /// Lines removed relative to the file on disk
+    pub deletions: usize,
+    /// Unified diff against the file on disk (empty if identical)
+    pub diff: String,
+}
+
+/// Review of a generation: the record plus per-file diffs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationReview {
+    /// The generation record
+    pub generation: Generation,
+    /// Per-file previews
+    pub files: Vec<FileReview>,
+}
+
+impl GenerationReview {
+    /// Files that have not been written to disk yet
+    pub fn unapplied(&self) -> impl Iterator<Item = &FileReview> {
+        self.files.iter().filter(|f| !f.applied)
+    }
+}
+
+/// Resolve an artifact path under the output directory, rejecting escapes
+pub(crate) fn artifact_target(output_dir: &str, file_path: &str) -> Result<PathBuf> {
+    let relative = Path::new(file_path);
+    let escapes = relative.components().any(|c| {
+        matches!(
+            c,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_)
+        )
+    });
+    if escapes {
+        return Err(Error::InvalidInput(format!(
+            "Generated path '{}' escapes the output directory",
+            file_path
+        )));
+    }
+    Ok(Path::new(output_dir).join(relative))
+}
+
+/// Build a preview of one artifact against the current file on disk
+fn review_artifact(output_dir: &str, artifact: &GenerationArtifact) -> Result<FileReview> {
+    let target = artifact_target(output_dir, &artifact.file_path)?;
+    let existing = std::fs::read_to_string(&target).ok();
+    let current = existing.as_deref();
+
+    let (additions, deletions) = line_diff(current.unwrap_or(""), &artifact.content)
+        .iter()
+        .fold((0, 0), |(added, removed), line| match line {
+            DiffLine::Added(_) => (added + 1, removed),
+            DiffLine::Removed(_) => (added, removed + 1),
+            DiffLine::Context(_) => (added, removed),
+        });
+
+    Ok(FileReview {
+        path: artifact.file_path.clone(),
+        is_new: existing.is_none(),
+        language: artifact.language.clone(),
+        decision: artifact.decision,
+        applied: artifact.is_applied(),
+        validation_status: artifact.validation_status,
+        validation_errors: artifact.validation_errors.clone(),
+        additions,
+        deletions,
+        diff: unified_diff(Path::new(&artifact.file_path), current, &artifact.content),
+    })
+}
+
+/// Fail if writing the artifacts would break the output directory's guardrails
+async fn check_guardrails(generation: &Generation, artifacts: &[GenerationArtifact]) -> Result<()> {
+    let config = Config::load().map_err(|e| Error::ConfigError(e.to_string()))?;
+    let root = Path::new(&generation.output_dir);
+    let guardrails = Guardrails::load(&config.guardrails, root)?;
+    let writes: Vec<FileWrite<'_>> = artifacts
+        .iter()
+        .map(|a| FileWrite::read(root, &a.file_path, &a.content))
+        .collect();
+    guardrails
+        .enforce(
+            &HooksManager::from_config(&config.hooks),
+            &generation.id,
+            &writes,
+        )
+        .await
+}
+
+/// Write artifacts to disk as one staged batch, then record each as written
+///
+/// Either every file lands or none do; an apply that was interrupted has to
+/// be recovered before another one runs in the same output directory.
+async fn write_artifacts(
+    repo: &GenerationRepository<'_>,
+    events: &AgentEventWriter,
+    generation: &Generation,
+    artifacts: &[GenerationArtifact],
+) -> Result<()> {
+    if artifacts.is_empty() {
+        return Ok(());
+    }
+    let root = Path::new(&generation.output_dir);
+    staging::ensure_clean(root)?;
+    for artifact in artifacts {
+        artifact_target(&generation.output_dir, &artifact.file_path)?;
+    }
+    staging::write_all(
+        root,
+        &generation.id,
+        artifacts
+            .iter()
+            .map(|a| (a.file_path.as_str(), a.content.as_str())),
+    )?;
+
+    let total = artifacts.len();
+    for (i, artifact) in artifacts.iter().enumerate() {
+        record_written(repo, events, generation, artifact, (i + 1, total)).await?;
+    }
+    Ok(())
+}
+
+/// Mark a written artifact applied and emit a file written event
+///
+/// For project generations the written content also becomes the edit
+/// detection baseline, so later manual edits to it are flagged.
+/// `position` is the 1-based index and total of the batch being written.
+async fn record_written(
+    repo: &GenerationRepository<'_>,
+    events: &AgentEventWriter,
+    generation: &Generation,
+    artifact: &GenerationArtifact,
+    position: (usize, usize),
+) -> Result<()> {
+    repo.mark_applied(&generation.id, &artifact.file_path)
+        .await?;
+
+    if let Some(project_id) = generation
+        .project_id
+        .as_deref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+    {
+        let feature_id = generation
+            .feature_id
+            .as_deref()
+            .and_then(|id| Uuid::parse_str(id).ok());
+        EditDetectionService::new(repo.db.pool().clone())
+            .track_generated_file(
+                project_id,
+                feature_id,
+                &artifact.file_path,
+                &artifact.content,
+            )
+            .await?;
+    }
+
+    let writer_id = AgentId::parse(&generation.id).unwrap_or_default();
+    events.emit_file_written(
+        &writer_id,
+        FileEventData::new(
+            &artifact.file_path,
+            &artifact.content,
+            artifact.is_new,
+            position.0,
+            position.1,
+        ),
+    );
+    Ok(())
+}
+
+/// Build a pending artifact for a generated file
+///
+/// Files that failed syntax validation or have blocking security findings
+/// are recorded as rejected.
+fn artifact_for_file(
+    generation_id: &str,
+    task_id: Option<&str>,
+    file: &GeneratedFile,
+) -> GenerationArtifact {
+    let mut artifact =
+        GenerationArtifact::new(generation_id, file.path.to_string_lossy(), &file.content);
+    artifact.task_id = task_id.map(str::to_string);
+    artifact.language = file.language.clone();
+    artifact.is_new = file.is_new;
+    if let Some(ref validation) = file.validation {
+        artifact.validation_status = Some(validation.status);
+        artifact.validation_errors = validation.errors.clone();
+        if validation.is_invalid() {
+            artifact.decision = ArtifactDecision::Rejected;
+            artifact.decided_at = Some(Utc::now());
+        }
+    }
+    if file.is_blocked() {
+        artifact.decision = ArtifactDecision::Rejected;
+        artifact.decided_at = Some(Utc::now());
+    }
+    artifact
+}
+
+/// Record a generated file as an artifact along with its audit findings
+async fn save_file(
+    repo: &GenerationRepository<'_>,
+    generation_id: &str,
+    task_id: Option<&str>,
+    file: &GeneratedFile,
+) -> Result<()> {
+    repo.save_artifact(&artifact_for_file(generation_id, task_id, file))
+        .await?;
+    repo.save_findings(
+        generation_id,
+        &file.path.to_string_lossy(),
+        &file.security_findings,
+    )
+    .await
+}
+
+async fn get_generation(repo: &GenerationRepository<'_>, id: &str) -> Result<Generation> {
+    repo.get(id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Generation not found: {}", id)))
+}
+
+// ============================================================================
+// Public API functions
+// ============================================================================
+
+/// Record a completed generation and its files as pending artifacts
+///
+/// `output_dir` is the directory the generated paths are relative to. No
+/// files are written; use [`decide`] or [`apply`] to write them. Files that
+/// failed syntax validation are recorded as rejected.
+pub async fn record(
+    db: &Database,
+    generation: Generation,
+    result: &GenerationResult,
+) -> Result<Generation> {
+    let repo = GenerationRepository::new(db);
+
+    let mut generation = generation;
+    fill_provenance(db, &mut generation).await;
+    generation.status = GenerationStatus::Completed;
+    generation.tokens_used = result.tokens_used as i64;
+    generation.cost_usd = result.cost_usd;
+    repo.create(&generation).await?;
+
+    for file in &result.files {
+        save_file(&repo, &generation.id, None, file).await?;
+    }
+
+    publish_completed(&generation, result.files.len()).await;
+    Ok(generation)
+}
+
+/// Attribute a new generation to the active session and snapshot its environment
+async fn fill_provenance(db: &Database, generation: &mut Generation) {
+    if generation.session_id.is_none() {
+        match SessionRepository::new(db.pool().clone()).get_active().await {
+            Ok(session) => generation.session_id = session.map(|s| s.id.to_string()),
+            Err(e) => tracing::debug!(error = %e, "Could not look up the active session"),
+        }
+    }
+    generation
+        .environment
+        .get_or_insert_with(GenerationEnvironment::current);
+}
+
+/// Tell event subscribers a generation completed and log their annotations
+async fn publish_completed(generation: &Generation, files: usize) {
+    let outcome = events::global()
+        .publish(CoreEvent::GenerationCompleted {
+            generation_id: generation.id.clone(),
+            project_id: generation.project_id.clone(),
+            description: generation.description.clone(),
+            files,
+            cost_usd: generation.cost_usd,
+        })
+        .await;
+    for annotation in &outcome.annotations {
+        tracing::info!(subscriber = %annotation.subscriber, generation_id = %generation.id, "{}", annotation.text);
+    }
+}
+
+/// A plan task that failed during a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskFailure {
+    /// ID of the failed task
+    pub task_id: String,
+    /// Error the task failed with
+    pub error: String,
+}
+
+/// Outcome of running or resuming a planned generation
+#[derive(Debug, Clone)]
+pub struct PlanRun {
+    /// The generation record, with totals across all runs
+    pub generation: Generation,
+    /// Files, tokens and cost from the tasks executed in this run only
+    pub result: GenerationResult,
+    /// Tasks that failed in this run
+    pub failures: Vec<TaskFailure>,
+}
+
+/// Plan for a generation that runs the whole description as one coding task
+pub fn single_task_plan(description: &str) -> ExecutionPlan {
+    ExecutionPlan::new(description)
+        .with_review(false)
+        .with_tests(false)
+        .with_task(PlanTask::coding("task-1", description))
+}
+
+/// Most coding tasks a planned generation is split into
+const MAX_PLANNED_TASKS: usize = 8;
+
+/// A generation's plan and what the planner spent making it
+#[derive(Debug, Clone)]
+pub struct GenerationPlan {
+    /// Tasks to run
+    pub plan: ExecutionPlan,
+    /// Tokens the planner used
+    pub tokens_used: u32,
+    /// Estimated planner cost in USD
+    pub cost_usd: f64,
+}
+
+impl From<ExecutionPlan> for GenerationPlan {
+    fn from(plan: ExecutionPlan) -> Self {
+        Self {
+            plan,
+            tokens_used: 0,
+            cost_usd: 0.0,
+        }
+    }
+}
+
+/// Plan a generation of `description` as separate coding tasks, so a failure
+/// part way through can be resumed without redoing the parts that completed
+///
+/// A description that lays out its parts as `##` sections or a list gets one
+/// task per part. Otherwise the planner prompt splits it through `planner`,
+/// whose cost tracker records the spend; see [`planner_client`]. A
+/// description it keeps whole, or any planning failure (including no
+/// planner), runs as a single task. Charge the returned usage to the
+/// generation with [`Generation::with_planning`].
+pub async fn plan_generation(description: &str, planner: Option<&LlmClient>) -> GenerationPlan {
+    if let Some(plan) = outline_plan(description) {
+        return plan.into();
+    }
+    let Some(client) = planner else {
+        return single_task_plan(description).into();
+    };
+    match planner_plan(description, client).await {
+        Ok(planned) => planned,
+        Err(e) => {
+            tracing::debug!(error = %e, "Planning failed; generating as one task");
+            single_task_plan(description).into()
+        }
+    }
+}
+
+/// An LLM client for [`plan_generation`] that records its spend on
+/// `cost_tracker`
+///
+/// `None` without an API key.
+pub fn planner_client(
+    config: &Config,
+    cost_tracker: Arc<CostTracker>,
+) -> Result<Option<LlmClient>> {
+    let Some(api_key) = config
+        .llm
+        .resolved_api_key()
+        .map_err(|e| Error::ConfigError(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+    let client = LlmClient::builder()
+        .config(config.llm.clone())
+        .api_key(api_key)
+        .cost_tracker(cost_tracker)
+        .build()?;
+    Ok(Some(client))
+}
+
+/// One coding task per `##` section or top-level list item of `description`,
+/// if it has at least two
+pub fn outline_plan(description: &str) -> Option<ExecutionPlan> {
+    if description
+        .lines()
+        .any(|l| l.trim_start().starts_with("## "))
+    {
+        let plan = super::spec::spec_plan(description);
+        return (plan.tasks.len() > 1).then_some(plan);
+    }
+
+    let mut intro = Vec::new();
+    let mut items: Vec<String> = Vec::new();
+    for line in description.lines() {
+        match list_item(line) {
+            Some(item) => items.push(item.to_string()),
+            // Indented lines continue the item above
+            None if !items.is_empty() && line.starts_with([' ', '\t']) => {
+                let last = items.len() - 1;
+                items[last].push('\n');
+                items[last].push_str(line.trim());
+            }
+            None if items.is_empty() => intro.push(line),
+            None => {}
+        }
+    }
+    if items.len() < 2 || items.len() > MAX_PLANNED_TASKS {
+        return None;
+    }
+
+    let intro = intro.join("\n").trim().to_string();
+    let tasks = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let task = if intro.is_empty() {
+                item.clone()
+            } else {
+                format!("{}\n\n{}", intro, item)
+            };
+            PlanTask::coding(format!("task-{}", i + 1), task)
+        })
+        .collect();
+    Some(
+        ExecutionPlan::new(description)
+            .with_review(false)
+            .with_tests(false)
+            .with_tasks(tasks),
+    )
+}
+
+/// The text of a top-level `-`, `*` or numbered list item
+fn list_item(line: &str) -> Option<&str> {
+    if let Some(rest) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some(rest.trim()).filter(|r| !r.is_empty());
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = line[digits..]
+        .strip_prefix(". ")
+        .or_else(|| line[digits..].strip_prefix(") "));
+    match rest {
+        Some(rest) if digits > 0 => Some(rest.trim()).filter(|r| !r.is_empty()),
+        _ => None,
+    }
+}
+
+/// Ask the planner prompt to split `description` into coding tasks
+///
+/// A single task when the planner keeps it whole.
+async fn planner_plan(description: &str, client: &LlmClient) -> Result<GenerationPlan> {
+    use crate::agents::planner::PlannerAgent;
+    use crate::agents::traits::Agent;
+    use crate::domain::feature_decomposition::PlanParser;
+
+    let messages = vec![
+        Message::system(PlannerAgent::new().system_prompt()),
+        Message::user(description),
+    ];
+    let response = client.complete(messages, None).await?;
+    let plan = PlanParser::new()
+        .try_parse_json(&response.content)
+        .and_then(|planned| planner_tasks(description, planned))
+        .unwrap_or_else(|| single_task_plan(description));
+    Ok(GenerationPlan {
+        plan,
+        tokens_used: response.tokens_used,
+        cost_usd: estimate_cost(
+            &response.model,
+            response.input_tokens,
+            response.output_tokens,
+        ),
+    })
+}
+
+/// The coding tasks of a planner's plan, each carrying the full description
+/// so it can be generated on its own
+fn planner_tasks(description: &str, planned: ExecutionPlan) -> Option<ExecutionPlan> {
+    let coding: Vec<PlanTask> = planned
+        .tasks
+        .into_iter()
+        .filter(|t| t.is_for_agent("coder"))
+        .take(MAX_PLANNED_TASKS)
+        .collect();
+    if coding.len() < 2 {
+        return None;
+    }
+    let ids: Vec<String> = coding.iter().map(|t| t.id.clone()).collect();
+    let tasks = coding
+        .into_iter()
+        .map(|task| {
+            let depends_on = task
+                .depends_on
+                .into_iter()
+                .filter(|id| ids.contains(id))
+                .collect();
+            PlanTask::coding(
+                task.id,
+                format!(
+                    "{}\n\nThis is one part of: {}",
+                    task.description, description
+                ),
+            )
+            .with_dependencies(depends_on)
+            .with_priority(task.priority)
+        })
+        .collect();
+    Some(
+        ExecutionPlan::new(description)
+            .with_review(false)
+            .with_tests(false)
+            .with_tasks(tasks),
+    )
+}
+
+/// Record a planned generation and execute its tasks
+///
+/// `run_task` generates the files for one task without writing them. Each
+/// task's files are recorded as pending artifacts as soon as it completes and
+/// task status is persisted after every task, so a failure part way through
+/// can be picked up with [`resume`]. A task whose dependencies failed is left
+/// pending. The generation is marked failed if any task did not complete.
+pub async fn start<F, Fut>(
+    db: &Database,
+    generation: Generation,
+    plan: ExecutionPlan,
+    run_task: F,
+) -> Result<PlanRun>
+where
+    F: FnMut(PlanTask) -> Fut,
+    Fut: Future<Output = Result<GenerationResult>>,
+{
+    start_cancellable(db, generation, plan, &CancellationToken::new(), run_task).await
+}
+
+/// [`start`] a generation that stops when `cancel` is cancelled
+///
+/// The task running at the time is dropped and set back to pending, and the
+/// generation is marked [`GenerationStatus::Interrupted`] so it can be
+/// picked up with [`resume`]. Completed tasks keep their artifacts and cost.
+pub async fn start_cancellable<F, Fut>(
+    db: &Database,
+    generation: Generation,
+    plan: ExecutionPlan,
+    cancel: &CancellationToken,
+    run_task: F,
+) -> Result<PlanRun>
+where
+    F: FnMut(PlanTask) -> Fut,
+    Fut: Future<Output = Result<GenerationResult>>,
+{
+    let repo = GenerationRepository::new(db);
+    let mut generation = generation.with_plan(plan);
+    fill_provenance(db, &mut generation).await;
+    repo.create(&generation).await?;
+    run_plan(&repo, generation, cancel, run_task).await
+}
+
+/// Resume a planned generation, re-running only tasks that did not complete
+///
+/// Failed and interrupted tasks are reset to pending. Artifacts and usage from
+/// completed tasks stay attached to the generation; usage from this run is
+/// added to the totals.
+pub async fn resume<F, Fut>(db: &Database, generation_id: &str, run_task: F) -> Result<PlanRun>
+where
+    F: FnMut(PlanTask) -> Fut,
+    Fut: Future<Output = Result<GenerationResult>>,
+{
+    resume_cancellable(db, generation_id, &CancellationToken::new(), run_task).await
+}
+
+/// [`resume`] a generation that stops when `cancel` is cancelled
+///
+/// See [`start_cancellable`].
+pub async fn resume_cancellable<F, Fut>(
+    db: &Database,
+    generation_id: &str,
+    cancel: &CancellationToken,
+    run_task: F,
+) -> Result<PlanRun>
+where
+    F: FnMut(PlanTask) -> Fut,
+    Fut: Future<Output = Result<GenerationResult>>,
+{
+    let repo = GenerationRepository::new(db);
+    let mut generation = get_generation(&repo, generation_id).await?;
+
+    let Some(plan) = generation.plan.as_mut() else {
+        return Err(Error::InvalidInput(format!(
+            "Generation {} has no task plan and cannot be resumed",
+            generation_id
+        )));
+    };
+    if plan.is_complete() {
+        return Err(Error::InvalidInput(format!(
+            "Generation {} has no unfinished tasks",
+            generation_id
+        )));
+    }
+    for task in &mut plan.tasks {
+        if matches!(task.status, TaskStatus::Failed | TaskStatus::InProgress) {
+            task.status = TaskStatus::Pending;
+        }
+    }
+
+    run_plan(&repo, generation, cancel, run_task).await
+}
+
+/// Execute the ready tasks of a generation's plan until none are left or
+/// `cancel` is cancelled
+async fn run_plan<F, Fut>(
+    repo: &GenerationRepository<'_>,
+    mut generation: Generation,
+    cancel: &CancellationToken,
+    mut run_task: F,
+) -> Result<PlanRun>
+where
+    F: FnMut(PlanTask) -> Fut,
+    Fut: Future<Output = Result<GenerationResult>>,
+{
+    let mut plan = generation
+        .plan
+        .take()
+        .unwrap_or_else(|| ExecutionPlan::new(&generation.description));
+    let mut run = GenerationResult::default();
+    let mut failures = Vec::new();
+    let started = Instant::now();
+    let prior_duration_ms = generation.duration_ms;
+
+    generation.status = GenerationStatus::Running;
+    repo.update_status(
+        &generation.id,
+        generation.status,
+        generation.tokens_used,
+        generation.cost_usd,
+        generation.duration_ms,
+    )
+    .await?;
+
+    let mut interrupted = false;
+    while let Some(task) = plan.ready_tasks().first().map(|t| (*t).clone()) {
+        if cancel.is_cancelled() {
+            interrupted = true;
+            break;
+        }
+        if let Some(t) = plan.get_task_mut(&task.id) {
+            t.start();
+        }
+        repo.update_plan(&generation.id, &plan).await?;
+
+        let task_id = task.id.clone();
+        let outcome = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => None,
+            outcome = run_task(task) => Some(outcome),
+        };
+        let Some(outcome) = outcome else {
+            tracing::info!(generation_id = %generation.id, task_id = %task_id, "Generation interrupted");
+            if let Some(t) = plan.get_task_mut(&task_id) {
+                t.status = TaskStatus::Pending;
+            }
+            interrupted = true;
+            break;
+        };
+        match outcome {
+            Ok(result) => {
+                for file in &result.files {
+                    save_file(repo, &generation.id, Some(&task_id), file).await?;
+                }
+                generation.tokens_used += result.tokens_used as i64;
+                generation.cost_usd += result.cost_usd;
+                merge_result(&mut run, result);
+                if let Some(t) = plan.get_task_mut(&task_id) {
+                    t.complete();
+                }
+            }
+            Err(e) => {
+                tracing::warn!(generation_id = %generation.id, task_id = %task_id, error = %e, "Generation task failed");
+                if let Some(t) = plan.get_task_mut(&task_id) {
+                    t.fail();
+                }
+                failures.push(TaskFailure {
+                    task_id,
+                    error: e.to_string(),
+                });
+            }
+        }
+
+        repo.update_plan(&generation.id, &plan).await?;
+        generation.duration_ms = prior_duration_ms + started.elapsed().as_millis() as i64;
+        repo.update_status(
+            &generation.id,
+            generation.status,
+            generation.tokens_used,
+            generation.cost_usd,
+            generation.duration_ms,
+        )
+        .await?;
+    }
+
+    generation.status = if plan.is_complete() {
+        GenerationStatus::Completed
+    } else if interrupted {
+        GenerationStatus::Interrupted
+    } else {
+        GenerationStatus::Failed
+    };
+    generation.duration_ms = prior_duration_ms + started.elapsed().as_millis() as i64;
+    repo.update_plan(&generation.id, &plan).await?;
+    repo.update_status(
+        &generation.id,
+        generation.status,
+        generation.tokens_used,
+        generation.cost_usd,
+        generation.duration_ms,
+    )
+    .await?;
+    generation.plan = Some(plan);
+    if generation.status == GenerationStatus::Completed {
+        publish_completed(&generation, run.files.len()).await;
+    }
+
+    Ok(PlanRun {
+        generation,
+        result: run,
+        failures,
+    })
+}
+
+/// Fold one task's result into the run totals; later files replace earlier ones
+fn merge_result(run: &mut GenerationResult, result: GenerationResult) {
+    run.tokens_used += result.tokens_used;
+    run.cost_usd += result.cost_usd;
+    for file in result.files {
+        run.files.retain(|f| f.path != file.path);
+        run.files.push(file);
+    }
+    let accepted = run.files.iter().filter(|f| !f.has_syntax_errors());
+    run.files_created = accepted.clone().filter(|f| f.is_new).count();
+    run.files_modified = accepted.count() - run.files_created;
+}
+
+/// List recorded generations, newest first
+pub async fn list(db: &Database, filter: &GenerationFilter) -> Result<Vec<GenerationSummary>> {
+    GenerationRepository::new(db).list(filter).await
+}
+
+/// Get a generation with all of its files
+pub async fn show(db: &Database, generation_id: &str) -> Result<GenerationDetail> {
+    let repo = GenerationRepository::new(db);
+    let generation = get_generation(&repo, generation_id).await?;
+    let artifacts = repo.list_artifacts(generation_id).await?;
+    let findings = repo.list_findings(generation_id).await?;
+    Ok(GenerationDetail {
+        generation,
+        artifacts,
+        findings,
+    })
+}
+
+/// Delete a generation record and its stored files
+///
+/// Files already written to the project are left alone.
+pub async fn delete(db: &Database, generation_id: &str) -> Result<()> {
+    if !GenerationRepository::new(db).delete(generation_id).await? {
+        return Err(Error::NotFound(format!(
+            "Generation not found: {}",
+            generation_id
+        )));
+    }
+    Ok(())
+}
+
+/// Outcome of regenerating one artifact
+#[derive(Debug, Clone)]
+pub struct Regeneration {
+    /// The new version of the artifact, pending review
+    pub artifact: GenerationArtifact,
+    /// Version that was replaced
+    pub previous_version: i64,
+    /// Task that was re-run
+    pub task_id: String,
+    /// Tokens used by the re-run
+    pub tokens_used: u32,
+    /// Cost of the re-run in USD
+    pub cost_usd: f64,
+}
+
+/// Re-run the task that produced one artifact, with extra instructions
+///
+/// `run_task` gets a copy of the original plan task whose description asks
+/// for just `file_path` and appends `instructions`. Only that file is kept
+/// from the result; the rest of the generation is untouched. The artifact
+/// gets a new version and is left pending, so it goes through review or
+/// [`apply`] like any other file. Usage is added to the generation's totals.
+pub async fn regenerate<F, Fut>(
+    db: &Database,
+    generation_id: &str,
+    file_path: &str,
+    instructions: &str,
+    run_task: F,
+) -> Result<Regeneration>
+where
+    F: FnOnce(PlanTask) -> Fut,
+    Fut: Future<Output = Result<GenerationResult>>,
+{
+    let repo = GenerationRepository::new(db);
+    let mut generation = get_generation(&repo, generation_id).await?;
+    if generation.status == GenerationStatus::Running {
+        return Err(Error::InvalidInput(format!(
+            "Generation {} is still running",
+            generation_id
+        )));
+    }
+    let previous = repo
+        .list_artifacts(generation_id)
+        .await?
+        .into_iter()
+        .find(|a| a.file_path == file_path)
+        .ok_or_else(|| {
+            Error::NotFound(format!(
+                "Artifact '{}' not found in generation {}",
+                file_path, generation_id
+            ))
+        })?;
+
+    // Generations recorded without a plan, or before artifacts knew their
+    // task, ran the whole description as one task
+    let original = previous
+        .task_id
+        .as_deref()
+        .and_then(|id| generation.plan.as_ref()?.get_task(id).cloned())
+        .unwrap_or_else(|| single_task_plan(&generation.description).tasks.remove(0));
+    let task_id = original.id.clone();
+    let mut task = original;
+    task.description = format!(
+        "{}\n\nOnly regenerate the file `{}`; output just that file.\nAdditional instructions: {}",
+        task.description, file_path, instructions
+    );
+
+    let started = Instant::now();
+    let result = run_task(task).await?;
+    let file = result
+        .files
+        .iter()
+        .find(|f| f.path.to_string_lossy() == file_path)
+        .ok_or_else(|| {
+            Error::Other(format!(
+                "Regeneration did not produce '{}'; nothing was changed",
+                file_path
+            ))
+        })?;
+
+    save_file(&repo, &generation.id, Some(&task_id), file).await?;
+    generation.tokens_used += result.tokens_used as i64;
+    generation.cost_usd += result.cost_usd;
+    generation.duration_ms += started.elapsed().as_millis() as i64;
+    repo.update_status(
+        &generation.id,
+        generation.status,
+        generation.tokens_used,
+        generation.cost_usd,
+        generation.duration_ms,
+    )
+    .await?;
+
+    let artifact = repo
+        .list_artifacts(generation_id)
+        .await?
+        .into_iter()
+        .find(|a| a.file_path == file_path)
+        .ok_or_else(|| Error::NotFound(format!("Artifact '{}' disappeared", file_path)))?;
+    tracing::info!(generation_id = %generation.id, file = %file_path, version = artifact.version, "Regenerated artifact");
+
+    Ok(Regeneration {
+        artifact,
+        previous_version: previous.version,
+        task_id,
+        tokens_used: result.tokens_used,
+        cost_usd: result.cost_usd,
+    })
+}
+
+/// Get per-file diffs for a generation against the files currently on disk
+pub async fn review(db: &Database, generation_id: &str) -> Result<GenerationReview> {
+    let repo = GenerationRepository::new(db);
+    let generation = get_generation(&repo, generation_id).await?;
+    let artifacts = repo.list_artifacts(generation_id).await?;
+
+    let files = artifacts
+        .iter()
+        .map(|a| review_artifact(&generation.output_dir, a))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(GenerationReview { generation, files })
+}
+
+/// Record a decision for one file
+///
+/// Accepting a file writes it to disk. Rejecting keeps the artifact stored
+/// so it can be applied later with [`apply`].
+pub async fn decide(
+    db: &Database,
+    generation_id: &str,
+    file_path: &str,
+    decision: ArtifactDecision,
+) -> Result<()> {
+    let repo = GenerationRepository::new(db);
+    let generation = get_generation(&repo, generation_id).await?;
+
+    if decision == ArtifactDecision::Accepted {
+        let artifact = repo
+            .list_artifacts(generation_id)
+            .await?
+            .into_iter()
+            .find(|a| a.file_path == file_path)
+            .ok_or_else(|| {
+                Error::NotFound(format!(
+                    "Artifact '{}' not found in generation {}",
+                    file_path, generation_id
+                ))
+            })?;
+        check_guardrails(&generation, std::slice::from_ref(&artifact)).await?;
+        let events = AgentEventWriter::resume_latest();
+        write_artifacts(&repo, &events, &generation, std::slice::from_ref(&artifact)).await?;
+    }
+
+    repo.set_decision(generation_id, file_path, decision).await
+}
+
+/// Apply stored artifacts that have not been written yet
+///
+/// When `file_path` is given only that artifact is applied; otherwise every
+/// unapplied artifact (pending or rejected) is, except those that failed
+/// syntax validation or have blocking security findings. Applied artifacts
+/// are marked accepted. Returns the paths that were written.
+pub async fn apply(
+    db: &Database,
+    generation_id: &str,
+    file_path: Option<&str>,
+) -> Result<Vec<String>> {
+    let repo = GenerationRepository::new(db);
+    let generation = get_generation(&repo, generation_id).await?;
+    let blocked: Vec<String> = repo
+        .list_findings(generation_id)
+        .await?
+        .into_iter()
+        .filter(|f| f.blocking)
+        .map(|f| f.file)
+        .collect();
+
+    let to_apply: Vec<GenerationArtifact> = repo
+        .list_artifacts(generation_id)
+        .await?
+        .into_iter()
+        .filter(|artifact| {
+            let selected = match file_path {
+                Some(path) => path == artifact.file_path,
+                None => !artifact.has_syntax_errors() && !blocked.contains(&artifact.file_path),
+            };
+            selected && !artifact.is_applied()
+        })
+        .collect();
+    check_guardrails(&generation, &to_apply).await?;
+
+    let events = AgentEventWriter::resume_latest();
+    write_artifacts(&repo, &events, &generation, &to_apply).await?;
+    let mut written = Vec::new();
+    for artifact in to_apply {
+        repo.set_decision(
+            generation_id,
+            &artifact.file_path,
+            ArtifactDecision::Accepted,
+        )
+        .await?;
+        written.push(artifact.file_path);
+    }
+
+    if let Some(path) = file_path {
+        if written.is_empty() {
+            return Err(Error::NotFound(format!(
+                "No unapplied artifact '{}' in generation {}",
+                path, generation_id
+            )));
+        }
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::validation::validate_source;
+
+    fn sample_result() -> GenerationResult {
+        GenerationResult {
+            files_created: 1,
+            files_modified: 1,
+            tokens_used: 1200,
+            cost_usd: 0.02,
+            files: vec![
+                GeneratedFile {
+                    path: PathBuf::from("src/new.rs"),
+                    content: "pub fn new() {}\n".to_string(),
+                    is_new: true,
+                    language: Some("rust".to_string()),
+                    validation: None,
+                    lint_findings: Vec::new(),
+                    security_findings: Vec::new(),
+                },
+                GeneratedFile {
+                    path: PathBuf::from("src/lib.rs"),
+                    content: "pub mod new;\npub fn lib() {}\n".to_string(),
+                    is_new: false,
+                    language: Some("rust".to_string()),
+                    validation: None,
+                    lint_findings: Vec::new(),
+                    security_findings: Vec::new(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_decision_parse() {
+        for decision in [
+            ArtifactDecision::Pending,
+            ArtifactDecision::Accepted,
+            ArtifactDecision::Rejected,
+        ] {
+            assert_eq!(ArtifactDecision::parse(decision.as_str()), Some(decision));
+        }
+        assert_eq!(ArtifactDecision::parse("maybe"), None);
+    }
+
+    #[test]
+    fn test_artifact_target_rejects_escapes() {
+        assert!(artifact_target("/tmp/out", "../etc/passwd").is_err());
+        assert!(artifact_target("/tmp/out", "/etc/passwd").is_err());
+        assert_eq!(
+            artifact_target("/tmp/out", "src/lib.rs").unwrap(),
+            PathBuf::from("/tmp/out/src/lib.rs")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_review_accept_reject_apply() {
+        let db = Database::in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "pub fn lib() {}\n").unwrap();
+
+        let generation = Generation::new("add module", dir.path().to_string_lossy());
+        let generation = record(&db, generation, &sample_result()).await.unwrap();
+        assert_eq!(generation.status, GenerationStatus::Completed);
+        assert_eq!(generation.tokens_used, 1200);
+
+        let preview = review(&db, &generation.id).await.unwrap();
+        assert_eq!(preview.files.len(), 2);
+        let lib = preview
+            .files
+            .iter()
+            .find(|f| f.path == "src/lib.rs")
+            .unwrap();
+        assert!(!lib.is_new);
+        assert_eq!((lib.additions, lib.deletions), (1, 0));
+        assert!(lib.diff.contains("+pub mod new;"));
+        let new = preview
+            .files
+            .iter()
+            .find(|f| f.path == "src/new.rs")
+            .unwrap();
+        assert!(new.is_new);
+        assert_eq!(new.decision, ArtifactDecision::Pending);
+
+        // Accept writes the file; reject leaves disk untouched
+        decide(
+            &db,
+            &generation.id,
+            "src/lib.rs",
+            ArtifactDecision::Accepted,
+        )
+        .await
+        .unwrap();
+        decide(
+            &db,
+            &generation.id,
+            "src/new.rs",
+            ArtifactDecision::Rejected,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/lib.rs")).unwrap(),
+            "pub mod new;\npub fn lib() {}\n"
+        );
+        assert!(!dir.path().join("src/new.rs").exists());
+
+        let preview = review(&db, &generation.id).await.unwrap();
+        assert_eq!(preview.unapplied().count(), 1);
+
+        // Rejected artifacts can be applied later
+        let written = apply(&db, &generation.id, None).await.unwrap();
+        assert_eq!(written, vec!["src/new.rs".to_string()]);
+        assert!(dir.path().join("src/new.rs").exists());
+
+        let artifacts = GenerationRepository::new(&db)
+            .list_artifacts(&generation.id)
+            .await
+            .unwrap();
+        assert!(artifacts
+            .iter()
+            .all(|a| a.is_applied() && a.decision == ArtifactDecision::Accepted));
+    }
+
+    #[tokio::test]
+    async fn test_review_missing_generation() {
+        let db = Database::in_memory().await.unwrap();
+        assert!(matches!(
+            review(&db, "missing").await,
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_files_recorded_as_rejected() {
+        let db = Database::in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut result = sample_result();
+        let broken = &mut result.files[0];
+        broken.content = "pub fn new( {\n".to_string();
+        broken.validation = Some(validate_source(&broken.path, None, &broken.content));
+
+        let generation = Generation::new("add module", dir.path().to_string_lossy());
+        let generation = record(&db, generation, &result).await.unwrap();
+
+        let preview = review(&db, &generation.id).await.unwrap();
+        let new = preview
+            .files
+            .iter()
+            .find(|f| f.path == "src/new.rs")
+            .unwrap();
+        assert_eq!(new.decision, ArtifactDecision::Rejected);
+        assert_eq!(new.validation_status, Some(ValidationStatus::Invalid));
+        assert!(!new.validation_errors.is_empty());
+
+        // Bulk apply skips files with syntax errors; naming the file forces it
+        let written = apply(&db, &generation.id, None).await.unwrap();
+        assert_eq!(written, vec!["src/lib.rs".to_string()]);
+        assert!(!dir.path().join("src/new.rs").exists());
+
+        let written = apply(&db, &generation.id, Some("src/new.rs"))
+            .await
+            .unwrap();
+        assert_eq!(written, vec!["src/new.rs".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_blocking_findings_are_stored_and_skipped() {
+        let db = Database::in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut result = sample_result();
+        let mut finding = crate::agents::security_audit::scan(
+            &result.files[0].path,
+            "let q = format!(\"SELECT * FROM users WHERE id = {}\", id);\n",
+        )
+        .remove(0);
+        finding.blocking = true;
+        result.files[0].security_findings.push(finding);
+
+        let generation = Generation::new("add module", dir.path().to_string_lossy());
+        let generation = record(&db, generation, &result).await.unwrap();
+
+        let detail = show(&db, &generation.id).await.unwrap();
+        assert_eq!(detail.findings.len(), 1);
+        assert_eq!(detail.findings[0].cwe, "CWE-89");
+        assert_eq!(detail.findings[0].file, "src/new.rs");
+        assert!(detail.findings[0].blocking);
+
+        let written = apply(&db, &generation.id, None).await.unwrap();
+        assert_eq!(written, vec!["src/lib.rs".to_string()]);
+        assert!(!dir.path().join("src/new.rs").exists());
+    }
+
+    fn task_result(path: &str, tokens: u32) -> GenerationResult {
+        GenerationResult {
+            files_created: 1,
+            files_modified: 0,
+            tokens_used: tokens,
+            cost_usd: 0.01,
+            files: vec![GeneratedFile {
+                path: PathBuf::from(path),
+                content: format!("// {}\n", path),
+                is_new: true,
+                language: Some("rust".to_string()),
+                validation: None,
+                lint_findings: Vec::new(),
+                security_findings: Vec::new(),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_reruns_only_unfinished_tasks() {
+        let db = Database::in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let plan = ExecutionPlan::new("api")
+            .with_task(PlanTask::coding("models", "Write models"))
+            .with_task(PlanTask::coding("routes", "Write routes"))
+            .with_task(PlanTask::test("tests", "Write tests").with_dependency("routes"));
+        let generation = Generation::new("api", dir.path().to_string_lossy());
+
+        let mut calls = Vec::new();
+        let run = start(&db, generation, plan, |task| {
+            calls.push(task.id.clone());
+            async move {
+                match task.id.as_str() {
+                    "models" => Ok(task_result("src/models.rs", 100)),
+                    _ => Err(Error::LLMError("connection reset".to_string())),
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        // The dependent task never ran; the generation keeps the first task's work
+        assert_eq!(calls, vec!["models", "routes"]);
+        assert_eq!(run.generation.status, GenerationStatus::Failed);
+        assert_eq!(run.failures.len(), 1);
+        assert_eq!(run.failures[0].task_id, "routes");
+        let stored = GenerationRepository::new(&db)
+            .get(&run.generation.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.tokens_used, 100);
+        let unfinished: Vec<_> = stored
+            .unfinished_tasks()
+            .iter()
+            .map(|t| t.id.clone())
+            .collect();
+        assert_eq!(unfinished, vec!["routes", "tests"]);
+
+        let mut calls = Vec::new();
+        let run = resume(&db, &stored.id, |task| {
+            calls.push(task.id.clone());
+            let path = format!("src/{}.rs", task.id);
+            async move { Ok(task_result(&path, 50)) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(calls, vec!["routes", "tests"]);
+        assert!(run.failures.is_empty());
+        assert_eq!(run.result.files.len(), 2);
+        assert_eq!(run.generation.status, GenerationStatus::Completed);
+        assert_eq!(run.generation.tokens_used, 200);
+        assert!((run.generation.cost_usd - 0.03).abs() < 1e-9);
+        assert!(run.generation.unfinished_tasks().is_empty());
+
+        let artifacts = GenerationRepository::new(&db)
+            .list_artifacts(&stored.id)
+            .await
+            .unwrap();
+        assert_eq!(artifacts.len(), 3);
+
+        // Nothing left to resume
+        assert!(matches!(
+            resume(&db, &stored.id, |_| async {
+                Ok(GenerationResult::default())
+            })
+            .await,
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_planned_generation_resumes_only_failed_part() {
+        let db = Database::in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        // Planned the way `generate` and queued jobs plan a description
+        let description = "A todo API:\n- Write the models\n- Write the routes";
+        let plan = plan_generation(description, None).await.plan;
+        let ids: Vec<&str> = plan.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["task-1", "task-2"]);
+        let generation = Generation::new(description, dir.path().to_string_lossy());
+
+        let run = start(&db, generation, plan, |task| async move {
+            if task.description.contains("routes") {
+                Err(Error::LLMError("connection reset".to_string()))
+            } else {
+                Ok(task_result("src/models.rs", 100))
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(run.generation.status, GenerationStatus::Failed);
+
+        let mut calls = Vec::new();
+        let run = resume(&db, &run.generation.id, |task| {
+            calls.push(task.id.clone());
+            async move { Ok(task_result("src/routes.rs", 50)) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(calls, vec!["task-2"]);
+        assert_eq!(run.generation.status, GenerationStatus::Completed);
+        assert_eq!(run.generation.tokens_used, 150);
+    }
+
+    #[test]
+    fn test_outline_plan_splits_sections_and_lists() {
+        assert!(outline_plan("Add a login page").is_none());
+        assert!(outline_plan("Only one part:\n- login").is_none());
+
+        let plan = outline_plan("Shop:\n1. Cart\n   with totals\n2) Checkout").unwrap();
+        let tasks: Vec<&str> = plan.tasks.iter().map(|t| t.description.as_str()).collect();
+        assert_eq!(
+            tasks,
+            vec!["Shop:\n\nCart\nwith totals", "Shop:\n\nCheckout"]
+        );
+        assert!(!plan.requires_review && !plan.requires_tests);
+
+        let plan = outline_plan("# Shop\n## Cart\nTotals\n## Checkout\nPay").unwrap();
+        assert_eq!(plan.tasks.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_generation_is_interrupted_and_resumable() {
+        let db = Database::in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let plan = ExecutionPlan::new("api")
+            .with_task(PlanTask::coding("models", "Write models"))
+            .with_task(PlanTask::coding("routes", "Write routes").with_dependency("models"));
+        let generation = Generation::new("api", dir.path().to_string_lossy());
+
+        // The signal arrives while the second task is still running
+        let cancel = CancellationToken::new();
+        let run = start_cancellable(&db, generation, plan, &cancel, |task| {
+            let cancel = cancel.clone();
+            async move {
+                if task.id == "routes" {
+                    cancel.cancel();
+                    std::future::pending::<()>().await;
+                }
+                Ok(task_result("src/models.rs", 100))
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(run.generation.status, GenerationStatus::Interrupted);
+        assert!(run.failures.is_empty());
+        let stored = GenerationRepository::new(&db)
+            .get(&run.generation.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, GenerationStatus::Interrupted);
+        assert_eq!(stored.tokens_used, 100);
+        let unfinished = stored.unfinished_tasks();
+        assert_eq!(unfinished.len(), 1);
+        assert_eq!(unfinished[0].id, "routes");
+        assert_eq!(unfinished[0].status, TaskStatus::Pending);
+
+        let run = resume(&db, &stored.id, |task| {
+            let path = format!("src/{}.rs", task.id);
+            async move { Ok(task_result(&path, 50)) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(run.generation.status, GenerationStatus::Completed);
+        assert_eq!(run.generation.tokens_used, 150);
+    }
+
+    #[tokio::test]
+    async fn test_resume_requires_plan() {
+        let db = Database::in_memory().await.unwrap();
+        let generation = record(&db, Generation::new("x", "."), &sample_result())
+            .await
+            .unwrap();
+        assert!(matches!(
+            resume(&db, &generation.id, |_| async {
+                Ok(GenerationResult::default())
+            })
+            .await,
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_environment_is_recorded() {
+        let db = Database::in_memory().await.unwrap();
+        let environment = GenerationEnvironment::current().with_plugins([("lint", "1.0.0")]);
+        let generation = record(
+            &db,
+            Generation::new("x", ".").with_environment(environment.clone()),
+            &sample_result(),
+        )
+        .await
+        .unwrap();
+
+        let stored = GenerationRepository::new(&db)
+            .get(&generation.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.environment, Some(environment));
+
+        // Captured automatically when not given
+        let generation = record(&db, Generation::new("y", "."), &sample_result())
+            .await
+            .unwrap();
+        let stored = GenerationRepository::new(&db)
+            .get(&generation.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.environment.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_list_show_and_delete() {
+        let db = Database::in_memory().await.unwrap();
+        let first = record(&db, Generation::new("first", "."), &sample_result())
+            .await
+            .unwrap();
+        // Started while a session is active, so attributed to it
+        let session = crate::domain::session::SessionManager::new(db.pool().clone())
+            .create(None, None, None)
+            .await
+            .unwrap();
+        let second = start(
+            &db,
+            Generation::new("second", "."),
+            single_task_plan("second"),
+            |_| async { Ok(sample_result()) },
+        )
+        .await
+        .unwrap()
+        .generation;
+
+        let all = list(&db, &GenerationFilter::default()).await.unwrap();
+        assert_eq!(all.len(), 2);
+        let listed = all.iter().find(|g| g.id == first.id).unwrap();
+        assert_eq!(listed.file_count, sample_result().files.len());
+        assert_eq!(listed.session_id, None);
+
+        let by_session = list(
+            &db,
+            &GenerationFilter {
+                session_id: Some(session.id.to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(by_session.len(), 1);
+        assert_eq!(by_session[0].id, second.id);
+
+        let detail = show(&db, &second.id).await.unwrap();
+        assert_eq!(detail.generation.description, "second");
+        assert_eq!(detail.generation.status, GenerationStatus::Completed);
+        assert!(detail.generation.duration_ms >= 0);
+        assert_eq!(detail.artifacts.len(), sample_result().files.len());
+
+        delete(&db, &first.id).await.unwrap();
+        assert!(matches!(
+            show(&db, &first.id).await,
+            Err(Error::NotFound(_))
+        ));
+        assert!(GenerationRepository::new(&db)
+            .list_artifacts(&first.id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            delete(&db, &first.id).await,
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_regenerate_one_artifact() {
+        let db = Database::in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let run = start(
+            &db,
+            Generation::new("add module", dir.path().to_string_lossy()),
+            single_task_plan("add module"),
+            |_| async { Ok(sample_result()) },
+        )
+        .await
+        .unwrap();
+        let id = run.generation.id.clone();
+
+        let regen = regenerate(&db, &id, "src/new.rs", "make it async", |task| async move {
+            assert!(task.description.starts_with("add module"));
+            assert!(task.description.contains("`src/new.rs`"));
+            assert!(task.description.contains("make it async"));
+            let mut result = sample_result();
+            result.files[0].content = "pub async fn new() {}\n".to_string();
+            Ok(result)
+        })
+        .await
+        .unwrap();
+        assert_eq!(regen.task_id, "task-1");
+        assert_eq!(regen.previous_version, 1);
+        assert_eq!(regen.artifact.version, 2);
+        assert_eq!(regen.artifact.content, "pub async fn new() {}\n");
+        assert_eq!(regen.artifact.decision, ArtifactDecision::Pending);
+
+        // The other file is preserved as it was
+        let artifacts = GenerationRepository::new(&db)
+            .list_artifacts(&id)
+            .await
+            .unwrap();
+        let lib = artifacts
+            .iter()
+            .find(|a| a.file_path == "src/lib.rs")
+            .unwrap();
+        assert_eq!(lib.version, 1);
+
+        let stored = GenerationRepository::new(&db)
+            .get(&id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.tokens_used, 2 * sample_result().tokens_used as i64);
+
+        // A result without the file changes nothing
+        let missing = regenerate(&db, &id, "src/new.rs", "x", |_| async {
+            Ok(GenerationResult::default())
+        })
+        .await;
+        assert!(matches!(missing, Err(Error::Other(_))));
+        assert!(matches!(
+            regenerate(&db, &id, "src/other.rs", "x", |_| async {
+                Ok(sample_result())
+            })
+            .await,
+            Err(Error::NotFound(_))
+        ));
+    }
+}
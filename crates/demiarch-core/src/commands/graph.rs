@@ -4,12 +4,15 @@
 
 use std::collections::HashMap;
 
+use crate::commands::feature_import::levenshtein;
 use crate::domain::knowledge::{
-    EntityType, EntityWithDistance, KnowledgeEntity, KnowledgeGraphRepository,
-    KnowledgeRelationship, RelationshipType,
+    DecayModel, EntityMerge, EntityType, EntityWithDistance, KnowledgeEntity,
+    KnowledgeGraphRepository, KnowledgeRelationship, RelationshipType,
 };
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::infrastructure::knowledge::SqliteKnowledgeGraphRepository;
+use crate::skills::SkillStore;
+use chrono::Utc;
 use sqlx::SqlitePool;
 
 /// Statistics about the knowledge graph
@@ -53,6 +56,51 @@ pub struct EntityWithRelationships {
     pub incoming: Vec<(RelationshipType, String)>,
 }
 
+/// A pair of entities whose names suggest they are the same thing
+#[derive(Debug, Clone)]
+pub struct DuplicateCandidate {
+    /// Suggested entity to keep (higher confidence, more source skills)
+    pub keep: KnowledgeEntity,
+    /// Suggested entity to merge into it
+    pub duplicate: KnowledgeEntity,
+    /// Name similarity (0.0 to 1.0)
+    pub similarity: f32,
+    /// Why the pair was flagged
+    pub reason: &'static str,
+}
+
+/// Name similarity at or above which entities are reported as duplicates
+pub const DEFAULT_DUPLICATE_THRESHOLD: f32 = 0.85;
+
+/// What one aging pass changed (or would change, on a dry run)
+#[derive(Debug, Clone, Default)]
+pub struct AgingReport {
+    /// Entities whose confidence was lowered
+    pub entities_downgraded: usize,
+    /// Names of entities moved to the archive
+    pub entities_archived: Vec<String>,
+    /// Relationships whose weight was lowered
+    pub relationships_downgraded: usize,
+    /// Relationships moved to the archive
+    pub relationships_archived: usize,
+    /// Names of skills dropped one confidence level
+    pub skills_downgraded: Vec<String>,
+}
+
+impl AgingReport {
+    /// Whether the pass changed nothing
+    pub fn is_empty(&self) -> bool {
+        self.entities_downgraded == 0
+            && self.entities_archived.is_empty()
+            && self.relationships_downgraded == 0
+            && self.relationships_archived == 0
+            && self.skills_downgraded.is_empty()
+    }
+}
+
+/// Smallest drop in a stored score worth writing back
+const MIN_AGING_STEP: f32 = 0.01;
+
 /// Get knowledge graph statistics
 pub async fn get_stats(pool: &SqlitePool) -> Result<GraphStatistics> {
     let repo = SqliteKnowledgeGraphRepository::new(pool.clone());
@@ -221,6 +269,260 @@ pub async fn get_linked_skills(pool: &SqlitePool, entity_id: &str) -> Result<Vec
     repo.get_skills_for_entity(entity_id).await
 }
 
+/// Merge a duplicate entity into the one to keep
+///
+/// The duplicate's relationships and skill links move to the kept entity and
+/// its names are recorded as aliases, so ingestion maps them to the kept
+/// entity from now on.
+pub async fn merge_entities(
+    pool: &SqlitePool,
+    keep_id: &str,
+    duplicate_id: &str,
+) -> Result<EntityMerge> {
+    let repo = SqliteKnowledgeGraphRepository::new(pool.clone());
+    repo.merge_entities(keep_id, duplicate_id).await
+}
+
+/// Record another name for an entity
+///
+/// Fails if the name already belongs to a different entity; merge the two
+/// instead.
+pub async fn add_alias(pool: &SqlitePool, entity_id: &str, alias: &str) -> Result<KnowledgeEntity> {
+    let repo = SqliteKnowledgeGraphRepository::new(pool.clone());
+    let mut entity = repo
+        .get_entity(entity_id)
+        .await?
+        .ok_or_else(|| Error::EntityNotFound(entity_id.to_string()))?;
+
+    let canonical = KnowledgeEntity::canonicalize(alias);
+    if canonical.is_empty() {
+        return Err(Error::InvalidInput(format!("Invalid alias '{}'", alias)));
+    }
+    if let Some(other) = repo.get_entity_by_canonical_name(&canonical).await? {
+        if other.id != entity.id {
+            return Err(Error::InvalidInput(format!(
+                "'{}' already names entity '{}' ({}); merge the two instead",
+                alias, other.name, other.id
+            )));
+        }
+    }
+
+    if canonical != entity.canonical_name {
+        entity.add_alias(alias.to_string());
+        repo.save_entity(&entity).await?;
+    }
+    repo.save_alias(&entity.id, alias).await?;
+    Ok(entity)
+}
+
+/// Find pairs of entities that are probably duplicates, most similar first
+pub async fn find_duplicate_candidates(
+    pool: &SqlitePool,
+    threshold: f32,
+) -> Result<Vec<DuplicateCandidate>> {
+    let repo = SqliteKnowledgeGraphRepository::new(pool.clone());
+    let entities = repo.list_entities().await?;
+
+    let mut candidates = Vec::new();
+    for (i, a) in entities.iter().enumerate() {
+        for b in &entities[i + 1..] {
+            let Some((similarity, reason)) = name_similarity(a, b) else {
+                continue;
+            };
+            if similarity < threshold {
+                continue;
+            }
+            // Keep the better-established entity
+            let a_first = (
+                a.confidence,
+                a.source_skill_ids.len(),
+                std::cmp::Reverse(a.created_at),
+            ) >= (
+                b.confidence,
+                b.source_skill_ids.len(),
+                std::cmp::Reverse(b.created_at),
+            );
+            let (keep, duplicate) = if a_first { (a, b) } else { (b, a) };
+            candidates.push(DuplicateCandidate {
+                keep: keep.clone(),
+                duplicate: duplicate.clone(),
+                similarity,
+                reason,
+            });
+        }
+    }
+
+    candidates.sort_by(|x, y| y.similarity.total_cmp(&x.similarity));
+    Ok(candidates)
+}
+
+/// How alike two entities' names are, and why
+///
+/// Compares canonical names and aliases: an exact match, a match once spaces
+/// are dropped ("next js"/"nextjs"), a short suffix ("postgres"/"postgresql",
+/// "react"/"reactjs"), or otherwise edit distance.
+fn name_similarity(a: &KnowledgeEntity, b: &KnowledgeEntity) -> Option<(f32, &'static str)> {
+    let names = |e: &KnowledgeEntity| {
+        std::iter::once(e.canonical_name.clone())
+            .chain(
+                e.aliases
+                    .iter()
+                    .map(|alias| KnowledgeEntity::canonicalize(alias)),
+            )
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>()
+    };
+    let (names_a, names_b) = (names(a), names(b));
+
+    let mut best: Option<(f32, &'static str)> = None;
+    for x in &names_a {
+        for y in &names_b {
+            let candidate = if x == y {
+                (1.0, "same name or alias")
+            } else {
+                let (x, y) = (x.replace(' ', ""), y.replace(' ', ""));
+                let (short, long) = if x.len() <= y.len() {
+                    (&x, &y)
+                } else {
+                    (&y, &x)
+                };
+                if x == y {
+                    (1.0, "same name ignoring spaces")
+                } else if short.len() >= 3
+                    && long.starts_with(short.as_str())
+                    && long.len() - short.len() <= 2
+                {
+                    (0.9, "name differs by a short suffix")
+                } else {
+                    let longest = x.chars().count().max(y.chars().count());
+                    (
+                        1.0 - levenshtein(&x, &y) as f32 / longest as f32,
+                        "similar spelling",
+                    )
+                }
+            };
+            if !matches!(best, Some((score, _)) if score >= candidate.0) {
+                best = Some(candidate);
+            }
+        }
+    }
+    best
+}
+
+/// Write time decay back into the knowledge graph and skill library
+///
+/// Entity confidence and relationship weight are decayed from their last
+/// update; anything that fades below `archive_below` moves to the archive,
+/// and the rest is saved at its decayed value, which restarts its clock.
+/// Skills not reinforced for a half-life drop one confidence level. With
+/// `dry_run` nothing is written and the report says what would change.
+pub async fn age_knowledge(
+    pool: &SqlitePool,
+    decay: DecayModel,
+    archive_below: f32,
+    dry_run: bool,
+) -> Result<AgingReport> {
+    let mut report = AgingReport::default();
+    if !decay.is_enabled() {
+        return Ok(report);
+    }
+
+    let repo = SqliteKnowledgeGraphRepository::new(pool.clone());
+    let now = Utc::now();
+    let entities = repo.list_entities().await?;
+
+    // Relationships first, so an entity archived below takes along only the
+    // edges that survived
+    for entity in &entities {
+        for mut relationship in repo.list_outgoing_relationships(&entity.id).await? {
+            let weight = decay.apply(relationship.weight, relationship.updated_at, now);
+            if weight < archive_below {
+                if !dry_run {
+                    repo.archive_relationship(&relationship.id).await?;
+                }
+                report.relationships_archived += 1;
+            } else if relationship.weight - weight >= MIN_AGING_STEP {
+                if !dry_run {
+                    relationship.weight = weight;
+                    relationship.updated_at = now;
+                    repo.save_relationship(&relationship).await?;
+                }
+                report.relationships_downgraded += 1;
+            }
+        }
+    }
+
+    for mut entity in entities {
+        let confidence = decay.apply(entity.confidence, entity.updated_at, now);
+        if confidence < archive_below {
+            if !dry_run {
+                repo.archive_entity(&entity.id).await?;
+            }
+            report.entities_archived.push(entity.name);
+        } else if entity.confidence - confidence >= MIN_AGING_STEP {
+            if !dry_run {
+                entity.confidence = confidence;
+                entity.updated_at = now;
+                repo.save_entity(&entity).await?;
+            }
+            report.entities_downgraded += 1;
+        }
+    }
+
+    // Skill confidence is a level, not a score: step down once per half-life
+    let store = SkillStore::new(pool.clone());
+    for mut skill in store.list().await? {
+        let downgraded = skill.confidence.downgraded();
+        if downgraded == skill.confidence || decay.factor(skill.updated_at, now) > 0.5 {
+            continue;
+        }
+        if !dry_run {
+            skill.confidence = downgraded;
+            skill.updated_at = now;
+            store.save(&skill).await?;
+        }
+        report.skills_downgraded.push(skill.name);
+    }
+
+    Ok(report)
+}
+
+/// Format an aging report for display
+pub fn format_aging_report(report: &AgingReport) -> String {
+    if report.is_empty() {
+        return "Nothing has aged enough to change.\n".to_string();
+    }
+
+    let mut output = String::new();
+    output.push_str(&format!(
+        "Relationships weakened: {}\n",
+        report.relationships_downgraded
+    ));
+    output.push_str(&format!(
+        "Relationships archived: {}\n",
+        report.relationships_archived
+    ));
+    output.push_str(&format!(
+        "Entities downgraded:    {}\n",
+        report.entities_downgraded
+    ));
+    output.push_str(&format!(
+        "Entities archived:      {}\n",
+        report.entities_archived.len()
+    ));
+    for name in &report.entities_archived {
+        output.push_str(&format!("  - {}\n", name));
+    }
+    output.push_str(&format!(
+        "Skills downgraded:      {}\n",
+        report.skills_downgraded.len()
+    ));
+    for name in &report.skills_downgraded {
+        output.push_str(&format!("  - {}\n", name));
+    }
+    output
+}
+
 /// Format graph statistics for display
 pub fn format_stats(stats: &GraphStatistics) -> String {
     let mut output = String::new();
@@ -425,4 +727,93 @@ mod tests {
         assert!(output.contains("100"));
         assert!(output.contains("78.0%"));
     }
+
+    #[test]
+    fn test_name_similarity() {
+        let entity = |name: &str| KnowledgeEntity::new(name, EntityType::Framework);
+
+        assert_eq!(
+            name_similarity(&entity("Next.js"), &entity("NextJS")),
+            Some((1.0, "same name or alias"))
+        );
+        assert_eq!(
+            name_similarity(&entity("Next js"), &entity("Nextjs")),
+            Some((1.0, "same name ignoring spaces"))
+        );
+        assert_eq!(
+            name_similarity(&entity("Postgres"), &entity("PostgreSQL")).map(|(s, _)| s),
+            Some(0.9)
+        );
+        let aliased = entity("Kubernetes").with_aliases(vec!["k8s".into()]);
+        assert_eq!(
+            name_similarity(&aliased, &entity("K8s")).map(|(s, _)| s),
+            Some(1.0)
+        );
+        let (score, _) = name_similarity(&entity("tokio"), &entity("serde")).unwrap();
+        assert!(score < DEFAULT_DUPLICATE_THRESHOLD);
+    }
+
+    #[tokio::test]
+    async fn test_age_knowledge() {
+        use crate::skills::{LearnedSkill, SkillCategory, SkillConfidence, SkillPattern};
+        use chrono::Duration;
+
+        let db = crate::storage::Database::in_memory().await.unwrap();
+        let pool = db.pool();
+        let repo = SqliteKnowledgeGraphRepository::new(pool.clone());
+        let long_ago = Utc::now() - Duration::days(120);
+
+        let mut fresh = KnowledgeEntity::new("Rust", EntityType::Language);
+        fresh.confidence = 0.9;
+        let mut stale = KnowledgeEntity::new("Grunt", EntityType::Tool);
+        stale.confidence = 0.5;
+        stale.updated_at = long_ago;
+        let mut fading = KnowledgeEntity::new("Webpack", EntityType::Tool);
+        fading.confidence = 0.9;
+        fading.updated_at = long_ago;
+        for entity in [&fresh, &stale, &fading] {
+            repo.save_entity(entity).await.unwrap();
+        }
+
+        let mut skill = LearnedSkill::new(
+            "Gruntfile setup",
+            "Configure Grunt tasks",
+            SkillCategory::CodeGeneration,
+            SkillPattern::code("// grunt"),
+        )
+        .with_confidence(SkillConfidence::High);
+        skill.updated_at = long_ago;
+        SkillStore::new(pool.clone()).save(&skill).await.unwrap();
+
+        // 120 days at a 30-day half-life leaves 1/16 of the stored score
+        let decay = DecayModel::new(30.0);
+        let preview = age_knowledge(pool, decay, 0.05, true).await.unwrap();
+        assert_eq!(preview.entities_archived, vec!["Grunt".to_string()]);
+        assert!(repo.get_entity(&stale.id).await.unwrap().is_some());
+
+        let report = age_knowledge(pool, decay, 0.05, false).await.unwrap();
+        assert_eq!(report.entities_archived, vec!["Grunt".to_string()]);
+        assert_eq!(report.entities_downgraded, 1);
+        assert_eq!(
+            report.skills_downgraded,
+            vec!["Gruntfile setup".to_string()]
+        );
+        assert!(repo.get_entity(&stale.id).await.unwrap().is_none());
+        let fading = repo.get_entity(&fading.id).await.unwrap().unwrap();
+        assert!((fading.confidence - 0.05625).abs() < 1e-3);
+        let fresh = repo.get_entity(&fresh.id).await.unwrap().unwrap();
+        assert_eq!(fresh.confidence, 0.9);
+        let skill = SkillStore::new(pool.clone())
+            .get(&skill.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(skill.confidence, SkillConfidence::Medium);
+
+        // Aged values restart their clock, so an immediate rerun is a no-op
+        assert!(age_knowledge(pool, decay, 0.05, false)
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }
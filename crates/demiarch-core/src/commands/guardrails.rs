@@ -0,0 +1,366 @@
+//! Guardrail policies for generated file writes
+//!
+//! Before generated code reaches disk, the batch is checked against the
+//! `[guardrails]` configuration section merged with the project's
+//! `.demiarch/guardrails.toml`. Paths matching a protected glob are never
+//! written, and limits cap the size of each file, the number of files and the
+//! number of changed lines in one generation. Any violation fails the whole
+//! write, so a generation never lands half applied, and fires the
+//! `on_policy_violation` hooks.
+//!
+//! A project file adds protected globs to the global ones and overrides any
+//! limit it sets:
+//!
+//! ```toml
+//! protected_paths = ["infra/**", "*.lock"]
+//! max_files = 10
+//! ```
+
+use std::fmt;
+use std::path::Path;
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::warn;
+
+use crate::agents::patch::{line_diff, DiffLine};
+use crate::config::GuardrailsConfig;
+use crate::hooks::{HookEvent, HooksManager};
+use crate::{Error, Result};
+
+/// Project policy file, relative to the project root
+pub const PROJECT_POLICY_FILE: &str = ".demiarch/guardrails.toml";
+
+/// Project additions to the global guardrails
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectGuardrails {
+    /// Protected globs added to the global list
+    pub protected_paths: Vec<String>,
+    pub max_file_kb: Option<u64>,
+    pub max_files: Option<usize>,
+    pub max_diff_lines: Option<usize>,
+}
+
+impl ProjectGuardrails {
+    /// Read a project's policy file; a missing file adds nothing
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(PROJECT_POLICY_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content)
+                .map_err(|e| Error::ConfigError(format!("{}: {}", path.display(), e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// A file a generation is about to write
+#[derive(Debug, Clone)]
+pub struct FileWrite<'a> {
+    /// Path relative to the project root
+    pub path: String,
+    pub content: &'a str,
+    /// Current content of the file, if it exists
+    pub previous: Option<String>,
+}
+
+impl<'a> FileWrite<'a> {
+    /// Describe writing `content` to `path` under `root`, reading the file it replaces
+    pub fn read(root: &Path, path: &str, content: &'a str) -> Self {
+        Self {
+            path: normalize(path),
+            content,
+            previous: std::fs::read_to_string(root.join(path)).ok(),
+        }
+    }
+
+    /// Lines added plus lines removed by the write
+    fn changed_lines(&self) -> usize {
+        line_diff(self.previous.as_deref().unwrap_or(""), self.content)
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Context(_)))
+            .count()
+    }
+}
+
+/// One broken guardrail
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum PolicyViolation {
+    ProtectedPath {
+        path: String,
+        pattern: String,
+    },
+    FileTooLarge {
+        path: String,
+        bytes: u64,
+        limit: u64,
+    },
+    TooManyFiles {
+        count: usize,
+        limit: usize,
+    },
+    DiffTooLarge {
+        lines: usize,
+        limit: usize,
+    },
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ProtectedPath { path, pattern } => {
+                write!(f, "{} is protected by '{}'", path, pattern)
+            }
+            Self::FileTooLarge { path, bytes, limit } => {
+                write!(f, "{} is {} bytes (limit {})", path, bytes, limit)
+            }
+            Self::TooManyFiles { count, limit } => {
+                write!(f, "{} files written (limit {})", count, limit)
+            }
+            Self::DiffTooLarge { lines, limit } => {
+                write!(f, "{} lines changed (limit {})", lines, limit)
+            }
+        }
+    }
+}
+
+/// Compiled guardrail policy
+#[derive(Debug, Clone)]
+pub struct Guardrails {
+    patterns: Vec<String>,
+    protected: GlobSet,
+    /// Limits of 0 are unlimited
+    max_file_bytes: u64,
+    max_files: usize,
+    max_diff_lines: usize,
+}
+
+impl Guardrails {
+    /// Build the policy from the global configuration alone
+    pub fn from_config(config: &GuardrailsConfig) -> Result<Self> {
+        Self::merged(config, &ProjectGuardrails::default())
+    }
+
+    /// Build the policy for a project, merging its policy file if it has one
+    pub fn load(config: &GuardrailsConfig, project_dir: &Path) -> Result<Self> {
+        Self::merged(config, &ProjectGuardrails::load(project_dir)?)
+    }
+
+    /// Add a project's protected globs to the global ones and apply its limits
+    pub fn merged(config: &GuardrailsConfig, project: &ProjectGuardrails) -> Result<Self> {
+        let mut patterns = config.protected_paths.clone();
+        for pattern in &project.protected_paths {
+            if !patterns.contains(pattern) {
+                patterns.push(pattern.clone());
+            }
+        }
+
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &patterns {
+            let glob = Glob::new(pattern).map_err(|e| {
+                Error::ConfigError(format!("Invalid protected path '{}': {}", pattern, e))
+            })?;
+            builder.add(glob);
+        }
+        let protected = builder
+            .build()
+            .map_err(|e| Error::ConfigError(e.to_string()))?;
+
+        Ok(Self {
+            patterns,
+            protected,
+            max_file_bytes: project.max_file_kb.unwrap_or(config.max_file_kb) * 1024,
+            max_files: project.max_files.unwrap_or(config.max_files),
+            max_diff_lines: project.max_diff_lines.unwrap_or(config.max_diff_lines),
+        })
+    }
+
+    /// Protected globs, global ones first
+    pub fn protected_paths(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Every guardrail the batch of writes would break
+    pub fn check(&self, writes: &[FileWrite<'_>]) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+
+        if self.max_files > 0 && writes.len() > self.max_files {
+            violations.push(PolicyViolation::TooManyFiles {
+                count: writes.len(),
+                limit: self.max_files,
+            });
+        }
+
+        let mut changed_lines = 0;
+        for write in writes {
+            if let Some(index) = self.protected.matches(&write.path).first() {
+                violations.push(PolicyViolation::ProtectedPath {
+                    path: write.path.clone(),
+                    pattern: self.patterns[*index].clone(),
+                });
+            }
+            let bytes = write.content.len() as u64;
+            if self.max_file_bytes > 0 && bytes > self.max_file_bytes {
+                violations.push(PolicyViolation::FileTooLarge {
+                    path: write.path.clone(),
+                    bytes,
+                    limit: self.max_file_bytes,
+                });
+            }
+            if self.max_diff_lines > 0 {
+                changed_lines += write.changed_lines();
+            }
+        }
+
+        if self.max_diff_lines > 0 && changed_lines > self.max_diff_lines {
+            violations.push(PolicyViolation::DiffTooLarge {
+                lines: changed_lines,
+                limit: self.max_diff_lines,
+            });
+        }
+        violations
+    }
+
+    /// Check a batch of writes, failing it if any guardrail is broken
+    ///
+    /// Violations fire the `on_policy_violation` hooks with the generation
+    /// and the violations as JSON. The hooks are informational: their exit
+    /// status does not change the outcome.
+    pub async fn enforce(
+        &self,
+        hooks: &HooksManager,
+        generation: &str,
+        writes: &[FileWrite<'_>],
+    ) -> Result<()> {
+        let violations = self.check(writes);
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        let payload = json!({ "generation": generation, "violations": violations });
+        if let Err(e) = hooks.fire(HookEvent::OnPolicyViolation, &payload).await {
+            warn!(error = %e, "on_policy_violation hook failed");
+        }
+
+        Err(Error::PolicyViolation(
+            violations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; "),
+        ))
+    }
+}
+
+/// Project-relative path with forward slashes and no leading `./`
+fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut path = path.as_str();
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest;
+    }
+    path.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write<'a>(path: &str, content: &'a str) -> FileWrite<'a> {
+        FileWrite {
+            path: normalize(path),
+            content,
+            previous: None,
+        }
+    }
+
+    #[test]
+    fn test_check_protected_paths_and_limits() {
+        let config = GuardrailsConfig {
+            protected_paths: vec!["infra/**".to_string()],
+            max_file_kb: 1,
+            max_files: 2,
+            max_diff_lines: 0,
+        };
+        let project = ProjectGuardrails {
+            protected_paths: vec!["*.lock".to_string()],
+            ..Default::default()
+        };
+        let guardrails = Guardrails::merged(&config, &project).unwrap();
+        let large = "x".repeat(2048);
+
+        let violations = guardrails.check(&[
+            write("./infra/main.tf", "resource {}"),
+            write("web/package.lock", "{}"),
+            write("src/lib.rs", &large),
+        ]);
+        assert_eq!(
+            violations,
+            vec![
+                PolicyViolation::TooManyFiles { count: 3, limit: 2 },
+                PolicyViolation::ProtectedPath {
+                    path: "infra/main.tf".to_string(),
+                    pattern: "infra/**".to_string(),
+                },
+                PolicyViolation::ProtectedPath {
+                    path: "web/package.lock".to_string(),
+                    pattern: "*.lock".to_string(),
+                },
+                PolicyViolation::FileTooLarge {
+                    path: "src/lib.rs".to_string(),
+                    bytes: 2048,
+                    limit: 1024,
+                },
+            ]
+        );
+        assert!(guardrails
+            .check(&[write("src/main.rs", "fn main() {}")])
+            .is_empty());
+    }
+
+    #[test]
+    fn test_diff_limit_counts_changed_lines() {
+        let config = GuardrailsConfig {
+            max_diff_lines: 2,
+            ..Default::default()
+        };
+        let guardrails = Guardrails::from_config(&config).unwrap();
+
+        let mut edit = write("src/lib.rs", "a\nb\nc\n");
+        edit.previous = Some("a\nx\nc\n".to_string());
+        assert!(guardrails.check(std::slice::from_ref(&edit)).is_empty());
+
+        let violations = guardrails.check(&[edit, write("src/new.rs", "1\n2\n")]);
+        assert_eq!(
+            violations,
+            vec![PolicyViolation::DiffTooLarge { lines: 4, limit: 2 }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_enforce_fails_and_loads_project_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".demiarch")).unwrap();
+        std::fs::write(
+            dir.path().join(PROJECT_POLICY_FILE),
+            "protected_paths = [\"*.lock\"]\nmax_files = 5\n",
+        )
+        .unwrap();
+
+        let guardrails = Guardrails::load(&GuardrailsConfig::default(), dir.path()).unwrap();
+        assert!(guardrails.protected_paths().contains(&"*.lock".to_string()));
+
+        let writes = [FileWrite::read(dir.path(), "Cargo.lock", "")];
+        let err = guardrails
+            .enforce(&HooksManager::new(), "gen-1", &writes)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::PolicyViolation(_)));
+        assert!(err
+            .to_string()
+            .contains("Cargo.lock is protected by '*.lock'"));
+    }
+}
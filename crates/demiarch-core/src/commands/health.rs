@@ -0,0 +1,505 @@
+//! Project health score and stale-item report
+//!
+//! `demiarch projects health <id>` collects signals that a project needs
+//! attention and folds them into a 0-100 score:
+//!
+//! - features stuck in `in_progress` for longer than the stale threshold
+//! - failing verifications: failed generations and generated files that did
+//!   not pass syntax validation but were never rejected
+//! - unresolved conflicts: generated changes to existing files still waiting
+//!   for an accept/reject decision
+//! - a stale context index: conversations or generations newer than the
+//!   newest context memory by more than the stale threshold
+//! - budget burn rate: the project's average daily spend over the last week
+//!   against the daily limit
+//!
+//! Each signal subtracts a capped penalty from 100 and carries a
+//! recommendation. The same report backs the GUI health widget.
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::commands::project::ProjectRepository;
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Days after which in-progress features and the context index are stale
+pub const DEFAULT_STALE_DAYS: i64 = 7;
+
+/// Days of spending the burn rate is averaged over
+const BURN_WINDOW_DAYS: i64 = 7;
+
+/// Items listed per signal
+const MAX_ITEMS: usize = 10;
+
+/// Inputs to a health check that come from configuration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthOptions {
+    pub stale_days: i64,
+    pub daily_limit_usd: f64,
+    /// Share of the daily limit at which burn rate becomes a warning
+    pub alert_threshold: f64,
+}
+
+impl Default for HealthOptions {
+    fn default() -> Self {
+        Self {
+            stale_days: DEFAULT_STALE_DAYS,
+            daily_limit_usd: 10.0,
+            alert_threshold: 0.8,
+        }
+    }
+}
+
+/// Overall health band
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Healthy,
+    Warning,
+    Critical,
+}
+
+impl HealthStatus {
+    fn from_score(score: u32) -> Self {
+        match score {
+            80.. => Self::Healthy,
+            50.. => Self::Warning,
+            _ => Self::Critical,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// What a signal measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalKind {
+    StaleFeatures,
+    FailingVerifications,
+    UnresolvedConflicts,
+    StaleContext,
+    BudgetBurn,
+}
+
+/// An item behind a signal, e.g. a stuck feature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthItem {
+    pub id: String,
+    pub title: String,
+    pub detail: String,
+}
+
+/// One problem found by a health check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthSignal {
+    pub kind: SignalKind,
+    /// Points subtracted from the score
+    pub penalty: u32,
+    pub summary: String,
+    pub recommendation: String,
+    /// Up to ten items behind the signal
+    pub items: Vec<HealthItem>,
+}
+
+/// Scored health report for a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub project_id: String,
+    pub project_name: String,
+    /// 0-100, higher is healthier
+    pub score: u32,
+    pub status: HealthStatus,
+    /// Problems found, largest penalty first; empty for a healthy project
+    pub signals: Vec<HealthSignal>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl HealthReport {
+    fn new(
+        project_id: String,
+        project_name: String,
+        mut signals: Vec<HealthSignal>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        signals.sort_by_key(|s| std::cmp::Reverse(s.penalty));
+        let penalty: u32 = signals.iter().map(|s| s.penalty).sum();
+        let score = 100u32.saturating_sub(penalty);
+        Self {
+            project_id,
+            project_name,
+            score,
+            status: HealthStatus::from_score(score),
+            signals,
+            checked_at: now,
+        }
+    }
+}
+
+/// Penalty for `count` items at `each` points, capped at `cap`
+fn penalty(count: usize, each: u32, cap: u32) -> u32 {
+    (count as u32).saturating_mul(each).min(cap)
+}
+
+fn sql_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn short(id: &str) -> &str {
+    &id[..8.min(id.len())]
+}
+
+/// Check a project's health
+pub async fn check(
+    db: &Database,
+    project_id: &str,
+    options: HealthOptions,
+    now: DateTime<Utc>,
+) -> Result<HealthReport> {
+    let project = ProjectRepository::new(db)
+        .get(project_id)
+        .await?
+        .ok_or_else(|| Error::ProjectNotFound(project_id.to_string()))?;
+    let stale_cutoff = now - Duration::days(options.stale_days);
+
+    let signals = [
+        stale_features(db, &project.id, stale_cutoff, options.stale_days).await?,
+        failing_verifications(db, &project.id).await?,
+        unresolved_conflicts(db, &project.id).await?,
+        stale_context(db, &project.id, options.stale_days).await?,
+        budget_burn(db, &project.id, options, now).await?,
+    ];
+
+    Ok(HealthReport::new(
+        project.id,
+        project.name,
+        signals.into_iter().flatten().collect(),
+        now,
+    ))
+}
+
+async fn stale_features(
+    db: &Database,
+    project_id: &str,
+    cutoff: DateTime<Utc>,
+    stale_days: i64,
+) -> Result<Option<HealthSignal>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, title, updated_at
+        FROM features
+        WHERE project_id = ? AND status = 'in_progress'
+          AND julianday(updated_at) < julianday(?)
+        ORDER BY updated_at
+        "#,
+    )
+    .bind(project_id)
+    .bind(sql_time(cutoff))
+    .fetch_all(db.pool())
+    .await?;
+    if rows.is_empty() {
+        return Ok(None);
+    }
+
+    let items = rows
+        .iter()
+        .take(MAX_ITEMS)
+        .map(|row| {
+            let updated: DateTime<Utc> = row.get("updated_at");
+            HealthItem {
+                id: row.get("id"),
+                title: row.get("title"),
+                detail: format!("in progress since {}", updated.format("%Y-%m-%d")),
+            }
+        })
+        .collect();
+    Ok(Some(HealthSignal {
+        kind: SignalKind::StaleFeatures,
+        penalty: penalty(rows.len(), 5, 25),
+        summary: format!(
+            "{} feature(s) in progress for more than {} days",
+            rows.len(),
+            stale_days
+        ),
+        recommendation: "Finish, split or move stuck features back to todo".to_string(),
+        items,
+    }))
+}
+
+async fn failing_verifications(db: &Database, project_id: &str) -> Result<Option<HealthSignal>> {
+    let failed = sqlx::query(
+        r#"
+        SELECT id, description
+        FROM generations
+        WHERE project_id = ? AND status = 'failed'
+        ORDER BY updated_at DESC
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?;
+    let invalid = sqlx::query(
+        r#"
+        SELECT a.id, a.file_path, a.generation_id
+        FROM generation_artifacts a
+        JOIN generations g ON g.id = a.generation_id
+        WHERE g.project_id = ? AND a.validation_status = 'invalid'
+          AND a.decision != 'rejected'
+        ORDER BY a.created_at DESC
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?;
+    let total = failed.len() + invalid.len();
+    if total == 0 {
+        return Ok(None);
+    }
+
+    let failed_items = failed.iter().map(|row| {
+        let id: String = row.get("id");
+        HealthItem {
+            title: row.get("description"),
+            detail: format!(
+                "generation failed; resume with `demiarch generate --resume {}`",
+                id
+            ),
+            id,
+        }
+    });
+    let invalid_items = invalid.iter().map(|row| {
+        let generation_id: String = row.get("generation_id");
+        HealthItem {
+            id: row.get("id"),
+            title: row.get("file_path"),
+            detail: format!("syntax errors in generation {}", short(&generation_id)),
+        }
+    });
+    Ok(Some(HealthSignal {
+        kind: SignalKind::FailingVerifications,
+        penalty: penalty(total, 5, 25),
+        summary: format!(
+            "{} failed generation(s), {} file(s) failing validation",
+            failed.len(),
+            invalid.len()
+        ),
+        recommendation: "Resume failed generations and reject or fix files with syntax errors"
+            .to_string(),
+        items: failed_items.chain(invalid_items).take(MAX_ITEMS).collect(),
+    }))
+}
+
+async fn unresolved_conflicts(db: &Database, project_id: &str) -> Result<Option<HealthSignal>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT a.id, a.file_path, a.generation_id
+        FROM generation_artifacts a
+        JOIN generations g ON g.id = a.generation_id
+        WHERE g.project_id = ? AND a.decision = 'pending' AND a.is_new = 0
+        ORDER BY a.created_at
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?;
+    if rows.is_empty() {
+        return Ok(None);
+    }
+
+    let items = rows
+        .iter()
+        .take(MAX_ITEMS)
+        .map(|row| {
+            let generation_id: String = row.get("generation_id");
+            HealthItem {
+                id: row.get("id"),
+                title: row.get("file_path"),
+                detail: format!(
+                    "review with `demiarch generations review {}`",
+                    generation_id
+                ),
+            }
+        })
+        .collect();
+    Ok(Some(HealthSignal {
+        kind: SignalKind::UnresolvedConflicts,
+        penalty: penalty(rows.len(), 3, 15),
+        summary: format!(
+            "{} generated change(s) to existing files awaiting review",
+            rows.len()
+        ),
+        recommendation: "Accept or reject pending changes before they drift from the working tree"
+            .to_string(),
+        items,
+    }))
+}
+
+async fn stale_context(
+    db: &Database,
+    project_id: &str,
+    stale_days: i64,
+) -> Result<Option<HealthSignal>> {
+    let row = sqlx::query(
+        r#"
+        SELECT
+            (SELECT MAX(julianday(created_at)) FROM context_entries WHERE project_id = ?1)
+                AS indexed,
+            MAX(
+                COALESCE((SELECT MAX(julianday(m.created_at))
+                          FROM messages m JOIN conversations c ON c.id = m.conversation_id
+                          WHERE c.project_id = ?1), 0.0),
+                COALESCE((SELECT MAX(julianday(updated_at))
+                          FROM generations WHERE project_id = ?1), 0.0)
+            ) AS active
+        "#,
+    )
+    .bind(project_id)
+    .fetch_one(db.pool())
+    .await?;
+    let indexed: Option<f64> = row.get("indexed");
+    let active: f64 = row.get("active");
+    if active == 0.0 {
+        return Ok(None);
+    }
+
+    let summary = match indexed {
+        None => "Context index is empty but the project has activity".to_string(),
+        Some(indexed) if active - indexed > stale_days as f64 => format!(
+            "Context index is {:.0} days behind the latest activity",
+            active - indexed
+        ),
+        Some(_) => return Ok(None),
+    };
+    Ok(Some(HealthSignal {
+        kind: SignalKind::StaleContext,
+        penalty: 10,
+        summary,
+        recommendation: "Rebuild the index with `demiarch context rebuild`".to_string(),
+        items: Vec::new(),
+    }))
+}
+
+async fn budget_burn(
+    db: &Database,
+    project_id: &str,
+    options: HealthOptions,
+    now: DateTime<Utc>,
+) -> Result<Option<HealthSignal>> {
+    if options.daily_limit_usd <= 0.0 {
+        return Ok(None);
+    }
+    let spent: f64 = sqlx::query(
+        r#"
+        SELECT COALESCE(SUM(input_cost_usd + output_cost_usd), 0.0) AS spent
+        FROM llm_costs
+        WHERE project_id = ? AND julianday(created_at) >= julianday(?)
+        "#,
+    )
+    .bind(project_id)
+    .bind(sql_time(now - Duration::days(BURN_WINDOW_DAYS)))
+    .fetch_one(db.pool())
+    .await?
+    .get("spent");
+
+    let daily = spent / BURN_WINDOW_DAYS as f64;
+    let ratio = daily / options.daily_limit_usd;
+    let penalty = if ratio >= 1.0 {
+        20
+    } else if ratio >= options.alert_threshold {
+        10
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(HealthSignal {
+        kind: SignalKind::BudgetBurn,
+        penalty,
+        summary: format!(
+            "Spending ${:.2}/day over the last {} days ({:.0}% of the ${:.2} daily limit)",
+            daily,
+            BURN_WINDOW_DAYS,
+            ratio * 100.0,
+            options.daily_limit_usd
+        ),
+        recommendation:
+            "Switch routing to cost (`demiarch routing set-preference cost`) or raise cost.daily_limit_usd"
+                .to_string(),
+        items: Vec::new(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(kind: SignalKind, penalty: u32) -> HealthSignal {
+        HealthSignal {
+            kind,
+            penalty,
+            summary: String::new(),
+            recommendation: String::new(),
+            items: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_penalties_are_capped() {
+        assert_eq!(penalty(2, 5, 25), 10);
+        assert_eq!(penalty(40, 5, 25), 25);
+        assert_eq!(penalty(0, 5, 25), 0);
+    }
+
+    #[test]
+    fn test_report_scores_and_orders_signals() {
+        let now = Utc::now();
+        let healthy = HealthReport::new("p".into(), "P".into(), Vec::new(), now);
+        assert_eq!(healthy.score, 100);
+        assert_eq!(healthy.status, HealthStatus::Healthy);
+
+        let report = HealthReport::new(
+            "p".into(),
+            "P".into(),
+            vec![
+                signal(SignalKind::StaleContext, 10),
+                signal(SignalKind::FailingVerifications, 25),
+                signal(SignalKind::BudgetBurn, 20),
+            ],
+            now,
+        );
+        assert_eq!(report.score, 45);
+        assert_eq!(report.status, HealthStatus::Critical);
+        assert_eq!(report.signals[0].kind, SignalKind::FailingVerifications);
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_stuck_features() {
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'Demo')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO features (id, project_id, title, status, updated_at)
+             VALUES ('f1', 'p1', 'Login', 'in_progress', '2020-01-01 00:00:00'),
+                    ('f2', 'p1', 'Signup', 'in_progress', CURRENT_TIMESTAMP)",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let report = check(&db, "p1", HealthOptions::default(), Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(report.signals.len(), 1);
+        assert_eq!(report.signals[0].kind, SignalKind::StaleFeatures);
+        assert_eq!(report.signals[0].items[0].id, "f1");
+        assert_eq!(report.score, 95);
+    }
+}
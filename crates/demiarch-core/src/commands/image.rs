@@ -10,9 +10,14 @@ use crate::error::{Error, Result};
 use crate::image::operations::{check_api_key_available, generate_output_path};
 use crate::image::{
     generate_image, inpaint_image, transform_image, upscale_image, ImageClient, ImageFormat,
-    ImageRequest, ImageSize, ImageStyle, InpaintRequest, TransformRequest, UpscaleRequest,
-    IMAGE_MODELS,
+    ImageModel, ImageRequest, ImageSize, ImageStyle, InpaintRequest, TransformRequest,
+    UpscaleRequest, IMAGE_MODELS,
 };
+use crate::storage::write_queue::{write_or_queue, QueuedCost, QueuedWrite};
+use crate::storage::Database;
+
+/// `llm_costs.context` of image operation costs, which invoices bill separately
+pub const COST_CONTEXT: &str = "image";
 
 /// Generate an image from a text prompt
 pub async fn generate(
@@ -267,6 +272,27 @@ pub async fn inpaint(
     Ok(output_path)
 }
 
+/// Store an image operation's approximate cost in `llm_costs`
+///
+/// Uses the model's listed per-image price (the default text-to-image model
+/// when `model` is unset or unknown) and returns the amount recorded.
+/// Queued for retry if the database is busy.
+pub async fn record_cost(
+    db: &Database,
+    project_id: Option<&str>,
+    model: Option<&str>,
+) -> Result<f64> {
+    let model = model
+        .and_then(ImageModel::by_id)
+        .unwrap_or_else(ImageModel::default_text_to_image);
+    let Some(cost) = model.cost_per_image.filter(|c| *c > 0.0) else {
+        return Ok(0.0);
+    };
+    let record = QueuedCost::new(project_id, model.id, cost).with_context(COST_CONTEXT);
+    write_or_queue(db.pool(), QueuedWrite::LlmCost(record)).await?;
+    Ok(cost)
+}
+
 /// List available image models
 pub fn list_models() -> Vec<ModelInfo> {
     IMAGE_MODELS
@@ -0,0 +1,422 @@
+//! Review queue: everything in a project waiting for a person
+//!
+//! `demiarch inbox` and the GUI inbox list, in this order:
+//!
+//! - approval gates a running generation is waiting on
+//! - unresolved conflicts: generated changes to existing files still waiting
+//!   for an accept/reject decision
+//! - blocking reviewer findings on files that were neither applied nor
+//!   rejected
+//! - failed verifications: failed generations and generated files that did
+//!   not pass syntax validation but were never rejected
+//! - features moved to `review`
+//!
+//! Each item links to the record that resolves it in the desktop app, with
+//! the GUI route the link opens.
+//!
+//! Approval requests live in the process that is waiting on them, so the
+//! caller passes the ones it knows about; the CLI answers its gates inline
+//! and has none to pass.
+
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::commands::approval::ApprovalRequest;
+use crate::commands::project::ProjectRepository;
+use crate::deeplink::DeepLink;
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Why an item is in the queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewKind {
+    Approval,
+    Conflict,
+    BlockingFinding,
+    FailedVerification,
+    FeatureReview,
+}
+
+impl ReviewKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Approval => "approval",
+            Self::Conflict => "conflict",
+            Self::BlockingFinding => "blocking_finding",
+            Self::FailedVerification => "failed_verification",
+            Self::FeatureReview => "feature_review",
+        }
+    }
+
+    /// Heading for a group of items
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Approval => "Pending approvals",
+            Self::Conflict => "Unresolved conflicts",
+            Self::BlockingFinding => "Blocking findings",
+            Self::FailedVerification => "Failed verifications",
+            Self::FeatureReview => "Features in review",
+        }
+    }
+}
+
+/// One thing waiting for a person
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewItem {
+    pub kind: ReviewKind,
+    /// ID of the approval request, artifact, finding, generation or feature
+    pub id: String,
+    pub title: String,
+    pub detail: String,
+    /// `demiarch://` link to the record that resolves the item
+    pub link: Option<String>,
+    /// GUI route of that record
+    pub route: Option<String>,
+}
+
+/// Items per kind
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewCounts {
+    pub approvals: usize,
+    pub conflicts: usize,
+    pub blocking_findings: usize,
+    pub failed_verifications: usize,
+    pub features_in_review: usize,
+}
+
+impl ReviewCounts {
+    fn of(items: &[ReviewItem]) -> Self {
+        let mut counts = Self::default();
+        for item in items {
+            let count = match item.kind {
+                ReviewKind::Approval => &mut counts.approvals,
+                ReviewKind::Conflict => &mut counts.conflicts,
+                ReviewKind::BlockingFinding => &mut counts.blocking_findings,
+                ReviewKind::FailedVerification => &mut counts.failed_verifications,
+                ReviewKind::FeatureReview => &mut counts.features_in_review,
+            };
+            *count += 1;
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.approvals
+            + self.conflicts
+            + self.blocking_findings
+            + self.failed_verifications
+            + self.features_in_review
+    }
+}
+
+/// Everything in a project awaiting human attention
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewQueue {
+    pub project_id: String,
+    pub project_name: String,
+    pub counts: ReviewCounts,
+    pub total: usize,
+    /// Grouped by kind in the order of [`ReviewKind`], oldest first
+    pub items: Vec<ReviewItem>,
+}
+
+/// Link and GUI route of a generation's review page
+fn generation_link(project_id: &str, generation_id: &str) -> (Option<String>, Option<String>) {
+    (
+        Some(DeepLink::Generation(generation_id.to_string()).url()),
+        Some(format!(
+            "/projects/{}/conflicts?generation={}",
+            project_id, generation_id
+        )),
+    )
+}
+
+fn short(id: &str) -> &str {
+    &id[..8.min(id.len())]
+}
+
+/// Collect a project's review queue
+///
+/// `approvals` are the requests waiting in this process; they carry no
+/// project, so all of them are listed.
+pub async fn review_queue(
+    db: &Database,
+    project_id: &str,
+    approvals: &[ApprovalRequest],
+) -> Result<ReviewQueue> {
+    let project = ProjectRepository::new(db)
+        .get(project_id)
+        .await?
+        .ok_or_else(|| Error::ProjectNotFound(project_id.to_string()))?;
+
+    let mut items: Vec<ReviewItem> = approvals
+        .iter()
+        .map(|request| ReviewItem {
+            kind: ReviewKind::Approval,
+            id: request.id.clone(),
+            title: request.summary.clone(),
+            detail: format!(
+                "{} gate for '{}', expires {}",
+                request.gate,
+                request.generation,
+                request.expires_at.format("%H:%M:%S UTC")
+            ),
+            link: None,
+            route: None,
+        })
+        .collect();
+    items.extend(conflicts(db, &project.id).await?);
+    items.extend(blocking_findings(db, &project.id).await?);
+    items.extend(failed_verifications(db, &project.id).await?);
+    items.extend(features_in_review(db, &project.id).await?);
+
+    let counts = ReviewCounts::of(&items);
+    Ok(ReviewQueue {
+        project_id: project.id,
+        project_name: project.name,
+        counts,
+        total: counts.total(),
+        items,
+    })
+}
+
+async fn conflicts(db: &Database, project_id: &str) -> Result<Vec<ReviewItem>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT a.id, a.file_path, a.generation_id
+        FROM generation_artifacts a
+        JOIN generations g ON g.id = a.generation_id
+        WHERE g.project_id = ? AND a.decision = 'pending' AND a.is_new = 0
+        ORDER BY a.created_at
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let generation_id: String = row.get("generation_id");
+            let (link, route) = generation_link(project_id, &generation_id);
+            ReviewItem {
+                kind: ReviewKind::Conflict,
+                id: row.get("id"),
+                title: row.get("file_path"),
+                detail: format!(
+                    "review with `demiarch generations review {}`",
+                    generation_id
+                ),
+                link,
+                route,
+            }
+        })
+        .collect())
+}
+
+async fn blocking_findings(db: &Database, project_id: &str) -> Result<Vec<ReviewItem>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT f.id, f.file_path, f.line, f.severity, f.description, f.generation_id
+        FROM review_findings f
+        JOIN generations g ON g.id = f.generation_id
+        WHERE g.project_id = ? AND f.blocking = 1
+          AND NOT EXISTS (
+              SELECT 1 FROM generation_artifacts a
+              WHERE a.generation_id = f.generation_id AND a.file_path = f.file_path
+                AND (a.decision = 'rejected' OR a.applied_at IS NOT NULL)
+          )
+        ORDER BY f.created_at, f.file_path, f.line
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let generation_id: String = row.get("generation_id");
+            let file_path: String = row.get("file_path");
+            let line: Option<i64> = row.get("line");
+            let severity: String = row.get("severity");
+            let description: String = row.get("description");
+            let (link, route) = generation_link(project_id, &generation_id);
+            ReviewItem {
+                kind: ReviewKind::BlockingFinding,
+                id: row.get("id"),
+                title: match line {
+                    Some(line) => format!("{}:{}", file_path, line),
+                    None => file_path,
+                },
+                detail: format!(
+                    "{}: {} (generation {})",
+                    severity,
+                    description,
+                    short(&generation_id)
+                ),
+                link,
+                route,
+            }
+        })
+        .collect())
+}
+
+async fn failed_verifications(db: &Database, project_id: &str) -> Result<Vec<ReviewItem>> {
+    let failed = sqlx::query(
+        r#"
+        SELECT id, description
+        FROM generations
+        WHERE project_id = ? AND status = 'failed'
+        ORDER BY updated_at
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?;
+    let invalid = sqlx::query(
+        r#"
+        SELECT a.id, a.file_path, a.generation_id
+        FROM generation_artifacts a
+        JOIN generations g ON g.id = a.generation_id
+        WHERE g.project_id = ? AND a.validation_status = 'invalid'
+          AND a.decision != 'rejected'
+        ORDER BY a.created_at
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?;
+
+    let failed_items = failed.iter().map(|row| {
+        let id: String = row.get("id");
+        let (link, route) = generation_link(project_id, &id);
+        ReviewItem {
+            kind: ReviewKind::FailedVerification,
+            title: row.get("description"),
+            detail: format!(
+                "generation failed; resume with `demiarch generate --resume {}`",
+                id
+            ),
+            link,
+            route,
+            id,
+        }
+    });
+    let invalid_items = invalid.iter().map(|row| {
+        let generation_id: String = row.get("generation_id");
+        let (link, route) = generation_link(project_id, &generation_id);
+        ReviewItem {
+            kind: ReviewKind::FailedVerification,
+            id: row.get("id"),
+            title: row.get("file_path"),
+            detail: format!("syntax errors in generation {}", short(&generation_id)),
+            link,
+            route,
+        }
+    });
+    Ok(failed_items.chain(invalid_items).collect())
+}
+
+async fn features_in_review(db: &Database, project_id: &str) -> Result<Vec<ReviewItem>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, title, updated_at
+        FROM features
+        WHERE project_id = ? AND status = 'review'
+        ORDER BY updated_at
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let id: String = row.get("id");
+            let updated: chrono::DateTime<chrono::Utc> = row.get("updated_at");
+            ReviewItem {
+                kind: ReviewKind::FeatureReview,
+                title: row.get("title"),
+                detail: format!("in review since {}", updated.format("%Y-%m-%d")),
+                link: Some(DeepLink::Feature(id.clone()).url()),
+                route: Some(format!("/projects/{}/kanban?feature={}", project_id, id)),
+                id,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::approval::ApprovalGate;
+    use chrono::Utc;
+
+    #[tokio::test]
+    async fn test_collects_items_awaiting_review() {
+        let db = Database::in_memory().await.unwrap();
+        sqlx::raw_sql(
+            r#"
+            INSERT INTO projects (id, name) VALUES ('p1', 'Shop');
+            INSERT INTO features (id, project_id, title, status)
+                VALUES ('f1', 'p1', 'Checkout', 'review'),
+                       ('f2', 'p1', 'Search', 'in_progress');
+            INSERT INTO generations (id, project_id, description, output_dir, status)
+                VALUES ('g1', 'p1', 'add cart', '/tmp', 'completed'),
+                       ('g2', 'p1', 'add search', '/tmp', 'failed');
+            INSERT INTO generation_artifacts (id, generation_id, file_path, content, is_new, decision)
+                VALUES ('a1', 'g1', 'src/cart.rs', '', 0, 'pending'),
+                       ('a2', 'g1', 'src/db.rs', '', 1, 'rejected');
+            INSERT INTO review_findings (id, generation_id, file_path, severity, category, description, line, blocking)
+                VALUES ('r1', 'g1', 'src/cart.rs', 'critical', 'injection', 'SQL built from input', 12, 1),
+                       ('r2', 'g1', 'src/db.rs', 'critical', 'injection', 'rejected file', 3, 1),
+                       ('r3', 'g1', 'src/cart.rs', 'minor', 'style', 'not blocking', 4, 0);
+            "#,
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let approval = ApprovalRequest {
+            id: "req1".to_string(),
+            gate: ApprovalGate::AfterPlanning,
+            generation: "add cart".to_string(),
+            summary: "3 tasks planned".to_string(),
+            details: Vec::new(),
+            requested_at: Utc::now(),
+            expires_at: Utc::now(),
+        };
+        let queue = review_queue(&db, "p1", &[approval]).await.unwrap();
+
+        assert_eq!(
+            queue.counts,
+            ReviewCounts {
+                approvals: 1,
+                conflicts: 1,
+                blocking_findings: 1,
+                failed_verifications: 1,
+                features_in_review: 1,
+            }
+        );
+        assert_eq!(queue.total, 5);
+        let ids: Vec<&str> = queue.items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec!["req1", "a1", "r1", "g2", "f1"]);
+        assert_eq!(queue.items[2].title, "src/cart.rs:12");
+        assert_eq!(
+            queue.items[4].link.as_deref(),
+            Some("demiarch://feature/f1")
+        );
+        assert_eq!(
+            queue.items[1].route.as_deref(),
+            Some("/projects/p1/conflicts?generation=g1")
+        );
+
+        assert!(matches!(
+            review_queue(&db, "missing", &[]).await,
+            Err(Error::ProjectNotFound(_))
+        ));
+    }
+}
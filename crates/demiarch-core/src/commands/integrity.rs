@@ -0,0 +1,636 @@
+//! Deep data integrity audit
+//!
+//! `demiarch db verify` complements `doctor` with a full scan of the data:
+//!
+//! - SQLite's own `PRAGMA integrity_check` for page-level corruption
+//! - referential integrity between tables (features → projects, generated
+//!   files → features, session events → sessions, ...). Foreign keys are
+//!   enforced on every connection, but rows written by older builds, JSONL
+//!   imports or external tools can still point at records that are gone
+//! - checkpoint snapshots and signatures, in bulk
+//! - generated files on disk against the hashes recorded when they were
+//!   written
+//!
+//! The audit itself never writes. `--repair` then fixes the orphans it found:
+//! optional references are cleared, matching their `ON DELETE SET NULL`
+//! behaviour, and rows that cannot exist without their parent are deleted,
+//! matching `ON DELETE CASCADE`. Other findings are reported only.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::domain::recovery::{compute_file_hash, CheckpointVerifier};
+use crate::storage::{content, ensure_writable, Database};
+use crate::Result;
+
+/// Issues listed per check before the rest are only counted
+const MAX_ISSUES_PER_CHECK: usize = 100;
+
+/// Length of an Ed25519 signature
+const SIGNATURE_LEN: usize = 64;
+
+/// A reference from one table to another that must resolve
+#[derive(Debug, Clone, Copy)]
+struct Reference {
+    table: &'static str,
+    column: &'static str,
+    parent: &'static str,
+    /// Whether the reference may be cleared instead of deleting the row
+    optional: bool,
+}
+
+impl Reference {
+    const fn required(table: &'static str, column: &'static str, parent: &'static str) -> Self {
+        Self {
+            table,
+            column,
+            parent,
+            optional: false,
+        }
+    }
+
+    const fn optional(table: &'static str, column: &'static str, parent: &'static str) -> Self {
+        Self {
+            table,
+            column,
+            parent,
+            optional: true,
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("{}.{} -> {}", self.table, self.column, self.parent)
+    }
+}
+
+/// References checked by the audit
+const REFERENCES: &[Reference] = &[
+    Reference::required("features", "project_id", "projects"),
+    Reference::required("phases", "project_id", "projects"),
+    Reference::required("conversations", "project_id", "projects"),
+    Reference::required("messages", "conversation_id", "conversations"),
+    Reference::optional("messages", "parent_message_id", "messages"),
+    Reference::optional("messages", "variant_of", "messages"),
+    Reference::required("conversation_branches", "conversation_id", "conversations"),
+    Reference::required("checkpoints", "project_id", "projects"),
+    Reference::optional("checkpoints", "feature_id", "features"),
+    Reference::required("generated_files", "project_id", "projects"),
+    Reference::optional("generated_files", "feature_id", "features"),
+    Reference::required("generations", "project_id", "projects"),
+    Reference::optional("generations", "feature_id", "features"),
+    Reference::required("generation_artifacts", "generation_id", "generations"),
+    Reference::required("review_findings", "generation_id", "generations"),
+    Reference::optional("llm_costs", "project_id", "projects"),
+    Reference::required("project_secrets", "project_id", "projects"),
+    Reference::required("personas", "project_id", "projects"),
+    Reference::required("session_events", "session_id", "sessions"),
+    Reference::required("feature_time", "session_id", "sessions"),
+];
+
+/// Kind of problem the audit found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// SQLite reported corruption
+    Corruption,
+    /// A row references a record that does not exist
+    Orphan,
+    /// A checkpoint's snapshot or signature is unusable
+    BadCheckpoint,
+    /// A generated file no longer matches its recorded hash
+    FileModified,
+    /// A generated file is missing from disk
+    FileMissing,
+}
+
+impl IssueKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Corruption => "corruption",
+            Self::Orphan => "orphan",
+            Self::BadCheckpoint => "bad_checkpoint",
+            Self::FileModified => "file_modified",
+            Self::FileMissing => "file_missing",
+        }
+    }
+}
+
+/// A single finding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    pub kind: IssueKind,
+    /// Check that found it, e.g. `features.project_id -> projects`
+    pub check: String,
+    /// Identifier of the offending row, if any
+    pub record: Option<String>,
+    pub detail: String,
+    /// Whether `--repair` fixes it
+    pub repairable: bool,
+    #[serde(skip)]
+    repair: Option<Repair>,
+}
+
+/// How an orphan is repaired
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Repair {
+    reference: usize,
+    rowid: i64,
+}
+
+/// Rows examined and issues found by one check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckSummary {
+    pub name: String,
+    pub rows: i64,
+    pub issues: i64,
+}
+
+/// What the audit should cover
+#[derive(Debug, Clone, Default)]
+pub struct VerifyOptions {
+    /// Public key to verify checkpoint signatures with. Without one only the
+    /// signature format is checked
+    pub verifying_key: Option<Vec<u8>>,
+    /// Skip reading generated files from disk
+    pub skip_files: bool,
+}
+
+impl VerifyOptions {
+    /// Verify checkpoint signatures with a hex-encoded Ed25519 public key
+    pub fn with_verifying_key_hex(mut self, key: &str) -> Result<Self> {
+        let bytes = hex::decode(key.trim())
+            .map_err(|e| crate::Error::InvalidInput(format!("Invalid verifying key: {}", e)))?;
+        self.verifying_key = Some(bytes);
+        Ok(self)
+    }
+}
+
+/// Result of an audit
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub checks: Vec<CheckSummary>,
+    pub issues: Vec<IntegrityIssue>,
+    /// Whether checkpoint signatures were verified cryptographically
+    pub signatures_verified: bool,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Number of issues `--repair` would fix
+    pub fn repairable(&self) -> usize {
+        self.issues.iter().filter(|i| i.repairable).count()
+    }
+
+    fn record(&mut self, name: String, rows: i64, issues: Vec<IntegrityIssue>) {
+        self.checks.push(CheckSummary {
+            name,
+            rows,
+            issues: issues.len() as i64,
+        });
+        self.issues.extend(issues);
+    }
+}
+
+/// Outcome of `--repair`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RepairOutcome {
+    /// Rows whose dangling optional reference was cleared
+    pub cleared: u64,
+    /// Rows deleted because their required parent is gone
+    pub deleted: u64,
+}
+
+/// Run every check without modifying anything
+pub async fn verify(db: &Database, options: &VerifyOptions) -> Result<IntegrityReport> {
+    let mut report = IntegrityReport::default();
+    check_sqlite(db, &mut report).await?;
+    for (index, reference) in REFERENCES.iter().enumerate() {
+        check_reference(db, index, reference, &mut report).await?;
+    }
+    check_checkpoints(db, options, &mut report).await?;
+    if !options.skip_files {
+        check_generated_files(db, &mut report).await?;
+    }
+    Ok(report)
+}
+
+/// Fix the repairable issues from a report
+///
+/// Every row is re-checked before it is changed, so a stale report cannot
+/// delete a row whose parent has reappeared since.
+pub async fn repair(db: &Database, report: &IntegrityReport) -> Result<RepairOutcome> {
+    ensure_writable("integrity repair")?;
+    let mut outcome = RepairOutcome::default();
+    let mut tx = db.pool().begin().await?;
+    for repair in report.issues.iter().filter_map(|i| i.repair) {
+        let r = REFERENCES[repair.reference];
+        let still_orphaned = format!(
+            "rowid = ? AND {column} IS NOT NULL
+             AND NOT EXISTS (SELECT 1 FROM {parent} p WHERE p.id = {table}.{column})",
+            table = r.table,
+            column = r.column,
+            parent = r.parent
+        );
+        let sql = if r.optional {
+            format!(
+                "UPDATE {} SET {} = NULL WHERE {}",
+                r.table, r.column, still_orphaned
+            )
+        } else {
+            format!("DELETE FROM {} WHERE {}", r.table, still_orphaned)
+        };
+        let affected = sqlx::query(&sql)
+            .bind(repair.rowid)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if r.optional {
+            outcome.cleared += affected;
+        } else {
+            outcome.deleted += affected;
+        }
+    }
+    tx.commit().await?;
+    Ok(outcome)
+}
+
+async fn check_sqlite(db: &Database, report: &mut IntegrityReport) -> Result<()> {
+    let rows = sqlx::query("PRAGMA integrity_check")
+        .fetch_all(db.pool())
+        .await?;
+    let issues = rows
+        .iter()
+        .map(|row| row.get::<String, _>(0))
+        .filter(|message| message != "ok")
+        .map(|detail| IntegrityIssue {
+            kind: IssueKind::Corruption,
+            check: "sqlite".to_string(),
+            record: None,
+            detail,
+            repairable: false,
+            repair: None,
+        })
+        .collect();
+    let pages: i64 = sqlx::query_scalar("PRAGMA page_count")
+        .fetch_one(db.pool())
+        .await?;
+    report.record("sqlite".to_string(), pages, issues);
+    Ok(())
+}
+
+async fn check_reference(
+    db: &Database,
+    index: usize,
+    reference: &Reference,
+    report: &mut IntegrityReport,
+) -> Result<()> {
+    let Reference {
+        table,
+        column,
+        parent,
+        optional,
+    } = *reference;
+    let rows: i64 = sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM {} WHERE {} IS NOT NULL",
+        table, column
+    ))
+    .fetch_one(db.pool())
+    .await?;
+    let orphans = sqlx::query(&format!(
+        "SELECT rowid, {column} AS parent_id FROM {table} t
+         WHERE {column} IS NOT NULL
+           AND NOT EXISTS (SELECT 1 FROM {parent} p WHERE p.id = t.{column})
+         ORDER BY rowid",
+    ))
+    .fetch_all(db.pool())
+    .await?;
+
+    let action = if optional {
+        "clear reference"
+    } else {
+        "delete row"
+    };
+    let issues = orphans
+        .iter()
+        .map(|row| {
+            let rowid: i64 = row.get("rowid");
+            let parent_id: String = row.get("parent_id");
+            IntegrityIssue {
+                kind: IssueKind::Orphan,
+                check: reference.name(),
+                record: Some(format!("{} rowid {}", table, rowid)),
+                detail: format!(
+                    "{} {} does not exist (repair: {})",
+                    parent.trim_end_matches('s'),
+                    parent_id,
+                    action
+                ),
+                repairable: true,
+                repair: Some(Repair {
+                    reference: index,
+                    rowid,
+                }),
+            }
+        })
+        .collect();
+    report.record(reference.name(), rows, issues);
+    Ok(())
+}
+
+async fn check_checkpoints(
+    db: &Database,
+    options: &VerifyOptions,
+    report: &mut IntegrityReport,
+) -> Result<()> {
+    let verifier = options
+        .verifying_key
+        .as_deref()
+        .map(CheckpointVerifier::from_bytes)
+        .transpose()
+        .map_err(|e| crate::Error::InvalidInput(format!("Invalid verifying key: {}", e)))?;
+    report.signatures_verified = verifier.is_some();
+
+    let rows = sqlx::query(
+        r#"
+        SELECT c.id, c.snapshot_data, b.data AS snapshot_blob, c.snapshot_hash, c.size_bytes, c.signature
+        FROM checkpoints c
+        LEFT JOIN content_blobs b ON b.hash = c.snapshot_hash
+        ORDER BY c.created_at
+        "#,
+    )
+    .fetch_all(db.pool())
+    .await?;
+
+    let mut issues = Vec::new();
+    for row in &rows {
+        let id: String = row.get("id");
+        let blob: Option<Vec<u8>> = row.get("snapshot_blob");
+        let stored = row.get::<Option<String>, _>("snapshot_hash").is_some();
+        let size_bytes: i64 = row.get("size_bytes");
+        let signature: Vec<u8> = row.get("signature");
+        let snapshot = match (stored, blob) {
+            (true, None) => Err("stored snapshot is missing".to_string()),
+            (_, blob) => content::decode(row.get("snapshot_data"), blob)
+                .map_err(|e| format!("stored snapshot is unreadable: {}", e)),
+        };
+
+        let problem = match snapshot.and_then(|snapshot| {
+            serde_json::from_str::<serde_json::Value>(&snapshot)
+                .map(|value| (snapshot, value))
+                .map_err(|e| format!("snapshot is not valid JSON: {}", e))
+        }) {
+            Err(detail) => Some(detail),
+            Ok(_) if signature.len() != SIGNATURE_LEN => Some(format!(
+                "signature is {} bytes, expected {}",
+                signature.len(),
+                SIGNATURE_LEN
+            )),
+            Ok((snapshot, _)) if size_bytes != snapshot.len() as i64 => Some(format!(
+                "snapshot is {} bytes but {} were recorded",
+                snapshot.len(),
+                size_bytes
+            )),
+            Ok((_, value)) => verifier.as_ref().and_then(|verifier| {
+                // Signatures cover the serialized value, as in CheckpointManager
+                let bytes = serde_json::to_vec(&value).unwrap_or_default();
+                verifier
+                    .verify(&bytes, &signature)
+                    .err()
+                    .map(|e| format!("signature does not verify: {}", e))
+            }),
+        };
+        if let Some(detail) = problem {
+            issues.push(IntegrityIssue {
+                kind: IssueKind::BadCheckpoint,
+                check: "checkpoints".to_string(),
+                record: Some(id),
+                detail,
+                repairable: false,
+                repair: None,
+            });
+        }
+    }
+    report.record("checkpoints".to_string(), rows.len() as i64, issues);
+    Ok(())
+}
+
+async fn check_generated_files(db: &Database, report: &mut IntegrityReport) -> Result<()> {
+    let rows = sqlx::query(
+        "SELECT g.file_path, g.content_hash, g.last_verified_hash, p.path AS root
+         FROM generated_files g
+         JOIN projects p ON p.id = g.project_id
+         WHERE p.path IS NOT NULL
+         ORDER BY g.project_id, g.file_path",
+    )
+    .fetch_all(db.pool())
+    .await?;
+
+    let mut issues = Vec::new();
+    for row in &rows {
+        let file_path: String = row.get("file_path");
+        let content_hash: String = row.get("content_hash");
+        let last_verified: Option<String> = row.get("last_verified_hash");
+        let root: String = row.get("root");
+        let full_path = Path::new(&root).join(&file_path);
+
+        let issue = match std::fs::read_to_string(&full_path) {
+            Ok(content) => {
+                let hash = compute_file_hash(&content);
+                // An edit already seen by edit detection is not news
+                (hash != content_hash && last_verified.as_deref() != Some(hash.as_str())).then_some(
+                    (
+                        IssueKind::FileModified,
+                        "content differs from the recorded hash",
+                    ),
+                )
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Some((IssueKind::FileMissing, "file is missing"))
+            }
+            Err(_) => Some((IssueKind::FileMissing, "file cannot be read")),
+        };
+        if let Some((kind, detail)) = issue {
+            issues.push(IntegrityIssue {
+                kind,
+                check: "generated_files".to_string(),
+                record: Some(full_path.display().to_string()),
+                detail: detail.to_string(),
+                repairable: false,
+                repair: None,
+            });
+        }
+    }
+    report.record("generated_files".to_string(), rows.len() as i64, issues);
+    Ok(())
+}
+
+/// Issues of one check, truncated for display
+pub fn issues_for<'a>(report: &'a IntegrityReport, check: &str) -> Vec<&'a IntegrityIssue> {
+    report
+        .issues
+        .iter()
+        .filter(|i| i.check == check)
+        .take(MAX_ISSUES_PER_CHECK)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::recovery::CheckpointSigner;
+
+    async fn db_without_foreign_keys() -> Database {
+        let db = Database::in_memory().await.unwrap();
+        // Simulate rows written while foreign keys were not enforced
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn test_clean_database_has_no_issues() {
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'Demo')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO features (id, project_id, title) VALUES ('f1', 'p1', 'login')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let report = verify(&db, &VerifyOptions::default()).await.unwrap();
+        assert!(report.is_clean(), "{:?}", report.issues);
+        let features = report
+            .checks
+            .iter()
+            .find(|c| c.name == "features.project_id -> projects")
+            .unwrap();
+        assert_eq!(features.rows, 1);
+    }
+
+    #[tokio::test]
+    async fn test_finds_and_repairs_orphans() {
+        let db = db_without_foreign_keys().await;
+        sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'Demo')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO features (id, project_id, title) VALUES ('f1', 'gone', 'orphan')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO generations (id, project_id, feature_id, description, output_dir)
+             VALUES ('g1', 'p1', 'missing', 'adhoc', '/tmp')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let report = verify(&db, &VerifyOptions::default()).await.unwrap();
+        assert_eq!(report.repairable(), 2);
+        assert!(report.issues.iter().all(|i| i.kind == IssueKind::Orphan));
+
+        let outcome = repair(&db, &report).await.unwrap();
+        assert_eq!(
+            outcome,
+            RepairOutcome {
+                cleared: 1,
+                deleted: 1
+            }
+        );
+
+        let feature_id: Option<String> =
+            sqlx::query_scalar("SELECT feature_id FROM generations WHERE id = 'g1'")
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+        assert_eq!(feature_id, None);
+        assert!(verify(&db, &VerifyOptions::default())
+            .await
+            .unwrap()
+            .is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_checks_checkpoint_signatures() {
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'Demo')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let signer = CheckpointSigner::generate();
+        let snapshot = serde_json::json!({ "features": [] });
+        let signature = signer.sign(&serde_json::to_vec(&snapshot).unwrap());
+        for (id, signature) in [("good", signature), ("short", vec![1, 2, 3])] {
+            let data = snapshot.to_string();
+            sqlx::query(
+                "INSERT INTO checkpoints (id, project_id, description, snapshot_data, size_bytes, signature)
+                 VALUES (?, 'p1', 'test', ?, ?, ?)",
+            )
+            .bind(id)
+            .bind(&data)
+            .bind(data.len() as i64)
+            .bind(signature)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+
+        let report = verify(&db, &VerifyOptions::default()).await.unwrap();
+        assert!(!report.signatures_verified);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].record.as_deref(), Some("short"));
+
+        let other = CheckpointSigner::generate();
+        let options = VerifyOptions {
+            verifying_key: Some(other.verifying_key_bytes().to_vec()),
+            skip_files: true,
+        };
+        let report = verify(&db, &options).await.unwrap();
+        assert!(report.signatures_verified);
+        assert_eq!(report.issues.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_checks_generated_file_hashes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("kept.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("edited.rs"), "fn edited() {}").unwrap();
+
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query("INSERT INTO projects (id, name, path) VALUES ('p1', 'Demo', ?)")
+            .bind(dir.path().to_string_lossy())
+            .execute(db.pool())
+            .await
+            .unwrap();
+        for (id, path) in [("a", "kept.rs"), ("b", "edited.rs"), ("c", "gone.rs")] {
+            sqlx::query(
+                "INSERT INTO generated_files (id, project_id, file_path, content_hash)
+                 VALUES (?, 'p1', ?, ?)",
+            )
+            .bind(id)
+            .bind(path)
+            .bind(compute_file_hash("fn main() {}"))
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+
+        let report = verify(&db, &VerifyOptions::default()).await.unwrap();
+        let kinds: Vec<_> = issues_for(&report, "generated_files")
+            .iter()
+            .map(|i| i.kind)
+            .collect();
+        assert_eq!(kinds, [IssueKind::FileModified, IssueKind::FileMissing]);
+        assert_eq!(report.repairable(), 0);
+    }
+}
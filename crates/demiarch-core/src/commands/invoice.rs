@@ -0,0 +1,433 @@
+//! Client invoices for AI spend
+//!
+//! `demiarch costs invoice --project <id> --month 2025-03 --output invoice.csv`
+//! bills one project's month of AI costs through to a client: a line item
+//! per feature from its generations, one for generations not tied to a
+//! feature, and from `llm_costs` one for other LLM calls (chat,
+//! transcription) and one for image generation. Each line is marked up by
+//! `invoice.markup_percent` and converted from USD with the static
+//! `invoice.rates` table.
+
+use std::fmt;
+use std::io::Write;
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use super::image::COST_CONTEXT as IMAGE_COST_CONTEXT;
+use super::project::Project;
+use crate::config::InvoiceConfig;
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// A calendar month, UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Month {
+    pub year: i32,
+    pub month: u32,
+}
+
+impl Month {
+    /// Parse `YYYY-MM`
+    pub fn parse(s: &str) -> Result<Self> {
+        NaiveDate::parse_from_str(&format!("{}-01", s.trim()), "%Y-%m-%d")
+            .map(|day| Self {
+                year: day.year(),
+                month: day.month(),
+            })
+            .map_err(|_| Error::InvalidInput(format!("Invalid month '{}': use YYYY-MM", s)))
+    }
+
+    pub fn first_day(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, self.month, 1).unwrap_or_default()
+    }
+
+    pub fn last_day(&self) -> NaiveDate {
+        let next = if self.month == 12 {
+            NaiveDate::from_ymd_opt(self.year + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(self.year, self.month + 1, 1)
+        };
+        next.and_then(|d| d.pred_opt()).unwrap_or_default()
+    }
+
+    /// Half-open timestamp bounds for SQL comparisons
+    fn bounds(&self) -> (String, String) {
+        let next = self.last_day().succ_opt().unwrap_or_default();
+        (
+            format!("{} 00:00:00", self.first_day()),
+            format!("{} 00:00:00", next),
+        )
+    }
+}
+
+impl fmt::Display for Month {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+/// Markup and currency an invoice is issued with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvoiceOptions {
+    pub markup_percent: f64,
+    pub currency: String,
+    /// Units of `currency` per US dollar
+    pub rate: f64,
+}
+
+impl InvoiceOptions {
+    /// Options from `[invoice]`, with command-line overrides
+    pub fn resolve(
+        config: &InvoiceConfig,
+        markup_percent: Option<f64>,
+        currency: Option<&str>,
+    ) -> Result<Self> {
+        let markup_percent = markup_percent.unwrap_or(config.markup_percent);
+        if markup_percent < 0.0 {
+            return Err(Error::InvalidInput("Markup cannot be negative".to_string()));
+        }
+        let currency = currency.unwrap_or(&config.currency).trim().to_uppercase();
+        let rate = match config.rates.get(&currency) {
+            Some(rate) => *rate,
+            None if currency == "USD" => 1.0,
+            None => {
+                return Err(Error::InvalidInput(format!(
+                    "No exchange rate for {}. Add one with `demiarch config set invoice.rates.{} <rate>`",
+                    currency, currency
+                )))
+            }
+        };
+        Ok(Self {
+            markup_percent,
+            currency,
+            rate,
+        })
+    }
+
+    /// Billed amount, in the invoice currency, for a cost in USD
+    fn bill(&self, cost_usd: f64) -> f64 {
+        round_cents(cost_usd * (1.0 + self.markup_percent / 100.0) * self.rate)
+    }
+}
+
+/// What a line item bills for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineKind {
+    /// Generations for one feature
+    Feature,
+    /// Generations not tied to a feature
+    Generations,
+    /// LLM calls outside generations
+    Llm,
+    /// Image generation
+    Image,
+}
+
+/// One billed line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineItem {
+    pub kind: LineKind,
+    pub feature_id: Option<String>,
+    pub description: String,
+    /// Generations, calls or images
+    pub quantity: i64,
+    pub tokens: i64,
+    pub cost_usd: f64,
+    /// Marked-up amount in the invoice currency
+    pub amount: f64,
+}
+
+/// A month of one project's AI costs, ready to bill
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invoice {
+    pub project_id: String,
+    pub project_name: String,
+    pub month: Month,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    #[serde(flatten)]
+    pub options: InvoiceOptions,
+    pub lines: Vec<LineItem>,
+    pub cost_usd: f64,
+    /// Converted cost before markup
+    pub subtotal: f64,
+    pub markup: f64,
+    pub total: f64,
+}
+
+/// Build an invoice for `project`'s costs in `month`
+pub async fn build(
+    db: &Database,
+    project: &Project,
+    month: Month,
+    options: InvoiceOptions,
+) -> Result<Invoice> {
+    let (start, end) = month.bounds();
+    let mut lines = Vec::new();
+
+    let rows = sqlx::query(
+        r#"
+        SELECT g.feature_id, f.title, COUNT(*) AS quantity,
+               COALESCE(SUM(g.tokens_used), 0) AS tokens,
+               COALESCE(SUM(g.cost_usd), 0.0) AS cost_usd
+        FROM generations g
+        LEFT JOIN features f ON f.id = g.feature_id
+        WHERE g.project_id = ?
+          AND datetime(g.created_at) >= datetime(?) AND datetime(g.created_at) < datetime(?)
+        GROUP BY g.feature_id
+        ORDER BY g.feature_id IS NULL, f.title, g.feature_id
+        "#,
+    )
+    .bind(&project.id)
+    .bind(&start)
+    .bind(&end)
+    .fetch_all(db.pool())
+    .await?;
+    for row in rows {
+        let feature_id: Option<String> = row.get("feature_id");
+        let title: Option<String> = row.get("title");
+        let (kind, description) = match (&feature_id, title) {
+            (None, _) => (LineKind::Generations, "Other generations".to_string()),
+            (Some(_), Some(title)) => (LineKind::Feature, format!("Feature: {}", title)),
+            (Some(id), None) => (LineKind::Feature, format!("Feature {} (deleted)", id)),
+        };
+        lines.push(line(
+            kind,
+            feature_id,
+            description,
+            row.get("quantity"),
+            row.get("tokens"),
+            row.get("cost_usd"),
+            &options,
+        ));
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT COALESCE(context, '') = ? AS is_image, COUNT(*) AS quantity,
+               COALESCE(SUM(input_tokens + output_tokens), 0) AS tokens,
+               COALESCE(SUM(input_cost_usd + output_cost_usd), 0.0) AS cost_usd
+        FROM llm_costs
+        WHERE project_id = ?
+          AND datetime(created_at) >= datetime(?) AND datetime(created_at) < datetime(?)
+        GROUP BY is_image
+        ORDER BY is_image
+        "#,
+    )
+    .bind(IMAGE_COST_CONTEXT)
+    .bind(&project.id)
+    .bind(&start)
+    .bind(&end)
+    .fetch_all(db.pool())
+    .await?;
+    for row in rows {
+        let (kind, description) = if row.get::<i64, _>("is_image") != 0 {
+            (LineKind::Image, "Image generation")
+        } else {
+            (LineKind::Llm, "Other LLM usage (chat, transcription)")
+        };
+        lines.push(line(
+            kind,
+            None,
+            description.to_string(),
+            row.get("quantity"),
+            row.get("tokens"),
+            row.get("cost_usd"),
+            &options,
+        ));
+    }
+
+    let cost_usd: f64 = lines.iter().map(|l| l.cost_usd).sum();
+    let subtotal = round_cents(cost_usd * options.rate);
+    let total = round_cents(lines.iter().map(|l| l.amount).sum());
+    Ok(Invoice {
+        project_id: project.id.clone(),
+        project_name: project.name.clone(),
+        month,
+        period_start: month.first_day(),
+        period_end: month.last_day(),
+        options,
+        lines,
+        cost_usd,
+        subtotal,
+        markup: round_cents(total - subtotal),
+        total,
+    })
+}
+
+fn line(
+    kind: LineKind,
+    feature_id: Option<String>,
+    description: String,
+    quantity: i64,
+    tokens: i64,
+    cost_usd: f64,
+    options: &InvoiceOptions,
+) -> LineItem {
+    LineItem {
+        kind,
+        feature_id,
+        description,
+        quantity,
+        tokens,
+        cost_usd,
+        amount: options.bill(cost_usd),
+    }
+}
+
+/// Write the invoice as CSV: one row per line item, then subtotal, markup
+/// and total rows
+pub fn write_csv<W: Write>(invoice: &Invoice, writer: W) -> Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    let currency = invoice.options.currency.as_str();
+    let markup = invoice.options.markup_percent.to_string();
+    let amount = |v: f64| format!("{:.2}", v);
+
+    csv.write_record([
+        "item",
+        "feature_id",
+        "quantity",
+        "tokens",
+        "cost_usd",
+        "markup_percent",
+        "currency",
+        "amount",
+    ])
+    .map_err(csv_error)?;
+    for line in &invoice.lines {
+        csv.write_record([
+            line.description.clone(),
+            line.feature_id.clone().unwrap_or_default(),
+            line.quantity.to_string(),
+            line.tokens.to_string(),
+            format!("{:.6}", line.cost_usd),
+            markup.clone(),
+            currency.to_string(),
+            amount(line.amount),
+        ])
+        .map_err(csv_error)?;
+    }
+    for (label, value) in [
+        ("Subtotal", invoice.subtotal),
+        ("Markup", invoice.markup),
+        ("Total", invoice.total),
+    ] {
+        csv.write_record([label, "", "", "", "", "", currency, amount(value).as_str()])
+            .map_err(csv_error)?;
+    }
+    csv.flush()?;
+    Ok(())
+}
+
+fn round_cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn csv_error(e: csv::Error) -> Error {
+    Error::Other(format!("CSV export failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::project::ProjectRepository;
+
+    #[test]
+    fn test_month_and_options() {
+        let month = Month::parse("2024-02").unwrap();
+        assert_eq!(month.to_string(), "2024-02");
+        assert_eq!(month.last_day().to_string(), "2024-02-29");
+        assert_eq!(
+            Month::parse("2024-12").unwrap().bounds().1,
+            "2025-01-01 00:00:00"
+        );
+        assert!(Month::parse("March").is_err());
+
+        let config = InvoiceConfig::default();
+        let options = InvoiceOptions::resolve(&config, Some(10.0), Some("eur")).unwrap();
+        assert_eq!(options.currency, "EUR");
+        assert_eq!(options.bill(100.0), 101.2);
+        assert!(InvoiceOptions::resolve(&config, None, Some("XYZ")).is_err());
+        assert!(InvoiceOptions::resolve(&config, Some(-1.0), None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_invoice_line_items() {
+        let db = Database::in_memory().await.unwrap();
+        let project = Project::new("client-site", "react", "");
+        ProjectRepository::new(&db).create(&project).await.unwrap();
+        sqlx::query("INSERT INTO features (id, project_id, title) VALUES ('f1', ?, 'Checkout')")
+            .bind(&project.id)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        for (id, feature, cost, at) in [
+            ("g1", Some("f1"), 2.0, "2025-03-02 10:00:00"),
+            ("g2", Some("f1"), 1.0, "2025-03-31 23:00:00"),
+            ("g3", None, 0.5, "2025-03-10 10:00:00"),
+            ("g4", Some("f1"), 9.0, "2025-04-01 00:00:00"),
+        ] {
+            sqlx::query(
+                "INSERT INTO generations (id, project_id, feature_id, description, output_dir, tokens_used, cost_usd, created_at) \
+                 VALUES (?, ?, ?, 'gen', '/tmp', 1000, ?, ?)",
+            )
+            .bind(id)
+            .bind(&project.id)
+            .bind(feature)
+            .bind(cost)
+            .bind(at)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+        for (id, context, cost) in [("c1", Some("image"), 0.04), ("c2", None, 0.46)] {
+            sqlx::query(
+                "INSERT INTO llm_costs (id, project_id, model, input_cost_usd, context, created_at) \
+                 VALUES (?, ?, 'm', ?, ?, '2025-03-15 12:00:00')",
+            )
+            .bind(id)
+            .bind(&project.id)
+            .bind(cost)
+            .bind(context)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+
+        let options = InvoiceOptions {
+            markup_percent: 20.0,
+            currency: "USD".to_string(),
+            rate: 1.0,
+        };
+        let invoice = build(&db, &project, Month::parse("2025-03").unwrap(), options)
+            .await
+            .unwrap();
+
+        let kinds: Vec<_> = invoice.lines.iter().map(|l| l.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                LineKind::Feature,
+                LineKind::Generations,
+                LineKind::Llm,
+                LineKind::Image
+            ]
+        );
+        assert_eq!(invoice.lines[0].description, "Feature: Checkout");
+        assert_eq!(invoice.lines[0].quantity, 2);
+        assert_eq!(invoice.lines[0].amount, 3.6);
+        assert_eq!(invoice.subtotal, 4.0);
+        assert_eq!(invoice.markup, 0.8);
+        assert_eq!(invoice.total, 4.8);
+
+        let mut out = Vec::new();
+        write_csv(&invoice, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        assert!(csv.starts_with("item,feature_id,quantity,tokens,cost_usd"));
+        assert!(csv.contains("Feature: Checkout,f1,2,2000,3.000000,20,USD,3.60"));
+        assert!(csv.trim_end().ends_with("Total,,,,,,USD,4.80"));
+    }
+}
@@ -0,0 +1,776 @@
+//! Scheduled generation jobs
+//!
+//! Generations, document builds, context consolidation and knowledge aging
+//! can be queued to run later (`demiarch jobs enqueue generate "..." --at
+//! 02:00`). Jobs are stored in SQLite and picked up by a worker (`demiarch jobs run`), which claims due
+//! jobs one at a time. A failed job is retried with exponential backoff until
+//! it runs out of attempts.
+
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::commands::approval::ApprovalGates;
+use crate::commands::document;
+use crate::commands::generate;
+use crate::commands::generation::{self, Generation};
+use crate::commands::graph;
+use crate::config::{watch, Config};
+use crate::cost::CostTracker;
+use crate::domain::knowledge::DecayModel;
+use crate::domain::memory::{PersistentMemoryStore, DEFAULT_CONSOLIDATION_THRESHOLD};
+use crate::progress::Progress;
+use crate::storage::Database;
+use crate::{Error, ErrorCategory, Result};
+
+/// Default number of attempts before a job is marked failed
+pub const DEFAULT_MAX_ATTEMPTS: i64 = 3;
+
+/// Delay before the first retry; doubles with each further attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(60);
+
+/// Longest delay between retries
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Work a job performs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobSpec {
+    /// Generate code from a description and write the accepted files
+    Generate {
+        description: String,
+        /// Directory the files are written to
+        output_dir: String,
+        project_id: Option<String>,
+        framework: Option<String>,
+    },
+    /// Generate a PRD for a project
+    Prd {
+        project_id: String,
+        language: Option<String>,
+    },
+    /// Generate an architecture document for a project
+    Architecture {
+        project_id: String,
+        language: Option<String>,
+    },
+    /// Merge near-duplicate context memories for a project
+    ConsolidateContext { project_id: String },
+    /// Decay and archive stale knowledge graph entries and skill confidence
+    AgeKnowledge {
+        /// Hours until the job queues itself again; 0 runs it once
+        every_hours: u32,
+    },
+}
+
+impl JobSpec {
+    /// Short kind name stored alongside the payload
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Generate { .. } => "generate",
+            Self::Prd { .. } => "prd",
+            Self::Architecture { .. } => "architecture",
+            Self::ConsolidateContext { .. } => "consolidate_context",
+            Self::AgeKnowledge { .. } => "age_knowledge",
+        }
+    }
+
+    /// One-line summary for listings
+    pub fn summary(&self) -> String {
+        match self {
+            Self::Generate { description, .. } => description.clone(),
+            Self::Prd { project_id, .. } => format!("PRD for project {}", project_id),
+            Self::Architecture { project_id, .. } => {
+                format!("Architecture document for project {}", project_id)
+            }
+            Self::ConsolidateContext { project_id } => {
+                format!("Context consolidation for project {}", project_id)
+            }
+            Self::AgeKnowledge { every_hours: 0 } => "Knowledge aging".to_string(),
+            Self::AgeKnowledge { every_hours } => {
+                format!("Knowledge aging every {}h", every_hours)
+            }
+        }
+    }
+}
+
+/// Status of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting for its run time (or for a retry)
+    #[default]
+    Queued,
+    /// Claimed by a worker
+    Running,
+    /// Finished successfully
+    Succeeded,
+    /// Failed on its last attempt
+    Failed,
+    /// Cancelled before it ran
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Convert to string for database storage
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    /// Parse from database string
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "queued" => Some(Self::Queued),
+            "running" => Some(Self::Running),
+            "succeeded" => Some(Self::Succeeded),
+            "failed" => Some(Self::Failed),
+            "cancelled" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A queued unit of work
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    /// Unique job identifier
+    pub id: String,
+    /// What the job does
+    pub spec: JobSpec,
+    /// Current status
+    pub status: JobStatus,
+    /// Earliest time the job may run
+    pub run_at: DateTime<Utc>,
+    /// Attempts made so far
+    pub attempts: i64,
+    /// Attempts allowed before the job is marked failed
+    pub max_attempts: i64,
+    /// Error from the most recent failed attempt
+    pub last_error: Option<String>,
+    /// ID of what the job produced (generation or document)
+    pub result_ref: Option<String>,
+    /// When the job was enqueued
+    pub created_at: DateTime<Utc>,
+    /// When the most recent attempt started
+    pub started_at: Option<DateTime<Utc>>,
+    /// When the job reached a final status
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl Job {
+    /// Create a job that may run immediately
+    pub fn new(spec: JobSpec) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            spec,
+            status: JobStatus::Queued,
+            run_at: now,
+            attempts: 0,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            last_error: None,
+            result_ref: None,
+            created_at: now,
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    /// Set the earliest run time
+    pub fn with_run_at(mut self, run_at: DateTime<Utc>) -> Self {
+        self.run_at = run_at;
+        self
+    }
+
+    /// Set the number of attempts allowed
+    pub fn with_max_attempts(mut self, max_attempts: i64) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+}
+
+/// Job repository for database operations
+pub struct JobRepository<'a> {
+    db: &'a Database,
+}
+
+impl<'a> JobRepository<'a> {
+    /// Create a new job repository
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    /// Insert a job
+    pub async fn create(&self, job: &Job) -> Result<()> {
+        let payload = serde_json::to_string(&job.spec)
+            .map_err(|e| Error::Parse(format!("Failed to serialize job: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO jobs (id, kind, payload, status, run_at, attempts, max_attempts, last_error, result_ref, created_at, started_at, finished_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&job.id)
+        .bind(job.spec.kind())
+        .bind(payload)
+        .bind(job.status.as_str())
+        .bind(job.run_at)
+        .bind(job.attempts)
+        .bind(job.max_attempts)
+        .bind(&job.last_error)
+        .bind(&job.result_ref)
+        .bind(job.created_at)
+        .bind(job.started_at)
+        .bind(job.finished_at)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get a job by ID
+    pub async fn get(&self, id: &str) -> Result<Option<Job>> {
+        let row = sqlx::query("SELECT * FROM jobs WHERE id = ?")
+            .bind(id)
+            .fetch_optional(self.db.pool())
+            .await?;
+
+        row.map(|r| self.row_to_job(r)).transpose()
+    }
+
+    /// List jobs, newest first, optionally filtered by status
+    pub async fn list(&self, status: Option<JobStatus>, limit: Option<i64>) -> Result<Vec<Job>> {
+        let limit = limit.unwrap_or(50);
+        let rows = match status {
+            Some(status) => sqlx::query(
+                "SELECT * FROM jobs WHERE status = ? ORDER BY run_at DESC, created_at DESC LIMIT ?",
+            )
+            .bind(status.as_str())
+            .bind(limit)
+            .fetch_all(self.db.pool())
+            .await?,
+            None => {
+                sqlx::query("SELECT * FROM jobs ORDER BY run_at DESC, created_at DESC LIMIT ?")
+                    .bind(limit)
+                    .fetch_all(self.db.pool())
+                    .await?
+            }
+        };
+
+        rows.into_iter().map(|r| self.row_to_job(r)).collect()
+    }
+
+    /// Claim the next due job, marking it running
+    ///
+    /// The status check in the update makes the claim safe when several
+    /// workers poll the same database.
+    pub async fn claim_next(&self, now: DateTime<Utc>) -> Result<Option<Job>> {
+        loop {
+            let candidate: Option<(String,)> = sqlx::query_as(
+                "SELECT id FROM jobs WHERE status = 'queued' AND run_at <= ? ORDER BY run_at, created_at LIMIT 1",
+            )
+            .bind(now)
+            .fetch_optional(self.db.pool())
+            .await?;
+
+            let Some((id,)) = candidate else {
+                return Ok(None);
+            };
+
+            let claimed = sqlx::query(
+                "UPDATE jobs SET status = 'running', attempts = attempts + 1, started_at = ? WHERE id = ? AND status = 'queued'",
+            )
+            .bind(now)
+            .bind(&id)
+            .execute(self.db.pool())
+            .await?
+            .rows_affected();
+
+            if claimed == 1 {
+                return self.get(&id).await;
+            }
+        }
+    }
+
+    /// Mark a running job succeeded
+    pub async fn succeed(&self, id: &str, result_ref: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE jobs SET status = 'succeeded', result_ref = ?, last_error = NULL, finished_at = ? WHERE id = ?",
+        )
+        .bind(result_ref)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a failed attempt, re-queueing the job at `retry_at` if given
+    pub async fn fail(&self, id: &str, error: &str, retry_at: Option<DateTime<Utc>>) -> Result<()> {
+        match retry_at {
+            Some(retry_at) => {
+                sqlx::query(
+                    "UPDATE jobs SET status = 'queued', last_error = ?, run_at = ? WHERE id = ?",
+                )
+                .bind(error)
+                .bind(retry_at)
+                .bind(id)
+                .execute(self.db.pool())
+                .await?;
+            }
+            None => {
+                sqlx::query(
+                    "UPDATE jobs SET status = 'failed', last_error = ?, finished_at = ? WHERE id = ?",
+                )
+                .bind(error)
+                .bind(Utc::now())
+                .bind(id)
+                .execute(self.db.pool())
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Cancel a queued job; returns false if it is not queued
+    pub async fn cancel(&self, id: &str) -> Result<bool> {
+        let cancelled = sqlx::query(
+            "UPDATE jobs SET status = 'cancelled', finished_at = ? WHERE id = ? AND status = 'queued'",
+        )
+        .bind(Utc::now())
+        .bind(id)
+        .execute(self.db.pool())
+        .await?
+        .rows_affected();
+
+        Ok(cancelled == 1)
+    }
+
+    /// Re-queue jobs left running by a worker that exited mid-job
+    pub async fn requeue_interrupted(&self) -> Result<u64> {
+        let requeued = sqlx::query("UPDATE jobs SET status = 'queued' WHERE status = 'running'")
+            .execute(self.db.pool())
+            .await?
+            .rows_affected();
+
+        Ok(requeued)
+    }
+
+    /// Convert a database row to a Job
+    fn row_to_job(&self, row: sqlx::sqlite::SqliteRow) -> Result<Job> {
+        let payload: String = row.get("payload");
+        let spec = serde_json::from_str(&payload)
+            .map_err(|e| Error::Parse(format!("Invalid job payload: {}", e)))?;
+
+        Ok(Job {
+            id: row.get("id"),
+            spec,
+            status: JobStatus::parse(row.get("status")).unwrap_or_default(),
+            run_at: row.get("run_at"),
+            attempts: row.get("attempts"),
+            max_attempts: row.get("max_attempts"),
+            last_error: row.get("last_error"),
+            result_ref: row.get("result_ref"),
+            created_at: row.get("created_at"),
+            started_at: row.get("started_at"),
+            finished_at: row.get("finished_at"),
+        })
+    }
+}
+
+/// Enqueue a job
+pub async fn enqueue(db: &Database, job: Job) -> Result<Job> {
+    JobRepository::new(db).create(&job).await?;
+    Ok(job)
+}
+
+/// Cancel a queued job
+pub async fn cancel(db: &Database, id: &str) -> Result<Job> {
+    let repo = JobRepository::new(db);
+    let job = repo
+        .get(id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Job not found: {}", id)))?;
+
+    if !repo.cancel(id).await? {
+        return Err(Error::InvalidInput(format!(
+            "Job {} is {} and can no longer be cancelled",
+            id, job.status
+        )));
+    }
+
+    repo.get(id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Job not found: {}", id)))
+}
+
+/// Delay before retrying after the given attempt
+pub fn retry_delay(attempt: i64) -> Duration {
+    let exponent = attempt.clamp(1, 16) as u32 - 1;
+    RETRY_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(exponent))
+        .min(RETRY_MAX_DELAY)
+}
+
+/// Claim and run the next due job, if any
+///
+/// Returns the job with its updated status. Errors from `execute` are
+/// recorded on the job (and retried if attempts remain) rather than returned.
+/// Jobs stopped by a person, such as a rejected approval, are not retried.
+pub async fn process_next<F, Fut>(db: &Database, execute: F) -> Result<Option<Job>>
+where
+    F: FnOnce(Job) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let repo = JobRepository::new(db);
+    let Some(job) = repo.claim_next(Utc::now()).await? else {
+        return Ok(None);
+    };
+    let id = job.id.clone();
+    let (attempts, max_attempts) = (job.attempts, job.max_attempts);
+
+    match execute(job).await {
+        Ok(result_ref) => repo.succeed(&id, &result_ref).await?,
+        Err(e) => {
+            tracing::warn!(job_id = %id, attempt = attempts, error = %e, "Job failed");
+            let retry = attempts < max_attempts && e.category() != ErrorCategory::Cancelled;
+            let retry_at = retry.then(|| {
+                Utc::now()
+                    + chrono::Duration::from_std(retry_delay(attempts))
+                        .unwrap_or_else(|_| chrono::Duration::minutes(1))
+            });
+            repo.fail(&id, &e.to_string(), retry_at).await?;
+        }
+    }
+
+    repo.get(&id).await
+}
+
+/// Run a job's work and return the ID of what it produced
+///
+/// Generation jobs write their accepted files to the job's output directory
+/// and return the generation ID; document jobs return the document ID.
+/// Required approval gates fail the job, since there is no one to ask; see
+/// [`execute_with_approvals`].
+pub async fn execute(db: &Database, job: Job) -> Result<String> {
+    let config = Config::load().map_err(|e| Error::ConfigError(e.to_string()))?;
+    execute_with_approvals(db, job, &ApprovalGates::new(&config.approvals)).await
+}
+
+/// Run a job's work, stopping at `gates` for a person to confirm
+pub async fn execute_with_approvals(
+    db: &Database,
+    job: Job,
+    gates: &ApprovalGates,
+) -> Result<String> {
+    match job.spec {
+        JobSpec::Generate {
+            description,
+            output_dir,
+            project_id,
+            framework,
+        } => {
+            let mut new_generation = Generation::new(&description, &output_dir);
+            if let Some(project_id) = &project_id {
+                new_generation = new_generation.with_project(project_id);
+            }
+            let planner = generation::planner_client(&watch::current(), job_cost_tracker())?;
+            let planned = generation::plan_generation(&description, planner.as_ref()).await;
+            gates.after_planning(&description, &planned.plan).await?;
+            let new_generation = new_generation.with_planning(&planned);
+            let run = generation::start(db, new_generation, planned.plan, |task| {
+                let framework = framework.clone();
+                async move {
+                    generate::generate_for_framework(&task.description, framework.as_deref(), true)
+                        .await
+                }
+            })
+            .await?;
+
+            if let Some(failure) = run.failures.first() {
+                return Err(Error::Other(format!(
+                    "Generation {} failed: {}",
+                    run.generation.id, failure.error
+                )));
+            }
+            gates.after_review(&description, &run.result).await?;
+            for file in run
+                .result
+                .files
+                .iter()
+                .filter(|f| !f.has_syntax_errors() && !f.is_blocked())
+            {
+                generation::apply(db, &run.generation.id, Some(&file.path.to_string_lossy()))
+                    .await?;
+            }
+            Ok(run.generation.id)
+        }
+        JobSpec::Prd {
+            project_id,
+            language,
+        } => {
+            let doc = document::generate_prd(
+                db,
+                &project_id,
+                Some(job_cost_tracker()),
+                language.as_deref(),
+                &Progress::hidden(),
+            )
+            .await?;
+            Ok(doc.id)
+        }
+        JobSpec::Architecture {
+            project_id,
+            language,
+        } => {
+            let doc = document::generate_architecture(
+                db,
+                &project_id,
+                Some(job_cost_tracker()),
+                language.as_deref(),
+                &Progress::hidden(),
+            )
+            .await?;
+            Ok(doc.id)
+        }
+        JobSpec::ConsolidateContext { project_id } => {
+            let report = PersistentMemoryStore::new(db.pool().clone())
+                .consolidate(Some(&project_id), DEFAULT_CONSOLIDATION_THRESHOLD, false)
+                .await
+                .map_err(|e| Error::Other(e.to_string()))?;
+            Ok(format!(
+                "{} records merged, {} tokens saved",
+                report.records_removed(),
+                report.tokens_saved()
+            ))
+        }
+        JobSpec::AgeKnowledge { every_hours } => {
+            let config = Config::load().map_err(|e| Error::ConfigError(e.to_string()))?;
+            let report = graph::age_knowledge(
+                db.pool(),
+                DecayModel::new(config.knowledge.half_life_days),
+                config.knowledge.archive_below,
+                false,
+            )
+            .await?;
+
+            // Periodic maintenance: queue the next pass before reporting this one
+            if every_hours > 0 {
+                let next = Job::new(JobSpec::AgeKnowledge { every_hours })
+                    .with_run_at(Utc::now() + chrono::Duration::hours(every_hours.into()))
+                    .with_max_attempts(job.max_attempts);
+                enqueue(db, next).await?;
+            }
+
+            Ok(format!(
+                "{} entities and {} relationships archived, {} skills downgraded",
+                report.entities_archived.len(),
+                report.relationships_archived,
+                report.skills_downgraded.len()
+            ))
+        }
+    }
+}
+
+/// Cost tracker shared by every job this process runs
+///
+/// It follows budget changes from config reloads, so a long-running worker
+/// enforces the limits in effect rather than those it started with.
+fn job_cost_tracker() -> Arc<CostTracker> {
+    static TRACKER: OnceLock<Arc<CostTracker>> = OnceLock::new();
+    let tracker = TRACKER.get_or_init(|| {
+        let tracker = Arc::new(CostTracker::from_config(&watch::current().cost));
+        tracker.follow_config();
+        tracker
+    });
+    Arc::clone(tracker)
+}
+
+/// Parse a run time for `--at`
+///
+/// Accepts `now`, a relative offset (`+30m`, `+2h`, `+1d`), a local time of
+/// day (`02:00`, the next occurrence), a local date and time
+/// (`2026-03-01 02:00`) or an RFC 3339 timestamp.
+pub fn parse_run_at(input: &str, now: DateTime<Local>) -> Result<DateTime<Utc>> {
+    let input = input.trim();
+    let invalid = || {
+        Error::InvalidInput(format!(
+            "Invalid time '{}'. Use HH:MM, 'YYYY-MM-DD HH:MM', +30m/+2h/+1d, or RFC 3339.",
+            input
+        ))
+    };
+
+    if input.eq_ignore_ascii_case("now") {
+        return Ok(now.with_timezone(&Utc));
+    }
+
+    if let Some(offset) = input.strip_prefix('+') {
+        let split = offset.len().saturating_sub(1);
+        let (amount, unit) = offset.split_at(split);
+        let amount: i64 = amount.parse().map_err(|_| invalid())?;
+        let delta = match unit {
+            "s" => chrono::Duration::seconds(amount),
+            "m" => chrono::Duration::minutes(amount),
+            "h" => chrono::Duration::hours(amount),
+            "d" => chrono::Duration::days(amount),
+            _ => return Err(invalid()),
+        };
+        return Ok((now + delta).with_timezone(&Utc));
+    }
+
+    if let Ok(at) = DateTime::parse_from_rfc3339(input) {
+        return Ok(at.with_timezone(&Utc));
+    }
+
+    if let Ok(naive) = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M") {
+        return Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|at| at.with_timezone(&Utc))
+            .ok_or_else(invalid);
+    }
+
+    let time = NaiveTime::parse_from_str(input, "%H:%M").map_err(|_| invalid())?;
+    let mut date = now.date_naive();
+    if time <= now.time() {
+        date = date.succ_opt().ok_or_else(invalid)?;
+    }
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|at| at.with_timezone(&Utc))
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Timelike};
+
+    fn prd_job() -> Job {
+        Job::new(JobSpec::Prd {
+            project_id: "p1".to_string(),
+            language: None,
+        })
+    }
+
+    #[test]
+    fn test_parse_run_at() {
+        let now = Local.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+
+        let later = parse_run_at("14:30", now).unwrap().with_timezone(&Local);
+        assert_eq!((later.day(), later.hour(), later.minute()), (1, 14, 30));
+        let tomorrow = parse_run_at("02:00", now).unwrap().with_timezone(&Local);
+        assert_eq!((tomorrow.day(), tomorrow.hour()), (2, 2));
+
+        assert_eq!(
+            parse_run_at("+2h", now).unwrap(),
+            (now + chrono::Duration::hours(2)).with_timezone(&Utc)
+        );
+        assert!(parse_run_at("2026-04-01 09:15", now).is_ok());
+        assert!(parse_run_at("tomorrow-ish", now).is_err());
+        assert!(parse_run_at("+5y", now).is_err());
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        assert_eq!(retry_delay(1), Duration::from_secs(60));
+        assert_eq!(retry_delay(2), Duration::from_secs(120));
+        assert_eq!(retry_delay(20), RETRY_MAX_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_claim_only_due_jobs() {
+        let db = Database::in_memory().await.unwrap();
+        let later = prd_job().with_run_at(Utc::now() + chrono::Duration::hours(1));
+        enqueue(&db, later.clone()).await.unwrap();
+        let due = enqueue(&db, prd_job()).await.unwrap();
+
+        let repo = JobRepository::new(&db);
+        let claimed = repo.claim_next(Utc::now()).await.unwrap().unwrap();
+        assert_eq!(claimed.id, due.id);
+        assert_eq!(claimed.status, JobStatus::Running);
+        assert_eq!(claimed.attempts, 1);
+        assert!(repo.claim_next(Utc::now()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failed_job_retries_then_fails() {
+        let db = Database::in_memory().await.unwrap();
+        let job = enqueue(&db, prd_job().with_max_attempts(2)).await.unwrap();
+        let repo = JobRepository::new(&db);
+
+        let first = process_next(&db, |_| async {
+            Err(Error::LLMError("connection reset".to_string()))
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(first.status, JobStatus::Queued);
+        assert!(first.run_at > Utc::now());
+        assert!(first
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.contains("connection reset")));
+
+        // Make the retry due now
+        let retry = repo.claim_next(first.run_at).await.unwrap().unwrap();
+        assert_eq!(retry.id, job.id);
+        repo.fail(&retry.id, "still down", None).await.unwrap();
+
+        let failed = repo.get(&job.id).await.unwrap().unwrap();
+        assert_eq!(failed.status, JobStatus::Failed);
+        assert_eq!(failed.attempts, 2);
+        assert!(failed.finished_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_process_success_and_cancel() {
+        let db = Database::in_memory().await.unwrap();
+        let done = enqueue(&db, prd_job()).await.unwrap();
+
+        let finished = process_next(&db, |_| async { Ok("doc-1".to_string()) })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(finished.id, done.id);
+        assert_eq!(finished.status, JobStatus::Succeeded);
+        assert_eq!(finished.result_ref.as_deref(), Some("doc-1"));
+        assert!(cancel(&db, &done.id).await.is_err());
+
+        let queued = enqueue(
+            &db,
+            prd_job().with_run_at(Utc::now() + chrono::Duration::hours(1)),
+        )
+        .await
+        .unwrap();
+        let cancelled = cancel(&db, &queued.id).await.unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+
+        let listed = JobRepository::new(&db)
+            .list(Some(JobStatus::Cancelled), None)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+    }
+}
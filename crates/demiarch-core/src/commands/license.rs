@@ -0,0 +1,333 @@
+//! End-user license activation
+//!
+//! A license key is a signed claim about who may use which tier of
+//! Demiarch until when. Keys have the form `<payload>.<signature>`, both
+//! base64url without padding: the payload is JSON [`LicenseClaims`] and the
+//! signature is ed25519 over the payload bytes, made by the license issuer.
+//!
+//! Verification is fully offline against the trusted issuer key (the same
+//! `DEMIARCH_LICENSE_ISSUER_KEY` used for plugin licenses). An activated key
+//! is stored encrypted in the `encrypted_keys` table under the keyring master
+//! key, and re-verified every time it is read, so editing the database cannot
+//! raise the tier. The active tier gates Pro and Enterprise plugins.
+
+use std::fmt;
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::security::{KeyError, KeyRepository, KeyService, MasterKeyRepository};
+use crate::infrastructure::security::{KeyringMasterKeyRepository, SqliteKeyRepository};
+use crate::storage::{ensure_writable, Database};
+use crate::{Error, Result};
+
+/// Environment variable holding the trusted issuer's public key (base64)
+pub const ISSUER_KEY_ENV: &str = "DEMIARCH_LICENSE_ISSUER_KEY";
+
+/// Name the activated key is stored under in the key repository
+const LICENSE_KEY_NAME: &str = "demiarch-license";
+
+/// Licensed feature tier, ordered from least to most capable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LicenseTier {
+    Free,
+    Pro,
+    Enterprise,
+}
+
+impl fmt::Display for LicenseTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Free => "Free",
+            Self::Pro => "Pro",
+            Self::Enterprise => "Enterprise",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The signed content of a license key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LicenseClaims {
+    /// Issuer-assigned license ID
+    pub id: String,
+    /// Person or organisation the license was issued to
+    pub licensee: String,
+    pub tier: LicenseTier,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl LicenseClaims {
+    pub fn is_expired(&self) -> bool {
+        Utc::now() > self.expires_at
+    }
+
+    /// Tier currently granted: expired licenses fall back to Free
+    pub fn effective_tier(&self) -> LicenseTier {
+        if self.is_expired() {
+            LicenseTier::Free
+        } else {
+            self.tier
+        }
+    }
+
+    /// Whole days until expiry (negative once expired)
+    pub fn days_remaining(&self) -> i64 {
+        (self.expires_at - Utc::now()).num_days()
+    }
+}
+
+/// Verify a license key against the issuer key and return its claims
+///
+/// Expiry is not checked here so that `status` can still describe an
+/// expired license; callers decide what an expired license means.
+pub fn verify_key(key: &str, issuer: &VerifyingKey) -> Result<LicenseClaims> {
+    let payload = verify_signed(key, issuer).map_err(Error::InvalidLicense)?;
+    serde_json::from_slice(&payload)
+        .map_err(|e| Error::InvalidLicense(format!("unreadable license payload: {}", e)))
+}
+
+/// Check a `<payload>.<signature>` envelope and return the payload bytes
+///
+/// Shared by license keys and the release manifests `demiarch self update`
+/// trusts; the error is a short reason for the caller's own error variant.
+pub(crate) fn verify_signed(
+    envelope: &str,
+    issuer: &VerifyingKey,
+) -> std::result::Result<Vec<u8>, String> {
+    let (payload_b64, signature_b64) = envelope
+        .trim()
+        .split_once('.')
+        .ok_or_else(|| "malformed signed payload".to_string())?;
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload_b64)
+        .map_err(|_| "malformed signed payload".to_string())?;
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature_b64)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or_else(|| "malformed signature".to_string())?;
+
+    issuer
+        .verify(&payload, &signature)
+        .map_err(|_| "signature does not match the trusted issuer".to_string())?;
+    Ok(payload)
+}
+
+/// Trusted issuer key from `DEMIARCH_LICENSE_ISSUER_KEY`
+pub fn issuer_key_from_env() -> Result<VerifyingKey> {
+    let encoded = std::env::var(ISSUER_KEY_ENV).map_err(|_| {
+        Error::InvalidLicense(format!(
+            "no trusted issuer key configured (set {})",
+            ISSUER_KEY_ENV
+        ))
+    })?;
+    let bytes: [u8; ed25519_dalek::PUBLIC_KEY_LENGTH] = STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| {
+            Error::InvalidLicense("issuer key must be 32 bytes of base64".to_string())
+        })?;
+    VerifyingKey::from_bytes(&bytes)
+        .map_err(|e| Error::InvalidLicense(format!("invalid issuer key: {}", e)))
+}
+
+/// Activates, reads and removes the user's license
+pub struct LicenseManager {
+    keys: KeyService,
+    issuer: Option<VerifyingKey>,
+}
+
+impl LicenseManager {
+    /// License stored in the database's key table, encrypted with the keyring master key
+    pub fn new(db: &Database) -> Self {
+        Self::with_repositories(
+            Box::new(SqliteKeyRepository::new(db.pool().clone())),
+            Box::new(KeyringMasterKeyRepository::new()),
+        )
+    }
+
+    /// License stored in specific key repositories
+    pub fn with_repositories(
+        keys: Box<dyn KeyRepository>,
+        master_keys: Box<dyn MasterKeyRepository>,
+    ) -> Self {
+        Self {
+            keys: KeyService::new(keys, master_keys),
+            issuer: None,
+        }
+    }
+
+    /// Verify against this issuer key instead of `DEMIARCH_LICENSE_ISSUER_KEY`
+    pub fn with_issuer_key(mut self, issuer: VerifyingKey) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
+
+    fn issuer(&self) -> Result<VerifyingKey> {
+        match self.issuer {
+            Some(issuer) => Ok(issuer),
+            None => issuer_key_from_env(),
+        }
+    }
+
+    /// Verify and store a license key, replacing any active one
+    pub async fn activate(&self, key: &str) -> Result<LicenseClaims> {
+        ensure_writable("license activation")?;
+        let claims = verify_key(key, &self.issuer()?)?;
+        if claims.is_expired() {
+            return Err(Error::InvalidLicense(format!(
+                "license {} expired on {}",
+                claims.id,
+                claims.expires_at.format("%Y-%m-%d")
+            )));
+        }
+
+        if self.keys.key_exists(LICENSE_KEY_NAME).await? {
+            self.keys.update_key(LICENSE_KEY_NAME, key.trim()).await?;
+        } else {
+            self.keys
+                .store_key(LICENSE_KEY_NAME, key.trim(), Some("Demiarch license"))
+                .await?;
+        }
+        Ok(claims)
+    }
+
+    /// Claims of the activated license, re-verified, if one is stored
+    pub async fn status(&self) -> Result<Option<LicenseClaims>> {
+        let key = match self.keys.get_key(LICENSE_KEY_NAME).await {
+            Ok(key) => key,
+            Err(KeyError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        verify_key(key.as_str(), &self.issuer()?).map(Some)
+    }
+
+    /// Tier to grant right now; Free when nothing valid is activated
+    pub async fn active_tier(&self) -> LicenseTier {
+        match self.status().await {
+            Ok(Some(claims)) => claims.effective_tier(),
+            Ok(None) => LicenseTier::Free,
+            Err(e) => {
+                tracing::warn!(error = %e, "Ignoring stored license");
+                LicenseTier::Free
+            }
+        }
+    }
+
+    /// Remove the stored license, returning whether one was active
+    pub async fn deactivate(&self) -> Result<bool> {
+        ensure_writable("license deactivation")?;
+        if !self.keys.key_exists(LICENSE_KEY_NAME).await? {
+            return Ok(false);
+        }
+        self.keys.delete_key(LICENSE_KEY_NAME).await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::security::{InMemoryKeyRepository, InMemoryMasterKeyRepository};
+    use chrono::Duration;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn issue(signer: &SigningKey, tier: LicenseTier, expires_in: Duration) -> String {
+        let claims = LicenseClaims {
+            id: "lic-1".to_string(),
+            licensee: "Acme".to_string(),
+            tier,
+            issued_at: Utc::now(),
+            expires_at: Utc::now() + expires_in,
+        };
+        let payload = serde_json::to_vec(&claims).unwrap();
+        let signature = signer.sign(&payload);
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(&payload),
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        )
+    }
+
+    fn manager(signer: &SigningKey) -> LicenseManager {
+        LicenseManager::with_repositories(
+            Box::new(InMemoryKeyRepository::new()),
+            Box::new(InMemoryMasterKeyRepository::new()),
+        )
+        .with_issuer_key(signer.verifying_key())
+    }
+
+    #[tokio::test]
+    async fn test_activate_status_and_deactivate() {
+        let signer = SigningKey::from_bytes(&[7; 32]);
+        let manager = manager(&signer);
+        assert_eq!(manager.active_tier().await, LicenseTier::Free);
+
+        let claims = manager
+            .activate(&issue(&signer, LicenseTier::Pro, Duration::days(30)))
+            .await
+            .unwrap();
+        assert_eq!(claims.licensee, "Acme");
+        assert_eq!(manager.active_tier().await, LicenseTier::Pro);
+
+        // Activating again replaces the stored key
+        manager
+            .activate(&issue(&signer, LicenseTier::Enterprise, Duration::days(30)))
+            .await
+            .unwrap();
+        assert_eq!(manager.active_tier().await, LicenseTier::Enterprise);
+
+        assert!(manager.deactivate().await.unwrap());
+        assert!(manager.status().await.unwrap().is_none());
+        assert!(!manager.deactivate().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_untrusted_tampered_and_expired_keys() {
+        let signer = SigningKey::from_bytes(&[7; 32]);
+        let other = SigningKey::from_bytes(&[9; 32]);
+        let manager = manager(&signer);
+
+        let forged = issue(&other, LicenseTier::Enterprise, Duration::days(30));
+        assert!(matches!(
+            manager.activate(&forged).await,
+            Err(Error::InvalidLicense(_))
+        ));
+
+        let genuine = issue(&signer, LicenseTier::Free, Duration::days(30));
+        let (_, signature) = genuine.split_once('.').unwrap();
+        let upgraded = issue(&other, LicenseTier::Enterprise, Duration::days(30));
+        let (payload, _) = upgraded.split_once('.').unwrap();
+        assert!(manager
+            .activate(&format!("{}.{}", payload, signature))
+            .await
+            .is_err());
+
+        let expired = issue(&signer, LicenseTier::Pro, -Duration::days(1));
+        assert!(manager.activate(&expired).await.is_err());
+        assert!(manager.activate("not-a-license").await.is_err());
+        assert_eq!(manager.active_tier().await, LicenseTier::Free);
+    }
+
+    #[test]
+    fn test_expired_license_grants_free_tier() {
+        let claims = LicenseClaims {
+            id: "lic-1".to_string(),
+            licensee: "Acme".to_string(),
+            tier: LicenseTier::Enterprise,
+            issued_at: Utc::now() - Duration::days(400),
+            expires_at: Utc::now() - Duration::days(35),
+        };
+        assert!(claims.is_expired());
+        assert_eq!(claims.effective_tier(), LicenseTier::Free);
+        assert!(claims.days_remaining() < 0);
+        assert!(LicenseTier::Enterprise > LicenseTier::Pro);
+    }
+}
@@ -0,0 +1,436 @@
+//! Project archival and cleanup policies
+//!
+//! `demiarch projects cleanup` applies the `[lifecycle]` configuration:
+//!
+//! - active projects with no activity for `archive_after_days` are archived
+//! - checkpoints older than `checkpoint_retention_days` are purged, keeping
+//!   each project's newest checkpoint so it can always be restored
+//! - finished jobs and cross-project search logs older than
+//!   `log_retention_days` are purged
+//! - session and knowledge events older than `event_retention_days` are
+//!   purged
+//!
+//! A cleanup is planned first, so `--dry-run` can report exactly what would
+//! happen. Before each project is archived the `before_archive` hooks run,
+//! and before each kind of record is purged the `before_purge` hooks run;
+//! either can veto its step without stopping the rest of the cleanup.
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::Row;
+use tracing::warn;
+
+use crate::commands::project::ProjectRepository;
+use crate::config::LifecycleConfig;
+use crate::hooks::{HookDecision, HookEvent, HooksManager};
+use crate::storage::Database;
+use crate::Result;
+
+/// A project that would be archived for inactivity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveCandidate {
+    pub project_id: String,
+    pub name: String,
+    pub last_activity: DateTime<Utc>,
+    pub idle_days: i64,
+}
+
+/// Kind of record a cleanup purges
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurgeKind {
+    Checkpoints,
+    Jobs,
+    SearchLogs,
+    SessionEvents,
+    KnowledgeEvents,
+}
+
+impl PurgeKind {
+    pub const ALL: [PurgeKind; 5] = [
+        Self::Checkpoints,
+        Self::Jobs,
+        Self::SearchLogs,
+        Self::SessionEvents,
+        Self::KnowledgeEvents,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Checkpoints => "checkpoints",
+            Self::Jobs => "jobs",
+            Self::SearchLogs => "search_logs",
+            Self::SessionEvents => "session_events",
+            Self::KnowledgeEvents => "knowledge_events",
+        }
+    }
+
+    /// Retention period in days from the policy; 0 keeps records forever
+    fn retention_days(&self, policy: &LifecycleConfig) -> u32 {
+        match self {
+            Self::Checkpoints => policy.checkpoint_retention_days,
+            Self::Jobs | Self::SearchLogs => policy.log_retention_days,
+            Self::SessionEvents | Self::KnowledgeEvents => policy.event_retention_days,
+        }
+    }
+
+    /// Table and condition selecting expired rows; binds the cutoff once
+    fn selection(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::Checkpoints => (
+                "checkpoints",
+                "julianday(created_at) < julianday(?)
+                 AND EXISTS (SELECT 1 FROM checkpoints newer
+                             WHERE newer.project_id = checkpoints.project_id
+                               AND newer.created_at > checkpoints.created_at)",
+            ),
+            Self::Jobs => (
+                "jobs",
+                "status IN ('succeeded', 'failed', 'cancelled')
+                 AND julianday(COALESCE(finished_at, created_at)) < julianday(?)",
+            ),
+            Self::SearchLogs => (
+                "cross_project_search_log",
+                "julianday(created_at) < julianday(?)",
+            ),
+            Self::SessionEvents => ("session_events", "julianday(created_at) < julianday(?)"),
+            Self::KnowledgeEvents => ("knowledge_events", "julianday(created_at) < julianday(?)"),
+        }
+    }
+}
+
+/// Records of one kind that would be purged
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PurgeCandidate {
+    pub kind: PurgeKind,
+    pub count: i64,
+    pub older_than: DateTime<Utc>,
+}
+
+/// What a cleanup would do
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CleanupPlan {
+    pub archive: Vec<ArchiveCandidate>,
+    /// Kinds with at least one expired record
+    pub purge: Vec<PurgeCandidate>,
+}
+
+impl CleanupPlan {
+    pub fn is_empty(&self) -> bool {
+        self.archive.is_empty() && self.purge.is_empty()
+    }
+}
+
+/// A step a hook refused
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VetoedStep {
+    /// Project name or purge kind
+    pub target: String,
+    pub command: String,
+    pub reason: String,
+}
+
+/// What a cleanup did
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CleanupOutcome {
+    pub archived: Vec<ArchiveCandidate>,
+    /// Purged kinds with the number of records actually deleted
+    pub purged: Vec<PurgeCandidate>,
+    pub vetoed: Vec<VetoedStep>,
+}
+
+fn sql_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Plan a cleanup without changing anything
+pub async fn plan(
+    db: &Database,
+    policy: &LifecycleConfig,
+    now: DateTime<Utc>,
+) -> Result<CleanupPlan> {
+    let archive = archive_candidates(db, policy, now).await?;
+
+    let mut purge = Vec::new();
+    for kind in PurgeKind::ALL {
+        let days = kind.retention_days(policy);
+        if days == 0 {
+            continue;
+        }
+        let older_than = now - Duration::days(days.into());
+        let (table, condition) = kind.selection();
+        let count: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM {} WHERE {}",
+            table, condition
+        ))
+        .bind(sql_time(older_than))
+        .fetch_one(db.pool())
+        .await?;
+        if count > 0 {
+            purge.push(PurgeCandidate {
+                kind,
+                count,
+                older_than,
+            });
+        }
+    }
+
+    Ok(CleanupPlan { archive, purge })
+}
+
+/// Carry out a planned cleanup, asking hooks before each step
+pub async fn apply(
+    db: &Database,
+    plan: &CleanupPlan,
+    hooks: &HooksManager,
+) -> Result<CleanupOutcome> {
+    let mut outcome = archive(db, &plan.archive, hooks).await?;
+
+    for candidate in &plan.purge {
+        let payload = json!({
+            "event": HookEvent::BeforePurge.as_str(),
+            "kind": candidate.kind.as_str(),
+            "count": candidate.count,
+            "older_than": sql_time(candidate.older_than),
+        });
+        if let Some(vetoed) = ask(
+            hooks,
+            HookEvent::BeforePurge,
+            candidate.kind.as_str(),
+            &payload,
+        )
+        .await
+        {
+            outcome.vetoed.push(vetoed);
+            continue;
+        }
+
+        let (table, condition) = candidate.kind.selection();
+        let deleted = sqlx::query(&format!("DELETE FROM {} WHERE {}", table, condition))
+            .bind(sql_time(candidate.older_than))
+            .execute(db.pool())
+            .await?
+            .rows_affected();
+        outcome.purged.push(PurgeCandidate {
+            count: deleted as i64,
+            ..candidate.clone()
+        });
+    }
+
+    Ok(outcome)
+}
+
+/// Archive inactive projects only, as done before project commands
+///
+/// Does nothing unless `archive_after_days` is set.
+pub async fn auto_archive(
+    db: &Database,
+    policy: &LifecycleConfig,
+    hooks: &HooksManager,
+    now: DateTime<Utc>,
+) -> Result<CleanupOutcome> {
+    let candidates = archive_candidates(db, policy, now).await?;
+    archive(db, &candidates, hooks).await
+}
+
+async fn archive(
+    db: &Database,
+    candidates: &[ArchiveCandidate],
+    hooks: &HooksManager,
+) -> Result<CleanupOutcome> {
+    let repo = ProjectRepository::new(db);
+    let mut outcome = CleanupOutcome::default();
+    for candidate in candidates {
+        let payload = json!({
+            "event": HookEvent::BeforeArchive.as_str(),
+            "project_id": candidate.project_id,
+            "project_name": candidate.name,
+            "last_activity": sql_time(candidate.last_activity),
+            "idle_days": candidate.idle_days,
+        });
+        if let Some(vetoed) = ask(hooks, HookEvent::BeforeArchive, &candidate.name, &payload).await
+        {
+            outcome.vetoed.push(vetoed);
+            continue;
+        }
+        repo.archive(&candidate.project_id).await?;
+        outcome.archived.push(candidate.clone());
+    }
+    Ok(outcome)
+}
+
+/// Fire hooks for a step, returning the veto if the step must be skipped
+///
+/// A hook that cannot run or times out vetoes the step: nothing is
+/// destroyed unless every hook agreed.
+async fn ask(
+    hooks: &HooksManager,
+    event: HookEvent,
+    target: &str,
+    payload: &serde_json::Value,
+) -> Option<VetoedStep> {
+    match hooks.fire(event, payload).await {
+        Ok(HookDecision::Proceed) => None,
+        Ok(HookDecision::Veto { command, reason }) => Some(VetoedStep {
+            target: target.to_string(),
+            command,
+            reason,
+        }),
+        Err(e) => {
+            warn!(event = event.as_str(), target, error = %e, "Lifecycle hook failed");
+            Some(VetoedStep {
+                target: target.to_string(),
+                command: hooks.commands(event).join("; "),
+                reason: e.to_string(),
+            })
+        }
+    }
+}
+
+/// Active projects whose latest activity is older than the archive threshold
+///
+/// Activity is the newest of the project's own update, its features,
+/// generations, chat messages and sessions.
+async fn archive_candidates(
+    db: &Database,
+    policy: &LifecycleConfig,
+    now: DateTime<Utc>,
+) -> Result<Vec<ArchiveCandidate>> {
+    if policy.archive_after_days == 0 {
+        return Ok(Vec::new());
+    }
+    let cutoff = now - Duration::days(policy.archive_after_days.into());
+    let rows = sqlx::query(
+        r#"
+        SELECT id, name, datetime(last_activity) AS last_activity
+        FROM (
+            SELECT p.id, p.name,
+                MAX(
+                    julianday(p.updated_at),
+                    COALESCE((SELECT MAX(julianday(updated_at))
+                              FROM features WHERE project_id = p.id), 0),
+                    COALESCE((SELECT MAX(julianday(updated_at))
+                              FROM generations WHERE project_id = p.id), 0),
+                    COALESCE((SELECT MAX(julianday(m.created_at))
+                              FROM messages m JOIN conversations c ON c.id = m.conversation_id
+                              WHERE c.project_id = p.id), 0),
+                    COALESCE((SELECT MAX(julianday(last_activity))
+                              FROM sessions WHERE current_project_id = p.id), 0)
+                ) AS last_activity
+            FROM projects p
+            WHERE p.status = 'active'
+        )
+        WHERE last_activity < julianday(?)
+        ORDER BY last_activity
+        "#,
+    )
+    .bind(sql_time(cutoff))
+    .fetch_all(db.pool())
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let last_activity: DateTime<Utc> = row.get("last_activity");
+            ArchiveCandidate {
+                project_id: row.get("id"),
+                name: row.get("name"),
+                idle_days: (now - last_activity).num_days(),
+                last_activity,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seed() -> Database {
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query(
+            "INSERT INTO projects (id, name, updated_at) VALUES
+                ('p1', 'Dormant', '2020-01-01 00:00:00'),
+                ('p2', 'Busy', '2020-01-01 00:00:00')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO features (id, project_id, title, updated_at)
+             VALUES ('f1', 'p2', 'Login', CURRENT_TIMESTAMP)",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO checkpoints (id, project_id, description, snapshot_data, size_bytes, signature, created_at) VALUES
+                ('c1', 'p1', 'before login', '{}', 2, X'00', '2020-01-01 00:00:00'),
+                ('c2', 'p1', 'after login', '{}', 2, X'00', '2020-02-01 00:00:00')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        db
+    }
+
+    fn policy() -> LifecycleConfig {
+        LifecycleConfig {
+            archive_after_days: 30,
+            ..LifecycleConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plan_finds_idle_projects_and_keeps_newest_checkpoint() {
+        let db = seed().await;
+        let plan = plan(&db, &policy(), Utc::now()).await.unwrap();
+
+        assert_eq!(plan.archive.len(), 1);
+        assert_eq!(plan.archive[0].project_id, "p1");
+        assert_eq!(
+            plan.purge,
+            vec![PurgeCandidate {
+                kind: PurgeKind::Checkpoints,
+                count: 1,
+                older_than: plan.purge[0].older_than,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_archives_and_purges() {
+        let db = seed().await;
+        let plan = plan(&db, &policy(), Utc::now()).await.unwrap();
+        let outcome = apply(&db, &plan, &HooksManager::new()).await.unwrap();
+
+        assert_eq!(outcome.archived.len(), 1);
+        assert_eq!(outcome.purged[0].count, 1);
+        let status: String = sqlx::query_scalar("SELECT status FROM projects WHERE id = 'p1'")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(status, "archived");
+        let remaining: Vec<String> = sqlx::query_scalar("SELECT id FROM checkpoints")
+            .fetch_all(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(remaining, vec!["c2"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hook_vetoes_archive() {
+        let db = seed().await;
+        let hooks =
+            HooksManager::new().with_hook(HookEvent::BeforeArchive, "echo keep >&2; exit 1");
+        let outcome = auto_archive(&db, &policy(), &hooks, Utc::now())
+            .await
+            .unwrap();
+
+        assert!(outcome.archived.is_empty());
+        assert_eq!(outcome.vetoed[0].target, "Dormant");
+        assert_eq!(outcome.vetoed[0].reason, "keep");
+    }
+}
@@ -2,15 +2,52 @@
 //!
 //! These commands are used by CLI, TUI, and GUI interfaces.
 
+pub mod analytics;
+pub mod approval;
+pub mod blame;
+pub mod changelog;
 pub mod chat;
 pub mod checkpoint;
+pub mod cost_compare;
+pub mod criteria;
+pub mod digest;
 pub mod document;
+pub mod editor;
+pub mod environment;
+pub mod estimate;
+pub mod eval;
 pub mod feature;
+pub mod feature_branch;
+pub mod feature_import;
 pub mod generate;
+pub mod generation;
 pub mod graph;
+pub mod guardrails;
+pub mod health;
 pub mod image;
+pub mod inbox;
+pub mod integrity;
+pub mod invoice;
+pub mod jobs;
+pub mod license;
+pub mod lifecycle;
+pub mod open;
+pub mod persona;
 pub mod phase;
 pub mod planner;
 pub mod project;
+pub mod pull_request;
+pub mod queue;
+pub mod related;
+pub mod report;
+pub mod roadmap;
+pub mod secrets;
 pub mod skills;
+pub mod snippets;
+pub mod spec;
+pub mod staging;
 pub mod sync;
+pub mod update;
+pub mod upgrade_assist;
+pub mod workspace;
+pub mod worktree;
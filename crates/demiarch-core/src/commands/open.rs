@@ -0,0 +1,352 @@
+//! Open records outside the terminal
+//!
+//! `demiarch open project|feature|document|checkpoint <id-or-prefix>` turns
+//! an ID prefix into one record and hands it to whatever shows it best:
+//!
+//! - a project's directory goes to the file manager (or `$EDITOR`)
+//! - a document is exported to a temporary file opened with its default app
+//! - a checkpoint's snapshot is exported as JSON the same way
+//! - a feature, which only lives in the database, opens in the desktop app
+//!   through its `demiarch://` link, or as a Markdown summary without it
+//!
+//! With the desktop app installed, projects can open there as well.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::commands::document;
+use crate::commands::editor;
+use crate::commands::feature::FeatureRepository;
+use crate::commands::project;
+use crate::deeplink::DeepLink;
+use crate::domain::recovery::CheckpointRepository;
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Kind of record to open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenKind {
+    Project,
+    Feature,
+    Document,
+    Checkpoint,
+}
+
+impl OpenKind {
+    /// Name used in messages
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Project => "project",
+            Self::Feature => "feature",
+            Self::Document => "document",
+            Self::Checkpoint => "checkpoint",
+        }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            Self::Project => "projects",
+            Self::Feature => "features",
+            Self::Document => "documents",
+            Self::Checkpoint => "checkpoints",
+        }
+    }
+}
+
+/// Where a record opens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenTarget {
+    /// A directory or file on disk
+    Path(PathBuf),
+    /// A page in the desktop app
+    Link(DeepLink),
+}
+
+impl fmt::Display for OpenTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path(path) => write!(f, "{}", path.display()),
+            Self::Link(link) => write!(f, "{}", link),
+        }
+    }
+}
+
+/// A record resolved from an ID prefix, ready to open
+#[derive(Debug, Clone)]
+pub struct Resolved {
+    pub kind: OpenKind,
+    /// Full record ID
+    pub id: String,
+    /// Name or title shown to the user
+    pub label: String,
+    pub target: OpenTarget,
+}
+
+/// Full ID of the one record of `kind` whose ID starts with `prefix`
+pub async fn resolve_id(db: &Database, kind: OpenKind, prefix: &str) -> Result<String> {
+    let prefix = prefix.trim();
+    if prefix.is_empty()
+        || !prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(Error::InvalidInput(format!(
+            "Invalid {} ID '{}'",
+            kind.as_str(),
+            prefix
+        )));
+    }
+
+    // `_` is a LIKE wildcard, so compare the prefix itself
+    let sql = format!(
+        "SELECT id FROM {} WHERE substr(id, 1, ?) = ? LIMIT 2",
+        kind.table()
+    );
+    let rows = sqlx::query(&sql)
+        .bind(prefix.len() as i64)
+        .bind(prefix)
+        .fetch_all(db.pool())
+        .await?;
+
+    match rows.as_slice() {
+        [row] => Ok(row.get("id")),
+        [] => Err(Error::NotFound(format!(
+            "No {} found matching '{}'",
+            kind.as_str(),
+            prefix
+        ))),
+        _ => Err(Error::InvalidInput(format!(
+            "Ambiguous {} ID '{}'. Use a longer prefix.",
+            kind.as_str(),
+            prefix
+        ))),
+    }
+}
+
+/// Resolve a record and prepare what opens it
+///
+/// Documents, checkpoints and (without the app) features are written to
+/// temporary files here. With `in_app`, projects and features open in the
+/// desktop app instead.
+pub async fn resolve(
+    db: &Database,
+    kind: OpenKind,
+    id_or_prefix: &str,
+    in_app: bool,
+) -> Result<Resolved> {
+    let id = resolve_id(db, kind, id_or_prefix).await?;
+
+    let (label, target) = match kind {
+        OpenKind::Project => {
+            let project = project::get_with_db(db, &id)
+                .await?
+                .ok_or_else(|| Error::ProjectNotFound(id.clone()))?;
+            let target = match project.path.filter(|_| !in_app) {
+                Some(path) => OpenTarget::Path(PathBuf::from(path)),
+                None if in_app || desktop_app_installed() => {
+                    OpenTarget::Link(DeepLink::Project(id.clone()))
+                }
+                None => {
+                    return Err(Error::InvalidInput(format!(
+                        "Project '{}' has no directory on record",
+                        project.name
+                    )))
+                }
+            };
+            (project.name, target)
+        }
+        OpenKind::Feature => {
+            let feature = FeatureRepository::new(db)
+                .get(&id)
+                .await?
+                .ok_or_else(|| Error::FeatureNotFound(id.clone()))?;
+            let target = if in_app || desktop_app_installed() {
+                OpenTarget::Link(DeepLink::Feature(id.clone()))
+            } else {
+                let mut summary = format!(
+                    "# {}\n\nStatus: {}  \nPriority: {}\n",
+                    feature.title,
+                    feature.status.as_str(),
+                    feature.priority
+                );
+                if let Some(description) = &feature.description {
+                    summary.push_str(&format!("\n{}\n", description));
+                }
+                if let Some(criteria) = &feature.acceptance_criteria {
+                    summary.push_str(&format!("\n## Acceptance Criteria\n\n{}\n", criteria));
+                }
+                OpenTarget::Path(write_temp(kind, &id, "md", &summary)?)
+            };
+            (feature.title, target)
+        }
+        OpenKind::Document => {
+            let doc = document::get_document(db, &id)
+                .await?
+                .ok_or_else(|| Error::NotFound(format!("Document not found: {}", id)))?;
+            let extension = if doc.format == "json" { "json" } else { "md" };
+            let path = write_temp(kind, &id, extension, &doc.content)?;
+            (doc.title, OpenTarget::Path(path))
+        }
+        OpenKind::Checkpoint => {
+            let checkpoint_id = Uuid::parse_str(&id)
+                .map_err(|_| Error::InvalidInput(format!("Invalid checkpoint ID: {}", id)))?;
+            let checkpoint = CheckpointRepository::new(db.pool().clone())
+                .get(checkpoint_id)
+                .await?
+                .ok_or_else(|| Error::NotFound(format!("Checkpoint {} not found", id)))?;
+            let snapshot = serde_json::to_string_pretty(&checkpoint.snapshot_data)
+                .map_err(|e| Error::Parse(e.to_string()))?;
+            let path = write_temp(kind, &id, "json", &snapshot)?;
+            (checkpoint.description, OpenTarget::Path(path))
+        }
+    };
+
+    Ok(Resolved {
+        kind,
+        id,
+        label,
+        target,
+    })
+}
+
+/// Open a target with the system's default handler, or a path in the editor
+pub fn launch(target: &OpenTarget, in_editor: bool) -> Result<()> {
+    match target {
+        OpenTarget::Path(path) if in_editor => editor::run_editor(&editor::editor_command(), path),
+        OpenTarget::Path(path) => system_open(path.as_os_str()),
+        OpenTarget::Link(link) => system_open(link.url().as_ref()),
+    }
+}
+
+/// Whether a desktop app has registered the `demiarch://` scheme
+pub fn desktop_app_installed() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        let home = dirs::home_dir().unwrap_or_default();
+        [
+            PathBuf::from("/Applications/Demiarch.app"),
+            home.join("Applications/Demiarch.app"),
+        ]
+        .iter()
+        .any(|app| app.exists())
+    }
+    #[cfg(windows)]
+    {
+        Command::new("reg")
+            .args([
+                "query",
+                &format!("HKCU\\Software\\Classes\\{}", crate::deeplink::SCHEME),
+            ])
+            .output()
+            .is_ok_and(|out| out.status.success())
+    }
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        Command::new("xdg-mime")
+            .args([
+                "query",
+                "default",
+                &format!("x-scheme-handler/{}", crate::deeplink::SCHEME),
+            ])
+            .output()
+            .is_ok_and(|out| out.status.success() && !out.stdout.trim_ascii().is_empty())
+    }
+}
+
+/// Hand a path or URL to the platform opener
+fn system_open(target: &std::ffi::OsStr) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    let status = Command::new("open").arg(target).status();
+    #[cfg(windows)]
+    let status = Command::new("cmd")
+        .args(["/C", "start", ""])
+        .arg(target)
+        .status();
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let status = Command::new("xdg-open").arg(target).status();
+
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(Error::Other(format!(
+            "Could not open {}: opener exited with {}",
+            Path::new(target).display(),
+            status
+        ))),
+        Err(e) => Err(Error::Other(format!(
+            "Could not open {}: {}",
+            Path::new(target).display(),
+            e
+        ))),
+    }
+}
+
+/// Write `content` to a temporary file named after the record
+fn write_temp(kind: OpenKind, id: &str, extension: &str, content: &str) -> Result<PathBuf> {
+    let short = id.get(..8).unwrap_or(id);
+    let path = std::env::temp_dir().join(format!(
+        "demiarch-{}-{}.{}",
+        kind.as_str(),
+        short,
+        extension
+    ));
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::project::{Project, ProjectRepository};
+
+    #[tokio::test]
+    async fn test_resolves_prefixes_and_exports_documents() {
+        let db = Database::in_memory().await.unwrap();
+        let mut project = Project::new("shop", "rust", "");
+        project.path = Some("/work/shop".to_string());
+        ProjectRepository::new(&db).create(&project).await.unwrap();
+
+        let resolved = resolve(&db, OpenKind::Project, &project.id[..6], false)
+            .await
+            .unwrap();
+        assert_eq!(resolved.id, project.id);
+        assert_eq!(
+            resolved.target,
+            OpenTarget::Path(PathBuf::from("/work/shop"))
+        );
+        let in_app = resolve(&db, OpenKind::Project, &project.id, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            in_app.target,
+            OpenTarget::Link(DeepLink::Project(project.id.clone()))
+        );
+
+        let doc =
+            document::Document::new(&project.id, document::DocumentType::Prd, "PRD", "# Shop");
+        document::DocumentRepository::new(&db)
+            .create(&doc)
+            .await
+            .unwrap();
+        let resolved = resolve(&db, OpenKind::Document, &doc.id[..8], false)
+            .await
+            .unwrap();
+        let OpenTarget::Path(path) = &resolved.target else {
+            panic!("documents open as files");
+        };
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "# Shop");
+        let _ = std::fs::remove_file(path);
+
+        assert!(matches!(
+            resolve_id(&db, OpenKind::Feature, "abc").await,
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            resolve_id(&db, OpenKind::Project, "a%").await,
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
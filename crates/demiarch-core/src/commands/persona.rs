@@ -0,0 +1,402 @@
+//! Per-project chat and agent personas
+//!
+//! A persona describes how the assistant should come across for a project:
+//! its tone, the seniority it writes for, the stack it prefers and any
+//! further instructions. Personas are stored per project in the `personas`
+//! table and picked with `/persona <name>` in chat or the `persona.default`
+//! setting; the chosen one is appended to the chat and code generation
+//! system prompts.
+//!
+//! Personas are shared as TOML files with one `[[persona]]` table each, the
+//! same format `demiarch personas export` writes and `import` reads.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::storage::{ensure_writable, Database};
+use crate::{Error, Result};
+
+/// A named persona of a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Persona {
+    pub name: String,
+    /// How replies should sound, e.g. "concise and direct"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tone: Option<String>,
+    /// Experience level to write for, e.g. "senior"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seniority: Option<String>,
+    /// Preferred languages, frameworks and libraries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stack: Vec<String>,
+    /// Anything else the assistant should keep in mind
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
+
+impl Persona {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            tone: None,
+            seniority: None,
+            stack: Vec::new(),
+            instructions: None,
+        }
+    }
+
+    pub fn with_tone(mut self, tone: impl Into<String>) -> Self {
+        self.tone = Some(tone.into());
+        self
+    }
+
+    pub fn with_seniority(mut self, seniority: impl Into<String>) -> Self {
+        self.seniority = Some(seniority.into());
+        self
+    }
+
+    pub fn with_stack(mut self, stack: Vec<String>) -> Self {
+        self.stack = stack;
+        self
+    }
+
+    pub fn with_instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
+    /// System prompt section describing the persona
+    pub fn prompt(&self) -> String {
+        let mut prompt = format!("Persona for this project: {}.", self.name);
+        if let Some(ref tone) = self.tone {
+            prompt.push_str(&format!("\nTone: {}.", tone.trim_end_matches('.')));
+        }
+        if let Some(ref seniority) = self.seniority {
+            prompt.push_str(&format!(
+                "\nWrite for a {} developer.",
+                seniority.trim_end_matches('.')
+            ));
+        }
+        if !self.stack.is_empty() {
+            prompt.push_str(&format!(
+                "\nPrefer this stack unless asked otherwise: {}.",
+                self.stack.join(", ")
+            ));
+        }
+        if let Some(ref instructions) = self.instructions {
+            prompt.push('\n');
+            prompt.push_str(instructions.trim());
+        }
+        prompt
+    }
+}
+
+/// A persona as stored for a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredPersona {
+    pub project_id: String,
+    #[serde(flatten)]
+    pub persona: Persona,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// File format for sharing personas
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersonaFile {
+    #[serde(default, rename = "persona")]
+    personas: Vec<Persona>,
+}
+
+/// What an import did
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub added: Vec<String>,
+    pub replaced: Vec<String>,
+    /// Personas that already existed and were kept
+    pub skipped: Vec<String>,
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(Error::InvalidInput(format!(
+            "Invalid persona name '{}': use letters, digits, '-', '_' or '.'",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Store or replace a persona of a project
+pub async fn save(db: &Database, project_id: &str, persona: &Persona) -> Result<()> {
+    ensure_writable("persona update")?;
+    validate_name(&persona.name)?;
+    let stack = serde_json::to_string(&persona.stack).map_err(|e| Error::Parse(e.to_string()))?;
+    let now = Utc::now();
+    sqlx::query(
+        "INSERT INTO personas (id, project_id, name, tone, seniority, stack, instructions, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(project_id, name) DO UPDATE SET
+             tone = excluded.tone,
+             seniority = excluded.seniority,
+             stack = excluded.stack,
+             instructions = excluded.instructions,
+             updated_at = excluded.updated_at",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(project_id)
+    .bind(&persona.name)
+    .bind(&persona.tone)
+    .bind(&persona.seniority)
+    .bind(stack)
+    .bind(&persona.instructions)
+    .bind(now)
+    .bind(now)
+    .execute(db.pool())
+    .await?;
+    Ok(())
+}
+
+fn row_to_persona(row: sqlx::sqlite::SqliteRow) -> StoredPersona {
+    let stack: String = row.get("stack");
+    StoredPersona {
+        project_id: row.get("project_id"),
+        persona: Persona {
+            name: row.get("name"),
+            tone: row.get("tone"),
+            seniority: row.get("seniority"),
+            stack: serde_json::from_str(&stack).unwrap_or_default(),
+            instructions: row.get("instructions"),
+        },
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Get a persona of a project by name
+pub async fn get(db: &Database, project_id: &str, name: &str) -> Result<Option<StoredPersona>> {
+    let row = sqlx::query(
+        "SELECT project_id, name, tone, seniority, stack, instructions, updated_at
+         FROM personas WHERE project_id = ? AND name = ?",
+    )
+    .bind(project_id)
+    .bind(name)
+    .fetch_optional(db.pool())
+    .await?;
+    Ok(row.map(row_to_persona))
+}
+
+/// List a project's personas by name
+pub async fn list(db: &Database, project_id: &str) -> Result<Vec<StoredPersona>> {
+    let rows = sqlx::query(
+        "SELECT project_id, name, tone, seniority, stack, instructions, updated_at
+         FROM personas WHERE project_id = ? ORDER BY name",
+    )
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?;
+    Ok(rows.into_iter().map(row_to_persona).collect())
+}
+
+/// Remove a persona, returning whether it existed
+pub async fn remove(db: &Database, project_id: &str, name: &str) -> Result<bool> {
+    ensure_writable("persona removal")?;
+    let result = sqlx::query("DELETE FROM personas WHERE project_id = ? AND name = ?")
+        .bind(project_id)
+        .bind(name)
+        .execute(db.pool())
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// The persona to use: `name` if given, else `persona.default` when the
+/// project has a persona by that name
+///
+/// An explicitly named persona that does not exist is an error; a missing
+/// default is not, since the setting applies to every project.
+pub async fn resolve(
+    db: &Database,
+    project_id: &str,
+    name: Option<&str>,
+    config: &Config,
+) -> Result<Option<Persona>> {
+    if let Some(name) = name {
+        return get(db, project_id, name)
+            .await?
+            .map(|p| Some(p.persona))
+            .ok_or_else(|| Error::NotFound(format!("Persona not found: {}", name)));
+    }
+    let default = config.persona.default.trim();
+    if default.is_empty() {
+        return Ok(None);
+    }
+    Ok(get(db, project_id, default).await?.map(|p| p.persona))
+}
+
+/// Append the persona section to a system prompt
+pub fn apply(system_prompt: &str, persona: Option<&Persona>) -> String {
+    match persona {
+        Some(persona) => format!("{}\n\n{}", system_prompt, persona.prompt()),
+        None => system_prompt.to_string(),
+    }
+}
+
+/// Render personas as a shareable TOML file
+pub fn to_toml(personas: &[Persona]) -> Result<String> {
+    let file = PersonaFile {
+        personas: personas.to_vec(),
+    };
+    toml::to_string_pretty(&file)
+        .map_err(|e| Error::Other(format!("Failed to serialize personas: {}", e)))
+}
+
+/// Parse a shared personas file
+pub fn from_toml(text: &str) -> Result<Vec<Persona>> {
+    let file: PersonaFile =
+        toml::from_str(text).map_err(|e| Error::Parse(format!("Invalid personas file: {}", e)))?;
+    for persona in &file.personas {
+        validate_name(&persona.name)?;
+    }
+    Ok(file.personas)
+}
+
+/// Export a project's personas, or only those named, as TOML
+pub async fn export(db: &Database, project_id: &str, names: &[String]) -> Result<String> {
+    let mut personas: Vec<Persona> = list(db, project_id)
+        .await?
+        .into_iter()
+        .map(|p| p.persona)
+        .collect();
+    if !names.is_empty() {
+        if let Some(missing) = names
+            .iter()
+            .find(|n| !personas.iter().any(|p| &p.name == *n))
+        {
+            return Err(Error::NotFound(format!("Persona not found: {}", missing)));
+        }
+        personas.retain(|p| names.contains(&p.name));
+    }
+    to_toml(&personas)
+}
+
+/// Import personas from a TOML file into a project
+///
+/// Existing personas with the same name are kept unless `replace` is set.
+pub async fn import(
+    db: &Database,
+    project_id: &str,
+    text: &str,
+    replace: bool,
+) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    for persona in from_toml(text)? {
+        let exists = get(db, project_id, &persona.name).await?.is_some();
+        if exists && !replace {
+            summary.skipped.push(persona.name);
+            continue;
+        }
+        save(db, project_id, &persona).await?;
+        if exists {
+            summary.replaced.push(persona.name);
+        } else {
+            summary.added.push(persona.name);
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> (Database, String) {
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'Demo')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        (db, "p1".to_string())
+    }
+
+    fn reviewer() -> Persona {
+        Persona::new("reviewer")
+            .with_tone("blunt")
+            .with_seniority("senior")
+            .with_stack(vec!["Rust".into(), "axum".into()])
+            .with_instructions("Point out missing tests.")
+    }
+
+    #[test]
+    fn test_prompt_lists_preferences() {
+        let prompt = reviewer().prompt();
+        assert!(prompt.contains("Tone: blunt."));
+        assert!(prompt.contains("Write for a senior developer."));
+        assert!(prompt.contains("Prefer this stack unless asked otherwise: Rust, axum."));
+        assert!(prompt.ends_with("Point out missing tests."));
+        assert_eq!(apply("Base.", None), "Base.");
+        assert!(apply("Base.", Some(&reviewer())).starts_with("Base.\n\nPersona"));
+    }
+
+    #[tokio::test]
+    async fn test_save_resolve_and_remove() {
+        let (db, project_id) = setup().await;
+        save(&db, &project_id, &reviewer()).await.unwrap();
+        save(&db, &project_id, &reviewer().with_tone("gentle"))
+            .await
+            .unwrap();
+        assert!(save(&db, &project_id, &Persona::new("bad name"))
+            .await
+            .is_err());
+
+        let personas = list(&db, &project_id).await.unwrap();
+        assert_eq!(personas.len(), 1);
+        assert_eq!(personas[0].persona.tone.as_deref(), Some("gentle"));
+
+        let mut config = Config::default();
+        assert_eq!(
+            resolve(&db, &project_id, None, &config).await.unwrap(),
+            None
+        );
+        config.persona.default = "reviewer".into();
+        let persona = resolve(&db, &project_id, None, &config).await.unwrap();
+        assert_eq!(persona.unwrap().name, "reviewer");
+        assert!(resolve(&db, &project_id, Some("missing"), &config)
+            .await
+            .is_err());
+
+        assert!(remove(&db, &project_id, "reviewer").await.unwrap());
+        assert!(!remove(&db, &project_id, "reviewer").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_export_import_roundtrip() {
+        let (db, project_id) = setup().await;
+        save(&db, &project_id, &reviewer()).await.unwrap();
+        let text = export(&db, &project_id, &[]).await.unwrap();
+        assert!(text.contains("[[persona]]"));
+
+        let (other, other_id) = setup().await;
+        save(
+            &other,
+            &other_id,
+            &Persona::new("reviewer").with_tone("gentle"),
+        )
+        .await
+        .unwrap();
+        let extra = format!("{}\n[[persona]]\nname = \"mentor\"\n", text);
+        let summary = import(&other, &other_id, &extra, false).await.unwrap();
+        assert_eq!(summary.added, vec!["mentor"]);
+        assert_eq!(summary.skipped, vec!["reviewer"]);
+
+        let summary = import(&other, &other_id, &text, true).await.unwrap();
+        assert_eq!(summary.replaced, vec!["reviewer"]);
+        let imported = get(&other, &other_id, "reviewer").await.unwrap().unwrap();
+        assert_eq!(imported.persona, reviewer());
+        assert!(export(&other, &other_id, &["nobody".into()]).await.is_err());
+    }
+}
@@ -1,10 +1,11 @@
 //! Phase management commands
 //!
-//! Provides CRUD operations for project phases and phase planning.
+//! Provides CRUD operations for project phases and phase planning, and
+//! progress rollups (completion percentage and burndown) per phase.
 
 use crate::storage::Database;
 use crate::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::Row;
 use uuid::Uuid;
@@ -58,6 +59,8 @@ pub struct Phase {
     pub status: PhaseStatus,
     /// Order index for display ordering
     pub order_index: i32,
+    /// Date the phase is due to be finished
+    pub target_date: Option<NaiveDate>,
     /// When the phase was created
     pub created_at: DateTime<Utc>,
     /// When the phase was last updated
@@ -75,6 +78,7 @@ impl Phase {
             description: None,
             status: PhaseStatus::Pending,
             order_index,
+            target_date: None,
             created_at: now,
             updated_at: now,
         }
@@ -91,6 +95,12 @@ impl Phase {
         self.status = status;
         self
     }
+
+    /// Set the target date
+    pub fn with_target_date(mut self, target_date: NaiveDate) -> Self {
+        self.target_date = Some(target_date);
+        self
+    }
 }
 
 /// Default phases for a project
@@ -116,8 +126,8 @@ impl<'a> PhaseRepository<'a> {
     pub async fn create(&self, phase: &Phase) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO phases (id, project_id, name, description, status, order_index, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO phases (id, project_id, name, description, status, order_index, target_date, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&phase.id)
@@ -126,6 +136,7 @@ impl<'a> PhaseRepository<'a> {
         .bind(&phase.description)
         .bind(phase.status.as_str())
         .bind(phase.order_index)
+        .bind(phase.target_date)
         .bind(phase.created_at)
         .bind(phase.updated_at)
         .execute(self.db.pool())
@@ -137,7 +148,7 @@ impl<'a> PhaseRepository<'a> {
     /// Get a phase by ID
     pub async fn get(&self, id: &str) -> Result<Option<Phase>> {
         let row = sqlx::query(
-            "SELECT id, project_id, name, description, status, order_index, created_at, updated_at FROM phases WHERE id = ?",
+            "SELECT id, project_id, name, description, status, order_index, target_date, created_at, updated_at FROM phases WHERE id = ?",
         )
         .bind(id)
         .fetch_optional(self.db.pool())
@@ -149,7 +160,7 @@ impl<'a> PhaseRepository<'a> {
     /// Get a phase by name for a project
     pub async fn get_by_name(&self, project_id: &str, name: &str) -> Result<Option<Phase>> {
         let row = sqlx::query(
-            "SELECT id, project_id, name, description, status, order_index, created_at, updated_at FROM phases WHERE project_id = ? AND name = ?",
+            "SELECT id, project_id, name, description, status, order_index, target_date, created_at, updated_at FROM phases WHERE project_id = ? AND name = ?",
         )
         .bind(project_id)
         .bind(name)
@@ -162,7 +173,7 @@ impl<'a> PhaseRepository<'a> {
     /// List phases for a project (ordered by order_index)
     pub async fn list_by_project(&self, project_id: &str) -> Result<Vec<Phase>> {
         let rows = sqlx::query(
-            "SELECT id, project_id, name, description, status, order_index, created_at, updated_at FROM phases WHERE project_id = ? ORDER BY order_index ASC",
+            "SELECT id, project_id, name, description, status, order_index, target_date, created_at, updated_at FROM phases WHERE project_id = ? ORDER BY order_index ASC",
         )
         .bind(project_id)
         .fetch_all(self.db.pool())
@@ -176,7 +187,7 @@ impl<'a> PhaseRepository<'a> {
         sqlx::query(
             r#"
             UPDATE phases
-            SET name = ?, description = ?, status = ?, order_index = ?, updated_at = ?
+            SET name = ?, description = ?, status = ?, order_index = ?, target_date = ?, updated_at = ?
             WHERE id = ?
             "#,
         )
@@ -184,6 +195,7 @@ impl<'a> PhaseRepository<'a> {
         .bind(&phase.description)
         .bind(phase.status.as_str())
         .bind(phase.order_index)
+        .bind(phase.target_date)
         .bind(Utc::now())
         .bind(&phase.id)
         .execute(self.db.pool())
@@ -243,6 +255,7 @@ impl<'a> PhaseRepository<'a> {
             description: row.get("description"),
             status: PhaseStatus::parse(row.get("status")).unwrap_or_default(),
             order_index: row.get("order_index"),
+            target_date: row.get("target_date"),
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
         }
@@ -351,6 +364,177 @@ pub struct PhaseWithCount {
     pub feature_count: i64,
 }
 
+/// Find a project's phase by ID or by name (case-insensitive)
+pub async fn resolve_phase(db: &Database, project_id: &str, id_or_name: &str) -> Result<Phase> {
+    let repo = PhaseRepository::new(db);
+
+    if let Some(phase) = repo.get(id_or_name).await? {
+        if phase.project_id == project_id {
+            return Ok(phase);
+        }
+    }
+    if let Some(phase) = repo.get_by_name(project_id, id_or_name).await? {
+        return Ok(phase);
+    }
+
+    repo.list_by_project(project_id)
+        .await?
+        .into_iter()
+        .find(|p| p.name.eq_ignore_ascii_case(id_or_name) || p.id.starts_with(id_or_name))
+        .ok_or_else(|| crate::Error::PhaseNotFound(id_or_name.to_string()))
+}
+
+/// Order index that places a new phase after the project's existing phases
+pub async fn next_order_index(db: &Database, project_id: &str) -> Result<i32> {
+    let row: (Option<i32>,) =
+        sqlx::query_as("SELECT MAX(order_index) FROM phases WHERE project_id = ?")
+            .bind(project_id)
+            .fetch_one(db.pool())
+            .await?;
+
+    Ok(row.0.map_or(0, |max| max + 1))
+}
+
+/// One day of a phase's burndown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BurndownPoint {
+    pub date: NaiveDate,
+    /// Features in the phase that were not done at the end of the day
+    pub remaining: i64,
+    /// Remaining work on a straight line from the start to the target date
+    pub ideal: Option<f64>,
+}
+
+/// Completion rollup for a phase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseProgress {
+    /// The phase
+    #[serde(flatten)]
+    pub phase: Phase,
+    /// Features assigned to the phase
+    pub total: i64,
+    pub done: i64,
+    pub in_progress: i64,
+    /// Share of the phase's features that are done, 0-100
+    pub percent: f64,
+    pub burndown: Vec<BurndownPoint>,
+}
+
+impl PhaseProgress {
+    /// Whether every feature in the phase is done
+    pub fn is_complete(&self) -> bool {
+        self.total > 0 && self.done == self.total
+    }
+}
+
+/// Longest burndown series reported, in days
+const MAX_BURNDOWN_DAYS: i64 = 180;
+
+/// Roll up progress for one phase as of `today`
+pub async fn phase_progress(
+    db: &Database,
+    phase: Phase,
+    today: NaiveDate,
+) -> Result<PhaseProgress> {
+    let rows =
+        sqlx::query("SELECT status, created_at, completed_at FROM features WHERE phase_id = ?")
+            .bind(&phase.id)
+            .fetch_all(db.pool())
+            .await?;
+
+    let mut spans = Vec::with_capacity(rows.len());
+    let (mut done, mut in_progress) = (0, 0);
+    for row in &rows {
+        let status: String = row.get("status");
+        match status.as_str() {
+            "done" => done += 1,
+            "in_progress" | "review" => in_progress += 1,
+            _ => {}
+        }
+        let created_at: DateTime<Utc> = row.get("created_at");
+        let completed_at: Option<DateTime<Utc>> = row.get("completed_at");
+        spans.push((
+            created_at.date_naive(),
+            completed_at.map(|at| at.date_naive()),
+        ));
+    }
+
+    let total = rows.len() as i64;
+    let start = spans
+        .iter()
+        .map(|(created, _)| *created)
+        .chain([phase.created_at.date_naive()])
+        .min()
+        .unwrap_or(today);
+    let burndown = burndown(&spans, start, today, phase.target_date);
+
+    Ok(PhaseProgress {
+        phase,
+        total,
+        done,
+        in_progress,
+        percent: if total == 0 {
+            0.0
+        } else {
+            done as f64 * 100.0 / total as f64
+        },
+        burndown,
+    })
+}
+
+/// Roll up progress for every phase of a project, in phase order
+pub async fn project_progress(
+    db: &Database,
+    project_id: &str,
+    today: NaiveDate,
+) -> Result<Vec<PhaseProgress>> {
+    let phases = PhaseRepository::new(db).list_by_project(project_id).await?;
+
+    let mut result = Vec::with_capacity(phases.len());
+    for phase in phases {
+        result.push(phase_progress(db, phase, today).await?);
+    }
+    Ok(result)
+}
+
+/// Daily remaining-feature counts from `start` to `end`
+///
+/// `spans` holds each feature's creation day and, if done, completion day.
+/// With a target date, `ideal` falls linearly from the features open on the
+/// first day to zero on the target date.
+pub fn burndown(
+    spans: &[(NaiveDate, Option<NaiveDate>)],
+    start: NaiveDate,
+    end: NaiveDate,
+    target_date: Option<NaiveDate>,
+) -> Vec<BurndownPoint> {
+    let start = start.max(end - Duration::days(MAX_BURNDOWN_DAYS - 1));
+    let remaining_on = |day: NaiveDate| {
+        spans
+            .iter()
+            .filter(|(created, completed)| *created <= day && completed.is_none_or(|c| c > day))
+            .count() as i64
+    };
+    let initial = remaining_on(start) as f64;
+
+    start
+        .iter_days()
+        .take_while(|day| *day <= end)
+        .map(|date| BurndownPoint {
+            date,
+            remaining: remaining_on(date),
+            ideal: target_date.map(|target| {
+                let span = (target - start).num_days();
+                if span <= 0 || date >= target {
+                    0.0
+                } else {
+                    initial * (1.0 - (date - start).num_days() as f64 / span as f64)
+                }
+            }),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -468,4 +652,69 @@ mod tests {
         assert!(phase.is_some());
         assert_eq!(phase.unwrap().order_index, 2);
     }
+
+    #[test]
+    fn test_burndown_counts_open_features() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+        let spans = [
+            (day(1), Some(day(2))),
+            (day(1), None),
+            (day(2), Some(day(3))),
+        ];
+
+        let points = burndown(&spans, day(1), day(4), Some(day(5)));
+        let remaining: Vec<i64> = points.iter().map(|p| p.remaining).collect();
+        assert_eq!(remaining, vec![2, 2, 1, 1]);
+        assert_eq!(points[0].ideal, Some(2.0));
+        assert_eq!(points[2].ideal, Some(1.0));
+
+        assert!(burndown(&spans, day(1), day(2), None)
+            .iter()
+            .all(|p| p.ideal.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_phase_progress_and_resolve() {
+        use crate::commands::feature::{Feature, FeatureRepository, FeatureStatus};
+
+        let db = Database::in_memory()
+            .await
+            .expect("Failed to create database");
+        let project = Project::new("test-project", "rust", "");
+        ProjectRepository::new(&db).create(&project).await.unwrap();
+
+        let target = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        let phase = Phase::new(&project.id, "MVP", 0).with_target_date(target);
+        PhaseRepository::new(&db).create(&phase).await.unwrap();
+
+        let features = FeatureRepository::new(&db);
+        for (title, status) in [
+            ("a", FeatureStatus::Done),
+            ("b", FeatureStatus::InProgress),
+            ("c", FeatureStatus::Backlog),
+            ("d", FeatureStatus::Backlog),
+        ] {
+            let feature = Feature::new(&project.id, title).with_phase(&phase.id);
+            features.create(&feature).await.unwrap();
+            features.update_status(&feature.id, status).await.unwrap();
+        }
+
+        let resolved = resolve_phase(&db, &project.id, "mvp").await.unwrap();
+        assert_eq!(resolved.id, phase.id);
+        assert_eq!(resolved.target_date, Some(target));
+        assert!(matches!(
+            resolve_phase(&db, &project.id, "Launch").await,
+            Err(crate::Error::PhaseNotFound(_))
+        ));
+        assert_eq!(next_order_index(&db, &project.id).await.unwrap(), 1);
+
+        let today = Utc::now().date_naive();
+        let progress = project_progress(&db, &project.id, today).await.unwrap();
+        assert_eq!(progress.len(), 1);
+        assert_eq!(progress[0].total, 4);
+        assert_eq!(progress[0].done, 1);
+        assert_eq!(progress[0].in_progress, 1);
+        assert_eq!(progress[0].percent, 25.0);
+        assert_eq!(progress[0].burndown.last().unwrap().remaining, 3);
+    }
 }
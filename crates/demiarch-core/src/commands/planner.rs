@@ -431,6 +431,69 @@ pub async fn extract_features_from_conversation(
         .collect())
 }
 
+/// Build a generation brief covering a phase's unfinished features
+///
+/// Used by `demiarch generate --phase` so a single run implements one
+/// milestone rather than a free-form description.
+pub async fn scope_to_phase(db: &Database, phase: &Phase) -> Result<String> {
+    let features = FeatureRepository::new(db).list_by_phase(&phase.id).await?;
+    phase_brief(phase, &features).ok_or_else(|| {
+        crate::Error::InvalidInput(format!(
+            "Phase '{}' has no unfinished features to generate",
+            phase.name
+        ))
+    })
+}
+
+/// Generation brief for the features in `features` that are not done
+pub fn phase_brief(phase: &Phase, features: &[Feature]) -> Option<String> {
+    let open: Vec<&Feature> = features
+        .iter()
+        .filter(|f| f.status != FeatureStatus::Done)
+        .collect();
+    if open.is_empty() {
+        return None;
+    }
+
+    let mut brief = format!("Implement the \"{}\" phase", phase.name);
+    if let Some(description) = phase.description.as_deref().filter(|d| !d.is_empty()) {
+        brief.push_str(&format!(" ({})", description));
+    }
+    brief.push_str(". Features:\n");
+    for feature in open {
+        brief.push_str(&format!("\n- {}", feature.title));
+        if let Some(description) = feature.description.as_deref().filter(|d| !d.is_empty()) {
+            brief.push_str(&format!(": {}", description));
+        }
+        if let Some(criteria) = feature
+            .acceptance_criteria
+            .as_deref()
+            .filter(|c| !c.is_empty())
+        {
+            brief.push_str(&format!("\n  Acceptance criteria: {}", criteria));
+        }
+    }
+    Some(brief)
+}
+
+/// Generation brief for a single feature
+///
+/// Used by `demiarch generate --feature`, which plans each feature on its own.
+pub fn feature_brief(feature: &Feature) -> String {
+    let mut brief = format!("Implement the \"{}\" feature", feature.title);
+    if let Some(description) = feature.description.as_deref().filter(|d| !d.is_empty()) {
+        brief.push_str(&format!(": {}", description));
+    }
+    if let Some(criteria) = feature
+        .acceptance_criteria
+        .as_deref()
+        .filter(|c| !c.is_empty())
+    {
+        brief.push_str(&format!("\n\nAcceptance criteria: {}", criteria));
+    }
+    brief
+}
+
 // ============================================================================
 // Helper functions for chat integration
 // ============================================================================
@@ -506,6 +569,20 @@ pub fn format_planning_result(result: &PlanningResult) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_phase_brief_skips_done_features() {
+        let phase = Phase::new("proj", "MVP", 0).with_description("First release");
+        let mut done = Feature::new("proj", "Login");
+        done.status = FeatureStatus::Done;
+        let open = Feature::new("proj", "Signup").with_description("Email and password");
+
+        let brief = phase_brief(&phase, &[done.clone(), open]).unwrap();
+        assert!(brief.starts_with("Implement the \"MVP\" phase (First release)"));
+        assert!(brief.contains("- Signup: Email and password"));
+        assert!(!brief.contains("Login"));
+        assert_eq!(phase_brief(&phase, &[done]), None);
+    }
+
     #[test]
     fn test_is_planning_request() {
         assert!(is_planning_request("Can you break down this project?"));
@@ -2,6 +2,7 @@
 //!
 //! Provides CRUD operations for demiarch projects.
 
+use crate::pagination::{Page, PageRequest};
 use crate::storage::Database;
 use crate::Result;
 use chrono::{DateTime, Utc};
@@ -187,6 +188,44 @@ impl<'a> ProjectRepository<'a> {
         Ok(rows.into_iter().map(|r| self.row_to_project(r)).collect())
     }
 
+    /// List one page of projects, with the total matching count
+    pub async fn list_page(
+        &self,
+        status: Option<ProjectStatus>,
+        page: PageRequest,
+    ) -> Result<Page<Project>> {
+        let filter = if status.is_some() {
+            "WHERE status = ?"
+        } else {
+            ""
+        };
+
+        let count_sql = format!("SELECT COUNT(*) FROM projects {}", filter);
+        let rows_sql = format!(
+            "SELECT id, name, framework, repo_url, status, description, path, created_at, updated_at FROM projects {} ORDER BY name LIMIT ? OFFSET ?",
+            filter
+        );
+        let mut count = sqlx::query_as::<_, (i64,)>(&count_sql);
+        let mut rows = sqlx::query(&rows_sql);
+        if let Some(status) = status {
+            count = count.bind(status.as_str());
+            rows = rows.bind(status.as_str());
+        }
+
+        let (total,) = count.fetch_one(self.db.pool()).await?;
+        let rows = rows
+            .bind(page.sql_limit())
+            .bind(page.sql_offset())
+            .fetch_all(self.db.pool())
+            .await?;
+
+        Ok(Page::new(
+            rows.into_iter().map(|r| self.row_to_project(r)).collect(),
+            total as usize,
+            page,
+        ))
+    }
+
     /// Update a project
     pub async fn update(&self, project: &Project) -> Result<()> {
         sqlx::query(
@@ -390,6 +429,16 @@ pub async fn list_with_db(db: &Database, status: Option<ProjectStatus>) -> Resul
     repo.list(status).await
 }
 
+/// List one page of projects from database
+pub async fn list_page_with_db(
+    db: &Database,
+    status: Option<ProjectStatus>,
+    page: PageRequest,
+) -> Result<Page<Project>> {
+    let repo = ProjectRepository::new(db);
+    repo.list_page(status, page).await
+}
+
 /// Get project by ID (legacy API)
 pub async fn get(id: &str) -> Result<Option<String>> {
     // Return the ID if valid UUID format, otherwise None
@@ -0,0 +1,469 @@
+//! Opening pull requests for isolated generations
+//!
+//! `demiarch generate --open-pr` generates on a worktree branch (see
+//! [`crate::commands::worktree`]) and, once verification passes, pushes the
+//! branch to `origin` and opens a GitHub pull request or GitLab merge request
+//! against the branch that was checked out. The body lists the plan, the
+//! generated files, review findings, verification results and cost.
+//!
+//! Forge tokens are stored with `demiarch pr set-token` in the
+//! `encrypted_keys` table under the keyring master key, like the license;
+//! `GITHUB_TOKEN` and `GITLAB_TOKEN` are used when none is stored.
+
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::commands::generate::GenerationResult;
+use crate::commands::worktree::{git, IsolationOutcome};
+use crate::domain::feature_decomposition::ExecutionPlan;
+use crate::domain::security::{
+    KeyError, KeyRepository, KeyService, MasterKeyRepository, SecureString,
+};
+use crate::infrastructure::network;
+use crate::infrastructure::security::{KeyringMasterKeyRepository, SqliteKeyRepository};
+use crate::storage::{ensure_writable, Database};
+use crate::{Error, Result};
+
+/// Remote pushed to and read to find the forge
+pub const REMOTE: &str = "origin";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Code host a pull request is opened on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Forge {
+    GitHub,
+    GitLab,
+}
+
+impl Forge {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "github" => Some(Self::GitHub),
+            "gitlab" => Some(Self::GitLab),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GitHub => "github",
+            Self::GitLab => "gitlab",
+        }
+    }
+
+    /// Name the token is stored under in the key repository
+    fn key_name(&self) -> String {
+        format!("{}_token", self.as_str())
+    }
+
+    /// Environment variable read when no token is stored
+    pub fn token_env(&self) -> &'static str {
+        match self {
+            Self::GitHub => "GITHUB_TOKEN",
+            Self::GitLab => "GITLAB_TOKEN",
+        }
+    }
+}
+
+impl fmt::Display for Forge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A repository on a forge, parsed from a git remote URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteRepo {
+    pub forge: Forge,
+    pub host: String,
+    /// `owner/repo`, or the full group path on GitLab
+    pub path: String,
+}
+
+impl RemoteRepo {
+    /// Parse SSH (`git@host:owner/repo.git`, `ssh://git@host/owner/repo`) and
+    /// HTTPS remote URLs; the forge is recognised from the host name
+    pub fn parse(url: &str) -> Option<Self> {
+        let url = url.trim();
+        let (host, path) = match url.split_once("://") {
+            Some((_, rest)) => rest.split_once('/')?,
+            None => url.split_once(':')?,
+        };
+        let host = host.rsplit('@').next()?;
+        let host = host.split(':').next()?.to_lowercase();
+        let path = path.trim_matches('/').trim_end_matches(".git");
+        if !path.contains('/') {
+            return None;
+        }
+        let forge = if host.contains("gitlab") {
+            Forge::GitLab
+        } else if host.contains("github") {
+            Forge::GitHub
+        } else {
+            return None;
+        };
+        Some(Self {
+            forge,
+            host,
+            path: path.to_string(),
+        })
+    }
+
+    /// The `origin` remote of the repository at `repo_root`
+    pub async fn from_origin(repo_root: &Path) -> Result<Self> {
+        let url = git(repo_root, &["remote", "get-url", REMOTE]).await?;
+        Self::parse(&url).ok_or_else(|| {
+            Error::InvalidInput(format!(
+                "Remote {} ({}) is not a GitHub or GitLab repository",
+                REMOTE,
+                url.trim()
+            ))
+        })
+    }
+
+    fn api_base(&self) -> String {
+        match (self.forge, self.host.as_str()) {
+            (Forge::GitHub, "github.com") => "https://api.github.com".to_string(),
+            (Forge::GitHub, host) => format!("https://{}/api/v3", host),
+            (Forge::GitLab, host) => format!("https://{}/api/v4", host),
+        }
+    }
+}
+
+/// An opened pull or merge request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PullRequest {
+    pub forge: Forge,
+    pub number: u64,
+    pub url: String,
+}
+
+/// Stores, reads and removes forge tokens
+pub struct ForgeTokens {
+    keys: KeyService,
+}
+
+impl ForgeTokens {
+    /// Tokens stored in the database's key table, encrypted with the keyring master key
+    pub fn new(db: &Database) -> Self {
+        Self::with_repositories(
+            Box::new(SqliteKeyRepository::new(db.pool().clone())),
+            Box::new(KeyringMasterKeyRepository::new()),
+        )
+    }
+
+    /// Tokens stored in specific key repositories
+    pub fn with_repositories(
+        keys: Box<dyn KeyRepository>,
+        master_keys: Box<dyn MasterKeyRepository>,
+    ) -> Self {
+        Self {
+            keys: KeyService::new(keys, master_keys),
+        }
+    }
+
+    /// Store a forge's token, replacing any stored one
+    pub async fn set(&self, forge: Forge, token: &str) -> Result<()> {
+        ensure_writable("storing a forge token")?;
+        let name = forge.key_name();
+        if self.keys.key_exists(&name).await? {
+            self.keys.update_key(&name, token.trim()).await?;
+        } else {
+            let description = format!("{} token for pull requests", forge);
+            self.keys
+                .store_key(&name, token.trim(), Some(&description))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// The stored token, falling back to the forge's environment variable
+    pub async fn get(&self, forge: Forge) -> Result<Option<SecureString>> {
+        match self.keys.get_key(&forge.key_name()).await {
+            Ok(token) => return Ok(Some(token)),
+            Err(KeyError::NotFound(_)) => {}
+            Err(e) => return Err(e.into()),
+        }
+        Ok(std::env::var(forge.token_env())
+            .ok()
+            .filter(|t| !t.trim().is_empty())
+            .map(SecureString::new))
+    }
+
+    /// Remove a stored token, returning whether one was stored
+    pub async fn remove(&self, forge: Forge) -> Result<bool> {
+        ensure_writable("removing a forge token")?;
+        let name = forge.key_name();
+        if !self.keys.key_exists(&name).await? {
+            return Ok(false);
+        }
+        self.keys.delete_key(&name).await?;
+        Ok(true)
+    }
+}
+
+/// Pull request title for a generation
+pub fn title(description: &str) -> String {
+    let first_line = description.lines().next().unwrap_or_default().trim();
+    let mut title: String = first_line.chars().take(72).collect();
+    if title.len() < first_line.len() {
+        title.push_str("...");
+    }
+    format!("demiarch: {}", title)
+}
+
+/// Markdown body describing what a generation did
+pub fn body(
+    description: &str,
+    plan: &ExecutionPlan,
+    result: &GenerationResult,
+    outcome: &IsolationOutcome,
+    generation_id: &str,
+) -> String {
+    let mut body = format!("{}\n\n", description.trim());
+
+    body.push_str("## Plan\n\n");
+    for task in &plan.tasks {
+        body.push_str(&format!(
+            "- `{}` ({}): {}\n",
+            task.id, task.agent_type, task.description
+        ));
+    }
+
+    body.push_str("\n## Generated files\n\n");
+    for file in &result.files {
+        let status = if file.has_syntax_errors() || file.is_blocked() {
+            "rejected"
+        } else if file.is_new {
+            "new"
+        } else {
+            "modified"
+        };
+        body.push_str(&format!("- `{}` ({})\n", file.path.display(), status));
+    }
+
+    let findings: Vec<String> = result
+        .rejected_files()
+        .map(|f| format!("`{}`: syntax errors, not written", f.path.display()))
+        .chain(result.security_findings().map(|f| format!("`{}`", f)))
+        .chain(result.lint_findings().map(|f| format!("`{}`", f)))
+        .collect();
+    body.push_str("\n## Review findings\n\n");
+    if findings.is_empty() {
+        body.push_str("None.\n");
+    }
+    for finding in &findings {
+        body.push_str(&format!("- {}\n", finding));
+    }
+
+    if !outcome.checks.is_empty() {
+        body.push_str("\n## Verification\n\n");
+        for check in &outcome.checks {
+            let status = if check.passed { "pass" } else { "fail" };
+            body.push_str(&format!("- {}: `{}`\n", status, check.command));
+        }
+    }
+
+    body.push_str(&format!(
+        "\n## Cost\n\n{} tokens, ${:.4}\n\nGeneration `{}`\n",
+        result.tokens_used, result.cost_usd, generation_id
+    ));
+    body
+}
+
+/// The branch checked out in the repository at `repo_root`
+pub async fn current_branch(repo_root: &Path) -> Result<String> {
+    let branch = git(repo_root, &["rev-parse", "--abbrev-ref", "HEAD"]).await?;
+    let branch = branch.trim();
+    if branch == "HEAD" {
+        return Err(Error::InvalidInput(
+            "HEAD is detached; check out the branch the pull request should target".to_string(),
+        ));
+    }
+    Ok(branch.to_string())
+}
+
+/// Push `branch` to `origin` and open a pull request from it into `base`
+pub async fn open(
+    repo_root: &Path,
+    tokens: &ForgeTokens,
+    branch: &str,
+    base: &str,
+    title: &str,
+    body: &str,
+) -> Result<PullRequest> {
+    network::ensure_online("opening a pull request")?;
+    let remote = RemoteRepo::from_origin(repo_root).await?;
+    let token = tokens.get(remote.forge).await?.ok_or_else(|| {
+        Error::ConfigError(format!(
+            "No {} token. Store one with `demiarch pr set-token {}` or set {}",
+            remote.forge,
+            remote.forge,
+            remote.forge.token_env()
+        ))
+    })?;
+
+    git(repo_root, &["push", "--set-upstream", REMOTE, branch]).await?;
+    let pr = create(&remote, token.as_str(), branch, base, title, body).await?;
+    tracing::info!(forge = %pr.forge, number = pr.number, "Opened pull request");
+    Ok(pr)
+}
+
+async fn create(
+    remote: &RemoteRepo,
+    token: &str,
+    branch: &str,
+    base: &str,
+    title: &str,
+    body: &str,
+) -> Result<PullRequest> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("demiarch/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let request = match remote.forge {
+        Forge::GitHub => client
+            .post(format!("{}/repos/{}/pulls", remote.api_base(), remote.path))
+            .bearer_auth(token)
+            .header("Accept", "application/vnd.github+json")
+            .json(&json!({ "title": title, "head": branch, "base": base, "body": body })),
+        Forge::GitLab => client
+            .post(format!(
+                "{}/projects/{}/merge_requests",
+                remote.api_base(),
+                remote.path.replace('/', "%2F")
+            ))
+            .header("PRIVATE-TOKEN", token)
+            .json(&json!({
+                "title": title,
+                "source_branch": branch,
+                "target_branch": base,
+                "description": body,
+            })),
+    };
+
+    let response = request.send().await?;
+    let status = response.status();
+    let value: serde_json::Value = response.json().await.unwrap_or_default();
+    if !status.is_success() {
+        let message = value["message"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| value["message"].to_string());
+        return Err(Error::Other(format!(
+            "{} rejected the pull request ({}): {}",
+            remote.forge, status, message
+        )));
+    }
+
+    let (number, url) = match remote.forge {
+        Forge::GitHub => (value["number"].as_u64(), value["html_url"].as_str()),
+        Forge::GitLab => (value["iid"].as_u64(), value["web_url"].as_str()),
+    };
+    match (number, url) {
+        (Some(number), Some(url)) => Ok(PullRequest {
+            forge: remote.forge,
+            number,
+            url: url.to_string(),
+        }),
+        _ => Err(Error::Parse(format!(
+            "unexpected {} pull request response",
+            remote.forge
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::generate::GeneratedFile;
+    use crate::commands::worktree::CheckResult;
+    use crate::domain::feature_decomposition::PlanTask;
+    use crate::infrastructure::security::{InMemoryKeyRepository, InMemoryMasterKeyRepository};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_remote_urls() {
+        let ssh = RemoteRepo::parse("git@github.com:strataga/demiarch.git").unwrap();
+        assert_eq!(ssh.forge, Forge::GitHub);
+        assert_eq!(ssh.path, "strataga/demiarch");
+        assert_eq!(ssh.api_base(), "https://api.github.com");
+
+        let https =
+            RemoteRepo::parse("https://token@gitlab.example.com/group/sub/app.git\n").unwrap();
+        assert_eq!(https.forge, Forge::GitLab);
+        assert_eq!(https.host, "gitlab.example.com");
+        assert_eq!(https.path, "group/sub/app");
+        assert_eq!(https.api_base(), "https://gitlab.example.com/api/v4");
+
+        let enterprise = RemoteRepo::parse("ssh://git@github.acme.io:22/team/app").unwrap();
+        assert_eq!(enterprise.api_base(), "https://github.acme.io/api/v3");
+
+        assert!(RemoteRepo::parse("https://bitbucket.org/team/app.git").is_none());
+        assert!(RemoteRepo::parse("/srv/git/app.git").is_none());
+    }
+
+    #[test]
+    fn test_body_lists_plan_files_findings_and_cost() {
+        let mut plan = ExecutionPlan::new("Add login");
+        plan.tasks
+            .push(PlanTask::new("task-1", "coder", "Add login form"));
+        let result = GenerationResult {
+            files_created: 1,
+            files_modified: 0,
+            tokens_used: 1500,
+            cost_usd: 0.03,
+            files: vec![GeneratedFile {
+                path: PathBuf::from("src/login.rs"),
+                content: "pub fn login() {}\n".to_string(),
+                is_new: true,
+                language: Some("rust".to_string()),
+                validation: None,
+                lint_findings: Vec::new(),
+                security_findings: Vec::new(),
+            }],
+        };
+        let outcome = IsolationOutcome {
+            branch: "demiarch/generate-abc".to_string(),
+            committed: true,
+            checks: vec![CheckResult {
+                command: "cargo check".to_string(),
+                passed: true,
+                output: String::new(),
+            }],
+            merged: false,
+            merge_error: None,
+        };
+
+        let body = body("Add login", &plan, &result, &outcome, "gen-1");
+        assert!(body.contains("- `task-1` (coder): Add login form"));
+        assert!(body.contains("- `src/login.rs` (new)"));
+        assert!(body.contains("## Review findings\n\nNone."));
+        assert!(body.contains("- pass: `cargo check`"));
+        assert!(body.contains("1500 tokens, $0.0300"));
+
+        assert_eq!(title("Add login\nwith OAuth"), "demiarch: Add login");
+    }
+
+    #[tokio::test]
+    async fn test_tokens_set_get_and_remove() {
+        let tokens = ForgeTokens::with_repositories(
+            Box::new(InMemoryKeyRepository::new()),
+            Box::new(InMemoryMasterKeyRepository::new()),
+        );
+        tokens.set(Forge::GitLab, "glpat-1").await.unwrap();
+        tokens.set(Forge::GitLab, "glpat-2\n").await.unwrap();
+        assert_eq!(
+            tokens.get(Forge::GitLab).await.unwrap().unwrap().as_str(),
+            "glpat-2"
+        );
+        assert!(tokens.remove(Forge::GitLab).await.unwrap());
+        assert!(!tokens.remove(Forge::GitLab).await.unwrap());
+    }
+}
@@ -0,0 +1,477 @@
+//! Generating several features in one run
+//!
+//! `demiarch generate --feature A --feature B` gives each feature its own
+//! generation, planned from that feature alone, so one feature failing or
+//! being rejected at an approval gate leaves the others untouched. Up to
+//! `concurrency` features run at once with their LLM calls interleaved.
+//!
+//! The run's budget is checked before each feature starts, against what the
+//! features that already finished spent; once it is reached, the remaining
+//! features are skipped rather than started. The daily budget still applies
+//! to every LLM call. Each feature can take a project checkpoint first so its
+//! changes can be rolled back on their own, and the run ends with a
+//! [`QueueReport`] aggregating every feature.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::Instant;
+
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commands::approval::ApprovalGates;
+use crate::commands::checkpoint::create_checkpoint_with_db;
+use crate::commands::feature::{Feature, FeatureRepository};
+use crate::commands::generate::GenerationResult;
+use crate::commands::generation::{self, Generation};
+use crate::commands::planner;
+use crate::domain::feature_decomposition::PlanTask;
+use crate::llm::LlmClient;
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Features generated at once when no concurrency is given
+pub const DEFAULT_CONCURRENCY: usize = 2;
+
+/// How a queue runs its features
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueOptions {
+    /// Most features generating at once
+    pub concurrency: usize,
+    /// Stop starting features once the run has spent this much
+    pub budget_usd: Option<f64>,
+    /// Checkpoint the project before each feature
+    pub checkpoint: bool,
+    /// Record generations without writing files
+    pub dry_run: bool,
+}
+
+impl Default for QueueOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_CONCURRENCY,
+            budget_usd: None,
+            checkpoint: true,
+            dry_run: false,
+        }
+    }
+}
+
+/// Outcome of one queued feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureRunStatus {
+    Completed,
+    Failed,
+    /// Not started because the run's budget was spent
+    Skipped,
+}
+
+impl FeatureRunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+        }
+    }
+}
+
+/// One feature's part of a queue run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureRun {
+    pub feature_id: String,
+    pub title: String,
+    pub status: FeatureRunStatus,
+    pub generation_id: Option<String>,
+    pub checkpoint_id: Option<String>,
+    /// Files generated without syntax errors
+    pub files: Vec<String>,
+    pub tokens_used: u32,
+    pub cost_usd: f64,
+    pub error: Option<String>,
+}
+
+impl FeatureRun {
+    fn new(feature: &Feature, status: FeatureRunStatus) -> Self {
+        Self {
+            feature_id: feature.id.clone(),
+            title: feature.title.clone(),
+            status,
+            generation_id: None,
+            checkpoint_id: None,
+            files: Vec::new(),
+            tokens_used: 0,
+            cost_usd: 0.0,
+            error: None,
+        }
+    }
+}
+
+/// Aggregated result of a queue run, in the order features were queued
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueueReport {
+    pub runs: Vec<FeatureRun>,
+    pub duration_ms: i64,
+}
+
+impl QueueReport {
+    pub fn total_tokens(&self) -> u64 {
+        self.runs.iter().map(|r| r.tokens_used as u64).sum()
+    }
+
+    pub fn total_cost_usd(&self) -> f64 {
+        self.runs.iter().map(|r| r.cost_usd).sum()
+    }
+
+    pub fn count(&self, status: FeatureRunStatus) -> usize {
+        self.runs.iter().filter(|r| r.status == status).count()
+    }
+
+    /// Files generated by more than one feature, with the features' titles
+    ///
+    /// The feature that finished last wrote its version.
+    pub fn overlapping_files(&self) -> BTreeMap<String, Vec<String>> {
+        let mut owners: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for run in &self.runs {
+            for file in &run.files {
+                owners
+                    .entry(file.clone())
+                    .or_default()
+                    .push(run.title.clone());
+            }
+        }
+        owners.retain(|_, titles| titles.len() > 1);
+        owners
+    }
+}
+
+/// Find features of a project by ID, ID prefix or title
+pub async fn resolve_features(
+    db: &Database,
+    project_id: &str,
+    ids_or_titles: &[String],
+) -> Result<Vec<Feature>> {
+    let all = FeatureRepository::new(db)
+        .list_by_project(project_id, None)
+        .await?;
+    let mut features: Vec<Feature> = Vec::new();
+    for wanted in ids_or_titles {
+        let feature = all
+            .iter()
+            .find(|f| &f.id == wanted)
+            .or_else(|| {
+                let prefixed: Vec<&Feature> = all
+                    .iter()
+                    .filter(|f| wanted.len() >= 4 && f.id.starts_with(wanted.as_str()))
+                    .collect();
+                (prefixed.len() == 1).then(|| prefixed[0])
+            })
+            .or_else(|| all.iter().find(|f| f.title.eq_ignore_ascii_case(wanted)))
+            .ok_or_else(|| Error::FeatureNotFound(wanted.clone()))?;
+        if !features.iter().any(|f| f.id == feature.id) {
+            features.push(feature.clone());
+        }
+    }
+    Ok(features)
+}
+
+/// Generate `features` into `output_dir`, each with its own plan
+///
+/// `run_task` generates one plan task's files without writing them, as for
+/// [`generation::start`], and `planner` splits features into tasks, as for
+/// [`generation::plan_generation`]. Failures are recorded on the feature's
+/// run rather than returned; errors are only returned for the queue itself.
+pub async fn run<F, Fut>(
+    db: &Database,
+    output_dir: &str,
+    features: Vec<Feature>,
+    options: &QueueOptions,
+    gates: &ApprovalGates,
+    planner: Option<&LlmClient>,
+    run_task: F,
+) -> Result<QueueReport>
+where
+    F: Fn(PlanTask) -> Fut + Clone,
+    Fut: Future<Output = Result<GenerationResult>>,
+{
+    let started = Instant::now();
+    let concurrency = options.concurrency.max(1);
+    let mut queued = features.into_iter().enumerate();
+    let mut running = FuturesUnordered::new();
+    let mut finished: Vec<(usize, FeatureRun)> = Vec::new();
+    let mut spent = 0.0;
+
+    loop {
+        while running.len() < concurrency {
+            let Some((index, feature)) = queued.next() else {
+                break;
+            };
+            if options.budget_usd.is_some_and(|budget| spent >= budget) {
+                tracing::info!(feature_id = %feature.id, spent, "Queue budget spent; skipping feature");
+                finished.push((index, FeatureRun::new(&feature, FeatureRunStatus::Skipped)));
+                continue;
+            }
+            let run_task = run_task.clone();
+            running.push(async move {
+                (
+                    index,
+                    run_feature(db, output_dir, feature, options, gates, planner, run_task).await,
+                )
+            });
+        }
+
+        let Some((index, run)) = running.next().await else {
+            break;
+        };
+        spent += run.cost_usd;
+        finished.push((index, run));
+    }
+
+    finished.sort_by_key(|(index, _)| *index);
+    Ok(QueueReport {
+        runs: finished.into_iter().map(|(_, run)| run).collect(),
+        duration_ms: started.elapsed().as_millis() as i64,
+    })
+}
+
+/// Plan, generate and apply one feature, recording what happened
+async fn run_feature<F, Fut>(
+    db: &Database,
+    output_dir: &str,
+    feature: Feature,
+    options: &QueueOptions,
+    gates: &ApprovalGates,
+    planner: Option<&LlmClient>,
+    run_task: F,
+) -> FeatureRun
+where
+    F: FnMut(PlanTask) -> Fut,
+    Fut: Future<Output = Result<GenerationResult>>,
+{
+    let mut run = FeatureRun::new(&feature, FeatureRunStatus::Completed);
+    if let Err(e) = generate_feature(
+        db, output_dir, &feature, options, gates, planner, run_task, &mut run,
+    )
+    .await
+    {
+        tracing::warn!(feature_id = %feature.id, error = %e, "Queued feature failed");
+        run.status = FeatureRunStatus::Failed;
+        run.error = Some(e.to_string());
+    }
+    run
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn generate_feature<F, Fut>(
+    db: &Database,
+    output_dir: &str,
+    feature: &Feature,
+    options: &QueueOptions,
+    gates: &ApprovalGates,
+    planner: Option<&LlmClient>,
+    run_task: F,
+    run: &mut FeatureRun,
+) -> Result<()>
+where
+    F: FnMut(PlanTask) -> Fut,
+    Fut: Future<Output = Result<GenerationResult>>,
+{
+    let brief = planner::feature_brief(feature);
+    let planned = generation::plan_generation(&brief, planner).await;
+    gates.after_planning(&brief, &planned.plan).await?;
+
+    if options.checkpoint && !options.dry_run {
+        let project_id = Uuid::parse_str(&feature.project_id)
+            .map_err(|e| Error::InvalidInput(format!("Invalid project ID: {}", e)))?;
+        let checkpoint = create_checkpoint_with_db(
+            db,
+            project_id,
+            format!("Before generating {}", feature.title),
+            Uuid::parse_str(&feature.id).ok(),
+        )
+        .await?;
+        run.checkpoint_id = Some(checkpoint.id.to_string());
+    }
+
+    let record = Generation::new(&brief, output_dir)
+        .with_project(&feature.project_id)
+        .with_feature(&feature.id)
+        .with_planning(&planned);
+    let plan_run = generation::start(db, record, planned.plan, run_task).await?;
+    run.generation_id = Some(plan_run.generation.id.clone());
+    // Planning counts toward the feature's spend
+    run.tokens_used = plan_run.generation.tokens_used as u32;
+    run.cost_usd = plan_run.generation.cost_usd;
+    let accepted: Vec<String> = plan_run
+        .result
+        .files
+        .iter()
+        .filter(|f| !f.has_syntax_errors() && !f.is_blocked())
+        .map(|f| f.path.to_string_lossy().into_owned())
+        .collect();
+    run.files = accepted.clone();
+
+    if let Some(failure) = plan_run.failures.first() {
+        return Err(Error::Other(format!(
+            "Task {} failed: {}",
+            failure.task_id, failure.error
+        )));
+    }
+    if !options.dry_run {
+        gates.after_review(&brief, &plan_run.result).await?;
+        for path in &accepted {
+            generation::apply(db, &plan_run.generation.id, Some(path)).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::generate::GeneratedFile;
+    use crate::commands::project::{Project, ProjectRepository};
+    use crate::config::ApprovalsConfig;
+    use std::path::PathBuf;
+
+    fn result(path: &str) -> GenerationResult {
+        GenerationResult {
+            files_created: 1,
+            files_modified: 0,
+            tokens_used: 100,
+            cost_usd: 1.0,
+            files: vec![GeneratedFile {
+                path: PathBuf::from(path),
+                content: format!("// {}\n", path),
+                is_new: true,
+                language: Some("rust".to_string()),
+                validation: None,
+                lint_findings: Vec::new(),
+                security_findings: Vec::new(),
+            }],
+        }
+    }
+
+    async fn setup(db: &Database, titles: &[&str]) -> Vec<Feature> {
+        let project = Project::new("shop", "rust", "");
+        ProjectRepository::new(db).create(&project).await.unwrap();
+        let repo = FeatureRepository::new(db);
+        let mut features = Vec::new();
+        for title in titles {
+            let feature = Feature::new(&project.id, *title);
+            repo.create(&feature).await.unwrap();
+            features.push(feature);
+        }
+        features
+    }
+
+    #[tokio::test]
+    async fn test_resolve_features_by_id_prefix_or_title() {
+        let db = Database::in_memory().await.unwrap();
+        let features = setup(&db, &["Login", "Cart"]).await;
+        let project_id = features[0].project_id.clone();
+
+        let wanted = vec![
+            "cart".to_string(),
+            features[0].id[..8].to_string(),
+            "Cart".to_string(),
+        ];
+        let resolved = resolve_features(&db, &project_id, &wanted).await.unwrap();
+        let titles: Vec<&str> = resolved.iter().map(|f| f.title.as_str()).collect();
+        assert_eq!(titles, vec!["Cart", "Login"]);
+
+        let missing = resolve_features(&db, &project_id, &["Search".to_string()]).await;
+        assert!(matches!(missing, Err(Error::FeatureNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_run_interleaves_features_and_stops_at_budget() {
+        let db = Database::in_memory().await.unwrap();
+        let features = setup(&db, &["Login", "Cart", "Search"]).await;
+        let dir = tempfile::tempdir().unwrap();
+        let options = QueueOptions {
+            concurrency: 2,
+            budget_usd: Some(1.5),
+            checkpoint: false,
+            dry_run: true,
+        };
+        let gates = ApprovalGates::new(&ApprovalsConfig::default());
+
+        let report = run(
+            &db,
+            &dir.path().to_string_lossy(),
+            features,
+            &options,
+            &gates,
+            None,
+            |task: PlanTask| async move {
+                if task.description.contains("Cart") {
+                    Err(Error::LLMError("overloaded".to_string()))
+                } else {
+                    Ok(result("src/shared.rs"))
+                }
+            },
+        )
+        .await
+        .unwrap();
+
+        let statuses: Vec<FeatureRunStatus> = report.runs.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                FeatureRunStatus::Completed,
+                FeatureRunStatus::Failed,
+                FeatureRunStatus::Completed,
+            ]
+        );
+        assert!(report.runs[0].generation_id.is_some());
+        assert!(report.runs[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("overloaded"));
+        assert_eq!(report.total_cost_usd(), 2.0);
+        assert_eq!(
+            report.overlapping_files()["src/shared.rs"],
+            vec!["Login", "Search"]
+        );
+
+        let options = QueueOptions {
+            concurrency: 1,
+            ..options
+        };
+        let features = setup(&db, &["Profile", "Settings"]).await;
+        let report = run(
+            &db,
+            &dir.path().to_string_lossy(),
+            features,
+            &options,
+            &gates,
+            None,
+            |_task: PlanTask| async move { Ok(result("src/a.rs")) },
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.count(FeatureRunStatus::Completed), 2);
+
+        let options = QueueOptions {
+            budget_usd: Some(1.0),
+            ..options
+        };
+        let features = setup(&db, &["Billing", "Invoices"]).await;
+        let report = run(
+            &db,
+            &dir.path().to_string_lossy(),
+            features,
+            &options,
+            &gates,
+            None,
+            |_task: PlanTask| async move { Ok(result("src/b.rs")) },
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.runs[1].status, FeatureRunStatus::Skipped);
+    }
+}
@@ -0,0 +1,313 @@
+//! Related prior work for a task description
+//!
+//! Before decomposing a task, the planner asks what has been built before
+//! that looks like it: skills the knowledge graph links to the task's
+//! concepts, and features in any project whose title and description share
+//! its key terms ("you built similar auth in project X using pattern Y").
+//! [`RelatedWork::recommendations`] turns the answer into a short block for
+//! the plan context; `demiarch graph related "<description>"` prints it.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::domain::knowledge::{extract_terms, ContextEnricher, DecayModel, EnrichmentConfig};
+use crate::infrastructure::knowledge::SqliteKnowledgeGraphRepository;
+use crate::skills::{LearnedSkill, SkillStore};
+use crate::storage::Database;
+use crate::Result;
+
+/// Most skills and features returned when a caller doesn't say
+pub const DEFAULT_LIMIT: usize = 5;
+
+/// Share of the description's terms a feature must mention to count
+const MIN_FEATURE_SCORE: f32 = 0.3;
+
+/// A learned skill linked to the description through the knowledge graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedSkill {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub pattern_type: String,
+    pub project_id: Option<String>,
+    pub project_name: Option<String>,
+    /// Knowledge graph entities that led to the skill
+    pub via: Vec<String>,
+}
+
+/// An earlier feature whose title or description overlaps the description
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedFeature {
+    pub id: String,
+    pub title: String,
+    pub status: String,
+    pub project_id: String,
+    pub project_name: String,
+    /// Share of the description's terms the feature mentions, 0.0–1.0
+    pub score: f32,
+    /// Names of skills learned while building the feature
+    pub patterns: Vec<String>,
+}
+
+/// Prior work related to a task description
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RelatedWork {
+    /// Knowledge graph concepts the description matched
+    pub concepts: Vec<String>,
+    pub skills: Vec<RelatedSkill>,
+    pub features: Vec<RelatedFeature>,
+}
+
+impl RelatedWork {
+    /// Whether nothing related was found
+    pub fn is_empty(&self) -> bool {
+        self.skills.is_empty() && self.features.is_empty()
+    }
+
+    /// A short Markdown block for a planning prompt, if anything was found
+    pub fn recommendations(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut lines = vec!["## Related Prior Work".to_string(), String::new()];
+        for feature in &self.features {
+            let verb = if feature.status == "done" {
+                "You built"
+            } else {
+                "You are working on"
+            };
+            let mut line = format!(
+                "- {} similar \"{}\" in project {}",
+                verb, feature.title, feature.project_name
+            );
+            if !feature.patterns.is_empty() {
+                line.push_str(&format!(" using pattern {}", feature.patterns.join(", ")));
+            }
+            lines.push(line);
+        }
+        for skill in &self.skills {
+            let mut line = format!("- Skill \"{}\" ({})", skill.name, skill.pattern_type);
+            if let Some(project) = &skill.project_name {
+                line.push_str(&format!(" from project {}", project));
+            }
+            if !skill.description.is_empty() {
+                line.push_str(&format!(": {}", skill.description));
+            }
+            lines.push(line);
+        }
+        lines.push(String::new());
+        lines.push("Reuse these where they fit instead of starting from scratch.".to_string());
+
+        Some(lines.join("\n"))
+    }
+}
+
+/// Find skills and features related to `description`
+///
+/// `exclude_feature_id` leaves out the feature being planned, so it doesn't
+/// recommend itself.
+pub async fn find_related(
+    db: &Database,
+    description: &str,
+    exclude_feature_id: Option<&str>,
+    limit: usize,
+) -> Result<RelatedWork> {
+    let terms: HashSet<String> = extract_terms(description).into_iter().collect();
+    if terms.is_empty() || limit == 0 {
+        return Ok(RelatedWork::default());
+    }
+
+    let project_names = project_names(db).await?;
+    let store = SkillStore::new(db.pool().clone());
+
+    // Skills reached from the concepts the description mentions, skipping
+    // concepts that have faded since they were last reinforced
+    let decay = Config::load()
+        .map(|config| DecayModel::new(config.knowledge.half_life_days))
+        .unwrap_or_default();
+    let repository = Arc::new(SqliteKnowledgeGraphRepository::new(db.pool().clone()));
+    let enriched = ContextEnricher::with_config(repository, EnrichmentConfig::minimal())
+        .with_decay(decay)
+        .enrich_from_query(description)
+        .await?;
+
+    let mut concepts = Vec::new();
+    let mut skill_ids: Vec<String> = Vec::new();
+    let mut via: HashMap<String, Vec<String>> = HashMap::new();
+    for context in &enriched.entities {
+        concepts.push(context.entity.name.clone());
+        for skill_id in &context.related_skills {
+            if !via.contains_key(skill_id) {
+                skill_ids.push(skill_id.clone());
+            }
+            via.entry(skill_id.clone())
+                .or_default()
+                .push(context.entity.name.clone());
+        }
+    }
+
+    let mut skills = Vec::new();
+    for skill_id in skill_ids {
+        if skills.len() >= limit {
+            break;
+        }
+        if let Some(skill) = store.get(&skill_id).await? {
+            skills.push(related_skill(
+                skill,
+                via.remove(&skill_id).unwrap_or_default(),
+                &project_names,
+            ));
+        }
+    }
+
+    // Features in any project sharing the description's terms
+    let rows: Vec<(String, String, Option<String>, String, String)> = sqlx::query_as(
+        "SELECT id, title, description, status, project_id FROM features ORDER BY created_at",
+    )
+    .fetch_all(db.pool())
+    .await?;
+
+    let mut features = Vec::new();
+    for (id, title, feature_description, status, project_id) in rows {
+        if exclude_feature_id == Some(id.as_str()) {
+            continue;
+        }
+        let Some(project_name) = project_names.get(&project_id) else {
+            continue;
+        };
+        let text = format!("{} {}", title, feature_description.unwrap_or_default());
+        let score = term_overlap(&terms, &text);
+        if score >= MIN_FEATURE_SCORE {
+            features.push(RelatedFeature {
+                id,
+                title,
+                status,
+                project_id,
+                project_name: project_name.clone(),
+                score,
+                patterns: Vec::new(),
+            });
+        }
+    }
+    // Best match first; among equals, finished work is the better example
+    features.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| (b.status == "done").cmp(&(a.status == "done")))
+    });
+    features.truncate(limit);
+
+    // Skills learned while building the matched features
+    let project_ids: HashSet<String> = features.iter().map(|f| f.project_id.clone()).collect();
+    let mut patterns: HashMap<String, Vec<String>> = HashMap::new();
+    for project_id in project_ids {
+        for skill in store.list_by_project(&project_id).await? {
+            if let Some(feature_id) = skill.source.feature_id {
+                patterns.entry(feature_id).or_default().push(skill.name);
+            }
+        }
+    }
+    for feature in &mut features {
+        feature.patterns = patterns.remove(&feature.id).unwrap_or_default();
+    }
+
+    Ok(RelatedWork {
+        concepts,
+        skills,
+        features,
+    })
+}
+
+/// Names of projects that haven't been deleted, by id
+async fn project_names(db: &Database) -> Result<HashMap<String, String>> {
+    let rows: Vec<(String, String)> =
+        sqlx::query_as("SELECT id, name FROM projects WHERE status != 'deleted'")
+            .fetch_all(db.pool())
+            .await?;
+    Ok(rows.into_iter().collect())
+}
+
+fn related_skill(
+    skill: LearnedSkill,
+    via: Vec<String>,
+    project_names: &HashMap<String, String>,
+) -> RelatedSkill {
+    let project_name = skill
+        .source
+        .project_id
+        .as_ref()
+        .and_then(|id| project_names.get(id))
+        .cloned();
+    RelatedSkill {
+        id: skill.id,
+        name: skill.name,
+        description: skill.description,
+        pattern_type: skill.pattern.pattern_type.as_str().to_string(),
+        project_id: skill.source.project_id,
+        project_name,
+        via,
+    }
+}
+
+/// Share of `terms` that appear among the terms of `text`
+fn term_overlap(terms: &HashSet<String>, text: &str) -> f32 {
+    let found: HashSet<String> = extract_terms(text).into_iter().collect();
+    terms.intersection(&found).count() as f32 / terms.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::feature;
+    use crate::commands::project::{Project, ProjectRepository};
+
+    #[tokio::test]
+    async fn test_finds_similar_features_across_projects() {
+        let db = Database::in_memory().await.unwrap();
+        let shop = Project::new("shop", "rust", "");
+        let blog = Project::new("blog", "rust", "");
+        ProjectRepository::new(&db).create(&shop).await.unwrap();
+        ProjectRepository::new(&db).create(&blog).await.unwrap();
+
+        let login = feature::create_with_db(
+            &db,
+            &shop.id,
+            "OAuth login",
+            Some("Authentication with GitHub OAuth tokens"),
+            None,
+        )
+        .await
+        .unwrap();
+        feature::create_with_db(&db, &shop.id, "Dark mode", None, None)
+            .await
+            .unwrap();
+        let current = feature::create_with_db(&db, &blog.id, "OAuth authentication", None, None)
+            .await
+            .unwrap();
+
+        let related = find_related(
+            &db,
+            "Add OAuth authentication for admins",
+            Some(&current.id),
+            DEFAULT_LIMIT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(related.features.len(), 1);
+        assert_eq!(related.features[0].id, login.id);
+        assert_eq!(related.features[0].project_name, "shop");
+
+        let block = related.recommendations().unwrap();
+        assert!(block.starts_with("## Related Prior Work"));
+        assert!(block.contains("\"OAuth login\" in project shop"));
+
+        let nothing = find_related(&db, "the and of", None, DEFAULT_LIMIT)
+            .await
+            .unwrap();
+        assert!(nothing.recommendations().is_none());
+    }
+}
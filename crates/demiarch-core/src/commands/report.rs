@@ -0,0 +1,906 @@
+//! Session reports
+//!
+//! `demiarch sessions report <id>` compiles what happened during a session
+//! into one document: the session timeline, features touched, generations
+//! with their files and costs, checkpoints, LLM spend by model, agent
+//! activity and the notes taken along the way. Reports render as Markdown or HTML for standups and billing,
+//! or JSON for tooling.
+//!
+//! Sessions span projects, so records are matched to a session by time:
+//! anything created between the session's start and its last activity
+//! belongs to it.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::agents::events::{read_events_since, AgentEvent, AgentEventType};
+use crate::domain::session::{
+    Session, SessionEvent, SessionEventType, SessionManager, SessionNote,
+};
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Output format for a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    /// Markdown
+    #[default]
+    Markdown,
+    /// Standalone HTML page
+    Html,
+    /// Pretty-printed JSON
+    Json,
+}
+
+impl ReportFormat {
+    /// Parse a format name ("md", "markdown", "html", "json")
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Pick a format from a file extension, defaulting to Markdown
+    pub fn from_path(path: &Path) -> Self {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(Self::parse)
+            .unwrap_or_default()
+    }
+}
+
+/// A feature worked on during the session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureEntry {
+    /// Feature ID
+    pub id: String,
+    /// Feature title, if the feature still exists
+    pub title: Option<String>,
+    /// Current status
+    pub status: Option<String>,
+}
+
+/// A generation run during the session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationEntry {
+    /// Generation ID
+    pub id: String,
+    /// What was generated
+    pub description: String,
+    /// Generation status
+    pub status: String,
+    /// Tokens used
+    pub tokens_used: i64,
+    /// Cost in USD
+    pub cost_usd: f64,
+    /// Files produced
+    pub files: i64,
+    /// Files accepted
+    pub accepted_files: i64,
+    /// When the generation started
+    pub created_at: DateTime<Utc>,
+}
+
+/// A checkpoint created during the session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointEntry {
+    /// Checkpoint ID
+    pub id: String,
+    /// Checkpoint description
+    pub description: String,
+    /// Snapshot size
+    pub size_bytes: i64,
+    /// When the checkpoint was taken
+    pub created_at: DateTime<Utc>,
+}
+
+/// LLM spend for one model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelCost {
+    /// Model ID
+    pub model: String,
+    /// Number of calls
+    pub calls: i64,
+    /// Input tokens
+    pub input_tokens: i64,
+    /// Output tokens
+    pub output_tokens: i64,
+    /// Cost in USD
+    pub cost_usd: f64,
+}
+
+/// Agent activity from the agent event log
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentActivity {
+    /// Events in the session window
+    pub events: usize,
+    /// Agents spawned
+    pub agents_spawned: usize,
+    /// Agents that failed
+    pub agents_failed: usize,
+    /// Files written
+    pub files_written: usize,
+    /// Untrusted content quarantined
+    pub content_quarantined: usize,
+}
+
+impl AgentActivity {
+    /// Summarize agent events that fall within a time window
+    pub fn from_events(events: &[AgentEvent], start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        let mut activity = Self::default();
+        for event in events
+            .iter()
+            .filter(|e| e.timestamp >= start && e.timestamp <= end)
+        {
+            activity.events += 1;
+            match event.event_type {
+                AgentEventType::Spawned => activity.agents_spawned += 1,
+                AgentEventType::Failed => activity.agents_failed += 1,
+                AgentEventType::FileWritten => activity.files_written += 1,
+                AgentEventType::ContentQuarantined => activity.content_quarantined += 1,
+                _ => {}
+            }
+        }
+        activity
+    }
+}
+
+/// Everything that happened during a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionReport {
+    /// The session
+    pub session: Session,
+    /// Start of the reporting window
+    pub started_at: DateTime<Utc>,
+    /// End of the reporting window (last activity, or now for live sessions)
+    pub ended_at: DateTime<Utc>,
+    /// Session events, oldest first
+    pub events: Vec<SessionEvent>,
+    /// Features touched
+    pub features: Vec<FeatureEntry>,
+    /// Generations run
+    pub generations: Vec<GenerationEntry>,
+    /// Checkpoints taken
+    pub checkpoints: Vec<CheckpointEntry>,
+    /// Recorded LLM spend by model
+    pub model_costs: Vec<ModelCost>,
+    /// Agent activity
+    pub agent_activity: AgentActivity,
+    /// Notes taken during the session, oldest first
+    pub notes: Vec<SessionNote>,
+    /// When the report was generated
+    pub generated_at: DateTime<Utc>,
+}
+
+impl SessionReport {
+    /// Compile the report for a session
+    pub async fn build(db: &Database, session_id: Uuid) -> Result<Self> {
+        let manager = SessionManager::new(db.pool().clone());
+        let session = manager
+            .get(session_id)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("Session {} not found", session_id)))?;
+
+        let started_at = session.created_at;
+        let ended_at = if session.status.has_ended() {
+            session.last_activity.max(session.updated_at)
+        } else {
+            Utc::now()
+        };
+
+        let mut events = manager.get_events(session_id, None).await?;
+        events.sort_by_key(|e| e.created_at);
+
+        let agent_events = read_events_since(started_at);
+
+        let generations = load_generations(db, started_at, ended_at).await?;
+        let checkpoints = load_checkpoints(db, started_at, ended_at).await?;
+        let model_costs = load_model_costs(db, started_at, ended_at).await?;
+        let feature_ids = touched_features(&session, &events, db, started_at, ended_at).await?;
+        let features = load_features(db, &feature_ids).await?;
+        let notes = manager.notes().list(session_id).await?;
+
+        Ok(Self {
+            agent_activity: AgentActivity::from_events(&agent_events, started_at, ended_at),
+            session,
+            started_at,
+            ended_at,
+            events,
+            features,
+            generations,
+            checkpoints,
+            model_costs,
+            notes,
+            generated_at: Utc::now(),
+        })
+    }
+
+    /// Session length
+    pub fn duration(&self) -> chrono::Duration {
+        self.ended_at - self.started_at
+    }
+
+    /// Cost of the session's generations
+    pub fn generation_cost_usd(&self) -> f64 {
+        self.generations.iter().map(|g| g.cost_usd).sum()
+    }
+
+    /// Recorded LLM spend, falling back to generation costs when no
+    /// per-call costs were recorded
+    pub fn total_cost_usd(&self) -> f64 {
+        let recorded: f64 = self.model_costs.iter().map(|m| m.cost_usd).sum();
+        if self.model_costs.is_empty() {
+            self.generation_cost_usd()
+        } else {
+            recorded
+        }
+    }
+
+    /// Tokens used by the session's generations
+    pub fn generation_tokens(&self) -> i64 {
+        self.generations.iter().map(|g| g.tokens_used).sum()
+    }
+
+    /// Render in the given format
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Markdown => Ok(self.to_markdown()),
+            ReportFormat::Html => Ok(self.to_html()),
+            ReportFormat::Json => {
+                serde_json::to_string_pretty(self).map_err(|e| Error::Parse(e.to_string()))
+            }
+        }
+    }
+
+    /// Render as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let id = self.session.id.to_string();
+        let short_id = short(&id);
+        let _ = writeln!(out, "# Session report {}", short_id);
+        out.push('\n');
+        if let Some(description) = &self.session.description {
+            let _ = writeln!(out, "{}\n", description);
+        }
+
+        let _ = writeln!(out, "## Summary\n");
+        let _ = writeln!(out, "| | |");
+        let _ = writeln!(out, "|---|---|");
+        for (label, value) in self.summary_rows() {
+            let _ = writeln!(out, "| {} | {} |", label, value);
+        }
+
+        if !self.features.is_empty() {
+            let _ = writeln!(out, "\n## Features\n");
+            for feature in &self.features {
+                let _ = writeln!(
+                    out,
+                    "- {} ({}){}",
+                    feature.title.as_deref().unwrap_or("(deleted feature)"),
+                    short(&feature.id),
+                    feature
+                        .status
+                        .as_ref()
+                        .map(|s| format!(" - {}", s))
+                        .unwrap_or_default()
+                );
+            }
+        }
+
+        if !self.generations.is_empty() {
+            let _ = writeln!(out, "\n## Generations\n");
+            let _ = writeln!(
+                out,
+                "| Time | Description | Status | Files | Tokens | Cost (USD) |"
+            );
+            let _ = writeln!(out, "|---|---|---|---|---|---|");
+            for g in &self.generations {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {}/{} | {} | {:.4} |",
+                    g.created_at.format("%H:%M"),
+                    g.description.replace('|', "\\|"),
+                    g.status,
+                    g.accepted_files,
+                    g.files,
+                    g.tokens_used,
+                    g.cost_usd
+                );
+            }
+        }
+
+        if !self.model_costs.is_empty() {
+            let _ = writeln!(out, "\n## Costs by model\n");
+            let _ = writeln!(
+                out,
+                "| Model | Calls | Input tokens | Output tokens | Cost (USD) |"
+            );
+            let _ = writeln!(out, "|---|---|---|---|---|");
+            for m in &self.model_costs {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {:.4} |",
+                    m.model, m.calls, m.input_tokens, m.output_tokens, m.cost_usd
+                );
+            }
+        }
+
+        if !self.checkpoints.is_empty() {
+            let _ = writeln!(out, "\n## Checkpoints\n");
+            for c in &self.checkpoints {
+                let _ = writeln!(
+                    out,
+                    "- {} {} ({})",
+                    c.created_at.format("%Y-%m-%d %H:%M"),
+                    c.description,
+                    short(&c.id)
+                );
+            }
+        }
+
+        if !self.notes.is_empty() {
+            let _ = writeln!(out, "\n## Notes\n");
+            for note in &self.notes {
+                let _ = writeln!(
+                    out,
+                    "- {} {}{}",
+                    note.created_at.format("%Y-%m-%d %H:%M"),
+                    note.content,
+                    if note.remind { " (reminder)" } else { "" }
+                );
+            }
+        }
+
+        if !self.events.is_empty() {
+            let _ = writeln!(out, "\n## Timeline\n");
+            for event in &self.events {
+                let _ = writeln!(
+                    out,
+                    "- {} {}{}",
+                    event.created_at.format("%Y-%m-%d %H:%M:%S"),
+                    event.event_type,
+                    event_detail(event)
+                        .map(|d| format!(": {}", d))
+                        .unwrap_or_default()
+                );
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "\n_Generated {}_",
+            self.generated_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        out
+    }
+
+    /// Render as a standalone HTML page
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let title = format!("Session report {}", short(&self.session.id.to_string()));
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+             <style>body{{font-family:sans-serif;max-width:960px;margin:2em auto;}}\
+             table{{border-collapse:collapse;}}td,th{{border:1px solid #ccc;padding:4px 8px;text-align:left;}}</style>\n\
+             </head>\n<body>\n<h1>{}</h1>",
+            escape_html(&title),
+            escape_html(&title)
+        );
+        if let Some(description) = &self.session.description {
+            let _ = writeln!(out, "<p>{}</p>", escape_html(description));
+        }
+
+        let _ = writeln!(out, "<h2>Summary</h2>\n<table>");
+        for (label, value) in self.summary_rows() {
+            let _ = writeln!(
+                out,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                escape_html(label),
+                escape_html(&value)
+            );
+        }
+        let _ = writeln!(out, "</table>");
+
+        if !self.features.is_empty() {
+            let _ = writeln!(out, "<h2>Features</h2>\n<ul>");
+            for feature in &self.features {
+                let _ = writeln!(
+                    out,
+                    "<li>{} <code>{}</code> {}</li>",
+                    escape_html(feature.title.as_deref().unwrap_or("(deleted feature)")),
+                    escape_html(short(&feature.id)),
+                    escape_html(feature.status.as_deref().unwrap_or_default())
+                );
+            }
+            let _ = writeln!(out, "</ul>");
+        }
+
+        if !self.generations.is_empty() {
+            let _ = writeln!(
+                out,
+                "<h2>Generations</h2>\n<table>\n<tr><th>Time</th><th>Description</th><th>Status</th>\
+                 <th>Files</th><th>Tokens</th><th>Cost (USD)</th></tr>"
+            );
+            for g in &self.generations {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}/{}</td><td>{}</td><td>{:.4}</td></tr>",
+                    g.created_at.format("%H:%M"),
+                    escape_html(&g.description),
+                    escape_html(&g.status),
+                    g.accepted_files,
+                    g.files,
+                    g.tokens_used,
+                    g.cost_usd
+                );
+            }
+            let _ = writeln!(out, "</table>");
+        }
+
+        if !self.model_costs.is_empty() {
+            let _ = writeln!(
+                out,
+                "<h2>Costs by model</h2>\n<table>\n<tr><th>Model</th><th>Calls</th>\
+                 <th>Input tokens</th><th>Output tokens</th><th>Cost (USD)</th></tr>"
+            );
+            for m in &self.model_costs {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.4}</td></tr>",
+                    escape_html(&m.model),
+                    m.calls,
+                    m.input_tokens,
+                    m.output_tokens,
+                    m.cost_usd
+                );
+            }
+            let _ = writeln!(out, "</table>");
+        }
+
+        if !self.checkpoints.is_empty() {
+            let _ = writeln!(out, "<h2>Checkpoints</h2>\n<ul>");
+            for c in &self.checkpoints {
+                let _ = writeln!(
+                    out,
+                    "<li>{} {} <code>{}</code></li>",
+                    c.created_at.format("%Y-%m-%d %H:%M"),
+                    escape_html(&c.description),
+                    escape_html(short(&c.id))
+                );
+            }
+            let _ = writeln!(out, "</ul>");
+        }
+
+        if !self.notes.is_empty() {
+            let _ = writeln!(out, "<h2>Notes</h2>\n<ul>");
+            for note in &self.notes {
+                let _ = writeln!(
+                    out,
+                    "<li>{} {}{}</li>",
+                    note.created_at.format("%Y-%m-%d %H:%M"),
+                    escape_html(&note.content),
+                    if note.remind {
+                        " <em>(reminder)</em>"
+                    } else {
+                        ""
+                    }
+                );
+            }
+            let _ = writeln!(out, "</ul>");
+        }
+
+        if !self.events.is_empty() {
+            let _ = writeln!(out, "<h2>Timeline</h2>\n<ul>");
+            for event in &self.events {
+                let _ = writeln!(
+                    out,
+                    "<li>{} <strong>{}</strong>{}</li>",
+                    event.created_at.format("%Y-%m-%d %H:%M:%S"),
+                    event.event_type,
+                    event_detail(event)
+                        .map(|d| format!(": {}", escape_html(&d)))
+                        .unwrap_or_default()
+                );
+            }
+            let _ = writeln!(out, "</ul>");
+        }
+
+        let _ = writeln!(
+            out,
+            "<p><em>Generated {}</em></p>\n</body>\n</html>",
+            self.generated_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        out
+    }
+
+    fn summary_rows(&self) -> Vec<(&'static str, String)> {
+        let duration = self.duration();
+        vec![
+            ("Status", self.session.status.to_string()),
+            (
+                "Started",
+                self.started_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+            ),
+            (
+                "Ended",
+                if self.session.status.has_ended() {
+                    self.ended_at.format("%Y-%m-%d %H:%M UTC").to_string()
+                } else {
+                    "(in progress)".to_string()
+                },
+            ),
+            (
+                "Duration",
+                format!(
+                    "{}h {:02}m",
+                    duration.num_hours(),
+                    duration.num_minutes() % 60
+                ),
+            ),
+            ("Features touched", self.features.len().to_string()),
+            ("Generations", self.generations.len().to_string()),
+            ("Generation tokens", self.generation_tokens().to_string()),
+            ("Checkpoints", self.checkpoints.len().to_string()),
+            (
+                "Agents spawned",
+                self.agent_activity.agents_spawned.to_string(),
+            ),
+            ("Total cost", format!("${:.4}", self.total_cost_usd())),
+        ]
+    }
+
+    /// Write the report to a file, choosing the format from its extension
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = self.render(ReportFormat::from_path(path))?;
+        std::fs::write(path, content).map_err(Error::Io)
+    }
+}
+
+/// Features referenced by the session, its events and its generations
+async fn touched_features(
+    session: &Session,
+    events: &[SessionEvent],
+    db: &Database,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<String>> {
+    let mut ids: Vec<String> = Vec::new();
+    let mut push = |id: String| {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    };
+
+    for event in events
+        .iter()
+        .filter(|e| e.event_type == SessionEventType::FeatureSwitched)
+    {
+        if let Some(id) = event
+            .data
+            .as_ref()
+            .and_then(|d| d.get("new_feature_id"))
+            .and_then(|v| v.as_str())
+        {
+            push(id.to_string());
+        }
+    }
+    if let Some(id) = session.current_feature_id {
+        push(id.to_string());
+    }
+
+    let rows = sqlx::query(
+        "SELECT DISTINCT feature_id FROM generations \
+         WHERE feature_id IS NOT NULL AND datetime(created_at) BETWEEN datetime(?) AND datetime(?)",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(db.pool())
+    .await?;
+    for row in rows {
+        push(row.get("feature_id"));
+    }
+
+    Ok(ids)
+}
+
+async fn load_features(db: &Database, ids: &[String]) -> Result<Vec<FeatureEntry>> {
+    let mut features = Vec::with_capacity(ids.len());
+    for id in ids {
+        let row = sqlx::query("SELECT title, status FROM features WHERE id = ?")
+            .bind(id)
+            .fetch_optional(db.pool())
+            .await?;
+        features.push(FeatureEntry {
+            id: id.clone(),
+            title: row.as_ref().map(|r| r.get("title")),
+            status: row.as_ref().map(|r| r.get("status")),
+        });
+    }
+    Ok(features)
+}
+
+async fn load_generations(
+    db: &Database,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<GenerationEntry>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT g.id, g.description, g.status, g.tokens_used, g.cost_usd, g.created_at,
+               COUNT(a.id) AS files,
+               COALESCE(SUM(CASE WHEN a.decision = 'accepted' THEN 1 ELSE 0 END), 0) AS accepted_files
+        FROM generations g
+        LEFT JOIN generation_artifacts a ON a.generation_id = g.id
+        WHERE datetime(g.created_at) BETWEEN datetime(?) AND datetime(?)
+        GROUP BY g.id
+        ORDER BY g.created_at
+        "#,
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(db.pool())
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| GenerationEntry {
+            id: r.get("id"),
+            description: r.get("description"),
+            status: r.get("status"),
+            tokens_used: r.get("tokens_used"),
+            cost_usd: r.get("cost_usd"),
+            files: r.get("files"),
+            accepted_files: r.get("accepted_files"),
+            created_at: r.get("created_at"),
+        })
+        .collect())
+}
+
+async fn load_checkpoints(
+    db: &Database,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<CheckpointEntry>> {
+    let rows = sqlx::query(
+        "SELECT id, description, size_bytes, created_at FROM checkpoints \
+         WHERE datetime(created_at) BETWEEN datetime(?) AND datetime(?) ORDER BY created_at",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(db.pool())
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| CheckpointEntry {
+            id: r.get("id"),
+            description: r.get("description"),
+            size_bytes: r.get("size_bytes"),
+            created_at: r.get("created_at"),
+        })
+        .collect())
+}
+
+async fn load_model_costs(
+    db: &Database,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<ModelCost>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT model, COUNT(*) AS calls,
+               SUM(input_tokens) AS input_tokens, SUM(output_tokens) AS output_tokens,
+               SUM(input_cost_usd + output_cost_usd) AS cost_usd
+        FROM llm_costs
+        WHERE datetime(created_at) BETWEEN datetime(?) AND datetime(?)
+        GROUP BY model
+        "#,
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(db.pool())
+    .await?;
+
+    let mut costs: BTreeMap<String, ModelCost> = BTreeMap::new();
+    for r in rows {
+        let model: String = r.get("model");
+        costs.insert(
+            model.clone(),
+            ModelCost {
+                model,
+                calls: r.get("calls"),
+                input_tokens: r.get("input_tokens"),
+                output_tokens: r.get("output_tokens"),
+                cost_usd: r.get("cost_usd"),
+            },
+        );
+    }
+    let mut costs: Vec<ModelCost> = costs.into_values().collect();
+    costs.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd));
+    Ok(costs)
+}
+
+/// Human-readable detail for a timeline entry
+fn event_detail(event: &SessionEvent) -> Option<String> {
+    let data = event.data.as_ref()?;
+    let field = |key: &str| data.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    match event.event_type {
+        SessionEventType::ProjectSwitched => {
+            field("new_project_id").map(|id| format!("project {}", short(&id)))
+        }
+        SessionEventType::FeatureSwitched => {
+            field("new_feature_id").map(|id| format!("feature {}", short(&id)))
+        }
+        SessionEventType::PhaseChanged => Some(format!(
+            "{} -> {}",
+            field("old_phase").unwrap_or_default(),
+            field("new_phase").unwrap_or_default()
+        )),
+        SessionEventType::CheckpointCreated => {
+            field("checkpoint_id").map(|id| format!("checkpoint {}", short(&id)))
+        }
+        SessionEventType::Error => field("message"),
+        SessionEventType::Custom => field("name"),
+        _ => None,
+    }
+}
+
+fn short(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::events::AgentEventData;
+
+    fn agent_event(event_type: AgentEventType, timestamp: DateTime<Utc>) -> AgentEvent {
+        AgentEvent {
+            timestamp,
+            event_id: Uuid::new_v4(),
+            session_id: Uuid::new_v4(),
+            event_type,
+            agent: AgentEventData {
+                id: "a".to_string(),
+                agent_type: String::new(),
+                name: String::new(),
+                parent_id: None,
+                path: String::new(),
+                status: String::new(),
+                tokens: 0,
+                task: None,
+                error: None,
+            },
+            file: None,
+            context: None,
+        }
+    }
+
+    fn report() -> SessionReport {
+        let session = Session::new(None, None, Some("Build <login> page".to_string()));
+        let started_at = session.created_at;
+        let note = SessionNote {
+            id: "n1".to_string(),
+            session_id: session.id.to_string(),
+            content: "remember to refactor auth".to_string(),
+            remind: true,
+            reminded_at: None,
+            created_at: started_at,
+        };
+        SessionReport {
+            events: vec![SessionEvent::phase_changed(
+                session.id, "planning", "building",
+            )],
+            session,
+            started_at,
+            ended_at: started_at + chrono::Duration::minutes(90),
+            features: vec![FeatureEntry {
+                id: "f1234567890".to_string(),
+                title: Some("Login".to_string()),
+                status: Some("in_progress".to_string()),
+            }],
+            generations: vec![GenerationEntry {
+                id: "g1".to_string(),
+                description: "login form".to_string(),
+                status: "completed".to_string(),
+                tokens_used: 1200,
+                cost_usd: 0.05,
+                files: 3,
+                accepted_files: 2,
+                created_at: started_at,
+            }],
+            checkpoints: Vec::new(),
+            model_costs: Vec::new(),
+            agent_activity: AgentActivity::default(),
+            notes: vec![note],
+            generated_at: started_at,
+        }
+    }
+
+    #[test]
+    fn test_agent_activity_filters_by_window() {
+        let start = Utc::now();
+        let end = start + chrono::Duration::hours(1);
+        let events = vec![
+            agent_event(AgentEventType::Spawned, start),
+            agent_event(
+                AgentEventType::FileWritten,
+                start + chrono::Duration::minutes(5),
+            ),
+            agent_event(AgentEventType::Failed, end + chrono::Duration::minutes(1)),
+        ];
+
+        let activity = AgentActivity::from_events(&events, start, end);
+        assert_eq!(activity.events, 2);
+        assert_eq!(activity.agents_spawned, 1);
+        assert_eq!(activity.files_written, 1);
+        assert_eq!(activity.agents_failed, 0);
+    }
+
+    #[test]
+    fn test_markdown_report() {
+        let markdown = report().to_markdown();
+        assert!(markdown.contains("## Summary"));
+        assert!(markdown.contains("| Duration | 1h 30m |"));
+        assert!(markdown.contains("- Login (f1234567) - in_progress"));
+        assert!(markdown.contains("| login form | completed | 2/3 | 1200 | 0.0500 |"));
+        assert!(markdown.contains("phase_changed: planning -> building"));
+        assert!(markdown.contains("remember to refactor auth (reminder)"));
+    }
+
+    #[test]
+    fn test_html_report_escapes_content() {
+        let html = report().to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Build &lt;login&gt; page"));
+        assert!(!html.contains("<login>"));
+    }
+
+    #[test]
+    fn test_total_cost_falls_back_to_generations() {
+        let mut report = report();
+        assert!((report.total_cost_usd() - 0.05).abs() < 1e-9);
+
+        report.model_costs.push(ModelCost {
+            model: "m".to_string(),
+            calls: 2,
+            input_tokens: 10,
+            output_tokens: 20,
+            cost_usd: 0.2,
+        });
+        assert!((report.total_cost_usd() - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_report_format_from_path() {
+        assert_eq!(
+            ReportFormat::from_path(Path::new("r.html")),
+            ReportFormat::Html
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("r.json")),
+            ReportFormat::Json
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("report")),
+            ReportFormat::Markdown
+        );
+    }
+}
@@ -0,0 +1,477 @@
+//! Roadmap documents assembled from phases and features
+//!
+//! `demiarch documents generate-roadmap` lays a project's unfinished
+//! features out in dependency order — phase order, then feature priority —
+//! and renders a Markdown document with a Mermaid Gantt chart and a table
+//! per phase. Each feature is scheduled for its estimated duration from
+//! similar past work (at least a day). The roadmap is stored as a
+//! `roadmap` document; regenerating it after statuses change records a new
+//! revision of the same document.
+//!
+//! A roadmap in another language is rendered in English and then translated
+//! by the model; the language is stored with the document and kept when it
+//! is regenerated.
+
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::document::{
+    resolve_language, revise_document, Document, DocumentGenerator, DocumentRepository,
+    DocumentType,
+};
+use crate::commands::estimate;
+use crate::commands::feature::{Feature, FeatureRepository, FeatureStatus};
+use crate::commands::phase;
+use crate::commands::project::ProjectRepository;
+use crate::config::Config;
+use crate::cost::CostTracker;
+use crate::llm::ResponseCache;
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Name of the section holding features that are not in any phase
+pub const UNPHASED: &str = "Unphased";
+
+/// A feature placed on the roadmap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoadmapItem {
+    pub feature_id: String,
+    pub title: String,
+    pub status: FeatureStatus,
+    pub priority: i32,
+    /// Days the feature is scheduled for
+    pub estimated_days: i64,
+    pub estimated_cost_usd: Option<f64>,
+    /// Scheduled start; `None` once the feature is done
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
+
+/// A phase and its features, in roadmap order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoadmapPhase {
+    /// Phase ID; `None` for the unphased section
+    pub id: Option<String>,
+    pub name: String,
+    pub target_date: Option<NaiveDate>,
+    pub percent: f64,
+    pub items: Vec<RoadmapItem>,
+    /// Whether the scheduled work runs past the target date
+    pub at_risk: bool,
+}
+
+/// A project's roadmap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Roadmap {
+    pub project_id: String,
+    pub project_name: String,
+    /// Day remaining work is scheduled from
+    pub start: NaiveDate,
+    pub phases: Vec<RoadmapPhase>,
+}
+
+impl Roadmap {
+    /// Schedule unfinished items back to back from `start`
+    ///
+    /// Work runs phase by phase in order, so a phase's features start after
+    /// everything in the phases before it.
+    pub fn schedule(&mut self) {
+        let mut cursor = self.start;
+        for phase in &mut self.phases {
+            for item in &mut phase.items {
+                if item.status == FeatureStatus::Done {
+                    item.start = None;
+                    item.end = None;
+                    continue;
+                }
+                let end = cursor + Duration::days(item.estimated_days.max(1));
+                item.start = Some(cursor);
+                item.end = Some(end);
+                cursor = end;
+            }
+            let last_end = phase.items.iter().filter_map(|i| i.end).max();
+            phase.at_risk = matches!(
+                (phase.target_date, last_end),
+                (Some(target), Some(end)) if end > target
+            );
+        }
+    }
+
+    /// Render the roadmap as Markdown with a Mermaid Gantt chart
+    pub fn to_markdown(&self) -> String {
+        let features: usize = self.phases.iter().map(|p| p.items.len()).sum();
+        let mut out = format!("# {} Roadmap\n\n", self.project_name);
+        out.push_str(&format!(
+            "_Generated {} from {} features in {} phases. Regenerate with \
+             `demiarch documents generate-roadmap --project {}` as statuses change._\n\n",
+            self.start,
+            features,
+            self.phases.iter().filter(|p| p.id.is_some()).count(),
+            self.project_id
+        ));
+
+        out.push_str("## Timeline\n\n```mermaid\ngantt\n");
+        out.push_str(&format!(
+            "    title {} Roadmap\n    dateFormat YYYY-MM-DD\n",
+            gantt_label(&self.project_name)
+        ));
+        let mut previous: Option<usize> = None;
+        let mut task = 0;
+        for (index, phase) in self.phases.iter().enumerate() {
+            let open: Vec<&RoadmapItem> =
+                phase.items.iter().filter(|i| i.start.is_some()).collect();
+            if open.is_empty() && phase.target_date.is_none() {
+                continue;
+            }
+            out.push_str(&format!("    section {}\n", gantt_label(&phase.name)));
+            for item in open {
+                task += 1;
+                let mut tags = Vec::new();
+                if matches!(
+                    item.status,
+                    FeatureStatus::InProgress | FeatureStatus::Review
+                ) {
+                    tags.push("active".to_string());
+                }
+                if matches!((phase.target_date, item.end), (Some(t), Some(e)) if e > t) {
+                    tags.push("crit".to_string());
+                }
+                tags.push(format!("t{}", task));
+                match previous {
+                    Some(prev) => tags.push(format!("after t{}", prev)),
+                    None => tags.push(self.start.to_string()),
+                }
+                tags.push(format!("{}d", item.estimated_days.max(1)));
+                out.push_str(&format!(
+                    "    {} :{}\n",
+                    gantt_label(&item.title),
+                    tags.join(", ")
+                ));
+                previous = Some(task);
+            }
+            if let Some(target) = phase.target_date {
+                out.push_str(&format!(
+                    "    {} target :milestone, m{}, {}, 0d\n",
+                    gantt_label(&phase.name),
+                    index + 1,
+                    target
+                ));
+            }
+        }
+        out.push_str("```\n\n## Phases\n");
+
+        for (index, phase) in self.phases.iter().enumerate() {
+            out.push_str(&format!("\n### {}. {}", index + 1, phase.name));
+            if phase.id.is_some() {
+                out.push_str(&format!(" — {:.0}% complete", phase.percent));
+            }
+            if let Some(target) = phase.target_date {
+                out.push_str(&format!(" (target {}", target));
+                if phase.at_risk {
+                    out.push_str(", at risk");
+                }
+                out.push(')');
+            }
+            out.push_str("\n\n");
+            if phase.items.is_empty() {
+                out.push_str("No features yet.\n");
+                continue;
+            }
+            out.push_str("| Feature | Status | Priority | Est. cost | Scheduled |\n");
+            out.push_str("|---|---|---|---|---|\n");
+            for item in &phase.items {
+                let cost = item
+                    .estimated_cost_usd
+                    .map(|c| format!("${:.2}", c))
+                    .unwrap_or_else(|| "-".to_string());
+                let scheduled = match (item.start, item.end) {
+                    (Some(start), Some(end)) => format!("{} → {}", start, end),
+                    _ => "-".to_string(),
+                };
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    item.title.replace('|', "\\|"),
+                    item.status.as_str(),
+                    item.priority,
+                    cost,
+                    scheduled
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Text Mermaid accepts as a Gantt task or section name
+fn gantt_label(text: &str) -> String {
+    text.chars()
+        .map(|c| if matches!(c, ':' | '#' | ';') { ' ' } else { c })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Assemble a project's roadmap, scheduling remaining work from `start`
+pub async fn build(db: &Database, project_id: &str, start: NaiveDate) -> Result<Roadmap> {
+    let project = ProjectRepository::new(db)
+        .get(project_id)
+        .await?
+        .ok_or_else(|| Error::ProjectNotFound(project_id.to_string()))?;
+    let progress = phase::project_progress(db, project_id, start).await?;
+    let features = FeatureRepository::new(db)
+        .list_by_project(project_id, None)
+        .await?;
+    let history = estimate::history(db, Some(project_id), None).await?;
+
+    let item = |feature: &Feature| -> Result<RoadmapItem> {
+        let estimate = if feature.status == FeatureStatus::Done {
+            None
+        } else {
+            estimate::estimate(
+                &estimate::feature_text(feature),
+                &history,
+                estimate::DEFAULT_NEIGHBOURS,
+            )?
+        };
+        Ok(RoadmapItem {
+            feature_id: feature.id.clone(),
+            title: feature.title.clone(),
+            status: feature.status,
+            priority: feature.priority,
+            estimated_days: estimate
+                .as_ref()
+                .map(|e| (e.duration_secs.expected / 86_400.0).ceil() as i64)
+                .unwrap_or(1)
+                .max(1),
+            estimated_cost_usd: estimate.map(|e| e.cost_usd.expected),
+            start: None,
+            end: None,
+        })
+    };
+
+    let mut phases = Vec::with_capacity(progress.len() + 1);
+    for p in progress {
+        let items = features
+            .iter()
+            .filter(|f| f.phase_id.as_deref() == Some(p.phase.id.as_str()))
+            .map(item)
+            .collect::<Result<Vec<_>>>()?;
+        phases.push(RoadmapPhase {
+            id: Some(p.phase.id),
+            name: p.phase.name,
+            target_date: p.phase.target_date,
+            percent: p.percent,
+            items,
+            at_risk: false,
+        });
+    }
+    let phased: Vec<&str> = phases.iter().filter_map(|p| p.id.as_deref()).collect();
+    let unphased = features
+        .iter()
+        .filter(|f| f.phase_id.as_deref().is_none_or(|id| !phased.contains(&id)))
+        .map(item)
+        .collect::<Result<Vec<_>>>()?;
+    if !unphased.is_empty() {
+        let done = unphased
+            .iter()
+            .filter(|i| i.status == FeatureStatus::Done)
+            .count();
+        phases.push(RoadmapPhase {
+            id: None,
+            name: UNPHASED.to_string(),
+            target_date: None,
+            percent: done as f64 * 100.0 / unphased.len() as f64,
+            items: unphased,
+            at_risk: false,
+        });
+    }
+
+    let mut roadmap = Roadmap {
+        project_id: project.id,
+        project_name: project.name,
+        start,
+        phases,
+    };
+    roadmap.schedule();
+    Ok(roadmap)
+}
+
+/// Generate the project's roadmap document, or revise the existing one
+///
+/// The document keeps its ID across regenerations; a new version is only
+/// recorded when the content changed. `language` is resolved as for other
+/// documents (see [`resolve_language`]).
+pub async fn generate_roadmap(
+    db: &Database,
+    project_id: &str,
+    start: NaiveDate,
+    language: Option<&str>,
+) -> Result<Document> {
+    let roadmap = build(db, project_id, start).await?;
+    let title = format!("{} Roadmap", roadmap.project_name);
+    let language = resolve_language(db, project_id, DocumentType::Roadmap, language).await?;
+    let content = match &language {
+        Some(language) => translate(db, &title, &roadmap.to_markdown(), language).await?,
+        None => roadmap.to_markdown(),
+    };
+    let repo = DocumentRepository::new(db);
+
+    let existing = repo
+        .list_by_project(project_id, Some(DocumentType::Roadmap))
+        .await?
+        .into_iter()
+        .next();
+    match existing {
+        Some(doc) if doc.content == content => Ok(doc),
+        Some(mut doc) => {
+            if doc.language != language {
+                doc.language = language;
+                repo.update(&doc).await?;
+            }
+            revise_document(
+                db,
+                &doc.id,
+                content,
+                Some("Regenerated from current phases and features".to_string()),
+            )
+            .await
+        }
+        None => {
+            let doc = Document::new(project_id, DocumentType::Roadmap, title, content)
+                .with_description("Phases and features in delivery order")
+                .with_language(language.as_deref());
+            repo.create(&doc).await?;
+            Ok(doc)
+        }
+    }
+}
+
+/// Translate a rendered roadmap
+///
+/// Unchanged roadmaps are served from the response cache when it is
+/// enabled, so regenerating them records no new version.
+async fn translate(db: &Database, title: &str, content: &str, language: &str) -> Result<String> {
+    let config = Config::load().map_err(|e| Error::ConfigError(e.to_string()))?;
+    let cost_tracker = Arc::new(CostTracker::from_config(&config.cost));
+    let cache = ResponseCache::from_config(db, &config.cache);
+    let mut generator = DocumentGenerator::new(config, Some(cost_tracker))?.with_language(language);
+    if let Some(cache) = cache {
+        generator = generator.with_response_cache(cache);
+    }
+    let translated = generator
+        .translate(DocumentType::Roadmap, title, content)
+        .await?;
+    Ok(translated.content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::phase::{Phase, PhaseRepository};
+    use crate::commands::project::Project;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, d).unwrap()
+    }
+
+    fn item(title: &str, status: FeatureStatus, days: i64) -> RoadmapItem {
+        RoadmapItem {
+            feature_id: title.to_string(),
+            title: title.to_string(),
+            status,
+            priority: 0,
+            estimated_days: days,
+            estimated_cost_usd: None,
+            start: None,
+            end: None,
+        }
+    }
+
+    #[test]
+    fn test_schedule_runs_phases_in_order() {
+        let mut roadmap = Roadmap {
+            project_id: "p".to_string(),
+            project_name: "Shop".to_string(),
+            start: day(1),
+            phases: vec![
+                RoadmapPhase {
+                    id: Some("a".to_string()),
+                    name: "MVP".to_string(),
+                    target_date: Some(day(3)),
+                    percent: 50.0,
+                    items: vec![
+                        item("Login", FeatureStatus::Done, 1),
+                        item("Cart: checkout", FeatureStatus::InProgress, 3),
+                    ],
+                    at_risk: false,
+                },
+                RoadmapPhase {
+                    id: Some("b".to_string()),
+                    name: "Launch".to_string(),
+                    target_date: None,
+                    percent: 0.0,
+                    items: vec![item("Emails", FeatureStatus::Backlog, 0)],
+                    at_risk: false,
+                },
+            ],
+        };
+        roadmap.schedule();
+
+        let mvp = &roadmap.phases[0];
+        assert_eq!(mvp.items[0].start, None);
+        assert_eq!(mvp.items[1].start, Some(day(1)));
+        assert_eq!(mvp.items[1].end, Some(day(4)));
+        assert!(mvp.at_risk);
+        assert_eq!(roadmap.phases[1].items[0].start, Some(day(4)));
+        assert_eq!(roadmap.phases[1].items[0].end, Some(day(5)));
+
+        let markdown = roadmap.to_markdown();
+        assert!(markdown.contains("Cart  checkout :active, crit, t1, 2025-03-01, 3d"));
+        assert!(markdown.contains("Emails :t2, after t1, 1d"));
+        assert!(markdown.contains("MVP target :milestone, m1, 2025-03-03, 0d"));
+        assert!(markdown.contains("### 1. MVP — 50% complete (target 2025-03-03, at risk)"));
+        assert!(markdown.contains("| Login | done | 0 | - | - |"));
+    }
+
+    #[tokio::test]
+    async fn test_generate_roadmap_revises_existing_document() {
+        let db = Database::in_memory().await.unwrap();
+        let project = Project::new("shop", "rust", "");
+        ProjectRepository::new(&db).create(&project).await.unwrap();
+        let mvp = Phase::new(&project.id, "MVP", 0);
+        PhaseRepository::new(&db).create(&mvp).await.unwrap();
+        let features = FeatureRepository::new(&db);
+        let login = Feature::new(&project.id, "Login").with_phase(&mvp.id);
+        features.create(&login).await.unwrap();
+        features
+            .create(&Feature::new(&project.id, "Search"))
+            .await
+            .unwrap();
+
+        let first = generate_roadmap(&db, &project.id, day(1), None)
+            .await
+            .unwrap();
+        assert_eq!(first.doc_type, DocumentType::Roadmap);
+        assert!(first.content.contains("### 2. Unphased"));
+        assert_eq!(
+            generate_roadmap(&db, &project.id, day(1), None)
+                .await
+                .unwrap()
+                .version,
+            first.version
+        );
+
+        features
+            .update_status(&login.id, FeatureStatus::Done)
+            .await
+            .unwrap();
+        let second = generate_roadmap(&db, &project.id, day(1), None)
+            .await
+            .unwrap();
+        assert_eq!(second.id, first.id);
+        assert_eq!(second.version, first.version + 1);
+        assert!(second.content.contains("MVP — 100% complete"));
+    }
+}
@@ -0,0 +1,307 @@
+//! Per-project encrypted secrets
+//!
+//! Generated apps need environment variables such as database URLs and
+//! third-party API keys. `demiarch secrets` stores them per project in the
+//! `project_secrets` table, encrypted with AES-256-GCM under the same master
+//! key (kept in the OS keyring) that protects the stored LLM API keys.
+//!
+//! Values are only decrypted for `secrets get` and `secrets export --dotenv`.
+//! Code generation sees secret names, never values, so generated config and
+//! `.env.example` files reference them without leaking them into prompts.
+//! Secrets are not part of the JSONL sync export or `db export`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::domain::security::{EncryptedKey, MasterKey, MasterKeyRepository, SecureString};
+use crate::infrastructure::security::KeyringMasterKeyRepository;
+use crate::storage::{ensure_writable, Database};
+use crate::{Error, Result};
+
+/// A stored secret without its value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecretInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Encrypted secret store for projects
+pub struct SecretsVault {
+    db: Database,
+    master_keys: Box<dyn MasterKeyRepository>,
+}
+
+impl SecretsVault {
+    /// Open the vault with the master key from the OS keyring
+    pub fn new(db: &Database) -> Self {
+        Self::with_master_keys(db, Box::new(KeyringMasterKeyRepository::new()))
+    }
+
+    /// Open the vault with a specific master key store
+    pub fn with_master_keys(db: &Database, master_keys: Box<dyn MasterKeyRepository>) -> Self {
+        Self {
+            db: db.clone(),
+            master_keys,
+        }
+    }
+
+    /// Master key, created on first use like the API key store does
+    async fn master_key(&self) -> Result<MasterKey> {
+        if let Some(key) = self.master_keys.get().await? {
+            return Ok(key);
+        }
+        let key = MasterKey::generate();
+        self.master_keys.store(&key).await?;
+        tracing::info!("Generated and stored new master encryption key");
+        Ok(key)
+    }
+
+    /// Store or replace a secret
+    pub async fn set(
+        &self,
+        project_id: &str,
+        name: &str,
+        value: &str,
+        description: Option<&str>,
+    ) -> Result<()> {
+        ensure_writable("secret update")?;
+        validate_name(name)?;
+        let master_key = self.master_key().await?;
+        let encrypted = EncryptedKey::encrypt(
+            name.to_string(),
+            value,
+            &master_key,
+            description.map(str::to_string),
+        )?;
+
+        sqlx::query(
+            "INSERT INTO project_secrets (id, project_id, name, ciphertext, nonce, description, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(project_id, name) DO UPDATE SET
+                 ciphertext = excluded.ciphertext,
+                 nonce = excluded.nonce,
+                 description = COALESCE(excluded.description, project_secrets.description),
+                 updated_at = excluded.updated_at",
+        )
+        .bind(encrypted.id.to_string())
+        .bind(project_id)
+        .bind(name)
+        .bind(&encrypted.ciphertext)
+        .bind(&encrypted.nonce)
+        .bind(&encrypted.description)
+        .bind(encrypted.created_at)
+        .bind(encrypted.updated_at)
+        .execute(self.db.pool())
+        .await?;
+        Ok(())
+    }
+
+    /// Decrypt a secret
+    pub async fn get(&self, project_id: &str, name: &str) -> Result<SecureString> {
+        let row = sqlx::query(
+            "SELECT ciphertext, nonce FROM project_secrets WHERE project_id = ? AND name = ?",
+        )
+        .bind(project_id)
+        .bind(name)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Secret {}", name)))?;
+        self.decrypt(name, row.get("ciphertext"), row.get("nonce"))
+            .await
+    }
+
+    /// Remove a secret, returning whether it existed
+    pub async fn remove(&self, project_id: &str, name: &str) -> Result<bool> {
+        ensure_writable("secret removal")?;
+        let result = sqlx::query("DELETE FROM project_secrets WHERE project_id = ? AND name = ?")
+            .bind(project_id)
+            .bind(name)
+            .execute(self.db.pool())
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Render every secret of a project as a `.env` file
+    pub async fn export_dotenv(&self, project_id: &str) -> Result<String> {
+        let rows = sqlx::query(
+            "SELECT name, ciphertext, nonce FROM project_secrets WHERE project_id = ? ORDER BY name",
+        )
+        .bind(project_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut out = String::new();
+        for row in rows {
+            let name: String = row.get("name");
+            let value = self
+                .decrypt(&name, row.get("ciphertext"), row.get("nonce"))
+                .await?;
+            out.push_str(&format!("{}={}\n", name, dotenv_quote(value.as_str())));
+        }
+        Ok(out)
+    }
+
+    async fn decrypt(&self, name: &str, ciphertext: String, nonce: String) -> Result<SecureString> {
+        let master_key = self
+            .master_keys
+            .get()
+            .await?
+            .ok_or_else(|| Error::Other("No master key in the keyring".to_string()))?;
+        let now = Utc::now();
+        let encrypted = EncryptedKey {
+            id: uuid::Uuid::nil(),
+            name: name.to_string(),
+            ciphertext,
+            nonce,
+            description: None,
+            created_at: now,
+            updated_at: now,
+            last_used_at: None,
+        };
+        Ok(encrypted.decrypt(&master_key)?)
+    }
+}
+
+/// List a project's secrets without decrypting them
+pub async fn list(db: &Database, project_id: &str) -> Result<Vec<SecretInfo>> {
+    let rows = sqlx::query(
+        "SELECT name, description, updated_at FROM project_secrets WHERE project_id = ? ORDER BY name",
+    )
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?;
+    Ok(rows
+        .iter()
+        .map(|row| SecretInfo {
+            name: row.get("name"),
+            description: row.get("description"),
+            updated_at: row.get("updated_at"),
+        })
+        .collect())
+}
+
+/// Names of a project's secrets, for generation prompts
+pub async fn names(db: &Database, project_id: &str) -> Result<Vec<String>> {
+    Ok(list(db, project_id)
+        .await?
+        .into_iter()
+        .map(|s| s.name)
+        .collect())
+}
+
+/// `.env.example` listing secret names with empty values
+pub fn env_example(names: &[String]) -> String {
+    names.iter().map(|name| format!("{}=\n", name)).collect()
+}
+
+/// Secret names must be usable as environment variable names
+fn validate_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(Error::InvalidInput(format!(
+            "Invalid secret name '{}'. Use letters, digits and underscores, e.g. DATABASE_URL",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Quote a value for a `.env` file when it needs it
+fn dotenv_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-.:/@+,".contains(c));
+    if plain {
+        return value.to_string();
+    }
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('$', "\\$");
+    format!("\"{}\"", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::security::InMemoryMasterKeyRepository;
+
+    async fn vault() -> (Database, SecretsVault) {
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'Demo')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let vault =
+            SecretsVault::with_master_keys(&db, Box::new(InMemoryMasterKeyRepository::new()));
+        (db, vault)
+    }
+
+    #[tokio::test]
+    async fn test_set_get_and_list_secrets() {
+        let (db, vault) = vault().await;
+        vault
+            .set("p1", "DATABASE_URL", "postgres://localhost/app", None)
+            .await
+            .unwrap();
+        vault
+            .set("p1", "STRIPE_KEY", "sk_test_1", Some("Stripe test key"))
+            .await
+            .unwrap();
+        vault
+            .set("p1", "STRIPE_KEY", "sk_test_2", None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vault.get("p1", "STRIPE_KEY").await.unwrap().as_str(),
+            "sk_test_2"
+        );
+        let listed = list(&db, "p1").await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[1].description.as_deref(), Some("Stripe test key"));
+
+        // Values are never stored in plaintext
+        let stored: String =
+            sqlx::query_scalar("SELECT ciphertext FROM project_secrets WHERE name = 'STRIPE_KEY'")
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+        assert!(!stored.contains("sk_test"));
+
+        assert!(vault.remove("p1", "STRIPE_KEY").await.unwrap());
+        assert!(vault.get("p1", "STRIPE_KEY").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exports_dotenv() {
+        let (_db, vault) = vault().await;
+        vault.set("p1", "PORT", "8080", None).await.unwrap();
+        vault
+            .set("p1", "GREETING", "hello \"world\"", None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vault.export_dotenv("p1").await.unwrap(),
+            "GREETING=\"hello \\\"world\\\"\"\nPORT=8080\n"
+        );
+        assert_eq!(env_example(&["PORT".to_string()]), "PORT=\n");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_names() {
+        let (_db, vault) = vault().await;
+        for name in ["", "1PASSWORD", "MY-KEY", "A B"] {
+            assert!(vault.set("p1", name, "x", None).await.is_err(), "{}", name);
+        }
+    }
+}
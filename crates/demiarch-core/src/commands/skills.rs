@@ -12,7 +12,7 @@ pub async fn list_skills(
     category: Option<SkillCategory>,
     limit: Option<u32>,
 ) -> Result<Vec<LearnedSkill>> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let store = SkillStore::new(db.pool().clone());
@@ -35,7 +35,7 @@ pub async fn list_skills(
 
 /// Get a specific skill by ID
 pub async fn get_skill(id: &str) -> Result<Option<LearnedSkill>> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let store = SkillStore::new(db.pool().clone());
@@ -45,7 +45,7 @@ pub async fn get_skill(id: &str) -> Result<Option<LearnedSkill>> {
 
 /// Search skills by query string
 pub async fn search_skills(query: &str) -> Result<Vec<LearnedSkill>> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let store = SkillStore::new(db.pool().clone());
@@ -55,7 +55,7 @@ pub async fn search_skills(query: &str) -> Result<Vec<LearnedSkill>> {
 
 /// Search skills by tags
 pub async fn search_skills_by_tags(tags: &[String]) -> Result<Vec<LearnedSkill>> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let store = SkillStore::new(db.pool().clone());
@@ -65,7 +65,7 @@ pub async fn search_skills_by_tags(tags: &[String]) -> Result<Vec<LearnedSkill>>
 
 /// Get top skills by usage
 pub async fn top_skills(limit: u32) -> Result<Vec<LearnedSkill>> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let store = SkillStore::new(db.pool().clone());
@@ -75,7 +75,7 @@ pub async fn top_skills(limit: u32) -> Result<Vec<LearnedSkill>> {
 
 /// Get skills for a specific project
 pub async fn skills_by_project(project_id: &str) -> Result<Vec<LearnedSkill>> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let store = SkillStore::new(db.pool().clone());
@@ -85,7 +85,7 @@ pub async fn skills_by_project(project_id: &str) -> Result<Vec<LearnedSkill>> {
 
 /// Save a skill
 pub async fn save_skill(skill: &LearnedSkill) -> Result<()> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let store = SkillStore::new(db.pool().clone());
@@ -95,7 +95,7 @@ pub async fn save_skill(skill: &LearnedSkill) -> Result<()> {
 
 /// Record that a skill was used
 pub async fn record_skill_usage(skill_id: &str, success: bool) -> Result<()> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let store = SkillStore::new(db.pool().clone());
@@ -105,7 +105,7 @@ pub async fn record_skill_usage(skill_id: &str, success: bool) -> Result<()> {
 
 /// Delete a skill
 pub async fn delete_skill(id: &str) -> Result<bool> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let store = SkillStore::new(db.pool().clone());
@@ -115,7 +115,7 @@ pub async fn delete_skill(id: &str) -> Result<bool> {
 
 /// Get skill statistics
 pub async fn skill_stats() -> Result<SkillStats> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let store = SkillStore::new(db.pool().clone());
@@ -125,7 +125,7 @@ pub async fn skill_stats() -> Result<SkillStats> {
 
 /// Get total skill count
 pub async fn skill_count() -> Result<u64> {
-    let db = Database::default()
+    let db = Database::shared()
         .await
         .map_err(|e| crate::error::Error::Other(e.to_string()))?;
     let store = SkillStore::new(db.pool().clone());
@@ -0,0 +1,264 @@
+//! Reusable prompt snippets
+//!
+//! Snippets are Markdown files in `<config dir>/snippets/` (for example
+//! `~/.config/demiarch/snippets/api-conventions.md`). Chat messages and
+//! generate descriptions reference them as `{{snippet:api-conventions}}`, and
+//! the reference is replaced with the file's content before anything is sent
+//! to the LLM. Snippets may reference other snippets.
+//!
+//! Template variables such as `{{project}}` or `{{date}}` are filled in the
+//! same pass, both in the text and in the snippets it pulls in. Placeholders
+//! that name no known variable are left as they are, so templates from other
+//! tools pass through untouched.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::commands::project::Project;
+use crate::config::Config;
+use crate::{Error, Result};
+
+/// Prefix of a snippet reference inside `{{ }}`
+pub const SNIPPET_PREFIX: &str = "snippet:";
+
+/// How deeply snippets may include other snippets
+const MAX_DEPTH: usize = 8;
+
+/// Template variables by name
+pub type Variables = BTreeMap<String, String>;
+
+/// A snippet file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    /// Name used in references: the file name without `.md`
+    pub name: String,
+    pub path: PathBuf,
+    pub content: String,
+}
+
+impl Snippet {
+    /// First non-empty line, without Markdown heading marks
+    pub fn summary(&self) -> &str {
+        self.content
+            .lines()
+            .map(|line| line.trim().trim_start_matches('#').trim())
+            .find(|line| !line.is_empty())
+            .unwrap_or("")
+    }
+}
+
+/// Directory snippets are loaded from
+pub fn snippets_dir() -> Option<PathBuf> {
+    Config::config_dir().ok().map(|dir| dir.join("snippets"))
+}
+
+/// Variables every expansion gets: `date`, plus `project`, `framework` and
+/// `path` when there is a current project
+pub fn builtin_variables(project: Option<&Project>) -> Variables {
+    let mut vars = Variables::new();
+    vars.insert(
+        "date".to_string(),
+        chrono::Local::now().format("%Y-%m-%d").to_string(),
+    );
+    if let Some(project) = project {
+        vars.insert("project".to_string(), project.name.clone());
+        vars.insert("framework".to_string(), project.framework.clone());
+        if let Some(path) = &project.path {
+            vars.insert("path".to_string(), path.clone());
+        }
+    }
+    vars
+}
+
+/// The snippets available for expansion
+#[derive(Debug, Clone, Default)]
+pub struct SnippetLibrary {
+    snippets: BTreeMap<String, Snippet>,
+}
+
+impl SnippetLibrary {
+    /// Load the user's snippets; no snippet directory means no snippets
+    pub fn load() -> Result<Self> {
+        match snippets_dir() {
+            Some(dir) => Self::load_from(&dir),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Load every `*.md` file in `dir`
+    pub fn load_from(dir: &Path) -> Result<Self> {
+        let mut snippets = BTreeMap::new();
+        if !dir.is_dir() {
+            return Ok(Self { snippets });
+        }
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("md") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if !is_valid_name(name) {
+                tracing::warn!(path = %path.display(), "Skipping snippet with an unusable name");
+                continue;
+            }
+            let content = std::fs::read_to_string(&path)?;
+            snippets.insert(
+                name.to_string(),
+                Snippet {
+                    name: name.to_string(),
+                    path,
+                    content,
+                },
+            );
+        }
+        Ok(Self { snippets })
+    }
+
+    /// Snippets sorted by name
+    pub fn list(&self) -> impl Iterator<Item = &Snippet> {
+        self.snippets.values()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Snippet> {
+        self.snippets.get(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snippets.is_empty()
+    }
+
+    /// Replace snippet references and known variables in `text`
+    ///
+    /// Fails on a reference to a missing snippet or on snippets that
+    /// include each other in a loop.
+    pub fn expand(&self, text: &str, vars: &Variables) -> Result<String> {
+        let mut out = String::with_capacity(text.len());
+        self.render(text, vars, &mut Vec::new(), &mut out)?;
+        Ok(out)
+    }
+
+    fn render<'a>(
+        &'a self,
+        text: &str,
+        vars: &Variables,
+        stack: &mut Vec<&'a str>,
+        out: &mut String,
+    ) -> Result<()> {
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            let Some(len) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            let placeholder = &rest[start..start + 2 + len + 2];
+            let key = rest[start + 2..start + 2 + len].trim();
+            out.push_str(&rest[..start]);
+            rest = &rest[start + placeholder.len()..];
+
+            if let Some(name) = key.strip_prefix(SNIPPET_PREFIX) {
+                let snippet = self.resolve(name.trim(), stack)?;
+                stack.push(&snippet.name);
+                self.render(snippet.content.trim_end(), vars, stack, out)?;
+                stack.pop();
+            } else if let Some(value) = vars.get(key) {
+                out.push_str(value);
+            } else {
+                out.push_str(placeholder);
+            }
+        }
+        out.push_str(rest);
+        Ok(())
+    }
+
+    fn resolve(&self, name: &str, stack: &[&str]) -> Result<&Snippet> {
+        let snippet = self.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.snippets.keys().map(String::as_str).collect();
+            Error::NotFound(if known.is_empty() {
+                format!("Snippet '{}' not found (no snippets defined)", name)
+            } else {
+                format!(
+                    "Snippet '{}' not found. Available: {}",
+                    name,
+                    known.join(", ")
+                )
+            })
+        })?;
+        if stack.contains(&name) {
+            return Err(Error::InvalidInput(format!(
+                "Snippet '{}' includes itself ({} -> {})",
+                name,
+                stack.join(" -> "),
+                name
+            )));
+        }
+        if stack.len() >= MAX_DEPTH {
+            return Err(Error::InvalidInput(format!(
+                "Snippets are nested more than {} levels deep",
+                MAX_DEPTH
+            )));
+        }
+        Ok(snippet)
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        std::fs::write(dir.join(name), content).unwrap();
+    }
+
+    #[test]
+    fn test_expands_snippets_and_variables() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "api-conventions.md",
+            "# API conventions\nUse {{framework}} handlers. {{snippet:errors}}\n",
+        );
+        write(dir.path(), "errors.md", "Return RFC 7807 errors.");
+        write(dir.path(), "loop-a.md", "{{snippet:loop-b}}");
+        write(dir.path(), "loop-b.md", "{{snippet:loop-a}}");
+        write(dir.path(), "notes.txt", "not a snippet");
+
+        let library = SnippetLibrary::load_from(dir.path()).unwrap();
+        assert_eq!(library.list().count(), 4);
+        assert_eq!(
+            library.get("api-conventions").unwrap().summary(),
+            "API conventions"
+        );
+
+        let project = Project::new("shop", "axum", "");
+        let vars = builtin_variables(Some(&project));
+        let expanded = library
+            .expand(
+                "For {{ project }}: {{snippet:api-conventions}} Keep {{name}}.",
+                &vars,
+            )
+            .unwrap();
+        assert_eq!(
+            expanded,
+            "For shop: # API conventions\nUse axum handlers. Return RFC 7807 errors. Keep {{name}}."
+        );
+
+        assert!(matches!(
+            library.expand("{{snippet:missing}}", &vars),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            library.expand("{{snippet:loop-a}}", &vars),
+            Err(Error::InvalidInput(_))
+        ));
+        assert_eq!(library.expand("plain {{", &vars).unwrap(), "plain {{");
+    }
+}
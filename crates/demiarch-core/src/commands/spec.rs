@@ -0,0 +1,601 @@
+//! Spec-driven generation
+//!
+//! A feature spec is a Markdown file whose `##` sections each become a coding
+//! task. `demiarch generate --watch --from-file feature.md` re-runs generation
+//! every time the file changes, but only for the sections that were added or
+//! edited since the last iteration; unchanged sections keep the work from
+//! earlier runs.
+//!
+//! Every iteration is recorded in a ledger with the tasks it ran and the
+//! tokens and cost it used, so the cost of iterating on a spec is visible
+//! across restarts.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::commands::generate::GenerationResult;
+use crate::commands::generation::{self, Generation, GenerationRepository, PlanRun};
+use crate::domain::feature_decomposition::{ExecutionPlan, PlanTask, TaskStatus};
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Default interval between checks of the spec file
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Build an execution plan from a Markdown spec
+///
+/// The first `#` heading names the feature. Each `##` section becomes a
+/// coding task identified by its heading, so editing one section changes only
+/// that task. A spec without `##` sections is a single task.
+pub fn spec_plan(source: &str) -> ExecutionPlan {
+    let mut title: Option<String> = None;
+    let mut preamble = Vec::new();
+    let mut sections: Vec<(String, Vec<&str>)> = Vec::new();
+
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        if let Some(heading) = trimmed.strip_prefix("## ") {
+            sections.push((heading.trim().to_string(), Vec::new()));
+        } else if let Some(heading) = trimmed
+            .strip_prefix("# ")
+            .filter(|_| title.is_none() && sections.is_empty())
+        {
+            title = Some(heading.trim().to_string());
+        } else if let Some((_, body)) = sections.last_mut() {
+            body.push(line);
+        } else {
+            preamble.push(line);
+        }
+    }
+
+    let preamble = preamble.join("\n").trim().to_string();
+    let title = title.unwrap_or_else(|| {
+        preamble
+            .lines()
+            .next()
+            .unwrap_or("Feature spec")
+            .trim()
+            .to_string()
+    });
+
+    let mut plan = ExecutionPlan::new(&title)
+        .with_review(false)
+        .with_tests(false);
+
+    if sections.is_empty() {
+        let description = source.trim().to_string();
+        plan = plan.with_task(PlanTask::coding("spec", description));
+        return plan;
+    }
+
+    let mut ids: Vec<String> = Vec::new();
+    for (heading, body) in sections {
+        let base = slugify(&heading);
+        let mut id = base.clone();
+        let mut n = 2;
+        while ids.contains(&id) {
+            id = format!("{}-{}", base, n);
+            n += 1;
+        }
+        ids.push(id.clone());
+
+        let mut description = format!("{}: {}", title, heading);
+        if !preamble.is_empty() {
+            description.push_str("\n\n");
+            description.push_str(&preamble);
+        }
+        let body = body.join("\n");
+        if !body.trim().is_empty() {
+            description.push_str("\n\n");
+            description.push_str(body.trim());
+        }
+        plan = plan.with_task(PlanTask::coding(id, description));
+    }
+
+    plan
+}
+
+/// Turn a heading into a task id: "User Login (OAuth)" -> "user-login-oauth"
+fn slugify(heading: &str) -> String {
+    let slug = heading
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "section".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Difference between the plans of two spec versions, by task id
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanDiff {
+    /// Tasks that are new in this version
+    pub added: Vec<String>,
+    /// Tasks whose description changed
+    pub changed: Vec<String>,
+    /// Tasks that no longer exist
+    pub removed: Vec<String>,
+    /// Tasks that are unchanged and already done
+    pub unchanged: Vec<String>,
+}
+
+impl PlanDiff {
+    /// Whether any task needs to run
+    pub fn has_work(&self) -> bool {
+        !self.added.is_empty() || !self.changed.is_empty()
+    }
+}
+
+/// Compare a new plan against the previous iteration's plan
+///
+/// A task counts as unchanged only if its description is identical and it
+/// completed last time; unchanged tasks that failed are run again.
+pub fn diff_plans(previous: Option<&ExecutionPlan>, next: &ExecutionPlan) -> PlanDiff {
+    let mut diff = PlanDiff::default();
+
+    for task in &next.tasks {
+        match previous.and_then(|p| p.get_task(&task.id)) {
+            None => diff.added.push(task.id.clone()),
+            Some(old)
+                if old.description.trim() == task.description.trim()
+                    && matches!(old.status, TaskStatus::Completed | TaskStatus::Skipped) =>
+            {
+                diff.unchanged.push(task.id.clone())
+            }
+            Some(_) => diff.changed.push(task.id.clone()),
+        }
+    }
+
+    if let Some(previous) = previous {
+        diff.removed = previous
+            .tasks
+            .iter()
+            .filter(|t| next.get_task(&t.id).is_none())
+            .map(|t| t.id.clone())
+            .collect();
+    }
+
+    diff
+}
+
+/// Build the plan for the next iteration, marking unchanged tasks complete
+pub fn incremental_plan(
+    previous: Option<&ExecutionPlan>,
+    mut next: ExecutionPlan,
+) -> (ExecutionPlan, PlanDiff) {
+    let diff = diff_plans(previous, &next);
+    for task in &mut next.tasks {
+        if diff.unchanged.contains(&task.id) {
+            task.complete();
+        }
+    }
+    (next, diff)
+}
+
+/// One recorded iteration of a watched spec
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecIteration {
+    /// Unique iteration identifier
+    pub id: String,
+    /// Absolute path of the spec file
+    pub spec_path: String,
+    /// Iteration number for this spec, starting at 1
+    pub iteration: i64,
+    /// Hash of the spec contents this iteration ran against
+    pub spec_hash: String,
+    /// Generation created for this iteration
+    pub generation_id: String,
+    /// Tasks that ran
+    pub tasks_run: i64,
+    /// Unchanged tasks that were skipped
+    pub tasks_skipped: i64,
+    /// Tasks that failed
+    pub tasks_failed: i64,
+    /// Tokens used by this iteration
+    pub tokens_used: i64,
+    /// Estimated cost of this iteration in USD
+    pub cost_usd: f64,
+    /// When the iteration ran
+    pub created_at: DateTime<Utc>,
+}
+
+/// Token and cost totals across all iterations of a spec
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpecLedgerTotals {
+    /// Number of iterations
+    pub iterations: i64,
+    /// Tokens used across iterations
+    pub tokens_used: i64,
+    /// Cost across iterations in USD
+    pub cost_usd: f64,
+}
+
+/// Spec iteration ledger repository for database operations
+pub struct SpecLedgerRepository<'a> {
+    db: &'a Database,
+}
+
+impl<'a> SpecLedgerRepository<'a> {
+    /// Create a new spec ledger repository
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    /// Insert an iteration record
+    pub async fn create(&self, iteration: &SpecIteration) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO spec_iterations (id, spec_path, iteration, spec_hash, generation_id, tasks_run, tasks_skipped, tasks_failed, tokens_used, cost_usd, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&iteration.id)
+        .bind(&iteration.spec_path)
+        .bind(iteration.iteration)
+        .bind(&iteration.spec_hash)
+        .bind(&iteration.generation_id)
+        .bind(iteration.tasks_run)
+        .bind(iteration.tasks_skipped)
+        .bind(iteration.tasks_failed)
+        .bind(iteration.tokens_used)
+        .bind(iteration.cost_usd)
+        .bind(iteration.created_at)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// The most recent iteration of a spec
+    pub async fn latest(&self, spec_path: &str) -> Result<Option<SpecIteration>> {
+        let row = sqlx::query(
+            "SELECT * FROM spec_iterations WHERE spec_path = ? ORDER BY iteration DESC LIMIT 1",
+        )
+        .bind(spec_path)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row.map(|r| self.row_to_iteration(r)))
+    }
+
+    /// All iterations of a spec, oldest first
+    pub async fn list(&self, spec_path: &str) -> Result<Vec<SpecIteration>> {
+        let rows =
+            sqlx::query("SELECT * FROM spec_iterations WHERE spec_path = ? ORDER BY iteration")
+                .bind(spec_path)
+                .fetch_all(self.db.pool())
+                .await?;
+
+        Ok(rows.into_iter().map(|r| self.row_to_iteration(r)).collect())
+    }
+
+    /// Token and cost totals across all iterations of a spec
+    pub async fn totals(&self, spec_path: &str) -> Result<SpecLedgerTotals> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS iterations, COALESCE(SUM(tokens_used), 0) AS tokens_used, COALESCE(SUM(cost_usd), 0.0) AS cost_usd FROM spec_iterations WHERE spec_path = ?",
+        )
+        .bind(spec_path)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(SpecLedgerTotals {
+            iterations: row.get("iterations"),
+            tokens_used: row.get("tokens_used"),
+            cost_usd: row.get("cost_usd"),
+        })
+    }
+
+    /// Convert a database row to a SpecIteration
+    fn row_to_iteration(&self, row: sqlx::sqlite::SqliteRow) -> SpecIteration {
+        SpecIteration {
+            id: row.get("id"),
+            spec_path: row.get("spec_path"),
+            iteration: row.get("iteration"),
+            spec_hash: row.get("spec_hash"),
+            generation_id: row.get("generation_id"),
+            tasks_run: row.get("tasks_run"),
+            tasks_skipped: row.get("tasks_skipped"),
+            tasks_failed: row.get("tasks_failed"),
+            tokens_used: row.get("tokens_used"),
+            cost_usd: row.get("cost_usd"),
+            created_at: row.get("created_at"),
+        }
+    }
+}
+
+/// Hash of spec contents, ignoring line-ending and trailing whitespace changes
+pub fn spec_hash(source: &str) -> String {
+    let mut hasher = Sha256::new();
+    for line in source.trim().lines() {
+        hasher.update(line.trim_end().as_bytes());
+        hasher.update([b'\n']);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Outcome of one spec iteration
+#[derive(Debug, Clone)]
+pub struct SpecRun {
+    /// How the spec's plan changed since the previous iteration
+    pub diff: PlanDiff,
+    /// The recorded iteration, if any task ran
+    pub iteration: Option<SpecIteration>,
+    /// The generation run, if any task ran
+    pub run: Option<PlanRun>,
+}
+
+/// Run one iteration of a spec
+///
+/// Builds the plan from `source`, diffs it against the plan of the previous
+/// iteration of the same spec, and runs only added and changed tasks as a new
+/// generation. Nothing is recorded when no task needs to run.
+pub async fn run_iteration<F, Fut>(
+    db: &Database,
+    spec_path: &Path,
+    source: &str,
+    output_dir: &Path,
+    project_id: Option<&str>,
+    run_task: F,
+) -> Result<SpecRun>
+where
+    F: FnMut(PlanTask) -> Fut,
+    Fut: Future<Output = Result<GenerationResult>>,
+{
+    let spec_key = spec_key(spec_path);
+    let ledger = SpecLedgerRepository::new(db);
+    let latest = ledger.latest(&spec_key).await?;
+
+    let previous_plan = match &latest {
+        Some(iteration) => GenerationRepository::new(db)
+            .get(&iteration.generation_id)
+            .await?
+            .and_then(|g| g.plan),
+        None => None,
+    };
+
+    let (plan, diff) = incremental_plan(previous_plan.as_ref(), spec_plan(source));
+    if !diff.has_work() {
+        return Ok(SpecRun {
+            diff,
+            iteration: None,
+            run: None,
+        });
+    }
+
+    let mut new_generation =
+        Generation::new(&plan.feature_description, output_dir.to_string_lossy());
+    if let Some(project_id) = project_id {
+        new_generation = new_generation.with_project(project_id);
+    }
+    let run = generation::start(db, new_generation, plan, run_task).await?;
+
+    let iteration = SpecIteration {
+        id: Uuid::new_v4().to_string(),
+        spec_path: spec_key,
+        iteration: latest.map(|i| i.iteration).unwrap_or(0) + 1,
+        spec_hash: spec_hash(source),
+        generation_id: run.generation.id.clone(),
+        tasks_run: (diff.added.len() + diff.changed.len()) as i64,
+        tasks_skipped: diff.unchanged.len() as i64,
+        tasks_failed: run.failures.len() as i64,
+        tokens_used: run.result.tokens_used as i64,
+        cost_usd: run.result.cost_usd,
+        created_at: Utc::now(),
+    };
+    ledger.create(&iteration).await?;
+
+    Ok(SpecRun {
+        diff,
+        iteration: Some(iteration),
+        run: Some(run),
+    })
+}
+
+/// Ledger key for a spec file: its absolute path
+pub fn spec_key(spec_path: &Path) -> String {
+    std::fs::canonicalize(spec_path)
+        .unwrap_or_else(|_| spec_path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Polls a spec file for content changes
+///
+/// Changes are detected by content hash, so saves that don't change the text
+/// (or only touch whitespace at line ends) don't trigger a regeneration.
+pub struct SpecWatcher {
+    path: PathBuf,
+    interval: Duration,
+    last_hash: Option<String>,
+}
+
+impl SpecWatcher {
+    /// Watch a spec file
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            interval: DEFAULT_POLL_INTERVAL,
+            last_hash: None,
+        }
+    }
+
+    /// Set the polling interval
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Read the spec if its contents changed since the last call
+    ///
+    /// The first call always returns the contents. A missing or unreadable
+    /// file (e.g. mid-save by an editor) counts as unchanged.
+    pub fn poll(&mut self) -> Option<String> {
+        let source = std::fs::read_to_string(&self.path).ok()?;
+        let hash = spec_hash(&source);
+        if self.last_hash.as_deref() == Some(hash.as_str()) {
+            return None;
+        }
+        self.last_hash = Some(hash);
+        Some(source)
+    }
+
+    /// Wait until the spec changes and return its new contents
+    ///
+    /// Waits one more interval after a change is seen so a burst of saves is
+    /// handled as a single change.
+    pub async fn changed(&mut self) -> Result<String> {
+        loop {
+            tokio::time::sleep(self.interval).await;
+            if let Some(mut source) = self.poll() {
+                tokio::time::sleep(self.interval).await;
+                if let Some(latest) = self.poll() {
+                    source = latest;
+                }
+                return Ok(source);
+            }
+            if !self.path.exists() {
+                return Err(Error::InvalidInput(format!(
+                    "Spec file {} no longer exists",
+                    self.path.display()
+                )));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::generate::GeneratedFile;
+
+    const SPEC: &str = "# Todo API\n\nA REST API for todos.\n\n## Models\n\nTodo with title and done flag.\n\n## Routes\n\nCRUD endpoints.\n";
+
+    fn task_result(path: &str, tokens: u32) -> GenerationResult {
+        GenerationResult {
+            files_created: 1,
+            files_modified: 0,
+            tokens_used: tokens,
+            cost_usd: tokens as f64 / 10_000.0,
+            files: vec![GeneratedFile {
+                path: PathBuf::from(path),
+                content: "pub fn f() {}\n".to_string(),
+                is_new: true,
+                language: Some("rust".to_string()),
+                validation: None,
+                lint_findings: Vec::new(),
+                security_findings: Vec::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_spec_plan_sections() {
+        let plan = spec_plan(SPEC);
+        assert_eq!(plan.feature_description, "Todo API");
+        let ids: Vec<_> = plan.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["models", "routes"]);
+        let models = plan.get_task("models").unwrap();
+        assert!(models.description.starts_with("Todo API: Models"));
+        assert!(models.description.contains("A REST API for todos."));
+        assert!(models.description.contains("title and done flag"));
+
+        let single = spec_plan("Add a health check endpoint");
+        assert_eq!(single.tasks.len(), 1);
+        assert_eq!(single.tasks[0].id, "spec");
+    }
+
+    #[test]
+    fn test_diff_plans() {
+        let mut previous = spec_plan(SPEC);
+        for task in &mut previous.tasks {
+            task.complete();
+        }
+        let edited = SPEC.replace("CRUD endpoints.", "CRUD endpoints with paging.")
+            + "\n## Auth\n\nToken auth.\n";
+        let (plan, diff) = incremental_plan(Some(&previous), spec_plan(&edited));
+
+        assert_eq!(diff.added, vec!["auth"]);
+        assert_eq!(diff.changed, vec!["routes"]);
+        assert_eq!(diff.unchanged, vec!["models"]);
+        assert!(diff.has_work());
+        assert_eq!(
+            plan.get_task("models").unwrap().status,
+            TaskStatus::Completed
+        );
+        assert_eq!(plan.get_task("routes").unwrap().status, TaskStatus::Pending);
+
+        let (_, same) = incremental_plan(Some(&previous), spec_plan(SPEC));
+        assert!(!same.has_work());
+    }
+
+    #[tokio::test]
+    async fn test_iterations_only_rerun_changed_sections() {
+        let db = Database::in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let spec_path = dir.path().join("feature.md");
+
+        let mut calls = Vec::new();
+        let first = run_iteration(&db, &spec_path, SPEC, dir.path(), None, |task| {
+            calls.push(task.id.clone());
+            let path = format!("src/{}.rs", task.id);
+            async move { Ok(task_result(&path, 100)) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(calls, vec!["models", "routes"]);
+        assert_eq!(first.iteration.unwrap().iteration, 1);
+
+        let edited = SPEC.replace("CRUD endpoints.", "CRUD endpoints with paging.");
+        let mut calls = Vec::new();
+        let second = run_iteration(&db, &spec_path, &edited, dir.path(), None, |task| {
+            calls.push(task.id.clone());
+            let path = format!("src/{}.rs", task.id);
+            async move { Ok(task_result(&path, 40)) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(calls, vec!["routes"]);
+        let iteration = second.iteration.unwrap();
+        assert_eq!(iteration.iteration, 2);
+        assert_eq!((iteration.tasks_run, iteration.tasks_skipped), (1, 1));
+        assert_eq!(iteration.tokens_used, 40);
+
+        let unchanged = run_iteration(&db, &spec_path, &edited, dir.path(), None, |_| async {
+            Ok(GenerationResult::default())
+        })
+        .await
+        .unwrap();
+        assert!(unchanged.iteration.is_none());
+
+        let ledger = SpecLedgerRepository::new(&db);
+        let totals = ledger.totals(&spec_key(&spec_path)).await.unwrap();
+        assert_eq!(totals.iterations, 2);
+        assert_eq!(totals.tokens_used, 240);
+    }
+
+    #[test]
+    fn test_watcher_ignores_whitespace_only_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("feature.md");
+        std::fs::write(&path, "# Spec\n").unwrap();
+
+        let mut watcher = SpecWatcher::new(&path);
+        assert_eq!(watcher.poll().as_deref(), Some("# Spec\n"));
+        assert!(watcher.poll().is_none());
+        std::fs::write(&path, "# Spec   \r\n").unwrap();
+        assert!(watcher.poll().is_none());
+        std::fs::write(&path, "# Spec\n\n## Models\n").unwrap();
+        assert!(watcher.poll().is_some());
+    }
+}
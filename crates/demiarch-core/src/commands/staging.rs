@@ -0,0 +1,408 @@
+//! Write-ahead staging of generated files
+//!
+//! Generated files are never written straight into a project. Each batch is
+//! first written under `.demiarch/staging/<id>/` next to the files it
+//! replaces, with copies of any files it overwrites, and a manifest listing
+//! every write. Only then are the files renamed into place one by one; a
+//! failed rename puts back the files already moved. Staging inside the
+//! output directory keeps each rename on one filesystem, so a file is
+//! either the old version or the new one, never half written.
+//!
+//! The manifest is saved after every step. If the process dies while
+//! applying, [`pending`] finds the batch and [`recover`] either completes
+//! it or undoes the files that were already moved.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{Error, Result};
+
+/// Where staged batches live, relative to the output directory
+pub const STAGING_DIR: &str = ".demiarch/staging";
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// How far a staged batch got
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StagingPhase {
+    /// Files are still being staged; nothing in the project has changed
+    Staging,
+    /// Files are being moved into place
+    Applying,
+}
+
+/// One file of a staged batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StagedWrite {
+    /// Path relative to the output directory
+    pub path: String,
+    /// Whether a file already existed at `path` and was backed up
+    pub had_original: bool,
+    /// Whether the staged file has been moved into place
+    pub moved: bool,
+}
+
+/// The record of a staged batch, saved as `manifest.json` in its directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StagingManifest {
+    pub id: String,
+    /// What the batch writes, such as a generation ID
+    pub label: String,
+    pub phase: StagingPhase,
+    pub created_at: DateTime<Utc>,
+    pub writes: Vec<StagedWrite>,
+}
+
+impl StagingManifest {
+    /// Files already moved into place
+    pub fn moved(&self) -> usize {
+        self.writes.iter().filter(|w| w.moved).count()
+    }
+}
+
+/// How to finish an interrupted batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Move the remaining staged files into place
+    Complete,
+    /// Restore the files that were already replaced
+    Undo,
+}
+
+/// A batch of file writes staged under an output directory
+#[derive(Debug)]
+pub struct Staging {
+    root: PathBuf,
+    dir: PathBuf,
+    manifest: StagingManifest,
+}
+
+impl Staging {
+    /// Start a batch under `root`
+    pub fn begin(root: impl Into<PathBuf>, label: impl Into<String>) -> Result<Self> {
+        let root = root.into();
+        let id = Uuid::new_v4().to_string();
+        let dir = root.join(STAGING_DIR).join(&id);
+        fs::create_dir_all(dir.join("files"))?;
+        fs::create_dir_all(dir.join("originals"))?;
+        let staging = Self {
+            root,
+            dir,
+            manifest: StagingManifest {
+                id,
+                label: label.into(),
+                phase: StagingPhase::Staging,
+                created_at: Utc::now(),
+                writes: Vec::new(),
+            },
+        };
+        staging.save()?;
+        Ok(staging)
+    }
+
+    /// Stage `content` for `path`, relative to the output directory, backing
+    /// up the file it will replace
+    pub fn stage(&mut self, path: &str, content: &str) -> Result<()> {
+        let index = self.manifest.writes.len();
+        let target = self.root.join(path);
+        let had_original = target.is_file();
+        if had_original {
+            fs::copy(&target, original_path(&self.dir, index))?;
+        }
+        write_synced(&staged_path(&self.dir, index), content.as_bytes())?;
+        self.manifest.writes.push(StagedWrite {
+            path: path.to_string(),
+            had_original,
+            moved: false,
+        });
+        self.save()
+    }
+
+    /// Move every staged file into place, putting back the ones already
+    /// moved if one fails, and remove the staging directory
+    pub fn commit(mut self) -> Result<()> {
+        self.manifest.phase = StagingPhase::Applying;
+        self.save()?;
+        for index in 0..self.manifest.writes.len() {
+            if let Err(e) = self.move_into_place(index) {
+                tracing::warn!(
+                    staging = %self.manifest.id,
+                    path = %self.manifest.writes[index].path,
+                    error = %e,
+                    "Staged write failed; rolling back"
+                );
+                return match self.undo() {
+                    Ok(()) => Err(e),
+                    Err(undo) => Err(Error::Other(format!(
+                        "{}; rolling back also failed ({}), run `demiarch generations recover --dir {}`",
+                        e,
+                        undo,
+                        self.root.display()
+                    ))),
+                };
+            }
+        }
+        self.finish()
+    }
+
+    /// Drop the batch without touching the project
+    pub fn discard(self) -> Result<()> {
+        self.finish()
+    }
+
+    fn move_into_place(&mut self, index: usize) -> Result<()> {
+        let target = self.root.join(&self.manifest.writes[index].path);
+        if let Some(parent) = target.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        fs::rename(staged_path(&self.dir, index), &target)?;
+        self.manifest.writes[index].moved = true;
+        self.save()
+    }
+
+    /// Restore moved files from their backups, newest first
+    fn undo(&mut self) -> Result<()> {
+        for index in (0..self.manifest.writes.len()).rev() {
+            let write = &self.manifest.writes[index];
+            // A rename can land before the manifest records it
+            let moved = write.moved || !staged_path(&self.dir, index).exists();
+            if !moved || self.manifest.phase == StagingPhase::Staging {
+                continue;
+            }
+            let target = self.root.join(&write.path);
+            let original = original_path(&self.dir, index);
+            if write.had_original {
+                // Already restored if the backup is gone
+                if original.exists() {
+                    fs::rename(&original, &target)?;
+                }
+            } else if target.exists() {
+                fs::remove_file(&target)?;
+            }
+            self.manifest.writes[index].moved = false;
+            self.save()?;
+        }
+        self.finish()
+    }
+
+    fn finish(&self) -> Result<()> {
+        fs::remove_dir_all(&self.dir)?;
+        Ok(())
+    }
+
+    /// Save the manifest so an interrupted batch can be recovered
+    fn save(&self) -> Result<()> {
+        let json =
+            serde_json::to_vec_pretty(&self.manifest).map_err(|e| Error::Parse(e.to_string()))?;
+        let tmp = self.dir.join(format!("{}.tmp", MANIFEST_FILE));
+        write_synced(&tmp, &json)?;
+        fs::rename(&tmp, self.dir.join(MANIFEST_FILE))?;
+        Ok(())
+    }
+
+    fn open(root: &Path, id: &str) -> Result<Self> {
+        let dir = root.join(STAGING_DIR).join(id);
+        let json = fs::read(dir.join(MANIFEST_FILE))
+            .map_err(|_| Error::NotFound(format!("No interrupted apply '{}'", id)))?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            dir,
+            manifest: serde_json::from_slice(&json).map_err(|e| Error::Parse(e.to_string()))?,
+        })
+    }
+}
+
+fn staged_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join("files").join(index.to_string())
+}
+
+fn original_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join("originals").join(index.to_string())
+}
+
+fn write_synced(path: &Path, bytes: &[u8]) -> Result<()> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Write `files` (path relative to `root`, content) to `root` through a
+/// staged batch
+pub fn write_all<'a>(
+    root: &Path,
+    label: &str,
+    files: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<()> {
+    let mut staging = Staging::begin(root, label)?;
+    for (path, content) in files {
+        if let Err(e) = staging.stage(path, content) {
+            let _ = staging.discard();
+            return Err(e);
+        }
+    }
+    staging.commit()
+}
+
+/// Batches under `root` that were interrupted, oldest first
+pub fn pending(root: &Path) -> Result<Vec<StagingManifest>> {
+    let dir = root.join(STAGING_DIR);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut manifests = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        let path = entry.path().join(MANIFEST_FILE);
+        match fs::read(&path).map(|json| serde_json::from_slice::<StagingManifest>(&json)) {
+            Ok(Ok(manifest)) => manifests.push(manifest),
+            Ok(Err(e)) => {
+                tracing::warn!(path = %path.display(), error = %e, "Unreadable staging manifest")
+            }
+            Err(_) => {}
+        }
+    }
+    manifests.sort_by_key(|m| m.created_at);
+    Ok(manifests)
+}
+
+/// Fail if `root` has an interrupted batch, so a new one is not applied on
+/// top of a half-applied project
+pub fn ensure_clean(root: &Path) -> Result<()> {
+    match pending(root)?.first() {
+        Some(manifest) => Err(Error::InvalidInput(format!(
+            "An earlier apply of {} in {} was interrupted after {} of {} file(s); run `demiarch generations recover --dir {}` first",
+            manifest.label,
+            root.display(),
+            manifest.moved(),
+            manifest.writes.len(),
+            root.display()
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Complete or undo the interrupted batch `id` under `root`
+///
+/// A batch interrupted while staging never touched the project and can only
+/// be undone (discarded).
+pub fn recover(root: &Path, id: &str, action: RecoveryAction) -> Result<StagingManifest> {
+    let mut staging = Staging::open(root, id)?;
+    let manifest = staging.manifest.clone();
+    match action {
+        RecoveryAction::Undo => staging.undo()?,
+        RecoveryAction::Complete => {
+            if staging.manifest.phase == StagingPhase::Staging {
+                return Err(Error::InvalidInput(format!(
+                    "Apply of {} was interrupted while staging, so its files are incomplete; undo it and apply again",
+                    staging.manifest.label
+                )));
+            }
+            for index in 0..staging.manifest.writes.len() {
+                let moved = staging.manifest.writes[index].moved
+                    || !staged_path(&staging.dir, index).exists();
+                if !moved {
+                    staging.move_into_place(index)?;
+                }
+            }
+            staging.finish()?;
+        }
+    }
+    tracing::info!(staging = %manifest.id, label = %manifest.label, ?action, "Recovered interrupted apply");
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(root: &Path, path: &str) -> Option<String> {
+        fs::read_to_string(root.join(path)).ok()
+    }
+
+    #[test]
+    fn test_commit_writes_and_cleans_up() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "old\n").unwrap();
+
+        write_all(
+            dir.path(),
+            "gen-1",
+            [("lib.rs", "new\n"), ("src/mod.rs", "pub mod a;\n")],
+        )
+        .unwrap();
+
+        assert_eq!(read(dir.path(), "lib.rs").as_deref(), Some("new\n"));
+        assert_eq!(
+            read(dir.path(), "src/mod.rs").as_deref(),
+            Some("pub mod a;\n")
+        );
+        assert!(pending(dir.path()).unwrap().is_empty());
+        assert!(ensure_clean(dir.path()).is_ok());
+    }
+
+    #[test]
+    fn test_failed_move_rolls_back() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "old\n").unwrap();
+        // A directory where the second file should go makes its rename fail
+        fs::create_dir_all(dir.path().join("blocked/inner")).unwrap();
+
+        let result = write_all(
+            dir.path(),
+            "gen-1",
+            [
+                ("lib.rs", "new\n"),
+                ("new.rs", "fn a() {}\n"),
+                ("blocked", "x"),
+            ],
+        );
+
+        assert!(result.is_err());
+        assert_eq!(read(dir.path(), "lib.rs").as_deref(), Some("old\n"));
+        assert!(!dir.path().join("new.rs").exists());
+        assert!(pending(dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recover_interrupted_apply() {
+        for action in [RecoveryAction::Complete, RecoveryAction::Undo] {
+            let dir = tempfile::tempdir().unwrap();
+            fs::write(dir.path().join("a.rs"), "old a\n").unwrap();
+            let mut staging = Staging::begin(dir.path(), "gen-2").unwrap();
+            staging.stage("a.rs", "new a\n").unwrap();
+            staging.stage("b.rs", "new b\n").unwrap();
+            // Crash after the first file was moved
+            staging.manifest.phase = StagingPhase::Applying;
+            staging.save().unwrap();
+            staging.move_into_place(0).unwrap();
+            drop(staging);
+
+            let interrupted = pending(dir.path()).unwrap();
+            assert_eq!(interrupted.len(), 1);
+            assert_eq!(interrupted[0].moved(), 1);
+            assert!(ensure_clean(dir.path()).is_err());
+
+            recover(dir.path(), &interrupted[0].id, action).unwrap();
+            assert!(pending(dir.path()).unwrap().is_empty());
+            match action {
+                RecoveryAction::Complete => {
+                    assert_eq!(read(dir.path(), "a.rs").as_deref(), Some("new a\n"));
+                    assert_eq!(read(dir.path(), "b.rs").as_deref(), Some("new b\n"));
+                }
+                RecoveryAction::Undo => {
+                    assert_eq!(read(dir.path(), "a.rs").as_deref(), Some("old a\n"));
+                    assert!(!dir.path().join("b.rs").exists());
+                }
+            }
+        }
+    }
+}
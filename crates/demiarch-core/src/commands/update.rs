@@ -0,0 +1,509 @@
+//! Self-update from the signed release channels
+//!
+//! Each channel (stable, beta) publishes a release manifest signed by the
+//! same ed25519 issuer key as licenses, in the same `<payload>.<signature>`
+//! form. The manifest lists one artifact per platform with its SHA-256 and an
+//! ed25519 signature over the binary. `demiarch self update` only swaps in a
+//! binary when the manifest, the digest and the binary signature all verify.
+//!
+//! The passive "new version available" notice reuses the last check for a
+//! day, so `doctor` and the GUI do not hit the network on every run.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::commands::license::{issuer_key_from_env, verify_signed};
+use crate::config::UpdatesConfig;
+use crate::infrastructure::network;
+use crate::{Error, Result};
+
+/// Version of the running binary
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// How long a check answers the passive notice
+const NOTICE_TTL_HOURS: i64 = 24;
+
+/// Time allowed for fetching the manifest
+const MANIFEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time allowed for downloading a binary
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Release channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    Stable,
+    Beta,
+}
+
+impl Channel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Beta => "beta",
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Channel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "stable" => Ok(Self::Stable),
+            "beta" => Ok(Self::Beta),
+            _ => Err(Error::InvalidInput(format!(
+                "Unknown release channel '{}'. Use stable or beta",
+                s
+            ))),
+        }
+    }
+}
+
+/// The signed content of a channel's release manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    pub channel: Channel,
+    pub version: String,
+    pub published_at: DateTime<Utc>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    pub artifacts: Vec<ReleaseArtifact>,
+}
+
+impl ReleaseManifest {
+    pub fn artifact_for(&self, target: &str) -> Option<&ReleaseArtifact> {
+        self.artifacts.iter().find(|a| a.target == target)
+    }
+}
+
+/// A downloadable binary for one platform
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseArtifact {
+    /// `<os>-<arch>`, e.g. `linux-x86_64` (see [`platform_target`])
+    pub target: String,
+    pub url: String,
+    /// Hex SHA-256 of the binary
+    pub sha256: String,
+    /// base64url ed25519 signature over the binary
+    pub signature: String,
+}
+
+/// Result of comparing the running version with a channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateStatus {
+    pub channel: Channel,
+    pub current_version: String,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub notes: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl UpdateStatus {
+    fn from_manifest(manifest: &ReleaseManifest) -> Self {
+        Self {
+            channel: manifest.channel,
+            current_version: CURRENT_VERSION.to_string(),
+            latest_version: manifest.version.clone(),
+            update_available: is_newer(&manifest.version, CURRENT_VERSION),
+            notes: manifest.notes.clone(),
+            checked_at: Utc::now(),
+        }
+    }
+}
+
+/// Artifact target of the running platform
+pub fn platform_target() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// Whether `candidate` is a later version than `current`
+///
+/// Versions are dotted numbers with an optional `-prerelease` suffix; a
+/// prerelease sorts before the release it precedes. Unparseable versions
+/// are never newer.
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    match (parse_version(candidate), parse_version(current)) {
+        (Some(candidate), Some(current)) => compare_versions(&candidate, &current).is_gt(),
+        _ => false,
+    }
+}
+
+type Version = (Vec<u64>, Option<String>);
+
+fn parse_version(version: &str) -> Option<Version> {
+    let version = version.trim().trim_start_matches('v');
+    let (numbers, pre) = match version.split_once('-') {
+        Some((numbers, pre)) => (numbers, Some(pre.to_string())),
+        None => (version, None),
+    };
+    let numbers = numbers
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    Some((numbers, pre))
+}
+
+fn compare_versions(a: &Version, b: &Version) -> Ordering {
+    let len = a.0.len().max(b.0.len());
+    for i in 0..len {
+        let ordering = a.0.get(i).unwrap_or(&0).cmp(b.0.get(i).unwrap_or(&0));
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+    match (&a.1, &b.1) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => a.cmp(b),
+    }
+}
+
+/// Verify a signed manifest envelope and return the manifest
+pub fn verify_manifest(envelope: &str, issuer: &VerifyingKey) -> Result<ReleaseManifest> {
+    let payload = verify_signed(envelope, issuer)
+        .map_err(|reason| Error::UpdateRejected(format!("release manifest: {}", reason)))?;
+    serde_json::from_slice(&payload)
+        .map_err(|e| Error::UpdateRejected(format!("unreadable release manifest: {}", e)))
+}
+
+/// Check a downloaded binary against its manifest entry
+pub fn verify_artifact(
+    artifact: &ReleaseArtifact,
+    bytes: &[u8],
+    issuer: &VerifyingKey,
+) -> Result<()> {
+    let digest = format!("{:x}", Sha256::digest(bytes));
+    if !digest.eq_ignore_ascii_case(artifact.sha256.trim()) {
+        return Err(Error::UpdateRejected(format!(
+            "{} does not match the published SHA-256",
+            artifact.url
+        )));
+    }
+    let signature = URL_SAFE_NO_PAD
+        .decode(artifact.signature.trim())
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or_else(|| Error::UpdateRejected("malformed binary signature".to_string()))?;
+    issuer.verify(bytes, &signature).map_err(|_| {
+        Error::UpdateRejected("binary signature does not match the trusted issuer".to_string())
+    })
+}
+
+/// Replace the executable at `target` with `bytes`
+///
+/// The new binary is written next to the old one and renamed over it, so the
+/// swap is atomic on the same filesystem. Windows cannot overwrite a running
+/// executable, so there the old one is first moved aside.
+pub fn replace_executable(target: &Path, bytes: &[u8]) -> Result<()> {
+    let dir = target
+        .parent()
+        .ok_or_else(|| Error::Other(format!("{} has no parent directory", target.display())))?;
+    let name = target
+        .file_name()
+        .ok_or_else(|| Error::Other(format!("{} is not a file", target.display())))?
+        .to_string_lossy()
+        .to_string();
+    let staged = dir.join(format!(".{}.new", name));
+    fs::write(&staged, bytes)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(target)
+            .map(|m| m.permissions().mode())
+            .unwrap_or(0o755);
+        fs::set_permissions(&staged, fs::Permissions::from_mode(mode))?;
+    }
+
+    #[cfg(windows)]
+    {
+        let backup = dir.join(format!(".{}.old", name));
+        let _ = fs::remove_file(&backup);
+        fs::rename(target, &backup)?;
+        if let Err(e) = fs::rename(&staged, target) {
+            let _ = fs::rename(&backup, target);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(windows))]
+    {
+        if let Err(e) = fs::rename(&staged, target) {
+            let _ = fs::remove_file(&staged);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+/// Checks channels and installs verified releases
+pub struct Updater {
+    manifest_url: String,
+    issuer: Option<VerifyingKey>,
+    cache_path: PathBuf,
+}
+
+impl Updater {
+    pub fn new(config: &UpdatesConfig) -> Self {
+        Self {
+            manifest_url: config.manifest_url.clone(),
+            issuer: None,
+            cache_path: default_cache_path(),
+        }
+    }
+
+    /// Verify against this issuer key instead of `DEMIARCH_LICENSE_ISSUER_KEY`
+    pub fn with_issuer_key(mut self, issuer: VerifyingKey) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
+
+    /// Keep the last check result here instead of `~/.demiarch/update-check.json`
+    pub fn with_cache_path(mut self, path: PathBuf) -> Self {
+        self.cache_path = path;
+        self
+    }
+
+    fn issuer(&self) -> Result<VerifyingKey> {
+        match self.issuer {
+            Some(issuer) => Ok(issuer),
+            None => issuer_key_from_env()
+                .map_err(|e| Error::UpdateRejected(format!("no trusted key: {}", e))),
+        }
+    }
+
+    /// Download and verify a channel's manifest
+    pub async fn fetch_manifest(&self, channel: Channel) -> Result<ReleaseManifest> {
+        network::ensure_online("update check")?;
+        let url = self.manifest_url.replace("{channel}", channel.as_str());
+        let client = reqwest::Client::builder()
+            .timeout(MANIFEST_TIMEOUT)
+            .build()?;
+        let envelope = client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let manifest = verify_manifest(&envelope, &self.issuer()?)?;
+        if manifest.channel != channel {
+            return Err(Error::UpdateRejected(format!(
+                "manifest at {} is for the {} channel",
+                url, manifest.channel
+            )));
+        }
+        Ok(manifest)
+    }
+
+    /// Compare the running version with a channel and remember the answer
+    pub async fn check(&self, channel: Channel) -> Result<UpdateStatus> {
+        let manifest = self.fetch_manifest(channel).await?;
+        let status = UpdateStatus::from_manifest(&manifest);
+        self.save_cache(&status);
+        Ok(status)
+    }
+
+    /// Install the channel's release over the running binary
+    ///
+    /// Returns `None` when the running version is already current.
+    pub async fn install(&self, channel: Channel) -> Result<Option<UpdateStatus>> {
+        let manifest = self.fetch_manifest(channel).await?;
+        let status = UpdateStatus::from_manifest(&manifest);
+        self.save_cache(&status);
+        if !status.update_available {
+            return Ok(None);
+        }
+
+        let target = platform_target();
+        let artifact = manifest.artifact_for(&target).ok_or_else(|| {
+            Error::UpdateRejected(format!(
+                "release {} has no binary for {}",
+                manifest.version, target
+            ))
+        })?;
+        let client = reqwest::Client::builder()
+            .timeout(DOWNLOAD_TIMEOUT)
+            .build()?;
+        let bytes = client
+            .get(&artifact.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        verify_artifact(artifact, &bytes, &self.issuer()?)?;
+
+        replace_executable(&std::env::current_exe()?, &bytes)?;
+        tracing::info!(version = %manifest.version, "Installed update");
+        Ok(Some(UpdateStatus {
+            update_available: false,
+            ..status
+        }))
+    }
+
+    /// Newer release on `channel`, for passive notices
+    ///
+    /// Uses the last check if it is recent, otherwise checks again. Never
+    /// fails: offline mode, network errors and bad manifests mean no notice.
+    pub async fn notice(&self, channel: Channel) -> Option<UpdateStatus> {
+        let status = match self.cached(channel) {
+            Some(status) => status,
+            None if network::is_offline() => return None,
+            None => match self.check(channel).await {
+                Ok(status) => status,
+                Err(e) => {
+                    tracing::debug!(error = %e, "Update check failed");
+                    return None;
+                }
+            },
+        };
+        // The cache may predate an update installed since
+        let newer = status.update_available && is_newer(&status.latest_version, CURRENT_VERSION);
+        newer.then_some(status)
+    }
+
+    fn cached(&self, channel: Channel) -> Option<UpdateStatus> {
+        let data = fs::read_to_string(&self.cache_path).ok()?;
+        let status: UpdateStatus = serde_json::from_str(&data).ok()?;
+        let fresh = Utc::now() - status.checked_at < chrono::Duration::hours(NOTICE_TTL_HOURS);
+        (fresh && status.channel == channel).then_some(status)
+    }
+
+    fn save_cache(&self, status: &UpdateStatus) {
+        let result = self
+            .cache_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                fs::write(
+                    &self.cache_path,
+                    serde_json::to_string(status).unwrap_or_default(),
+                )
+            });
+        if let Err(e) = result {
+            tracing::debug!(error = %e, "Failed to cache update check");
+        }
+    }
+}
+
+fn default_cache_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".demiarch")
+        .join("update-check.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn sign(signer: &SigningKey, manifest: &ReleaseManifest) -> String {
+        let payload = serde_json::to_vec(manifest).unwrap();
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(&payload),
+            URL_SAFE_NO_PAD.encode(signer.sign(&payload).to_bytes())
+        )
+    }
+
+    fn manifest(signer: &SigningKey, binary: &[u8]) -> ReleaseManifest {
+        ReleaseManifest {
+            channel: Channel::Beta,
+            version: "99.0.0-beta.1".to_string(),
+            published_at: Utc::now(),
+            notes: None,
+            artifacts: vec![ReleaseArtifact {
+                target: platform_target(),
+                url: "https://example.com/demiarch".to_string(),
+                sha256: format!("{:x}", Sha256::digest(binary)),
+                signature: URL_SAFE_NO_PAD.encode(signer.sign(binary).to_bytes()),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_compares_versions() {
+        assert!(is_newer("0.2.0", "0.1.9"));
+        assert!(is_newer("v1.0", "0.9.9"));
+        assert!(is_newer("1.0.0", "1.0.0-beta.2"));
+        assert!(is_newer("1.0.0-beta.2", "1.0.0-beta.1"));
+        assert!(!is_newer("1.0.0-beta.1", "1.0.0"));
+        assert!(!is_newer("0.1.0", "0.1"));
+        assert!(!is_newer("latest", "0.1.0"));
+    }
+
+    #[test]
+    fn test_verifies_manifest_and_binary() {
+        let signer = SigningKey::from_bytes(&[3; 32]);
+        let other = SigningKey::from_bytes(&[4; 32]);
+        let binary = b"new demiarch binary";
+        let release = manifest(&signer, binary);
+
+        let verified = verify_manifest(&sign(&signer, &release), &signer.verifying_key()).unwrap();
+        assert_eq!(verified, release);
+        assert!(matches!(
+            verify_manifest(&sign(&other, &release), &signer.verifying_key()),
+            Err(Error::UpdateRejected(_))
+        ));
+
+        let artifact = verified.artifact_for(&platform_target()).unwrap();
+        verify_artifact(artifact, binary, &signer.verifying_key()).unwrap();
+        assert!(verify_artifact(artifact, b"tampered", &signer.verifying_key()).is_err());
+        let mut forged = artifact.clone();
+        forged.signature = URL_SAFE_NO_PAD.encode(other.sign(binary).to_bytes());
+        assert!(verify_artifact(&forged, binary, &signer.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn test_replaces_executable_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("demiarch");
+        fs::write(&exe, b"old").unwrap();
+        replace_executable(&exe, b"new").unwrap();
+        assert_eq!(fs::read(&exe).unwrap(), b"new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_notice_uses_fresh_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let updater = Updater::new(&UpdatesConfig::default())
+            .with_cache_path(dir.path().join("update-check.json"));
+        let status = UpdateStatus {
+            channel: Channel::Stable,
+            current_version: CURRENT_VERSION.to_string(),
+            latest_version: "99.0.0".to_string(),
+            update_available: true,
+            notes: None,
+            checked_at: Utc::now(),
+        };
+        updater.save_cache(&status);
+
+        let notice = updater.notice(Channel::Stable).await.unwrap();
+        assert_eq!(notice.latest_version, "99.0.0");
+    }
+}
@@ -0,0 +1,708 @@
+//! Framework upgrade assistant
+//!
+//! `demiarch upgrade-assist --target nextjs@15` scans the project for code
+//! affected by the upgrade, using a built-in guide of migration rules for the
+//! target, and reports every location per rule. Each rule with matches
+//! becomes a migration step; steps are planned as project features so they
+//! can be tracked, and mechanical steps (renames, awaiting newly async APIs,
+//! package bumps) can be handed to generation. Manual steps, which need a
+//! decision about behavior, are listed as remaining work.
+//!
+//! Re-running the assistant reuses the features it created earlier, so the
+//! report can be refreshed as steps are completed.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::feature::{Feature, FeatureRepository, FeatureStatus};
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Directories never scanned
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    ".git",
+    ".next",
+    ".demiarch",
+    "target",
+    "dist",
+    "build",
+    "out",
+    "coverage",
+    ".venv",
+    "venv",
+    "__pycache__",
+];
+
+/// Files larger than this are not scanned
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Locations listed per step in a feature description
+const MAX_LISTED_LOCATIONS: usize = 20;
+
+/// Longest source excerpt kept per location
+const MAX_EXCERPT_LEN: usize = 120;
+
+const SCRIPT_EXTENSIONS: &[&str] = &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"];
+
+/// Whether a step can be done by generation or needs a decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepKind {
+    /// A transformation that follows from the code, e.g. a rename
+    Mechanical,
+    /// A change in behavior someone has to decide how to handle
+    Manual,
+}
+
+impl StepKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mechanical => "mechanical",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+/// One migration rule of an upgrade guide
+struct Rule {
+    id: &'static str,
+    title: &'static str,
+    kind: StepKind,
+    /// Substrings that mark affected code
+    patterns: &'static [&'static str],
+    /// File names the rule applies to; empty for any file with `extensions`
+    file_names: &'static [&'static str],
+    extensions: &'static [&'static str],
+    instructions: &'static str,
+}
+
+/// Migration rules for upgrading one package to a major version
+struct Guide {
+    name: &'static str,
+    aliases: &'static [&'static str],
+    /// npm package whose version is upgraded
+    package: &'static str,
+    major: u32,
+    rules: &'static [Rule],
+}
+
+const USE_ACTION_STATE: Rule = Rule {
+    id: "use-action-state",
+    title: "Replace useFormState with useActionState",
+    kind: StepKind::Mechanical,
+    patterns: &["useFormState"],
+    file_names: &[],
+    extensions: SCRIPT_EXTENSIONS,
+    instructions: "useFormState from react-dom is replaced by useActionState from react. \
+                   Change the import and the call; the arguments are the same.",
+};
+
+const GUIDES: &[Guide] = &[
+    Guide {
+        name: "nextjs",
+        aliases: &["nextjs", "next", "next.js"],
+        package: "next",
+        major: 15,
+        rules: &[
+            Rule {
+                id: "packages",
+                title: "Upgrade next, react and react-dom",
+                kind: StepKind::Mechanical,
+                patterns: &["\"next\":"],
+                file_names: &["package.json"],
+                extensions: &[],
+                instructions: "Set next and eslint-config-next to ^15, react and react-dom to \
+                               ^19, and @types/react and @types/react-dom to ^19 if present.",
+            },
+            Rule {
+                id: "async-request-apis",
+                title: "Await async request APIs",
+                kind: StepKind::Mechanical,
+                patterns: &["cookies()", "headers()", "draftMode()"],
+                file_names: &[],
+                extensions: SCRIPT_EXTENSIONS,
+                instructions: "cookies(), headers() and draftMode() return promises. Await them \
+                               (`const cookieStore = await cookies()`) and make the calling \
+                               function async.",
+            },
+            Rule {
+                id: "async-params",
+                title: "Await params and searchParams",
+                kind: StepKind::Mechanical,
+                patterns: &["{ params }", "{ params,", "searchParams"],
+                file_names: &[],
+                extensions: SCRIPT_EXTENSIONS,
+                instructions: "params and searchParams passed to pages, layouts, route handlers \
+                               and generateMetadata are promises. Await them before reading \
+                               properties and update their types to Promise<...>; in client \
+                               components unwrap them with React.use().",
+            },
+            Rule {
+                id: "next-font",
+                title: "Move from @next/font to next/font",
+                kind: StepKind::Mechanical,
+                patterns: &["@next/font"],
+                file_names: &[],
+                extensions: &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts", "json"],
+                instructions: "The @next/font package is removed. Import from next/font instead \
+                               and remove @next/font from package.json.",
+            },
+            Rule {
+                id: "config-renames",
+                title: "Rename stabilized next.config options",
+                kind: StepKind::Mechanical,
+                patterns: &["serverComponentsExternalPackages", "bundlePagesExternals"],
+                file_names: &["next.config.js", "next.config.mjs", "next.config.ts"],
+                extensions: &[],
+                instructions: "experimental.serverComponentsExternalPackages becomes the \
+                               top-level serverExternalPackages, and \
+                               experimental.bundlePagesExternals becomes \
+                               bundlePagesRouterDependencies.",
+            },
+            Rule {
+                id: "edge-runtime",
+                title: "Use the edge runtime name",
+                kind: StepKind::Mechanical,
+                patterns: &["'experimental-edge'", "\"experimental-edge\""],
+                file_names: &[],
+                extensions: SCRIPT_EXTENSIONS,
+                instructions: "The experimental-edge runtime is now called edge.",
+            },
+            USE_ACTION_STATE,
+            Rule {
+                id: "fetch-caching",
+                title: "Decide which fetch requests stay cached",
+                kind: StepKind::Manual,
+                patterns: &["fetch("],
+                file_names: &[],
+                extensions: SCRIPT_EXTENSIONS,
+                instructions: "fetch requests are no longer cached by default. For each request \
+                               that should be, add `cache: 'force-cache'` or a route segment \
+                               config such as `export const fetchCache = 'default-cache'`.",
+            },
+            Rule {
+                id: "route-handler-caching",
+                title: "Decide which GET route handlers stay cached",
+                kind: StepKind::Manual,
+                patterns: &["function GET"],
+                file_names: &["route.js", "route.ts"],
+                extensions: &[],
+                instructions: "GET route handlers are no longer cached by default. Add \
+                               `export const dynamic = 'force-static'` to handlers that \
+                               should be.",
+            },
+            Rule {
+                id: "request-geo-ip",
+                title: "Replace request.geo and request.ip",
+                kind: StepKind::Manual,
+                patterns: &["request.geo", "req.geo", "request.ip", "req.ip"],
+                file_names: &[],
+                extensions: SCRIPT_EXTENSIONS,
+                instructions: "NextRequest no longer has geo and ip. Read them from the hosting \
+                               provider instead, e.g. geolocation() and ipAddress() from \
+                               @vercel/functions.",
+            },
+        ],
+    },
+    Guide {
+        name: "react",
+        aliases: &["react"],
+        package: "react",
+        major: 19,
+        rules: &[
+            Rule {
+                id: "packages",
+                title: "Upgrade react and react-dom",
+                kind: StepKind::Mechanical,
+                patterns: &["\"react\":"],
+                file_names: &["package.json"],
+                extensions: &[],
+                instructions: "Set react and react-dom to ^19, and @types/react and \
+                               @types/react-dom to ^19 if present.",
+            },
+            Rule {
+                id: "create-root",
+                title: "Render with createRoot and hydrateRoot",
+                kind: StepKind::Mechanical,
+                patterns: &[
+                    "ReactDOM.render(",
+                    "ReactDOM.hydrate(",
+                    "unmountComponentAtNode(",
+                ],
+                file_names: &[],
+                extensions: SCRIPT_EXTENSIONS,
+                instructions: "ReactDOM.render, ReactDOM.hydrate and unmountComponentAtNode are \
+                               removed. Use createRoot or hydrateRoot from react-dom/client and \
+                               root.unmount().",
+            },
+            Rule {
+                id: "test-utils-act",
+                title: "Import act from react",
+                kind: StepKind::Mechanical,
+                patterns: &["react-dom/test-utils"],
+                file_names: &[],
+                extensions: SCRIPT_EXTENSIONS,
+                instructions: "react-dom/test-utils is removed. Import act from react.",
+            },
+            USE_ACTION_STATE,
+            Rule {
+                id: "prop-types",
+                title: "Replace propTypes and function defaultProps",
+                kind: StepKind::Manual,
+                patterns: &[".propTypes =", ".defaultProps ="],
+                file_names: &[],
+                extensions: SCRIPT_EXTENSIONS,
+                instructions: "propTypes are no longer checked and defaultProps is ignored on \
+                               function components. Use TypeScript types and default \
+                               parameter values.",
+            },
+            Rule {
+                id: "string-refs",
+                title: "Replace string refs",
+                kind: StepKind::Manual,
+                patterns: &["ref=\"", "this.refs."],
+                file_names: &[],
+                extensions: SCRIPT_EXTENSIONS,
+                instructions: "String refs are removed. Use useRef, createRef or a callback ref.",
+            },
+            Rule {
+                id: "legacy-context",
+                title: "Migrate legacy context",
+                kind: StepKind::Manual,
+                patterns: &["contextTypes", "childContextTypes", "getChildContext"],
+                file_names: &[],
+                extensions: SCRIPT_EXTENSIONS,
+                instructions: "Legacy context is removed. Move providers and consumers to \
+                               createContext and useContext.",
+            },
+            Rule {
+                id: "test-renderer",
+                title: "Move off react-test-renderer",
+                kind: StepKind::Manual,
+                patterns: &["react-test-renderer"],
+                file_names: &[],
+                extensions: &["js", "jsx", "ts", "tsx", "json"],
+                instructions: "react-test-renderer is deprecated. Move the tests to \
+                               @testing-library/react.",
+            },
+        ],
+    },
+];
+
+/// A framework and major version to upgrade to, e.g. `nextjs@15`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpgradeTarget {
+    pub framework: String,
+    pub major: u32,
+}
+
+impl UpgradeTarget {
+    /// Parse `<framework>@<major>`; minor and patch parts are ignored
+    pub fn parse(s: &str) -> Result<Self> {
+        let (name, version) = s.trim().rsplit_once('@').ok_or_else(|| {
+            Error::InvalidInput(format!(
+                "Invalid upgrade target '{}'. Use <framework>@<major>, e.g. nextjs@15",
+                s
+            ))
+        })?;
+        let major = version
+            .trim_start_matches(['v', '^', '~'])
+            .split('.')
+            .next()
+            .and_then(|m| m.parse().ok())
+            .ok_or_else(|| {
+                Error::InvalidInput(format!("Invalid version '{}' in '{}'", version, s))
+            })?;
+        let name = name.trim().to_lowercase();
+        let framework = GUIDES
+            .iter()
+            .find(|g| g.aliases.contains(&name.as_str()))
+            .map_or(name, |g| g.name.to_string());
+        Ok(Self { framework, major })
+    }
+
+    fn guide(&self) -> Result<&'static Guide> {
+        GUIDES
+            .iter()
+            .find(|g| g.name == self.framework && g.major == self.major)
+            .ok_or_else(|| {
+                Error::InvalidInput(format!(
+                    "No upgrade guide for {}. Supported targets: {}",
+                    self,
+                    supported_targets().join(", ")
+                ))
+            })
+    }
+}
+
+impl std::fmt::Display for UpgradeTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.framework, self.major)
+    }
+}
+
+/// Targets with a built-in upgrade guide
+pub fn supported_targets() -> Vec<String> {
+    GUIDES
+        .iter()
+        .map(|g| format!("{}@{}", g.name, g.major))
+        .collect()
+}
+
+/// A line of code a migration step applies to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Occurrence {
+    /// Path relative to the project root
+    pub path: String,
+    /// 1-based line number
+    pub line: usize,
+    /// The line, trimmed and shortened
+    pub excerpt: String,
+}
+
+/// One migration step and the code it applies to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpgradeStep {
+    pub id: String,
+    pub title: String,
+    pub kind: StepKind,
+    pub instructions: String,
+    pub occurrences: Vec<Occurrence>,
+    /// Feature tracking the step, once planned
+    pub feature_id: Option<String>,
+    /// Whether the tracking feature is done
+    #[serde(default)]
+    pub done: bool,
+}
+
+impl UpgradeStep {
+    /// Files the step touches, in path order
+    pub fn files(&self) -> BTreeSet<&str> {
+        self.occurrences.iter().map(|o| o.path.as_str()).collect()
+    }
+}
+
+/// Inventory of a project's usage affected by an upgrade
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpgradeReport {
+    pub target: UpgradeTarget,
+    /// Package whose version is upgraded
+    pub package: String,
+    /// Version requirement in package.json, if declared
+    pub current_version: Option<String>,
+    pub files_scanned: usize,
+    pub steps: Vec<UpgradeStep>,
+}
+
+impl UpgradeReport {
+    /// Whether package.json already requires the target major version
+    pub fn is_current(&self) -> bool {
+        self.current_version
+            .as_deref()
+            .and_then(major_version)
+            .is_some_and(|major| major >= self.target.major)
+    }
+
+    /// Mechanical steps that are not done yet
+    pub fn mechanical_steps(&self) -> impl Iterator<Item = &UpgradeStep> {
+        self.steps
+            .iter()
+            .filter(|s| s.kind == StepKind::Mechanical && !s.done)
+    }
+
+    /// Manual steps that are not done yet
+    pub fn manual_steps(&self) -> impl Iterator<Item = &UpgradeStep> {
+        self.steps
+            .iter()
+            .filter(|s| s.kind == StepKind::Manual && !s.done)
+    }
+}
+
+/// Major version of a requirement such as "^14.2.3"
+fn major_version(requirement: &str) -> Option<u32> {
+    let digits: String = requirement
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+/// Version requirement for `package` in the project's package.json
+fn declared_version(root: &Path, package: &str) -> Option<String> {
+    let manifest = std::fs::read_to_string(root.join("package.json")).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&manifest).ok()?;
+    ["dependencies", "devDependencies", "peerDependencies"]
+        .iter()
+        .find_map(|section| value[section][package].as_str())
+        .map(str::to_string)
+}
+
+/// Files under `dir`, skipping dependency and build directories
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            let name = entry.file_name();
+            if !SKIPPED_DIRS.iter().any(|d| name == *d) {
+                collect_files(root, &path, files);
+            }
+        } else if file_type.is_file() && entry.metadata().is_ok_and(|m| m.len() <= MAX_FILE_BYTES) {
+            if let Ok(relative) = path.strip_prefix(root) {
+                files.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+}
+
+impl Rule {
+    fn applies_to(&self, path: &str) -> bool {
+        let path = Path::new(path);
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if !self.file_names.is_empty() {
+            return self.file_names.contains(&name);
+        }
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| self.extensions.contains(&e))
+    }
+}
+
+/// Scan the project at `root` for code affected by upgrading to `target`
+pub fn inventory(root: &Path, target: &UpgradeTarget) -> Result<UpgradeReport> {
+    let guide = target.guide()?;
+    let mut files = Vec::new();
+    collect_files(root, root, &mut files);
+
+    let mut steps: Vec<UpgradeStep> = guide
+        .rules
+        .iter()
+        .map(|rule| UpgradeStep {
+            id: rule.id.to_string(),
+            title: rule.title.to_string(),
+            kind: rule.kind,
+            instructions: rule.instructions.to_string(),
+            occurrences: Vec::new(),
+            feature_id: None,
+            done: false,
+        })
+        .collect();
+
+    let mut files_scanned = 0;
+    for path in &files {
+        let applicable: Vec<usize> = (0..guide.rules.len())
+            .filter(|&i| guide.rules[i].applies_to(path))
+            .collect();
+        if applicable.is_empty() {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(root.join(path)) else {
+            continue;
+        };
+        files_scanned += 1;
+        for (n, line) in content.lines().enumerate() {
+            for &i in &applicable {
+                if guide.rules[i].patterns.iter().any(|p| line.contains(p)) {
+                    steps[i].occurrences.push(Occurrence {
+                        path: path.clone(),
+                        line: n + 1,
+                        excerpt: line.trim().chars().take(MAX_EXCERPT_LEN).collect(),
+                    });
+                }
+            }
+        }
+    }
+    steps.retain(|s| !s.occurrences.is_empty());
+
+    Ok(UpgradeReport {
+        target: target.clone(),
+        package: guide.package.to_string(),
+        current_version: declared_version(root, guide.package),
+        files_scanned,
+        steps,
+    })
+}
+
+/// Title of the feature tracking a step
+fn feature_title(target: &UpgradeTarget, step: &UpgradeStep) -> String {
+    format!("Upgrade to {}: {}", target, step.title)
+}
+
+/// Feature description for a step: instructions followed by the locations
+pub fn step_brief(target: &UpgradeTarget, step: &UpgradeStep) -> String {
+    let mut brief = format!(
+        "Part of the upgrade to {} ({} step).\n\n{}\n\nAffected code:\n",
+        target,
+        step.kind.as_str(),
+        step.instructions
+    );
+    for occurrence in step.occurrences.iter().take(MAX_LISTED_LOCATIONS) {
+        brief.push_str(&format!(
+            "- {}:{}: {}\n",
+            occurrence.path, occurrence.line, occurrence.excerpt
+        ));
+    }
+    if step.occurrences.len() > MAX_LISTED_LOCATIONS {
+        brief.push_str(&format!(
+            "- and {} more\n",
+            step.occurrences.len() - MAX_LISTED_LOCATIONS
+        ));
+    }
+    brief
+}
+
+/// Plan each step as a feature of the project
+///
+/// Steps whose feature already exists (matched by title) reuse it and pick
+/// up whether it is done; new features get the step's brief, labels for the
+/// target and step kind, and a higher priority for mechanical steps.
+pub async fn plan_features(
+    db: &Database,
+    project_id: &str,
+    report: &mut UpgradeReport,
+) -> Result<()> {
+    let repo = FeatureRepository::new(db);
+    let existing = repo.list_by_project(project_id, None).await?;
+    for step in &mut report.steps {
+        let title = feature_title(&report.target, step);
+        if let Some(feature) = existing.iter().find(|f| f.title == title) {
+            step.feature_id = Some(feature.id.clone());
+            step.done = feature.status == FeatureStatus::Done;
+            continue;
+        }
+        let criteria = match step.kind {
+            StepKind::Mechanical => "No affected code listed in the description remains unchanged",
+            StepKind::Manual => "Each listed location has been reviewed and updated or kept",
+        };
+        let feature = Feature::new(project_id, title)
+            .with_description(step_brief(&report.target, step))
+            .with_acceptance_criteria(criteria)
+            .with_labels(vec![
+                "upgrade".to_string(),
+                report.target.to_string(),
+                step.kind.as_str().to_string(),
+            ])
+            .with_priority(match step.kind {
+                StepKind::Mechanical => 2,
+                StepKind::Manual => 3,
+            });
+        repo.create(&feature).await?;
+        step.feature_id = Some(feature.id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::project::{Project, ProjectRepository};
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn sample_project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "package.json",
+            r#"{"dependencies": {"next": "^14.2.3", "react": "^18.3.0"}}"#,
+        );
+        write(
+            root,
+            "app/page.tsx",
+            "import { cookies } from 'next/headers';\n\nexport default function Page() {\n  const theme = cookies().get('theme');\n  const data = fetch('https://api.example.com');\n}\n",
+        );
+        write(root, "node_modules/next/index.js", "const c = cookies();\n");
+        dir
+    }
+
+    #[test]
+    fn test_parse_target() {
+        let target = UpgradeTarget::parse("Next.js@15.0.1").unwrap();
+        assert_eq!(target.framework, "nextjs");
+        assert_eq!(target.major, 15);
+        assert_eq!(target.to_string(), "nextjs@15");
+        assert!(UpgradeTarget::parse("nextjs").is_err());
+        assert!(UpgradeTarget::parse("nextjs@latest").is_err());
+
+        let unsupported = UpgradeTarget::parse("angular@18").unwrap();
+        assert!(inventory(Path::new("."), &unsupported).is_err());
+    }
+
+    #[test]
+    fn test_inventory_finds_affected_code() {
+        let dir = sample_project();
+        let target = UpgradeTarget::parse("nextjs@15").unwrap();
+        let report = inventory(dir.path(), &target).unwrap();
+
+        assert_eq!(report.current_version.as_deref(), Some("^14.2.3"));
+        assert!(!report.is_current());
+        assert_eq!(report.files_scanned, 2);
+
+        let ids: Vec<&str> = report.steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["packages", "async-request-apis", "fetch-caching"]);
+        let cookies = &report.steps[1];
+        assert_eq!(cookies.occurrences.len(), 1);
+        assert_eq!(cookies.occurrences[0].path, "app/page.tsx");
+        assert_eq!(cookies.occurrences[0].line, 4);
+        assert_eq!(report.mechanical_steps().count(), 2);
+        assert_eq!(report.manual_steps().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_plan_features_reuses_existing() {
+        let db = Database::in_memory().await.unwrap();
+        let project = Project::new("shop", "nextjs", "");
+        ProjectRepository::new(&db).create(&project).await.unwrap();
+        let dir = sample_project();
+        let target = UpgradeTarget::parse("nextjs@15").unwrap();
+
+        let mut report = inventory(dir.path(), &target).unwrap();
+        plan_features(&db, &project.id, &mut report).await.unwrap();
+        let repo = FeatureRepository::new(&db);
+        let features = repo.list_by_project(&project.id, None).await.unwrap();
+        assert_eq!(features.len(), 3);
+        let feature = repo
+            .get(report.steps[1].feature_id.as_deref().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            feature.title,
+            "Upgrade to nextjs@15: Await async request APIs"
+        );
+        assert!(feature
+            .description
+            .as_deref()
+            .unwrap()
+            .contains("app/page.tsx:4: const theme = cookies().get('theme');"));
+
+        let mut done = feature;
+        done.status = FeatureStatus::Done;
+        repo.update(&done).await.unwrap();
+
+        let mut again = inventory(dir.path(), &target).unwrap();
+        plan_features(&db, &project.id, &mut again).await.unwrap();
+        assert_eq!(
+            repo.list_by_project(&project.id, None).await.unwrap().len(),
+            3
+        );
+        assert!(again.steps[1].done);
+        assert_eq!(again.mechanical_steps().count(), 1);
+    }
+}
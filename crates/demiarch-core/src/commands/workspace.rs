@@ -0,0 +1,363 @@
+//! Workspace rollups
+//!
+//! A workspace is every project in the database. `demiarch workspace stats`
+//! and the GUI workspace overview sum each member project's LLM spend,
+//! sessions, features and agent activity (generation runs and tracked
+//! active time), with a per-project breakdown that can be exported as CSV.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use super::project::{Project, ProjectRepository, ProjectStatus};
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Costs and activity for one project, or summed over several
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Rollup {
+    /// LLM spend from `llm_costs`, generations included
+    pub cost_usd: f64,
+    pub llm_calls: i64,
+    pub tokens: i64,
+    pub sessions: i64,
+    pub active_sessions: i64,
+    pub features: i64,
+    pub features_in_progress: i64,
+    pub features_done: i64,
+    /// Agent runs
+    pub generations: i64,
+    pub failed_generations: i64,
+    pub generation_tokens: i64,
+    /// Time tracked against features across all sessions
+    pub active_secs: i64,
+}
+
+impl Rollup {
+    fn add(&mut self, other: &Rollup) {
+        self.cost_usd += other.cost_usd;
+        self.llm_calls += other.llm_calls;
+        self.tokens += other.tokens;
+        self.sessions += other.sessions;
+        self.active_sessions += other.active_sessions;
+        self.features += other.features;
+        self.features_in_progress += other.features_in_progress;
+        self.features_done += other.features_done;
+        self.generations += other.generations;
+        self.failed_generations += other.failed_generations;
+        self.generation_tokens += other.generation_tokens;
+        self.active_secs += other.active_secs;
+    }
+}
+
+/// One member project's share of the workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectRollup {
+    pub project_id: String,
+    pub project_name: String,
+    pub status: String,
+    #[serde(flatten)]
+    pub rollup: Rollup,
+}
+
+/// Costs and activity across the workspace's member projects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceStats {
+    /// Per-project breakdown, highest spend first
+    pub projects: Vec<ProjectRollup>,
+    pub totals: Rollup,
+}
+
+/// Member projects: those named by ID or name in `filter`, or every active
+/// project when it is empty
+pub async fn members(db: &Database, filter: &[String]) -> Result<Vec<Project>> {
+    let repo = ProjectRepository::new(db);
+    if filter.is_empty() {
+        return repo.list(Some(ProjectStatus::Active)).await;
+    }
+    let mut projects: Vec<Project> = Vec::with_capacity(filter.len());
+    for id_or_name in filter {
+        let project = match repo.get(id_or_name).await? {
+            Some(project) => project,
+            None => repo
+                .get_by_name(id_or_name)
+                .await?
+                .ok_or_else(|| Error::ProjectNotFound(id_or_name.clone()))?,
+        };
+        if !projects.iter().any(|p| p.id == project.id) {
+            projects.push(project);
+        }
+    }
+    Ok(projects)
+}
+
+/// Roll up costs and activity for `projects`
+pub async fn stats(db: &Database, projects: &[Project]) -> Result<WorkspaceStats> {
+    let mut rollups: HashMap<String, Rollup> = projects
+        .iter()
+        .map(|p| (p.id.clone(), Rollup::default()))
+        .collect();
+
+    let rows = sqlx::query(
+        r#"
+        SELECT project_id, COUNT(*) AS calls,
+               COALESCE(SUM(input_tokens + output_tokens), 0) AS tokens,
+               COALESCE(SUM(input_cost_usd + output_cost_usd), 0.0) AS cost_usd
+        FROM llm_costs
+        WHERE project_id IS NOT NULL
+        GROUP BY project_id
+        "#,
+    )
+    .fetch_all(db.pool())
+    .await?;
+    for row in rows {
+        if let Some(r) = rollups.get_mut(&row.get::<String, _>("project_id")) {
+            r.llm_calls = row.get("calls");
+            r.tokens = row.get("tokens");
+            r.cost_usd = row.get("cost_usd");
+        }
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT current_project_id AS project_id, COUNT(*) AS sessions,
+               COALESCE(SUM(status = 'active'), 0) AS active
+        FROM sessions
+        WHERE current_project_id IS NOT NULL
+        GROUP BY current_project_id
+        "#,
+    )
+    .fetch_all(db.pool())
+    .await?;
+    for row in rows {
+        if let Some(r) = rollups.get_mut(&row.get::<String, _>("project_id")) {
+            r.sessions = row.get("sessions");
+            r.active_sessions = row.get("active");
+        }
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT project_id, COUNT(*) AS features,
+               COALESCE(SUM(status = 'in_progress'), 0) AS in_progress,
+               COALESCE(SUM(status = 'done'), 0) AS done
+        FROM features
+        GROUP BY project_id
+        "#,
+    )
+    .fetch_all(db.pool())
+    .await?;
+    for row in rows {
+        if let Some(r) = rollups.get_mut(&row.get::<String, _>("project_id")) {
+            r.features = row.get("features");
+            r.features_in_progress = row.get("in_progress");
+            r.features_done = row.get("done");
+        }
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT project_id, COUNT(*) AS runs,
+               COALESCE(SUM(status = 'failed'), 0) AS failed,
+               COALESCE(SUM(tokens_used), 0) AS tokens
+        FROM generations
+        GROUP BY project_id
+        "#,
+    )
+    .fetch_all(db.pool())
+    .await?;
+    for row in rows {
+        if let Some(r) = rollups.get_mut(&row.get::<String, _>("project_id")) {
+            r.generations = row.get("runs");
+            r.failed_generations = row.get("failed");
+            r.generation_tokens = row.get("tokens");
+        }
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT project_id, COALESCE(SUM(active_secs), 0) AS active_secs
+        FROM feature_time
+        WHERE project_id IS NOT NULL
+        GROUP BY project_id
+        "#,
+    )
+    .fetch_all(db.pool())
+    .await?;
+    for row in rows {
+        if let Some(r) = rollups.get_mut(&row.get::<String, _>("project_id")) {
+            r.active_secs = row.get("active_secs");
+        }
+    }
+
+    let mut totals = Rollup::default();
+    let mut breakdown: Vec<ProjectRollup> = projects
+        .iter()
+        .map(|p| {
+            let rollup = rollups.remove(&p.id).unwrap_or_default();
+            totals.add(&rollup);
+            ProjectRollup {
+                project_id: p.id.clone(),
+                project_name: p.name.clone(),
+                status: p.status.as_str().to_string(),
+                rollup,
+            }
+        })
+        .collect();
+    breakdown.sort_by(|a, b| {
+        b.rollup
+            .cost_usd
+            .total_cmp(&a.rollup.cost_usd)
+            .then_with(|| a.project_name.cmp(&b.project_name))
+    });
+
+    Ok(WorkspaceStats {
+        projects: breakdown,
+        totals,
+    })
+}
+
+/// Write the per-project breakdown as CSV, one row per project and a final
+/// total row
+pub fn write_csv<W: Write>(stats: &WorkspaceStats, writer: W) -> Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record([
+        "project_id",
+        "project",
+        "status",
+        "cost_usd",
+        "llm_calls",
+        "tokens",
+        "sessions",
+        "active_sessions",
+        "features",
+        "features_in_progress",
+        "features_done",
+        "generations",
+        "failed_generations",
+        "generation_tokens",
+        "active_secs",
+    ])
+    .map_err(csv_error)?;
+    let totals = ProjectRollup {
+        project_id: String::new(),
+        project_name: "Total".to_string(),
+        status: String::new(),
+        rollup: stats.totals.clone(),
+    };
+    for project in stats.projects.iter().chain(std::iter::once(&totals)) {
+        let r = &project.rollup;
+        csv.write_record([
+            project.project_id.clone(),
+            project.project_name.clone(),
+            project.status.clone(),
+            format!("{:.6}", r.cost_usd),
+            r.llm_calls.to_string(),
+            r.tokens.to_string(),
+            r.sessions.to_string(),
+            r.active_sessions.to_string(),
+            r.features.to_string(),
+            r.features_in_progress.to_string(),
+            r.features_done.to_string(),
+            r.generations.to_string(),
+            r.failed_generations.to_string(),
+            r.generation_tokens.to_string(),
+            r.active_secs.to_string(),
+        ])
+        .map_err(csv_error)?;
+    }
+    csv.flush()?;
+    Ok(())
+}
+
+fn csv_error(e: csv::Error) -> Error {
+    Error::Other(format!("CSV export failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_workspace_rollup() {
+        let db = Database::in_memory().await.unwrap();
+        let repo = ProjectRepository::new(&db);
+        let shop = Project::new("shop", "react", "");
+        let blog = Project::new("blog", "astro", "");
+        let old = Project::new("old", "rust", "");
+        for project in [&shop, &blog, &old] {
+            repo.create(project).await.unwrap();
+        }
+        repo.archive(&old.id).await.unwrap();
+
+        for (id, project, status) in [
+            ("f1", &shop.id, "done"),
+            ("f2", &shop.id, "in_progress"),
+            ("f3", &blog.id, "backlog"),
+        ] {
+            sqlx::query(
+                "INSERT INTO features (id, project_id, title, status) VALUES (?, ?, 't', ?)",
+            )
+            .bind(id)
+            .bind(project)
+            .bind(status)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+        for (id, project, cost) in [
+            ("c1", &shop.id, 1.5),
+            ("c2", &blog.id, 0.25),
+            ("c3", &old.id, 9.0),
+        ] {
+            sqlx::query(
+                "INSERT INTO llm_costs (id, project_id, model, input_tokens, input_cost_usd) VALUES (?, ?, 'm', 100, ?)",
+            )
+            .bind(id)
+            .bind(project)
+            .bind(cost)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO generations (id, project_id, description, output_dir, status, tokens_used) \
+             VALUES ('g1', ?, 'gen', '/tmp', 'failed', 500)",
+        )
+        .bind(&shop.id)
+        .execute(db.pool())
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO sessions (id, current_project_id) VALUES ('s1', ?)")
+            .bind(&blog.id)
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let projects = members(&db, &[]).await.unwrap();
+        assert_eq!(projects.len(), 2);
+        let stats = stats(&db, &projects).await.unwrap();
+
+        assert_eq!(stats.projects[0].project_name, "shop");
+        assert_eq!(stats.projects[0].rollup.features_done, 1);
+        assert_eq!(stats.projects[0].rollup.failed_generations, 1);
+        assert_eq!(stats.projects[1].rollup.active_sessions, 1);
+        assert_eq!(stats.totals.cost_usd, 1.75);
+        assert_eq!(stats.totals.features, 3);
+        assert_eq!(stats.totals.tokens, 200);
+
+        let named = members(&db, &["old".to_string()]).await.unwrap();
+        assert_eq!(named[0].id, old.id);
+        assert!(members(&db, &["missing".to_string()]).await.is_err());
+
+        let mut out = Vec::new();
+        write_csv(&stats, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        assert!(csv.starts_with("project_id,project,status,cost_usd"));
+        assert!(csv
+            .trim_end()
+            .ends_with(",Total,,1.750000,2,200,1,1,3,1,1,1,1,500,0"));
+    }
+}
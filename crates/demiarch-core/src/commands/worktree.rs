@@ -0,0 +1,413 @@
+//! Generating into an isolated git worktree
+//!
+//! With `generate.isolation = "worktree"`, a generation writes into a fresh
+//! worktree on a branch of its own instead of the live working directory.
+//! The generated files are committed there and `generate.verify_commands`
+//! run against them; only when every command passes is the branch merged
+//! back. The user's uncommitted work is never touched by the generation
+//! itself, and git refuses a merge that would overwrite it. A branch that
+//! fails verification or cannot be merged is kept for inspection.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::GenerateConfig;
+use crate::{Error, Result};
+
+/// Prefix of the branches generations are isolated on
+pub const BRANCH_PREFIX: &str = "demiarch/generate-";
+
+/// Most output kept from a verification command, in bytes
+const MAX_CHECK_OUTPUT: usize = 4000;
+
+/// Where a generation writes its files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Isolation {
+    InPlace,
+    Worktree,
+}
+
+impl Isolation {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "in_place" => Some(Self::InPlace),
+            "worktree" => Some(Self::Worktree),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InPlace => "in_place",
+            Self::Worktree => "worktree",
+        }
+    }
+
+    /// The configured isolation; unknown values fall back to in-place
+    pub fn from_config(config: &GenerateConfig) -> Self {
+        Self::parse(&config.isolation).unwrap_or(Self::InPlace)
+    }
+}
+
+/// One verification command run in the worktree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub command: String,
+    pub passed: bool,
+    /// End of the command's combined stderr and stdout
+    pub output: String,
+}
+
+/// What happened to an isolated generation's branch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IsolationOutcome {
+    pub branch: String,
+    /// Whether the generation changed any files
+    pub committed: bool,
+    pub checks: Vec<CheckResult>,
+    /// Whether the branch was merged into the working directory's branch
+    pub merged: bool,
+    /// Why merging failed, if it was attempted
+    pub merge_error: Option<String>,
+}
+
+impl IsolationOutcome {
+    pub fn checks_passed(&self) -> bool {
+        self.checks.iter().all(|c| c.passed)
+    }
+
+    /// Whether the branch was kept for the user to inspect
+    pub fn branch_kept(&self) -> bool {
+        self.committed && !self.merged
+    }
+}
+
+/// A temporary worktree a generation writes into
+#[derive(Debug, Clone)]
+pub struct Worktree {
+    /// Root of the repository the worktree was created from
+    pub repo_root: PathBuf,
+    /// Directory of the worktree
+    pub path: PathBuf,
+    pub branch: String,
+}
+
+impl Worktree {
+    /// Check out `HEAD` of the repository containing `dir` into a new
+    /// worktree on branch `demiarch/generate-<name>`
+    pub async fn create(dir: &Path, name: &str) -> Result<Self> {
+        let repo_root = PathBuf::from(
+            git(dir, &["rev-parse", "--show-toplevel"])
+                .await
+                .map_err(|_| {
+                    Error::InvalidInput(format!(
+                        "{} is not in a git repository; set generate.isolation = \"in_place\"",
+                        dir.display()
+                    ))
+                })?
+                .trim(),
+        );
+        let branch = format!("{}{}", BRANCH_PREFIX, name);
+        let path = std::env::temp_dir().join(format!("demiarch-worktree-{}", name));
+        git(
+            &repo_root,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                &branch,
+                &path.to_string_lossy(),
+                "HEAD",
+            ],
+        )
+        .await?;
+        tracing::info!(branch = %branch, path = %path.display(), "Created generation worktree");
+        Ok(Self {
+            repo_root,
+            path,
+            branch,
+        })
+    }
+
+    /// The directory in the worktree that corresponds to `dir`
+    ///
+    /// Generating from a subdirectory of the repository writes into the same
+    /// subdirectory of the worktree.
+    pub fn output_dir(&self, dir: &Path) -> PathBuf {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let root = self
+            .repo_root
+            .canonicalize()
+            .unwrap_or_else(|_| self.repo_root.clone());
+        match dir.strip_prefix(&root) {
+            Ok(relative) => self.path.join(relative),
+            Err(_) => self.path.clone(),
+        }
+    }
+
+    /// Commit everything written to the worktree, returning false if nothing changed
+    pub async fn commit(&self, message: &str) -> Result<bool> {
+        git(&self.path, &["add", "--all"]).await?;
+        let status = git(&self.path, &["status", "--porcelain"]).await?;
+        if status.trim().is_empty() {
+            return Ok(false);
+        }
+        git(&self.path, &["commit", "--quiet", "--message", message]).await?;
+        Ok(true)
+    }
+
+    /// Run each command with `sh -c` in the worktree
+    pub async fn verify(&self, commands: &[String], timeout: Duration) -> Vec<CheckResult> {
+        let mut checks = Vec::new();
+        for command in commands {
+            let check = run_check(&self.path, command, timeout).await;
+            tracing::info!(command = %command, passed = check.passed, "Ran worktree verification");
+            checks.push(check);
+        }
+        checks
+    }
+
+    /// Merge the worktree's branch into the repository's checked-out branch
+    pub async fn merge(&self) -> Result<()> {
+        git(&self.repo_root, &["merge", "--no-edit", &self.branch])
+            .await
+            .map(|_| ())
+    }
+
+    /// Remove the worktree directory, deleting the branch unless `keep_branch`
+    pub async fn remove(&self, keep_branch: bool) -> Result<()> {
+        git(
+            &self.repo_root,
+            &[
+                "worktree",
+                "remove",
+                "--force",
+                &self.path.to_string_lossy(),
+            ],
+        )
+        .await?;
+        if !keep_branch {
+            git(
+                &self.repo_root,
+                &["branch", "--delete", "--force", &self.branch],
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Remove the worktree and its branch, logging rather than returning failures
+    ///
+    /// Used when a generation fails before anything worth keeping was written.
+    pub async fn discard(&self) {
+        if let Err(e) = self.remove(false).await {
+            tracing::warn!(branch = %self.branch, error = %e, "Failed to discard generation worktree");
+        }
+    }
+
+    /// Commit, verify and, if `merge`, merge back, then remove the worktree
+    ///
+    /// The branch is deleted once merged or when there was nothing to commit,
+    /// and kept otherwise, e.g. to open a pull request from.
+    pub async fn finish(
+        &self,
+        config: &GenerateConfig,
+        message: &str,
+        merge: bool,
+    ) -> Result<IsolationOutcome> {
+        let mut outcome = IsolationOutcome {
+            branch: self.branch.clone(),
+            committed: false,
+            checks: Vec::new(),
+            merged: false,
+            merge_error: None,
+        };
+        outcome.committed = match self.commit(message).await {
+            Ok(committed) => committed,
+            Err(e) => {
+                // Keep the branch and the uncommitted files for the user
+                tracing::warn!(branch = %self.branch, error = %e, "Failed to commit generation worktree");
+                return Err(e);
+            }
+        };
+
+        if outcome.committed {
+            outcome.checks = self
+                .verify(
+                    &config.verify_commands,
+                    Duration::from_secs(config.verify_timeout_secs),
+                )
+                .await;
+            if merge && outcome.checks_passed() {
+                match self.merge().await {
+                    Ok(()) => outcome.merged = true,
+                    Err(e) => outcome.merge_error = Some(e.to_string()),
+                }
+            }
+        }
+
+        self.remove(outcome.branch_kept()).await?;
+        Ok(outcome)
+    }
+}
+
+async fn run_check(dir: &Path, command: &str, timeout: Duration) -> CheckResult {
+    let child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+    let result = match child {
+        Ok(child) => tokio::time::timeout(timeout, child.wait_with_output()).await,
+        Err(e) => {
+            return CheckResult {
+                command: command.to_string(),
+                passed: false,
+                output: e.to_string(),
+            }
+        }
+    };
+    let (passed, output) = match result {
+        Ok(Ok(output)) => {
+            let text = format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stderr),
+                String::from_utf8_lossy(&output.stdout)
+            );
+            (output.status.success(), text)
+        }
+        Ok(Err(e)) => (false, e.to_string()),
+        Err(_) => (false, format!("timed out after {}s", timeout.as_secs())),
+    };
+    CheckResult {
+        command: command.to_string(),
+        passed,
+        output: tail(output.trim(), MAX_CHECK_OUTPUT).to_string(),
+    }
+}
+
+/// The last `max` bytes of `s`, on a character boundary
+fn tail(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut start = s.len() - max;
+    while !s.is_char_boundary(start) {
+        start += 1;
+    }
+    &s[start..]
+}
+
+pub(crate) async fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = tokio::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(Error::InvalidInput(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    async fn init_repo(dir: &Path) {
+        for args in [
+            vec!["init", "--quiet"],
+            vec!["config", "user.name", "Test"],
+            vec!["config", "user.email", "test@example.com"],
+        ] {
+            git(dir, &args).await.unwrap();
+        }
+        std::fs::write(dir.join("README.md"), "# app\n").unwrap();
+        git(dir, &["add", "--all"]).await.unwrap();
+        git(dir, &["commit", "--quiet", "--message", "init"])
+            .await
+            .unwrap();
+    }
+
+    fn config(verify: &str) -> GenerateConfig {
+        GenerateConfig {
+            isolation: "worktree".to_string(),
+            verify_commands: vec![verify.to_string()],
+            verify_timeout_secs: 30,
+            ..GenerateConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_isolation_parse() {
+        assert_eq!(Isolation::parse("in-place"), Some(Isolation::InPlace));
+        assert_eq!(Isolation::parse("WORKTREE"), Some(Isolation::Worktree));
+        assert_eq!(Isolation::parse("container"), None);
+        assert_eq!(
+            Isolation::from_config(&GenerateConfig::default()),
+            Isolation::InPlace
+        );
+    }
+
+    #[test]
+    fn test_tail_keeps_char_boundary() {
+        assert_eq!(tail("abc", 10), "abc");
+        assert_eq!(tail("héllo", 4), "llo");
+    }
+
+    #[tokio::test]
+    async fn test_finish_merges_only_when_checks_pass() {
+        let repo = tempfile::tempdir().unwrap();
+        init_repo(repo.path()).await;
+        // Uncommitted work in the live directory survives the generation
+        std::fs::write(repo.path().join("notes.txt"), "draft\n").unwrap();
+
+        let name = uuid::Uuid::new_v4().simple().to_string();
+        let worktree = Worktree::create(repo.path(), &name[..8]).await.unwrap();
+        std::fs::write(
+            worktree.output_dir(repo.path()).join("main.rs"),
+            "fn main() {}\n",
+        )
+        .unwrap();
+        let outcome = worktree
+            .finish(&config("test -f main.rs"), "Generate main", true)
+            .await
+            .unwrap();
+        assert!(outcome.committed && outcome.merged);
+        assert!(!outcome.branch_kept());
+        assert!(repo.path().join("main.rs").exists());
+        assert_eq!(
+            std::fs::read_to_string(repo.path().join("notes.txt")).unwrap(),
+            "draft\n"
+        );
+        assert!(!worktree.path.exists());
+
+        let name = uuid::Uuid::new_v4().simple().to_string();
+        let worktree = Worktree::create(repo.path(), &name[..8]).await.unwrap();
+        std::fs::write(worktree.path.join("lib.rs"), "broken\n").unwrap();
+        let outcome = worktree
+            .finish(&config("echo failing >&2; false"), "Generate lib", true)
+            .await
+            .unwrap();
+        assert!(!outcome.merged && outcome.branch_kept());
+        assert_eq!(outcome.checks[0].output, "failing");
+        assert!(!repo.path().join("lib.rs").exists());
+        let branches = git(repo.path(), &["branch", "--list", &worktree.branch])
+            .await
+            .unwrap();
+        assert!(branches.contains(&worktree.branch));
+    }
+}
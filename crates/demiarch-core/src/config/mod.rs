@@ -1,10 +1,18 @@
 //! Configuration management with file persistence
+//!
+//! Long-running processes pick up edits to the config file through [`watch`].
+
+pub mod watch;
 
 use anyhow::{anyhow, Context};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::commands::feature_branch::{self, DEFAULT_TEMPLATE as DEFAULT_BRANCH_TEMPLATE};
+use crate::commands::license::LicenseTier;
 
 /// Demiarch configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -14,6 +22,52 @@ pub struct Config {
     pub routing: RoutingConfig,
     #[serde(default)]
     pub context: ContextConfig,
+    #[serde(default)]
+    pub formatting: FormattingConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    #[serde(default)]
+    pub safety: SafetyConfig,
+    #[serde(default)]
+    pub analytics: AnalyticsConfig,
+    #[serde(default)]
+    pub lifecycle: LifecycleConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub secrets: SecretsConfig,
+    #[serde(default)]
+    pub events: EventsConfig,
+    #[serde(default)]
+    pub plugins: PluginsConfig,
+    #[serde(default)]
+    pub updates: UpdatesConfig,
+    #[serde(default)]
+    pub transcription: TranscriptionConfig,
+    #[serde(default)]
+    pub checkpoint: CheckpointSettings,
+    #[serde(default)]
+    pub invoice: InvoiceConfig,
+    #[serde(default)]
+    pub guardrails: GuardrailsConfig,
+    #[serde(default)]
+    pub approvals: ApprovalsConfig,
+    #[serde(default)]
+    pub generate: GenerateConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub persona: PersonaConfig,
+    #[serde(default)]
+    pub database: DatabaseSettings,
+    #[serde(default)]
+    pub knowledge: KnowledgeConfig,
 }
 
 /// Configuration for progressive disclosure context management
@@ -27,6 +81,128 @@ pub struct ContextConfig {
     pub enable_compression: bool,
 }
 
+/// Configuration for post-generation formatting and linting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormattingConfig {
+    /// Whether to run formatters over generated files before finalizing
+    pub enabled: bool,
+    /// Whether to run linters (auto-fixes applied, remaining findings reported)
+    pub lint: bool,
+    /// Timeout for a single tool run in seconds
+    pub timeout_secs: u64,
+    /// Tools to run (e.g. "rustfmt", "prettier"); empty uses the project's
+    /// configured tools plus framework defaults
+    pub tools: Vec<String>,
+}
+
+/// Configuration for the LLM response cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Whether identical completion requests are served from the cache
+    pub enabled: bool,
+    /// How long cached responses stay valid, in seconds
+    pub ttl_secs: u64,
+    /// Cap on total cached content in megabytes; least recently used
+    /// entries are evicted beyond it
+    pub max_size_mb: u64,
+}
+
+/// Configuration for network access
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Disable all network calls; remote features fail with an offline error
+    pub offline: bool,
+}
+
+/// Configuration for user-facing output
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    /// Locale for user-facing strings (e.g. "de", "pt-BR"); empty uses the
+    /// DEMIARCH_LANG or system locale
+    pub locale: String,
+    /// Replace emoji and box-drawing glyphs with ASCII words and lines
+    pub plain: bool,
+    /// Keep the GUI running in the system tray when its window is closed
+    pub close_to_tray: bool,
+}
+
+/// Configuration for screening content fed back into agents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafetyConfig {
+    /// How aggressively suspected prompt injection is stripped and
+    /// quarantined: "off", "low", "standard" or "strict"
+    pub strictness: String,
+}
+
+/// Configuration for local usage analytics
+///
+/// Analytics are computed from the local database and never leave the
+/// machine. They are off until the user opts in.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalyticsConfig {
+    /// Whether weekly usage rollups are recorded and `demiarch stats` is shown
+    pub enabled: bool,
+}
+
+/// Configuration for project lifecycle policies
+///
+/// Retention periods apply when `demiarch projects cleanup` runs; a value of
+/// 0 keeps records forever.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifecycleConfig {
+    /// Archive active projects with no activity for this many days (0 = never)
+    pub archive_after_days: u32,
+    /// Purge checkpoints older than this, keeping each project's newest one
+    pub checkpoint_retention_days: u32,
+    /// Purge finished jobs and search logs older than this
+    pub log_retention_days: u32,
+    /// Purge session and knowledge events older than this
+    pub event_retention_days: u32,
+}
+
+/// Shell commands run before destructive lifecycle steps
+///
+/// Each command receives the step as JSON on stdin; a non-zero exit vetoes
+/// it. Commands are edited in config.toml rather than with `config set`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Run before a project is archived
+    pub before_archive: Vec<String>,
+    /// Run before old checkpoints, logs or events are purged
+    pub before_purge: Vec<String>,
+    /// Run when generated files break a guardrail; cannot veto
+    pub on_policy_violation: Vec<String>,
+    /// Time a hook may take before it counts as a veto, in seconds
+    pub timeout_secs: u64,
+}
+
+/// Desktop notifications and terminal bells for long-running operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Whether finished generations, budget trips and conflicts are announced
+    pub enabled: bool,
+    /// How often the GUI checks for new events, in seconds
+    pub poll_secs: u64,
+}
+
+/// Configuration for per-project secrets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretsConfig {
+    /// Tell code generation which secret names the project has, so config
+    /// and `.env.example` files reference them (values are never sent)
+    pub reference_in_generation: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlmConfig {
     #[serde(skip)]
@@ -42,6 +218,16 @@ pub struct LlmConfig {
 pub struct CostConfig {
     pub daily_limit_usd: f64,
     pub alert_threshold: f64,
+    /// Ask before generating when the estimated cost exceeds this (0 = never ask)
+    #[serde(default)]
+    pub confirm_above_usd: f64,
+    /// Monthly budget the spend forecast is checked against
+    /// (0 = the daily limit times the days in the month)
+    #[serde(default)]
+    pub monthly_limit_usd: f64,
+    /// Show each chat message's prompt tokens and cost before sending it
+    #[serde(default)]
+    pub chat_preview: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +256,9 @@ impl Default for CostConfig {
         Self {
             daily_limit_usd: 10.0,
             alert_threshold: 0.8,
+            confirm_above_usd: 0.0,
+            monthly_limit_usd: 0.0,
+            chat_preview: false,
         }
     }
 }
@@ -92,6 +281,473 @@ impl Default for ContextConfig {
     }
 }
 
+impl Default for FormattingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lint: true,
+            timeout_secs: 30,
+            tools: Vec::new(),
+        }
+    }
+}
+
+impl Default for SafetyConfig {
+    fn default() -> Self {
+        Self {
+            strictness: "standard".to_string(),
+        }
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: 7 * 24 * 60 * 60,
+            max_size_mb: 50,
+        }
+    }
+}
+
+impl Default for LifecycleConfig {
+    fn default() -> Self {
+        Self {
+            archive_after_days: 0,
+            checkpoint_retention_days: 90,
+            log_retention_days: 30,
+            event_retention_days: 90,
+        }
+    }
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            before_archive: Vec::new(),
+            before_purge: Vec::new(),
+            on_policy_violation: Vec::new(),
+            timeout_secs: 30,
+        }
+    }
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_secs: 15,
+        }
+    }
+}
+
+/// Webhooks subscribed to the core event bus
+///
+/// Each webhook receives matching events as JSON POSTs and may answer with
+/// annotations or a veto. Webhooks are edited in config.toml:
+///
+/// ```toml
+/// [[events.webhooks]]
+/// url = "https://example.com/demiarch"
+/// events = ["generation_completed", "feature_created"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    pub webhooks: Vec<WebhookConfig>,
+    /// Time a webhook may take to answer, in seconds
+    pub webhook_timeout_secs: u64,
+}
+
+/// A single webhook subscription
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Events to send; empty means all
+    #[serde(default)]
+    pub events: Vec<crate::events::EventKind>,
+}
+
+/// Configuration for `demiarch self update` and the new-version notice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdatesConfig {
+    /// Release channel: stable or beta
+    pub channel: String,
+    /// Signed release manifest; `{channel}` is replaced with the channel
+    pub manifest_url: String,
+    /// Mention new versions in `doctor` and the GUI (checked at most daily)
+    pub notify: bool,
+}
+
+impl Default for UpdatesConfig {
+    fn default() -> Self {
+        Self {
+            channel: "stable".to_string(),
+            manifest_url:
+                "https://github.com/demiarch/demiarch/releases/download/channel-{channel}/manifest.signed"
+                    .to_string(),
+            notify: true,
+        }
+    }
+}
+
+/// Configuration for turning voice notes into text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptionConfig {
+    /// Where audio is sent: "openai" (Whisper API, key from OPENAI_API_KEY)
+    /// or "whisper_cpp" (a local whisper.cpp binary)
+    pub backend: String,
+    /// Model name sent to the Whisper API
+    pub model: String,
+    /// Whisper API transcription endpoint
+    pub api_url: String,
+    /// Spoken language as an ISO-639-1 code; empty lets the backend detect it
+    pub language: String,
+    /// whisper.cpp executable, looked up on PATH unless absolute
+    pub whisper_cpp_bin: String,
+    /// Path to the ggml model whisper.cpp loads
+    pub whisper_cpp_model: String,
+    /// Price of the Whisper API per minute of audio, in USD
+    pub cost_per_minute_usd: f64,
+}
+
+impl Default for TranscriptionConfig {
+    fn default() -> Self {
+        Self {
+            backend: "openai".to_string(),
+            model: "whisper-1".to_string(),
+            api_url: "https://api.openai.com/v1/audio/transcriptions".to_string(),
+            language: String::new(),
+            whisper_cpp_bin: "whisper-cli".to_string(),
+            whisper_cpp_model: String::new(),
+            cost_per_minute_usd: 0.006,
+        }
+    }
+}
+
+/// Configuration for checkpoints, the `[checkpoint]` section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckpointSettings {
+    pub triggers: CheckpointTriggersConfig,
+}
+
+/// When checkpoints are taken besides before each generation
+///
+/// Apart from `before_restore`, which controls the safety backup a restore
+/// takes itself, triggers run as the first step of the matching hook event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckpointTriggersConfig {
+    /// Back up the current state before restoring a checkpoint
+    pub before_restore: bool,
+    /// Checkpoint the project before `demiarch sync import`
+    pub before_sync_import: bool,
+    /// Checkpoint a project before commands that change several features
+    pub before_bulk_features: bool,
+    /// Checkpoint the project when a chat session ends
+    pub on_session_end: bool,
+    /// Checkpoint the project this often during a chat session, in
+    /// minutes; 0 turns interval checkpoints off
+    pub interval_mins: u32,
+}
+
+impl Default for CheckpointTriggersConfig {
+    fn default() -> Self {
+        Self {
+            before_restore: true,
+            before_sync_import: true,
+            before_bulk_features: true,
+            on_session_end: false,
+            interval_mins: 0,
+        }
+    }
+}
+
+/// Policy checked before generated files are written
+///
+/// A project's `.demiarch/guardrails.toml` adds protected globs and
+/// overrides limits. A limit of 0 is unlimited.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuardrailsConfig {
+    /// Globs, relative to the project root, that generation never writes
+    pub protected_paths: Vec<String>,
+    /// Largest file one generation may write, in KB
+    pub max_file_kb: u64,
+    /// Most files one generation may write
+    pub max_files: usize,
+    /// Most lines added plus removed across one generation's files
+    pub max_diff_lines: usize,
+}
+
+impl Default for GuardrailsConfig {
+    fn default() -> Self {
+        Self {
+            protected_paths: vec![".git/**".to_string(), ".demiarch/**".to_string()],
+            max_file_kb: 512,
+            max_files: 50,
+            max_diff_lines: 5000,
+        }
+    }
+}
+
+/// Human confirmation between generation phases
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApprovalsConfig {
+    /// Confirm the plan before any task runs
+    pub after_planning: bool,
+    /// Confirm review findings before files are written
+    pub after_review: bool,
+    /// Time to wait for an answer before aborting, in seconds
+    pub timeout_secs: u64,
+}
+
+impl Default for ApprovalsConfig {
+    fn default() -> Self {
+        Self {
+            after_planning: false,
+            after_review: false,
+            timeout_secs: 600,
+        }
+    }
+}
+
+/// Where `demiarch generate` writes its files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerateConfig {
+    /// "in_place" writes into the working directory; "worktree" writes into a
+    /// temporary git worktree and merges back once verification passes
+    pub isolation: String,
+    /// Shell commands run in the worktree before merging, e.g. "cargo check"
+    pub verify_commands: Vec<String>,
+    /// Time each verification command may take, in seconds
+    pub verify_timeout_secs: u64,
+    /// Run the package manager after adding dependencies the generated code
+    /// imports, e.g. `npm install`
+    pub install_dependencies: bool,
+    /// Name of the git branch a feature is developed on; placeholders are
+    /// {feature-id}, {feature-id-short} and {slug}
+    pub branch_template: String,
+}
+
+impl Default for GenerateConfig {
+    fn default() -> Self {
+        Self {
+            isolation: "in_place".to_string(),
+            verify_commands: Vec::new(),
+            verify_timeout_secs: 600,
+            install_dependencies: false,
+            branch_template: DEFAULT_BRANCH_TEMPLATE.to_string(),
+        }
+    }
+}
+
+/// Security audit of generated code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// Check generated files against the security checklist
+    pub enabled: bool,
+    /// Also ask the model to audit the files, for issues such as missing
+    /// authorization that line-level checks cannot see
+    pub llm: bool,
+    /// Keep files with critical findings from being applied
+    pub block_critical: bool,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            llm: true,
+            block_critical: true,
+        }
+    }
+}
+
+/// SQLite query instrumentation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseSettings {
+    /// Time every query and keep per-statement latency and row counts
+    pub instrument: bool,
+    /// Queries slower than this are written to the slow-query log
+    pub slow_query_ms: u64,
+}
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        Self {
+            instrument: false,
+            slow_query_ms: 200,
+        }
+    }
+}
+
+/// Aging of knowledge graph and skill confidence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KnowledgeConfig {
+    /// Days for unreinforced confidence to halve; 0 disables aging
+    pub half_life_days: f64,
+    /// Aged confidence below which maintenance archives an entity or
+    /// relationship
+    pub archive_below: f32,
+}
+
+impl Default for KnowledgeConfig {
+    fn default() -> Self {
+        Self {
+            half_life_days: 90.0,
+            archive_below: 0.1,
+        }
+    }
+}
+
+/// Persona used in chat and generation prompts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonaConfig {
+    /// Name of the project persona to use when none is picked with
+    /// `/persona`; empty for none
+    pub default: String,
+}
+
+/// Configuration for `demiarch costs invoice`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InvoiceConfig {
+    /// Percentage added on top of the recorded cost of each line item
+    pub markup_percent: f64,
+    /// Currency invoices are issued in, e.g. "EUR"
+    pub currency: String,
+    /// Units of each currency per US dollar; a static table, not live rates
+    pub rates: BTreeMap<String, f64>,
+}
+
+impl Default for InvoiceConfig {
+    fn default() -> Self {
+        Self {
+            markup_percent: 0.0,
+            currency: "USD".to_string(),
+            rates: [
+                ("USD", 1.0),
+                ("EUR", 0.92),
+                ("GBP", 0.79),
+                ("CAD", 1.36),
+                ("AUD", 1.52),
+                ("JPY", 150.0),
+            ]
+            .into_iter()
+            .map(|(code, rate)| (code.to_string(), rate))
+            .collect(),
+        }
+    }
+}
+
+/// Configuration for WASM plugin execution
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginsConfig {
+    pub limits: PluginLimitsConfig,
+}
+
+/// Resource limits for a plugin invocation, per license tier
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginLimitsConfig {
+    pub free: PluginTierLimits,
+    pub pro: PluginTierLimits,
+    pub enterprise: PluginTierLimits,
+}
+
+/// Limits applied to each invocation of a plugin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginTierLimits {
+    /// Fuel (roughly, WASM instructions) available to one invocation
+    pub fuel: u64,
+    /// Linear memory cap in megabytes
+    pub memory_mb: u64,
+    /// Wall-clock limit in seconds
+    pub timeout_secs: u64,
+}
+
+impl PluginLimitsConfig {
+    pub fn for_tier(&self, tier: LicenseTier) -> &PluginTierLimits {
+        match tier {
+            LicenseTier::Free => &self.free,
+            LicenseTier::Pro => &self.pro,
+            LicenseTier::Enterprise => &self.enterprise,
+        }
+    }
+
+    /// Limits and field named by a `plugins.limits.<tier>.<field>` key
+    fn field_mut(&mut self, key: &str) -> anyhow::Result<(&mut u64, &'static str)> {
+        let rest = key
+            .strip_prefix("plugins.limits.")
+            .ok_or_else(|| anyhow!("Unknown configuration key: {}", key))?;
+        let (tier, field) = rest
+            .split_once('.')
+            .ok_or_else(|| anyhow!("Unknown configuration key: {}", key))?;
+        let limits = match tier {
+            "free" => &mut self.free,
+            "pro" => &mut self.pro,
+            "enterprise" => &mut self.enterprise,
+            _ => return Err(anyhow!("Unknown license tier in {}", key)),
+        };
+        match field {
+            "fuel" => Ok((&mut limits.fuel, "fuel")),
+            "memory_mb" => Ok((&mut limits.memory_mb, "memory_mb")),
+            "timeout_secs" => Ok((&mut limits.timeout_secs, "timeout_secs")),
+            _ => Err(anyhow!("Unknown configuration key: {}", key)),
+        }
+    }
+}
+
+impl Default for PluginLimitsConfig {
+    fn default() -> Self {
+        Self {
+            free: PluginTierLimits {
+                fuel: 10_000_000,
+                memory_mb: 16,
+                timeout_secs: 5,
+            },
+            pro: PluginTierLimits {
+                fuel: 100_000_000,
+                memory_mb: 64,
+                timeout_secs: 30,
+            },
+            enterprise: PluginTierLimits {
+                fuel: 1_000_000_000,
+                memory_mb: 256,
+                timeout_secs: 120,
+            },
+        }
+    }
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            webhooks: Vec::new(),
+            webhook_timeout_secs: 10,
+        }
+    }
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self {
+            reference_in_generation: true,
+        }
+    }
+}
+
 impl ContextConfig {
     /// Create a context budget from this configuration
     pub fn to_context_budget(&self) -> crate::context::ContextBudget {
@@ -151,10 +807,14 @@ impl Config {
 
     /// Load configuration from file, or create default if it doesn't exist
     pub fn load() -> anyhow::Result<Self> {
-        let path = Self::config_path()?;
+        Self::load_from(&Self::config_path()?)
+    }
 
+    /// Load configuration from the file at `path`, or the default if it
+    /// doesn't exist
+    pub fn load_from(path: &Path) -> anyhow::Result<Self> {
         if path.exists() {
-            let contents = fs::read_to_string(&path)
+            let contents = fs::read_to_string(path)
                 .with_context(|| format!("Failed to read config file: {}", path.display()))?;
             let config: Config = toml::from_str(&contents)
                 .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
@@ -201,6 +861,9 @@ impl Config {
             // Cost settings
             "cost.daily_limit_usd" => Ok(self.cost.daily_limit_usd.to_string()),
             "cost.alert_threshold" => Ok(self.cost.alert_threshold.to_string()),
+            "cost.confirm_above_usd" => Ok(self.cost.confirm_above_usd.to_string()),
+            "cost.monthly_limit_usd" => Ok(self.cost.monthly_limit_usd.to_string()),
+            "cost.chat_preview" => Ok(self.cost.chat_preview.to_string()),
 
             // Routing settings
             "routing.preference" => Ok(self.routing.preference.clone()),
@@ -210,6 +873,150 @@ impl Config {
             "context.output_reserve" => Ok(self.context.output_reserve.to_string()),
             "context.enable_compression" => Ok(self.context.enable_compression.to_string()),
 
+            // Formatting settings
+            "formatting.enabled" => Ok(self.formatting.enabled.to_string()),
+            "formatting.lint" => Ok(self.formatting.lint.to_string()),
+            "formatting.timeout_secs" => Ok(self.formatting.timeout_secs.to_string()),
+            "formatting.tools" => Ok(if self.formatting.tools.is_empty() {
+                "(auto)".to_string()
+            } else {
+                self.formatting.tools.join(", ")
+            }),
+
+            // Cache settings
+            "cache.enabled" => Ok(self.cache.enabled.to_string()),
+            "cache.ttl_secs" => Ok(self.cache.ttl_secs.to_string()),
+            "cache.max_size_mb" => Ok(self.cache.max_size_mb.to_string()),
+
+            // Network settings
+            "network.offline" => Ok(self.network.offline.to_string()),
+
+            // UI settings
+            "ui.locale" => Ok(self.ui.locale.clone()),
+            "ui.plain" => Ok(self.ui.plain.to_string()),
+            "ui.close_to_tray" => Ok(self.ui.close_to_tray.to_string()),
+
+            // Safety settings
+            "safety.strictness" => Ok(self.safety.strictness.clone()),
+
+            // Analytics settings
+            "analytics.enabled" => Ok(self.analytics.enabled.to_string()),
+
+            // Lifecycle settings
+            "lifecycle.archive_after_days" => Ok(self.lifecycle.archive_after_days.to_string()),
+            "lifecycle.checkpoint_retention_days" => {
+                Ok(self.lifecycle.checkpoint_retention_days.to_string())
+            }
+            "lifecycle.log_retention_days" => Ok(self.lifecycle.log_retention_days.to_string()),
+            "lifecycle.event_retention_days" => Ok(self.lifecycle.event_retention_days.to_string()),
+
+            // Hook settings
+            "hooks.before_archive" => Ok(hook_list(&self.hooks.before_archive)),
+            "hooks.before_purge" => Ok(hook_list(&self.hooks.before_purge)),
+            "hooks.on_policy_violation" => Ok(hook_list(&self.hooks.on_policy_violation)),
+            "hooks.timeout_secs" => Ok(self.hooks.timeout_secs.to_string()),
+
+            // Notification settings
+            "notifications.enabled" => Ok(self.notifications.enabled.to_string()),
+            "notifications.poll_secs" => Ok(self.notifications.poll_secs.to_string()),
+
+            // Secrets settings
+            "secrets.reference_in_generation" => {
+                Ok(self.secrets.reference_in_generation.to_string())
+            }
+
+            // Event settings
+            "events.webhooks" => Ok(hook_list(
+                &self
+                    .events
+                    .webhooks
+                    .iter()
+                    .map(|w| w.url.clone())
+                    .collect::<Vec<_>>(),
+            )),
+            "events.webhook_timeout_secs" => Ok(self.events.webhook_timeout_secs.to_string()),
+
+            // Update settings
+            "updates.channel" => Ok(self.updates.channel.clone()),
+            "updates.manifest_url" => Ok(self.updates.manifest_url.clone()),
+            "updates.notify" => Ok(self.updates.notify.to_string()),
+
+            // Transcription settings
+            "transcription.backend" => Ok(self.transcription.backend.clone()),
+            "transcription.model" => Ok(self.transcription.model.clone()),
+            "transcription.api_url" => Ok(self.transcription.api_url.clone()),
+            "transcription.language" => Ok(self.transcription.language.clone()),
+            "transcription.whisper_cpp_bin" => Ok(self.transcription.whisper_cpp_bin.clone()),
+            "transcription.whisper_cpp_model" => Ok(self.transcription.whisper_cpp_model.clone()),
+            "transcription.cost_per_minute_usd" => {
+                Ok(self.transcription.cost_per_minute_usd.to_string())
+            }
+
+            // Checkpoint trigger settings
+            "checkpoint.triggers.before_restore" => {
+                Ok(self.checkpoint.triggers.before_restore.to_string())
+            }
+            "checkpoint.triggers.before_sync_import" => {
+                Ok(self.checkpoint.triggers.before_sync_import.to_string())
+            }
+            "checkpoint.triggers.before_bulk_features" => {
+                Ok(self.checkpoint.triggers.before_bulk_features.to_string())
+            }
+            "checkpoint.triggers.on_session_end" => {
+                Ok(self.checkpoint.triggers.on_session_end.to_string())
+            }
+            "checkpoint.triggers.interval_mins" => {
+                Ok(self.checkpoint.triggers.interval_mins.to_string())
+            }
+
+            // Guardrail settings
+            "guardrails.protected_paths" => Ok(if self.guardrails.protected_paths.is_empty() {
+                "(none)".to_string()
+            } else {
+                self.guardrails.protected_paths.join(", ")
+            }),
+            "guardrails.max_file_kb" => Ok(self.guardrails.max_file_kb.to_string()),
+            "guardrails.max_files" => Ok(self.guardrails.max_files.to_string()),
+            "guardrails.max_diff_lines" => Ok(self.guardrails.max_diff_lines.to_string()),
+
+            // Approval settings
+            "approvals.after_planning" => Ok(self.approvals.after_planning.to_string()),
+            "approvals.after_review" => Ok(self.approvals.after_review.to_string()),
+            "approvals.timeout_secs" => Ok(self.approvals.timeout_secs.to_string()),
+
+            // Generation settings
+            "generate.isolation" => Ok(self.generate.isolation.clone()),
+            "generate.verify_commands" => Ok(hook_list(&self.generate.verify_commands)),
+            "generate.verify_timeout_secs" => Ok(self.generate.verify_timeout_secs.to_string()),
+            "generate.install_dependencies" => Ok(self.generate.install_dependencies.to_string()),
+            "generate.branch_template" => Ok(self.generate.branch_template.clone()),
+            "audit.enabled" => Ok(self.audit.enabled.to_string()),
+            "audit.llm" => Ok(self.audit.llm.to_string()),
+            "audit.block_critical" => Ok(self.audit.block_critical.to_string()),
+            "persona.default" => Ok(self.persona.default.clone()),
+            "database.instrument" => Ok(self.database.instrument.to_string()),
+            "database.slow_query_ms" => Ok(self.database.slow_query_ms.to_string()),
+            "knowledge.half_life_days" => Ok(self.knowledge.half_life_days.to_string()),
+            "knowledge.archive_below" => Ok(self.knowledge.archive_below.to_string()),
+
+            // Invoice settings
+            "invoice.markup_percent" => Ok(self.invoice.markup_percent.to_string()),
+            "invoice.currency" => Ok(self.invoice.currency.clone()),
+            key if key.starts_with("invoice.rates.") => {
+                let code = &key["invoice.rates.".len()..];
+                self.invoice
+                    .rates
+                    .get(&code.to_uppercase())
+                    .map(|rate| rate.to_string())
+                    .ok_or_else(|| anyhow!("No exchange rate for {}", code))
+            }
+
+            // Plugin limit settings
+            key if key.starts_with("plugins.limits.") => {
+                let mut limits = self.plugins.limits.clone();
+                Ok(limits.field_mut(key)?.0.to_string())
+            }
+
             // API key (special handling - show redacted)
             "llm.api_key" | "api_key" => match self.llm.redacted_api_key()? {
                 Some(redacted) => Ok(redacted),
@@ -278,6 +1085,29 @@ impl Config {
                 }
                 self.cost.alert_threshold = threshold;
             }
+            "cost.confirm_above_usd" => {
+                let threshold: f64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid confirm_above_usd value: {}", value))?;
+                if threshold < 0.0 {
+                    return Err(anyhow!("Confirmation threshold must be non-negative"));
+                }
+                self.cost.confirm_above_usd = threshold;
+            }
+            "cost.monthly_limit_usd" => {
+                let limit: f64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid monthly_limit_usd value: {}", value))?;
+                if limit < 0.0 {
+                    return Err(anyhow!("Monthly limit must be non-negative"));
+                }
+                self.cost.monthly_limit_usd = limit;
+            }
+            "cost.chat_preview" => {
+                self.cost.chat_preview = value
+                    .parse()
+                    .with_context(|| format!("Invalid cost.chat_preview value: {}", value))?;
+            }
 
             // Routing settings
             "routing.preference" => {
@@ -318,6 +1148,466 @@ impl Config {
                 self.context.enable_compression = enabled;
             }
 
+            // Formatting settings
+            "formatting.enabled" => {
+                self.formatting.enabled = value
+                    .parse()
+                    .with_context(|| format!("Invalid formatting.enabled value: {}", value))?;
+            }
+            "formatting.lint" => {
+                self.formatting.lint = value
+                    .parse()
+                    .with_context(|| format!("Invalid formatting.lint value: {}", value))?;
+            }
+            "formatting.timeout_secs" => {
+                let secs: u64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid timeout_secs value: {}", value))?;
+                if secs == 0 {
+                    return Err(anyhow!("formatting.timeout_secs must be greater than 0"));
+                }
+                self.formatting.timeout_secs = secs;
+            }
+            "formatting.tools" => {
+                let valid_tools = ["rustfmt", "prettier", "black", "eslint"];
+                let tools: Vec<String> = value
+                    .split(',')
+                    .map(|s| s.trim().to_lowercase())
+                    .filter(|s| !s.is_empty() && s != "auto")
+                    .collect();
+                if let Some(unknown) = tools.iter().find(|t| !valid_tools.contains(&t.as_str())) {
+                    return Err(anyhow!(
+                        "Unknown formatting tool: {}. Valid options: {}",
+                        unknown,
+                        valid_tools.join(", ")
+                    ));
+                }
+                self.formatting.tools = tools;
+            }
+
+            // Cache settings
+            "cache.enabled" => {
+                self.cache.enabled = value
+                    .parse()
+                    .with_context(|| format!("Invalid cache.enabled value: {}", value))?;
+            }
+            "cache.ttl_secs" => {
+                let secs: u64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid ttl_secs value: {}", value))?;
+                if secs == 0 {
+                    return Err(anyhow!("cache.ttl_secs must be greater than 0"));
+                }
+                self.cache.ttl_secs = secs;
+            }
+            "cache.max_size_mb" => {
+                let size: u64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid max_size_mb value: {}", value))?;
+                if size == 0 {
+                    return Err(anyhow!("cache.max_size_mb must be greater than 0"));
+                }
+                self.cache.max_size_mb = size;
+            }
+
+            // Network settings
+            "network.offline" => {
+                self.network.offline = value
+                    .parse()
+                    .with_context(|| format!("Invalid network.offline value: {}", value))?;
+            }
+
+            // UI settings
+            "ui.locale" => {
+                self.ui.locale = value.trim().to_string();
+            }
+            "ui.plain" => {
+                self.ui.plain = value
+                    .parse()
+                    .with_context(|| format!("Invalid ui.plain value: {}", value))?;
+            }
+            "ui.close_to_tray" => {
+                self.ui.close_to_tray = value
+                    .parse()
+                    .with_context(|| format!("Invalid ui.close_to_tray value: {}", value))?;
+            }
+
+            // Safety settings
+            "safety.strictness" => {
+                let valid_levels = ["off", "low", "standard", "strict"];
+                let level = value.trim().to_lowercase();
+                if !valid_levels.contains(&level.as_str()) {
+                    return Err(anyhow!(
+                        "Invalid safety strictness: {}. Valid options: {}",
+                        value,
+                        valid_levels.join(", ")
+                    ));
+                }
+                self.safety.strictness = level;
+            }
+
+            // Analytics settings
+            "analytics.enabled" => {
+                self.analytics.enabled = value
+                    .parse()
+                    .with_context(|| format!("Invalid analytics.enabled value: {}", value))?;
+            }
+
+            // Lifecycle settings
+            "lifecycle.archive_after_days" => {
+                self.lifecycle.archive_after_days = parse_days(key, value)?;
+            }
+            "lifecycle.checkpoint_retention_days" => {
+                self.lifecycle.checkpoint_retention_days = parse_days(key, value)?;
+            }
+            "lifecycle.log_retention_days" => {
+                self.lifecycle.log_retention_days = parse_days(key, value)?;
+            }
+            "lifecycle.event_retention_days" => {
+                self.lifecycle.event_retention_days = parse_days(key, value)?;
+            }
+
+            // Hook settings
+            "hooks.before_archive" | "hooks.before_purge" | "hooks.on_policy_violation" => {
+                return Err(anyhow!(
+                    "Hook commands are edited in {}",
+                    Self::config_path()?.display()
+                ));
+            }
+            "hooks.timeout_secs" => {
+                let secs: u64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid timeout_secs value: {}", value))?;
+                if secs == 0 {
+                    return Err(anyhow!("hooks.timeout_secs must be greater than 0"));
+                }
+                self.hooks.timeout_secs = secs;
+            }
+
+            // Notification settings
+            "notifications.enabled" => {
+                self.notifications.enabled = value
+                    .parse()
+                    .with_context(|| format!("Invalid notifications.enabled value: {}", value))?;
+            }
+            "notifications.poll_secs" => {
+                let secs: u64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid poll_secs value: {}", value))?;
+                if secs == 0 {
+                    return Err(anyhow!("notifications.poll_secs must be greater than 0"));
+                }
+                self.notifications.poll_secs = secs;
+            }
+
+            // Secrets settings
+            "secrets.reference_in_generation" => {
+                self.secrets.reference_in_generation = value.parse().with_context(|| {
+                    format!("Invalid secrets.reference_in_generation value: {}", value)
+                })?;
+            }
+
+            // Event settings
+            "events.webhooks" => {
+                return Err(anyhow!(
+                    "Webhooks are edited in {}",
+                    Self::config_path()?.display()
+                ));
+            }
+            "events.webhook_timeout_secs" => {
+                let secs: u64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid webhook_timeout_secs value: {}", value))?;
+                if secs == 0 {
+                    return Err(anyhow!(
+                        "events.webhook_timeout_secs must be greater than 0"
+                    ));
+                }
+                self.events.webhook_timeout_secs = secs;
+            }
+
+            // Update settings
+            "updates.channel" => {
+                let channel = value.to_lowercase();
+                if !["stable", "beta"].contains(&channel.as_str()) {
+                    return Err(anyhow!(
+                        "Invalid updates.channel: {}. Use stable or beta",
+                        value
+                    ));
+                }
+                self.updates.channel = channel;
+            }
+            "updates.manifest_url" => {
+                if !value.starts_with("https://") {
+                    return Err(anyhow!("updates.manifest_url must be an https URL"));
+                }
+                self.updates.manifest_url = value.to_string();
+            }
+            "updates.notify" => {
+                self.updates.notify = value
+                    .parse()
+                    .with_context(|| format!("Invalid updates.notify value: {}", value))?;
+            }
+
+            // Transcription settings
+            "transcription.backend" => {
+                let backend = value.to_lowercase().replace('-', "_");
+                if !["openai", "whisper_cpp"].contains(&backend.as_str()) {
+                    return Err(anyhow!(
+                        "Invalid transcription.backend: {}. Use openai or whisper_cpp",
+                        value
+                    ));
+                }
+                self.transcription.backend = backend;
+            }
+            "transcription.model" => {
+                self.transcription.model = value.to_string();
+            }
+            "transcription.api_url" => {
+                if !value.starts_with("https://") {
+                    return Err(anyhow!("transcription.api_url must be an https URL"));
+                }
+                self.transcription.api_url = value.to_string();
+            }
+            "transcription.language" => {
+                self.transcription.language = value.to_lowercase();
+            }
+            "transcription.whisper_cpp_bin" => {
+                self.transcription.whisper_cpp_bin = value.to_string();
+            }
+            "transcription.whisper_cpp_model" => {
+                self.transcription.whisper_cpp_model = value.to_string();
+            }
+            "transcription.cost_per_minute_usd" => {
+                let cost: f64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid cost_per_minute_usd value: {}", value))?;
+                if cost < 0.0 {
+                    return Err(anyhow!(
+                        "transcription.cost_per_minute_usd cannot be negative"
+                    ));
+                }
+                self.transcription.cost_per_minute_usd = cost;
+            }
+
+            // Checkpoint trigger settings
+            "checkpoint.triggers.before_restore"
+            | "checkpoint.triggers.before_sync_import"
+            | "checkpoint.triggers.before_bulk_features"
+            | "checkpoint.triggers.on_session_end" => {
+                let enabled: bool = value
+                    .parse()
+                    .with_context(|| format!("Invalid {} value: {}", key, value))?;
+                let triggers = &mut self.checkpoint.triggers;
+                match key {
+                    "checkpoint.triggers.before_restore" => triggers.before_restore = enabled,
+                    "checkpoint.triggers.before_sync_import" => {
+                        triggers.before_sync_import = enabled
+                    }
+                    "checkpoint.triggers.before_bulk_features" => {
+                        triggers.before_bulk_features = enabled
+                    }
+                    _ => triggers.on_session_end = enabled,
+                }
+            }
+            "checkpoint.triggers.interval_mins" => {
+                self.checkpoint.triggers.interval_mins = value
+                    .parse()
+                    .with_context(|| format!("Invalid interval_mins value: {}", value))?;
+            }
+
+            // Guardrail settings
+            "guardrails.protected_paths" => {
+                let patterns: Vec<String> = value
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+                for pattern in &patterns {
+                    globset::Glob::new(pattern)
+                        .with_context(|| format!("Invalid protected path: {}", pattern))?;
+                }
+                self.guardrails.protected_paths = patterns;
+            }
+            "guardrails.max_file_kb" => {
+                self.guardrails.max_file_kb = value
+                    .parse()
+                    .with_context(|| format!("Invalid max_file_kb value: {}", value))?;
+            }
+            "guardrails.max_files" => {
+                self.guardrails.max_files = value
+                    .parse()
+                    .with_context(|| format!("Invalid max_files value: {}", value))?;
+            }
+            "guardrails.max_diff_lines" => {
+                self.guardrails.max_diff_lines = value
+                    .parse()
+                    .with_context(|| format!("Invalid max_diff_lines value: {}", value))?;
+            }
+
+            // Approval settings
+            "approvals.after_planning" => {
+                self.approvals.after_planning = value
+                    .parse()
+                    .with_context(|| format!("Invalid after_planning value: {}", value))?;
+            }
+            "approvals.after_review" => {
+                self.approvals.after_review = value
+                    .parse()
+                    .with_context(|| format!("Invalid after_review value: {}", value))?;
+            }
+            "approvals.timeout_secs" => {
+                let secs: u64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid timeout_secs value: {}", value))?;
+                if secs == 0 {
+                    return Err(anyhow!("approvals.timeout_secs must be greater than 0"));
+                }
+                self.approvals.timeout_secs = secs;
+            }
+
+            // Generation settings
+            "generate.isolation" => {
+                let isolation = value.to_lowercase().replace('-', "_");
+                if !["in_place", "worktree"].contains(&isolation.as_str()) {
+                    return Err(anyhow!(
+                        "Invalid generate.isolation: {}. Use in_place or worktree",
+                        value
+                    ));
+                }
+                self.generate.isolation = isolation;
+            }
+            "generate.verify_commands" => {
+                return Err(anyhow!(
+                    "Verification commands are edited in {}",
+                    Self::config_path()?.display()
+                ));
+            }
+            "generate.verify_timeout_secs" => {
+                let secs: u64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid verify_timeout_secs value: {}", value))?;
+                if secs == 0 {
+                    return Err(anyhow!(
+                        "generate.verify_timeout_secs must be greater than 0"
+                    ));
+                }
+                self.generate.verify_timeout_secs = secs;
+            }
+            "generate.install_dependencies" => {
+                self.generate.install_dependencies = value.parse().with_context(|| {
+                    format!("Invalid generate.install_dependencies value: {}", value)
+                })?;
+            }
+            "generate.branch_template" => {
+                feature_branch::check_template(value)
+                    .map_err(|e| anyhow!("Invalid generate.branch_template: {}", e))?;
+                self.generate.branch_template = value.to_string();
+            }
+
+            // Security audit settings
+            "audit.enabled" => {
+                self.audit.enabled = value
+                    .parse()
+                    .with_context(|| format!("Invalid audit.enabled value: {}", value))?;
+            }
+            "audit.llm" => {
+                self.audit.llm = value
+                    .parse()
+                    .with_context(|| format!("Invalid audit.llm value: {}", value))?;
+            }
+            "audit.block_critical" => {
+                self.audit.block_critical = value
+                    .parse()
+                    .with_context(|| format!("Invalid audit.block_critical value: {}", value))?;
+            }
+
+            // Persona settings
+            "persona.default" => {
+                self.persona.default = value.to_string();
+            }
+
+            // Query instrumentation settings
+            "database.instrument" => {
+                self.database.instrument = value
+                    .parse()
+                    .with_context(|| format!("Invalid database.instrument value: {}", value))?;
+            }
+            "database.slow_query_ms" => {
+                self.database.slow_query_ms = value
+                    .parse()
+                    .with_context(|| format!("Invalid database.slow_query_ms value: {}", value))?;
+            }
+
+            // Knowledge aging settings
+            "knowledge.half_life_days" => {
+                let days: f64 = value.parse().with_context(|| {
+                    format!("Invalid knowledge.half_life_days value: {}", value)
+                })?;
+                if days < 0.0 {
+                    return Err(anyhow!("knowledge.half_life_days must be non-negative"));
+                }
+                self.knowledge.half_life_days = days;
+            }
+            "knowledge.archive_below" => {
+                let floor: f32 = value
+                    .parse()
+                    .with_context(|| format!("Invalid knowledge.archive_below value: {}", value))?;
+                if !(0.0..=1.0).contains(&floor) {
+                    return Err(anyhow!(
+                        "knowledge.archive_below must be between 0.0 and 1.0"
+                    ));
+                }
+                self.knowledge.archive_below = floor;
+            }
+
+            // Invoice settings
+            "invoice.markup_percent" => {
+                let markup: f64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid markup_percent value: {}", value))?;
+                if markup < 0.0 {
+                    return Err(anyhow!("invoice.markup_percent cannot be negative"));
+                }
+                self.invoice.markup_percent = markup;
+            }
+            "invoice.currency" => {
+                let code = value.trim().to_uppercase();
+                if !self.invoice.rates.contains_key(&code) {
+                    return Err(anyhow!(
+                        "No exchange rate for {}. Add one with `demiarch config set invoice.rates.{} <rate>`",
+                        code,
+                        code
+                    ));
+                }
+                self.invoice.currency = code;
+            }
+            key if key.starts_with("invoice.rates.") => {
+                let code = key["invoice.rates.".len()..].to_uppercase();
+                if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+                    return Err(anyhow!("Invalid currency code: {}", code));
+                }
+                let rate: f64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid exchange rate: {}", value))?;
+                if rate <= 0.0 {
+                    return Err(anyhow!("Exchange rates must be greater than 0"));
+                }
+                self.invoice.rates.insert(code, rate);
+            }
+
+            // Plugin limit settings
+            key if key.starts_with("plugins.limits.") => {
+                let (field, name) = self.plugins.limits.field_mut(key)?;
+                let limit: u64 = value
+                    .parse()
+                    .with_context(|| format!("Invalid {} value: {}", name, value))?;
+                if limit == 0 {
+                    return Err(anyhow!("{} must be greater than 0", key));
+                }
+                *field = limit;
+            }
+
             // API key cannot be set via config
             "llm.api_key" | "api_key" => {
                 return Err(anyhow!(
@@ -347,10 +1637,85 @@ impl Config {
             "llm.api_key",
             "cost.daily_limit_usd",
             "cost.alert_threshold",
+            "cost.confirm_above_usd",
+            "cost.monthly_limit_usd",
+            "cost.chat_preview",
             "routing.preference",
             "context.total_tokens",
             "context.output_reserve",
             "context.enable_compression",
+            "formatting.enabled",
+            "formatting.lint",
+            "formatting.timeout_secs",
+            "formatting.tools",
+            "cache.enabled",
+            "cache.ttl_secs",
+            "cache.max_size_mb",
+            "network.offline",
+            "ui.locale",
+            "ui.plain",
+            "ui.close_to_tray",
+            "safety.strictness",
+            "analytics.enabled",
+            "lifecycle.archive_after_days",
+            "lifecycle.checkpoint_retention_days",
+            "lifecycle.log_retention_days",
+            "lifecycle.event_retention_days",
+            "hooks.before_archive",
+            "hooks.before_purge",
+            "hooks.on_policy_violation",
+            "hooks.timeout_secs",
+            "notifications.enabled",
+            "notifications.poll_secs",
+            "secrets.reference_in_generation",
+            "events.webhooks",
+            "events.webhook_timeout_secs",
+            "updates.channel",
+            "updates.manifest_url",
+            "updates.notify",
+            "transcription.backend",
+            "transcription.model",
+            "transcription.api_url",
+            "transcription.language",
+            "transcription.whisper_cpp_bin",
+            "transcription.whisper_cpp_model",
+            "transcription.cost_per_minute_usd",
+            "checkpoint.triggers.before_restore",
+            "checkpoint.triggers.before_sync_import",
+            "checkpoint.triggers.before_bulk_features",
+            "checkpoint.triggers.on_session_end",
+            "checkpoint.triggers.interval_mins",
+            "invoice.markup_percent",
+            "invoice.currency",
+            "guardrails.protected_paths",
+            "guardrails.max_file_kb",
+            "guardrails.max_files",
+            "guardrails.max_diff_lines",
+            "approvals.after_planning",
+            "approvals.after_review",
+            "approvals.timeout_secs",
+            "generate.isolation",
+            "generate.verify_commands",
+            "generate.verify_timeout_secs",
+            "generate.install_dependencies",
+            "generate.branch_template",
+            "audit.enabled",
+            "audit.llm",
+            "audit.block_critical",
+            "persona.default",
+            "database.instrument",
+            "database.slow_query_ms",
+            "knowledge.half_life_days",
+            "knowledge.archive_below",
+            "plugins.limits.free.fuel",
+            "plugins.limits.free.memory_mb",
+            "plugins.limits.free.timeout_secs",
+            "plugins.limits.pro.fuel",
+            "plugins.limits.pro.memory_mb",
+            "plugins.limits.pro.timeout_secs",
+            "plugins.limits.enterprise.fuel",
+            "plugins.limits.enterprise.memory_mb",
+            "plugins.limits.enterprise.timeout_secs",
         ];
 
         keys.into_iter()
@@ -371,3 +1736,19 @@ impl Config {
         Ok(())
     }
 }
+
+/// Parse a day count for a lifecycle setting
+fn parse_days(key: &str, value: &str) -> anyhow::Result<u32> {
+    value
+        .parse()
+        .with_context(|| format!("Invalid {} value: {}", key, value))
+}
+
+/// Hook commands for display
+fn hook_list(commands: &[String]) -> String {
+    if commands.is_empty() {
+        "(none)".to_string()
+    } else {
+        commands.join("; ")
+    }
+}
@@ -0,0 +1,421 @@
+//! Config change notification and hot-reload
+//!
+//! Long-running processes (the GUI, `demiarch jobs run`) read the config once
+//! at startup. A [`ConfigWatcher`] polls `config.toml` for changes, re-parses
+//! and re-validates it, and publishes the new config on the process-wide
+//! [`ConfigHub`] along with what changed as typed [`ConfigChange`]s. Budgets
+//! and models are applied by the subsystems that hold them (see
+//! [`CostTracker::follow_config`](crate::cost::CostTracker::follow_config)
+//! and [`ModelRouter::follow_config`](crate::routing::ModelRouter::follow_config));
+//! everything else can read [`current`] or [`subscribe`].
+//!
+//! An edit that does not parse or validate is logged and skipped, and the
+//! previous config stays in effect. `demiarch config reload` touches a
+//! `reload` marker beside the config file, which makes every watcher reload
+//! even if the file itself looks unchanged.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, PoisonError, RwLock, Weak};
+use std::time::Duration;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use super::Config;
+
+/// How often a watcher checks the config file
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Name of the marker file `demiarch config reload` touches
+pub const RELOAD_MARKER: &str = "reload";
+
+/// Updates a slow subscriber may fall behind by before it has to catch up
+/// from the current config
+const UPDATE_BUFFER: usize = 16;
+
+/// One thing that differs between two configs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConfigChange {
+    /// `llm.default_model` or `llm.fallback_models`
+    ModelChanged {
+        default_model: String,
+        fallback_models: Vec<String>,
+    },
+    /// `cost.daily_limit_usd`, `cost.alert_threshold` or `cost.monthly_limit_usd`
+    BudgetChanged {
+        daily_limit_usd: f64,
+        alert_threshold: f64,
+        monthly_limit_usd: f64,
+    },
+    /// `routing.preference`
+    RoutingChanged { preference: String },
+    /// Any other setting, by its dotted key; values are left out so secrets
+    /// in other sections are never broadcast
+    SettingChanged { key: String },
+}
+
+/// Keys reported by the typed changes rather than as [`ConfigChange::SettingChanged`]
+const TYPED_KEYS: &[&str] = &[
+    "llm.default_model",
+    "llm.fallback_models",
+    "cost.daily_limit_usd",
+    "cost.alert_threshold",
+    "cost.monthly_limit_usd",
+    "routing.preference",
+];
+
+/// What changed from `old` to `new`
+pub fn diff(old: &Config, new: &Config) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    if old.llm.default_model != new.llm.default_model
+        || old.llm.fallback_models != new.llm.fallback_models
+    {
+        changes.push(ConfigChange::ModelChanged {
+            default_model: new.llm.default_model.clone(),
+            fallback_models: new.llm.fallback_models.clone(),
+        });
+    }
+    if old.cost.daily_limit_usd != new.cost.daily_limit_usd
+        || old.cost.alert_threshold != new.cost.alert_threshold
+        || old.cost.monthly_limit_usd != new.cost.monthly_limit_usd
+    {
+        changes.push(ConfigChange::BudgetChanged {
+            daily_limit_usd: new.cost.daily_limit_usd,
+            alert_threshold: new.cost.alert_threshold,
+            monthly_limit_usd: new.cost.monthly_limit_usd,
+        });
+    }
+    if old.routing.preference != new.routing.preference {
+        changes.push(ConfigChange::RoutingChanged {
+            preference: new.routing.preference.clone(),
+        });
+    }
+
+    let old_values = flatten(old);
+    let new_values = flatten(new);
+    let mut keys: Vec<&String> = old_values.keys().chain(new_values.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        if !TYPED_KEYS.contains(&key.as_str()) && old_values.get(key) != new_values.get(key) {
+            changes.push(ConfigChange::SettingChanged { key: key.clone() });
+        }
+    }
+    changes
+}
+
+/// Every leaf setting of `config` by dotted key
+fn flatten(config: &Config) -> BTreeMap<String, toml::Value> {
+    let mut values = BTreeMap::new();
+    if let Ok(toml::Value::Table(table)) = toml::Value::try_from(config) {
+        flatten_into("", table, &mut values);
+    }
+    values
+}
+
+fn flatten_into(prefix: &str, table: toml::Table, values: &mut BTreeMap<String, toml::Value>) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        match value {
+            toml::Value::Table(table) => flatten_into(&key, table, values),
+            value => {
+                values.insert(key, value);
+            }
+        }
+    }
+}
+
+/// A config that took effect and what changed in it
+#[derive(Debug, Clone)]
+pub struct ConfigUpdate {
+    pub config: Arc<Config>,
+    pub changes: Vec<ConfigChange>,
+}
+
+/// The config in effect, and a broadcast of each change to it
+#[derive(Debug)]
+pub struct ConfigHub {
+    current: RwLock<Arc<Config>>,
+    updates: broadcast::Sender<Arc<ConfigUpdate>>,
+}
+
+impl ConfigHub {
+    pub fn new(config: Config) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(config)),
+            updates: broadcast::channel(UPDATE_BUFFER).0,
+        }
+    }
+
+    /// The config in effect
+    pub fn current(&self) -> Arc<Config> {
+        Arc::clone(&self.current.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Receive every update published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<ConfigUpdate>> {
+        self.updates.subscribe()
+    }
+
+    /// Put `config` into effect, broadcasting what changed, if anything
+    pub fn publish(&self, config: Config) -> Vec<ConfigChange> {
+        let config = Arc::new(config);
+        let changes = {
+            let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+            let changes = diff(&current, &config);
+            *current = Arc::clone(&config);
+            changes
+        };
+        if !changes.is_empty() {
+            // No receivers is fine: nothing is following the config yet
+            let _ = self.updates.send(Arc::new(ConfigUpdate {
+                config,
+                changes: changes.clone(),
+            }));
+        }
+        changes
+    }
+
+    /// Call `apply` with each change published from now on until `target`
+    /// is dropped
+    ///
+    /// Needs a tokio runtime; outside of one, `target` keeps the settings it
+    /// was created with.
+    pub fn follow<T, F>(&'static self, target: Weak<T>, apply: F)
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T, &ConfigChange) + Send + 'static,
+    {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let mut updates = self.subscribe();
+        let mut seen = self.current();
+        handle.spawn(async move {
+            loop {
+                let changes = match updates.recv().await {
+                    Ok(update) => {
+                        seen = Arc::clone(&update.config);
+                        update.changes.clone()
+                    }
+                    // Missed updates are made up by diffing against the latest
+                    Err(RecvError::Lagged(_)) => {
+                        let latest = self.current();
+                        let changes = diff(&seen, &latest);
+                        seen = latest;
+                        changes
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let Some(target) = target.upgrade() else {
+                    break;
+                };
+                for change in &changes {
+                    apply(&target, change);
+                }
+            }
+        });
+    }
+}
+
+static HUB: OnceLock<ConfigHub> = OnceLock::new();
+
+/// The process-wide config hub, starting from the config on disk
+pub fn hub() -> &'static ConfigHub {
+    HUB.get_or_init(|| ConfigHub::new(Config::load().unwrap_or_default()))
+}
+
+/// The config in effect in this process
+pub fn current() -> Arc<Config> {
+    hub().current()
+}
+
+/// Receive every config update published in this process from now on
+pub fn subscribe() -> broadcast::Receiver<Arc<ConfigUpdate>> {
+    hub().subscribe()
+}
+
+/// Ask every running watcher to reload the config
+pub fn request_reload() -> anyhow::Result<PathBuf> {
+    let dir = Config::config_dir()?;
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create config directory: {}", dir.display()))?;
+    let marker = dir.join(RELOAD_MARKER);
+    std::fs::write(&marker, chrono::Utc::now().to_rfc3339())
+        .with_context(|| format!("Failed to write {}", marker.display()))?;
+    Ok(marker)
+}
+
+/// Polls a config file and its reload marker for changes
+pub struct ConfigWatcher {
+    path: PathBuf,
+    marker: PathBuf,
+    interval: Duration,
+    /// Hashes of the config file and marker as last seen; `None` until the
+    /// first poll
+    seen: Option<(Option<String>, Option<String>)>,
+}
+
+impl ConfigWatcher {
+    /// Watch the user's config file
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self::at(Config::config_path()?))
+    }
+
+    /// Watch the config file at `path`
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let marker = path.with_file_name(RELOAD_MARKER);
+        Self {
+            path,
+            marker,
+            interval: DEFAULT_POLL_INTERVAL,
+            seen: None,
+        }
+    }
+
+    /// Set the polling interval
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Load the config if the file changed or a reload was requested since
+    /// the last call
+    ///
+    /// The first call only records the current state. An edit that fails to
+    /// parse or validate is returned once as an error and not retried until
+    /// the file changes again.
+    pub fn poll(&mut self) -> Option<anyhow::Result<Config>> {
+        let state = (file_hash(&self.path), file_hash(&self.marker));
+        let previous = self.seen.replace(state.clone())?;
+        if previous == state {
+            return None;
+        }
+        Some(Config::load_from(&self.path))
+    }
+
+    /// Poll forever, publishing each reloaded config on the process-wide hub
+    pub async fn run(mut self) {
+        let hub = hub();
+        self.poll();
+        loop {
+            tokio::time::sleep(self.interval).await;
+            match self.poll() {
+                Some(Ok(config)) => {
+                    let changes = hub.publish(config);
+                    tracing::info!(changes = changes.len(), "Reloaded configuration");
+                    for change in &changes {
+                        tracing::debug!(?change, "Configuration changed");
+                    }
+                }
+                Some(Err(e)) => {
+                    tracing::warn!(error = %format!("{:#}", e), "Ignoring invalid configuration; keeping the previous one");
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Run the watcher on the current tokio runtime, if there is one
+    pub fn spawn(self) -> Option<tokio::task::JoinHandle<()>> {
+        let handle = tokio::runtime::Handle::try_current().ok()?;
+        Some(handle.spawn(self.run()))
+    }
+}
+
+/// Hex SHA-256 of a file's contents, `None` if it cannot be read
+fn file_hash(path: &Path) -> Option<String> {
+    let contents = std::fs::read(path).ok()?;
+    Some(hex::encode(Sha256::digest(&contents)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_reports_typed_and_other_changes() {
+        let old = Config::default();
+        assert!(diff(&old, &old).is_empty());
+
+        let mut new = old.clone();
+        new.llm.default_model = "openai/gpt-4o".to_string();
+        new.cost.daily_limit_usd = 25.0;
+        new.routing.preference = "cost".to_string();
+        new.ui.close_to_tray = !old.ui.close_to_tray;
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                ConfigChange::ModelChanged {
+                    default_model: "openai/gpt-4o".to_string(),
+                    fallback_models: old.llm.fallback_models.clone(),
+                },
+                ConfigChange::BudgetChanged {
+                    daily_limit_usd: 25.0,
+                    alert_threshold: old.cost.alert_threshold,
+                    monthly_limit_usd: old.cost.monthly_limit_usd,
+                },
+                ConfigChange::RoutingChanged {
+                    preference: "cost".to_string(),
+                },
+                ConfigChange::SettingChanged {
+                    key: "ui.close_to_tray".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hub_publishes_changes() {
+        let hub = ConfigHub::new(Config::default());
+        let mut updates = hub.subscribe();
+
+        assert!(hub.publish(Config::default()).is_empty());
+        let mut config = Config::default();
+        config.cost.alert_threshold = 0.5;
+        assert_eq!(hub.publish(config).len(), 1);
+
+        let update = updates.recv().await.unwrap();
+        assert_eq!(update.config.cost.alert_threshold, 0.5);
+        assert!(matches!(
+            update.changes.as_slice(),
+            [ConfigChange::BudgetChanged { .. }]
+        ));
+        assert_eq!(hub.current().cost.alert_threshold, 0.5);
+    }
+
+    #[test]
+    fn test_watcher_reloads_on_change_and_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut config = Config::default();
+        std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+
+        let mut watcher = ConfigWatcher::at(&path);
+        assert!(watcher.poll().is_none());
+        assert!(watcher.poll().is_none());
+
+        config.cost.daily_limit_usd = 3.0;
+        std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+        let reloaded = watcher.poll().unwrap().unwrap();
+        assert_eq!(reloaded.cost.daily_limit_usd, 3.0);
+        assert!(watcher.poll().is_none());
+
+        std::fs::write(dir.path().join(RELOAD_MARKER), "now").unwrap();
+        assert!(watcher.poll().unwrap().is_ok());
+
+        std::fs::write(&path, "[llm\n").unwrap();
+        assert!(watcher.poll().unwrap().is_err());
+        assert!(watcher.poll().is_none());
+    }
+}
@@ -1,6 +1,6 @@
 //! Config module tests
 
-use crate::config::{Config, CostConfig, RoutingConfig};
+use crate::config::{Config, CostConfig, RoutingConfig, WebhookConfig};
 
 #[test]
 fn test_llm_config_fallback_models() {
@@ -21,10 +21,16 @@ fn test_cost_config_values() {
     let cost = CostConfig {
         daily_limit_usd: 50.0,
         alert_threshold: 0.9,
+        confirm_above_usd: 1.0,
+        monthly_limit_usd: 200.0,
+        chat_preview: true,
     };
 
     assert_eq!(cost.daily_limit_usd, 50.0);
     assert_eq!(cost.alert_threshold, 0.9);
+    assert_eq!(cost.confirm_above_usd, 1.0);
+    assert_eq!(cost.monthly_limit_usd, 200.0);
+    assert!(cost.chat_preview);
 }
 
 #[test]
@@ -104,3 +110,381 @@ fn test_config_custom_fallback_models() {
 
     assert_eq!(config.llm.fallback_models.len(), 2);
 }
+
+#[test]
+fn test_formatting_config_set_get() {
+    let mut config = Config::default();
+    assert!(config.formatting.enabled);
+    assert_eq!(config.get("formatting.tools").unwrap(), "(auto)");
+
+    config.set("formatting.tools", "rustfmt, Prettier").unwrap();
+    assert_eq!(config.formatting.tools, vec!["rustfmt", "prettier"]);
+    config.set("formatting.enabled", "false").unwrap();
+    assert_eq!(config.get("formatting.enabled").unwrap(), "false");
+
+    assert!(config.set("formatting.tools", "gofmt").is_err());
+    assert!(config.set("formatting.timeout_secs", "0").is_err());
+}
+
+#[test]
+fn test_cache_config_set_get() {
+    let mut config = Config::default();
+    assert!(!config.cache.enabled);
+    assert_eq!(config.get("cache.max_size_mb").unwrap(), "50");
+
+    config.set("cache.enabled", "true").unwrap();
+    config.set("cache.ttl_secs", "3600").unwrap();
+    assert!(config.cache.enabled);
+    assert_eq!(config.cache.ttl_secs, 3600);
+
+    assert!(config.set("cache.ttl_secs", "0").is_err());
+    assert!(config.set("cache.max_size_mb", "lots").is_err());
+}
+
+#[test]
+fn test_analytics_is_opt_in() {
+    let mut config = Config::default();
+    assert!(!config.analytics.enabled);
+
+    config.set("analytics.enabled", "true").unwrap();
+    assert_eq!(config.get("analytics.enabled").unwrap(), "true");
+    assert!(config.set("analytics.enabled", "sure").is_err());
+}
+
+#[test]
+fn test_network_config_offline() {
+    let mut config = Config::default();
+    assert!(!config.network.offline);
+
+    config.set("network.offline", "true").unwrap();
+    assert_eq!(config.get("network.offline").unwrap(), "true");
+    assert!(config.set("network.offline", "maybe").is_err());
+
+    let parsed: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+    assert!(parsed.network.offline);
+}
+
+#[test]
+fn test_ui_config_locale() {
+    let mut config = Config::default();
+    assert!(config.ui.locale.is_empty());
+
+    config.set("ui.locale", " pt-BR ").unwrap();
+    assert_eq!(config.get("ui.locale").unwrap(), "pt-BR");
+
+    let parsed: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+    assert_eq!(parsed.ui.locale, "pt-BR");
+}
+
+#[test]
+fn test_ui_config_plain() {
+    let mut config = Config::default();
+    assert!(!config.ui.plain);
+
+    config.set("ui.plain", "true").unwrap();
+    assert_eq!(config.get("ui.plain").unwrap(), "true");
+    assert!(config.set("ui.plain", "sometimes").is_err());
+}
+
+#[test]
+fn test_safety_config_strictness() {
+    let mut config = Config::default();
+    assert_eq!(config.safety.strictness, "standard");
+
+    config.set("safety.strictness", "Strict").unwrap();
+    assert_eq!(config.get("safety.strictness").unwrap(), "strict");
+    assert!(config.set("safety.strictness", "paranoid").is_err());
+
+    let parsed: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+    assert_eq!(parsed.safety.strictness, "strict");
+}
+
+#[test]
+fn test_lifecycle_config() {
+    let mut config = Config::default();
+    assert_eq!(config.lifecycle.archive_after_days, 0);
+    assert_eq!(config.get("hooks.before_archive").unwrap(), "(none)");
+
+    config.set("lifecycle.archive_after_days", "60").unwrap();
+    assert_eq!(config.get("lifecycle.archive_after_days").unwrap(), "60");
+    assert!(config.set("lifecycle.log_retention_days", "-1").is_err());
+    assert!(config.set("hooks.before_purge", "true").is_err());
+
+    let parsed: Config = toml::from_str(
+        "[llm]\ndefault_model = \"m\"\nfallback_models = []\ntemperature = 0.7\nmax_tokens = 1\ntimeout_secs = 1\n\
+         [cost]\ndaily_limit_usd = 1.0\nalert_threshold = 0.5\n[routing]\npreference = \"balanced\"\n\
+         [hooks]\nbefore_archive = [\"./backup.sh\"]\n",
+    )
+    .unwrap();
+    assert_eq!(parsed.hooks.before_archive, vec!["./backup.sh"]);
+    assert_eq!(parsed.hooks.timeout_secs, 30);
+}
+
+#[test]
+fn test_notifications_config() {
+    let mut config = Config::default();
+    assert!(config.notifications.enabled);
+    assert_eq!(config.get("notifications.poll_secs").unwrap(), "15");
+
+    config.set("notifications.enabled", "false").unwrap();
+    assert!(!config.notifications.enabled);
+    assert!(config.set("notifications.poll_secs", "0").is_err());
+}
+
+#[test]
+fn test_secrets_config() {
+    let mut config = Config::default();
+    assert_eq!(
+        config.get("secrets.reference_in_generation").unwrap(),
+        "true"
+    );
+    config
+        .set("secrets.reference_in_generation", "false")
+        .unwrap();
+    assert!(!config.secrets.reference_in_generation);
+    assert!(config.set("secrets.reference_in_generation", "1").is_err());
+}
+
+#[test]
+fn test_close_to_tray_config() {
+    let mut config = Config::default();
+    assert_eq!(config.get("ui.close_to_tray").unwrap(), "false");
+    config.set("ui.close_to_tray", "true").unwrap();
+    assert!(config.ui.close_to_tray);
+    assert!(config.set("ui.close_to_tray", "sometimes").is_err());
+}
+
+#[test]
+fn test_events_config() {
+    let webhook: WebhookConfig = toml::from_str(
+        r#"
+        url = "https://example.com/hook"
+        events = ["feature_created"]
+        "#,
+    )
+    .unwrap();
+    let mut config = Config::default();
+    config.events.webhooks.push(webhook);
+    assert_eq!(config.events.webhooks.len(), 1);
+    assert_eq!(
+        config.events.webhooks[0].events,
+        vec![crate::events::EventKind::FeatureCreated]
+    );
+    assert_eq!(
+        config.get("events.webhooks").unwrap(),
+        "https://example.com/hook"
+    );
+    assert!(config.set("events.webhooks", "https://other").is_err());
+    config.set("events.webhook_timeout_secs", "3").unwrap();
+    assert_eq!(config.events.webhook_timeout_secs, 3);
+    assert!(config.set("events.webhook_timeout_secs", "0").is_err());
+}
+
+#[test]
+fn test_plugin_limits_config() {
+    let mut config = Config::default();
+    assert_eq!(config.get("plugins.limits.free.timeout_secs").unwrap(), "5");
+    config.set("plugins.limits.pro.fuel", "5000").unwrap();
+    assert_eq!(
+        config
+            .plugins
+            .limits
+            .for_tier(crate::commands::license::LicenseTier::Pro)
+            .fuel,
+        5000
+    );
+    assert!(config.set("plugins.limits.pro.fuel", "0").is_err());
+    assert!(config.set("plugins.limits.gold.fuel", "1").is_err());
+    assert!(config.get("plugins.limits.free.cpu").is_err());
+}
+
+#[test]
+fn test_updates_config() {
+    let mut config = Config::default();
+    assert_eq!(config.get("updates.channel").unwrap(), "stable");
+    config.set("updates.channel", "Beta").unwrap();
+    assert_eq!(config.updates.channel, "beta");
+    assert!(config.set("updates.channel", "nightly").is_err());
+    assert!(config
+        .set("updates.manifest_url", "http://insecure.example/{channel}")
+        .is_err());
+    config.set("updates.notify", "false").unwrap();
+    assert!(!config.updates.notify);
+}
+
+#[test]
+fn test_transcription_config() {
+    let mut config = Config::default();
+    assert_eq!(config.get("transcription.backend").unwrap(), "openai");
+    config.set("transcription.backend", "whisper-cpp").unwrap();
+    assert_eq!(config.transcription.backend, "whisper_cpp");
+    assert!(config.set("transcription.backend", "vosk").is_err());
+    assert!(config
+        .set("transcription.api_url", "http://insecure.example")
+        .is_err());
+    assert!(config
+        .set("transcription.cost_per_minute_usd", "-1")
+        .is_err());
+    config.set("transcription.language", "DE").unwrap();
+    assert_eq!(config.transcription.language, "de");
+}
+
+#[test]
+fn test_checkpoint_triggers_config() {
+    let mut config = Config::default();
+    assert_eq!(
+        config.get("checkpoint.triggers.before_restore").unwrap(),
+        "true"
+    );
+    assert_eq!(
+        config.get("checkpoint.triggers.interval_mins").unwrap(),
+        "0"
+    );
+    config
+        .set("checkpoint.triggers.on_session_end", "true")
+        .unwrap();
+    assert!(config.checkpoint.triggers.on_session_end);
+    config
+        .set("checkpoint.triggers.before_sync_import", "false")
+        .unwrap();
+    assert!(!config.checkpoint.triggers.before_sync_import);
+    config
+        .set("checkpoint.triggers.interval_mins", "15")
+        .unwrap();
+    assert_eq!(config.checkpoint.triggers.interval_mins, 15);
+    assert!(config
+        .set("checkpoint.triggers.before_restore", "sometimes")
+        .is_err());
+    assert!(config
+        .set("checkpoint.triggers.interval_mins", "-5")
+        .is_err());
+}
+
+#[test]
+fn test_invoice_config() {
+    let mut config = Config::default();
+    assert_eq!(config.get("invoice.currency").unwrap(), "USD");
+    assert_eq!(config.get("invoice.rates.eur").unwrap(), "0.92");
+
+    config.set("invoice.markup_percent", "15").unwrap();
+    config.set("invoice.rates.NZD", "1.65").unwrap();
+    config.set("invoice.currency", "nzd").unwrap();
+    assert_eq!(config.invoice.markup_percent, 15.0);
+    assert_eq!(config.invoice.currency, "NZD");
+
+    assert!(config.set("invoice.currency", "XYZ").is_err());
+    assert!(config.set("invoice.rates.EUR", "0").is_err());
+    assert!(config.set("invoice.rates.EURO", "1.0").is_err());
+    assert!(config.set("invoice.markup_percent", "-5").is_err());
+}
+
+#[test]
+fn test_guardrails_config() {
+    let mut config = Config::default();
+    assert_eq!(
+        config.get("guardrails.protected_paths").unwrap(),
+        ".git/**, .demiarch/**"
+    );
+    assert_eq!(config.get("guardrails.max_files").unwrap(), "50");
+
+    config
+        .set("guardrails.protected_paths", "infra/**, *.lock")
+        .unwrap();
+    config.set("guardrails.max_diff_lines", "0").unwrap();
+    assert_eq!(
+        config.guardrails.protected_paths,
+        vec!["infra/**", "*.lock"]
+    );
+    assert_eq!(config.guardrails.max_diff_lines, 0);
+
+    assert!(config.set("guardrails.protected_paths", "src/[").is_err());
+    assert!(config.set("guardrails.max_file_kb", "big").is_err());
+    assert!(config.set("hooks.on_policy_violation", "true").is_err());
+}
+
+#[test]
+fn test_approvals_config() {
+    let mut config = Config::default();
+    assert_eq!(config.get("approvals.after_planning").unwrap(), "false");
+    assert_eq!(config.get("approvals.timeout_secs").unwrap(), "600");
+
+    config.set("approvals.after_planning", "true").unwrap();
+    config.set("approvals.timeout_secs", "120").unwrap();
+    assert!(config.approvals.after_planning);
+    assert_eq!(config.approvals.timeout_secs, 120);
+
+    assert!(config.set("approvals.after_review", "maybe").is_err());
+    assert!(config.set("approvals.timeout_secs", "0").is_err());
+}
+
+#[test]
+fn test_generate_config() {
+    let mut config = Config::default();
+    assert_eq!(config.get("generate.isolation").unwrap(), "in_place");
+    assert_eq!(config.get("generate.verify_commands").unwrap(), "(none)");
+
+    config.set("generate.isolation", "worktree").unwrap();
+    assert_eq!(config.generate.isolation, "worktree");
+    config.set("generate.isolation", "in-place").unwrap();
+    assert_eq!(config.generate.isolation, "in_place");
+
+    assert!(config.set("generate.isolation", "container").is_err());
+    assert!(config.set("generate.verify_timeout_secs", "0").is_err());
+
+    assert_eq!(
+        config.get("generate.install_dependencies").unwrap(),
+        "false"
+    );
+    config.set("generate.install_dependencies", "true").unwrap();
+    assert!(config.generate.install_dependencies);
+    assert!(config.set("generate.install_dependencies", "yes").is_err());
+}
+
+#[test]
+fn test_audit_config() {
+    let mut config = Config::default();
+    assert_eq!(config.get("audit.enabled").unwrap(), "false");
+    assert_eq!(config.get("audit.block_critical").unwrap(), "true");
+
+    config.set("audit.enabled", "true").unwrap();
+    config.set("audit.llm", "false").unwrap();
+    assert!(config.audit.enabled);
+    assert!(!config.audit.llm);
+    assert!(config.set("audit.block_critical", "sometimes").is_err());
+}
+
+#[test]
+fn test_database_settings() {
+    let mut config = Config::default();
+    assert_eq!(config.get("database.instrument").unwrap(), "false");
+    assert_eq!(config.get("database.slow_query_ms").unwrap(), "200");
+
+    config.set("database.instrument", "true").unwrap();
+    config.set("database.slow_query_ms", "50").unwrap();
+    assert!(config.database.instrument);
+    assert_eq!(config.database.slow_query_ms, 50);
+    assert!(config.set("database.slow_query_ms", "-1").is_err());
+}
+
+#[test]
+fn test_knowledge_settings() {
+    let mut config = Config::default();
+    assert_eq!(config.get("knowledge.half_life_days").unwrap(), "90");
+
+    config.set("knowledge.half_life_days", "30").unwrap();
+    config.set("knowledge.archive_below", "0.05").unwrap();
+    assert_eq!(config.knowledge.half_life_days, 30.0);
+    assert_eq!(config.knowledge.archive_below, 0.05);
+    assert!(config.set("knowledge.half_life_days", "-1").is_err());
+    assert!(config.set("knowledge.archive_below", "2").is_err());
+}
+
+#[test]
+fn test_persona_config() {
+    let mut config = Config::default();
+    assert_eq!(config.get("persona.default").unwrap(), "");
+
+    config.set("persona.default", "reviewer").unwrap();
+    assert_eq!(config.persona.default, "reviewer");
+    assert_eq!(config.get("persona.default").unwrap(), "reviewer");
+}
@@ -10,6 +10,7 @@
 //! - **DisclosureLevel**: Granularity of context (Full, Summary, Essential, Minimal)
 //! - **ContextWindow**: Manages context within token limits with automatic compression
 //! - **MessageSummarizer**: Compresses messages while preserving semantic content
+//! - **ContextPacker**: Picks the most relevant candidate context that fits a budget
 //!
 //! # Usage
 //!
@@ -29,6 +30,12 @@
 //! window.add_message(message);
 //! ```
 
+pub mod packing;
+
+pub use packing::{
+    ContextPacker, ContextPiece, Exclusion, PackingReport, PackingStrategy, PieceKind,
+};
+
 use std::collections::VecDeque;
 
 use serde::{Deserialize, Serialize};
@@ -253,10 +260,14 @@ pub struct ContextWindow {
     allocation: TokenAllocation,
     /// System messages (always preserved)
     system_messages: Vec<Message>,
+    /// Pinned messages (always preserved and passed to children verbatim)
+    pinned_messages: Vec<Message>,
     /// Context messages (can be compressed/pruned)
     context_messages: VecDeque<Message>,
     /// Current token count for system messages
     system_tokens: usize,
+    /// Current token count for pinned messages
+    pinned_tokens: usize,
     /// Current token count for context messages
     context_tokens: usize,
     /// Disclosure level for this window
@@ -269,8 +280,10 @@ impl ContextWindow {
         Self {
             allocation,
             system_messages: Vec::new(),
+            pinned_messages: Vec::new(),
             context_messages: VecDeque::new(),
             system_tokens: 0,
+            pinned_tokens: 0,
             context_tokens: 0,
             disclosure_level: DisclosureLevel::Full,
         }
@@ -294,6 +307,21 @@ impl ContextWindow {
         self.system_tokens += tokens;
     }
 
+    /// Add a pinned message
+    ///
+    /// Pinned messages are never pruned or compressed, and child windows
+    /// inherit them unchanged regardless of disclosure level.
+    pub fn add_pinned_message(&mut self, message: Message) {
+        let tokens = estimate_message_tokens(&message);
+        self.pinned_messages.push(message);
+        self.pinned_tokens += tokens;
+    }
+
+    /// Pinned messages in the order they were added
+    pub fn pinned_messages(&self) -> &[Message] {
+        &self.pinned_messages
+    }
+
     /// Add a context message, managing overflow
     pub fn add_context_message(&mut self, message: Message) {
         let tokens = estimate_message_tokens(&message);
@@ -320,16 +348,91 @@ impl ContextWindow {
         }
     }
 
-    /// Get all messages in order (system first, then context)
+    /// Add the candidate pieces that fit the room left for context
+    ///
+    /// Rather than evicting the oldest context to make room, the most
+    /// relevant pieces that fit are chosen (see [`ContextPacker`]) and the
+    /// rest are left out. The decision is logged at debug level.
+    pub fn add_packed(&mut self, pieces: Vec<ContextPiece>) -> PackingReport {
+        let room = self
+            .allocation
+            .context_tokens
+            .saturating_sub(self.context_tokens);
+        let report = ContextPacker::new(room).pack(pieces);
+        report.log("context window");
+        for message in report.messages() {
+            self.context_tokens += estimate_message_tokens(&message);
+            self.context_messages.push_back(message);
+        }
+        report
+    }
+
+    /// Add conversation history, oldest first
+    ///
+    /// The newest messages are kept verbatim for as long as they fit in the
+    /// context allocation. Older messages that don't fit are folded into a
+    /// single rolling summary placed ahead of them, capped at a quarter of
+    /// the allocation.
+    pub fn add_history(&mut self, history: &[Message]) -> HistoryFit {
+        let budget = self.allocation.context_tokens;
+        let total = estimate_messages_tokens(history);
+
+        let recent_budget = if total <= budget {
+            budget
+        } else {
+            budget - budget / 4
+        };
+        let mut split = history.len();
+        let mut recent_tokens = 0usize;
+        while split > 0 {
+            let tokens = estimate_message_tokens(&history[split - 1]);
+            // Always keep the newest message, truncated if need be
+            if split < history.len() && recent_tokens + tokens > recent_budget {
+                break;
+            }
+            recent_tokens += tokens;
+            split -= 1;
+        }
+
+        let (older, recent) = history.split_at(split);
+        let mut summary_tokens = 0;
+        if !older.is_empty() {
+            let summary = rolling_summary(older, budget / 4);
+            summary_tokens = estimate_message_tokens(&summary);
+            self.add_context_message(summary);
+        }
+        for message in recent {
+            self.add_context_message(message.clone());
+        }
+
+        HistoryFit {
+            kept: recent.len(),
+            summarized: older.len(),
+            summary_tokens,
+        }
+    }
+
+    /// Token allocation for this window
+    pub fn allocation(&self) -> TokenAllocation {
+        self.allocation
+    }
+
+    /// Tokens used by context messages
+    pub fn context_tokens(&self) -> usize {
+        self.context_tokens
+    }
+
+    /// Get all messages in order (system first, then pinned, then context)
     pub fn messages(&self) -> Vec<Message> {
         let mut result = self.system_messages.clone();
+        result.extend(self.pinned_messages.iter().cloned());
         result.extend(self.context_messages.iter().cloned());
         result
     }
 
     /// Get the current token count
     pub fn token_count(&self) -> usize {
-        self.system_tokens + self.context_tokens
+        self.system_tokens + self.pinned_tokens + self.context_tokens
     }
 
     /// Get remaining token capacity
@@ -339,6 +442,11 @@ impl ContextWindow {
             .saturating_sub(self.token_count())
     }
 
+    /// How full the window is
+    pub fn stats(&self) -> WindowStats {
+        WindowStats::new(self.token_count(), self.allocation.total_input())
+    }
+
     /// Check if the window is at or near capacity
     pub fn is_near_capacity(&self) -> bool {
         let usage = self.token_count() as f32 / self.allocation.total_input() as f32;
@@ -362,6 +470,10 @@ impl ContextWindow {
         let disclosure = DisclosureLevel::for_depth(child_depth);
         let mut child = ContextWindow::new(child_allocation).with_disclosure_level(disclosure);
 
+        for message in &self.pinned_messages {
+            child.add_pinned_message(message.clone());
+        }
+
         // Compress and inherit context based on disclosure level
         let compressed_context = self.compress_for_disclosure(disclosure);
 
@@ -494,6 +606,125 @@ impl ContextWindow {
     }
 }
 
+/// How conversation history was fitted into a [`ContextWindow`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryFit {
+    /// Messages kept verbatim
+    pub kept: usize,
+    /// Older messages folded into the rolling summary
+    pub summarized: usize,
+    /// Tokens used by the rolling summary
+    pub summary_tokens: usize,
+}
+
+/// Share of a context window in use above which frontends warn
+pub const CONTEXT_WARNING_RATIO: f32 = 0.8;
+
+/// How much of a context window a prompt takes, e.g. "6.2k / 16k tokens"
+///
+/// Shown after each chat turn and per agent in the TUI. The count starts as
+/// an estimate and is replaced by the provider's prompt token count once a
+/// response reports one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowStats {
+    /// Tokens in the prompt
+    pub used_tokens: usize,
+    /// Input tokens the window allows
+    pub capacity_tokens: usize,
+    /// Whether `used_tokens` was reported by the provider
+    #[serde(default)]
+    pub measured: bool,
+}
+
+impl WindowStats {
+    /// Estimated usage of a window of `capacity_tokens`
+    pub fn new(used_tokens: usize, capacity_tokens: usize) -> Self {
+        Self {
+            used_tokens,
+            capacity_tokens,
+            measured: false,
+        }
+    }
+
+    /// Estimated usage of `messages` in a window of `capacity_tokens`
+    pub fn for_messages(messages: &[Message], capacity_tokens: usize) -> Self {
+        Self::new(estimate_messages_tokens(messages), capacity_tokens)
+    }
+
+    /// Use the provider's prompt token count; 0 (not reported) keeps the estimate
+    pub fn with_measured(mut self, prompt_tokens: u32) -> Self {
+        if prompt_tokens > 0 {
+            self.used_tokens = prompt_tokens as usize;
+            self.measured = true;
+        }
+        self
+    }
+
+    /// Share of the window in use; above 1.0 when over capacity
+    pub fn ratio(&self) -> f32 {
+        if self.capacity_tokens == 0 {
+            return 0.0;
+        }
+        self.used_tokens as f32 / self.capacity_tokens as f32
+    }
+
+    /// Whether usage is above [`CONTEXT_WARNING_RATIO`]
+    pub fn is_near_limit(&self) -> bool {
+        self.ratio() > CONTEXT_WARNING_RATIO
+    }
+}
+
+impl std::fmt::Display for WindowStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} / {} tokens",
+            format_token_count(self.used_tokens),
+            format_token_count(self.capacity_tokens)
+        )
+    }
+}
+
+/// Compact token count, e.g. 950 -> "950", 6200 -> "6.2k", 16000 -> "16k"
+pub fn format_token_count(tokens: usize) -> String {
+    if tokens < 1_000 {
+        return tokens.to_string();
+    }
+    let (value, unit) = if tokens < 1_000_000 {
+        (tokens as f64 / 1_000.0, "k")
+    } else {
+        (tokens as f64 / 1_000_000.0, "M")
+    };
+    let formatted = format!("{:.1}", value);
+    format!("{}{}", formatted.trim_end_matches(".0"), unit)
+}
+
+/// Fold messages into one summary message of at most `max_tokens`
+fn rolling_summary(messages: &[Message], max_tokens: usize) -> Message {
+    let header = format!("Summary of {} earlier messages:", messages.len());
+    let max_chars = max_tokens
+        .saturating_mul(4)
+        .saturating_sub(header.len() + 8);
+
+    // Keep the most recent lines when the summary itself is too long
+    let mut lines: Vec<String> = Vec::new();
+    let mut chars = 0usize;
+    for message in messages.iter().rev() {
+        let line = format!("- {}: {}", message.role, summarize_text(&message.content));
+        if chars + line.len() + 1 > max_chars {
+            if lines.is_empty() {
+                lines.push(line.chars().take(max_chars).collect());
+            }
+            break;
+        }
+        chars += line.len() + 1;
+        lines.push(line);
+    }
+    lines.reverse();
+
+    Message::system(format!("{}\n{}", header, lines.join("\n")))
+}
+
 /// Progressive context manager for agent hierarchies
 ///
 /// Coordinates context passing between parent and child agents,
@@ -552,12 +783,13 @@ impl Default for ProgressiveContext {
 // Context manager facade
 // ----------------------------------------------------------------------------
 use crate::domain::memory::{
-    ContextStats, MemoryError, MemoryRecord, MemoryStore, PersistentMemoryStore, RecallQuery,
-    SimpleEmbedder,
+    ConsolidationReport, ContextStats, MemoryError, MemoryRecord, MemoryStore,
+    PersistentMemoryStore, RecallQuery, SimpleEmbedder, DEFAULT_PIN_BUDGET_TOKENS,
 };
 use chrono::{Duration, Utc};
 use std::fmt;
 use std::sync::Arc;
+use uuid::Uuid;
 
 /// High-level manager that wraps progressive context handling and storage.
 #[derive(Clone)]
@@ -565,6 +797,7 @@ pub struct ContextManager {
     store: MemoryStore,
     persistent: Option<PersistentMemoryStore>,
     budget: ContextBudget,
+    pin_budget: usize,
 }
 
 impl ContextManager {
@@ -574,6 +807,7 @@ impl ContextManager {
             store: MemoryStore::new(Arc::new(SimpleEmbedder::default()), "context-embedder"),
             persistent: None,
             budget: ContextBudget::default(),
+            pin_budget: DEFAULT_PIN_BUDGET_TOKENS,
         }
     }
 
@@ -583,6 +817,7 @@ impl ContextManager {
             store,
             persistent: None,
             budget: ContextBudget::default(),
+            pin_budget: DEFAULT_PIN_BUDGET_TOKENS,
         }
     }
 
@@ -592,6 +827,12 @@ impl ContextManager {
         self
     }
 
+    /// Override the token cap for pinned records
+    pub fn with_pin_budget(mut self, tokens: usize) -> Self {
+        self.pin_budget = tokens;
+        self
+    }
+
     /// Access the underlying memory store
     pub fn store(&self) -> &MemoryStore {
         &self.store
@@ -607,6 +848,11 @@ impl ContextManager {
         &self.budget
     }
 
+    /// Token cap for pinned records
+    pub fn pin_budget(&self) -> usize {
+        self.pin_budget
+    }
+
     /// Ingest a list of messages into the memory store
     pub async fn ingest_messages(&self, messages: &[Message]) -> Result<MemoryRecord, MemoryError> {
         let mut content = String::new();
@@ -642,6 +888,7 @@ impl ContextManager {
         let recall_query = RecallQuery {
             query: query.to_string(),
             max_tokens,
+            pin_budget_tokens: self.pin_budget,
             ..Default::default()
         };
         if let Some(persistent) = &self.persistent {
@@ -651,6 +898,48 @@ impl ContextManager {
         }
     }
 
+    /// Pin a memory so recall always returns it and pruning never drops it
+    pub async fn pin(&self, project_id: &str, text: &str) -> Result<MemoryRecord, MemoryError> {
+        if text.trim().is_empty() {
+            return Err(MemoryError::invalid("cannot pin empty text"));
+        }
+        if let Some(persistent) = &self.persistent {
+            let record = persistent.pin(project_id, text, self.pin_budget).await?;
+            self.store.insert_record(record.clone()).await;
+            Ok(record)
+        } else {
+            self.store.pin(text, self.pin_budget).await
+        }
+    }
+
+    /// Unpin a memory, returning whether it existed
+    pub async fn unpin(&self, id: Uuid) -> Result<bool, MemoryError> {
+        let in_store = self.store.set_pinned(id, false).await;
+        if let Some(persistent) = &self.persistent {
+            persistent.set_pinned(id, false).await
+        } else {
+            Ok(in_store)
+        }
+    }
+
+    /// Pinned memories, oldest first
+    pub async fn pinned(&self, project_id: Option<&str>) -> Result<Vec<MemoryRecord>, MemoryError> {
+        if let Some(persistent) = &self.persistent {
+            persistent.pinned(project_id).await
+        } else {
+            Ok(self.store.pinned().await)
+        }
+    }
+
+    /// Merge near-duplicate memories, see [`PersistentMemoryStore::consolidate`]
+    pub async fn consolidate(&self, threshold: f32) -> Result<ConsolidationReport, MemoryError> {
+        if let Some(persistent) = &self.persistent {
+            persistent.consolidate(None, threshold, false).await
+        } else {
+            self.store.consolidate(threshold).await
+        }
+    }
+
     /// Prune entries older than the provided number of days
     pub async fn prune(&self, older_than_days: u32) -> usize {
         let cutoff = Utc::now() - Duration::days(older_than_days as i64);
@@ -1001,6 +1290,111 @@ mod tests {
         assert_eq!(child.disclosure_level(), DisclosureLevel::Summary);
     }
 
+    #[test]
+    fn test_add_history_keeps_newest_and_summarizes_the_rest() {
+        let mut window = ContextWindow::new(TokenAllocation::new(1024, 400, 0, 1024));
+        let history: Vec<Message> = (0..20)
+            .map(|i| Message::user(format!("Turn {}. {}", i, "text ".repeat(40))))
+            .collect();
+
+        let fit = window.add_history(&history);
+        assert!(fit.summarized > 0);
+        assert_eq!(fit.kept + fit.summarized, 20);
+        assert!(window.context_tokens() <= 400);
+
+        let messages = window.messages();
+        assert!(messages[0].content.starts_with("Summary of"));
+        assert!(messages.last().unwrap().content.starts_with("Turn 19"));
+    }
+
+    #[test]
+    fn test_add_history_within_budget_is_verbatim() {
+        let mut window = ContextWindow::new(TokenAllocation::default());
+        let fit = window.add_history(&[Message::user("Hi"), Message::assistant("Hello")]);
+        assert_eq!(
+            fit,
+            HistoryFit {
+                kept: 2,
+                summarized: 0,
+                summary_tokens: 0
+            }
+        );
+        assert_eq!(window.messages().len(), 2);
+    }
+
+    #[test]
+    fn test_add_packed_keeps_existing_context() {
+        let mut window = ContextWindow::new(TokenAllocation::new(1024, 100, 0, 1024));
+        window.add_context_message(Message::user("Earlier turn"));
+        let report = window.add_packed(vec![
+            ContextPiece::new("map", PieceKind::RepoMap, "src/ ".repeat(100), 0.9),
+            ContextPiece::new("skill", PieceKind::Skill, "Use sqlx", 0.6),
+            ContextPiece::new("stale", PieceKind::Memory, "Old note", 0.0),
+        ]);
+
+        assert_eq!(report.included.len(), 1);
+        assert_eq!(report.included[0].id, "skill");
+        assert_eq!(report.excluded[0].1, Exclusion::TooLarge);
+        assert_eq!(report.excluded[1].1, Exclusion::Irrelevant);
+        let messages = window.messages();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "Earlier turn");
+        assert!(window.context_tokens() <= 100);
+    }
+
+    #[test]
+    fn test_pinned_messages_survive_compression() {
+        let mut parent = ContextWindow::new(TokenAllocation::new(1024, 2048, 2048, 3072));
+        let decision = "We use Postgres, not SQLite. Never suggest SQLite.";
+        parent.add_pinned_message(Message::system(decision));
+        parent.add_context_message(Message::user("Some context. With sentences. Lots of them."));
+
+        parent.compress_to(0);
+        assert!(parent.context_messages.is_empty());
+        assert_eq!(parent.pinned_messages().len(), 1);
+
+        // Even the most compressed disclosure level keeps the pin verbatim
+        let child = parent.child_window(TokenAllocation::new(1024, 16, 2048, 3072), 2);
+        let grandchild = child.child_window(TokenAllocation::new(1024, 16, 2048, 3072), 3);
+        assert_eq!(grandchild.pinned_messages()[0].content, decision);
+        assert_eq!(grandchild.messages()[0].content, decision);
+    }
+
+    #[tokio::test]
+    async fn test_context_manager_pins_lead_recall() {
+        let manager = ContextManager::new();
+        manager
+            .store()
+            .add("bug bug bug in the parser")
+            .await
+            .unwrap();
+        let pin = manager
+            .pin("project", "We use Postgres, not SQLite")
+            .await
+            .unwrap();
+
+        let results = manager.recall("bug", 4096).await.unwrap();
+        assert_eq!(results[0].id, pin.id);
+
+        assert_eq!(manager.pinned(None).await.unwrap().len(), 1);
+        assert!(manager.unpin(pin.id).await.unwrap());
+        assert!(manager.pinned(None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_context_manager_pin_budget() {
+        let manager = ContextManager::new().with_pin_budget(8);
+        manager.pin("project", "Short decision").await.unwrap();
+        let err = manager
+            .pin(
+                "project",
+                "A much longer decision that will not fit in the cap",
+            )
+            .await;
+        assert!(err.is_err());
+        assert!(manager.pin("project", "   ").await.is_err());
+    }
+
     #[test]
     fn test_progressive_context_new() {
         let budget = ContextBudget::new(8192);
@@ -1065,4 +1459,26 @@ mod tests {
         assert!(truncated.contains("Second sentence."));
         assert!(!truncated.contains("Third"));
     }
+
+    #[test]
+    fn test_context_stats() {
+        let mut window = ContextWindow::new(TokenAllocation::new(1000, 4000, 0, 2000));
+        window.add_system_message(Message::system("x".repeat(4000)));
+        let stats = window.stats();
+        assert_eq!(stats.capacity_tokens, 5000);
+        assert_eq!(stats.used_tokens, 1002);
+        assert!(!stats.measured);
+        assert!(!stats.is_near_limit());
+
+        let measured = stats.with_measured(4200);
+        assert!(measured.measured);
+        assert!(measured.is_near_limit());
+        assert_eq!(measured.to_string(), "4.2k / 5k tokens");
+        assert_eq!(stats.with_measured(0), stats);
+
+        assert_eq!(format_token_count(950), "950");
+        assert_eq!(format_token_count(6_200), "6.2k");
+        assert_eq!(format_token_count(16_000), "16k");
+        assert_eq!(format_token_count(1_500_000), "1.5M");
+    }
 }
@@ -0,0 +1,374 @@
+//! Budget-constrained context packing
+//!
+//! An agent call can draw on more context than fits its allocation: recalled
+//! memory records, matching skills, the repository map, attachments. Adding
+//! them to a [`ContextWindow`](super::ContextWindow) one by one evicts the
+//! oldest first, whatever its value. The packer instead picks the subset
+//! with the highest total relevance that fits the token budget, solving a
+//! 0/1 knapsack (or greedily by relevance per token when there are too many
+//! pieces), and reports what it left out and why.
+
+use serde::{Deserialize, Serialize};
+
+use crate::llm::Message;
+
+use super::estimate_message_tokens;
+
+/// Most pieces solved exactly; larger sets are packed greedily
+const MAX_KNAPSACK_PIECES: usize = 256;
+
+/// Most capacity buckets in the knapsack table; token sizes are scaled to fit
+const MAX_KNAPSACK_BUCKETS: usize = 2048;
+
+/// Where a piece of context comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PieceKind {
+    Memory,
+    Skill,
+    RepoMap,
+    Attachment,
+    Knowledge,
+    Other,
+}
+
+impl PieceKind {
+    /// Heading the piece is introduced with in the prompt
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Memory => "Relevant memory",
+            Self::Skill => "Learned skill",
+            Self::RepoMap => "Repository map",
+            Self::Attachment => "Attachment",
+            Self::Knowledge => "Project knowledge",
+            Self::Other => "Context",
+        }
+    }
+}
+
+/// A candidate piece of context for an agent call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextPiece {
+    /// Identifies the piece in packing logs, e.g. a memory record or skill ID
+    pub id: String,
+    pub kind: PieceKind,
+    pub content: String,
+    /// Estimated tokens of the piece as sent, heading included
+    pub tokens: usize,
+    /// How useful the piece is for the call; zero or less is never included
+    pub relevance: f32,
+}
+
+impl ContextPiece {
+    pub fn new(
+        id: impl Into<String>,
+        kind: PieceKind,
+        content: impl Into<String>,
+        relevance: f32,
+    ) -> Self {
+        let mut piece = Self {
+            id: id.into(),
+            kind,
+            content: content.into(),
+            tokens: 0,
+            relevance,
+        };
+        piece.tokens = estimate_message_tokens(&piece.to_message());
+        piece
+    }
+
+    /// The piece as a context message
+    pub fn to_message(&self) -> Message {
+        Message::system(format!("{}:\n{}", self.kind.label(), self.content))
+    }
+}
+
+/// How the packer chooses pieces
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackingStrategy {
+    /// Highest relevance per token first
+    Greedy,
+    /// Best total relevance within the budget, falling back to greedy for
+    /// very large candidate sets
+    #[default]
+    Knapsack,
+}
+
+/// Why a piece was left out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Exclusion {
+    /// No relevance to the call
+    Irrelevant,
+    /// Larger than the whole budget
+    TooLarge,
+    /// Other pieces were worth more for the tokens
+    OverBudget,
+}
+
+impl std::fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Irrelevant => write!(f, "irrelevant"),
+            Self::TooLarge => write!(f, "larger than budget"),
+            Self::OverBudget => write!(f, "over budget"),
+        }
+    }
+}
+
+/// What was packed for one call
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackingReport {
+    /// Token budget the pieces were packed into
+    pub budget: usize,
+    /// Included pieces, in the order they were offered
+    pub included: Vec<ContextPiece>,
+    /// Left-out pieces with the reason, in the order they were offered
+    pub excluded: Vec<(ContextPiece, Exclusion)>,
+}
+
+impl PackingReport {
+    /// Tokens of the included pieces
+    pub fn used_tokens(&self) -> usize {
+        self.included.iter().map(|p| p.tokens).sum()
+    }
+
+    /// Summed relevance of the included pieces
+    pub fn total_relevance(&self) -> f32 {
+        self.included.iter().map(|p| p.relevance).sum()
+    }
+
+    /// Included pieces as context messages
+    pub fn messages(&self) -> Vec<Message> {
+        self.included.iter().map(ContextPiece::to_message).collect()
+    }
+
+    /// Log the decision for every piece at debug level
+    pub fn log(&self, call: &str) {
+        tracing::debug!(
+            call,
+            budget = self.budget,
+            used_tokens = self.used_tokens(),
+            included = self.included.len(),
+            excluded = self.excluded.len(),
+            "Packed context"
+        );
+        for piece in &self.included {
+            tracing::debug!(
+                call,
+                id = %piece.id,
+                kind = ?piece.kind,
+                tokens = piece.tokens,
+                relevance = piece.relevance,
+                "Context piece included"
+            );
+        }
+        for (piece, reason) in &self.excluded {
+            tracing::debug!(
+                call,
+                id = %piece.id,
+                kind = ?piece.kind,
+                tokens = piece.tokens,
+                relevance = piece.relevance,
+                %reason,
+                "Context piece excluded"
+            );
+        }
+    }
+}
+
+/// Selects context pieces for a token budget
+#[derive(Debug, Clone, Copy)]
+pub struct ContextPacker {
+    budget: usize,
+    strategy: PackingStrategy,
+}
+
+impl ContextPacker {
+    /// Packer for `budget` tokens
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            strategy: PackingStrategy::default(),
+        }
+    }
+
+    pub fn with_strategy(mut self, strategy: PackingStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Choose the pieces to include
+    pub fn pack(&self, pieces: Vec<ContextPiece>) -> PackingReport {
+        let candidates: Vec<usize> = pieces
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.relevance > 0.0 && p.tokens <= self.budget)
+            .map(|(i, _)| i)
+            .collect();
+
+        let chosen = match self.strategy {
+            PackingStrategy::Knapsack if candidates.len() <= MAX_KNAPSACK_PIECES => {
+                self.knapsack(&pieces, &candidates)
+            }
+            _ => self.greedy(&pieces, &candidates),
+        };
+
+        let mut report = PackingReport {
+            budget: self.budget,
+            ..Default::default()
+        };
+        for (i, piece) in pieces.into_iter().enumerate() {
+            if chosen.contains(&i) {
+                report.included.push(piece);
+            } else {
+                let reason = if piece.relevance <= 0.0 {
+                    Exclusion::Irrelevant
+                } else if piece.tokens > self.budget {
+                    Exclusion::TooLarge
+                } else {
+                    Exclusion::OverBudget
+                };
+                report.excluded.push((piece, reason));
+            }
+        }
+        report
+    }
+
+    /// Highest relevance per token first, or the single most relevant piece
+    /// when that alone is worth more
+    fn greedy(&self, pieces: &[ContextPiece], candidates: &[usize]) -> Vec<usize> {
+        let mut order = candidates.to_vec();
+        order.sort_by(|&a, &b| {
+            let density = |i: usize| pieces[i].relevance / pieces[i].tokens.max(1) as f32;
+            density(b).total_cmp(&density(a))
+        });
+
+        let mut chosen = Vec::new();
+        let mut used = 0;
+        let mut value = 0.0;
+        for i in order {
+            if used + pieces[i].tokens <= self.budget {
+                used += pieces[i].tokens;
+                value += pieces[i].relevance;
+                chosen.push(i);
+            }
+        }
+
+        let best_single = candidates
+            .iter()
+            .copied()
+            .max_by(|&a, &b| pieces[a].relevance.total_cmp(&pieces[b].relevance));
+        match best_single {
+            Some(i) if pieces[i].relevance > value => vec![i],
+            _ => chosen,
+        }
+    }
+
+    /// 0/1 knapsack over token sizes scaled into at most
+    /// [`MAX_KNAPSACK_BUCKETS`] buckets, rounding sizes up so the result
+    /// always fits
+    fn knapsack(&self, pieces: &[ContextPiece], candidates: &[usize]) -> Vec<usize> {
+        let unit = self.budget.div_ceil(MAX_KNAPSACK_BUCKETS).max(1);
+        let capacity = self.budget / unit;
+        let weights: Vec<usize> = candidates
+            .iter()
+            .map(|&i| pieces[i].tokens.div_ceil(unit))
+            .collect();
+
+        let mut best = vec![0.0f64; capacity + 1];
+        let mut taken = vec![vec![false; capacity + 1]; candidates.len()];
+        for (row, &i) in candidates.iter().enumerate() {
+            let weight = weights[row];
+            let value = pieces[i].relevance as f64;
+            for c in (weight..=capacity).rev() {
+                if best[c - weight] + value > best[c] {
+                    best[c] = best[c - weight] + value;
+                    taken[row][c] = true;
+                }
+            }
+        }
+
+        let mut chosen = Vec::new();
+        let mut c = capacity;
+        for row in (0..candidates.len()).rev() {
+            if taken[row][c] {
+                chosen.push(candidates[row]);
+                c -= weights[row];
+            }
+        }
+        chosen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn piece(id: &str, tokens: usize, relevance: f32) -> ContextPiece {
+        ContextPiece {
+            id: id.to_string(),
+            kind: PieceKind::Memory,
+            content: String::new(),
+            tokens,
+            relevance,
+        }
+    }
+
+    fn ids(report: &PackingReport) -> Vec<&str> {
+        report.included.iter().map(|p| p.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_knapsack_beats_greedy() {
+        // Greedy takes the densest piece and then has no room for either of
+        // the two that together are worth more
+        let pieces = vec![
+            piece("dense", 60, 0.7),
+            piece("a", 50, 0.5),
+            piece("b", 50, 0.5),
+        ];
+
+        let greedy = ContextPacker::new(100)
+            .with_strategy(PackingStrategy::Greedy)
+            .pack(pieces.clone());
+        assert_eq!(ids(&greedy), vec!["dense"]);
+
+        let report = ContextPacker::new(100).pack(pieces);
+        assert_eq!(ids(&report), vec!["a", "b"]);
+        assert_eq!(report.used_tokens(), 100);
+        assert!((report.total_relevance() - 1.0).abs() < 1e-6);
+        assert_eq!(report.excluded[0].1, Exclusion::OverBudget);
+    }
+
+    #[test]
+    fn test_reports_exclusions_and_scales_large_budgets() {
+        let pieces = vec![
+            piece("huge", 50_000, 0.9),
+            piece("noise", 10, 0.0),
+            piece("map", 9_000, 0.4),
+            piece("skill", 30_000, 0.8),
+        ];
+        let report = ContextPacker::new(40_000).pack(pieces);
+
+        assert_eq!(ids(&report), vec!["map", "skill"]);
+        assert!(report.used_tokens() <= 40_000);
+        let reasons: Vec<_> = report
+            .excluded
+            .iter()
+            .map(|(p, r)| (p.id.as_str(), *r))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("huge", Exclusion::TooLarge),
+                ("noise", Exclusion::Irrelevant)
+            ]
+        );
+
+        let built = ContextPiece::new("m1", PieceKind::RepoMap, "src/lib.rs", 0.5);
+        assert!(built.to_message().content.starts_with("Repository map:\n"));
+        assert!(built.tokens > 0);
+    }
+}
@@ -0,0 +1,227 @@
+//! Spend forecasting from persisted cost history
+//!
+//! The end-of-month projection is the month-to-date spend in `llm_costs`
+//! plus the trailing burn rate (average daily spend over the last
+//! [`BURN_WINDOW_DAYS`] days) for the rest of the month. When it exceeds the
+//! monthly limit, `demiarch costs`, the TUI stats tab and the GUI dashboard
+//! warn, and the notification feed raises an alert once a day.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::config::CostConfig;
+use crate::storage::Database;
+use crate::Result;
+
+/// Days of spending the burn rate is averaged over
+pub const BURN_WINDOW_DAYS: i64 = 7;
+
+/// Projected spend for the current calendar month (UTC)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpendForecast {
+    pub project_id: Option<String>,
+    pub month_start: NaiveDate,
+    /// Last day of the month
+    pub month_end: NaiveDate,
+    pub month_to_date_usd: f64,
+    /// Average daily spend over the trailing window
+    pub burn_rate_usd_per_day: f64,
+    pub window_days: i64,
+    /// Days (fractional) left in the month
+    pub days_remaining: f64,
+    pub projected_usd: f64,
+    /// Limit the projection is checked against; 0 means no limit
+    pub monthly_limit_usd: f64,
+    /// Whether the projection exceeds the monthly limit
+    pub over_limit: bool,
+    /// Day the limit is reached at the current burn rate, if within the month
+    pub limit_reached_on: Option<NaiveDate>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl SpendForecast {
+    /// Project month-end spend from month-to-date and trailing-window spend
+    pub fn project(
+        month_to_date_usd: f64,
+        window_spend_usd: f64,
+        monthly_limit_usd: f64,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let (month_start, next_month) = month_bounds(now.date_naive());
+        let days_remaining = (midnight(next_month) - now).num_seconds().max(0) as f64 / 86_400.0;
+        let burn_rate_usd_per_day = window_spend_usd / BURN_WINDOW_DAYS as f64;
+        let projected_usd = month_to_date_usd + burn_rate_usd_per_day * days_remaining;
+
+        let limit_reached_on = if monthly_limit_usd <= 0.0 {
+            None
+        } else if month_to_date_usd >= monthly_limit_usd {
+            Some(now.date_naive())
+        } else if burn_rate_usd_per_day > 0.0 {
+            let days = (monthly_limit_usd - month_to_date_usd) / burn_rate_usd_per_day;
+            let at = now + Duration::seconds((days * 86_400.0) as i64);
+            (at.date_naive() < next_month).then(|| at.date_naive())
+        } else {
+            None
+        };
+
+        Self {
+            project_id: None,
+            month_start,
+            month_end: next_month.pred_opt().unwrap_or(next_month),
+            month_to_date_usd,
+            burn_rate_usd_per_day,
+            window_days: BURN_WINDOW_DAYS,
+            days_remaining,
+            projected_usd,
+            monthly_limit_usd,
+            over_limit: monthly_limit_usd > 0.0 && projected_usd > monthly_limit_usd,
+            limit_reached_on,
+            generated_at: now,
+        }
+    }
+
+    /// Projection as a share of the monthly limit
+    pub fn projected_share(&self) -> Option<f64> {
+        (self.monthly_limit_usd > 0.0).then(|| self.projected_usd / self.monthly_limit_usd)
+    }
+}
+
+/// Monthly limit for the month containing `day`
+///
+/// `cost.monthly_limit_usd`, or the daily limit times the days in the month
+/// when that is unset.
+pub fn monthly_limit(cost: &CostConfig, day: NaiveDate) -> f64 {
+    if cost.monthly_limit_usd > 0.0 {
+        return cost.monthly_limit_usd;
+    }
+    let (start, next) = month_bounds(day);
+    cost.daily_limit_usd.max(0.0) * (next - start).num_days() as f64
+}
+
+/// Forecast this month's spend, optionally for one project
+pub async fn forecast(
+    db: &Database,
+    project_id: Option<&str>,
+    cost: &CostConfig,
+    now: DateTime<Utc>,
+) -> Result<SpendForecast> {
+    let (month_start, _) = month_bounds(now.date_naive());
+    let month_to_date = spent_since(db, project_id, midnight(month_start)).await?;
+    let window = spent_since(db, project_id, now - Duration::days(BURN_WINDOW_DAYS)).await?;
+
+    let mut forecast = SpendForecast::project(
+        month_to_date,
+        window,
+        monthly_limit(cost, now.date_naive()),
+        now,
+    );
+    forecast.project_id = project_id.map(str::to_string);
+    Ok(forecast)
+}
+
+async fn spent_since(db: &Database, project_id: Option<&str>, since: DateTime<Utc>) -> Result<f64> {
+    let row = sqlx::query(
+        r#"
+        SELECT COALESCE(SUM(input_cost_usd + output_cost_usd), 0.0) AS spent
+        FROM llm_costs
+        WHERE julianday(created_at) >= julianday(?)
+          AND (? IS NULL OR project_id = ?)
+        "#,
+    )
+    .bind(since.to_rfc3339_opts(SecondsFormat::Millis, true))
+    .bind(project_id)
+    .bind(project_id)
+    .fetch_one(db.pool())
+    .await?;
+    Ok(row.get("spent"))
+}
+
+/// First day of the month containing `day`, and of the following month
+fn month_bounds(day: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start = day.with_day(1).unwrap_or(day);
+    let next = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    }
+    .unwrap_or(start);
+    (start, next)
+}
+
+fn midnight(day: NaiveDate) -> DateTime<Utc> {
+    day.and_hms_opt(0, 0, 0)
+        .map(|t| t.and_utc())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn cost(daily: f64, monthly: f64) -> CostConfig {
+        CostConfig {
+            daily_limit_usd: daily,
+            monthly_limit_usd: monthly,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_projection_from_burn_rate() {
+        // Noon on Jan 21: 10.5 days left
+        let now = Utc.with_ymd_and_hms(2025, 1, 21, 12, 0, 0).unwrap();
+        let forecast = SpendForecast::project(40.0, 14.0, 50.0, now);
+
+        assert_eq!(forecast.month_start.to_string(), "2025-01-01");
+        assert_eq!(forecast.month_end.to_string(), "2025-01-31");
+        assert_eq!(forecast.burn_rate_usd_per_day, 2.0);
+        assert_eq!(forecast.days_remaining, 10.5);
+        assert_eq!(forecast.projected_usd, 61.0);
+        assert!(forecast.over_limit);
+        assert_eq!(
+            forecast.limit_reached_on.map(|d| d.to_string()),
+            Some("2025-01-26".to_string())
+        );
+
+        let quiet = SpendForecast::project(10.0, 0.0, 50.0, now);
+        assert!(!quiet.over_limit);
+        assert_eq!(quiet.limit_reached_on, None);
+        assert!(!SpendForecast::project(40.0, 14.0, 0.0, now).over_limit);
+    }
+
+    #[test]
+    fn test_monthly_limit_defaults_to_daily_limit() {
+        let feb = NaiveDate::from_ymd_opt(2025, 2, 10).unwrap();
+        assert_eq!(monthly_limit(&cost(10.0, 0.0), feb), 280.0);
+        assert_eq!(monthly_limit(&cost(10.0, 100.0), feb), 100.0);
+        let dec = NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+        assert_eq!(month_bounds(dec).1.to_string(), "2026-01-01");
+    }
+
+    #[tokio::test]
+    async fn test_forecast_reads_cost_history() {
+        let db = Database::in_memory().await.unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 1, 21, 12, 0, 0).unwrap();
+        for (id, at) in [
+            ("c1", now - Duration::hours(1)),
+            ("c2", now - Duration::days(40)),
+        ] {
+            sqlx::query(
+                "INSERT INTO llm_costs (id, model, input_cost_usd, output_cost_usd, created_at) VALUES (?, 'm', 5.0, 2.0, ?)",
+            )
+            .bind(id)
+            .bind(at)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+
+        let forecast = forecast(&db, None, &cost(0.0, 1.0), now).await.unwrap();
+        assert_eq!(forecast.month_to_date_usd, 7.0);
+        assert_eq!(forecast.burn_rate_usd_per_day, 1.0);
+        assert!(forecast.over_limit);
+        assert_eq!(forecast.limit_reached_on, Some(now.date_naive()));
+    }
+}
@@ -3,13 +3,26 @@
 //! This module provides:
 //! - Token usage tracking per LLM call
 //! - Cost calculation based on model pricing
-//! - Daily cost aggregation and budget enforcement
+//! - Daily cost aggregation and budget enforcement, with lock-free recording
+//! - Budget limits that follow config reloads ([`CostTracker::follow_config`])
 //! - Cost history for reporting
-
-use chrono::{DateTime, NaiveDate, Utc};
+//! - End-of-month spend forecasts ([`forecast`])
+//! - Prompt cost previews against the remaining budget ([`preview`])
+
+pub mod forecast;
+pub mod preview;
+
+use crate::config::{
+    self,
+    watch::{ConfigChange, ConfigHub},
+};
+use crate::events::{self, CoreEvent};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
 /// Token usage for a single LLM call
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -204,19 +217,147 @@ fn default_pricing_table() -> HashMap<String, ModelPricing> {
     table
 }
 
-/// Cost tracker for recording and aggregating LLM costs
+/// Budget counter resolution: 1e-7 USD
+const COUNTER_UNITS_PER_USD: f64 = 1e7;
+
+/// The budget counter packs the day into the bits above its spend
+const COUNTER_DAY_SHIFT: u32 = 44;
+const COUNTER_UNITS_MASK: u64 = (1 << COUNTER_DAY_SHIFT) - 1;
+
+/// Today's spend as a single atomic word, for budget checks on every call
+///
+/// The day is stored alongside the spend so the first call of a new day
+/// resets the total in the same compare-and-swap that adds to it.
+#[derive(Debug, Default)]
+struct DailyCounter(AtomicU64);
+
+impl DailyCounter {
+    fn day_key(date: NaiveDate) -> u64 {
+        date.num_days_from_ce().max(0) as u64
+    }
+
+    fn add(&self, date: NaiveDate, cost_usd: f64) {
+        let day = Self::day_key(date);
+        let units = (cost_usd.max(0.0) * COUNTER_UNITS_PER_USD).round() as u64;
+        let _ = self
+            .0
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |packed| {
+                let counted_day = packed >> COUNTER_DAY_SHIFT;
+                let total = packed & COUNTER_UNITS_MASK;
+                if counted_day > day {
+                    // A late record for an earlier day only goes into the summaries
+                    return None;
+                }
+                let total = if counted_day == day { total } else { 0 };
+                let total = total.saturating_add(units).min(COUNTER_UNITS_MASK);
+                Some((day << COUNTER_DAY_SHIFT) | total)
+            });
+    }
+
+    fn total(&self, date: NaiveDate) -> f64 {
+        let packed = self.0.load(Ordering::Acquire);
+        if packed >> COUNTER_DAY_SHIFT == Self::day_key(date) {
+            (packed & COUNTER_UNITS_MASK) as f64 / COUNTER_UNITS_PER_USD
+        } else {
+            0.0
+        }
+    }
+
+    fn reset(&self) {
+        self.0.store(0, Ordering::Release);
+    }
+}
+
+/// Records and summaries, folded in by whoever reads them next
+#[derive(Debug)]
+struct Aggregate {
+    /// Records recorded but not folded in yet
+    pending: Receiver<LlmCost>,
+    /// Cost records, oldest first
+    records: Vec<LlmCost>,
+    daily_summaries: HashMap<NaiveDate, DailyCostSummary>,
+}
+
+impl Aggregate {
+    fn apply(&mut self, cost: LlmCost) {
+        let date = cost.timestamp.date_naive();
+        self.daily_summaries
+            .entry(date)
+            .or_insert_with(|| DailyCostSummary::new(date))
+            .add(&cost);
+        self.records.push(cost);
+    }
+}
+
+/// State shared by a tracker and its clones
 #[derive(Debug)]
+struct Shared {
+    today: DailyCounter,
+    /// Hands records to the aggregate without taking its lock
+    inbox: Sender<LlmCost>,
+    aggregate: Mutex<Aggregate>,
+    /// Daily budget limit in USD, as `f64` bits
+    daily_limit_usd: AtomicU64,
+    /// Alert threshold (0.0 to 1.0), as `f64` bits
+    alert_threshold: AtomicU64,
+}
+
+impl Shared {
+    fn new(daily_limit_usd: f64, alert_threshold: f64) -> Self {
+        let (inbox, pending) = mpsc::channel();
+        Self {
+            today: DailyCounter::default(),
+            inbox,
+            aggregate: Mutex::new(Aggregate {
+                pending,
+                records: Vec::new(),
+                daily_summaries: HashMap::new(),
+            }),
+            daily_limit_usd: AtomicU64::new(daily_limit_usd.to_bits()),
+            alert_threshold: AtomicU64::new(alert_threshold.to_bits()),
+        }
+    }
+
+    fn daily_limit(&self) -> f64 {
+        f64::from_bits(self.daily_limit_usd.load(Ordering::Relaxed))
+    }
+
+    fn alert_threshold(&self) -> f64 {
+        f64::from_bits(self.alert_threshold.load(Ordering::Relaxed))
+    }
+
+    fn set_limits(&self, daily_limit_usd: f64, alert_threshold: f64) {
+        self.daily_limit_usd
+            .store(daily_limit_usd.to_bits(), Ordering::Relaxed);
+        self.alert_threshold
+            .store(alert_threshold.to_bits(), Ordering::Relaxed);
+    }
+
+    /// The aggregate with every record sent before this call folded in
+    fn caught_up(&self) -> MutexGuard<'_, Aggregate> {
+        let mut aggregate = self
+            .aggregate
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        while let Ok(cost) = aggregate.pending.try_recv() {
+            aggregate.apply(cost);
+        }
+        aggregate
+    }
+}
+
+/// Cost tracker for recording and aggregating LLM costs
+///
+/// Recording a cost never takes a lock: today's spend is a single atomic
+/// counter, which the budget checks read, and the record itself is queued
+/// on a channel. Reading the history or a summary folds the queued records
+/// into it first, so it never waits on another thread. Clones share the
+/// same records, counter and limits.
+#[derive(Debug, Clone)]
 pub struct CostTracker {
     /// Model pricing table
     pricing: HashMap<String, ModelPricing>,
-    /// Cost records (in-memory, most recent first)
-    records: Arc<RwLock<Vec<LlmCost>>>,
-    /// Daily summaries cache
-    daily_summaries: Arc<RwLock<HashMap<NaiveDate, DailyCostSummary>>>,
-    /// Daily budget limit in USD
-    daily_limit_usd: f64,
-    /// Alert threshold (0.0 to 1.0)
-    alert_threshold: f64,
+    shared: Arc<Shared>,
 }
 
 impl CostTracker {
@@ -224,10 +365,7 @@ impl CostTracker {
     pub fn new(daily_limit_usd: f64, alert_threshold: f64) -> Self {
         Self {
             pricing: default_pricing_table(),
-            records: Arc::new(RwLock::new(Vec::new())),
-            daily_summaries: Arc::new(RwLock::new(HashMap::new())),
-            daily_limit_usd,
-            alert_threshold,
+            shared: Arc::new(Shared::new(daily_limit_usd, alert_threshold)),
         }
     }
 
@@ -236,6 +374,33 @@ impl CostTracker {
         Self::new(config.daily_limit_usd, config.alert_threshold)
     }
 
+    /// Change the daily limit and alert threshold, for this tracker and its
+    /// clones
+    pub fn set_limits(&self, daily_limit_usd: f64, alert_threshold: f64) {
+        self.shared.set_limits(daily_limit_usd, alert_threshold);
+    }
+
+    /// Apply budget changes from config reloads until the tracker and all
+    /// its clones are dropped
+    pub fn follow_config(&self) {
+        self.follow_hub(config::watch::hub());
+    }
+
+    /// Apply budget changes published on `hub`, as [`Self::follow_config`]
+    /// does for the process-wide one
+    pub fn follow_hub(&self, hub: &'static ConfigHub) {
+        hub.follow(Arc::downgrade(&self.shared), |shared, change| {
+            if let ConfigChange::BudgetChanged {
+                daily_limit_usd,
+                alert_threshold,
+                ..
+            } = change
+            {
+                shared.set_limits(*daily_limit_usd, *alert_threshold);
+            }
+        });
+    }
+
     /// Add custom model pricing
     pub fn add_pricing(&mut self, pricing: ModelPricing) {
         self.pricing.insert(pricing.model.clone(), pricing);
@@ -263,120 +428,114 @@ impl CostTracker {
             timestamp: Utc::now(),
             context,
         };
+        self.store(cost)
+    }
 
-        // Add to records
-        if let Ok(mut records) = self.records.write() {
-            records.insert(0, cost.clone());
-        }
-
-        // Update daily summary
-        let date = cost.timestamp.date_naive();
-        if let Ok(mut summaries) = self.daily_summaries.write() {
-            let summary = summaries
-                .entry(date)
-                .or_insert_with(|| DailyCostSummary::new(date));
-            summary.add(&cost);
-        }
+    /// Record a charge that is not priced per token, such as audio billed by the minute
+    pub fn record_usd(&self, model: &str, cost_usd: f64, context: Option<String>) -> LlmCost {
+        let cost = LlmCost {
+            id: uuid::Uuid::new_v4().to_string(),
+            model: model.to_string(),
+            tokens: TokenUsage::new(0, 0),
+            input_cost_usd: cost_usd,
+            output_cost_usd: 0.0,
+            timestamp: Utc::now(),
+            context,
+        };
+        self.store(cost)
+    }
+
+    /// Count a cost against today's budget, queue it for the history, then
+    /// announce it
+    fn store(&self, cost: LlmCost) -> LlmCost {
+        self.shared
+            .today
+            .add(cost.timestamp.date_naive(), cost.total_cost_usd());
+        // The receiver lives as long as `shared`, so the send cannot fail
+        let _ = self.shared.inbox.send(cost.clone());
+
+        events::global().emit(CoreEvent::CostRecorded {
+            cost_id: cost.id.clone(),
+            model: cost.model.clone(),
+            cost_usd: cost.total_cost_usd(),
+            input_tokens: cost.tokens.input_tokens,
+            output_tokens: cost.tokens.output_tokens,
+        });
 
         cost
     }
 
     /// Get today's total cost
     pub fn today_total(&self) -> f64 {
-        let today = Utc::now().date_naive();
-        self.daily_summaries
-            .read()
-            .ok()
-            .and_then(|s| s.get(&today).map(|d| d.total_cost_usd))
-            .unwrap_or(0.0)
+        self.shared.today.total(Utc::now().date_naive())
     }
 
     /// Get today's summary
     pub fn today_summary(&self) -> Option<DailyCostSummary> {
-        let today = Utc::now().date_naive();
-        self.daily_summaries
-            .read()
-            .ok()
-            .and_then(|s| s.get(&today).cloned())
+        self.summary_for_date(Utc::now().date_naive())
     }
 
     /// Check if we're approaching the daily limit
     pub fn is_approaching_limit(&self) -> bool {
-        self.today_total() >= self.daily_limit_usd * self.alert_threshold
+        self.today_total() >= self.shared.daily_limit() * self.shared.alert_threshold()
     }
 
     /// Check if we've exceeded the daily limit
     pub fn is_over_limit(&self) -> bool {
-        self.today_total() >= self.daily_limit_usd
+        self.today_total() >= self.shared.daily_limit()
     }
 
     /// Get remaining budget for today
     pub fn remaining_budget(&self) -> f64 {
-        (self.daily_limit_usd - self.today_total()).max(0.0)
+        (self.shared.daily_limit() - self.today_total()).max(0.0)
     }
 
     /// Get the daily limit
     pub fn daily_limit(&self) -> f64 {
-        self.daily_limit_usd
+        self.shared.daily_limit()
     }
 
     /// Get all cost records (most recent first)
     pub fn records(&self) -> Vec<LlmCost> {
-        self.records
-            .read()
-            .ok()
-            .map(|r| r.clone())
-            .unwrap_or_default()
+        self.shared
+            .caught_up()
+            .records
+            .iter()
+            .rev()
+            .cloned()
+            .collect()
     }
 
     /// Get records for a specific date
     pub fn records_for_date(&self, date: NaiveDate) -> Vec<LlmCost> {
-        self.records
-            .read()
-            .ok()
-            .map(|r| {
-                r.iter()
-                    .filter(|c| c.timestamp.date_naive() == date)
-                    .cloned()
-                    .collect()
-            })
-            .unwrap_or_default()
+        self.shared
+            .caught_up()
+            .records
+            .iter()
+            .rev()
+            .filter(|c| c.timestamp.date_naive() == date)
+            .cloned()
+            .collect()
     }
 
     /// Get summary for a specific date
     pub fn summary_for_date(&self, date: NaiveDate) -> Option<DailyCostSummary> {
-        self.daily_summaries
-            .read()
-            .ok()
-            .and_then(|s| s.get(&date).cloned())
+        self.shared.caught_up().daily_summaries.get(&date).cloned()
     }
 
     /// Clear all records and summaries (useful for testing)
     pub fn clear(&self) {
-        if let Ok(mut records) = self.records.write() {
-            records.clear();
-        }
-        if let Ok(mut summaries) = self.daily_summaries.write() {
-            summaries.clear();
-        }
-    }
-}
-
-impl Clone for CostTracker {
-    fn clone(&self) -> Self {
-        Self {
-            pricing: self.pricing.clone(),
-            records: self.records.clone(),
-            daily_summaries: self.daily_summaries.clone(),
-            daily_limit_usd: self.daily_limit_usd,
-            alert_threshold: self.alert_threshold,
-        }
+        let mut aggregate = self.shared.caught_up();
+        aggregate.records.clear();
+        aggregate.daily_summaries.clear();
+        self.shared.today.reset();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Config;
 
     #[test]
     fn test_token_usage() {
@@ -440,6 +599,17 @@ mod tests {
         assert!((today_total - 3.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_cost_tracker_record_usd() {
+        let tracker = CostTracker::new(10.0, 0.8);
+
+        let cost = tracker.record_usd("openai/whisper-1", 0.012, None);
+
+        assert_eq!(cost.tokens.total(), 0);
+        assert!((cost.total_cost_usd() - 0.012).abs() < 1e-9);
+        assert!((tracker.today_total() - 0.012).abs() < 1e-9);
+    }
+
     #[test]
     fn test_cost_tracker_budget_checks() {
         let tracker = CostTracker::new(1.0, 0.8);
@@ -549,5 +719,97 @@ mod tests {
 
         assert!(tracker.records().is_empty());
         assert!(tracker.today_summary().is_none());
+        assert_eq!(tracker.today_total(), 0.0);
+    }
+
+    #[test]
+    fn test_parallel_recording() {
+        let tracker = CostTracker::new(1_000.0, 0.8);
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                let tracker = tracker.clone();
+                scope.spawn(move || {
+                    for _ in 0..250 {
+                        tracker.record("openai/gpt-4o-mini", TokenUsage::new(1000, 100), None);
+                    }
+                });
+            }
+        });
+
+        // 2,000 calls at $0.00021 each
+        assert!((tracker.today_total() - 0.42).abs() < 1e-6);
+        let summary = tracker.today_summary().unwrap();
+        assert_eq!(summary.call_count, 2000);
+        assert!((summary.total_cost_usd - 0.42).abs() < 1e-6);
+        assert_eq!(tracker.records().len(), 2000);
+    }
+
+    #[tokio::test]
+    async fn test_records_readable_from_async_callers() {
+        let tracker = CostTracker::new(10.0, 0.8);
+        let recorder = tracker.clone();
+        tokio::spawn(async move {
+            recorder.record_usd("whisper-1", 0.25, None);
+        })
+        .await
+        .unwrap();
+
+        // Folded in by the reader, on the runtime's only thread
+        assert_eq!(tracker.records().len(), 1);
+        assert_eq!(tracker.today_summary().unwrap().call_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_followed_budget_change_reaches_tracker() {
+        let hub: &'static ConfigHub = Box::leak(Box::new(ConfigHub::new(Config::default())));
+        let tracker = CostTracker::from_config(&hub.current().cost);
+        tracker.follow_hub(hub);
+
+        let mut config = Config::default();
+        config.cost.daily_limit_usd = 42.0;
+        config.cost.alert_threshold = 0.5;
+        hub.publish(config);
+
+        for _ in 0..100 {
+            if tracker.daily_limit() == 42.0 {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(tracker.daily_limit(), 42.0);
+        tracker.record_usd("whisper-1", 21.0, None);
+        assert!(tracker.is_approaching_limit());
+    }
+
+    #[test]
+    fn test_set_limits_applies_to_clones() {
+        let tracker = CostTracker::new(1.0, 0.8);
+        let clone = tracker.clone();
+        tracker.record_usd("whisper-1", 0.6, None);
+        assert!(!clone.is_approaching_limit());
+
+        tracker.set_limits(0.5, 0.8);
+        assert_eq!(clone.daily_limit(), 0.5);
+        assert!(clone.is_over_limit());
+        assert_eq!(clone.remaining_budget(), 0.0);
+    }
+
+    #[test]
+    fn test_daily_counter_rolls_over() {
+        let counter = DailyCounter::default();
+        let monday = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let tuesday = monday.succ_opt().unwrap();
+
+        counter.add(monday, 1.25);
+        counter.add(monday, 0.5);
+        assert!((counter.total(monday) - 1.75).abs() < 1e-9);
+
+        counter.add(tuesday, 0.1);
+        assert!((counter.total(tuesday) - 0.1).abs() < 1e-9);
+        assert_eq!(counter.total(monday), 0.0);
+
+        // Late records for an earlier day leave today's count alone
+        counter.add(monday, 5.0);
+        assert!((counter.total(tuesday) - 0.1).abs() < 1e-9);
     }
 }
@@ -0,0 +1,171 @@
+//! Prompt cost previews
+//!
+//! With `cost.chat_preview` (or `/preview` in the chat), each message shows
+//! its prompt tokens, counted with the model's tokenizer, and what it costs
+//! at the model's pricing before it is sent. A message whose prompt plus a
+//! full-length reply would go over what is left of today's budget is held
+//! back, and the models that would fit are offered instead.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::config::CostConfig;
+use crate::llm::tokenizer::count_message_tokens;
+use crate::llm::Message;
+use crate::routing::ModelRegistry;
+use crate::storage::Database;
+use crate::Result;
+
+/// What sending a prompt to one model would cost
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptPreview {
+    pub model: String,
+    pub prompt_tokens: usize,
+    /// Longest reply the request allows
+    pub max_output_tokens: u32,
+    /// Cost of the prompt alone, if the model's pricing is known
+    pub prompt_cost_usd: Option<f64>,
+    /// Cost with a reply of `max_output_tokens`
+    pub max_cost_usd: Option<f64>,
+    /// What is left of today's budget, `None` without a daily limit
+    pub remaining_usd: Option<f64>,
+}
+
+impl PromptPreview {
+    pub fn new(
+        registry: &ModelRegistry,
+        model: &str,
+        messages: &[Message],
+        max_output_tokens: u32,
+        remaining_usd: Option<f64>,
+    ) -> Self {
+        let prompt_tokens = count_message_tokens(model, messages);
+        let pricing = registry.get(model);
+        Self {
+            model: model.to_string(),
+            prompt_tokens,
+            max_output_tokens,
+            prompt_cost_usd: pricing.map(|p| p.estimate_cost(prompt_tokens, 0)),
+            max_cost_usd: pricing
+                .map(|p| p.estimate_cost(prompt_tokens, max_output_tokens as usize)),
+            remaining_usd,
+        }
+    }
+
+    /// Whether a full-length reply stays within today's remaining budget;
+    /// models without known pricing are not held back
+    pub fn fits(&self) -> bool {
+        match (self.max_cost_usd, self.remaining_usd) {
+            (Some(cost), Some(remaining)) => cost <= remaining,
+            _ => true,
+        }
+    }
+}
+
+/// Known models other than `current` whose full-length reply to `messages`
+/// fits the remaining budget, cheapest first
+pub fn alternatives(
+    registry: &ModelRegistry,
+    current: &str,
+    messages: &[Message],
+    max_output_tokens: u32,
+    remaining_usd: Option<f64>,
+) -> Vec<PromptPreview> {
+    let mut previews: Vec<PromptPreview> = registry
+        .all()
+        .filter(|candidate| candidate.model_id != current)
+        .map(|candidate| {
+            PromptPreview::new(
+                registry,
+                &candidate.model_id,
+                messages,
+                max_output_tokens,
+                remaining_usd,
+            )
+        })
+        .filter(PromptPreview::fits)
+        .collect();
+    previews.sort_by(|a, b| {
+        a.max_cost_usd
+            .unwrap_or_default()
+            .total_cmp(&b.max_cost_usd.unwrap_or_default())
+    });
+    previews
+}
+
+/// What is left of today's (UTC) budget at `now`, `None` without a daily limit
+pub async fn remaining_today(
+    db: &Database,
+    cost: &CostConfig,
+    now: DateTime<Utc>,
+) -> Result<Option<f64>> {
+    if cost.daily_limit_usd <= 0.0 {
+        return Ok(None);
+    }
+    let midnight = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .map(|t| t.and_utc())
+        .unwrap_or(now);
+    let spent: f64 = sqlx::query(
+        "SELECT COALESCE(SUM(input_cost_usd + output_cost_usd), 0.0) AS spent FROM llm_costs WHERE julianday(created_at) >= julianday(?)",
+    )
+    .bind(midnight.to_rfc3339())
+    .fetch_one(db.pool())
+    .await?
+    .get("spent");
+    Ok(Some((cost.daily_limit_usd - spent).max(0.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_and_alternatives() {
+        let registry = ModelRegistry::with_defaults();
+        let messages = vec![Message::user("x ".repeat(5_000))];
+
+        let opus = PromptPreview::new(
+            &registry,
+            "anthropic/claude-opus-4-20250514",
+            &messages,
+            4096,
+            Some(0.10),
+        );
+        assert!(opus.prompt_tokens > 4_000);
+        assert!(opus.prompt_cost_usd.unwrap() < opus.max_cost_usd.unwrap());
+        assert!(!opus.fits());
+
+        let cheaper = alternatives(&registry, &opus.model, &messages, 4096, Some(0.10));
+        assert!(!cheaper.is_empty());
+        assert_eq!(cheaper[0].model, "openai/gpt-4o-mini");
+        assert!(cheaper.iter().all(|p| p.fits() && p.model != opus.model));
+
+        let unknown = PromptPreview::new(&registry, "local/llama", &messages, 4096, Some(0.0));
+        assert_eq!(unknown.max_cost_usd, None);
+        assert!(unknown.fits());
+    }
+
+    #[tokio::test]
+    async fn test_remaining_today() {
+        let db = Database::in_memory().await.unwrap();
+        let now = Utc::now();
+        sqlx::query(
+            "INSERT INTO llm_costs (id, model, input_cost_usd, created_at) VALUES ('c1', 'm', 1.5, ?)",
+        )
+        .bind(now.to_rfc3339())
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let cost = CostConfig::default();
+        assert_eq!(remaining_today(&db, &cost, now).await.unwrap(), Some(8.5));
+        let unlimited = CostConfig {
+            daily_limit_usd: 0.0,
+            ..cost
+        };
+        assert_eq!(remaining_today(&db, &unlimited, now).await.unwrap(), None);
+    }
+}
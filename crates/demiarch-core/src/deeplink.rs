@@ -0,0 +1,176 @@
+//! `demiarch://` deep links
+//!
+//! Links name a project, feature or generation, e.g.
+//! `demiarch://feature/3f2a...`. The CLI prints them next to IDs so terminal
+//! summaries, reports and webhooks can click through to the desktop app,
+//! which registers the scheme and opens the matching page.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// URI scheme registered by the desktop app
+pub const SCHEME: &str = "demiarch";
+
+/// A record a deep link points at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum DeepLink {
+    Project(String),
+    Feature(String),
+    Generation(String),
+}
+
+impl DeepLink {
+    /// The `demiarch://` URL for this link
+    pub fn url(&self) -> String {
+        let (kind, id) = match self {
+            Self::Project(id) => ("project", id),
+            Self::Feature(id) => ("feature", id),
+            Self::Generation(id) => ("generation", id),
+        };
+        format!("{}://{}/{}", SCHEME, kind, id)
+    }
+
+    /// Parse a `demiarch://<kind>/<id>` URL
+    ///
+    /// Trailing slashes, query strings and fragments are ignored.
+    pub fn parse(url: &str) -> Result<Self> {
+        let invalid = || {
+            Error::InvalidInput(format!(
+                "Invalid link '{}'. Expected {}://project/<id>, feature/<id> or generation/<id>",
+                url, SCHEME
+            ))
+        };
+        let rest = url
+            .trim()
+            .strip_prefix(SCHEME)
+            .and_then(|r| r.strip_prefix("://"))
+            .ok_or_else(invalid)?;
+        let rest = rest.split(['?', '#']).next().unwrap_or_default();
+        let mut parts = rest.trim_matches('/').split('/');
+        let (Some(kind), Some(id), None) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(invalid());
+        }
+        match kind {
+            "project" => Ok(Self::Project(id.to_string())),
+            "feature" => Ok(Self::Feature(id.to_string())),
+            "generation" => Ok(Self::Generation(id.to_string())),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// GUI route that shows the linked record
+    ///
+    /// Features open on their project's board and generations on their
+    /// project's review page, so both need their project looked up.
+    pub async fn route(&self, db: &Database) -> Result<String> {
+        match self {
+            Self::Project(id) => Ok(format!("/projects/{}", id)),
+            Self::Feature(id) => {
+                let project_id = owning_project(db, "features", id)
+                    .await?
+                    .ok_or_else(|| Error::FeatureNotFound(id.clone()))?;
+                Ok(format!("/projects/{}/kanban?feature={}", project_id, id))
+            }
+            Self::Generation(id) => match owning_project(db, "generations", id).await? {
+                Some(project_id) => Ok(format!(
+                    "/projects/{}/conflicts?generation={}",
+                    project_id, id
+                )),
+                // Generations run outside a project are only visible as jobs
+                None => Ok("/jobs".to_string()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for DeepLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.url())
+    }
+}
+
+/// Project of a feature or generation; errors if the record does not exist
+async fn owning_project(db: &Database, table: &str, id: &str) -> Result<Option<String>> {
+    let row = sqlx::query(&format!("SELECT project_id FROM {} WHERE id = ?", table))
+        .bind(id)
+        .fetch_optional(db.pool())
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("{} {}", table.trim_end_matches('s'), id)))?;
+    Ok(row.get("project_id"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for link in [
+            DeepLink::Project("p1".to_string()),
+            DeepLink::Feature("3f2a-b".to_string()),
+            DeepLink::Generation("g_1".to_string()),
+        ] {
+            assert_eq!(DeepLink::parse(&link.url()).unwrap(), link);
+        }
+        assert_eq!(
+            DeepLink::parse("demiarch://project/abc/?from=report").unwrap(),
+            DeepLink::Project("abc".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed_links() {
+        for url in [
+            "https://project/abc",
+            "demiarch://project",
+            "demiarch://project/",
+            "demiarch://session/abc",
+            "demiarch://project/abc/extra",
+            "demiarch://project/..%2Fetc",
+        ] {
+            assert!(DeepLink::parse(url).is_err(), "{} should be rejected", url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes() {
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'Demo')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO features (id, project_id, title) VALUES ('f1', 'p1', 'login')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO generations (id, description, output_dir) VALUES ('g1', 'adhoc', '/tmp')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let feature = DeepLink::Feature("f1".to_string());
+        assert_eq!(
+            feature.route(&db).await.unwrap(),
+            "/projects/p1/kanban?feature=f1"
+        );
+        let generation = DeepLink::Generation("g1".to_string());
+        assert_eq!(generation.route(&db).await.unwrap(), "/jobs");
+        let missing = DeepLink::Feature("missing".to_string());
+        assert!(missing.route(&db).await.is_err());
+    }
+}
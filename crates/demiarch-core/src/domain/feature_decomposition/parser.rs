@@ -28,8 +28,9 @@ impl PlanParser {
         self.parse_heuristic(original_task, response)
     }
 
-    /// Try to parse JSON from the response
-    fn try_parse_json(&self, response: &str) -> Option<ExecutionPlan> {
+    /// Try to parse a JSON plan from the response, without the heuristic
+    /// fallback
+    pub fn try_parse_json(&self, response: &str) -> Option<ExecutionPlan> {
         // Find JSON in the response
         let json_start = response.find('{')?;
         let json_end = response.rfind('}')?;
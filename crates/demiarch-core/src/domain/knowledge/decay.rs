@@ -0,0 +1,82 @@
+//! Time decay for knowledge confidence
+//!
+//! Entity confidence, relationship weight and skill confidence only go up as
+//! knowledge is reinforced, so without aging a concept learned once a year
+//! ago ranks like one used yesterday. A stored score is treated as accurate
+//! as of the item's `updated_at` (the last time it was reinforced or aged);
+//! its effective value halves with every half-life since then.
+//!
+//! Queries apply the decay on the fly. Maintenance ([`crate::commands::graph::age_knowledge`])
+//! writes decayed values back and archives what has faded below a floor.
+
+use chrono::{DateTime, Utc};
+
+/// Exponential decay with a fixed half-life
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecayModel {
+    /// Days for a score to halve; zero or less disables decay
+    half_life_days: f64,
+}
+
+impl DecayModel {
+    /// Decay that halves a score every `half_life_days`
+    pub fn new(half_life_days: f64) -> Self {
+        Self { half_life_days }
+    }
+
+    /// No decay: scores keep their stored value
+    pub fn disabled() -> Self {
+        Self::new(0.0)
+    }
+
+    /// Whether scores decay at all
+    pub fn is_enabled(&self) -> bool {
+        self.half_life_days > 0.0
+    }
+
+    /// Days for a score to halve
+    pub fn half_life_days(&self) -> f64 {
+        self.half_life_days
+    }
+
+    /// Multiplier for a score last reinforced at `since` (1.0 when fresh)
+    pub fn factor(&self, since: DateTime<Utc>, now: DateTime<Utc>) -> f32 {
+        if !self.is_enabled() {
+            return 1.0;
+        }
+        let age_days = (now - since).num_seconds().max(0) as f64 / 86_400.0;
+        0.5_f64.powf(age_days / self.half_life_days) as f32
+    }
+
+    /// `score` decayed from `since` to `now`
+    pub fn apply(&self, score: f32, since: DateTime<Utc>, now: DateTime<Utc>) -> f32 {
+        score * self.factor(since, now)
+    }
+}
+
+impl Default for DecayModel {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_halves_every_half_life() {
+        let now = Utc::now();
+        let decay = DecayModel::new(30.0);
+
+        assert_eq!(decay.factor(now, now), 1.0);
+        assert!((decay.apply(0.8, now - Duration::days(30), now) - 0.4).abs() < 1e-4);
+        assert!((decay.apply(0.8, now - Duration::days(60), now) - 0.2).abs() < 1e-4);
+        // Timestamps in the future don't boost a score
+        assert_eq!(decay.factor(now + Duration::days(5), now), 1.0);
+
+        let off = DecayModel::disabled();
+        assert_eq!(off.apply(0.8, now - Duration::days(365), now), 0.8);
+    }
+}
@@ -10,6 +10,7 @@ use tracing::{debug, info};
 
 use crate::error::Result;
 
+use super::decay::DecayModel;
 use super::entity::{EntityType, KnowledgeEntity};
 use super::relationship::{KnowledgeRelationship, RelationshipType};
 use super::repository::{EntityWithDistance, KnowledgeGraphRepository};
@@ -142,6 +143,8 @@ pub struct ContextEnricher<R: KnowledgeGraphRepository> {
     repository: Arc<R>,
     /// Configuration
     config: EnrichmentConfig,
+    /// Aging applied to entity confidence
+    decay: DecayModel,
 }
 
 impl<R: KnowledgeGraphRepository> ContextEnricher<R> {
@@ -150,12 +153,29 @@ impl<R: KnowledgeGraphRepository> ContextEnricher<R> {
         Self {
             repository,
             config: EnrichmentConfig::default(),
+            decay: DecayModel::disabled(),
         }
     }
 
     /// Create with custom configuration
     pub fn with_config(repository: Arc<R>, config: EnrichmentConfig) -> Self {
-        Self { repository, config }
+        Self {
+            repository,
+            config,
+            decay: DecayModel::disabled(),
+        }
+    }
+
+    /// Age entity confidence with `decay` before filtering and ranking
+    pub fn with_decay(mut self, decay: DecayModel) -> Self {
+        self.decay = decay;
+        self
+    }
+
+    /// An entity's confidence after aging
+    fn confidence(&self, entity: &KnowledgeEntity) -> f32 {
+        self.decay
+            .apply(entity.confidence, entity.updated_at, chrono::Utc::now())
     }
 
     /// Enrich context based on a query string
@@ -166,7 +186,7 @@ impl<R: KnowledgeGraphRepository> ContextEnricher<R> {
         info!(query_len = query.len(), "Enriching context from query");
 
         // Step 1: Extract key terms from query
-        let terms = self.extract_terms(query);
+        let terms = extract_terms(query);
         debug!(terms = ?terms, "Extracted terms from query");
 
         // Step 2: Search for matching entities
@@ -177,7 +197,7 @@ impl<R: KnowledgeGraphRepository> ContextEnricher<R> {
             let search_results = self.repository.search_entities(term, 10).await?;
 
             for entity in search_results {
-                if entity.confidence >= self.config.min_confidence {
+                if self.confidence(&entity) >= self.config.min_confidence {
                     matched_terms.push(term.clone());
                     all_entities
                         .entry(entity.id.clone())
@@ -207,7 +227,7 @@ impl<R: KnowledgeGraphRepository> ContextEnricher<R> {
                 .await?;
 
             for neighbor in neighbors {
-                if neighbor.entity.confidence >= self.config.min_confidence {
+                if self.confidence(&neighbor.entity) >= self.config.min_confidence {
                     all_entities
                         .entry(neighbor.entity.id.clone())
                         .or_insert(neighbor);
@@ -286,7 +306,7 @@ impl<R: KnowledgeGraphRepository> ContextEnricher<R> {
                 .await?;
 
             for neighbor in neighbors {
-                if neighbor.entity.confidence >= self.config.min_confidence {
+                if self.confidence(&neighbor.entity) >= self.config.min_confidence {
                     all_entities
                         .entry(neighbor.entity.id.clone())
                         .or_insert(neighbor);
@@ -331,136 +351,6 @@ impl<R: KnowledgeGraphRepository> ContextEnricher<R> {
         self.enrich_from_entities(&entity_ids).await
     }
 
-    /// Extract searchable terms from a query
-    fn extract_terms(&self, query: &str) -> Vec<String> {
-        // Simple term extraction: split on whitespace and punctuation,
-        // filter short words and common stop words
-        let stop_words: HashSet<&str> = [
-            "the",
-            "a",
-            "an",
-            "is",
-            "are",
-            "was",
-            "were",
-            "be",
-            "been",
-            "being",
-            "have",
-            "has",
-            "had",
-            "do",
-            "does",
-            "did",
-            "will",
-            "would",
-            "could",
-            "should",
-            "may",
-            "might",
-            "must",
-            "shall",
-            "can",
-            "need",
-            "dare",
-            "to",
-            "of",
-            "in",
-            "for",
-            "on",
-            "with",
-            "at",
-            "by",
-            "from",
-            "up",
-            "about",
-            "into",
-            "through",
-            "during",
-            "before",
-            "after",
-            "above",
-            "below",
-            "between",
-            "under",
-            "again",
-            "further",
-            "then",
-            "once",
-            "here",
-            "there",
-            "when",
-            "where",
-            "why",
-            "how",
-            "all",
-            "each",
-            "few",
-            "more",
-            "most",
-            "other",
-            "some",
-            "such",
-            "no",
-            "nor",
-            "not",
-            "only",
-            "own",
-            "same",
-            "so",
-            "than",
-            "too",
-            "very",
-            "just",
-            "now",
-            "and",
-            "but",
-            "if",
-            "or",
-            "because",
-            "as",
-            "until",
-            "while",
-            "this",
-            "that",
-            "these",
-            "those",
-            "what",
-            "which",
-            "who",
-            "whom",
-            "i",
-            "me",
-            "my",
-            "we",
-            "our",
-            "you",
-            "your",
-            "it",
-            "its",
-            "they",
-            "them",
-            "code",
-            "write",
-            "create",
-            "make",
-            "implement",
-            "add",
-            "use",
-            "using",
-        ]
-        .into_iter()
-        .collect();
-
-        query
-            .to_lowercase()
-            .split(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
-            .filter(|word| word.len() >= 3)
-            .filter(|word| !stop_words.contains(word))
-            .map(|s| s.to_string())
-            .collect()
-    }
-
     /// Rank entities and select the top ones
     fn rank_and_select_entities(
         &self,
@@ -498,8 +388,8 @@ impl<R: KnowledgeGraphRepository> ContextEnricher<R> {
         let entity = &entity_with_distance.entity;
         let distance = entity_with_distance.distance;
 
-        // Base score from confidence
-        let mut score = entity.confidence;
+        // Base score from (aged) confidence
+        let mut score = self.confidence(entity);
 
         // Distance penalty (closer = higher score)
         let distance_penalty = 0.85_f32.powi(distance as i32);
@@ -681,6 +571,136 @@ impl<R: KnowledgeGraphRepository> ContextEnricher<R> {
     }
 }
 
+/// Extract searchable terms from a query, dropping short and common words
+pub fn extract_terms(query: &str) -> Vec<String> {
+    // Simple term extraction: split on whitespace and punctuation,
+    // filter short words and common stop words
+    let stop_words: HashSet<&str> = [
+        "the",
+        "a",
+        "an",
+        "is",
+        "are",
+        "was",
+        "were",
+        "be",
+        "been",
+        "being",
+        "have",
+        "has",
+        "had",
+        "do",
+        "does",
+        "did",
+        "will",
+        "would",
+        "could",
+        "should",
+        "may",
+        "might",
+        "must",
+        "shall",
+        "can",
+        "need",
+        "dare",
+        "to",
+        "of",
+        "in",
+        "for",
+        "on",
+        "with",
+        "at",
+        "by",
+        "from",
+        "up",
+        "about",
+        "into",
+        "through",
+        "during",
+        "before",
+        "after",
+        "above",
+        "below",
+        "between",
+        "under",
+        "again",
+        "further",
+        "then",
+        "once",
+        "here",
+        "there",
+        "when",
+        "where",
+        "why",
+        "how",
+        "all",
+        "each",
+        "few",
+        "more",
+        "most",
+        "other",
+        "some",
+        "such",
+        "no",
+        "nor",
+        "not",
+        "only",
+        "own",
+        "same",
+        "so",
+        "than",
+        "too",
+        "very",
+        "just",
+        "now",
+        "and",
+        "but",
+        "if",
+        "or",
+        "because",
+        "as",
+        "until",
+        "while",
+        "this",
+        "that",
+        "these",
+        "those",
+        "what",
+        "which",
+        "who",
+        "whom",
+        "i",
+        "me",
+        "my",
+        "we",
+        "our",
+        "you",
+        "your",
+        "it",
+        "its",
+        "they",
+        "them",
+        "code",
+        "write",
+        "create",
+        "make",
+        "implement",
+        "add",
+        "use",
+        "using",
+    ]
+    .into_iter()
+    .collect();
+
+    query
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+        .filter(|word| word.len() >= 3)
+        .filter(|word| !stop_words.contains(word))
+        .map(|s| s.to_string())
+        .collect()
+}
+
 /// Format a relationship type as a verb phrase
 fn format_relationship_type(rel_type: RelationshipType) -> &'static str {
     match rel_type {
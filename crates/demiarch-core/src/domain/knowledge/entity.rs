@@ -109,6 +109,28 @@ impl KnowledgeEntity {
         self.updated_at = Utc::now();
     }
 
+    /// Fold a duplicate of this entity into it
+    ///
+    /// Keeps this entity's id, name and type. The duplicate's name and
+    /// aliases become aliases, source skills are unioned, a missing
+    /// description is filled in, and confidences combine with
+    /// [`combine_confidence`].
+    pub fn absorb(&mut self, duplicate: &KnowledgeEntity) {
+        for name in std::iter::once(&duplicate.name).chain(&duplicate.aliases) {
+            if Self::canonicalize(name) != self.canonical_name {
+                self.add_alias(name.clone());
+            }
+        }
+        for skill_id in &duplicate.source_skill_ids {
+            self.add_source_skill(skill_id.clone());
+        }
+        if self.description.is_none() {
+            self.description = duplicate.description.clone();
+        }
+        self.confidence = combine_confidence(self.confidence, duplicate.confidence);
+        self.updated_at = Utc::now();
+    }
+
     /// Canonicalize a name for deduplication
     ///
     /// Converts to lowercase, removes special characters, and normalizes whitespace
@@ -134,6 +156,13 @@ impl KnowledgeEntity {
     }
 }
 
+/// Combine two confidence scores as independent evidence for the same thing
+///
+/// `1 - (1 - a)(1 - b)`: never lower than either score, and never above 1.0.
+pub fn combine_confidence(a: f32, b: f32) -> f32 {
+    (1.0 - (1.0 - a) * (1.0 - b)).clamp(0.0, 1.0)
+}
+
 /// Types of knowledge entities
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -277,6 +306,27 @@ mod tests {
         assert_eq!(entity.source_skill_ids.len(), 2);
     }
 
+    #[test]
+    fn test_absorb_duplicate() {
+        let mut keep = KnowledgeEntity::new("Next.js", EntityType::Framework)
+            .with_source_skills(vec!["skill-1".into()])
+            .with_confidence(0.6);
+        let duplicate = KnowledgeEntity::new("NextJS", EntityType::Library)
+            .with_description("React framework")
+            .with_aliases(vec!["next js".into()])
+            .with_source_skills(vec!["skill-1".into(), "skill-2".into()])
+            .with_confidence(0.5);
+
+        keep.absorb(&duplicate);
+
+        // "NextJS" canonicalizes to keep's own name, so only "next js" is new
+        assert_eq!(keep.aliases, vec!["next js".to_string()]);
+        assert_eq!(keep.source_skill_ids.len(), 2);
+        assert_eq!(keep.description.as_deref(), Some("React framework"));
+        assert!((keep.confidence - 0.8).abs() < 1e-6);
+        assert_eq!(keep.entity_type, EntityType::Framework);
+    }
+
     #[test]
     fn test_confidence_clamping() {
         let mut entity = KnowledgeEntity::new("test", EntityType::Concept);
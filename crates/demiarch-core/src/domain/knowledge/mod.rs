@@ -56,6 +56,7 @@
 //! println!("Related concepts: {}", context.formatted_context);
 //! ```
 
+mod decay;
 mod enricher;
 mod entity;
 mod event;
@@ -65,19 +66,20 @@ mod repository;
 mod search;
 mod service;
 
+pub use decay::DecayModel;
 pub use enricher::{
-    ContextEnricher, EnrichedContext, EnrichmentConfig, EnrichmentStats, EntityContext,
-    RelationshipContext,
+    extract_terms, ContextEnricher, EnrichedContext, EnrichmentConfig, EnrichmentStats,
+    EntityContext, RelationshipContext,
 };
-pub use entity::{EntityType, KnowledgeEntity};
+pub use entity::{combine_confidence, EntityType, KnowledgeEntity};
 pub use event::KnowledgeEvent;
 pub use extractor::{EntityExtractor, ExtractionResult};
 pub use relationship::{
     EvidenceSource, KnowledgeRelationship, RelationshipEvidence, RelationshipType,
 };
 pub use repository::{
-    EntitySearchResult, EntityWithDistance, KnowledgeGraphRepository, KnowledgeGraphStats,
-    PathRelationship, PathStep, TraversalDirection,
+    EntityMerge, EntitySearchResult, EntityWithDistance, KnowledgeGraphRepository,
+    KnowledgeGraphStats, PathRelationship, PathStep, TraversalDirection,
 };
 pub use search::{
     EntityRelevance, GraphSearchQuery, HybridRanker, HybridRankingConfig, HybridSearchResult,
@@ -24,7 +24,7 @@ pub trait KnowledgeGraphRepository: Send + Sync {
     /// Get an entity by ID
     async fn get_entity(&self, id: &str) -> Result<Option<KnowledgeEntity>>;
 
-    /// Get an entity by canonical name
+    /// Get an entity by canonical name, or by an alias recorded for it
     async fn get_entity_by_canonical_name(
         &self,
         canonical_name: &str,
@@ -156,6 +156,30 @@ pub trait KnowledgeGraphRepository: Send + Sync {
         min_similarity: f32,
     ) -> Result<Vec<EntitySearchResult>>;
 
+    // ========== Alias and Merge Operations ==========
+
+    /// Record `alias` as another name for an entity, so later lookups by
+    /// canonical name resolve to it
+    async fn save_alias(&self, entity_id: &str, alias: &str) -> Result<()>;
+
+    /// List the aliases recorded for an entity
+    async fn list_aliases(&self, entity_id: &str) -> Result<Vec<String>>;
+
+    /// Fold `duplicate_id` into `keep_id` and delete the duplicate
+    ///
+    /// Relationships and skill links move to the kept entity, confidences
+    /// are unioned, and the duplicate's names are recorded as aliases.
+    async fn merge_entities(&self, keep_id: &str, duplicate_id: &str) -> Result<EntityMerge>;
+
+    // ========== Archive Operations ==========
+
+    /// Move an entity and its relationships out of the live graph into the
+    /// archive
+    async fn archive_entity(&self, id: &str) -> Result<bool>;
+
+    /// Move a relationship out of the live graph into the archive
+    async fn archive_relationship(&self, id: &str) -> Result<bool>;
+
     // ========== Statistics ==========
 
     /// Get graph statistics
@@ -213,6 +237,25 @@ pub struct EntitySearchResult {
     pub similarity: f32,
 }
 
+/// Outcome of merging a duplicate entity into another
+#[derive(Debug, Clone)]
+pub struct EntityMerge {
+    /// The kept entity, after the merge
+    pub entity: KnowledgeEntity,
+    /// ID of the deleted duplicate
+    pub duplicate_id: String,
+    /// Relationships re-pointed at the kept entity
+    pub relationships_moved: usize,
+    /// Relationships folded into one the kept entity already had
+    pub relationships_combined: usize,
+    /// Relationships between the two entities, which were dropped
+    pub relationships_dropped: usize,
+    /// Skill links moved to the kept entity
+    pub skill_links_moved: usize,
+    /// Names recorded as aliases of the kept entity
+    pub aliases_recorded: Vec<String>,
+}
+
 /// Statistics about the knowledge graph
 #[derive(Debug, Clone, Default)]
 pub struct KnowledgeGraphStats {
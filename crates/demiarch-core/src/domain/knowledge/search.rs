@@ -14,6 +14,7 @@ use tracing::debug;
 use crate::error::Result;
 use crate::skills::{LearnedSkill, SkillStore};
 
+use super::decay::DecayModel;
 use super::entity::KnowledgeEntity;
 use super::relationship::RelationshipType;
 use super::repository::KnowledgeGraphRepository;
@@ -231,6 +232,8 @@ pub struct HybridRanker<R: KnowledgeGraphRepository> {
     repository: Arc<R>,
     /// Skill store for FTS and embeddings
     skill_store: Arc<SkillStore>,
+    /// Aging applied to usage and confidence signals
+    decay: DecayModel,
 }
 
 impl<R: KnowledgeGraphRepository> HybridRanker<R> {
@@ -239,9 +242,16 @@ impl<R: KnowledgeGraphRepository> HybridRanker<R> {
         Self {
             repository,
             skill_store,
+            decay: DecayModel::disabled(),
         }
     }
 
+    /// Fade usage and confidence signals of skills not reinforced recently
+    pub fn with_decay(mut self, decay: DecayModel) -> Self {
+        self.decay = decay;
+        self
+    }
+
     /// Execute a hybrid search
     pub async fn search(&self, query: &GraphSearchQuery) -> Result<Vec<HybridSearchResult>> {
         let config = &query.config;
@@ -487,9 +497,12 @@ impl<R: KnowledgeGraphRepository> HybridRanker<R> {
             breakdown.graph_score = graph_score.min(1.0);
         }
 
+        // Usage and confidence fade with time since the skill was last
+        // used or updated
+        let recency_factor = self.decay.factor(skill.updated_at, chrono::Utc::now());
+
         // Usage score: based on success rate and usage count
         let usage_score = if skill.usage_stats.times_used > 0 {
-            let recency_factor = 1.0; // Could factor in last_used_at
             let success_factor = skill.usage_stats.success_rate() as f32;
             let usage_factor = (skill.usage_stats.times_used as f32).ln() / 10.0;
             ((success_factor * 0.5 + usage_factor * 0.5) * recency_factor).min(1.0)
@@ -499,7 +512,7 @@ impl<R: KnowledgeGraphRepository> HybridRanker<R> {
         breakdown.usage_score = usage_score;
 
         // Confidence score: skill confidence
-        breakdown.confidence_score = skill.confidence.score() as f32 / 3.0;
+        breakdown.confidence_score = skill.confidence.score() as f32 / 3.0 * recency_factor;
 
         Ok(breakdown)
     }
@@ -0,0 +1,176 @@
+//! Consolidation of near-duplicate memory records
+//!
+//! Repeated ingestion of the same conversation produces records whose
+//! embeddings are almost identical. Consolidation clusters those records,
+//! merges each cluster into a single record with the duplicate paragraphs
+//! removed, and keeps the IDs of the originals as provenance.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{cosine_similarity, MemoryRecord};
+
+/// Default similarity above which two records are considered duplicates
+pub const DEFAULT_CONSOLIDATION_THRESHOLD: f32 = 0.97;
+
+/// Group near-duplicate records by embedding similarity.
+///
+/// Returns clusters of indices into `records` with at least two members.
+/// Each cluster is seeded by its earliest unassigned record, so the input
+/// order decides which record the others are compared against. Pinned
+/// records are never clustered.
+pub fn cluster_by_similarity(records: &[MemoryRecord], threshold: f32) -> Vec<Vec<usize>> {
+    let mut assigned = vec![false; records.len()];
+    let mut clusters = Vec::new();
+
+    for i in 0..records.len() {
+        if assigned[i] || records[i].pinned {
+            continue;
+        }
+        let mut cluster = vec![i];
+        for j in (i + 1)..records.len() {
+            if assigned[j] || records[j].pinned {
+                continue;
+            }
+            let sim = cosine_similarity(
+                records[i].embeddings.index.as_slice(),
+                records[j].embeddings.index.as_slice(),
+            );
+            if sim >= threshold {
+                cluster.push(j);
+            }
+        }
+        if cluster.len() > 1 {
+            for &idx in &cluster {
+                assigned[idx] = true;
+            }
+            clusters.push(cluster);
+        }
+    }
+
+    clusters
+}
+
+/// Merge the content of clustered records, dropping repeated paragraphs
+pub fn merge_contents<'a>(records: impl IntoIterator<Item = &'a MemoryRecord>) -> String {
+    let mut seen = HashSet::new();
+    let mut paragraphs = Vec::new();
+    for record in records {
+        for paragraph in record.full_context.split("\n\n") {
+            let paragraph = paragraph.trim();
+            if paragraph.is_empty() {
+                continue;
+            }
+            let key = paragraph
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase();
+            if seen.insert(key) {
+                paragraphs.push(paragraph);
+            }
+        }
+    }
+    paragraphs.join("\n\n")
+}
+
+/// Provenance of every record a merged record replaces, including the
+/// sources of records that were themselves consolidated earlier
+pub fn merged_sources<'a>(records: impl IntoIterator<Item = &'a MemoryRecord>) -> Vec<Uuid> {
+    let mut sources = Vec::new();
+    for record in records {
+        for id in std::iter::once(&record.id).chain(record.sources.iter()) {
+            if !sources.contains(id) {
+                sources.push(*id);
+            }
+        }
+    }
+    sources
+}
+
+/// A record produced by consolidation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsolidatedRecord {
+    pub id: Uuid,
+    /// Records merged into this one
+    pub sources: Vec<Uuid>,
+    pub tokens: usize,
+}
+
+/// Outcome of a consolidation pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConsolidationReport {
+    pub records_before: usize,
+    pub records_after: usize,
+    pub tokens_before: usize,
+    pub tokens_after: usize,
+    pub merged: Vec<ConsolidatedRecord>,
+    /// Whether the report describes changes that were not applied
+    pub dry_run: bool,
+}
+
+impl ConsolidationReport {
+    /// Number of records removed by merging
+    pub fn records_removed(&self) -> usize {
+        self.records_before.saturating_sub(self.records_after)
+    }
+
+    /// Estimated tokens no longer spent on duplicates
+    pub fn tokens_saved(&self) -> usize {
+        self.tokens_before.saturating_sub(self.tokens_after)
+    }
+
+    /// Fold another pass (e.g. for a different project) into this report
+    pub fn absorb(&mut self, other: ConsolidationReport) {
+        self.records_before += other.records_before;
+        self.records_after += other.records_after;
+        self.tokens_before += other.tokens_before;
+        self.tokens_after += other.tokens_after;
+        self.merged.extend(other.merged);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::memory::SimpleEmbedder;
+
+    fn record(content: &str) -> MemoryRecord {
+        MemoryRecord::new(content, "test", &SimpleEmbedder::default()).unwrap()
+    }
+
+    #[test]
+    fn test_clusters_near_duplicates_only() {
+        let records = vec![
+            record("[user] How do we deploy the API?"),
+            record("Completely different notes about the billing schema and invoices"),
+            record("[user] How do we deploy the api"),
+        ];
+        let clusters = cluster_by_similarity(&records, DEFAULT_CONSOLIDATION_THRESHOLD);
+        assert_eq!(clusters, vec![vec![0, 2]]);
+    }
+
+    #[test]
+    fn test_pinned_records_are_not_clustered() {
+        let mut records = vec![record("Use Postgres"), record("Use Postgres")];
+        records[1].pinned = true;
+        assert!(cluster_by_similarity(&records, DEFAULT_CONSOLIDATION_THRESHOLD).is_empty());
+    }
+
+    #[test]
+    fn test_merge_drops_repeated_paragraphs_and_keeps_provenance() {
+        let a = record("[user] Deploy?\n\n[assistant] Use the release script.");
+        let mut b = record("[user]  deploy?\n\n[assistant] Tag first, then release.");
+        let earlier = Uuid::new_v4();
+        b.sources = vec![earlier];
+
+        let merged = merge_contents([&a, &b]);
+        assert_eq!(
+            merged,
+            "[user] Deploy?\n\n[assistant] Use the release script.\n\n[assistant] Tag first, then release."
+        );
+        assert_eq!(merged_sources([&a, &b]), vec![a.id, b.id, earlier]);
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Provides ~10x token savings via layered summarization and embedding-based retrieval.
 
+mod consolidation;
 mod embedding;
 mod error;
 mod full;
@@ -9,7 +10,9 @@ mod index;
 mod persistent;
 mod timeline;
 
-pub use self::{embedding::*, error::*, full::*, index::*, persistent::*, timeline::*};
+pub use self::{
+    consolidation::*, embedding::*, error::*, full::*, index::*, persistent::*, timeline::*,
+};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -25,6 +28,9 @@ pub enum MemoryLayer {
     Full,
 }
 
+/// Default token cap for pinned memory records
+pub const DEFAULT_PIN_BUDGET_TOKENS: usize = 1024;
+
 /// Memory recall request specifying disclosure boundaries
 #[derive(Debug, Clone)]
 pub struct RecallQuery {
@@ -33,6 +39,8 @@ pub struct RecallQuery {
     pub max_tokens: usize,
     pub time_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
     pub relevance_threshold: f32,
+    /// Token cap for pinned records, which are returned ahead of ranked ones
+    pub pin_budget_tokens: usize,
 }
 
 impl Default for RecallQuery {
@@ -43,6 +51,7 @@ impl Default for RecallQuery {
             max_tokens: 4096,
             time_range: None,
             relevance_threshold: 0.25,
+            pin_budget_tokens: DEFAULT_PIN_BUDGET_TOKENS,
         }
     }
 }
@@ -57,6 +66,10 @@ pub struct MemoryRecord {
     pub timeline_entry: TimelineEntry,
     pub full_context: String,
     pub embeddings: Embeddings,
+    /// Pinned records are always recalled and never pruned or compressed away
+    pub pinned: bool,
+    /// Records merged into this one by consolidation
+    pub sources: Vec<Uuid>,
 }
 
 impl MemoryRecord {
@@ -83,6 +96,8 @@ impl MemoryRecord {
             timeline_entry,
             full_context,
             embeddings,
+            pinned: false,
+            sources: Vec::new(),
         })
     }
 
@@ -137,38 +152,7 @@ impl MemoryStore {
             &[&query.query, &query.query, &query.query],
         )?;
 
-        let records = self.records.read().await.clone();
-        let mut ranked: Vec<(f32, MemoryRecord)> = records
-            .into_iter()
-            .filter(|record| match query.time_range {
-                Some((start, end)) => record.created_at >= start && record.created_at <= end,
-                None => true,
-            })
-            .map(|record| {
-                let sim = cosine_similarity(
-                    query_embeddings.index.as_slice(),
-                    record.embeddings.index.as_slice(),
-                );
-                (sim, record)
-            })
-            .filter(|(sim, _)| *sim >= query.relevance_threshold)
-            .collect();
-
-        ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
-
-        let mut results = Vec::new();
-        let mut tokens_accumulated = 0usize;
-        for (_sim, mut record) in ranked {
-            record.updated_at = Utc::now();
-            let tokens = record.token_estimate();
-            if tokens_accumulated + tokens > query.max_tokens {
-                break;
-            }
-            tokens_accumulated += tokens;
-            results.push(record);
-        }
-
-        Ok(results)
+        Ok(self.rank(&query, query_embeddings.index.as_slice()).await)
     }
 
     /// Recall with externally provided embeddings to avoid recomputing
@@ -177,8 +161,28 @@ impl MemoryStore {
         query: RecallQuery,
         query_embedding: &[f32],
     ) -> Result<Vec<MemoryRecord>, MemoryError> {
+        Ok(self.rank(&query, query_embedding).await)
+    }
+
+    /// Pinned records first (within the pin budget), then records ranked by
+    /// similarity within the query's token budget
+    async fn rank(&self, query: &RecallQuery, query_embedding: &[f32]) -> Vec<MemoryRecord> {
         let records = self.records.read().await.clone();
-        let mut ranked: Vec<(f32, MemoryRecord)> = records
+        let (pinned, unpinned): (Vec<_>, Vec<_>) = records.into_iter().partition(|r| r.pinned);
+
+        let mut results = Vec::new();
+        let mut pin_tokens = 0usize;
+        for mut record in pinned {
+            let tokens = record.token_estimate();
+            if pin_tokens + tokens > query.pin_budget_tokens {
+                continue;
+            }
+            pin_tokens += tokens;
+            record.updated_at = Utc::now();
+            results.push(record);
+        }
+
+        let mut ranked: Vec<(f32, MemoryRecord)> = unpinned
             .into_iter()
             .filter(|record| match query.time_range {
                 Some((start, end)) => record.created_at >= start && record.created_at <= end,
@@ -193,7 +197,6 @@ impl MemoryStore {
 
         ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
 
-        let mut results = Vec::new();
         let mut tokens_accumulated = 0usize;
         for (_sim, mut record) in ranked {
             record.updated_at = Utc::now();
@@ -205,14 +208,110 @@ impl MemoryStore {
             results.push(record);
         }
 
-        Ok(results)
+        results
+    }
+
+    /// Add a pinned memory entry, refusing it if pinned records would exceed
+    /// `budget_tokens`
+    pub async fn pin(
+        &self,
+        content: &str,
+        budget_tokens: usize,
+    ) -> Result<MemoryRecord, MemoryError> {
+        let mut record = MemoryRecord::new(content, &self.embedding_model, self.embedder.as_ref())?;
+        record.pinned = true;
+
+        let mut guard = self.records.write().await;
+        let used: usize = guard
+            .iter()
+            .filter(|r| r.pinned)
+            .map(|r| r.token_estimate())
+            .sum();
+        check_pin_budget(used, record.token_estimate(), budget_tokens)?;
+        guard.push(record.clone());
+        Ok(record)
+    }
+
+    /// Pin or unpin an existing record. Returns false if the record is unknown.
+    pub async fn set_pinned(&self, id: Uuid, pinned: bool) -> bool {
+        let mut guard = self.records.write().await;
+        match guard.iter_mut().find(|r| r.id == id) {
+            Some(record) => {
+                record.pinned = pinned;
+                record.updated_at = Utc::now();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pinned records, oldest first
+    pub async fn pinned(&self) -> Vec<MemoryRecord> {
+        let mut pinned: Vec<_> = self
+            .records
+            .read()
+            .await
+            .iter()
+            .filter(|r| r.pinned)
+            .cloned()
+            .collect();
+        pinned.sort_by_key(|r| r.created_at);
+        pinned
+    }
+
+    /// Merge near-duplicate records into consolidated ones
+    pub async fn consolidate(&self, threshold: f32) -> Result<ConsolidationReport, MemoryError> {
+        let mut guard = self.records.write().await;
+        let mut records = std::mem::take(&mut *guard);
+        records.sort_by_key(|r| r.created_at);
+
+        let mut report = ConsolidationReport {
+            records_before: records.len(),
+            tokens_before: records.iter().map(|r| r.token_estimate()).sum(),
+            ..Default::default()
+        };
+
+        let clusters = cluster_by_similarity(&records, threshold);
+        let mut merged_away = vec![false; records.len()];
+        let mut consolidated = Vec::new();
+        for cluster in &clusters {
+            let members: Vec<&MemoryRecord> = cluster.iter().map(|&i| &records[i]).collect();
+            let mut record = MemoryRecord::new(
+                &merge_contents(members.iter().copied()),
+                &self.embedding_model,
+                self.embedder.as_ref(),
+            )?;
+            record.created_at = members[0].created_at;
+            record.sources = merged_sources(members.iter().copied());
+            report.merged.push(ConsolidatedRecord {
+                id: record.id,
+                sources: record.sources.clone(),
+                tokens: record.token_estimate(),
+            });
+            consolidated.push(record);
+            for &i in cluster {
+                merged_away[i] = true;
+            }
+        }
+
+        records = records
+            .into_iter()
+            .zip(merged_away)
+            .filter_map(|(record, merged)| (!merged).then_some(record))
+            .chain(consolidated)
+            .collect();
+        report.records_after = records.len();
+        report.tokens_after = records.iter().map(|r| r.token_estimate()).sum();
+        *guard = records;
+
+        Ok(report)
     }
 
-    /// Remove entries older than the provided timestamp
+    /// Remove unpinned entries older than the provided timestamp
     pub async fn prune_before(&self, cutoff: DateTime<Utc>) -> usize {
         let mut guard = self.records.write().await;
         let before = guard.len();
-        guard.retain(|r| r.created_at >= cutoff);
+        guard.retain(|r| r.pinned || r.created_at >= cutoff);
         before - guard.len()
     }
 
@@ -305,7 +404,18 @@ fn text_to_vec(text: &str, dims: usize) -> Vec<f32> {
     vec
 }
 
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+/// Reject a new pin that would push pinned records past the budget
+fn check_pin_budget(used: usize, adding: usize, budget: usize) -> Result<(), MemoryError> {
+    if used + adding > budget {
+        return Err(MemoryError::invalid(format!(
+            "pin budget exceeded: {} pinned tokens + {} would exceed the {} token cap",
+            used, adding, budget
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     if a.is_empty() || b.is_empty() || a.len() != b.len() {
         return 0.0;
     }
@@ -349,6 +459,62 @@ mod tests {
         assert!(results[0].index_summary.to_lowercase().contains("bug"));
     }
 
+    #[tokio::test]
+    async fn test_pinned_records_are_recalled_and_kept() {
+        let store = MemoryStore::default();
+        store.add("Unrelated chatter about lunch").await.unwrap();
+        let pinned = store
+            .pin(
+                "Deploy target is always Postgres",
+                DEFAULT_PIN_BUDGET_TOKENS,
+            )
+            .await
+            .unwrap();
+
+        let results = store
+            .recall(RecallQuery {
+                query: "lunch".into(),
+                relevance_threshold: 0.99,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(results[0].id, pinned.id);
+
+        let removed = store.prune_before(Utc::now()).await;
+        assert_eq!(removed, 1);
+        assert_eq!(store.pinned().await.len(), 1);
+
+        assert!(store.set_pinned(pinned.id, false).await);
+        assert!(store.pinned().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_consolidation_merges_duplicates() {
+        let store = MemoryStore::default();
+        store.add("[user] How do we deploy the API?").await.unwrap();
+        store.add("[user] How do we deploy the API?").await.unwrap();
+        store
+            .add("Completely different notes about the billing schema and invoices")
+            .await
+            .unwrap();
+
+        let report = store
+            .consolidate(DEFAULT_CONSOLIDATION_THRESHOLD)
+            .await
+            .unwrap();
+        assert_eq!(report.records_removed(), 1);
+        assert!(report.tokens_saved() > 0);
+        assert_eq!(report.merged[0].sources.len(), 2);
+        assert_eq!(store.all().await.len(), 2);
+    }
+
+    #[test]
+    fn test_pin_budget_is_enforced() {
+        assert!(check_pin_budget(10, 5, 15).is_ok());
+        assert!(check_pin_budget(10, 6, 15).is_err());
+    }
+
     #[tokio::test]
     async fn pruning_removes_old_entries() {
         let store = MemoryStore::default();
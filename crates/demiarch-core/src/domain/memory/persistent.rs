@@ -5,8 +5,9 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use super::{
-    Embeddings, MemoryError, MemoryRecord, MemoryStats, MemoryStore, RecallQuery, SimpleEmbedder,
-    TimelineEntry,
+    check_pin_budget, cluster_by_similarity, merge_contents, merged_sources, ConsolidatedRecord,
+    ConsolidationReport, Embeddings, MemoryError, MemoryRecord, MemoryStats, MemoryStore,
+    RecallQuery, SimpleEmbedder, TimelineEntry,
 };
 
 /// Persistent backing store for progressive disclosure context.
@@ -54,10 +55,101 @@ impl PersistentMemoryStore {
         content: &str,
     ) -> Result<MemoryRecord, MemoryError> {
         let record = MemoryRecord::new(content, &self.embedding_model, self.embedder.as_ref())?;
+        self.insert(
+            project_id,
+            conversation_id,
+            source,
+            source_reference,
+            &record,
+        )
+        .await?;
+        Ok(record)
+    }
+
+    /// Pin a decision or fact for the project so recall always includes it.
+    ///
+    /// Fails if the project's pinned records would exceed `budget_tokens`.
+    pub async fn pin(
+        &self,
+        project_id: &str,
+        content: &str,
+        budget_tokens: usize,
+    ) -> Result<MemoryRecord, MemoryError> {
+        let mut record = MemoryRecord::new(content, &self.embedding_model, self.embedder.as_ref())?;
+        record.pinned = true;
+
+        let used = self.pinned_tokens(project_id).await?;
+        check_pin_budget(used, record.token_estimate(), budget_tokens)?;
+
+        self.insert(project_id, None, "pin", None, &record).await?;
+        Ok(record)
+    }
+
+    /// Pin or unpin an existing entry. Returns false if no entry has that ID.
+    pub async fn set_pinned(&self, id: Uuid, pinned: bool) -> Result<bool, MemoryError> {
+        let result =
+            sqlx::query("UPDATE context_entries SET pinned = ?, updated_at = ? WHERE id = ?")
+                .bind(pinned)
+                .bind(Utc::now())
+                .bind(id.to_string())
+                .execute(&self.pool)
+                .await
+                .map_err(|e| MemoryError::Storage(format!("Failed to update pin: {e}")))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Pinned entries, oldest first.
+    pub async fn pinned(&self, project_id: Option<&str>) -> Result<Vec<MemoryRecord>, MemoryError> {
+        let rows: Vec<ContextEntryRow> = if let Some(pid) = project_id {
+            sqlx::query_as(
+                "SELECT * FROM context_entries WHERE pinned = 1 AND project_id = ? ORDER BY created_at",
+            )
+            .bind(pid)
+            .fetch_all(&self.pool)
+            .await
+        } else {
+            sqlx::query_as("SELECT * FROM context_entries WHERE pinned = 1 ORDER BY created_at")
+                .fetch_all(&self.pool)
+                .await
+        }
+        .map_err(|e| MemoryError::Storage(format!("Failed to load pinned context: {e}")))?;
+
+        rows.into_iter()
+            .map(|row| row.into_memory_record())
+            .collect()
+    }
+
+    /// Estimated tokens used by a project's pinned entries.
+    pub async fn pinned_tokens(&self, project_id: &str) -> Result<usize, MemoryError> {
+        let row: (i64,) = sqlx::query_as(
+            "SELECT COALESCE(SUM(tokens_estimated),0) FROM context_entries WHERE pinned = 1 AND project_id = ?",
+        )
+        .bind(project_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| MemoryError::Storage(format!("Failed to sum pinned tokens: {e}")))?;
+        Ok(row.0 as usize)
+    }
+
+    async fn insert(
+        &self,
+        project_id: &str,
+        conversation_id: Option<&str>,
+        source: &str,
+        source_reference: Option<&str>,
+        record: &MemoryRecord,
+    ) -> Result<(), MemoryError> {
         let highlight_json = serde_json::to_string(&record.timeline_entry.highlights)
             .map_err(|e| MemoryError::Storage(format!("Failed to serialize highlights: {e}")))?;
         let embedding_json = serde_json::to_string(&record.embeddings.index)
             .map_err(|e| MemoryError::Storage(format!("Failed to serialize embeddings: {e}")))?;
+        let sources_json = if record.sources.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&record.sources).map_err(|e| {
+                MemoryError::Storage(format!("Failed to serialize provenance: {e}"))
+            })?)
+        };
 
         sqlx::query(
             r#"
@@ -65,12 +157,12 @@ impl PersistentMemoryStore {
                 id, project_id, conversation_id, source, source_reference,
                 index_summary, timeline_summary, highlights, full_context,
                 embedding_model, embedding_json, tokens_estimated,
-                created_at, updated_at
+                pinned, consolidated_from, created_at, updated_at
             ) VALUES (
                 ?, ?, ?, ?, ?,
                 ?, ?, ?, ?,
                 ?, ?, ?,
-                ?, ?
+                ?, ?, ?, ?
             )
             ON CONFLICT(id) DO UPDATE SET
                 index_summary = excluded.index_summary,
@@ -95,16 +187,19 @@ impl PersistentMemoryStore {
         .bind(&record.embeddings.model)
         .bind(embedding_json)
         .bind(record.token_estimate() as i32)
+        .bind(record.pinned)
+        .bind(sources_json)
         .bind(record.created_at)
         .bind(record.updated_at)
         .execute(&self.pool)
         .await
         .map_err(|e| MemoryError::Storage(format!("Failed to insert context entry: {e}")))?;
 
-        Ok(record)
+        Ok(())
     }
 
-    /// Retrieve matching context ordered by similarity.
+    /// Retrieve matching context: pinned entries first, then the rest ordered
+    /// by similarity.
     pub async fn recall(
         &self,
         project_id: Option<&str>,
@@ -115,7 +210,7 @@ impl PersistentMemoryStore {
                 r#"
                 SELECT * FROM context_entries
                 WHERE project_id = ?
-                ORDER BY created_at DESC
+                ORDER BY pinned DESC, created_at DESC
                 LIMIT 500
                 "#,
             )
@@ -126,7 +221,7 @@ impl PersistentMemoryStore {
             sqlx::query_as(
                 r#"
                 SELECT * FROM context_entries
-                ORDER BY created_at DESC
+                ORDER BY pinned DESC, created_at DESC
                 LIMIT 500
                 "#,
             )
@@ -145,7 +240,7 @@ impl PersistentMemoryStore {
         store.recall(query).await
     }
 
-    /// Delete entries older than the cutoff. Returns number removed.
+    /// Delete unpinned entries older than the cutoff. Returns number removed.
     pub async fn prune(
         &self,
         project_id: Option<&str>,
@@ -154,14 +249,14 @@ impl PersistentMemoryStore {
     ) -> Result<usize, MemoryError> {
         let count: (i64,) = if let Some(pid) = project_id {
             sqlx::query_as(
-                "SELECT COUNT(*) FROM context_entries WHERE project_id = ? AND created_at < ?",
+                "SELECT COUNT(*) FROM context_entries WHERE project_id = ? AND created_at < ? AND pinned = 0",
             )
             .bind(pid)
             .bind(cutoff)
             .fetch_one(&self.pool)
             .await
         } else {
-            sqlx::query_as("SELECT COUNT(*) FROM context_entries WHERE created_at < ?")
+            sqlx::query_as("SELECT COUNT(*) FROM context_entries WHERE created_at < ? AND pinned = 0")
                 .bind(cutoff)
                 .fetch_one(&self.pool)
                 .await
@@ -173,13 +268,13 @@ impl PersistentMemoryStore {
         }
 
         let deleted = if let Some(pid) = project_id {
-            sqlx::query("DELETE FROM context_entries WHERE project_id = ? AND created_at < ?")
+            sqlx::query("DELETE FROM context_entries WHERE project_id = ? AND created_at < ? AND pinned = 0")
                 .bind(pid)
                 .bind(cutoff)
                 .execute(&self.pool)
                 .await
         } else {
-            sqlx::query("DELETE FROM context_entries WHERE created_at < ?")
+            sqlx::query("DELETE FROM context_entries WHERE created_at < ? AND pinned = 0")
                 .bind(cutoff)
                 .execute(&self.pool)
                 .await
@@ -189,6 +284,113 @@ impl PersistentMemoryStore {
         Ok(deleted.rows_affected() as usize)
     }
 
+    /// Merge near-duplicate entries within each project.
+    ///
+    /// Each cluster of entries whose embeddings are at least `threshold`
+    /// similar is replaced by one consolidated entry that records the merged
+    /// IDs as provenance. Pinned entries are left alone.
+    pub async fn consolidate(
+        &self,
+        project_id: Option<&str>,
+        threshold: f32,
+        dry_run: bool,
+    ) -> Result<ConsolidationReport, MemoryError> {
+        let rows: Vec<ContextEntryRow> = if let Some(pid) = project_id {
+            sqlx::query_as(
+                "SELECT * FROM context_entries WHERE project_id = ? ORDER BY created_at, id",
+            )
+            .bind(pid)
+            .fetch_all(&self.pool)
+            .await
+        } else {
+            sqlx::query_as("SELECT * FROM context_entries ORDER BY project_id, created_at, id")
+                .fetch_all(&self.pool)
+                .await
+        }
+        .map_err(|e| MemoryError::Storage(format!("Failed to load context rows: {e}")))?;
+
+        let mut by_project: Vec<(String, Vec<MemoryRecord>)> = Vec::new();
+        for row in rows {
+            let pid = row.project_id.clone();
+            let Ok(record) = row.into_memory_record() else {
+                continue;
+            };
+            match by_project.last_mut() {
+                Some((last, records)) if *last == pid => records.push(record),
+                _ => by_project.push((pid, vec![record])),
+            }
+        }
+
+        let mut report = ConsolidationReport {
+            dry_run,
+            ..Default::default()
+        };
+        for (pid, records) in by_project {
+            report.absorb(
+                self.consolidate_project(&pid, records, threshold, dry_run)
+                    .await?,
+            );
+        }
+        Ok(report)
+    }
+
+    async fn consolidate_project(
+        &self,
+        project_id: &str,
+        records: Vec<MemoryRecord>,
+        threshold: f32,
+        dry_run: bool,
+    ) -> Result<ConsolidationReport, MemoryError> {
+        let tokens_before: usize = records.iter().map(|r| r.token_estimate()).sum();
+        let mut report = ConsolidationReport {
+            records_before: records.len(),
+            records_after: records.len(),
+            tokens_before,
+            tokens_after: tokens_before,
+            ..Default::default()
+        };
+
+        for cluster in cluster_by_similarity(&records, threshold) {
+            let members: Vec<&MemoryRecord> = cluster.iter().map(|&i| &records[i]).collect();
+            let mut merged = MemoryRecord::new(
+                &merge_contents(members.iter().copied()),
+                &self.embedding_model,
+                self.embedder.as_ref(),
+            )?;
+            merged.created_at = members[0].created_at;
+            merged.sources = merged_sources(members.iter().copied());
+
+            let member_tokens: usize = members.iter().map(|r| r.token_estimate()).sum();
+            report.records_after = report.records_after + 1 - members.len();
+            report.tokens_after = report.tokens_after + merged.token_estimate() - member_tokens;
+            report.merged.push(ConsolidatedRecord {
+                id: merged.id,
+                sources: merged.sources.clone(),
+                tokens: merged.token_estimate(),
+            });
+
+            if dry_run {
+                continue;
+            }
+
+            // Insert before deleting so an interrupted pass only leaves
+            // duplicates behind, never a gap
+            self.insert(project_id, None, "consolidated", None, &merged)
+                .await?;
+            for member in &members {
+                sqlx::query("DELETE FROM context_entries WHERE id = ?")
+                    .bind(member.id.to_string())
+                    .execute(&self.pool)
+                    .await
+                    .map_err(|e| {
+                        MemoryError::Storage(format!("Failed to remove merged entry: {e}"))
+                    })?;
+            }
+        }
+
+        Ok(report)
+    }
+
     /// Recompute summaries/embeddings from the stored full_context.
     pub async fn rebuild(&self, project_id: Option<&str>) -> Result<usize, MemoryError> {
         let rows: Vec<ContextEntryRow> = if let Some(pid) = project_id {
@@ -315,6 +517,8 @@ struct ContextEntryRow {
     embedding_model: String,
     embedding_json: String,
     tokens_estimated: i32,
+    pinned: bool,
+    consolidated_from: Option<String>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
@@ -343,6 +547,12 @@ impl ContextEntryRow {
             ))
         })?;
 
+        let sources: Vec<Uuid> = self
+            .consolidated_from
+            .as_deref()
+            .map(|s| serde_json::from_str(s).unwrap_or_default())
+            .unwrap_or_default();
+
         let embeddings = Embeddings::new(
             &self.embedding_model,
             embedding_vec.clone(),
@@ -358,6 +568,8 @@ impl ContextEntryRow {
             timeline_entry,
             full_context: self.full_context,
             embeddings,
+            pinned: self.pinned,
+            sources,
         })
     }
 }
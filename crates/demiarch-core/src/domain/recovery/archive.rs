@@ -0,0 +1,403 @@
+//! Checkpoint archive files
+//!
+//! A checkpoint can be exported to a zstd-compressed tar file to move it
+//! between machines or attach it to a bug report. The archive holds:
+//!
+//! - `manifest.json`: the checkpoint's metadata, the public key that signed
+//!   it and the list of file blobs
+//! - `snapshot.json`: the project state, with generated file contents
+//!   moved out to blobs
+//! - `signature`: the Ed25519 signature over the full snapshot
+//! - `files/<sha256>`: one blob per distinct generated file
+//!
+//! Signing keys are per session, so export signs the snapshot afresh and
+//! ships the public key. Import checks every blob against its hash and the
+//! reassembled snapshot against the signature before anything is stored.
+//! The signature only shows the archive is intact, not who made it, so file
+//! paths are also checked to stay inside the project directory.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Component, Path};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::checkpoint::Checkpoint;
+use super::manager::compute_content_hash;
+use super::signing::{CheckpointSigner, CheckpointVerifier};
+use crate::error::Error;
+
+/// Version of the archive layout written by [`write_archive`]
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const SNAPSHOT_ENTRY: &str = "snapshot.json";
+const SIGNATURE_ENTRY: &str = "signature";
+const FILES_DIR: &str = "files/";
+
+/// zstd level used for archives
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Errors reading or writing a checkpoint archive
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    #[error("Checkpoint archive I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Checkpoint archive is missing {0}")]
+    MissingEntry(String),
+
+    #[error("Checkpoint archive is malformed: {0}")]
+    Malformed(String),
+
+    #[error("Checkpoint archive format {0} is not supported (expected {ARCHIVE_FORMAT_VERSION})")]
+    UnsupportedVersion(u32),
+
+    #[error("File blob for {0} does not match its hash")]
+    BlobMismatch(String),
+
+    #[error("Checkpoint archive signature verification failed")]
+    SignatureVerificationFailed,
+
+    #[error("Checkpoint archive file path {0} leaves the project directory")]
+    UnsafePath(String),
+}
+
+impl From<ArchiveError> for Error {
+    fn from(err: ArchiveError) -> Self {
+        Error::Other(err.to_string())
+    }
+}
+
+/// Metadata stored in `manifest.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    /// ID of the checkpoint on the exporting machine
+    pub checkpoint_id: Uuid,
+    /// Project the checkpoint was taken of on the exporting machine
+    pub project_id: Uuid,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub exported_at: DateTime<Utc>,
+    /// Hex-encoded Ed25519 key that signed the snapshot
+    pub public_key: String,
+    /// Generated files, whose contents are stored as blobs
+    pub files: Vec<ArchivedFile>,
+}
+
+/// A generated file stored as a blob
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedFile {
+    pub path: String,
+    pub content_hash: String,
+    pub size_bytes: u64,
+}
+
+/// A verified archive, read back by [`read_archive`]
+#[derive(Debug, Clone)]
+pub struct CheckpointArchive {
+    pub manifest: ArchiveManifest,
+    /// Snapshot with file contents restored, as originally signed
+    pub snapshot_data: serde_json::Value,
+}
+
+/// Write `checkpoint` to `path`, signing its snapshot with `signer`
+pub fn write_archive(
+    checkpoint: &Checkpoint,
+    signer: &CheckpointSigner,
+    path: &Path,
+) -> Result<ArchiveManifest, ArchiveError> {
+    let signed_bytes = serde_json::to_vec(&checkpoint.snapshot_data)
+        .map_err(|e| ArchiveError::Malformed(e.to_string()))?;
+    let signature = signer.sign(&signed_bytes);
+
+    // Move file contents out of the snapshot into blobs
+    let mut snapshot = checkpoint.snapshot_data.clone();
+    let mut files = Vec::new();
+    let mut blobs: HashMap<String, String> = HashMap::new();
+    for file in generated_code_mut(&mut snapshot) {
+        let content = match file.get_mut("content") {
+            Some(content) => content.take(),
+            None => continue,
+        };
+        let content = content.as_str().unwrap_or_default().to_string();
+        let content_hash = compute_content_hash(&content);
+        files.push(ArchivedFile {
+            path: file
+                .get("path")
+                .and_then(|p| p.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            content_hash: content_hash.clone(),
+            size_bytes: content.len() as u64,
+        });
+        blobs.entry(content_hash).or_insert(content);
+    }
+
+    let manifest = ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        checkpoint_id: checkpoint.id,
+        project_id: checkpoint.project_id,
+        description: checkpoint.description.clone(),
+        created_at: checkpoint.created_at,
+        exported_at: Utc::now(),
+        public_key: hex::encode(signer.verifying_key_bytes()),
+        files,
+    };
+
+    let encoder = zstd::Encoder::new(File::create(path)?, COMPRESSION_LEVEL)?;
+    let mut builder = tar::Builder::new(encoder);
+    append(
+        &mut builder,
+        MANIFEST_ENTRY,
+        &serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| ArchiveError::Malformed(e.to_string()))?,
+    )?;
+    append(
+        &mut builder,
+        SNAPSHOT_ENTRY,
+        &serde_json::to_vec(&snapshot).map_err(|e| ArchiveError::Malformed(e.to_string()))?,
+    )?;
+    append(&mut builder, SIGNATURE_ENTRY, &signature)?;
+    for (hash, content) in &blobs {
+        append(
+            &mut builder,
+            &format!("{}{}", FILES_DIR, hash),
+            content.as_bytes(),
+        )?;
+    }
+    builder.into_inner()?.finish()?.flush()?;
+
+    Ok(manifest)
+}
+
+/// Whether `path` stays inside the directory it is joined to: relative, with
+/// no `..`, root or drive prefix components
+pub fn is_contained_path(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Read and verify the archive at `path`
+pub fn read_archive(path: &Path) -> Result<CheckpointArchive, ArchiveError> {
+    let mut entries = read_entries(path)?;
+    let mut take = |name: &str| {
+        entries
+            .remove(name)
+            .ok_or_else(|| ArchiveError::MissingEntry(name.to_string()))
+    };
+
+    let manifest: ArchiveManifest = serde_json::from_slice(&take(MANIFEST_ENTRY)?)
+        .map_err(|e| ArchiveError::Malformed(format!("{}: {}", MANIFEST_ENTRY, e)))?;
+    if manifest.format_version != ARCHIVE_FORMAT_VERSION {
+        return Err(ArchiveError::UnsupportedVersion(manifest.format_version));
+    }
+    let mut snapshot: serde_json::Value = serde_json::from_slice(&take(SNAPSHOT_ENTRY)?)
+        .map_err(|e| ArchiveError::Malformed(format!("{}: {}", SNAPSHOT_ENTRY, e)))?;
+    let signature = take(SIGNATURE_ENTRY)?;
+    if let Some(file) = manifest.files.iter().find(|f| !is_contained_path(&f.path)) {
+        return Err(ArchiveError::UnsafePath(file.path.clone()));
+    }
+
+    // Put each file's content back from its blob
+    let hashes: HashMap<&str, &str> = manifest
+        .files
+        .iter()
+        .map(|f| (f.path.as_str(), f.content_hash.as_str()))
+        .collect();
+    for file in generated_code_mut(&mut snapshot) {
+        let path = file
+            .get("path")
+            .and_then(|p| p.as_str())
+            .unwrap_or_default()
+            .to_string();
+        if !is_contained_path(&path) {
+            return Err(ArchiveError::UnsafePath(path));
+        }
+        let hash = hashes
+            .get(path.as_str())
+            .ok_or_else(|| ArchiveError::MissingEntry(format!("blob for {}", path)))?;
+        let blob = entries
+            .get(&format!("{}{}", FILES_DIR, hash))
+            .ok_or_else(|| ArchiveError::MissingEntry(format!("blob for {}", path)))?;
+        let content = String::from_utf8(blob.clone())
+            .map_err(|_| ArchiveError::BlobMismatch(path.clone()))?;
+        if compute_content_hash(&content) != *hash {
+            return Err(ArchiveError::BlobMismatch(path));
+        }
+        file.insert("content".to_string(), serde_json::Value::String(content));
+    }
+
+    let public_key = hex::decode(&manifest.public_key)
+        .map_err(|e| ArchiveError::Malformed(format!("public key: {}", e)))?;
+    let signed_bytes =
+        serde_json::to_vec(&snapshot).map_err(|e| ArchiveError::Malformed(e.to_string()))?;
+    CheckpointVerifier::from_bytes(&public_key)
+        .and_then(|verifier| verifier.verify(&signed_bytes, &signature))
+        .map_err(|_| ArchiveError::SignatureVerificationFailed)?;
+
+    Ok(CheckpointArchive {
+        manifest,
+        snapshot_data: snapshot,
+    })
+}
+
+/// Every entry in the archive, by name
+fn read_entries(path: &Path) -> Result<HashMap<String, Vec<u8>>, ArchiveError> {
+    let decoder = zstd::Decoder::new(File::open(path)?)?;
+    let mut archive = tar::Archive::new(decoder);
+    let mut entries = HashMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        entries.insert(name, data);
+    }
+    Ok(entries)
+}
+
+/// The snapshot's generated file objects
+fn generated_code_mut(
+    snapshot: &mut serde_json::Value,
+) -> impl Iterator<Item = &mut serde_json::Map<String, serde_json::Value>> {
+    snapshot
+        .get_mut("generated_code")
+        .and_then(|files| files.as_array_mut())
+        .into_iter()
+        .flatten()
+        .filter_map(|file| file.as_object_mut())
+}
+
+fn append<W: Write>(builder: &mut tar::Builder<W>, name: &str, data: &[u8]) -> std::io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, name, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::recovery::checkpoint::{GeneratedCodeSnapshot, SnapshotData};
+
+    fn checkpoint(signer: &CheckpointSigner) -> Checkpoint {
+        let mut snapshot = SnapshotData::empty();
+        for (path, content) in [("src/a.rs", "fn a() {}"), ("src/b.rs", "fn a() {}")] {
+            snapshot.generated_code.push(GeneratedCodeSnapshot {
+                path: path.to_string(),
+                content: content.to_string(),
+                content_hash: compute_content_hash(content),
+            });
+        }
+        let snapshot = serde_json::to_value(&snapshot).unwrap();
+        let signature = signer.sign(&serde_json::to_vec(&snapshot).unwrap());
+        Checkpoint::new(
+            Uuid::new_v4(),
+            None,
+            "Before bug".to_string(),
+            snapshot,
+            signature,
+        )
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let signer = CheckpointSigner::generate();
+        let original = checkpoint(&signer);
+        let path = std::env::temp_dir().join(format!("cp-{}.tar.zst", Uuid::new_v4()));
+
+        let manifest = write_archive(&original, &CheckpointSigner::generate(), &path).unwrap();
+        assert_eq!(manifest.files.len(), 2);
+        assert_eq!(read_entries(&path).unwrap().len(), 4); // identical files share a blob
+
+        let archive = read_archive(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(archive.manifest, manifest);
+        assert_eq!(archive.snapshot_data, original.snapshot_data);
+    }
+
+    #[test]
+    fn test_tampered_archive_is_rejected() {
+        let signer = CheckpointSigner::generate();
+        let original = checkpoint(&signer);
+        let path = std::env::temp_dir().join(format!("cp-{}.tar.zst", Uuid::new_v4()));
+        write_archive(&original, &signer, &path).unwrap();
+
+        // Rewrite the archive with an edited snapshot but the old signature
+        let mut entries = read_entries(&path).unwrap();
+        let mut snapshot: serde_json::Value =
+            serde_json::from_slice(&entries[SNAPSHOT_ENTRY]).unwrap();
+        snapshot["generated_code"][0]["path"] = "src/evil.rs".into();
+        entries.insert(
+            SNAPSHOT_ENTRY.to_string(),
+            serde_json::to_vec(&snapshot).unwrap(),
+        );
+        let mut manifest: ArchiveManifest =
+            serde_json::from_slice(&entries[MANIFEST_ENTRY]).unwrap();
+        manifest.files[0].path = "src/evil.rs".to_string();
+        entries.insert(
+            MANIFEST_ENTRY.to_string(),
+            serde_json::to_vec(&manifest).unwrap(),
+        );
+        let mut builder = tar::Builder::new(
+            zstd::Encoder::new(File::create(&path).unwrap(), COMPRESSION_LEVEL).unwrap(),
+        );
+        for (name, data) in &entries {
+            append(&mut builder, name, data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let result = read_archive(&path);
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(
+            result,
+            Err(ArchiveError::SignatureVerificationFailed)
+        ));
+    }
+
+    #[test]
+    fn test_archive_with_escaping_paths_is_rejected() {
+        for evil in ["../../x", "/home/u/.bashrc", "src/../../x"] {
+            // Signed by its own key, as anyone re-signing a tampered archive would
+            let signer = CheckpointSigner::generate();
+            let mut snapshot = SnapshotData::empty();
+            snapshot.generated_code.push(GeneratedCodeSnapshot {
+                path: evil.to_string(),
+                content: "echo pwned".to_string(),
+                content_hash: compute_content_hash("echo pwned"),
+            });
+            let snapshot = serde_json::to_value(&snapshot).unwrap();
+            let signature = signer.sign(&serde_json::to_vec(&snapshot).unwrap());
+            let original = Checkpoint::new(
+                Uuid::new_v4(),
+                None,
+                "Evil".to_string(),
+                snapshot,
+                signature,
+            );
+            let path = std::env::temp_dir().join(format!("cp-{}.tar.zst", Uuid::new_v4()));
+            write_archive(&original, &signer, &path).unwrap();
+
+            let result = read_archive(&path);
+            let _ = std::fs::remove_file(&path);
+            assert!(
+                matches!(result, Err(ArchiveError::UnsafePath(ref p)) if p == evil),
+                "{} was accepted",
+                evil
+            );
+        }
+
+        assert!(is_contained_path("src/a.rs"));
+        assert!(is_contained_path("./src/a.rs"));
+        assert!(!is_contained_path(""));
+    }
+}
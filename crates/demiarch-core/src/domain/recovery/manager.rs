@@ -11,6 +11,7 @@ use super::repository::CheckpointRepository;
 use super::restore::{self, RestoreResult};
 use super::signing::{CheckpointSigner, CheckpointVerifier, SigningError};
 use crate::error::Result;
+use crate::progress::Progress;
 use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
 use tracing::{debug, info};
@@ -30,6 +31,9 @@ pub struct CheckpointConfig {
 
     /// Maximum number of checkpoints per project
     pub max_per_project: usize,
+
+    /// Whether a restore first checkpoints the state it replaces
+    pub safety_backup: bool,
 }
 
 impl Default for CheckpointConfig {
@@ -37,6 +41,7 @@ impl Default for CheckpointConfig {
         Self {
             retention_days: DEFAULT_RETENTION_DAYS,
             max_per_project: DEFAULT_MAX_PER_PROJECT,
+            safety_backup: true,
         }
     }
 }
@@ -78,6 +83,11 @@ impl CheckpointManager {
         &self.repository
     }
 
+    /// Get the manager's configuration
+    pub fn config(&self) -> &CheckpointConfig {
+        &self.config
+    }
+
     /// Create a checkpoint before code generation
     ///
     /// This captures the current project state including phases, features,
@@ -304,6 +314,21 @@ impl CheckpointManager {
         restore::restore_checkpoint(self.repository.pool(), self, checkpoint_id).await
     }
 
+    /// Restore a checkpoint, reporting each step to a progress handle
+    pub async fn restore_checkpoint_with_progress(
+        &self,
+        checkpoint_id: Uuid,
+        progress: &Progress,
+    ) -> Result<RestoreResult> {
+        restore::restore_checkpoint_with_progress(
+            self.repository.pool(),
+            self,
+            checkpoint_id,
+            progress,
+        )
+        .await
+    }
+
     /// Get statistics about checkpoints for a project
     pub async fn get_stats(&self, project_id: Uuid) -> Result<CheckpointStats> {
         let checkpoints = self.repository.list_by_project(project_id).await?;
@@ -46,6 +46,7 @@
 //! }
 //! ```
 
+pub mod archive;
 pub mod checkpoint;
 pub mod edit_detection;
 pub mod event;
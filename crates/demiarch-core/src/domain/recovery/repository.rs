@@ -8,6 +8,7 @@ use super::repository_trait::{
     PhaseRow as TraitPhaseRow,
 };
 use crate::error::{Error, Result};
+use crate::storage::content::{self, ContentStore};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
@@ -43,23 +44,28 @@ impl CheckpointRepository {
     }
 
     /// Save a checkpoint to the database
+    ///
+    /// The snapshot goes to the content store, so checkpoints of a project
+    /// that has not changed share one compressed copy.
     pub async fn save(&self, checkpoint: &Checkpoint) -> Result<()> {
         let id = checkpoint.id.to_string();
         let project_id = checkpoint.project_id.to_string();
         let feature_id = checkpoint.feature_id.map(|f| f.to_string());
-        let snapshot_data = checkpoint.snapshot_data.to_string();
+        let snapshot_hash = ContentStore::new(&self.pool)
+            .put(&checkpoint.snapshot_data.to_string())
+            .await?;
 
         sqlx::query(
             r#"
-            INSERT INTO checkpoints (id, project_id, feature_id, description, snapshot_data, size_bytes, signature, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO checkpoints (id, project_id, feature_id, description, snapshot_data, snapshot_hash, size_bytes, signature, created_at)
+            VALUES (?, ?, ?, ?, '', ?, ?, ?, ?)
             "#,
         )
         .bind(&id)
         .bind(&project_id)
         .bind(&feature_id)
         .bind(&checkpoint.description)
-        .bind(&snapshot_data)
+        .bind(&snapshot_hash)
         .bind(checkpoint.size_bytes)
         .bind(&checkpoint.signature)
         .bind(checkpoint.created_at)
@@ -76,9 +82,11 @@ impl CheckpointRepository {
 
         let row: Option<CheckpointRow> = sqlx::query_as(
             r#"
-            SELECT id, project_id, feature_id, description, snapshot_data, size_bytes, signature, created_at
-            FROM checkpoints
-            WHERE id = ?
+            SELECT c.id, c.project_id, c.feature_id, c.description, c.snapshot_data,
+                   b.data AS snapshot_blob, c.size_bytes, c.signature, c.created_at
+            FROM checkpoints c
+            LEFT JOIN content_blobs b ON b.hash = c.snapshot_hash
+            WHERE c.id = ?
             "#,
         )
         .bind(&id)
@@ -305,6 +313,7 @@ struct CheckpointRow {
     feature_id: Option<String>,
     description: String,
     snapshot_data: String,
+    snapshot_blob: Option<Vec<u8>>,
     size_bytes: i64,
     signature: Vec<u8>,
     created_at: DateTime<Utc>,
@@ -321,7 +330,8 @@ impl CheckpointRow {
             .map(|f| Uuid::parse_str(&f))
             .transpose()
             .map_err(|e| Error::Parse(format!("Invalid feature ID: {}", e)))?;
-        let snapshot_data: serde_json::Value = serde_json::from_str(&self.snapshot_data)
+        let snapshot = content::decode(self.snapshot_data, self.snapshot_blob)?;
+        let snapshot_data: serde_json::Value = serde_json::from_str(&snapshot)
             .map_err(|e| Error::Parse(format!("Invalid snapshot JSON: {}", e)))?;
 
         Ok(Checkpoint {
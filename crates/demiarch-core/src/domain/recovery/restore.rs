@@ -3,11 +3,14 @@
 //! Handles restoring project state from a checkpoint, including database
 //! rollback and file restoration.
 
+use super::archive::is_contained_path;
 use super::checkpoint::SnapshotData;
 use super::manager::CheckpointManager;
 use crate::error::{Error, Result};
+use crate::progress::{Progress, Stage};
 use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
@@ -23,8 +26,8 @@ pub struct RestoreResult {
     /// Description of the restored checkpoint
     pub checkpoint_description: String,
 
-    /// Safety backup checkpoint created before restore
-    pub safety_backup_id: Uuid,
+    /// Safety backup checkpoint created before restore, unless disabled
+    pub safety_backup_id: Option<Uuid>,
 
     /// Number of phases restored
     pub phases_restored: usize,
@@ -42,14 +45,17 @@ pub struct RestoreResult {
 impl RestoreResult {
     /// Get a user-friendly summary message
     pub fn summary(&self) -> String {
-        format!(
-            "Project restored to state from {}. Restored {} phases, {} features, {} messages. Safety backup: {}",
+        let summary = format!(
+            "Project restored to state from {}. Restored {} phases, {} features, {} messages.",
             self.checkpoint_timestamp.format("%Y-%m-%d %H:%M:%S"),
             self.phases_restored,
             self.features_restored,
             self.messages_restored,
-            &self.safety_backup_id.to_string()[..8]
-        )
+        );
+        match self.safety_backup_id {
+            Some(id) => format!("{} Safety backup: {}", summary, &id.to_string()[..8]),
+            None => summary,
+        }
     }
 }
 
@@ -88,7 +94,7 @@ impl From<RestoreError> for Error {
 ///
 /// This function:
 /// 1. Verifies the checkpoint signature
-/// 2. Creates a safety backup before restore
+/// 2. Creates a safety backup before restore, unless the manager disables it
 /// 3. Restores database state within a transaction
 /// 4. Restores any tracked files
 ///
@@ -97,6 +103,16 @@ pub async fn restore_checkpoint(
     pool: &SqlitePool,
     manager: &CheckpointManager,
     checkpoint_id: Uuid,
+) -> Result<RestoreResult> {
+    restore_checkpoint_with_progress(pool, manager, checkpoint_id, &Progress::hidden()).await
+}
+
+/// Restore project state from a checkpoint, reporting each step to `progress`
+pub async fn restore_checkpoint_with_progress(
+    pool: &SqlitePool,
+    manager: &CheckpointManager,
+    checkpoint_id: Uuid,
+    progress: &Progress,
 ) -> Result<RestoreResult> {
     use std::time::Instant;
 
@@ -111,6 +127,7 @@ pub async fn restore_checkpoint(
 
     // 2. Verify signature
     debug!("Verifying checkpoint signature");
+    progress.stage(Stage::Restore, "Verifying checkpoint signature");
     manager
         .verify_checkpoint(&checkpoint)
         .map_err(|_| RestoreError::SignatureVerificationFailed)?;
@@ -121,33 +138,52 @@ pub async fn restore_checkpoint(
         .map_err(|e| RestoreError::DeserializationFailed(e.to_string()))?;
 
     // 4. Create safety backup before restore
-    info!("Creating safety backup before restore");
-    let safety_backup = manager
-        .create_checkpoint(
-            checkpoint.project_id,
-            checkpoint.feature_id,
-            format!(
-                "Auto-backup before restore to checkpoint {}",
-                &checkpoint_id.to_string()[..8]
-            ),
-        )
-        .await
-        .map_err(|e| RestoreError::SafetyBackupFailed(e.to_string()))?;
-    info!(safety_backup_id = %safety_backup.id, "Safety backup created");
+    let safety_backup_id = if manager.config().safety_backup {
+        info!("Creating safety backup before restore");
+        progress.stage(Stage::Restore, "Creating safety backup");
+        let safety_backup = manager
+            .create_checkpoint(
+                checkpoint.project_id,
+                checkpoint.feature_id,
+                format!(
+                    "Auto-backup before restore to checkpoint {}",
+                    &checkpoint_id.to_string()[..8]
+                ),
+            )
+            .await
+            .map_err(|e| RestoreError::SafetyBackupFailed(e.to_string()))?;
+        info!(safety_backup_id = %safety_backup.id, "Safety backup created");
+        Some(safety_backup.id)
+    } else {
+        None
+    };
 
     // 5. Restore database state within a transaction
+    progress.stage(Stage::Restore, "Restoring project data");
     let (phases_restored, features_restored, messages_restored) =
         restore_database_state(pool, checkpoint.project_id, &snapshot).await?;
 
-    // 6. Restore files (if any were tracked)
-    let files_restored = restore_files(&snapshot).await?;
+    // 6. Restore files (if any were tracked) into the project directory
+    let project_dir: Option<String> = sqlx::query_scalar("SELECT path FROM projects WHERE id = ?")
+        .bind(checkpoint.project_id.to_string())
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| RestoreError::FileRestoreFailed(e.to_string()))?
+        .flatten();
+    let project_dir = match project_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            std::env::current_dir().map_err(|e| RestoreError::FileRestoreFailed(e.to_string()))?
+        }
+    };
+    let files_restored = restore_files(&snapshot, &project_dir, progress).await?;
 
     let elapsed = start.elapsed();
     let result = RestoreResult {
         checkpoint_id,
         checkpoint_timestamp: checkpoint.created_at,
         checkpoint_description: checkpoint.description.clone(),
-        safety_backup_id: safety_backup.id,
+        safety_backup_id,
         phases_restored,
         features_restored,
         messages_restored,
@@ -319,23 +355,48 @@ async fn restore_database_state(
     ))
 }
 
-/// Restore files from snapshot
+/// Restore the snapshot's generated files under `project_dir`
 ///
-/// Currently, generated code files are not tracked in snapshots.
-/// This is a placeholder for future file restoration functionality.
-async fn restore_files(snapshot: &SnapshotData) -> Result<usize> {
+/// Snapshots can come from imported archives, so a path that would land
+/// outside the project directory, directly or through a symlink, is refused.
+async fn restore_files(
+    snapshot: &SnapshotData,
+    project_dir: &Path,
+    progress: &Progress,
+) -> Result<usize> {
     if snapshot.generated_code.is_empty() {
         debug!("No generated code files to restore");
         return Ok(0);
     }
 
+    std::fs::create_dir_all(project_dir).map_err(|e| {
+        RestoreError::FileRestoreFailed(format!(
+            "Failed to create directory {}: {}",
+            project_dir.display(),
+            e
+        ))
+    })?;
+    let root = project_dir.canonicalize().map_err(|e| {
+        RestoreError::FileRestoreFailed(format!(
+            "Failed to resolve {}: {}",
+            project_dir.display(),
+            e
+        ))
+    })?;
+    let paths = snapshot
+        .generated_code
+        .iter()
+        .map(|code_file| resolve_within(&root, &code_file.path))
+        .collect::<Result<Vec<_>>>()?;
+
+    let total = snapshot.generated_code.len() as u64;
+    progress.stage(Stage::Write, format!("Restoring {} file(s)", total));
     let mut restored_count = 0;
 
-    for code_file in &snapshot.generated_code {
+    for (code_file, path) in snapshot.generated_code.iter().zip(&paths) {
         debug!(path = %code_file.path, "Restoring generated code file");
 
         // Ensure parent directory exists
-        let path = std::path::Path::new(&code_file.path);
         if let Some(parent) = path.parent() {
             if !parent.exists() {
                 std::fs::create_dir_all(parent).map_err(|e| {
@@ -357,6 +418,7 @@ async fn restore_files(snapshot: &SnapshotData) -> Result<usize> {
         })?;
 
         restored_count += 1;
+        progress.advance(restored_count as u64, total);
     }
 
     if restored_count > 0 {
@@ -366,6 +428,33 @@ async fn restore_files(snapshot: &SnapshotData) -> Result<usize> {
     Ok(restored_count)
 }
 
+/// `relative` joined onto `root`, or an error if it would land outside `root`
+fn resolve_within(root: &Path, relative: &str) -> Result<PathBuf> {
+    let refuse = || {
+        Error::from(RestoreError::FileRestoreFailed(format!(
+            "Refusing to restore {} outside the project directory",
+            relative
+        )))
+    };
+    if !is_contained_path(relative) {
+        return Err(refuse());
+    }
+    let path = root.join(relative);
+
+    // A symlinked directory or file inside the project could point elsewhere
+    let existing = path.ancestors().skip(1).find(|dir| dir.exists());
+    let inside = existing
+        .and_then(|dir| dir.canonicalize().ok())
+        .is_some_and(|dir| dir.starts_with(root));
+    let is_link = path
+        .symlink_metadata()
+        .is_ok_and(|m| m.file_type().is_symlink());
+    if !inside || is_link {
+        return Err(refuse());
+    }
+    Ok(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -413,7 +502,7 @@ mod tests {
             checkpoint_id: Uuid::new_v4(),
             checkpoint_timestamp: Utc::now(),
             checkpoint_description: "Test checkpoint".to_string(),
-            safety_backup_id: Uuid::new_v4(),
+            safety_backup_id: Some(Uuid::new_v4()),
             phases_restored: 3,
             features_restored: 5,
             messages_restored: 10,
@@ -492,4 +581,88 @@ mod tests {
             .expect("Failed to list checkpoints");
         assert!(checkpoints.len() >= 2); // Original + safety backup
     }
+
+    #[tokio::test]
+    async fn test_restore_without_safety_backup() {
+        let pool = create_test_db().await;
+        let project_id = create_test_project(&pool).await;
+        create_test_phase(&pool, project_id, "Initial Phase").await;
+
+        let manager = CheckpointManager::with_config(
+            pool.clone(),
+            CheckpointSigner::generate(),
+            crate::domain::recovery::CheckpointConfig {
+                safety_backup: false,
+                ..Default::default()
+            },
+        );
+        let checkpoint = manager
+            .create_checkpoint(project_id, None, "Test checkpoint".to_string())
+            .await
+            .expect("Failed to create checkpoint");
+
+        let result = restore_checkpoint(&pool, &manager, checkpoint.id)
+            .await
+            .expect("Failed to restore checkpoint");
+        assert_eq!(result.safety_backup_id, None);
+        assert!(!result.summary().contains("Safety backup"));
+
+        let checkpoints = manager
+            .list_checkpoints(project_id)
+            .await
+            .expect("Failed to list checkpoints");
+        assert_eq!(checkpoints.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_restore_files_stays_in_project_dir() {
+        use crate::domain::recovery::checkpoint::GeneratedCodeSnapshot;
+
+        let dir = tempfile::tempdir().unwrap();
+        let project_dir = dir.path().join("project");
+        let snapshot_of = |path: &str| {
+            let mut snapshot = SnapshotData::empty();
+            snapshot.generated_code.push(GeneratedCodeSnapshot {
+                path: path.to_string(),
+                content: "pwned".to_string(),
+                content_hash: String::new(),
+            });
+            snapshot
+        };
+
+        let restored = restore_files(&snapshot_of("src/a.rs"), &project_dir, &Progress::hidden())
+            .await
+            .unwrap();
+        assert_eq!(restored, 1);
+        assert!(project_dir.join("src/a.rs").exists());
+
+        for evil in ["../escaped.txt", "src/../../escaped.txt"] {
+            assert!(
+                restore_files(&snapshot_of(evil), &project_dir, &Progress::hidden())
+                    .await
+                    .is_err()
+            );
+        }
+        let absolute = dir.path().join("escaped.txt");
+        assert!(restore_files(
+            &snapshot_of(&absolute.to_string_lossy()),
+            &project_dir,
+            &Progress::hidden()
+        )
+        .await
+        .is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path(), project_dir.join("link")).unwrap();
+            assert!(restore_files(
+                &snapshot_of("link/escaped.txt"),
+                &project_dir,
+                &Progress::hidden()
+            )
+            .await
+            .is_err());
+        }
+        assert!(!dir.path().join("escaped.txt").exists());
+    }
 }
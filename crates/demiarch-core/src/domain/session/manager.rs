@@ -4,11 +4,14 @@
 //! creation, lifecycle transitions, and context switching.
 
 use super::event::SessionEvent;
+use super::notes::SessionNoteRepository;
 use super::repository::SessionRepository;
 use super::session::{
     RecoveryInfo, RecoveryResult, Session, SessionInfo, SessionPhase, SessionStatus,
 };
+use super::time::{self, FeatureTimeRepository};
 use crate::error::{Error, Result};
+use chrono::{Duration, Utc};
 use sqlx::SqlitePool;
 use tracing::{info, warn};
 use uuid::Uuid;
@@ -17,21 +20,68 @@ use uuid::Uuid;
 #[derive(Debug, Clone)]
 pub struct SessionManager {
     repository: SessionRepository,
+    feature_time: FeatureTimeRepository,
+    notes: SessionNoteRepository,
+    idle_threshold: Duration,
 }
 
 impl SessionManager {
     /// Create a new session manager
     pub fn new(pool: SqlitePool) -> Self {
         Self {
+            feature_time: FeatureTimeRepository::new(pool.clone()),
+            notes: SessionNoteRepository::new(pool.clone()),
             repository: SessionRepository::new(pool),
+            idle_threshold: time::DEFAULT_IDLE_THRESHOLD,
         }
     }
 
+    /// Set how long a gap between heartbeats may be before it counts as idle
+    pub fn with_idle_threshold(mut self, idle_threshold: Duration) -> Self {
+        self.idle_threshold = idle_threshold;
+        self
+    }
+
     /// Get the underlying repository
     pub fn repository(&self) -> &SessionRepository {
         &self.repository
     }
 
+    /// Get the feature time repository
+    pub fn feature_time(&self) -> &FeatureTimeRepository {
+        &self.feature_time
+    }
+
+    /// Get the session notes repository
+    pub fn notes(&self) -> &SessionNoteRepository {
+        &self.notes
+    }
+
+    /// Attribute time since the session's last activity to its current feature
+    ///
+    /// Call before the session records new activity. Nothing accrues for
+    /// paused or ended sessions, sessions without a feature, or idle gaps.
+    async fn accrue_feature_time(&self, session: &Session) -> Result<()> {
+        let Some(feature_id) = session.current_feature_id else {
+            return Ok(());
+        };
+        if !session.is_active() {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        let secs = time::active_secs(session.last_activity, now, self.idle_threshold);
+        self.feature_time
+            .add(
+                feature_id,
+                session.id,
+                session.current_project_id,
+                secs,
+                now,
+            )
+            .await
+    }
+
     // ========== Session Lifecycle ==========
 
     /// Create a new session
@@ -46,6 +96,7 @@ impl SessionManager {
         // Pause any existing active session
         if let Some(mut active) = self.repository.get_active().await? {
             info!(session_id = %active.id, "Pausing existing active session");
+            self.accrue_feature_time(&active).await?;
             active.pause();
             self.repository.update(&active).await?;
             self.repository
@@ -238,6 +289,7 @@ impl SessionManager {
             )));
         }
 
+        self.accrue_feature_time(&session).await?;
         session.pause();
         self.repository.update(&session).await?;
         self.repository
@@ -269,6 +321,7 @@ impl SessionManager {
         if let Some(mut active) = self.repository.get_active().await? {
             if active.id != session_id {
                 info!(session_id = %active.id, "Pausing existing active session");
+                self.accrue_feature_time(&active).await?;
                 active.pause();
                 self.repository.update(&active).await?;
                 self.repository
@@ -304,6 +357,7 @@ impl SessionManager {
             return Ok(session);
         }
 
+        self.accrue_feature_time(&session).await?;
         session.complete();
         self.repository.update(&session).await?;
         self.repository
@@ -331,6 +385,7 @@ impl SessionManager {
             return Ok(session);
         }
 
+        self.accrue_feature_time(&session).await?;
         session.abandon();
         self.repository.update(&session).await?;
         self.repository
@@ -362,6 +417,7 @@ impl SessionManager {
             )));
         }
 
+        self.accrue_feature_time(&session).await?;
         let old_project_id = session.current_project_id;
         session.set_project(project_id);
         // Clear feature when switching projects
@@ -405,6 +461,7 @@ impl SessionManager {
             )));
         }
 
+        self.accrue_feature_time(&session).await?;
         let old_feature_id = session.current_feature_id;
         session.set_feature(feature_id);
 
@@ -520,6 +577,7 @@ impl SessionManager {
             .await?
             .ok_or_else(|| Error::NotFound(format!("Session {} not found", session_id)))?;
 
+        self.accrue_feature_time(&session).await?;
         session.touch();
         self.repository.update(&session).await?;
 
@@ -1078,4 +1136,41 @@ mod tests {
         let info = super::super::session::RecoveryInfo::new(session, SessionStatus::Paused);
         assert!(info.has_checkpoint);
     }
+
+    #[tokio::test]
+    async fn test_feature_time_accrues_between_heartbeats() {
+        let db = Database::in_memory().await.unwrap();
+        let feature_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'shop')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO features (id, project_id, title) VALUES (?, 'p1', 'Login')")
+            .bind(feature_id.to_string())
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let manager = SessionManager::new(db.pool().clone());
+        let mut session = manager.create(None, Some(feature_id), None).await.unwrap();
+
+        // Five active minutes, then an idle gap that must not count
+        session.last_activity = Utc::now() - Duration::minutes(5);
+        manager.repository().update(&session).await.unwrap();
+        let mut session = manager.touch(session.id).await.unwrap();
+
+        session.last_activity = Utc::now() - Duration::hours(2);
+        manager.repository().update(&session).await.unwrap();
+        manager.pause(session.id).await.unwrap();
+
+        // Paused sessions accrue nothing
+        manager.touch(session.id).await.unwrap();
+
+        let time = manager
+            .feature_time()
+            .for_feature(&feature_id.to_string())
+            .await
+            .unwrap();
+        assert!((299..=301).contains(&time.total_secs));
+        assert_eq!(time.sessions.len(), 1);
+    }
 }
@@ -15,6 +15,8 @@
 //! - Event logging for session activities
 //! - Automatic session recovery on restart
 //! - Cross-project context switching
+//! - Active time tracking per feature, with idle detection
+//! - Scratchpad notes, optionally passed to the next generation as reminders
 //!
 //! # Example
 //!
@@ -44,19 +46,26 @@
 pub mod event;
 pub mod locked_manager;
 pub mod manager;
+pub mod notes;
 pub mod repository;
 pub mod repository_trait;
 #[allow(clippy::module_inception)]
 pub mod session;
 pub mod shutdown;
+pub mod time;
 
 // Re-export main types
 pub use event::{SessionEvent, SessionEventType};
 pub use locked_manager::LockedSessionManager;
 pub use manager::{CleanupSummary, SessionManager, SessionStats};
+pub use notes::{SessionNote, SessionNoteRepository};
 pub use repository::SessionRepository;
 pub use repository_trait::SessionRepositoryTrait;
 pub use session::{
     RecoveryInfo, RecoveryResult, Session, SessionInfo, SessionPhase, SessionStatus,
 };
-pub use shutdown::{ShutdownConfig, ShutdownHandler, ShutdownHandlerBuilder, ShutdownResult};
+pub use shutdown::{
+    ShutdownConfig, ShutdownHandler, ShutdownHandlerBuilder, ShutdownResult, ShutdownSignal,
+    SignalGuard,
+};
+pub use time::{FeatureTime, FeatureTimeRepository, FeatureTimeSummary};
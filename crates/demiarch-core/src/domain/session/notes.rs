@@ -0,0 +1,189 @@
+//! Scratchpad notes for a session
+//!
+//! Quick notes taken while working (`demiarch notes add "remember to refactor
+//! auth"`, `/note` in chat) are attached to the active session and listed in
+//! its report. A note marked as a reminder is also added to the context of
+//! the next generation, once.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+/// A note attached to a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionNote {
+    /// Note ID
+    pub id: String,
+    /// Session the note belongs to
+    pub session_id: String,
+    /// Note text
+    pub content: String,
+    /// Whether to remind the next generation of the note
+    pub remind: bool,
+    /// When the note was given to a generation as a reminder
+    pub reminded_at: Option<DateTime<Utc>>,
+    /// When the note was taken
+    pub created_at: DateTime<Utc>,
+}
+
+impl SessionNote {
+    /// Whether the note is a reminder no generation has seen yet
+    pub fn is_pending_reminder(&self) -> bool {
+        self.remind && self.reminded_at.is_none()
+    }
+}
+
+/// Repository for session notes
+#[derive(Debug, Clone)]
+pub struct SessionNoteRepository {
+    pool: SqlitePool,
+}
+
+impl SessionNoteRepository {
+    /// Create a new repository with the given connection pool
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Attach a note to a session
+    pub async fn add(&self, session_id: Uuid, content: &str, remind: bool) -> Result<SessionNote> {
+        let content = content.trim();
+        if content.is_empty() {
+            return Err(Error::InvalidInput("Note is empty".to_string()));
+        }
+
+        let note = SessionNote {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.to_string(),
+            content: content.to_string(),
+            remind,
+            reminded_at: None,
+            created_at: Utc::now(),
+        };
+        sqlx::query(
+            "INSERT INTO session_notes (id, session_id, content, remind, created_at) \
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&note.id)
+        .bind(&note.session_id)
+        .bind(&note.content)
+        .bind(note.remind)
+        .bind(note.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(note)
+    }
+
+    /// Notes for a session, oldest first
+    pub async fn list(&self, session_id: Uuid) -> Result<Vec<SessionNote>> {
+        let rows = sqlx::query(
+            "SELECT id, session_id, content, remind, reminded_at, created_at FROM session_notes \
+             WHERE session_id = ? ORDER BY created_at",
+        )
+        .bind(session_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(note_from_row).collect())
+    }
+
+    /// Reminders for a session that no generation has seen yet
+    pub async fn pending_reminders(&self, session_id: Uuid) -> Result<Vec<SessionNote>> {
+        Ok(self
+            .list(session_id)
+            .await?
+            .into_iter()
+            .filter(SessionNote::is_pending_reminder)
+            .collect())
+    }
+
+    /// Record that a generation was given these reminders
+    pub async fn mark_reminded(&self, ids: &[String]) -> Result<()> {
+        let now = Utc::now();
+        for id in ids {
+            sqlx::query("UPDATE session_notes SET reminded_at = ? WHERE id = ?")
+                .bind(now)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Delete a note
+    pub async fn delete(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM session_notes WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn note_from_row(r: sqlx::sqlite::SqliteRow) -> SessionNote {
+    SessionNote {
+        id: r.get("id"),
+        session_id: r.get("session_id"),
+        content: r.get("content"),
+        remind: r.get("remind"),
+        reminded_at: r.get("reminded_at"),
+        created_at: r.get("created_at"),
+    }
+}
+
+/// A system message listing reminders for a generation, if there are any
+pub fn reminders_prompt(notes: &[SessionNote]) -> Option<String> {
+    if notes.is_empty() {
+        return None;
+    }
+    let mut prompt = "The developer left these reminders during the current session. \
+                      Take them into account where they apply:"
+        .to_string();
+    for note in notes {
+        prompt.push_str("\n- ");
+        prompt.push_str(&note.content);
+    }
+    Some(prompt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Database;
+
+    #[tokio::test]
+    async fn test_reminders_are_pending_until_marked() {
+        let db = Database::in_memory().await.unwrap();
+        let sessions = crate::domain::session::SessionManager::new(db.pool().clone());
+        let session = sessions.create(None, None, None).await.unwrap();
+        let repo = SessionNoteRepository::new(db.pool().clone());
+
+        repo.add(session.id, "check the staging logs", false)
+            .await
+            .unwrap();
+        let reminder = repo
+            .add(session.id, "  remember to refactor auth ", true)
+            .await
+            .unwrap();
+        assert_eq!(reminder.content, "remember to refactor auth");
+        assert!(repo.add(session.id, "   ", false).await.is_err());
+
+        assert_eq!(repo.list(session.id).await.unwrap().len(), 2);
+        let pending = repo.pending_reminders(session.id).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, reminder.id);
+        let prompt = reminders_prompt(&pending).unwrap();
+        assert!(prompt.ends_with("\n- remember to refactor auth"));
+
+        repo.mark_reminded(&[reminder.id.clone()]).await.unwrap();
+        assert!(repo.pending_reminders(session.id).await.unwrap().is_empty());
+        assert!(reminders_prompt(&[]).is_none());
+
+        assert!(repo.delete(&reminder.id).await.unwrap());
+        assert_eq!(repo.list(session.id).await.unwrap().len(), 1);
+    }
+}
@@ -6,6 +6,7 @@ use super::event::{SessionEvent, SessionEventType};
 use super::repository_trait::SessionRepositoryTrait;
 use super::session::{Session, SessionInfo, SessionPhase, SessionStatus};
 use crate::error::{Error, Result};
+use crate::storage::write_queue::{write_or_queue, QueuedSessionEvent, QueuedWrite};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
@@ -294,25 +295,18 @@ impl SessionRepository {
     // ========== Session Events ==========
 
     /// Save a session event
+    ///
+    /// If the database is busy the event is queued and written the next time
+    /// it is opened.
     pub async fn save_event(&self, event: &SessionEvent) -> Result<()> {
-        let id = event.id.to_string();
-        let session_id = event.session_id.to_string();
-        let data = event.data.as_ref().map(|d| d.to_string());
-
-        sqlx::query(
-            r#"
-            INSERT INTO session_events (id, session_id, event_type, data, created_at)
-            VALUES (?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&id)
-        .bind(&session_id)
-        .bind(event.event_type.as_str())
-        .bind(&data)
-        .bind(event.created_at)
-        .execute(&self.pool)
-        .await
-        .map_err(Error::DatabaseError)?;
+        let write = QueuedWrite::SessionEvent(QueuedSessionEvent {
+            id: event.id.to_string(),
+            session_id: event.session_id.to_string(),
+            event_type: event.event_type.as_str().to_string(),
+            data: event.data.as_ref().map(|d| d.to_string()),
+            created_at: event.created_at,
+        });
+        write_or_queue(&self.pool, write).await?;
 
         Ok(())
     }
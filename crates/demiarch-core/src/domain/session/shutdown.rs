@@ -28,14 +28,22 @@
 //! let result = handler.shutdown_gracefully().await?;
 //! println!("Shutdown complete: {}", result.summary());
 //! ```
+//!
+//! # Signals
+//!
+//! [`SignalGuard`] cancels a token on Ctrl-C (SIGINT) or SIGTERM so long
+//! running work such as a generation can stop cooperatively and clean up
+//! before the handler runs.
 
 use super::manager::SessionManager;
 use super::session::SessionStatus;
 use crate::domain::locking::LockManager;
 use crate::error::{Error, Result};
 use crate::storage::Database;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
@@ -538,6 +546,118 @@ impl Default for ShutdownHandlerBuilder {
     }
 }
 
+/// Signal that asked the process to stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownSignal {
+    /// SIGINT, usually Ctrl-C
+    Interrupt,
+    /// SIGTERM
+    Terminate,
+}
+
+impl ShutdownSignal {
+    /// Conventional signal name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Interrupt => "SIGINT",
+            Self::Terminate => "SIGTERM",
+        }
+    }
+
+    /// Exit code for a process stopped by this signal (128 + signal number)
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Interrupt => 130,
+            Self::Terminate => 143,
+        }
+    }
+}
+
+impl std::fmt::Display for ShutdownSignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Cancels a token when the process receives a shutdown signal
+///
+/// Listening stops when the guard is dropped. Must be created inside a tokio
+/// runtime.
+#[derive(Debug)]
+pub struct SignalGuard {
+    token: CancellationToken,
+    received: Arc<OnceLock<ShutdownSignal>>,
+    listener: JoinHandle<()>,
+}
+
+impl SignalGuard {
+    /// Start listening for SIGINT and, on Unix, SIGTERM
+    pub fn install() -> Self {
+        let token = CancellationToken::new();
+        let received = Arc::new(OnceLock::new());
+        let listener = tokio::spawn({
+            let token = token.clone();
+            let received = received.clone();
+            async move {
+                let signal = wait_for_signal().await;
+                warn!(signal = %signal, "Shutdown signal received");
+                let _ = received.set(signal);
+                token.cancel();
+            }
+        });
+        Self {
+            token,
+            received,
+            listener,
+        }
+    }
+
+    /// Token cancelled when a signal arrives
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// The signal that arrived, if any
+    pub fn received(&self) -> Option<ShutdownSignal> {
+        self.received.get().copied()
+    }
+}
+
+impl Drop for SignalGuard {
+    fn drop(&mut self) {
+        self.listener.abort();
+    }
+}
+
+/// Wait for SIGINT or, on Unix, SIGTERM
+///
+/// A signal that cannot be listened for is never reported.
+pub async fn wait_for_signal() -> ShutdownSignal {
+    let interrupt = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut stream) => {
+                stream.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => ShutdownSignal::Interrupt,
+        _ = terminate => ShutdownSignal::Terminate,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -772,4 +892,15 @@ mod tests {
         assert!(summary.contains("3 locks released"));
         assert!(summary.contains("Database closed"));
     }
+
+    #[tokio::test]
+    async fn test_signal_guard_not_cancelled_without_signal() {
+        let guard = SignalGuard::install();
+        tokio::task::yield_now().await;
+
+        assert!(!guard.token().is_cancelled());
+        assert_eq!(guard.received(), None);
+        assert_eq!(ShutdownSignal::Interrupt.exit_code(), 130);
+        assert_eq!(ShutdownSignal::Terminate.to_string(), "SIGTERM");
+    }
 }
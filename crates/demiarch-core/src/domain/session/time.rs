@@ -0,0 +1,235 @@
+//! Time tracking per feature
+//!
+//! While a session is active and has a current feature, the time between
+//! activity heartbeats is attributed to that feature. A gap longer than the
+//! idle threshold means nobody was working, so it is not counted.
+//!
+//! Time accrues whenever the session manager records activity: touches,
+//! feature and project switches, pausing and ending the session.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use crate::error::Result;
+
+/// Gaps between heartbeats longer than this count as idle
+pub const DEFAULT_IDLE_THRESHOLD: Duration = Duration::minutes(15);
+
+/// Active seconds between two heartbeats, or zero if the gap was idle
+pub fn active_secs(last_activity: DateTime<Utc>, now: DateTime<Utc>, idle: Duration) -> i64 {
+    let gap = now - last_activity;
+    if gap <= Duration::zero() || gap > idle {
+        0
+    } else {
+        gap.num_seconds()
+    }
+}
+
+/// Time spent on a feature in one session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureSessionTime {
+    /// Session the time was spent in
+    pub session_id: String,
+    /// Active seconds
+    pub active_secs: i64,
+    /// First heartbeat attributed to the feature
+    pub first_active_at: DateTime<Utc>,
+    /// Last heartbeat attributed to the feature
+    pub last_active_at: DateTime<Utc>,
+}
+
+/// Total time spent on a feature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureTime {
+    /// Feature ID
+    pub feature_id: String,
+    /// Active seconds across all sessions
+    pub total_secs: i64,
+    /// Breakdown by session, most recent first
+    pub sessions: Vec<FeatureSessionTime>,
+}
+
+/// One feature's line in a project time summary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureTimeSummary {
+    /// Feature ID
+    pub feature_id: String,
+    /// Feature title, if the feature still exists
+    pub title: Option<String>,
+    /// Active seconds across all sessions
+    pub total_secs: i64,
+    /// Number of sessions that worked on the feature
+    pub sessions: i64,
+    /// Last time the feature was worked on
+    pub last_active_at: DateTime<Utc>,
+}
+
+/// Repository for feature time records
+#[derive(Debug, Clone)]
+pub struct FeatureTimeRepository {
+    pool: SqlitePool,
+}
+
+impl FeatureTimeRepository {
+    /// Create a new repository with the given connection pool
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Add active time to a feature for a session
+    pub async fn add(
+        &self,
+        feature_id: Uuid,
+        session_id: Uuid,
+        project_id: Option<Uuid>,
+        secs: i64,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO feature_time (feature_id, session_id, project_id, active_secs, first_active_at, last_active_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(feature_id, session_id) DO UPDATE SET
+                active_secs = active_secs + excluded.active_secs,
+                last_active_at = excluded.last_active_at,
+                project_id = COALESCE(excluded.project_id, project_id)
+            "#,
+        )
+        .bind(feature_id.to_string())
+        .bind(session_id.to_string())
+        .bind(project_id.map(|id| id.to_string()))
+        .bind(secs)
+        .bind(at)
+        .bind(at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Time spent on a feature
+    pub async fn for_feature(&self, feature_id: &str) -> Result<FeatureTime> {
+        let rows = sqlx::query(
+            "SELECT session_id, active_secs, first_active_at, last_active_at FROM feature_time \
+             WHERE feature_id = ? ORDER BY last_active_at DESC",
+        )
+        .bind(feature_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let sessions: Vec<FeatureSessionTime> = rows
+            .into_iter()
+            .map(|r| FeatureSessionTime {
+                session_id: r.get("session_id"),
+                active_secs: r.get("active_secs"),
+                first_active_at: r.get("first_active_at"),
+                last_active_at: r.get("last_active_at"),
+            })
+            .collect();
+
+        Ok(FeatureTime {
+            feature_id: feature_id.to_string(),
+            total_secs: sessions.iter().map(|s| s.active_secs).sum(),
+            sessions,
+        })
+    }
+
+    /// Time per feature for a project, most time first
+    pub async fn project_summary(&self, project_id: &str) -> Result<Vec<FeatureTimeSummary>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT t.feature_id, f.title,
+                   SUM(t.active_secs) AS total_secs,
+                   COUNT(*) AS sessions,
+                   MAX(t.last_active_at) AS last_active_at
+            FROM feature_time t
+            LEFT JOIN features f ON f.id = t.feature_id
+            WHERE t.project_id = ? OR f.project_id = ?
+            GROUP BY t.feature_id
+            ORDER BY total_secs DESC
+            "#,
+        )
+        .bind(project_id)
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| FeatureTimeSummary {
+                feature_id: r.get("feature_id"),
+                title: r.get("title"),
+                total_secs: r.get("total_secs"),
+                sessions: r.get("sessions"),
+                last_active_at: r.get("last_active_at"),
+            })
+            .collect())
+    }
+}
+
+/// Format seconds as "2h 05m" or "4m 10s"
+pub fn format_secs(secs: i64) -> String {
+    let hours = secs / 3600;
+    let minutes = (secs % 3600) / 60;
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{}m {:02}s", minutes, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Database;
+
+    #[test]
+    fn test_active_secs_ignores_idle_gaps() {
+        let start = Utc::now();
+        let idle = Duration::minutes(15);
+
+        assert_eq!(active_secs(start, start + Duration::minutes(5), idle), 300);
+        assert_eq!(active_secs(start, start + Duration::minutes(16), idle), 0);
+        assert_eq!(active_secs(start, start - Duration::minutes(1), idle), 0);
+    }
+
+    #[test]
+    fn test_format_secs() {
+        assert_eq!(format_secs(250), "4m 10s");
+        assert_eq!(format_secs(7500), "2h 05m");
+    }
+
+    #[tokio::test]
+    async fn test_repository_accumulates_per_session() {
+        let db = Database::in_memory().await.unwrap();
+        let sessions = crate::domain::session::SessionManager::new(db.pool().clone());
+        let first = sessions.create(None, None, None).await.unwrap();
+        let second = sessions.create(None, None, None).await.unwrap();
+        let repo = FeatureTimeRepository::new(db.pool().clone());
+        let feature = Uuid::new_v4();
+        let project = Uuid::new_v4();
+        let now = Utc::now();
+
+        repo.add(feature, first.id, Some(project), 60, now)
+            .await
+            .unwrap();
+        repo.add(feature, first.id, Some(project), 30, now)
+            .await
+            .unwrap();
+        repo.add(feature, second.id, Some(project), 10, now)
+            .await
+            .unwrap();
+
+        let time = repo.for_feature(&feature.to_string()).await.unwrap();
+        assert_eq!(time.total_secs, 100);
+        assert_eq!(time.sessions.len(), 2);
+
+        let summary = repo.project_summary(&project.to_string()).await.unwrap();
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].total_secs, 100);
+        assert_eq!(summary[0].sessions, 2);
+        assert!(summary[0].title.is_none());
+    }
+}
@@ -3,7 +3,13 @@
 //! This module provides a unified error type for the entire application.
 //! Domain-specific errors (LockError, KeyError, SigningError, etc.) are wrapped
 //! in the main Error enum for consistent error handling.
+//!
+//! Every error has a stable code (e.g. "E001") and a category. Frontends that
+//! need to handle errors programmatically (the GUI, `--format json`) receive
+//! an [`ErrorPayload`] rather than the formatted message.
 
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use thiserror::Error;
 
 use crate::domain::locking::types::LockError;
@@ -23,7 +29,7 @@ pub enum Error {
     #[error("Project '{0}' not found. Run `demiarch projects list` to see all projects.")]
     ProjectNotFound(String),
 
-    #[error("Phase '{0}' not found.")]
+    #[error("Phase '{0}' not found. Run `demiarch phases list` to see all phases.")]
     PhaseNotFound(String),
 
     // Network errors (E100-E199)
@@ -41,6 +47,9 @@ pub enum Error {
     #[error("Rate limited. Waiting {0} seconds before retry.")]
     RateLimited(u64),
 
+    #[error("Offline mode is enabled: {0} requires network access.")]
+    OfflineMode(String),
+
     // Cost errors (E200-E299)
     #[error(
         "Daily budget exceeded (${0:.2}/${1:.2}). Increase limit with `demiarch config set cost_daily_limit_usd {2}`."
@@ -56,7 +65,10 @@ pub enum Error {
 
     // Database errors (E400-E499)
     #[error("Database error: {0}")]
-    DatabaseError(#[from] sqlx::Error),
+    DatabaseError(sqlx::Error),
+
+    #[error("Read-only mode: {0} would modify the database.")]
+    ReadOnly(String),
 
     // Security errors (E450-E499)
     #[error("Security error: {0}")]
@@ -75,6 +87,9 @@ pub enum Error {
     #[error("License expired for plugin '{0}'. Renew at {1}")]
     LicenseExpired(String, String),
 
+    #[error("Invalid license: {0}")]
+    InvalidLicense(String),
+
     // Config errors (E600-E699)
     #[error("Configuration error: {0}")]
     ConfigError(String),
@@ -83,6 +98,12 @@ pub enum Error {
     #[error("User cancelled operation")]
     UserCancelled,
 
+    #[error("Approval not given: {0}. Nothing was written.")]
+    ApprovalRejected(String),
+
+    #[error("No approval after {0} seconds. Nothing was written.")]
+    ApprovalTimeout(u64),
+
     // Input errors (E800-E899)
     #[error("Invalid input: {0}")]
     InvalidInput(String),
@@ -164,6 +185,18 @@ pub enum Error {
     #[error("E1405: Failed to save image: {0}")]
     ImageSaveError(String),
 
+    // Update errors (E1500-E1599)
+    #[error("Update rejected: {0}")]
+    UpdateRejected(String),
+
+    // Transcription errors (E1600-E1699)
+    #[error("E1600: Transcription failed: {0}")]
+    TranscriptionFailed(String),
+
+    // Guardrail errors (E1700-E1799)
+    #[error("Guardrail policy violated: {0}")]
+    PolicyViolation(String),
+
     // Generic errors
     #[error("{0}")]
     Other(String),
@@ -183,17 +216,22 @@ impl Error {
             Self::LLMError(_) => "E101",
             Self::LlmError(_) => "E102",
             Self::RateLimited(_) => "E103",
+            Self::OfflineMode(_) => "E104",
             Self::BudgetExceeded(..) => "E200",
             Self::LockTimeout(_) => "E300",
             Self::Lock(e) => e.code(),
             Self::DatabaseError(_) => "E400",
+            Self::ReadOnly(_) => "E401",
             Self::Security(_) => "E450",
             Self::Signing(_) => "E451",
             Self::PluginNotFound(_) => "E500",
             Self::PluginValidationFailed(_) => "E501",
             Self::LicenseExpired(..) => "E502",
+            Self::InvalidLicense(_) => "E503",
             Self::ConfigError(_) => "E600",
             Self::UserCancelled => "E700",
+            Self::ApprovalRejected(_) => "E701",
+            Self::ApprovalTimeout(_) => "E702",
             Self::InvalidInput(_) => "E800",
             Self::Validation(_) => "E801",
             Self::Parse(_) => "E802",
@@ -218,29 +256,299 @@ impl Error {
             Self::ImageModelNotAvailable(_) => "E1403",
             Self::ImageReadError(_) => "E1404",
             Self::ImageSaveError(_) => "E1405",
+            Self::UpdateRejected(_) => "E1500",
+            Self::TranscriptionFailed(_) => "E1600",
+            Self::PolicyViolation(_) => "E1700",
             Self::Other(_) | Self::Io(_) => "E9999",
         }
     }
 
+    /// Get the category of this error
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::FeatureNotFound(_)
+            | Self::ProjectNotFound(_)
+            | Self::PhaseNotFound(_)
+            | Self::NotFound(_)
+            | Self::SkillNotFound(_)
+            | Self::EntityNotFound(_)
+            | Self::RelationshipNotFound(_) => ErrorCategory::NotFound,
+            Self::NetworkError(_) | Self::RateLimited(_) => ErrorCategory::Network,
+            Self::OfflineMode(_) => ErrorCategory::Offline,
+            Self::LLMError(_)
+            | Self::LlmError(_)
+            | Self::RoutingFailed(_)
+            | Self::NoSuitableModel(_)
+            | Self::EmbeddingFailed(_)
+            | Self::TranscriptionFailed(_) => ErrorCategory::Llm,
+            Self::BudgetExceeded(..) => ErrorCategory::Budget,
+            Self::LockTimeout(_) | Self::Lock(_) => ErrorCategory::Lock,
+            Self::DatabaseError(_) | Self::ReadOnly(_) => ErrorCategory::Database,
+            Self::Security(_)
+            | Self::Signing(_)
+            | Self::UpdateRejected(_)
+            | Self::PolicyViolation(_) => ErrorCategory::Security,
+            Self::PluginNotFound(_)
+            | Self::PluginValidationFailed(_)
+            | Self::LicenseExpired(..)
+            | Self::InvalidLicense(_) => ErrorCategory::Plugin,
+            Self::ConfigError(_) => ErrorCategory::Config,
+            Self::UserCancelled | Self::ApprovalRejected(_) | Self::ApprovalTimeout(_) => {
+                ErrorCategory::Cancelled
+            }
+            Self::InvalidInput(_)
+            | Self::Validation(_)
+            | Self::Parse(_)
+            | Self::InvalidGraphOperation(_) => ErrorCategory::Validation,
+            Self::SkillExtractionFailed(_)
+            | Self::ContextRetrievalFailed(_)
+            | Self::EntityExtractionFailed(_)
+            | Self::GraphTraversalFailed(_)
+            | Self::GraphQueryFailed(_) => ErrorCategory::Knowledge,
+            Self::HookFailed(_) | Self::HookTimeout(_) => ErrorCategory::Hook,
+            Self::ImageApiKeyMissing
+            | Self::ImageGenerationError(_)
+            | Self::InvalidImageFormat(_)
+            | Self::ImageModelNotAvailable(_)
+            | Self::ImageReadError(_)
+            | Self::ImageSaveError(_) => ErrorCategory::Image,
+            Self::Io(_) => ErrorCategory::Io,
+            Self::Other(_) => ErrorCategory::Internal,
+        }
+    }
+
+    /// Whether retrying the same operation later may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::NetworkError(_)
+            | Self::RateLimited(_)
+            | Self::LockTimeout(_)
+            | Self::HookTimeout(_) => true,
+            Self::Lock(e) => matches!(e, LockError::Timeout { .. } | LockError::Contention { .. }),
+            Self::DatabaseError(e) => matches!(e, sqlx::Error::PoolTimedOut),
+            _ => false,
+        }
+    }
+
+    /// Structured details for variants that carry more than a message
+    pub fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            Self::FeatureNotFound(id)
+            | Self::ProjectNotFound(id)
+            | Self::PhaseNotFound(id)
+            | Self::SkillNotFound(id)
+            | Self::EntityNotFound(id)
+            | Self::RelationshipNotFound(id)
+            | Self::PluginNotFound(id)
+            | Self::LockTimeout(id) => Some(json!({ "id": id })),
+            Self::RateLimited(secs) => Some(json!({ "retry_after_secs": secs })),
+            Self::HookTimeout(secs) | Self::ApprovalTimeout(secs) => {
+                Some(json!({ "timeout_secs": secs }))
+            }
+            Self::BudgetExceeded(spent, limit, suggested) => Some(json!({
+                "spent_usd": spent,
+                "limit_usd": limit,
+                "suggested_limit_usd": suggested,
+            })),
+            Self::LicenseExpired(plugin, renew_url) => Some(json!({
+                "plugin": plugin,
+                "renew_url": renew_url,
+            })),
+            Self::OfflineMode(operation) | Self::ReadOnly(operation) => {
+                Some(json!({ "operation": operation }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Convert to a serializable payload for frontends
+    pub fn to_payload(&self) -> ErrorPayload {
+        ErrorPayload {
+            code: self.code().to_string(),
+            category: self.category(),
+            message: self.to_string(),
+            details: self.details(),
+            suggestion: self.suggestion(),
+            retryable: self.is_retryable(),
+        }
+    }
+
     /// Get suggestion for how to fix this error
     pub fn suggestion(&self) -> Option<String> {
         match self {
             Self::FeatureNotFound(_) => Some("demiarch features list".to_string()),
             Self::ProjectNotFound(_) => Some("demiarch projects list".to_string()),
+            Self::PhaseNotFound(_) => Some("demiarch phases list".to_string()),
             Self::NetworkError(_) => Some("Check internet connection".to_string()),
+            Self::OfflineMode(_) => Some(
+                "Run without --offline, unset DEMIARCH_OFFLINE, or demiarch config set network.offline false"
+                    .to_string(),
+            ),
+            Self::ReadOnly(_) => {
+                Some("Run without --read-only or unset DEMIARCH_READ_ONLY".to_string())
+            }
             Self::LLMError(_) => Some("demiarch config get openrouter_api_key".to_string()),
             Self::BudgetExceeded(_, _, suggested) => Some(format!(
                 "demiarch config set cost_daily_limit_usd {}",
                 suggested
             )),
             Self::PluginNotFound(name) => Some(format!("demiarch plugin install {}", name)),
+            Self::InvalidLicense(_) => Some("demiarch license status".to_string()),
             Self::SkillNotFound(_) => Some("demiarch skills list".to_string()),
             Self::ContextRetrievalFailed(_) => Some("demiarch context rebuild".to_string()),
             Self::ImageApiKeyMissing => {
                 Some("Set OPENROUTER_API_KEY or DEMIARCH_API_KEY environment variable".to_string())
             }
             Self::ImageModelNotAvailable(_) => Some("demiarch image models".to_string()),
+            Self::UpdateRejected(_) => Some(
+                "Check updates.manifest_url and DEMIARCH_LICENSE_ISSUER_KEY, or download the release manually"
+                    .to_string(),
+            ),
+            Self::TranscriptionFailed(_) => Some(
+                "Check transcription.backend with demiarch config get transcription.backend"
+                    .to_string(),
+            ),
+            Self::ApprovalTimeout(_) => Some(
+                "Respond sooner, or raise it with demiarch config set approvals.timeout_secs <secs>"
+                    .to_string(),
+            ),
+            Self::PolicyViolation(_) => Some(
+                "Review the guardrails.* settings in demiarch config list and the project's .demiarch/guardrails.toml"
+                    .to_string(),
+            ),
             _ => None,
         }
     }
 }
+
+impl From<sqlx::Error> for Error {
+    /// Writes rejected by a read-only connection surface as [`Error::ReadOnly`]
+    fn from(error: sqlx::Error) -> Self {
+        let read_only = match &error {
+            sqlx::Error::Database(e) => e
+                .code()
+                .and_then(|code| code.parse::<u32>().ok())
+                .is_some_and(|code| code & 0xff == SQLITE_READONLY),
+            _ => false,
+        };
+        if read_only {
+            Self::ReadOnly("this operation".to_string())
+        } else {
+            Self::DatabaseError(error)
+        }
+    }
+}
+
+/// SQLite primary result code for writes to a read-only database
+const SQLITE_READONLY: u32 = 8;
+
+/// Broad category of an error, stable for programmatic handling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    NotFound,
+    Network,
+    Offline,
+    Llm,
+    Budget,
+    Lock,
+    Database,
+    Security,
+    Plugin,
+    Config,
+    Cancelled,
+    Validation,
+    Knowledge,
+    Hook,
+    Image,
+    Io,
+    Internal,
+}
+
+impl ErrorCategory {
+    /// Stable string form (matches the serialized value)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotFound => "not_found",
+            Self::Network => "network",
+            Self::Offline => "offline",
+            Self::Llm => "llm",
+            Self::Budget => "budget",
+            Self::Lock => "lock",
+            Self::Database => "database",
+            Self::Security => "security",
+            Self::Plugin => "plugin",
+            Self::Config => "config",
+            Self::Cancelled => "cancelled",
+            Self::Validation => "validation",
+            Self::Knowledge => "knowledge",
+            Self::Hook => "hook",
+            Self::Image => "image",
+            Self::Io => "io",
+            Self::Internal => "internal",
+        }
+    }
+}
+
+/// Machine-readable form of an [`Error`]
+///
+/// This is what the GUI receives from failed commands and what the CLI prints
+/// with `--format json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorPayload {
+    /// Stable error code (e.g. "E001")
+    pub code: String,
+    /// Error category
+    pub category: ErrorCategory,
+    /// Human-readable message
+    pub message: String,
+    /// Variant-specific structured details
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    /// Suggested fix, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    /// Whether retrying later may succeed
+    pub retryable: bool,
+}
+
+impl ErrorPayload {
+    /// Payload for an error that did not originate from [`Error`]
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self {
+            code: Error::Other(String::new()).code().to_string(),
+            category: ErrorCategory::Internal,
+            message: message.into(),
+            details: None,
+            suggestion: None,
+            retryable: false,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<Error> for ErrorPayload {
+    fn from(error: Error) -> Self {
+        error.to_payload()
+    }
+}
+
+impl From<&Error> for ErrorPayload {
+    fn from(error: &Error) -> Self {
+        error.to_payload()
+    }
+}
+
+impl From<anyhow::Error> for ErrorPayload {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast_ref::<Error>() {
+            Some(e) => e.to_payload(),
+            None => Self::internal(error.to_string()),
+        }
+    }
+}
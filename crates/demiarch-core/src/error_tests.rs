@@ -1,6 +1,6 @@
 //! Error module tests
 
-use crate::error::{Error, Result};
+use crate::error::{Error, ErrorCategory, ErrorPayload, Result};
 
 #[tokio::test]
 async fn test_feature_not_found_error() {
@@ -28,7 +28,7 @@ async fn test_project_not_found_error() {
 async fn test_phase_not_found_error() {
     let error = Error::PhaseNotFound("planning".to_string());
     assert_eq!(error.code(), "E003");
-    assert_eq!(error.suggestion(), None);
+    assert_eq!(error.suggestion(), Some("demiarch phases list".to_string()));
     assert!(error.to_string().contains("planning"));
 }
 
@@ -212,11 +212,20 @@ async fn test_database_error() {
     assert_eq!(error.code(), "E400");
 }
 
+#[tokio::test]
+async fn test_read_only_error() {
+    let error = Error::ReadOnly("feature update".to_string());
+    assert_eq!(error.code(), "E401");
+    assert_eq!(error.category(), ErrorCategory::Database);
+    assert!(error.to_string().contains("Read-only mode"));
+    assert!(!error.is_retryable());
+}
+
 #[tokio::test]
 async fn test_phase_not_found_error_suggestion() {
     let error = Error::PhaseNotFound("planning".to_string());
     assert_eq!(error.code(), "E003");
-    assert_eq!(error.suggestion(), None);
+    assert_eq!(error.suggestion(), Some("demiarch phases list".to_string()));
 }
 
 #[tokio::test]
@@ -306,6 +315,14 @@ async fn test_result_type_alias() {
     assert!(failure.is_err());
 }
 
+#[tokio::test]
+async fn test_offline_mode_error() {
+    let error = Error::OfflineMode("LLM completion".to_string());
+    assert_eq!(error.code(), "E104");
+    assert!(error.to_string().contains("LLM completion"));
+    assert!(error.suggestion().unwrap().contains("network.offline"));
+}
+
 #[tokio::test]
 async fn test_all_error_codes_unique() {
     let errors = vec![
@@ -314,6 +331,7 @@ async fn test_all_error_codes_unique() {
         Error::PhaseNotFound("test".to_string()).code(),
         Error::LLMError("test".to_string()).code(),
         Error::RateLimited(30).code(),
+        Error::OfflineMode("test".to_string()).code(),
         Error::BudgetExceeded(10.0, 15.0, 20.0).code(),
         Error::LockTimeout("test".to_string()).code(),
         Error::DatabaseError(sqlx::Error::RowNotFound).code(),
@@ -322,6 +340,8 @@ async fn test_all_error_codes_unique() {
         Error::LicenseExpired("test".to_string(), "2024".to_string()).code(),
         Error::ConfigError("test".to_string()).code(),
         Error::UserCancelled.code(),
+        Error::ApprovalRejected("test".to_string()).code(),
+        Error::ApprovalTimeout(60).code(),
         Error::InvalidInput("test".to_string()).code(),
         Error::SkillNotFound("test".to_string()).code(),
         Error::SkillExtractionFailed("test".to_string()).code(),
@@ -335,5 +355,61 @@ async fn test_all_error_codes_unique() {
     ];
 
     let unique_codes: std::collections::HashSet<_> = errors.into_iter().collect();
-    assert_eq!(unique_codes.len(), 23);
+    assert_eq!(unique_codes.len(), 26);
+}
+
+#[test]
+fn test_error_payload_for_budget_exceeded() {
+    let payload = ErrorPayload::from(Error::BudgetExceeded(10.0, 15.0, 20.0));
+    assert_eq!(payload.code, "E200");
+    assert_eq!(payload.category, ErrorCategory::Budget);
+    assert!(!payload.retryable);
+    assert!(payload.suggestion.is_some());
+
+    let details = payload.details.unwrap();
+    assert_eq!(details["spent_usd"], 10.0);
+    assert_eq!(details["limit_usd"], 15.0);
+}
+
+#[test]
+fn test_error_payload_serializes_stable_fields() {
+    let payload = Error::RateLimited(30).to_payload();
+    assert!(payload.retryable);
+    assert_eq!(payload.category, ErrorCategory::Network);
+
+    let json = serde_json::to_value(&payload).unwrap();
+    assert_eq!(json["code"], "E103");
+    assert_eq!(json["category"], "network");
+    assert_eq!(json["details"]["retry_after_secs"], 30);
+    assert_eq!(json["retryable"], true);
+
+    let roundtrip: ErrorPayload = serde_json::from_value(json).unwrap();
+    assert_eq!(roundtrip, payload);
+}
+
+#[test]
+fn test_error_payload_from_anyhow() {
+    let wrapped = anyhow::Error::new(Error::ProjectNotFound("p1".to_string()));
+    let payload = ErrorPayload::from(wrapped);
+    assert_eq!(payload.code, "E002");
+    assert_eq!(payload.category, ErrorCategory::NotFound);
+    assert_eq!(payload.details.unwrap()["id"], "p1");
+
+    let payload = ErrorPayload::from(anyhow::anyhow!("boom"));
+    assert_eq!(payload.category, ErrorCategory::Internal);
+    assert_eq!(payload.message, "boom");
+    assert!(payload.details.is_none());
+}
+
+#[test]
+fn test_error_category_as_str_matches_serde() {
+    for category in [
+        ErrorCategory::NotFound,
+        ErrorCategory::Llm,
+        ErrorCategory::Offline,
+        ErrorCategory::Internal,
+    ] {
+        let json = serde_json::to_value(category).unwrap();
+        assert_eq!(json, category.as_str());
+    }
 }
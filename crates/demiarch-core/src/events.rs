@@ -0,0 +1,494 @@
+//! Core event bus
+//!
+//! Things other tools may want to react to (a generation completing, a
+//! feature being created, an LLM cost being recorded, an activity digest
+//! being written) are published as [`CoreEvent`]s on the process-wide
+//! [`EventBus`]. Webhooks from the `[events]` config section and WASM
+//! plugins that declare `events` in their manifest attach to the same bus as
+//! [`EventSubscriber`]s.
+//!
+//! Subscribers may answer with an [`EventReaction`]: annotations are passed
+//! back to the publisher, and a veto cancels the step for vetoable events
+//! (currently feature creation), like a `[hooks]` command exiting non-zero.
+//! A failing subscriber is logged and skipped; it never blocks the step.
+//! Events published with [`EventBus::emit`] are delivered in the background;
+//! [`EventBus::flush`] waits for them before the process exits.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::config::{EventsConfig, WebhookConfig};
+use crate::infrastructure::network;
+use crate::{Error, Result};
+
+/// Kinds of event a subscriber can ask for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    GenerationCompleted,
+    FeatureCreated,
+    CostRecorded,
+    DigestCreated,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GenerationCompleted => "generation_completed",
+            Self::FeatureCreated => "feature_created",
+            Self::CostRecorded => "cost_recorded",
+            Self::DigestCreated => "digest_created",
+        }
+    }
+
+    /// Whether a subscriber's veto cancels the step that raised the event
+    pub fn is_vetoable(&self) -> bool {
+        matches!(self, Self::FeatureCreated)
+    }
+}
+
+/// Something that happened in core
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CoreEvent {
+    GenerationCompleted {
+        generation_id: String,
+        project_id: Option<String>,
+        description: String,
+        files: usize,
+        cost_usd: f64,
+    },
+    FeatureCreated {
+        feature_id: String,
+        project_id: String,
+        title: String,
+    },
+    CostRecorded {
+        cost_id: String,
+        model: String,
+        cost_usd: f64,
+        input_tokens: u32,
+        output_tokens: u32,
+    },
+    /// An activity digest; `text` is its Markdown, as Slack incoming
+    /// webhooks expect
+    DigestCreated {
+        document_id: String,
+        project_id: String,
+        period: String,
+        title: String,
+        text: String,
+    },
+}
+
+impl CoreEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Self::GenerationCompleted { .. } => EventKind::GenerationCompleted,
+            Self::FeatureCreated { .. } => EventKind::FeatureCreated,
+            Self::CostRecorded { .. } => EventKind::CostRecorded,
+            Self::DigestCreated { .. } => EventKind::DigestCreated,
+        }
+    }
+}
+
+/// An event as delivered to subscribers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    pub id: String,
+    pub occurred_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: CoreEvent,
+}
+
+impl EventEnvelope {
+    pub fn new(event: CoreEvent) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            occurred_at: Utc::now(),
+            event,
+        }
+    }
+}
+
+/// A subscriber's optional answer to an event
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EventReaction {
+    #[serde(default)]
+    pub annotations: Vec<String>,
+    /// Reason to cancel the step (ignored for events that are not vetoable)
+    #[serde(default)]
+    pub veto: Option<String>,
+}
+
+/// Receives events from the bus
+#[async_trait]
+pub trait EventSubscriber: Send + Sync {
+    /// Shown in logs, annotations and veto messages
+    fn name(&self) -> &str;
+
+    fn subscribes_to(&self, kind: EventKind) -> bool;
+
+    async fn deliver(&self, event: &EventEnvelope) -> Result<EventReaction>;
+}
+
+/// An annotation and the subscriber that made it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub subscriber: String,
+    pub text: String,
+}
+
+/// What subscribers said about a published event
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PublishOutcome {
+    pub annotations: Vec<Annotation>,
+    /// Subscriber and reason of the first veto, for vetoable events
+    pub veto: Option<(String, String)>,
+}
+
+impl PublishOutcome {
+    /// Fail with a validation error if a subscriber vetoed the step
+    pub fn ensure_not_vetoed(&self, step: &str) -> Result<()> {
+        match &self.veto {
+            Some((subscriber, reason)) => Err(Error::Validation(format!(
+                "{} vetoed by {}: {}",
+                step, subscriber, reason
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Fans events out to subscribers
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: RwLock<Vec<Arc<dyn EventSubscriber>>>,
+    /// Emitted events still being delivered
+    pending: AtomicUsize,
+    delivered: Notify,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self, subscriber: Arc<dyn EventSubscriber>) {
+        if let Ok(mut subscribers) = self.subscribers.write() {
+            subscribers.push(subscriber);
+        }
+    }
+
+    /// Names of the attached subscribers
+    pub fn subscriber_names(&self) -> Vec<String> {
+        self.subscribers
+            .read()
+            .map(|s| s.iter().map(|s| s.name().to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether any subscriber wants events of `kind`
+    pub fn has_subscribers(&self, kind: EventKind) -> bool {
+        !self.subscribers_for(kind).is_empty()
+    }
+
+    fn subscribers_for(&self, kind: EventKind) -> Vec<Arc<dyn EventSubscriber>> {
+        self.subscribers
+            .read()
+            .map(|s| {
+                s.iter()
+                    .filter(|s| s.subscribes_to(kind))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Deliver an event to its subscribers in order and collect reactions
+    ///
+    /// For vetoable events delivery stops at the first veto.
+    pub async fn publish(&self, event: CoreEvent) -> PublishOutcome {
+        let kind = event.kind();
+        let subscribers = self.subscribers_for(kind);
+        let mut outcome = PublishOutcome::default();
+        if subscribers.is_empty() {
+            return outcome;
+        }
+
+        let envelope = EventEnvelope::new(event);
+        for subscriber in subscribers {
+            let reaction = match subscriber.deliver(&envelope).await {
+                Ok(reaction) => reaction,
+                Err(e) => {
+                    tracing::warn!(subscriber = subscriber.name(), event = kind.as_str(), error = %e, "Event delivery failed");
+                    continue;
+                }
+            };
+            outcome
+                .annotations
+                .extend(reaction.annotations.into_iter().map(|text| Annotation {
+                    subscriber: subscriber.name().to_string(),
+                    text,
+                }));
+            if let Some(reason) = reaction.veto {
+                if kind.is_vetoable() {
+                    outcome.veto = Some((subscriber.name().to_string(), reason));
+                    break;
+                }
+                tracing::debug!(
+                    subscriber = subscriber.name(),
+                    event = kind.as_str(),
+                    "Ignoring veto for an event that cannot be vetoed"
+                );
+            }
+        }
+        outcome
+    }
+
+    /// Publish without waiting, for callers that cannot await
+    ///
+    /// Does nothing outside a tokio runtime or when nobody subscribes.
+    pub fn emit(&'static self, event: CoreEvent) {
+        if self.subscribers_for(event.kind()).is_empty() {
+            return;
+        }
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            self.pending.fetch_add(1, Ordering::SeqCst);
+            handle.spawn(async move {
+                let outcome = self.publish(event).await;
+                for annotation in outcome.annotations {
+                    tracing::info!(subscriber = %annotation.subscriber, "{}", annotation.text);
+                }
+                self.pending.fetch_sub(1, Ordering::SeqCst);
+                self.delivered.notify_waiters();
+            });
+        }
+    }
+
+    /// Number of emitted events not yet delivered
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    /// Wait up to `timeout` for emitted events to be delivered
+    ///
+    /// Returns the number still pending when the wait ended.
+    pub async fn flush(&self, timeout: Duration) -> usize {
+        let wait = async {
+            loop {
+                let delivered = self.delivered.notified();
+                if self.pending() == 0 {
+                    break;
+                }
+                delivered.await;
+            }
+        };
+        let _ = tokio::time::timeout(timeout, wait).await;
+        self.pending()
+    }
+}
+
+static GLOBAL: OnceLock<EventBus> = OnceLock::new();
+
+/// The process-wide event bus
+pub fn global() -> &'static EventBus {
+    GLOBAL.get_or_init(EventBus::new)
+}
+
+/// Posts events as JSON to a URL
+///
+/// A JSON response body is read as an [`EventReaction`]; anything else
+/// counts as no reaction.
+pub struct WebhookSubscriber {
+    url: String,
+    kinds: Vec<EventKind>,
+    client: reqwest::Client,
+}
+
+impl WebhookSubscriber {
+    pub fn new(config: &WebhookConfig, timeout: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        Ok(Self {
+            url: config.url.clone(),
+            kinds: config.events.clone(),
+            client,
+        })
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for WebhookSubscriber {
+    fn name(&self) -> &str {
+        &self.url
+    }
+
+    fn subscribes_to(&self, kind: EventKind) -> bool {
+        self.kinds.is_empty() || self.kinds.contains(&kind)
+    }
+
+    async fn deliver(&self, event: &EventEnvelope) -> Result<EventReaction> {
+        network::ensure_online("webhook delivery")?;
+        let response = self
+            .client
+            .post(&self.url)
+            .json(event)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.json().await.unwrap_or_default())
+    }
+}
+
+/// Attach a subscriber for every configured webhook
+pub fn attach_webhooks(bus: &EventBus, config: &EventsConfig) -> Result<usize> {
+    let timeout = Duration::from_secs(config.webhook_timeout_secs);
+    for webhook in &config.webhooks {
+        bus.subscribe(Arc::new(WebhookSubscriber::new(webhook, timeout)?));
+    }
+    Ok(config.webhooks.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct Recorder {
+        name: String,
+        kinds: Vec<EventKind>,
+        reaction: EventReaction,
+        seen: Mutex<Vec<EventKind>>,
+    }
+
+    impl Recorder {
+        fn new(name: &str, kinds: Vec<EventKind>, reaction: EventReaction) -> Arc<Self> {
+            Arc::new(Self {
+                name: name.to_string(),
+                kinds,
+                reaction,
+                seen: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl EventSubscriber for Recorder {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn subscribes_to(&self, kind: EventKind) -> bool {
+            self.kinds.contains(&kind)
+        }
+
+        async fn deliver(&self, event: &EventEnvelope) -> Result<EventReaction> {
+            self.seen.lock().unwrap().push(event.event.kind());
+            Ok(self.reaction.clone())
+        }
+    }
+
+    fn feature_created() -> CoreEvent {
+        CoreEvent::FeatureCreated {
+            feature_id: "f1".to_string(),
+            project_id: "p1".to_string(),
+            title: "Login".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delivers_only_subscribed_kinds_and_collects_annotations() {
+        let bus = EventBus::new();
+        let costs = Recorder::new(
+            "costs",
+            vec![EventKind::CostRecorded],
+            EventReaction::default(),
+        );
+        let features = Recorder::new(
+            "features",
+            vec![EventKind::FeatureCreated],
+            EventReaction {
+                annotations: vec!["Remember to add tests".to_string()],
+                veto: None,
+            },
+        );
+        bus.subscribe(costs.clone());
+        bus.subscribe(features.clone());
+
+        let outcome = bus.publish(feature_created()).await;
+        assert!(costs.seen.lock().unwrap().is_empty());
+        assert_eq!(features.seen.lock().unwrap().len(), 1);
+        assert_eq!(outcome.annotations[0].subscriber, "features");
+        assert!(outcome.ensure_not_vetoed("Feature creation").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_veto_stops_delivery_only_for_vetoable_events() {
+        let bus = EventBus::new();
+        let vetoer = Recorder::new(
+            "policy",
+            vec![EventKind::FeatureCreated, EventKind::GenerationCompleted],
+            EventReaction {
+                annotations: vec![],
+                veto: Some("Feature freeze".to_string()),
+            },
+        );
+        let later = Recorder::new(
+            "later",
+            vec![EventKind::FeatureCreated, EventKind::GenerationCompleted],
+            EventReaction::default(),
+        );
+        bus.subscribe(vetoer);
+        bus.subscribe(later.clone());
+
+        let outcome = bus.publish(feature_created()).await;
+        assert_eq!(
+            outcome.veto,
+            Some(("policy".to_string(), "Feature freeze".to_string()))
+        );
+        assert!(outcome.ensure_not_vetoed("Feature creation").is_err());
+        assert!(later.seen.lock().unwrap().is_empty());
+
+        let outcome = bus
+            .publish(CoreEvent::GenerationCompleted {
+                generation_id: "g1".to_string(),
+                project_id: None,
+                description: "Add login".to_string(),
+                files: 2,
+                cost_usd: 0.01,
+            })
+            .await;
+        assert!(outcome.veto.is_none());
+        assert_eq!(later.seen.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_flush_waits_for_emitted_events() {
+        let bus: &'static EventBus = Box::leak(Box::new(EventBus::new()));
+        let features = Recorder::new(
+            "features",
+            vec![EventKind::FeatureCreated],
+            EventReaction::default(),
+        );
+        bus.subscribe(features.clone());
+
+        bus.emit(feature_created());
+        bus.emit(feature_created());
+        assert_eq!(bus.flush(Duration::from_secs(5)).await, 0);
+        assert_eq!(features.seen.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_envelope_serializes_flat_with_kind_tag() {
+        let json = serde_json::to_value(EventEnvelope::new(feature_created())).unwrap();
+        assert_eq!(json["kind"], "feature_created");
+        assert_eq!(json["title"], "Login");
+        assert!(json["id"].is_string());
+    }
+}
@@ -0,0 +1,143 @@
+//! Checkpoints taken when hook events fire
+//!
+//! `[checkpoint.triggers]` picks the events that snapshot project state
+//! before their hook commands run: sync imports, commands that change
+//! several features, and the end of (or an interval during) a chat session.
+//! The projects to snapshot come from the event payload's `project_id` or
+//! `project_ids`.
+
+use uuid::Uuid;
+
+use super::HookEvent;
+use crate::commands::checkpoint::create_checkpoint_with_db;
+use crate::config::CheckpointTriggersConfig;
+use crate::domain::recovery::CheckpointInfo;
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Takes checkpoints for the hook events enabled in the configuration
+#[derive(Debug, Clone)]
+pub struct CheckpointTriggers {
+    db: Database,
+    config: CheckpointTriggersConfig,
+}
+
+impl CheckpointTriggers {
+    pub fn new(db: Database, config: CheckpointTriggersConfig) -> Self {
+        Self { db, config }
+    }
+
+    /// Whether firing `event` takes checkpoints
+    pub fn is_enabled(&self, event: HookEvent) -> bool {
+        match event {
+            HookEvent::BeforeSyncImport => self.config.before_sync_import,
+            HookEvent::BeforeBulkFeatures => self.config.before_bulk_features,
+            HookEvent::SessionEnd => self.config.on_session_end,
+            HookEvent::SessionInterval => self.config.interval_mins > 0,
+            HookEvent::BeforeArchive | HookEvent::BeforePurge | HookEvent::OnPolicyViolation => {
+                false
+            }
+        }
+    }
+
+    /// Checkpoint each project named in `payload`, if `event` is enabled
+    pub async fn run(
+        &self,
+        event: HookEvent,
+        payload: &serde_json::Value,
+    ) -> Result<Vec<CheckpointInfo>> {
+        if !self.is_enabled(event) {
+            return Ok(Vec::new());
+        }
+        let mut checkpoints = Vec::new();
+        for project_id in project_ids(payload)? {
+            checkpoints.push(
+                create_checkpoint_with_db(&self.db, project_id, description(event), None).await?,
+            );
+        }
+        Ok(checkpoints)
+    }
+}
+
+/// Description stored on a triggered checkpoint
+fn description(event: HookEvent) -> String {
+    match event {
+        HookEvent::BeforeSyncImport => "Auto-checkpoint before sync import",
+        HookEvent::BeforeBulkFeatures => "Auto-checkpoint before bulk feature change",
+        HookEvent::SessionEnd => "Auto-checkpoint at session end",
+        HookEvent::SessionInterval => "Auto-checkpoint during session",
+        HookEvent::BeforeArchive => "Auto-checkpoint before archive",
+        HookEvent::BeforePurge => "Auto-checkpoint before purge",
+        HookEvent::OnPolicyViolation => "Auto-checkpoint on policy violation",
+    }
+    .to_string()
+}
+
+/// Project IDs from a payload's `project_id` and `project_ids` fields
+fn project_ids(payload: &serde_json::Value) -> Result<Vec<Uuid>> {
+    let single = payload.get("project_id").into_iter();
+    let many = payload
+        .get("project_ids")
+        .and_then(|ids| ids.as_array())
+        .into_iter()
+        .flatten();
+    single
+        .chain(many)
+        .filter(|id| !id.is_null())
+        .map(|id| {
+            id.as_str()
+                .and_then(|id| Uuid::parse_str(id).ok())
+                .ok_or_else(|| Error::InvalidInput(format!("Invalid project ID: {}", id)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::project::{Project, ProjectRepository};
+    use crate::domain::recovery::{CheckpointManager, CheckpointSigner};
+    use crate::hooks::HooksManager;
+    use serde_json::json;
+
+    #[test]
+    fn test_project_ids_from_payload() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        let ids = project_ids(&json!({
+            "project_id": a.to_string(),
+            "project_ids": [b.to_string()],
+        }))
+        .unwrap();
+        assert_eq!(ids, vec![a, b]);
+        assert!(project_ids(&json!({})).unwrap().is_empty());
+        assert!(project_ids(&json!({ "project_id": "nope" })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_enabled_events_take_checkpoints() {
+        let db = Database::in_memory().await.unwrap();
+        let project = Project::new("triggers", "rust", "");
+        ProjectRepository::new(&db).create(&project).await.unwrap();
+        let project_id = Uuid::parse_str(&project.id).unwrap();
+
+        let config = CheckpointTriggersConfig {
+            on_session_end: true,
+            ..Default::default()
+        };
+        let hooks = HooksManager::new().with_checkpoint_triggers(db.clone(), &config);
+        let payload = json!({ "project_id": project.id });
+        hooks.fire(HookEvent::SessionEnd, &payload).await.unwrap();
+        hooks
+            .fire(HookEvent::SessionInterval, &payload)
+            .await
+            .unwrap();
+
+        let checkpoints = CheckpointManager::new(db.pool().clone(), CheckpointSigner::generate())
+            .list_checkpoints(project_id)
+            .await
+            .unwrap();
+        assert_eq!(checkpoints.len(), 1);
+        assert_eq!(checkpoints[0].description, "Auto-checkpoint at session end");
+    }
+}
@@ -1 +1,290 @@
 //! Lifecycle hooks system
+//!
+//! Hooks are shell commands from the `[hooks]` configuration section that
+//! run before destructive lifecycle steps, such as archiving a project or
+//! purging old checkpoints. Each command gets the event name in
+//! `DEMIARCH_HOOK_EVENT` and a JSON description of the step on stdin. A
+//! non-zero exit vetoes the step, with the command's stderr (or stdout) as
+//! the reason.
+//!
+//! Events can also take checkpoints before any command runs; see
+//! [`checkpoints`].
+
+pub mod checkpoints;
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::info;
+
+use crate::config::{CheckpointTriggersConfig, HooksConfig};
+use crate::storage::Database;
+use crate::{Error, Result};
+
+pub use checkpoints::CheckpointTriggers;
+
+/// Default time a hook may run
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Environment variable carrying the event name
+pub const HOOK_EVENT_ENV: &str = "DEMIARCH_HOOK_EVENT";
+
+/// Lifecycle point a hook runs at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    BeforeArchive,
+    BeforePurge,
+    BeforeSyncImport,
+    /// A command is about to change several features at once
+    BeforeBulkFeatures,
+    SessionEnd,
+    /// A long session reached its checkpoint interval
+    SessionInterval,
+    /// Generated files broke a guardrail policy and were not written
+    OnPolicyViolation,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BeforeArchive => "before_archive",
+            Self::BeforePurge => "before_purge",
+            Self::BeforeSyncImport => "before_sync_import",
+            Self::BeforeBulkFeatures => "before_bulk_features",
+            Self::SessionEnd => "session_end",
+            Self::SessionInterval => "session_interval",
+            Self::OnPolicyViolation => "on_policy_violation",
+        }
+    }
+}
+
+/// Outcome of firing the hooks for an event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum HookDecision {
+    Proceed,
+    /// A hook exited non-zero; later hooks were not run
+    Veto {
+        command: String,
+        reason: String,
+    },
+}
+
+impl HookDecision {
+    pub fn is_veto(&self) -> bool {
+        matches!(self, Self::Veto { .. })
+    }
+}
+
+/// Runs the hooks configured for lifecycle events
+#[derive(Debug, Clone)]
+pub struct HooksManager {
+    commands: HashMap<HookEvent, Vec<String>>,
+    checkpoints: Option<CheckpointTriggers>,
+    timeout: Duration,
+}
+
+impl Default for HooksManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HooksManager {
+    /// Create a manager with no hooks
+    pub fn new() -> Self {
+        Self {
+            commands: HashMap::new(),
+            checkpoints: None,
+            timeout: DEFAULT_HOOK_TIMEOUT,
+        }
+    }
+
+    /// Create a manager from the `[hooks]` configuration section
+    pub fn from_config(config: &HooksConfig) -> Self {
+        Self {
+            commands: HashMap::from([
+                (HookEvent::BeforeArchive, config.before_archive.clone()),
+                (HookEvent::BeforePurge, config.before_purge.clone()),
+                (
+                    HookEvent::OnPolicyViolation,
+                    config.on_policy_violation.clone(),
+                ),
+            ]),
+            checkpoints: None,
+            timeout: Duration::from_secs(config.timeout_secs),
+        }
+    }
+
+    /// Add a command for an event
+    pub fn with_hook(mut self, event: HookEvent, command: impl Into<String>) -> Self {
+        self.commands.entry(event).or_default().push(command.into());
+        self
+    }
+
+    /// Take checkpoints for the events enabled in `[checkpoint.triggers]`
+    pub fn with_checkpoint_triggers(
+        mut self,
+        db: Database,
+        triggers: &CheckpointTriggersConfig,
+    ) -> Self {
+        self.checkpoints = Some(CheckpointTriggers::new(db, triggers.clone()));
+        self
+    }
+
+    /// Set how long a hook may run
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Commands configured for an event
+    pub fn commands(&self, event: HookEvent) -> &[String] {
+        self.commands
+            .get(&event)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Run the hooks for an event in order, stopping at the first veto
+    ///
+    /// Checkpoint triggers for the event run first. Errors are returned when
+    /// a checkpoint cannot be taken or a hook cannot be started or times
+    /// out; callers about to destroy data should treat them as a veto.
+    pub async fn fire(
+        &self,
+        event: HookEvent,
+        payload: &serde_json::Value,
+    ) -> Result<HookDecision> {
+        if let Some(checkpoints) = &self.checkpoints {
+            for checkpoint in checkpoints.run(event, payload).await? {
+                info!(
+                    event = event.as_str(),
+                    checkpoint_id = %checkpoint.id,
+                    project_id = %checkpoint.project_id,
+                    "Checkpoint taken by trigger"
+                );
+            }
+        }
+
+        let input = payload.to_string();
+        for command in self.commands(event) {
+            if let Some(reason) = self.run(event, command, &input).await? {
+                return Ok(HookDecision::Veto {
+                    command: command.clone(),
+                    reason,
+                });
+            }
+        }
+        Ok(HookDecision::Proceed)
+    }
+
+    /// Run one hook, returning the veto reason if it exited non-zero
+    async fn run(&self, event: HookEvent, command: &str, input: &str) -> Result<Option<String>> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env(HOOK_EVENT_ENV, event.as_str())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| Error::HookFailed(format!("{}: {}", command, e)))?;
+
+        if let Some(mut pipe) = child.stdin.take() {
+            let input = input.to_string();
+            // A hook that ignores stdin must not block us on a full pipe
+            tokio::spawn(async move {
+                let _ = pipe.write_all(input.as_bytes()).await;
+                let _ = pipe.shutdown().await;
+            });
+        }
+
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| Error::HookTimeout(self.timeout.as_secs()))?
+            .map_err(|e| Error::HookFailed(format!("{}: {}", command, e)))?;
+        if output.status.success() {
+            return Ok(None);
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let reason = [stderr.trim(), stdout.trim()]
+            .into_iter()
+            .find(|s| !s.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| match output.status.code() {
+                Some(code) => format!("exited with status {}", code),
+                None => "terminated by signal".to_string(),
+            });
+        Ok(Some(reason))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_no_hooks_proceeds() {
+        let decision = HooksManager::new()
+            .fire(HookEvent::BeforePurge, &json!({}))
+            .await
+            .unwrap();
+        assert_eq!(decision, HookDecision::Proceed);
+    }
+
+    #[tokio::test]
+    async fn test_failing_hook_vetoes_with_reason() {
+        let hooks = HooksManager::new()
+            .with_hook(
+                HookEvent::BeforeArchive,
+                "grep -q '\"keep\"' && echo pinned >&2 && exit 1 || true",
+            )
+            .with_hook(
+                HookEvent::BeforeArchive,
+                "test \"$DEMIARCH_HOOK_EVENT\" = before_archive",
+            );
+
+        let keep = hooks
+            .fire(HookEvent::BeforeArchive, &json!({ "project_name": "keep" }))
+            .await
+            .unwrap();
+        assert_eq!(
+            keep,
+            HookDecision::Veto {
+                command: hooks.commands(HookEvent::BeforeArchive)[0].clone(),
+                reason: "pinned".to_string(),
+            }
+        );
+
+        let other = hooks
+            .fire(
+                HookEvent::BeforeArchive,
+                &json!({ "project_name": "other" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(other, HookDecision::Proceed);
+    }
+
+    #[tokio::test]
+    async fn test_slow_hook_times_out() {
+        let hooks = HooksManager::new()
+            .with_hook(HookEvent::BeforePurge, "sleep 5")
+            .with_timeout(Duration::from_millis(100));
+        let err = hooks
+            .fire(HookEvent::BeforePurge, &json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::HookTimeout(_)));
+    }
+}
@@ -0,0 +1,352 @@
+//! Localization of user-facing strings
+//!
+//! Messages live in Fluent-style `.ftl` catalogs keyed by message id:
+//!
+//! ```text
+//! # comment
+//! projects-none = No projects found.
+//! projects-archived = Project '{ $id }' archived.
+//! help-text =
+//!     First line
+//!     Second line
+//! ```
+//!
+//! English ships with the binary and is always the fallback. Community
+//! translations are picked up from `<config dir>/locales/<locale>.ftl`, and
+//! only need to contain the messages they translate. A regional catalog
+//! (`pt-BR.ftl`) is layered over its language catalog (`pt.ftl`).
+//!
+//! The locale is chosen from `DEMIARCH_LANG`, then the `ui.locale` config
+//! key, then the standard `LC_ALL` / `LC_MESSAGES` / `LANG` variables.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use crate::config::Config;
+
+/// Environment variable that overrides the locale
+pub const LOCALE_ENV: &str = "DEMIARCH_LANG";
+
+/// Locale used when nothing else is configured, and as the fallback
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Built-in English catalog
+const EN_FTL: &str = include_str!("../../locales/en.ftl");
+
+static CURRENT: RwLock<Option<Arc<Catalog>>> = RwLock::new(None);
+
+/// A set of messages for one locale, with English fallback
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    locale: String,
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// The built-in English catalog
+    pub fn english() -> Self {
+        Self {
+            locale: DEFAULT_LOCALE.to_string(),
+            messages: parse_ftl(EN_FTL),
+        }
+    }
+
+    /// Load the catalog for a locale
+    ///
+    /// Starts from English and overlays any installed translation files.
+    pub fn load(locale: &str) -> Self {
+        let locale = normalize_locale(locale);
+        let mut catalog = Self::english();
+        catalog.locale = locale.clone();
+
+        if let Some(dir) = locales_dir() {
+            for candidate in locale_chain(&locale) {
+                if let Ok(source) = std::fs::read_to_string(dir.join(format!("{}.ftl", candidate)))
+                {
+                    catalog.overlay(&source);
+                }
+            }
+        }
+
+        catalog
+    }
+
+    /// Overlay messages from `.ftl` source, replacing existing ones
+    pub fn overlay(&mut self, source: &str) {
+        self.messages.extend(parse_ftl(source));
+    }
+
+    /// Locale of this catalog (e.g. "en", "pt-BR")
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// All messages, keyed by message id
+    pub fn messages(&self) -> &HashMap<String, String> {
+        &self.messages
+    }
+
+    /// Get a message without substituting arguments
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(String::as_str)
+    }
+
+    /// Format a message, substituting `{ $name }` placeables
+    ///
+    /// Unknown message ids are returned as-is so a missing translation is
+    /// visible rather than silently blank.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        match self.get(key) {
+            Some(template) => substitute(template, args),
+            None => key.to_string(),
+        }
+    }
+}
+
+/// Initialize the process-wide catalog from config and environment
+///
+/// Returns the selected locale.
+pub fn init(config: Option<&Config>) -> String {
+    let configured = config.map(|c| c.ui.locale.as_str());
+    let locale = resolve_locale(configured);
+    set_catalog(Catalog::load(&locale));
+    locale
+}
+
+/// Replace the process-wide catalog
+pub fn set_catalog(catalog: Catalog) {
+    let mut current = CURRENT.write().unwrap_or_else(|e| e.into_inner());
+    *current = Some(Arc::new(catalog));
+}
+
+/// The process-wide catalog (English until [`init`] is called)
+pub fn catalog() -> Arc<Catalog> {
+    if let Some(catalog) = CURRENT.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return catalog.clone();
+    }
+    let catalog = Arc::new(Catalog::english());
+    let mut current = CURRENT.write().unwrap_or_else(|e| e.into_inner());
+    current.get_or_insert(catalog).clone()
+}
+
+/// Translate a message with no arguments
+pub fn t(key: &str) -> String {
+    catalog().format(key, &[])
+}
+
+/// Translate a message with `{ $name }` arguments
+pub fn t_args(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    catalog().format(key, args)
+}
+
+/// Pick the locale from `DEMIARCH_LANG`, config, then the system locale
+pub fn resolve_locale(configured: Option<&str>) -> String {
+    let from_env = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+
+    from_env(LOCALE_ENV)
+        .or_else(|| {
+            configured
+                .filter(|c| !c.trim().is_empty())
+                .map(String::from)
+        })
+        .or_else(|| from_env("LC_ALL"))
+        .or_else(|| from_env("LC_MESSAGES"))
+        .or_else(|| from_env("LANG"))
+        .map(|raw| normalize_locale(&raw))
+        .filter(|locale| locale != "C" && locale != "POSIX")
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// Normalize a locale tag: "de_DE.UTF-8" -> "de-DE"
+pub fn normalize_locale(raw: &str) -> String {
+    let tag = raw
+        .trim()
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-");
+    let mut parts = tag.split('-');
+    let language = parts.next().unwrap_or_default().to_lowercase();
+    let rest: Vec<String> = parts
+        .map(|part| {
+            if part.len() == 2 {
+                part.to_uppercase()
+            } else {
+                part.to_string()
+            }
+        })
+        .collect();
+
+    if language.is_empty() {
+        DEFAULT_LOCALE.to_string()
+    } else if language == "c" || language == "posix" {
+        language.to_uppercase()
+    } else if rest.is_empty() {
+        language
+    } else {
+        format!("{}-{}", language, rest.join("-"))
+    }
+}
+
+/// Directory community translations are loaded from
+pub fn locales_dir() -> Option<PathBuf> {
+    Config::config_dir().ok().map(|dir| dir.join("locales"))
+}
+
+/// Catalog files to layer for a locale, least specific first
+fn locale_chain(locale: &str) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut tag = String::new();
+    for part in locale.split('-') {
+        if !tag.is_empty() {
+            tag.push('-');
+        }
+        tag.push_str(part);
+        chain.push(tag.clone());
+    }
+    chain
+}
+
+/// Parse Fluent-style `key = value` messages
+///
+/// Supports comments, multiline values (indented continuation lines) and
+/// `{ $name }` placeables. Attributes, terms and selectors are not supported.
+pub fn parse_ftl(source: &str) -> HashMap<String, String> {
+    let mut messages = HashMap::new();
+    let mut current: Option<(String, Vec<String>)> = None;
+    let mut blank_lines = 0;
+
+    let mut finish = |entry: Option<(String, Vec<String>)>| {
+        if let Some((key, lines)) = entry {
+            messages.insert(key, lines.join("\n"));
+        }
+    };
+
+    for line in source.lines() {
+        let is_continuation = line.starts_with([' ', '\t']) && !line.trim().is_empty();
+
+        if is_continuation {
+            if let Some((_, lines)) = current.as_mut() {
+                // Blank lines inside a multiline value are kept, but only
+                // once another continuation line follows them
+                if !lines.is_empty() {
+                    lines.extend(std::iter::repeat_n(String::new(), blank_lines));
+                }
+                lines.push(line.trim().to_string());
+            }
+            blank_lines = 0;
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        blank_lines = 0;
+        if trimmed.starts_with('#') {
+            finish(current.take());
+            continue;
+        }
+
+        finish(current.take());
+        if let Some((key, value)) = trimmed.split_once('=') {
+            let key = key.trim();
+            if !key.is_empty() {
+                let value = value.trim();
+                let lines = if value.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![value.to_string()]
+                };
+                current = Some((key.to_string(), lines));
+            }
+        }
+    }
+    finish(current.take());
+
+    messages
+}
+
+/// Replace `{ $name }` placeables with argument values
+fn substitute(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let placeable = &rest[start + 1..start + len];
+        let name = placeable.trim().trim_start_matches('$');
+        match args.iter().find(|(arg, _)| *arg == name) {
+            Some((_, value)) => out.push_str(&value.to_string()),
+            None => out.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ftl_multiline_and_comments() {
+        let messages = parse_ftl(
+            "# comment\n\
+             greeting = Hello, { $name }!\n\
+             help =\n    First line\n\n    Second line\n\
+             \n\
+             bye = Bye\n",
+        );
+        assert_eq!(messages["greeting"], "Hello, { $name }!");
+        assert_eq!(messages["help"], "First line\n\nSecond line");
+        assert_eq!(messages["bye"], "Bye");
+    }
+
+    #[test]
+    fn test_format_substitutes_arguments() {
+        let mut catalog = Catalog::english();
+        catalog.overlay("greeting = Hello, { $name }! You have { $count } { $unknown }.");
+        assert_eq!(
+            catalog.format("greeting", &[("name", &"Ada"), ("count", &3)]),
+            "Hello, Ada! You have 3 { $unknown }."
+        );
+        assert_eq!(catalog.format("missing-key", &[]), "missing-key");
+    }
+
+    #[test]
+    fn test_overlay_keeps_english_fallback() {
+        let mut catalog = Catalog::english();
+        let english_count = catalog.messages().len();
+        catalog.overlay("projects-none = Keine Projekte gefunden.");
+        assert_eq!(
+            catalog.get("projects-none"),
+            Some("Keine Projekte gefunden.")
+        );
+        assert_eq!(catalog.messages().len(), english_count);
+        assert!(catalog.get("features-none").is_some());
+    }
+
+    #[test]
+    fn test_normalize_locale() {
+        assert_eq!(normalize_locale("de_DE.UTF-8"), "de-DE");
+        assert_eq!(normalize_locale("pt-br"), "pt-BR");
+        assert_eq!(normalize_locale("EN"), "en");
+        assert_eq!(normalize_locale("C.UTF-8"), "C");
+        assert_eq!(normalize_locale(""), "en");
+    }
+
+    #[test]
+    fn test_locale_chain() {
+        assert_eq!(locale_chain("pt-BR"), vec!["pt", "pt-BR"]);
+        assert_eq!(locale_chain("en"), vec!["en"]);
+    }
+}
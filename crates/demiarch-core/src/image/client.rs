@@ -12,6 +12,7 @@ use serde_json::json;
 use tracing::{debug, warn};
 
 use crate::error::{Error, Result};
+use crate::infrastructure::network;
 
 use super::types::{ImageFormat, ImageRequest, ImageResponse};
 
@@ -262,6 +263,7 @@ impl ImageClient {
         model: &str,
         prompt: &str,
     ) -> Result<ParsedImageResponse> {
+        network::ensure_online("Image generation")?;
         let url = format!("{}/chat/completions", self.base_url);
 
         // Build request body with modalities for image generation
@@ -308,6 +310,7 @@ impl ImageClient {
         model: &str,
         content: serde_json::Value,
     ) -> Result<ParsedImageResponse> {
+        network::ensure_online("Image generation")?;
         let url = format!("{}/chat/completions", self.base_url);
 
         let body = json!({
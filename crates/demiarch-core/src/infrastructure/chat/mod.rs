@@ -1,7 +1,7 @@
 //! Chat infrastructure module
 //!
-//! Database repositories for conversations and messages.
+//! Database repositories for conversations, messages and branches.
 
 pub mod repository;
 
-pub use repository::{ConversationRepository, MessageRepository};
+pub use repository::{BranchRepository, ConversationRepository, MessageRepository};
@@ -1,11 +1,12 @@
 //! Chat repository implementations
 //!
-//! Database operations for conversations and messages.
+//! Database operations for conversations, messages and branches.
 
 use chrono::Utc;
 use sqlx::Row;
 
-use crate::commands::chat::{ChatMessage, Conversation, MessageRole};
+use crate::commands::chat::{ChatMessage, Conversation, ConversationBranch, MessageRole};
+use crate::pagination::{Page, PageRequest};
 use crate::storage::Database;
 use crate::Result;
 
@@ -42,7 +43,7 @@ impl<'a> ConversationRepository<'a> {
     /// Get a conversation by ID
     pub async fn get(&self, id: &str) -> Result<Option<Conversation>> {
         let row = sqlx::query(
-            "SELECT id, project_id, title, created_at, updated_at FROM conversations WHERE id = ?",
+            "SELECT id, project_id, title, active_branch_id, created_at, updated_at FROM conversations WHERE id = ?",
         )
         .bind(id)
         .fetch_optional(self.db.pool())
@@ -54,7 +55,7 @@ impl<'a> ConversationRepository<'a> {
     /// List all conversations for a project
     pub async fn list_by_project(&self, project_id: &str) -> Result<Vec<Conversation>> {
         let rows = sqlx::query(
-            "SELECT id, project_id, title, created_at, updated_at FROM conversations WHERE project_id = ? ORDER BY updated_at DESC",
+            "SELECT id, project_id, title, active_branch_id, created_at, updated_at FROM conversations WHERE project_id = ? ORDER BY updated_at DESC",
         )
         .bind(project_id)
         .fetch_all(self.db.pool())
@@ -78,6 +79,17 @@ impl<'a> ConversationRepository<'a> {
         Ok(())
     }
 
+    /// Set the branch new messages are added to
+    pub async fn set_active_branch(&self, id: &str, branch_id: &str) -> Result<()> {
+        sqlx::query("UPDATE conversations SET active_branch_id = ? WHERE id = ?")
+            .bind(branch_id)
+            .bind(id)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(())
+    }
+
     /// Update the conversation's updated_at timestamp
     pub async fn touch(&self, id: &str) -> Result<()> {
         sqlx::query("UPDATE conversations SET updated_at = ? WHERE id = ?")
@@ -115,6 +127,7 @@ impl<'a> ConversationRepository<'a> {
             id: row.get("id"),
             project_id: row.get("project_id"),
             title: row.get("title"),
+            active_branch_id: row.get("active_branch_id"),
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
         }
@@ -136,8 +149,8 @@ impl<'a> MessageRepository<'a> {
     pub async fn create(&self, message: &ChatMessage) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO messages (id, conversation_id, role, content, model, tokens_used, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO messages (id, conversation_id, role, content, model, tokens_used, parent_message_id, variant_of, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&message.id)
@@ -146,6 +159,8 @@ impl<'a> MessageRepository<'a> {
         .bind(&message.content)
         .bind(&message.model)
         .bind(message.tokens_used)
+        .bind(&message.parent_message_id)
+        .bind(&message.variant_of)
         .bind(message.created_at)
         .execute(self.db.pool())
         .await?;
@@ -156,7 +171,7 @@ impl<'a> MessageRepository<'a> {
     /// Get a message by ID
     pub async fn get(&self, id: &str) -> Result<Option<ChatMessage>> {
         let row = sqlx::query(
-            "SELECT id, conversation_id, role, content, model, tokens_used, created_at FROM messages WHERE id = ?",
+            "SELECT id, conversation_id, role, content, model, tokens_used, parent_message_id, variant_of, created_at FROM messages WHERE id = ?",
         )
         .bind(id)
         .fetch_optional(self.db.pool())
@@ -168,7 +183,7 @@ impl<'a> MessageRepository<'a> {
     /// List all messages in a conversation (ordered by creation time)
     pub async fn list_by_conversation(&self, conversation_id: &str) -> Result<Vec<ChatMessage>> {
         let rows = sqlx::query(
-            "SELECT id, conversation_id, role, content, model, tokens_used, created_at FROM messages WHERE conversation_id = ? ORDER BY created_at ASC",
+            "SELECT id, conversation_id, role, content, model, tokens_used, parent_message_id, variant_of, created_at FROM messages WHERE conversation_id = ? ORDER BY created_at ASC",
         )
         .bind(conversation_id)
         .fetch_all(self.db.pool())
@@ -185,7 +200,7 @@ impl<'a> MessageRepository<'a> {
         offset: usize,
     ) -> Result<Vec<ChatMessage>> {
         let rows = sqlx::query(
-            "SELECT id, conversation_id, role, content, model, tokens_used, created_at FROM messages WHERE conversation_id = ? ORDER BY created_at ASC LIMIT ? OFFSET ?",
+            "SELECT id, conversation_id, role, content, model, tokens_used, parent_message_id, variant_of, created_at FROM messages WHERE conversation_id = ? ORDER BY created_at ASC LIMIT ? OFFSET ?",
         )
         .bind(conversation_id)
         .bind(limit as i64)
@@ -204,7 +219,7 @@ impl<'a> MessageRepository<'a> {
     ) -> Result<Vec<ChatMessage>> {
         // Get the most recent messages, then reverse to maintain chronological order
         let rows = sqlx::query(
-            "SELECT id, conversation_id, role, content, model, tokens_used, created_at FROM messages WHERE conversation_id = ? ORDER BY created_at DESC LIMIT ?",
+            "SELECT id, conversation_id, role, content, model, tokens_used, parent_message_id, variant_of, created_at FROM messages WHERE conversation_id = ? ORDER BY created_at DESC LIMIT ?",
         )
         .bind(conversation_id)
         .bind(limit as i64)
@@ -217,6 +232,138 @@ impl<'a> MessageRepository<'a> {
         Ok(messages)
     }
 
+    /// One page of a conversation's messages, counting `offset` back from
+    /// the newest; messages within the page are oldest first
+    pub async fn list_recent_page(
+        &self,
+        conversation_id: &str,
+        page: PageRequest,
+    ) -> Result<Page<ChatMessage>> {
+        let total = self.count_by_conversation(conversation_id).await?;
+        let rows = sqlx::query(
+            "SELECT id, conversation_id, role, content, model, tokens_used, parent_message_id, variant_of, created_at FROM messages WHERE conversation_id = ? ORDER BY created_at DESC LIMIT ? OFFSET ?",
+        )
+        .bind(conversation_id)
+        .bind(page.sql_limit())
+        .bind(page.sql_offset())
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut messages: Vec<ChatMessage> =
+            rows.into_iter().map(|r| self.row_to_message(r)).collect();
+        messages.reverse();
+        Ok(Page::new(messages, total as usize, page))
+    }
+
+    /// List the path from the first message to `head_id`, keeping the last
+    /// `limit` messages when given
+    pub async fn list_path(&self, head_id: &str, limit: Option<usize>) -> Result<Vec<ChatMessage>> {
+        let rows = sqlx::query(
+            r#"
+            WITH RECURSIVE path(id, parent_id, depth) AS (
+                SELECT id, parent_message_id, 0 FROM messages WHERE id = ?
+                UNION ALL
+                SELECT m.id, m.parent_message_id, path.depth + 1
+                FROM messages m JOIN path ON m.id = path.parent_id
+            )
+            SELECT m.id, m.conversation_id, m.role, m.content, m.model, m.tokens_used,
+                   m.parent_message_id, m.variant_of, m.created_at
+            FROM messages m JOIN path ON m.id = path.id
+            ORDER BY path.depth ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(head_id)
+        .bind(limit.map_or(-1, |l| l as i64))
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut messages: Vec<ChatMessage> =
+            rows.into_iter().map(|r| self.row_to_message(r)).collect();
+        messages.reverse();
+        Ok(messages)
+    }
+
+    /// One page of the path ending at `head_id`, counting `offset` back from
+    /// the head; messages within the page are oldest first
+    pub async fn list_path_page(
+        &self,
+        head_id: &str,
+        page: PageRequest,
+    ) -> Result<Page<ChatMessage>> {
+        let (total,): (i64,) = sqlx::query_as(
+            r#"
+            WITH RECURSIVE path(id, parent_id) AS (
+                SELECT id, parent_message_id FROM messages WHERE id = ?
+                UNION ALL
+                SELECT m.id, m.parent_message_id
+                FROM messages m JOIN path ON m.id = path.parent_id
+            )
+            SELECT COUNT(*) FROM path
+            "#,
+        )
+        .bind(head_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let rows = sqlx::query(
+            r#"
+            WITH RECURSIVE path(id, parent_id, depth) AS (
+                SELECT id, parent_message_id, 0 FROM messages WHERE id = ?
+                UNION ALL
+                SELECT m.id, m.parent_message_id, path.depth + 1
+                FROM messages m JOIN path ON m.id = path.parent_id
+            )
+            SELECT m.id, m.conversation_id, m.role, m.content, m.model, m.tokens_used,
+                   m.parent_message_id, m.variant_of, m.created_at
+            FROM messages m JOIN path ON m.id = path.id
+            ORDER BY path.depth ASC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(head_id)
+        .bind(page.sql_limit())
+        .bind(page.sql_offset())
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut messages: Vec<ChatMessage> =
+            rows.into_iter().map(|r| self.row_to_message(r)).collect();
+        messages.reverse();
+        Ok(Page::new(messages, total as usize, page))
+    }
+
+    /// List a message and its variants (the messages whose `variant_of` is
+    /// `original_id`), oldest first
+    pub async fn list_variants(&self, original_id: &str) -> Result<Vec<ChatMessage>> {
+        let rows = sqlx::query(
+            "SELECT id, conversation_id, role, content, model, tokens_used, parent_message_id, variant_of, created_at FROM messages WHERE id = ? OR variant_of = ? ORDER BY created_at ASC, rowid ASC",
+        )
+        .bind(original_id)
+        .bind(original_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(|r| self.row_to_message(r)).collect())
+    }
+
+    /// List the replies to a message, oldest first
+    pub async fn list_children(&self, parent_id: &str) -> Result<Vec<ChatMessage>> {
+        let rows = sqlx::query(
+            "SELECT id, conversation_id, role, content, model, tokens_used, parent_message_id, variant_of, created_at FROM messages WHERE parent_message_id = ? ORDER BY created_at ASC, rowid ASC",
+        )
+        .bind(parent_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(|r| self.row_to_message(r)).collect())
+    }
+
+    /// Get the most recently created message in a conversation
+    pub async fn latest(&self, conversation_id: &str) -> Result<Option<ChatMessage>> {
+        Ok(self.list_recent(conversation_id, 1).await?.pop())
+    }
+
     /// Count messages in a conversation
     pub async fn count_by_conversation(&self, conversation_id: &str) -> Result<i64> {
         let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM messages WHERE conversation_id = ?")
@@ -256,7 +403,99 @@ impl<'a> MessageRepository<'a> {
             content: row.get("content"),
             model: row.get("model"),
             tokens_used: row.get("tokens_used"),
+            parent_message_id: row.get("parent_message_id"),
+            variant_of: row.get("variant_of"),
+            created_at: row.get("created_at"),
+        }
+    }
+}
+
+/// Conversation branch repository for database operations
+pub struct BranchRepository<'a> {
+    db: &'a Database,
+}
+
+impl<'a> BranchRepository<'a> {
+    /// Create a new branch repository
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    /// Create a new branch in the database
+    pub async fn create(&self, branch: &ConversationBranch) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO conversation_branches
+                (id, conversation_id, name, parent_branch_id, fork_message_id, head_message_id, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&branch.id)
+        .bind(&branch.conversation_id)
+        .bind(&branch.name)
+        .bind(&branch.parent_branch_id)
+        .bind(&branch.fork_message_id)
+        .bind(&branch.head_message_id)
+        .bind(branch.created_at)
+        .bind(branch.updated_at)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get a branch by ID
+    pub async fn get(&self, id: &str) -> Result<Option<ConversationBranch>> {
+        let row = sqlx::query(
+            "SELECT id, conversation_id, name, parent_branch_id, fork_message_id, head_message_id, created_at, updated_at FROM conversation_branches WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row.map(|r| self.row_to_branch(r)))
+    }
+
+    /// List a conversation's branches, oldest first
+    pub async fn list_by_conversation(
+        &self,
+        conversation_id: &str,
+    ) -> Result<Vec<ConversationBranch>> {
+        let rows = sqlx::query(
+            "SELECT id, conversation_id, name, parent_branch_id, fork_message_id, head_message_id, created_at, updated_at FROM conversation_branches WHERE conversation_id = ? ORDER BY created_at ASC, rowid ASC",
+        )
+        .bind(conversation_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(|r| self.row_to_branch(r)).collect())
+    }
+
+    /// Point a branch at its newest message
+    pub async fn set_head(&self, id: &str, head_message_id: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE conversation_branches SET head_message_id = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(head_message_id)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Convert a database row to a ConversationBranch
+    fn row_to_branch(&self, row: sqlx::sqlite::SqliteRow) -> ConversationBranch {
+        ConversationBranch {
+            id: row.get("id"),
+            conversation_id: row.get("conversation_id"),
+            name: row.get("name"),
+            parent_branch_id: row.get("parent_branch_id"),
+            fork_message_id: row.get("fork_message_id"),
+            head_message_id: row.get("head_message_id"),
             created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
         }
     }
 }
@@ -27,9 +27,9 @@ impl<'a> DocumentRepository<'a> {
             INSERT INTO documents (
                 id, project_id, doc_type, title, description, content, format,
                 version, status, model_used, tokens_used, generation_cost_usd,
-                created_at, updated_at
+                language, created_at, updated_at
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&document.id)
@@ -44,6 +44,7 @@ impl<'a> DocumentRepository<'a> {
         .bind(&document.model_used)
         .bind(document.tokens_used)
         .bind(document.generation_cost_usd)
+        .bind(&document.language)
         .bind(document.created_at)
         .bind(document.updated_at)
         .execute(self.db.pool())
@@ -58,7 +59,7 @@ impl<'a> DocumentRepository<'a> {
             r#"
             SELECT id, project_id, doc_type, title, description, content, format,
                    version, status, model_used, tokens_used, generation_cost_usd,
-                   created_at, updated_at
+                   language, created_at, updated_at
             FROM documents WHERE id = ?
             "#,
         )
@@ -80,7 +81,7 @@ impl<'a> DocumentRepository<'a> {
                 r#"
                 SELECT id, project_id, doc_type, title, description, content, format,
                        version, status, model_used, tokens_used, generation_cost_usd,
-                       created_at, updated_at
+                       language, created_at, updated_at
                 FROM documents WHERE project_id = ? AND doc_type = ?
                 ORDER BY created_at DESC
                 "#,
@@ -94,7 +95,7 @@ impl<'a> DocumentRepository<'a> {
                 r#"
                 SELECT id, project_id, doc_type, title, description, content, format,
                        version, status, model_used, tokens_used, generation_cost_usd,
-                       created_at, updated_at
+                       language, created_at, updated_at
                 FROM documents WHERE project_id = ?
                 ORDER BY created_at DESC
                 "#,
@@ -113,7 +114,7 @@ impl<'a> DocumentRepository<'a> {
             r#"
             UPDATE documents
             SET title = ?, description = ?, content = ?, version = ?,
-                status = ?, updated_at = ?
+                status = ?, language = ?, updated_at = ?
             WHERE id = ?
             "#,
         )
@@ -122,6 +123,7 @@ impl<'a> DocumentRepository<'a> {
         .bind(&document.content)
         .bind(document.version)
         .bind(document.status.as_str())
+        .bind(&document.language)
         .bind(Utc::now())
         .bind(&document.id)
         .execute(self.db.pool())
@@ -217,6 +219,7 @@ impl<'a> DocumentRepository<'a> {
             model_used: row.get("model_used"),
             tokens_used: row.get("tokens_used"),
             generation_cost_usd: row.get("generation_cost_usd"),
+            language: row.get("language"),
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
         }
@@ -479,6 +482,8 @@ mod tests {
             DocumentType::Architecture,
             DocumentType::Design,
             DocumentType::TechSpec,
+            DocumentType::Roadmap,
+            DocumentType::Digest,
             DocumentType::Custom,
         ];
 
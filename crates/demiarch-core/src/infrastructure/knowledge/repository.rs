@@ -8,9 +8,9 @@ use sqlx::{FromRow, SqlitePool};
 use tracing::{debug, info};
 
 use crate::domain::knowledge::{
-    EntitySearchResult, EntityType, EntityWithDistance, KnowledgeEntity, KnowledgeGraphRepository,
-    KnowledgeGraphStats, KnowledgeRelationship, PathRelationship, PathStep, RelationshipType,
-    TraversalDirection,
+    combine_confidence, EntityMerge, EntitySearchResult, EntityType, EntityWithDistance,
+    KnowledgeEntity, KnowledgeGraphRepository, KnowledgeGraphStats, KnowledgeRelationship,
+    PathRelationship, PathStep, RelationshipType, TraversalDirection,
 };
 use crate::error::{Error, Result};
 
@@ -92,6 +92,23 @@ impl KnowledgeGraphRepository for SqliteKnowledgeGraphRepository {
                 .fetch_optional(&self.pool)
                 .await?;
 
+        // Fall back to names recorded when duplicates were merged
+        let row = match row {
+            Some(row) => Some(row),
+            None => {
+                sqlx::query_as(
+                    r#"
+                    SELECT e.* FROM knowledge_entities e
+                    JOIN knowledge_entity_aliases a ON a.entity_id = e.id
+                    WHERE a.canonical_alias = ?
+                    "#,
+                )
+                .bind(canonical_name)
+                .fetch_optional(&self.pool)
+                .await?
+            }
+        };
+
         row.map(|r| r.into_entity()).transpose()
     }
 
@@ -718,6 +735,333 @@ impl KnowledgeGraphRepository for SqliteKnowledgeGraphRepository {
         Ok(search_results)
     }
 
+    // ========== Alias and Merge Operations ==========
+
+    async fn save_alias(&self, entity_id: &str, alias: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO knowledge_entity_aliases (canonical_alias, alias, entity_id, created_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(canonical_alias) DO UPDATE SET
+                alias = excluded.alias,
+                entity_id = excluded.entity_id
+            "#,
+        )
+        .bind(KnowledgeEntity::canonicalize(alias))
+        .bind(alias)
+        .bind(entity_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        debug!(entity_id = %entity_id, alias = %alias, "Entity alias saved");
+        Ok(())
+    }
+
+    async fn list_aliases(&self, entity_id: &str) -> Result<Vec<String>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT alias FROM knowledge_entity_aliases WHERE entity_id = ? ORDER BY alias",
+        )
+        .bind(entity_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(alias,)| alias).collect())
+    }
+
+    async fn merge_entities(&self, keep_id: &str, duplicate_id: &str) -> Result<EntityMerge> {
+        if keep_id == duplicate_id {
+            return Err(Error::InvalidInput(
+                "Cannot merge an entity into itself".to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let keep: Option<EntityRow> =
+            sqlx::query_as("SELECT * FROM knowledge_entities WHERE id = ?")
+                .bind(keep_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let mut keep = keep
+            .ok_or_else(|| Error::EntityNotFound(keep_id.to_string()))?
+            .into_entity()?;
+        let duplicate: Option<EntityRow> =
+            sqlx::query_as("SELECT * FROM knowledge_entities WHERE id = ?")
+                .bind(duplicate_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let duplicate = duplicate
+            .ok_or_else(|| Error::EntityNotFound(duplicate_id.to_string()))?
+            .into_entity()?;
+
+        // Re-point the duplicate's relationships, folding any the kept entity
+        // already has into the existing edge
+        let rows: Vec<RelationshipRow> = sqlx::query_as(
+            "SELECT * FROM knowledge_relationships WHERE source_entity_id = ? OR target_entity_id = ?",
+        )
+        .bind(duplicate_id)
+        .bind(duplicate_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let (mut moved, mut combined, mut dropped) = (0, 0, 0);
+        for row in rows {
+            let relationship = row.into_relationship()?;
+            let repoint = |id: &str| {
+                if id == duplicate_id {
+                    keep_id.to_string()
+                } else {
+                    id.to_string()
+                }
+            };
+            let source = repoint(&relationship.source_entity_id);
+            let target = repoint(&relationship.target_entity_id);
+
+            if source == target {
+                sqlx::query("DELETE FROM knowledge_relationships WHERE id = ?")
+                    .bind(&relationship.id)
+                    .execute(&mut *tx)
+                    .await?;
+                dropped += 1;
+                continue;
+            }
+
+            let existing: Option<RelationshipRow> = sqlx::query_as(
+                r#"
+                SELECT * FROM knowledge_relationships
+                WHERE source_entity_id = ? AND target_entity_id = ? AND relationship_type = ?
+                "#,
+            )
+            .bind(&source)
+            .bind(&target)
+            .bind(relationship.relationship_type.as_str())
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if let Some(existing) = existing {
+                let mut existing = existing.into_relationship()?;
+                existing.weight = combine_confidence(existing.weight, relationship.weight);
+                existing.evidence.extend(relationship.evidence);
+                let evidence_json = serde_json::to_string(&existing.evidence)
+                    .map_err(|e| Error::Other(format!("Failed to serialize evidence: {}", e)))?;
+
+                sqlx::query(
+                    "UPDATE knowledge_relationships SET weight = ?, evidence = ?, updated_at = ? WHERE id = ?",
+                )
+                .bind(existing.weight)
+                .bind(&evidence_json)
+                .bind(Utc::now().to_rfc3339())
+                .bind(&existing.id)
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query("DELETE FROM knowledge_relationships WHERE id = ?")
+                    .bind(&relationship.id)
+                    .execute(&mut *tx)
+                    .await?;
+                combined += 1;
+            } else {
+                sqlx::query(
+                    "UPDATE knowledge_relationships SET source_entity_id = ?, target_entity_id = ?, updated_at = ? WHERE id = ?",
+                )
+                .bind(&source)
+                .bind(&target)
+                .bind(Utc::now().to_rfc3339())
+                .bind(&relationship.id)
+                .execute(&mut *tx)
+                .await?;
+                moved += 1;
+            }
+        }
+
+        // Move skill links, keeping the higher relevance where both exist
+        let links: Vec<(String, f32)> = sqlx::query_as(
+            "SELECT skill_id, relevance FROM skill_entity_links WHERE entity_id = ?",
+        )
+        .bind(duplicate_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for (skill_id, relevance) in &links {
+            sqlx::query(
+                r#"
+                INSERT INTO skill_entity_links (skill_id, entity_id, relevance, created_at)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT(skill_id, entity_id) DO UPDATE SET
+                    relevance = MAX(relevance, excluded.relevance)
+                "#,
+            )
+            .bind(skill_id)
+            .bind(keep_id)
+            .bind(relevance)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        keep.absorb(&duplicate);
+        let aliases_json = serde_json::to_string(&keep.aliases)
+            .map_err(|e| Error::Other(format!("Failed to serialize aliases: {}", e)))?;
+        let source_skill_ids_json = serde_json::to_string(&keep.source_skill_ids)
+            .map_err(|e| Error::Other(format!("Failed to serialize source_skill_ids: {}", e)))?;
+        sqlx::query(
+            r#"
+            UPDATE knowledge_entities
+            SET description = ?, aliases = ?, source_skill_ids = ?, confidence = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&keep.description)
+        .bind(&aliases_json)
+        .bind(&source_skill_ids_json)
+        .bind(keep.confidence)
+        .bind(keep.updated_at.to_rfc3339())
+        .bind(keep_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Names that pointed at the duplicate now point at the kept entity
+        sqlx::query("UPDATE knowledge_entity_aliases SET entity_id = ? WHERE entity_id = ?")
+            .bind(keep_id)
+            .bind(duplicate_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let mut aliases_recorded = Vec::new();
+        for name in std::iter::once(&duplicate.name).chain(&duplicate.aliases) {
+            let canonical = KnowledgeEntity::canonicalize(name);
+            if canonical.is_empty() || canonical == keep.canonical_name {
+                continue;
+            }
+            sqlx::query(
+                r#"
+                INSERT INTO knowledge_entity_aliases (canonical_alias, alias, entity_id, created_at)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT(canonical_alias) DO UPDATE SET entity_id = excluded.entity_id
+                "#,
+            )
+            .bind(&canonical)
+            .bind(name)
+            .bind(keep_id)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+            aliases_recorded.push(name.clone());
+        }
+
+        sqlx::query("DELETE FROM knowledge_entities WHERE id = ?")
+            .bind(duplicate_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        info!(
+            keep_id = %keep_id,
+            duplicate_id = %duplicate_id,
+            relationships_moved = moved,
+            relationships_combined = combined,
+            "Entities merged"
+        );
+
+        Ok(EntityMerge {
+            entity: keep,
+            duplicate_id: duplicate_id.to_string(),
+            relationships_moved: moved,
+            relationships_combined: combined,
+            relationships_dropped: dropped,
+            skill_links_moved: links.len(),
+            aliases_recorded,
+        })
+    }
+
+    // ========== Archive Operations ==========
+
+    async fn archive_entity(&self, id: &str) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let row: Option<EntityRow> =
+            sqlx::query_as("SELECT * FROM knowledge_entities WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some(row) = row else {
+            return Ok(false);
+        };
+        let entity = row.into_entity()?;
+
+        // Archive the entity's edges with it rather than losing them to the cascade
+        let rows: Vec<RelationshipRow> = sqlx::query_as(
+            "SELECT * FROM knowledge_relationships WHERE source_entity_id = ? OR target_entity_id = ?",
+        )
+        .bind(id)
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for row in rows {
+            let relationship = row.into_relationship()?;
+            let name = format!(
+                "{} {} {}",
+                relationship.source_entity_id,
+                relationship.relationship_type.as_str(),
+                relationship.target_entity_id
+            );
+            insert_archive(
+                &mut tx,
+                &relationship.id,
+                "relationship",
+                &name,
+                &relationship,
+            )
+            .await?;
+            sqlx::query("DELETE FROM knowledge_relationships WHERE id = ?")
+                .bind(&relationship.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        insert_archive(&mut tx, &entity.id, "entity", &entity.name, &entity).await?;
+        sqlx::query("DELETE FROM knowledge_entities WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        info!(entity_id = %id, name = %entity.name, "Entity archived");
+        Ok(true)
+    }
+
+    async fn archive_relationship(&self, id: &str) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let row: Option<RelationshipRow> =
+            sqlx::query_as("SELECT * FROM knowledge_relationships WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some(row) = row else {
+            return Ok(false);
+        };
+        let relationship = row.into_relationship()?;
+        let name = format!(
+            "{} {} {}",
+            relationship.source_entity_id,
+            relationship.relationship_type.as_str(),
+            relationship.target_entity_id
+        );
+
+        insert_archive(&mut tx, id, "relationship", &name, &relationship).await?;
+        sqlx::query("DELETE FROM knowledge_relationships WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        info!(relationship_id = %id, "Relationship archived");
+        Ok(true)
+    }
+
     // ========== Statistics ==========
 
     async fn get_stats(&self) -> Result<KnowledgeGraphStats> {
@@ -933,6 +1277,34 @@ struct EmbeddingRow {
     embedding: Vec<u8>,
 }
 
+/// Store a JSON snapshot of an archived entity or relationship
+async fn insert_archive<T: serde::Serialize>(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    id: &str,
+    kind: &str,
+    name: &str,
+    item: &T,
+) -> Result<()> {
+    let data = serde_json::to_string(item)
+        .map_err(|e| Error::Other(format!("Failed to serialize archived {}: {}", kind, e)))?;
+
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO knowledge_archive (id, kind, name, data, archived_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(id)
+    .bind(kind)
+    .bind(name)
+    .bind(&data)
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1725,4 +2097,119 @@ mod tests {
             .iter()
             .any(|(t, _)| *t == RelationshipType::Uses));
     }
+
+    #[tokio::test]
+    async fn test_merge_entities() {
+        let repo = setup_test_db().await;
+
+        let keep = KnowledgeEntity::new("Next.js", EntityType::Framework).with_confidence(0.6);
+        let duplicate = KnowledgeEntity::new("Next", EntityType::Framework)
+            .with_aliases(vec!["next js".into()])
+            .with_confidence(0.5);
+        let react = KnowledgeEntity::new("React", EntityType::Library);
+        let ssr = KnowledgeEntity::new("SSR", EntityType::Concept);
+        for entity in [&keep, &duplicate, &react, &ssr] {
+            repo.save_entity(entity).await.unwrap();
+        }
+
+        // Same edge on both: combined. Edge only on the duplicate: moved.
+        // Edge between the two: dropped.
+        for (source, target, weight) in [
+            (&keep.id, &react.id, 0.5),
+            (&duplicate.id, &react.id, 0.5),
+            (&duplicate.id, &ssr.id, 0.7),
+            (&duplicate.id, &keep.id, 0.5),
+        ] {
+            let rel = KnowledgeRelationship::new(source, target, RelationshipType::Uses)
+                .with_weight(weight);
+            repo.save_relationship(&rel).await.unwrap();
+        }
+        create_test_skill(&repo, "skill-1").await;
+        repo.link_skill_to_entity("skill-1", &duplicate.id, 0.9)
+            .await
+            .unwrap();
+
+        let merge = repo.merge_entities(&keep.id, &duplicate.id).await.unwrap();
+        assert_eq!(merge.relationships_moved, 1);
+        assert_eq!(merge.relationships_combined, 1);
+        assert_eq!(merge.relationships_dropped, 1);
+        assert_eq!(merge.skill_links_moved, 1);
+        assert!((merge.entity.confidence - 0.8).abs() < 1e-6);
+
+        assert!(repo.get_entity(&duplicate.id).await.unwrap().is_none());
+        let edge = repo
+            .get_relationship_between(&keep.id, &react.id, RelationshipType::Uses)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!((edge.weight - 0.75).abs() < 1e-6);
+        assert!(repo
+            .get_relationship_between(&keep.id, &ssr.id, RelationshipType::Uses)
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            repo.get_skills_for_entity(&keep.id).await.unwrap(),
+            vec!["skill-1".to_string()]
+        );
+
+        // Later ingestion of either old name resolves to the kept entity
+        let mut aliases = repo.list_aliases(&keep.id).await.unwrap();
+        aliases.sort();
+        assert_eq!(aliases, vec!["Next".to_string(), "next js".to_string()]);
+        for name in ["next", "next js"] {
+            let found = repo.get_entity_by_canonical_name(name).await.unwrap();
+            assert_eq!(found.map(|e| e.id), Some(keep.id.clone()));
+        }
+
+        assert!(matches!(
+            repo.merge_entities(&keep.id, &keep.id).await,
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            repo.merge_entities(&keep.id, &duplicate.id).await,
+            Err(Error::EntityNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_archive_entity_and_relationship() {
+        let repo = setup_test_db().await;
+
+        let rust = KnowledgeEntity::new("Rust", EntityType::Language);
+        let tokio = KnowledgeEntity::new("Tokio", EntityType::Library);
+        let serde = KnowledgeEntity::new("Serde", EntityType::Library);
+        for entity in [&rust, &tokio, &serde] {
+            repo.save_entity(entity).await.unwrap();
+        }
+        let uses_tokio = KnowledgeRelationship::new(&rust.id, &tokio.id, RelationshipType::Uses);
+        let uses_serde = KnowledgeRelationship::new(&rust.id, &serde.id, RelationshipType::Uses);
+        repo.save_relationship(&uses_tokio).await.unwrap();
+        repo.save_relationship(&uses_serde).await.unwrap();
+
+        assert!(repo.archive_relationship(&uses_serde.id).await.unwrap());
+        assert!(!repo.archive_relationship(&uses_serde.id).await.unwrap());
+        assert!(repo
+            .get_relationship(&uses_serde.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        assert!(repo.archive_entity(&tokio.id).await.unwrap());
+        assert!(repo.get_entity(&tokio.id).await.unwrap().is_none());
+        assert!(repo
+            .get_relationship(&uses_tokio.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(!repo.archive_entity(&tokio.id).await.unwrap());
+
+        let archived: Vec<(String, String)> =
+            sqlx::query_as("SELECT id, kind FROM knowledge_archive ORDER BY kind, id")
+                .fetch_all(&repo.pool)
+                .await
+                .unwrap();
+        assert_eq!(archived.len(), 3);
+        assert_eq!(archived[0], (tokio.id.clone(), "entity".to_string()));
+    }
 }
@@ -5,4 +5,6 @@
 pub mod chat;
 pub mod document;
 pub mod knowledge;
+pub mod network;
+pub mod sandbox;
 pub mod security;
@@ -0,0 +1,66 @@
+//! Network access policy
+//!
+//! Demiarch is local-first: projects, features, checkpoints, sync and the TUI
+//! never need the network. Offline mode (`demiarch --offline`,
+//! `network.offline = true` in config, or `DEMIARCH_OFFLINE=1`) makes every
+//! component that would otherwise talk to a remote service fail fast with
+//! [`Error::OfflineMode`] instead of attempting network IO.
+//!
+//! Code that performs network requests must call [`ensure_online`] first.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::{Error, Result};
+
+/// Environment variable that enables offline mode
+pub const OFFLINE_ENV: &str = "DEMIARCH_OFFLINE";
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable offline mode for this process
+///
+/// Frontends call this at startup with the combined CLI flag and config value.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::SeqCst);
+}
+
+/// Whether offline mode is enabled for this process or via the environment
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::SeqCst)
+        || std::env::var(OFFLINE_ENV)
+            .map(|v| env_flag_enabled(&v))
+            .unwrap_or(false)
+}
+
+/// Fail with [`Error::OfflineMode`] if offline mode is enabled
+///
+/// `operation` names what needed the network (e.g. "LLM completion").
+pub fn ensure_online(operation: &str) -> Result<()> {
+    if is_offline() {
+        return Err(Error::OfflineMode(operation.to_string()));
+    }
+    Ok(())
+}
+
+/// Interpret an environment flag value
+pub(crate) fn env_flag_enabled(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_flag_enabled() {
+        assert!(env_flag_enabled("1"));
+        assert!(env_flag_enabled(" TRUE "));
+        assert!(env_flag_enabled("on"));
+        assert!(!env_flag_enabled("0"));
+        assert!(!env_flag_enabled(""));
+        assert!(!env_flag_enabled("false"));
+    }
+}
@@ -0,0 +1,274 @@
+//! Sandboxed runner for external developer tools
+//!
+//! Runs formatters, linters and similar tools on behalf of the agents with a
+//! deliberately narrow surface:
+//! - Only explicitly allowed programs can be run, and never through a shell
+//! - The environment is cleared except for a few variables tools need to start
+//! - Input is passed on stdin and results are read from stdout, so tools are
+//!   never handed paths they could write to
+//! - Every run has a wall-clock timeout and captured output is capped
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::error::{Error, Result};
+
+/// Default wall-clock timeout for a single tool run
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default cap on captured stdout/stderr, per stream
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 4 * 1024 * 1024;
+
+/// Environment variables passed through to sandboxed tools
+const PASSTHROUGH_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "LANG",
+    "LC_ALL",
+    "SYSTEMROOT",
+    "TEMP",
+    "TMP",
+];
+
+/// Output of a sandboxed run
+#[derive(Debug, Clone, Default)]
+pub struct SandboxOutput {
+    /// Exit code (None if killed or terminated by a signal)
+    pub exit_code: Option<i32>,
+    /// Captured stdout (lossy UTF-8, possibly truncated)
+    pub stdout: String,
+    /// Captured stderr (lossy UTF-8, possibly truncated)
+    pub stderr: String,
+    /// Whether the run was killed for exceeding the timeout
+    pub timed_out: bool,
+    /// Whether stdout or stderr was truncated
+    pub truncated: bool,
+}
+
+impl SandboxOutput {
+    /// Whether the tool exited with status 0
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Runs allowlisted programs with restricted environment and I/O
+#[derive(Debug, Clone)]
+pub struct SandboxedRunner {
+    working_dir: PathBuf,
+    allowed_programs: Vec<String>,
+    search_paths: Vec<PathBuf>,
+    timeout: Duration,
+    max_output_bytes: usize,
+}
+
+impl SandboxedRunner {
+    /// Create a runner whose tools start in `working_dir`
+    ///
+    /// No programs are allowed until added with [`Self::allow`].
+    pub fn new(working_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            working_dir: working_dir.into(),
+            allowed_programs: Vec::new(),
+            search_paths: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+        }
+    }
+
+    /// Allow a program (by bare name, e.g. "rustfmt") to be run
+    pub fn allow(mut self, program: impl Into<String>) -> Self {
+        self.allowed_programs.push(program.into());
+        self
+    }
+
+    /// Search this directory before PATH (e.g. `node_modules/.bin`)
+    pub fn with_search_path(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.search_paths.push(dir.into());
+        self
+    }
+
+    /// Set the wall-clock timeout for each run
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the cap on captured output per stream
+    pub fn with_max_output_bytes(mut self, max: usize) -> Self {
+        self.max_output_bytes = max;
+        self
+    }
+
+    /// Directory tools are started in
+    pub fn working_dir(&self) -> &Path {
+        &self.working_dir
+    }
+
+    /// Whether a program is allowed to run
+    pub fn is_allowed(&self, program: &str) -> bool {
+        self.allowed_programs.iter().any(|p| p == program)
+    }
+
+    /// Locate an allowed program in the search paths, then PATH
+    pub fn resolve(&self, program: &str) -> Option<PathBuf> {
+        if !self.is_allowed(program) {
+            return None;
+        }
+        let path_dirs = std::env::var_os("PATH")
+            .map(|p| std::env::split_paths(&p).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        self.search_paths
+            .iter()
+            .chain(path_dirs.iter())
+            .flat_map(|dir| executable_candidates(dir, program))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Run an allowed program, feeding `stdin` and capturing its output
+    ///
+    /// A non-zero exit is not an error; callers inspect [`SandboxOutput`].
+    /// Errors are returned only if the program is not allowed or cannot be
+    /// started.
+    pub async fn run(
+        &self,
+        program: &str,
+        args: &[String],
+        stdin: Option<&str>,
+    ) -> Result<SandboxOutput> {
+        if !self.is_allowed(program) {
+            return Err(Error::InvalidInput(format!(
+                "Program '{}' is not allowed in the sandbox",
+                program
+            )));
+        }
+        let executable = self
+            .resolve(program)
+            .ok_or_else(|| Error::NotFound(format!("Program not found: {}", program)))?;
+
+        let mut command = Command::new(&executable);
+        command
+            .args(args)
+            .current_dir(&self.working_dir)
+            .env_clear()
+            .envs(passthrough_env())
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = command.spawn()?;
+
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            let input = input.to_string();
+            // Write from a task so a tool that fills stdout before reading all
+            // of stdin cannot deadlock us
+            tokio::spawn(async move {
+                let _ = pipe.write_all(input.as_bytes()).await;
+                let _ = pipe.shutdown().await;
+            });
+        }
+
+        let output = match tokio::time::timeout(self.timeout, child.wait_with_output()).await {
+            Ok(output) => output?,
+            Err(_) => {
+                // Dropping the child future kills the process (kill_on_drop)
+                return Ok(SandboxOutput {
+                    timed_out: true,
+                    ..Default::default()
+                });
+            }
+        };
+
+        let (stdout, stdout_truncated) = capped(&output.stdout, self.max_output_bytes);
+        let (stderr, stderr_truncated) = capped(&output.stderr, self.max_output_bytes);
+
+        Ok(SandboxOutput {
+            exit_code: output.status.code(),
+            stdout,
+            stderr,
+            timed_out: false,
+            truncated: stdout_truncated || stderr_truncated,
+        })
+    }
+}
+
+/// Environment variables inherited by sandboxed tools
+fn passthrough_env() -> Vec<(&'static str, OsString)> {
+    PASSTHROUGH_ENV
+        .iter()
+        .filter_map(|key| std::env::var_os(key).map(|value| (*key, value)))
+        .collect()
+}
+
+/// Candidate executable paths for a program in a directory
+fn executable_candidates(dir: &Path, program: &str) -> Vec<PathBuf> {
+    if cfg!(windows) {
+        ["exe", "cmd", "bat"]
+            .iter()
+            .map(|ext| dir.join(format!("{}.{}", program, ext)))
+            .collect()
+    } else {
+        vec![dir.join(program)]
+    }
+}
+
+/// Decode output as lossy UTF-8, truncating to `max` bytes
+fn capped(bytes: &[u8], max: usize) -> (String, bool) {
+    if bytes.len() <= max {
+        (String::from_utf8_lossy(bytes).into_owned(), false)
+    } else {
+        (String::from_utf8_lossy(&bytes[..max]).into_owned(), true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_allowed_programs_resolve() {
+        let runner = SandboxedRunner::new(".");
+        assert!(!runner.is_allowed("sh"));
+        assert!(runner.resolve("sh").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_program_is_rejected() {
+        let runner = SandboxedRunner::new(".").allow("rustfmt");
+        let result = runner.run("sh", &["-c".to_string()], None).await;
+        assert!(matches!(result, Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_capped_output() {
+        assert_eq!(capped(b"hello", 10), ("hello".to_string(), false));
+        assert_eq!(capped(b"hello", 3), ("hel".to_string(), true));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_pipes_stdin_with_cleared_env() {
+        std::env::set_var("DEMIARCH_SANDBOX_SECRET", "leak");
+        let runner = SandboxedRunner::new(std::env::temp_dir())
+            .allow("cat")
+            .allow("env");
+
+        let output = runner.run("cat", &[], Some("formatted\n")).await.unwrap();
+        assert!(output.success());
+        assert_eq!(output.stdout, "formatted\n");
+
+        let output = runner.run("env", &[], None).await.unwrap();
+        assert!(!output.stdout.contains("DEMIARCH_SANDBOX_SECRET"));
+    }
+}
@@ -11,6 +11,12 @@
 //! - Dynamic model routing
 //! - Lifecycle hooks
 //! - Encrypted API key storage (AES-256-GCM)
+//! - Localization of user-facing strings
+//! - Paginated listings for large projects
+//! - Progress reporting for long-running operations
+//! - Notifications when long-running operations finish
+//! - `demiarch://` deep links into the desktop app
+//! - Voice note transcription
 
 pub mod agents;
 pub mod api;
@@ -19,18 +25,25 @@ pub mod commands;
 pub mod config;
 pub mod context;
 pub mod cost;
+pub mod deeplink;
 pub mod domain;
 pub mod error;
+pub mod events;
 pub mod hooks;
+pub mod i18n;
 pub mod image;
 pub mod infrastructure;
 pub mod llm;
+pub mod notify;
+pub mod pagination;
+pub mod progress;
 pub mod routing;
 pub mod skills;
 pub mod storage;
+pub mod transcription;
 pub mod visualization;
 
-pub use error::{Error, Result};
+pub use error::{Error, ErrorCategory, ErrorPayload, Result};
 
 /// Re-export commonly used types
 pub mod prelude {
@@ -54,12 +67,12 @@ pub mod security {
 pub mod knowledge {
     pub use crate::domain::knowledge::{
         CognifyResult, ContextEnricher, EnrichedContext, EnrichmentConfig, EnrichmentStats,
-        EntityContext, EntityExtractor, EntityRelevance, EntitySearchResult, EntityType,
-        EntityWithDistance, ExtractionResult, GraphSearchQuery, HybridRanker, HybridRankingConfig,
-        HybridSearchResult, KnowledgeEntity, KnowledgeEvent, KnowledgeGraphRepository,
-        KnowledgeGraphService, KnowledgeGraphStats, KnowledgeRelationship, PathRelationship,
-        PathStep, RelatedEntityMatch, RelationshipContext, RelationshipEvidence, RelationshipType,
-        ScoreBreakdown, TraversalDirection,
+        EntityContext, EntityExtractor, EntityMerge, EntityRelevance, EntitySearchResult,
+        EntityType, EntityWithDistance, ExtractionResult, GraphSearchQuery, HybridRanker,
+        HybridRankingConfig, HybridSearchResult, KnowledgeEntity, KnowledgeEvent,
+        KnowledgeGraphRepository, KnowledgeGraphService, KnowledgeGraphStats,
+        KnowledgeRelationship, PathRelationship, PathStep, RelatedEntityMatch, RelationshipContext,
+        RelationshipEvidence, RelationshipType, ScoreBreakdown, TraversalDirection,
     };
     pub use crate::infrastructure::knowledge::SqliteKnowledgeGraphRepository;
 }
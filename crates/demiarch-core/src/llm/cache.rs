@@ -0,0 +1,369 @@
+//! Response cache for chat completions
+//!
+//! Non-streaming completions can be cached in SQLite keyed by a hash of the
+//! model, temperature and normalized prompt. Repeated identical calls (reruns
+//! of deterministic planning steps, document regeneration, tests) are then
+//! served locally instead of hitting the API.
+//!
+//! Entries expire after a TTL, and the least recently used entries are
+//! evicted once the cache grows past its size cap. Cache hits report zero
+//! token usage since nothing was spent; the tokens they saved are reported by
+//! [`ResponseCache::stats`].
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+
+use crate::config::CacheConfig;
+use crate::error::Result;
+use crate::storage::Database;
+
+use super::types::{FinishReason, LlmResponse, Message};
+
+/// Default time-to-live for cached responses (7 days)
+pub const DEFAULT_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Default size cap for cached response content (50 MB)
+pub const DEFAULT_MAX_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Statistics about the response cache
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Number of cached responses (including expired ones not yet pruned)
+    pub entries: u64,
+    /// Entries older than the TTL
+    pub expired: u64,
+    /// Total size of cached content in bytes
+    pub total_bytes: u64,
+    /// Number of times a cached response was served
+    pub hits: u64,
+    /// Tokens that would have been spent on the served hits
+    pub tokens_saved: u64,
+    /// When the oldest entry was cached
+    pub oldest: Option<DateTime<Utc>>,
+    /// When the newest entry was cached
+    pub newest: Option<DateTime<Utc>>,
+}
+
+/// SQLite-backed cache of chat completion responses
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    db: Database,
+    ttl: Duration,
+    max_bytes: u64,
+}
+
+impl ResponseCache {
+    /// Create a cache with the default TTL and size cap
+    pub fn new(db: Database) -> Self {
+        Self {
+            db,
+            ttl: Duration::seconds(DEFAULT_TTL_SECS as i64),
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+
+    /// Create a cache from configuration, or `None` if caching is disabled
+    pub fn from_config(db: &Database, config: &CacheConfig) -> Option<Self> {
+        config.enabled.then(|| {
+            Self::new(db.clone())
+                .with_ttl_secs(config.ttl_secs)
+                .with_max_bytes(config.max_size_mb * 1024 * 1024)
+        })
+    }
+
+    /// Set how long entries stay valid
+    pub fn with_ttl_secs(mut self, secs: u64) -> Self {
+        self.ttl = Duration::seconds(secs.min(i64::MAX as u64) as i64);
+        self
+    }
+
+    /// Set the cap on total cached content size
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Compute the cache key for a request
+    ///
+    /// Line endings and trailing whitespace are normalized so prompts that
+    /// differ only in formatting share an entry.
+    pub fn key(model: &str, temperature: f32, messages: &[Message]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(model.as_bytes());
+        hasher.update([0]);
+        hasher.update(format!("{:.3}", temperature).as_bytes());
+        for message in messages {
+            hasher.update([0]);
+            hasher.update(message.role.to_string().as_bytes());
+            hasher.update([0]);
+            hasher.update(normalize_prompt(&message.content).as_bytes());
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Look up a cached response, recording the hit
+    ///
+    /// Expired entries are treated as misses.
+    pub async fn get(&self, key: &str) -> Result<Option<LlmResponse>> {
+        let row = sqlx::query(
+            "SELECT content, response_model, finish_reason FROM llm_cache WHERE key = ? AND created_at > ?",
+        )
+        .bind(key)
+        .bind(Utc::now() - self.ttl)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        sqlx::query("UPDATE llm_cache SET hits = hits + 1, last_used_at = ? WHERE key = ?")
+            .bind(Utc::now())
+            .bind(key)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(Some(LlmResponse {
+            content: row.get("content"),
+            model: row.get("response_model"),
+            tokens_used: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            finish_reason: parse_finish_reason(row.get("finish_reason")),
+        }))
+    }
+
+    /// Store a response, then prune expired and over-cap entries
+    pub async fn put(
+        &self,
+        key: &str,
+        model: &str,
+        temperature: f32,
+        response: &LlmResponse,
+    ) -> Result<()> {
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO llm_cache (key, model, temperature, content, response_model, input_tokens, output_tokens, finish_reason, size_bytes, hits, created_at, last_used_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 0, ?, ?)
+            ON CONFLICT(key) DO UPDATE SET
+                content = excluded.content,
+                response_model = excluded.response_model,
+                input_tokens = excluded.input_tokens,
+                output_tokens = excluded.output_tokens,
+                finish_reason = excluded.finish_reason,
+                size_bytes = excluded.size_bytes,
+                created_at = excluded.created_at,
+                last_used_at = excluded.last_used_at
+            "#,
+        )
+        .bind(key)
+        .bind(model)
+        .bind(temperature as f64)
+        .bind(&response.content)
+        .bind(&response.model)
+        .bind(response.input_tokens as i64)
+        .bind(response.output_tokens as i64)
+        .bind(response.finish_reason.to_string())
+        .bind(response.content.len() as i64)
+        .bind(now)
+        .bind(now)
+        .execute(self.db.pool())
+        .await?;
+
+        self.prune().await?;
+        Ok(())
+    }
+
+    /// Remove expired entries and evict least recently used entries over the cap
+    ///
+    /// Returns the number of entries removed.
+    pub async fn prune(&self) -> Result<u64> {
+        let mut removed = sqlx::query("DELETE FROM llm_cache WHERE created_at <= ?")
+            .bind(Utc::now() - self.ttl)
+            .execute(self.db.pool())
+            .await?
+            .rows_affected();
+
+        let total: i64 = sqlx::query("SELECT COALESCE(SUM(size_bytes), 0) AS total FROM llm_cache")
+            .fetch_one(self.db.pool())
+            .await?
+            .get("total");
+        let mut excess = (total as u64).saturating_sub(self.max_bytes);
+        if excess == 0 {
+            return Ok(removed);
+        }
+
+        let rows = sqlx::query("SELECT key, size_bytes FROM llm_cache ORDER BY last_used_at ASC")
+            .fetch_all(self.db.pool())
+            .await?;
+        for row in rows {
+            if excess == 0 {
+                break;
+            }
+            let size: i64 = row.get("size_bytes");
+            sqlx::query("DELETE FROM llm_cache WHERE key = ?")
+                .bind(row.get::<String, _>("key"))
+                .execute(self.db.pool())
+                .await?;
+            excess = excess.saturating_sub(size as u64);
+            removed += 1;
+        }
+
+        Ok(removed)
+    }
+
+    /// Get cache statistics
+    pub async fn stats(&self) -> Result<CacheStats> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                COUNT(*) AS entries,
+                COALESCE(SUM(CASE WHEN created_at <= ? THEN 1 ELSE 0 END), 0) AS expired,
+                COALESCE(SUM(size_bytes), 0) AS total_bytes,
+                COALESCE(SUM(hits), 0) AS hits,
+                COALESCE(SUM(hits * (input_tokens + output_tokens)), 0) AS tokens_saved,
+                MIN(created_at) AS oldest,
+                MAX(created_at) AS newest
+            FROM llm_cache
+            "#,
+        )
+        .bind(Utc::now() - self.ttl)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(CacheStats {
+            entries: row.get::<i64, _>("entries") as u64,
+            expired: row.get::<i64, _>("expired") as u64,
+            total_bytes: row.get::<i64, _>("total_bytes") as u64,
+            hits: row.get::<i64, _>("hits") as u64,
+            tokens_saved: row.get::<i64, _>("tokens_saved") as u64,
+            oldest: row.get("oldest"),
+            newest: row.get("newest"),
+        })
+    }
+
+    /// Remove all cached responses, returning the number removed
+    pub async fn clear(&self) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM llm_cache")
+            .execute(self.db.pool())
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// Normalize prompt text for hashing
+fn normalize_prompt(content: &str) -> String {
+    content
+        .replace("\r\n", "\n")
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Parse a stored finish reason
+fn parse_finish_reason(s: String) -> FinishReason {
+    serde_json::from_value(serde_json::Value::String(s)).unwrap_or(FinishReason::Unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(content: &str) -> LlmResponse {
+        LlmResponse {
+            content: content.to_string(),
+            model: "test/model".to_string(),
+            tokens_used: 30,
+            input_tokens: 20,
+            output_tokens: 10,
+            finish_reason: FinishReason::Stop,
+        }
+    }
+
+    #[test]
+    fn test_key_normalizes_whitespace() {
+        let a = ResponseCache::key("m", 0.0, &[Message::user("plan this\r\nnow  \n")]);
+        let b = ResponseCache::key("m", 0.0, &[Message::user("plan this\nnow")]);
+        assert_eq!(a, b);
+
+        assert_ne!(
+            a,
+            ResponseCache::key("m", 0.7, &[Message::user("plan this\nnow")])
+        );
+        assert_ne!(
+            a,
+            ResponseCache::key("other", 0.0, &[Message::user("plan this\nnow")])
+        );
+        assert_ne!(
+            a,
+            ResponseCache::key("m", 0.0, &[Message::system("plan this\nnow")])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_put_get_stats_clear() {
+        let db = Database::in_memory().await.unwrap();
+        let cache = ResponseCache::new(db);
+        let key = ResponseCache::key("test/model", 0.0, &[Message::user("hi")]);
+
+        assert!(cache.get(&key).await.unwrap().is_none());
+        cache
+            .put(&key, "test/model", 0.0, &response("hello"))
+            .await
+            .unwrap();
+
+        let hit = cache.get(&key).await.unwrap().unwrap();
+        assert_eq!(hit.content, "hello");
+        assert_eq!(hit.finish_reason, FinishReason::Stop);
+        assert_eq!(hit.tokens_used, 0);
+
+        let stats = cache.stats().await.unwrap();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.tokens_saved, 30);
+        assert_eq!(stats.total_bytes, 5);
+
+        assert_eq!(cache.clear().await.unwrap(), 1);
+        assert!(cache.get(&key).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_miss_and_prune() {
+        let db = Database::in_memory().await.unwrap();
+        let cache = ResponseCache::new(db.clone());
+        cache
+            .put("k", "test/model", 0.0, &response("stale"))
+            .await
+            .unwrap();
+
+        let expired = ResponseCache::new(db).with_ttl_secs(0);
+        assert!(expired.get("k").await.unwrap().is_none());
+        assert_eq!(expired.stats().await.unwrap().expired, 1);
+        assert_eq!(expired.prune().await.unwrap(), 1);
+        assert_eq!(cache.stats().await.unwrap().entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_size_cap_evicts_least_recently_used() {
+        let db = Database::in_memory().await.unwrap();
+        let cache = ResponseCache::new(db).with_max_bytes(10);
+
+        cache
+            .put("old", "test/model", 0.0, &response("aaaaaa"))
+            .await
+            .unwrap();
+        cache
+            .put("new", "test/model", 0.0, &response("bbbbbb"))
+            .await
+            .unwrap();
+
+        assert!(cache.get("old").await.unwrap().is_none());
+        assert!(cache.get("new").await.unwrap().is_some());
+    }
+}
@@ -5,6 +5,7 @@
 //! - Cost tracking integration
 //! - Model fallback with automatic retry
 //! - Rate limit handling with exponential backoff
+//! - Optional response caching for non-streaming completions
 
 use std::sync::Arc;
 use std::time::Duration;
@@ -15,9 +16,11 @@ use tracing::{debug, error, info, warn};
 use crate::config::LlmConfig;
 use crate::cost::{CostTracker, TokenUsage};
 use crate::error::{Error, Result};
+use crate::infrastructure::network;
 
+use super::cache::ResponseCache;
 use super::streaming::{parse_sse_line, StreamEvent};
-use super::types::{ChatRequest, ChatResponse, LlmResponse, Message};
+use super::types::{ChatRequest, ChatResponse, FinishReason, LlmResponse, Message};
 
 /// OpenRouter API base URL
 const OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api/v1";
@@ -44,6 +47,8 @@ pub struct LlmClient {
     base_url: String,
     /// Cost tracker for recording usage (optional)
     cost_tracker: Option<Arc<CostTracker>>,
+    /// Cache for completion responses (optional)
+    response_cache: Option<ResponseCache>,
 }
 
 impl std::fmt::Debug for LlmClient {
@@ -52,6 +57,7 @@ impl std::fmt::Debug for LlmClient {
             .field("base_url", &self.base_url)
             .field("default_model", &self.config.default_model)
             .field("cost_tracker", &self.cost_tracker.is_some())
+            .field("response_cache", &self.response_cache.is_some())
             .finish()
     }
 }
@@ -62,6 +68,7 @@ pub struct LlmClientBuilder {
     api_key: Option<String>,
     base_url: Option<String>,
     cost_tracker: Option<Arc<CostTracker>>,
+    response_cache: Option<ResponseCache>,
     timeout_secs: Option<u64>,
 }
 
@@ -79,6 +86,7 @@ impl LlmClientBuilder {
             api_key: None,
             base_url: None,
             cost_tracker: None,
+            response_cache: None,
             timeout_secs: None,
         }
     }
@@ -107,6 +115,12 @@ impl LlmClientBuilder {
         self
     }
 
+    /// Set the response cache
+    pub fn response_cache(mut self, cache: ResponseCache) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
     /// Set the request timeout
     pub fn timeout_secs(mut self, secs: u64) -> Self {
         self.timeout_secs = Some(secs);
@@ -135,6 +149,7 @@ impl LlmClientBuilder {
                 .base_url
                 .unwrap_or_else(|| OPENROUTER_BASE_URL.to_string()),
             cost_tracker: self.cost_tracker,
+            response_cache: self.response_cache,
         })
     }
 }
@@ -159,6 +174,12 @@ impl LlmClient {
         self
     }
 
+    /// Set the cache for completion responses
+    pub fn with_response_cache(mut self, cache: ResponseCache) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
     /// Get the default model from configuration
     pub fn default_model(&self) -> &str {
         &self.config.default_model
@@ -172,7 +193,8 @@ impl LlmClient {
     /// Make a chat completion request
     ///
     /// Sends messages to the specified model and returns the response.
-    /// Records cost if a cost tracker is configured.
+    /// Records cost if a cost tracker is configured. If a response cache is
+    /// configured, identical requests are served from it.
     pub async fn complete(
         &self,
         messages: Vec<Message>,
@@ -180,6 +202,21 @@ impl LlmClient {
     ) -> Result<LlmResponse> {
         let model = model.unwrap_or(&self.config.default_model);
 
+        let cache_key = self
+            .response_cache
+            .as_ref()
+            .map(|_| ResponseCache::key(model, self.config.temperature, &messages));
+        if let (Some(cache), Some(key)) = (&self.response_cache, &cache_key) {
+            match cache.get(key).await {
+                Ok(Some(response)) => {
+                    debug!(model = %model, "Serving chat completion from cache");
+                    return Ok(response);
+                }
+                Ok(None) => {}
+                Err(e) => warn!(error = %e, "Response cache lookup failed"),
+            }
+        }
+
         // Check budget before making request
         if let Some(tracker) = &self.cost_tracker {
             if tracker.is_over_limit() {
@@ -193,7 +230,22 @@ impl LlmClient {
             .with_temperature(self.config.temperature)
             .with_max_tokens(self.config.max_tokens);
 
-        self.execute_request(&request).await
+        let response = self.execute_request(&request).await?;
+
+        // Only complete answers are cached; truncated or filtered ones may
+        // succeed on a retry
+        if let (Some(cache), Some(key)) = (&self.response_cache, &cache_key) {
+            if response.finish_reason == FinishReason::Stop {
+                if let Err(e) = cache
+                    .put(key, model, self.config.temperature, &response)
+                    .await
+                {
+                    warn!(error = %e, "Failed to cache chat completion");
+                }
+            }
+        }
+
+        Ok(response)
     }
 
     /// Make a chat completion request with automatic fallback
@@ -295,6 +347,7 @@ impl LlmClient {
 
     /// Send a single request to the API
     async fn send_request(&self, request: &ChatRequest) -> Result<LlmResponse> {
+        network::ensure_online("LLM completion")?;
         let url = format!("{}/chat/completions", self.base_url);
 
         debug!(
@@ -334,6 +387,7 @@ impl LlmClient {
         &self,
         request: ChatRequest,
     ) -> Result<impl futures_core::Stream<Item = Result<StreamEvent>>> {
+        network::ensure_online("LLM streaming completion")?;
         let url = format!("{}/chat/completions", self.base_url);
 
         debug!(
@@ -481,6 +535,7 @@ impl LlmClient {
         &self,
         request: &super::types::EmbeddingRequest,
     ) -> Result<super::types::Embedding> {
+        network::ensure_online("Embedding generation")?;
         let url = format!("{}/embeddings", self.base_url);
 
         debug!(
@@ -542,6 +597,7 @@ impl LlmClient {
         &self,
         request: &super::types::EmbeddingRequest,
     ) -> Result<Vec<super::types::Embedding>> {
+        network::ensure_online("Embedding generation")?;
         let url = format!("{}/embeddings", self.base_url);
 
         debug!(
@@ -7,14 +7,20 @@
 //! - Model fallback with automatic retry
 //! - Streaming response support
 //! - Embedding generation for semantic search
+//! - Optional SQLite response cache for repeated identical calls
+//! - Prompt token counting with the model's tokenizer
 
+mod cache;
 mod client;
 mod streaming;
+pub mod tokenizer;
 mod types;
 
+pub use cache::{CacheStats, ResponseCache};
 pub use client::LlmClient;
 pub use streaming::{StreamChunk, StreamEvent};
 pub use types::{
     ChatRequest, ChatResponse, Choice, Embedding, EmbeddingData, EmbeddingInput, EmbeddingRequest,
-    EmbeddingResponse, EmbeddingUsage, FinishReason, LlmResponse, Message, MessageRole, Usage,
+    EmbeddingResponse, EmbeddingUsage, FinishReason, LlmResponse, Message, MessageRole,
+    StreamOptions, Usage,
 };
@@ -4,7 +4,7 @@
 
 use serde::Deserialize;
 
-use super::types::{FinishReason, MessageRole};
+use super::types::{FinishReason, MessageRole, Usage};
 
 /// A delta update in a streaming response
 #[derive(Debug, Clone, Deserialize)]
@@ -39,6 +39,9 @@ pub struct StreamChunk {
     pub model: String,
     /// List of streaming choices
     pub choices: Vec<StreamChoice>,
+    /// Token usage (final chunk only, when requested)
+    #[serde(default)]
+    pub usage: Option<Usage>,
 }
 
 impl StreamChunk {
@@ -115,6 +118,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_sse_usage_chunk() {
+        let line = r#"data: {"id":"gen-123","object":"chat.completion.chunk","created":1234567890,"model":"test","choices":[],"usage":{"prompt_tokens":6200,"completion_tokens":80,"total_tokens":6280}}"#;
+
+        match parse_sse_line(line).unwrap() {
+            StreamEvent::Chunk(chunk) => {
+                assert_eq!(chunk.content(), None);
+                assert_eq!(chunk.usage.map(|u| u.prompt_tokens), Some(6200));
+            }
+            _ => panic!("Expected Chunk event"),
+        }
+    }
+
     #[test]
     fn test_parse_sse_done() {
         let line = "data: [DONE]";
@@ -162,6 +178,7 @@ mod tests {
                 },
                 finish_reason: None,
             }],
+            usage: None,
         };
 
         assert_eq!(chunk.content(), Some("Hello"));
@@ -0,0 +1,77 @@
+//! Token counting with BPE tokenizers
+//!
+//! OpenAI models are counted with their own encoding: `o200k_base` for the
+//! GPT-4o and o-series families, `cl100k_base` for older ones. Other
+//! providers do not publish their tokenizers, so their prompts are counted
+//! with `cl100k_base`, which stays within a few percent for English text
+//! and code. Counts fall back to the ~4 characters per token estimate if an
+//! encoding cannot be loaded.
+
+use std::sync::OnceLock;
+
+use tiktoken_rs::CoreBPE;
+
+use super::types::Message;
+use crate::context::estimate_tokens;
+
+/// Tokens every chat message adds for its role and separators
+const TOKENS_PER_MESSAGE: usize = 3;
+
+/// Tokens that prime the assistant's reply
+const REPLY_PRIMING_TOKENS: usize = 3;
+
+static O200K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+static CL100K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+
+/// The encoding `model` is counted with
+fn encoding(model: &str) -> Option<&'static CoreBPE> {
+    let name = model.rsplit('/').next().unwrap_or(model);
+    let o200k = name.starts_with("gpt-4o")
+        || name.starts_with("gpt-4.1")
+        || name.starts_with("gpt-5")
+        || name.starts_with("o1")
+        || name.starts_with("o3")
+        || name.starts_with("o4");
+    if o200k {
+        O200K
+            .get_or_init(|| tiktoken_rs::o200k_base().ok())
+            .as_ref()
+    } else {
+        CL100K
+            .get_or_init(|| tiktoken_rs::cl100k_base().ok())
+            .as_ref()
+    }
+}
+
+/// Tokens in `text` for `model`
+pub fn count_tokens(model: &str, text: &str) -> usize {
+    match encoding(model) {
+        Some(bpe) => bpe.encode_with_special_tokens(text).len(),
+        None => estimate_tokens(text),
+    }
+}
+
+/// Prompt tokens `messages` take for `model`, message framing included
+pub fn count_message_tokens(model: &str, messages: &[Message]) -> usize {
+    messages
+        .iter()
+        .map(|m| TOKENS_PER_MESSAGE + count_tokens(model, &m.content))
+        .sum::<usize>()
+        + REPLY_PRIMING_TOKENS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_with_model_encoding() {
+        assert_eq!(count_tokens("openai/gpt-4o", "hello world"), 2);
+        assert_eq!(count_tokens("anthropic/claude-sonnet-4-20250514", ""), 0);
+
+        let messages = vec![Message::system("Be brief."), Message::user("hello world")];
+        let tokens = count_message_tokens("openai/gpt-4o-mini", &messages);
+        let content = count_tokens("openai/gpt-4o-mini", "Be brief.") + 2;
+        assert_eq!(tokens, 2 * TOKENS_PER_MESSAGE + content + REPLY_PRIMING_TOKENS);
+    }
+}
@@ -76,6 +76,16 @@ pub struct ChatRequest {
     /// Enable streaming responses
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
+    /// Streaming options (asks for usage in the final chunk)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+}
+
+/// Options for streaming responses
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct StreamOptions {
+    /// Report token usage in the final chunk
+    pub include_usage: bool,
 }
 
 impl ChatRequest {
@@ -87,6 +97,7 @@ impl ChatRequest {
             temperature: None,
             max_tokens: None,
             stream: None,
+            stream_options: None,
         }
     }
 
@@ -105,6 +116,9 @@ impl ChatRequest {
     /// Enable streaming
     pub fn with_streaming(mut self, stream: bool) -> Self {
         self.stream = Some(stream);
+        self.stream_options = stream.then_some(StreamOptions {
+            include_usage: true,
+        });
         self
     }
 }
@@ -0,0 +1,564 @@
+//! Notifications for long-running operations
+//!
+//! Generations can run for minutes, often in a background job worker. When
+//! one finishes, a budget trips, spend is forecast to pass the monthly limit
+//! or a generation leaves files that conflict
+//! with the project, the user should hear about it even if they are looking
+//! at another window.
+//!
+//! A [`NotificationFeed`] turns database changes into [`Notification`]s, and a
+//! [`Notifier`] hands them to a [`NotificationSink`]: the CLI rings the
+//! terminal bell, the GUI shows a native desktop notification. Both read the
+//! same feed, so they announce the same events.
+
+use std::fmt;
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::config::{CostConfig, NotificationsConfig};
+use crate::cost::forecast::{self, SpendForecast};
+use crate::storage::Database;
+use crate::visualization::glyphs;
+use crate::Result;
+
+/// Longest description shown in a notification body
+const MAX_DESCRIPTION_CHARS: usize = 80;
+
+/// What a notification announces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    GenerationCompleted,
+    GenerationFailed,
+    BudgetExceeded,
+    ForecastOverLimit,
+    ConflictsDetected,
+}
+
+impl NotificationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GenerationCompleted => "generation_completed",
+            Self::GenerationFailed => "generation_failed",
+            Self::BudgetExceeded => "budget_exceeded",
+            Self::ForecastOverLimit => "forecast_over_limit",
+            Self::ConflictsDetected => "conflicts_detected",
+        }
+    }
+
+    /// Whether the event needs the user's attention rather than just news
+    pub fn is_alert(&self) -> bool {
+        !matches!(self, Self::GenerationCompleted)
+    }
+}
+
+/// A single user-facing notification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    pub project_id: Option<String>,
+    pub generation_id: Option<String>,
+}
+
+impl Notification {
+    /// A generation finished and its files are ready for review
+    pub fn generation_completed(
+        generation_id: &str,
+        project_id: Option<String>,
+        description: &str,
+    ) -> Self {
+        Self {
+            kind: NotificationKind::GenerationCompleted,
+            title: "Generation completed".to_string(),
+            body: truncate(description),
+            project_id,
+            generation_id: Some(generation_id.to_string()),
+        }
+    }
+
+    /// A generation stopped with failed tasks
+    pub fn generation_failed(
+        generation_id: &str,
+        project_id: Option<String>,
+        description: &str,
+    ) -> Self {
+        Self {
+            kind: NotificationKind::GenerationFailed,
+            title: "Generation failed".to_string(),
+            body: truncate(description),
+            project_id,
+            generation_id: Some(generation_id.to_string()),
+        }
+    }
+
+    /// Today's spend reached the daily limit
+    pub fn budget_exceeded(spent: f64, limit: f64) -> Self {
+        Self {
+            kind: NotificationKind::BudgetExceeded,
+            title: "Daily budget reached".to_string(),
+            body: format!("Spent ${:.2} of the ${:.2} daily limit", spent, limit),
+            project_id: None,
+            generation_id: None,
+        }
+    }
+
+    /// This month's spend is projected to pass the monthly limit
+    pub fn forecast_over_limit(forecast: &SpendForecast) -> Self {
+        let reached = forecast
+            .limit_reached_on
+            .map(|day| format!(", reached around {}", day.format("%b %-d")))
+            .unwrap_or_default();
+        Self {
+            kind: NotificationKind::ForecastOverLimit,
+            title: "Monthly budget at risk".to_string(),
+            body: format!(
+                "Projected ${:.2} of the ${:.2} monthly limit{}",
+                forecast.projected_usd, forecast.monthly_limit_usd, reached
+            ),
+            project_id: forecast.project_id.clone(),
+            generation_id: None,
+        }
+    }
+
+    /// A generation wants to change files that already exist
+    pub fn conflicts_detected(
+        generation_id: &str,
+        project_id: Option<String>,
+        files: usize,
+    ) -> Self {
+        Self {
+            kind: NotificationKind::ConflictsDetected,
+            title: "Conflicts need review".to_string(),
+            body: format!(
+                "{} existing file(s) changed by generation {}",
+                files,
+                &generation_id[..8.min(generation_id.len())]
+            ),
+            project_id,
+            generation_id: Some(generation_id.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.title, self.body)
+    }
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() <= MAX_DESCRIPTION_CHARS {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_DESCRIPTION_CHARS - 3).collect();
+    format!("{}...", cut.trim_end())
+}
+
+/// Receiver of notifications
+///
+/// Implemented by [`TerminalBell`]; the GUI implements it with native desktop
+/// notifications.
+pub trait NotificationSink: Send + Sync {
+    fn notify(&self, notification: &Notification);
+}
+
+/// Cloneable handle notifications are dispatched through
+#[derive(Clone, Default)]
+pub struct Notifier {
+    sink: Option<Arc<dyn NotificationSink>>,
+}
+
+impl fmt::Debug for Notifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notifier")
+            .field("enabled", &self.is_enabled())
+            .finish()
+    }
+}
+
+impl Notifier {
+    /// A handle that discards all notifications
+    pub fn silent() -> Self {
+        Self::default()
+    }
+
+    /// A handle that forwards notifications to a sink
+    pub fn new(sink: Arc<dyn NotificationSink>) -> Self {
+        Self { sink: Some(sink) }
+    }
+
+    /// Ring the terminal bell and print a line on stderr
+    pub fn terminal_bell() -> Self {
+        Self::new(Arc::new(TerminalBell))
+    }
+
+    /// The right handle for a CLI output mode
+    ///
+    /// Silent when notifications are disabled, in quiet and JSON modes, and
+    /// when stderr is not a terminal.
+    pub fn for_output(config: &NotificationsConfig, quiet: bool, json: bool) -> Self {
+        if !config.enabled || quiet || json || !std::io::stderr().is_terminal() {
+            Self::silent()
+        } else {
+            Self::terminal_bell()
+        }
+    }
+
+    /// Whether notifications are delivered anywhere
+    pub fn is_enabled(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    pub fn notify(&self, notification: &Notification) {
+        if let Some(sink) = &self.sink {
+            sink.notify(notification);
+        }
+    }
+
+    pub fn notify_all(&self, notifications: &[Notification]) {
+        for notification in notifications {
+            self.notify(notification);
+        }
+    }
+}
+
+/// Terminal sink: a bell plus one line on stderr
+#[derive(Debug, Default)]
+pub struct TerminalBell;
+
+impl NotificationSink for TerminalBell {
+    fn notify(&self, notification: &Notification) {
+        let glyph = if notification.kind.is_alert() {
+            glyphs::cross()
+        } else {
+            glyphs::check()
+        };
+        let mut stderr = std::io::stderr().lock();
+        let _ = writeln!(stderr, "\x07{} {}", glyph, notification);
+        let _ = stderr.flush();
+    }
+}
+
+/// Turns database changes into notifications
+///
+/// Each [`poll`](Self::poll) reports what happened since the previous one:
+/// generations that finished, generations that left conflicting files, the
+/// daily budget being reached and the month-end forecast passing the monthly
+/// limit (each at most once per day).
+#[derive(Debug, Clone)]
+pub struct NotificationFeed {
+    since: DateTime<Utc>,
+    cost: CostConfig,
+    budget_reported_on: Option<NaiveDate>,
+    forecast_reported_on: Option<NaiveDate>,
+}
+
+impl NotificationFeed {
+    /// Start a feed that reports events after `now`
+    pub fn new(cost: &CostConfig, now: DateTime<Utc>) -> Self {
+        Self {
+            since: now,
+            cost: cost.clone(),
+            budget_reported_on: None,
+            forecast_reported_on: None,
+        }
+    }
+
+    /// Check budgets against `cost` from now on, e.g. after a config reload;
+    /// changed limits are reported afresh
+    pub fn set_cost(&mut self, cost: &CostConfig) {
+        let daily_changed = self.cost.daily_limit_usd != cost.daily_limit_usd;
+        if daily_changed || self.cost.alert_threshold != cost.alert_threshold {
+            self.budget_reported_on = None;
+        }
+        // The monthly budget defaults to the daily limit times the month's days
+        if daily_changed || self.cost.monthly_limit_usd != cost.monthly_limit_usd {
+            self.forecast_reported_on = None;
+        }
+        self.cost = cost.clone();
+    }
+
+    /// Collect notifications for events up to `now`
+    pub async fn poll(&mut self, db: &Database, now: DateTime<Utc>) -> Result<Vec<Notification>> {
+        let mut notifications = self.finished_generations(db, now).await?;
+        if let Some(budget) = self.budget(db, now).await? {
+            notifications.push(budget);
+        }
+        if let Some(forecast) = self.forecast(db, now).await? {
+            notifications.push(forecast);
+        }
+        self.since = now;
+        Ok(notifications)
+    }
+
+    async fn finished_generations(
+        &self,
+        db: &Database,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Notification>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT g.id, g.project_id, g.description, g.status,
+                   (SELECT COUNT(*) FROM generation_artifacts a
+                    WHERE a.generation_id = g.id AND a.decision = 'pending' AND a.is_new = 0)
+                       AS conflicts
+            FROM generations g
+            WHERE g.status IN ('completed', 'failed')
+              AND julianday(g.updated_at) >= julianday(?)
+              AND julianday(g.updated_at) < julianday(?)
+            ORDER BY g.updated_at
+            "#,
+        )
+        .bind(sql_time(self.since))
+        .bind(sql_time(now))
+        .fetch_all(db.pool())
+        .await?;
+
+        let mut notifications = Vec::new();
+        for row in rows {
+            let id: String = row.get("id");
+            let project_id: Option<String> = row.get("project_id");
+            let description: String = row.get("description");
+            let status: String = row.get("status");
+            let conflicts: i64 = row.get("conflicts");
+
+            if status == "failed" {
+                notifications.push(Notification::generation_failed(
+                    &id,
+                    project_id.clone(),
+                    &description,
+                ));
+            } else {
+                notifications.push(Notification::generation_completed(
+                    &id,
+                    project_id.clone(),
+                    &description,
+                ));
+            }
+            if conflicts > 0 {
+                notifications.push(Notification::conflicts_detected(
+                    &id,
+                    project_id,
+                    conflicts as usize,
+                ));
+            }
+        }
+        Ok(notifications)
+    }
+
+    async fn budget(&mut self, db: &Database, now: DateTime<Utc>) -> Result<Option<Notification>> {
+        let today = now.date_naive();
+        if self.cost.daily_limit_usd <= 0.0 || self.budget_reported_on == Some(today) {
+            return Ok(None);
+        }
+        let start = today
+            .and_hms_opt(0, 0, 0)
+            .map(|t| t.and_utc())
+            .unwrap_or(now);
+        let spent: f64 = sqlx::query(
+            r#"
+            SELECT COALESCE(SUM(input_cost_usd + output_cost_usd), 0.0) AS spent
+            FROM llm_costs
+            WHERE julianday(created_at) >= julianday(?)
+            "#,
+        )
+        .bind(sql_time(start))
+        .fetch_one(db.pool())
+        .await?
+        .get("spent");
+
+        if spent < self.cost.daily_limit_usd {
+            return Ok(None);
+        }
+        self.budget_reported_on = Some(today);
+        Ok(Some(Notification::budget_exceeded(
+            spent,
+            self.cost.daily_limit_usd,
+        )))
+    }
+
+    async fn forecast(
+        &mut self,
+        db: &Database,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Notification>> {
+        let today = now.date_naive();
+        if self.forecast_reported_on == Some(today) {
+            return Ok(None);
+        }
+        let forecast = forecast::forecast(db, None, &self.cost, now).await?;
+        if !forecast.over_limit {
+            return Ok(None);
+        }
+        self.forecast_reported_on = Some(today);
+        Ok(Some(Notification::forecast_over_limit(&forecast)))
+    }
+}
+
+fn sql_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        seen: Mutex<Vec<NotificationKind>>,
+    }
+
+    impl NotificationSink for Recorder {
+        fn notify(&self, notification: &Notification) {
+            self.seen.lock().unwrap().push(notification.kind);
+        }
+    }
+
+    fn cost(limit: f64) -> CostConfig {
+        CostConfig {
+            daily_limit_usd: limit,
+            alert_threshold: 0.8,
+            confirm_above_usd: 0.0,
+            monthly_limit_usd: 0.0,
+            chat_preview: false,
+        }
+    }
+
+    async fn insert_generation(db: &Database, id: &str, status: &str, at: DateTime<Utc>) {
+        sqlx::query(
+            "INSERT INTO generations (id, description, output_dir, status, created_at, updated_at) VALUES (?, 'add login page', '/tmp', ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(status)
+        .bind(at)
+        .bind(at)
+        .execute(db.pool())
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn test_notifier_forwards_to_sink() {
+        let recorder = Arc::new(Recorder::default());
+        let notifier = Notifier::new(recorder.clone());
+        notifier.notify_all(&[
+            Notification::budget_exceeded(12.0, 10.0),
+            Notification::generation_completed("gen-1", None, "login"),
+        ]);
+        Notifier::silent().notify(&Notification::budget_exceeded(1.0, 1.0));
+
+        assert_eq!(
+            *recorder.seen.lock().unwrap(),
+            vec![
+                NotificationKind::BudgetExceeded,
+                NotificationKind::GenerationCompleted
+            ]
+        );
+        let disabled = NotificationsConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(!Notifier::for_output(&disabled, false, false).is_enabled());
+    }
+
+    #[tokio::test]
+    async fn test_feed_reports_each_event_once() {
+        let db = Database::in_memory().await.unwrap();
+        let start = Utc::now() - Duration::minutes(10);
+        let mut feed = NotificationFeed::new(&cost(5.0), start);
+
+        insert_generation(
+            &db,
+            "before-start",
+            "completed",
+            start - Duration::minutes(1),
+        )
+        .await;
+        insert_generation(&db, "gen-ok", "completed", start + Duration::minutes(1)).await;
+        insert_generation(&db, "gen-bad", "failed", start + Duration::minutes(2)).await;
+        insert_generation(&db, "gen-run", "running", start + Duration::minutes(2)).await;
+        sqlx::query(
+            "INSERT INTO generation_artifacts (id, generation_id, file_path, content, is_new) VALUES ('a1', 'gen-ok', 'src/main.rs', '', 0)",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO llm_costs (id, model, input_cost_usd, output_cost_usd, created_at) VALUES ('c1', 'm', 4.0, 2.0, ?)",
+        )
+        .bind(Utc::now())
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let now = Utc::now();
+        let kinds: Vec<_> = feed
+            .poll(&db, now)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|n| n.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                NotificationKind::GenerationCompleted,
+                NotificationKind::ConflictsDetected,
+                NotificationKind::GenerationFailed,
+                NotificationKind::BudgetExceeded,
+            ]
+        );
+
+        assert!(feed
+            .poll(&db, now + Duration::seconds(1))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_feed_reports_forecast_once_a_day() {
+        let db = Database::in_memory().await.unwrap();
+        let now = Utc::now();
+        let mut feed = NotificationFeed::new(
+            &CostConfig {
+                monthly_limit_usd: 1.0,
+                ..cost(0.0)
+            },
+            now - Duration::minutes(1),
+        );
+        sqlx::query(
+            "INSERT INTO llm_costs (id, model, input_cost_usd, output_cost_usd, created_at) VALUES ('c1', 'm', 4.0, 2.0, ?)",
+        )
+        .bind(now)
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let notifications = feed.poll(&db, now).await.unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].kind, NotificationKind::ForecastOverLimit);
+        assert!(notifications[0].body.contains("$1.00 monthly limit"));
+        assert!(feed
+            .poll(&db, now + Duration::seconds(1))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_long_descriptions_are_truncated() {
+        let n = Notification::generation_failed("g", None, &"x".repeat(200));
+        assert_eq!(n.body.chars().count(), MAX_DESCRIPTION_CHARS);
+        assert!(n.body.ends_with("..."));
+    }
+}
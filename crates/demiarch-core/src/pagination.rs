@@ -0,0 +1,174 @@
+//! Limit/offset pagination for list endpoints
+//!
+//! Listing every feature, project, chat message or agent event at once is
+//! fine for small projects but not for large ones. List functions that take a
+//! [`PageRequest`] return a [`Page`] holding one slice of the results along
+//! with the total count, so frontends can show "51–100 of 1,240" and load the
+//! next slice on demand.
+//!
+//! Listings in creation order (features, projects) count `offset` from the
+//! start. Timelines (chat history, agent events) count it back from the
+//! newest entry, so offset 0 is the latest page and larger offsets load older
+//! entries; items within a page are still oldest first.
+
+use serde::{Deserialize, Serialize};
+
+/// Which slice of a listing to return
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageRequest {
+    /// Most items to return
+    pub limit: usize,
+    /// Items to skip
+    pub offset: usize,
+}
+
+impl PageRequest {
+    /// Page size used when a caller doesn't ask for one
+    pub const DEFAULT_LIMIT: usize = 50;
+    /// Largest page a caller may ask for
+    pub const MAX_LIMIT: usize = 1000;
+
+    /// Create a request, clamping `limit` to `1..=MAX_LIMIT`
+    pub fn new(limit: usize, offset: usize) -> Self {
+        Self {
+            limit: limit.clamp(1, Self::MAX_LIMIT),
+            offset,
+        }
+    }
+
+    /// Create a request from optional parameters, as passed by frontends
+    pub fn from_parts(limit: Option<usize>, offset: Option<usize>) -> Self {
+        Self::new(
+            limit.unwrap_or(Self::DEFAULT_LIMIT),
+            offset.unwrap_or_default(),
+        )
+    }
+
+    /// The request for the page after this one
+    pub fn next(&self) -> Self {
+        Self::new(self.limit, self.offset + self.limit)
+    }
+
+    /// `limit` as bound into SQL
+    pub(crate) fn sql_limit(&self) -> i64 {
+        self.limit as i64
+    }
+
+    /// `offset` as bound into SQL
+    pub(crate) fn sql_offset(&self) -> i64 {
+        self.offset as i64
+    }
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_LIMIT, 0)
+    }
+}
+
+/// One page of a listing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Items in the whole listing
+    pub total: usize,
+    pub limit: usize,
+    pub offset: usize,
+}
+
+impl<T> Page<T> {
+    /// Wrap the items returned for `request`
+    pub fn new(items: Vec<T>, total: usize, request: PageRequest) -> Self {
+        Self {
+            items,
+            total,
+            limit: request.limit,
+            offset: request.offset,
+        }
+    }
+
+    /// A single page holding a whole listing
+    pub fn all(items: Vec<T>) -> Self {
+        Self {
+            total: items.len(),
+            limit: items.len(),
+            offset: 0,
+            items,
+        }
+    }
+
+    /// Take the requested page out of a fully loaded listing
+    pub fn from_vec(mut all: Vec<T>, request: PageRequest) -> Self {
+        let total = all.len();
+        let start = request.offset.min(total);
+        let end = start.saturating_add(request.limit).min(total);
+        all.truncate(end);
+        let items = all.split_off(start);
+        Self::new(items, total, request)
+    }
+
+    /// Take the requested page out of a fully loaded timeline, counting
+    /// `offset` back from its newest (last) item
+    pub fn from_vec_newest(mut all: Vec<T>, request: PageRequest) -> Self {
+        let total = all.len();
+        let end = total.saturating_sub(request.offset);
+        let start = end.saturating_sub(request.limit);
+        all.truncate(end);
+        let items = all.split_off(start);
+        Self::new(items, total, request)
+    }
+
+    /// Whether items remain past this page
+    pub fn has_more(&self) -> bool {
+        self.offset + self.items.len() < self.total
+    }
+
+    /// The request for the page after this one, if there is one
+    pub fn next(&self) -> Option<PageRequest> {
+        self.has_more()
+            .then(|| PageRequest::new(self.limit, self.offset).next())
+    }
+
+    /// Convert every item, keeping the counts
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            limit: self.limit,
+            offset: self.offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pages_from_start_and_from_newest() {
+        let all: Vec<u32> = (1..=12).collect();
+
+        let page = Page::from_vec(all.clone(), PageRequest::new(5, 10));
+        assert_eq!(page.items, vec![11, 12]);
+        assert_eq!(page.total, 12);
+        assert!(!page.has_more());
+
+        let page = Page::from_vec(all.clone(), PageRequest::new(5, 0));
+        assert_eq!(page.items, vec![1, 2, 3, 4, 5]);
+        assert_eq!(page.next(), Some(PageRequest::new(5, 5)));
+
+        let page = Page::from_vec_newest(all.clone(), PageRequest::new(5, 0));
+        assert_eq!(page.items, vec![8, 9, 10, 11, 12]);
+        let page = Page::from_vec_newest(all.clone(), PageRequest::new(5, 10));
+        assert_eq!(page.items, vec![1, 2]);
+        let page = Page::from_vec_newest(all, PageRequest::new(5, 20));
+        assert!(page.items.is_empty());
+
+        assert_eq!(PageRequest::new(0, 0).limit, 1);
+        assert_eq!(
+            PageRequest::from_parts(Some(5000), None).limit,
+            PageRequest::MAX_LIMIT
+        );
+    }
+}
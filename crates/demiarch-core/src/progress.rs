@@ -0,0 +1,309 @@
+//! Progress reporting for long-running operations
+//!
+//! Generation, document generation, checkpoint restore and syncing large
+//! databases can take minutes. Commands report what they are doing through a
+//! [`Progress`] handle, which the frontend decides how to render: a spinner
+//! or bar on a terminal, or nothing at all in quiet and JSON modes.
+//!
+//! A hidden handle is the default, so library callers that don't care about
+//! progress pay nothing.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io::IsTerminal;
+use std::sync::Arc;
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressFinish, ProgressStyle};
+
+use crate::visualization::glyphs;
+
+/// Phase of a long-running operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Working through an execution plan
+    Plan,
+    /// Waiting for the model to write code
+    Code,
+    /// Validating, repairing and formatting generated files
+    Review,
+    /// Writing files to disk
+    Write,
+    /// Generating a document
+    Document,
+    /// Restoring a checkpoint
+    Restore,
+    /// Exporting or importing JSONL sync files
+    Sync,
+}
+
+impl Stage {
+    /// Short lowercase label, e.g. "code"
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Plan => "plan",
+            Stage::Code => "code",
+            Stage::Review => "review",
+            Stage::Write => "write",
+            Stage::Document => "document",
+            Stage::Restore => "restore",
+            Stage::Sync => "sync",
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Receiver of progress updates
+///
+/// Implemented by [`TerminalProgress`]; frontends with their own UI (or tests)
+/// can implement it to observe stage changes.
+pub trait ProgressSink: Send + Sync {
+    /// A new stage started
+    fn stage(&self, stage: Stage, message: &str);
+
+    /// Progress within the current stage, e.g. file 3 of 7
+    fn advance(&self, position: u64, total: u64);
+
+    /// The operation finished successfully
+    fn finish(&self, message: &str);
+
+    /// The operation stopped early, e.g. on an error
+    fn abandon(&self, message: &str);
+}
+
+/// Cloneable handle commands report progress through
+#[derive(Clone, Default)]
+pub struct Progress {
+    sink: Option<Arc<dyn ProgressSink>>,
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("visible", &self.is_visible())
+            .finish()
+    }
+}
+
+impl Progress {
+    /// A handle that discards all updates
+    pub fn hidden() -> Self {
+        Self::default()
+    }
+
+    /// A handle that forwards updates to a sink
+    pub fn new(sink: Arc<dyn ProgressSink>) -> Self {
+        Self { sink: Some(sink) }
+    }
+
+    /// A spinner/bar drawn on stderr
+    pub fn terminal() -> Self {
+        Self::new(Arc::new(TerminalProgress::new()))
+    }
+
+    /// The right handle for a CLI output mode
+    ///
+    /// Progress is hidden in quiet and JSON modes, and when stderr is not a
+    /// terminal so redirected output stays clean.
+    pub fn for_output(quiet: bool, json: bool) -> Self {
+        if quiet || json || !std::io::stderr().is_terminal() {
+            Self::hidden()
+        } else {
+            Self::terminal()
+        }
+    }
+
+    /// Whether updates are rendered anywhere
+    pub fn is_visible(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    /// Start a new stage
+    pub fn stage(&self, stage: Stage, message: impl AsRef<str>) {
+        if let Some(sink) = &self.sink {
+            sink.stage(stage, message.as_ref());
+        }
+    }
+
+    /// Report progress within the current stage
+    pub fn advance(&self, position: u64, total: u64) {
+        if let Some(sink) = &self.sink {
+            sink.advance(position, total);
+        }
+    }
+
+    /// Mark the operation finished
+    pub fn finish(&self, message: impl AsRef<str>) {
+        if let Some(sink) = &self.sink {
+            sink.finish(message.as_ref());
+        }
+    }
+
+    /// Mark the operation stopped early
+    pub fn abandon(&self, message: impl AsRef<str>) {
+        if let Some(sink) = &self.sink {
+            sink.abandon(message.as_ref());
+        }
+    }
+}
+
+/// Indicatif-backed spinner that turns into a bar once a total is known
+pub struct TerminalProgress {
+    bar: ProgressBar,
+}
+
+impl TerminalProgress {
+    /// Create a spinner drawing to stderr
+    ///
+    /// Nothing is drawn until the first stage starts, and the line is cleared
+    /// if the handle is dropped without finishing (e.g. on an error).
+    pub fn new() -> Self {
+        let bar = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr())
+            .with_finish(ProgressFinish::AndClear);
+        bar.set_style(spinner_style());
+        Self { bar }
+    }
+}
+
+impl Default for TerminalProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressSink for TerminalProgress {
+    fn stage(&self, stage: Stage, message: &str) {
+        // Each stage starts as a spinner until it reports a total
+        self.bar.set_style(spinner_style());
+        self.bar.set_position(0);
+        self.bar.set_prefix(stage.as_str());
+        self.bar.set_message(display(message));
+        self.bar.enable_steady_tick(Duration::from_millis(100));
+    }
+
+    fn advance(&self, position: u64, total: u64) {
+        self.bar.set_style(bar_style());
+        self.bar.set_length(total);
+        self.bar.set_position(position.min(total));
+    }
+
+    fn finish(&self, message: &str) {
+        if message.is_empty() {
+            self.bar.finish_and_clear();
+        } else {
+            self.bar.set_style(spinner_style());
+            self.bar.finish_with_message(display(message));
+        }
+    }
+
+    fn abandon(&self, message: &str) {
+        if message.is_empty() {
+            self.bar.abandon();
+        } else {
+            self.bar.abandon_with_message(display(message));
+        }
+    }
+}
+
+fn display(message: &str) -> Cow<'static, str> {
+    Cow::Owned(glyphs::display(message).into_owned())
+}
+
+fn spinner_style() -> ProgressStyle {
+    let style = ProgressStyle::with_template("{spinner} [{prefix}] {msg} ({elapsed})")
+        .unwrap_or_else(|_| ProgressStyle::default_spinner());
+    if glyphs::is_plain() {
+        style.tick_chars("-\\|/ ")
+    } else {
+        style
+    }
+}
+
+fn bar_style() -> ProgressStyle {
+    let style = ProgressStyle::with_template("{spinner} [{prefix}] {msg} [{bar:30}] {pos}/{len}")
+        .unwrap_or_else(|_| ProgressStyle::default_bar());
+    if glyphs::is_plain() {
+        style.tick_chars("-\\|/ ").progress_chars("#>-")
+    } else {
+        style.progress_chars("=> ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl ProgressSink for Recorder {
+        fn stage(&self, stage: Stage, message: &str) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("{}: {}", stage, message));
+        }
+
+        fn advance(&self, position: u64, total: u64) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("{}/{}", position, total));
+        }
+
+        fn finish(&self, message: &str) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("done: {}", message));
+        }
+
+        fn abandon(&self, message: &str) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("abandoned: {}", message));
+        }
+    }
+
+    #[test]
+    fn test_progress_forwards_to_sink() {
+        let recorder = Arc::new(Recorder::default());
+        let progress = Progress::new(recorder.clone());
+        let cloned = progress.clone();
+
+        progress.stage(Stage::Code, "Waiting for model");
+        cloned.stage(Stage::Write, "Writing files");
+        cloned.advance(1, 2);
+        progress.finish("ok");
+
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            vec![
+                "code: Waiting for model",
+                "write: Writing files",
+                "1/2",
+                "done: ok"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hidden_progress_ignores_updates() {
+        let progress = Progress::hidden();
+        assert!(!progress.is_visible());
+        progress.stage(Stage::Plan, "ignored");
+        progress.abandon("ignored");
+
+        assert!(!Progress::for_output(true, false).is_visible());
+        assert!(!Progress::for_output(false, true).is_visible());
+    }
+}
@@ -0,0 +1,552 @@
+//! Benchmark suites for seeding routing statistics
+//!
+//! A fresh install has no routing history, so the bandit's first choices are
+//! guesses from model tiers. `demiarch routing benchmark --suite basic` runs a
+//! small suite of coding, testing, review and planning tasks against each
+//! configured model and scores the answers:
+//!
+//! - Tasks with a `check` run the model's code (plus the task's tests) in the
+//!   [`SandboxedRunner`]; the check passes when the program exits with 0.
+//! - Tasks with `expect` terms score the share of terms the answer mentions.
+//!
+//! Scored results are stored in `routing_benchmark_results` and folded into
+//! `routing_stats` as ordinary routing rewards, so the router starts from
+//! measured quality instead of a uniform prior.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::store::RoutingStore;
+use super::types::{ModelRegistry, ModelStats, RoutingReward, TaskComplexity, TaskContext};
+use crate::agents::{extract_code_blocks, AgentType};
+use crate::error::{Error, Result};
+use crate::infrastructure::sandbox::SandboxedRunner;
+use crate::llm::{LlmClient, Message};
+use crate::progress::{Progress, Stage};
+
+/// The bundled suite used when no suite is named
+pub const DEFAULT_SUITE: &str = "basic";
+
+/// Bundled suites, by name
+const BUNDLED_SUITES: &[(&str, &str)] = &[("basic", include_str!("../../benchmarks/basic.yaml"))];
+
+/// Default time a task's check may run
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Score at or above which a task counts as a success
+pub const PASS_SCORE: f64 = 0.75;
+
+/// A named set of benchmark tasks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkSuite {
+    pub name: String,
+    pub tasks: Vec<BenchmarkTask>,
+}
+
+impl BenchmarkSuite {
+    /// Resolve a suite by bundled name, falling back to a YAML file path
+    pub fn resolve(suite: &str) -> Result<Self> {
+        if let Some((_, source)) = BUNDLED_SUITES.iter().find(|(name, _)| *name == suite) {
+            return Self::from_yaml(source);
+        }
+        let path = Path::new(suite);
+        if path.is_file() {
+            return Self::load(path);
+        }
+        Err(Error::NotFound(format!(
+            "Benchmark suite '{}' (bundled suites: {})",
+            suite,
+            Self::bundled_names().join(", ")
+        )))
+    }
+
+    /// Names of the suites shipped with demiarch
+    pub fn bundled_names() -> Vec<&'static str> {
+        BUNDLED_SUITES.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Load a suite from a YAML file
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path).map_err(Error::Io)?;
+        Self::from_yaml(&source)
+    }
+
+    /// Parse a YAML suite
+    pub fn from_yaml(source: &str) -> Result<Self> {
+        let suite: Self = serde_yaml::from_str(source)
+            .map_err(|e| Error::Parse(format!("benchmark suite: {}", e)))?;
+        if suite.tasks.is_empty() {
+            return Err(Error::InvalidInput(format!(
+                "Benchmark suite '{}' has no tasks",
+                suite.name
+            )));
+        }
+        Ok(suite)
+    }
+}
+
+/// One task in a suite
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkTask {
+    pub name: String,
+    /// Agent whose routing statistics the task informs
+    pub agent: AgentType,
+    pub complexity: TaskComplexity,
+    /// Language of the expected code block; `None` for prose answers
+    #[serde(default)]
+    pub language: Option<String>,
+    pub prompt: String,
+    /// Code placed before the model's code when running the check
+    #[serde(default)]
+    pub prelude: Option<String>,
+    /// Code placed after the model's code when running the check
+    #[serde(default)]
+    pub tests: Option<String>,
+    /// Terms a good answer mentions (case-insensitive)
+    #[serde(default)]
+    pub expect: Vec<String>,
+    #[serde(default)]
+    pub check: Option<BenchmarkCheck>,
+}
+
+impl BenchmarkTask {
+    /// Routing key the task's results are recorded under
+    pub fn routing_key(&self) -> String {
+        TaskContext::new(self.agent)
+            .with_complexity(self.complexity)
+            .routing_key()
+    }
+
+    fn messages(&self) -> Vec<Message> {
+        let instruction = match &self.language {
+            Some(language) => format!(
+                "Answer with a single fenced ```{}``` code block and nothing else.",
+                language
+            ),
+            None => "Answer concisely in plain text.".to_string(),
+        };
+        vec![Message::system(instruction), Message::user(&self.prompt)]
+    }
+
+    /// Program source for the check: prelude, the model's code, then tests
+    ///
+    /// Returns `None` if the answer has no code block.
+    pub fn check_source(&self, answer: &str) -> Option<String> {
+        let blocks = extract_code_blocks(answer);
+        let block = self
+            .language
+            .as_deref()
+            .and_then(|language| {
+                blocks
+                    .iter()
+                    .find(|b| b.language.eq_ignore_ascii_case(language))
+            })
+            .or_else(|| blocks.first())?;
+
+        let parts = [
+            self.prelude.as_deref(),
+            Some(block.code.as_str()),
+            self.tests.as_deref(),
+        ];
+        Some(
+            parts
+                .into_iter()
+                .flatten()
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+                .join("\n\n")
+                + "\n",
+        )
+    }
+}
+
+/// A program that verifies a coding task, fed the check source on stdin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchmarkCheck {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Share of `expect` terms mentioned in an answer
+pub fn expectation_score(answer: &str, expect: &[String]) -> f64 {
+    if expect.is_empty() {
+        return 1.0;
+    }
+    let answer = answer.to_lowercase();
+    let hits = expect
+        .iter()
+        .filter(|term| answer.contains(&term.to_lowercase()))
+        .count();
+    hits as f64 / expect.len() as f64
+}
+
+/// Outcome of one task for one model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub task: String,
+    pub routing_key: String,
+    pub model: String,
+    /// Score from 0.0 to 1.0; `None` if the task could not be scored
+    pub score: Option<f64>,
+    pub passed: bool,
+    pub tokens: u32,
+    pub cost_usd: f64,
+    pub latency_ms: u64,
+    /// Why the task was not scored, or why its check failed
+    pub error: Option<String>,
+}
+
+impl BenchmarkResult {
+    fn skipped(task: &BenchmarkTask, model: &str, error: String) -> Self {
+        Self {
+            task: task.name.clone(),
+            routing_key: task.routing_key(),
+            model: model.to_string(),
+            score: None,
+            passed: false,
+            tokens: 0,
+            cost_usd: 0.0,
+            latency_ms: 0,
+            error: Some(error),
+        }
+    }
+}
+
+/// Per-model aggregate of a benchmark
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelBenchmark {
+    pub model: String,
+    pub scored: usize,
+    pub passed: usize,
+    pub skipped: usize,
+    pub avg_score: Option<f64>,
+    pub cost_usd: f64,
+}
+
+/// Results of a benchmark run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub suite: String,
+    pub models: Vec<String>,
+    pub started_at: DateTime<Utc>,
+    pub results: Vec<BenchmarkResult>,
+}
+
+impl BenchmarkReport {
+    /// Aggregate results per model, in model order
+    pub fn summaries(&self) -> Vec<ModelBenchmark> {
+        self.models
+            .iter()
+            .map(|model| {
+                let runs: Vec<&BenchmarkResult> =
+                    self.results.iter().filter(|r| &r.model == model).collect();
+                let scores: Vec<f64> = runs.iter().filter_map(|r| r.score).collect();
+                ModelBenchmark {
+                    model: model.clone(),
+                    scored: scores.len(),
+                    passed: runs.iter().filter(|r| r.passed).count(),
+                    skipped: runs.len() - scores.len(),
+                    avg_score: (!scores.is_empty())
+                        .then(|| scores.iter().sum::<f64>() / scores.len() as f64),
+                    cost_usd: runs.iter().map(|r| r.cost_usd).sum(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Run every task in a suite against every model
+///
+/// Each request names its model explicitly, so the client's fallbacks never
+/// blur which model produced an answer. Failed requests and checks whose
+/// program is not installed are reported as skipped rather than scored.
+pub async fn run(
+    client: &LlmClient,
+    suite: &BenchmarkSuite,
+    models: &[String],
+    progress: &Progress,
+) -> Result<BenchmarkReport> {
+    if models.is_empty() {
+        return Err(Error::InvalidInput(
+            "At least one model is required".to_string(),
+        ));
+    }
+
+    let mut report = BenchmarkReport {
+        suite: suite.name.clone(),
+        models: models.to_vec(),
+        started_at: Utc::now(),
+        results: Vec::new(),
+    };
+    let registry = ModelRegistry::with_defaults();
+    let total = (models.len() * suite.tasks.len()) as u64;
+
+    for model in models {
+        for task in &suite.tasks {
+            progress.stage(Stage::Code, format!("{}: {}", model, task.name));
+            progress.advance(report.results.len() as u64, total);
+
+            let result = run_task(client, &registry, task, model).await;
+            info!(
+                model = %model,
+                task = %task.name,
+                score = ?result.score,
+                "Benchmark task finished"
+            );
+            report.results.push(result);
+        }
+    }
+    progress.advance(total, total);
+    Ok(report)
+}
+
+async fn run_task(
+    client: &LlmClient,
+    registry: &ModelRegistry,
+    task: &BenchmarkTask,
+    model: &str,
+) -> BenchmarkResult {
+    let started = Instant::now();
+    let response = match client.complete(task.messages(), Some(model)).await {
+        Ok(response) => response,
+        Err(e) => return BenchmarkResult::skipped(task, model, e.to_string()),
+    };
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let cost_usd = registry
+        .get(model)
+        .map(|m| {
+            m.estimate_cost(
+                response.input_tokens as usize,
+                response.output_tokens as usize,
+            )
+        })
+        .unwrap_or(0.0);
+
+    let mut result = BenchmarkResult {
+        task: task.name.clone(),
+        routing_key: task.routing_key(),
+        model: model.to_string(),
+        score: None,
+        passed: false,
+        tokens: response.tokens_used,
+        cost_usd,
+        latency_ms,
+        error: None,
+    };
+
+    let mut scores = Vec::new();
+    if !task.expect.is_empty() {
+        scores.push(expectation_score(&response.content, &task.expect));
+    }
+    if let Some(check) = &task.check {
+        match run_check(task, check, &response.content).await {
+            Ok(Ok(())) => scores.push(1.0),
+            Ok(Err(reason)) => {
+                scores.push(0.0);
+                result.error = Some(reason);
+            }
+            Err(e) => {
+                result.error = Some(e.to_string());
+                return result;
+            }
+        }
+    }
+
+    let score = if scores.is_empty() {
+        0.0
+    } else {
+        scores.iter().sum::<f64>() / scores.len() as f64
+    };
+    result.score = Some(score);
+    result.passed = score >= PASS_SCORE;
+    result
+}
+
+/// Run a task's check on an answer
+///
+/// The outer error means the check could not run (e.g. the program is not
+/// installed); the inner error is the reason a check that ran failed.
+async fn run_check(
+    task: &BenchmarkTask,
+    check: &BenchmarkCheck,
+    answer: &str,
+) -> Result<std::result::Result<(), String>> {
+    let Some(source) = task.check_source(answer) else {
+        return Ok(Err("Answer has no code block".to_string()));
+    };
+    let timeout = check
+        .timeout_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_CHECK_TIMEOUT);
+    let runner = SandboxedRunner::new(std::env::temp_dir())
+        .allow(&check.program)
+        .with_timeout(timeout);
+
+    let output = runner
+        .run(&check.program, &check.args, Some(&source))
+        .await?;
+    if output.success() {
+        Ok(Ok(()))
+    } else if output.timed_out {
+        Ok(Err(format!("Check timed out after {}s", timeout.as_secs())))
+    } else {
+        let detail = output.stderr.lines().last().unwrap_or_default().to_string();
+        Ok(Err(format!(
+            "Check failed (exit {:?}): {}",
+            output.exit_code, detail
+        )))
+    }
+}
+
+/// Store a report's results and fold the scored ones into routing statistics
+///
+/// Cost and latency are judged against the mean of all models on the same
+/// task, so a model is rewarded for being cheaper or faster than its peers.
+/// Returns the number of results that updated routing statistics.
+pub async fn seed(store: &RoutingStore, report: &BenchmarkReport) -> Result<usize> {
+    store.init().await?;
+
+    let mut baselines: HashMap<&str, (f64, f64, usize)> = HashMap::new();
+    for result in report.results.iter().filter(|r| r.score.is_some()) {
+        let entry = baselines.entry(result.task.as_str()).or_default();
+        entry.0 += result.cost_usd;
+        entry.1 += result.latency_ms as f64;
+        entry.2 += 1;
+    }
+
+    let mut stats: HashMap<String, HashMap<String, ModelStats>> = HashMap::new();
+    let mut seeded = 0;
+    for result in &report.results {
+        store.save_benchmark_result(&report.suite, result).await?;
+        let Some(score) = result.score else {
+            continue;
+        };
+
+        let by_model = match stats.entry(result.routing_key.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(store.load_stats_for_key(&result.routing_key).await?)
+            }
+        };
+        let model_stats = by_model
+            .entry(result.model.clone())
+            .or_insert_with(|| ModelStats::new(result.routing_key.clone(), result.model.clone()));
+
+        let (cost_sum, latency_sum, n) = baselines.get(result.task.as_str()).copied().unwrap_or((
+            result.cost_usd,
+            result.latency_ms as f64,
+            1,
+        ));
+        let reward = RoutingReward::new(
+            result.routing_key.clone(),
+            result.model.clone(),
+            result.passed,
+        )
+        .with_cost(result.cost_usd)
+        .with_latency(result.latency_ms)
+        .with_quality(score);
+        let computed = reward.compute_reward(cost_sum / n as f64, (latency_sum / n as f64) as u64);
+        model_stats.update(&reward, computed);
+        seeded += 1;
+    }
+
+    let all: Vec<ModelStats> = stats
+        .into_values()
+        .flat_map(|by_model| by_model.into_values())
+        .collect();
+    store.save_all_stats(&all).await?;
+    Ok(seeded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_suite_parses() {
+        let suite = BenchmarkSuite::resolve(DEFAULT_SUITE).unwrap();
+        assert_eq!(suite.name, "basic");
+        assert!(suite.tasks.iter().any(|t| t.check.is_some()));
+        assert!(suite.tasks.iter().any(|t| t.agent == AgentType::Planner));
+        assert!(BenchmarkSuite::resolve("no-such-suite").is_err());
+    }
+
+    #[test]
+    fn test_check_source_wraps_matching_block() {
+        let task = BenchmarkTask {
+            name: "t".to_string(),
+            agent: AgentType::Coder,
+            complexity: TaskComplexity::Simple,
+            language: Some("python".to_string()),
+            prompt: "p".to_string(),
+            prelude: Some("import math".to_string()),
+            tests: Some("assert f() == 1".to_string()),
+            expect: Vec::new(),
+            check: None,
+        };
+        let answer = "Here:\n```text\nnot code\n```\n```python\ndef f():\n    return 1\n```\n";
+        assert_eq!(
+            task.check_source(answer).unwrap(),
+            "import math\n\ndef f():\n    return 1\n\nassert f() == 1\n"
+        );
+        assert!(task.check_source("no code").is_none());
+        assert_eq!(task.routing_key(), "coder:simple");
+    }
+
+    fn result(model: &str, score: Option<f64>) -> BenchmarkResult {
+        BenchmarkResult {
+            task: "fizzbuzz".to_string(),
+            routing_key: "coder:simple".to_string(),
+            model: model.to_string(),
+            score,
+            passed: score.is_some_and(|s| s >= PASS_SCORE),
+            tokens: 100,
+            cost_usd: 0.001,
+            latency_ms: 1000,
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_seed_updates_routing_stats_from_scored_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RoutingStore::connect(&dir.path().join("routing.db"))
+            .await
+            .unwrap();
+        let report = BenchmarkReport {
+            suite: "basic".to_string(),
+            models: vec!["good".to_string(), "bad".to_string(), "offline".to_string()],
+            started_at: Utc::now(),
+            results: vec![
+                result("good", Some(1.0)),
+                result("bad", Some(0.0)),
+                result("offline", None),
+            ],
+        };
+
+        assert_eq!(seed(&store, &report).await.unwrap(), 2);
+
+        let stats = store.load_stats_for_key("coder:simple").await.unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats["good"].successes, 1);
+        assert_eq!(stats["bad"].failures, 1);
+        assert!(stats["good"].expected_value() > stats["bad"].expected_value());
+    }
+
+    #[test]
+    fn test_expectation_score_is_case_insensitive_share() {
+        let expect = vec!["Hash".to_string(), "session".to_string()];
+        assert_eq!(expectation_score("hash the password", &expect), 0.5);
+        assert_eq!(expectation_score("HASH, then a Session", &expect), 1.0);
+        assert_eq!(expectation_score("anything", &[]), 1.0);
+    }
+}
@@ -14,6 +14,9 @@
 //!
 //! - **Routing Store**: SQLite persistence for learning across sessions.
 //!
+//! - **Benchmarks**: Bundled task suites that score models up front and seed
+//!   the routing statistics, so early decisions are not cold-start guesses.
+//!
 //! ## How It Works
 //!
 //! 1. When a task needs to select a model, the router examines the task context
@@ -50,13 +53,18 @@
 //! ```
 
 mod bandit;
+pub mod benchmark;
 mod router;
 mod store;
 mod types;
 
 pub use bandit::ThompsonSamplingBandit;
+pub use benchmark::{BenchmarkReport, BenchmarkResult, BenchmarkSuite};
 pub use router::{ModelRouter, ModelRouterBuilder, RouterConfig};
-pub use store::{RoutingStore, RoutingStoreSummary, CREATE_ROUTING_STATS_TABLE_SQL};
+pub use store::{
+    RoutingStore, RoutingStoreSummary, CREATE_BENCHMARK_RESULTS_TABLE_SQL,
+    CREATE_ROUTING_STATS_TABLE_SQL,
+};
 pub use types::{
     ModelCandidate, ModelRegistry, ModelStats, RoutingDecision, RoutingPreference, RoutingReason,
     RoutingReward, TaskComplexity, TaskContext,
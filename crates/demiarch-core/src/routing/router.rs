@@ -7,7 +7,7 @@
 //! - Persistent learning across sessions
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError, RwLock as StdRwLock};
 use std::time::Instant;
 
 use tokio::sync::RwLock;
@@ -19,6 +19,7 @@ use super::types::{
     ModelCandidate, ModelRegistry, ModelStats, RoutingDecision, RoutingPreference, RoutingReason,
     RoutingReward, TaskContext,
 };
+use crate::config::watch::{self, ConfigChange};
 use crate::cost::CostTracker;
 use crate::error::{Error, Result};
 
@@ -72,8 +73,8 @@ impl RouterConfig {
 /// perform best for different task types. It also considers cost constraints
 /// and user preferences when making routing decisions.
 pub struct ModelRouter {
-    /// Configuration
-    config: RouterConfig,
+    /// Configuration, updated in place by [`ModelRouter::apply_change`]
+    config: Arc<StdRwLock<RouterConfig>>,
     /// Model registry
     registry: ModelRegistry,
     /// Thompson Sampling bandit
@@ -106,7 +107,7 @@ impl ModelRouter {
     /// Create a new router with default configuration
     pub fn new() -> Self {
         Self {
-            config: RouterConfig::default(),
+            config: Arc::new(StdRwLock::new(RouterConfig::default())),
             registry: ModelRegistry::with_defaults(),
             bandit: Arc::new(RwLock::new(ThompsonSamplingBandit::new())),
             store: None,
@@ -122,7 +123,7 @@ impl ModelRouter {
             .with_min_samples(config.min_samples);
 
         Self {
-            config,
+            config: Arc::new(StdRwLock::new(config)),
             registry: ModelRegistry::with_defaults(),
             bandit: Arc::new(RwLock::new(bandit)),
             store: None,
@@ -206,8 +207,9 @@ impl ModelRouter {
         }
 
         // Use Thompson Sampling to select
+        let preference = self.config().preference;
         let mut bandit = self.bandit.write().await;
-        let selection = bandit.select(&routing_key, &filtered_candidates, self.config.preference);
+        let selection = bandit.select(&routing_key, &filtered_candidates, preference);
 
         match selection {
             Some((model, sampled_value, is_exploration)) => {
@@ -303,7 +305,7 @@ impl ModelRouter {
         }
 
         // Persist if configured
-        if self.config.persist_stats {
+        if self.config().persist_stats {
             // We could batch this for efficiency, but for now save immediately
             if let Some(store) = &self.store {
                 let bandit = self.bandit.read().await;
@@ -342,8 +344,22 @@ impl ModelRouter {
     }
 
     /// Get the current configuration
-    pub fn config(&self) -> &RouterConfig {
-        &self.config
+    pub fn config(&self) -> RouterConfig {
+        self.config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Apply a model or routing preference change from a config reload
+    pub fn apply_change(&self, change: &ConfigChange) {
+        apply_change(&self.config, change);
+    }
+
+    /// Apply model and routing preference changes from config reloads until
+    /// the router is dropped
+    pub fn follow_config(&self) {
+        watch::hub().follow(Arc::downgrade(&self.config), apply_change);
     }
 
     /// Apply budget filtering to candidates
@@ -382,17 +398,19 @@ impl ModelRouter {
 
     /// Create a fallback decision when no candidates are available
     async fn create_fallback_decision(&self, context: &TaskContext) -> Result<RoutingDecision> {
+        let config = self.config();
+
         // Try default model
-        if self.registry.get(&self.config.default_model).is_some() {
+        if self.registry.get(&config.default_model).is_some() {
             return Ok(RoutingDecision::new(
-                self.config.default_model.clone(),
+                config.default_model.clone(),
                 0.5,
                 RoutingReason::Default,
             ));
         }
 
         // Try fallback models
-        for fallback in &self.config.fallback_models {
+        for fallback in &config.fallback_models {
             if self.registry.get(fallback).is_some() {
                 return Ok(RoutingDecision::new(
                     fallback.clone(),
@@ -524,7 +542,7 @@ impl ModelRouterBuilder {
             .with_min_samples(self.config.min_samples);
 
         ModelRouter {
-            config: self.config,
+            config: Arc::new(StdRwLock::new(self.config)),
             registry: self.registry.unwrap_or_else(ModelRegistry::with_defaults),
             bandit: Arc::new(RwLock::new(bandit)),
             store: self.store,
@@ -534,6 +552,23 @@ impl ModelRouterBuilder {
     }
 }
 
+fn apply_change(config: &StdRwLock<RouterConfig>, change: &ConfigChange) {
+    let mut config = config.write().unwrap_or_else(PoisonError::into_inner);
+    match change {
+        ConfigChange::ModelChanged {
+            default_model,
+            fallback_models,
+        } => {
+            config.default_model = default_model.clone();
+            config.fallback_models = fallback_models.clone();
+        }
+        ConfigChange::RoutingChanged { preference } => {
+            config.preference = preference.parse().unwrap_or(RoutingPreference::Balanced);
+        }
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -742,6 +777,26 @@ mod tests {
         assert_eq!(router.config().preference, RoutingPreference::Cost);
     }
 
+    #[test]
+    fn test_router_applies_config_changes() {
+        let router = ModelRouter::new();
+        router.apply_change(&ConfigChange::RoutingChanged {
+            preference: "fast".to_string(),
+        });
+        router.apply_change(&ConfigChange::ModelChanged {
+            default_model: "openai/gpt-4o".to_string(),
+            fallback_models: vec![],
+        });
+        router.apply_change(&ConfigChange::SettingChanged {
+            key: "ui.theme".to_string(),
+        });
+
+        let config = router.config();
+        assert_eq!(config.preference, RoutingPreference::Fast);
+        assert_eq!(config.default_model, "openai/gpt-4o");
+        assert!(config.fallback_models.is_empty());
+    }
+
     #[tokio::test]
     async fn test_get_expected_values() {
         let router = ModelRouter::new();
@@ -9,6 +9,7 @@ use std::path::Path;
 use sqlx::{Row, SqlitePool};
 use tracing::{debug, info, warn};
 
+use super::benchmark::BenchmarkResult;
 use super::types::ModelStats;
 use crate::error::{Error, Result};
 
@@ -36,6 +37,26 @@ CREATE INDEX IF NOT EXISTS idx_routing_stats_key ON routing_stats(routing_key);
 CREATE INDEX IF NOT EXISTS idx_routing_stats_model ON routing_stats(model_id);
 "#;
 
+/// SQL to create the table of individual benchmark results
+pub const CREATE_BENCHMARK_RESULTS_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS routing_benchmark_results (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    suite TEXT NOT NULL,
+    task TEXT NOT NULL,
+    routing_key TEXT NOT NULL,
+    model_id TEXT NOT NULL,
+    score REAL,
+    passed INTEGER NOT NULL DEFAULT 0,
+    tokens INTEGER NOT NULL DEFAULT 0,
+    cost_usd REAL NOT NULL DEFAULT 0.0,
+    latency_ms INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_routing_benchmark_model ON routing_benchmark_results(model_id, routing_key);
+"#;
+
 /// Store for persisting routing statistics
 pub struct RoutingStore {
     pool: SqlitePool,
@@ -64,6 +85,10 @@ impl RoutingStore {
             .execute(&self.pool)
             .await
             .map_err(Error::DatabaseError)?;
+        sqlx::query(CREATE_BENCHMARK_RESULTS_TABLE_SQL)
+            .execute(&self.pool)
+            .await
+            .map_err(Error::DatabaseError)?;
 
         info!("Routing statistics table initialized");
         Ok(())
@@ -166,6 +191,32 @@ impl RoutingStore {
         Ok(())
     }
 
+    /// Record one benchmark result
+    pub async fn save_benchmark_result(&self, suite: &str, result: &BenchmarkResult) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO routing_benchmark_results (
+                suite, task, routing_key, model_id, score, passed,
+                tokens, cost_usd, latency_ms, error
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(suite)
+        .bind(&result.task)
+        .bind(&result.routing_key)
+        .bind(&result.model)
+        .bind(result.score)
+        .bind(result.passed)
+        .bind(result.tokens as i64)
+        .bind(result.cost_usd)
+        .bind(result.latency_ms as i64)
+        .bind(&result.error)
+        .execute(&self.pool)
+        .await
+        .map_err(Error::DatabaseError)?;
+        Ok(())
+    }
+
     /// Load statistics for a specific routing key
     pub async fn load_stats_for_key(
         &self,
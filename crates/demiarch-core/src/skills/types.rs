@@ -465,6 +465,14 @@ impl SkillConfidence {
         }
     }
 
+    /// One level lower; `Low` stays `Low`
+    pub fn downgraded(&self) -> Self {
+        match self {
+            Self::High => Self::Medium,
+            Self::Medium | Self::Low => Self::Low,
+        }
+    }
+
     /// Get a numeric score for ranking
     pub fn score(&self) -> u8 {
         match self {
@@ -0,0 +1,289 @@
+//! Process-wide cache of open database pools
+//!
+//! Opening a database runs migrations and replays the write queue, so doing
+//! it once per command path adds up. [`ConnectionCache`] keeps one pool per
+//! database file and access mode, opens it on first use, and drops pools that
+//! sit unused longer than [`ConnectionCacheConfig::idle_timeout`] (or the
+//! least recently used one once [`ConnectionCacheConfig::max_entries`] is
+//! reached). Evicting only drops the cache's handle; a pool a caller still
+//! holds stays open until that caller is done with it.
+//!
+//! In-memory databases are never cached, since each one is a separate
+//! database.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::database::{Database, DatabaseConfig};
+
+/// Tuning for a [`ConnectionCache`]
+#[derive(Debug, Clone)]
+pub struct ConnectionCacheConfig {
+    /// How long a pool may go unused before it is dropped
+    pub idle_timeout: Duration,
+    /// Most pools kept open at once
+    pub max_entries: usize,
+}
+
+impl Default for ConnectionCacheConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(300),
+            max_entries: 8,
+        }
+    }
+}
+
+/// Counters for a connection cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionCacheMetrics {
+    /// Requests served by an already open pool
+    pub hits: u64,
+    /// Requests that had to open a pool
+    pub misses: u64,
+    /// Opens that failed
+    pub open_failures: u64,
+    /// Pools dropped for being idle or to make room
+    pub evictions: u64,
+    /// Pools currently cached
+    pub cached: u64,
+    /// Time taken by the latest open, in microseconds
+    pub last_open_us: u64,
+    /// Slowest open, in microseconds
+    pub max_open_us: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    open_failures: AtomicU64,
+    evictions: AtomicU64,
+    cached: AtomicU64,
+    last_open_us: AtomicU64,
+    max_open_us: AtomicU64,
+}
+
+impl Counters {
+    fn snapshot(&self) -> ConnectionCacheMetrics {
+        ConnectionCacheMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            open_failures: self.open_failures.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            cached: self.cached.load(Ordering::Relaxed),
+            last_open_us: self.last_open_us.load(Ordering::Relaxed),
+            max_open_us: self.max_open_us.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A database file and whether it was opened read-only
+type CacheKey = (PathBuf, bool);
+
+struct Entry {
+    db: Database,
+    last_used: Instant,
+}
+
+/// Lazily opened database pools keyed by path
+pub struct ConnectionCache {
+    entries: Mutex<HashMap<CacheKey, Entry>>,
+    config: ConnectionCacheConfig,
+    counters: Counters,
+}
+
+impl ConnectionCache {
+    /// Create an empty cache
+    pub fn new(config: ConnectionCacheConfig) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            config,
+            counters: Counters::default(),
+        }
+    }
+
+    /// Return the cached pool for `config.path`, opening it if needed
+    ///
+    /// The first open of a path uses `config` as given (migrations, write
+    /// queue replay); later calls reuse that pool.
+    pub async fn open(&self, config: DatabaseConfig) -> Result<Database> {
+        if is_in_memory(&config) {
+            return Database::new(config).await;
+        }
+
+        let key = (config.path.clone(), config.read_only);
+        let mut entries = self.entries.lock().await;
+        self.evict_expired(&mut entries);
+
+        if let Some(entry) = entries.get_mut(&key) {
+            entry.last_used = Instant::now();
+            self.counters.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(entry.db.clone());
+        }
+
+        self.counters.misses.fetch_add(1, Ordering::Relaxed);
+        let started = Instant::now();
+        let db = match Database::new(config).await {
+            Ok(db) => db,
+            Err(e) => {
+                self.counters.open_failures.fetch_add(1, Ordering::Relaxed);
+                return Err(e);
+            }
+        };
+        let us = started.elapsed().as_micros() as u64;
+        self.counters.last_open_us.store(us, Ordering::Relaxed);
+        self.counters.max_open_us.fetch_max(us, Ordering::Relaxed);
+        tracing::debug!(path = %key.0.display(), open_us = us, "Opened database");
+
+        while entries.len() >= self.config.max_entries.max(1) {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+            self.counters.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+        entries.insert(
+            key,
+            Entry {
+                db: db.clone(),
+                last_used: Instant::now(),
+            },
+        );
+        self.counters
+            .cached
+            .store(entries.len() as u64, Ordering::Relaxed);
+        Ok(db)
+    }
+
+    /// Drop pools idle longer than the idle timeout, returning how many
+    pub async fn evict_idle(&self) -> usize {
+        let mut entries = self.entries.lock().await;
+        self.evict_expired(&mut entries)
+    }
+
+    /// Drop every cached pool
+    pub async fn clear(&self) {
+        let mut entries = self.entries.lock().await;
+        self.counters
+            .evictions
+            .fetch_add(entries.len() as u64, Ordering::Relaxed);
+        entries.clear();
+        self.counters.cached.store(0, Ordering::Relaxed);
+    }
+
+    /// This cache's counters
+    pub fn metrics(&self) -> ConnectionCacheMetrics {
+        self.counters.snapshot()
+    }
+
+    fn evict_expired(&self, entries: &mut HashMap<CacheKey, Entry>) -> usize {
+        let before = entries.len();
+        entries.retain(|_, entry| entry.last_used.elapsed() < self.config.idle_timeout);
+        let evicted = before - entries.len();
+        if evicted > 0 {
+            self.counters
+                .evictions
+                .fetch_add(evicted as u64, Ordering::Relaxed);
+            self.counters
+                .cached
+                .store(entries.len() as u64, Ordering::Relaxed);
+        }
+        evicted
+    }
+}
+
+impl Default for ConnectionCache {
+    fn default() -> Self {
+        Self::new(ConnectionCacheConfig::default())
+    }
+}
+
+fn is_in_memory(config: &DatabaseConfig) -> bool {
+    let path = config.path.to_string_lossy();
+    path == ":memory:" || path.starts_with("file:")
+}
+
+/// The cache shared by every frontend and command in this process
+pub fn shared() -> &'static ConnectionCache {
+    static SHARED: OnceLock<ConnectionCache> = OnceLock::new();
+    SHARED.get_or_init(ConnectionCache::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reuses_pool_per_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ConnectionCache::default();
+
+        let first = cache
+            .open(DatabaseConfig::with_path(dir.path().join("a.db")))
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'x')")
+            .execute(first.pool())
+            .await
+            .unwrap();
+        let second = cache
+            .open(DatabaseConfig::with_path(dir.path().join("a.db")))
+            .await
+            .unwrap();
+        cache
+            .open(DatabaseConfig::with_path(dir.path().join("b.db")))
+            .await
+            .unwrap();
+
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM projects")
+            .fetch_one(second.pool())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.misses, 2);
+        assert_eq!(metrics.cached, 2);
+
+        cache.open(DatabaseConfig::in_memory()).await.unwrap();
+        assert_eq!(cache.metrics().cached, 2);
+    }
+
+    #[tokio::test]
+    async fn test_evicts_idle_and_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ConnectionCache::new(ConnectionCacheConfig {
+            idle_timeout: Duration::from_millis(50),
+            max_entries: 2,
+        });
+
+        for name in ["a.db", "b.db", "c.db"] {
+            cache
+                .open(DatabaseConfig::with_path(dir.path().join(name)))
+                .await
+                .unwrap();
+        }
+        assert_eq!(cache.metrics().cached, 2);
+        assert_eq!(cache.metrics().evictions, 1);
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(cache.evict_idle().await, 2);
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.cached, 0);
+        assert_eq!(metrics.evictions, 3);
+    }
+}
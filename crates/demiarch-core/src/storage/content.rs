@@ -0,0 +1,324 @@
+//! Compressed, deduplicated content storage
+//!
+//! Generated file contents and checkpoint snapshots are the bulk of a large
+//! project's database, and most of it repeats: a regenerated file that did
+//! not change, or consecutive checkpoints of the same project. Instead of
+//! storing that text inline, rows keep the SHA-256 hash of their content and
+//! the content itself is stored once in `content_blobs`, compressed with
+//! zstd.
+//!
+//! Rows that point at a blob leave their inline column empty; rows without a
+//! hash (written before migration 41, or imported from a sync file) still
+//! carry their content inline. Readers join `content_blobs` on the hash and
+//! pass both to [`decode`], which returns whichever is set.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Row, SqlitePool};
+
+use crate::{Error, Result};
+
+/// zstd level for stored content; text compresses well at low levels
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Compress `content` for storage
+pub fn compress(content: &str) -> Result<Vec<u8>> {
+    Ok(zstd::encode_all(content.as_bytes(), COMPRESSION_LEVEL)?)
+}
+
+/// Decompress stored content
+pub fn decompress(data: &[u8]) -> Result<String> {
+    let bytes = zstd::decode_all(data)?;
+    String::from_utf8(bytes)
+        .map_err(|e| Error::Parse(format!("Stored content is not UTF-8: {}", e)))
+}
+
+/// Hex SHA-256 of `content`, the key it is stored under
+pub fn content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// A row's content: the joined blob if it has one, otherwise the inline text
+pub fn decode(inline: String, blob: Option<Vec<u8>>) -> Result<String> {
+    match blob {
+        Some(data) => decompress(&data),
+        None => Ok(inline),
+    }
+}
+
+/// How much space the content store saves
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContentStats {
+    /// Distinct contents stored
+    pub blobs: i64,
+    /// Artifact and checkpoint rows pointing at a blob
+    pub references: i64,
+    /// What the referencing rows would take stored inline
+    pub logical_bytes: i64,
+    /// Size of the distinct contents before compression
+    pub unique_bytes: i64,
+    /// Size of the distinct contents after compression
+    pub stored_bytes: i64,
+    /// Rows still holding their content inline
+    pub inline_rows: i64,
+    pub inline_bytes: i64,
+    /// Blobs no row points at any more
+    pub unreferenced_blobs: i64,
+    pub unreferenced_bytes: i64,
+}
+
+impl ContentStats {
+    /// Bytes saved by storing each content once
+    pub fn dedup_savings(&self) -> i64 {
+        self.logical_bytes - self.unique_bytes
+    }
+
+    /// Bytes saved by compression
+    pub fn compression_savings(&self) -> i64 {
+        self.unique_bytes - self.stored_bytes
+    }
+
+    /// Stored size as a fraction of the logical size
+    pub fn ratio(&self) -> f64 {
+        if self.logical_bytes == 0 {
+            1.0
+        } else {
+            self.stored_bytes as f64 / self.logical_bytes as f64
+        }
+    }
+}
+
+/// Rows moved into the content store by [`ContentStore::compact`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompactResult {
+    pub artifacts: u64,
+    pub checkpoints: u64,
+}
+
+/// Content-addressed store in the `content_blobs` table
+pub struct ContentStore<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ContentStore<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Store `content` unless it is already stored, returning its hash
+    pub async fn put(&self, content: &str) -> Result<String> {
+        let hash = content_hash(content);
+        sqlx::query(
+            "INSERT OR IGNORE INTO content_blobs (hash, size_bytes, data) VALUES (?, ?, ?)",
+        )
+        .bind(&hash)
+        .bind(content.len() as i64)
+        .bind(compress(content)?)
+        .execute(self.pool)
+        .await?;
+        Ok(hash)
+    }
+
+    /// The content stored under `hash`
+    pub async fn get(&self, hash: &str) -> Result<String> {
+        let data: Option<Vec<u8>> =
+            sqlx::query_scalar("SELECT data FROM content_blobs WHERE hash = ?")
+                .bind(hash)
+                .fetch_optional(self.pool)
+                .await?;
+        let data = data.ok_or_else(|| Error::NotFound(format!("Stored content {}", hash)))?;
+        decompress(&data)
+    }
+
+    /// Move inline artifact contents and checkpoint snapshots into the store
+    pub async fn compact(&self) -> Result<CompactResult> {
+        let mut result = CompactResult::default();
+
+        let rows = sqlx::query(
+            "SELECT id, content FROM generation_artifacts WHERE content_hash IS NULL AND content != ''",
+        )
+        .fetch_all(self.pool)
+        .await?;
+        for row in rows {
+            let hash = self.put(row.get("content")).await?;
+            sqlx::query(
+                "UPDATE generation_artifacts SET content = '', content_hash = ? WHERE id = ?",
+            )
+            .bind(hash)
+            .bind(row.get::<String, _>("id"))
+            .execute(self.pool)
+            .await?;
+            result.artifacts += 1;
+        }
+
+        let rows = sqlx::query(
+            "SELECT id, snapshot_data FROM checkpoints WHERE snapshot_hash IS NULL AND snapshot_data != ''",
+        )
+        .fetch_all(self.pool)
+        .await?;
+        for row in rows {
+            let hash = self.put(row.get("snapshot_data")).await?;
+            sqlx::query(
+                "UPDATE checkpoints SET snapshot_data = '', snapshot_hash = ? WHERE id = ?",
+            )
+            .bind(hash)
+            .bind(row.get::<String, _>("id"))
+            .execute(self.pool)
+            .await?;
+            result.checkpoints += 1;
+        }
+
+        Ok(result)
+    }
+
+    /// Delete blobs no artifact or checkpoint points at, returning how many
+    pub async fn prune(&self) -> Result<u64> {
+        let result = sqlx::query(&format!(
+            "DELETE FROM content_blobs WHERE hash NOT IN ({})",
+            REFERENCED_HASHES
+        ))
+        .execute(self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Sizes of the stored, referenced and inline content
+    pub async fn stats(&self) -> Result<ContentStats> {
+        let blobs = sqlx::query(
+            "SELECT COUNT(*) AS blobs, COALESCE(SUM(size_bytes), 0) AS unique_bytes, COALESCE(SUM(LENGTH(data)), 0) AS stored_bytes FROM content_blobs",
+        )
+        .fetch_one(self.pool)
+        .await?;
+        let references = sqlx::query(&format!(
+            "SELECT COUNT(*) AS refs, COALESCE(SUM(b.size_bytes), 0) AS logical_bytes FROM ({}) r JOIN content_blobs b ON b.hash = r.hash",
+            REFERENCES
+        ))
+        .fetch_one(self.pool)
+        .await?;
+        let inline = sqlx::query(
+            r#"
+            SELECT COUNT(*) AS inline_rows, COALESCE(SUM(bytes), 0) AS bytes FROM (
+                SELECT LENGTH(CAST(content AS BLOB)) AS bytes FROM generation_artifacts WHERE content_hash IS NULL AND content != ''
+                UNION ALL
+                SELECT LENGTH(CAST(snapshot_data AS BLOB)) FROM checkpoints WHERE snapshot_hash IS NULL AND snapshot_data != ''
+            )
+            "#,
+        )
+        .fetch_one(self.pool)
+        .await?;
+        let unreferenced = sqlx::query(&format!(
+            "SELECT COUNT(*) AS blobs, COALESCE(SUM(LENGTH(data)), 0) AS bytes FROM content_blobs WHERE hash NOT IN ({})",
+            REFERENCED_HASHES
+        ))
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(ContentStats {
+            blobs: blobs.get("blobs"),
+            references: references.get("refs"),
+            logical_bytes: references.get("logical_bytes"),
+            unique_bytes: blobs.get("unique_bytes"),
+            stored_bytes: blobs.get("stored_bytes"),
+            inline_rows: inline.get("inline_rows"),
+            inline_bytes: inline.get("bytes"),
+            unreferenced_blobs: unreferenced.get("blobs"),
+            unreferenced_bytes: unreferenced.get("bytes"),
+        })
+    }
+}
+
+/// Every row's content hash, one per referencing row
+const REFERENCES: &str =
+    "SELECT content_hash AS hash FROM generation_artifacts WHERE content_hash IS NOT NULL \
+     UNION ALL SELECT snapshot_hash FROM checkpoints WHERE snapshot_hash IS NOT NULL";
+
+/// Hashes at least one row points at
+const REFERENCED_HASHES: &str =
+    "SELECT content_hash FROM generation_artifacts WHERE content_hash IS NOT NULL \
+     UNION SELECT snapshot_hash FROM checkpoints WHERE snapshot_hash IS NOT NULL";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Database;
+
+    #[tokio::test]
+    async fn test_store_dedups_compresses_and_prunes() {
+        let db = Database::in_memory().await.unwrap();
+        let store = ContentStore::new(db.pool());
+        let content = "fn main() {}\n".repeat(200);
+
+        let first = store.put(&content).await.unwrap();
+        assert_eq!(store.put(&content).await.unwrap(), first);
+        assert_eq!(store.get(&first).await.unwrap(), content);
+
+        sqlx::query(
+            "INSERT INTO generations (id, description, output_dir) VALUES ('g1', 'gen', '/tmp')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        for (id, path) in [("a1", "a.rs"), ("a2", "b.rs")] {
+            sqlx::query(
+                "INSERT INTO generation_artifacts (id, generation_id, file_path, content, content_hash) VALUES (?, 'g1', ?, '', ?)",
+            )
+            .bind(id)
+            .bind(path)
+            .bind(&first)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+        let orphan = store.put("unused").await.unwrap();
+
+        let stats = store.stats().await.unwrap();
+        assert_eq!(stats.blobs, 2);
+        assert_eq!(stats.references, 2);
+        assert_eq!(stats.logical_bytes, 2 * content.len() as i64);
+        assert_eq!(
+            stats.dedup_savings(),
+            content.len() as i64 - "unused".len() as i64
+        );
+        assert!(stats.compression_savings() > 0);
+        assert_eq!(stats.unreferenced_blobs, 1);
+
+        assert_eq!(store.prune().await.unwrap(), 1);
+        assert!(store.get(&orphan).await.is_err());
+        assert_eq!(store.get(&first).await.unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn test_compact_moves_inline_rows() {
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query(
+            "INSERT INTO generations (id, description, output_dir) VALUES ('g1', 'gen', '/tmp')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO generation_artifacts (id, generation_id, file_path, content) VALUES ('a1', 'g1', 'a.rs', 'pub fn a() {}')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let store = ContentStore::new(db.pool());
+        assert_eq!(store.stats().await.unwrap().inline_rows, 1);
+        let result = store.compact().await.unwrap();
+        assert_eq!(result.artifacts, 1);
+
+        let row = sqlx::query(
+            "SELECT a.content, b.data FROM generation_artifacts a LEFT JOIN content_blobs b ON b.hash = a.content_hash",
+        )
+        .fetch_one(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(row.get::<String, _>("content"), "");
+        assert_eq!(
+            decode(row.get("content"), row.get("data")).unwrap(),
+            "pub fn a() {}"
+        );
+        assert_eq!(store.stats().await.unwrap().inline_rows, 0);
+    }
+}
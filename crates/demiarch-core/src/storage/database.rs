@@ -1,17 +1,71 @@
 //! SQLite database operations
 //!
 //! Provides connection pool management and database initialization for demiarch.
+//!
+//! Read-only mode (`demiarch --read-only`, or `DEMIARCH_READ_ONLY=1`) opens
+//! databases with SQLite's read-only flag so a teammate's synced snapshot can
+//! be inspected without risk of writes. Combined with `--database <path>`
+//! (or `DEMIARCH_DATABASE`) it points every frontend at that snapshot.
 
+use crate::infrastructure::network::env_flag_enabled;
+use crate::storage::connection_cache::{self, ConnectionCacheMetrics};
 use crate::storage::migrations;
-use anyhow::{Context, Result};
+use crate::storage::write_queue::WriteQueue;
+use anyhow::{bail, Context, Result};
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
 use sqlx::SqlitePool;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::OnceCell;
 
 /// Default maximum connections in the pool
 const DEFAULT_MAX_CONNECTIONS: u32 = 5;
 
+/// Environment variable that enables read-only mode
+pub const READ_ONLY_ENV: &str = "DEMIARCH_READ_ONLY";
+
+/// Environment variable that overrides the global database path
+pub const DATABASE_PATH_ENV: &str = "DEMIARCH_DATABASE";
+
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+static DATABASE_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Enable or disable read-only mode for this process
+///
+/// Frontends call this at startup from their `--read-only` flag.
+pub fn set_read_only(read_only: bool) {
+    READ_ONLY.store(read_only, Ordering::SeqCst);
+}
+
+/// Whether read-only mode is enabled for this process or via the environment
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::SeqCst)
+        || std::env::var(READ_ONLY_ENV)
+            .map(|v| env_flag_enabled(&v))
+            .unwrap_or(false)
+}
+
+/// Fail with [`crate::Error::ReadOnly`] if read-only mode is enabled
+///
+/// `operation` names what would have written (e.g. "feature update").
+pub fn ensure_writable(operation: &str) -> crate::Result<()> {
+    if is_read_only() {
+        return Err(crate::Error::ReadOnly(operation.to_string()));
+    }
+    Ok(())
+}
+
+/// Use a different global database file for this process
+///
+/// Only the first call takes effect, so a path chosen at startup cannot
+/// change under open connections.
+pub fn set_database_path(path: impl Into<PathBuf>) {
+    let _ = DATABASE_PATH.set(path.into());
+}
+
 /// Database configuration options
 #[derive(Debug, Clone)]
 pub struct DatabaseConfig {
@@ -25,6 +79,8 @@ pub struct DatabaseConfig {
     pub journal_mode: SqliteJournalMode,
     /// Synchronous mode (default: NORMAL for balance of safety/performance)
     pub synchronous: SqliteSynchronous,
+    /// Open without write access; the file must exist and be migrated
+    pub read_only: bool,
 }
 
 impl Default for DatabaseConfig {
@@ -35,6 +91,7 @@ impl Default for DatabaseConfig {
             auto_migrate: true,
             journal_mode: SqliteJournalMode::Wal,
             synchronous: SqliteSynchronous::Normal,
+            read_only: is_read_only(),
         }
     }
 }
@@ -57,6 +114,7 @@ impl DatabaseConfig {
             // WAL mode doesn't work with :memory: databases - use Memory journal mode
             journal_mode: SqliteJournalMode::Memory,
             synchronous: SqliteSynchronous::Off,
+            read_only: false,
         }
     }
 
@@ -71,10 +129,25 @@ impl DatabaseConfig {
         self.auto_migrate = false;
         self
     }
+
+    /// Open the database without write access
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
 }
 
 /// Get the default database path
+///
+/// A path set with [`set_database_path`] or `DEMIARCH_DATABASE` wins over
+/// the per-user location.
 pub fn default_database_path() -> PathBuf {
+    if let Some(path) = DATABASE_PATH.get() {
+        return path.clone();
+    }
+    if let Some(path) = std::env::var_os(DATABASE_PATH_ENV).filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
     if let Some(config_dir) = dirs::config_dir() {
         config_dir.join("demiarch").join("demiarch.db")
     } else {
@@ -97,6 +170,10 @@ pub struct Database {
 impl Database {
     /// Create a new database connection with the given configuration
     pub async fn new(config: DatabaseConfig) -> Result<Self> {
+        if config.read_only {
+            return Self::open_read_only(config).await;
+        }
+
         // Ensure the directory exists
         if config.path.to_string_lossy() != ":memory:" {
             if let Some(parent) = config.path.parent() {
@@ -138,16 +215,70 @@ impl Database {
         // Run migrations if auto_migrate is enabled
         if config.auto_migrate {
             db.migrate().await?;
+
+            // Writes queued while the database was unavailable
+            if let Some(queue) = WriteQueue::for_database(&db) {
+                if let Err(e) = queue.replay(&db).await {
+                    tracing::warn!(error = %e, "Failed to replay write queue");
+                }
+            }
         }
 
         Ok(db)
     }
 
+    /// Open an existing database file without write access
+    ///
+    /// Nothing is created and no migrations run; a snapshot from an older
+    /// schema is rejected rather than half-readable.
+    async fn open_read_only(config: DatabaseConfig) -> Result<Self> {
+        if !config.path.is_file() {
+            bail!(
+                "Read-only mode: database {} does not exist",
+                config.path.display()
+            );
+        }
+
+        let connect_options = SqliteConnectOptions::new()
+            .filename(&config.path)
+            .read_only(true)
+            .create_if_missing(false);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect_with(connect_options)
+            .await
+            .with_context(|| format!("Failed to open database read-only: {:?}", config.path))?;
+
+        let db = Self { pool, config };
+        let status = db.migration_status().await?;
+        if status.needs_migration {
+            bail!(
+                "Read-only mode: {} uses schema v{}, this version of demiarch needs v{}. \
+                 Open a copy without --read-only to upgrade it.",
+                db.path().display(),
+                status.current_version,
+                status.target_version
+            );
+        }
+        Ok(db)
+    }
+
     /// Create a database connection with default configuration
     pub async fn default() -> Result<Self> {
         Self::new(DatabaseConfig::default()).await
     }
 
+    /// The default database from the process-wide connection cache
+    ///
+    /// Opens it on first use; later calls reuse the same pool instead of
+    /// reconnecting and re-running migrations.
+    pub async fn shared() -> Result<Self> {
+        connection_cache::shared()
+            .open(DatabaseConfig::default())
+            .await
+    }
+
     /// Create an in-memory database (useful for testing)
     pub async fn in_memory() -> Result<Self> {
         Self::new(DatabaseConfig::in_memory()).await
@@ -195,45 +326,82 @@ impl Database {
     pub fn path(&self) -> &Path {
         &self.config.path
     }
+
+    /// Whether the connection was opened without write access
+    pub fn is_read_only(&self) -> bool {
+        self.config.read_only
+    }
 }
 
 /// Application-wide database manager
 ///
-/// Manages both the global database and project-specific databases.
+/// Manages both the global database and project-specific databases. Nothing
+/// is opened until a command asks for it, and databases opened through the
+/// manager come from the shared connection cache.
 #[derive(Debug, Clone)]
 pub struct DatabaseManager {
+    /// Configuration for the global database
+    global_config: DatabaseConfig,
     /// Global database for application-wide data (e.g., encrypted keys, global settings)
-    global: Database,
+    global: Arc<OnceCell<Database>>,
 }
 
 impl DatabaseManager {
-    /// Create a new database manager
-    pub async fn new() -> Result<Self> {
-        let global = Database::default().await?;
-        Ok(Self { global })
+    /// Create a database manager for the default global database
+    pub fn new() -> Self {
+        Self::with_config(DatabaseConfig::default())
     }
 
     /// Create a database manager with custom global database path
-    pub async fn with_global_path(path: impl Into<PathBuf>) -> Result<Self> {
-        let global = Database::new(DatabaseConfig::with_path(path)).await?;
-        Ok(Self { global })
+    pub fn with_global_path(path: impl Into<PathBuf>) -> Self {
+        Self::with_config(DatabaseConfig::with_path(path))
+    }
+
+    fn with_config(global_config: DatabaseConfig) -> Self {
+        Self {
+            global_config,
+            global: Arc::new(OnceCell::new()),
+        }
     }
 
     /// Create an in-memory database manager (useful for testing)
     pub async fn in_memory() -> Result<Self> {
-        let global = Database::in_memory().await?;
-        Ok(Self { global })
+        let manager = Self::with_config(DatabaseConfig::in_memory());
+        manager.global().await?;
+        Ok(manager)
     }
 
-    /// Get the global database
-    pub fn global(&self) -> &Database {
-        &self.global
+    /// Get the global database, opening it on first use
+    pub async fn global(&self) -> Result<&Database> {
+        self.global
+            .get_or_try_init(|| connection_cache::shared().open(self.global_config.clone()))
+            .await
+    }
+
+    /// Whether the global database has been opened yet
+    pub fn is_global_open(&self) -> bool {
+        self.global.initialized()
     }
 
     /// Open a project-specific database
+    ///
+    /// Opened read-only when the global database is.
     pub async fn open_project(&self, project_dir: &Path) -> Result<Database> {
         let path = project_database_path(project_dir);
-        Database::new(DatabaseConfig::with_path(path)).await
+        connection_cache::shared()
+            .open(DatabaseConfig::with_path(path).read_only(self.global_config.read_only))
+            .await
+    }
+
+    /// Counters for the shared connection cache
+    pub fn cache_metrics(&self) -> ConnectionCacheMetrics {
+        connection_cache::shared().metrics()
+    }
+}
+
+impl Default for DatabaseManager {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -278,11 +446,62 @@ mod tests {
         // Global database should be accessible
         manager
             .global()
+            .await
+            .expect("Failed to open global database")
             .health_check()
             .await
             .expect("Global database health check failed");
     }
 
+    #[tokio::test]
+    async fn test_database_manager_opens_global_lazily() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("global.db");
+        let manager = DatabaseManager::with_global_path(&path);
+        assert!(!manager.is_global_open());
+        assert!(!path.exists());
+
+        let first = manager.global().await.unwrap().clone();
+        assert!(manager.is_global_open());
+        assert!(path.exists());
+
+        let project = manager.open_project(dir.path()).await.unwrap();
+        let again = manager.open_project(dir.path()).await.unwrap();
+        assert_eq!(project.path(), again.path());
+        assert_eq!(first.path(), manager.clone().global().await.unwrap().path());
+    }
+
+    #[tokio::test]
+    async fn test_read_only_rejects_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.db");
+        let db = Database::new(DatabaseConfig::with_path(&path).read_only(false))
+            .await
+            .unwrap();
+        db.close().await;
+
+        let db = Database::new(DatabaseConfig::with_path(&path).read_only(true))
+            .await
+            .unwrap();
+        assert!(db.is_read_only());
+        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM projects")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(count.0, 0);
+
+        let err: crate::Error = sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'x')")
+            .execute(db.pool())
+            .await
+            .unwrap_err()
+            .into();
+        assert!(matches!(err, crate::Error::ReadOnly(_)));
+
+        let missing = DatabaseConfig::with_path(dir.path().join("missing.db")).read_only(true);
+        assert!(Database::new(missing).await.is_err());
+        assert!(!dir.path().join("missing.db").exists());
+    }
+
     #[tokio::test]
     async fn test_foreign_keys_enabled() {
         let db = Database::in_memory()
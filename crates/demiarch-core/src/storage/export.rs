@@ -0,0 +1,708 @@
+//! Schema introspection and tabular export for analytics
+//!
+//! `demiarch db schema` lists the tables and columns of the database, and
+//! `demiarch db export` writes one table to CSV or Parquet so cost and usage
+//! data can be analyzed in pandas, DuckDB or a spreadsheet:
+//!
+//! ```text
+//! demiarch db export --table costs --format parquet --output costs.parquet \
+//!     --columns model,input_tokens,output_tokens,created_at --since 2025-01-01
+//! ```
+//!
+//! Rows are streamed from SQLite and written as they arrive; Parquet output
+//! is flushed in row groups of [`BATCH_ROWS`], so large tables never have to
+//! fit in memory. Column types follow the declared SQLite type: integers and
+//! reals keep their numeric type, timestamps are exported as ISO-8601 text.
+//! Artifact contents and checkpoint snapshots kept in the content store are
+//! exported decoded, as the text they were saved as.
+
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::builder::{BinaryBuilder, Float64Builder, Int64Builder, StringBuilder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use chrono::NaiveDate;
+use futures_util::TryStreamExt;
+use parquet::arrow::ArrowWriter;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+
+use crate::storage::{content, Database};
+use crate::{Error, Result};
+
+/// Rows per Parquet row group
+pub const BATCH_ROWS: usize = 8192;
+
+/// Tables that are never exported
+///
+/// `content_blobs` only holds compressed copies of the columns listed in
+/// [`STORED_CONTENT`], which are exported decoded instead.
+const HIDDEN_TABLES: &[&str] = &[
+    "encrypted_keys",
+    "project_secrets",
+    "_migrations",
+    "content_blobs",
+];
+
+/// Text columns whose content may live in `content_blobs`, with the column
+/// holding its hash: `(table, column, hash column)`
+const STORED_CONTENT: &[(&str, &str, &str)] = &[
+    ("generation_artifacts", "content", "content_hash"),
+    ("checkpoints", "snapshot_data", "snapshot_hash"),
+];
+
+/// Short names accepted for `--table`
+const TABLE_ALIASES: &[(&str, &str)] = &[
+    ("costs", "llm_costs"),
+    ("daily_costs", "daily_cost_summaries"),
+    ("usage", "usage_rollups"),
+    ("artifacts", "generation_artifacts"),
+    ("findings", "review_findings"),
+    ("branches", "conversation_branches"),
+];
+
+/// Output file format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            other => Err(Error::InvalidInput(format!(
+                "Unknown export format '{}'. Use csv or parquet",
+                other
+            ))),
+        }
+    }
+}
+
+/// Storage class a column is exported as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnKind {
+    Integer,
+    Real,
+    Text,
+    Blob,
+}
+
+impl ColumnKind {
+    /// Kind for a declared SQLite type, following SQLite's affinity rules
+    /// except that untyped and date-like columns are exported as text
+    fn from_declared(declared: &str) -> Self {
+        let declared = declared.to_uppercase();
+        if declared.contains("INT") {
+            Self::Integer
+        } else if ["REAL", "FLOA", "DOUB"]
+            .iter()
+            .any(|t| declared.contains(t))
+        {
+            Self::Real
+        } else if declared.contains("BLOB") {
+            Self::Blob
+        } else {
+            Self::Text
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        match self {
+            Self::Integer => DataType::Int64,
+            Self::Real => DataType::Float64,
+            Self::Text => DataType::Utf8,
+            Self::Blob => DataType::Binary,
+        }
+    }
+
+    /// Expression selecting the column of table `t` with a predictable
+    /// storage class
+    fn select(&self, column: &str) -> String {
+        match self {
+            Self::Integer => format!("CAST(t.\"{0}\" AS INTEGER) AS \"{0}\"", column),
+            Self::Real => format!("CAST(t.\"{0}\" AS REAL) AS \"{0}\"", column),
+            Self::Text => format!("CAST(t.\"{0}\" AS TEXT) AS \"{0}\"", column),
+            Self::Blob => format!("t.\"{0}\" AS \"{0}\"", column),
+        }
+    }
+}
+
+/// A column of a table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// Type as declared in the schema
+    pub declared_type: String,
+    pub kind: ColumnKind,
+    pub nullable: bool,
+    pub primary_key: bool,
+}
+
+/// A table and its columns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableInfo {
+    pub name: String,
+    pub rows: i64,
+    pub columns: Vec<ColumnInfo>,
+}
+
+/// What to export from a table
+#[derive(Debug, Clone, Default)]
+pub struct TableExport {
+    pub table: String,
+    pub format: ExportFormat,
+    /// Columns to include, in order; all columns when empty
+    pub columns: Vec<String>,
+    /// First day to include
+    pub since: Option<NaiveDate>,
+    /// Last day to include
+    pub until: Option<NaiveDate>,
+    /// Column the date range applies to; defaults to `created_at`, then `date`
+    pub date_column: Option<String>,
+}
+
+impl TableExport {
+    pub fn new(table: impl Into<String>, format: ExportFormat) -> Self {
+        Self {
+            table: table.into(),
+            format,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_columns(mut self, columns: Vec<String>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn with_range(mut self, since: Option<NaiveDate>, until: Option<NaiveDate>) -> Self {
+        self.since = since;
+        self.until = until;
+        self
+    }
+
+    pub fn with_date_column(mut self, column: impl Into<String>) -> Self {
+        self.date_column = Some(column.into());
+        self
+    }
+}
+
+/// Resolve an alias such as `costs` to its table name
+pub fn resolve_table(name: &str) -> &str {
+    TABLE_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, table)| *table)
+        .unwrap_or(name)
+}
+
+/// List exportable tables with their columns and row counts
+pub async fn list_tables(db: &Database) -> Result<Vec<TableInfo>> {
+    let names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+         ORDER BY name",
+    )
+    .fetch_all(db.pool())
+    .await?;
+
+    let mut tables = Vec::new();
+    for name in names {
+        if HIDDEN_TABLES.contains(&name.as_str()) {
+            continue;
+        }
+        tables.push(describe_table(db, &name).await?);
+    }
+    Ok(tables)
+}
+
+/// Columns and row count of one table
+pub async fn describe_table(db: &Database, table: &str) -> Result<TableInfo> {
+    let table = resolve_table(table);
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?)",
+    )
+    .bind(table)
+    .fetch_one(db.pool())
+    .await?;
+    if !exists || HIDDEN_TABLES.contains(&table) {
+        return Err(Error::NotFound(format!("Table {}", table)));
+    }
+
+    // The name was checked against sqlite_master, so quoting it is safe
+    let columns = sqlx::query(&format!("PRAGMA table_info(\"{}\")", table))
+        .fetch_all(db.pool())
+        .await?
+        .iter()
+        .map(|row| {
+            let declared_type: String = row.get("type");
+            ColumnInfo {
+                name: row.get("name"),
+                kind: ColumnKind::from_declared(&declared_type),
+                declared_type,
+                nullable: row.get::<i64, _>("notnull") == 0,
+                primary_key: row.get::<i64, _>("pk") > 0,
+            }
+        })
+        .collect();
+    let rows: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM \"{}\"", table))
+        .fetch_one(db.pool())
+        .await?;
+
+    Ok(TableInfo {
+        name: table.to_string(),
+        rows,
+        columns,
+    })
+}
+
+/// Export a table to `writer`, returning the number of rows written
+pub async fn export_table<W: Write + Send>(
+    db: &Database,
+    export: &TableExport,
+    writer: W,
+) -> Result<u64> {
+    let info = describe_table(db, &export.table).await?;
+    let columns = select_columns(&info, &export.columns)?;
+    let query = build_query(&info, &columns, export)?;
+
+    let mut statement = sqlx::query(&query.sql);
+    for bind in query.binds {
+        statement = statement.bind(bind);
+    }
+    let mut rows = statement.fetch(db.pool());
+
+    let layout = RowLayout {
+        columns,
+        blobs: query.blobs,
+    };
+    match export.format {
+        ExportFormat::Csv => {
+            let mut out = CsvSink::new(writer, layout)?;
+            while let Some(row) = rows.try_next().await? {
+                out.write_row(&row)?;
+            }
+            out.finish()
+        }
+        ExportFormat::Parquet => {
+            let mut out = ParquetSink::new(writer, layout)?;
+            while let Some(row) = rows.try_next().await? {
+                out.write_row(&row)?;
+            }
+            out.finish()
+        }
+    }
+}
+
+fn select_columns(info: &TableInfo, requested: &[String]) -> Result<Vec<ColumnInfo>> {
+    if requested.is_empty() {
+        return Ok(info.columns.clone());
+    }
+    requested
+        .iter()
+        .map(|name| {
+            info.columns
+                .iter()
+                .find(|c| c.name == *name)
+                .cloned()
+                .ok_or_else(|| {
+                    Error::InvalidInput(format!(
+                        "Table {} has no column '{}'. Columns: {}",
+                        info.name,
+                        name,
+                        info.columns
+                            .iter()
+                            .map(|c| c.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                })
+        })
+        .collect()
+}
+
+/// SQL for an export and the values bound to it
+struct ExportQuery {
+    sql: String,
+    binds: Vec<String>,
+    /// For each exported column, where its `content_blobs` data is selected
+    blobs: Vec<Option<usize>>,
+}
+
+fn build_query(
+    info: &TableInfo,
+    columns: &[ColumnInfo],
+    export: &TableExport,
+) -> Result<ExportQuery> {
+    let mut select: Vec<String> = columns.iter().map(|c| c.kind.select(&c.name)).collect();
+    let mut joins = String::new();
+    let mut blobs = Vec::with_capacity(columns.len());
+    for column in columns {
+        let stored = STORED_CONTENT
+            .iter()
+            .find(|(table, name, _)| *table == info.name && *name == column.name);
+        blobs.push(stored.map(|(_, _, hash_column)| {
+            let alias = format!("b{}", select.len());
+            joins.push_str(&format!(
+                " LEFT JOIN content_blobs {0} ON {0}.hash = t.\"{1}\"",
+                alias, hash_column
+            ));
+            select.push(format!("{}.data", alias));
+            select.len() - 1
+        }));
+    }
+    let mut sql = format!(
+        "SELECT {} FROM \"{}\" t{}",
+        select.join(", "),
+        info.name,
+        joins
+    );
+    let mut binds = Vec::new();
+
+    if export.since.is_some() || export.until.is_some() {
+        let has = |name: &str| info.columns.iter().any(|c| c.name == name);
+        let date_column = match &export.date_column {
+            Some(column) if has(column) => column.as_str(),
+            Some(column) => {
+                return Err(Error::InvalidInput(format!(
+                    "Table {} has no column '{}'",
+                    info.name, column
+                )))
+            }
+            None if has("created_at") => "created_at",
+            None if has("date") => "date",
+            None => {
+                return Err(Error::InvalidInput(format!(
+                    "Table {} has no created_at or date column; pass --date-column",
+                    info.name
+                )))
+            }
+        };
+
+        let mut conditions = Vec::new();
+        if let Some(since) = export.since {
+            conditions.push(format!("julianday(t.\"{}\") >= julianday(?)", date_column));
+            binds.push(since.to_string());
+        }
+        if let Some(until) = export.until {
+            // Inclusive of the whole last day
+            conditions.push(format!(
+                "julianday(t.\"{}\") < julianday(?, '+1 day')",
+                date_column
+            ));
+            binds.push(until.to_string());
+        }
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
+    }
+    sql.push_str(" ORDER BY t.rowid");
+    Ok(ExportQuery { sql, binds, blobs })
+}
+
+/// The exported columns of a row, and where stored content is joined in
+struct RowLayout {
+    columns: Vec<ColumnInfo>,
+    blobs: Vec<Option<usize>>,
+}
+
+impl RowLayout {
+    /// Text of column `index`, decoded from `content_blobs` if it is there
+    fn text(&self, row: &SqliteRow, index: usize) -> Result<Option<String>> {
+        let inline = row.try_get::<Option<String>, _>(index)?;
+        let Some(blob_index) = self.blobs[index] else {
+            return Ok(inline);
+        };
+        match row.try_get::<Option<Vec<u8>>, _>(blob_index)? {
+            Some(blob) => content::decode(inline.unwrap_or_default(), Some(blob)).map(Some),
+            None => Ok(inline),
+        }
+    }
+}
+
+struct CsvSink<W: Write> {
+    writer: csv::Writer<W>,
+    layout: RowLayout,
+    rows: u64,
+}
+
+impl<W: Write> CsvSink<W> {
+    fn new(writer: W, layout: RowLayout) -> Result<Self> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer
+            .write_record(layout.columns.iter().map(|c| c.name.as_str()))
+            .map_err(csv_error)?;
+        Ok(Self {
+            writer,
+            layout,
+            rows: 0,
+        })
+    }
+
+    fn write_row(&mut self, row: &SqliteRow) -> Result<()> {
+        let mut record = Vec::with_capacity(self.layout.columns.len());
+        for (i, column) in self.layout.columns.iter().enumerate() {
+            // NULL is written as an empty field
+            let field = match column.kind {
+                ColumnKind::Integer => row.try_get::<Option<i64>, _>(i)?.map(|v| v.to_string()),
+                ColumnKind::Real => row.try_get::<Option<f64>, _>(i)?.map(|v| v.to_string()),
+                ColumnKind::Text => self.layout.text(row, i)?,
+                ColumnKind::Blob => row.try_get::<Option<Vec<u8>>, _>(i)?.map(hex::encode),
+            };
+            record.push(field.unwrap_or_default());
+        }
+        self.writer.write_record(&record).map_err(csv_error)?;
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<u64> {
+        self.writer.flush()?;
+        Ok(self.rows)
+    }
+}
+
+fn csv_error(e: csv::Error) -> Error {
+    Error::Other(format!("CSV export failed: {}", e))
+}
+
+enum ColumnBuilder {
+    Integer(Int64Builder),
+    Real(Float64Builder),
+    Text(StringBuilder),
+    Blob(BinaryBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(kind: ColumnKind) -> Self {
+        match kind {
+            ColumnKind::Integer => Self::Integer(Int64Builder::with_capacity(BATCH_ROWS)),
+            ColumnKind::Real => Self::Real(Float64Builder::with_capacity(BATCH_ROWS)),
+            ColumnKind::Text => Self::Text(StringBuilder::new()),
+            ColumnKind::Blob => Self::Blob(BinaryBuilder::new()),
+        }
+    }
+
+    fn append(&mut self, row: &SqliteRow, index: usize, layout: &RowLayout) -> Result<()> {
+        match self {
+            Self::Integer(b) => b.append_option(row.try_get::<Option<i64>, _>(index)?),
+            Self::Real(b) => b.append_option(row.try_get::<Option<f64>, _>(index)?),
+            Self::Text(b) => b.append_option(layout.text(row, index)?),
+            Self::Blob(b) => b.append_option(row.try_get::<Option<Vec<u8>>, _>(index)?),
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Integer(b) => Arc::new(b.finish()),
+            Self::Real(b) => Arc::new(b.finish()),
+            Self::Text(b) => Arc::new(b.finish()),
+            Self::Blob(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+struct ParquetSink<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    schema: SchemaRef,
+    layout: RowLayout,
+    builders: Vec<ColumnBuilder>,
+    pending: usize,
+    rows: u64,
+}
+
+impl<W: Write + Send> ParquetSink<W> {
+    fn new(writer: W, layout: RowLayout) -> Result<Self> {
+        let schema: SchemaRef = Arc::new(Schema::new(
+            layout
+                .columns
+                .iter()
+                .map(|c| Field::new(&c.name, c.kind.data_type(), true))
+                .collect::<Vec<_>>(),
+        ));
+        let writer = ArrowWriter::try_new(writer, schema.clone(), None).map_err(parquet_error)?;
+        Ok(Self {
+            writer,
+            schema,
+            builders: layout
+                .columns
+                .iter()
+                .map(|c| ColumnBuilder::new(c.kind))
+                .collect(),
+            layout,
+            pending: 0,
+            rows: 0,
+        })
+    }
+
+    fn write_row(&mut self, row: &SqliteRow) -> Result<()> {
+        for (i, builder) in self.builders.iter_mut().enumerate() {
+            builder.append(row, i, &self.layout)?;
+        }
+        self.pending += 1;
+        self.rows += 1;
+        if self.pending >= BATCH_ROWS {
+            self.flush_batch()?;
+        }
+        Ok(())
+    }
+
+    fn flush_batch(&mut self) -> Result<()> {
+        if self.pending == 0 {
+            return Ok(());
+        }
+        let arrays = self.builders.iter_mut().map(|b| b.finish()).collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)
+            .map_err(|e| Error::Other(format!("Parquet export failed: {}", e)))?;
+        self.writer.write(&batch).map_err(parquet_error)?;
+        self.pending = 0;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<u64> {
+        self.flush_batch()?;
+        self.writer.close().map_err(parquet_error)?;
+        Ok(self.rows)
+    }
+}
+
+fn parquet_error(e: parquet::errors::ParquetError) -> Error {
+    Error::Other(format!("Parquet export failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    async fn db_with_costs() -> Database {
+        let db = Database::in_memory().await.unwrap();
+        for (id, model, tokens, created_at) in [
+            ("c1", "gpt-4o", 100, "2025-01-01 10:00:00"),
+            ("c2", "claude", 250, "2025-01-15 12:00:00"),
+            ("c3", "claude", 50, "2025-02-01 09:00:00"),
+        ] {
+            sqlx::query(
+                "INSERT INTO llm_costs (id, model, input_tokens, input_cost_usd, created_at)
+                 VALUES (?, ?, ?, 0.5, ?)",
+            )
+            .bind(id)
+            .bind(model)
+            .bind(tokens)
+            .bind(created_at)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+        db
+    }
+
+    #[tokio::test]
+    async fn test_describes_tables_and_hides_secrets() {
+        let db = db_with_costs().await;
+        let tables = list_tables(&db).await.unwrap();
+        assert!(tables.iter().all(|t| t.name != "encrypted_keys"));
+
+        let costs = describe_table(&db, "costs").await.unwrap();
+        assert_eq!(costs.name, "llm_costs");
+        assert_eq!(costs.rows, 3);
+        let tokens = costs
+            .columns
+            .iter()
+            .find(|c| c.name == "input_tokens")
+            .unwrap();
+        assert_eq!(tokens.kind, ColumnKind::Integer);
+        assert!(describe_table(&db, "encrypted_keys").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exports_csv_with_columns_and_dates() {
+        let db = db_with_costs().await;
+        let export = TableExport::new("costs", ExportFormat::Csv)
+            .with_columns(vec!["id".into(), "model".into(), "input_tokens".into()])
+            .with_range(
+                NaiveDate::from_ymd_opt(2025, 1, 2),
+                NaiveDate::from_ymd_opt(2025, 2, 1),
+            );
+        let mut out = Vec::new();
+        let rows = export_table(&db, &export, &mut out).await.unwrap();
+        assert_eq!(rows, 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "id,model,input_tokens\nc2,claude,250\nc3,claude,50\n"
+        );
+
+        let bad = TableExport::new("costs", ExportFormat::Csv).with_columns(vec!["nope".into()]);
+        assert!(export_table(&db, &bad, Vec::new()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exports_stored_artifact_content_decoded() {
+        let db = Database::in_memory().await.unwrap();
+        sqlx::query(
+            "INSERT INTO generations (id, description, output_dir) VALUES ('g1', 'gen', '/tmp')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        let hash = content::ContentStore::new(db.pool())
+            .put("pub fn a() {}\n")
+            .await
+            .unwrap();
+        for (id, path, inline, hash) in [
+            ("a1", "a.rs", "", Some(hash.as_str())),
+            ("a2", "b.rs", "pub fn b() {}", None),
+        ] {
+            sqlx::query(
+                "INSERT INTO generation_artifacts (id, generation_id, file_path, content, content_hash) VALUES (?, 'g1', ?, ?, ?)",
+            )
+            .bind(id)
+            .bind(path)
+            .bind(inline)
+            .bind(hash)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+
+        // Both joined tables have a created_at; the range applies to the artifact's
+        let export = TableExport::new("artifacts", ExportFormat::Csv)
+            .with_columns(vec!["file_path".into(), "content".into()])
+            .with_range(NaiveDate::from_ymd_opt(2000, 1, 1), None);
+        let mut out = Vec::new();
+        assert_eq!(export_table(&db, &export, &mut out).await.unwrap(), 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "file_path,content\na.rs,\"pub fn a() {}\n\"\nb.rs,pub fn b() {}\n"
+        );
+
+        assert!(describe_table(&db, "content_blobs").await.is_err());
+        let tables = list_tables(&db).await.unwrap();
+        assert!(tables.iter().all(|t| t.name != "content_blobs"));
+    }
+
+    #[tokio::test]
+    async fn test_exports_parquet() {
+        let db = db_with_costs().await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("costs.parquet");
+        let export = TableExport::new("llm_costs", ExportFormat::Parquet);
+        let rows = export_table(&db, &export, std::fs::File::create(&path).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(rows, 3);
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+    }
+}
@@ -0,0 +1,466 @@
+//! SQLite query timing and the slow-query log
+//!
+//! sqlx reports every statement it runs as a `sqlx::query` tracing event
+//! carrying the SQL, rows returned/affected and elapsed time. When
+//! `database.instrument` is enabled, frontends add [`layer`] to their
+//! subscriber (or call [`init`] if they have none) so those events are
+//! aggregated per statement into [`query_stats`], and statements slower than
+//! `database.slow_query_ms` are appended to the slow-query log reviewed with
+//! `demiarch db slow-queries`.
+//!
+//! Bind values never appear in sqlx's events, and literals written inline in
+//! the SQL are replaced with `?` by [`redact_sql`] before anything is kept.
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{Filtered, Targets};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config::DatabaseSettings;
+use crate::error::Error;
+use crate::Result;
+
+/// Target sqlx logs statements under
+const QUERY_TARGET: &str = "sqlx::query";
+
+/// Distinct statements tracked before new ones are ignored
+const MAX_TRACKED_STATEMENTS: usize = 1000;
+
+/// Path to the slow-query log
+pub fn slow_query_log_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".demiarch")
+        .join("slow-queries.jsonl")
+}
+
+/// Timing for one statement, aggregated over its calls
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryStat {
+    /// Redacted SQL
+    pub sql: String,
+    /// Times the statement ran
+    pub calls: u64,
+    /// Total time spent, in microseconds
+    pub total_us: u64,
+    /// Slowest call, in microseconds
+    pub max_us: u64,
+    /// Rows returned plus rows affected, over all calls
+    pub rows: u64,
+}
+
+impl QueryStat {
+    /// Average time per call, in microseconds
+    pub fn avg_us(&self) -> u64 {
+        self.total_us.checked_div(self.calls).unwrap_or(0)
+    }
+
+    fn add(&mut self, elapsed_us: u64, rows: u64) {
+        self.calls += 1;
+        self.total_us += elapsed_us;
+        self.max_us = self.max_us.max(elapsed_us);
+        self.rows += rows;
+    }
+}
+
+/// One entry in the slow-query log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlowQuery {
+    pub at: DateTime<Utc>,
+    /// Redacted SQL
+    pub sql: String,
+    pub elapsed_ms: f64,
+    pub rows_returned: u64,
+    pub rows_affected: u64,
+}
+
+/// Per-statement stats and the slow-query log writer
+struct Recorder {
+    stats: Mutex<HashMap<String, QueryStat>>,
+    slow_threshold: Duration,
+    log_path: PathBuf,
+    log: Mutex<Option<File>>,
+}
+
+impl Recorder {
+    fn record(&self, query: &QueryEvent) {
+        let sql = redact_sql(query.sql());
+        if sql.is_empty() {
+            return;
+        }
+        let elapsed_us = (query.elapsed_secs * 1_000_000.0) as u64;
+        let rows = query.rows_returned + query.rows_affected;
+
+        if let Ok(mut stats) = self.stats.lock() {
+            if let Some(stat) = stats.get_mut(&sql) {
+                stat.add(elapsed_us, rows);
+            } else if stats.len() < MAX_TRACKED_STATEMENTS {
+                let mut stat = QueryStat {
+                    sql: sql.clone(),
+                    ..Default::default()
+                };
+                stat.add(elapsed_us, rows);
+                stats.insert(sql.clone(), stat);
+            }
+        }
+
+        if query.elapsed_secs >= self.slow_threshold.as_secs_f64() {
+            self.log_slow(SlowQuery {
+                at: Utc::now(),
+                sql,
+                elapsed_ms: query.elapsed_secs * 1000.0,
+                rows_returned: query.rows_returned,
+                rows_affected: query.rows_affected,
+            });
+        }
+    }
+
+    fn log_slow(&self, entry: SlowQuery) {
+        let Ok(mut log) = self.log.lock() else {
+            return;
+        };
+        if log.is_none() {
+            if let Some(parent) = self.log_path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            *log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.log_path)
+                .ok();
+        }
+        if let (Some(file), Ok(line)) = (log.as_mut(), serde_json::to_string(&entry)) {
+            // Logging from here would feed back into the subscriber
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    fn snapshot(&self) -> Vec<QueryStat> {
+        let mut stats: Vec<_> = self
+            .stats
+            .lock()
+            .map(|stats| stats.values().cloned().collect())
+            .unwrap_or_default();
+        stats.sort_by(|a, b| b.total_us.cmp(&a.total_us).then(a.sql.cmp(&b.sql)));
+        stats
+    }
+}
+
+/// Fields of a `sqlx::query` event
+#[derive(Default)]
+struct QueryEvent {
+    summary: String,
+    statement: String,
+    rows_returned: u64,
+    rows_affected: u64,
+    elapsed_secs: f64,
+}
+
+impl QueryEvent {
+    /// The full statement; sqlx leaves it empty when the summary is the
+    /// whole statement
+    fn sql(&self) -> &str {
+        if self.statement.trim().is_empty() {
+            &self.summary
+        } else {
+            &self.statement
+        }
+    }
+}
+
+impl Visit for QueryEvent {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "summary" => self.summary = value.to_string(),
+            "db.statement" => self.statement = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            "rows_returned" => self.rows_returned = value,
+            "rows_affected" => self.rows_affected = value,
+            _ => {}
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_u64(field, value.max(0) as u64);
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = value;
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+/// Tracing layer that times sqlx statements
+pub struct QueryLayer {
+    recorder: Arc<Recorder>,
+}
+
+impl QueryLayer {
+    /// Record into `log_path`, logging statements at or over `slow_threshold`
+    pub fn new(slow_threshold: Duration, log_path: impl Into<PathBuf>) -> Self {
+        Self {
+            recorder: Arc::new(Recorder {
+                stats: Mutex::new(HashMap::new()),
+                slow_threshold,
+                log_path: log_path.into(),
+                log: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// A layer for `database.*` settings, writing to [`slow_query_log_path`]
+    pub fn from_settings(settings: &DatabaseSettings) -> Self {
+        Self::new(
+            Duration::from_millis(settings.slow_query_ms),
+            slow_query_log_path(),
+        )
+    }
+
+    /// Statements seen by this layer, slowest total first
+    pub fn stats(&self) -> Vec<QueryStat> {
+        self.recorder.snapshot()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for QueryLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if event.metadata().target() != QUERY_TARGET {
+            return;
+        }
+        let mut query = QueryEvent::default();
+        event.record(&mut query);
+        self.recorder.record(&query);
+    }
+}
+
+/// The recorder behind [`query_stats`]
+static INSTALLED: OnceLock<Arc<Recorder>> = OnceLock::new();
+
+/// The query layer for `settings`, or `None` when instrumentation is off
+///
+/// Only `sqlx::query` events reach it, whatever the other layers' filters.
+pub fn layer<S>(settings: &DatabaseSettings) -> Option<Filtered<QueryLayer, Targets, S>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    if !settings.instrument {
+        return None;
+    }
+    let layer = QueryLayer::from_settings(settings);
+    let _ = INSTALLED.set(layer.recorder.clone());
+    Some(layer.with_filter(Targets::new().with_target(QUERY_TARGET, Level::TRACE)))
+}
+
+/// Install a subscriber holding only the query layer
+///
+/// For frontends without their own tracing subscriber. Returns whether
+/// instrumentation is now active.
+pub fn init(settings: &DatabaseSettings) -> bool {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    match layer(settings) {
+        Some(layer) => tracing_subscriber::registry()
+            .with(layer)
+            .try_init()
+            .is_ok(),
+        None => false,
+    }
+}
+
+/// Statements timed in this process, slowest total first
+///
+/// Empty unless instrumentation is enabled.
+pub fn query_stats() -> Vec<QueryStat> {
+    INSTALLED
+        .get()
+        .map(|recorder| recorder.snapshot())
+        .unwrap_or_default()
+}
+
+/// Read the slow-query log, skipping lines that don't parse
+pub fn read_slow_queries(path: &Path) -> Result<Vec<SlowQuery>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::Io(e)),
+    };
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(Error::Io)?;
+        if let Ok(entry) = serde_json::from_str(&line) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Group slow-query log entries by statement, slowest total first
+pub fn hotspots(entries: &[SlowQuery]) -> Vec<QueryStat> {
+    let mut by_sql: HashMap<&str, QueryStat> = HashMap::new();
+    for entry in entries {
+        by_sql
+            .entry(&entry.sql)
+            .or_insert_with(|| QueryStat {
+                sql: entry.sql.clone(),
+                ..Default::default()
+            })
+            .add(
+                (entry.elapsed_ms * 1000.0) as u64,
+                entry.rows_returned + entry.rows_affected,
+            );
+    }
+    let mut stats: Vec<_> = by_sql.into_values().collect();
+    stats.sort_by(|a, b| b.total_us.cmp(&a.total_us).then(a.sql.cmp(&b.sql)));
+    stats
+}
+
+/// Collapse whitespace and replace inline literals with `?`
+///
+/// String, blob and numeric literals are replaced; quoted identifiers,
+/// keywords and existing placeholders (`?`, `?1`, `:name`) are kept.
+pub fn redact_sql(sql: &str) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::with_capacity(sql.len());
+    let mut i = 0;
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let prev_ident = |out: &String| out.chars().last().is_some_and(is_ident);
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            while i < chars.len() && chars[i].is_whitespace() {
+                i += 1;
+            }
+            if !out.is_empty() && i < chars.len() {
+                out.push(' ');
+            }
+            continue;
+        }
+
+        // x'0A1B' blob literal: drop the prefix, the string branch does the rest
+        if (c == 'x' || c == 'X') && chars.get(i + 1) == Some(&'\'') && !prev_ident(&out) {
+            i += 1;
+            continue;
+        }
+
+        if c == '\'' {
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == '\'' {
+                    if chars.get(i + 1) == Some(&'\'') {
+                        i += 2;
+                        continue;
+                    }
+                    break;
+                }
+                i += 1;
+            }
+            i += 1;
+            out.push('?');
+            continue;
+        }
+
+        if c == '"' || c == '`' {
+            out.push(c);
+            i += 1;
+            while i < chars.len() {
+                out.push(chars[i]);
+                i += 1;
+                if chars[i - 1] == c {
+                    break;
+                }
+            }
+            continue;
+        }
+
+        if c.is_ascii_digit() && !prev_ident(&out) && !out.ends_with('?') {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            out.push('?');
+            continue;
+        }
+
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_redact_sql() {
+        assert_eq!(
+            redact_sql("SELECT *\n  FROM features\n  WHERE id = ? AND status = 'done'"),
+            "SELECT * FROM features WHERE id = ? AND status = ?"
+        );
+        assert_eq!(
+            redact_sql("UPDATE t SET n = 42, f = 1.5e3, k = 'it''s', b = x'00ff' WHERE c2 = ?1"),
+            "UPDATE t SET n = ?, f = ?, k = ?, b = ? WHERE c2 = ?1"
+        );
+        assert_eq!(
+            redact_sql("SELECT \"col 1\" FROM t LIMIT :limit"),
+            "SELECT \"col 1\" FROM t LIMIT :limit"
+        );
+    }
+
+    #[test]
+    fn test_layer_records_stats_and_slow_queries() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("slow.jsonl");
+        let layer = QueryLayer::new(Duration::from_millis(100), &log);
+        let recorder = layer.recorder.clone();
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            for elapsed in [0.01, 0.25] {
+                tracing::debug!(
+                    target: "sqlx::query",
+                    summary = "SELECT * FROM features WHERE id = 'f1'",
+                    db.statement = "",
+                    rows_affected = 0u64,
+                    rows_returned = 3u64,
+                    elapsed_secs = elapsed,
+                );
+            }
+            tracing::debug!(target: "demiarch", "unrelated");
+        });
+
+        let stats = recorder.snapshot();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].sql, "SELECT * FROM features WHERE id = ?");
+        assert_eq!(stats[0].calls, 2);
+        assert_eq!(stats[0].rows, 6);
+        assert_eq!(stats[0].max_us, 250_000);
+
+        let slow = read_slow_queries(&log).unwrap();
+        assert_eq!(slow.len(), 1);
+        assert_eq!(slow[0].rows_returned, 3);
+        let hot = hotspots(&slow);
+        assert_eq!(hot[0].calls, 1);
+        assert_eq!(hot[0].avg_us(), 250_000);
+    }
+}
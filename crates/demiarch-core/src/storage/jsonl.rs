@@ -30,17 +30,29 @@
 //!
 //! Note: `encrypted_keys` and `phase_templates` are NOT exported for security
 //! and because phase_templates are built-in defaults.
+//!
+//! # Large tables
+//!
+//! Export streams rows from a database cursor straight into a buffered file,
+//! and import reads one line at a time and commits in transactions of
+//! [`SyncOptions::chunk_rows`], so memory use does not grow with table size.
+//! With [`SyncOptions::compress`] each table is written as
+//! `<table>.jsonl.zst`; import reads whichever of the two files exists.
 
 use base64::Engine;
 use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Sqlite, SqliteConnection, SqlitePool, Transaction};
 use std::collections::HashMap;
 use std::fs::{self, File};
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 
+use super::content;
 use crate::error::Error;
+use crate::progress::{Progress, Stage};
 use crate::Result;
 
 /// Default sync directory name within project
@@ -64,6 +76,33 @@ pub const EXPORTABLE_TABLES: &[&str] = &[
     "learned_skills",
 ];
 
+/// Extension of zstd-compressed table files
+const COMPRESSED_EXTENSION: &str = "jsonl.zst";
+
+/// Bytes buffered before a table file is written to
+const WRITE_CHUNK_BYTES: usize = 256 * 1024;
+
+/// zstd level for compressed exports; favours speed over ratio
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Options for streaming export and import
+#[derive(Debug, Clone)]
+pub struct SyncOptions {
+    /// Write each table as `<table>.jsonl.zst` instead of plain JSONL
+    pub compress: bool,
+    /// Rows between progress updates, and rows per transaction on import
+    pub chunk_rows: usize,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            compress: false,
+            chunk_rows: 500,
+        }
+    }
+}
+
 // =============================================================================
 // Record Types - One struct per table for type-safe export/import
 // =============================================================================
@@ -130,6 +169,10 @@ pub struct MessageRecord {
     pub model: Option<String>,
     pub tokens_used: Option<i32>,
     pub created_at: String,
+    #[serde(default)]
+    pub parent_message_id: Option<String>,
+    #[serde(default)]
+    pub variant_of: Option<String>,
 }
 
 /// Context entry record for JSONL export
@@ -147,6 +190,10 @@ pub struct ContextEntryRecord {
     pub embedding_model: String,
     pub embedding_json: String,
     pub tokens_estimated: i32,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub consolidated_from: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -173,6 +220,8 @@ struct CheckpointRow {
     feature_id: Option<String>,
     description: String,
     snapshot_data: String,
+    /// Compressed snapshot from the content store, if it was moved there
+    snapshot_blob: Option<Vec<u8>>,
     size_bytes: i64,
     signature: Vec<u8>,
     created_at: String,
@@ -223,6 +272,8 @@ pub struct DocumentRecord {
     pub generation_cost_usd: Option<f64>,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub language: Option<String>,
 }
 
 /// Document version record for JSONL export
@@ -323,6 +374,9 @@ pub struct SyncMetadata {
     pub record_counts: HashMap<String, usize>,
     /// Total records exported
     pub total_records: usize,
+    /// Whether table files were written zstd-compressed
+    #[serde(default)]
+    pub compressed: bool,
 }
 
 /// Result of an export operation
@@ -360,6 +414,24 @@ pub struct ImportResult {
 /// # Returns
 /// Export result with metadata and file paths
 pub async fn export_to_jsonl(pool: &SqlitePool, project_dir: &Path) -> Result<ExportResult> {
+    export_to_jsonl_with(
+        pool,
+        project_dir,
+        &SyncOptions::default(),
+        &Progress::hidden(),
+    )
+    .await
+}
+
+/// Export all database tables, streaming each one to disk
+///
+/// Reports per-table progress through `progress`.
+pub async fn export_to_jsonl_with(
+    pool: &SqlitePool,
+    project_dir: &Path,
+    options: &SyncOptions,
+    progress: &Progress,
+) -> Result<ExportResult> {
     let sync_dir = project_dir.join(SYNC_DIR);
 
     // Create sync directory if it doesn't exist
@@ -371,8 +443,15 @@ pub async fn export_to_jsonl(pool: &SqlitePool, project_dir: &Path) -> Result<Ex
 
     // Export each table
     for table in EXPORTABLE_TABLES {
-        let file_path = sync_dir.join(format!("{}.jsonl", table));
-        let count = export_table(pool, table, &file_path).await?;
+        let file_path = table_path(&sync_dir, table, options.compress);
+        let count = export_table(pool, table, &file_path, options, progress).await?;
+
+        // Drop the other format so import can't pick up a stale copy
+        let stale = table_path(&sync_dir, table, !options.compress);
+        if stale.exists() {
+            fs::remove_file(&stale).map_err(Error::Io)?;
+        }
+
         record_counts.insert(table.to_string(), count);
         total_records += count;
         files_written.push(file_path);
@@ -384,6 +463,7 @@ pub async fn export_to_jsonl(pool: &SqlitePool, project_dir: &Path) -> Result<Ex
         schema_version: crate::storage::CURRENT_VERSION,
         record_counts: record_counts.clone(),
         total_records,
+        compressed: options.compress,
     };
 
     let metadata_path = sync_dir.join("_metadata.json");
@@ -399,207 +479,347 @@ pub async fn export_to_jsonl(pool: &SqlitePool, project_dir: &Path) -> Result<Ex
     })
 }
 
+/// Path of a table's file in either format
+fn table_path(sync_dir: &Path, table: &str, compressed: bool) -> PathBuf {
+    if compressed {
+        sync_dir.join(format!("{}.{}", table, COMPRESSED_EXTENSION))
+    } else {
+        sync_dir.join(format!("{}.jsonl", table))
+    }
+}
+
+/// Output file for one table
+///
+/// Lines collect in a [`WRITE_CHUNK_BYTES`] buffer and reach the file (or
+/// the zstd encoder in front of it) a chunk at a time.
+enum TableWriter {
+    Plain(BufWriter<File>),
+    Zstd(BufWriter<zstd::Encoder<'static, File>>),
+}
+
+impl TableWriter {
+    fn create(path: &Path, compress: bool) -> Result<Self> {
+        let file = File::create(path).map_err(Error::Io)?;
+        if compress {
+            let encoder = zstd::Encoder::new(file, COMPRESSION_LEVEL).map_err(Error::Io)?;
+            Ok(Self::Zstd(BufWriter::with_capacity(
+                WRITE_CHUNK_BYTES,
+                encoder,
+            )))
+        } else {
+            Ok(Self::Plain(BufWriter::with_capacity(
+                WRITE_CHUNK_BYTES,
+                file,
+            )))
+        }
+    }
+
+    /// Write out buffered lines and, when compressing, end the zstd frame
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Plain(mut file) => file.flush().map_err(Error::Io),
+            Self::Zstd(buffer) => {
+                let encoder = buffer.into_inner().map_err(|e| Error::Io(e.into_error()))?;
+                encoder.finish().map_err(Error::Io)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Write for TableWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(file) => file.write(buf),
+            Self::Zstd(buffer) => buffer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(file) => file.flush(),
+            Self::Zstd(buffer) => buffer.flush(),
+        }
+    }
+}
+
+/// Per-table progress, reported every `chunk_rows` rows
+struct RowProgress<'a> {
+    progress: &'a Progress,
+    chunk_rows: usize,
+    /// Expected rows, when known up front
+    total: Option<usize>,
+}
+
+impl<'a> RowProgress<'a> {
+    fn start(
+        progress: &'a Progress,
+        options: &SyncOptions,
+        message: String,
+        total: Option<usize>,
+    ) -> Self {
+        progress.stage(Stage::Sync, message);
+        Self {
+            progress,
+            chunk_rows: options.chunk_rows.max(1),
+            total,
+        }
+    }
+
+    // `is_multiple_of` needs Rust 1.87, above the workspace MSRV
+    #[allow(clippy::manual_is_multiple_of)]
+    fn row(&self, count: usize) {
+        if count % self.chunk_rows == 0 {
+            self.report(count);
+        }
+    }
+
+    fn report(&self, count: usize) {
+        let total = self.total.unwrap_or(count).max(count);
+        self.progress.advance(count as u64, total as u64);
+    }
+}
+
 /// Export a single table to a JSONL file
-async fn export_table(pool: &SqlitePool, table: &str, file_path: &Path) -> Result<usize> {
-    let file = File::create(file_path).map_err(Error::Io)?;
-    let mut writer = BufWriter::new(file);
+async fn export_table(
+    pool: &SqlitePool,
+    table: &str,
+    file_path: &Path,
+    options: &SyncOptions,
+    progress: &Progress,
+) -> Result<usize> {
+    let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM {}", table))
+        .fetch_one(pool)
+        .await?;
+    let rows = RowProgress::start(
+        progress,
+        options,
+        format!("Exporting {}", table),
+        Some(total as usize),
+    );
+    let mut writer = TableWriter::create(file_path, options.compress)?;
 
     let count = match table {
-        "projects" => export_projects(pool, &mut writer).await?,
-        "phases" => export_phases(pool, &mut writer).await?,
-        "features" => export_features(pool, &mut writer).await?,
-        "conversations" => export_conversations(pool, &mut writer).await?,
-        "messages" => export_messages(pool, &mut writer).await?,
-        "context_entries" => export_context_entries(pool, &mut writer).await?,
-        "checkpoints" => export_checkpoints(pool, &mut writer).await?,
-        "generated_files" => export_generated_files(pool, &mut writer).await?,
-        "documents" => export_documents(pool, &mut writer).await?,
-        "document_versions" => export_document_versions(pool, &mut writer).await?,
-        "llm_costs" => export_llm_costs(pool, &mut writer).await?,
-        "daily_cost_summaries" => export_daily_cost_summaries(pool, &mut writer).await?,
+        "projects" => export_projects(pool, &mut writer, &rows).await?,
+        "phases" => export_phases(pool, &mut writer, &rows).await?,
+        "features" => export_features(pool, &mut writer, &rows).await?,
+        "conversations" => export_conversations(pool, &mut writer, &rows).await?,
+        "messages" => export_messages(pool, &mut writer, &rows).await?,
+        "context_entries" => export_context_entries(pool, &mut writer, &rows).await?,
+        "checkpoints" => export_checkpoints(pool, &mut writer, &rows).await?,
+        "generated_files" => export_generated_files(pool, &mut writer, &rows).await?,
+        "documents" => export_documents(pool, &mut writer, &rows).await?,
+        "document_versions" => export_document_versions(pool, &mut writer, &rows).await?,
+        "llm_costs" => export_llm_costs(pool, &mut writer, &rows).await?,
+        "daily_cost_summaries" => export_daily_cost_summaries(pool, &mut writer, &rows).await?,
         "feature_extraction_history" => {
-            export_feature_extraction_history(pool, &mut writer).await?
+            export_feature_extraction_history(pool, &mut writer, &rows).await?
         }
-        "learned_skills" => export_learned_skills(pool, &mut writer).await?,
+        "learned_skills" => export_learned_skills(pool, &mut writer, &rows).await?,
         _ => return Err(Error::Other(format!("Unknown table: {}", table))),
     };
 
-    writer.flush().map_err(Error::Io)?;
+    writer.finish()?;
+    rows.report(count);
+    Ok(count)
+}
+
+/// Stream the rows of `sql` into `writer` one JSON line at a time
+async fn export_rows<R, T>(
+    pool: &SqlitePool,
+    sql: &str,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+    to_record: impl Fn(R) -> T,
+) -> Result<usize>
+where
+    R: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+    T: Serialize,
+{
+    let mut rows = sqlx::query_as::<_, R>(sql).fetch(pool);
+    let mut count = 0;
+    while let Some(row) = rows.try_next().await? {
+        serde_json::to_writer(&mut *writer, &to_record(row))
+            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
+        writeln!(writer).map_err(Error::Io)?;
+        count += 1;
+        progress.row(count);
+    }
     Ok(count)
 }
 
-async fn export_projects<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<ProjectRecord> = sqlx::query_as(
+async fn export_projects(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
         SELECT id, name, framework, repo_url, status, description,
                created_at, updated_at
         FROM projects
         ORDER BY id
         "#,
+        writer,
+        progress,
+        |record: ProjectRecord| record,
     )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for record in rows {
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+    .await
 }
 
-async fn export_phases<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<PhaseRecord> = sqlx::query_as(
+async fn export_phases(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
         SELECT id, project_id, name, description, order_index, status,
                created_at, updated_at
         FROM phases
         ORDER BY project_id, order_index, id
         "#,
+        writer,
+        progress,
+        |record: PhaseRecord| record,
     )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for record in rows {
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+    .await
 }
 
-async fn export_features<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<FeatureRecord> = sqlx::query_as(
+async fn export_features(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
         SELECT id, project_id, title, description, phase_id, status, priority,
                acceptance_criteria, labels, created_at, updated_at
         FROM features
         ORDER BY project_id, phase_id, priority DESC, id
         "#,
+        writer,
+        progress,
+        |record: FeatureRecord| record,
     )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for record in rows {
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+    .await
 }
 
-async fn export_conversations<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<ConversationRecord> = sqlx::query_as(
+async fn export_conversations(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
         SELECT id, project_id, title, created_at, updated_at
         FROM conversations
         ORDER BY project_id, created_at, id
         "#,
+        writer,
+        progress,
+        |record: ConversationRecord| record,
     )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for record in rows {
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+    .await
 }
 
-async fn export_messages<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<MessageRecord> = sqlx::query_as(
+async fn export_messages(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
-        SELECT id, conversation_id, role, content, model, tokens_used, created_at
+        SELECT id, conversation_id, role, content, model, tokens_used, created_at,
+               parent_message_id, variant_of
         FROM messages
         ORDER BY conversation_id, created_at, id
         "#,
+        writer,
+        progress,
+        |record: MessageRecord| record,
     )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for record in rows {
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+    .await
 }
 
-async fn export_context_entries<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<ContextEntryRecord> = sqlx::query_as(
+async fn export_context_entries(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
         SELECT id, project_id, conversation_id, source, source_reference,
                index_summary, timeline_summary, highlights, full_context,
                embedding_model, embedding_json, tokens_estimated,
-               created_at, updated_at
+               pinned, consolidated_from, created_at, updated_at
         FROM context_entries
         ORDER BY created_at, id
         "#,
+        writer,
+        progress,
+        |record: ContextEntryRecord| record,
     )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for record in rows {
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+    .await
 }
 
-async fn export_checkpoints<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<CheckpointRow> = sqlx::query_as(
+async fn export_checkpoints(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
-        SELECT id, project_id, feature_id, description, snapshot_data,
-               size_bytes, signature, created_at
-        FROM checkpoints
-        ORDER BY project_id, created_at, id
+        SELECT c.id, c.project_id, c.feature_id, c.description, c.snapshot_data,
+               b.data AS snapshot_blob, c.size_bytes, c.signature, c.created_at
+        FROM checkpoints c
+        LEFT JOIN content_blobs b ON b.hash = c.snapshot_hash
+        ORDER BY c.project_id, c.created_at, c.id
         "#,
-    )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for row in rows {
-        let record = CheckpointRecord {
+        writer,
+        progress,
+        |row: CheckpointRow| CheckpointRecord {
+            snapshot_data: content::decode(row.snapshot_data, row.snapshot_blob).unwrap_or_else(
+                |e| {
+                    tracing::warn!(checkpoint = %row.id, error = %e, "Unreadable stored snapshot");
+                    String::new()
+                },
+            ),
             id: row.id,
             project_id: row.project_id,
             feature_id: row.feature_id,
             description: row.description,
-            snapshot_data: row.snapshot_data,
             size_bytes: row.size_bytes,
             signature: base64::engine::general_purpose::STANDARD.encode(&row.signature),
             created_at: row.created_at,
-        };
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+        },
+    )
+    .await
 }
 
-async fn export_generated_files<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<GeneratedFileRow> = sqlx::query_as(
+async fn export_generated_files(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
         SELECT id, project_id, feature_id, file_path, content_hash,
                generation_timestamp, last_verified_hash, last_verified_at, edit_detected
         FROM generated_files
         ORDER BY project_id, file_path, id
         "#,
-    )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for row in rows {
-        let record = GeneratedFileRecord {
+        writer,
+        progress,
+        |row: GeneratedFileRow| GeneratedFileRecord {
             id: row.id,
             project_id: row.project_id,
             feature_id: row.feature_id,
@@ -609,125 +829,119 @@ async fn export_generated_files<W: Write>(pool: &SqlitePool, writer: &mut W) ->
             last_verified_hash: row.last_verified_hash,
             last_verified_at: row.last_verified_at,
             edit_detected: row.edit_detected != 0,
-        };
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+        },
+    )
+    .await
 }
 
-async fn export_documents<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<DocumentRecord> = sqlx::query_as(
+async fn export_documents(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
         SELECT id, project_id, doc_type, title, description, content, format,
                version, status, model_used, tokens_used, generation_cost_usd,
-               created_at, updated_at
+               created_at, updated_at, language
         FROM documents
         ORDER BY project_id, doc_type, id
         "#,
+        writer,
+        progress,
+        |record: DocumentRecord| record,
     )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for record in rows {
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+    .await
 }
 
-async fn export_document_versions<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<DocumentVersionRecord> = sqlx::query_as(
+async fn export_document_versions(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
         SELECT id, document_id, version_number, content, change_summary,
                model_used, created_at
         FROM document_versions
         ORDER BY document_id, version_number, id
         "#,
+        writer,
+        progress,
+        |record: DocumentVersionRecord| record,
     )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for record in rows {
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+    .await
 }
 
-async fn export_llm_costs<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<LlmCostRecord> = sqlx::query_as(
+async fn export_llm_costs(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
         SELECT id, project_id, model, input_tokens, output_tokens,
                input_cost_usd, output_cost_usd, context, created_at
         FROM llm_costs
         ORDER BY created_at, id
         "#,
+        writer,
+        progress,
+        |record: LlmCostRecord| record,
     )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for record in rows {
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+    .await
 }
 
-async fn export_daily_cost_summaries<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<DailyCostSummaryRecord> = sqlx::query_as(
+async fn export_daily_cost_summaries(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
         SELECT date, project_id, model, total_cost_usd, total_input_tokens,
                total_output_tokens, call_count, updated_at
         FROM daily_cost_summaries
         ORDER BY date, project_id, model
         "#,
+        writer,
+        progress,
+        |record: DailyCostSummaryRecord| record,
     )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for record in rows {
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+    .await
 }
 
-async fn export_feature_extraction_history<W: Write>(
+async fn export_feature_extraction_history(
     pool: &SqlitePool,
-    writer: &mut W,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
 ) -> Result<usize> {
-    let rows: Vec<FeatureExtractionHistoryRecord> = sqlx::query_as(
+    export_rows(
+        pool,
         r#"
         SELECT id, project_id, conversation_id, model_used, tokens_used,
                cost_usd, phases_created, features_created, raw_response, created_at
         FROM feature_extraction_history
         ORDER BY project_id, created_at, id
         "#,
+        writer,
+        progress,
+        |record: FeatureExtractionHistoryRecord| record,
     )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for record in rows {
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+    .await
 }
 
-async fn export_learned_skills<W: Write>(pool: &SqlitePool, writer: &mut W) -> Result<usize> {
-    let rows: Vec<LearnedSkillRecord> = sqlx::query_as(
+async fn export_learned_skills(
+    pool: &SqlitePool,
+    writer: &mut TableWriter,
+    progress: &RowProgress<'_>,
+) -> Result<usize> {
+    export_rows(
+        pool,
         r#"
         SELECT id, name, description, category,
                pattern_type, pattern_template, pattern_variables,
@@ -740,17 +954,11 @@ async fn export_learned_skills<W: Write>(pool: &SqlitePool, writer: &mut W) -> R
         FROM learned_skills
         ORDER BY created_at, id
         "#,
+        writer,
+        progress,
+        |record: LearnedSkillRecord| record,
     )
-    .fetch_all(pool)
-    .await?;
-
-    let count = rows.len();
-    for record in rows {
-        serde_json::to_writer(&mut *writer, &record)
-            .map_err(|e| Error::Other(format!("JSON serialization error: {}", e)))?;
-        writeln!(writer).map_err(Error::Io)?;
-    }
-    Ok(count)
+    .await
 }
 
 // =============================================================================
@@ -766,6 +974,24 @@ async fn export_learned_skills<W: Write>(pool: &SqlitePool, writer: &mut W) -> R
 /// # Returns
 /// Import result with counts and any warnings
 pub async fn import_from_jsonl(pool: &SqlitePool, project_dir: &Path) -> Result<ImportResult> {
+    import_from_jsonl_with(
+        pool,
+        project_dir,
+        &SyncOptions::default(),
+        &Progress::hidden(),
+    )
+    .await
+}
+
+/// Import JSONL files line by line, committing every `options.chunk_rows`
+///
+/// Reads plain or zstd-compressed table files; `options.compress` is ignored.
+pub async fn import_from_jsonl_with(
+    pool: &SqlitePool,
+    project_dir: &Path,
+    options: &SyncOptions,
+    progress: &Progress,
+) -> Result<ImportResult> {
     let sync_dir = project_dir.join(SYNC_DIR);
 
     if !sync_dir.exists() {
@@ -775,15 +1001,33 @@ pub async fn import_from_jsonl(pool: &SqlitePool, project_dir: &Path) -> Result<
         )));
     }
 
+    // Counts from the export, used as progress totals when present
+    let expected = File::open(sync_dir.join("_metadata.json"))
+        .ok()
+        .and_then(|file| serde_json::from_reader::<_, SyncMetadata>(BufReader::new(file)).ok())
+        .map(|metadata| metadata.record_counts)
+        .unwrap_or_default();
+
     let mut record_counts = HashMap::new();
     let mut total_records = 0usize;
     let mut warnings = Vec::new();
 
     // Import tables in dependency order (parents before children)
     for table in EXPORTABLE_TABLES {
-        let file_path = sync_dir.join(format!("{}.jsonl", table));
+        let compressed = table_path(&sync_dir, table, true);
+        let file_path = if compressed.exists() {
+            compressed
+        } else {
+            table_path(&sync_dir, table, false)
+        };
         if file_path.exists() {
-            match import_table(pool, table, &file_path).await {
+            let rows = RowProgress::start(
+                progress,
+                options,
+                format!("Importing {}", table),
+                expected.get(*table).copied(),
+            );
+            match import_table(pool, table, &file_path, rows).await {
                 Ok(count) => {
                     record_counts.insert(table.to_string(), count);
                     total_records += count;
@@ -804,31 +1048,110 @@ pub async fn import_from_jsonl(pool: &SqlitePool, project_dir: &Path) -> Result<
     })
 }
 
-/// Import a single table from a JSONL file
-async fn import_table(pool: &SqlitePool, table: &str, file_path: &Path) -> Result<usize> {
-    let file = File::open(file_path).map_err(Error::Io)?;
-    let reader = BufReader::new(file);
-
-    match table {
-        "projects" => import_projects(pool, reader).await,
-        "phases" => import_phases(pool, reader).await,
-        "features" => import_features(pool, reader).await,
-        "conversations" => import_conversations(pool, reader).await,
-        "messages" => import_messages(pool, reader).await,
-        "context_entries" => import_context_entries(pool, reader).await,
-        "checkpoints" => import_checkpoints(pool, reader).await,
-        "generated_files" => import_generated_files(pool, reader).await,
-        "documents" => import_documents(pool, reader).await,
-        "document_versions" => import_document_versions(pool, reader).await,
-        "llm_costs" => import_llm_costs(pool, reader).await,
-        "daily_cost_summaries" => import_daily_cost_summaries(pool, reader).await,
-        "feature_extraction_history" => import_feature_extraction_history(pool, reader).await,
-        "learned_skills" => import_learned_skills(pool, reader).await,
-        _ => Err(Error::Other(format!("Unknown table: {}", table))),
+/// Open a table file for line-by-line reading, decompressing `.zst` files
+fn open_table_reader(path: &Path) -> Result<Box<dyn BufRead + Send>> {
+    let file = File::open(path).map_err(Error::Io)?;
+    let compressed = path
+        .to_string_lossy()
+        .ends_with(&format!(".{}", COMPRESSED_EXTENSION));
+    if compressed {
+        let decoder = zstd::Decoder::new(file).map_err(Error::Io)?;
+        Ok(Box::new(BufReader::new(decoder)))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
     }
 }
 
-async fn import_projects<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+/// Writes imported rows in transactions of `chunk_rows`
+///
+/// A failure rolls back only the chunk in progress; earlier chunks stay
+/// committed, as they would have with one statement per row.
+struct ImportBatch<'a> {
+    pool: &'a SqlitePool,
+    tx: Option<Transaction<'static, Sqlite>>,
+    pending: usize,
+    progress: RowProgress<'a>,
+}
+
+impl<'a> ImportBatch<'a> {
+    fn new(pool: &'a SqlitePool, progress: RowProgress<'a>) -> Self {
+        Self {
+            pool,
+            tx: None,
+            pending: 0,
+            progress,
+        }
+    }
+
+    /// Connection for the next row, starting a transaction if needed
+    async fn conn(&mut self) -> Result<&mut SqliteConnection> {
+        let tx = match self.tx.take() {
+            Some(tx) => tx,
+            None => self.pool.begin().await?,
+        };
+        Ok(&mut **self.tx.insert(tx))
+    }
+
+    /// Record that the `count`th row was written
+    async fn row_done(&mut self, count: usize) -> Result<()> {
+        self.pending += 1;
+        self.progress.row(count);
+        if self.pending >= self.progress.chunk_rows {
+            self.commit().await?;
+        }
+        Ok(())
+    }
+
+    async fn commit(&mut self) -> Result<()> {
+        if let Some(tx) = self.tx.take() {
+            tx.commit().await?;
+        }
+        self.pending = 0;
+        Ok(())
+    }
+
+    /// Commit the last chunk
+    async fn finish(mut self, count: usize) -> Result<usize> {
+        self.commit().await?;
+        self.progress.report(count);
+        Ok(count)
+    }
+}
+
+/// Import a single table from a JSONL file
+async fn import_table(
+    pool: &SqlitePool,
+    table: &str,
+    file_path: &Path,
+    progress: RowProgress<'_>,
+) -> Result<usize> {
+    let reader = open_table_reader(file_path)?;
+    let mut batch = ImportBatch::new(pool, progress);
+
+    let count = match table {
+        "projects" => import_projects(&mut batch, reader).await?,
+        "phases" => import_phases(&mut batch, reader).await?,
+        "features" => import_features(&mut batch, reader).await?,
+        "conversations" => import_conversations(&mut batch, reader).await?,
+        "messages" => import_messages(&mut batch, reader).await?,
+        "context_entries" => import_context_entries(&mut batch, reader).await?,
+        "checkpoints" => import_checkpoints(&mut batch, reader).await?,
+        "generated_files" => import_generated_files(&mut batch, reader).await?,
+        "documents" => import_documents(&mut batch, reader).await?,
+        "document_versions" => import_document_versions(&mut batch, reader).await?,
+        "llm_costs" => import_llm_costs(&mut batch, reader).await?,
+        "daily_cost_summaries" => import_daily_cost_summaries(&mut batch, reader).await?,
+        "feature_extraction_history" => {
+            import_feature_extraction_history(&mut batch, reader).await?
+        }
+        "learned_skills" => import_learned_skills(&mut batch, reader).await?,
+        _ => return Err(Error::Other(format!("Unknown table: {}", table))),
+    };
+
+    batch.finish(count).await
+}
+
+async fn import_projects<R: BufRead>(batch: &mut ImportBatch<'_>, reader: R) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -853,15 +1176,16 @@ async fn import_projects<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usi
         .bind(&record.description)
         .bind(&record.created_at)
         .bind(&record.updated_at)
-        .execute(pool)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
-async fn import_phases<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+async fn import_phases<R: BufRead>(batch: &mut ImportBatch<'_>, reader: R) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -886,15 +1210,16 @@ async fn import_phases<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize
         .bind(&record.status)
         .bind(&record.created_at)
         .bind(&record.updated_at)
-        .execute(pool)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
-async fn import_features<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+async fn import_features<R: BufRead>(batch: &mut ImportBatch<'_>, reader: R) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -923,15 +1248,16 @@ async fn import_features<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usi
         .bind(&record.labels)
         .bind(&record.created_at)
         .bind(&record.updated_at)
-        .execute(pool)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
-async fn import_conversations<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+async fn import_conversations<R: BufRead>(batch: &mut ImportBatch<'_>, reader: R) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -953,15 +1279,16 @@ async fn import_conversations<R: BufRead>(pool: &SqlitePool, reader: R) -> Resul
         .bind(&record.title)
         .bind(&record.created_at)
         .bind(&record.updated_at)
-        .execute(pool)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
-async fn import_messages<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+async fn import_messages<R: BufRead>(batch: &mut ImportBatch<'_>, reader: R) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -974,8 +1301,8 @@ async fn import_messages<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usi
         sqlx::query(
             r#"
             INSERT OR REPLACE INTO messages
-            (id, conversation_id, role, content, model, tokens_used, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            (id, conversation_id, role, content, model, tokens_used, created_at, parent_message_id, variant_of)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&record.id)
@@ -985,15 +1312,21 @@ async fn import_messages<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usi
         .bind(&record.model)
         .bind(record.tokens_used)
         .bind(&record.created_at)
-        .execute(pool)
+        .bind(&record.parent_message_id)
+        .bind(&record.variant_of)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
-async fn import_context_entries<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+async fn import_context_entries<R: BufRead>(
+    batch: &mut ImportBatch<'_>,
+    reader: R,
+) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -1009,12 +1342,12 @@ async fn import_context_entries<R: BufRead>(pool: &SqlitePool, reader: R) -> Res
                 id, project_id, conversation_id, source, source_reference,
                 index_summary, timeline_summary, highlights, full_context,
                 embedding_model, embedding_json, tokens_estimated,
-                created_at, updated_at
+                pinned, consolidated_from, created_at, updated_at
             ) VALUES (
                 ?, ?, ?, ?, ?,
                 ?, ?, ?, ?, 
                 ?, ?, ?, 
-                ?, ?
+                ?, ?, ?, ?
             )
             "#,
         )
@@ -1030,17 +1363,20 @@ async fn import_context_entries<R: BufRead>(pool: &SqlitePool, reader: R) -> Res
         .bind(&record.embedding_model)
         .bind(&record.embedding_json)
         .bind(record.tokens_estimated)
+        .bind(record.pinned)
+        .bind(&record.consolidated_from)
         .bind(&record.created_at)
         .bind(&record.updated_at)
-        .execute(pool)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
-async fn import_checkpoints<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+async fn import_checkpoints<R: BufRead>(batch: &mut ImportBatch<'_>, reader: R) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -1070,15 +1406,19 @@ async fn import_checkpoints<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<
         .bind(record.size_bytes)
         .bind(&signature)
         .bind(&record.created_at)
-        .execute(pool)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
-async fn import_generated_files<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+async fn import_generated_files<R: BufRead>(
+    batch: &mut ImportBatch<'_>,
+    reader: R,
+) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -1107,15 +1447,16 @@ async fn import_generated_files<R: BufRead>(pool: &SqlitePool, reader: R) -> Res
         .bind(&record.last_verified_hash)
         .bind(&record.last_verified_at)
         .bind(edit_detected)
-        .execute(pool)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
-async fn import_documents<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+async fn import_documents<R: BufRead>(batch: &mut ImportBatch<'_>, reader: R) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -1129,8 +1470,9 @@ async fn import_documents<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<us
             r#"
             INSERT OR REPLACE INTO documents
             (id, project_id, doc_type, title, description, content, format, version,
-             status, model_used, tokens_used, generation_cost_usd, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             status, model_used, tokens_used, generation_cost_usd, created_at, updated_at,
+             language)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&record.id)
@@ -1147,15 +1489,20 @@ async fn import_documents<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<us
         .bind(record.generation_cost_usd)
         .bind(&record.created_at)
         .bind(&record.updated_at)
-        .execute(pool)
+        .bind(&record.language)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
-async fn import_document_versions<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+async fn import_document_versions<R: BufRead>(
+    batch: &mut ImportBatch<'_>,
+    reader: R,
+) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -1179,15 +1526,16 @@ async fn import_document_versions<R: BufRead>(pool: &SqlitePool, reader: R) -> R
         .bind(&record.change_summary)
         .bind(&record.model_used)
         .bind(&record.created_at)
-        .execute(pool)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
-async fn import_llm_costs<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+async fn import_llm_costs<R: BufRead>(batch: &mut ImportBatch<'_>, reader: R) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -1214,15 +1562,19 @@ async fn import_llm_costs<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<us
         .bind(record.output_cost_usd)
         .bind(&record.context)
         .bind(&record.created_at)
-        .execute(pool)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
-async fn import_daily_cost_summaries<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+async fn import_daily_cost_summaries<R: BufRead>(
+    batch: &mut ImportBatch<'_>,
+    reader: R,
+) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -1248,16 +1600,17 @@ async fn import_daily_cost_summaries<R: BufRead>(pool: &SqlitePool, reader: R) -
         .bind(record.total_output_tokens)
         .bind(record.call_count)
         .bind(&record.updated_at)
-        .execute(pool)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
 async fn import_feature_extraction_history<R: BufRead>(
-    pool: &SqlitePool,
+    batch: &mut ImportBatch<'_>,
     reader: R,
 ) -> Result<usize> {
     let mut count = 0;
@@ -1287,15 +1640,19 @@ async fn import_feature_extraction_history<R: BufRead>(
         .bind(record.features_created)
         .bind(&record.raw_response)
         .bind(&record.created_at)
-        .execute(pool)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
 
-async fn import_learned_skills<R: BufRead>(pool: &SqlitePool, reader: R) -> Result<usize> {
+async fn import_learned_skills<R: BufRead>(
+    batch: &mut ImportBatch<'_>,
+    reader: R,
+) -> Result<usize> {
     let mut count = 0;
     for line in reader.lines() {
         let line = line.map_err(Error::Io)?;
@@ -1352,10 +1709,11 @@ async fn import_learned_skills<R: BufRead>(pool: &SqlitePool, reader: R) -> Resu
         .bind(&record.metadata)
         .bind(&record.created_at)
         .bind(&record.updated_at)
-        .execute(pool)
+        .execute(batch.conn().await?)
         .await?;
 
         count += 1;
+        batch.row_done(count).await?;
     }
     Ok(count)
 }
@@ -1513,6 +1871,56 @@ mod tests {
         assert_eq!(name, "Test Project");
     }
 
+    #[tokio::test]
+    async fn test_compressed_export_and_import_in_chunks() {
+        let (db, temp_dir) = setup_test_db().await;
+        for i in 0..5 {
+            sqlx::query("INSERT INTO projects (id, name, framework, status) VALUES (?, ?, ?, ?)")
+                .bind(format!("p{}", i))
+                .bind(format!("Project {}", i))
+                .bind("rust")
+                .bind("active")
+                .execute(db.pool())
+                .await
+                .unwrap();
+        }
+
+        // A plain export first, which the compressed one must replace
+        export_to_jsonl(db.pool(), temp_dir.path()).await.unwrap();
+        let options = SyncOptions {
+            compress: true,
+            chunk_rows: 2,
+        };
+        let result =
+            export_to_jsonl_with(db.pool(), temp_dir.path(), &options, &Progress::hidden())
+                .await
+                .unwrap();
+        assert!(result.metadata.compressed);
+
+        let sync_dir = temp_dir.path().join(SYNC_DIR);
+        assert!(sync_dir.join("projects.jsonl.zst").exists());
+        assert!(!sync_dir.join("projects.jsonl").exists());
+        let decoded =
+            zstd::decode_all(File::open(sync_dir.join("projects.jsonl.zst")).unwrap()).unwrap();
+        assert_eq!(String::from_utf8(decoded).unwrap().lines().count(), 5);
+
+        sqlx::query("DELETE FROM projects")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let imported =
+            import_from_jsonl_with(db.pool(), temp_dir.path(), &options, &Progress::hidden())
+                .await
+                .unwrap();
+        assert_eq!(imported.record_counts.get("projects"), Some(&5));
+
+        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM projects")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(count.0, 5);
+    }
+
     #[tokio::test]
     async fn test_sync_status_no_previous_export() {
         let (db, temp_dir) = setup_test_db().await;
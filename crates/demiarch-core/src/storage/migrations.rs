@@ -6,7 +6,7 @@
 use sqlx::SqlitePool;
 
 /// Current schema version
-pub const CURRENT_VERSION: i32 = 13;
+pub const CURRENT_VERSION: i32 = 41;
 
 /// SQL for creating the migrations tracking table
 const CREATE_MIGRATIONS_TABLE: &str = r#"
@@ -777,6 +777,593 @@ const MIGRATION_V13: &str = r#"
     CREATE INDEX IF NOT EXISTS idx_context_entries_created_at ON context_entries(created_at);
 "#;
 
+/// Migration 14: Generation records with reviewable artifacts
+///
+/// Records each code generation run and the files it produced so that
+/// output can be previewed, accepted or rejected per file, and applied later.
+const MIGRATION_V14: &str = r#"
+    CREATE TABLE IF NOT EXISTS generations (
+        id TEXT PRIMARY KEY NOT NULL,
+        project_id TEXT REFERENCES projects(id) ON DELETE CASCADE,
+        feature_id TEXT REFERENCES features(id) ON DELETE SET NULL,
+        description TEXT NOT NULL,
+        output_dir TEXT NOT NULL,            -- Directory artifact paths are relative to
+        status TEXT NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed', 'failed')),
+        tokens_used INTEGER NOT NULL DEFAULT 0,
+        cost_usd REAL NOT NULL DEFAULT 0.0,
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_generations_project_id ON generations(project_id);
+    CREATE INDEX IF NOT EXISTS idx_generations_created_at ON generations(created_at);
+
+    CREATE TABLE IF NOT EXISTS generation_artifacts (
+        id TEXT PRIMARY KEY NOT NULL,
+        generation_id TEXT NOT NULL REFERENCES generations(id) ON DELETE CASCADE,
+        file_path TEXT NOT NULL,
+        content TEXT NOT NULL,
+        language TEXT,
+        is_new INTEGER NOT NULL DEFAULT 1,
+        decision TEXT NOT NULL DEFAULT 'pending' CHECK (decision IN ('pending', 'accepted', 'rejected')),
+        decided_at TIMESTAMP,
+        applied_at TIMESTAMP,
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE(generation_id, file_path)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_generation_artifacts_generation_id ON generation_artifacts(generation_id);
+    CREATE INDEX IF NOT EXISTS idx_generation_artifacts_decision ON generation_artifacts(decision);
+"#;
+
+/// Migration 15: Syntax validation results on generation artifacts
+///
+/// Stores the tree-sitter validation outcome for each generated file so
+/// rejected files can be reviewed with the errors that caused the rejection.
+const MIGRATION_V15: &str = r#"
+    ALTER TABLE generation_artifacts ADD COLUMN validation_status TEXT CHECK (validation_status IN ('valid', 'invalid', 'skipped'));
+    ALTER TABLE generation_artifacts ADD COLUMN validation_errors TEXT; -- JSON array of syntax errors
+"#;
+
+/// Migration 16: Task plans on generations
+///
+/// Stores the execution plan with per-task status so a generation that
+/// failed part way can be resumed without re-running completed tasks.
+const MIGRATION_V16: &str = r#"
+    ALTER TABLE generations ADD COLUMN plan TEXT; -- JSON execution plan with task statuses
+"#;
+
+/// Migration 17: LLM response cache
+///
+/// Caches chat completions keyed by a hash of model, temperature and
+/// normalized prompt so repeated identical calls skip the API.
+const MIGRATION_V17: &str = r#"
+    CREATE TABLE IF NOT EXISTS llm_cache (
+        key TEXT PRIMARY KEY NOT NULL,
+        model TEXT NOT NULL,
+        temperature REAL NOT NULL,
+        content TEXT NOT NULL,
+        response_model TEXT NOT NULL,
+        input_tokens INTEGER NOT NULL DEFAULT 0,
+        output_tokens INTEGER NOT NULL DEFAULT 0,
+        finish_reason TEXT NOT NULL,
+        size_bytes INTEGER NOT NULL,
+        hits INTEGER NOT NULL DEFAULT 0,
+        created_at TIMESTAMP NOT NULL,
+        last_used_at TIMESTAMP NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_llm_cache_created_at ON llm_cache(created_at);
+    CREATE INDEX IF NOT EXISTS idx_llm_cache_last_used_at ON llm_cache(last_used_at);
+"#;
+
+/// Migration 18: Spec iteration ledger
+///
+/// Records each iteration of a watched feature spec with the generation it
+/// created and the tokens and cost it used.
+const MIGRATION_V18: &str = r#"
+    CREATE TABLE IF NOT EXISTS spec_iterations (
+        id TEXT PRIMARY KEY NOT NULL,
+        spec_path TEXT NOT NULL,
+        iteration INTEGER NOT NULL,
+        spec_hash TEXT NOT NULL,
+        generation_id TEXT NOT NULL REFERENCES generations(id) ON DELETE CASCADE,
+        tasks_run INTEGER NOT NULL DEFAULT 0,
+        tasks_skipped INTEGER NOT NULL DEFAULT 0,
+        tasks_failed INTEGER NOT NULL DEFAULT 0,
+        tokens_used INTEGER NOT NULL DEFAULT 0,
+        cost_usd REAL NOT NULL DEFAULT 0.0,
+        created_at TIMESTAMP NOT NULL,
+        UNIQUE (spec_path, iteration)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_spec_iterations_spec_path ON spec_iterations(spec_path);
+"#;
+
+/// Migration 19: Job queue
+///
+/// Generations and document builds queued to run at a later time, with
+/// attempt counts for retries.
+const MIGRATION_V19: &str = r#"
+    CREATE TABLE IF NOT EXISTS jobs (
+        id TEXT PRIMARY KEY NOT NULL,
+        kind TEXT NOT NULL CHECK (kind IN ('generate', 'prd', 'architecture')),
+        payload TEXT NOT NULL, -- JSON job spec
+        status TEXT NOT NULL DEFAULT 'queued' CHECK (status IN ('queued', 'running', 'succeeded', 'failed', 'cancelled')),
+        run_at TIMESTAMP NOT NULL,
+        attempts INTEGER NOT NULL DEFAULT 0,
+        max_attempts INTEGER NOT NULL DEFAULT 3,
+        last_error TEXT,
+        result_ref TEXT, -- generation or document ID
+        created_at TIMESTAMP NOT NULL,
+        started_at TIMESTAMP,
+        finished_at TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_jobs_status_run_at ON jobs(status, run_at);
+"#;
+
+/// Migration 20: Feature time tracking
+///
+/// Active time attributed to a feature while it is a session's current
+/// feature, one row per feature and session.
+const MIGRATION_V20: &str = r#"
+    CREATE TABLE IF NOT EXISTS feature_time (
+        feature_id TEXT NOT NULL,
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        project_id TEXT,
+        active_secs INTEGER NOT NULL DEFAULT 0,
+        first_active_at TIMESTAMP NOT NULL,
+        last_active_at TIMESTAMP NOT NULL,
+        PRIMARY KEY (feature_id, session_id)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_feature_time_project_id ON feature_time(project_id);
+"#;
+
+/// Migration 21: Pinned context entries
+///
+/// Pinned entries are always recalled and never pruned.
+const MIGRATION_V21: &str = r#"
+    ALTER TABLE context_entries ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+
+    CREATE INDEX IF NOT EXISTS idx_context_entries_pinned ON context_entries(project_id, pinned);
+"#;
+
+/// Migration 22: Context consolidation provenance
+///
+/// JSON array of the entry IDs a consolidated entry replaced.
+const MIGRATION_V22: &str = r#"
+    ALTER TABLE context_entries ADD COLUMN consolidated_from TEXT;
+"#;
+
+/// Migration 23: Weekly usage rollups for local analytics
+///
+/// Rows are keyed by the Monday starting the week and the project ('' for
+/// generations without one).
+const MIGRATION_V23: &str = r#"
+    CREATE TABLE IF NOT EXISTS usage_rollups (
+        week_start TEXT NOT NULL,
+        project_id TEXT NOT NULL DEFAULT '',
+        generations INTEGER NOT NULL DEFAULT 0,
+        completed INTEGER NOT NULL DEFAULT 0,
+        failed INTEGER NOT NULL DEFAULT 0,
+        tokens INTEGER NOT NULL DEFAULT 0,
+        cost_usd REAL NOT NULL DEFAULT 0.0,
+        features_completed INTEGER NOT NULL DEFAULT 0,
+        updated_at TIMESTAMP NOT NULL,
+        PRIMARY KEY (week_start, project_id)
+    );
+"#;
+
+/// Migration 24: Per-project encrypted secrets
+///
+/// Environment variables for generated apps, encrypted with the same master
+/// key and AES-256-GCM scheme as `encrypted_keys`.
+const MIGRATION_V24: &str = r#"
+    CREATE TABLE IF NOT EXISTS project_secrets (
+        id TEXT PRIMARY KEY NOT NULL,
+        project_id TEXT NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        ciphertext TEXT NOT NULL,            -- Base64 AES-256-GCM ciphertext
+        nonce TEXT NOT NULL,                 -- Base64 nonce, unique per encryption
+        description TEXT,
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE(project_id, name)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_project_secrets_project_id ON project_secrets(project_id);
+"#;
+
+/// Migration 25: Environment snapshots on generations
+///
+/// Records the version, models, config and prompt hashes a generation ran
+/// under, so its output can be attributed and replays can flag drift.
+const MIGRATION_V25: &str = r#"
+    ALTER TABLE generations ADD COLUMN environment TEXT; -- JSON GenerationEnvironment
+"#;
+
+/// Migration 26: Generation history
+///
+/// Attributes generations to the session they ran in and records how long
+/// they ran, so past runs can be browsed and filtered.
+const MIGRATION_V26: &str = r#"
+    ALTER TABLE generations ADD COLUMN session_id TEXT REFERENCES sessions(id) ON DELETE SET NULL;
+    ALTER TABLE generations ADD COLUMN duration_ms INTEGER NOT NULL DEFAULT 0;
+
+    CREATE INDEX IF NOT EXISTS idx_generations_feature_id ON generations(feature_id);
+    CREATE INDEX IF NOT EXISTS idx_generations_session_id ON generations(session_id);
+"#;
+
+/// Migration 27: Artifact provenance for partial regeneration
+///
+/// Records which plan task produced each artifact, so one file can be
+/// regenerated by re-running only that task, and a version that increases
+/// each time the file is regenerated.
+const MIGRATION_V27: &str = r#"
+    ALTER TABLE generation_artifacts ADD COLUMN task_id TEXT;
+    ALTER TABLE generation_artifacts ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+"#;
+
+/// Migration 28: Phase target dates and feature completion times
+///
+/// Phases become milestones with an optional target date, and features
+/// record when they were completed so phase burndown can be charted.
+const MIGRATION_V28: &str = r#"
+    ALTER TABLE phases ADD COLUMN target_date TEXT;
+    ALTER TABLE features ADD COLUMN completed_at TIMESTAMP;
+
+    UPDATE features SET completed_at = updated_at WHERE status = 'done';
+"#;
+
+/// Migration 29: Roadmap document type
+///
+/// SQLite doesn't support ALTER TABLE for CHECK constraints, so the
+/// documents table is recreated with 'roadmap' allowed. Foreign keys are
+/// switched off while the old table is dropped so document versions are
+/// not cascade-deleted, and rowids are kept so the FTS index stays valid.
+const MIGRATION_V29: &str = r#"
+    PRAGMA foreign_keys = OFF;
+
+    CREATE TABLE IF NOT EXISTS documents_new (
+        id TEXT PRIMARY KEY NOT NULL,
+        project_id TEXT NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+        doc_type TEXT NOT NULL CHECK (doc_type IN ('prd', 'architecture', 'design', 'tech_spec', 'roadmap', 'custom')),
+        title TEXT NOT NULL,
+        description TEXT,
+        content TEXT NOT NULL,
+        format TEXT NOT NULL DEFAULT 'markdown' CHECK (format IN ('markdown', 'json')),
+        version INTEGER NOT NULL DEFAULT 1,
+        status TEXT NOT NULL DEFAULT 'draft' CHECK (status IN ('draft', 'review', 'final', 'archived')),
+        model_used TEXT,
+        tokens_used INTEGER,
+        generation_cost_usd REAL,
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    INSERT INTO documents_new (rowid, id, project_id, doc_type, title, description, content, format,
+                               version, status, model_used, tokens_used, generation_cost_usd,
+                               created_at, updated_at)
+    SELECT rowid, id, project_id, doc_type, title, description, content, format,
+           version, status, model_used, tokens_used, generation_cost_usd,
+           created_at, updated_at
+    FROM documents;
+
+    DROP TABLE documents;
+    ALTER TABLE documents_new RENAME TO documents;
+
+    CREATE INDEX IF NOT EXISTS idx_documents_project_id ON documents(project_id);
+    CREATE INDEX IF NOT EXISTS idx_documents_doc_type ON documents(doc_type);
+    CREATE INDEX IF NOT EXISTS idx_documents_status ON documents(status);
+
+    CREATE TRIGGER IF NOT EXISTS documents_ai AFTER INSERT ON documents BEGIN
+        INSERT INTO documents_fts(rowid, title, description, content)
+        VALUES (NEW.rowid, NEW.title, NEW.description, NEW.content);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS documents_ad AFTER DELETE ON documents BEGIN
+        INSERT INTO documents_fts(documents_fts, rowid, title, description, content)
+        VALUES ('delete', OLD.rowid, OLD.title, OLD.description, OLD.content);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS documents_au AFTER UPDATE ON documents BEGIN
+        INSERT INTO documents_fts(documents_fts, rowid, title, description, content)
+        VALUES ('delete', OLD.rowid, OLD.title, OLD.description, OLD.content);
+        INSERT INTO documents_fts(rowid, title, description, content)
+        VALUES (NEW.rowid, NEW.title, NEW.description, NEW.content);
+    END;
+
+    PRAGMA foreign_keys = ON;
+"#;
+
+/// Migration 30: Review findings
+///
+/// Structured findings from reviewing generated files, starting with the
+/// security audit. Each finding is tied to an artifact by path and carries a
+/// CWE identifier; blocking findings keep the file from being applied.
+const MIGRATION_V30: &str = r#"
+    CREATE TABLE IF NOT EXISTS review_findings (
+        id TEXT PRIMARY KEY NOT NULL,
+        generation_id TEXT NOT NULL REFERENCES generations(id) ON DELETE CASCADE,
+        file_path TEXT NOT NULL,
+        source TEXT NOT NULL DEFAULT 'security_audit',
+        severity TEXT NOT NULL CHECK (severity IN ('critical', 'major', 'minor', 'info')),
+        cwe TEXT,                            -- e.g. 'CWE-89'
+        category TEXT NOT NULL,
+        description TEXT NOT NULL,
+        line INTEGER,
+        suggestion TEXT,
+        blocking INTEGER NOT NULL DEFAULT 0,
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_review_findings_generation_id ON review_findings(generation_id);
+"#;
+
+/// Migration 31: Conversation branches
+///
+/// Messages form a tree through parent_message_id, and a branch is a named
+/// pointer to the newest message of one path through it. Existing messages
+/// are chained in creation order so each conversation starts as one path;
+/// branches are created on first use.
+const MIGRATION_V31: &str = r#"
+    ALTER TABLE messages ADD COLUMN parent_message_id TEXT REFERENCES messages(id) ON DELETE CASCADE;
+
+    UPDATE messages SET parent_message_id = (
+        SELECT p.id FROM messages p
+        WHERE p.conversation_id = messages.conversation_id
+          AND (p.created_at < messages.created_at
+               OR (p.created_at = messages.created_at AND p.rowid < messages.rowid))
+        ORDER BY p.created_at DESC, p.rowid DESC
+        LIMIT 1
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_messages_parent_message_id ON messages(parent_message_id);
+
+    CREATE TABLE IF NOT EXISTS conversation_branches (
+        id TEXT PRIMARY KEY NOT NULL,
+        conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        parent_branch_id TEXT REFERENCES conversation_branches(id) ON DELETE SET NULL,
+        fork_message_id TEXT REFERENCES messages(id) ON DELETE SET NULL,  -- Last message shared with the parent branch
+        head_message_id TEXT REFERENCES messages(id) ON DELETE SET NULL,
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (conversation_id, name)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_conversation_branches_conversation_id ON conversation_branches(conversation_id);
+
+    ALTER TABLE conversations ADD COLUMN active_branch_id TEXT REFERENCES conversation_branches(id) ON DELETE SET NULL;
+"#;
+
+/// Migration 32: Message variants
+///
+/// Retrying a reply or editing a message adds an alternative with the same
+/// parent instead of replacing it. variant_of points every alternative at
+/// the original, so the group can be listed and navigated.
+const MIGRATION_V32: &str = r#"
+    ALTER TABLE messages ADD COLUMN variant_of TEXT REFERENCES messages(id) ON DELETE CASCADE;
+
+    CREATE INDEX IF NOT EXISTS idx_messages_variant_of ON messages(variant_of);
+"#;
+
+/// Migration 33: Personas
+///
+/// Named descriptions of tone, seniority and preferred stack, stored per
+/// project and merged into the chat and generation system prompts.
+const MIGRATION_V33: &str = r#"
+    CREATE TABLE IF NOT EXISTS personas (
+        id TEXT PRIMARY KEY NOT NULL,
+        project_id TEXT NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        tone TEXT,
+        seniority TEXT,
+        stack TEXT NOT NULL DEFAULT '[]',    -- JSON array of languages/frameworks
+        instructions TEXT,
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE(project_id, name)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_personas_project_id ON personas(project_id);
+"#;
+
+/// Migration 34: Interrupted generations
+///
+/// Generations stopped by Ctrl-C or SIGTERM are recorded as 'interrupted'
+/// so they can be told apart from failures and resumed. As with migration
+/// 29, the table is recreated to change its CHECK constraint, with foreign
+/// keys off so artifacts and findings are not cascade-deleted.
+const MIGRATION_V34: &str = r#"
+    PRAGMA foreign_keys = OFF;
+
+    CREATE TABLE IF NOT EXISTS generations_new (
+        id TEXT PRIMARY KEY NOT NULL,
+        project_id TEXT REFERENCES projects(id) ON DELETE CASCADE,
+        feature_id TEXT REFERENCES features(id) ON DELETE SET NULL,
+        description TEXT NOT NULL,
+        output_dir TEXT NOT NULL,            -- Directory artifact paths are relative to
+        status TEXT NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed', 'failed', 'interrupted')),
+        tokens_used INTEGER NOT NULL DEFAULT 0,
+        cost_usd REAL NOT NULL DEFAULT 0.0,
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        plan TEXT,                           -- JSON execution plan with task statuses
+        environment TEXT,                    -- JSON GenerationEnvironment
+        session_id TEXT REFERENCES sessions(id) ON DELETE SET NULL,
+        duration_ms INTEGER NOT NULL DEFAULT 0
+    );
+
+    INSERT INTO generations_new (id, project_id, feature_id, description, output_dir, status,
+                                 tokens_used, cost_usd, created_at, updated_at, plan,
+                                 environment, session_id, duration_ms)
+    SELECT id, project_id, feature_id, description, output_dir, status,
+           tokens_used, cost_usd, created_at, updated_at, plan,
+           environment, session_id, duration_ms
+    FROM generations;
+
+    DROP TABLE generations;
+    ALTER TABLE generations_new RENAME TO generations;
+
+    CREATE INDEX IF NOT EXISTS idx_generations_project_id ON generations(project_id);
+    CREATE INDEX IF NOT EXISTS idx_generations_created_at ON generations(created_at);
+    CREATE INDEX IF NOT EXISTS idx_generations_feature_id ON generations(feature_id);
+    CREATE INDEX IF NOT EXISTS idx_generations_session_id ON generations(session_id);
+
+    PRAGMA foreign_keys = ON;
+"#;
+
+/// Migration 35: Knowledge entity aliases
+///
+/// Names merged into another entity are recorded here by canonical form, so
+/// later ingestion of "Nextjs" or "next js" resolves to the kept entity
+/// instead of creating the duplicate again.
+const MIGRATION_V35: &str = r#"
+    CREATE TABLE IF NOT EXISTS knowledge_entity_aliases (
+        canonical_alias TEXT PRIMARY KEY NOT NULL,  -- KnowledgeEntity::canonicalize of the alias
+        alias TEXT NOT NULL,
+        entity_id TEXT NOT NULL REFERENCES knowledge_entities(id) ON DELETE CASCADE,
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_knowledge_entity_aliases_entity ON knowledge_entity_aliases(entity_id);
+"#;
+
+/// Migration 36: Knowledge archive
+///
+/// Entities and relationships whose confidence has faded with age are moved
+/// out of the live graph into this table, kept as JSON so they can be
+/// inspected or restored.
+const MIGRATION_V36: &str = r#"
+    CREATE TABLE IF NOT EXISTS knowledge_archive (
+        id TEXT PRIMARY KEY NOT NULL,         -- ID of the archived entity or relationship
+        kind TEXT NOT NULL CHECK (kind IN ('entity', 'relationship')),
+        name TEXT NOT NULL,
+        data TEXT NOT NULL,                   -- JSON KnowledgeEntity or KnowledgeRelationship
+        archived_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_knowledge_archive_kind ON knowledge_archive(kind);
+"#;
+
+/// Migration 37: Session notes
+///
+/// Scratchpad notes attached to a session. Notes flagged as reminders are
+/// added to the next generation's context, which stamps `reminded_at`.
+const MIGRATION_V37: &str = r#"
+    CREATE TABLE IF NOT EXISTS session_notes (
+        id TEXT PRIMARY KEY NOT NULL,
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        content TEXT NOT NULL,
+        remind INTEGER NOT NULL DEFAULT 0,
+        reminded_at TIMESTAMP,
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_session_notes_session ON session_notes(session_id, created_at);
+"#;
+
+/// Migration 38: Document language
+///
+/// The language a document was generated in (e.g. `de`), so regenerating
+/// it keeps that language unless another one is asked for.
+const MIGRATION_V38: &str = r#"
+    ALTER TABLE documents ADD COLUMN language TEXT;
+"#;
+
+/// Migration 39: Digest document type
+///
+/// Recreates the documents table as in migration 29 so activity digests can
+/// be stored as 'digest' documents, keeping the language column.
+const MIGRATION_V39: &str = r#"
+    PRAGMA foreign_keys = OFF;
+
+    CREATE TABLE IF NOT EXISTS documents_new (
+        id TEXT PRIMARY KEY NOT NULL,
+        project_id TEXT NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+        doc_type TEXT NOT NULL CHECK (doc_type IN ('prd', 'architecture', 'design', 'tech_spec', 'roadmap', 'digest', 'custom')),
+        title TEXT NOT NULL,
+        description TEXT,
+        content TEXT NOT NULL,
+        format TEXT NOT NULL DEFAULT 'markdown' CHECK (format IN ('markdown', 'json')),
+        version INTEGER NOT NULL DEFAULT 1,
+        status TEXT NOT NULL DEFAULT 'draft' CHECK (status IN ('draft', 'review', 'final', 'archived')),
+        model_used TEXT,
+        tokens_used INTEGER,
+        generation_cost_usd REAL,
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        language TEXT
+    );
+
+    INSERT INTO documents_new (rowid, id, project_id, doc_type, title, description, content, format,
+                               version, status, model_used, tokens_used, generation_cost_usd,
+                               created_at, updated_at, language)
+    SELECT rowid, id, project_id, doc_type, title, description, content, format,
+           version, status, model_used, tokens_used, generation_cost_usd,
+           created_at, updated_at, language
+    FROM documents;
+
+    DROP TABLE documents;
+    ALTER TABLE documents_new RENAME TO documents;
+
+    CREATE INDEX IF NOT EXISTS idx_documents_project_id ON documents(project_id);
+    CREATE INDEX IF NOT EXISTS idx_documents_doc_type ON documents(doc_type);
+    CREATE INDEX IF NOT EXISTS idx_documents_status ON documents(status);
+
+    CREATE TRIGGER IF NOT EXISTS documents_ai AFTER INSERT ON documents BEGIN
+        INSERT INTO documents_fts(rowid, title, description, content)
+        VALUES (NEW.rowid, NEW.title, NEW.description, NEW.content);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS documents_ad AFTER DELETE ON documents BEGIN
+        INSERT INTO documents_fts(documents_fts, rowid, title, description, content)
+        VALUES ('delete', OLD.rowid, OLD.title, OLD.description, OLD.content);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS documents_au AFTER UPDATE ON documents BEGIN
+        INSERT INTO documents_fts(documents_fts, rowid, title, description, content)
+        VALUES ('delete', OLD.rowid, OLD.title, OLD.description, OLD.content);
+        INSERT INTO documents_fts(rowid, title, description, content)
+        VALUES (NEW.rowid, NEW.title, NEW.description, NEW.content);
+    END;
+
+    PRAGMA foreign_keys = ON;
+"#;
+
+/// Migration 40: Feature branches
+///
+/// The git branch a feature is developed on, linked when the branch is
+/// created from `generate.branch_template`.
+const MIGRATION_V40: &str = r#"
+    ALTER TABLE features ADD COLUMN branch TEXT;
+"#;
+
+/// Migration 41: Compressed, deduplicated content
+///
+/// Artifact contents and checkpoint snapshots move into `content_blobs`,
+/// stored once per SHA-256 hash and compressed with zstd; see
+/// [`super::content`]. Existing rows are moved by
+/// [`super::content::ContentStore::compact`] right after this runs.
+const MIGRATION_V41: &str = r#"
+    CREATE TABLE IF NOT EXISTS content_blobs (
+        hash TEXT PRIMARY KEY NOT NULL,  -- SHA-256 of the uncompressed content
+        size_bytes INTEGER NOT NULL,     -- Uncompressed size
+        data BLOB NOT NULL,              -- zstd-compressed content
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    -- When set, the inline content column is empty
+    ALTER TABLE generation_artifacts ADD COLUMN content_hash TEXT;
+    ALTER TABLE checkpoints ADD COLUMN snapshot_hash TEXT;
+
+    CREATE INDEX IF NOT EXISTS idx_generation_artifacts_content_hash ON generation_artifacts(content_hash);
+    CREATE INDEX IF NOT EXISTS idx_checkpoints_snapshot_hash ON checkpoints(snapshot_hash);
+"#;
+
 /// Get the current schema version from the database
 async fn get_current_version(pool: &SqlitePool) -> anyhow::Result<i32> {
     // Ensure migrations table exists
@@ -893,6 +1480,180 @@ pub async fn run_migrations(pool: &SqlitePool) -> anyhow::Result<()> {
         record_migration(pool, 13).await?;
     }
 
+    if current_version < 14 {
+        tracing::info!("Applying migration v14: Generation records with reviewable artifacts");
+        sqlx::raw_sql(MIGRATION_V14).execute(pool).await?;
+        record_migration(pool, 14).await?;
+    }
+
+    if current_version < 15 {
+        tracing::info!("Applying migration v15: Syntax validation results on generation artifacts");
+        sqlx::raw_sql(MIGRATION_V15).execute(pool).await?;
+        record_migration(pool, 15).await?;
+    }
+
+    if current_version < 16 {
+        tracing::info!("Applying migration v16: Task plans on generations");
+        sqlx::raw_sql(MIGRATION_V16).execute(pool).await?;
+        record_migration(pool, 16).await?;
+    }
+
+    if current_version < 17 {
+        tracing::info!("Applying migration v17: LLM response cache");
+        sqlx::raw_sql(MIGRATION_V17).execute(pool).await?;
+        record_migration(pool, 17).await?;
+    }
+
+    if current_version < 18 {
+        tracing::info!("Applying migration v18: Spec iteration ledger");
+        sqlx::raw_sql(MIGRATION_V18).execute(pool).await?;
+        record_migration(pool, 18).await?;
+    }
+
+    if current_version < 19 {
+        tracing::info!("Applying migration v19: Job queue");
+        sqlx::raw_sql(MIGRATION_V19).execute(pool).await?;
+        record_migration(pool, 19).await?;
+    }
+
+    if current_version < 20 {
+        tracing::info!("Applying migration v20: Feature time tracking");
+        sqlx::raw_sql(MIGRATION_V20).execute(pool).await?;
+        record_migration(pool, 20).await?;
+    }
+
+    if current_version < 21 {
+        tracing::info!("Applying migration v21: Pinned context entries");
+        sqlx::raw_sql(MIGRATION_V21).execute(pool).await?;
+        record_migration(pool, 21).await?;
+    }
+
+    if current_version < 22 {
+        tracing::info!("Applying migration v22: Context consolidation provenance");
+        sqlx::raw_sql(MIGRATION_V22).execute(pool).await?;
+        record_migration(pool, 22).await?;
+    }
+
+    if current_version < 23 {
+        tracing::info!("Applying migration v23: Weekly usage rollups");
+        sqlx::raw_sql(MIGRATION_V23).execute(pool).await?;
+        record_migration(pool, 23).await?;
+    }
+
+    if current_version < 24 {
+        tracing::info!("Applying migration v24: Per-project encrypted secrets");
+        sqlx::raw_sql(MIGRATION_V24).execute(pool).await?;
+        record_migration(pool, 24).await?;
+    }
+
+    if current_version < 25 {
+        tracing::info!("Applying migration v25: Environment snapshots on generations");
+        sqlx::raw_sql(MIGRATION_V25).execute(pool).await?;
+        record_migration(pool, 25).await?;
+    }
+
+    if current_version < 26 {
+        tracing::info!("Applying migration v26: Generation history");
+        sqlx::raw_sql(MIGRATION_V26).execute(pool).await?;
+        record_migration(pool, 26).await?;
+    }
+
+    if current_version < 27 {
+        tracing::info!("Applying migration v27: Artifact provenance for partial regeneration");
+        sqlx::raw_sql(MIGRATION_V27).execute(pool).await?;
+        record_migration(pool, 27).await?;
+    }
+
+    if current_version < 28 {
+        tracing::info!("Applying migration v28: Phase target dates and feature completion times");
+        sqlx::raw_sql(MIGRATION_V28).execute(pool).await?;
+        record_migration(pool, 28).await?;
+    }
+
+    if current_version < 29 {
+        tracing::info!("Applying migration v29: Roadmap document type");
+        sqlx::raw_sql(MIGRATION_V29).execute(pool).await?;
+        record_migration(pool, 29).await?;
+    }
+
+    if current_version < 30 {
+        tracing::info!("Applying migration v30: Review findings");
+        sqlx::raw_sql(MIGRATION_V30).execute(pool).await?;
+        record_migration(pool, 30).await?;
+    }
+
+    if current_version < 31 {
+        tracing::info!("Applying migration v31: Conversation branches");
+        sqlx::raw_sql(MIGRATION_V31).execute(pool).await?;
+        record_migration(pool, 31).await?;
+    }
+
+    if current_version < 32 {
+        tracing::info!("Applying migration v32: Message variants");
+        sqlx::raw_sql(MIGRATION_V32).execute(pool).await?;
+        record_migration(pool, 32).await?;
+    }
+
+    if current_version < 33 {
+        tracing::info!("Applying migration v33: Personas");
+        sqlx::raw_sql(MIGRATION_V33).execute(pool).await?;
+        record_migration(pool, 33).await?;
+    }
+
+    if current_version < 34 {
+        tracing::info!("Applying migration v34: Interrupted generations");
+        sqlx::raw_sql(MIGRATION_V34).execute(pool).await?;
+        record_migration(pool, 34).await?;
+    }
+
+    if current_version < 35 {
+        tracing::info!("Applying migration v35: Knowledge entity aliases");
+        sqlx::raw_sql(MIGRATION_V35).execute(pool).await?;
+        record_migration(pool, 35).await?;
+    }
+
+    if current_version < 36 {
+        tracing::info!("Applying migration v36: Knowledge archive");
+        sqlx::raw_sql(MIGRATION_V36).execute(pool).await?;
+        record_migration(pool, 36).await?;
+    }
+
+    if current_version < 37 {
+        tracing::info!("Applying migration v37: Session notes");
+        sqlx::raw_sql(MIGRATION_V37).execute(pool).await?;
+        record_migration(pool, 37).await?;
+    }
+
+    if current_version < 38 {
+        tracing::info!("Applying migration v38: Document language");
+        sqlx::raw_sql(MIGRATION_V38).execute(pool).await?;
+        record_migration(pool, 38).await?;
+    }
+
+    if current_version < 39 {
+        tracing::info!("Applying migration v39: Digest document type");
+        sqlx::raw_sql(MIGRATION_V39).execute(pool).await?;
+        record_migration(pool, 39).await?;
+    }
+
+    if current_version < 40 {
+        tracing::info!("Applying migration v40: Feature branches");
+        sqlx::raw_sql(MIGRATION_V40).execute(pool).await?;
+        record_migration(pool, 40).await?;
+    }
+
+    if current_version < 41 {
+        tracing::info!("Applying migration v41: Compressed, deduplicated content");
+        sqlx::raw_sql(MIGRATION_V41).execute(pool).await?;
+        let moved = super::content::ContentStore::new(pool).compact().await?;
+        tracing::info!(
+            artifacts = moved.artifacts,
+            checkpoints = moved.checkpoints,
+            "Recompressed existing content"
+        );
+        record_migration(pool, 41).await?;
+    }
+
     tracing::info!("Database migrations completed");
     Ok(())
 }
@@ -1000,6 +1761,12 @@ mod tests {
             "entity_embeddings",
             "skill_entity_links",
             "knowledge_events",
+            "generations",
+            "generation_artifacts",
+            "project_secrets",
+            "review_findings",
+            "conversation_branches",
+            "personas",
         ];
 
         for table in tables {
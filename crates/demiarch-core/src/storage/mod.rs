@@ -5,8 +5,13 @@
 //! # Architecture
 //!
 //! - `database`: Connection pool management and initialization
+//! - `connection_cache`: Lazily opened pools shared across commands, keyed by path
+//! - `content`: Compressed, deduplicated storage for artifact and checkpoint content
 //! - `migrations`: Schema versioning and automatic migration
 //! - `jsonl`: JSONL export format for git-based synchronization
+//! - `export`: Schema introspection and CSV/Parquet export for analytics
+//! - `instrumentation`: Per-statement query timing and the slow-query log
+//! - `write_queue`: Retry queue for cost and session event writes that failed
 //!
 //! # Usage
 //!
@@ -17,18 +22,29 @@
 //! let db = Database::in_memory().await?;
 //!
 //! // Or use the database manager for production
-//! let manager = DatabaseManager::new().await?;
-//! let global = manager.global();
+//! let manager = DatabaseManager::new();
+//! let global = manager.global().await?;
 //! ```
 
+pub mod connection_cache;
+pub mod content;
 pub mod database;
+pub mod export;
+pub mod instrumentation;
 pub mod jsonl;
 pub mod migrations;
+pub mod write_queue;
 
 // Re-export commonly used types
-pub use database::{Database, DatabaseConfig, DatabaseManager};
+pub use connection_cache::{ConnectionCache, ConnectionCacheConfig, ConnectionCacheMetrics};
+pub use database::{
+    ensure_writable, is_read_only, set_database_path, set_read_only, Database, DatabaseConfig,
+    DatabaseManager, DATABASE_PATH_ENV, READ_ONLY_ENV,
+};
 pub use jsonl::{
-    check_sync_status, export_to_jsonl, import_from_jsonl, ExportResult, ImportResult,
-    SyncMetadata, SyncStatus, EXPORTABLE_TABLES, SYNC_DIR,
+    check_sync_status, export_to_jsonl, export_to_jsonl_with, import_from_jsonl,
+    import_from_jsonl_with, ExportResult, ImportResult, SyncMetadata, SyncOptions, SyncStatus,
+    EXPORTABLE_TABLES, SYNC_DIR,
 };
 pub use migrations::{migration_status, run_migrations, MigrationStatus, CURRENT_VERSION};
+pub use write_queue::{write_or_queue, QueuedWrite, WriteQueue};
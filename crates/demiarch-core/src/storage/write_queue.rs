@@ -0,0 +1,386 @@
+//! Write-behind queue for cost records and session events
+//!
+//! Cost records and session events are written as a side effect of other
+//! work, so a failed insert used to be logged (or ignored) and the row lost.
+//! When an insert fails because the database is busy, locked, full or
+//! unreachable, [`write_or_queue`] appends the row to an append-only JSONL
+//! file next to the database instead. The queue is replayed the next time
+//! the database is opened; rows are inserted by ID, so replaying twice is
+//! harmless.
+//!
+//! In-memory databases have no queue and surface the error as before.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tracing::{debug, warn};
+
+use crate::storage::Database;
+use crate::Result;
+
+/// SQLite primary result codes worth retrying later
+const SQLITE_BUSY: u32 = 5;
+const SQLITE_LOCKED: u32 = 6;
+const SQLITE_IOERR: u32 = 10;
+const SQLITE_FULL: u32 = 13;
+const SQLITE_CANTOPEN: u32 = 14;
+
+/// A cost row for `llm_costs`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedCost {
+    pub id: String,
+    pub project_id: Option<String>,
+    pub model: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub input_cost_usd: f64,
+    pub output_cost_usd: f64,
+    pub context: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl QueuedCost {
+    /// A cost with a new ID, recorded now
+    pub fn new(project_id: Option<&str>, model: impl Into<String>, cost_usd: f64) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            project_id: project_id.map(str::to_string),
+            model: model.into(),
+            input_tokens: 0,
+            output_tokens: 0,
+            input_cost_usd: cost_usd,
+            output_cost_usd: 0.0,
+            context: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Set the context the cost is reported under
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+}
+
+/// A session event row for `session_events`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedSessionEvent {
+    pub id: String,
+    pub session_id: String,
+    pub event_type: String,
+    pub data: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A row waiting to be written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "table", rename_all = "snake_case")]
+pub enum QueuedWrite {
+    LlmCost(QueuedCost),
+    SessionEvent(QueuedSessionEvent),
+}
+
+impl QueuedWrite {
+    /// Insert the row, ignoring it if a row with its ID already exists
+    async fn insert(&self, pool: &SqlitePool) -> std::result::Result<(), sqlx::Error> {
+        match self {
+            Self::LlmCost(cost) => {
+                sqlx::query(
+                    "INSERT OR IGNORE INTO llm_costs (id, project_id, model, input_tokens, output_tokens, \
+                     input_cost_usd, output_cost_usd, context, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(&cost.id)
+                .bind(&cost.project_id)
+                .bind(&cost.model)
+                .bind(cost.input_tokens)
+                .bind(cost.output_tokens)
+                .bind(cost.input_cost_usd)
+                .bind(cost.output_cost_usd)
+                .bind(&cost.context)
+                .bind(cost.created_at.format("%Y-%m-%d %H:%M:%S").to_string())
+                .execute(pool)
+                .await?;
+            }
+            Self::SessionEvent(event) => {
+                sqlx::query(
+                    "INSERT OR IGNORE INTO session_events (id, session_id, event_type, data, created_at) \
+                     VALUES (?, ?, ?, ?, ?)",
+                )
+                .bind(&event.id)
+                .bind(&event.session_id)
+                .bind(&event.event_type)
+                .bind(&event.data)
+                .bind(event.created_at)
+                .execute(pool)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            Self::LlmCost(_) => "llm_costs",
+            Self::SessionEvent(_) => "session_events",
+        }
+    }
+}
+
+/// Whether a failed write may succeed if retried later
+pub fn is_transient(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Database(e) => e
+            .code()
+            .and_then(|code| code.parse::<u32>().ok())
+            .is_some_and(|code| {
+                matches!(
+                    code & 0xff,
+                    SQLITE_BUSY | SQLITE_LOCKED | SQLITE_IOERR | SQLITE_FULL | SQLITE_CANTOPEN
+                )
+            }),
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed => true,
+        _ => false,
+    }
+}
+
+/// Outcome of replaying a queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplaySummary {
+    /// Rows written
+    pub replayed: usize,
+    /// Rows that failed again and stay queued
+    pub remaining: usize,
+    /// Rows the database rejected outright, or could not be read
+    pub dropped: usize,
+}
+
+/// Append-only file of writes waiting for the database
+#[derive(Debug, Clone)]
+pub struct WriteQueue {
+    path: PathBuf,
+}
+
+impl WriteQueue {
+    /// Queue stored at `path`
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Queue for a database file, or None for in-memory databases
+    ///
+    /// `demiarch.db` queues to `demiarch.write-queue.jsonl` beside it.
+    /// In-memory databases are named `:memory:` or, once connected, a
+    /// `file:` URI.
+    pub fn for_path(db_path: &Path) -> Option<Self> {
+        let name = db_path.to_string_lossy();
+        if name.is_empty() || name == ":memory:" || name.starts_with("file:") {
+            return None;
+        }
+        let stem = db_path.file_stem()?.to_string_lossy();
+        Some(Self::at(
+            db_path.with_file_name(format!("{}.write-queue.jsonl", stem)),
+        ))
+    }
+
+    /// Queue for an open database
+    pub fn for_database(db: &Database) -> Option<Self> {
+        Self::for_path(db.path())
+    }
+
+    /// Queue for a connection pool, by the file it is connected to
+    pub fn for_pool(pool: &SqlitePool) -> Option<Self> {
+        Self::for_path(pool.connect_options().get_filename())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a write and sync it to disk
+    pub fn push(&self, write: &QueuedWrite) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let line = serde_json::to_string(write)
+            .map_err(|e| crate::Error::Parse(format!("Failed to encode queued write: {}", e)))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Number of writes waiting
+    pub fn depth(&self) -> usize {
+        count_lines(&self.path) + count_lines(&self.replaying_path())
+    }
+
+    /// Where the queue is moved while it is replayed
+    fn replaying_path(&self) -> PathBuf {
+        self.path.with_extension("jsonl.replaying")
+    }
+
+    /// Write every queued row to `db`
+    ///
+    /// The queue is moved aside first so writes queued while replaying are
+    /// not lost; rows that fail transiently again are queued again, and rows
+    /// the database rejects (for example because their session was deleted)
+    /// are dropped with a warning. A replay that was cut short is finished
+    /// before the current queue is touched.
+    pub async fn replay(&self, db: &Database) -> Result<ReplaySummary> {
+        let mut summary = ReplaySummary::default();
+        let replaying = self.replaying_path();
+        if !replaying.exists() {
+            if !self.path.exists() {
+                return Ok(summary);
+            }
+            fs::rename(&self.path, &replaying)?;
+        }
+        let file = File::open(&replaying)?;
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let write: QueuedWrite = match serde_json::from_str(&line) {
+                Ok(write) => write,
+                Err(e) => {
+                    warn!(error = %e, "Dropping unreadable queued write");
+                    summary.dropped += 1;
+                    continue;
+                }
+            };
+            match write.insert(db.pool()).await {
+                Ok(()) => summary.replayed += 1,
+                Err(e) if is_transient(&e) => {
+                    self.push(&write)?;
+                    summary.remaining += 1;
+                }
+                Err(e) => {
+                    warn!(table = write.table(), error = %e, "Dropping queued write rejected by the database");
+                    summary.dropped += 1;
+                }
+            }
+        }
+        fs::remove_file(&replaying)?;
+
+        if summary.replayed > 0 || summary.remaining > 0 || summary.dropped > 0 {
+            debug!(
+                replayed = summary.replayed,
+                remaining = summary.remaining,
+                dropped = summary.dropped,
+                "Replayed write queue"
+            );
+        }
+        Ok(summary)
+    }
+}
+
+/// Non-empty lines in a file, or 0 if it cannot be read
+fn count_lines(path: &Path) -> usize {
+    File::open(path)
+        .map(|file| {
+            BufReader::new(file)
+                .lines()
+                .map_while(|line| line.ok())
+                .filter(|line| !line.trim().is_empty())
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+/// Insert a row, queueing it if the database cannot take it right now
+///
+/// Returns true when the row was queued rather than written. Errors that a
+/// retry would not fix are returned as before.
+pub async fn write_or_queue(pool: &SqlitePool, write: QueuedWrite) -> Result<bool> {
+    let error = match write.insert(pool).await {
+        Ok(()) => return Ok(false),
+        Err(e) => e,
+    };
+    let queue = match WriteQueue::for_pool(pool) {
+        Some(queue) if is_transient(&error) => queue,
+        _ => return Err(error.into()),
+    };
+    warn!(table = write.table(), error = %error, path = %queue.path().display(), "Database write failed; queued for retry");
+    queue.push(&write)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::DatabaseConfig;
+
+    #[test]
+    fn test_queue_path_beside_database() {
+        let queue = WriteQueue::for_path(Path::new("/data/demiarch.db")).unwrap();
+        assert_eq!(queue.path(), Path::new("/data/demiarch.write-queue.jsonl"));
+        assert!(WriteQueue::for_path(Path::new(":memory:")).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_queued_writes_replay_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("demiarch.db");
+        let db = Database::new(DatabaseConfig::with_path(&db_path))
+            .await
+            .unwrap();
+        let queue = WriteQueue::for_database(&db).unwrap();
+
+        let cost = QueuedCost::new(None, "whisper-1", 0.006).with_context("transcription");
+        queue.push(&QueuedWrite::LlmCost(cost.clone())).unwrap();
+        queue
+            .push(&QueuedWrite::SessionEvent(QueuedSessionEvent {
+                id: "e1".to_string(),
+                session_id: "missing-session".to_string(),
+                event_type: "custom".to_string(),
+                data: None,
+                created_at: Utc::now(),
+            }))
+            .unwrap();
+        assert_eq!(queue.depth(), 2);
+        db.close().await;
+
+        // Reopening replays the queue; the orphaned event is dropped
+        let db = Database::new(DatabaseConfig::with_path(&db_path))
+            .await
+            .unwrap();
+        assert_eq!(queue.depth(), 0);
+        let (count, model): (i64, String) =
+            sqlx::query_as("SELECT COUNT(*), MAX(model) FROM llm_costs WHERE id = ?")
+                .bind(&cost.id)
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+        assert_eq!((count, model.as_str()), (1, "whisper-1"));
+
+        // Replaying a row that was already written is harmless
+        queue.push(&QueuedWrite::LlmCost(cost)).unwrap();
+        let summary = queue.replay(&db).await.unwrap();
+        assert_eq!(summary.replayed, 1);
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM llm_costs")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_write_or_queue_writes_directly_when_possible() {
+        let db = Database::in_memory().await.unwrap();
+        let queued = write_or_queue(
+            db.pool(),
+            QueuedWrite::LlmCost(QueuedCost::new(None, "m", 0.01)),
+        )
+        .await
+        .unwrap();
+        assert!(!queued);
+    }
+}
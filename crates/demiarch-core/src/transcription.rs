@@ -0,0 +1,466 @@
+//! Voice note transcription
+//!
+//! `demiarch chat --audio note.m4a`, `demiarch features create --audio` and
+//! the GUI's `transcribe_audio` turn a recording into text through the
+//! backend chosen by `transcription.backend`:
+//!
+//! - `openai` uploads the file to the Whisper API (key from `OPENAI_API_KEY`)
+//!   and is billed per minute of audio
+//! - `whisper_cpp` runs a local whisper.cpp binary; audio that is not WAV is
+//!   converted with `ffmpeg` first, and nothing leaves the machine
+//!
+//! API transcriptions are recorded in the cost tracker and `llm_costs`, so
+//! they count against the daily budget like completions do.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::TranscriptionConfig;
+use crate::cost::CostTracker;
+use crate::infrastructure::network;
+use crate::storage::write_queue::{write_or_queue, QueuedCost, QueuedWrite};
+use crate::storage::Database;
+use crate::{Error, Result};
+
+/// Environment variable holding the Whisper API key
+pub const OPENAI_API_KEY_ENV: &str = "OPENAI_API_KEY";
+
+/// Largest file the Whisper API accepts
+const MAX_UPLOAD_BYTES: u64 = 25 * 1024 * 1024;
+
+/// Time allowed for one transcription
+const TIMEOUT_SECS: u64 = 300;
+
+/// Context recorded with transcription costs
+const COST_CONTEXT: &str = "transcription";
+
+/// Where audio is transcribed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionBackend {
+    /// OpenAI Whisper API
+    OpenAi,
+    /// Local whisper.cpp binary
+    WhisperCpp,
+}
+
+impl TranscriptionBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OpenAi => "openai",
+            Self::WhisperCpp => "whisper_cpp",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "openai" => Some(Self::OpenAi),
+            "whisper_cpp" => Some(Self::WhisperCpp),
+            _ => None,
+        }
+    }
+}
+
+/// Text transcribed from an audio file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub text: String,
+    pub backend: TranscriptionBackend,
+    /// Model that produced the text
+    pub model: String,
+    /// Length of the audio, when the backend reports it
+    pub duration_secs: Option<f64>,
+    /// What the transcription cost; zero for local backends
+    pub cost_usd: f64,
+}
+
+/// Transcribes audio files with the configured backend
+pub struct Transcriber {
+    config: TranscriptionConfig,
+    backend: TranscriptionBackend,
+    cost_tracker: Option<Arc<CostTracker>>,
+}
+
+impl Transcriber {
+    /// Create a transcriber for `config`
+    pub fn new(config: &TranscriptionConfig) -> Result<Self> {
+        let backend = TranscriptionBackend::parse(&config.backend).ok_or_else(|| {
+            Error::ConfigError(format!(
+                "Unknown transcription.backend: {}. Use openai or whisper_cpp",
+                config.backend
+            ))
+        })?;
+        Ok(Self {
+            config: config.clone(),
+            backend,
+            cost_tracker: None,
+        })
+    }
+
+    /// Record transcription costs in `tracker`
+    pub fn with_cost_tracker(mut self, tracker: Arc<CostTracker>) -> Self {
+        self.cost_tracker = Some(tracker);
+        self
+    }
+
+    pub fn backend(&self) -> TranscriptionBackend {
+        self.backend
+    }
+
+    /// Transcribe the audio file at `path`
+    pub async fn transcribe(&self, path: &Path) -> Result<Transcript> {
+        if !path.is_file() {
+            return Err(Error::InvalidInput(format!(
+                "Audio file not found: {}",
+                path.display()
+            )));
+        }
+        let transcript = match self.backend {
+            TranscriptionBackend::OpenAi => self.transcribe_api(path).await?,
+            TranscriptionBackend::WhisperCpp => self.transcribe_local(path).await?,
+        };
+        if transcript.text.is_empty() {
+            return Err(Error::TranscriptionFailed(format!(
+                "No speech found in {}",
+                path.display()
+            )));
+        }
+        if let Some(tracker) = &self.cost_tracker {
+            if transcript.cost_usd > 0.0 {
+                tracker.record_usd(
+                    &transcript.model,
+                    transcript.cost_usd,
+                    Some(COST_CONTEXT.to_string()),
+                );
+            }
+        }
+        Ok(transcript)
+    }
+
+    async fn transcribe_api(&self, path: &Path) -> Result<Transcript> {
+        network::ensure_online("Audio transcription")?;
+        let api_key = std::env::var(OPENAI_API_KEY_ENV).map_err(|_| {
+            Error::TranscriptionFailed(format!(
+                "{} is not set; it is needed for the openai backend",
+                OPENAI_API_KEY_ENV
+            ))
+        })?;
+        let content_type = audio_content_type(path)?;
+        let size = tokio::fs::metadata(path).await?.len();
+        if size > MAX_UPLOAD_BYTES {
+            return Err(Error::InvalidInput(format!(
+                "{} is {} MB; the Whisper API accepts up to 25 MB",
+                path.display(),
+                size / (1024 * 1024)
+            )));
+        }
+        let audio = tokio::fs::read(path).await?;
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "audio".to_string());
+
+        let mut fields = vec![
+            ("model", self.config.model.as_str()),
+            ("response_format", "verbose_json"),
+        ];
+        if !self.config.language.is_empty() {
+            fields.push(("language", self.config.language.as_str()));
+        }
+        let boundary = format!("demiarch-{}", Uuid::new_v4().simple());
+        let body = multipart_body(&boundary, &fields, &file_name, content_type, &audio);
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(TIMEOUT_SECS))
+            .build()?;
+        let response = client
+            .post(&self.config.api_url)
+            .bearer_auth(api_key)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", boundary),
+            )
+            .body(body)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(Error::TranscriptionFailed(format!(
+                "Whisper API returned {}: {}",
+                status,
+                message.chars().take(300).collect::<String>()
+            )));
+        }
+
+        #[derive(Deserialize)]
+        struct ApiTranscript {
+            text: String,
+            duration: Option<f64>,
+        }
+
+        let parsed: ApiTranscript = response.json().await.map_err(|e| {
+            Error::TranscriptionFailed(format!("Failed to parse Whisper API response: {}", e))
+        })?;
+        let cost_usd = parsed
+            .duration
+            .map(|secs| secs / 60.0 * self.config.cost_per_minute_usd)
+            .unwrap_or(0.0);
+
+        Ok(Transcript {
+            text: parsed.text.trim().to_string(),
+            backend: self.backend,
+            model: self.config.model.clone(),
+            duration_secs: parsed.duration,
+            cost_usd,
+        })
+    }
+
+    async fn transcribe_local(&self, path: &Path) -> Result<Transcript> {
+        if self.config.whisper_cpp_model.is_empty() {
+            return Err(Error::ConfigError(
+                "transcription.whisper_cpp_model must point to a ggml model for the whisper_cpp backend"
+                    .to_string(),
+            ));
+        }
+
+        // whisper.cpp reads 16 kHz mono WAV; convert anything else first
+        let is_wav = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+        if is_wav {
+            return self.run_whisper_cpp(path).await;
+        }
+        let wav = std::env::temp_dir().join(format!("demiarch-{}.wav", Uuid::new_v4().simple()));
+        let result = self.convert_and_run(path, &wav).await;
+        let _ = tokio::fs::remove_file(&wav).await;
+        result
+    }
+
+    /// Convert `path` to WAV at `wav` with ffmpeg, then transcribe it
+    async fn convert_and_run(&self, path: &Path, wav: &Path) -> Result<Transcript> {
+        let output = tokio::process::Command::new("ffmpeg")
+            .args(["-nostdin", "-loglevel", "error", "-i"])
+            .arg(path)
+            .args(["-ar", "16000", "-ac", "1"])
+            .arg(wav)
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(Duration::from_secs(TIMEOUT_SECS), output)
+            .await
+            .map_err(|_| Error::TranscriptionFailed("ffmpeg timed out".to_string()))?
+            .map_err(|e| {
+                Error::TranscriptionFailed(format!(
+                    "Could not run ffmpeg to convert {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        if !output.status.success() {
+            return Err(Error::TranscriptionFailed(format!(
+                "ffmpeg could not convert {}: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        self.run_whisper_cpp(wav).await
+    }
+
+    async fn run_whisper_cpp(&self, wav: &Path) -> Result<Transcript> {
+        let mut command = tokio::process::Command::new(&self.config.whisper_cpp_bin);
+        command
+            .arg("-m")
+            .arg(&self.config.whisper_cpp_model)
+            .arg("-f")
+            .arg(wav)
+            .args(["--no-timestamps", "--no-prints"])
+            .kill_on_drop(true);
+        if !self.config.language.is_empty() {
+            command.args(["-l", self.config.language.as_str()]);
+        }
+        let output = tokio::time::timeout(Duration::from_secs(TIMEOUT_SECS), command.output())
+            .await
+            .map_err(|_| Error::TranscriptionFailed("whisper.cpp timed out".to_string()))?
+            .map_err(|e| {
+                Error::TranscriptionFailed(format!(
+                    "Could not run {}: {}",
+                    self.config.whisper_cpp_bin, e
+                ))
+            })?;
+        if !output.status.success() {
+            return Err(Error::TranscriptionFailed(format!(
+                "{} failed: {}",
+                self.config.whisper_cpp_bin,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let model = Path::new(&self.config.whisper_cpp_model)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.config.whisper_cpp_model.clone());
+        Ok(Transcript {
+            text: clean_whisper_output(&String::from_utf8_lossy(&output.stdout)),
+            backend: self.backend,
+            model,
+            duration_secs: None,
+            cost_usd: 0.0,
+        })
+    }
+}
+
+/// Store a transcription's cost in `llm_costs` so reports and budgets include it
+///
+/// Queued for retry if the database is busy; see [`crate::storage::write_queue`].
+pub async fn record_cost(
+    db: &Database,
+    project_id: Option<&str>,
+    transcript: &Transcript,
+) -> Result<()> {
+    if transcript.cost_usd <= 0.0 {
+        return Ok(());
+    }
+    let cost = QueuedCost::new(project_id, &transcript.model, transcript.cost_usd)
+        .with_context(COST_CONTEXT);
+    write_or_queue(db.pool(), QueuedWrite::LlmCost(cost)).await?;
+    Ok(())
+}
+
+/// MIME type the Whisper API expects for an audio file, by extension
+fn audio_content_type(path: &Path) -> Result<&'static str> {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let content_type = match ext.as_str() {
+        "m4a" | "mp4" => "audio/mp4",
+        "mp3" | "mpga" | "mpeg" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "webm" => "audio/webm",
+        "ogg" | "oga" => "audio/ogg",
+        "flac" => "audio/flac",
+        _ => {
+            return Err(Error::InvalidInput(format!(
+                "Unsupported audio format: {}. Use m4a, mp3, mp4, wav, webm, ogg or flac",
+                path.display()
+            )))
+        }
+    };
+    Ok(content_type)
+}
+
+/// Encode text fields and one file as `multipart/form-data`
+fn multipart_body(
+    boundary: &str,
+    fields: &[(&str, &str)],
+    file_name: &str,
+    content_type: &str,
+    data: &[u8],
+) -> Vec<u8> {
+    let mut body = Vec::with_capacity(data.len() + 512);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                boundary, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            boundary,
+            file_name.replace('"', "_"),
+            content_type
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(data);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// Join whisper.cpp's output lines into one paragraph
+fn clean_whisper_output(stdout: &str) -> String {
+    stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && *line != "[BLANK_AUDIO]")
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_from_config() {
+        let mut config = TranscriptionConfig::default();
+        assert_eq!(
+            Transcriber::new(&config).unwrap().backend(),
+            TranscriptionBackend::OpenAi
+        );
+        config.backend = "whisper_cpp".to_string();
+        assert_eq!(
+            Transcriber::new(&config).unwrap().backend(),
+            TranscriptionBackend::WhisperCpp
+        );
+        config.backend = "vosk".to_string();
+        assert!(Transcriber::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_multipart_body() {
+        let body = multipart_body(
+            "b",
+            &[("model", "whisper-1")],
+            "note.m4a",
+            "audio/mp4",
+            b"AUDIO",
+        );
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "--b\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n\
+             --b\r\nContent-Disposition: form-data; name=\"file\"; filename=\"note.m4a\"\r\n\
+             Content-Type: audio/mp4\r\n\r\nAUDIO\r\n--b--\r\n"
+        );
+        assert_eq!(
+            audio_content_type(Path::new("Note.M4A")).unwrap(),
+            "audio/mp4"
+        );
+        assert!(audio_content_type(Path::new("note.txt")).is_err());
+    }
+
+    #[test]
+    fn test_clean_whisper_output() {
+        assert_eq!(
+            clean_whisper_output(
+                "\n [BLANK_AUDIO]\n Add a logout button.\n  It should clear the session.\n"
+            ),
+            "Add a logout button. It should clear the session."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_backend_requires_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let audio = dir.path().join("note.wav");
+        std::fs::write(&audio, b"RIFF").unwrap();
+        let config = TranscriptionConfig {
+            backend: "whisper_cpp".to_string(),
+            ..Default::default()
+        };
+        let err = Transcriber::new(&config)
+            .unwrap()
+            .transcribe(&audio)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ConfigError(_)));
+    }
+}
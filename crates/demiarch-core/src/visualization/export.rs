@@ -0,0 +1,319 @@
+//! Standalone exports of the agent hierarchy
+//!
+//! Renders an [`AgentTreeNode`] into a self-contained file for sharing or
+//! attaching to bug reports:
+//!
+//! - **SVG**: a static indented tree with status colours, tokens and durations
+//! - **HTML**: the same information as nested collapsible nodes, with no
+//!   external scripts or stylesheets
+
+use std::fmt::Write as _;
+
+use super::tree::{AgentTreeNode, NodeStyle, StatusIcon};
+use crate::agents::AgentStatus;
+
+/// Row height of an SVG node
+const ROW_HEIGHT: usize = 28;
+
+/// Horizontal indent per depth level in the SVG
+const INDENT: usize = 28;
+
+/// Padding around the SVG drawing
+const PADDING: usize = 16;
+
+/// Approximate width of one character in the SVG font
+const CHAR_WIDTH: usize = 8;
+
+/// Output format for an exported tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Static SVG image
+    Svg,
+    /// Interactive HTML page
+    Html,
+}
+
+impl ExportFormat {
+    /// Parse a format name
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "svg" => Some(Self::Svg),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    /// File extension for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// Render a tree in the given format
+pub fn export_tree(root: &AgentTreeNode, title: &str, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Svg => render_svg(root, title),
+        ExportFormat::Html => render_html(root, title),
+    }
+}
+
+/// Render a tree as a standalone SVG document
+pub fn render_svg(root: &AgentTreeNode, title: &str) -> String {
+    let mut rows = Vec::new();
+    flatten(root, 0, None, &mut rows);
+
+    let widest = rows
+        .iter()
+        .map(|row| row.depth * INDENT + node_label(row.node).chars().count() * CHAR_WIDTH)
+        .max()
+        .unwrap_or(0)
+        .max(title.chars().count() * CHAR_WIDTH);
+    let width = widest + 2 * PADDING + 40;
+    let height = (rows.len() + 1) * ROW_HEIGHT + 2 * PADDING;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="ui-monospace, monospace" font-size="13">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(svg, "  <title>{}</title>", escape(title));
+    let _ = writeln!(
+        svg,
+        r##"  <rect width="100%" height="100%" fill="#0f172a"/>"##
+    );
+    let _ = writeln!(
+        svg,
+        r##"  <text x="{}" y="{}" fill="#e2e8f0" font-weight="bold">{}</text>"##,
+        PADDING,
+        PADDING + ROW_HEIGHT / 2,
+        escape(title)
+    );
+
+    for (i, row) in rows.iter().enumerate() {
+        let x = PADDING + row.depth * INDENT + 8;
+        let y = PADDING + (i + 1) * ROW_HEIGHT + ROW_HEIGHT / 2;
+
+        // Elbow connector from the parent's marker
+        if let Some(parent) = row.parent_row {
+            let px = PADDING + (row.depth - 1) * INDENT + 8;
+            let py = PADDING + (parent + 1) * ROW_HEIGHT + ROW_HEIGHT / 2;
+            let _ = writeln!(
+                svg,
+                r##"  <path d="M{px} {py} V{y} H{x}" fill="none" stroke="#475569"/>"##,
+                px = px,
+                py = py + 6,
+                y = y,
+                x = x - 6
+            );
+        }
+
+        let _ = writeln!(
+            svg,
+            r#"  <circle cx="{}" cy="{}" r="6" fill="{}"><title>{}</title></circle>"#,
+            x,
+            y,
+            status_color(row.node.status),
+            escape(&row.node.status.to_string())
+        );
+        let _ = writeln!(
+            svg,
+            r##"  <text x="{}" y="{}" fill="#e2e8f0">{}</text>"##,
+            x + 14,
+            y + 4,
+            escape(&node_label(row.node))
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Render a tree as a standalone HTML page with collapsible nodes
+pub fn render_html(root: &AgentTreeNode, title: &str) -> String {
+    let mut body = String::new();
+    html_node(root, &mut body);
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: ui-monospace, monospace; background: #0f172a; color: #e2e8f0; padding: 1.5rem; }}
+h1 {{ font-size: 1.1rem; }}
+.summary {{ color: #94a3b8; margin-bottom: 1rem; }}
+details {{ margin-left: 1.25rem; border-left: 1px solid #334155; padding-left: 0.5rem; }}
+details > summary {{ cursor: pointer; padding: 0.15rem 0; }}
+.leaf {{ margin-left: 1.25rem; padding: 0.15rem 0 0.15rem 1.5rem; border-left: 1px solid #334155; }}
+.dot {{ display: inline-block; width: 0.6rem; height: 0.6rem; border-radius: 50%; margin-right: 0.4rem; }}
+.meta {{ color: #94a3b8; margin-left: 0.5rem; }}
+button {{ background: #1e293b; color: #e2e8f0; border: 1px solid #334155; padding: 0.25rem 0.75rem; margin-right: 0.5rem; cursor: pointer; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p class="summary">{agents} agents &middot; {completed} completed &middot; {failed} failed &middot; {tokens} tokens</p>
+<p>
+<button onclick="document.querySelectorAll('details').forEach(d => d.open = true)">Expand all</button>
+<button onclick="document.querySelectorAll('details').forEach(d => d.open = false)">Collapse all</button>
+</p>
+{body}</body>
+</html>
+"#,
+        title = escape(title),
+        agents = root.count(),
+        completed = root.count_completed(),
+        failed = root.count_failed(),
+        tokens = root.tree_tokens(),
+        body = body
+    )
+}
+
+fn html_node(node: &AgentTreeNode, out: &mut String) {
+    let line = format!(
+        r#"<span class="dot" style="background: {}" title="{}"></span>{} <span class="meta">{}</span>"#,
+        status_color(node.status),
+        escape(&node.status.to_string()),
+        escape(&format!(
+            "{} {}",
+            StatusIcon::for_agent_type(node.agent_type, NodeStyle::Unicode),
+            node.display_name()
+        )),
+        escape(&node_meta(node))
+    );
+
+    if node.children.is_empty() {
+        let _ = writeln!(out, r#"<div class="leaf">{}</div>"#, line);
+    } else {
+        let _ = writeln!(out, "<details open><summary>{}</summary>", line);
+        for child in &node.children {
+            html_node(child, out);
+        }
+        out.push_str("</details>\n");
+    }
+}
+
+/// A node placed on an SVG row
+struct Row<'a> {
+    node: &'a AgentTreeNode,
+    depth: usize,
+    parent_row: Option<usize>,
+}
+
+fn flatten<'a>(
+    node: &'a AgentTreeNode,
+    depth: usize,
+    parent_row: Option<usize>,
+    rows: &mut Vec<Row<'a>>,
+) {
+    let index = rows.len();
+    rows.push(Row {
+        node,
+        depth,
+        parent_row,
+    });
+    for child in &node.children {
+        flatten(child, depth + 1, Some(index), rows);
+    }
+}
+
+/// Name, type and metadata on one line
+fn node_label(node: &AgentTreeNode) -> String {
+    let meta = node_meta(node);
+    if meta.is_empty() {
+        format!("{} ({})", node.display_name(), node.agent_type)
+    } else {
+        format!("{} ({})  {}", node.display_name(), node.agent_type, meta)
+    }
+}
+
+/// Status, tokens and duration, separated by middle dots
+fn node_meta(node: &AgentTreeNode) -> String {
+    let mut parts = vec![node.status.to_string()];
+    if let Some(tokens) = node.tokens_used {
+        parts.push(format!("{} tokens", tokens));
+    }
+    if let Some(ms) = node.duration_ms {
+        parts.push(format_duration_ms(ms));
+    }
+    parts.join(" · ")
+}
+
+/// Format milliseconds as "850ms", "12.4s" or "3m 05s"
+pub fn format_duration_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else if ms < 60_000 {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    } else {
+        format!("{}m {:02}s", ms / 60_000, (ms % 60_000) / 1000)
+    }
+}
+
+fn status_color(status: AgentStatus) -> &'static str {
+    match status {
+        AgentStatus::Ready => "#94a3b8",
+        AgentStatus::Running => "#38bdf8",
+        AgentStatus::WaitingForChildren => "#facc15",
+        AgentStatus::Completed => "#4ade80",
+        AgentStatus::Failed => "#f87171",
+        AgentStatus::Cancelled => "#64748b",
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualization::TreeBuilder;
+
+    #[test]
+    fn test_svg_has_a_row_per_agent() {
+        let tree = TreeBuilder::demo_tree();
+        let svg = render_svg(&tree, "Demo <session>");
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<circle").count(), tree.count());
+        assert!(svg.contains("Demo &lt;session&gt;"));
+        // Every node but the root gets a connector
+        assert_eq!(svg.matches("<path").count(), tree.count() - 1);
+    }
+
+    #[test]
+    fn test_html_nests_collapsible_parents() {
+        let tree = TreeBuilder::demo_tree();
+        let html = render_html(&tree, "Demo");
+
+        // Orchestrator and planner have children; three workers are leaves
+        assert_eq!(html.matches("<details open>").count(), 2);
+        assert_eq!(html.matches(r#"<div class="leaf">"#).count(), 3);
+        assert!(html.contains("580 tokens"));
+    }
+
+    #[test]
+    fn test_format_duration_ms() {
+        assert_eq!(format_duration_ms(850), "850ms");
+        assert_eq!(format_duration_ms(12_400), "12.4s");
+        assert_eq!(format_duration_ms(185_000), "3m 05s");
+    }
+
+    #[test]
+    fn test_export_format_parse() {
+        assert_eq!(ExportFormat::parse("SVG"), Some(ExportFormat::Svg));
+        assert_eq!(ExportFormat::parse("html"), Some(ExportFormat::Html));
+        assert_eq!(ExportFormat::parse("png"), None);
+    }
+}
@@ -0,0 +1,270 @@
+//! Plain output mode and status glyphs
+//!
+//! Emoji and Unicode glyphs (🟢, ◐, box-drawing trees) garble screen readers
+//! and some terminals. Plain mode replaces them with ASCII words and lines
+//! ("[active]", "+--"). It is a process-wide switch set once at startup from
+//! `--plain`, `ui.plain = true` or `DEMIARCH_PLAIN=1`, so every renderer picks
+//! it up without threading a flag through each command.
+
+use std::borrow::Cow;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::agents::events::AgentEventType;
+use crate::agents::AgentType;
+use crate::commands::feature::FeatureStatus;
+use crate::domain::session::SessionStatus;
+use crate::infrastructure::network::env_flag_enabled;
+
+use super::NodeStyle;
+
+/// Environment variable that enables plain mode
+pub const PLAIN_ENV: &str = "DEMIARCH_PLAIN";
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable plain output for this process
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+}
+
+/// Whether plain output is enabled for this process or via the environment
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+        || std::env::var(PLAIN_ENV)
+            .map(|v| env_flag_enabled(&v))
+            .unwrap_or(false)
+}
+
+/// Node style matching the current output mode
+pub fn default_style() -> NodeStyle {
+    if is_plain() {
+        NodeStyle::Plain
+    } else {
+        NodeStyle::Unicode
+    }
+}
+
+/// Icon for a feature status
+pub fn feature_status(status: FeatureStatus) -> &'static str {
+    if is_plain() {
+        match status {
+            FeatureStatus::Backlog => "[backlog]",
+            FeatureStatus::Todo => "[todo]",
+            FeatureStatus::InProgress => "[in-progress]",
+            FeatureStatus::Review => "[review]",
+            FeatureStatus::Done => "[done]",
+        }
+    } else {
+        match status {
+            FeatureStatus::Backlog => "○",
+            FeatureStatus::Todo => "◐",
+            FeatureStatus::InProgress => "◑",
+            FeatureStatus::Review => "◕",
+            FeatureStatus::Done => "●",
+        }
+    }
+}
+
+/// Icon for a session status
+pub fn session_status(status: SessionStatus) -> &'static str {
+    if is_plain() {
+        match status {
+            SessionStatus::Active => "[active]",
+            SessionStatus::Paused => "[paused]",
+            SessionStatus::Completed => "[completed]",
+            SessionStatus::Abandoned => "[abandoned]",
+        }
+    } else {
+        match status {
+            SessionStatus::Active => "🟢",
+            SessionStatus::Paused => "⏸️ ",
+            SessionStatus::Completed => "✅",
+            SessionStatus::Abandoned => "❌",
+        }
+    }
+}
+
+/// Agent type with its icon, e.g. "🎭 Orchestrator" (plain: "Orchestrator")
+pub fn agent_type_label(agent_type: AgentType) -> String {
+    let name = match agent_type {
+        AgentType::Orchestrator => "Orchestrator",
+        AgentType::Planner => "Planner",
+        AgentType::Coder => "Coder",
+        AgentType::Reviewer => "Reviewer",
+        AgentType::Tester => "Tester",
+    };
+    if is_plain() {
+        name.to_string()
+    } else {
+        format!(
+            "{} {}",
+            super::StatusIcon::for_agent_type(agent_type, NodeStyle::Unicode),
+            name
+        )
+    }
+}
+
+/// Short label for an agent event, e.g. "✓  Done" (plain: "[done]")
+pub fn agent_event(event_type: &AgentEventType) -> &'static str {
+    if is_plain() {
+        match event_type {
+            AgentEventType::Spawned => "[spawned]",
+            AgentEventType::Started => "[started]",
+            AgentEventType::StatusUpdate => "[status]",
+            AgentEventType::Completed => "[done]",
+            AgentEventType::Failed => "[failed]",
+            AgentEventType::Cancelled => "[cancelled]",
+            AgentEventType::TokenUpdate => "[tokens]",
+            AgentEventType::FileExtracted => "[extracted]",
+            AgentEventType::FileWritten => "[wrote]",
+            AgentEventType::ContentQuarantined => "[quarantined]",
+            AgentEventType::ContextUpdate => "[context]",
+        }
+    } else {
+        match event_type {
+            AgentEventType::Spawned => "🆕 Spawned",
+            AgentEventType::Started => "▶️  Started",
+            AgentEventType::StatusUpdate => "📊 Status",
+            AgentEventType::Completed => "✓  Done",
+            AgentEventType::Failed => "✗  Failed",
+            AgentEventType::Cancelled => "⊘  Cancel",
+            AgentEventType::TokenUpdate => "🎫 Tokens",
+            AgentEventType::FileExtracted => "📄 Extract",
+            AgentEventType::FileWritten => "💾 Wrote",
+            AgentEventType::ContentQuarantined => "🛡  Quarantine",
+            AgentEventType::ContextUpdate => "📐 Context",
+        }
+    }
+}
+
+/// Check mark (plain: "ok")
+pub fn check() -> &'static str {
+    if is_plain() {
+        "ok"
+    } else {
+        "✓"
+    }
+}
+
+/// Cross mark (plain: "x")
+pub fn cross() -> &'static str {
+    if is_plain() {
+        "x"
+    } else {
+        "✗"
+    }
+}
+
+/// Horizontal rule of the given width
+pub fn rule(width: usize) -> String {
+    let ch = if is_plain() { "-" } else { "─" };
+    ch.repeat(width)
+}
+
+/// Horizontal bar of the given length, for inline charts
+pub fn bar(len: usize) -> String {
+    let ch = if is_plain() { "#" } else { "█" };
+    ch.repeat(len)
+}
+
+/// Text flagged as a warning: yellow on a color terminal, "[warning]" in
+/// plain mode
+pub fn warning(text: &str) -> String {
+    if is_plain() {
+        format!("[warning] {}", text)
+    } else if std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal() {
+        format!("\x1b[33m{}\x1b[0m", text)
+    } else {
+        format!("⚠ {}", text)
+    }
+}
+
+/// Prepare free-form text for output, converting glyphs in plain mode
+pub fn display(text: &str) -> Cow<'_, str> {
+    if is_plain() {
+        Cow::Owned(to_plain(text))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Replace emoji, box-drawing and symbol glyphs with ASCII equivalents
+///
+/// Status glyphs become bracketed words so screen readers announce them;
+/// drawing characters become `+`, `-` and `|`. Other non-ASCII text (names,
+/// translated messages) is left alone.
+pub fn to_plain(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match plain_glyph(ch) {
+            Some(replacement) => out.push_str(replacement),
+            None => out.push(ch),
+        }
+    }
+    out
+}
+
+/// ASCII replacement for a single glyph, if it has one
+fn plain_glyph(ch: char) -> Option<&'static str> {
+    let replacement = match ch {
+        // Status symbols
+        '✓' | '✔' | '✅' => "[ok]",
+        '✗' | '✘' | '❌' => "[x]",
+        '⊘' => "[cancelled]",
+        '○' => "[ready]",
+        '●' => "[running]",
+        '◐' | '◑' | '◕' => "[waiting]",
+        '🟢' => "[active]",
+        '⏸' => "[paused]",
+        '⚠' => "[warning]",
+        '…' => "...",
+        '•' => "*",
+        '→' => "->",
+        '←' => "<-",
+        '↑' => "up",
+        '↓' => "down",
+        // Agent and event icons
+        '🎭' => "[orchestrator]",
+        '📋' => "[planner]",
+        '💻' => "[coder]",
+        '🔍' => "[reviewer]",
+        '🧪' => "[tester]",
+        '🆕' => "[new]",
+        '▶' => "[start]",
+        '📊' => "[status]",
+        '🎫' => "[tokens]",
+        '📄' => "[file]",
+        '💾' => "[saved]",
+        // Variation selectors that follow emoji
+        '\u{FE0E}' | '\u{FE0F}' => "",
+        // Box drawing
+        '─' | '━' | '═' | '╌' => "-",
+        '│' | '┃' | '║' => "|",
+        '┌' | '┐' | '└' | '┘' | '├' | '┤' | '┬' | '┴' | '┼' | '╭' | '╮' | '╯' | '╰' | '╔' | '╗'
+        | '╚' | '╝' => "+",
+        _ => return None,
+    };
+    Some(replacement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_plain_replaces_glyphs() {
+        assert_eq!(
+            to_plain("├── 🎭 ● orchestrator"),
+            "+-- [orchestrator] [running] orchestrator"
+        );
+        assert_eq!(to_plain("✅ done ⏸️ paused"), "[ok] done [paused] paused");
+        assert_eq!(to_plain("═══"), "---");
+    }
+
+    #[test]
+    fn test_to_plain_keeps_other_text() {
+        assert_eq!(to_plain("Größe: 3 Dateien"), "Größe: 3 Dateien");
+        assert!(to_plain("plain ascii").is_ascii());
+    }
+}
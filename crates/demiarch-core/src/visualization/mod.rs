@@ -9,6 +9,10 @@
 //! - **Ratatui Widgets**: `HierarchyTreeWidget` and `AgentStatusBar` for TUI integration
 //! - **Flexible Styling**: Customizable colors, icons, and display options
 //! - **Status Tracking**: Visual indicators for agent states (running, completed, failed)
+//! - **Plain Mode**: ASCII words and lines instead of emoji and box drawing
+//!   (see [`glyphs`])
+//! - **Export**: Standalone SVG and collapsible HTML renderings of a tree
+//! - **Timeline**: Gantt view of agent runs with critical path and idle time
 //!
 //! # Example
 //!
@@ -29,8 +33,13 @@
 //!     .block(Block::default().title("Agents").borders(Borders::ALL));
 //! ```
 
+pub mod export;
+pub mod glyphs;
+pub mod timeline;
 mod tree;
 mod widget;
 
+pub use export::{export_tree, ExportFormat};
+pub use timeline::{Timeline, TimelineSpan};
 pub use tree::{AgentTreeNode, HierarchyTree, NodeStyle, RenderOptions, StatusIcon, TreeBuilder};
-pub use widget::{AgentStatusBar, HierarchyTreeWidget, TreeColors};
+pub use widget::{bordered_block, AgentStatusBar, HierarchyTreeWidget, TreeColors};
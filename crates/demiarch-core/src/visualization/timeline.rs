@@ -0,0 +1,573 @@
+//! Gantt timeline of agent execution
+//!
+//! Lays agents out by start and end time to show where a session spent its
+//! time. Two derived measures point at wasted wall-clock time:
+//!
+//! - **Critical path**: the chain from the root agent down through the child
+//!   that finished last at each level. Speeding up anything off this chain
+//!   doesn't shorten the session.
+//! - **Idle time**: stretches where no worker (leaf agent) was running, e.g.
+//!   while a planner waited on a model before spawning anything.
+//!
+//! Rendered as terminal text or a standalone SVG.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use chrono::{DateTime, Utc};
+
+use super::export::format_duration_ms;
+use super::glyphs;
+use crate::agents::events::{AgentEvent, AgentEventType};
+
+/// Width of the name column in text output
+const NAME_WIDTH: usize = 24;
+
+/// SVG layout constants
+const SVG_ROW_HEIGHT: usize = 22;
+const SVG_LABEL_WIDTH: usize = 200;
+const SVG_CHART_WIDTH: usize = 720;
+const SVG_PADDING: usize = 16;
+
+/// One agent's run on the timeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineSpan {
+    /// Agent ID as recorded in events
+    pub id: String,
+    /// Agent name
+    pub name: String,
+    /// Agent type
+    pub agent_type: String,
+    /// Parent agent ID
+    pub parent_id: Option<String>,
+    /// Depth in the hierarchy (0 = root)
+    pub depth: usize,
+    /// When the agent was spawned
+    pub start: DateTime<Utc>,
+    /// When the agent finished; unfinished agents run to the end of the timeline
+    pub end: DateTime<Utc>,
+    /// Whether the agent reached a final state
+    pub finished: bool,
+    /// Whether the agent failed
+    pub failed: bool,
+    /// Whether the agent has no children
+    pub is_leaf: bool,
+    /// Whether the agent is on the critical path
+    pub critical: bool,
+}
+
+impl TimelineSpan {
+    /// Run time in milliseconds
+    pub fn duration_ms(&self) -> u64 {
+        (self.end - self.start).num_milliseconds().max(0) as u64
+    }
+}
+
+/// Agent runs of a session laid out in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+    /// First event
+    pub start: DateTime<Utc>,
+    /// Last event
+    pub end: DateTime<Utc>,
+    /// Spans in hierarchy order, children after their parent by start time
+    pub spans: Vec<TimelineSpan>,
+    /// Stretches where no worker was running
+    pub idle: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl Timeline {
+    /// Build a timeline from a session's events
+    pub fn from_events(events: &[AgentEvent]) -> Self {
+        let start = events
+            .iter()
+            .map(|e| e.timestamp)
+            .min()
+            .unwrap_or_else(Utc::now);
+        let end = events.iter().map(|e| e.timestamp).max().unwrap_or(start);
+
+        let mut spans: Vec<TimelineSpan> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for event in events {
+            let id = &event.agent.id;
+            match event.event_type {
+                AgentEventType::Spawned => {
+                    index.insert(id.clone(), spans.len());
+                    spans.push(TimelineSpan {
+                        id: id.clone(),
+                        name: event.agent.name.clone(),
+                        agent_type: event.agent.agent_type.clone(),
+                        parent_id: event.agent.parent_id.clone(),
+                        depth: 0,
+                        start: event.timestamp,
+                        end,
+                        finished: false,
+                        failed: false,
+                        is_leaf: true,
+                        critical: false,
+                    });
+                }
+                AgentEventType::Completed | AgentEventType::Failed | AgentEventType::Cancelled => {
+                    if let Some(span) = index.get(id).map(|&i| &mut spans[i]) {
+                        span.end = event.timestamp;
+                        span.finished = true;
+                        span.failed = event.event_type == AgentEventType::Failed;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut timeline = Self {
+            start,
+            end,
+            spans: order_by_hierarchy(spans),
+            idle: Vec::new(),
+        };
+        timeline.mark_critical_path();
+        timeline.idle = timeline.idle_gaps();
+        timeline
+    }
+
+    /// Wall-clock length in milliseconds
+    pub fn duration_ms(&self) -> u64 {
+        (self.end - self.start).num_milliseconds().max(0) as u64
+    }
+
+    /// Total idle time in milliseconds
+    pub fn idle_ms(&self) -> u64 {
+        self.idle
+            .iter()
+            .map(|(from, to)| (*to - *from).num_milliseconds().max(0) as u64)
+            .sum()
+    }
+
+    /// Average number of workers running while any worker was running
+    pub fn parallelism(&self) -> f64 {
+        let busy = self.duration_ms().saturating_sub(self.idle_ms());
+        if busy == 0 {
+            return 0.0;
+        }
+        let work: u64 = self
+            .spans
+            .iter()
+            .filter(|s| s.is_leaf)
+            .map(|s| s.duration_ms())
+            .sum();
+        work as f64 / busy as f64
+    }
+
+    /// Spans on the critical path, root first
+    pub fn critical_path(&self) -> Vec<&TimelineSpan> {
+        self.spans.iter().filter(|s| s.critical).collect()
+    }
+
+    /// Render as terminal text with bars `width` columns wide
+    pub fn render_text(&self, width: usize) -> String {
+        let width = width.max(10);
+        let plain = glyphs::is_plain();
+        let (critical_bar, bar, idle_mark) = if plain {
+            ('#', '=', '.')
+        } else {
+            ('█', '▒', '·')
+        };
+
+        let mut out = String::new();
+        if self.spans.is_empty() {
+            out.push_str("No agent activity recorded.\n");
+            return out;
+        }
+
+        let _ = writeln!(
+            out,
+            "{} {} {} ({})",
+            self.start.format("%H:%M:%S"),
+            if plain { "->" } else { "→" },
+            self.end.format("%H:%M:%S"),
+            format_duration_ms(self.duration_ms())
+        );
+
+        for span in &self.spans {
+            let mut row = vec![' '; width];
+            let (from, to) = (self.column(span.start, width), self.column(span.end, width));
+            for cell in row.iter_mut().take(to + 1).skip(from) {
+                *cell = if span.critical { critical_bar } else { bar };
+            }
+            let label = format!("{}{}", "  ".repeat(span.depth), span_label(span));
+            let _ = writeln!(
+                out,
+                "{:<name$} |{}| {:>8}{}",
+                truncate(&label, NAME_WIDTH),
+                row.into_iter().collect::<String>(),
+                format_duration_ms(span.duration_ms()),
+                if span.critical { " *" } else { "" },
+                name = NAME_WIDTH
+            );
+        }
+
+        if !self.idle.is_empty() {
+            let mut row = vec![' '; width];
+            for (from, to) in &self.idle {
+                let (a, b) = (self.column(*from, width), self.column(*to, width));
+                for cell in row.iter_mut().take(b + 1).skip(a) {
+                    *cell = idle_mark;
+                }
+            }
+            let _ = writeln!(
+                out,
+                "{:<name$} |{}| {:>8}",
+                "idle",
+                row.into_iter().collect::<String>(),
+                format_duration_ms(self.idle_ms()),
+                name = NAME_WIDTH
+            );
+        }
+
+        out.push('\n');
+        let path: Vec<String> = self.critical_path().iter().map(|s| span_label(s)).collect();
+        let _ = writeln!(
+            out,
+            "Critical path (*): {}",
+            path.join(if plain { " -> " } else { " → " })
+        );
+        let _ = writeln!(
+            out,
+            "Idle {} of {}, average parallelism {:.1}x",
+            format_duration_ms(self.idle_ms()),
+            format_duration_ms(self.duration_ms()),
+            self.parallelism()
+        );
+        out
+    }
+
+    /// Render as a standalone SVG document
+    pub fn render_svg(&self, title: &str) -> String {
+        let rows = self.spans.len() + usize::from(!self.idle.is_empty());
+        let width = SVG_LABEL_WIDTH + SVG_CHART_WIDTH + 2 * SVG_PADDING + 80;
+        let height = (rows + 3) * SVG_ROW_HEIGHT + 2 * SVG_PADDING;
+        let chart_x = SVG_PADDING + SVG_LABEL_WIDTH;
+        let chart_top = SVG_PADDING + 2 * SVG_ROW_HEIGHT;
+        let x = |t: DateTime<Utc>| chart_x + self.column(t, SVG_CHART_WIDTH);
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="ui-monospace, monospace" font-size="12">"#,
+            w = width,
+            h = height
+        );
+        let _ = writeln!(svg, "  <title>{}</title>", escape(title));
+        let _ = writeln!(
+            svg,
+            r##"  <rect width="100%" height="100%" fill="#0f172a"/>"##
+        );
+        let _ = writeln!(
+            svg,
+            r##"  <text x="{}" y="{}" fill="#e2e8f0" font-weight="bold">{}</text>"##,
+            SVG_PADDING,
+            SVG_PADDING + 14,
+            escape(title)
+        );
+
+        // Time axis at quarter marks
+        let total = self.end - self.start;
+        for quarter in 0..=4 {
+            let t = self.start + total * quarter / 4;
+            let tx = x(t);
+            let _ = writeln!(
+                svg,
+                r##"  <line x1="{tx}" y1="{top}" x2="{tx}" y2="{bottom}" stroke="#1e293b"/>"##,
+                tx = tx,
+                top = chart_top,
+                bottom = chart_top + rows * SVG_ROW_HEIGHT
+            );
+            let _ = writeln!(
+                svg,
+                r##"  <text x="{}" y="{}" fill="#94a3b8" text-anchor="middle">{}</text>"##,
+                tx,
+                chart_top - 6,
+                t.format("%H:%M:%S")
+            );
+        }
+
+        // Idle stretches shade the whole chart
+        for (from, to) in &self.idle {
+            let _ = writeln!(
+                svg,
+                r##"  <rect x="{}" y="{}" width="{}" height="{}" fill="#f59e0b" opacity="0.12"><title>idle {}</title></rect>"##,
+                x(*from),
+                chart_top,
+                (x(*to) - x(*from)).max(1),
+                rows * SVG_ROW_HEIGHT,
+                format_duration_ms((*to - *from).num_milliseconds().max(0) as u64)
+            );
+        }
+
+        for (i, span) in self.spans.iter().enumerate() {
+            let y = chart_top + i * SVG_ROW_HEIGHT;
+            let fill = if span.failed {
+                "#f87171"
+            } else if span.critical {
+                "#38bdf8"
+            } else {
+                "#64748b"
+            };
+            let _ = writeln!(
+                svg,
+                r##"  <text x="{}" y="{}" fill="#e2e8f0">{}</text>"##,
+                SVG_PADDING + span.depth * 12,
+                y + 15,
+                escape(&truncate(&span_label(span), 24))
+            );
+            let _ = writeln!(
+                svg,
+                r#"  <rect x="{}" y="{}" width="{}" height="{}" rx="3" fill="{}"><title>{} ({})</title></rect>"#,
+                x(span.start),
+                y + 4,
+                (x(span.end) - x(span.start)).max(2),
+                SVG_ROW_HEIGHT - 8,
+                fill,
+                escape(&span_label(span)),
+                format_duration_ms(span.duration_ms())
+            );
+        }
+
+        if !self.idle.is_empty() {
+            let _ = writeln!(
+                svg,
+                r##"  <text x="{}" y="{}" fill="#f59e0b">idle {}</text>"##,
+                SVG_PADDING,
+                chart_top + self.spans.len() * SVG_ROW_HEIGHT + 15,
+                format_duration_ms(self.idle_ms())
+            );
+        }
+
+        let _ = writeln!(
+            svg,
+            r##"  <text x="{}" y="{}" fill="#94a3b8">critical path in blue &#183; average parallelism {:.1}x</text>"##,
+            SVG_PADDING,
+            height - SVG_PADDING,
+            self.parallelism()
+        );
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Column of a time within `width` columns
+    fn column(&self, t: DateTime<Utc>, width: usize) -> usize {
+        let total = self.duration_ms();
+        if total == 0 {
+            return 0;
+        }
+        let offset = (t - self.start).num_milliseconds().max(0) as u64;
+        ((offset * width as u64 / total) as usize).min(width - 1)
+    }
+
+    /// Follow the latest-finishing child from the latest-finishing root
+    fn mark_critical_path(&mut self) {
+        let mut current: Option<usize> = self
+            .spans
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.depth == 0)
+            .max_by_key(|(_, s)| s.end)
+            .map(|(i, _)| i);
+
+        while let Some(i) = current {
+            self.spans[i].critical = true;
+            let id = self.spans[i].id.clone();
+            current = self
+                .spans
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| s.parent_id.as_deref() == Some(id.as_str()))
+                .max_by_key(|(_, s)| s.end)
+                .map(|(i, _)| i);
+        }
+    }
+
+    /// Gaps in the union of worker spans
+    fn idle_gaps(&self) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let mut busy: Vec<(DateTime<Utc>, DateTime<Utc>)> = self
+            .spans
+            .iter()
+            .filter(|s| s.is_leaf)
+            .map(|s| (s.start, s.end))
+            .collect();
+        busy.sort();
+
+        let mut gaps = Vec::new();
+        let mut cursor = self.start;
+        for (from, to) in busy {
+            if from > cursor {
+                gaps.push((cursor, from));
+            }
+            cursor = cursor.max(to);
+        }
+        if self.end > cursor {
+            gaps.push((cursor, self.end));
+        }
+        gaps
+    }
+}
+
+/// Order spans depth-first, siblings by start time, and fill in depth and leaf flags
+fn order_by_hierarchy(spans: Vec<TimelineSpan>) -> Vec<TimelineSpan> {
+    let known: Vec<String> = spans.iter().map(|s| s.id.clone()).collect();
+    let mut children: HashMap<Option<String>, Vec<TimelineSpan>> = HashMap::new();
+    for span in spans {
+        // Agents whose parent never showed up are treated as roots
+        let parent = span.parent_id.clone().filter(|p| known.contains(p));
+        children.entry(parent).or_default().push(span);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|s| s.start);
+    }
+
+    fn visit(
+        parent: Option<String>,
+        depth: usize,
+        children: &mut HashMap<Option<String>, Vec<TimelineSpan>>,
+        out: &mut Vec<TimelineSpan>,
+    ) {
+        for mut span in children.remove(&parent).unwrap_or_default() {
+            let id = span.id.clone();
+            span.depth = depth;
+            span.is_leaf = !children.contains_key(&Some(id.clone()));
+            out.push(span);
+            visit(Some(id), depth + 1, children, out);
+        }
+    }
+
+    let mut ordered = Vec::new();
+    visit(None, 0, &mut children, &mut ordered);
+    ordered
+}
+
+fn span_label(span: &TimelineSpan) -> String {
+    if span.name.is_empty() {
+        span.id.clone()
+    } else {
+        span.name.clone()
+    }
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let mut out: String = s.chars().take(max.saturating_sub(1)).collect();
+        out.push('…');
+        out
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::events::AgentEventData;
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    fn event(
+        start: DateTime<Utc>,
+        secs: i64,
+        event_type: AgentEventType,
+        id: &str,
+        parent: Option<&str>,
+    ) -> AgentEvent {
+        AgentEvent {
+            timestamp: start + Duration::seconds(secs),
+            event_id: Uuid::new_v4(),
+            session_id: Uuid::nil(),
+            event_type,
+            agent: AgentEventData {
+                id: id.to_string(),
+                agent_type: String::new(),
+                name: id.to_string(),
+                parent_id: parent.map(String::from),
+                path: String::new(),
+                status: String::new(),
+                tokens: 0,
+                task: None,
+                error: None,
+            },
+            file: None,
+            context: None,
+        }
+    }
+
+    /// root 0-20, planner 1-19, coder-a 4-10, coder-b 6-16
+    fn sample() -> Timeline {
+        let t = Utc::now();
+        Timeline::from_events(&[
+            event(t, 0, AgentEventType::Spawned, "root", None),
+            event(t, 1, AgentEventType::Spawned, "planner", Some("root")),
+            event(t, 4, AgentEventType::Spawned, "coder-a", Some("planner")),
+            event(t, 6, AgentEventType::Spawned, "coder-b", Some("planner")),
+            event(t, 10, AgentEventType::Completed, "coder-a", None),
+            event(t, 16, AgentEventType::Completed, "coder-b", None),
+            event(t, 19, AgentEventType::Completed, "planner", None),
+            event(t, 20, AgentEventType::Completed, "root", None),
+        ])
+    }
+
+    #[test]
+    fn test_spans_follow_hierarchy() {
+        let timeline = sample();
+        let names: Vec<_> = timeline.spans.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["root", "planner", "coder-a", "coder-b"]);
+        assert_eq!(timeline.spans[2].depth, 2);
+        assert!(timeline.spans[2].is_leaf);
+        assert!(!timeline.spans[1].is_leaf);
+        assert_eq!(timeline.spans[3].duration_ms(), 10_000);
+    }
+
+    #[test]
+    fn test_critical_path_follows_latest_child() {
+        let timeline = sample();
+        let path: Vec<_> = timeline
+            .critical_path()
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(path, ["root", "planner", "coder-b"]);
+    }
+
+    #[test]
+    fn test_idle_and_parallelism() {
+        let timeline = sample();
+        // No worker runs for 0-4 and 16-20
+        assert_eq!(timeline.idle.len(), 2);
+        assert_eq!(timeline.idle_ms(), 8_000);
+        // 16s of worker time over 12s busy
+        assert!((timeline.parallelism() - 16.0 / 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_render_text_and_svg() {
+        let timeline = sample();
+        let text = timeline.render_text(40);
+        assert!(text.contains("Critical path (*)"));
+        assert_eq!(text.lines().filter(|l| l.ends_with(" *")).count(), 3);
+        assert!(text.lines().any(|l| l.starts_with("idle")));
+
+        let svg = timeline.render_svg("Session");
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("rx=\"3\"").count(), 4);
+    }
+
+    #[test]
+    fn test_empty_timeline() {
+        let timeline = Timeline::from_events(&[]);
+        assert!(timeline.spans.is_empty());
+        assert!(timeline.render_text(20).contains("No agent activity"));
+    }
+}
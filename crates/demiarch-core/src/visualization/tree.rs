@@ -7,9 +7,15 @@
 
 use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
 use crate::agents::context::{ChildAgentInfo, SharedAgentState};
-use crate::agents::events::{read_current_session_events, AgentEvent, AgentEventType};
+use crate::agents::events::{
+    read_current_session_events, read_events_for, AgentEvent, AgentEventType,
+};
 use crate::agents::{AgentContext, AgentId, AgentPath, AgentStatus, AgentType};
+use crate::context::WindowStats;
 
 /// Style configuration for tree rendering
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -21,6 +27,8 @@ pub enum NodeStyle {
     Unicode,
     /// Rounded Unicode style with status indicators
     Rounded,
+    /// ASCII lines with status words, for screen readers (plain mode)
+    Plain,
 }
 
 /// Status icons for agent states
@@ -39,6 +47,14 @@ impl StatusIcon {
                 AgentStatus::Failed => "[X]",
                 AgentStatus::Cancelled => "[-]",
             },
+            NodeStyle::Plain => match status {
+                AgentStatus::Ready => "[ready]",
+                AgentStatus::Running => "[running]",
+                AgentStatus::WaitingForChildren => "[waiting]",
+                AgentStatus::Completed => "[completed]",
+                AgentStatus::Failed => "[failed]",
+                AgentStatus::Cancelled => "[cancelled]",
+            },
             NodeStyle::Unicode | NodeStyle::Rounded => match status {
                 AgentStatus::Ready => "○",
                 AgentStatus::Running => "●",
@@ -60,6 +76,13 @@ impl StatusIcon {
                 AgentType::Reviewer => "[R]",
                 AgentType::Tester => "[T]",
             },
+            NodeStyle::Plain => match agent_type {
+                AgentType::Orchestrator => "[orchestrator]",
+                AgentType::Planner => "[planner]",
+                AgentType::Coder => "[coder]",
+                AgentType::Reviewer => "[reviewer]",
+                AgentType::Tester => "[tester]",
+            },
             NodeStyle::Unicode | NodeStyle::Rounded => match agent_type {
                 AgentType::Orchestrator => "🎭",
                 AgentType::Planner => "📋",
@@ -95,9 +118,10 @@ pub struct RenderOptions {
 }
 
 impl Default for RenderOptions {
+    /// Unicode style, or plain style when plain output is enabled
     fn default() -> Self {
         Self {
-            style: NodeStyle::Unicode,
+            style: super::glyphs::default_style(),
             show_ids: true,
             show_paths: false,
             show_tokens: true,
@@ -120,6 +144,15 @@ impl RenderOptions {
         }
     }
 
+    /// Create plain options: ASCII lines and status words, no type icons
+    pub fn plain() -> Self {
+        Self {
+            style: NodeStyle::Plain,
+            show_type_icons: false,
+            ..Default::default()
+        }
+    }
+
     /// Create minimal options (no extras)
     pub fn minimal() -> Self {
         Self {
@@ -171,6 +204,10 @@ pub struct AgentTreeNode {
     pub total_tokens: Option<u32>,
     /// Success state (if completed)
     pub success: Option<bool>,
+    /// Run time in milliseconds (if the agent finished)
+    pub duration_ms: Option<u64>,
+    /// Context window usage of the agent's latest prompt (if reported)
+    pub context: Option<WindowStats>,
     /// Child nodes
     pub children: Vec<AgentTreeNode>,
 }
@@ -197,6 +234,8 @@ impl AgentTreeNode {
             tokens_used,
             total_tokens,
             success,
+            duration_ms: None,
+            context: None,
             children: Vec::new(),
         }
     }
@@ -212,6 +251,8 @@ impl AgentTreeNode {
             tokens_used: None,
             total_tokens: None,
             success: None,
+            duration_ms: None,
+            context: None,
             children: Vec::new(),
         }
     }
@@ -387,6 +428,8 @@ impl TreeBuilder {
             tokens_used: None,
             total_tokens: None,
             success: None,
+            duration_ms: None,
+            context: None,
             children: Vec::new(),
         }
     }
@@ -400,6 +443,13 @@ impl TreeBuilder {
         Self::build_from_events(&events)
     }
 
+    /// Build a tree for a given session, or the current one if `None`
+    ///
+    /// Archived sessions are read from `~/.demiarch/events`.
+    pub fn from_session_events(session_id: Option<Uuid>) -> AgentTreeNode {
+        Self::build_from_events(&read_events_for(session_id))
+    }
+
     /// Build a tree from a list of agent events
     pub fn build_from_events(events: &[AgentEvent]) -> AgentTreeNode {
         if events.is_empty() {
@@ -425,6 +475,9 @@ impl TreeBuilder {
                             status: AgentStatus::Running,
                             tokens: event.agent.tokens,
                             task: event.agent.task.clone(),
+                            spawned_at: event.timestamp,
+                            ended_at: None,
+                            context: None,
                         },
                     );
                 }
@@ -439,6 +492,7 @@ impl TreeBuilder {
                 AgentEventType::Completed => {
                     if let Some(info) = agents.get_mut(id) {
                         info.status = AgentStatus::Completed;
+                        info.ended_at = Some(event.timestamp);
                         if event.agent.tokens > 0 {
                             info.tokens = event.agent.tokens;
                         }
@@ -447,11 +501,13 @@ impl TreeBuilder {
                 AgentEventType::Failed => {
                     if let Some(info) = agents.get_mut(id) {
                         info.status = AgentStatus::Failed;
+                        info.ended_at = Some(event.timestamp);
                     }
                 }
                 AgentEventType::Cancelled => {
                     if let Some(info) = agents.get_mut(id) {
                         info.status = AgentStatus::Cancelled;
+                        info.ended_at = Some(event.timestamp);
                     }
                 }
                 AgentEventType::Started => {
@@ -459,6 +515,15 @@ impl TreeBuilder {
                         info.status = AgentStatus::Running;
                     }
                 }
+                AgentEventType::ContextUpdate => {
+                    if let Some(info) = agents.get_mut(id) {
+                        info.context = event.context.or(info.context);
+                    }
+                }
+                // File and quarantine events don't change agent state
+                AgentEventType::FileExtracted
+                | AgentEventType::FileWritten
+                | AgentEventType::ContentQuarantined => {}
             }
         }
 
@@ -486,6 +551,8 @@ impl TreeBuilder {
             tokens_used: Some(150),
             total_tokens: None,
             success: None,
+            duration_ms: None,
+            context: None,
             children: Vec::new(),
         };
 
@@ -498,6 +565,8 @@ impl TreeBuilder {
             tokens_used: Some(320),
             total_tokens: None,
             success: None,
+            duration_ms: None,
+            context: None,
             children: Vec::new(),
         };
 
@@ -510,6 +579,8 @@ impl TreeBuilder {
             tokens_used: Some(580),
             total_tokens: Some(580),
             success: None,
+            duration_ms: None,
+            context: None,
             children: Vec::new(),
         };
 
@@ -522,6 +593,8 @@ impl TreeBuilder {
             tokens_used: Some(210),
             total_tokens: Some(210),
             success: Some(true),
+            duration_ms: None,
+            context: None,
             children: Vec::new(),
         };
 
@@ -534,6 +607,8 @@ impl TreeBuilder {
             tokens_used: None,
             total_tokens: None,
             success: None,
+            duration_ms: None,
+            context: None,
             children: Vec::new(),
         };
 
@@ -654,6 +729,13 @@ impl HierarchyTree {
                     ("+-- ", "|   ")
                 }
             }
+            NodeStyle::Plain => {
+                if is_last {
+                    ("+-- ", "    ")
+                } else {
+                    ("+-- ", "|   ")
+                }
+            }
             NodeStyle::Unicode => {
                 if is_last {
                     ("└── ", "    ")
@@ -714,8 +796,10 @@ impl HierarchyTree {
             }
         }
 
-        // Status text for terminal states
-        if node.status.is_terminal() {
+        // Status text for terminal states (plain status icons already say it)
+        let status_shown_as_word =
+            self.options.show_status && self.options.style == NodeStyle::Plain;
+        if node.status.is_terminal() && !status_shown_as_word {
             line.push_str(&format!(" [{}]", node.status));
         }
 
@@ -768,6 +852,9 @@ struct EventAgentInfo {
     tokens: u64,
     #[allow(dead_code)]
     task: Option<String>,
+    spawned_at: DateTime<Utc>,
+    ended_at: Option<DateTime<Utc>>,
+    context: Option<WindowStats>,
 }
 
 /// Parse status string to AgentStatus enum
@@ -866,6 +953,10 @@ fn build_tree_from_events_map(agents: &HashMap<String, EventAgentInfo>, id: &str
             AgentStatus::Failed => Some(false),
             _ => None,
         },
+        duration_ms: info
+            .ended_at
+            .map(|end| (end - info.spawned_at).num_milliseconds().max(0) as u64),
+        context: info.context,
         children: Vec::new(),
     };
 
@@ -946,6 +1037,19 @@ mod tests {
         assert!(!output.contains("└──"));
     }
 
+    #[test]
+    fn test_render_plain() {
+        let tree = TreeBuilder::demo_tree();
+        let renderer = HierarchyTree::with_options(tree, RenderOptions::plain());
+        let output = renderer.render();
+
+        assert!(output.is_ascii());
+        assert!(output.contains("+-- [running] coder-0"));
+        assert!(output.contains("[completed] reviewer-1"));
+        // Terminal status is not repeated after the name
+        assert!(!output.contains("[completed] reviewer-1 [completed]"));
+    }
+
     #[test]
     fn test_render_with_summary() {
         let tree = TreeBuilder::demo_tree();
@@ -1076,4 +1180,53 @@ mod tests {
         tree.children[0].children[1].success = Some(false);
         assert!(!tree.all_succeeded());
     }
+
+    #[test]
+    fn test_build_from_events_records_durations() {
+        use crate::agents::events::AgentEventData;
+
+        let start = Utc::now();
+        let event = |event_type, id: &str, parent: Option<&str>, secs| AgentEvent {
+            timestamp: start + chrono::Duration::seconds(secs),
+            event_id: Uuid::new_v4(),
+            session_id: Uuid::nil(),
+            event_type,
+            agent: AgentEventData {
+                id: id.to_string(),
+                agent_type: if parent.is_some() {
+                    "coder"
+                } else {
+                    "orchestrator"
+                }
+                .to_string(),
+                name: id.to_string(),
+                parent_id: parent.map(String::from),
+                path: String::new(),
+                status: String::new(),
+                tokens: 0,
+                task: None,
+                error: None,
+            },
+            file: None,
+            context: None,
+        };
+
+        let mut context_update = event(AgentEventType::ContextUpdate, "coder", None, 2);
+        context_update.context = Some(WindowStats::new(6_200, 8_000));
+        let tree = TreeBuilder::build_from_events(&[
+            event(AgentEventType::Spawned, "root", None, 0),
+            event(AgentEventType::Spawned, "coder", Some("root"), 1),
+            context_update,
+            event(AgentEventType::Completed, "coder", None, 4),
+        ]);
+
+        assert_eq!(tree.duration_ms, None);
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].duration_ms, Some(3000));
+        assert_eq!(tree.context, None);
+        assert_eq!(
+            tree.children[0].context,
+            Some(WindowStats::new(6_200, 8_000))
+        );
+    }
 }
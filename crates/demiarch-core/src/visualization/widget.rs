@@ -6,14 +6,37 @@ use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::{Color, Modifier, Style},
+    symbols::border,
     text::{Line, Span},
-    widgets::{Block, Paragraph, Widget, Wrap},
+    widgets::{Block, Borders, Paragraph, Widget, Wrap},
 };
 
 use crate::agents::{AgentStatus, AgentType};
 
 use super::{AgentTreeNode, NodeStyle, RenderOptions};
 
+/// ASCII border characters used in plain mode
+const PLAIN_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+/// A block with borders on all sides, drawn in ASCII in plain mode
+pub fn bordered_block<'a>() -> Block<'a> {
+    let block = Block::default().borders(Borders::ALL);
+    if super::glyphs::is_plain() {
+        block.border_set(PLAIN_BORDER)
+    } else {
+        block
+    }
+}
+
 /// Color scheme for the tree widget
 #[derive(Debug, Clone)]
 pub struct TreeColors {
@@ -43,6 +66,8 @@ pub struct TreeColors {
     pub id: Color,
     /// Color for token counts
     pub tokens: Color,
+    /// Color for context usage above the warning threshold
+    pub context_warning: Color,
 }
 
 impl Default for TreeColors {
@@ -61,6 +86,7 @@ impl Default for TreeColors {
             tree_chars: Color::DarkGray,
             id: Color::DarkGray,
             tokens: Color::Cyan,
+            context_warning: Color::LightRed,
         }
     }
 }
@@ -243,6 +269,7 @@ impl<'a> HierarchyTreeWidget<'a> {
                     ("+-- ", "|   ")
                 }
             }
+            NodeStyle::Plain => ("+-- ", if is_last { "    " } else { "|   " }),
             NodeStyle::Unicode | NodeStyle::Rounded => {
                 if is_last {
                     ("└── ", "    ")
@@ -315,6 +342,21 @@ impl<'a> HierarchyTreeWidget<'a> {
             }
         }
 
+        // Context window usage, highlighted when nearly full
+        if self.options.show_tokens {
+            if let Some(context) = node.context {
+                let color = if context.is_near_limit() {
+                    self.colors.context_warning
+                } else {
+                    self.colors.tokens
+                };
+                spans.push(Span::styled(
+                    format!(" ctx {}", context),
+                    Style::default().fg(color),
+                ));
+            }
+        }
+
         // Status text for terminal states
         if node.status.is_terminal() {
             let status_color = self.colors.for_status(node.status);
@@ -423,9 +465,15 @@ impl Widget for AgentStatusBar<'_> {
                 Style::default().fg(Color::White),
             ),
             Span::styled(" | ", Style::default().fg(Color::DarkGray)),
-            Span::styled("✓", Style::default().fg(self.colors.completed)),
+            Span::styled(
+                super::glyphs::check(),
+                Style::default().fg(self.colors.completed),
+            ),
             Span::styled(format!("{} ", completed), Style::default().fg(Color::White)),
-            Span::styled("✗", Style::default().fg(self.colors.failed)),
+            Span::styled(
+                super::glyphs::cross(),
+                Style::default().fg(self.colors.failed),
+            ),
             Span::styled(format!("{} ", failed), Style::default().fg(Color::White)),
             Span::styled("| ", Style::default().fg(Color::DarkGray)),
             Span::styled(
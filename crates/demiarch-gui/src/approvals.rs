@@ -0,0 +1,40 @@
+//! Approval gates answered from the frontend
+//!
+//! Background generations that reach a gate in `[approvals]` post an
+//! [`ApprovalRequest`] to the window as an `approval-requested` event and
+//! wait for `respond_to_approval`. Unanswered requests abort when the gate's
+//! timeout runs out.
+
+use std::sync::Arc;
+
+use demiarch_core::commands::approval::{ApprovalBroker, ApprovalGates, ApprovalRequest};
+use demiarch_core::config::Config;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::tray;
+
+/// Event name carrying an [`ApprovalRequest`] to the frontend
+pub const APPROVAL_EVENT: &str = "approval-requested";
+
+/// Requests waiting for the frontend
+pub struct Approvals(pub Arc<ApprovalBroker>);
+
+impl Approvals {
+    /// Gates from the current configuration, answered through this broker
+    pub fn gates(&self) -> ApprovalGates {
+        let config = Config::load().unwrap_or_default();
+        ApprovalGates::new(&config.approvals).with_approver(self.0.clone())
+    }
+}
+
+/// Start forwarding approval requests to the window
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    let broker = ApprovalBroker::new().with_listener(move |request: &ApprovalRequest| {
+        tray::show_main_window(&handle);
+        if let Err(e) = handle.emit(APPROVAL_EVENT, request) {
+            tracing::warn!(error = %e, "Failed to emit {}", APPROVAL_EVENT);
+        }
+    });
+    app.manage(Approvals(Arc::new(broker)));
+}
@@ -0,0 +1,140 @@
+//! Background work that outlives the main window
+//!
+//! The job worker, notification feed, tray status refresh and config watcher
+//! are owned by the app rather than a window, so closing the window to the
+//! tray leaves queued generations running and events announced.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use demiarch_core::agents::events::{active_agent_count, read_current_session_events};
+use demiarch_core::api;
+use demiarch_core::commands::jobs;
+use demiarch_core::config::watch::{self, ConfigChange, ConfigWatcher};
+use demiarch_core::infrastructure::network;
+use demiarch_core::storage;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::approvals::Approvals;
+use crate::{notifications, tray};
+
+/// How often the worker looks for due jobs
+const JOB_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often the tray status line is refreshed
+const TRAY_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Event name carrying the [`ConfigChange`]s of a config reload to the window
+pub const CONFIG_CHANGED_EVENT: &str = "config-changed";
+
+/// Shared switches for the background tasks
+#[derive(Debug, Default)]
+pub struct BackgroundState {
+    generation_paused: AtomicBool,
+}
+
+impl BackgroundState {
+    /// Whether the job worker is holding off on new jobs
+    pub fn is_paused(&self) -> bool {
+        self.generation_paused.load(Ordering::Relaxed)
+    }
+
+    /// Stop or resume claiming new jobs; a running job finishes either way
+    pub fn set_paused(&self, paused: bool) {
+        self.generation_paused.store(paused, Ordering::Relaxed);
+    }
+}
+
+/// Start every background task
+pub fn start(app: &AppHandle) {
+    app.manage(BackgroundState::default());
+    notifications::spawn(app.clone());
+    // Jobs write their results, so a read-only snapshot is only browsed
+    if !storage::is_read_only() {
+        spawn_job_worker(app.clone());
+    }
+    spawn_tray_refresh(app.clone());
+    spawn_config_watch(app.clone());
+}
+
+fn spawn_job_worker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let paused = app.state::<BackgroundState>().is_paused();
+            if !paused {
+                match run_next_job(&app).await {
+                    // Check for the next job straight away, unless paused meanwhile
+                    Ok(true) => continue,
+                    Ok(false) => {}
+                    Err(e) => tracing::warn!(error = %e, "Background job worker failed"),
+                }
+            }
+            tokio::time::sleep(JOB_POLL_INTERVAL).await;
+        }
+    });
+}
+
+/// Run the next due job, returning whether there was one
+///
+/// Approval gates are answered from the window.
+async fn run_next_job(app: &AppHandle) -> demiarch_core::Result<bool> {
+    let db = api::get_database().await?;
+    let gates = app.state::<Approvals>().gates();
+    let Some(job) =
+        jobs::process_next(&db, |job| jobs::execute_with_approvals(&db, job, &gates)).await?
+    else {
+        return Ok(false);
+    };
+    tracing::info!(job_id = %job.id, status = %job.status, "Background job finished");
+    Ok(true)
+}
+
+fn spawn_tray_refresh(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let active =
+                tokio::task::spawn_blocking(|| active_agent_count(&read_current_session_events()))
+                    .await
+                    .unwrap_or(0);
+            let spent = api::costs::spent_today().await.unwrap_or(0.0);
+            tray::refresh(&app, active, spent);
+            tokio::time::sleep(TRAY_REFRESH_INTERVAL).await;
+        }
+    });
+}
+
+/// Reload the config when it changes on disk, applying what the app holds
+/// and telling the window so settings views refresh
+fn spawn_config_watch(app: AppHandle) {
+    let mut updates = watch::subscribe();
+    match ConfigWatcher::new() {
+        Ok(watcher) => {
+            tauri::async_runtime::spawn(watcher.run());
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Config changes will not be picked up until restart");
+            return;
+        }
+    }
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            for change in &update.changes {
+                if let ConfigChange::SettingChanged { key } = change {
+                    if key == "network.offline" {
+                        network::set_offline(update.config.network.offline);
+                    }
+                }
+            }
+            if let Err(e) = app.emit(CONFIG_CHANGED_EVENT, &update.changes) {
+                tracing::warn!(error = %e, "Failed to announce config change");
+            }
+        }
+    });
+}
@@ -3,8 +3,26 @@
 //! These commands bridge the React frontend to demiarch-core functionality.
 //! Each command is exposed to the frontend via Tauri's invoke system.
 
+use demiarch_core::agents::events::AgentEvent;
 use demiarch_core::api;
+use demiarch_core::commands::approval::{ApprovalDecision, ApprovalRequest};
+use demiarch_core::commands::update::{self, UpdateStatus};
+use demiarch_core::config::Config;
+use demiarch_core::i18n;
+use demiarch_core::pagination::{Page, PageRequest};
+use demiarch_core::transcription::Transcript;
+use demiarch_core::{Error, ErrorPayload};
+use demiarch_plugins::permissions::{GrantStore, PluginGrants};
+use demiarch_plugins::{Permission, PluginError};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tauri::{AppHandle, Emitter};
+
+/// Result type for Tauri commands
+///
+/// Errors reach the frontend as a structured [`ErrorPayload`] (code,
+/// category, message, details, retryable) rather than a formatted string.
+pub type CommandResult<T> = Result<T, ErrorPayload>;
 
 /// Project summary for listing
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,8 +95,12 @@ pub struct CostSummary {
 pub struct SessionSummary {
     pub id: String,
     pub status: String,
+    pub phase: String,
+    pub description: Option<String>,
     pub current_project_id: Option<String>,
+    pub current_feature_id: Option<String>,
     pub started_at: String,
+    pub last_activity: String,
 }
 
 impl From<api::sessions::SessionSummary> for SessionSummary {
@@ -86,8 +108,58 @@ impl From<api::sessions::SessionSummary> for SessionSummary {
         Self {
             id: s.id,
             status: s.status,
+            phase: s.phase,
+            description: s.description,
             current_project_id: s.current_project_id,
+            current_feature_id: s.current_feature_id,
             started_at: s.created_at,
+            last_activity: s.last_activity,
+        }
+    }
+}
+
+/// Event name emitted whenever a session changes state
+pub const SESSION_CHANGED_EVENT: &str = "session-changed";
+
+/// Payload of the `session-changed` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionChanged {
+    /// What happened: started, paused, resumed, completed or abandoned
+    pub action: String,
+    /// The session after the change
+    pub session: SessionSummary,
+}
+
+/// Queued or finished background job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobSummary {
+    pub id: String,
+    pub kind: String,
+    pub summary: String,
+    pub status: String,
+    pub run_at: String,
+    pub attempts: i64,
+    pub max_attempts: i64,
+    pub last_error: Option<String>,
+    pub result_ref: Option<String>,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+}
+
+impl From<api::jobs::JobSummary> for JobSummary {
+    fn from(j: api::jobs::JobSummary) -> Self {
+        Self {
+            id: j.id,
+            kind: j.kind,
+            summary: j.summary,
+            status: j.status,
+            run_at: j.run_at,
+            attempts: j.attempts,
+            max_attempts: j.max_attempts,
+            last_error: j.last_error,
+            result_ref: j.result_ref,
+            created_at: j.created_at,
+            finished_at: j.finished_at,
         }
     }
 }
@@ -124,27 +196,212 @@ pub struct Conflict {
     pub created_at: String,
 }
 
+/// Generation in the history list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationSummary {
+    pub id: String,
+    pub project_id: Option<String>,
+    pub feature_id: Option<String>,
+    pub session_id: Option<String>,
+    pub description: String,
+    pub status: String,
+    pub tokens_used: i64,
+    pub cost_usd: f64,
+    pub duration_ms: i64,
+    pub file_count: usize,
+    pub created_at: String,
+}
+
+impl From<demiarch_core::commands::generation::GenerationSummary> for GenerationSummary {
+    fn from(g: demiarch_core::commands::generation::GenerationSummary) -> Self {
+        Self {
+            id: g.id,
+            project_id: g.project_id,
+            feature_id: g.feature_id,
+            session_id: g.session_id,
+            description: g.description,
+            status: g.status.as_str().to_string(),
+            tokens_used: g.tokens_used,
+            cost_usd: g.cost_usd,
+            duration_ms: g.duration_ms,
+            file_count: g.file_count,
+            created_at: g.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// File produced by a generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationFile {
+    pub path: String,
+    pub language: Option<String>,
+    pub is_new: bool,
+    pub decision: String,
+    pub applied: bool,
+    pub version: i64,
+}
+
+/// Generation with its plan, environment and files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationDetail {
+    #[serde(flatten)]
+    pub summary: GenerationSummary,
+    pub output_dir: String,
+    pub plan: Option<demiarch_core::domain::feature_decomposition::ExecutionPlan>,
+    pub environment: Option<demiarch_core::commands::environment::GenerationEnvironment>,
+    pub files: Vec<GenerationFile>,
+}
+
+impl From<demiarch_core::commands::generation::GenerationDetail> for GenerationDetail {
+    fn from(d: demiarch_core::commands::generation::GenerationDetail) -> Self {
+        let generation = d.generation;
+        let files: Vec<GenerationFile> = d
+            .artifacts
+            .into_iter()
+            .map(|a| GenerationFile {
+                applied: a.is_applied(),
+                path: a.file_path,
+                language: a.language,
+                is_new: a.is_new,
+                decision: a.decision.as_str().to_string(),
+                version: a.version,
+            })
+            .collect();
+        Self {
+            summary: GenerationSummary {
+                id: generation.id,
+                project_id: generation.project_id,
+                feature_id: generation.feature_id,
+                session_id: generation.session_id,
+                description: generation.description,
+                status: generation.status.as_str().to_string(),
+                tokens_used: generation.tokens_used,
+                cost_usd: generation.cost_usd,
+                duration_ms: generation.duration_ms,
+                file_count: files.len(),
+                created_at: generation.created_at.to_rfc3339(),
+            },
+            output_dir: generation.output_dir,
+            plan: generation.plan,
+            environment: generation.environment,
+            files,
+        }
+    }
+}
+
+/// Per-file diff in a generation review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationFileDiff {
+    pub path: String,
+    pub is_new: bool,
+    pub language: Option<String>,
+    pub decision: String,
+    pub applied: bool,
+    pub validation_status: Option<String>,
+    pub validation_errors: Vec<String>,
+    pub additions: usize,
+    pub deletions: usize,
+    pub diff: String,
+}
+
+/// Generation review with per-file diffs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationReview {
+    pub id: String,
+    pub description: String,
+    pub status: String,
+    pub files: Vec<GenerationFileDiff>,
+}
+
+impl From<demiarch_core::commands::generation::GenerationReview> for GenerationReview {
+    fn from(r: demiarch_core::commands::generation::GenerationReview) -> Self {
+        Self {
+            id: r.generation.id,
+            description: r.generation.description,
+            status: r.generation.status.as_str().to_string(),
+            files: r
+                .files
+                .into_iter()
+                .map(|f| GenerationFileDiff {
+                    path: f.path,
+                    is_new: f.is_new,
+                    language: f.language,
+                    decision: f.decision.as_str().to_string(),
+                    applied: f.applied,
+                    validation_status: f.validation_status.map(|s| s.as_str().to_string()),
+                    validation_errors: f.validation_errors.iter().map(|e| e.to_string()).collect(),
+                    additions: f.additions,
+                    deletions: f.deletions,
+                    diff: f.diff,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Live progress of a file being generated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationFileProgress {
+    pub path: String,
+    pub bytes: u64,
+    pub is_new: bool,
+    pub written: bool,
+}
+
+impl From<demiarch_core::agents::events::FileProgress> for GenerationFileProgress {
+    fn from(f: demiarch_core::agents::events::FileProgress) -> Self {
+        Self {
+            path: f.path,
+            bytes: f.bytes,
+            is_new: f.is_new,
+            written: f.written,
+        }
+    }
+}
+
+/// Localized messages for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Translations {
+    pub locale: String,
+    pub messages: HashMap<String, String>,
+}
+
 // ============================================================
 // Project Commands
 // ============================================================
 
 #[tauri::command]
-pub async fn get_projects() -> Result<Vec<ProjectSummary>, String> {
-    let projects = api::projects::list(None).await.map_err(|e| e.to_string())?;
+pub async fn get_projects() -> CommandResult<Vec<ProjectSummary>> {
+    let projects = api::projects::list(None)
+        .await
+        .map_err(ErrorPayload::from)?;
     Ok(projects.into_iter().map(ProjectSummary::from).collect())
 }
 
+/// One page of projects with the total count, for long project lists
+#[tauri::command]
+pub async fn get_projects_page(
+    status: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> CommandResult<Page<ProjectSummary>> {
+    let page = api::projects::list_page(status.as_deref(), PageRequest::from_parts(limit, offset))
+        .await
+        .map_err(ErrorPayload::from)?;
+    Ok(page.map(ProjectSummary::from))
+}
+
 #[tauri::command]
-pub async fn get_project(id: String) -> Result<ProjectSummary, String> {
+pub async fn get_project(id: String) -> CommandResult<ProjectSummary> {
     let project = api::projects::get(&id)
         .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| format!("Project not found: {}", id))?;
+        .map_err(ErrorPayload::from)?
+        .ok_or_else(|| Error::ProjectNotFound(id.clone()))?;
     Ok(ProjectSummary::from(project))
 }
 
 #[tauri::command]
-pub async fn create_project(name: String, framework: String) -> Result<ProjectSummary, String> {
+pub async fn create_project(name: String, framework: String) -> CommandResult<ProjectSummary> {
     let request = api::projects::CreateProjectRequest {
         name,
         framework,
@@ -154,18 +411,29 @@ pub async fn create_project(name: String, framework: String) -> Result<ProjectSu
     };
     let project = api::projects::create(request)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(ErrorPayload::from)?;
     Ok(ProjectSummary::from(project))
 }
 
 #[tauri::command]
-pub async fn delete_project(id: String, hard: bool) -> Result<(), String> {
+pub async fn delete_project(id: String, hard: bool) -> CommandResult<()> {
     // Soft delete by default (hard=false) - keeps data but marks as deleted
     // Hard delete (hard=true) - permanently removes project and all related data
     // Note: This does NOT delete the physical folder on disk
     api::projects::delete(&id, hard)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(ErrorPayload::from)
+}
+
+/// Costs and activity rolled up across member projects, all active projects
+/// when none are given, for the workspace overview
+#[tauri::command]
+pub async fn get_workspace_overview(
+    project_ids: Option<Vec<String>>,
+) -> CommandResult<demiarch_core::commands::workspace::WorkspaceStats> {
+    api::projects::workspace_overview(&project_ids.unwrap_or_default())
+        .await
+        .map_err(ErrorPayload::from)
 }
 
 // ============================================================
@@ -173,54 +441,233 @@ pub async fn delete_project(id: String, hard: bool) -> Result<(), String> {
 // ============================================================
 
 #[tauri::command]
-pub async fn get_features(project_id: String) -> Result<Vec<FeatureSummary>, String> {
+pub async fn get_features(project_id: String) -> CommandResult<Vec<FeatureSummary>> {
     let features = api::features::list_by_project(&project_id, None)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(ErrorPayload::from)?;
     Ok(features.into_iter().map(FeatureSummary::from).collect())
 }
 
+/// One page of a project's features with the total count
+#[tauri::command]
+pub async fn get_features_page(
+    project_id: String,
+    status: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> CommandResult<Page<FeatureSummary>> {
+    let page = api::features::list_page(
+        &project_id,
+        status.as_deref(),
+        PageRequest::from_parts(limit, offset),
+    )
+    .await
+    .map_err(ErrorPayload::from)?;
+    Ok(page.map(FeatureSummary::from))
+}
+
 #[tauri::command]
-pub async fn get_feature(id: String) -> Result<FeatureSummary, String> {
+pub async fn get_feature(id: String) -> CommandResult<FeatureSummary> {
     let feature = api::features::get(&id)
         .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| format!("Feature not found: {}", id))?;
+        .map_err(ErrorPayload::from)?
+        .ok_or_else(|| Error::FeatureNotFound(id.clone()))?;
     Ok(FeatureSummary::from(feature))
 }
 
 #[tauri::command]
-pub async fn update_feature_status(id: String, status: String) -> Result<FeatureSummary, String> {
+pub async fn update_feature_status(id: String, status: String) -> CommandResult<FeatureSummary> {
     api::features::update_status(&id, &status)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(ErrorPayload::from)?;
 
     // Return the updated feature
     let feature = api::features::get(&id)
         .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| format!("Feature not found: {}", id))?;
+        .map_err(ErrorPayload::from)?
+        .ok_or_else(|| Error::FeatureNotFound(id.clone()))?;
     Ok(FeatureSummary::from(feature))
 }
 
+// ============================================================
+// Generation Commands
+// ============================================================
+
+#[tauri::command]
+pub async fn get_generations(
+    project_id: Option<String>,
+    status: Option<String>,
+    limit: Option<i64>,
+) -> CommandResult<Vec<GenerationSummary>> {
+    let generations = api::generations::list(project_id.as_deref(), status.as_deref(), limit)
+        .await
+        .map_err(ErrorPayload::from)?;
+    Ok(generations
+        .into_iter()
+        .map(GenerationSummary::from)
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_generation(id: String) -> CommandResult<GenerationDetail> {
+    let detail = api::generations::show(&id)
+        .await
+        .map_err(ErrorPayload::from)?;
+    Ok(GenerationDetail::from(detail))
+}
+
+#[tauri::command]
+pub async fn delete_generation(id: String) -> CommandResult<()> {
+    api::generations::delete(&id)
+        .await
+        .map_err(ErrorPayload::from)
+}
+
+#[tauri::command]
+pub async fn review_generation(id: String) -> CommandResult<GenerationReview> {
+    let review = api::generations::review(&id)
+        .await
+        .map_err(ErrorPayload::from)?;
+    Ok(GenerationReview::from(review))
+}
+
+#[tauri::command]
+pub async fn decide_generation_file(
+    id: String,
+    file_path: String,
+    decision: String,
+) -> CommandResult<GenerationReview> {
+    api::generations::decide(&id, &file_path, &decision)
+        .await
+        .map_err(ErrorPayload::from)?;
+    review_generation(id).await
+}
+
+#[tauri::command]
+pub async fn apply_generation(id: String, file_path: Option<String>) -> CommandResult<Vec<String>> {
+    api::generations::apply(&id, file_path.as_deref())
+        .await
+        .map_err(ErrorPayload::from)
+}
+
+#[tauri::command]
+pub async fn get_generation_progress(
+    session_id: Option<String>,
+) -> CommandResult<Vec<GenerationFileProgress>> {
+    let progress = api::generations::progress(session_id.as_deref()).map_err(ErrorPayload::from)?;
+    Ok(progress
+        .into_iter()
+        .map(GenerationFileProgress::from)
+        .collect())
+}
+
+/// One page of an agent session's events, newest page first
+#[tauri::command]
+pub async fn get_agent_events_page(
+    session_id: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> CommandResult<Page<AgentEvent>> {
+    api::generations::events_page(
+        session_id.as_deref(),
+        PageRequest::from_parts(limit, offset),
+    )
+    .map_err(ErrorPayload::from)
+}
+
 // ============================================================
 // Session Commands
 // ============================================================
 
 #[tauri::command]
-pub async fn get_sessions() -> Result<Vec<SessionSummary>, String> {
+pub async fn get_sessions() -> CommandResult<Vec<SessionSummary>> {
     let sessions = api::sessions::list(None, Some(50))
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(ErrorPayload::from)?;
     Ok(sessions.into_iter().map(SessionSummary::from).collect())
 }
 
+#[tauri::command]
+pub async fn get_active_session() -> CommandResult<Option<SessionSummary>> {
+    let session = api::sessions::get_active()
+        .await
+        .map_err(ErrorPayload::from)?;
+    Ok(session.map(SessionSummary::from))
+}
+
+#[tauri::command]
+pub async fn start_session(
+    app: AppHandle,
+    project_id: Option<String>,
+    feature_id: Option<String>,
+    description: Option<String>,
+) -> CommandResult<SessionSummary> {
+    let session = api::sessions::start(
+        project_id.as_deref(),
+        feature_id.as_deref(),
+        description.as_deref(),
+    )
+    .await
+    .map_err(ErrorPayload::from)?;
+    Ok(emit_session_changed(&app, "started", session))
+}
+
+#[tauri::command]
+pub async fn pause_session(app: AppHandle, id: Option<String>) -> CommandResult<SessionSummary> {
+    let session = api::sessions::pause(id.as_deref())
+        .await
+        .map_err(ErrorPayload::from)?;
+    Ok(emit_session_changed(&app, "paused", session))
+}
+
+#[tauri::command]
+pub async fn resume_session(app: AppHandle, id: String) -> CommandResult<SessionSummary> {
+    let session = api::sessions::resume(&id)
+        .await
+        .map_err(ErrorPayload::from)?;
+    Ok(emit_session_changed(&app, "resumed", session))
+}
+
+#[tauri::command]
+pub async fn end_session(
+    app: AppHandle,
+    id: Option<String>,
+    abandon: Option<bool>,
+) -> CommandResult<SessionSummary> {
+    let abandon = abandon.unwrap_or(false);
+    let session = api::sessions::end(id.as_deref(), abandon)
+        .await
+        .map_err(ErrorPayload::from)?;
+    let action = if abandon { "abandoned" } else { "completed" };
+    Ok(emit_session_changed(&app, action, session))
+}
+
+/// Notify every window that a session changed and hand the session back
+///
+/// A failed emit only means no window is listening, so it is logged rather
+/// than failing the command that already changed the session.
+pub(crate) fn emit_session_changed(
+    app: &AppHandle,
+    action: &str,
+    session: api::sessions::SessionSummary,
+) -> SessionSummary {
+    let session = SessionSummary::from(session);
+    let payload = SessionChanged {
+        action: action.to_string(),
+        session: session.clone(),
+    };
+    if let Err(e) = app.emit(SESSION_CHANGED_EVENT, payload) {
+        tracing::warn!(error = %e, "Failed to emit {}", SESSION_CHANGED_EVENT);
+    }
+    session
+}
+
 // ============================================================
 // Cost Commands
 // ============================================================
 
 #[tauri::command]
-pub async fn get_costs() -> Result<CostSummary, String> {
+pub async fn get_costs() -> CommandResult<CostSummary> {
     // Cost tracking requires a CostTracker instance which is typically
     // managed by the application state. For now, return defaults.
     // TODO: Add CostTracker to application state
@@ -232,12 +679,174 @@ pub async fn get_costs() -> Result<CostSummary, String> {
     })
 }
 
+/// Month-end spend projection for the dashboard forecast widget
+#[tauri::command]
+pub async fn get_spend_forecast(
+    project_id: Option<String>,
+) -> CommandResult<demiarch_core::cost::forecast::SpendForecast> {
+    api::costs::spend_forecast(project_id.as_deref())
+        .await
+        .map_err(ErrorPayload::from)
+}
+
+// ============================================================
+// Analytics Commands
+// ============================================================
+
+/// Local usage dashboard; statistics are only computed once the user opts in
+#[tauri::command]
+pub async fn get_usage_stats(
+    project_id: Option<String>,
+    weeks: Option<u32>,
+) -> CommandResult<api::analytics::UsageDashboard> {
+    api::analytics::usage(project_id.as_deref(), weeks)
+        .await
+        .map_err(ErrorPayload::from)
+}
+
+#[tauri::command]
+pub async fn set_analytics_enabled(enabled: bool) -> CommandResult<()> {
+    api::analytics::set_enabled(enabled).map_err(ErrorPayload::from)
+}
+
+// ============================================================
+// Chat Commands
+// ============================================================
+
+/// Messages on a conversation's active branch, with variant navigation
+#[tauri::command]
+pub async fn get_chat_history(
+    conversation_id: String,
+) -> CommandResult<Vec<api::chat::ChatMessageSummary>> {
+    api::chat::history(&conversation_id)
+        .await
+        .map_err(ErrorPayload::from)
+}
+
+/// One page of the active branch, newest page first, so long conversations
+/// load as the user scrolls back
+#[tauri::command]
+pub async fn get_chat_history_page(
+    conversation_id: String,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> CommandResult<Page<api::chat::ChatMessageSummary>> {
+    api::chat::history_page(&conversation_id, PageRequest::from_parts(limit, offset))
+        .await
+        .map_err(ErrorPayload::from)
+}
+
+/// Show another variant of a retried or edited message
+#[tauri::command]
+pub async fn select_message_variant(
+    conversation_id: String,
+    message_id: String,
+) -> CommandResult<Vec<api::chat::ChatMessageSummary>> {
+    api::chat::select_variant(&conversation_id, &message_id)
+        .await
+        .map_err(ErrorPayload::from)
+}
+
+// ============================================================
+// Project Health Commands
+// ============================================================
+
+/// Health score and stale-item report for the project health widget
+#[tauri::command]
+pub async fn get_project_health(
+    project_id: String,
+) -> CommandResult<demiarch_core::commands::health::HealthReport> {
+    api::health::project(&project_id)
+        .await
+        .map_err(ErrorPayload::from)
+}
+
+/// Features in review, unresolved conflicts, pending approvals, blocking
+/// findings and failed verifications for the inbox
+#[tauri::command]
+pub async fn get_review_queue(
+    approvals: tauri::State<'_, crate::approvals::Approvals>,
+    project_id: String,
+) -> CommandResult<demiarch_core::commands::inbox::ReviewQueue> {
+    api::health::review_queue(&project_id, &approvals.0.pending())
+        .await
+        .map_err(ErrorPayload::from)
+}
+
+// ============================================================
+// Project File Commands
+// ============================================================
+
+/// List one directory of a project's files for the file tree
+#[tauri::command]
+pub async fn list_project_files(
+    project_id: String,
+    subpath: Option<String>,
+) -> CommandResult<Vec<api::files::FileEntry>> {
+    api::files::list_project_files(&project_id, subpath.as_deref())
+        .await
+        .map_err(ErrorPayload::from)
+}
+
+/// Read a text file inside a project for preview
+#[tauri::command]
+pub async fn read_project_file(path: String) -> CommandResult<api::files::FileContent> {
+    api::files::read_project_file(&path)
+        .await
+        .map_err(ErrorPayload::from)
+}
+
+#[tauri::command]
+pub async fn file_metadata(path: String) -> CommandResult<api::files::FileEntry> {
+    api::files::file_metadata(&path)
+        .await
+        .map_err(ErrorPayload::from)
+}
+
+// ============================================================
+// Approval Commands
+// ============================================================
+
+/// Approval requests still waiting for an answer
+#[tauri::command]
+pub async fn get_pending_approvals(
+    approvals: tauri::State<'_, crate::approvals::Approvals>,
+) -> CommandResult<Vec<ApprovalRequest>> {
+    Ok(approvals.0.pending())
+}
+
+/// Approve or reject a waiting request ("approve" or "reject")
+#[tauri::command]
+pub async fn respond_to_approval(
+    approvals: tauri::State<'_, crate::approvals::Approvals>,
+    request_id: String,
+    decision: String,
+) -> CommandResult<()> {
+    let decision = ApprovalDecision::parse(&decision)?;
+    approvals
+        .0
+        .respond(&request_id, decision)
+        .map_err(ErrorPayload::from)
+}
+
+// ============================================================
+// Deep Link Commands
+// ============================================================
+
+/// Route of the `demiarch://` link the app was launched with, if any
+#[tauri::command]
+pub async fn take_pending_deep_link(
+    pending: tauri::State<'_, crate::deeplinks::PendingDeepLink>,
+) -> CommandResult<Option<String>> {
+    Ok(pending.take())
+}
+
 // ============================================================
 // Agent Commands
 // ============================================================
 
 #[tauri::command]
-pub async fn get_agents() -> Result<Vec<AgentStatus>, String> {
+pub async fn get_agents() -> CommandResult<Vec<AgentStatus>> {
     // Agent status tracking is not yet implemented in the core API
     // TODO: Add agent status API
     Ok(vec![])
@@ -248,8 +857,8 @@ pub async fn get_agents() -> Result<Vec<AgentStatus>, String> {
 // ============================================================
 
 #[tauri::command]
-pub async fn doctor() -> Result<DoctorResult, String> {
-    let health = api::health::doctor().await.map_err(|e| e.to_string())?;
+pub async fn doctor() -> CommandResult<DoctorResult> {
+    let health = api::health::doctor().await.map_err(ErrorPayload::from)?;
 
     let database_ok = health
         .checks
@@ -280,12 +889,50 @@ pub async fn doctor() -> Result<DoctorResult, String> {
     })
 }
 
+/// Running version, shown in the About section
+#[tauri::command]
+pub async fn app_version() -> CommandResult<String> {
+    Ok(update::CURRENT_VERSION.to_string())
+}
+
+/// Newer release on the configured channel, if there is one
+///
+/// Passive: disabled by `updates.notify = false`, and silent when offline
+/// or the check fails.
+#[tauri::command]
+pub async fn check_for_update() -> CommandResult<Option<UpdateStatus>> {
+    let config = Config::load().map_err(ErrorPayload::from)?;
+    if !config.updates.notify {
+        return Ok(None);
+    }
+    let channel: update::Channel = config.updates.channel.parse().map_err(ErrorPayload::from)?;
+    Ok(update::Updater::new(&config.updates).notice(channel).await)
+}
+
+// ============================================================
+// Transcription Commands
+// ============================================================
+
+/// Transcribe a voice note into text for a chat message or feature description
+///
+/// `audio` is the file's bytes; `file_name` tells the backend its format.
+#[tauri::command]
+pub async fn transcribe_audio(
+    file_name: String,
+    audio: Vec<u8>,
+    project_id: Option<String>,
+) -> CommandResult<Transcript> {
+    api::transcription::transcribe_audio(&file_name, &audio, project_id.as_deref())
+        .await
+        .map_err(ErrorPayload::from)
+}
+
 // ============================================================
 // Conflict Resolution Commands
 // ============================================================
 
 #[tauri::command]
-pub async fn get_conflicts(project_id: String) -> Result<Vec<Conflict>, String> {
+pub async fn get_conflicts(project_id: String) -> CommandResult<Vec<Conflict>> {
     // Conflict resolution is not yet implemented in the core API
     // TODO: Add conflict resolution API
     let _ = project_id;
@@ -298,7 +945,7 @@ pub async fn resolve_conflict_hunk(
     hunk_id: String,
     resolution: String,
     custom_content: Option<String>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     // Conflict resolution is not yet implemented in the core API
     // TODO: Add conflict resolution API
     let _ = (conflict_id, hunk_id, resolution, custom_content);
@@ -306,9 +953,82 @@ pub async fn resolve_conflict_hunk(
 }
 
 #[tauri::command]
-pub async fn apply_conflict_resolutions(conflict_id: String) -> Result<(), String> {
+pub async fn apply_conflict_resolutions(conflict_id: String) -> CommandResult<()> {
     // Conflict resolution is not yet implemented in the core API
     // TODO: Add conflict resolution API
     let _ = conflict_id;
     Ok(())
 }
+
+// ============================================================
+// Job Commands
+// ============================================================
+
+#[tauri::command]
+pub async fn get_jobs(
+    status: Option<String>,
+    limit: Option<i64>,
+) -> CommandResult<Vec<JobSummary>> {
+    let jobs = api::jobs::list(status.as_deref(), limit)
+        .await
+        .map_err(ErrorPayload::from)?;
+    Ok(jobs.into_iter().map(JobSummary::from).collect())
+}
+
+#[tauri::command]
+pub async fn cancel_job(id: String) -> CommandResult<JobSummary> {
+    let job = api::jobs::cancel(&id).await.map_err(ErrorPayload::from)?;
+    Ok(JobSummary::from(job))
+}
+
+// ============================================================
+// Plugin Permission Commands
+// ============================================================
+
+fn plugin_error(error: PluginError) -> ErrorPayload {
+    Error::PluginValidationFailed(error.to_string()).into()
+}
+
+/// Remembered permission decisions, by plugin ID
+#[tauri::command]
+pub async fn get_plugin_grants() -> CommandResult<BTreeMap<String, PluginGrants>> {
+    let store = GrantStore::open().map_err(plugin_error)?;
+    Ok(store
+        .plugins()
+        .map(|(id, grants)| (id.clone(), grants.clone()))
+        .collect())
+}
+
+/// Always allow or always deny a permission for a plugin
+#[tauri::command]
+pub async fn set_plugin_grant(
+    plugin_id: String,
+    permission: Permission,
+    allowed: bool,
+) -> CommandResult<()> {
+    let mut store = GrantStore::open().map_err(plugin_error)?;
+    store.set(&plugin_id, permission, allowed);
+    store.save().map_err(plugin_error)
+}
+
+/// Forget every decision for a plugin so it is asked again on next use
+#[tauri::command]
+pub async fn revoke_plugin_grants(plugin_id: String) -> CommandResult<bool> {
+    let mut store = GrantStore::open().map_err(plugin_error)?;
+    let revoked = store.revoke(&plugin_id);
+    store.save().map_err(plugin_error)?;
+    Ok(revoked)
+}
+
+// ============================================================
+// Localization Commands
+// ============================================================
+
+#[tauri::command]
+pub async fn get_translations() -> CommandResult<Translations> {
+    let catalog = i18n::catalog();
+    Ok(Translations {
+        locale: catalog.locale().to_string(),
+        messages: catalog.messages().clone(),
+    })
+}
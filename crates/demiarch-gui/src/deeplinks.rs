@@ -0,0 +1,91 @@
+//! `demiarch://` links opened from terminals, reports and webhooks
+//!
+//! The OS hands registered links to the app, which resolves them to a
+//! frontend route and tells the window to navigate there. A link that
+//! launched the app is also kept until the frontend asks for it, since it
+//! arrives before any window is listening.
+
+use std::sync::Mutex;
+
+use demiarch_core::api;
+use demiarch_core::deeplink::DeepLink;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::tray;
+
+/// Event name carrying a [`DeepLinkOpened`] to the frontend
+pub const DEEP_LINK_EVENT: &str = "deep-link";
+
+/// Payload of the `deep-link` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeepLinkOpened {
+    pub url: String,
+    /// Frontend route showing the linked record
+    pub route: String,
+}
+
+/// Route of the most recent link, until the frontend takes it
+#[derive(Debug, Default)]
+pub struct PendingDeepLink(Mutex<Option<String>>);
+
+impl PendingDeepLink {
+    pub fn take(&self) -> Option<String> {
+        self.0.lock().ok().and_then(|mut route| route.take())
+    }
+
+    fn set(&self, route: String) {
+        if let Ok(mut pending) = self.0.lock() {
+            *pending = Some(route);
+        }
+    }
+}
+
+/// Register the scheme and start handling links
+pub fn init(app: &AppHandle) {
+    app.manage(PendingDeepLink::default());
+
+    // Installed bundles register the scheme themselves; Linux and Windows
+    // development builds have to do it at runtime
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(e) = app.deep_link().register_all() {
+        tracing::warn!(error = %e, "Failed to register the demiarch:// scheme");
+    }
+
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in urls {
+            open(app.clone(), url.to_string());
+        }
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open(handle.clone(), url.to_string());
+        }
+    });
+}
+
+fn open(app: AppHandle, url: String) {
+    tauri::async_runtime::spawn(async move {
+        let route = match resolve(&url).await {
+            Ok(route) => route,
+            Err(e) => {
+                tracing::warn!(url = %url, error = %e, "Ignoring deep link");
+                return;
+            }
+        };
+        app.state::<PendingDeepLink>().set(route.clone());
+        tray::show_main_window(&app);
+        if let Err(e) = app.emit(DEEP_LINK_EVENT, DeepLinkOpened { url, route }) {
+            tracing::warn!(error = %e, "Failed to emit {}", DEEP_LINK_EVENT);
+        }
+    });
+}
+
+async fn resolve(url: &str) -> demiarch_core::Result<String> {
+    let link = DeepLink::parse(url)?;
+    let db = api::get_database().await?;
+    link.route(&db).await
+}
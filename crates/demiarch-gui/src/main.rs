@@ -8,26 +8,102 @@
     windows_subsystem = "windows"
 )]
 
+mod approvals;
+mod background;
 mod commands;
+mod deeplinks;
+mod notifications;
+mod tray;
+
+use demiarch_core::config::Config;
+use demiarch_core::i18n;
+use demiarch_core::infrastructure::network;
+use demiarch_core::storage::instrumentation;
 
 fn main() {
+    let config = Config::load().ok();
+    if let Some(config) = &config {
+        network::set_offline(config.network.offline);
+        instrumentation::init(&config.database);
+    }
+    i18n::init(config.as_ref());
+    let close_to_tray = config.as_ref().is_some_and(|c| c.ui.close_to_tray);
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .setup(|app| {
+            tray::build(app.handle())?;
+            approvals::init(app.handle());
+            background::start(app.handle());
+            deeplinks::init(app.handle());
+            Ok(())
+        })
+        .on_window_event(move |window, event| {
+            // Background tasks belong to the app, so hiding the window keeps
+            // jobs and notifications running
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                if close_to_tray {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             commands::get_projects,
+            commands::get_projects_page,
             commands::get_project,
             commands::create_project,
             commands::delete_project,
+            commands::get_workspace_overview,
             commands::get_features,
+            commands::get_features_page,
             commands::get_feature,
             commands::update_feature_status,
+            commands::get_generations,
+            commands::get_generation,
+            commands::delete_generation,
+            commands::review_generation,
+            commands::decide_generation_file,
+            commands::apply_generation,
+            commands::get_generation_progress,
+            commands::get_agent_events_page,
+            commands::get_pending_approvals,
+            commands::respond_to_approval,
             commands::get_sessions,
+            commands::get_active_session,
+            commands::start_session,
+            commands::pause_session,
+            commands::resume_session,
+            commands::end_session,
+            commands::get_jobs,
+            commands::cancel_job,
             commands::get_costs,
+            commands::get_spend_forecast,
+            commands::get_usage_stats,
+            commands::set_analytics_enabled,
+            commands::get_chat_history,
+            commands::get_chat_history_page,
+            commands::select_message_variant,
+            commands::get_project_health,
+            commands::get_review_queue,
+            commands::list_project_files,
+            commands::read_project_file,
+            commands::file_metadata,
+            commands::take_pending_deep_link,
             commands::get_agents,
             commands::doctor,
+            commands::app_version,
+            commands::check_for_update,
+            commands::transcribe_audio,
             commands::get_conflicts,
             commands::resolve_conflict_hunk,
             commands::apply_conflict_resolutions,
+            commands::get_translations,
+            commands::get_plugin_grants,
+            commands::set_plugin_grant,
+            commands::revoke_plugin_grants,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
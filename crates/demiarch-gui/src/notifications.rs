@@ -0,0 +1,88 @@
+//! Desktop notifications for long-running operations
+//!
+//! A background task polls the core [`NotificationFeed`]. While a Demiarch
+//! window has focus, notifications are emitted to the frontend as in-app
+//! toasts; otherwise they are shown as native desktop notifications.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use demiarch_core::api;
+use demiarch_core::config::watch;
+use demiarch_core::notify::{Notification, NotificationFeed, NotificationSink, Notifier};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// Event name carrying a [`Notification`] to a focused window
+pub const NOTIFICATION_EVENT: &str = "notification";
+
+/// Sink that picks between an in-app toast and a native notification
+struct DesktopNotifier {
+    app: AppHandle,
+}
+
+impl DesktopNotifier {
+    fn window_focused(&self) -> bool {
+        self.app
+            .webview_windows()
+            .values()
+            .any(|w| w.is_focused().unwrap_or(false))
+    }
+}
+
+impl NotificationSink for DesktopNotifier {
+    fn notify(&self, notification: &Notification) {
+        if self.window_focused() {
+            if let Err(e) = self.app.emit(NOTIFICATION_EVENT, notification) {
+                tracing::warn!(error = %e, "Failed to emit {}", NOTIFICATION_EVENT);
+            }
+            return;
+        }
+        if let Err(e) = self
+            .app
+            .notification()
+            .builder()
+            .title(&notification.title)
+            .body(&notification.body)
+            .show()
+        {
+            tracing::warn!(error = %e, "Failed to show desktop notification");
+        }
+    }
+}
+
+/// Start polling for events to announce
+///
+/// Does nothing when `notifications.enabled` is off at startup.
+pub fn spawn(app: AppHandle) {
+    let config = watch::current();
+    if !config.notifications.enabled {
+        return;
+    }
+    let notifier = Notifier::new(Arc::new(DesktopNotifier { app }));
+    let interval = Duration::from_secs(config.notifications.poll_secs.max(1));
+
+    tauri::async_runtime::spawn(async move {
+        let mut feed = NotificationFeed::new(&config.cost, Utc::now());
+        let mut updates = watch::subscribe();
+        loop {
+            tokio::time::sleep(interval).await;
+            // Budgets edited since the last poll apply from this one
+            while let Ok(update) = updates.try_recv() {
+                feed.set_cost(&update.config.cost);
+            }
+            let db = match api::get_database().await {
+                Ok(db) => db,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Notification feed has no database");
+                    continue;
+                }
+            };
+            match feed.poll(&db, Utc::now()).await {
+                Ok(notifications) => notifier.notify_all(&notifications),
+                Err(e) => tracing::warn!(error = %e, "Failed to check for notifications"),
+            }
+        }
+    });
+}
@@ -0,0 +1,127 @@
+//! System tray icon
+//!
+//! Shows how many agents are running and today's spend, with quick actions
+//! to reopen the dashboard, pause queued generations and end the session.
+
+use demiarch_core::api;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::background::BackgroundState;
+use crate::commands;
+
+const TRAY_ID: &str = "demiarch";
+const MAIN_WINDOW: &str = "main";
+
+const STATUS_ID: &str = "status";
+const OPEN_ID: &str = "open";
+const PAUSE_ID: &str = "pause";
+const END_SESSION_ID: &str = "end_session";
+const QUIT_ID: &str = "quit";
+
+/// Menu items whose text changes while the app runs
+struct TrayItems {
+    status: MenuItem<Wry>,
+    pause: MenuItem<Wry>,
+}
+
+/// Create the tray icon and its menu
+pub fn build(app: &AppHandle) -> tauri::Result<()> {
+    let status = MenuItem::with_id(app, STATUS_ID, "No active agents", false, None::<&str>)?;
+    let open = MenuItem::with_id(app, OPEN_ID, "Open dashboard", true, None::<&str>)?;
+    let pause = MenuItem::with_id(app, PAUSE_ID, "Pause generation", true, None::<&str>)?;
+    let end_session = MenuItem::with_id(app, END_SESSION_ID, "End session", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, QUIT_ID, "Quit Demiarch", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status,
+            &PredefinedMenuItem::separator(app)?,
+            &open,
+            &pause,
+            &end_session,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Demiarch")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(on_menu_event)
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    app.manage(TrayItems { status, pause });
+    Ok(())
+}
+
+/// Update the status line and tooltip
+pub fn refresh(app: &AppHandle, active_agents: usize, spent_today_usd: f64) {
+    let text = match active_agents {
+        0 => format!("No active agents · ${:.2} today", spent_today_usd),
+        1 => format!("1 active agent · ${:.2} today", spent_today_usd),
+        n => format!("{} active agents · ${:.2} today", n, spent_today_usd),
+    };
+    if let Some(items) = app.try_state::<TrayItems>() {
+        let _ = items.status.set_text(&text);
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(format!("Demiarch - {}", text)));
+    }
+}
+
+/// Bring the main window back from the tray
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        OPEN_ID => show_main_window(app),
+        PAUSE_ID => {
+            let state = app.state::<BackgroundState>();
+            let paused = !state.is_paused();
+            state.set_paused(paused);
+            if let Some(items) = app.try_state::<TrayItems>() {
+                let label = if paused {
+                    "Resume generation"
+                } else {
+                    "Pause generation"
+                };
+                let _ = items.pause.set_text(label);
+            }
+        }
+        END_SESSION_ID => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                match api::sessions::end(None, false).await {
+                    Ok(session) => {
+                        commands::emit_session_changed(&app, "completed", session);
+                    }
+                    Err(e) => tracing::warn!(error = %e, "Failed to end session from tray"),
+                }
+            });
+        }
+        QUIT_ID => app.exit(0),
+        _ => {}
+    }
+}
@@ -0,0 +1,131 @@
+//! Plugin subscribers for core events
+//!
+//! A plugin that lists event kinds in its manifest's `events` field receives
+//! each matching event through its `on_event` export (see
+//! [`Sandbox::deliver_event`]). The event is the JSON [`EventEnvelope`]; the
+//! plugin may answer with a JSON [`EventReaction`] to annotate the event or,
+//! for vetoable events, to veto it.
+//!
+//! Event delivery never prompts: host calls made while handling an event are
+//! decided by previously persisted grants only.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use demiarch_core::events::{EventBus, EventEnvelope, EventKind, EventReaction, EventSubscriber};
+use demiarch_core::Error;
+
+use crate::loader::{discover_manifests, load_manifest_for_tier, load_wasm_bytes};
+use crate::permissions::{GrantStore, PermissionMediator};
+use crate::sandbox::{ResourceLimits, Sandbox};
+use crate::{LicenseTier, PluginError, PluginManifest, PluginResult};
+
+/// Largest plugin module loaded for event delivery
+const MAX_WASM_BYTES: usize = 32 * 1024 * 1024;
+
+/// Delivers core events to a plugin's `on_event` export
+pub struct PluginSubscriber {
+    manifest: PluginManifest,
+    wasm: Arc<Vec<u8>>,
+    sandbox: Arc<Sandbox>,
+}
+
+impl PluginSubscriber {
+    pub fn new(manifest: PluginManifest, wasm: Vec<u8>, sandbox: Sandbox) -> Self {
+        Self {
+            manifest,
+            wasm: Arc::new(wasm),
+            sandbox: Arc::new(sandbox),
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for PluginSubscriber {
+    fn name(&self) -> &str {
+        &self.manifest.id
+    }
+
+    fn subscribes_to(&self, kind: EventKind) -> bool {
+        self.manifest.events.contains(&kind)
+    }
+
+    async fn deliver(&self, event: &EventEnvelope) -> demiarch_core::Result<EventReaction> {
+        let payload = serde_json::to_vec(event)
+            .map_err(|e| Error::Other(format!("Failed to serialize event: {}", e)))?;
+        let wasm = Arc::clone(&self.wasm);
+        let sandbox = Arc::clone(&self.sandbox);
+        let permissions = self.manifest.permissions.clone();
+
+        let reaction = tokio::task::spawn_blocking(move || {
+            sandbox.deliver_event(&wasm, &permissions, &payload)
+        })
+        .await
+        .map_err(|e| Error::Other(format!("Plugin event task failed: {}", e)))?
+        .map_err(plugin_error)?;
+
+        match reaction {
+            Some(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                Error::PluginValidationFailed(format!(
+                    "{} returned an invalid event reaction: {}",
+                    self.manifest.id, e
+                ))
+            }),
+            None => Ok(EventReaction::default()),
+        }
+    }
+}
+
+fn plugin_error(error: PluginError) -> Error {
+    Error::PluginValidationFailed(error.to_string())
+}
+
+/// Subscribe every installed plugin that declares events, returning how many
+///
+/// Each delivery runs within `limits` and is recorded in the plugin stats.
+/// Plugins the active tier does not allow, or that fail to load, are skipped
+/// with a warning so one broken plugin cannot block the others.
+pub fn attach_installed(
+    bus: &EventBus,
+    tier: LicenseTier,
+    limits: ResourceLimits,
+    project_dir: &Path,
+) -> PluginResult<usize> {
+    let mut attached = 0;
+    for manifest_path in discover_manifests()? {
+        match load_subscriber(&manifest_path, tier, limits, project_dir) {
+            Ok(Some(subscriber)) => {
+                bus.subscribe(Arc::new(subscriber));
+                attached += 1;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!(manifest = %manifest_path.display(), error = %e, "Skipping plugin event subscriber");
+            }
+        }
+    }
+    Ok(attached)
+}
+
+fn load_subscriber(
+    manifest_path: &Path,
+    tier: LicenseTier,
+    limits: ResourceLimits,
+    project_dir: &Path,
+) -> PluginResult<Option<PluginSubscriber>> {
+    let manifest = load_manifest_for_tier(manifest_path, tier)?;
+    if manifest.events.is_empty() {
+        return Ok(None);
+    }
+
+    let wasm_path = manifest_path.with_file_name("plugin.wasm");
+    let wasm = load_wasm_bytes(&wasm_path, MAX_WASM_BYTES)?;
+    let mediator = PermissionMediator::new(&manifest.id, &manifest.permissions, project_dir)?
+        .with_grants(GrantStore::open()?);
+    let sandbox = Sandbox::new(manifest.permissions.clone())?
+        .with_host(mediator)
+        .with_limits(limits)
+        .with_meter(&manifest.id)?;
+    Ok(Some(PluginSubscriber::new(manifest, wasm, sandbox)))
+}
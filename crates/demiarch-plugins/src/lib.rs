@@ -6,14 +6,19 @@
 //! - Offline license verification (ed25519 signatures)
 //! - Plugin marketplace integration
 
+pub mod events;
 pub mod license;
 pub mod loader;
+pub mod metering;
+pub mod permissions;
 pub mod registry;
 pub mod sandbox;
+pub mod scaffold;
 
 #[cfg(test)]
 mod lib_tests;
 
+use demiarch_core::events::EventKind;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -27,6 +32,16 @@ pub enum PluginError {
     #[error("License expired for plugin '{0}'")]
     LicenseExpired(String),
 
+    #[error("Plugin '{plugin}' requires a {required:?} license (active: {active:?}). Run `demiarch license activate <key>`")]
+    TierRequired {
+        plugin: String,
+        required: LicenseTier,
+        active: LicenseTier,
+    },
+
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
     #[error("WASM execution error: {0}")]
     WasmError(String),
 
@@ -46,15 +61,32 @@ pub struct PluginManifest {
     pub author: String,
     pub license_tier: LicenseTier,
     pub permissions: Vec<Permission>,
+    /// Core events the plugin's `on_event` export receives
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<EventKind>,
 }
 
-#[derive(Debug, Clone, Copy, serde::Deserialize, serde::Serialize)]
+/// Tier a plugin requires, ordered from least to most capable
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Deserialize, serde::Serialize,
+)]
 pub enum LicenseTier {
     Free,
     Pro,
     Enterprise,
 }
 
+impl From<demiarch_core::commands::license::LicenseTier> for LicenseTier {
+    fn from(tier: demiarch_core::commands::license::LicenseTier) -> Self {
+        use demiarch_core::commands::license::LicenseTier as UserTier;
+        match tier {
+            UserTier::Free => Self::Free,
+            UserTier::Pro => Self::Pro,
+            UserTier::Enterprise => Self::Enterprise,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
 pub enum Permission {
     ReadFiles,
@@ -12,6 +12,7 @@ fn test_plugin_manifest_serialization() {
         author: "Test Author".to_string(),
         license_tier: LicenseTier::Free,
         permissions: vec![Permission::ReadFiles],
+        events: vec![],
     };
 
     let json = serde_json::to_string(&manifest).expect("serialize");
@@ -38,6 +39,7 @@ fn test_plugin_manifest_all_permissions() {
             Permission::Network,
             Permission::Subprocess,
         ],
+        events: vec![],
     };
 
     assert_eq!(manifest.permissions.len(), 4);
@@ -134,6 +136,7 @@ fn test_plugin_manifest_clone() {
         author: "Tester".to_string(),
         license_tier: LicenseTier::Pro,
         permissions: vec![Permission::Network],
+        events: vec![],
     };
 
     let cloned = manifest.clone();
@@ -165,13 +168,15 @@ fn test_plugin_manifest_empty_permissions() {
         author: "Author".to_string(),
         license_tier: LicenseTier::Free,
         permissions: vec![],
+        events: vec![],
     };
 
     assert!(manifest.permissions.is_empty());
 }
 
 mod sandbox_tests {
-    use crate::sandbox::Sandbox;
+    use crate::metering::StatsStore;
+    use crate::sandbox::{ResourceLimits, Sandbox};
     use crate::Permission;
 
     #[test]
@@ -229,12 +234,95 @@ mod sandbox_tests {
             }
         }
     }
+
+    #[test]
+    fn test_deliver_event_reads_reaction() {
+        let Ok(sandbox) = Sandbox::new(vec![Permission::ReadFiles]) else {
+            return;
+        };
+        let requested = [Permission::ReadFiles];
+        let reacting = r#"(module
+            (memory (export "memory") 1)
+            (data (i32.const 16) "{\"veto\":\"no\"}")
+            (func (export "alloc") (param i32) (result i32) i32.const 1024)
+            (func (export "on_event") (param i32 i32) (result i64)
+                (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 13))))"#;
+        let reaction = sandbox
+            .deliver_event(
+                reacting.as_bytes(),
+                &requested,
+                br#"{"kind":"feature_created"}"#,
+            )
+            .unwrap();
+        assert_eq!(reaction.as_deref(), Some(&br#"{"veto":"no"}"#[..]));
+
+        let silent = r#"(module (func (export "run")))"#;
+        assert!(sandbox
+            .deliver_event(silent.as_bytes(), &requested, b"{}")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_limits_are_metered() {
+        let dir = tempfile::tempdir().unwrap();
+        let stats_path = dir.path().join("stats.json");
+        let Ok(sandbox) = Sandbox::new(vec![Permission::ReadFiles]) else {
+            return;
+        };
+        let sandbox = sandbox
+            .with_limits(ResourceLimits {
+                fuel: 10_000,
+                ..ResourceLimits::default()
+            })
+            .with_meter_at("looper", stats_path.clone());
+        let requested = [Permission::ReadFiles];
+
+        let spin = r#"(module (func (export "run") (loop (br 0))))"#;
+        assert!(sandbox.execute(spin.as_bytes(), &requested).is_err());
+        let quick = r#"(module (func (export "run")))"#;
+        sandbox.execute(quick.as_bytes(), &requested).unwrap();
+
+        let store = StatsStore::load(&stats_path).unwrap();
+        let stats = store.stats("looper").unwrap();
+        assert_eq!(stats.executions, 2);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.fuel_exhausted, 1);
+        assert_eq!(stats.timeouts, 0);
+        assert!(stats.fuel_used >= 10_000);
+    }
 }
 
 mod license_tests {
-    use crate::license::License;
+    use crate::license::{check_tier, License};
+    use crate::{LicenseTier, PluginError, PluginManifest};
     use chrono::{Duration, Utc};
 
+    #[test]
+    fn test_check_tier_gates_paid_plugins() {
+        let manifest = PluginManifest {
+            id: "pro-plugin".to_string(),
+            name: "Pro Plugin".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Needs Pro".to_string(),
+            author: "Vendor".to_string(),
+            license_tier: LicenseTier::Pro,
+            permissions: vec![],
+            events: vec![],
+        };
+
+        assert!(matches!(
+            check_tier(&manifest, LicenseTier::Free),
+            Err(PluginError::TierRequired { .. })
+        ));
+        assert!(check_tier(&manifest, LicenseTier::Pro).is_ok());
+        assert!(check_tier(&manifest, LicenseTier::Enterprise).is_ok());
+        assert_eq!(
+            LicenseTier::from(demiarch_core::commands::license::LicenseTier::Enterprise),
+            LicenseTier::Enterprise
+        );
+    }
+
     #[test]
     fn test_license_serialization() {
         let license = License {
@@ -268,3 +356,188 @@ mod license_tests {
         assert_eq!(license.payload, cloned.payload);
     }
 }
+
+mod permission_tests {
+    use crate::permissions::{
+        GrantStore, PermissionMediator, PermissionPrompt, PermissionRequest, PromptAnswer,
+    };
+    use crate::sandbox::Sandbox;
+    use crate::{Permission, PluginError};
+    use demiarch_core::infrastructure::network;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct CountingPrompt {
+        answer: PromptAnswer,
+        asked: Arc<AtomicUsize>,
+    }
+
+    impl PermissionPrompt for CountingPrompt {
+        fn ask(&self, _request: &PermissionRequest) -> PromptAnswer {
+            self.asked.fetch_add(1, Ordering::SeqCst);
+            self.answer
+        }
+    }
+
+    fn prompt(answer: PromptAnswer) -> (Box<CountingPrompt>, Arc<AtomicUsize>) {
+        let asked = Arc::new(AtomicUsize::new(0));
+        let prompt = Box::new(CountingPrompt {
+            answer,
+            asked: asked.clone(),
+        });
+        (prompt, asked)
+    }
+
+    #[test]
+    fn test_file_access_confined_to_project() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(project.join("notes.txt"), "hi").unwrap();
+        std::fs::write(root.path().join("secret.txt"), "no").unwrap();
+
+        let (prompt, asked) = prompt(PromptAnswer::AllowOnce);
+        let mediator = PermissionMediator::new(
+            "reader",
+            &[Permission::ReadFiles, Permission::WriteFiles],
+            &project,
+        )
+        .unwrap()
+        .with_prompt(prompt);
+
+        assert!(mediator.read_path("notes.txt").is_ok());
+        assert!(mediator.read_path("notes.txt").is_ok());
+        assert_eq!(asked.load(Ordering::SeqCst), 1, "once answers last the run");
+        assert!(matches!(
+            mediator.read_path("../secret.txt"),
+            Err(PluginError::PermissionDenied(_))
+        ));
+        assert!(mediator.write_path("out/new.txt").is_err());
+        assert!(mediator.write_path("new.txt").is_ok());
+        assert!(mediator.write_path("../escape.txt").is_err());
+    }
+
+    #[test]
+    fn test_undeclared_permission_is_denied_without_prompting() {
+        let dir = tempfile::tempdir().unwrap();
+        let (prompt, asked) = prompt(PromptAnswer::AllowAlways);
+        let mediator = PermissionMediator::new("quiet", &[Permission::ReadFiles], dir.path())
+            .unwrap()
+            .with_prompt(prompt);
+
+        assert!(mediator.authorize_url("https://example.com/").is_err());
+        assert!(mediator.authorize_subprocess("git").is_err());
+        assert_eq!(asked.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_network_hosts_and_persisted_grants() {
+        let dir = tempfile::tempdir().unwrap();
+        let grants_path = dir.path().join("grants.json");
+
+        let (prompt, asked) = prompt(PromptAnswer::AllowAlways);
+        let mediator = PermissionMediator::new("fetcher", &[Permission::Network], dir.path())
+            .unwrap()
+            .with_allowed_host("api.example.com")
+            .with_grants(GrantStore::load(&grants_path).unwrap())
+            .with_prompt(prompt);
+
+        assert!(mediator.authorize_url("https://api.example.com/v1").is_ok());
+        assert_eq!(asked.load(Ordering::SeqCst), 0);
+        assert!(mediator.authorize_url("https://cdn.example.org/x").is_ok());
+        assert_eq!(asked.load(Ordering::SeqCst), 1);
+        assert!(mediator.authorize_url("file:///etc/passwd").is_err());
+        assert!(mediator.authorize_url("http://api.example.com/v1").is_err());
+
+        let mut store = GrantStore::load(&grants_path).unwrap();
+        assert!(store.host_allowed("fetcher", "cdn.example.org"));
+        assert!(!store.host_allowed("fetcher", "other.example.org"));
+
+        store.set("fetcher", Permission::Network, false);
+        store.save().unwrap();
+        let (prompt, asked) = prompt(PromptAnswer::AllowOnce);
+        let mediator = PermissionMediator::new("fetcher", &[Permission::Network], dir.path())
+            .unwrap()
+            .with_grants(GrantStore::load(&grants_path).unwrap())
+            .with_prompt(prompt);
+        assert!(mediator.authorize_url("https://new.example.org/").is_err());
+        assert_eq!(
+            asked.load(Ordering::SeqCst),
+            0,
+            "never answers are remembered"
+        );
+
+        let mut store = GrantStore::load(&grants_path).unwrap();
+        assert!(store.revoke("fetcher"));
+        assert!(store.grants("fetcher").is_none());
+    }
+
+    #[test]
+    fn test_http_get_refused_offline() {
+        let dir = tempfile::tempdir().unwrap();
+        let mediator = PermissionMediator::new("fetcher", &[Permission::Network], dir.path())
+            .unwrap()
+            .with_allowed_host("example.com");
+        let Ok(sandbox) = Sandbox::new(vec![Permission::Network]) else {
+            return;
+        };
+        let sandbox = sandbox.with_host(mediator);
+        // Traps unless the host answers HOST_OFFLINE
+        let fetching = r#"(module
+            (import "demiarch" "http_get" (func $get (param i32 i32 i32 i32) (result i64)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "https://example.com/")
+            (func (export "run")
+                (if (i64.ne (call $get (i32.const 0) (i32.const 20) (i32.const 64) (i32.const 64))
+                            (i64.const -5))
+                    (then unreachable))))"#;
+
+        network::set_offline(true);
+        let result = sandbox.execute(fetching.as_bytes(), &[Permission::Network]);
+        network::set_offline(false);
+        result.unwrap();
+    }
+}
+
+mod scaffold_tests {
+    use crate::scaffold::{scaffold, Capability};
+    use crate::PluginManifest;
+
+    #[test]
+    fn test_scaffold_hook_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let capability: Capability = "hook".parse().unwrap();
+        let root = scaffold("title-check", capability, dir.path()).unwrap();
+
+        for file in [
+            "Cargo.toml",
+            "abi/Cargo.toml",
+            "abi/src/lib.rs",
+            "src/lib.rs",
+            "tests/capability.rs",
+            "README.md",
+        ] {
+            let content = std::fs::read_to_string(root.join(file)).unwrap();
+            assert!(!content.contains("{{"), "unfilled placeholder in {}", file);
+        }
+        let cargo = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+        assert!(cargo.contains("name = \"title_check\""));
+
+        let manifest: PluginManifest =
+            serde_json::from_str(&std::fs::read_to_string(root.join("manifest.json")).unwrap())
+                .unwrap();
+        assert_eq!(manifest.id, "title-check");
+        assert_eq!(manifest.events.len(), 1);
+
+        assert!(scaffold("title-check", capability, dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_scaffold_rejects_bad_names_and_capabilities() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["", "Upper", "1st", "trailing-", "a/b"] {
+            assert!(scaffold(name, Capability::Generator, dir.path()).is_err());
+        }
+        assert!("widget".parse::<Capability>().is_err());
+    }
+}
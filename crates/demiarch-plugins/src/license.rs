@@ -1,6 +1,6 @@
 //! Offline license verification using ed25519 signatures
 
-use crate::{PluginError, PluginManifest, PluginResult};
+use crate::{LicenseTier, PluginError, PluginManifest, PluginResult};
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use base64::Engine;
 use chrono::{DateTime, Utc};
@@ -61,6 +61,21 @@ pub fn verify_license(manifest: &PluginManifest, license: &License) -> PluginRes
         .map_err(|e| PluginError::ValidationFailed(format!("License verification failed: {e}")))
 }
 
+/// Check the user's activated tier covers the tier a plugin requires
+///
+/// The active tier comes from `demiarch license status`
+/// (`LicenseManager::active_tier` in demiarch-core).
+pub fn check_tier(manifest: &PluginManifest, active: LicenseTier) -> PluginResult<()> {
+    if active < manifest.license_tier {
+        return Err(PluginError::TierRequired {
+            plugin: manifest.id.clone(),
+            required: manifest.license_tier,
+            active,
+        });
+    }
+    Ok(())
+}
+
 fn resolve_issuer_key() -> PluginResult<VerifyingKey> {
     let key_b64 = env::var("DEMIARCH_LICENSE_ISSUER_KEY").map_err(|_| {
         PluginError::ValidationFailed(
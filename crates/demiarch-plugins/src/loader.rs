@@ -1,8 +1,8 @@
 //! Plugin loading and discovery
 
 use crate::{
-    license::{verify_license, License},
-    PluginError, PluginManifest, PluginResult,
+    license::{check_tier, verify_license, License},
+    LicenseTier, PluginError, PluginManifest, PluginResult,
 };
 use std::{
     fs,
@@ -36,6 +36,26 @@ pub fn load_manifest(path: &Path) -> PluginResult<PluginManifest> {
     Ok(manifest)
 }
 
+/// Load a manifest and check the user's activated license tier allows it
+pub fn load_manifest_for_tier(path: &Path, active: LicenseTier) -> PluginResult<PluginManifest> {
+    let manifest = load_manifest(path)?;
+    check_tier(&manifest, active)?;
+    Ok(manifest)
+}
+
+/// Manifests of installed plugins: `<plugin dir>/<plugin>/manifest.json`
+pub fn discover_manifests() -> PluginResult<Vec<PathBuf>> {
+    let base_dir = plugin_base_dir()?;
+    let mut manifests: Vec<PathBuf> = fs::read_dir(&base_dir)
+        .map_err(PluginError::IoError)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join("manifest.json"))
+        .filter(|path| path.is_file())
+        .collect();
+    manifests.sort();
+    Ok(manifests)
+}
+
 /// Load WASM module bytes with a size cap
 pub fn load_wasm_bytes(path: &Path, max_bytes: usize) -> PluginResult<Vec<u8>> {
     let canonical_path = resolve_plugin_path(path)?;
@@ -102,7 +122,7 @@ fn resolve_plugin_path(path: &Path) -> PluginResult<PathBuf> {
     Ok(canonical_target)
 }
 
-fn plugin_base_dir() -> PluginResult<PathBuf> {
+pub(crate) fn plugin_base_dir() -> PluginResult<PathBuf> {
     let base = if let Ok(path) = std::env::var("DEMIARCH_PLUGIN_DIR") {
         PathBuf::from(path)
     } else if let Some(home) = dirs::home_dir() {
@@ -0,0 +1,144 @@
+//! Resource metering for plugin invocations
+//!
+//! Every sandboxed invocation reports the fuel it burned, how long it ran
+//! and how it ended. Totals per plugin are kept in `stats.json` in the plugin
+//! directory and shown by `demiarch plugins stats`.
+
+use crate::loader::plugin_base_dir;
+use crate::{PluginError, PluginResult};
+use chrono::{DateTime, Utc};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// How an invocation ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Completed,
+    Failed,
+    /// Ran out of fuel
+    FuelExhausted,
+    /// Hit the wall-clock limit
+    TimedOut,
+}
+
+/// Resources one invocation used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    pub fuel_used: u64,
+    pub duration: Duration,
+    pub outcome: Outcome,
+}
+
+/// Totals for one plugin
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PluginStats {
+    pub executions: u64,
+    /// Invocations that did not complete, including limit hits
+    pub failures: u64,
+    pub fuel_exhausted: u64,
+    pub timeouts: u64,
+    pub fuel_used: u64,
+    pub total_duration_ms: u64,
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+impl PluginStats {
+    pub fn record(&mut self, usage: &Usage) {
+        self.executions += 1;
+        if usage.outcome != Outcome::Completed {
+            self.failures += 1;
+        }
+        match usage.outcome {
+            Outcome::FuelExhausted => self.fuel_exhausted += 1,
+            Outcome::TimedOut => self.timeouts += 1,
+            Outcome::Completed | Outcome::Failed => {}
+        }
+        self.fuel_used = self.fuel_used.saturating_add(usage.fuel_used);
+        self.total_duration_ms = self
+            .total_duration_ms
+            .saturating_add(usage.duration.as_millis() as u64);
+        self.last_run = Some(Utc::now());
+    }
+
+    pub fn average_fuel(&self) -> u64 {
+        self.fuel_used.checked_div(self.executions).unwrap_or(0)
+    }
+
+    pub fn average_duration_ms(&self) -> u64 {
+        self.total_duration_ms
+            .checked_div(self.executions)
+            .unwrap_or(0)
+    }
+}
+
+/// Per-plugin totals, saved as JSON
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct StatsStore {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    #[serde(default)]
+    plugins: BTreeMap<String, PluginStats>,
+}
+
+impl StatsStore {
+    /// `stats.json` in the plugin directory
+    pub fn default_path() -> PluginResult<PathBuf> {
+        Ok(plugin_base_dir()?.join("stats.json"))
+    }
+
+    /// Load the store at the default path
+    pub fn open() -> PluginResult<Self> {
+        Self::load(&Self::default_path()?)
+    }
+
+    /// Load a store, starting empty if the file does not exist yet
+    pub fn load(path: &Path) -> PluginResult<Self> {
+        let mut store = match fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| {
+                PluginError::ValidationFailed(format!("Invalid stats file {:?}: {e}", path))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(PluginError::IoError(e)),
+        };
+        store.path = Some(path.to_path_buf());
+        Ok(store)
+    }
+
+    /// Write the store back to where it was loaded from
+    pub fn save(&self) -> PluginResult<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            PluginError::ValidationFailed(format!("Failed to serialize stats: {e}"))
+        })?;
+        fs::write(path, json).map_err(PluginError::IoError)
+    }
+
+    pub fn stats(&self, plugin_id: &str) -> Option<&PluginStats> {
+        self.plugins.get(plugin_id)
+    }
+
+    pub fn plugins(&self) -> impl Iterator<Item = (&String, &PluginStats)> {
+        self.plugins.iter()
+    }
+
+    pub fn record(&mut self, plugin_id: &str, usage: &Usage) {
+        self.plugins
+            .entry(plugin_id.to_string())
+            .or_default()
+            .record(usage);
+    }
+}
+
+/// Add one invocation to the stats file at `path`
+pub fn record_at(path: &Path, plugin_id: &str, usage: &Usage) -> PluginResult<()> {
+    let mut store = StatsStore::load(path)?;
+    store.record(plugin_id, usage);
+    store.save()
+}
@@ -0,0 +1,406 @@
+//! Runtime permission mediation for plugin host functions
+//!
+//! A manifest only declares which permissions a plugin may ask for. Every
+//! host call is checked again here at the moment it happens:
+//! - ReadFiles/WriteFiles are confined to the project directory
+//! - Network is limited to hosts the user allow-listed
+//! - Subprocess goes through core's [`SandboxedRunner`] and its program allowlist
+//!
+//! The first use of a permission asks the user through a [`PermissionPrompt`].
+//! "Always" and "never" answers are persisted per plugin in a [`GrantStore`]
+//! (`~/.demiarch/plugins/grants.json`); "once" answers last for the run.
+
+use crate::loader::plugin_base_dir;
+use crate::{Permission, PluginError, PluginResult};
+use demiarch_core::infrastructure::sandbox::SandboxedRunner;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+/// Answer to a first-use permission prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptAnswer {
+    AllowOnce,
+    AllowAlways,
+    Deny,
+    DenyAlways,
+}
+
+/// What a plugin is trying to do, shown to the user
+#[derive(Debug, Clone)]
+pub struct PermissionRequest {
+    pub plugin_id: String,
+    pub permission: Permission,
+    /// Path, host or command the call targets
+    pub detail: String,
+}
+
+/// Asks the user whether a plugin may use a permission
+pub trait PermissionPrompt: Send + Sync {
+    fn ask(&self, request: &PermissionRequest) -> PromptAnswer;
+}
+
+/// Prompt for non-interactive runs: anything not already granted is denied
+pub struct DenyPrompt;
+
+impl PermissionPrompt for DenyPrompt {
+    fn ask(&self, _request: &PermissionRequest) -> PromptAnswer {
+        PromptAnswer::Deny
+    }
+}
+
+/// Persisted decisions for one plugin
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PluginGrants {
+    #[serde(default)]
+    pub allowed: Vec<Permission>,
+    #[serde(default)]
+    pub denied: Vec<Permission>,
+    /// Hosts the user allowed for Network
+    #[serde(default)]
+    pub hosts: Vec<String>,
+}
+
+/// Per-plugin permission decisions, saved as JSON
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct GrantStore {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    #[serde(default)]
+    plugins: BTreeMap<String, PluginGrants>,
+}
+
+impl GrantStore {
+    /// `grants.json` in the plugin directory
+    pub fn default_path() -> PluginResult<PathBuf> {
+        Ok(plugin_base_dir()?.join("grants.json"))
+    }
+
+    /// Load the store at the default path
+    pub fn open() -> PluginResult<Self> {
+        Self::load(&Self::default_path()?)
+    }
+
+    /// Load a store, starting empty if the file does not exist yet
+    pub fn load(path: &Path) -> PluginResult<Self> {
+        let mut store = match fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| {
+                PluginError::ValidationFailed(format!("Invalid grants file {:?}: {e}", path))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(PluginError::IoError(e)),
+        };
+        store.path = Some(path.to_path_buf());
+        Ok(store)
+    }
+
+    /// Store that is never written to disk
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Write the store back to where it was loaded from
+    pub fn save(&self) -> PluginResult<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            PluginError::ValidationFailed(format!("Failed to serialize grants: {e}"))
+        })?;
+        fs::write(path, json).map_err(PluginError::IoError)
+    }
+
+    pub fn grants(&self, plugin_id: &str) -> Option<&PluginGrants> {
+        self.plugins.get(plugin_id)
+    }
+
+    pub fn plugins(&self) -> impl Iterator<Item = (&String, &PluginGrants)> {
+        self.plugins.iter()
+    }
+
+    /// Remembered decision: Some(true) allowed, Some(false) denied
+    pub fn decision(&self, plugin_id: &str, permission: Permission) -> Option<bool> {
+        let grants = self.plugins.get(plugin_id)?;
+        if grants.denied.contains(&permission) {
+            Some(false)
+        } else if grants.allowed.contains(&permission) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    /// Remember a decision, replacing any earlier one
+    pub fn set(&mut self, plugin_id: &str, permission: Permission, allowed: bool) {
+        let grants = self.plugins.entry(plugin_id.to_string()).or_default();
+        grants.allowed.retain(|p| *p != permission);
+        grants.denied.retain(|p| *p != permission);
+        if allowed {
+            grants.allowed.push(permission);
+        } else {
+            grants.denied.push(permission);
+        }
+    }
+
+    pub fn host_allowed(&self, plugin_id: &str, host: &str) -> bool {
+        self.plugins
+            .get(plugin_id)
+            .is_some_and(|g| g.hosts.iter().any(|h| h == host))
+    }
+
+    pub fn allow_host(&mut self, plugin_id: &str, host: &str) {
+        let grants = self.plugins.entry(plugin_id.to_string()).or_default();
+        if !grants.hosts.iter().any(|h| h == host) {
+            grants.hosts.push(host.to_string());
+        }
+    }
+
+    /// Forget every decision for a plugin, returning whether there were any
+    pub fn revoke(&mut self, plugin_id: &str) -> bool {
+        self.plugins.remove(plugin_id).is_some()
+    }
+}
+
+/// Checks each host call a plugin makes against its grants
+pub struct PermissionMediator {
+    plugin_id: String,
+    requested: Vec<Permission>,
+    project_dir: PathBuf,
+    allowed_hosts: Vec<String>,
+    runner: Option<SandboxedRunner>,
+    grants: Mutex<GrantStore>,
+    prompt: Box<dyn PermissionPrompt>,
+    /// "Once" answers, keyed by permission (and host for Network)
+    session: Mutex<HashMap<String, bool>>,
+}
+
+impl PermissionMediator {
+    /// Mediate calls for a plugin confined to `project_dir`
+    ///
+    /// Starts with an in-memory grant store and a prompt that denies.
+    pub fn new(
+        plugin_id: &str,
+        requested: &[Permission],
+        project_dir: &Path,
+    ) -> PluginResult<Self> {
+        Ok(Self {
+            plugin_id: plugin_id.to_string(),
+            requested: requested.to_vec(),
+            project_dir: project_dir.canonicalize().map_err(PluginError::IoError)?,
+            allowed_hosts: Vec::new(),
+            runner: None,
+            grants: Mutex::new(GrantStore::in_memory()),
+            prompt: Box::new(DenyPrompt),
+            session: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Use and update this grant store
+    pub fn with_grants(mut self, grants: GrantStore) -> Self {
+        self.grants = Mutex::new(grants);
+        self
+    }
+
+    /// Ask the user through this prompt on first use
+    pub fn with_prompt(mut self, prompt: Box<dyn PermissionPrompt>) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
+    /// Allow a network host without prompting
+    pub fn with_allowed_host(mut self, host: impl Into<String>) -> Self {
+        self.allowed_hosts.push(host.into());
+        self
+    }
+
+    /// Run subprocesses through this runner (its allowlist applies)
+    pub fn with_runner(mut self, runner: SandboxedRunner) -> Self {
+        self.runner = Some(runner);
+        self
+    }
+
+    pub fn plugin_id(&self) -> &str {
+        &self.plugin_id
+    }
+
+    pub fn project_dir(&self) -> &Path {
+        &self.project_dir
+    }
+
+    /// Whether the manifest declared a permission
+    pub fn requested(&self, permission: Permission) -> bool {
+        self.requested.contains(&permission)
+    }
+
+    /// Check a permission, prompting the user on first use
+    pub fn authorize(&self, permission: Permission, detail: &str) -> PluginResult<()> {
+        self.authorize_keyed(permission, format!("{:?}", permission), detail, None)
+    }
+
+    fn authorize_keyed(
+        &self,
+        permission: Permission,
+        session_key: String,
+        detail: &str,
+        host: Option<&str>,
+    ) -> PluginResult<()> {
+        let denied = || {
+            PluginError::PermissionDenied(format!(
+                "{} may not use {:?} ({})",
+                self.plugin_id, permission, detail
+            ))
+        };
+        if !self.requested(permission) {
+            return Err(denied());
+        }
+        if let Some(allowed) = self.lock_session().get(&session_key) {
+            return if *allowed { Ok(()) } else { Err(denied()) };
+        }
+        {
+            let grants = self.lock_grants();
+            match grants.decision(&self.plugin_id, permission) {
+                Some(false) => return Err(denied()),
+                Some(true) if host.is_none() => return Ok(()),
+                _ => {}
+            }
+            if host.is_some_and(|h| grants.host_allowed(&self.plugin_id, h)) {
+                return Ok(());
+            }
+        }
+
+        let answer = self.prompt.ask(&PermissionRequest {
+            plugin_id: self.plugin_id.clone(),
+            permission,
+            detail: detail.to_string(),
+        });
+        let allowed = matches!(answer, PromptAnswer::AllowOnce | PromptAnswer::AllowAlways);
+        if matches!(answer, PromptAnswer::AllowAlways | PromptAnswer::DenyAlways) {
+            let mut grants = self.lock_grants();
+            match host {
+                Some(host) if allowed => grants.allow_host(&self.plugin_id, host),
+                _ => grants.set(&self.plugin_id, permission, allowed),
+            }
+            if let Err(e) = grants.save() {
+                tracing::warn!(error = %e, "Failed to save plugin grants");
+            }
+        }
+        tracing::info!(
+            plugin = %self.plugin_id,
+            permission = ?permission,
+            detail,
+            allowed,
+            "Plugin permission decision"
+        );
+        self.lock_session().insert(session_key, allowed);
+        if allowed {
+            Ok(())
+        } else {
+            Err(denied())
+        }
+    }
+
+    /// Resolve a path the plugin wants to read, confined to the project
+    pub fn read_path(&self, path: &str) -> PluginResult<PathBuf> {
+        let resolved = self
+            .project_dir
+            .join(path)
+            .canonicalize()
+            .map_err(PluginError::IoError)?;
+        self.confine(path, &resolved)?;
+        self.authorize(Permission::ReadFiles, &resolved.display().to_string())?;
+        Ok(resolved)
+    }
+
+    /// Resolve a path the plugin wants to write, confined to the project
+    ///
+    /// The parent directory must exist and the target may not be a symlink.
+    pub fn write_path(&self, path: &str) -> PluginResult<PathBuf> {
+        let candidate = self.project_dir.join(path);
+        let file_name = match candidate.components().next_back() {
+            Some(Component::Normal(name)) => name.to_owned(),
+            _ => {
+                return Err(PluginError::PermissionDenied(format!(
+                    "{} is not a file path",
+                    path
+                )))
+            }
+        };
+        let parent = candidate
+            .parent()
+            .ok_or_else(|| PluginError::PermissionDenied(format!("{} has no parent", path)))?
+            .canonicalize()
+            .map_err(PluginError::IoError)?;
+        let resolved = parent.join(file_name);
+        self.confine(path, &resolved)?;
+        if fs::symlink_metadata(&resolved).is_ok_and(|m| m.file_type().is_symlink()) {
+            return Err(PluginError::PermissionDenied(format!(
+                "{} is a symlink",
+                path
+            )));
+        }
+        self.authorize(Permission::WriteFiles, &resolved.display().to_string())?;
+        Ok(resolved)
+    }
+
+    /// Check a URL's host is allow-listed (or approved by the user)
+    pub fn authorize_url(&self, url: &str) -> PluginResult<reqwest::Url> {
+        let parsed = reqwest::Url::parse(url)
+            .map_err(|e| PluginError::PermissionDenied(format!("Invalid URL {}: {e}", url)))?;
+        if parsed.scheme() != "https" {
+            return Err(PluginError::PermissionDenied(format!(
+                "Only https URLs are allowed, got {}",
+                parsed.scheme()
+            )));
+        }
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| PluginError::PermissionDenied(format!("{} has no host", url)))?
+            .to_ascii_lowercase();
+        if self.requested(Permission::Network) && self.allowed_hosts.iter().any(|h| *h == host) {
+            return Ok(parsed);
+        }
+        self.authorize_keyed(
+            Permission::Network,
+            format!("Network:{}", host),
+            &host,
+            Some(&host),
+        )?;
+        Ok(parsed)
+    }
+
+    /// Check a subprocess may run and return the runner to run it with
+    pub fn authorize_subprocess(&self, program: &str) -> PluginResult<&SandboxedRunner> {
+        let runner = self
+            .runner
+            .as_ref()
+            .filter(|r| r.is_allowed(program))
+            .ok_or_else(|| {
+                PluginError::PermissionDenied(format!(
+                    "{} is not in the plugin runner's allowlist",
+                    program
+                ))
+            })?;
+        self.authorize(Permission::Subprocess, program)?;
+        Ok(runner)
+    }
+
+    fn confine(&self, requested: &str, resolved: &Path) -> PluginResult<()> {
+        if resolved.starts_with(&self.project_dir) {
+            Ok(())
+        } else {
+            Err(PluginError::PermissionDenied(format!(
+                "{} is outside the project directory",
+                requested
+            )))
+        }
+    }
+
+    fn lock_grants(&self) -> std::sync::MutexGuard<'_, GrantStore> {
+        self.grants.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_session(&self) -> std::sync::MutexGuard<'_, HashMap<String, bool>> {
+        self.session.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -1,22 +1,107 @@
 //! WASM sandbox execution via wasmtime
+//!
+//! Modules may import host functions from the `demiarch` namespace, one per
+//! permission. Arguments are (pointer, length) pairs into the module's
+//! exported `memory`; results are written to an output buffer and the call
+//! returns the number of bytes written, or a negative `HOST_*` code.
+//!
+//! | Import        | Permission | Arguments                            |
+//! |---------------|------------|--------------------------------------|
+//! | `read_file`   | ReadFiles  | path, out buffer                     |
+//! | `write_file`  | WriteFiles | path, data                           |
+//! | `http_get`    | Network    | url, out buffer                      |
+//! | `run_program` | Subprocess | JSON `{program, args, stdin}`, out buffer (JSON output) |
+//!
+//! The subprocess import is not called `run` so that it cannot collide with
+//! the module's own exported `run` entry point at link time.
+//!
+//! Every call is checked by the [`PermissionMediator`] at the time it is made.
 
+use crate::metering::{self, Outcome, StatsStore, Usage};
+use crate::permissions::PermissionMediator;
 use crate::{Permission, PluginError, PluginResult};
+use demiarch_core::config::PluginTierLimits;
+use demiarch_core::infrastructure::network;
 use std::{
     collections::HashSet,
+    future::Future,
+    path::PathBuf,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
         Arc,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use wasmtime::{
-    Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder, WasmBacktraceDetails,
+    Caller, Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder, WasmBacktraceDetails,
 };
 
+/// Namespace host functions are imported from
+pub const HOST_MODULE: &str = "demiarch";
+
+/// The permission was not granted, or the target is outside what it allows
+pub const HOST_DENIED: i64 = -1;
+/// Arguments could not be read from guest memory or were malformed
+pub const HOST_INVALID: i64 = -2;
+/// The operation itself failed (I/O, HTTP or process error)
+pub const HOST_FAILED: i64 = -3;
+/// The result does not fit in the output buffer
+pub const HOST_TOO_LARGE: i64 = -4;
+/// Network access is off (`--offline` or `network.offline`)
+pub const HOST_OFFLINE: i64 = -5;
+
+/// Largest payload a single host call reads or returns
+const MAX_HOST_IO_BYTES: usize = 4 * 1024 * 1024;
+
+/// Timeout for a plugin's HTTP request
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Host function imports and the permission each one needs
+const HOST_FUNCTIONS: &[(&str, Permission)] = &[
+    ("read_file", Permission::ReadFiles),
+    ("write_file", Permission::WriteFiles),
+    ("http_get", Permission::Network),
+    ("run_program", Permission::Subprocess),
+];
+
+/// CPU, memory and time available to one invocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub fuel: u64,
+    pub memory_bytes: usize,
+    pub timeout: Duration,
+}
+
+impl Default for ResourceLimits {
+    /// The Free tier's limits
+    fn default() -> Self {
+        Self {
+            fuel: 10_000_000,
+            memory_bytes: 16 * 1024 * 1024,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl From<&PluginTierLimits> for ResourceLimits {
+    fn from(limits: &PluginTierLimits) -> Self {
+        Self {
+            fuel: limits.fuel,
+            memory_bytes: usize::try_from(limits.memory_mb.saturating_mul(1024 * 1024))
+                .unwrap_or(usize::MAX),
+            timeout: Duration::from_secs(limits.timeout_secs),
+        }
+    }
+}
+
 pub struct Sandbox {
     engine: Engine,
     allowed_permissions: Vec<Permission>,
+    host: Option<Arc<PermissionMediator>>,
+    /// Plugin ID and stats file every invocation is recorded under
+    meter: Option<(String, PathBuf)>,
     fuel_limit: u64,
     memory_limit_bytes: usize,
     table_elements_limit: usize,
@@ -40,20 +125,127 @@ impl Sandbox {
             PluginError::WasmError(format!("Failed to initialize wasmtime engine: {e}"))
         })?;
 
+        let limits = ResourceLimits::default();
         Ok(Self {
             engine,
             allowed_permissions,
-            fuel_limit: 10_000_000,
-            memory_limit_bytes: 16 * 1024 * 1024,
+            host: None,
+            meter: None,
+            fuel_limit: limits.fuel,
+            memory_limit_bytes: limits.memory_bytes,
             table_elements_limit: 1_024,
             instance_limit: 16,
-            execution_timeout: Duration::from_secs(5),
+            execution_timeout: limits.timeout,
         })
     }
 
+    /// Apply per-invocation limits, usually those of the active license tier
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.fuel_limit = limits.fuel;
+        self.memory_limit_bytes = limits.memory_bytes;
+        self.execution_timeout = limits.timeout;
+        self
+    }
+
+    /// Record every invocation in the plugin stats file under `plugin_id`
+    pub fn with_meter(self, plugin_id: &str) -> PluginResult<Self> {
+        Ok(self.with_meter_at(plugin_id, StatsStore::default_path()?))
+    }
+
+    /// Record every invocation in a specific stats file
+    pub fn with_meter_at(mut self, plugin_id: &str, path: PathBuf) -> Self {
+        self.meter = Some((plugin_id.to_string(), path));
+        self
+    }
+
+    /// Expose host functions, mediated per call by `mediator`
+    ///
+    /// Without a mediator no host functions are exposed at all.
+    pub fn with_host(mut self, mediator: PermissionMediator) -> Self {
+        self.host = Some(Arc::new(mediator));
+        self
+    }
+
     /// Execute a WASM module in a constrained sandbox.
-    /// No host functions are exposed; capabilities must be explicitly granted.
+    ///
+    /// The module is instantiated (running its start function), then its
+    /// exported `run` function is called if it has one. Only host functions
+    /// for requested permissions may be imported.
     pub fn execute(&self, wasm: &[u8], requested_permissions: &[Permission]) -> PluginResult<()> {
+        self.with_instance(
+            wasm,
+            requested_permissions,
+            |store, instance| match instance.get_typed_func::<(), ()>(&mut *store, "run") {
+                Ok(run) => run
+                    .call(&mut *store, ())
+                    .map_err(|e| PluginError::WasmError(format!("Execution failed: {e}"))),
+                Err(_) => Ok(()),
+            },
+        )
+    }
+
+    /// Deliver a serialized event to the module's `on_event` export
+    ///
+    /// The host calls `alloc(len) -> ptr` to get space for the event, then
+    /// `on_event(ptr, len) -> i64`. A positive result packs the location of
+    /// the reaction JSON as `(ptr << 32) | len`; zero means no reaction.
+    /// Modules without `on_event` never react.
+    pub fn deliver_event(
+        &self,
+        wasm: &[u8],
+        requested_permissions: &[Permission],
+        event: &[u8],
+    ) -> PluginResult<Option<Vec<u8>>> {
+        let exec_error =
+            |e: wasmtime::Error| PluginError::WasmError(format!("Event delivery failed: {e}"));
+        self.with_instance(wasm, requested_permissions, |store, instance| {
+            let Ok(on_event) = instance.get_typed_func::<(i32, i32), i64>(&mut *store, "on_event")
+            else {
+                return Ok(None);
+            };
+            let alloc = instance
+                .get_typed_func::<i32, i32>(&mut *store, "alloc")
+                .map_err(|_| {
+                    PluginError::ValidationFailed("on_event requires an alloc export".to_string())
+                })?;
+            let memory = instance.get_memory(&mut *store, "memory").ok_or_else(|| {
+                PluginError::ValidationFailed("on_event requires an exported memory".to_string())
+            })?;
+            let len = i32::try_from(event.len())
+                .ok()
+                .filter(|len| (*len as usize) <= MAX_HOST_IO_BYTES)
+                .ok_or_else(|| PluginError::ValidationFailed("Event too large".to_string()))?;
+
+            let ptr = alloc.call(&mut *store, len).map_err(exec_error)?;
+            memory
+                .write(&mut *store, ptr as u32 as usize, event)
+                .map_err(|e| PluginError::WasmError(format!("Event delivery failed: {e}")))?;
+            let packed = on_event.call(&mut *store, (ptr, len)).map_err(exec_error)?;
+            if packed <= 0 {
+                return Ok(None);
+            }
+
+            let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+            if out_len > MAX_HOST_IO_BYTES {
+                return Err(PluginError::ValidationFailed(
+                    "Reaction too large".to_string(),
+                ));
+            }
+            let mut reaction = vec![0; out_len];
+            memory
+                .read(&*store, out_ptr, &mut reaction)
+                .map_err(|e| PluginError::WasmError(format!("Invalid reaction pointer: {e}")))?;
+            Ok(Some(reaction))
+        })
+    }
+
+    /// Validate, instantiate and run `call` against a module within the limits
+    fn with_instance<R>(
+        &self,
+        wasm: &[u8],
+        requested_permissions: &[Permission],
+        call: impl FnOnce(&mut Store<SandboxState>, &Instance) -> PluginResult<R>,
+    ) -> PluginResult<R> {
         let unique_permissions = self.assert_permissions(requested_permissions)?;
 
         if self.fuel_limit == 0 {
@@ -61,6 +253,11 @@ impl Sandbox {
                 "Fuel limit must be greater than zero".to_string(),
             ));
         }
+        if self.memory_limit_bytes == 0 || self.execution_timeout.is_zero() {
+            return Err(PluginError::ValidationFailed(
+                "Memory and time limits must be greater than zero".to_string(),
+            ));
+        }
 
         Module::validate(&self.engine, wasm)
             .map_err(|e| PluginError::WasmError(format!("Module validation failed: {e}")))?;
@@ -68,22 +265,30 @@ impl Sandbox {
         let module = Module::new(&self.engine, wasm)
             .map_err(|e| PluginError::WasmError(format!("Invalid module: {e}")))?;
 
-        if module.imports().next().is_some() {
-            return Err(PluginError::ValidationFailed(
-                "Imports are not allowed unless explicitly exposed via permitted host functions"
-                    .to_string(),
-            ));
+        for import in module.imports() {
+            let permitted = self.host.is_some()
+                && import.module() == HOST_MODULE
+                && HOST_FUNCTIONS.iter().any(|(name, permission)| {
+                    *name == import.name() && unique_permissions.contains(permission)
+                });
+            if !permitted {
+                return Err(PluginError::ValidationFailed(
+                    "Imports are not allowed unless explicitly exposed via permitted host functions"
+                        .to_string(),
+                ));
+            }
         }
 
         let mut store = Store::new(
             &self.engine,
-            SandboxLimits {
+            SandboxState {
                 limits: StoreLimitsBuilder::new()
                     .memory_size(self.memory_limit_bytes)
                     .table_elements(self.table_elements_limit)
                     .instances(self.instance_limit)
                     .trap_on_grow_failure(true)
                     .build(),
+                host: self.host.clone(),
             },
         );
 
@@ -96,31 +301,58 @@ impl Sandbox {
             .set_fuel(self.fuel_limit)
             .map_err(|e| PluginError::WasmError(format!("Failed to add fuel: {e}")))?;
 
-        let deadline_triggered = Arc::new(AtomicBool::new(false));
-        let deadline_flag = deadline_triggered.clone();
+        // The watchdog bumps the epoch unless told the run finished first
+        let (finished, finished_rx) = mpsc::channel::<()>();
         let engine = self.engine.clone();
         let timeout = self.execution_timeout;
 
         let watchdog = thread::spawn(move || {
-            thread::sleep(timeout);
-            if !deadline_flag.load(Ordering::Relaxed) {
+            let fired = matches!(
+                finished_rx.recv_timeout(timeout),
+                Err(RecvTimeoutError::Timeout)
+            );
+            if fired {
                 engine.increment_epoch();
             }
+            fired
         });
+        let started = Instant::now();
 
-        // No imports -> empty host environment by default
-        let instantiation_result = Instance::new(&mut store, &module, &[]);
+        let mut linker = Linker::new(&self.engine);
+        if self.host.is_some() {
+            define_host_functions(&mut linker)?;
+        }
 
-        deadline_triggered.store(true, Ordering::Relaxed);
-        let _ = watchdog.join();
+        let result = linker
+            .instantiate(&mut store, &module)
+            .map_err(|e| PluginError::WasmError(format!("Instantiation failed: {e}")))
+            .and_then(|instance| call(&mut store, &instance));
 
-        instantiation_result
-            .map_err(|e| PluginError::WasmError(format!("Instantiation failed: {e}")))?;
+        let _ = finished.send(());
+        let timed_out = watchdog.join().unwrap_or(false);
+
+        let remaining_fuel = store.get_fuel().unwrap_or(0);
+        let outcome = match &result {
+            Ok(_) => Outcome::Completed,
+            Err(_) if timed_out => Outcome::TimedOut,
+            Err(_) if remaining_fuel == 0 => Outcome::FuelExhausted,
+            Err(_) => Outcome::Failed,
+        };
+        self.record_usage(&Usage {
+            fuel_used: self.fuel_limit.saturating_sub(remaining_fuel),
+            duration: started.elapsed(),
+            outcome,
+        });
 
-        // Ensure requested permissions are logged as used for auditing even if empty host
-        let _ = unique_permissions;
+        result
+    }
 
-        Ok(())
+    fn record_usage(&self, usage: &Usage) {
+        if let Some((plugin_id, path)) = &self.meter {
+            if let Err(e) = metering::record_at(path, plugin_id, usage) {
+                tracing::warn!(plugin = %plugin_id, error = %e, "Failed to record plugin usage");
+            }
+        }
     }
 
     pub fn allows(&self, permission: Permission) -> bool {
@@ -152,6 +384,255 @@ impl Sandbox {
     }
 }
 
-struct SandboxLimits {
+struct SandboxState {
     limits: StoreLimits,
+    host: Option<Arc<PermissionMediator>>,
+}
+
+fn define_host_functions(linker: &mut Linker<SandboxState>) -> PluginResult<()> {
+    let link_error = |e: wasmtime::Error| {
+        PluginError::WasmError(format!("Failed to define host functions: {e}"))
+    };
+
+    linker
+        .func_wrap(
+            HOST_MODULE,
+            "read_file",
+            |mut caller: Caller<'_, SandboxState>,
+             path_ptr: i32,
+             path_len: i32,
+             out_ptr: i32,
+             out_cap: i32|
+             -> i64 {
+                let Some((memory, mediator)) = host_parts(&mut caller) else {
+                    return HOST_INVALID;
+                };
+                let Some(path) = read_string(&memory, &caller, path_ptr, path_len) else {
+                    return HOST_INVALID;
+                };
+                let data = match mediator.read_path(&path) {
+                    Ok(resolved) => match std::fs::read(resolved) {
+                        Ok(data) => data,
+                        Err(_) => return HOST_FAILED,
+                    },
+                    Err(e) => return denied(&e),
+                };
+                write_output(&memory, &mut caller, out_ptr, out_cap, &data)
+            },
+        )
+        .map_err(link_error)?;
+
+    linker
+        .func_wrap(
+            HOST_MODULE,
+            "write_file",
+            |mut caller: Caller<'_, SandboxState>,
+             path_ptr: i32,
+             path_len: i32,
+             data_ptr: i32,
+             data_len: i32|
+             -> i64 {
+                let Some((memory, mediator)) = host_parts(&mut caller) else {
+                    return HOST_INVALID;
+                };
+                let Some(path) = read_string(&memory, &caller, path_ptr, path_len) else {
+                    return HOST_INVALID;
+                };
+                let Some(data) = read_bytes(&memory, &caller, data_ptr, data_len) else {
+                    return HOST_INVALID;
+                };
+                match mediator.write_path(&path) {
+                    Ok(resolved) => match std::fs::write(resolved, &data) {
+                        Ok(()) => data.len() as i64,
+                        Err(_) => HOST_FAILED,
+                    },
+                    Err(e) => denied(&e),
+                }
+            },
+        )
+        .map_err(link_error)?;
+
+    linker
+        .func_wrap(
+            HOST_MODULE,
+            "http_get",
+            |mut caller: Caller<'_, SandboxState>,
+             url_ptr: i32,
+             url_len: i32,
+             out_ptr: i32,
+             out_cap: i32|
+             -> i64 {
+                let Some((memory, mediator)) = host_parts(&mut caller) else {
+                    return HOST_INVALID;
+                };
+                let Some(url) = read_string(&memory, &caller, url_ptr, url_len) else {
+                    return HOST_INVALID;
+                };
+                if let Err(e) = network::ensure_online("Plugin HTTP request") {
+                    tracing::warn!(error = %e, "Plugin host call rejected");
+                    return HOST_OFFLINE;
+                }
+                let url = match mediator.authorize_url(&url) {
+                    Ok(url) => url,
+                    Err(e) => return denied(&e),
+                };
+                let body = block_on(async move {
+                    let client = reqwest::Client::builder()
+                        .timeout(HTTP_TIMEOUT)
+                        .redirect(reqwest::redirect::Policy::none())
+                        .build()
+                        .ok()?;
+                    let response = client.get(url).send().await.ok()?;
+                    response.bytes().await.ok()
+                })
+                .flatten();
+                match body {
+                    Some(body) => write_output(&memory, &mut caller, out_ptr, out_cap, &body),
+                    None => HOST_FAILED,
+                }
+            },
+        )
+        .map_err(link_error)?;
+
+    linker
+        .func_wrap(
+            HOST_MODULE,
+            "run_program",
+            |mut caller: Caller<'_, SandboxState>,
+             cmd_ptr: i32,
+             cmd_len: i32,
+             out_ptr: i32,
+             out_cap: i32|
+             -> i64 {
+                let Some((memory, mediator)) = host_parts(&mut caller) else {
+                    return HOST_INVALID;
+                };
+                let Some(request) = read_bytes(&memory, &caller, cmd_ptr, cmd_len)
+                    .and_then(|bytes| serde_json::from_slice::<RunRequest>(&bytes).ok())
+                else {
+                    return HOST_INVALID;
+                };
+                let runner = match mediator.authorize_subprocess(&request.program) {
+                    Ok(runner) => runner.clone(),
+                    Err(e) => return denied(&e),
+                };
+                let output = block_on(async move {
+                    runner
+                        .run(&request.program, &request.args, request.stdin.as_deref())
+                        .await
+                        .ok()
+                })
+                .flatten();
+                let Some(output) = output else {
+                    return HOST_FAILED;
+                };
+                let json = serde_json::json!({
+                    "exit_code": output.exit_code,
+                    "stdout": output.stdout,
+                    "stderr": output.stderr,
+                    "timed_out": output.timed_out,
+                });
+                write_output(
+                    &memory,
+                    &mut caller,
+                    out_ptr,
+                    out_cap,
+                    json.to_string().as_bytes(),
+                )
+            },
+        )
+        .map_err(link_error)?;
+
+    Ok(())
+}
+
+/// Arguments of the `run_program` host function
+#[derive(serde::Deserialize)]
+struct RunRequest {
+    program: String,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    stdin: Option<String>,
+}
+
+fn host_parts(caller: &mut Caller<'_, SandboxState>) -> Option<(Memory, Arc<PermissionMediator>)> {
+    let mediator = caller.data().host.clone()?;
+    let memory = caller.get_export("memory")?.into_memory()?;
+    Some((memory, mediator))
+}
+
+fn read_bytes(
+    memory: &Memory,
+    caller: &Caller<'_, SandboxState>,
+    ptr: i32,
+    len: i32,
+) -> Option<Vec<u8>> {
+    let (ptr, len) = (usize::try_from(ptr).ok()?, usize::try_from(len).ok()?);
+    if len > MAX_HOST_IO_BYTES {
+        return None;
+    }
+    let mut buf = vec![0; len];
+    memory.read(caller, ptr, &mut buf).ok()?;
+    Some(buf)
+}
+
+fn read_string(
+    memory: &Memory,
+    caller: &Caller<'_, SandboxState>,
+    ptr: i32,
+    len: i32,
+) -> Option<String> {
+    String::from_utf8(read_bytes(memory, caller, ptr, len)?).ok()
+}
+
+fn write_output(
+    memory: &Memory,
+    caller: &mut Caller<'_, SandboxState>,
+    ptr: i32,
+    cap: i32,
+    data: &[u8],
+) -> i64 {
+    let (Ok(ptr), Ok(cap)) = (usize::try_from(ptr), usize::try_from(cap)) else {
+        return HOST_INVALID;
+    };
+    if data.len() > cap || data.len() > MAX_HOST_IO_BYTES {
+        return HOST_TOO_LARGE;
+    }
+    match memory.write(caller, ptr, data) {
+        Ok(()) => data.len() as i64,
+        Err(_) => HOST_INVALID,
+    }
+}
+
+fn denied(error: &PluginError) -> i64 {
+    tracing::warn!(error = %error, "Plugin host call rejected");
+    match error {
+        PluginError::IoError(_) => HOST_FAILED,
+        _ => HOST_DENIED,
+    }
+}
+
+/// Run async host work to completion from a synchronous host call
+///
+/// Uses a dedicated thread so it works whether or not the caller is already
+/// inside a tokio runtime.
+fn block_on<F>(future: F) -> Option<F::Output>
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .ok()
+                    .map(|runtime| runtime.block_on(future))
+            })
+            .join()
+            .ok()
+            .flatten()
+    })
 }
@@ -0,0 +1,186 @@
+//! Plugin project scaffolding
+//!
+//! `demiarch plugins new <name>` creates a Rust crate that builds to a WASM
+//! plugin: a manifest, a small bindings crate for the host ABI (with a mock
+//! host for native tests), an example capability and tests for it.
+
+use crate::{LicenseTier, Permission, PluginError, PluginManifest, PluginResult};
+use demiarch_core::events::EventKind;
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Kind of plugin to scaffold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    /// `run` writes generated files into the project
+    Generator,
+    /// `on_event` reacts to core events and may veto feature creation
+    Hook,
+    /// `run` reads project files and renders a Markdown panel
+    Panel,
+}
+
+impl Capability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Generator => "generator",
+            Self::Hook => "hook",
+            Self::Panel => "panel",
+        }
+    }
+
+    fn permissions(&self) -> Vec<Permission> {
+        match self {
+            Self::Generator => vec![Permission::WriteFiles],
+            Self::Hook => vec![Permission::ReadFiles],
+            Self::Panel => vec![Permission::ReadFiles, Permission::WriteFiles],
+        }
+    }
+
+    fn events(&self) -> Vec<EventKind> {
+        match self {
+            Self::Hook => vec![EventKind::FeatureCreated],
+            Self::Generator | Self::Panel => Vec::new(),
+        }
+    }
+
+    fn templates(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::Generator => (
+                include_str!("../templates/scaffold/capabilities/generator.rs.tmpl"),
+                include_str!("../templates/scaffold/capabilities/generator_test.rs.tmpl"),
+            ),
+            Self::Hook => (
+                include_str!("../templates/scaffold/capabilities/hook.rs.tmpl"),
+                include_str!("../templates/scaffold/capabilities/hook_test.rs.tmpl"),
+            ),
+            Self::Panel => (
+                include_str!("../templates/scaffold/capabilities/panel.rs.tmpl"),
+                include_str!("../templates/scaffold/capabilities/panel_test.rs.tmpl"),
+            ),
+        }
+    }
+
+    fn usage(&self) -> &'static str {
+        match self {
+            Self::Generator | Self::Panel => {
+                "Run it from a project directory with `demiarch plugins run ~/.demiarch/plugins/{{id}}/manifest.json`."
+            }
+            Self::Hook => {
+                "Installed hooks are called automatically for the events in `manifest.json`. \
+                 Event handlers never prompt, so grant permissions once with `demiarch plugins run` first."
+            }
+        }
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Capability {
+    type Err = PluginError;
+
+    fn from_str(s: &str) -> PluginResult<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "generator" => Ok(Self::Generator),
+            "hook" => Ok(Self::Hook),
+            "panel" => Ok(Self::Panel),
+            other => Err(PluginError::ValidationFailed(format!(
+                "Unknown capability '{other}' (expected generator, hook or panel)"
+            ))),
+        }
+    }
+}
+
+/// Create a plugin project named `name` inside `parent`, returning its path
+pub fn scaffold(name: &str, capability: Capability, parent: &Path) -> PluginResult<PathBuf> {
+    validate_name(name)?;
+    let root = parent.join(name);
+    if root.exists() {
+        return Err(PluginError::ValidationFailed(format!(
+            "{} already exists",
+            root.display()
+        )));
+    }
+
+    let manifest = PluginManifest {
+        id: name.to_string(),
+        name: name.to_string(),
+        version: "0.1.0".to_string(),
+        description: format!("A {capability} plugin"),
+        author: "Your Name".to_string(),
+        license_tier: LicenseTier::Free,
+        permissions: capability.permissions(),
+        events: capability.events(),
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| PluginError::ValidationFailed(format!("Failed to write manifest: {e}")))?;
+
+    let (source, test) = capability.templates();
+    let files = [
+        (
+            "Cargo.toml",
+            include_str!("../templates/scaffold/Cargo.toml.tmpl"),
+        ),
+        (
+            ".gitignore",
+            include_str!("../templates/scaffold/gitignore.tmpl"),
+        ),
+        (
+            "README.md",
+            include_str!("../templates/scaffold/README.md.tmpl"),
+        ),
+        (
+            "abi/Cargo.toml",
+            include_str!("../templates/scaffold/abi/Cargo.toml.tmpl"),
+        ),
+        (
+            "abi/src/lib.rs",
+            include_str!("../templates/scaffold/abi/lib.rs"),
+        ),
+        ("src/lib.rs", source),
+        ("tests/capability.rs", test),
+    ];
+
+    for (path, template) in files {
+        let target = root.join(path);
+        if let Some(dir) = target.parent() {
+            fs::create_dir_all(dir).map_err(PluginError::IoError)?;
+        }
+        fs::write(&target, render(template, name, capability)).map_err(PluginError::IoError)?;
+    }
+    fs::write(root.join("manifest.json"), manifest_json + "\n").map_err(PluginError::IoError)?;
+
+    Ok(root)
+}
+
+/// Fill in a template's placeholders
+fn render(template: &str, name: &str, capability: Capability) -> String {
+    template
+        .replace("{{usage}}", capability.usage())
+        .replace("{{crate_name}}", &name.replace('-', "_"))
+        .replace("{{capability}}", capability.as_str())
+        .replace("{{name}}", name)
+        .replace("{{id}}", name)
+}
+
+/// Plugin names double as IDs, directory and crate names
+fn validate_name(name: &str) -> PluginResult<()> {
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !name.ends_with('-');
+    if !valid {
+        return Err(PluginError::ValidationFailed(format!(
+            "Invalid plugin name '{name}'. Use lowercase letters, digits and hyphens, e.g. license-headers"
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,350 @@
+//! Bindings for the Demiarch plugin host ABI
+//!
+//! Host functions are imported from the `demiarch` module. Each takes
+//! (pointer, length) pairs into this module's memory and returns the number
+//! of bytes written, or a negative error code; the wrappers below hide that.
+//! A host call only works if the manifest requests its permission:
+//!
+//! | Function        | Permission |
+//! |-----------------|------------|
+//! | [`read_file`]   | ReadFiles  |
+//! | [`write_file`]  | WriteFiles |
+//! | [`http_get`]    | Network    |
+//! | [`run_program`] | Subprocess |
+//!
+//! Paths are relative to the project directory. The user is asked the first
+//! time each permission is used, and may refuse.
+//!
+//! Entry points are exported with [`export_run!`] (called by
+//! `demiarch plugins run`) and [`export_on_event!`] (called for the events
+//! listed in the manifest).
+//!
+//! On targets other than `wasm32` the same functions use the in-memory
+//! [`mock`] host, so plugin logic can be tested with plain `cargo test`.
+
+use serde::{Deserialize, Serialize};
+
+/// Buffer for results of host calls; larger results fail with `TooLarge`
+pub const OUTPUT_BUFFER_BYTES: usize = 1024 * 1024;
+
+/// Why a host call failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostError {
+    /// The permission was not granted, or the target is outside what it allows
+    Denied,
+    /// Arguments were malformed
+    Invalid,
+    /// The I/O, HTTP or process operation itself failed
+    Failed,
+    /// The result did not fit in the output buffer
+    TooLarge,
+    /// Network access is off on the host
+    Offline,
+    Unknown(i64),
+}
+
+impl HostError {
+    pub fn from_code(code: i64) -> Self {
+        match code {
+            -1 => Self::Denied,
+            -2 => Self::Invalid,
+            -3 => Self::Failed,
+            -4 => Self::TooLarge,
+            -5 => Self::Offline,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl std::fmt::Display for HostError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "host call failed: {:?}", self)
+    }
+}
+
+pub type HostResult<T> = Result<T, HostError>;
+
+/// Result of [`run_program`]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProgramOutput {
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
+}
+
+/// An event delivered to an [`export_on_event!`] handler
+#[derive(Debug, Clone, Deserialize)]
+pub struct Event {
+    pub id: String,
+    pub occurred_at: String,
+    /// `generation_completed`, `feature_created`, `cost_recorded` or
+    /// `digest_created`
+    pub kind: String,
+    /// Event-specific fields, e.g. `title` for `feature_created`
+    #[serde(flatten)]
+    pub payload: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Event {
+    /// A string field of the payload
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.payload.get(name).and_then(|value| value.as_str())
+    }
+}
+
+/// A handler's answer to an event
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Reaction {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<String>,
+    /// Cancel the step (only `feature_created` can be vetoed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub veto: Option<String>,
+}
+
+impl Reaction {
+    pub fn annotate(text: impl Into<String>) -> Self {
+        Self {
+            annotations: vec![text.into()],
+            veto: None,
+        }
+    }
+
+    pub fn veto(reason: impl Into<String>) -> Self {
+        Self {
+            annotations: Vec::new(),
+            veto: Some(reason.into()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ProgramRequest<'a> {
+    program: &'a str,
+    args: &'a [&'a str],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stdin: Option<&'a str>,
+}
+
+#[cfg(target_arch = "wasm32")]
+mod host {
+    #[link(wasm_import_module = "demiarch")]
+    extern "C" {
+        pub fn read_file(path: *const u8, path_len: usize, out: *mut u8, out_cap: usize) -> i64;
+        pub fn write_file(path: *const u8, path_len: usize, data: *const u8, data_len: usize)
+            -> i64;
+        pub fn http_get(url: *const u8, url_len: usize, out: *mut u8, out_cap: usize) -> i64;
+        pub fn run_program(cmd: *const u8, cmd_len: usize, out: *mut u8, out_cap: usize) -> i64;
+    }
+
+    /// Call a host function that writes into an output buffer
+    pub fn with_output(call: impl FnOnce(*mut u8, usize) -> i64) -> super::HostResult<Vec<u8>> {
+        let mut out = vec![0u8; super::OUTPUT_BUFFER_BYTES];
+        let written = call(out.as_mut_ptr(), out.len());
+        if written < 0 {
+            return Err(super::HostError::from_code(written));
+        }
+        out.truncate(written as usize);
+        Ok(out)
+    }
+}
+
+/// Read a project file
+#[cfg(target_arch = "wasm32")]
+pub fn read_file(path: &str) -> HostResult<Vec<u8>> {
+    host::with_output(|out, cap| unsafe { host::read_file(path.as_ptr(), path.len(), out, cap) })
+}
+
+/// Write a project file; its directory must already exist
+#[cfg(target_arch = "wasm32")]
+pub fn write_file(path: &str, data: &[u8]) -> HostResult<()> {
+    let written =
+        unsafe { host::write_file(path.as_ptr(), path.len(), data.as_ptr(), data.len()) };
+    if written < 0 {
+        return Err(HostError::from_code(written));
+    }
+    Ok(())
+}
+
+/// GET an https URL and return the body
+#[cfg(target_arch = "wasm32")]
+pub fn http_get(url: &str) -> HostResult<Vec<u8>> {
+    host::with_output(|out, cap| unsafe { host::http_get(url.as_ptr(), url.len(), out, cap) })
+}
+
+/// Run a program through the host's sandboxed runner
+#[cfg(target_arch = "wasm32")]
+pub fn run_program(program: &str, args: &[&str], stdin: Option<&str>) -> HostResult<ProgramOutput> {
+    let request = serde_json::to_vec(&ProgramRequest {
+        program,
+        args,
+        stdin,
+    })
+    .map_err(|_| HostError::Invalid)?;
+    let output = host::with_output(|out, cap| unsafe {
+        host::run_program(request.as_ptr(), request.len(), out, cap)
+    })?;
+    serde_json::from_slice(&output).map_err(|_| HostError::Invalid)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use mock::{http_get, read_file, run_program, write_file};
+
+/// In-memory host used when compiling for anything but `wasm32`
+///
+/// State is per thread, so each test sees its own files and responses.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod mock {
+    use super::{HostError, HostResult, ProgramOutput, ProgramRequest};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct Host {
+        files: HashMap<String, Vec<u8>>,
+        urls: HashMap<String, Vec<u8>>,
+        programs: HashMap<String, ProgramOutput>,
+        deny_all: bool,
+    }
+
+    thread_local! {
+        static HOST: RefCell<Host> = RefCell::new(Host::default());
+    }
+
+    /// Forget all files, responses and settings
+    pub fn reset() {
+        HOST.with(|host| *host.borrow_mut() = Host::default());
+    }
+
+    /// Make every host call fail as if the user refused the permission
+    pub fn deny_all(deny: bool) {
+        HOST.with(|host| host.borrow_mut().deny_all = deny);
+    }
+
+    pub fn set_file(path: &str, data: impl Into<Vec<u8>>) {
+        HOST.with(|host| {
+            host.borrow_mut().files.insert(path.to_string(), data.into());
+        });
+    }
+
+    /// Contents of a file, including ones the plugin wrote
+    pub fn file(path: &str) -> Option<Vec<u8>> {
+        HOST.with(|host| host.borrow().files.get(path).cloned())
+    }
+
+    pub fn set_url(url: &str, body: impl Into<Vec<u8>>) {
+        HOST.with(|host| {
+            host.borrow_mut().urls.insert(url.to_string(), body.into());
+        });
+    }
+
+    pub fn set_program(program: &str, output: ProgramOutput) {
+        HOST.with(|host| {
+            host.borrow_mut().programs.insert(program.to_string(), output);
+        });
+    }
+
+    fn with_host<T>(call: impl FnOnce(&mut Host) -> HostResult<T>) -> HostResult<T> {
+        HOST.with(|host| {
+            let mut host = host.borrow_mut();
+            if host.deny_all {
+                return Err(HostError::Denied);
+            }
+            call(&mut host)
+        })
+    }
+
+    pub fn read_file(path: &str) -> HostResult<Vec<u8>> {
+        with_host(|host| host.files.get(path).cloned().ok_or(HostError::Failed))
+    }
+
+    pub fn write_file(path: &str, data: &[u8]) -> HostResult<()> {
+        with_host(|host| {
+            host.files.insert(path.to_string(), data.to_vec());
+            Ok(())
+        })
+    }
+
+    pub fn http_get(url: &str) -> HostResult<Vec<u8>> {
+        with_host(|host| host.urls.get(url).cloned().ok_or(HostError::Denied))
+    }
+
+    pub fn run_program(
+        program: &str,
+        args: &[&str],
+        stdin: Option<&str>,
+    ) -> HostResult<ProgramOutput> {
+        // Build the request the real host would receive, to catch bad input
+        serde_json::to_vec(&ProgramRequest {
+            program,
+            args,
+            stdin,
+        })
+        .map_err(|_| HostError::Invalid)?;
+        with_host(|host| host.programs.get(program).cloned().ok_or(HostError::Denied))
+    }
+}
+
+/// Reserve `len` bytes for the host to write an event into
+///
+/// The memory is never freed: every invocation gets a fresh instance.
+#[doc(hidden)]
+pub fn alloc(len: usize) -> *mut u8 {
+    let mut buffer = Vec::<u8>::with_capacity(len.max(1));
+    let ptr = buffer.as_mut_ptr();
+    std::mem::forget(buffer);
+    ptr
+}
+
+/// Decode an event, run the handler and pack its reaction for the host
+#[doc(hidden)]
+pub fn dispatch_event(ptr: *const u8, len: usize, handler: fn(&Event) -> Option<Reaction>) -> i64 {
+    let bytes = unsafe { std::slice::from_raw_parts(ptr, len) };
+    let Ok(event) = serde_json::from_slice::<Event>(bytes) else {
+        return 0;
+    };
+    let Some(reaction) = handler(&event) else {
+        return 0;
+    };
+    let Ok(json) = serde_json::to_vec(&reaction) else {
+        return 0;
+    };
+    let packed = ((json.as_ptr() as usize as i64) << 32) | json.len() as i64;
+    std::mem::forget(json);
+    packed
+}
+
+/// Export `run`, called by `demiarch plugins run`
+///
+/// The handler returns `Result<(), String>`; an error aborts the run and is
+/// reported as a failed execution.
+#[macro_export]
+macro_rules! export_run {
+    ($handler:path) => {
+        #[no_mangle]
+        pub extern "C" fn run() {
+            if let Err(error) = $handler() {
+                panic!("{}", error);
+            }
+        }
+    };
+}
+
+/// Export `alloc` and `on_event` for the events listed in the manifest
+///
+/// The handler has the signature `fn(&Event) -> Option<Reaction>`.
+#[macro_export]
+macro_rules! export_on_event {
+    ($handler:path) => {
+        #[no_mangle]
+        pub extern "C" fn alloc(len: i32) -> i32 {
+            $crate::alloc(len as usize) as usize as i32
+        }
+
+        #[no_mangle]
+        pub extern "C" fn on_event(ptr: i32, len: i32) -> i64 {
+            $crate::dispatch_event(ptr as usize as *const u8, len as usize, $handler)
+        }
+    };
+}
@@ -3,51 +3,86 @@
 //! This TUI provides a live view of:
 //! - Active agent executions across all projects
 //! - Agent hierarchy tree with status indicators
+//! - Gantt timeline of agent runs with critical path and idle time
 //! - Token usage and costs in real-time
+//! - Projected end-of-month spend from the recorded cost history
 //! - Generation progress and status
 //! - Skill activations
 //! - Hook executions
+//!
+//! `demiarch-tui --session <id>` opens a recorded session instead and replays
+//! its agent tree with play/pause, speed and step controls.
 
 #[cfg(test)]
 mod main_tests;
+mod replay;
 
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use demiarch_core::agents::events::read_current_session_events;
+use demiarch_core::agents::events::{
+    file_progress, read_current_session_events, read_session_events, AgentEvent, FileProgress,
+};
+use demiarch_core::config::Config;
+use demiarch_core::cost::forecast::{self, SpendForecast};
+use demiarch_core::i18n::{self, t, t_args};
+use demiarch_core::storage::Database;
 use demiarch_core::visualization::{
-    AgentStatusBar, HierarchyTreeWidget, RenderOptions, TreeBuilder, TreeColors,
+    bordered_block, glyphs, AgentStatusBar, HierarchyTreeWidget, RenderOptions, Timeline,
+    TreeBuilder, TreeColors,
 };
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Paragraph, Tabs},
+    widgets::{Paragraph, Tabs},
     Terminal,
 };
+use replay::Replay;
+use std::borrow::Cow;
 use std::io;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How often the spend forecast is re-read from the database
+const FORECAST_REFRESH: Duration = Duration::from_secs(60);
 
 /// Application state
 struct App {
     /// Currently selected tab
     current_tab: usize,
     /// Tab titles
-    tabs: Vec<&'static str>,
+    tabs: Vec<String>,
     /// Scroll offset for agent tree
     tree_scroll: usize,
     /// Whether to use ASCII mode
     ascii_mode: bool,
+    /// Replay of a recorded session; the live session is shown if unset
+    replay: Option<Replay>,
+    /// Month-end spend projection, if the cost history could be read
+    forecast: Option<SpendForecast>,
+    /// When the forecast was last loaded
+    forecast_loaded: Option<Instant>,
 }
 
 impl App {
-    fn new() -> Self {
+    fn new(replay: Option<Replay>) -> Self {
         Self {
             current_tab: 1, // Start on Agents tab
-            tabs: vec!["Projects", "Agents", "Stats", "Help"],
+            tabs: vec![
+                t("tui-tab-projects"),
+                t("tui-tab-agents"),
+                t("tui-tab-stats"),
+                t("tui-tab-timeline"),
+                t("tui-tab-help"),
+            ],
             tree_scroll: 0,
             ascii_mode: false,
+            replay,
+            forecast: None,
+            forecast_loaded: None,
         }
     }
 
@@ -74,12 +109,50 @@ impl App {
     fn toggle_ascii(&mut self) {
         self.ascii_mode = !self.ascii_mode;
     }
+
+    /// Reload the spend forecast once it is older than [`FORECAST_REFRESH`]
+    fn refresh_forecast(&mut self, runtime: &tokio::runtime::Runtime) {
+        if self
+            .forecast_loaded
+            .is_some_and(|at| at.elapsed() < FORECAST_REFRESH)
+        {
+            return;
+        }
+        self.forecast = runtime.block_on(load_forecast());
+        self.forecast_loaded = Some(Instant::now());
+    }
+
+    /// Events to display: the replay position, or the live session
+    fn events(&self) -> Cow<'_, [AgentEvent]> {
+        match &self.replay {
+            Some(replay) => Cow::Borrowed(replay.visible()),
+            None => Cow::Owned(read_current_session_events()),
+        }
+    }
 }
 
 fn main() -> anyhow::Result<()> {
     // Load .env file if present (silently ignore if not found)
     dotenvy::dotenv().ok();
 
+    let config = Config::load().ok();
+    i18n::init(config.as_ref());
+    if config.as_ref().is_some_and(|c| c.ui.plain) {
+        glyphs::set_plain(true);
+    }
+
+    // `--session <id>` replays a recorded session instead of following the live one
+    let replay = match session_arg()? {
+        Some(id) => {
+            let events = read_session_events(id);
+            if events.is_empty() {
+                anyhow::bail!("No agent events recorded for session {}", id);
+            }
+            Some(Replay::new(events))
+        }
+        None => None,
+    };
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -88,7 +161,7 @@ fn main() -> anyhow::Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state
-    let mut app = App::new();
+    let mut app = App::new(replay);
 
     // Run app
     let result = run_app(&mut terminal, &mut app);
@@ -101,11 +174,40 @@ fn main() -> anyhow::Result<()> {
     result
 }
 
+async fn load_forecast() -> Option<SpendForecast> {
+    let config = Config::load().ok()?;
+    let db = Database::shared().await.ok()?;
+    forecast::forecast(&db, None, &config.cost, chrono::Utc::now())
+        .await
+        .ok()
+}
+
+fn session_arg() -> anyhow::Result<Option<Uuid>> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--session" {
+            let id = args
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("--session requires a session ID"))?;
+            return Ok(Some(Uuid::parse_str(&id)?));
+        }
+    }
+    Ok(None)
+}
+
 fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
 ) -> anyhow::Result<()> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    let mut last_tick = Instant::now();
     loop {
+        if let Some(replay) = app.replay.as_mut() {
+            replay.tick(last_tick.elapsed());
+        }
+        last_tick = Instant::now();
+        app.refresh_forecast(&runtime);
+
         terminal.draw(|frame| {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
@@ -119,11 +221,8 @@ fn run_app(
 
             // Header with tabs
             let tabs = Tabs::new(app.tabs.to_vec())
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title("Demiarch Monitor"),
-                )
+                .divider(if glyphs::is_plain() { "|" } else { "│" })
+                .block(bordered_block().title(t("tui-title")))
                 .select(app.current_tab)
                 .style(Style::default().fg(Color::White))
                 .highlight_style(
@@ -137,8 +236,9 @@ fn run_app(
             match app.current_tab {
                 0 => render_projects_tab(frame, chunks[1]),
                 1 => render_agents_tab(frame, chunks[1], app),
-                2 => render_stats_tab(frame, chunks[1]),
-                3 => render_help_tab(frame, chunks[1]),
+                2 => render_stats_tab(frame, chunks[1], app),
+                3 => render_timeline_tab(frame, chunks[1], app),
+                4 => render_help_tab(frame, chunks[1]),
                 _ => {}
             }
 
@@ -147,9 +247,20 @@ fn run_app(
         })?;
 
         // Handle input
-        if event::poll(std::time::Duration::from_millis(100))? {
+        if event::poll(Duration::from_millis(100))? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
+                    if let Some(replay) = app.replay.as_mut() {
+                        match key.code {
+                            KeyCode::Char(' ') => replay.toggle(),
+                            KeyCode::Char('+') | KeyCode::Char('=') => replay.faster(),
+                            KeyCode::Char('-') => replay.slower(),
+                            KeyCode::Char('.') => replay.step_forward(),
+                            KeyCode::Char(',') => replay.step_back(),
+                            KeyCode::Char('r') => replay.restart(),
+                            _ => {}
+                        }
+                    }
                     match key.code {
                         KeyCode::Char('q') => return Ok(()),
                         KeyCode::Tab | KeyCode::Right => app.next_tab(),
@@ -160,7 +271,8 @@ fn run_app(
                         KeyCode::Char('1') => app.current_tab = 0,
                         KeyCode::Char('2') => app.current_tab = 1,
                         KeyCode::Char('3') => app.current_tab = 2,
-                        KeyCode::Char('4') | KeyCode::Char('?') => app.current_tab = 3,
+                        KeyCode::Char('4') => app.current_tab = 3,
+                        KeyCode::Char('5') | KeyCode::Char('?') => app.current_tab = 4,
                         _ => {}
                     }
                 }
@@ -170,18 +282,9 @@ fn run_app(
 }
 
 fn render_projects_tab(frame: &mut ratatui::Frame, area: Rect) {
-    let content = Paragraph::new(
-        "No active projects\n\n\
-         Projects will appear here during code generation.\n\n\
-         To create a project:\n\
-         $ demiarch new <name> --framework <framework>\n\n\
-         To start code generation:\n\
-         $ demiarch generate \"description\"",
-    )
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title("Projects")
+    let content = Paragraph::new(glyphs::display(&t("tui-projects-empty")).into_owned()).block(
+        bordered_block()
+            .title(t("tui-tab-projects"))
             .style(Style::default()),
     );
     frame.render_widget(content, area);
@@ -194,8 +297,8 @@ fn render_agents_tab(frame: &mut ratatui::Frame, area: Rect, app: &App) {
         .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
         .split(area);
 
-    // Build tree from live events (falls back to placeholder if no events)
-    let tree = TreeBuilder::from_live_events();
+    // Build tree from events (falls back to placeholder if no events)
+    let tree = TreeBuilder::build_from_events(&app.events());
 
     // Configure options based on app state
     let options = if app.ascii_mode {
@@ -212,16 +315,16 @@ fn render_agents_tab(frame: &mut ratatui::Frame, area: Rect, app: &App) {
         .show_header(true)
         .show_footer(true)
         .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Agent Hierarchy")
+            bordered_block()
+                .title(t("tui-agent-hierarchy"))
                 .style(Style::default()),
         );
     frame.render_widget(tree_widget, chunks[0]);
 
     // Agent details panel
     let details = Paragraph::new(
-        "Agent Details\n\
+        glyphs::display(
+            "Agent Details\n\
          ─────────────\n\n\
          Select an agent to view details.\n\n\
          During execution:\n\
@@ -234,32 +337,36 @@ fn render_agents_tab(frame: &mut ratatui::Frame, area: Rect, app: &App) {
          💻 Coder - Code generation\n\
          🔍 Reviewer - Code review\n\
          🧪 Tester - Test generation\n\n\
+         ctx shows each agent's context window\n\
+         use; red above 80%\n\n\
          Use ↑/↓ or j/k to scroll\n\
          Press 'a' to toggle ASCII mode",
+        )
+        .into_owned(),
     )
     .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title("Details")
+        bordered_block()
+            .title(t("tui-details"))
             .style(Style::default()),
     );
     frame.render_widget(details, chunks[1]);
 }
 
-fn render_stats_tab(frame: &mut ratatui::Frame, area: Rect) {
+fn render_stats_tab(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     // Split into multiple stat panels
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(7), // Session stats
-            Constraint::Length(7), // Token usage
+            Constraint::Length(7), // Token usage and spend forecast
+            Constraint::Length(8), // Files
             Constraint::Min(5),    // Recent activity
         ])
         .split(area);
 
-    // Get real data from live events
-    let tree = TreeBuilder::from_live_events();
-    let events = read_current_session_events();
+    // Get real data from events
+    let events = app.events();
+    let tree = TreeBuilder::build_from_events(&events);
 
     let total_agents = tree.count();
     let active_agents = tree.count_active();
@@ -268,38 +375,50 @@ fn render_stats_tab(frame: &mut ratatui::Frame, area: Rect) {
     let total_tokens = tree.tree_tokens();
 
     // Session stats
-    let session_stats = Paragraph::new(format!(
-        "Total Agents: {}\n\
-         Active Agents: {}\n\
-         Completed: {}\n\
-         Failed: {}\n\
-         Events: {}",
-        total_agents,
-        active_agents,
-        completed,
-        failed,
-        events.len()
+    let session_stats = Paragraph::new(t_args(
+        "tui-session-stats",
+        &[
+            ("total", &total_agents),
+            ("active", &active_agents),
+            ("completed", &completed),
+            ("failed", &failed),
+            ("events", &events.len()),
+        ],
     ))
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title("Session Statistics"),
-    );
+    .block(bordered_block().title(t("tui-session-statistics")));
     frame.render_widget(session_stats, chunks[0]);
 
     // Token usage
     // Rough cost estimate: $3/million input, $15/million output (Claude pricing)
     let estimated_cost = (total_tokens as f64 / 1_000_000.0) * 10.0; // rough average
-    let token_stats = Paragraph::new(format!(
-        "Total Tokens: {}\n\
-         Estimated Cost: ${:.4}\n\
-         \n\
-         (Token breakdown per agent\n\
-         shown in agent tree)",
-        total_tokens, estimated_cost
+    let token_stats = Paragraph::new(t_args(
+        "tui-token-stats",
+        &[
+            ("tokens", &total_tokens),
+            ("cost", &format!("{:.4}", estimated_cost)),
+        ],
     ))
-    .block(Block::default().borders(Borders::ALL).title("Token Usage"));
-    frame.render_widget(token_stats, chunks[1]);
+    .block(bordered_block().title(t("tui-token-usage")));
+    let cost_row = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+    frame.render_widget(token_stats, cost_row[0]);
+    render_forecast(frame, cost_row[1], app.forecast.as_ref());
+
+    // Files extracted/written during generation
+    let files = file_progress(&events);
+    let written = files.iter().filter(|f| f.written).count();
+    let files_text = if files.is_empty() {
+        t("tui-files-empty")
+    } else {
+        format_file_progress(&files, chunks[2].height.saturating_sub(2) as usize)
+    };
+    let files_panel = Paragraph::new(files_text).block(bordered_block().title(t_args(
+        "tui-files",
+        &[("written", &written), ("total", &files.len())],
+    )));
+    frame.render_widget(files_panel, chunks[2]);
 
     // Recent activity - show last few events
     let recent_events: Vec<String> = events
@@ -308,36 +427,107 @@ fn render_stats_tab(frame: &mut ratatui::Frame, area: Rect) {
         .take(8)
         .map(|e| {
             let time = e.timestamp.format("%H:%M:%S");
-            let event_type = match e.event_type {
-                demiarch_core::agents::events::AgentEventType::Spawned => "🆕 Spawned",
-                demiarch_core::agents::events::AgentEventType::Started => "▶️  Started",
-                demiarch_core::agents::events::AgentEventType::StatusUpdate => "📊 Status",
-                demiarch_core::agents::events::AgentEventType::Completed => "✓  Done",
-                demiarch_core::agents::events::AgentEventType::Failed => "✗  Failed",
-                demiarch_core::agents::events::AgentEventType::Cancelled => "⊘  Cancel",
-                demiarch_core::agents::events::AgentEventType::TokenUpdate => "🎫 Tokens",
-            };
-            format!("[{}] {} {}", time, event_type, e.agent.name)
+            let event_type = glyphs::agent_event(&e.event_type);
+            let subject = e
+                .file
+                .as_ref()
+                .map(|f| f.path.as_str())
+                .unwrap_or(&e.agent.name);
+            format!("[{}] {} {}", time, event_type, subject)
         })
         .collect();
 
     let activity_text = if recent_events.is_empty() {
-        "No recent activity\n\n\
-         Events will appear here during code generation:\n\
-         • Agent spawned\n\
-         • Agent completed\n\
-         • File created"
-            .to_string()
+        glyphs::display(&t("tui-activity-empty")).into_owned()
     } else {
         recent_events.join("\n")
     };
 
-    let activity = Paragraph::new(activity_text).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title("Recent Activity"),
+    let activity =
+        Paragraph::new(activity_text).block(bordered_block().title(t("tui-recent-activity")));
+    frame.render_widget(activity, chunks[3]);
+}
+
+/// Month-to-date spend, burn rate and projection, red when over the limit
+fn render_forecast(frame: &mut ratatui::Frame, area: Rect, forecast: Option<&SpendForecast>) {
+    let Some(forecast) = forecast else {
+        let empty = Paragraph::new(t("tui-forecast-empty"))
+            .style(Style::default().fg(Color::DarkGray))
+            .block(bordered_block().title(t("tui-spend-forecast")));
+        frame.render_widget(empty, area);
+        return;
+    };
+
+    let limit = if forecast.monthly_limit_usd > 0.0 {
+        format!("{:.2}", forecast.monthly_limit_usd)
+    } else {
+        "-".to_string()
+    };
+    let text = t_args(
+        "tui-forecast",
+        &[
+            ("spent", &format!("{:.2}", forecast.month_to_date_usd)),
+            ("rate", &format!("{:.2}", forecast.burn_rate_usd_per_day)),
+            ("days", &forecast.window_days),
+            ("end", &forecast.month_end.format("%b %-d")),
+            ("projected", &format!("{:.2}", forecast.projected_usd)),
+            ("limit", &limit),
+        ],
     );
-    frame.render_widget(activity, chunks[2]);
+    let (title, style) = if forecast.over_limit {
+        (
+            format!(
+                "{} - {}",
+                t("tui-spend-forecast"),
+                t("tui-forecast-warning")
+            ),
+            Style::default().fg(Color::Red),
+        )
+    } else {
+        (t("tui-spend-forecast"), Style::default())
+    };
+    let panel = Paragraph::new(text)
+        .style(style)
+        .block(bordered_block().title(title));
+    frame.render_widget(panel, area);
+}
+
+/// Format the most recent `max_lines` files as "status path (size, new/modified)"
+fn format_file_progress(files: &[FileProgress], max_lines: usize) -> String {
+    let skip = files.len().saturating_sub(max_lines);
+    files
+        .iter()
+        .skip(skip)
+        .map(|f| {
+            let status = if f.written {
+                glyphs::check()
+            } else if glyphs::is_plain() {
+                "..."
+            } else {
+                "…"
+            };
+            let kind = if f.is_new { "new" } else { "modified" };
+            format!("{} {} ({} B, {})", status, f.path, f.bytes, kind)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_timeline_tab(frame: &mut ratatui::Frame, area: Rect, app: &App) {
+    let timeline = Timeline::from_events(&app.events());
+
+    // Leave room for the borders, name column, bar delimiters and duration
+    let width = (area.width as usize).saturating_sub(2 + 24 + 3 + 11);
+    let text = if timeline.spans.is_empty() {
+        t("tui-timeline-empty")
+    } else {
+        timeline.render_text(width)
+    };
+
+    let content = Paragraph::new(text)
+        .scroll((app.tree_scroll as u16, 0))
+        .block(bordered_block().title(t("tui-timeline")));
+    frame.render_widget(content, area);
 }
 
 fn render_help_tab(frame: &mut ratatui::Frame, area: Rect) {
@@ -348,13 +538,19 @@ Demiarch TUI - Agent Monitoring Dashboard
 NAVIGATION
   Tab / →      Next tab
   Shift+Tab / ←  Previous tab
-  1-4          Jump to tab
+  1-5          Jump to tab
   ↑ / k        Scroll up
   ↓ / j        Scroll down
 
 DISPLAY
   a            Toggle ASCII/Unicode mode
 
+REPLAY (demiarch-tui --session <id>)
+  Space        Play / pause
+  + / -        Faster / slower
+  . / ,        Step forward / back one event
+  r            Restart
+
 GENERAL
   q            Quit
   ?            Show this help
@@ -380,18 +576,14 @@ STATUS ICONS
 For more information, visit:
   https://github.com/demiarch/demiarch";
 
-    let help = Paragraph::new(help_text).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title("Help")
-            .style(Style::default()),
-    );
+    let help = Paragraph::new(glyphs::display(help_text).into_owned())
+        .block(bordered_block().title("Help").style(Style::default()));
     frame.render_widget(help, area);
 }
 
 fn render_status_bar(frame: &mut ratatui::Frame, area: Rect, app: &App) {
-    // Build tree from live events for status bar
-    let tree = TreeBuilder::from_live_events();
+    // Build tree from events for status bar
+    let tree = TreeBuilder::build_from_events(&app.events());
 
     // Split status bar into agent status and key hints
     let chunks = Layout::default()
@@ -400,7 +592,7 @@ fn render_status_bar(frame: &mut ratatui::Frame, area: Rect, app: &App) {
         .split(area);
 
     // Agent status bar
-    let status_block = Block::default().borders(Borders::ALL);
+    let status_block = bordered_block();
     let inner_status = status_block.inner(chunks[0]);
     frame.render_widget(status_block, chunks[0]);
 
@@ -410,14 +602,33 @@ fn render_status_bar(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     // Key hints
     let mode_hint = if app.ascii_mode {
         "[ASCII]"
+    } else if glyphs::is_plain() {
+        "[Plain]"
     } else {
         "[Unicode]"
     };
-    let hints = Paragraph::new(format!(
-        "q: Quit | Tab: Switch | ↑↓: Scroll | a: Toggle {} | ?: Help",
-        mode_hint
-    ))
-    .style(Style::default().fg(Color::DarkGray))
-    .block(Block::default().borders(Borders::ALL));
+    let hints_text = match &app.replay {
+        Some(replay) => t_args(
+            "tui-replay-hints",
+            &[
+                (
+                    "state",
+                    &if replay.is_playing() {
+                        t("tui-replay-playing")
+                    } else {
+                        t("tui-replay-paused")
+                    },
+                ),
+                ("speed", &replay.speed()),
+                ("shown", &replay.cursor()),
+                ("total", &replay.total()),
+                ("clock", &replay.clock()),
+            ],
+        ),
+        None => t_args("tui-key-hints", &[("mode", &mode_hint)]),
+    };
+    let hints = Paragraph::new(glyphs::display(&hints_text).into_owned())
+        .style(Style::default().fg(Color::DarkGray))
+        .block(bordered_block());
     frame.render_widget(hints, chunks[1]);
 }
@@ -18,7 +18,7 @@ mod app_state_tests {
         fn new() -> Self {
             Self {
                 current_tab: 1,
-                tabs: vec!["Projects", "Agents", "Stats", "Help"],
+                tabs: vec!["Projects", "Agents", "Stats", "Timeline", "Help"],
                 tree_scroll: 0,
                 ascii_mode: false,
             }
@@ -53,7 +53,7 @@ mod app_state_tests {
     fn test_app_initial_state() {
         let app = TestApp::new();
         assert_eq!(app.current_tab, 1); // Starts on Agents tab
-        assert_eq!(app.tabs.len(), 4);
+        assert_eq!(app.tabs.len(), 5);
         assert_eq!(app.tree_scroll, 0);
         assert!(!app.ascii_mode);
     }
@@ -61,7 +61,7 @@ mod app_state_tests {
     #[test]
     fn test_app_next_tab_wraps() {
         let mut app = TestApp::new();
-        app.current_tab = 3; // Last tab (Help)
+        app.current_tab = 4; // Last tab (Help)
         app.next_tab();
         assert_eq!(app.current_tab, 0); // Wraps to first tab
     }
@@ -71,7 +71,7 @@ mod app_state_tests {
         let mut app = TestApp::new();
         app.current_tab = 0; // First tab
         app.prev_tab();
-        assert_eq!(app.current_tab, 3); // Wraps to last tab
+        assert_eq!(app.current_tab, 4); // Wraps to last tab
     }
 
     #[test]
@@ -80,7 +80,7 @@ mod app_state_tests {
         let initial = app.current_tab;
 
         // Cycle through all tabs
-        for _ in 0..4 {
+        for _ in 0..5 {
             app.next_tab();
         }
 
@@ -144,6 +144,9 @@ mod app_state_tests {
         assert_eq!(app.tabs[app.current_tab], "Stats");
 
         app.current_tab = 3;
+        assert_eq!(app.tabs[app.current_tab], "Timeline");
+
+        app.current_tab = 4;
         assert_eq!(app.tabs[app.current_tab], "Help");
     }
 }
@@ -294,3 +297,126 @@ mod core_integration_tests {
         let _ = std::mem::size_of::<Error>();
     }
 }
+
+/// Test file progress formatting for the Stats tab
+mod file_progress_tests {
+    use crate::format_file_progress;
+    use demiarch_core::agents::events::FileProgress;
+
+    fn progress(path: &str, written: bool) -> FileProgress {
+        FileProgress {
+            path: path.to_string(),
+            bytes: 42,
+            is_new: true,
+            written,
+        }
+    }
+
+    #[test]
+    fn test_format_file_progress_shows_latest_files() {
+        let files = vec![
+            progress("src/a.rs", true),
+            progress("src/b.rs", true),
+            progress("src/c.rs", false),
+        ];
+
+        let text = format_file_progress(&files, 2);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "✓ src/b.rs (42 B, new)");
+        assert_eq!(lines[1], "… src/c.rs (42 B, new)");
+    }
+}
+
+/// Test session replay playback
+mod replay_tests {
+    use crate::replay::{Replay, MAX_IDLE};
+    use chrono::{Duration as ChronoDuration, Utc};
+    use demiarch_core::agents::events::{AgentEvent, AgentEventData, AgentEventType};
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    /// Events at the given offsets (in seconds) from a common start
+    fn events_at(offsets: &[i64]) -> Vec<AgentEvent> {
+        let start = Utc::now();
+        offsets
+            .iter()
+            .map(|secs| AgentEvent {
+                timestamp: start + ChronoDuration::seconds(*secs),
+                event_id: Uuid::new_v4(),
+                session_id: Uuid::nil(),
+                event_type: AgentEventType::StatusUpdate,
+                agent: AgentEventData {
+                    id: "agent".to_string(),
+                    agent_type: String::new(),
+                    name: String::new(),
+                    parent_id: None,
+                    path: String::new(),
+                    status: "running".to_string(),
+                    tokens: 0,
+                    task: None,
+                    error: None,
+                },
+                file: None,
+                context: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_replay_starts_paused_and_empty() {
+        let mut replay = Replay::new(events_at(&[0, 1]));
+        replay.tick(Duration::from_secs(10));
+        assert!(!replay.is_playing());
+        assert!(replay.visible().is_empty());
+    }
+
+    #[test]
+    fn test_replay_advances_with_speed() {
+        let mut replay = Replay::new(events_at(&[0, 1, 2, 3]));
+        replay.toggle();
+
+        replay.tick(Duration::from_millis(500));
+        assert_eq!(replay.cursor(), 1);
+
+        replay.faster(); // 2x
+        replay.tick(Duration::from_millis(500));
+        assert_eq!(replay.cursor(), 2);
+
+        replay.tick(Duration::from_secs(1));
+        assert!(replay.is_finished());
+
+        // The next tick notices the end and stops
+        replay.tick(Duration::from_millis(100));
+        assert!(!replay.is_playing());
+    }
+
+    #[test]
+    fn test_replay_skips_idle_gaps() {
+        let mut replay = Replay::new(events_at(&[0, 600]));
+        replay.toggle();
+        replay.tick(Duration::from_millis(100));
+        assert_eq!(replay.cursor(), 1);
+
+        // A ten-minute gap plays back in at most MAX_IDLE
+        replay.tick(MAX_IDLE);
+        assert_eq!(replay.cursor(), 2);
+    }
+
+    #[test]
+    fn test_replay_steps_pause_playback() {
+        let mut replay = Replay::new(events_at(&[0, 5, 10]));
+        replay.toggle();
+        replay.step_forward();
+        replay.step_forward();
+        assert!(!replay.is_playing());
+        assert_eq!(replay.cursor(), 2);
+
+        replay.step_back();
+        assert_eq!(replay.cursor(), 1);
+
+        replay.restart();
+        assert_eq!(replay.cursor(), 0);
+        assert!(replay.visible().is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+//! Replay of a recorded agent session
+//!
+//! Plays a session's events back on a virtual clock so the agent tree can be
+//! watched as it evolved. Long idle gaps are shortened so a replay doesn't
+//! sit on an unchanged tree while the orchestrator waited on a model.
+
+use std::time::Duration;
+
+use demiarch_core::agents::events::AgentEvent;
+
+/// Playback speeds, cycled with `+` and `-`
+pub const SPEEDS: [f64; 7] = [0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0];
+
+/// Longest stretch of session time replayed without an event
+pub const MAX_IDLE: Duration = Duration::from_secs(2);
+
+/// Replay state for a recorded session
+pub struct Replay {
+    events: Vec<AgentEvent>,
+    /// Offset of each event from the first one
+    offsets: Vec<Duration>,
+    /// Number of events shown so far
+    cursor: usize,
+    /// Position of the virtual clock, as an offset from the first event
+    position: Duration,
+    /// Index into [`SPEEDS`]
+    speed: usize,
+    playing: bool,
+}
+
+impl Replay {
+    /// Start a paused replay positioned before the first event
+    pub fn new(events: Vec<AgentEvent>) -> Self {
+        let offsets = match events.first() {
+            Some(first) => events
+                .iter()
+                .map(|e| (e.timestamp - first.timestamp).to_std().unwrap_or_default())
+                .collect(),
+            None => Vec::new(),
+        };
+        Self {
+            events,
+            offsets,
+            cursor: 0,
+            position: Duration::ZERO,
+            speed: 1,
+            playing: false,
+        }
+    }
+
+    /// Events shown at the current position
+    pub fn visible(&self) -> &[AgentEvent] {
+        &self.events[..self.cursor]
+    }
+
+    /// Total number of events in the session
+    pub fn total(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Number of events shown so far
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Whether the replay is advancing
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Whether every event has been shown
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.events.len()
+    }
+
+    /// Current playback speed multiplier
+    pub fn speed(&self) -> f64 {
+        SPEEDS[self.speed]
+    }
+
+    /// Timestamp of the last shown event, formatted for the status bar
+    pub fn clock(&self) -> String {
+        self.visible()
+            .last()
+            .map(|e| e.timestamp.format("%H:%M:%S").to_string())
+            .unwrap_or_else(|| "--:--:--".to_string())
+    }
+
+    /// Advance the virtual clock by `elapsed` real time
+    pub fn tick(&mut self, elapsed: Duration) {
+        if !self.playing {
+            return;
+        }
+        if self.is_finished() {
+            self.playing = false;
+            return;
+        }
+
+        // Skip ahead over idle stretches
+        let next = self.offsets[self.cursor];
+        if next > self.position + MAX_IDLE {
+            self.position = next - MAX_IDLE;
+        }
+
+        self.position += elapsed.mul_f64(self.speed());
+        while self.cursor < self.offsets.len() && self.offsets[self.cursor] <= self.position {
+            self.cursor += 1;
+        }
+    }
+
+    /// Play or pause; playing a finished replay starts it over
+    pub fn toggle(&mut self) {
+        if !self.playing && self.is_finished() {
+            self.restart();
+        }
+        self.playing = !self.playing;
+    }
+
+    /// Play faster
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    /// Play slower
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    /// Pause and show one more event
+    pub fn step_forward(&mut self) {
+        self.playing = false;
+        if !self.is_finished() {
+            self.cursor += 1;
+            self.sync_position();
+        }
+    }
+
+    /// Pause and hide the last shown event
+    pub fn step_back(&mut self) {
+        self.playing = false;
+        self.cursor = self.cursor.saturating_sub(1);
+        self.sync_position();
+    }
+
+    /// Go back to before the first event
+    pub fn restart(&mut self) {
+        self.cursor = 0;
+        self.position = Duration::ZERO;
+    }
+
+    /// Move the clock to the last shown event after stepping
+    fn sync_position(&mut self) {
+        self.position = match self.cursor {
+            0 => Duration::ZERO,
+            n => self.offsets[n - 1],
+        };
+    }
+}
@@ -43,6 +43,16 @@ impl Migrations {
             },
         );
 
+        migrations.insert(
+            "002",
+            Migration {
+                version: "002",
+                description: "Project index columns and features",
+                up: crate::infrastructure::db::schema::create_feature_tables_sql(),
+                down: crate::infrastructure::db::schema::drop_feature_tables_sql(),
+            },
+        );
+
         Self { migrations }
     }
 
@@ -88,6 +88,20 @@ impl DatabaseManager {
             .ok_or_else(|| anyhow::anyhow!("Database not initialized. Call initialize() first."))
     }
 
+    /// Close the connection pool, waiting for in-flight queries to finish
+    ///
+    /// The manager can be initialized again afterwards.
+    pub async fn close(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            pool.close().await;
+        }
+    }
+
+    /// Path to the database file
+    pub fn path(&self) -> &Path {
+        &self.config.path
+    }
+
     /// Create a new connection pool
     async fn create_pool(&self) -> Result<SqlitePool> {
         crate::infrastructure::db::connection::validate_database_path(&self.config.path)?;
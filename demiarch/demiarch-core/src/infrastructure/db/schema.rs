@@ -1,7 +1,7 @@
 //! Database schema definitions and SQL statements
 
 /// Database schema version
-pub const SCHEMA_VERSION: &str = "001";
+pub const SCHEMA_VERSION: &str = "002";
 
 /// Create all database tables
 pub fn create_tables_sql() -> &'static str {
@@ -154,6 +154,40 @@ pub fn create_tables_sql() -> &'static str {
     "#
 }
 
+/// Add the project index columns and feature table the GUI reads
+pub fn create_feature_tables_sql() -> &'static str {
+    r#"
+    ALTER TABLE projects ADD COLUMN framework TEXT NOT NULL DEFAULT '';
+    ALTER TABLE projects ADD COLUMN repo_url TEXT NOT NULL DEFAULT '';
+
+    -- Features table - stores planned work shown on the kanban board
+    CREATE TABLE IF NOT EXISTS features (
+        id TEXT PRIMARY KEY NOT NULL,
+        project_id TEXT NOT NULL,
+        title TEXT NOT NULL,
+        description TEXT,
+        status TEXT NOT NULL DEFAULT 'backlog',
+        priority INTEGER NOT NULL DEFAULT 0,
+        labels TEXT,
+        acceptance_criteria TEXT,
+        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_features_project_id ON features(project_id);
+    "#
+}
+
+/// Revert [`create_feature_tables_sql`]
+pub fn drop_feature_tables_sql() -> &'static str {
+    r#"
+    DROP TABLE IF EXISTS features;
+    ALTER TABLE projects DROP COLUMN repo_url;
+    ALTER TABLE projects DROP COLUMN framework;
+    "#
+}
+
 /// Drop all database tables (for testing/reset purposes)
 pub fn drop_tables_sql() -> &'static str {
     r#"
@@ -198,7 +232,7 @@ mod tests {
 
     #[test]
     fn test_schema_version() {
-        assert_eq!(SCHEMA_VERSION, "001");
+        assert_eq!(SCHEMA_VERSION, "002");
     }
 
     #[test]
@@ -3,24 +3,64 @@
 //! Tauri command implementations and application entry point
 //! for the Demiarch GUI interface.
 
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::PathBuf;
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
+use demiarch_core::db::{
+    create_connection_options, ensure_database_directory, ensure_secure_permissions,
+    get_schema_version, initialize_project_database, run_migrations as apply_migrations,
+    DatabaseManager, DatabaseUtils,
+};
 use serde::{Deserialize, Serialize};
 use sqlx::{sqlite::SqlitePoolOptions, FromRow, Row, SqlitePool};
-use tauri::{Emitter, Manager};
+use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
 use std::sync::Arc;
 
+/// Label prefix for windows opened on a single project
+const PROJECT_WINDOW_PREFIX: &str = "project-";
+
+/// A per-project database and the windows that have the project open
+struct ProjectDatabase {
+    manager: DatabaseManager,
+    windows: HashSet<String>,
+}
+
+/// State of the global database, reported to the frontend for first-run setup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum DbStatus {
+    /// No database file exists yet
+    Missing,
+    /// The database is being created or migrated
+    Migrating,
+    /// Connected and up to date
+    Ready {
+        path: String,
+        /// Schema version, or `None` when the CLI manages the schema
+        schema_version: Option<String>,
+    },
+    /// Setup failed; `run_migrations` retries it
+    Error { message: String },
+}
+
 /// Application state shared across all windows
+///
+/// The global pool holds the project index. Each project's features and
+/// checkpoints are in its own database, opened when the first window selects
+/// the project and closed when the last window using it switches away or
+/// closes.
 pub struct AppState {
     pub initialized: std::sync::atomic::AtomicBool,
     pub pool: Arc<RwLock<Option<SqlitePool>>>,
+    pub db_status: Arc<RwLock<DbStatus>>,
+    projects: Arc<RwLock<HashMap<String, ProjectDatabase>>>,
 }
 
 impl Default for AppState {
@@ -28,6 +68,8 @@ impl Default for AppState {
         Self {
             initialized: std::sync::atomic::AtomicBool::new(false),
             pool: Arc::new(RwLock::new(None)),
+            db_status: Arc::new(RwLock::new(DbStatus::Missing)),
+            projects: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 }
@@ -40,6 +82,81 @@ impl std::fmt::Debug for AppState {
     }
 }
 
+impl AppState {
+    /// Point a window at a project, opening the project's database if needed
+    ///
+    /// The window's previous project is released first.
+    async fn attach_window(&self, window: &str, project_id: &str) -> Result<(), String> {
+        DatabaseUtils::validate_project_id(project_id)
+            .map_err(|e| format!("Invalid project id: {}", e))?;
+
+        let mut projects = self.projects.write().await;
+        let previous = projects
+            .iter()
+            .find(|(id, db)| id.as_str() != project_id && db.windows.contains(window))
+            .map(|(id, _)| id.clone());
+        if let Some(previous) = previous {
+            release_window(&mut projects, &previous, window).await;
+        }
+
+        if !projects.contains_key(project_id) {
+            let manager = initialize_project_database(project_id)
+                .await
+                .map_err(|e| format!("Failed to open project database: {}", e))?;
+            projects.insert(
+                project_id.to_string(),
+                ProjectDatabase {
+                    manager,
+                    windows: HashSet::new(),
+                },
+            );
+        }
+        if let Some(db) = projects.get_mut(project_id) {
+            db.windows.insert(window.to_string());
+        }
+        Ok(())
+    }
+
+    /// Release whatever project a window has open
+    ///
+    /// Returns the project id, if the window had one.
+    async fn detach_window(&self, window: &str) -> Option<String> {
+        let mut projects = self.projects.write().await;
+        let project_id = projects
+            .iter()
+            .find(|(_, db)| db.windows.contains(window))
+            .map(|(id, _)| id.clone())?;
+        release_window(&mut projects, &project_id, window).await;
+        Some(project_id)
+    }
+
+    /// Connection pool of an open project
+    async fn project_pool(&self, project_id: &str) -> Result<SqlitePool, String> {
+        let projects = self.projects.read().await;
+        let db = projects
+            .get(project_id)
+            .ok_or_else(|| format!("Project {} is not open", project_id))?;
+        db.manager.pool().cloned().map_err(|e| e.to_string())
+    }
+}
+
+/// Remove a window from a project, closing the pool if it was the last one
+async fn release_window(
+    projects: &mut HashMap<String, ProjectDatabase>,
+    project_id: &str,
+    window: &str,
+) {
+    let Some(db) = projects.get_mut(project_id) else {
+        return;
+    };
+    db.windows.remove(window);
+    if db.windows.is_empty() {
+        if let Some(mut db) = projects.remove(project_id) {
+            db.manager.close().await;
+        }
+    }
+}
+
 /// Get the default demiarch database path
 fn get_db_path() -> PathBuf {
     dirs::config_dir()
@@ -48,6 +165,60 @@ fn get_db_path() -> PathBuf {
         .join("demiarch.db")
 }
 
+/// Record a database status change and tell every window about it
+async fn set_db_status(app: &tauri::AppHandle, status: DbStatus) {
+    let state = app.state::<AppState>();
+    *state.db_status.write().await = status.clone();
+    let _ = app.emit("db-status", &status);
+}
+
+/// Open the global database, creating and migrating it on first run
+///
+/// A database created by the CLI carries the CLI's own `_migrations`
+/// table; the CLI keeps that schema up to date, so it is used as is.
+async fn initialize_database(app: &tauri::AppHandle) -> DbStatus {
+    let db_path = get_db_path();
+    set_db_status(app, DbStatus::Migrating).await;
+
+    let result: anyhow::Result<DbStatus> = async {
+        ensure_database_directory(&db_path).await?;
+        let options = create_connection_options(&db_path)?;
+        let pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .connect_with(options)
+            .await?;
+        ensure_secure_permissions(&db_path)?;
+
+        let cli_managed: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_migrations')",
+        )
+        .fetch_one(&pool)
+        .await?;
+        let schema_version = if cli_managed {
+            None
+        } else {
+            apply_migrations(&pool).await?;
+            get_schema_version(&pool).await?
+        };
+
+        let state = app.state::<AppState>();
+        if let Some(old) = state.pool.write().await.replace(pool) {
+            old.close().await;
+        }
+        Ok(DbStatus::Ready {
+            path: db_path.display().to_string(),
+            schema_version,
+        })
+    }
+    .await;
+
+    let status = result.unwrap_or_else(|e| DbStatus::Error {
+        message: e.to_string(),
+    });
+    set_db_status(app, status.clone()).await;
+    status
+}
+
 /// Application info returned to the frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppInfo {
@@ -80,6 +251,36 @@ pub struct ProjectInfo {
     pub updated_at: String,
 }
 
+/// A project with its database open in one or more windows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenProject {
+    pub project_id: String,
+    pub windows: Vec<String>,
+}
+
+/// Per-project database status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectDatabaseStatus {
+    pub project_id: String,
+    pub path: String,
+    pub is_healthy: bool,
+    pub table_count: u32,
+    pub connection_count: u32,
+    pub idle_connection_count: u32,
+}
+
+/// Checkpoint stored in a project's own database
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CheckpointInfo {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "type")]
+    #[sqlx(rename = "type")]
+    pub kind: String,
+    pub created_at: String,
+}
+
 /// Feature info returned to the frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureInfo {
@@ -203,6 +404,20 @@ fn health_check() -> HealthStatus {
     }
 }
 
+/// Current state of the global database
+#[tauri::command]
+async fn db_status(state: tauri::State<'_, AppState>) -> Result<DbStatus, String> {
+    Ok(state.db_status.read().await.clone())
+}
+
+/// Create the global database if needed and apply pending migrations
+///
+/// Used to retry setup after an error; returns the resulting status.
+#[tauri::command]
+async fn run_migrations(app: tauri::AppHandle) -> Result<DbStatus, String> {
+    Ok(initialize_database(&app).await)
+}
+
 /// Greet command for testing IPC
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -251,7 +466,12 @@ async fn get_project(
     let pool_guard: tokio::sync::RwLockReadGuard<'_, Option<SqlitePool>> = state.pool.read().await;
     let pool: &SqlitePool = pool_guard.as_ref().ok_or("Database not initialized")?;
 
-    let project: ProjectInfo = sqlx::query_as::<_, ProjectInfo>(
+    fetch_project(pool, &project_id).await
+}
+
+/// Look up a project in the global database
+async fn fetch_project(pool: &SqlitePool, project_id: &str) -> Result<ProjectInfo, String> {
+    sqlx::query_as::<_, ProjectInfo>(
         r#"
         SELECT
             id,
@@ -266,23 +486,129 @@ async fn get_project(
         WHERE id = ?
         "#,
     )
-    .bind(&project_id)
+    .bind(project_id)
     .fetch_optional(pool)
     .await
     .map_err(|e| format!("Failed to get project: {}", e))?
-    .ok_or("Project not found")?;
+    .ok_or_else(|| "Project not found".to_string())
+}
 
+/// Open a project in the calling window
+///
+/// Opens the project's own database unless another window already has it
+/// open, and releases the project the window showed before.
+#[tauri::command]
+async fn open_project(
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, AppState>,
+    project_id: String,
+) -> Result<ProjectInfo, String> {
+    let project = {
+        let pool_guard = state.pool.read().await;
+        let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+        fetch_project(pool, &project_id).await?
+    };
+    state.attach_window(window.label(), &project.id).await?;
     Ok(project)
 }
 
-/// List features for a project
+/// Close the project shown in the calling window
+#[tauri::command]
+async fn close_project(
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    Ok(state.detach_window(window.label()).await)
+}
+
+/// Open a project in its own window, or focus the window if it exists
+///
+/// Returns the window label.
+#[tauri::command]
+async fn open_project_window(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    project_id: String,
+) -> Result<String, String> {
+    let project = {
+        let pool_guard = state.pool.read().await;
+        let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+        fetch_project(pool, &project_id).await?
+    };
+    DatabaseUtils::validate_project_id(&project.id)
+        .map_err(|e| format!("Invalid project id: {}", e))?;
+
+    let label = format!("{}{}", PROJECT_WINDOW_PREFIX, project.id);
+    if let Some(window) = app.get_webview_window(&label) {
+        window.set_focus().map_err(|e| e.to_string())?;
+        return Ok(label);
+    }
+
+    state.attach_window(&label, &project.id).await?;
+    let url = format!("index.html?project={}", project.id);
+    if let Err(e) = WebviewWindowBuilder::new(&app, &label, WebviewUrl::App(url.into()))
+        .title(format!("Demiarch - {}", project.name))
+        .inner_size(1024.0, 768.0)
+        .min_inner_size(800.0, 600.0)
+        .build()
+    {
+        state.detach_window(&label).await;
+        return Err(format!("Failed to open project window: {}", e));
+    }
+    Ok(label)
+}
+
+/// List projects with an open database and the windows showing them
+#[tauri::command]
+async fn list_open_projects(state: tauri::State<'_, AppState>) -> Result<Vec<OpenProject>, String> {
+    let projects = state.projects.read().await;
+    let mut open: Vec<OpenProject> = projects
+        .iter()
+        .map(|(id, db)| {
+            let mut windows: Vec<String> = db.windows.iter().cloned().collect();
+            windows.sort();
+            OpenProject {
+                project_id: id.clone(),
+                windows,
+            }
+        })
+        .collect();
+    open.sort_by(|a, b| a.project_id.cmp(&b.project_id));
+    Ok(open)
+}
+
+/// Health of an open project's database
+#[tauri::command]
+async fn project_database_status(
+    state: tauri::State<'_, AppState>,
+    project_id: String,
+) -> Result<ProjectDatabaseStatus, String> {
+    let projects = state.projects.read().await;
+    let db = projects
+        .get(&project_id)
+        .ok_or_else(|| format!("Project {} is not open", project_id))?;
+    let health = db
+        .manager
+        .health_check()
+        .await
+        .map_err(|e| format!("Failed to check project database: {}", e))?;
+    Ok(ProjectDatabaseStatus {
+        project_id,
+        path: db.manager.path().display().to_string(),
+        is_healthy: health.is_healthy,
+        table_count: health.table_count,
+        connection_count: health.connection_count,
+        idle_connection_count: health.idle_connection_count,
+    })
+}
+
+/// List features from an open project's database
 #[tauri::command]
 async fn list_features(
     state: tauri::State<'_, AppState>,
     project_id: String,
 ) -> Result<Vec<FeatureInfo>, String> {
-    let pool_guard: tokio::sync::RwLockReadGuard<'_, Option<SqlitePool>> = state.pool.read().await;
-    let pool: &SqlitePool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let pool = state.project_pool(&project_id).await?;
 
     let rows = sqlx::query(
         r#"
@@ -303,7 +629,7 @@ async fn list_features(
         "#,
     )
     .bind(&project_id)
-    .fetch_all(pool)
+    .fetch_all(&pool)
     .await
     .map_err(|e| format!("Failed to list features: {}", e))?;
 
@@ -334,15 +660,15 @@ async fn list_features(
     Ok(features)
 }
 
-/// Update a feature's status
+/// Update a feature's status in an open project's database
 #[tauri::command]
 async fn update_feature_status(
     state: tauri::State<'_, AppState>,
+    project_id: String,
     feature_id: String,
     status: String,
 ) -> Result<(), String> {
-    let pool_guard: tokio::sync::RwLockReadGuard<'_, Option<SqlitePool>> = state.pool.read().await;
-    let pool: &SqlitePool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let pool = state.project_pool(&project_id).await?;
 
     // Validate status
     let valid_statuses = ["backlog", "todo", "in_progress", "review", "done"];
@@ -353,22 +679,48 @@ async fn update_feature_status(
         ));
     }
 
-    sqlx::query(
+    let result = sqlx::query(
         r#"
         UPDATE features
         SET status = ?, updated_at = datetime('now')
-        WHERE id = ?
+        WHERE id = ? AND project_id = ?
         "#,
     )
     .bind(&status)
     .bind(&feature_id)
-    .execute(pool)
+    .bind(&project_id)
+    .execute(&pool)
     .await
     .map_err(|e| format!("Failed to update feature status: {}", e))?;
 
+    if result.rows_affected() == 0 {
+        return Err("Feature not found in this project".to_string());
+    }
     Ok(())
 }
 
+/// List checkpoints from an open project's database, newest first
+#[tauri::command]
+async fn list_project_checkpoints(
+    state: tauri::State<'_, AppState>,
+    project_id: String,
+) -> Result<Vec<CheckpointInfo>, String> {
+    let pool = state.project_pool(&project_id).await?;
+
+    sqlx::query_as::<_, CheckpointInfo>(
+        r#"
+        SELECT id, name, description, type, datetime(created_at) as created_at
+        FROM checkpoints
+        WHERE project_id = ?
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(&project_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to list checkpoints: {}", e))
+}
+
 // ============================================================================
 // Conflict Resolution Commands
 // ============================================================================
@@ -521,9 +873,17 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             get_app_info,
             health_check,
+            db_status,
+            run_migrations,
             greet,
             list_projects,
             get_project,
+            open_project,
+            close_project,
+            open_project_window,
+            list_open_projects,
+            project_database_status,
+            list_project_checkpoints,
             list_features,
             update_feature_status,
             check_for_conflicts,
@@ -534,41 +894,27 @@ pub fn run() {
             start_agent_watcher,
             get_recent_agent_events,
         ])
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                // Release the window's project so its pool closes with the last window
+                let label = window.label().to_string();
+                let app = window.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    app.state::<AppState>().detach_window(&label).await;
+                });
+            }
+        })
         .setup(|app| {
             let state = app.state::<AppState>();
             state
                 .initialized
                 .store(true, std::sync::atomic::Ordering::SeqCst);
 
-            // Initialize database connection - clone the Arc
-            let pool_arc = Arc::clone(&state.pool);
-            std::thread::spawn(move || {
-                let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
-                rt.block_on(async {
-                    let db_path = get_db_path();
-
-                    if !db_path.exists() {
-                        eprintln!("Database not found at {:?}", db_path);
-                        return;
-                    }
-
-                    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
-
-                    match SqlitePoolOptions::new()
-                        .max_connections(5)
-                        .connect(&db_url)
-                        .await
-                    {
-                        Ok(pool) => {
-                            let mut pool_guard = pool_arc.write().await;
-                            *pool_guard = Some(pool);
-                            eprintln!("Database connected: {:?}", db_path);
-                        }
-                        Err(e) => {
-                            eprintln!("Failed to connect to database: {}", e);
-                        }
-                    }
-                });
+            // Create, migrate and connect the global database in the background;
+            // the frontend follows progress through `db-status` events
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                initialize_database(&handle).await;
             });
 
             #[cfg(debug_assertions)]
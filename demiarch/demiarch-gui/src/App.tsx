@@ -1,7 +1,7 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { KanbanBoard } from "./components/KanbanBoard";
-import { ProjectProvider, useProjects } from "./contexts/ProjectContext";
+import { ProjectProvider, useProjects, type FeatureStatus } from "./contexts/ProjectContext";
 import { AgentProvider, useAgents } from "./contexts/AgentContext";
 import { ConflictProvider, useConflicts } from "./contexts/ConflictContext";
 import { ProjectSelector } from "./components/ProjectSelector";
@@ -9,6 +9,7 @@ import { ProjectProgress } from "./components/ProjectProgress";
 import { AgentRadar } from "./components/AgentRadar";
 import { AgentStatusPanel } from "./components/AgentStatus";
 import { ConflictPanel } from "./components/ConflictPanel";
+import { DatabaseSetup } from "./components/DatabaseSetup";
 import "./App.css";
 import "./components/AgentStatus/AgentStatus.css";
 import "./components/ConflictPanel/ConflictPanel.css";
@@ -24,13 +25,22 @@ interface HealthStatus {
   message: string;
 }
 
+/** Feature status for each default board column */
+const COLUMN_STATUSES: Record<string, FeatureStatus> = {
+  backlog: "backlog",
+  todo: "todo",
+  "in-progress": "in_progress",
+  review: "review",
+  done: "done",
+};
+
 function AppContent() {
   const [appInfo, setAppInfo] = useState<AppInfo | null>(null);
   const [healthStatus, setHealthStatus] = useState<HealthStatus | null>(null);
   const [isAppLoading, setIsAppLoading] = useState(true);
   const [showAgentRadar, setShowAgentRadar] = useState(false);
   const [showAgentPanel, setShowAgentPanel] = useState(false);
-  const { currentProject, updateProjectBoard, isLoading: isProjectsLoading, error: projectsError, refreshProjects } = useProjects();
+  const { currentProject, updateProjectBoard, updateFeatureStatus, isLoading: isProjectsLoading, error: projectsError, refreshProjects } = useProjects();
   const { checkForConflicts, isPanelVisible, setPanelVisible, summary } = useConflicts();
   const { agents } = useAgents();
 
@@ -55,6 +65,15 @@ function AppContent() {
     initialize();
   }, []);
 
+  // Moving a card between the default columns changes the feature's status
+  const handleCardColumnChange = (cardId: string, columnId: string) => {
+    const status = COLUMN_STATUSES[columnId];
+    if (!status) return;
+    updateFeatureStatus(cardId, status).catch((error) =>
+      console.error("Failed to update feature status:", error)
+    );
+  };
+
   // Calculate conflict count
   const conflictCount = summary ? summary.modifiedFiles.length + summary.deletedFiles.length : 0;
 
@@ -145,6 +164,7 @@ function AppContent() {
               key={currentProject.id}
               initialBoard={currentProject.board}
               onBoardChange={updateProjectBoard}
+              onCardColumnChange={handleCardColumnChange}
             />
           </>
         ) : (
@@ -203,13 +223,15 @@ function AppContent() {
 
 function App() {
   return (
-    <ProjectProvider>
-      <AgentProvider>
-        <ConflictProvider>
-          <AppContent />
-        </ConflictProvider>
-      </AgentProvider>
-    </ProjectProvider>
+    <DatabaseSetup>
+      <ProjectProvider>
+        <AgentProvider>
+          <ConflictProvider>
+            <AppContent />
+          </ConflictProvider>
+        </AgentProvider>
+      </ProjectProvider>
+    </DatabaseSetup>
   );
 }
 
@@ -0,0 +1,73 @@
+import { useEffect, useState, type ReactNode } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+/** Mirrors the backend `DbStatus` */
+export type DbStatus =
+  | { state: 'missing' }
+  | { state: 'migrating' }
+  | { state: 'ready'; path: string; schema_version: string | null }
+  | { state: 'error'; message: string };
+
+interface DatabaseSetupProps {
+  children: ReactNode;
+}
+
+/**
+ * First-run gate: shows database setup progress and errors, and renders
+ * its children once the database is ready.
+ */
+export function DatabaseSetup({ children }: DatabaseSetupProps) {
+  const [status, setStatus] = useState<DbStatus | null>(null);
+  const [isRetrying, setIsRetrying] = useState(false);
+
+  useEffect(() => {
+    const unlisten = listen<DbStatus>('db-status', (event) => setStatus(event.payload));
+    invoke<DbStatus>('db_status')
+      .then(setStatus)
+      .catch((error) => setStatus({ state: 'error', message: String(error) }));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  async function retry() {
+    setIsRetrying(true);
+    try {
+      setStatus(await invoke<DbStatus>('run_migrations'));
+    } catch (error) {
+      setStatus({ state: 'error', message: String(error) });
+    } finally {
+      setIsRetrying(false);
+    }
+  }
+
+  if (status?.state === 'ready') {
+    return <>{children}</>;
+  }
+
+  if (status?.state === 'error') {
+    return (
+      <div className="app-loading">
+        <p className="error-text">Could not set up the Demiarch database</p>
+        <p className="hint-text">{status.message}</p>
+        <button className="retry-btn" onClick={retry} disabled={isRetrying}>
+          {isRetrying ? 'Retrying...' : 'Retry'}
+        </button>
+      </div>
+    );
+  }
+
+  return (
+    <div className="app-loading">
+      <div className="spinner" />
+      <p>
+        {status?.state === 'migrating'
+          ? 'Setting up the database...'
+          : 'Preparing Demiarch for first use...'}
+      </p>
+    </div>
+  );
+}
+
+export default DatabaseSetup;
@@ -0,0 +1,3 @@
+export { DatabaseSetup } from './DatabaseSetup';
+export type { DbStatus } from './DatabaseSetup';
+export { default } from './DatabaseSetup';
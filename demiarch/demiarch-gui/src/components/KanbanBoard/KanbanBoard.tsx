@@ -11,6 +11,8 @@ import './KanbanBoard.css';
 interface KanbanBoardProps {
   initialBoard?: KanbanBoardType;
   onBoardChange?: (board: KanbanBoardType) => void;
+  /** Called when a card is moved to a different column */
+  onCardColumnChange?: (cardId: string, columnId: string) => void;
 }
 
 // Create default board with sample data
@@ -76,7 +78,7 @@ function createDefaultBoard(): KanbanBoardType {
   };
 }
 
-export function KanbanBoard({ initialBoard, onBoardChange }: KanbanBoardProps) {
+export function KanbanBoard({ initialBoard, onBoardChange, onCardColumnChange }: KanbanBoardProps) {
   const [board, setBoard] = useState<KanbanBoardType>(
     initialBoard ?? createDefaultBoard()
   );
@@ -144,6 +146,7 @@ export function KanbanBoard({ initialBoard, onBoardChange }: KanbanBoardProps) {
     });
 
     updateBoard({ ...board, columns: newColumns });
+    onCardColumnChange?.(cardId, targetColumnId);
   };
 
   const handleAddCard = (columnId: string, title: string) => {
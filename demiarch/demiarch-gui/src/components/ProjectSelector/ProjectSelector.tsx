@@ -3,7 +3,7 @@ import { useProjects } from '../../contexts/ProjectContext';
 import './ProjectSelector.css';
 
 export function ProjectSelector() {
-  const { projects, currentProject, selectProject } = useProjects();
+  const { projects, currentProject, selectProject, openProjectIds, openProjectWindow } = useProjects();
   const [isOpen, setIsOpen] = useState(false);
   const dropdownRef = useRef<HTMLDivElement>(null);
 
@@ -38,6 +38,11 @@ export function ProjectSelector() {
     setIsOpen(false);
   };
 
+  const handleOpenWindow = (projectId: string) => {
+    openProjectWindow(projectId).catch((e) => console.error('Failed to open project window:', e));
+    setIsOpen(false);
+  };
+
   if (projects.length === 0) {
     return (
       <div className="project-selector project-selector--empty">
@@ -92,11 +97,24 @@ export function ProjectSelector() {
                   <span className="project-selector__option-description">{project.description}</span>
                 )}
               </div>
+              {project.id !== currentProject?.id && openProjectIds.includes(project.id) && (
+                <span className="project-selector__open-elsewhere" title="Open in another window">
+                  &#9679;
+                </span>
+              )}
               {project.id === currentProject?.id && (
                 <span className="project-selector__check">&#10003;</span>
               )}
             </button>
           ))}
+          {currentProject && (
+            <button
+              className="project-selector__action"
+              onClick={() => handleOpenWindow(currentProject.id)}
+            >
+              Open {currentProject.name} in a new window
+            </button>
+          )}
         </div>
       )}
     </div>
@@ -1,4 +1,5 @@
-import { createContext, useContext, useState, useCallback, type ReactNode } from 'react';
+import { createContext, useContext, useState, useCallback, useEffect, type ReactNode } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import type { Project } from '../types/project';
 import { createProject as createProjectHelper, PROJECT_COLORS } from '../types/project';
 import type { KanbanBoard } from '../components/KanbanBoard/types';
@@ -11,10 +12,27 @@ interface ProjectContextValue {
   updateProject: (projectId: string, updates: Partial<Omit<Project, 'id' | 'createdAt'>>) => void;
   deleteProject: (projectId: string) => void;
   updateProjectBoard: (board: KanbanBoard) => void;
+  /** Projects whose database is open in some window */
+  openProjectIds: string[];
+  openProjectWindow: (projectId: string) => Promise<void>;
+  updateFeatureStatus: (featureId: string, status: FeatureStatus) => Promise<void>;
+}
+
+/** Feature statuses accepted by `update_feature_status` */
+export type FeatureStatus = 'backlog' | 'todo' | 'in_progress' | 'review' | 'done';
+
+interface OpenProject {
+  project_id: string;
+  windows: string[];
 }
 
 const ProjectContext = createContext<ProjectContextValue | null>(null);
 
+/** Project a dedicated project window was opened on (`?project=<id>`) */
+function windowProjectId(): string | null {
+  return new URLSearchParams(window.location.search).get('project');
+}
+
 interface ProjectProviderProps {
   children: ReactNode;
   initialProjects?: Project[];
@@ -23,11 +41,49 @@ interface ProjectProviderProps {
 export function ProjectProvider({ children, initialProjects = [] }: ProjectProviderProps) {
   const [projects, setProjects] = useState<Project[]>(initialProjects);
   const [currentProjectId, setCurrentProjectId] = useState<string | null>(
-    initialProjects.length > 0 ? initialProjects[0].id : null
+    () => windowProjectId() ?? (initialProjects.length > 0 ? initialProjects[0].id : null)
   );
 
+  const [openProjectIds, setOpenProjectIds] = useState<string[]>([]);
+
   const currentProject = projects.find((p) => p.id === currentProjectId) ?? null;
 
+  const refreshOpenProjects = useCallback(async () => {
+    try {
+      const open = await invoke<OpenProject[]>('list_open_projects');
+      setOpenProjectIds(open.map((p) => p.project_id));
+    } catch (e) {
+      console.warn('Failed to list open projects:', e);
+    }
+  }, []);
+
+  // Open the selected project's database for this window; the backend
+  // releases the project the window showed before
+  useEffect(() => {
+    const command = currentProjectId
+      ? invoke('open_project', { projectId: currentProjectId })
+      : invoke('close_project');
+    command
+      .catch((e) => console.warn('Failed to switch project database:', e))
+      .finally(refreshOpenProjects);
+  }, [currentProjectId, refreshOpenProjects]);
+
+  const openProjectWindow = useCallback(
+    async (projectId: string) => {
+      await invoke<string>('open_project_window', { projectId });
+      await refreshOpenProjects();
+    },
+    [refreshOpenProjects]
+  );
+
+  const updateFeatureStatus = useCallback(
+    async (featureId: string, status: FeatureStatus) => {
+      if (!currentProjectId) return;
+      await invoke('update_feature_status', { projectId: currentProjectId, featureId, status });
+    },
+    [currentProjectId]
+  );
+
   const selectProject = useCallback((projectId: string) => {
     setCurrentProjectId(projectId);
   }, []);
@@ -97,6 +153,9 @@ export function ProjectProvider({ children, initialProjects = [] }: ProjectProvi
         updateProject,
         deleteProject,
         updateProjectBoard,
+        openProjectIds,
+        openProjectWindow,
+        updateFeatureStatus,
       }}
     >
       {children}
@@ -1,31 +1,71 @@
-import { Routes, Route } from 'react-router-dom';
+import { useEffect } from 'react';
+import { Routes, Route, useNavigate } from 'react-router-dom';
 import Layout from './components/Layout';
 import Dashboard from './pages/Dashboard';
 import Projects from './pages/Projects';
 import ProjectDetail from './pages/ProjectDetail';
 import Kanban from './pages/Kanban';
 import Agents from './pages/Agents';
+import Jobs from './pages/Jobs';
+import Usage from './pages/Usage';
+import Workspace from './pages/Workspace';
 import Settings from './pages/Settings';
 import ConflictResolution from './pages/ConflictResolution';
 import DemoTodo from './pages/DemoTodo';
 import ToastContainer from './components/ToastContainer';
+import ApprovalDialog from './components/ApprovalDialog';
+import { invoke, onDeepLink, onNotification } from './lib/api';
+import { useToastStore } from './stores/toastStore';
 
 function App() {
+  const addToast = useToastStore((state) => state.addToast);
+  const navigate = useNavigate();
+
+  useEffect(() => {
+    const unsubscribe = onNotification((notification) => {
+      const type =
+        notification.kind === 'generation_completed'
+          ? 'success'
+          : notification.kind === 'generation_failed'
+            ? 'error'
+            : 'warning';
+      addToast(`${notification.title}: ${notification.body}`, type, 8000);
+    });
+    return () => {
+      unsubscribe.then((unlisten) => unlisten());
+    };
+  }, [addToast]);
+
+  useEffect(() => {
+    const unsubscribe = onDeepLink(({ route }) => navigate(route));
+    // Links that launched the app arrive before this listener exists
+    invoke<string | null>('take_pending_deep_link')
+      .then((route) => route && navigate(route))
+      .catch(() => {});
+    return () => {
+      unsubscribe.then((unlisten) => unlisten());
+    };
+  }, [navigate]);
+
   return (
     <>
       <Routes>
         <Route path="/" element={<Layout />}>
           <Route index element={<Dashboard />} />
           <Route path="projects" element={<Projects />} />
+          <Route path="workspace" element={<Workspace />} />
           <Route path="projects/:projectId" element={<ProjectDetail />} />
           <Route path="projects/:projectId/kanban" element={<Kanban />} />
           <Route path="projects/:projectId/conflicts" element={<ConflictResolution />} />
           <Route path="projects/:projectId/conflicts/:conflictId" element={<ConflictResolution />} />
           <Route path="agents" element={<Agents />} />
+          <Route path="jobs" element={<Jobs />} />
+          <Route path="usage" element={<Usage />} />
           <Route path="settings" element={<Settings />} />
           <Route path="demo/todo" element={<DemoTodo />} />
         </Route>
       </Routes>
+      <ApprovalDialog />
       <ToastContainer />
     </>
   );
@@ -0,0 +1,106 @@
+/**
+ * Approval Dialog Component
+ *
+ * Shows generations waiting at an approval gate, oldest first, and sends
+ * the user's answer back. Requests that expire are dropped: the generation
+ * has already aborted.
+ */
+
+import { useEffect, useState } from 'react';
+import { ShieldAlert, Check, X } from 'lucide-react';
+import { ApprovalRequest, invoke, onApprovalRequested, toApiError } from '../lib/api';
+import { useToastStore } from '../stores/toastStore';
+
+const GATE_LABELS: Record<ApprovalRequest['gate'], string> = {
+  after_planning: 'Plan ready',
+  after_review: 'Review findings',
+};
+
+export default function ApprovalDialog() {
+  const addToast = useToastStore((state) => state.addToast);
+  const [requests, setRequests] = useState<ApprovalRequest[]>([]);
+  const [responding, setResponding] = useState(false);
+
+  useEffect(() => {
+    invoke<ApprovalRequest[]>('get_pending_approvals')
+      .then(setRequests)
+      .catch(() => {});
+    const unsubscribe = onApprovalRequested((request) =>
+      setRequests((current) => [...current.filter((r) => r.id !== request.id), request])
+    );
+    return () => {
+      unsubscribe.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  useEffect(() => {
+    const timer = setInterval(() => {
+      const now = Date.now();
+      setRequests((current) => current.filter((r) => new Date(r.expires_at).getTime() > now));
+    }, 5000);
+    return () => clearInterval(timer);
+  }, []);
+
+  const request = requests[0];
+  if (!request) return null;
+
+  async function respond(decision: 'approve' | 'reject') {
+    setResponding(true);
+    try {
+      await invoke('respond_to_approval', { requestId: request.id, decision });
+    } catch (error) {
+      addToast(toApiError(error).message, 'error');
+    } finally {
+      setRequests((current) => current.filter((r) => r.id !== request.id));
+      setResponding(false);
+    }
+  }
+
+  return (
+    <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50 p-4">
+      <div className="bg-background-mid border border-background-surface rounded-lg w-full max-w-xl max-h-[85vh] flex flex-col">
+        <div className="flex items-center gap-3 p-4 border-b border-background-surface">
+          <div className="w-10 h-10 rounded-full bg-accent-amber/20 flex items-center justify-center">
+            <ShieldAlert className="w-5 h-5 text-accent-amber" />
+          </div>
+          <div>
+            <h2 className="text-lg font-semibold text-white">
+              {GATE_LABELS[request.gate]}: {request.summary}
+            </h2>
+            <p className="text-sm text-gray-400">{request.generation}</p>
+          </div>
+        </div>
+
+        <ul className="flex-1 overflow-y-auto p-4 space-y-1 text-sm font-mono text-gray-300">
+          {request.details.map((detail, i) => (
+            <li key={i}>{detail}</li>
+          ))}
+        </ul>
+
+        <div className="flex justify-between items-center gap-3 p-4 border-t border-background-surface">
+          <span className="text-xs text-gray-500">
+            Aborts at {new Date(request.expires_at).toLocaleTimeString()}
+          </span>
+          <div className="flex gap-3">
+            <button
+              onClick={() => respond('reject')}
+              disabled={responding}
+              className="flex items-center gap-2 px-4 py-2 text-gray-300 hover:text-white transition-colors disabled:opacity-50"
+            >
+              <X className="w-4 h-4" />
+              Reject
+            </button>
+            <button
+              onClick={() => respond('approve')}
+              disabled={responding}
+              className="flex items-center gap-2 px-4 py-2 bg-accent-teal text-background-deep rounded-lg font-medium hover:bg-accent-teal/90 transition-colors disabled:opacity-50"
+            >
+              <Check className="w-4 h-4" />
+              Approve
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -1,6 +1,6 @@
-import { useState } from 'react';
-import { X, Plus, Calendar, Tag } from 'lucide-react';
-import { invoke, Feature } from '../lib/api';
+import { useRef, useState } from 'react';
+import { X, Plus, Calendar, Tag, Mic } from 'lucide-react';
+import { invoke, Feature, Transcript } from '../lib/api';
 import { useModalShortcuts } from '../hooks/useKeyboardShortcuts';
 
 interface FeatureCreateModalProps {
@@ -33,7 +33,9 @@ export default function FeatureCreateModal({ projectId, onClose, onCreated }: Fe
   const [tagInput, setTagInput] = useState('');
   const [tags, setTags] = useState<string[]>([]);
   const [saving, setSaving] = useState(false);
+  const [transcribing, setTranscribing] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  const audioInput = useRef<HTMLInputElement>(null);
 
   // Keyboard shortcuts
   useModalShortcuts(onClose);
@@ -57,6 +59,29 @@ export default function FeatureCreateModal({ projectId, onClose, onCreated }: Fe
     }
   }
 
+  async function handleAudio(e: React.ChangeEvent<HTMLInputElement>) {
+    const file = e.target.files?.[0];
+    e.target.value = '';
+    if (!file) return;
+
+    setTranscribing(true);
+    setError(null);
+    try {
+      const audio = Array.from(new Uint8Array(await file.arrayBuffer()));
+      const transcript = await invoke<Transcript>('transcribe_audio', {
+        file_name: file.name,
+        audio,
+        project_id: projectId,
+      });
+      setDescription((current) => (current.trim() ? `${current.trim()}\n\n${transcript.text}` : transcript.text));
+    } catch (err) {
+      console.error('Failed to transcribe voice note:', err);
+      setError(String(err));
+    } finally {
+      setTranscribing(false);
+    }
+  }
+
   async function handleSubmit(e: React.FormEvent) {
     e.preventDefault();
     if (!name.trim()) {
@@ -126,7 +151,25 @@ export default function FeatureCreateModal({ projectId, onClose, onCreated }: Fe
 
           {/* Description */}
           <div>
-            <label className="block text-sm text-gray-400 mb-1">Description</label>
+            <div className="flex justify-between items-center mb-1">
+              <label className="block text-sm text-gray-400">Description</label>
+              <button
+                type="button"
+                onClick={() => audioInput.current?.click()}
+                disabled={transcribing}
+                className="text-xs text-gray-400 hover:text-white transition-colors flex items-center gap-1 disabled:opacity-50"
+              >
+                <Mic className="w-3 h-3" />
+                {transcribing ? 'Transcribing...' : 'Voice note'}
+              </button>
+              <input
+                ref={audioInput}
+                type="file"
+                accept="audio/*,.m4a"
+                onChange={handleAudio}
+                className="hidden"
+              />
+            </div>
             <textarea
               value={description}
               onChange={(e) => setDescription(e.target.value)}
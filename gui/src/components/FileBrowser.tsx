@@ -0,0 +1,163 @@
+import { useCallback, useEffect, useState } from 'react';
+import { ChevronDown, ChevronRight, File, Folder } from 'lucide-react';
+import { invoke, type FileContent, type FileEntry } from '../lib/api';
+
+function formatSize(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`;
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+}
+
+interface TreeNodeProps {
+  projectId: string;
+  entry: FileEntry;
+  depth: number;
+  selected: string | null;
+  onSelect: (entry: FileEntry) => void;
+}
+
+function TreeNode({ projectId, entry, depth, selected, onSelect }: TreeNodeProps) {
+  const [expanded, setExpanded] = useState(false);
+  const [children, setChildren] = useState<FileEntry[] | null>(null);
+
+  async function toggle() {
+    if (!entry.is_dir) {
+      onSelect(entry);
+      return;
+    }
+    if (!expanded && children === null) {
+      try {
+        setChildren(
+          await invoke<FileEntry[]>('list_project_files', {
+            projectId,
+            subpath: entry.relative_path,
+          })
+        );
+      } catch {
+        setChildren([]);
+      }
+    }
+    setExpanded(!expanded);
+  }
+
+  return (
+    <li>
+      <button
+        onClick={toggle}
+        className={`w-full flex items-center gap-1.5 py-0.5 pr-2 rounded text-left text-sm truncate hover:bg-background-surface ${
+          selected === entry.path ? 'bg-background-surface text-white' : 'text-gray-300'
+        }`}
+        style={{ paddingLeft: `${depth * 12 + 4}px` }}
+      >
+        {entry.is_dir ? (
+          <>
+            {expanded ? (
+              <ChevronDown className="w-3 h-3 shrink-0 text-gray-500" />
+            ) : (
+              <ChevronRight className="w-3 h-3 shrink-0 text-gray-500" />
+            )}
+            <Folder className="w-4 h-4 shrink-0 text-accent-teal/80" />
+          </>
+        ) : (
+          <File className="w-4 h-4 shrink-0 ml-[18px] text-gray-500" />
+        )}
+        <span className="truncate">{entry.name}</span>
+      </button>
+      {expanded && children && (
+        <ul>
+          {children.map((child) => (
+            <TreeNode
+              key={child.path}
+              projectId={projectId}
+              entry={child}
+              depth={depth + 1}
+              selected={selected}
+              onSelect={onSelect}
+            />
+          ))}
+        </ul>
+      )}
+    </li>
+  );
+}
+
+/**
+ * File tree of the project directory with a read-only preview pane
+ */
+export default function FileBrowser({ projectId }: { projectId: string }) {
+  const [entries, setEntries] = useState<FileEntry[] | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [preview, setPreview] = useState<FileContent | null>(null);
+  const [previewError, setPreviewError] = useState<string | null>(null);
+
+  const load = useCallback(async () => {
+    try {
+      setEntries(await invoke<FileEntry[]>('list_project_files', { projectId }));
+      setError(null);
+    } catch (e) {
+      setEntries([]);
+      setError(e instanceof Error ? e.message : String(e));
+    }
+  }, [projectId]);
+
+  useEffect(() => {
+    load();
+  }, [load]);
+
+  async function select(entry: FileEntry) {
+    try {
+      setPreview(await invoke<FileContent>('read_project_file', { path: entry.path }));
+      setPreviewError(null);
+    } catch (e) {
+      setPreview(null);
+      setPreviewError(e instanceof Error ? e.message : String(e));
+    }
+  }
+
+  if (entries === null) {
+    return <div className="text-sm text-gray-400">Loading files...</div>;
+  }
+
+  if (error || entries.length === 0) {
+    return (
+      <div className="text-center py-12 text-gray-400">
+        <Folder className="w-12 h-12 mx-auto mb-3 opacity-50" />
+        <p>{error || 'No files in the project directory yet'}</p>
+      </div>
+    );
+  }
+
+  return (
+    <div className="flex gap-4 h-full min-h-[500px]">
+      <ul className="w-64 shrink-0 overflow-auto border-r border-background-surface pr-2">
+        {entries.map((entry) => (
+          <TreeNode
+            key={entry.path}
+            projectId={projectId}
+            entry={entry}
+            depth={0}
+            selected={preview?.entry.path ?? null}
+            onSelect={select}
+          />
+        ))}
+      </ul>
+      <div className="flex-1 min-w-0 overflow-auto">
+        {preview ? (
+          <>
+            <div className="flex items-center justify-between mb-2 text-sm text-gray-400">
+              <span className="font-mono text-gray-200 truncate">{preview.entry.relative_path}</span>
+              <span>{formatSize(preview.entry.size)}</span>
+            </div>
+            <pre className="bg-background-deep rounded-lg p-4 text-sm font-mono text-gray-200 overflow-auto">
+              {preview.content}
+            </pre>
+          </>
+        ) : (
+          <div className="text-sm text-gray-400">
+            {previewError || 'Select a file to preview it'}
+          </div>
+        )}
+      </div>
+    </div>
+  );
+}
@@ -2,15 +2,22 @@ import { Outlet, NavLink } from 'react-router-dom';
 import {
   LayoutDashboard,
   FolderKanban,
+  Layers,
   Bot,
+  Clock,
+  BarChart3,
   Settings,
   Sparkles
 } from 'lucide-react';
+import SessionControl from './SessionControl';
 
 const navItems = [
   { to: '/', icon: LayoutDashboard, label: 'Dashboard' },
   { to: '/projects', icon: FolderKanban, label: 'Projects' },
+  { to: '/workspace', icon: Layers, label: 'Workspace' },
   { to: '/agents', icon: Bot, label: 'Agents' },
+  { to: '/jobs', icon: Clock, label: 'Jobs' },
+  { to: '/usage', icon: BarChart3, label: 'Usage' },
   { to: '/settings', icon: Settings, label: 'Settings' },
 ];
 
@@ -57,9 +64,14 @@ export default function Layout() {
       </aside>
 
       {/* Main content */}
-      <main className="flex-1 overflow-auto">
-        <Outlet />
-      </main>
+      <div className="flex-1 flex flex-col min-w-0">
+        <header className="h-12 px-6 flex items-center justify-end border-b border-background-surface bg-background-mid">
+          <SessionControl />
+        </header>
+        <main className="flex-1 overflow-auto">
+          <Outlet />
+        </main>
+      </div>
     </div>
   );
 }
@@ -0,0 +1,90 @@
+import { useCallback, useEffect, useState } from 'react';
+import { Activity, ChevronDown, ChevronRight, RefreshCw } from 'lucide-react';
+import { invoke, type ProjectHealth } from '../lib/api';
+
+const statusStyles: Record<ProjectHealth['status'], string> = {
+  healthy: 'text-accent-teal',
+  warning: 'text-yellow-400',
+  critical: 'text-red-400',
+};
+
+/**
+ * Project health score with the signals that lowered it
+ */
+export default function ProjectHealthWidget({ projectId }: { projectId: string }) {
+  const [health, setHealth] = useState<ProjectHealth | null>(null);
+  const [expanded, setExpanded] = useState(false);
+
+  const load = useCallback(async () => {
+    try {
+      setHealth(await invoke<ProjectHealth>('get_project_health', { projectId }));
+    } catch {
+      setHealth(null);
+    }
+  }, [projectId]);
+
+  useEffect(() => {
+    load();
+  }, [load]);
+
+  if (!health) return null;
+
+  const hasSignals = health.signals.length > 0;
+
+  return (
+    <div className="mb-4 bg-background-mid rounded-lg border border-background-surface">
+      <div className="flex items-center justify-between px-4 py-3">
+        <button
+          onClick={() => setExpanded(!expanded)}
+          disabled={!hasSignals}
+          className="flex items-center gap-3 text-left disabled:cursor-default"
+        >
+          {hasSignals &&
+            (expanded ? (
+              <ChevronDown className="w-4 h-4 text-gray-400" />
+            ) : (
+              <ChevronRight className="w-4 h-4 text-gray-400" />
+            ))}
+          <Activity className={`w-4 h-4 ${statusStyles[health.status]}`} />
+          <span className="font-medium">Health</span>
+          <span className={`font-bold ${statusStyles[health.status]}`}>{health.score}/100</span>
+          <span className="text-sm text-gray-400">
+            {hasSignals
+              ? `${health.signals.length} issue${health.signals.length === 1 ? '' : 's'}`
+              : 'Nothing needs attention'}
+          </span>
+        </button>
+        <button
+          onClick={load}
+          className="p-1 rounded text-gray-400 hover:text-white hover:bg-background-surface"
+          title="Refresh"
+        >
+          <RefreshCw className="w-4 h-4" />
+        </button>
+      </div>
+
+      {expanded && hasSignals && (
+        <div className="border-t border-background-surface px-4 py-3 space-y-4">
+          {health.signals.map((signal) => (
+            <div key={signal.kind} className="text-sm">
+              <div className="flex items-center gap-2">
+                <span className="text-red-400 w-8">-{signal.penalty}</span>
+                <span className="text-gray-200">{signal.summary}</span>
+              </div>
+              {signal.items.length > 0 && (
+                <ul className="ml-10 mt-1 space-y-0.5 text-gray-400">
+                  {signal.items.map((item) => (
+                    <li key={item.id} className="truncate">
+                      <span className="text-gray-300">{item.title}</span> — {item.detail}
+                    </li>
+                  ))}
+                </ul>
+              )}
+              <div className="ml-10 mt-1 text-accent-teal/80">{signal.recommendation}</div>
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
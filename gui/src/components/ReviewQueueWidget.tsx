@@ -0,0 +1,109 @@
+import { useCallback, useEffect, useState } from 'react';
+import { useNavigate } from 'react-router-dom';
+import { ChevronDown, ChevronRight, Inbox, RefreshCw } from 'lucide-react';
+import { invoke, onApprovalRequested, type ReviewItem, type ReviewQueue } from '../lib/api';
+
+const kindLabels: Record<ReviewItem['kind'], string> = {
+  approval: 'Pending approvals',
+  conflict: 'Unresolved conflicts',
+  blocking_finding: 'Blocking findings',
+  failed_verification: 'Failed verifications',
+  feature_review: 'Features in review',
+};
+
+/**
+ * Everything in the project waiting for a person, grouped by kind
+ */
+export default function ReviewQueueWidget({ projectId }: { projectId: string }) {
+  const navigate = useNavigate();
+  const [queue, setQueue] = useState<ReviewQueue | null>(null);
+  const [expanded, setExpanded] = useState(false);
+
+  const load = useCallback(async () => {
+    try {
+      setQueue(await invoke<ReviewQueue>('get_review_queue', { projectId }));
+    } catch {
+      setQueue(null);
+    }
+  }, [projectId]);
+
+  useEffect(() => {
+    load();
+  }, [load]);
+
+  useEffect(() => {
+    const unsubscribe = onApprovalRequested(() => load());
+    return () => {
+      unsubscribe.then((unlisten) => unlisten());
+    };
+  }, [load]);
+
+  if (!queue) return null;
+
+  const hasItems = queue.total > 0;
+  const groups = (Object.keys(kindLabels) as ReviewItem['kind'][])
+    .map((kind) => ({ kind, items: queue.items.filter((item) => item.kind === kind) }))
+    .filter((group) => group.items.length > 0);
+
+  return (
+    <div className="mb-4 bg-background-mid rounded-lg border border-background-surface">
+      <div className="flex items-center justify-between px-4 py-3">
+        <button
+          onClick={() => setExpanded(!expanded)}
+          disabled={!hasItems}
+          className="flex items-center gap-3 text-left disabled:cursor-default"
+        >
+          {hasItems &&
+            (expanded ? (
+              <ChevronDown className="w-4 h-4 text-gray-400" />
+            ) : (
+              <ChevronRight className="w-4 h-4 text-gray-400" />
+            ))}
+          <Inbox className={`w-4 h-4 ${hasItems ? 'text-yellow-400' : 'text-accent-teal'}`} />
+          <span className="font-medium">Inbox</span>
+          <span className="text-sm text-gray-400">
+            {hasItems
+              ? `${queue.total} item${queue.total === 1 ? '' : 's'} awaiting review`
+              : 'Nothing awaiting review'}
+          </span>
+        </button>
+        <button
+          onClick={load}
+          className="p-1 rounded text-gray-400 hover:text-white hover:bg-background-surface"
+          title="Refresh"
+        >
+          <RefreshCw className="w-4 h-4" />
+        </button>
+      </div>
+
+      {expanded && hasItems && (
+        <div className="border-t border-background-surface px-4 py-3 space-y-4">
+          {groups.map((group) => (
+            <div key={group.kind} className="text-sm">
+              <div className="text-gray-200">
+                {kindLabels[group.kind]} ({group.items.length})
+              </div>
+              <ul className="ml-4 mt-1 space-y-0.5 text-gray-400">
+                {group.items.map((item) => (
+                  <li key={item.id} className="truncate">
+                    {item.route ? (
+                      <button
+                        onClick={() => navigate(item.route as string)}
+                        className="text-gray-300 hover:text-accent-teal hover:underline"
+                      >
+                        {item.title}
+                      </button>
+                    ) : (
+                      <span className="text-gray-300">{item.title}</span>
+                    )}{' '}
+                    — {item.detail}
+                  </li>
+                ))}
+              </ul>
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,102 @@
+import { useCallback, useEffect, useState } from 'react';
+import { Pause, Play, Square } from 'lucide-react';
+import { invoke, onSessionChanged, type Session } from '../lib/api';
+import { useToastStore } from '../stores/toastStore';
+
+const statusStyles: Record<Session['status'], string> = {
+  active: 'bg-accent-teal',
+  paused: 'bg-yellow-400',
+  completed: 'bg-gray-500',
+  abandoned: 'bg-gray-500',
+};
+
+/**
+ * Header control showing the active (or last paused) session
+ */
+export default function SessionControl() {
+  const [session, setSession] = useState<Session | null>(null);
+  const [busy, setBusy] = useState(false);
+  const addToast = useToastStore((state) => state.addToast);
+
+  const load = useCallback(async () => {
+    try {
+      setSession(await invoke<Session | null>('get_active_session'));
+    } catch {
+      setSession(null);
+    }
+  }, []);
+
+  useEffect(() => {
+    load();
+    const unsubscribe = onSessionChanged(({ session: changed }) => {
+      // Ended sessions drop out of the header; anything else is shown as is
+      setSession(
+        changed.status === 'completed' || changed.status === 'abandoned' ? null : changed
+      );
+    });
+    return () => {
+      unsubscribe.then((unlisten) => unlisten());
+    };
+  }, [load]);
+
+  const run = async (cmd: string, args?: Record<string, unknown>) => {
+    setBusy(true);
+    try {
+      await invoke<Session>(cmd, args);
+    } catch (error) {
+      addToast(error instanceof Error ? error.message : String(error), 'error');
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  if (!session) {
+    return (
+      <button
+        onClick={() => run('start_session')}
+        disabled={busy}
+        className="flex items-center gap-2 px-3 py-1.5 text-sm rounded-lg bg-accent-teal/20 text-accent-teal hover:bg-accent-teal/30 disabled:opacity-50"
+      >
+        <Play className="w-4 h-4" />
+        Start session
+      </button>
+    );
+  }
+
+  return (
+    <div className="flex items-center gap-3 text-sm">
+      <span className={`w-2 h-2 rounded-full ${statusStyles[session.status]}`} />
+      <span className="text-gray-300">
+        {session.description || `Session ${session.id.slice(0, 8)}`}
+      </span>
+      <span className="text-gray-500">{session.status}</span>
+      {session.status === 'active' ? (
+        <button
+          onClick={() => run('pause_session', { id: session.id })}
+          disabled={busy}
+          title="Pause session"
+          className="p-1.5 rounded hover:bg-background-surface text-gray-400 hover:text-white disabled:opacity-50"
+        >
+          <Pause className="w-4 h-4" />
+        </button>
+      ) : (
+        <button
+          onClick={() => run('resume_session', { id: session.id })}
+          disabled={busy}
+          title="Resume session"
+          className="p-1.5 rounded hover:bg-background-surface text-gray-400 hover:text-white disabled:opacity-50"
+        >
+          <Play className="w-4 h-4" />
+        </button>
+      )}
+      <button
+        onClick={() => run('end_session', { id: session.id })}
+        disabled={busy}
+        title="End session"
+        className="p-1.5 rounded hover:bg-background-surface text-gray-400 hover:text-white disabled:opacity-50"
+      >
+        <Square className="w-4 h-4" />
+      </button>
+    </div>
+  );
+}
@@ -0,0 +1,82 @@
+import { useEffect, useState } from 'react';
+import { AlertTriangle, TrendingUp } from 'lucide-react';
+import { invoke, type SpendForecast } from '../lib/api';
+
+function formatDay(day: string) {
+  return new Date(`${day}T00:00:00Z`).toLocaleDateString(undefined, {
+    month: 'short',
+    day: 'numeric',
+    timeZone: 'UTC',
+  });
+}
+
+/**
+ * Month-to-date spend and the end-of-month projection against the monthly limit
+ */
+export default function SpendForecastWidget({ projectId }: { projectId?: string }) {
+  const [forecast, setForecast] = useState<SpendForecast | null>(null);
+
+  useEffect(() => {
+    invoke<SpendForecast>('get_spend_forecast', { projectId })
+      .then(setForecast)
+      .catch(() => setForecast(null));
+  }, [projectId]);
+
+  if (!forecast) return null;
+
+  const hasLimit = forecast.monthly_limit_usd > 0;
+  const spentPct = hasLimit
+    ? Math.min(100, (forecast.month_to_date_usd / forecast.monthly_limit_usd) * 100)
+    : 0;
+  const projectedPct = hasLimit
+    ? Math.min(100, (forecast.projected_usd / forecast.monthly_limit_usd) * 100)
+    : 0;
+  const accent = forecast.over_limit ? 'text-red-400' : 'text-accent-teal';
+
+  return (
+    <div className="bg-background-mid rounded-lg border border-background-surface p-4">
+      <div className="flex items-center justify-between mb-3">
+        <div className="flex items-center gap-2">
+          <TrendingUp className={`w-4 h-4 ${accent}`} />
+          <h2 className="font-semibold">Spend Forecast</h2>
+        </div>
+        <span className="text-sm text-gray-400">
+          ${forecast.burn_rate_usd_per_day.toFixed(2)}/day over the last {forecast.window_days} days
+        </span>
+      </div>
+
+      <div className="flex items-baseline gap-2">
+        <span className={`text-2xl font-bold ${accent}`}>${forecast.projected_usd.toFixed(2)}</span>
+        <span className="text-sm text-gray-400">
+          projected by {formatDay(forecast.month_end)}
+          {hasLimit && ` of $${forecast.monthly_limit_usd.toFixed(2)}`}
+        </span>
+      </div>
+
+      {hasLimit && (
+        <div className="relative h-2 mt-3 rounded bg-background-surface overflow-hidden">
+          <div
+            className={`absolute inset-y-0 left-0 ${forecast.over_limit ? 'bg-red-400/40' : 'bg-accent-teal/30'}`}
+            style={{ width: `${projectedPct}%` }}
+          />
+          <div
+            className={`absolute inset-y-0 left-0 ${forecast.over_limit ? 'bg-red-400' : 'bg-accent-teal'}`}
+            style={{ width: `${spentPct}%` }}
+          />
+        </div>
+      )}
+      <p className="mt-2 text-sm text-gray-400">
+        ${forecast.month_to_date_usd.toFixed(2)} spent since {formatDay(forecast.month_start)}
+      </p>
+
+      {forecast.over_limit && (
+        <div className="mt-3 flex items-center gap-2 text-sm text-red-400">
+          <AlertTriangle className="w-4 h-4" />
+          {forecast.limit_reached_on
+            ? `At this rate the monthly limit is reached around ${formatDay(forecast.limit_reached_on)}`
+            : 'Projected to exceed the monthly limit'}
+        </div>
+      )}
+    </div>
+  );
+}
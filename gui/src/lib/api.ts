@@ -4,6 +4,7 @@
  */
 
 import { invoke as tauriInvoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
 // Check if we're running in Tauri
 const isTauri = () => {
@@ -16,6 +17,7 @@ const STORAGE_KEYS = {
   features: 'demiarch_features',
   sessions: 'demiarch_sessions',
   agents: 'demiarch_agents',
+  analytics: 'demiarch_analytics_enabled',
 };
 
 // Project interface
@@ -140,12 +142,30 @@ function setStorage<T>(key: string, value: T): void {
   }
 }
 
+// Slice a stored list the way the backend pages it
+function paginate<T>(
+  items: T[],
+  args: Record<string, unknown> | undefined,
+  newestFirst = false,
+): Page<T> {
+  const limit = Math.max(1, Number(args?.limit ?? 50));
+  const offset = Math.max(0, Number(args?.offset ?? 0));
+  const [start, end] = newestFirst
+    ? [Math.max(0, items.length - offset - limit), Math.max(0, items.length - offset)]
+    : [offset, offset + limit];
+  return { items: items.slice(start, end), total: items.length, limit, offset };
+}
+
 // Mock implementations for when Tauri is not available
 const mockHandlers: Record<string, (args?: Record<string, unknown>) => unknown> = {
   get_projects: () => {
     return getStorage(STORAGE_KEYS.projects, []);
   },
 
+  get_projects_page: (args) => {
+    return paginate(getStorage(STORAGE_KEYS.projects, []), args);
+  },
+
   get_project: (args) => {
     const projects = getStorage<Array<{ id: string }>>(STORAGE_KEYS.projects, []);
     return projects.find((p) => p.id === args?.id) || null;
@@ -207,6 +227,15 @@ const mockHandlers: Record<string, (args?: Record<string, unknown>) => unknown>
     return features.filter((f) => f.project_id === projectId);
   },
 
+  get_features_page: (args) => {
+    const features = getStorage<Array<{ project_id: string }>>(STORAGE_KEYS.features, []);
+    const projectId = args?.project_id || args?.projectId;
+    return paginate(
+      features.filter((f) => f.project_id === projectId),
+      args,
+    );
+  },
+
   get_feature: (args) => {
     const features = getStorage<Array<{ id: string }>>(STORAGE_KEYS.features, []);
     return features.find((f) => f.id === args?.id) || null;
@@ -337,6 +366,74 @@ const mockHandlers: Record<string, (args?: Record<string, unknown>) => unknown>
     return getStorage(STORAGE_KEYS.sessions, []);
   },
 
+  get_active_session: () => {
+    const sessions = getStorage<Session[]>(STORAGE_KEYS.sessions, []);
+    return sessions.find((s) => s.status === 'active') ?? null;
+  },
+
+  start_session: (args) => {
+    const sessions = getStorage<Session[]>(STORAGE_KEYS.sessions, []);
+    const now = new Date().toISOString();
+    sessions.forEach((s) => {
+      if (s.status === 'active') s.status = 'paused';
+    });
+    const session: Session = {
+      id: uuid(),
+      status: 'active',
+      phase: 'discovery',
+      description: (args?.description as string) || null,
+      current_project_id: (args?.projectId as string) || null,
+      current_feature_id: (args?.featureId as string) || null,
+      started_at: now,
+      last_activity: now,
+    };
+    sessions.unshift(session);
+    setStorage(STORAGE_KEYS.sessions, sessions);
+    return emitMockSessionChanged('started', session);
+  },
+
+  pause_session: (args) => {
+    return updateMockSession(args?.id as string | undefined, 'paused');
+  },
+
+  resume_session: (args) => {
+    const sessions = getStorage<Session[]>(STORAGE_KEYS.sessions, []);
+    sessions.forEach((s) => {
+      if (s.status === 'active' && s.id !== args?.id) s.status = 'paused';
+    });
+    setStorage(STORAGE_KEYS.sessions, sessions);
+    return updateMockSession(args?.id as string, 'active');
+  },
+
+  end_session: (args) => {
+    return updateMockSession(
+      args?.id as string | undefined,
+      args?.abandon ? 'abandoned' : 'completed'
+    );
+  },
+
+  get_jobs: () => {
+    // Jobs are queued and run by the CLI worker; nothing to show without it
+    return [];
+  },
+
+  cancel_job: (args) => {
+    throw new Error(`Job not found: ${args?.id as string}`);
+  },
+
+  get_generations: () => {
+    // Generations are recorded by the backend; there is no history without it
+    return [];
+  },
+
+  get_generation: (args) => {
+    throw new Error(`Generation not found: ${args?.id as string}`);
+  },
+
+  delete_generation: (args) => {
+    throw new Error(`Generation not found: ${args?.id as string}`);
+  },
+
   get_costs: () => {
     return {
       today_usd: 0.0,
@@ -346,6 +443,141 @@ const mockHandlers: Record<string, (args?: Record<string, unknown>) => unknown>
     };
   },
 
+  get_spend_forecast: (args) => {
+    // Without the backend there is no cost history to project from
+    const now = new Date();
+    const start = new Date(Date.UTC(now.getUTCFullYear(), now.getUTCMonth(), 1));
+    const end = new Date(Date.UTC(now.getUTCFullYear(), now.getUTCMonth() + 1, 0));
+    const forecast: SpendForecast = {
+      project_id: (args?.projectId as string) || null,
+      month_start: start.toISOString().slice(0, 10),
+      month_end: end.toISOString().slice(0, 10),
+      month_to_date_usd: 0,
+      burn_rate_usd_per_day: 0,
+      window_days: 7,
+      days_remaining: (end.getTime() + 86_400_000 - now.getTime()) / 86_400_000,
+      projected_usd: 0,
+      monthly_limit_usd: 10 * end.getUTCDate(),
+      over_limit: false,
+      limit_reached_on: null,
+      generated_at: now.toISOString(),
+    };
+    return forecast;
+  },
+
+  get_usage_stats: () => {
+    // Without the backend there is no local usage history to aggregate
+    const enabled = getStorage<boolean>(STORAGE_KEYS.analytics, false);
+    return { enabled, stats: null };
+  },
+
+  set_analytics_enabled: (args) => {
+    setStorage(STORAGE_KEYS.analytics, Boolean(args?.enabled));
+    return null;
+  },
+
+  get_project_health: (args) => {
+    // Without the backend there is no generation or cost history to check
+    const projects = getStorage<Project[]>(STORAGE_KEYS.projects, []);
+    const project = projects.find((p) => p.id === args?.projectId);
+    const health: ProjectHealth = {
+      project_id: (args?.projectId as string) || '',
+      project_name: project?.name || '',
+      score: 100,
+      status: 'healthy',
+      signals: [],
+      checked_at: new Date().toISOString(),
+    };
+    return health;
+  },
+
+  get_review_queue: (args) => {
+    // Without the backend there are no generations, findings or approvals
+    const projects = getStorage<Project[]>(STORAGE_KEYS.projects, []);
+    const project = projects.find((p) => p.id === args?.projectId);
+    const queue: ReviewQueue = {
+      project_id: (args?.projectId as string) || '',
+      project_name: project?.name || '',
+      counts: {
+        approvals: 0,
+        conflicts: 0,
+        blocking_findings: 0,
+        failed_verifications: 0,
+        features_in_review: 0,
+      },
+      total: 0,
+      items: [],
+    };
+    return queue;
+  },
+
+  get_workspace_overview: (args) => {
+    // Browser mode records no costs, sessions or generations, only features
+    const ids = (args?.projectIds as string[] | null) ?? [];
+    const projects = getStorage<Project[]>(STORAGE_KEYS.projects, []).filter((p) =>
+      ids.length > 0 ? ids.includes(p.id) || ids.includes(p.name) : p.status === 'active'
+    );
+    const features = getStorage<Feature[]>(STORAGE_KEYS.features, []);
+    const rollup = (projectId: string): WorkspaceRollup => {
+      const owned = features.filter((f) => f.project_id === projectId);
+      return {
+        cost_usd: 0,
+        llm_calls: 0,
+        tokens: 0,
+        sessions: 0,
+        active_sessions: 0,
+        features: owned.length,
+        features_in_progress: owned.filter((f) => f.status === 'in_progress').length,
+        features_done: owned.filter((f) => f.status === 'complete' || f.status === 'done').length,
+        generations: 0,
+        failed_generations: 0,
+        generation_tokens: 0,
+        active_secs: 0,
+      };
+    };
+    const breakdown: WorkspaceProject[] = projects.map((p) => ({
+      project_id: p.id,
+      project_name: p.name,
+      status: p.status,
+      ...rollup(p.id),
+    }));
+    const totals = rollup('');
+    for (const project of breakdown) {
+      totals.features += project.features;
+      totals.features_in_progress += project.features_in_progress;
+      totals.features_done += project.features_done;
+    }
+    const stats: WorkspaceStats = { projects: breakdown, totals };
+    return stats;
+  },
+
+  get_pending_approvals: () => {
+    // Browser mode runs no background generations to hold at a gate
+    return [] as ApprovalRequest[];
+  },
+
+  respond_to_approval: (args) => {
+    throw new Error(`No pending approval request '${args?.requestId}'`);
+  },
+
+  take_pending_deep_link: () => {
+    // Browser mode is never launched from a demiarch:// link
+    return null;
+  },
+
+  list_project_files: () => {
+    // Browser mode has no access to the project directory
+    return [] as FileEntry[];
+  },
+
+  read_project_file: (args) => {
+    throw new Error(`Cannot read ${args?.path} without the desktop app`);
+  },
+
+  file_metadata: (args) => {
+    throw new Error(`Cannot stat ${args?.path} without the desktop app`);
+  },
+
   get_agents: () => {
     return getStorage<AgentStatus[]>(STORAGE_KEYS.agents, []);
   },
@@ -418,10 +650,28 @@ const mockHandlers: Record<string, (args?: Record<string, unknown>) => unknown>
     };
   },
 
+  app_version: () => {
+    return 'dev';
+  },
+
+  check_for_update: () => {
+    // Browser builds are updated by redeploying, not by the app
+    return null;
+  },
+
+  transcribe_audio: () => {
+    throw new Error('Voice notes need the desktop app to reach a transcription backend');
+  },
+
   get_conflicts: () => {
     return [];
   },
 
+  get_translations: () => {
+    // Without the backend the UI uses its built-in English strings
+    return { locale: 'en', messages: {} };
+  },
+
   resolve_conflict_hunk: () => {
     return null;
   },
@@ -429,14 +679,491 @@ const mockHandlers: Record<string, (args?: Record<string, unknown>) => unknown>
   apply_conflict_resolutions: () => {
     return null;
   },
+
+  get_chat_history: () => {
+    // Stored conversations live in the backend database
+    return [];
+  },
+
+  get_chat_history_page: (args) => {
+    return paginate([], args, true);
+  },
+
+  get_agent_events_page: (args) => {
+    // Agent events are written by the CLI and read by the backend
+    return paginate([], args, true);
+  },
+
+  select_message_variant: () => {
+    return [];
+  },
+};
+
+// Development session
+export interface Session {
+  id: string;
+  status: 'active' | 'paused' | 'completed' | 'abandoned';
+  phase: string;
+  description: string | null;
+  current_project_id: string | null;
+  current_feature_id: string | null;
+  started_at: string;
+  last_activity: string;
+}
+
+// Payload of the session-changed event
+export interface SessionChanged {
+  action: 'started' | 'paused' | 'resumed' | 'completed' | 'abandoned';
+  session: Session;
+}
+
+const SESSION_CHANGED_EVENT = 'session-changed';
+
+function emitMockSessionChanged(action: SessionChanged['action'], session: Session): Session {
+  window.dispatchEvent(
+    new CustomEvent<SessionChanged>(SESSION_CHANGED_EVENT, { detail: { action, session } })
+  );
+  return session;
+}
+
+function updateMockSession(id: string | undefined, status: Session['status']): Session {
+  const sessions = getStorage<Session[]>(STORAGE_KEYS.sessions, []);
+  const session = id
+    ? sessions.find((s) => s.id === id)
+    : sessions.find((s) => s.status === 'active');
+  if (!session) {
+    throw new Error(id ? `Session not found: ${id}` : 'No active session');
+  }
+  session.status = status;
+  session.last_activity = new Date().toISOString();
+  setStorage(STORAGE_KEYS.sessions, sessions);
+
+  const action: SessionChanged['action'] = status === 'active' ? 'resumed' : status;
+  return emitMockSessionChanged(action, session);
+}
+
+/**
+ * Subscribe to session-changed events; returns an unsubscribe function
+ */
+export async function onSessionChanged(
+  handler: (event: SessionChanged) => void
+): Promise<() => void> {
+  if (isTauri()) {
+    return listen<SessionChanged>(SESSION_CHANGED_EVENT, (event) => handler(event.payload));
+  }
+
+  const listener = (event: Event) => handler((event as CustomEvent<SessionChanged>).detail);
+  window.addEventListener(SESSION_CHANGED_EVENT, listener);
+  return () => window.removeEventListener(SESSION_CHANGED_EVENT, listener);
+}
+
+// Announcement of a finished generation, budget trip or conflict
+export interface AppNotification {
+  kind:
+    | 'generation_completed'
+    | 'generation_failed'
+    | 'budget_exceeded'
+    | 'forecast_over_limit'
+    | 'conflicts_detected';
+  title: string;
+  body: string;
+  project_id: string | null;
+  generation_id: string | null;
+}
+
+const NOTIFICATION_EVENT = 'notification';
+
+/**
+ * Subscribe to notifications sent while a window has focus; returns an
+ * unsubscribe function. Unfocused windows get native notifications instead.
+ */
+export async function onNotification(
+  handler: (notification: AppNotification) => void
+): Promise<() => void> {
+  if (isTauri()) {
+    return listen<AppNotification>(NOTIFICATION_EVENT, (event) => handler(event.payload));
+  }
+
+  const listener = (event: Event) => handler((event as CustomEvent<AppNotification>).detail);
+  window.addEventListener(NOTIFICATION_EVENT, listener);
+  return () => window.removeEventListener(NOTIFICATION_EVENT, listener);
+}
+
+// A demiarch:// link opened from outside the app
+export interface DeepLinkOpened {
+  url: string;
+  route: string;
+}
+
+const DEEP_LINK_EVENT = 'deep-link';
+
+/**
+ * Subscribe to demiarch:// links opened while the app runs; returns an
+ * unsubscribe function
+ */
+export async function onDeepLink(handler: (link: DeepLinkOpened) => void): Promise<() => void> {
+  if (isTauri()) {
+    return listen<DeepLinkOpened>(DEEP_LINK_EVENT, (event) => handler(event.payload));
+  }
+
+  const listener = (event: Event) => handler((event as CustomEvent<DeepLinkOpened>).detail);
+  window.addEventListener(DEEP_LINK_EVENT, listener);
+  return () => window.removeEventListener(DEEP_LINK_EVENT, listener);
+}
+
+// A generation waiting at an approval gate
+export interface ApprovalRequest {
+  id: string;
+  gate: 'after_planning' | 'after_review';
+  generation: string;
+  summary: string;
+  details: string[];
+  requested_at: string;
+  expires_at: string;
+}
+
+const APPROVAL_EVENT = 'approval-requested';
+
+/**
+ * Subscribe to generations stopping at an approval gate; returns an
+ * unsubscribe function. Answer with `respond_to_approval`.
+ */
+export async function onApprovalRequested(
+  handler: (request: ApprovalRequest) => void
+): Promise<() => void> {
+  if (isTauri()) {
+    return listen<ApprovalRequest>(APPROVAL_EVENT, (event) => handler(event.payload));
+  }
+
+  const listener = (event: Event) => handler((event as CustomEvent<ApprovalRequest>).detail);
+  window.addEventListener(APPROVAL_EVENT, listener);
+  return () => window.removeEventListener(APPROVAL_EVENT, listener);
+}
+
+// Queued generation or document job
+export interface Job {
+  id: string;
+  kind: 'generate' | 'prd' | 'architecture';
+  summary: string;
+  status: 'queued' | 'running' | 'succeeded' | 'failed' | 'cancelled';
+  run_at: string;
+  attempts: number;
+  max_attempts: number;
+  last_error: string | null;
+  result_ref: string | null;
+  created_at: string;
+  finished_at: string | null;
+}
+
+// Recorded generation run
+export interface GenerationSummary {
+  id: string;
+  project_id: string | null;
+  feature_id: string | null;
+  session_id: string | null;
+  description: string;
+  status: 'running' | 'completed' | 'failed' | 'interrupted';
+  tokens_used: number;
+  cost_usd: number;
+  duration_ms: number;
+  file_count: number;
+  created_at: string;
+}
+
+export interface GenerationFile {
+  path: string;
+  language: string | null;
+  is_new: boolean;
+  decision: 'pending' | 'accepted' | 'rejected';
+  applied: boolean;
+  version: number;
+}
+
+export interface GenerationDetail extends GenerationSummary {
+  output_dir: string;
+  plan: { tasks: Array<{ id: string; description: string; status: string }> } | null;
+  environment: {
+    demiarch_version: string;
+    models: string[];
+    config: Record<string, string>;
+    prompt_hashes: Record<string, string>;
+    plugins: Record<string, string> | null;
+    captured_at: string;
+  } | null;
+  files: GenerationFile[];
+}
+
+// Text transcribed from a voice note
+export interface Transcript {
+  text: string;
+  backend: 'openai' | 'whisper_cpp';
+  model: string;
+  duration_secs: number | null;
+  cost_usd: number;
+}
+
+// Local usage analytics (opt-in, computed on this machine only)
+export interface WeeklyUsage {
+  week_start: string;
+  generations: number;
+  completed: number;
+  failed: number;
+  tokens: number;
+  cost_usd: number;
+  features_completed: number;
+}
+
+export interface ModelShare {
+  model: string;
+  calls: number;
+  tokens: number;
+  cost_usd: number;
+  share: number;
+}
+
+export interface UsageStats {
+  project_id: string | null;
+  since: string;
+  weeks: WeeklyUsage[];
+  total_generations: number;
+  total_cost_usd: number;
+  success_rate: number | null;
+  features_generated: number;
+  avg_cost_per_feature: number | null;
+  model_mix: ModelShare[];
+}
+
+export interface UsageDashboard {
+  enabled: boolean;
+  stats: UsageStats | null;
+}
+
+// Month-end spend projected from the trailing burn rate
+export interface SpendForecast {
+  project_id: string | null;
+  month_start: string;
+  month_end: string;
+  month_to_date_usd: number;
+  burn_rate_usd_per_day: number;
+  window_days: number;
+  days_remaining: number;
+  projected_usd: number;
+  monthly_limit_usd: number;
+  over_limit: boolean;
+  limit_reached_on: string | null;
+  generated_at: string;
+}
+
+// Project health score and the signals that lowered it
+export interface HealthItem {
+  id: string;
+  title: string;
+  detail: string;
+}
+
+export interface HealthSignal {
+  kind:
+    | 'stale_features'
+    | 'failing_verifications'
+    | 'unresolved_conflicts'
+    | 'stale_context'
+    | 'budget_burn';
+  penalty: number;
+  summary: string;
+  recommendation: string;
+  items: HealthItem[];
+}
+
+export interface ProjectHealth {
+  project_id: string;
+  project_name: string;
+  score: number;
+  status: 'healthy' | 'warning' | 'critical';
+  signals: HealthSignal[];
+  checked_at: string;
+}
+
+// Everything in a project waiting for a person
+export interface ReviewItem {
+  kind: 'approval' | 'conflict' | 'blocking_finding' | 'failed_verification' | 'feature_review';
+  id: string;
+  title: string;
+  detail: string;
+  link: string | null;
+  route: string | null;
+}
+
+export interface ReviewQueue {
+  project_id: string;
+  project_name: string;
+  counts: {
+    approvals: number;
+    conflicts: number;
+    blocking_findings: number;
+    failed_verifications: number;
+    features_in_review: number;
+  };
+  total: number;
+  items: ReviewItem[];
+}
+
+// Costs and activity rolled up across the workspace's projects
+export interface WorkspaceRollup {
+  cost_usd: number;
+  llm_calls: number;
+  tokens: number;
+  sessions: number;
+  active_sessions: number;
+  features: number;
+  features_in_progress: number;
+  features_done: number;
+  generations: number;
+  failed_generations: number;
+  generation_tokens: number;
+  active_secs: number;
+}
+
+export interface WorkspaceProject extends WorkspaceRollup {
+  project_id: string;
+  project_name: string;
+  status: string;
+}
+
+export interface WorkspaceStats {
+  projects: WorkspaceProject[];
+  totals: WorkspaceRollup;
+}
+
+// Files under a project's root directory
+export interface FileEntry {
+  name: string;
+  path: string;
+  relative_path: string;
+  is_dir: boolean;
+  size: number;
+  modified: string | null;
+}
+
+export interface FileContent {
+  entry: FileEntry;
+  content: string;
+}
+
+// A chat message with its place among retried or edited variants
+export interface ChatMessageSummary {
+  id: string;
+  role: 'user' | 'assistant' | 'system';
+  content: string;
+  model: string | null;
+  created_at: string;
+  variant: number;
+  variant_count: number;
+  variant_ids: string[];
+}
+
+// One page of a listing; chat history and agent events page back from the newest
+export interface Page<T> {
+  items: T[];
+  total: number;
+  limit: number;
+  offset: number;
+}
+
+// Localized messages from the backend catalog
+export interface Translations {
+  locale: string;
+  messages: Record<string, string>;
+}
+
+/**
+ * Look up a message and substitute { $name } placeables.
+ * Falls back to the given default (or the key) when the message is missing.
+ */
+export function formatMessage(
+  translations: Translations | null,
+  key: string,
+  args: Record<string, string | number> = {},
+  fallback?: string,
+): string {
+  const template = translations?.messages[key] ?? fallback ?? key;
+  return template.replace(/\{\s*\$([\w-]+)\s*\}/g, (match, name: string) =>
+    name in args ? String(args[name]) : match,
+  );
+}
+
+// Structured error returned by backend commands
+export interface ErrorPayload {
+  code: string;
+  category: string;
+  message: string;
+  details?: Record<string, unknown>;
+  suggestion?: string;
+  retryable: boolean;
+}
+
+/**
+ * Error thrown when a backend command fails.
+ * Carries the structured payload so callers can branch on code/category.
+ */
+export class ApiError extends Error {
+  readonly payload: ErrorPayload;
+
+  constructor(payload: ErrorPayload) {
+    super(payload.message);
+    this.name = 'ApiError';
+    this.payload = payload;
+  }
+
+  get code(): string {
+    return this.payload.code;
+  }
+
+  get category(): string {
+    return this.payload.category;
+  }
+
+  get retryable(): boolean {
+    return this.payload.retryable;
+  }
+}
+
+const isErrorPayload = (value: unknown): value is ErrorPayload => {
+  return (
+    typeof value === 'object' &&
+    value !== null &&
+    typeof (value as ErrorPayload).code === 'string' &&
+    typeof (value as ErrorPayload).message === 'string'
+  );
 };
 
+/**
+ * Convert whatever a command rejected with into an ApiError
+ */
+export function toApiError(error: unknown): ApiError {
+  if (error instanceof ApiError) {
+    return error;
+  }
+  if (isErrorPayload(error)) {
+    return new ApiError(error);
+  }
+  return new ApiError({
+    code: 'E9999',
+    category: 'internal',
+    message: error instanceof Error ? error.message : String(error),
+    retryable: false,
+  });
+}
+
 /**
  * Invoke a command - uses Tauri if available, falls back to mock
  */
 export async function invoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {
   if (isTauri()) {
-    return tauriInvoke<T>(cmd, args);
+    try {
+      return await tauriInvoke<T>(cmd, args);
+    } catch (error) {
+      throw toApiError(error);
+    }
   }
 
   // Use mock handler
@@ -1,6 +1,7 @@
 import { useEffect, useState } from 'react';
 import { invoke } from '../lib/api';
 import { Link } from 'react-router-dom';
+import SpendForecastWidget from '../components/SpendForecastWidget';
 import {
   FolderOpen,
   Activity,
@@ -98,6 +99,8 @@ export default function Dashboard() {
         />
       </div>
 
+      <SpendForecastWidget />
+
       {/* Projects List */}
       <div className="bg-background-mid rounded-lg border border-background-surface">
         <div className="p-4 border-b border-background-surface flex justify-between items-center">
@@ -0,0 +1,135 @@
+import { useCallback, useEffect, useState } from 'react';
+import { invoke, type Job } from '../lib/api';
+import { useToastStore } from '../stores/toastStore';
+import { Clock, RefreshCw, XCircle } from 'lucide-react';
+
+const STATUS_FILTERS = ['all', 'queued', 'running', 'succeeded', 'failed', 'cancelled'] as const;
+
+export default function Jobs() {
+  const [jobs, setJobs] = useState<Job[]>([]);
+  const [filter, setFilter] = useState<(typeof STATUS_FILTERS)[number]>('all');
+  const [loading, setLoading] = useState(true);
+  const addToast = useToastStore((state) => state.addToast);
+
+  const loadJobs = useCallback(async () => {
+    try {
+      const data = await invoke<Job[]>('get_jobs', {
+        status: filter === 'all' ? null : filter,
+        limit: 100,
+      });
+      setJobs(data);
+    } catch (error) {
+      console.error('Failed to load jobs:', error);
+    } finally {
+      setLoading(false);
+    }
+  }, [filter]);
+
+  useEffect(() => {
+    loadJobs();
+    // Jobs are run by a separate worker, so poll for status changes
+    const interval = setInterval(loadJobs, 5000);
+    return () => clearInterval(interval);
+  }, [loadJobs]);
+
+  async function cancelJob(id: string) {
+    try {
+      await invoke<Job>('cancel_job', { id });
+      addToast('Job cancelled', 'success');
+      loadJobs();
+    } catch (error) {
+      addToast(error instanceof Error ? error.message : String(error), 'error');
+    }
+  }
+
+  if (loading) {
+    return (
+      <div className="flex items-center justify-center h-full">
+        <div className="animate-pulse text-accent-teal">Loading...</div>
+      </div>
+    );
+  }
+
+  return (
+    <div className="p-6 space-y-6">
+      <div className="flex justify-between items-center">
+        <h1 className="text-2xl font-bold">Jobs</h1>
+        <div className="flex items-center gap-2">
+          <select
+            value={filter}
+            onChange={(e) => setFilter(e.target.value as (typeof STATUS_FILTERS)[number])}
+            className="bg-background-mid border border-background-surface rounded-lg px-3 py-2 text-sm"
+          >
+            {STATUS_FILTERS.map((status) => (
+              <option key={status} value={status}>
+                {status}
+              </option>
+            ))}
+          </select>
+          <button
+            onClick={loadJobs}
+            className="p-2 rounded-lg text-gray-400 hover:text-white hover:bg-background-surface"
+            title="Refresh"
+          >
+            <RefreshCw className="w-4 h-4" />
+          </button>
+        </div>
+      </div>
+
+      <div className="bg-background-mid rounded-lg border border-background-surface">
+        {jobs.length === 0 ? (
+          <div className="p-8 text-center text-gray-400">
+            <Clock className="w-8 h-8 mx-auto mb-2" />
+            <p>No jobs.</p>
+            <p className="text-sm mt-1">
+              Queue one with <code>demiarch jobs enqueue generate "..." --at 02:00</code>
+            </p>
+          </div>
+        ) : (
+          <div className="divide-y divide-background-surface">
+            {jobs.map((job) => (
+              <div key={job.id} className="flex items-center justify-between p-4">
+                <div className="min-w-0">
+                  <h3 className="font-medium truncate">{job.summary}</h3>
+                  <p className="text-sm text-gray-400">
+                    {job.kind} · runs {new Date(job.run_at).toLocaleString()} · attempt{' '}
+                    {job.attempts}/{job.max_attempts}
+                  </p>
+                  {job.last_error && (
+                    <p className="text-sm text-accent-magenta truncate">{job.last_error}</p>
+                  )}
+                </div>
+                <div className="flex items-center gap-3 shrink-0">
+                  <JobStatusBadge status={job.status} />
+                  {job.status === 'queued' && (
+                    <button
+                      onClick={() => cancelJob(job.id)}
+                      className="text-gray-400 hover:text-accent-magenta"
+                      title="Cancel job"
+                    >
+                      <XCircle className="w-5 h-5" />
+                    </button>
+                  )}
+                </div>
+              </div>
+            ))}
+          </div>
+        )}
+      </div>
+    </div>
+  );
+}
+
+function JobStatusBadge({ status }: { status: Job['status'] }) {
+  const colors: Record<Job['status'], string> = {
+    queued: 'bg-blue-500/20 text-blue-400',
+    running: 'bg-accent-amber/20 text-accent-amber',
+    succeeded: 'bg-accent-teal/20 text-accent-teal',
+    failed: 'bg-accent-magenta/20 text-accent-magenta',
+    cancelled: 'bg-gray-500/20 text-gray-400',
+  };
+
+  return (
+    <span className={`px-2 py-1 rounded text-xs font-medium ${colors[status]}`}>{status}</span>
+  );
+}
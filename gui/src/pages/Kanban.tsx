@@ -1,5 +1,5 @@
 import { useEffect, useState, useCallback } from 'react';
-import { useParams } from 'react-router-dom';
+import { useParams, useSearchParams } from 'react-router-dom';
 import { invoke, Feature, Project } from '../lib/api';
 import {
   DndContext,
@@ -35,6 +35,8 @@ const COLUMNS = [
 
 export default function Kanban() {
   const { projectId } = useParams<{ projectId: string }>();
+  const [searchParams] = useSearchParams();
+  const linkedFeatureId = searchParams.get('feature');
   const [features, setFeatures] = useState<Feature[]>([]);
   const [project, setProject] = useState<Project | null>(null);
   const [loading, setLoading] = useState(true);
@@ -118,6 +120,13 @@ export default function Kanban() {
     loadFeatures();
   }, [projectId]);
 
+  // demiarch://feature/<id> links open the feature's details
+  useEffect(() => {
+    if (!linkedFeatureId) return;
+    const linked = features.find((f) => f.id === linkedFeatureId);
+    if (linked) setSelectedFeature(linked);
+  }, [linkedFeatureId, features]);
+
   function handleFeatureCreated(feature: Feature) {
     setFeatures((prev) => [...prev, feature]);
     setShowCreateModal(false);
@@ -1,9 +1,12 @@
 import { useEffect, useState } from 'react';
 import { useParams, Link, useNavigate } from 'react-router-dom';
 import { invoke, Feature } from '../lib/api';
-import { ArrowLeft, FileText, LayoutGrid, Clock, Edit3, Save, X, Sparkles, Trash2, AlertTriangle } from 'lucide-react';
+import { ArrowLeft, FileText, FolderTree, LayoutGrid, Clock, Edit3, Save, X, Sparkles, Trash2, AlertTriangle } from 'lucide-react';
 import ReactMarkdown from 'react-markdown';
 import ExtractFeaturesModal from '../components/ExtractFeaturesModal';
+import ProjectHealthWidget from '../components/ProjectHealthWidget';
+import ReviewQueueWidget from '../components/ReviewQueueWidget';
+import FileBrowser from '../components/FileBrowser';
 
 interface Project {
   id: string;
@@ -21,7 +24,7 @@ export default function ProjectDetail() {
   const navigate = useNavigate();
   const [project, setProject] = useState<Project | null>(null);
   const [loading, setLoading] = useState(true);
-  const [activeTab, setActiveTab] = useState<'prd' | 'kanban'>('prd');
+  const [activeTab, setActiveTab] = useState<'prd' | 'files'>('prd');
   const [isEditing, setIsEditing] = useState(false);
   const [editedPrd, setEditedPrd] = useState('');
   const [saving, setSaving] = useState(false);
@@ -47,6 +50,7 @@ export default function ProjectDetail() {
 
   function handleStartEdit() {
     setEditedPrd(project?.prd || '');
+    setActiveTab('prd');
     setIsEditing(true);
   }
 
@@ -187,6 +191,9 @@ export default function ProjectDetail() {
         )}
       </div>
 
+      <ProjectHealthWidget projectId={project.id} />
+      <ReviewQueueWidget projectId={project.id} />
+
       {/* Tabs */}
       <div className="flex gap-2 mb-4">
         <button
@@ -200,6 +207,20 @@ export default function ProjectDetail() {
           <FileText className="w-4 h-4" />
           PRD
         </button>
+        <button
+          onClick={() => {
+            setIsEditing(false);
+            setActiveTab('files');
+          }}
+          className={`flex items-center gap-2 px-4 py-2 rounded-lg font-medium transition-colors ${
+            activeTab === 'files'
+              ? 'bg-accent-teal text-background-deep'
+              : 'bg-background-surface text-gray-400 hover:text-white'
+          }`}
+        >
+          <FolderTree className="w-4 h-4" />
+          Files
+        </button>
         {/* Show Extract Features tab when PRD exists but no features yet */}
         {project.prd && project.feature_count === 0 && (
           <button
@@ -224,7 +245,9 @@ export default function ProjectDetail() {
 
       {/* Content */}
       <div className="flex-1 overflow-auto bg-background-mid rounded-lg border border-background-surface p-6">
-        {isEditing ? (
+        {activeTab === 'files' ? (
+          <FileBrowser projectId={project.id} />
+        ) : isEditing ? (
           <textarea
             value={editedPrd}
             onChange={(e) => setEditedPrd(e.target.value)}
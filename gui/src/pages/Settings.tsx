@@ -11,6 +11,7 @@ import {
   XCircle,
   Check,
   AlertTriangle,
+  Info,
 } from 'lucide-react';
 
 interface DoctorResult {
@@ -21,12 +22,23 @@ interface DoctorResult {
   project_count: number;
 }
 
+interface UpdateStatus {
+  channel: 'stable' | 'beta';
+  current_version: string;
+  latest_version: string;
+  update_available: boolean;
+  notes: string | null;
+  checked_at: string;
+}
+
 export default function Settings() {
   const [health, setHealth] = useState<DoctorResult | null>(null);
   const [loading, setLoading] = useState(true);
   const [apiKeyInput, setApiKeyInput] = useState('');
   const [selectedModel, setSelectedModel] = useState(getModel());
   const [saved, setSaved] = useState(false);
+  const [version, setVersion] = useState<string | null>(null);
+  const [update, setUpdate] = useState<UpdateStatus | null>(null);
 
   // Cost settings
   const settings = getSettings();
@@ -50,8 +62,19 @@ export default function Settings() {
     }
   }
 
+  async function loadAbout() {
+    try {
+      setVersion(await invoke<string>('app_version'));
+      setUpdate(await invoke<UpdateStatus | null>('check_for_update'));
+    } catch (error) {
+      // The update notice is best-effort
+      console.error('Failed to check for updates:', error);
+    }
+  }
+
   useEffect(() => {
     loadHealth();
+    loadAbout();
   }, []);
 
   function handleSaveApiKey() {
@@ -252,6 +275,30 @@ export default function Settings() {
           </p>
         </div>
       </div>
+
+      {/* About */}
+      <div className="bg-background-mid rounded-lg border border-background-surface">
+        <div className="p-4 border-b border-background-surface">
+          <h2 className="text-lg font-semibold flex items-center gap-2">
+            <Info className="w-5 h-5 text-accent-teal" />
+            About
+          </h2>
+        </div>
+        <div className="p-4 space-y-3">
+          <p className="text-sm text-gray-400">Demiarch {version ? `v${version}` : ''}</p>
+          {update && (
+            <div className="p-3 rounded-lg bg-background-surface text-sm">
+              <p className="font-medium text-accent-teal">
+                Version {update.latest_version} is available on the {update.channel} channel
+              </p>
+              {update.notes && <p className="text-gray-400 mt-1 whitespace-pre-line">{update.notes}</p>}
+              <p className="text-xs text-gray-500 mt-2">
+                Install it with <code>demiarch self update</code>
+              </p>
+            </div>
+          )}
+        </div>
+      </div>
     </div>
   );
 }
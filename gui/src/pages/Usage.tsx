@@ -0,0 +1,180 @@
+import { useCallback, useEffect, useState } from 'react';
+import { BarChart3, RefreshCw } from 'lucide-react';
+import { invoke, type UsageDashboard } from '../lib/api';
+import { useToastStore } from '../stores/toastStore';
+
+const WEEK_OPTIONS = [4, 8, 12, 26] as const;
+
+function percent(value: number | null): string {
+  return value === null ? '—' : `${Math.round(value * 100)}%`;
+}
+
+function usd(value: number | null): string {
+  return value === null ? '—' : `$${value.toFixed(2)}`;
+}
+
+export default function Usage() {
+  const [dashboard, setDashboard] = useState<UsageDashboard | null>(null);
+  const [weeks, setWeeks] = useState<number>(8);
+  const [loading, setLoading] = useState(true);
+  const addToast = useToastStore((state) => state.addToast);
+
+  const load = useCallback(async () => {
+    try {
+      setDashboard(await invoke<UsageDashboard>('get_usage_stats', { weeks }));
+    } catch (error) {
+      addToast(error instanceof Error ? error.message : String(error), 'error');
+    } finally {
+      setLoading(false);
+    }
+  }, [weeks, addToast]);
+
+  useEffect(() => {
+    load();
+  }, [load]);
+
+  async function setEnabled(enabled: boolean) {
+    try {
+      await invoke('set_analytics_enabled', { enabled });
+      await load();
+    } catch (error) {
+      addToast(error instanceof Error ? error.message : String(error), 'error');
+    }
+  }
+
+  if (loading) {
+    return (
+      <div className="flex items-center justify-center h-full">
+        <div className="animate-pulse text-accent-teal">Loading...</div>
+      </div>
+    );
+  }
+
+  if (!dashboard?.enabled) {
+    return (
+      <div className="p-6 max-w-xl space-y-4">
+        <h1 className="text-2xl font-bold">Usage</h1>
+        <p className="text-gray-400">
+          Usage analytics summarize generations, success rate, cost per feature and model mix
+          over time. They are computed from your local database and never leave this machine.
+        </p>
+        <button
+          onClick={() => setEnabled(true)}
+          className="px-4 py-2 rounded-lg bg-accent-teal/20 text-accent-teal hover:bg-accent-teal/30"
+        >
+          Enable usage analytics
+        </button>
+      </div>
+    );
+  }
+
+  const stats = dashboard.stats;
+  const peak = Math.max(1, ...(stats?.weeks.map((w) => w.generations) ?? []));
+
+  return (
+    <div className="p-6 space-y-6">
+      <div className="flex justify-between items-center">
+        <h1 className="text-2xl font-bold">Usage</h1>
+        <div className="flex items-center gap-2">
+          <select
+            value={weeks}
+            onChange={(e) => setWeeks(Number(e.target.value))}
+            className="bg-background-mid border border-background-surface rounded-lg px-3 py-2 text-sm"
+          >
+            {WEEK_OPTIONS.map((n) => (
+              <option key={n} value={n}>
+                Last {n} weeks
+              </option>
+            ))}
+          </select>
+          <button
+            onClick={load}
+            className="p-2 rounded-lg text-gray-400 hover:text-white hover:bg-background-surface"
+            title="Refresh"
+          >
+            <RefreshCw className="w-4 h-4" />
+          </button>
+          <button
+            onClick={() => setEnabled(false)}
+            className="px-3 py-2 text-sm rounded-lg text-gray-400 hover:text-white hover:bg-background-surface"
+          >
+            Disable
+          </button>
+        </div>
+      </div>
+
+      {!stats ? (
+        <p className="text-gray-400">No usage recorded yet.</p>
+      ) : (
+        <>
+          <div className="grid grid-cols-4 gap-4">
+            {[
+              ['Generations', String(stats.total_generations)],
+              ['Success rate', percent(stats.success_rate)],
+              ['Avg cost / feature', usd(stats.avg_cost_per_feature)],
+              ['Total cost', usd(stats.total_cost_usd)],
+            ].map(([label, value]) => (
+              <div
+                key={label}
+                className="bg-background-mid rounded-lg border border-background-surface p-4"
+              >
+                <div className="text-sm text-gray-400">{label}</div>
+                <div className="text-2xl font-bold mt-1">{value}</div>
+              </div>
+            ))}
+          </div>
+
+          <div className="bg-background-mid rounded-lg border border-background-surface p-4">
+            <h2 className="font-semibold mb-4 flex items-center gap-2">
+              <BarChart3 className="w-4 h-4 text-accent-teal" />
+              Generations per week
+            </h2>
+            <div className="flex items-end gap-2 h-40">
+              {stats.weeks.map((week) => (
+                <div
+                  key={week.week_start}
+                  className="flex-1 flex flex-col items-center justify-end h-full"
+                  title={`${week.generations} generations, ${percent(
+                    week.completed + week.failed > 0
+                      ? week.completed / (week.completed + week.failed)
+                      : null
+                  )} succeeded`}
+                >
+                  <div
+                    className="w-full bg-accent-teal/60 rounded-t"
+                    style={{ height: `${(week.generations / peak) * 100}%` }}
+                  />
+                  <span className="text-xs text-gray-500 mt-1">{week.week_start.slice(5)}</span>
+                </div>
+              ))}
+            </div>
+          </div>
+
+          <div className="bg-background-mid rounded-lg border border-background-surface p-4">
+            <h2 className="font-semibold mb-4">Model mix</h2>
+            {stats.model_mix.length === 0 ? (
+              <p className="text-sm text-gray-400">No LLM calls in this period.</p>
+            ) : (
+              <div className="space-y-2">
+                {stats.model_mix.map((model) => (
+                  <div key={model.model} className="flex items-center gap-3 text-sm">
+                    <span className="w-64 truncate text-gray-300">{model.model}</span>
+                    <div className="flex-1 bg-background-surface rounded h-2">
+                      <div
+                        className="bg-accent-teal h-2 rounded"
+                        style={{ width: `${model.share * 100}%` }}
+                      />
+                    </div>
+                    <span className="w-32 text-right text-gray-400">
+                      {model.calls} calls · {usd(model.cost_usd)}
+                    </span>
+                  </div>
+                ))}
+              </div>
+            )}
+          </div>
+        </>
+      )}
+    </div>
+  );
+}
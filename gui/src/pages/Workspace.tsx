@@ -0,0 +1,134 @@
+import { useCallback, useEffect, useState } from 'react';
+import { useNavigate } from 'react-router-dom';
+import { Layers, RefreshCw } from 'lucide-react';
+import { invoke, type WorkspaceStats } from '../lib/api';
+import { useToastStore } from '../stores/toastStore';
+
+function usd(value: number): string {
+  return `$${value.toFixed(2)}`;
+}
+
+function hours(secs: number): string {
+  return `${(secs / 3600).toFixed(1)}h`;
+}
+
+export default function Workspace() {
+  const navigate = useNavigate();
+  const [stats, setStats] = useState<WorkspaceStats | null>(null);
+  const [loading, setLoading] = useState(true);
+  const addToast = useToastStore((state) => state.addToast);
+
+  const load = useCallback(async () => {
+    try {
+      setStats(await invoke<WorkspaceStats>('get_workspace_overview', { projectIds: null }));
+    } catch (error) {
+      addToast(error instanceof Error ? error.message : String(error), 'error');
+    } finally {
+      setLoading(false);
+    }
+  }, [addToast]);
+
+  useEffect(() => {
+    load();
+  }, [load]);
+
+  if (loading) {
+    return (
+      <div className="flex items-center justify-center h-full">
+        <div className="animate-pulse text-accent-teal">Loading...</div>
+      </div>
+    );
+  }
+
+  const totals = stats?.totals;
+
+  return (
+    <div className="p-6 space-y-6">
+      <div className="flex justify-between items-center">
+        <h1 className="text-2xl font-bold">Workspace</h1>
+        <button
+          onClick={load}
+          className="p-2 rounded-lg text-gray-400 hover:text-white hover:bg-background-surface"
+          title="Refresh"
+        >
+          <RefreshCw className="w-4 h-4" />
+        </button>
+      </div>
+
+      {!stats || !totals || stats.projects.length === 0 ? (
+        <div className="p-8 text-center text-gray-400">
+          <Layers className="w-8 h-8 mx-auto mb-2" />
+          <p>No active projects.</p>
+        </div>
+      ) : (
+        <>
+          <div className="grid grid-cols-4 gap-4">
+            {[
+              ['Total cost', usd(totals.cost_usd)],
+              ['Sessions', `${totals.sessions} (${totals.active_sessions} active)`],
+              ['Features done', `${totals.features_done} / ${totals.features}`],
+              ['Agent runs', `${totals.generations} (${totals.failed_generations} failed)`],
+            ].map(([label, value]) => (
+              <div
+                key={label}
+                className="bg-background-mid rounded-lg border border-background-surface p-4"
+              >
+                <div className="text-sm text-gray-400">{label}</div>
+                <div className="text-2xl font-bold mt-1">{value}</div>
+              </div>
+            ))}
+          </div>
+
+          <div className="bg-background-mid rounded-lg border border-background-surface overflow-x-auto">
+            <table className="w-full text-sm">
+              <thead className="text-gray-400 text-left">
+                <tr className="border-b border-background-surface">
+                  <th className="p-3 font-medium">Project</th>
+                  <th className="p-3 font-medium text-right">Cost</th>
+                  <th className="p-3 font-medium text-right">LLM calls</th>
+                  <th className="p-3 font-medium text-right">Sessions</th>
+                  <th className="p-3 font-medium text-right">Features</th>
+                  <th className="p-3 font-medium text-right">Agent runs</th>
+                  <th className="p-3 font-medium text-right">Active time</th>
+                </tr>
+              </thead>
+              <tbody className="divide-y divide-background-surface">
+                {stats.projects.map((project) => (
+                  <tr key={project.project_id}>
+                    <td className="p-3">
+                      <button
+                        onClick={() => navigate(`/projects/${project.project_id}`)}
+                        className="hover:text-accent-teal hover:underline"
+                      >
+                        {project.project_name}
+                      </button>
+                    </td>
+                    <td className="p-3 text-right">{usd(project.cost_usd)}</td>
+                    <td className="p-3 text-right">{project.llm_calls}</td>
+                    <td className="p-3 text-right">{project.sessions}</td>
+                    <td className="p-3 text-right">
+                      {project.features_done} / {project.features}
+                    </td>
+                    <td className="p-3 text-right">
+                      {project.generations}
+                      {project.failed_generations > 0 && (
+                        <span className="text-accent-magenta">
+                          {' '}
+                          ({project.failed_generations} failed)
+                        </span>
+                      )}
+                    </td>
+                    <td className="p-3 text-right">{hours(project.active_secs)}</td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+          <p className="text-sm text-gray-400">
+            Export the breakdown with <code>demiarch workspace stats --csv workspace.csv</code>
+          </p>
+        </>
+      )}
+    </div>
+  );
+}